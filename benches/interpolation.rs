@@ -0,0 +1,81 @@
+//! Cost of each `resample_quality` for repitched playback.
+//!
+//! Run with `cargo bench --bench interpolation`. Prints the time to read one second of
+//! audio through the mixer (a 48 kHz sample mixed at 44.1 kHz) and through the live
+//! var-speed preview (1.5x), and how many times faster than real time that is.
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use devalang_wasm::engine::audio::mixer::{AudioMixer, MASTER_INSERT, SampleBuffer};
+use devalang_wasm::engine::audio::playback::speed::{PreviewRate, VarSpeed};
+use devalang_wasm::engine::audio::settings::ResampleQuality;
+
+const OUTPUT_RATE: u32 = 44_100;
+const SOURCE_RATE: u32 = 48_000;
+const ROUNDS: u32 = 5;
+
+const QUALITIES: [ResampleQuality; 7] = [
+    ResampleQuality::Linear2,
+    ResampleQuality::Sinc12,
+    ResampleQuality::Sinc24,
+    ResampleQuality::Sinc48,
+    ResampleQuality::Sinc96,
+    ResampleQuality::Sinc192,
+    ResampleQuality::Sinc512,
+];
+
+fn tone(rate: u32, channels: usize) -> Vec<f32> {
+    (0..rate as usize)
+        .flat_map(|n| {
+            let value = (2.0 * std::f32::consts::PI * 440.0 * n as f32 / rate as f32).sin();
+            std::iter::repeat_n(value, channels)
+        })
+        .collect()
+}
+
+/// Best of `ROUNDS` runs
+fn measure(mut run: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn report(path: &str, quality: ResampleQuality, elapsed: Duration) {
+    println!(
+        "{:<8} {:<16} {:>10.2?} {:>9.0}x real time",
+        path,
+        quality.label(),
+        elapsed,
+        1.0 / elapsed.as_secs_f64().max(1e-9)
+    );
+}
+
+fn main() {
+    let mono = SampleBuffer::new(Arc::new(tone(SOURCE_RATE, 1)), 1, SOURCE_RATE);
+    let stereo = tone(OUTPUT_RATE, 2);
+
+    for quality in QUALITIES {
+        let elapsed = measure(|| {
+            let mut mixer = AudioMixer::<f32>::new(OUTPUT_RATE, 2).with_resample_quality(quality);
+            mixer.mix_sample(MASTER_INSERT, 0, 0.0, &mono);
+            black_box(mixer.into_master_buffer(OUTPUT_RATE as usize));
+        });
+        report("mixer", quality, elapsed);
+    }
+
+    for quality in QUALITIES {
+        let elapsed = measure(|| {
+            let preview = PreviewRate::new(1.5, false).with_quality(quality);
+            let played = VarSpeed::new(stereo.iter().copied(), 2, OUTPUT_RATE, preview);
+            black_box(played.count());
+        });
+        report("live", quality, elapsed);
+    }
+}
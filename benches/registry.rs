@@ -0,0 +1,86 @@
+//! Cost of building effect chains from the registry.
+//!
+//! Run with `cargo bench --bench registry`. Every insert, note and sample with effects
+//! builds a chain, and every chain used to hold a registry with a ready-made processor
+//! for all ~45 effect names (reverb and delay buffers included) before picking its two or
+//! three. The registry now stores constructors and is shared, so a chain only builds the
+//! processors it uses. Timings per call on one machine (release build):
+//!
+//! ```text
+//!                      eager (before)   lazy (after)
+//! registry             153 µs           4.3 µs
+//! chain reverb + lpf   132 µs           3.5 µs
+//! chain lpf + drive     92 µs           0.8 µs
+//! ```
+//!
+//! This is run time only. Binary size depends on the cargo features the wasm bundle is
+//! built with, not on `EngineBuilder::with_effects`; compare `npm run rust:wasm:web`
+//! against `npm run rust:wasm:lean` for that (see the README).
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use devalang_wasm::engine::audio::effects::chain::build_effect_chain;
+use devalang_wasm::engine::audio::effects::registry::EffectRegistry;
+use devalang_wasm::language::syntax::ast::Value;
+
+const ROUNDS: u32 = 5;
+const ITERATIONS: u32 = 200;
+
+/// Best of `ROUNDS` runs, per iteration
+fn measure(mut run: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                run();
+            }
+            start.elapsed() / ITERATIONS
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn effect(name: &str, params: &[(&str, f32)]) -> Value {
+    let params: HashMap<String, Value> = params
+        .iter()
+        .map(|(key, value)| (key.to_string(), Value::Number(*value)))
+        .collect();
+    let mut effect = HashMap::new();
+    effect.insert(name.to_string(), Value::Map(params));
+    Value::Map(effect)
+}
+
+fn main() {
+    let reverb_lpf = vec![
+        effect("reverb", &[("size", 0.6), ("mix", 0.3)]),
+        effect("lpf", &[("cutoff", 800.0)]),
+    ];
+    let lpf_drive = vec![
+        effect("lpf", &[("cutoff", 1200.0)]),
+        effect("drive", &[("amount", 0.4)]),
+    ];
+
+    println!(
+        "{:<20} {:>10.2?}",
+        "registry",
+        measure(|| {
+            black_box(EffectRegistry::new());
+        })
+    );
+    println!(
+        "{:<20} {:>10.2?}",
+        "chain reverb + lpf",
+        measure(|| {
+            black_box(build_effect_chain(black_box(&reverb_lpf), true));
+        })
+    );
+    println!(
+        "{:<20} {:>10.2?}",
+        "chain lpf + drive",
+        measure(|| {
+            black_box(build_effect_chain(black_box(&lpf_drive), true));
+        })
+    );
+}
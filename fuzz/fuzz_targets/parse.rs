@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    devalang_wasm::language::syntax::parser::fuzz::fuzz_parse(data);
+});
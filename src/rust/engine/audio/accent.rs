@@ -0,0 +1,100 @@
+//! Accent maps: velocity multipliers by beat position
+//!
+//! Declared with `accent map name = [1.2, 0.8, 1.1, 0.8]` (one entry per beat, or per
+//! `per 1/8` step) and applied with `call drums with { accent: name }`, a group header
+//! `group drums with { accent: name }:` or a pattern option `{ accent: name }`. The map
+//! is a plain variable, so `export`/`import` shares it like any other preset; a few
+//! common shapes are built in and can be named directly (`accent: backbeat`).
+
+use crate::language::syntax::ast::Value;
+use std::collections::HashMap;
+
+/// Key marking a map value as an accent map
+pub const ACCENT_KEY: &str = "_accent";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccentMap {
+    /// Velocity multiplier of each step, repeating
+    pub steps: Vec<f32>,
+    /// Length of one step in beats
+    pub step_beats: f32,
+}
+
+impl AccentMap {
+    pub fn new(steps: Vec<f32>, step_beats: f32) -> Self {
+        Self {
+            steps,
+            step_beats: if step_beats > 0.0 { step_beats } else { 1.0 },
+        }
+    }
+
+    /// Built-in maps usable by name without a declaration
+    pub fn preset(name: &str) -> Option<Self> {
+        let map = match name {
+            // Stronger on 1 and 3
+            "strong" | "one_three" => Self::new(vec![1.2, 0.85, 1.1, 0.85], 1.0),
+            // Stronger on 2 and 4
+            "backbeat" => Self::new(vec![0.9, 1.2, 0.9, 1.2], 1.0),
+            // Only the first beat of the bar stands out
+            "downbeat" => Self::new(vec![1.25, 0.9, 0.9, 0.9], 1.0),
+            "waltz" => Self::new(vec![1.25, 0.85, 0.85], 1.0),
+            // Eighths with the off-beats pushed
+            "offbeat" => Self::new(vec![0.85, 1.15], 0.5),
+            // Sixteenths falling away from each beat
+            "sixteenths" => Self::new(vec![1.15, 0.8, 0.95, 0.8], 0.25),
+            _ => return None,
+        };
+        Some(map)
+    }
+
+    /// Read the map form stored by `accent map` declarations
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let Value::Map(map) = value else {
+            return None;
+        };
+        let Some(Value::Array(items)) = map.get(ACCENT_KEY) else {
+            return None;
+        };
+        let steps: Vec<f32> = items
+            .iter()
+            .filter_map(|item| match item {
+                Value::Number(n) => Some(n.max(0.0)),
+                _ => None,
+            })
+            .collect();
+        if steps.is_empty() {
+            return None;
+        }
+        let step_beats = match map.get("step") {
+            Some(Value::Number(n)) => *n,
+            _ => 1.0,
+        };
+        Some(Self::new(steps, step_beats))
+    }
+
+    /// `{ _accent: [..], step: beats }`
+    pub fn to_value(&self) -> Value {
+        Value::Map(HashMap::from([
+            (
+                ACCENT_KEY.to_string(),
+                Value::Array(self.steps.iter().map(|s| Value::Number(*s)).collect()),
+            ),
+            ("step".to_string(), Value::Number(self.step_beats)),
+        ]))
+    }
+
+    /// Multiplier for a hit at `beat` (0-based, from the start of the project); hits
+    /// between steps take the step they fall in
+    pub fn gain_at(&self, beat: f32) -> f32 {
+        if self.steps.is_empty() {
+            return 1.0;
+        }
+        // Tolerate float drift so a hit exactly on a step does not fall into the previous one
+        let index = (beat / self.step_beats + 1e-3).floor() as i64;
+        self.steps[index.rem_euclid(self.steps.len() as i64) as usize]
+    }
+}
+
+#[cfg(test)]
+#[path = "test_accent.rs"]
+mod tests;
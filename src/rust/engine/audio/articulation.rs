@@ -0,0 +1,144 @@
+//! Articulations: named envelope/filter modes a synth switches between per note, like
+//! keyswitches on a sampled instrument (`lead!pluck -> note(C4)`, or `lead!pluck C4 1/8`).
+//!
+//! `pluck`, `sustain` and `staccato` exist on every synth. A synth's `articulations` map
+//! overrides them or adds its own:
+//! `synth saw { articulations: { pluck: { decay: 0.08, cutoff: 1800 } } }`.
+
+use crate::engine::audio::events::SynthDefinition;
+use crate::engine::audio::generator::FilterDef;
+use crate::language::syntax::ast::Value;
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+
+/// Settings one articulation swaps in for a note; unset fields keep the synth's own
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Articulation {
+    /// Envelope stages in seconds (sustain as a level)
+    pub attack: Option<f32>,
+    pub decay: Option<f32>,
+    pub sustain: Option<f32>,
+    pub release: Option<f32>,
+    /// Filter cutoff in Hz; adds a lowpass to synths without filters
+    pub cutoff: Option<f32>,
+    /// Fraction of the written duration that sounds (the cursor still moves the full length)
+    pub length: Option<f32>,
+}
+
+impl Articulation {
+    /// Articulation every synth knows without declaring it
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "pluck" => Some(Articulation {
+                attack: Some(0.002),
+                decay: Some(0.15),
+                sustain: Some(0.0),
+                release: Some(0.08),
+                cutoff: Some(2500.0),
+                length: None,
+            }),
+            "sustain" => Some(Articulation {
+                attack: Some(0.02),
+                decay: Some(0.1),
+                sustain: Some(1.0),
+                release: Some(0.4),
+                cutoff: None,
+                length: None,
+            }),
+            "staccato" => Some(Articulation {
+                attack: Some(0.003),
+                decay: Some(0.05),
+                sustain: Some(0.6),
+                release: Some(0.03),
+                cutoff: None,
+                length: Some(0.5),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Read one entry of an `articulations` map
+    pub fn from_map(map: &HashMap<String, Value>) -> Self {
+        let number = |key: &str| match map.get(key) {
+            Some(Value::Number(n)) => Some(*n),
+            _ => None,
+        };
+        Articulation {
+            attack: number("attack"),
+            decay: number("decay"),
+            sustain: number("sustain"),
+            release: number("release"),
+            cutoff: number("cutoff"),
+            length: number("length").map(|length| length.clamp(0.0, 1.0)),
+        }
+    }
+
+    /// Replace the synth settings this articulation sets
+    pub fn apply(&self, synth: &mut SynthDefinition) {
+        if let Some(attack) = self.attack {
+            synth.attack = attack;
+        }
+        if let Some(decay) = self.decay {
+            synth.decay = decay;
+        }
+        if let Some(sustain) = self.sustain {
+            synth.sustain = sustain;
+        }
+        if let Some(release) = self.release {
+            synth.release = release;
+        }
+        if let Some(cutoff) = self.cutoff {
+            if synth.filters.is_empty() {
+                synth.filters.push(FilterDef {
+                    filter_type: "lowpass".to_string(),
+                    cutoff,
+                    resonance: 1.0,
+                });
+            } else {
+                for filter in &mut synth.filters {
+                    filter.cutoff = cutoff;
+                }
+            }
+        }
+    }
+}
+
+/// Articulations declared in a synth's `articulations` map
+pub fn extract_articulations(map: &HashMap<String, Value>) -> HashMap<String, Articulation> {
+    let Some(Value::Map(table)) = map.get("articulations") else {
+        return HashMap::new();
+    };
+    table
+        .iter()
+        .filter_map(|(name, entry)| match entry {
+            Value::Map(settings) => Some((name.clone(), Articulation::from_map(settings))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Split a note target into the synth and the articulation after `!` (`lead!pluck`)
+pub fn split_target(target: &str) -> (&str, Option<&str>) {
+    match target.split_once('!') {
+        Some((synth, mode)) if !mode.is_empty() => (synth, Some(mode)),
+        _ => (target, None),
+    }
+}
+
+/// Look up `name` on `synth`: its own table first, then the built-ins
+pub fn resolve(synth: &SynthDefinition, name: &str) -> Result<Articulation> {
+    if let Some(articulation) = synth.articulations.get(name) {
+        return Ok(*articulation);
+    }
+    match Articulation::builtin(name) {
+        Some(articulation) => Ok(articulation),
+        None => bail!(
+            "unknown articulation '{}' (built-in: pluck, sustain, staccato)",
+            name
+        ),
+    }
+}
+
+#[cfg(test)]
+#[path = "test_articulation.rs"]
+mod tests;
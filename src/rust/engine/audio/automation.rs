@@ -1,7 +1,13 @@
-use crate::language::syntax::ast::Value;
+use crate::language::syntax::ast::{TimeSpan, Value};
+use crate::language::syntax::parser::driver::statements::parse_automate_body;
 /// Automation system - parameter automation over time
 /// Supports linear, exponential, and custom curves
 use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::engine::audio::evaluator::formula::Formula;
+use crate::engine::curves::{CurveType, evaluate_curve, parse_curve};
+use crate::engine::special_vars::SpecialVarContext;
 
 /// Automation curve type (legacy simple curves)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -33,17 +39,80 @@ pub struct AutomationParam {
     pub start_time: f32, // seconds
     pub duration: f32,   // seconds
     pub curve: AutomationCurve,
+    /// Advanced curve; replaces `curve` when set
+    pub ease: Option<CurveType>,
+}
+
+/// Parameter driven by a formula (`automate lead.cutoff: 0.3 + 0.2 * sin($time * 2)`)
+#[derive(Debug, Clone)]
+pub struct FormulaParam {
+    pub param_name: String,
+    pub formula: Arc<Formula>,
+    /// Time the `automate` statement was reached; the formula applies from here on
+    pub start_time: f32,
+    /// Special variables as they stood at the statement; `$time` advances per evaluation
+    pub context: SpecialVarContext,
+}
+
+impl FormulaParam {
+    pub fn value_at(&self, time_seconds: f32) -> f32 {
+        let mut context = self.context.clone();
+        context.update_time(time_seconds);
+        self.formula.eval(&context)
+    }
+}
+
+/// How a keyframe moves on to the next one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentMode {
+    Linear,
+    /// Eased by a curve (`$curve.in`, `$ease.bezier(...)`, `smooth`, ...)
+    Curve(CurveType),
+    /// Keep the keyframe value until the next keyframe
+    Hold,
+    /// Jump to the next keyframe value at the start of the segment
+    Step,
+}
+
+impl SegmentMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "linear" | "lin" => Some(SegmentMode::Linear),
+            "hold" => Some(SegmentMode::Hold),
+            "step" => Some(SegmentMode::Step),
+            "exponential" | "exp" => Some(SegmentMode::Curve(CurveType::EaseIn)),
+            "logarithmic" | "log" => Some(SegmentMode::Curve(CurveType::EaseOut)),
+            "smooth" | "ease" => Some(SegmentMode::Curve(CurveType::EaseInOut)),
+            _ => parse_curve(name).map(SegmentMode::Curve),
+        }
+    }
+
+    /// Value between keyframes `v0` and `v1` at `local` progress (0.0..1.0) of the segment
+    pub fn interpolate(self, v0: f32, v1: f32, local: f32) -> f32 {
+        match self {
+            SegmentMode::Linear => v0 + (v1 - v0) * local,
+            SegmentMode::Curve(curve) => v0 + (v1 - v0) * evaluate_curve(curve, local),
+            SegmentMode::Hold => v0,
+            SegmentMode::Step => v1,
+        }
+    }
 }
 
-/// Lightweight template for per-note automation (percent-based points)
+/// Lightweight template for per-note automation (percent-based keyframes)
 #[derive(Debug, Clone)]
 pub struct AutomationParamTemplate {
     pub param_name: String,
-    /// Points as (progress_fraction 0.0-1.0, value)
-    pub points: Vec<(f32, f32)>,
+    /// Keyframes as (progress_fraction 0.0-1.0, value, mode of the segment they start)
+    pub points: Vec<(f32, f32, SegmentMode)>,
+    /// Keyframes keyed by a duration from the block start (e.g. `1/8 + 10ms = 0.5`),
+    /// folded into `points` once the block length is known
+    pub timed_points: Vec<(TimeSpan, f32, SegmentMode)>,
     pub curve: AutomationCurve,
-    /// Advanced curve (if specified)
-    pub advanced_curve: Option<crate::engine::curves::CurveType>,
+    /// Length of the looping region as a fraction of the block; keyframe positions are
+    /// then relative to the region, which repeats until the block ends
+    pub loop_length: Option<f32>,
+    /// Loop region given as a duration, folded into `loop_length` with the timed points
+    pub loop_span: Option<TimeSpan>,
 }
 
 /// Automation envelope - collection of automation parameters
@@ -51,6 +120,7 @@ pub struct AutomationParamTemplate {
 pub struct AutomationEnvelope {
     pub target: String, // Target entity (synth name, "global", etc.)
     pub params: Vec<AutomationParam>,
+    pub formulas: Vec<FormulaParam>,
 }
 
 impl AutomationEnvelope {
@@ -58,9 +128,19 @@ impl AutomationEnvelope {
         Self {
             target,
             params: Vec::new(),
+            formulas: Vec::new(),
         }
     }
 
+    pub fn add_formula(&mut self, formula: FormulaParam) {
+        self.formulas.push(formula);
+    }
+
+    /// Whether `param_name` is driven by a formula at any point
+    pub fn has_formula(&self, param_name: &str) -> bool {
+        self.formulas.iter().any(|f| f.param_name == param_name)
+    }
+
     /// Add automation parameter
     pub fn add_param(&mut self, param: AutomationParam) {
         self.params.push(param);
@@ -68,6 +148,23 @@ impl AutomationEnvelope {
 
     /// Get automated value for a parameter at a specific time
     pub fn get_value(&self, param_name: &str, time_seconds: f32) -> Option<f32> {
+        // The most recent statement wins, whether it was a formula or a segment
+        let formula = self
+            .formulas
+            .iter()
+            .rev()
+            .find(|f| f.param_name == param_name && f.start_time <= time_seconds);
+        if let Some(formula) = formula {
+            let overridden = self.params.iter().any(|p| {
+                p.param_name == param_name
+                    && p.start_time > formula.start_time
+                    && p.start_time <= time_seconds
+            });
+            if !overridden {
+                return Some(formula.value_at(time_seconds));
+            }
+        }
+
         // Find all automation params for this parameter name
         let matching: Vec<&AutomationParam> = self
             .params
@@ -85,9 +182,21 @@ impl AutomationEnvelope {
 
             if time_seconds >= param.start_time && time_seconds <= end_time {
                 // Currently in automation range
-                let progress = (time_seconds - param.start_time) / param.duration;
-                let value =
-                    interpolate_value(param.from_value, param.to_value, progress, param.curve);
+                let progress = if param.duration > 0.0 {
+                    (time_seconds - param.start_time) / param.duration
+                } else {
+                    1.0
+                };
+                let value = match param.ease {
+                    Some(curve) => {
+                        param.from_value
+                            + (param.to_value - param.from_value)
+                                * evaluate_curve(curve, progress.clamp(0.0, 1.0))
+                    }
+                    None => {
+                        interpolate_value(param.from_value, param.to_value, progress, param.curve)
+                    }
+                };
                 return Some(value);
             } else if time_seconds > end_time {
                 // Past automation - return end value
@@ -155,44 +264,73 @@ pub fn parse_automation_from_value(value: &Value) -> Option<AutomationEnvelope>
 /// Parse per-param templates from a raw automate body string.
 /// Expects blocks like: param <name> { 0% = 0.0 100% = 1.0 }
 pub fn parse_param_templates_from_raw(raw: &str) -> Vec<AutomationParamTemplate> {
-    use regex::Regex;
+    parse_param_templates_from_value(&parse_automate_body(raw))
+}
+
+/// Build templates from the timeline the parser stores under the `params` key of an
+/// `automate` statement
+pub fn parse_param_templates_from_value(params: &Value) -> Vec<AutomationParamTemplate> {
+    let Value::Array(params) = params else {
+        return Vec::new();
+    };
     let mut templates = Vec::new();
 
-    // Find param blocks: param <name> [curve <curveName>] { ... }
-    let re_block =
-        Regex::new(r"param\s+([A-Za-z_][A-Za-z0-9_]*)\s*(?:curve\s+([^\s{]+)\s*)?\{([^}]*)\}")
-            .unwrap();
-    let re_point = Regex::new(r"([0-9]+(?:\.[0-9]+)?)%?\s*=\s*([\-0-9\.eE]+)").unwrap();
-
-    for cap in re_block.captures_iter(raw) {
-        let name = cap.get(1).unwrap().as_str().to_string();
-        let curve_str = cap.get(2).map(|m| m.as_str());
-        let body = cap.get(3).unwrap().as_str();
-
-        let mut points: Vec<(f32, f32)> = Vec::new();
-        for pcap in re_point.captures_iter(body) {
-            if let (Some(p_str), Some(v_str)) = (pcap.get(1), pcap.get(2)) {
-                if let (Ok(pv), Ok(vv)) =
-                    (p_str.as_str().parse::<f32>(), v_str.as_str().parse::<f32>())
-                {
-                    let frac = (pv / 100.0).clamp(0.0, 1.0);
-                    points.push((frac, vv));
+    for param in params {
+        let Value::Map(param) = param else {
+            continue;
+        };
+        let Some(Value::String(name)) = param.get("name") else {
+            continue;
+        };
+        // The param curve is the mode of every keyframe that does not name its own
+        let default_mode = match param.get("curve") {
+            Some(Value::String(curve)) => SegmentMode::parse(curve).unwrap_or(SegmentMode::Linear),
+            _ => SegmentMode::Linear,
+        };
+
+        let mut points: Vec<(f32, f32, SegmentMode)> = Vec::new();
+        let mut timed_points: Vec<(TimeSpan, f32, SegmentMode)> = Vec::new();
+        if let Some(Value::Array(keyframes)) = param.get("keyframes") {
+            for keyframe in keyframes {
+                let Value::Map(keyframe) = keyframe else {
+                    continue;
+                };
+                let Some(Value::Number(value)) = keyframe.get("value") else {
+                    continue;
+                };
+                let mode = match keyframe.get("mode") {
+                    Some(Value::String(mode)) => SegmentMode::parse(mode).unwrap_or(default_mode),
+                    _ => default_mode,
+                };
+                match keyframe.get("at") {
+                    Some(Value::Number(frac)) => points.push((*frac, *value, mode)),
+                    Some(Value::Duration(duration)) => {
+                        if let Some(span) = duration.to_span() {
+                            timed_points.push((span, *value, mode));
+                        }
+                    }
+                    _ => {}
                 }
             }
         }
 
+        let (loop_length, loop_span) = match param.get("loop") {
+            Some(Value::Number(frac)) if *frac > 0.0 => (Some(*frac), None),
+            Some(Value::Duration(duration)) => (None, duration.to_span()),
+            _ => (None, None),
+        };
+
         // Sort by progress fraction
         points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
 
-        if !points.is_empty() {
-            // Parse advanced curve if specified
-            let advanced_curve = curve_str.and_then(|s| crate::engine::curves::parse_curve(s));
-
+        if !points.is_empty() || !timed_points.is_empty() {
             templates.push(AutomationParamTemplate {
-                param_name: name,
+                param_name: name.clone(),
                 points,
+                timed_points,
                 curve: AutomationCurve::Linear,
-                advanced_curve,
+                loop_length,
+                loop_span,
             });
         }
     }
@@ -200,12 +338,110 @@ pub fn parse_param_templates_from_raw(raw: &str) -> Vec<AutomationParamTemplate>
     templates
 }
 
+impl AutomationParamTemplate {
+    /// Fold duration-keyed points (and a duration loop region) into progress fractions of
+    /// a block lasting `length_secs`
+    pub fn resolve_timed_points(&mut self, length_secs: f32, bpm: f32) {
+        // Inside a loop region, positions are relative to the region
+        let mut region_secs = length_secs;
+        if let Some(span) = self.loop_span.take() {
+            region_secs = span.to_seconds(bpm);
+            if length_secs > 0.0 && region_secs > 0.0 {
+                self.loop_length = Some(region_secs / length_secs);
+            }
+        }
+        if self.timed_points.is_empty() {
+            return;
+        }
+        for (span, value, mode) in self.timed_points.drain(..) {
+            let frac = if region_secs > 0.0 {
+                (span.to_seconds(bpm) / region_secs).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            self.points.push((frac, value, mode));
+        }
+        self.points
+            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    }
+
+    /// Envelope segments for a block of `length` seconds starting at `start_time`, one
+    /// per keyframe pair and loop repetition
+    pub fn segments(&self, start_time: f32, length: f32) -> Vec<AutomationParam> {
+        let Some(&(first_at, first_value, _)) = self.points.first() else {
+            return Vec::new();
+        };
+        let region = self
+            .loop_length
+            .filter(|frac| *frac > 0.0 && *frac < 1.0)
+            .unwrap_or(1.0)
+            * length;
+        let repeats = if region > 0.0 {
+            (length / region).ceil().max(1.0) as usize
+        } else {
+            1
+        };
+        let block_end = start_time + length;
+
+        let segment = |from_value, to_value, start, duration, mode| AutomationParam {
+            param_name: self.param_name.clone(),
+            from_value,
+            to_value,
+            start_time: start,
+            duration,
+            curve: self.curve,
+            ease: match mode {
+                SegmentMode::Curve(curve) => Some(curve),
+                _ => None,
+            },
+        };
+
+        let mut segments = Vec::new();
+        for repeat in 0..repeats {
+            let offset = start_time + repeat as f32 * region;
+            if offset >= block_end && repeat > 0 {
+                break;
+            }
+            // Each repetition starts on the first keyframe value, like the per-note evaluation
+            if first_at > 0.0 || self.points.len() == 1 {
+                segments.push(segment(
+                    first_value,
+                    first_value,
+                    offset,
+                    first_at * region,
+                    SegmentMode::Hold,
+                ));
+            }
+            for w in self.points.windows(2) {
+                let (p0, v0, mode) = w[0];
+                let (p1, v1, _) = w[1];
+                let seg_start = offset + p0 * region;
+                let seg_dur = (p1 - p0) * region;
+                if seg_dur <= 0.0 || seg_start >= block_end {
+                    continue;
+                }
+                let (from, to) = match mode {
+                    SegmentMode::Hold => (v0, v0),
+                    SegmentMode::Step => (v1, v1),
+                    _ => (v0, v1),
+                };
+                segments.push(segment(from, to, seg_start, seg_dur, mode));
+            }
+        }
+        segments
+    }
+}
+
 /// Evaluate a template at a given progress fraction (0.0..1.0)
 pub fn evaluate_template_at(tpl: &AutomationParamTemplate, progress: f32) -> f32 {
-    let p = progress.clamp(0.0, 1.0);
+    let mut p = progress.clamp(0.0, 1.0);
     if tpl.points.is_empty() {
         return 0.0;
     }
+    // Progress through the current repetition of the loop region
+    if let Some(region) = tpl.loop_length.filter(|frac| *frac > 0.0 && *frac < 1.0) {
+        p = (p % region) / region;
+    }
     // Before first point
     if p <= tpl.points[0].0 {
         return tpl.points[0].1;
@@ -217,28 +453,75 @@ pub fn evaluate_template_at(tpl: &AutomationParamTemplate, progress: f32) -> f32
 
     // Find segment
     for w in tpl.points.windows(2) {
-        let (p0, v0) = w[0];
-        let (p1, v1) = w[1];
-        if p >= p0 && p <= p1 {
+        let (p0, v0, mode) = w[0];
+        let (p1, v1, _) = w[1];
+        if p >= p0 && p < p1 {
             let local = if (p1 - p0).abs() < f32::EPSILON {
                 0.0
             } else {
                 (p - p0) / (p1 - p0)
             };
+            return mode.interpolate(v0, v1, local);
+        }
+    }
 
-            // Apply advanced curve if specified
-            let eased_local = if let Some(curve) = &tpl.advanced_curve {
-                crate::engine::curves::evaluate_curve(*curve, local)
+    // Fallback
+    tpl.points.last().unwrap().1
+}
+
+/// Apply note-mode templates across one sample trigger (mono), progress running from the
+/// first to the last frame: `pitch` (semitones) / `detune` (cents) change the playback rate,
+/// `cutoff` sweeps a one-pole lowpass and `volume` / `gain` scale the output.
+pub fn apply_templates_to_sample(
+    samples: &[f32],
+    templates: &[AutomationParamTemplate],
+    sample_rate: u32,
+) -> Vec<f32> {
+    let find = |names: &[&str]| {
+        templates
+            .iter()
+            .find(|tpl| names.contains(&tpl.param_name.as_str()))
+    };
+
+    let mut out = match find(&["pitch", "detune"]) {
+        Some(tpl) if samples.len() > 1 => {
+            let to_semitones = if tpl.param_name == "detune" {
+                0.01
             } else {
-                local // Standard linear interpolation
+                1.0
             };
+            let len = samples.len() as f32;
+            let mut out = Vec::with_capacity(samples.len());
+            let mut pos = 0.0f32;
+            while pos < len - 1.0 {
+                let semitones = evaluate_template_at(tpl, pos / len) * to_semitones;
+                let idx = pos as usize;
+                let frac = pos - idx as f32;
+                out.push(samples[idx] + (samples[idx + 1] - samples[idx]) * frac);
+                pos += 2f32.powf(semitones.clamp(-48.0, 48.0) / 12.0);
+            }
+            out
+        }
+        _ => samples.to_vec(),
+    };
 
-            return v0 + (v1 - v0) * eased_local;
+    let len = out.len().max(1) as f32;
+    if let Some(tpl) = find(&["cutoff"]) {
+        let fs = sample_rate.max(1) as f32;
+        let mut state = 0.0f32;
+        for (i, sample) in out.iter_mut().enumerate() {
+            let fc = evaluate_template_at(tpl, i as f32 / len).clamp(20.0, fs * 0.49);
+            let omega = 2.0 * std::f32::consts::PI * fc / fs;
+            state += omega / (omega + 1.0) * (*sample - state);
+            *sample = state;
         }
     }
-
-    // Fallback
-    tpl.points.last().unwrap().1
+    if let Some(tpl) = find(&["volume", "gain"]) {
+        for (i, sample) in out.iter_mut().enumerate() {
+            *sample *= evaluate_template_at(tpl, i as f32 / len);
+        }
+    }
+    out
 }
 
 /// Parse single automation parameter from Value
@@ -287,6 +570,7 @@ fn parse_automation_param(value: &Value) -> Option<AutomationParam> {
             start_time,
             duration,
             curve,
+            ease: None,
         })
     } else {
         None
@@ -306,11 +590,34 @@ impl AutomationRegistry {
         }
     }
 
-    /// Register an automation envelope
-    pub fn register(&mut self, envelope: AutomationEnvelope) {
+    /// Register an automation envelope; formulas already set on the target are kept
+    pub fn register(&mut self, mut envelope: AutomationEnvelope) {
+        if let Some(previous) = self.envelopes.remove(&envelope.target) {
+            envelope.formulas.splice(0..0, previous.formulas);
+        }
         self.envelopes.insert(envelope.target.clone(), envelope);
     }
 
+    /// Drive `target`'s parameter with a formula from `formula.start_time` on
+    pub fn add_formula(&mut self, target: &str, formula: FormulaParam) {
+        self.envelopes
+            .entry(target.to_string())
+            .or_insert_with(|| AutomationEnvelope::new(target.to_string()))
+            .add_formula(formula);
+    }
+
+    /// Append a segment to `target`'s envelope, keeping the segments already there
+    pub fn add_param(&mut self, target: &str, param: AutomationParam) {
+        self.envelopes
+            .entry(target.to_string())
+            .or_insert_with(|| AutomationEnvelope::new(target.to_string()))
+            .add_param(param);
+    }
+
+    pub fn envelope(&self, target: &str) -> Option<&AutomationEnvelope> {
+        self.envelopes.get(target)
+    }
+
     /// Get automated value for a target and parameter at a specific time
     pub fn get_value(&self, target: &str, param_name: &str, time_seconds: f32) -> Option<f32> {
         self.envelopes
@@ -342,6 +649,7 @@ mod tests {
             start_time: 0.0,
             duration: 2.0,
             curve: AutomationCurve::Linear,
+            ease: None,
         };
 
         let mut envelope = AutomationEnvelope::new("synth1".to_string());
@@ -383,6 +691,7 @@ mod tests {
             start_time: 0.0,
             duration: 2.0,
             curve: AutomationCurve::Linear,
+            ease: None,
         });
 
         registry.register(envelope);
@@ -394,4 +703,147 @@ mod tests {
         assert!(value.is_some());
         assert!((value.unwrap() - 0.5).abs() < 0.001);
     }
+
+    #[test]
+    fn test_formula_param_follows_time_until_overridden() {
+        let formula = Formula::compile("0.5 + $time", &|_| None).unwrap();
+        let mut registry = AutomationRegistry::new();
+        registry.add_formula(
+            "lead",
+            FormulaParam {
+                param_name: "cutoff".to_string(),
+                formula: Arc::new(formula),
+                start_time: 1.0,
+                context: SpecialVarContext::default(),
+            },
+        );
+
+        // A later block automation keeps the formula and takes over once it starts
+        let mut envelope = AutomationEnvelope::new("lead".to_string());
+        envelope.add_param(AutomationParam {
+            param_name: "cutoff".to_string(),
+            from_value: 0.0,
+            to_value: 0.0,
+            start_time: 3.0,
+            duration: 1.0,
+            curve: AutomationCurve::Linear,
+            ease: None,
+        });
+        registry.register(envelope);
+
+        assert_eq!(registry.get_value("lead", "cutoff", 0.5), Some(0.0));
+        assert_eq!(registry.get_value("lead", "cutoff", 2.0), Some(2.5));
+        assert_eq!(registry.get_value("lead", "cutoff", 3.5), Some(0.0));
+        assert!(registry.envelope("lead").unwrap().has_formula("cutoff"));
+    }
+
+    fn ramp(param: &str, from: f32, to: f32) -> AutomationParamTemplate {
+        AutomationParamTemplate {
+            param_name: param.to_string(),
+            points: vec![
+                (0.0, from, SegmentMode::Linear),
+                (1.0, to, SegmentMode::Linear),
+            ],
+            timed_points: Vec::new(),
+            curve: AutomationCurve::Linear,
+            loop_length: None,
+            loop_span: None,
+        }
+    }
+
+    #[test]
+    fn test_sample_templates_ramp_gain_and_pitch() {
+        let samples = vec![1.0f32; 1000];
+
+        let faded = apply_templates_to_sample(&samples, &[ramp("gain", 0.0, 1.0)], 44100);
+        assert_eq!(faded.len(), 1000);
+        assert!(faded[0].abs() < 1e-6);
+        assert!(faded[500] > 0.45 && faded[500] < 0.55);
+        assert!(faded[999] > 0.99);
+
+        // An octave up for the whole trigger plays it back twice as fast
+        let raised = apply_templates_to_sample(&samples, &[ramp("pitch", 12.0, 12.0)], 44100);
+        assert!((raised.len() as i32 - 500).abs() <= 1);
+
+        // A cutoff sweep is a lowpass: the step response rises from silence
+        let swept = apply_templates_to_sample(&samples, &[ramp("cutoff", 200.0, 5000.0)], 44100);
+        assert!(swept[0] < 0.1);
+        assert!(swept[999] > 0.9);
+    }
+
+    #[test]
+    fn test_duration_keyed_points_resolve_against_block_length() {
+        let raw = "param cutoff { 0% = 0.0 1 beat = 0.5 1/2 + 250ms = 0.75 100% = 1.0 }";
+        let mut templates = parse_param_templates_from_raw(raw);
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].points.len(), 2);
+        assert_eq!(templates[0].timed_points.len(), 2);
+
+        // 2 seconds at 120 BPM: one beat is 25%, half a beat plus 250 ms is also 25%
+        templates[0].resolve_timed_points(2.0, 120.0);
+        let fracs: Vec<f32> = templates[0].points.iter().map(|p| p.0).collect();
+        assert_eq!(fracs, vec![0.0, 0.25, 0.25, 1.0]);
+        assert!(templates[0].timed_points.is_empty());
+    }
+
+    #[test]
+    fn test_segment_modes_hold_step_and_curves() {
+        let raw = "param cutoff curve smooth {\n    0% = 0.0 hold\n    25% = 1.0 step\n    50% = 0.5 linear\n    75% = 1.0\n    100% = 0.0\n}";
+        let templates = parse_param_templates_from_raw(raw);
+        let modes: Vec<SegmentMode> = templates[0].points.iter().map(|p| p.2).collect();
+        assert_eq!(
+            modes,
+            vec![
+                SegmentMode::Hold,
+                SegmentMode::Step,
+                SegmentMode::Linear,
+                SegmentMode::Curve(CurveType::EaseInOut),
+                SegmentMode::Curve(CurveType::EaseInOut),
+            ]
+        );
+
+        let tpl = &templates[0];
+        // Hold keeps 0.0 until 25%, step jumps straight to the 50% value
+        assert_eq!(evaluate_template_at(tpl, 0.2), 0.0);
+        assert_eq!(evaluate_template_at(tpl, 0.3), 0.5);
+        assert!((evaluate_template_at(tpl, 0.625) - 0.75).abs() < 1e-5);
+        // The param curve eases the segments without their own mode
+        assert!(evaluate_template_at(tpl, 0.8) > 0.85);
+
+        // Envelope segments follow the same modes
+        let mut envelope = AutomationEnvelope::new("lead".to_string());
+        for segment in tpl.segments(1.0, 4.0) {
+            envelope.add_param(segment);
+        }
+        assert_eq!(envelope.get_value("cutoff", 1.5), Some(0.0));
+        assert_eq!(envelope.get_value("cutoff", 2.5), Some(0.5));
+        assert!((envelope.get_value("cutoff", 3.5).unwrap() - 0.75).abs() < 1e-5);
+        assert_eq!(envelope.get_value("cutoff", 6.0), Some(0.0));
+    }
+
+    #[test]
+    fn test_loop_region_repeats_keyframes() {
+        let raw = "param gain loop 1 beat { 0% = 0.0 1/2 = 1.0 hold 100% = 0.0 }";
+        let mut templates = parse_param_templates_from_raw(raw);
+        assert_eq!(templates[0].loop_length, None);
+
+        // A 2 second block at 120 BPM holds four one-beat repetitions
+        templates[0].resolve_timed_points(2.0, 120.0);
+        let tpl = &templates[0];
+        assert_eq!(tpl.loop_length, Some(0.25));
+        let fracs: Vec<f32> = tpl.points.iter().map(|p| p.0).collect();
+        assert_eq!(fracs, vec![0.0, 0.5, 1.0]);
+        assert!((evaluate_template_at(tpl, 0.0625) - 0.5).abs() < 1e-5);
+        assert!((evaluate_template_at(tpl, 0.5625) - 0.5).abs() < 1e-5);
+        assert_eq!(evaluate_template_at(tpl, 0.95), 1.0);
+
+        let segments = tpl.segments(0.0, 2.0);
+        assert_eq!(segments.len(), 8);
+        let mut envelope = AutomationEnvelope::new("kit".to_string());
+        for segment in segments {
+            envelope.add_param(segment);
+        }
+        assert!((envelope.get_value("gain", 1.125).unwrap() - 0.5).abs() < 1e-5);
+        assert_eq!(envelope.get_value("gain", 1.9), Some(1.0));
+    }
 }
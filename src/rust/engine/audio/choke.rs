@@ -0,0 +1,81 @@
+//! Choke groups: a trigger cuts every sample of its group still ringing, like an
+//! open hi-hat silenced by the closed one on an MPC.
+
+use crate::engine::audio::events::AudioEvent;
+
+/// Fade out applied where a choked sample is cut, so the cut does not click
+pub const CHOKE_FADE_SECONDS: f32 = 0.005;
+
+/// Choke group of a sample URI, from the bank manifests loaded natively
+pub fn bank_choke_group(uri: &str) -> Option<String> {
+    #[cfg(feature = "cli")]
+    {
+        crate::engine::audio::samples::choke_group(uri)
+    }
+    #[cfg(not(feature = "cli"))]
+    {
+        let _ = uri;
+        None
+    }
+}
+
+/// For each event, the time (seconds) at which a later trigger of the same choke group
+/// cuts it. Triggers starting together do not choke each other.
+pub fn choke_times(
+    events: &[AudioEvent],
+    group_of: impl Fn(&str) -> Option<String>,
+) -> Vec<Option<f32>> {
+    let mut times = vec![None; events.len()];
+
+    // (start, event index) per group
+    let mut groups: Vec<(String, Vec<(f32, usize)>)> = Vec::new();
+    for (index, event) in events.iter().enumerate() {
+        let AudioEvent::Sample {
+            uri, start_time, ..
+        } = event
+        else {
+            continue;
+        };
+        let Some(group) = group_of(uri) else {
+            continue;
+        };
+        match groups.iter_mut().find(|(name, _)| *name == group) {
+            Some((_, members)) => members.push((*start_time, index)),
+            None => groups.push((group, vec![(*start_time, index)])),
+        }
+    }
+
+    for (_, mut members) in groups {
+        members.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (position, &(start, index)) in members.iter().enumerate() {
+            times[index] = members[position + 1..]
+                .iter()
+                .map(|&(next, _)| next)
+                .find(|&next| next > start);
+        }
+    }
+    times
+}
+
+/// Cut interleaved audio (`channels` wide) after `keep_frames`, fading the last
+/// `CHOKE_FADE_SECONDS` out
+pub fn cut(samples: &mut Vec<f32>, channels: usize, keep_frames: usize, sample_rate: u32) {
+    let channels = channels.max(1);
+    if samples.len() <= keep_frames * channels {
+        return;
+    }
+    samples.truncate(keep_frames * channels);
+
+    let fade = ((CHOKE_FADE_SECONDS * sample_rate as f32) as usize).clamp(1, keep_frames.max(1));
+    let first = keep_frames.saturating_sub(fade);
+    for frame in first..keep_frames {
+        let gain = (keep_frames - frame - 1) as f32 / fade as f32;
+        for sample in &mut samples[frame * channels..(frame + 1) * channels] {
+            *sample *= gain;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_choke.rs"]
+mod tests;
@@ -0,0 +1,84 @@
+//! Metronome track (`--click`, `metronome on`)
+//!
+//! Ticks fall on every beat of the meter (quarter notes in 4/4, eighths in 6/8) and follow
+//! the tempo map, so each one lands on the exact frame its beat is reached. Bar
+//! downbeats get a higher, louder blip.
+
+use crate::engine::audio::tempo::TempoMap;
+
+const CLICK_SECONDS: f32 = 0.03;
+const DOWNBEAT_HZ: f32 = 1600.0;
+const BEAT_HZ: f32 = 1000.0;
+
+/// One metronome tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Click {
+    /// Seconds from the start of the project
+    pub time: f32,
+    pub downbeat: bool,
+}
+
+/// Ticks in the first `length` seconds. `bpm` applies where the tempo map has no change.
+pub fn click_times(tempo_map: &TempoMap, bpm: f32, length: f32) -> Vec<Click> {
+    let mut clicks = Vec::new();
+    if bpm <= 0.0 || length <= 0.0 {
+        return clicks;
+    }
+    // Position in quarter notes, and ticks since the current bar started
+    let mut beat = 0.0f32;
+    let mut tick = 0usize;
+    loop {
+        let time = tempo_map.seconds_at(beat, bpm);
+        if time >= length {
+            break;
+        }
+        let (numerator, denominator) = tempo_map.meter_at(beat);
+        clicks.push(Click {
+            time,
+            downbeat: tick.is_multiple_of(numerator.max(1) as usize),
+        });
+
+        let mut next = beat + 4.0 / denominator.max(1) as f32;
+        tick += 1;
+        // A meter change starts a new bar, even between two ticks of the old meter
+        if let Some(change) = tempo_map
+            .meters
+            .iter()
+            .map(|change| change.beat)
+            .find(|&change| change > beat + 1e-4 && change < next + 1e-4)
+        {
+            next = change;
+            tick = 0;
+        }
+        beat = next;
+    }
+    clicks
+}
+
+/// Interleaved stereo track of `frames` frames with a blip at each click
+pub fn render_click(clicks: &[Click], sample_rate: u32, frames: usize) -> Vec<f32> {
+    let mut buffer = vec![0.0f32; frames * 2];
+    let rate = sample_rate.max(1) as f32;
+    let length = (CLICK_SECONDS * rate) as usize;
+    for click in clicks {
+        let start = (click.time as f64 * rate as f64).round() as usize;
+        let (freq, gain) = if click.downbeat {
+            (DOWNBEAT_HZ, 0.6)
+        } else {
+            (BEAT_HZ, 0.4)
+        };
+        for offset in 0..length.min(frames.saturating_sub(start)) {
+            let t = offset as f32 / rate;
+            let envelope = (-t / (CLICK_SECONDS / 4.0)).exp();
+            let sample = (std::f32::consts::TAU * freq * t).sin() * gain * envelope;
+            let frame = (start + offset) * 2;
+            buffer[frame] += sample;
+            buffer[frame + 1] += sample;
+        }
+    }
+    buffer
+}
+
+#[cfg(test)]
+#[path = "test_click.rs"]
+mod tests;
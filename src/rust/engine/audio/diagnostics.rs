@@ -0,0 +1,166 @@
+//! Silence diagnostics: how loud a render is over time, and why parts of it play nothing
+//!
+//! `AudioInterpreter::render_audio_diagnosed` renders with every group insert tapped and
+//! reports the level of each second of the master, when sound first starts, the level of
+//! each insert, sample triggers dropped because their sample is not loaded and trigger
+//! names that matched no sample. `SilenceReport::reasons` turns that into sentences a
+//! playground can show next to a silent program; `clipping_warning` does the same for
+//! a render that goes over full scale.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+/// Level (linear, about -60 dBFS) under which audio counts as silent
+pub const SILENCE_LEVEL: f32 = 0.001;
+
+/// A group insert to measure: its tapped stereo signal, empty when it rendered nothing
+pub struct InsertLevel<'a> {
+    pub name: String,
+    pub muted: bool,
+    pub signal: &'a [f32],
+}
+
+/// Level of one group insert after its effects and strip
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertActivity {
+    pub name: String,
+    pub muted: bool,
+    pub peak: f32,
+    pub rms: f32,
+    /// Seconds until the insert first goes above `SILENCE_LEVEL`
+    pub first_sound: Option<f32>,
+}
+
+/// Serialized in camelCase, as the web API's `SilenceReport` type declares it
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SilenceReport {
+    /// RMS of each second of the master, both channels together; the last one may be partial
+    pub rms_per_second: Vec<f32>,
+    /// Seconds until the master first goes above `SILENCE_LEVEL`
+    pub first_sound: Option<f32>,
+    pub peak: f32,
+    /// Every group insert by name; empty for routing graph renders, which are not tapped
+    pub inserts: Vec<InsertActivity>,
+    /// Sample triggers that rendered nothing because their sample is not loaded
+    pub dropped_events: usize,
+    /// URIs of those samples, sorted
+    pub missing_samples: Vec<String>,
+    /// Triggers that resolved to no sample, sorted
+    pub unresolved_triggers: Vec<String>,
+}
+
+impl SilenceReport {
+    /// Measure an interleaved stereo `master` and the tapped `inserts`. `dropped_samples`
+    /// holds the URI of each sample trigger that rendered nothing.
+    pub fn analyze(
+        master: &[f32],
+        sample_rate: u32,
+        inserts: &[InsertLevel],
+        dropped_samples: &[String],
+        unresolved_triggers: &BTreeSet<String>,
+    ) -> Self {
+        let second = (sample_rate as usize * 2).max(2);
+        let missing: BTreeSet<&String> = dropped_samples.iter().collect();
+        Self {
+            rms_per_second: master.chunks(second).map(rms).collect(),
+            first_sound: first_sound(master, sample_rate),
+            peak: peak(master),
+            inserts: inserts
+                .iter()
+                .map(|insert| InsertActivity {
+                    name: insert.name.clone(),
+                    muted: insert.muted,
+                    peak: peak(insert.signal),
+                    rms: rms(insert.signal),
+                    first_sound: first_sound(insert.signal, sample_rate),
+                })
+                .collect(),
+            dropped_events: dropped_samples.len(),
+            missing_samples: missing.into_iter().cloned().collect(),
+            unresolved_triggers: unresolved_triggers.iter().cloned().collect(),
+        }
+    }
+
+    /// Whether the master never goes above `SILENCE_LEVEL`
+    pub fn is_silent(&self) -> bool {
+        self.first_sound.is_none()
+    }
+
+    /// Why the render, or part of it, is silent, one sentence per cause
+    pub fn reasons(&self) -> Vec<String> {
+        let mut reasons = Vec::new();
+        for trigger in &self.unresolved_triggers {
+            reasons.push(format!(
+                "Trigger '{}' matches no sample, so it plays nothing",
+                trigger
+            ));
+        }
+        for uri in &self.missing_samples {
+            reasons.push(format!("Sample '{}' is not loaded", uri));
+        }
+        if self.dropped_events > 0 {
+            reasons.push(format!(
+                "{} sample trigger(s) dropped because their sample is missing",
+                self.dropped_events
+            ));
+        }
+        for insert in &self.inserts {
+            if insert.muted {
+                reasons.push(format!("Group '{}' is muted", insert.name));
+            } else if insert.first_sound.is_none() {
+                reasons.push(format!(
+                    "Group '{}' renders silence; check its notes, effects and strip gain",
+                    insert.name
+                ));
+            }
+        }
+        if self.rms_per_second.is_empty() {
+            reasons.push("Nothing was scheduled: the program plays no notes or samples".into());
+        } else if self.is_silent() && reasons.is_empty() {
+            reasons.push(format!(
+                "Output stays below {:.0} dBFS",
+                20.0 * SILENCE_LEVEL.log10()
+            ));
+        } else if let Some(start) = self.first_sound.filter(|start| *start >= 1.0) {
+            reasons.push(format!("Output is silent for the first {:.2}s", start));
+        }
+        reasons
+    }
+}
+
+fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Warning for an interleaved stereo `master` that goes over full scale: how many
+/// samples clip, the peak and when clipping starts
+pub fn clipping_warning(master: &[f32], sample_rate: u32) -> Option<String> {
+    let first = master.iter().position(|s| s.abs() > 1.0)?;
+    let clipped = master.iter().filter(|s| s.abs() > 1.0).count();
+    Some(format!(
+        "Output clips in {} samples (peak {:.1} dBFS, first at {:.2}s); lower gain or add a limiter",
+        clipped,
+        20.0 * peak(master).log10(),
+        (first / 2) as f32 / sample_rate as f32
+    ))
+}
+
+/// Seconds until interleaved stereo `samples` first exceed `SILENCE_LEVEL`
+fn first_sound(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let index = samples.iter().position(|s| s.abs() > SILENCE_LEVEL)?;
+    Some((index / 2) as f32 / sample_rate as f32)
+}
+
+#[cfg(test)]
+#[path = "test_diagnostics.rs"]
+mod tests;
@@ -0,0 +1,368 @@
+//! Sonic comparison of two renders (`devalang diff`)
+//!
+//! Renders are compared insert by insert: the master mix first, then any group inserts
+//! captured with a build. Each insert reports its RMS change; the masters also get a
+//! band-by-window spectral difference (heatmap data) and the timing shift of every onset
+//! found in both. A script refactor is sonically neutral when no insert changes level
+//! beyond a threshold and no onset appears, disappears or moves.
+
+use serde::Serialize;
+use std::f32::consts::PI;
+
+/// Frames per heatmap window (a power of two for the FFT)
+pub const SPECTRUM_FRAMES: usize = 2048;
+/// Log-spaced frequency bands per heatmap window
+pub const SPECTRUM_BANDS: usize = 24;
+/// Onsets further apart than this are reported as removed/added rather than shifted
+pub const ONSET_TOLERANCE_SECONDS: f32 = 0.05;
+
+/// Frames per step of the onset detector's energy envelope
+const ONSET_HOP: usize = 512;
+/// Level reported for digital silence
+const SILENCE_DB: f32 = -120.0;
+const LOWEST_BAND_HZ: f32 = 30.0;
+
+/// Interleaved audio of one insert (or of the master mix)
+#[derive(Debug, Clone)]
+pub struct InsertAudio {
+    pub name: String,
+    pub samples: Vec<f32>,
+    pub channels: usize,
+}
+
+impl InsertAudio {
+    pub fn new(name: impl Into<String>, samples: Vec<f32>, channels: usize) -> Self {
+        Self {
+            name: name.into(),
+            samples,
+            channels: channels.max(1),
+        }
+    }
+
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    fn mono(&self) -> Vec<f32> {
+        self.samples
+            .chunks(self.channels)
+            .map(|frame| frame.iter().sum::<f32>() / self.channels as f32)
+            .collect()
+    }
+}
+
+/// Level change of one insert; a side is `None` when the insert only exists in the other
+#[derive(Debug, Clone, Serialize)]
+pub struct InsertDiff {
+    pub name: String,
+    pub old_rms_db: Option<f32>,
+    pub new_rms_db: Option<f32>,
+}
+
+impl InsertDiff {
+    /// RMS change in dB, `None` for inserts added or removed
+    pub fn change_db(&self) -> Option<f32> {
+        Some(self.new_rms_db? - self.old_rms_db?)
+    }
+}
+
+/// Spectral difference of the masters: `cells[window][band]` is new minus old in dB
+#[derive(Debug, Clone, Serialize)]
+pub struct SpectralHeatmap {
+    pub window_seconds: f32,
+    /// `SPECTRUM_BANDS + 1` band edges in Hz
+    pub band_edges_hz: Vec<f32>,
+    pub cells: Vec<Vec<f32>>,
+}
+
+impl SpectralHeatmap {
+    pub fn max_abs_db(&self) -> f32 {
+        self.cells
+            .iter()
+            .flatten()
+            .fold(0.0f32, |max, cell| max.max(cell.abs()))
+    }
+
+    /// Start time of the first window where some band changed by more than `threshold_db`
+    pub fn first_change(&self, threshold_db: f32) -> Option<f32> {
+        self.cells
+            .iter()
+            .position(|bands| bands.iter().any(|cell| cell.abs() > threshold_db))
+            .map(|window| window as f32 * self.window_seconds)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnsetShift {
+    pub old_seconds: f32,
+    pub new_seconds: f32,
+}
+
+impl OnsetShift {
+    pub fn shift_ms(&self) -> f32 {
+        (self.new_seconds - self.old_seconds) * 1000.0
+    }
+}
+
+/// Onsets of the masters paired within `ONSET_TOLERANCE_SECONDS`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OnsetReport {
+    pub matched: Vec<OnsetShift>,
+    /// Onsets of the old render with no counterpart (seconds)
+    pub removed: Vec<f32>,
+    /// Onsets of the new render with no counterpart (seconds)
+    pub added: Vec<f32>,
+}
+
+impl OnsetReport {
+    pub fn max_shift_ms(&self) -> f32 {
+        self.matched
+            .iter()
+            .fold(0.0f32, |max, shift| max.max(shift.shift_ms().abs()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDiff {
+    pub sample_rate: u32,
+    pub length_change_seconds: f32,
+    pub inserts: Vec<InsertDiff>,
+    pub heatmap: SpectralHeatmap,
+    pub onsets: OnsetReport,
+}
+
+impl AudioDiff {
+    /// Compare two renders at `sample_rate`. The first insert of each side is the master.
+    pub fn compare(old: &[InsertAudio], new: &[InsertAudio], sample_rate: u32) -> Self {
+        let mut inserts: Vec<InsertDiff> = old
+            .iter()
+            .map(|insert| InsertDiff {
+                name: insert.name.clone(),
+                old_rms_db: Some(rms_db(&insert.samples)),
+                new_rms_db: new
+                    .iter()
+                    .find(|other| other.name == insert.name)
+                    .map(|other| rms_db(&other.samples)),
+            })
+            .collect();
+        inserts.extend(
+            new.iter()
+                .filter(|insert| !old.iter().any(|other| other.name == insert.name))
+                .map(|insert| InsertDiff {
+                    name: insert.name.clone(),
+                    old_rms_db: None,
+                    new_rms_db: Some(rms_db(&insert.samples)),
+                }),
+        );
+
+        let old_master = old.first().map(InsertAudio::mono).unwrap_or_default();
+        let new_master = new.first().map(InsertAudio::mono).unwrap_or_default();
+        let rate = sample_rate.max(1) as f32;
+
+        Self {
+            sample_rate,
+            length_change_seconds: (new_master.len() as f32 - old_master.len() as f32) / rate,
+            inserts,
+            heatmap: spectral_heatmap(&old_master, &new_master, sample_rate),
+            onsets: match_onsets(
+                &detect_onsets(&old_master, sample_rate),
+                &detect_onsets(&new_master, sample_rate),
+            ),
+        }
+    }
+
+    /// Neutral when every insert exists on both sides within `threshold_db` of its old
+    /// level and every onset is matched with a shift of at most `max_shift_ms`
+    pub fn is_neutral(&self, threshold_db: f32, max_shift_ms: f32) -> bool {
+        self.inserts.iter().all(|insert| {
+            insert
+                .change_db()
+                .is_some_and(|db| db.abs() <= threshold_db)
+        }) && self.onsets.removed.is_empty()
+            && self.onsets.added.is_empty()
+            && self.onsets.max_shift_ms() <= max_shift_ms
+    }
+}
+
+/// RMS level in dBFS
+pub fn rms_db(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return SILENCE_DB;
+    }
+    let power = samples
+        .iter()
+        .map(|&s| (s as f64) * (s as f64))
+        .sum::<f64>()
+        / samples.len() as f64;
+    if power <= 0.0 {
+        return SILENCE_DB;
+    }
+    (10.0 * power.log10()).max(SILENCE_DB as f64) as f32
+}
+
+/// Onset times (seconds) of mono audio: rises of the energy envelope that stand out from
+/// their surroundings, placed on the first sample reaching half the hit's peak
+pub fn detect_onsets(mono: &[f32], sample_rate: u32) -> Vec<f32> {
+    let envelope: Vec<f32> = mono
+        .chunks(ONSET_HOP)
+        .map(|hop| (hop.iter().map(|s| s * s).sum::<f32>() / hop.len() as f32).sqrt())
+        .collect();
+    let flux: Vec<f32> = envelope
+        .iter()
+        .enumerate()
+        .map(|(i, &level)| (level - i.checked_sub(1).map_or(0.0, |p| envelope[p])).max(0.0))
+        .collect();
+    let loudest = flux.iter().copied().fold(0.0f32, f32::max);
+    if loudest <= 1e-4 {
+        return Vec::new();
+    }
+
+    let mut onsets = Vec::new();
+    let mut last_hop: Option<usize> = None;
+    for hop in 0..flux.len() {
+        let around = hop.saturating_sub(8)..(hop + 9).min(flux.len());
+        let local_mean = flux[around.clone()].iter().sum::<f32>() / around.len() as f32;
+        let peak = flux[hop.saturating_sub(2)..(hop + 3).min(flux.len())]
+            .iter()
+            .copied()
+            .fold(0.0f32, f32::max);
+        let is_onset = flux[hop] >= peak
+            && flux[hop] > 1.5 * local_mean
+            && flux[hop] > 0.05 * loudest
+            && last_hop.is_none_or(|last| hop - last >= 3);
+        if !is_onset {
+            continue;
+        }
+        last_hop = Some(hop);
+
+        // Refine to sample precision inside the previous and current hop
+        let start = hop.saturating_sub(1) * ONSET_HOP;
+        let end = ((hop + 1) * ONSET_HOP).min(mono.len());
+        let region = &mono[start..end];
+        let level = region.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        let offset = region
+            .iter()
+            .position(|s| s.abs() >= 0.5 * level)
+            .unwrap_or(0);
+        onsets.push((start + offset) as f32 / sample_rate.max(1) as f32);
+    }
+    onsets
+}
+
+/// Pair sorted onset lists, nearest first within `ONSET_TOLERANCE_SECONDS`
+pub fn match_onsets(old: &[f32], new: &[f32]) -> OnsetReport {
+    let mut report = OnsetReport::default();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if (old[i] - new[j]).abs() <= ONSET_TOLERANCE_SECONDS {
+            report.matched.push(OnsetShift {
+                old_seconds: old[i],
+                new_seconds: new[j],
+            });
+            i += 1;
+            j += 1;
+        } else if old[i] < new[j] {
+            report.removed.push(old[i]);
+            i += 1;
+        } else {
+            report.added.push(new[j]);
+            j += 1;
+        }
+    }
+    report.removed.extend_from_slice(&old[i..]);
+    report.added.extend_from_slice(&new[j..]);
+    report
+}
+
+/// Band energy difference of two mono signals, one row per `SPECTRUM_FRAMES` window
+pub fn spectral_heatmap(old: &[f32], new: &[f32], sample_rate: u32) -> SpectralHeatmap {
+    let edges = band_edges(sample_rate);
+    let windows = old.len().max(new.len()).div_ceil(SPECTRUM_FRAMES);
+    let cells = (0..windows)
+        .map(|window| {
+            let old_bands = band_energies(old, window, &edges, sample_rate);
+            let new_bands = band_energies(new, window, &edges, sample_rate);
+            old_bands
+                .iter()
+                .zip(&new_bands)
+                .map(|(o, n)| 10.0 * ((n + 1e-10) / (o + 1e-10)).log10())
+                .collect()
+        })
+        .collect();
+    SpectralHeatmap {
+        window_seconds: SPECTRUM_FRAMES as f32 / sample_rate.max(1) as f32,
+        band_edges_hz: edges,
+        cells,
+    }
+}
+
+fn band_edges(sample_rate: u32) -> Vec<f32> {
+    let nyquist = sample_rate as f32 / 2.0;
+    let ratio = (nyquist / LOWEST_BAND_HZ).max(1.0);
+    (0..=SPECTRUM_BANDS)
+        .map(|band| LOWEST_BAND_HZ * ratio.powf(band as f32 / SPECTRUM_BANDS as f32))
+        .collect()
+}
+
+/// Hann-windowed energy per band of one window of `signal` (zero past its end)
+fn band_energies(signal: &[f32], window: usize, edges: &[f32], sample_rate: u32) -> Vec<f32> {
+    let start = window * SPECTRUM_FRAMES;
+    let mut re: Vec<f32> = (0..SPECTRUM_FRAMES)
+        .map(|i| {
+            let hann = 0.5 - 0.5 * (2.0 * PI * i as f32 / SPECTRUM_FRAMES as f32).cos();
+            signal.get(start + i).copied().unwrap_or(0.0) * hann
+        })
+        .collect();
+    let mut im = vec![0.0f32; SPECTRUM_FRAMES];
+    fft(&mut re, &mut im);
+
+    let mut bands = vec![0.0f32; SPECTRUM_BANDS];
+    let bin_hz = sample_rate as f32 / SPECTRUM_FRAMES as f32;
+    for bin in 1..SPECTRUM_FRAMES / 2 {
+        let band = edges.partition_point(|&edge| edge <= bin as f32 * bin_hz);
+        if (1..=SPECTRUM_BANDS).contains(&band) {
+            bands[band - 1] += re[bin] * re[bin] + im[bin] * im[bin];
+        }
+    }
+    bands
+}
+
+/// In-place radix-2 FFT; the length must be a power of two
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_re, w_im) = ((angle * k as f32).cos(), (angle * k as f32).sin());
+                let (a, b) = (start + k, start + k + len / 2);
+                let v_re = re[b] * w_re - im[b] * w_im;
+                let v_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - v_re;
+                im[b] = im[a] - v_im;
+                re[a] += v_re;
+                im[a] += v_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+#[path = "test_diff.rs"]
+mod tests;
@@ -0,0 +1,182 @@
+//! Sample editing primitives as pure functions
+//!
+//! Reverse, gain, fades, pitch, speed and stretch over `&[f32]`, returning new buffers.
+//! The host's effect processors, retrigger crossfades and sample repitching call these,
+//! and the plugin SDK re-exports them, so plugins and tests apply exactly what a render
+//! does. Functions taking `channels` read interleaved frames; the others treat the buffer
+//! as one channel.
+
+use crate::engine::audio::mixer::Interpolator;
+use crate::engine::audio::settings::ResampleQuality;
+
+/// Frames in reverse order (channels keep their place within each frame)
+pub fn reverse(samples: &[f32], channels: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    samples
+        .chunks(channels)
+        .rev()
+        .flat_map(|frame| frame.iter().copied())
+        .collect()
+}
+
+/// Every sample scaled by `gain` (linear)
+pub fn gain(samples: &[f32], gain: f32) -> Vec<f32> {
+    samples.iter().map(|sample| sample * gain).collect()
+}
+
+/// Linear gain of `db` decibels
+pub fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Equal-power fade in over the first `fade_frames` of interleaved audio. Gains are taken
+/// mid-frame, so they pair with `fade_out` to exactly constant power.
+pub fn fade_in(samples: &[f32], channels: usize, fade_frames: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    let mut out = samples.to_vec();
+    for (frame, chunk) in out.chunks_mut(channels).take(fade_frames).enumerate() {
+        let gain = ((frame as f32 + 0.5) / fade_frames as f32 * std::f32::consts::FRAC_PI_2).sin();
+        for sample in chunk {
+            *sample *= gain;
+        }
+    }
+    out
+}
+
+/// Equal-power fade out of interleaved audio over `fade_frames` from frame `at`, cut
+/// where the fade ends
+pub fn fade_out(samples: &[f32], channels: usize, at: usize, fade_frames: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    if samples.len() <= at * channels {
+        return samples.to_vec();
+    }
+    let end = ((at + fade_frames) * channels).min(samples.len());
+    let mut out = samples[..end].to_vec();
+    for (offset, chunk) in out[at * channels..].chunks_mut(channels).enumerate() {
+        let gain = ((offset as f32 + 0.5) / fade_frames as f32 * std::f32::consts::FRAC_PI_2).cos();
+        for sample in chunk {
+            *sample *= gain;
+        }
+    }
+    out
+}
+
+/// Resample mono PCM from `source_rate` to `target_rate`.
+///
+/// `Linear2` uses linear interpolation; sinc qualities use a Blackman-windowed
+/// sinc kernel with `quality.taps()` points, band-limited when downsampling.
+pub fn resample(
+    samples: &[f32],
+    source_rate: u32,
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> Vec<f32> {
+    if samples.is_empty() || source_rate == 0 || target_rate == 0 || source_rate == target_rate {
+        return samples.to_vec();
+    }
+
+    let step = source_rate as f64 / target_rate as f64;
+    let out_len = ((samples.len() as f64) / step).ceil() as usize;
+    // The kernel's cutoff drops when downsampling to avoid aliasing
+    let mut interpolator = Interpolator::new(quality, step);
+    (0..out_len)
+        .map(|j| {
+            interpolator.prepare(j as f64 * step, samples.len());
+            interpolator.apply(|k| samples[k])
+        })
+        .collect()
+}
+
+/// Mono PCM at `sample_rate` repitched by `semitones`, the way a sampler plays it: faster
+/// and shorter going up, slower and longer going down
+pub fn pitch(
+    samples: &[f32],
+    sample_rate: u32,
+    semitones: f32,
+    quality: ResampleQuality,
+) -> Vec<f32> {
+    let ratio = 2f32.powf(semitones / 12.0);
+    if (ratio - 1.0).abs() < 1e-4 || sample_rate == 0 {
+        return samples.to_vec();
+    }
+    // Playing `ratio` times faster is a conversion from a `ratio`-scaled source rate
+    let source_rate = (sample_rate as f32 * ratio).round() as u32;
+    resample(samples, source_rate, sample_rate, quality)
+}
+
+/// Mono PCM played `speed` times as fast (linear interpolation); a negative speed plays it
+/// backwards. Speeds closer to zero than 0.0001 are held there.
+pub fn speed(samples: &[f32], speed: f32) -> Vec<f32> {
+    let speed = if speed.abs() < 0.0001 {
+        0.0001f32.copysign(speed)
+    } else {
+        speed
+    };
+    if (speed - 1.0).abs() < f32::EPSILON {
+        return samples.to_vec();
+    }
+
+    let len = samples.len();
+    let new_len = (len as f32 / speed.abs()) as usize;
+    let at = |src_pos: f32| -> f32 {
+        if src_pos < 0.0 {
+            return 0.0;
+        }
+        let idx = src_pos.floor() as usize;
+        let frac = src_pos.fract();
+        if idx + 1 < len {
+            samples[idx] * (1.0 - frac) + samples[idx + 1] * frac
+        } else if idx < len {
+            samples[idx]
+        } else {
+            0.0
+        }
+    };
+    (0..new_len)
+        .map(|i| {
+            if speed > 0.0 {
+                at(i as f32 * speed)
+            } else {
+                // Map from the end for reversed playback
+                at((len as f32 - 1.0) - i as f32 * speed.abs())
+            }
+        })
+        .collect()
+}
+
+/// Interleaved audio read at `1 / factor` of its pace over the same number of frames
+/// (linear interpolation), the tail of a slowed-down read being cut
+pub fn stretch(samples: &[f32], channels: usize, factor: f32) -> Vec<f32> {
+    let channels = channels.max(1);
+    if factor == 1.0 || factor <= 0.0 {
+        return samples.to_vec();
+    }
+    let frames = samples.len() / channels;
+    let mut out = vec![0.0f32; samples.len()];
+    for i in 0..frames {
+        let src = i as f32 / factor;
+        let idx = src.floor() as usize;
+        let frac = src - idx as f32;
+        // Past the end a channel reads the first channel's sample, and that one silence
+        let mut first = 0.0;
+        for channel in 0..channels {
+            let a = samples
+                .get(idx * channels + channel)
+                .copied()
+                .unwrap_or(first);
+            if channel == 0 {
+                first = a;
+            }
+            let b = samples
+                .get((idx + 1) * channels + channel)
+                .copied()
+                .unwrap_or(a);
+            out[i * channels + channel] = a * (1.0 - frac) + b * frac;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[path = "test_dsp.rs"]
+mod tests;
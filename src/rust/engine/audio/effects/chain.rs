@@ -1,29 +1,41 @@
 /// Effect chain module - sequential processing of multiple effects
 use super::registry::{CloneableEffect, EffectRegistry};
+#[cfg(feature = "fx-modulation")]
 use crate::engine::audio::lfo::{LfoParams, LfoRate, LfoTarget, LfoWaveform};
+use crate::engine::plugin::effect::PLUGIN_EFFECT;
 use crate::language::syntax::ast::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Effect chain - processes audio through multiple effects in sequence
 #[derive(Debug)]
 pub struct EffectChain {
     effects: Vec<Box<dyn CloneableEffect>>,
-    registry: EffectRegistry,
+    registry: Arc<EffectRegistry>,
     synth_context: bool,
 }
 
 impl EffectChain {
-    /// Create a new effect chain with context
+    /// Create a new effect chain with context, able to use every built-in effect
     pub fn new(synth_context: bool) -> Self {
+        Self::with_registry(EffectRegistry::full(), synth_context)
+    }
+
+    /// Effect chain that only finds the effects in `registry`
+    pub fn with_registry(registry: Arc<EffectRegistry>, synth_context: bool) -> Self {
         Self {
             effects: Vec::new(),
-            registry: EffectRegistry::new(),
+            registry,
             synth_context,
         }
     }
 
     /// Add an effect to the chain if available in current context
     pub fn add_effect(&mut self, name: &str, params: Option<Value>) -> bool {
+        if name == PLUGIN_EFFECT {
+            return self.add_plugin_effect(params);
+        }
+
         if !self.registry.is_effect_available(name, self.synth_context) {
             return false;
         }
@@ -38,6 +50,33 @@ impl EffectChain {
         }
     }
 
+    /// Add a WASM effect export; params hold the normalized `ref` (see `engine::plugin::effect`)
+    #[cfg(feature = "cli")]
+    fn add_plugin_effect(&mut self, params: Option<Value>) -> bool {
+        let Some(Value::Map(params)) = params else {
+            return false;
+        };
+        match super::processors::PluginEffectProcessor::from_params(
+            &params,
+            super::processors::super_trait::PROCESS_CHANNELS,
+        ) {
+            Ok(processor) => {
+                self.effects.push(Box::new(processor));
+                true
+            }
+            Err(e) => {
+                crate::tools::logger::Logger::new().warn(format!("Skipping plugin effect: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Plugins are only loaded by native builds
+    #[cfg(not(feature = "cli"))]
+    fn add_plugin_effect(&mut self, _params: Option<Value>) -> bool {
+        false
+    }
+
     /// Process audio samples through all effects in the chain
     pub fn process(&mut self, samples: &mut [f32], sample_rate: u32) {
         for effect in &mut self.effects {
@@ -62,6 +101,22 @@ impl EffectChain {
         self.effects.is_empty()
     }
 
+    /// True when an effect produces a real stereo image (binaural, mid/side), so mono
+    /// sources must be upmixed before the chain runs
+    pub fn needs_stereo(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| matches!(effect.name(), "Binaural" | "MidSide"))
+    }
+
+    /// Seconds the chain keeps sounding after its input stops
+    pub fn tail_seconds(&self, sample_rate: u32) -> f32 {
+        self.effects
+            .iter()
+            .map(|effect| effect.tail_seconds(sample_rate))
+            .sum()
+    }
+
     /// Get all available effects for the current context
     pub fn available_effects(&self) -> Vec<&'static str> {
         self.registry.list_available_effects(self.synth_context)
@@ -81,7 +136,16 @@ impl Default for EffectChain {
 
 /// Build an effect chain from a Value::Array of effect definitions
 pub fn build_effect_chain(effects_array: &[Value], synth_context: bool) -> EffectChain {
-    let mut chain = EffectChain::new(synth_context);
+    build_effect_chain_with(EffectRegistry::full(), effects_array, synth_context)
+}
+
+/// `build_effect_chain` limited to the effects in `registry`
+pub fn build_effect_chain_with(
+    registry: Arc<EffectRegistry>,
+    effects_array: &[Value],
+    synth_context: bool,
+) -> EffectChain {
+    let mut chain = EffectChain::with_registry(registry, synth_context);
 
     for effect_value in effects_array {
         match effect_value {
@@ -126,10 +190,15 @@ fn build_effect_processor(
     params: Option<Value>,
     synth_context: bool,
 ) -> Option<Box<dyn CloneableEffect>> {
-    let base_processor = registry.get_effect(name, synth_context)?;
+    if !registry.is_effect_available(name, synth_context) {
+        return None;
+    }
+    // Effects without parameters (or without settings of their own) use the defaults
+    let base_processor = || registry.get_effect(name, synth_context);
 
     if let Some(Value::Map(params_map)) = params {
         match name {
+            #[cfg(feature = "fx-modulation")]
             "chorus" => {
                 let depth = get_f32_param(&params_map, "depth", 0.7);
                 let rate = get_f32_param(&params_map, "rate", 0.5);
@@ -138,6 +207,7 @@ fn build_effect_processor(
                     depth, rate, mix,
                 )))
             }
+            #[cfg(feature = "fx-modulation")]
             "flanger" => {
                 let depth = get_f32_param(&params_map, "depth", 0.7);
                 let rate = get_f32_param(&params_map, "rate", 0.5);
@@ -147,6 +217,7 @@ fn build_effect_processor(
                     depth, rate, feedback, mix,
                 )))
             }
+            #[cfg(feature = "fx-modulation")]
             "phaser" => {
                 let stages = get_f32_param(&params_map, "stages", 4.0) as usize;
                 let rate = get_f32_param(&params_map, "rate", 0.5);
@@ -157,6 +228,7 @@ fn build_effect_processor(
                     stages, rate, depth, feedback, mix,
                 )))
             }
+            #[cfg(feature = "fx-dynamics")]
             "compressor" => {
                 let threshold = get_f32_param(&params_map, "threshold", -20.0);
                 let ratio = get_f32_param(&params_map, "ratio", 4.0);
@@ -166,6 +238,37 @@ fn build_effect_processor(
                     threshold, ratio, attack, release,
                 )))
             }
+            #[cfg(feature = "fx-dynamics")]
+            "multiband" | "mbcomp" => {
+                use super::processors::multiband::BandSettings;
+                // `threshold` / `ratio` set every band, `<band>_threshold` etc. refine one band
+                let threshold = get_f32_param(&params_map, "threshold", -18.0);
+                let ratio = get_f32_param(&params_map, "ratio", 3.0);
+                let band = |prefix: &str| {
+                    BandSettings::new(
+                        get_f32_param(&params_map, &format!("{prefix}_threshold"), threshold),
+                        get_f32_param(&params_map, &format!("{prefix}_ratio"), ratio),
+                        get_f32_param(&params_map, &format!("{prefix}_makeup"), 0.0),
+                    )
+                };
+                Some(Box::new(
+                    super::processors::MultibandCompressorProcessor::new(
+                        get_f32_param(&params_map, "low_freq", 200.0),
+                        get_f32_param(&params_map, "high_freq", 2000.0),
+                        [band("low"), band("mid"), band("high")],
+                        get_f32_param(&params_map, "attack", 0.01),
+                        get_f32_param(&params_map, "release", 0.15),
+                    ),
+                ))
+            }
+            #[cfg(feature = "fx-dynamics")]
+            "transient" => {
+                let attack = get_f32_param(&params_map, "attack", 6.0);
+                let sustain = get_f32_param(&params_map, "sustain", 0.0);
+                Some(Box::new(super::processors::TransientShaperProcessor::new(
+                    attack, sustain,
+                )))
+            }
             "drive" => {
                 let amount = get_f32_param(&params_map, "amount", 0.7);
                 let mix = get_f32_param(&params_map, "mix", 0.5);
@@ -187,7 +290,12 @@ fn build_effect_processor(
                 )))
             }
             "delay" => {
-                let time = get_f32_param(&params_map, "time", 250.0);
+                // Musical times (`1/8d`) were converted to ms when the event was scheduled
+                let time = get_f32_param(
+                    &params_map,
+                    "time",
+                    get_f32_param(&params_map, "value", 250.0),
+                );
                 let feedback = get_f32_param(&params_map, "feedback", 0.4);
                 let mix = get_f32_param(&params_map, "mix", 0.3);
                 Some(Box::new(super::processors::DelayProcessor::new(
@@ -204,6 +312,7 @@ fn build_effect_processor(
 
                 Some(Box::new(super::processors::SpeedProcessor::new(speed)))
             }
+            #[cfg(feature = "fx-modulation")]
             "lfo" => {
                 // parse LFO params: rate, depth, waveform, target, phase
                 // rate may be a number or string like "1/8"
@@ -256,6 +365,118 @@ fn build_effect_processor(
                     cutoff_range,
                 )))
             }
+            #[cfg(feature = "fx-stereo")]
+            "binaural" => {
+                // `binaural: 45` is shorthand for the azimuth
+                let azimuth = get_f32_param(
+                    &params_map,
+                    "azimuth",
+                    get_f32_param(&params_map, "value", 0.0),
+                );
+                let elevation = get_f32_param(&params_map, "elevation", 0.0);
+                Some(Box::new(super::processors::BinauralProcessor::new(
+                    azimuth, elevation,
+                )))
+            }
+            #[cfg(feature = "fx-stereo")]
+            "midside" | "ms_encode" | "ms_decode" => {
+                use super::processors::MidSideMode;
+                let mode = match name {
+                    "ms_encode" => MidSideMode::Encode,
+                    "ms_decode" => MidSideMode::Decode,
+                    _ => MidSideMode::Process,
+                };
+                let mid = get_f32_param(&params_map, "mid", 1.0);
+                let side = get_f32_param(&params_map, "side", 1.0);
+                Some(Box::new(super::processors::MidSideProcessor::new(
+                    mode, mid, side,
+                )))
+            }
+            "gain" | "volume" => {
+                let gain =
+                    get_f32_param(&params_map, name, get_f32_param(&params_map, "value", 1.0));
+                Some(Box::new(super::processors::GainProcessor::new(gain)))
+            }
+            "pan" => {
+                let pan =
+                    get_f32_param(&params_map, "pan", get_f32_param(&params_map, "value", 0.0));
+                Some(Box::new(super::processors::PanProcessor::new(pan)))
+            }
+            "fadeIn" | "fadeOut" => {
+                // Milliseconds, as `time` or the bare value
+                let time = get_f32_param(
+                    &params_map,
+                    "time",
+                    get_f32_param(&params_map, "value", 0.0),
+                );
+                let (fade_in, fade_out) = if name == "fadeIn" {
+                    (time, 0.0)
+                } else {
+                    (0.0, time)
+                };
+                Some(Box::new(super::processors::FadeProcessor::new(
+                    fade_in, fade_out,
+                )))
+            }
+            "pitch" => {
+                let semitones = get_f32_param(
+                    &params_map,
+                    "semitones",
+                    get_f32_param(&params_map, "value", 0.0),
+                );
+                Some(Box::new(super::processors::PitchProcessor::new(semitones)))
+            }
+            #[cfg(feature = "fx-stereo")]
+            "stereo" => {
+                let width = get_f32_param(
+                    &params_map,
+                    "width",
+                    get_f32_param(&params_map, "value", 1.0),
+                );
+                Some(Box::new(super::processors::StereoProcessor::new(width)))
+            }
+            #[cfg(feature = "fx-character")]
+            "freeze" => {
+                let enabled = get_bool_param(
+                    &params_map,
+                    "enabled",
+                    get_bool_param(&params_map, "value", false),
+                );
+                let fade = get_f32_param(&params_map, "fade", 0.2);
+                let hold = get_f32_param(&params_map, "hold", 0.5);
+                Some(Box::new(super::processors::FreezeProcessor::new(
+                    enabled, fade, hold,
+                )))
+            }
+            #[cfg(feature = "fx-sampler")]
+            "slice" => {
+                let segments = get_f32_param(
+                    &params_map,
+                    "segments",
+                    get_f32_param(&params_map, "value", 4.0),
+                ) as i32;
+                let mode = match params_map.get("mode") {
+                    Some(Value::String(mode) | Value::Identifier(mode)) => mode.as_str(),
+                    _ => "sequential",
+                };
+                let crossfade = get_f32_param(&params_map, "crossfade", 0.01);
+                Some(Box::new(super::processors::SliceProcessor::new(
+                    segments, mode, crossfade,
+                )))
+            }
+            #[cfg(feature = "fx-sampler")]
+            "stretch" => {
+                let factor = get_f32_param(
+                    &params_map,
+                    "factor",
+                    get_f32_param(&params_map, "value", 1.0),
+                );
+                let pitch = get_f32_param(&params_map, "pitch", 0.0);
+                let formant = get_bool_param(&params_map, "formant", false);
+                Some(Box::new(super::processors::StretchProcessor::new(
+                    factor, pitch, formant,
+                )))
+            }
             "reverse" => {
                 let reverse = get_bool_param(
                     &params_map,
@@ -264,10 +485,10 @@ fn build_effect_processor(
                 );
                 Some(Box::new(super::processors::ReverseProcessor::new(reverse)))
             }
-            _ => Some(base_processor),
+            _ => base_processor(),
         }
     } else {
-        Some(base_processor)
+        base_processor()
     }
 }
 
@@ -350,4 +571,16 @@ mod tests {
         assert!(synth_chain.add_effect("reverb", None));
         assert!(trigger_chain.add_effect("reverb", None));
     }
+
+    #[test]
+    fn test_reverb_tail_follows_sample_rate() {
+        let mut chain = EffectChain::new(true);
+        assert!(chain.add_effect("reverb", None));
+
+        // The comb lengths are fixed in samples, so the tail halves at twice the rate
+        let tail_44k = chain.tail_seconds(44100);
+        let tail_88k = chain.tail_seconds(88200);
+        assert!(tail_44k > 0.0);
+        assert!((tail_88k - tail_44k / 2.0).abs() < 1e-4);
+    }
 }
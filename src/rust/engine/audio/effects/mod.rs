@@ -2,6 +2,7 @@
 pub mod chain;
 pub mod processors;
 pub mod registry;
+pub mod tempo_sync;
 
 use crate::language::syntax::ast::Value;
 use std::collections::HashMap;
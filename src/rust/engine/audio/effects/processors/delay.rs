@@ -70,6 +70,17 @@ impl EffectProcessor for DelayProcessor {
         self.buffer_pos = 0;
     }
 
+    fn tail_seconds(&self, _sample_rate: u32) -> f32 {
+        let time = self.time_ms / 1000.0;
+        if self.mix <= 0.0 {
+            0.0
+        } else if self.feedback <= 0.0 {
+            time
+        } else {
+            time * (0.001f32.ln() / self.feedback.ln()).max(1.0)
+        }
+    }
+
     fn name(&self) -> &str {
         "Delay"
     }
@@ -1,25 +1,26 @@
 use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
 
+/// Holds the first `hold` seconds it hears and loops them under the input, `fade` being
+/// the share of the frozen signal
 #[derive(Debug, Clone)]
 pub struct FreezeProcessor {
     pub enabled: bool,
     pub fade: f32,
     pub hold: f32,
-    buffer_l: Vec<f32>,
-    buffer_r: Vec<f32>,
-    captured: bool,
+    /// Interleaved stereo frames captured so far
+    frozen: Vec<f32>,
+    /// Next frozen frame to replay
+    position: usize,
 }
 
 impl FreezeProcessor {
     pub fn new(enabled: bool, fade: f32, hold: f32) -> Self {
-        let max = 44100usize * 5;
         Self {
             enabled,
             fade: fade.clamp(0.0, 1.0),
             hold: hold.clamp(0.05, 5.0),
-            buffer_l: vec![0.0; max],
-            buffer_r: vec![0.0; max],
-            captured: false,
+            frozen: Vec::new(),
+            position: 0,
         }
     }
 }
@@ -31,37 +32,27 @@ impl Default for FreezeProcessor {
 }
 
 impl EffectProcessor for FreezeProcessor {
-    fn process(&mut self, samples: &mut [f32], _sr: u32) {
+    fn process(&mut self, samples: &mut [f32], sr: u32) {
         if !self.enabled {
             return;
         }
-        // capture the buffer once then replay it
-        if !self.captured {
-            let len = samples.len() / 2;
-            for i in 0..len {
-                let idx = i * 2;
-                self.buffer_l[i] = samples[idx];
-                self.buffer_r[i] = if idx + 1 < samples.len() {
-                    samples[idx + 1]
-                } else {
-                    samples[idx]
-                };
+        let hold_frames = ((self.hold * sr as f32) as usize).max(1);
+        for frame in samples.chunks_exact_mut(2) {
+            // The captured frames play as they are; after that the loop runs under the input
+            if self.frozen.len() < hold_frames * 2 {
+                self.frozen.extend_from_slice(frame);
+                continue;
             }
-            self.captured = true;
-        }
-        let len = samples.len() / 2;
-        for i in 0..len {
-            let idx = i * 2;
-            let frozen_l = self.buffer_l[i];
-            let frozen_r = self.buffer_r[i];
-            samples[idx] = samples[idx] * (1.0 - self.fade) + frozen_l * self.fade;
-            if idx + 1 < samples.len() {
-                samples[idx + 1] = samples[idx + 1] * (1.0 - self.fade) + frozen_r * self.fade;
+            let frozen = &self.frozen[self.position * 2..self.position * 2 + 2];
+            for (sample, frozen) in frame.iter_mut().zip(frozen) {
+                *sample = *sample * (1.0 - self.fade) + frozen * self.fade;
             }
+            self.position = (self.position + 1) % hold_frames;
         }
     }
     fn reset(&mut self) {
-        self.captured = false;
+        self.frozen.clear();
+        self.position = 0;
     }
     fn name(&self) -> &str {
         "Freeze"
@@ -0,0 +1,164 @@
+use crate::engine::audio::dsp;
+use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
+use crate::engine::audio::settings::ResampleQuality;
+
+/// `gain` / `volume`: every sample scaled by a linear gain
+#[derive(Debug, Clone)]
+pub struct GainProcessor {
+    pub gain: f32,
+}
+
+impl GainProcessor {
+    pub fn new(gain: f32) -> Self {
+        Self {
+            gain: gain.max(0.0),
+        }
+    }
+}
+
+impl Default for GainProcessor {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl EffectProcessor for GainProcessor {
+    fn process(&mut self, samples: &mut [f32], _sr: u32) {
+        for sample in samples.iter_mut() {
+            *sample *= self.gain;
+        }
+    }
+    fn reset(&mut self) {}
+    fn name(&self) -> &str {
+        "Gain"
+    }
+}
+
+/// `pan`: stereo balance from -1 (left) to 1 (right), with the channel strip's law
+#[derive(Debug, Clone)]
+pub struct PanProcessor {
+    pub pan: f32,
+}
+
+impl PanProcessor {
+    pub fn new(pan: f32) -> Self {
+        Self {
+            pan: pan.clamp(-1.0, 1.0),
+        }
+    }
+}
+
+impl Default for PanProcessor {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl EffectProcessor for PanProcessor {
+    fn process(&mut self, samples: &mut [f32], _sr: u32) {
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let left = (self.pan.max(0.0) * quarter).cos();
+        let right = ((-self.pan).max(0.0) * quarter).cos();
+        for frame in samples.chunks_exact_mut(2) {
+            frame[0] *= left;
+            frame[1] *= right;
+        }
+    }
+    fn reset(&mut self) {}
+    fn name(&self) -> &str {
+        "Pan"
+    }
+}
+
+/// `fadeIn` / `fadeOut`: equal-power fades in milliseconds. The fade in starts with the
+/// first buffer after a reset; the fade out ends each buffer it is given.
+#[derive(Debug, Clone, Default)]
+pub struct FadeProcessor {
+    pub fade_in_ms: f32,
+    pub fade_out_ms: f32,
+    /// Frames already faded in
+    position: usize,
+}
+
+impl FadeProcessor {
+    pub fn new(fade_in_ms: f32, fade_out_ms: f32) -> Self {
+        Self {
+            fade_in_ms: fade_in_ms.max(0.0),
+            fade_out_ms: fade_out_ms.max(0.0),
+            position: 0,
+        }
+    }
+}
+
+impl EffectProcessor for FadeProcessor {
+    fn process(&mut self, samples: &mut [f32], sr: u32) {
+        let frames_of = |ms: f32| (ms / 1000.0 * sr as f32) as usize;
+        let fade_in = frames_of(self.fade_in_ms);
+        if self.position < fade_in {
+            let quarter = std::f32::consts::FRAC_PI_2;
+            for frame in samples.chunks_mut(2) {
+                if self.position >= fade_in {
+                    break;
+                }
+                let gain = ((self.position as f32 + 0.5) / fade_in as f32 * quarter).sin();
+                for sample in frame {
+                    *sample *= gain;
+                }
+                self.position += 1;
+            }
+        }
+
+        let frames = samples.len() / 2;
+        let fade_out = frames_of(self.fade_out_ms).min(frames);
+        if fade_out > 0 {
+            let faded = dsp::fade_out(samples, 2, frames - fade_out, fade_out);
+            samples[..faded.len()].copy_from_slice(&faded);
+        }
+    }
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+    fn name(&self) -> &str {
+        "Fade"
+    }
+}
+
+/// `pitch`: each channel repitched by semitones the way a sampler plays it, the buffer
+/// keeping its length (a shorter result ends in silence, a longer one is cut)
+#[derive(Debug, Clone)]
+pub struct PitchProcessor {
+    pub semitones: f32,
+}
+
+impl PitchProcessor {
+    pub fn new(semitones: f32) -> Self {
+        Self {
+            semitones: semitones.clamp(-48.0, 48.0),
+        }
+    }
+}
+
+impl Default for PitchProcessor {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl EffectProcessor for PitchProcessor {
+    fn process(&mut self, samples: &mut [f32], sr: u32) {
+        if self.semitones == 0.0 {
+            return;
+        }
+        for channel in 0..2 {
+            let mono: Vec<f32> = samples.iter().skip(channel).step_by(2).copied().collect();
+            let pitched = dsp::pitch(&mono, sr, self.semitones, ResampleQuality::Linear2);
+            for (frame, sample) in samples.chunks_exact_mut(2).enumerate() {
+                sample[channel] = pitched.get(frame).copied().unwrap_or(0.0);
+            }
+        }
+    }
+    fn reset(&mut self) {}
+    fn name(&self) -> &str {
+        "Pitch"
+    }
+}
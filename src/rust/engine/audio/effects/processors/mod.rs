@@ -1,53 +1,101 @@
 pub mod bandpass;
+#[cfg(feature = "fx-character")]
 pub mod bitcrush;
+#[cfg(feature = "fx-modulation")]
 pub mod chorus;
+#[cfg(feature = "fx-dynamics")]
 pub mod compressor;
 pub mod delay;
+#[cfg(feature = "fx-character")]
 pub mod distortion;
 pub mod drive;
+#[cfg(feature = "fx-modulation")]
 pub mod flanger;
+#[cfg(feature = "fx-character")]
 pub mod freeze;
+#[cfg(feature = "fx-dynamics")]
 pub mod gate;
 pub mod highpass;
+pub mod level;
+#[cfg(feature = "fx-modulation")]
 pub mod lfo;
 pub mod lowpass;
 pub mod monoizer;
+#[cfg(feature = "fx-dynamics")]
+pub mod multiband;
+#[cfg(feature = "fx-modulation")]
 pub mod phaser;
+#[cfg(feature = "cli")]
+pub mod plugin;
 pub mod reverb;
 pub mod reverse;
+#[cfg(feature = "fx-sampler")]
 pub mod roll;
+#[cfg(feature = "fx-sampler")]
 pub mod slice;
+#[cfg(feature = "fx-stereo")]
+pub mod spatial;
 pub mod speed;
+#[cfg(feature = "fx-stereo")]
 pub mod stereo;
+#[cfg(feature = "fx-sampler")]
 pub mod stretch;
 pub mod super_trait;
+#[cfg(feature = "fx-dynamics")]
+pub mod transient;
+#[cfg(feature = "fx-modulation")]
 pub mod tremolo;
+#[cfg(feature = "fx-modulation")]
 pub mod vibrato;
 
+#[cfg(feature = "fx-modulation")]
 pub use chorus::ChorusProcessor;
+#[cfg(feature = "fx-dynamics")]
 pub use compressor::CompressorProcessor;
 pub use delay::DelayProcessor;
+#[cfg(feature = "fx-character")]
 pub use distortion::DistortionProcessor;
 pub use drive::DriveProcessor;
+#[cfg(feature = "fx-modulation")]
 pub use flanger::FlangerProcessor;
+#[cfg(feature = "fx-dynamics")]
 pub use gate::GateProcessor;
+#[cfg(feature = "fx-modulation")]
 pub use phaser::PhaserProcessor;
+#[cfg(feature = "cli")]
+pub use plugin::PluginEffectProcessor;
 pub use reverb::ReverbProcessor;
 pub use reverse::ReverseProcessor;
 pub use speed::SpeedProcessor;
 
 pub use bandpass::BandpassProcessor;
+#[cfg(feature = "fx-character")]
 pub use bitcrush::BitcrushProcessor;
+#[cfg(feature = "fx-character")]
 pub use freeze::FreezeProcessor;
 pub use highpass::HighpassProcessor;
+pub use level::{FadeProcessor, GainProcessor, PanProcessor, PitchProcessor};
+#[cfg(feature = "fx-modulation")]
 pub use lfo::LfoProcessor;
 pub use lowpass::LowpassProcessor;
 pub use monoizer::MonoizerProcessor;
+#[cfg(feature = "fx-dynamics")]
+pub use multiband::MultibandCompressorProcessor;
+#[cfg(feature = "fx-sampler")]
 pub use roll::RollProcessor;
+#[cfg(feature = "fx-sampler")]
 pub use slice::SliceProcessor;
+#[cfg(feature = "fx-stereo")]
+pub use spatial::{BinauralProcessor, MidSideMode, MidSideProcessor};
+#[cfg(feature = "fx-stereo")]
 pub use stereo::StereoProcessor;
+#[cfg(feature = "fx-sampler")]
 pub use stretch::StretchProcessor;
+#[cfg(feature = "fx-dynamics")]
+pub use transient::TransientShaperProcessor;
+#[cfg(feature = "fx-modulation")]
 pub use tremolo::TremoloProcessor;
+#[cfg(feature = "fx-modulation")]
 pub use vibrato::VibratoProcessor;
 
 // Re-export the trait too
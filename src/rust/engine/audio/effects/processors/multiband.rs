@@ -0,0 +1,175 @@
+use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
+
+/// Per-band settings of the multiband compressor
+#[derive(Debug, Clone, Copy)]
+pub struct BandSettings {
+    pub threshold: f32,
+    pub ratio: f32,
+    pub makeup: f32,
+}
+
+impl BandSettings {
+    pub fn new(threshold: f32, ratio: f32, makeup: f32) -> Self {
+        Self {
+            threshold: threshold.clamp(-60.0, 0.0),
+            ratio: ratio.clamp(1.0, 20.0),
+            makeup: makeup.clamp(-24.0, 24.0),
+        }
+    }
+}
+
+/// Gain computer for one band; smooths the gain reduction (in dB) so the band
+/// starts at unity and never boosts beyond its makeup gain.
+#[derive(Debug, Clone)]
+struct BandCompressor {
+    settings: BandSettings,
+    reduction_db: f32,
+}
+
+impl BandCompressor {
+    fn new(settings: BandSettings) -> Self {
+        Self {
+            settings,
+            reduction_db: 0.0,
+        }
+    }
+
+    fn gain(&mut self, level: f32, attack_coeff: f32, release_coeff: f32) -> f32 {
+        let db = if level > 0.0001 {
+            20.0 * level.log10()
+        } else {
+            -100.0
+        };
+        let target = if db > self.settings.threshold {
+            (self.settings.threshold - db) * (1.0 - 1.0 / self.settings.ratio)
+        } else {
+            0.0
+        };
+        let coeff = if target < self.reduction_db {
+            attack_coeff
+        } else {
+            release_coeff
+        };
+        self.reduction_db = target + coeff * (self.reduction_db - target);
+        10.0_f32.powf((self.reduction_db + self.settings.makeup) / 20.0)
+    }
+}
+
+/// One-pole lowpass state for both channels
+#[derive(Debug, Clone, Default)]
+struct Crossover {
+    prev_l: f32,
+    prev_r: f32,
+}
+
+impl Crossover {
+    fn split(&mut self, alpha: f32, left: f32, right: f32) -> ((f32, f32), (f32, f32)) {
+        self.prev_l += alpha * (left - self.prev_l);
+        self.prev_r += alpha * (right - self.prev_r);
+        (
+            (self.prev_l, self.prev_r),
+            (left - self.prev_l, right - self.prev_r),
+        )
+    }
+}
+
+/// 3-band compressor. Bands are split with complementary filters (high = input - low),
+/// so with every band at ratio 1 the bands sum back to the dry signal.
+#[derive(Debug, Clone)]
+pub struct MultibandCompressorProcessor {
+    low_freq: f32,
+    high_freq: f32,
+    attack: f32,
+    release: f32,
+    low_split: Crossover,
+    high_split: Crossover,
+    bands: [BandCompressor; 3],
+}
+
+impl MultibandCompressorProcessor {
+    pub fn new(
+        low_freq: f32,
+        high_freq: f32,
+        bands: [BandSettings; 3],
+        attack: f32,
+        release: f32,
+    ) -> Self {
+        let low_freq = low_freq.clamp(20.0, 20000.0);
+        Self {
+            low_freq,
+            high_freq: high_freq.clamp(low_freq, 20000.0),
+            attack: attack.max(0.0001),
+            release: release.max(0.001),
+            low_split: Crossover::default(),
+            high_split: Crossover::default(),
+            bands: bands.map(BandCompressor::new),
+        }
+    }
+}
+
+impl Default for MultibandCompressorProcessor {
+    fn default() -> Self {
+        Self::new(
+            200.0,
+            2000.0,
+            [
+                BandSettings::new(-18.0, 3.0, 0.0),
+                BandSettings::new(-18.0, 2.0, 0.0),
+                BandSettings::new(-18.0, 3.0, 0.0),
+            ],
+            0.01,
+            0.15,
+        )
+    }
+}
+
+fn one_pole_alpha(cutoff: f32, fs: f32) -> f32 {
+    let omega = 2.0 * std::f32::consts::PI * cutoff.min(fs * 0.49) / fs;
+    omega / (omega + 1.0)
+}
+
+impl EffectProcessor for MultibandCompressorProcessor {
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32) {
+        let fs = sample_rate as f32;
+        let low_alpha = one_pole_alpha(self.low_freq, fs);
+        let high_alpha = one_pole_alpha(self.high_freq, fs);
+        let attack_coeff = (-1.0 / (self.attack * fs)).exp();
+        let release_coeff = (-1.0 / (self.release * fs)).exp();
+
+        for frame in samples.chunks_mut(2) {
+            let left = frame[0];
+            let right = frame.get(1).copied().unwrap_or(left);
+
+            let (low, rest) = self.low_split.split(low_alpha, left, right);
+            let (mid, high) = self.high_split.split(high_alpha, rest.0, rest.1);
+
+            let mut out = (0.0, 0.0);
+            for (band, (l, r)) in self.bands.iter_mut().zip([low, mid, high]) {
+                let gain = if band.settings.ratio <= 1.0 && band.settings.makeup == 0.0 {
+                    1.0
+                } else {
+                    band.gain(l.abs().max(r.abs()), attack_coeff, release_coeff)
+                };
+                out.0 += l * gain;
+                out.1 += r * gain;
+            }
+
+            frame[0] = out.0;
+            if frame.len() > 1 {
+                frame[1] = out.1;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.low_split = Crossover::default();
+        self.high_split = Crossover::default();
+        for band in &mut self.bands {
+            band.reduction_db = 0.0;
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Multiband"
+    }
+}
@@ -0,0 +1,142 @@
+use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
+use crate::engine::plugin::effect::{PluginEffectRef, ordered_params, plugin_ref};
+use crate::engine::plugin::{loader::load_plugin, runner::EffectInstance};
+use crate::language::syntax::ast::Value;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use wasmtime::Engine;
+
+/// Compilation engine shared by every plugin effect; each processor owns its instance
+static EFFECT_ENGINE: Lazy<Engine> = Lazy::new(Engine::default);
+
+/// Loaded plugins by `author.name`, with the declared params of each export
+static PLUGIN_CACHE: Lazy<Mutex<HashMap<String, LoadedPlugin>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone)]
+struct LoadedPlugin {
+    wasm: Arc<Vec<u8>>,
+    exports: HashMap<String, (String, Vec<String>)>,
+}
+
+/// Effect export of a WASM plugin (`-> plugin(alias.export)`)
+#[derive(Debug)]
+pub struct PluginEffectProcessor {
+    reference: PluginEffectRef,
+    wasm: Arc<Vec<u8>>,
+    params: Vec<f32>,
+    /// Interleaved channels of the buffers passed to `process`
+    channels: u16,
+    /// Created on the first block and dropped by `reset`
+    instance: Option<EffectInstance>,
+    input: Vec<f32>,
+    failed: bool,
+}
+
+impl PluginEffectProcessor {
+    /// Load the effect referenced by normalized `plugin` params, for buffers of `channels`
+    /// interleaved channels
+    pub fn from_params(params: &HashMap<String, Value>, channels: u16) -> Result<Self, String> {
+        let reference = plugin_ref(params)
+            .ok_or_else(|| "plugin effect is missing its reference".to_string())?;
+        let plugin = load_cached(&reference)?;
+        let declared = match plugin.exports.get(&reference.export) {
+            Some((kind, declared)) if kind == "effect" => declared,
+            Some((kind, _)) => {
+                return Err(format!(
+                    "plugin export '{}' is a {} export, not an effect",
+                    reference, kind
+                ));
+            }
+            None => return Err(format!("plugin export '{}' not found", reference)),
+        };
+
+        Ok(Self {
+            params: ordered_params(declared, params),
+            wasm: plugin.wasm,
+            channels,
+            instance: None,
+            reference,
+            input: Vec::new(),
+            failed: false,
+        })
+    }
+}
+
+fn load_cached(reference: &PluginEffectRef) -> Result<LoadedPlugin, String> {
+    let key = format!("{}.{}", reference.author, reference.name);
+    let mut cache = PLUGIN_CACHE.lock().unwrap();
+    if let Some(plugin) = cache.get(&key) {
+        return Ok(plugin.clone());
+    }
+
+    let (info, bytes) = load_plugin(&reference.author, &reference.name)?;
+    let plugin = LoadedPlugin {
+        wasm: Arc::new(bytes),
+        exports: info
+            .exports
+            .into_iter()
+            .map(|export| (export.name, (export.kind, export.params)))
+            .collect(),
+    };
+    cache.insert(key, plugin.clone());
+    Ok(plugin)
+}
+
+impl Clone for PluginEffectProcessor {
+    fn clone(&self) -> Self {
+        // A copy gets its own instance so the two never share effect state
+        Self {
+            reference: self.reference.clone(),
+            wasm: self.wasm.clone(),
+            params: self.params.clone(),
+            channels: self.channels,
+            instance: None,
+            input: Vec::new(),
+            failed: self.failed,
+        }
+    }
+}
+
+impl EffectProcessor for PluginEffectProcessor {
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32) {
+        if self.failed || samples.is_empty() {
+            return;
+        }
+        self.input.clear();
+        self.input.extend_from_slice(samples);
+
+        let result = match &mut self.instance {
+            Some(instance) => Ok(instance),
+            slot => EffectInstance::new(&EFFECT_ENGINE, &self.wasm, &self.reference.export)
+                .map(|instance| slot.insert(instance)),
+        }
+        .and_then(|instance| {
+            instance.process(
+                &self.input,
+                samples,
+                sample_rate as i32,
+                self.channels as i32,
+                &self.params,
+            )
+        });
+        if let Err(e) = result {
+            // Leave the audio dry rather than failing the whole render
+            crate::tools::logger::Logger::new().warn(format!(
+                "Plugin effect '{}' disabled: {}",
+                self.reference, e
+            ));
+            samples.copy_from_slice(&self.input);
+            self.failed = true;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.instance = None;
+    }
+
+    fn name(&self) -> &str {
+        "Plugin"
+    }
+}
@@ -120,4 +120,25 @@ impl EffectProcessor for ReverbProcessor {
     fn name(&self) -> &str {
         "Reverb"
     }
+
+    fn tail_seconds(&self, sample_rate: u32) -> f32 {
+        if self.mix <= 0.0 {
+            return 0.0;
+        }
+        // Comb lengths are fixed in samples of the interleaved stream
+        let rate = sample_rate.max(1) as f32;
+        self.comb_buffers
+            .iter()
+            .zip(&self.comb_feedback)
+            .map(|(buffer, feedback)| {
+                let gain = feedback * (1.0 - self.damping);
+                let round_trip = buffer.len() as f32 / (2.0 * rate);
+                if gain <= 0.0 {
+                    round_trip
+                } else {
+                    round_trip * (0.001f32.ln() / gain.ln()).max(1.0)
+                }
+            })
+            .fold(0.0, f32::max)
+    }
 }
@@ -1,3 +1,4 @@
+use crate::engine::audio::dsp;
 use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
 
 #[derive(Debug, Clone)]
@@ -20,7 +21,8 @@ impl Default for ReverseProcessor {
 impl EffectProcessor for ReverseProcessor {
     fn process(&mut self, samples: &mut [f32], _sample_rate: u32) {
         if self.reversed {
-            samples.reverse();
+            let reversed = dsp::reverse(samples, 1);
+            samples.copy_from_slice(&reversed);
         }
     }
 
@@ -34,6 +34,8 @@ impl EffectProcessor for SliceProcessor {
         if self.mode == "random" {
             order.shuffle(&mut thread_rng());
         }
+        // Each slice fades in and out over `crossfade` of its length, so the joins don't click
+        let fade = (self.crossfade * seg_len as f32) as usize;
         let mut dst = 0usize;
         for &s in order.iter() {
             let start = s * seg_len;
@@ -41,9 +43,15 @@ impl EffectProcessor for SliceProcessor {
             for i in start..end {
                 let si = i * 2;
                 if dst < frames {
+                    let edge = (i - start).min(end - 1 - i);
+                    let gain = if edge < fade {
+                        (edge as f32 + 0.5) / fade as f32
+                    } else {
+                        1.0
+                    };
                     let di = dst * 2;
-                    out[di] = samples[si];
-                    out[di + 1] = samples.get(si + 1).copied().unwrap_or(samples[si]);
+                    out[di] = samples[si] * gain;
+                    out[di + 1] = samples.get(si + 1).copied().unwrap_or(samples[si]) * gain;
                     dst += 1;
                 }
             }
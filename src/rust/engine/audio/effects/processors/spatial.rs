@@ -0,0 +1,204 @@
+//! Spatial processors for headphone-oriented output
+//!
+//! - `binaural(azimuth, elevation)`: spherical-head HRTF approximation (interaural time and
+//!   level differences, head shadow on the far ear, pinna shading behind and below)
+//! - `ms_encode` / `ms_decode` / `midside(mid, side)`: mid/side conversion and gain
+
+use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
+
+/// Head radius (m) and speed of sound (m/s) used for the interaural time difference
+const HEAD_RADIUS: f32 = 0.0875;
+const SPEED_OF_SOUND: f32 = 343.0;
+
+/// One-pole lowpass coefficient for `cutoff` Hz
+fn one_pole_alpha(cutoff: f32, sample_rate: f32) -> f32 {
+    let omega = 2.0 * std::f32::consts::PI * cutoff.clamp(20.0, sample_rate * 0.49) / sample_rate;
+    omega / (omega + 1.0)
+}
+
+#[derive(Debug, Clone)]
+pub struct BinauralProcessor {
+    /// Degrees, 0 = front, 90 = right, -90 = left, ±180 = behind
+    pub azimuth: f32,
+    /// Degrees, 90 = above, -90 = below
+    pub elevation: f32,
+    delay_line: Vec<f32>,
+    write_pos: usize,
+    shadow_state: f32,
+    rear_state: [f32; 2],
+    pinna_state: [f32; 2],
+}
+
+impl BinauralProcessor {
+    pub fn new(azimuth: f32, elevation: f32) -> Self {
+        // Wrap azimuth into -180..180
+        let azimuth = (azimuth + 180.0).rem_euclid(360.0) - 180.0;
+        Self {
+            azimuth,
+            elevation: elevation.clamp(-90.0, 90.0),
+            delay_line: Vec::new(),
+            write_pos: 0,
+            shadow_state: 0.0,
+            rear_state: [0.0; 2],
+            pinna_state: [0.0; 2],
+        }
+    }
+
+    /// -1 (fully left) .. 1 (fully right)
+    fn lateral(&self) -> f32 {
+        self.azimuth.to_radians().sin() * self.elevation.to_radians().cos()
+    }
+
+    /// Interaural time difference in seconds (Woodworth spherical-head formula)
+    pub fn itd_seconds(&self) -> f32 {
+        let lateral = self.lateral().abs();
+        HEAD_RADIUS / SPEED_OF_SOUND * (lateral.asin() + lateral)
+    }
+}
+
+impl Default for BinauralProcessor {
+    fn default() -> Self {
+        Self::new(0.0, 0.0)
+    }
+}
+
+impl EffectProcessor for BinauralProcessor {
+    fn process(&mut self, samples: &mut [f32], sr: u32) {
+        let fs = sr.max(1) as f32;
+        let lateral = self.lateral();
+        let amount = lateral.abs();
+
+        let delay = self.itd_seconds() * fs;
+        if self.delay_line.len() < delay.ceil() as usize + 2 {
+            self.delay_line = vec![0.0; delay.ceil() as usize + 2];
+            self.write_pos = 0;
+        }
+        let len = self.delay_line.len();
+
+        // The far ear is delayed, quieter and darker; near ear gets a small boost
+        let shadow_alpha = one_pole_alpha(1500.0, fs);
+        let shadow = amount.sqrt();
+        let (near_gain, far_gain) = (1.0 + 0.15 * amount, 1.0 - 0.35 * amount);
+        // Sources behind the head lose some top end on both ears
+        let rear = (-self.azimuth.to_radians().cos()).max(0.0);
+        let rear_alpha = one_pole_alpha(6000.0, fs);
+        // Above is brighter, below is darker (pinna/torso shading)
+        let tilt = 0.5 * self.elevation.to_radians().sin();
+        let pinna_alpha = one_pole_alpha(4000.0, fs);
+
+        for frame in samples.chunks_mut(2) {
+            let input = if frame.len() == 2 {
+                (frame[0] + frame[1]) * 0.5
+            } else {
+                frame[0]
+            };
+
+            self.delay_line[self.write_pos] = input;
+            let read = (self.write_pos as f32 - delay).rem_euclid(len as f32);
+            let idx = read as usize;
+            let frac = read - idx as f32;
+            let delayed =
+                self.delay_line[idx] * (1.0 - frac) + self.delay_line[(idx + 1) % len] * frac;
+            self.write_pos = (self.write_pos + 1) % len;
+
+            self.shadow_state += shadow_alpha * (delayed - self.shadow_state);
+            let near = input * near_gain;
+            let far = (delayed + shadow * (self.shadow_state - delayed)) * far_gain;
+            let (mut left, mut right) = if lateral >= 0.0 {
+                (far, near)
+            } else {
+                (near, far)
+            };
+
+            for (channel, value) in [&mut left, &mut right].into_iter().enumerate() {
+                self.rear_state[channel] += rear_alpha * (*value - self.rear_state[channel]);
+                let shaded = *value + rear * (self.rear_state[channel] - *value);
+                self.pinna_state[channel] += pinna_alpha * (shaded - self.pinna_state[channel]);
+                let high = shaded - self.pinna_state[channel];
+                *value = self.pinna_state[channel] + high * (1.0 + tilt);
+            }
+
+            frame[0] = left;
+            if frame.len() == 2 {
+                frame[1] = right;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.delay_line.iter_mut().for_each(|s| *s = 0.0);
+        self.write_pos = 0;
+        self.shadow_state = 0.0;
+        self.rear_state = [0.0; 2];
+        self.pinna_state = [0.0; 2];
+    }
+
+    fn name(&self) -> &str {
+        "Binaural"
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MidSideMode {
+    /// L/R in, M/S out (mid on the left channel, side on the right)
+    Encode,
+    /// M/S in, L/R out
+    Decode,
+    /// L/R in and out, with mid and side gains applied in between
+    Process,
+}
+
+#[derive(Debug, Clone)]
+pub struct MidSideProcessor {
+    pub mode: MidSideMode,
+    pub mid: f32,
+    pub side: f32,
+}
+
+impl MidSideProcessor {
+    pub fn new(mode: MidSideMode, mid: f32, side: f32) -> Self {
+        Self {
+            mode,
+            mid: mid.max(0.0),
+            side: side.max(0.0),
+        }
+    }
+}
+
+impl Default for MidSideProcessor {
+    fn default() -> Self {
+        Self::new(MidSideMode::Process, 1.0, 1.0)
+    }
+}
+
+impl EffectProcessor for MidSideProcessor {
+    fn process(&mut self, samples: &mut [f32], _sr: u32) {
+        for frame in samples.chunks_exact_mut(2) {
+            let (a, b) = (frame[0], frame[1]);
+            let (out_a, out_b) = match self.mode {
+                MidSideMode::Encode => ((a + b) * 0.5 * self.mid, (a - b) * 0.5 * self.side),
+                MidSideMode::Decode => {
+                    let (mid, side) = (a * self.mid, b * self.side);
+                    (mid + side, mid - side)
+                }
+                MidSideMode::Process => {
+                    let mid = (a + b) * 0.5 * self.mid;
+                    let side = (a - b) * 0.5 * self.side;
+                    (mid + side, mid - side)
+                }
+            };
+            frame[0] = out_a;
+            frame[1] = out_b;
+        }
+    }
+
+    fn reset(&mut self) {}
+
+    fn name(&self) -> &str {
+        "MidSide"
+    }
+}
+
+#[cfg(test)]
+#[path = "test_spatial.rs"]
+mod tests;
@@ -1,3 +1,4 @@
+use crate::engine::audio::dsp;
 use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
 
 #[derive(Debug, Clone)]
@@ -39,62 +40,14 @@ impl EffectProcessor for SpeedProcessor {
             return; // No speed change needed
         }
 
-        let original_len = samples.len();
-        let speed_abs = self.speed.abs();
-        let new_len = (original_len as f32 / speed_abs) as usize;
-
-        // Resize buffer if needed
-        self.buffer.resize(new_len, 0.0);
-
-        // Simple linear interpolation for speed change
-        // For positive speeds, map dest -> src forward. For negative speeds, produce reversed playback.
-        if self.speed > 0.0 {
-            for i in 0..new_len {
-                let src_pos = i as f32 * self.speed;
-                let src_idx = src_pos.floor() as isize;
-                let frac = src_pos.fract();
-
-                if src_idx >= 0 && (src_idx as usize) + 1 < original_len {
-                    let idx = src_idx as usize;
-                    self.buffer[i] = samples[idx] * (1.0 - frac) + samples[idx + 1] * frac;
-                } else if src_idx >= 0 && (src_idx as usize) < original_len {
-                    self.buffer[i] = samples[src_idx as usize];
-                } else {
-                    self.buffer[i] = 0.0;
-                }
-            }
-        } else {
-            // speed < 0: reversed playback at magnitude 'speed_abs'
-            for i in 0..new_len {
-                // map destination index i to source position from the end
-                let src_pos_from_end = i as f32 * speed_abs;
-                // compute source position relative to start: original_len - 1 - src_pos_from_end
-                let src_pos = (original_len as f32 - 1.0) - src_pos_from_end;
-                if src_pos < 0.0 {
-                    self.buffer[i] = 0.0;
-                    continue;
-                }
-                let src_idx = src_pos.floor() as usize;
-                let frac = src_pos.fract();
-
-                if src_idx + 1 < original_len {
-                    self.buffer[i] = samples[src_idx] * (1.0 - frac) + samples[src_idx + 1] * frac;
-                } else if src_idx < original_len {
-                    self.buffer[i] = samples[src_idx];
-                } else {
-                    self.buffer[i] = 0.0;
-                }
-            }
-        }
+        self.buffer = dsp::speed(samples, self.speed);
 
         // Copy processed samples back into the provided slice.
         // We cannot change the slice length; copy as many as fit and zero the remainder.
-        let copy_len = std::cmp::min(original_len, new_len);
+        let copy_len = std::cmp::min(samples.len(), self.buffer.len());
         samples[..copy_len].copy_from_slice(&self.buffer[..copy_len]);
-        if new_len < original_len {
-            for s in &mut samples[new_len..original_len] {
-                *s = 0.0;
-            }
+        for s in &mut samples[copy_len..] {
+            *s = 0.0;
         }
     }
 
@@ -1,3 +1,4 @@
+use crate::engine::audio::dsp;
 use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
 
 #[derive(Debug, Clone)]
@@ -25,27 +26,9 @@ impl Default for StretchProcessor {
 
 impl EffectProcessor for StretchProcessor {
     fn process(&mut self, samples: &mut [f32], _sr: u32) {
-        // naive time-stretch by resampling: when factor>1 we speed up (shorter), <1 we stretch
-        let frames = samples.len() / 2;
-        if self.factor == 1.0 {
-            return;
-        }
-        let mut out = vec![0.0f32; samples.len()];
-        for i in 0..frames {
-            let src_f = (i as f32) / self.factor;
-            let idx = src_f.floor() as usize;
-            let frac = src_f - idx as f32;
-
-            let a_l = samples.get(idx * 2).copied().unwrap_or(0.0);
-            let b_l = samples.get((idx + 1) * 2).copied().unwrap_or(a_l);
-            let val_l = a_l * (1.0 - frac) + b_l * frac;
-            out[i * 2] = val_l;
-            let a_r = samples.get(idx * 2 + 1).copied().unwrap_or(a_l);
-            let b_r = samples.get((idx + 1) * 2 + 1).copied().unwrap_or(a_r);
-            let val_r = a_r * (1.0 - frac) + b_r * frac;
-            out[i * 2 + 1] = val_r;
-        }
-        samples.copy_from_slice(&out);
+        // naive time-stretch by resampling the interleaved stereo frames
+        let stretched = dsp::stretch(samples, 2, self.factor);
+        samples.copy_from_slice(&stretched);
     }
     fn reset(&mut self) {}
     fn name(&self) -> &str {
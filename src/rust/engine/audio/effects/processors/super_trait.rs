@@ -1,5 +1,8 @@
 use std::fmt::Debug;
 
+/// Channels interleaved in the buffers passed to `EffectProcessor::process`
+pub const PROCESS_CHANNELS: u16 = 2;
+
 /// Trait for all effect processors
 pub trait EffectProcessor: Send + Debug {
     /// Process audio samples (stereo interleaved)
@@ -10,4 +13,9 @@ pub trait EffectProcessor: Send + Debug {
 
     /// Get effect name
     fn name(&self) -> &str;
+
+    /// Seconds the effect keeps sounding after its input goes silent (to -60 dB)
+    fn tail_seconds(&self, _sample_rate: u32) -> f32 {
+        0.0
+    }
 }
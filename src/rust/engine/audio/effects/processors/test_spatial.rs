@@ -0,0 +1,64 @@
+use super::*;
+
+fn impulse_stereo(frames: usize) -> Vec<f32> {
+    let mut samples = vec![0.0; frames * 2];
+    samples[0] = 1.0;
+    samples[1] = 1.0;
+    samples
+}
+
+fn first_nonzero(samples: &[f32], channel: usize) -> Option<usize> {
+    samples
+        .chunks(2)
+        .position(|frame| frame[channel].abs() > 1e-6)
+}
+
+#[test]
+fn test_binaural_front_is_transparent() {
+    let mut samples = vec![0.25, -0.5, 0.75, 0.1];
+    let expected: Vec<f32> = samples
+        .chunks(2)
+        .flat_map(|f| [(f[0] + f[1]) * 0.5; 2])
+        .collect();
+    BinauralProcessor::new(0.0, 0.0).process(&mut samples, 44100);
+    for (got, want) in samples.iter().zip(&expected) {
+        assert!((got - want).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_binaural_right_source_delays_and_attenuates_left_ear() {
+    let mut samples = impulse_stereo(128);
+    let processor = BinauralProcessor::new(90.0, 0.0);
+    // Woodworth: a/c * (pi/2 + 1) is about 0.66 ms
+    assert!((processor.itd_seconds() - 0.000_656).abs() < 1e-5);
+
+    let mut processor = processor;
+    processor.process(&mut samples, 44100);
+    assert_eq!(first_nonzero(&samples, 1), Some(0));
+    let left_onset = first_nonzero(&samples, 0).unwrap();
+    assert!((28..=30).contains(&left_onset));
+
+    let energy =
+        |channel: usize| -> f32 { samples.chunks(2).map(|f| f[channel] * f[channel]).sum() };
+    assert!(energy(0) < energy(1) * 0.5);
+}
+
+#[test]
+fn test_mid_side_round_trip_and_width() {
+    let original = vec![0.8, 0.2, -0.4, 0.6];
+
+    let mut encoded = original.clone();
+    MidSideProcessor::new(MidSideMode::Encode, 1.0, 1.0).process(&mut encoded, 44100);
+    assert!((encoded[0] - 0.5).abs() < 1e-6 && (encoded[1] - 0.3).abs() < 1e-6);
+
+    MidSideProcessor::new(MidSideMode::Decode, 1.0, 1.0).process(&mut encoded, 44100);
+    for (got, want) in encoded.iter().zip(&original) {
+        assert!((got - want).abs() < 1e-6);
+    }
+
+    // Muting the side channel collapses to mono
+    let mut mono = original.clone();
+    MidSideProcessor::new(MidSideMode::Process, 1.0, 0.0).process(&mut mono, 44100);
+    assert!((mono[0] - mono[1]).abs() < 1e-6 && (mono[2] - mono[3]).abs() < 1e-6);
+}
@@ -0,0 +1,73 @@
+use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
+
+/// Transient shaper: compares a fast and a slow envelope follower. While the fast
+/// envelope leads (an onset) the attack gain applies, otherwise the sustain gain.
+#[derive(Debug, Clone)]
+pub struct TransientShaperProcessor {
+    attack: f32,
+    sustain: f32,
+    fast_env: f32,
+    slow_env: f32,
+}
+
+impl TransientShaperProcessor {
+    /// `attack` and `sustain` are gains in dB (-24.0 to 24.0)
+    pub fn new(attack: f32, sustain: f32) -> Self {
+        Self {
+            attack: attack.clamp(-24.0, 24.0),
+            sustain: sustain.clamp(-24.0, 24.0),
+            fast_env: 0.0,
+            slow_env: 0.0,
+        }
+    }
+}
+
+impl Default for TransientShaperProcessor {
+    fn default() -> Self {
+        Self::new(6.0, 0.0)
+    }
+}
+
+fn follow(env: f32, level: f32, attack_coeff: f32, release_coeff: f32) -> f32 {
+    let coeff = if level > env {
+        attack_coeff
+    } else {
+        release_coeff
+    };
+    level + coeff * (env - level)
+}
+
+impl EffectProcessor for TransientShaperProcessor {
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32) {
+        let fs = sample_rate as f32;
+        let coeff = |secs: f32| (-1.0 / (secs * fs)).exp();
+        let (fast_attack, slow_attack, release) = (coeff(0.0005), coeff(0.02), coeff(0.08));
+
+        for frame in samples.chunks_mut(2) {
+            let level = frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+            self.fast_env = follow(self.fast_env, level, fast_attack, release);
+            self.slow_env = follow(self.slow_env, level, slow_attack, release);
+
+            let transient = if self.fast_env > 0.0001 {
+                ((self.fast_env - self.slow_env) / self.fast_env).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let gain_db = self.attack * transient + self.sustain * (1.0 - transient);
+            let gain = 10.0_f32.powf(gain_db / 20.0);
+
+            for s in frame.iter_mut() {
+                *s *= gain;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.fast_env = 0.0;
+        self.slow_env = 0.0;
+    }
+
+    fn name(&self) -> &str {
+        "Transient"
+    }
+}
@@ -1,250 +1,221 @@
 use super::EffectAvailability;
 use crate::engine::audio::effects::processors::EffectProcessor;
 use crate::engine::audio::effects::processors::{
-    BandpassProcessor, BitcrushProcessor, FreezeProcessor, HighpassProcessor, LfoProcessor,
-    LowpassProcessor, MonoizerProcessor, ReverseProcessor, RollProcessor, SliceProcessor,
-    SpeedProcessor, StereoProcessor, StretchProcessor, TremoloProcessor, VibratoProcessor,
+    BandpassProcessor, DelayProcessor, DriveProcessor, FadeProcessor, GainProcessor,
+    HighpassProcessor, LowpassProcessor, MonoizerProcessor, PanProcessor, PitchProcessor,
+    ReverbProcessor, ReverseProcessor, SpeedProcessor,
 };
+#[cfg(feature = "fx-stereo")]
 use crate::engine::audio::effects::processors::{
-    ChorusProcessor, CompressorProcessor, DelayProcessor, DistortionProcessor, DriveProcessor,
-    FlangerProcessor, GateProcessor, PhaserProcessor, ReverbProcessor,
+    BinauralProcessor, MidSideMode, MidSideProcessor, StereoProcessor,
 };
+#[cfg(feature = "fx-character")]
+use crate::engine::audio::effects::processors::{
+    BitcrushProcessor, DistortionProcessor, FreezeProcessor,
+};
+#[cfg(feature = "fx-modulation")]
+use crate::engine::audio::effects::processors::{
+    ChorusProcessor, FlangerProcessor, LfoProcessor, PhaserProcessor, TremoloProcessor,
+    VibratoProcessor,
+};
+#[cfg(feature = "fx-dynamics")]
+use crate::engine::audio::effects::processors::{
+    CompressorProcessor, GateProcessor, MultibandCompressorProcessor, TransientShaperProcessor,
+};
+#[cfg(feature = "fx-sampler")]
+use crate::engine::audio::effects::processors::{RollProcessor, SliceProcessor, StretchProcessor};
+use anyhow::{Result, anyhow};
+use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Builds a processor with its default settings
+pub type EffectFactory = fn() -> Box<dyn CloneableEffect>;
+
+fn boxed<T: CloneableEffect + Default + 'static>() -> Box<dyn CloneableEffect> {
+    Box::new(T::default())
+}
+
+use EffectAvailability::{Both, TriggerOnly};
+
+/// Effects built into this binary: name, where it can be used and how to build it. The
+/// optional families only appear when their cargo feature is on (see `GATED_EFFECTS`).
+pub const BUILTIN_EFFECTS: &[(&str, EffectAvailability, EffectFactory)] = &[
+    // Also read as note and sample parameters; these run in effect chains and routes
+    ("gain", Both, boxed::<GainProcessor>),
+    ("volume", Both, boxed::<GainProcessor>),
+    ("pan", Both, boxed::<PanProcessor>),
+    ("fadeIn", Both, boxed::<FadeProcessor>),
+    ("fadeOut", Both, boxed::<FadeProcessor>),
+    ("pitch", Both, boxed::<PitchProcessor>),
+    ("drive", Both, boxed::<DriveProcessor>),
+    ("reverb", Both, boxed::<ReverbProcessor>),
+    ("delay", Both, boxed::<DelayProcessor>),
+    ("lowpass", Both, boxed::<LowpassProcessor>),
+    ("lpf", Both, boxed::<LowpassProcessor>),
+    ("highpass", Both, boxed::<HighpassProcessor>),
+    ("hpf", Both, boxed::<HighpassProcessor>),
+    ("bandpass", Both, boxed::<BandpassProcessor>),
+    ("bpf", Both, boxed::<BandpassProcessor>),
+    ("mono", Both, boxed::<MonoizerProcessor>),
+    ("monoizer", Both, boxed::<MonoizerProcessor>),
+    ("reverse", TriggerOnly, boxed::<ReverseProcessor>),
+    ("speed", TriggerOnly, boxed::<SpeedProcessor>),
+    #[cfg(feature = "fx-modulation")]
+    ("chorus", Both, boxed::<ChorusProcessor>),
+    #[cfg(feature = "fx-modulation")]
+    ("flanger", Both, boxed::<FlangerProcessor>),
+    #[cfg(feature = "fx-modulation")]
+    ("phaser", Both, boxed::<PhaserProcessor>),
+    #[cfg(feature = "fx-modulation")]
+    ("tremolo", Both, boxed::<TremoloProcessor>),
+    #[cfg(feature = "fx-modulation")]
+    ("vibrato", Both, boxed::<VibratoProcessor>),
+    #[cfg(feature = "fx-modulation")]
+    ("lfo", Both, boxed::<LfoProcessor>),
+    #[cfg(feature = "fx-dynamics")]
+    ("compressor", Both, boxed::<CompressorProcessor>),
+    #[cfg(feature = "fx-dynamics")]
+    ("comp", Both, boxed::<CompressorProcessor>),
+    #[cfg(feature = "fx-dynamics")]
+    ("gate", Both, boxed::<GateProcessor>),
+    #[cfg(feature = "fx-dynamics")]
+    ("multiband", Both, boxed::<MultibandCompressorProcessor>),
+    #[cfg(feature = "fx-dynamics")]
+    ("mbcomp", Both, boxed::<MultibandCompressorProcessor>),
+    #[cfg(feature = "fx-dynamics")]
+    ("transient", Both, boxed::<TransientShaperProcessor>),
+    #[cfg(feature = "fx-stereo")]
+    ("stereo", Both, boxed::<StereoProcessor>),
+    #[cfg(feature = "fx-stereo")]
+    ("binaural", Both, boxed::<BinauralProcessor>),
+    #[cfg(feature = "fx-stereo")]
+    ("midside", Both, boxed::<MidSideProcessor>),
+    #[cfg(feature = "fx-stereo")]
+    ("ms_encode", Both, || {
+        Box::new(MidSideProcessor::new(MidSideMode::Encode, 1.0, 1.0))
+    }),
+    #[cfg(feature = "fx-stereo")]
+    ("ms_decode", Both, || {
+        Box::new(MidSideProcessor::new(MidSideMode::Decode, 1.0, 1.0))
+    }),
+    #[cfg(feature = "fx-character")]
+    ("distortion", Both, boxed::<DistortionProcessor>),
+    #[cfg(feature = "fx-character")]
+    ("dist", Both, boxed::<DistortionProcessor>),
+    #[cfg(feature = "fx-character")]
+    ("bitcrush", Both, boxed::<BitcrushProcessor>),
+    #[cfg(feature = "fx-character")]
+    ("freeze", Both, boxed::<FreezeProcessor>),
+    #[cfg(feature = "fx-sampler")]
+    ("slice", TriggerOnly, boxed::<SliceProcessor>),
+    #[cfg(feature = "fx-sampler")]
+    ("stretch", TriggerOnly, boxed::<StretchProcessor>),
+    #[cfg(feature = "fx-sampler")]
+    ("roll", TriggerOnly, boxed::<RollProcessor>),
+];
+
+/// Optional effects and the cargo feature that builds them, whether or not it is on
+pub const GATED_EFFECTS: &[(&str, &str)] = &[
+    ("chorus", "fx-modulation"),
+    ("flanger", "fx-modulation"),
+    ("phaser", "fx-modulation"),
+    ("tremolo", "fx-modulation"),
+    ("vibrato", "fx-modulation"),
+    ("lfo", "fx-modulation"),
+    ("compressor", "fx-dynamics"),
+    ("comp", "fx-dynamics"),
+    ("gate", "fx-dynamics"),
+    ("multiband", "fx-dynamics"),
+    ("mbcomp", "fx-dynamics"),
+    ("transient", "fx-dynamics"),
+    ("stereo", "fx-stereo"),
+    ("binaural", "fx-stereo"),
+    ("midside", "fx-stereo"),
+    ("ms_encode", "fx-stereo"),
+    ("ms_decode", "fx-stereo"),
+    ("distortion", "fx-character"),
+    ("dist", "fx-character"),
+    ("bitcrush", "fx-character"),
+    ("freeze", "fx-character"),
+    ("slice", "fx-sampler"),
+    ("stretch", "fx-sampler"),
+    ("roll", "fx-sampler"),
+];
+
+/// Every built-in effect, shared by the chains of unrestricted interpreters
+static FULL: Lazy<Arc<EffectRegistry>> = Lazy::new(|| Arc::new(EffectRegistry::new()));
 
-/// Effect registry - stores available effects and their processors
-#[derive(Debug)]
+/// Effect registry - stores available effects and how to build their processors.
+/// Processors are only built when a chain asks for one.
+#[derive(Debug, Clone)]
 pub struct EffectRegistry {
-    effects: HashMap<&'static str, (EffectAvailability, Box<dyn CloneableEffect>)>,
+    effects: HashMap<&'static str, (EffectAvailability, EffectFactory)>,
 }
 
 impl EffectRegistry {
+    /// Every effect built into this binary
     pub fn new() -> Self {
         let mut registry = Self {
             effects: HashMap::new(),
         };
+        for (name, availability, build) in BUILTIN_EFFECTS {
+            registry.register_effect(name, *availability, *build);
+        }
+        registry
+    }
 
-        // Common effects
-        registry.register_effect(
-            "gain",
-            EffectAvailability::Both,
-            Box::new(DriveProcessor::default()),
-        );
-        registry.register_effect(
-            "volume",
-            EffectAvailability::Both,
-            Box::new(DriveProcessor::default()),
-        );
-        registry.register_effect(
-            "pan",
-            EffectAvailability::Both,
-            Box::new(DriveProcessor::default()),
-        );
-        registry.register_effect(
-            "fadeIn",
-            EffectAvailability::Both,
-            Box::new(DriveProcessor::default()),
-        );
-        registry.register_effect(
-            "fadeOut",
-            EffectAvailability::Both,
-            Box::new(DriveProcessor::default()),
-        );
-        registry.register_effect(
-            "pitch",
-            EffectAvailability::Both,
-            Box::new(DriveProcessor::default()),
-        );
-        registry.register_effect(
-            "chorus",
-            EffectAvailability::Both,
-            Box::new(ChorusProcessor::default()),
-        );
-        registry.register_effect(
-            "flanger",
-            EffectAvailability::Both,
-            Box::new(FlangerProcessor::default()),
-        );
-        registry.register_effect(
-            "phaser",
-            EffectAvailability::Both,
-            Box::new(PhaserProcessor::default()),
-        );
-        registry.register_effect(
-            "compressor",
-            EffectAvailability::Both,
-            Box::new(CompressorProcessor::default()),
-        );
-        registry.register_effect(
-            "gate",
-            EffectAvailability::Both,
-            Box::new(GateProcessor::default()),
-        );
-        registry.register_effect(
-            "drive",
-            EffectAvailability::Both,
-            Box::new(DriveProcessor::default()),
-        );
-        registry.register_effect(
-            "reverb",
-            EffectAvailability::Both,
-            Box::new(ReverbProcessor::default()),
-        );
-        registry.register_effect(
-            "delay",
-            EffectAvailability::Both,
-            Box::new(DelayProcessor::default()),
-        );
-        registry.register_effect(
-            "bitcrush",
-            EffectAvailability::Both,
-            Box::new(BitcrushProcessor::default()),
-        );
-        registry.register_effect(
-            "lowpass",
-            EffectAvailability::Both,
-            Box::new(LowpassProcessor::default()),
-        );
-        registry.register_effect(
-            "highpass",
-            EffectAvailability::Both,
-            Box::new(HighpassProcessor::default()),
-        );
-        registry.register_effect(
-            "bandpass",
-            EffectAvailability::Both,
-            Box::new(BandpassProcessor::default()),
-        );
-        registry.register_effect(
-            "tremolo",
-            EffectAvailability::Both,
-            Box::new(TremoloProcessor::default()),
-        );
-        registry.register_effect(
-            "vibrato",
-            EffectAvailability::Both,
-            Box::new(VibratoProcessor::default()),
-        );
-        registry.register_effect(
-            "mono",
-            EffectAvailability::Both,
-            Box::new(MonoizerProcessor::default()),
-        );
-        registry.register_effect(
-            "monoizer",
-            EffectAvailability::Both,
-            Box::new(MonoizerProcessor::default()),
-        );
-        registry.register_effect(
-            "stereo",
-            EffectAvailability::Both,
-            Box::new(StereoProcessor::default()),
-        );
-        registry.register_effect(
-            "freeze",
-            EffectAvailability::Both,
-            Box::new(FreezeProcessor::default()),
-        );
-        registry.register_effect(
-            "distortion",
-            EffectAvailability::Both,
-            Box::new(DistortionProcessor::default()),
-        );
-        registry.register_effect(
-            "dist",
-            EffectAvailability::Both,
-            Box::new(DistortionProcessor::default()),
-        );
-        registry.register_effect(
-            "lfo",
-            EffectAvailability::Both,
-            Box::new(LfoProcessor::default()),
-        );
-
-        // Trigger-only effects
-        registry.register_effect(
-            "reverse",
-            EffectAvailability::TriggerOnly,
-            Box::new(ReverseProcessor::default()),
-        );
-        registry.register_effect(
-            "speed",
-            EffectAvailability::TriggerOnly,
-            Box::new(SpeedProcessor::default()),
-        );
-        registry.register_effect(
-            "slice",
-            EffectAvailability::TriggerOnly,
-            Box::new(SliceProcessor::default()),
-        );
-        registry.register_effect(
-            "stretch",
-            EffectAvailability::TriggerOnly,
-            Box::new(StretchProcessor::default()),
-        );
-        registry.register_effect(
-            "roll",
-            EffectAvailability::TriggerOnly,
-            Box::new(RollProcessor::default()),
-        );
-
-        // Aliases
-        registry.register_effect(
-            "dist",
-            EffectAvailability::Both,
-            Box::new(DistortionProcessor::default()),
-        );
-        registry.register_effect(
-            "comp",
-            EffectAvailability::Both,
-            Box::new(CompressorProcessor::default()),
-        );
-        registry.register_effect(
-            "lpf",
-            EffectAvailability::Both,
-            Box::new(LowpassProcessor::default()),
-        );
-        registry.register_effect(
-            "hpf",
-            EffectAvailability::Both,
-            Box::new(HighpassProcessor::default()),
-        );
-        registry.register_effect(
-            "bpf",
-            EffectAvailability::Both,
-            Box::new(BandpassProcessor::default()),
-        );
+    /// Shared registry with every built-in effect
+    pub fn full() -> Arc<Self> {
+        Arc::clone(&FULL)
+    }
 
-        // Synth-only effects (could add more specific synth effects here)
+    /// Only the effects named in `names`; an effect that is not built in, or whose
+    /// feature is off, is an error
+    pub fn restricted_to<S: AsRef<str>>(names: impl IntoIterator<Item = S>) -> Result<Self> {
+        let full = Self::new();
+        let mut registry = Self {
+            effects: HashMap::new(),
+        };
+        for name in names {
+            let name = name.as_ref();
+            let Some((&key, entry)) = full.effects.get_key_value(name) else {
+                return Err(match Self::feature_for(name) {
+                    Some(feature) => anyhow!(
+                        "effect '{}' needs the '{}' feature, which this build lacks",
+                        name,
+                        feature
+                    ),
+                    None => anyhow!("unknown effect '{}'", name),
+                });
+            };
+            registry.effects.insert(key, *entry);
+        }
+        Ok(registry)
+    }
 
-        registry
+    /// Cargo feature an optional effect is built with
+    pub fn feature_for(name: &str) -> Option<&'static str> {
+        GATED_EFFECTS
+            .iter()
+            .find(|(effect, _)| *effect == name)
+            .map(|(_, feature)| *feature)
     }
 
-    /// Register a new effect with its availability and processor
+    /// Register a new effect with its availability and constructor
     pub fn register_effect(
         &mut self,
         name: &'static str,
         availability: EffectAvailability,
-        processor: Box<dyn CloneableEffect>,
+        build: EffectFactory,
     ) {
-        self.effects.insert(name, (availability, processor));
+        self.effects.insert(name, (availability, build));
     }
 
-    /// Get an effect processor by name if it's available for the given context
+    /// Build an effect processor by name if it's available for the given context
     pub fn get_effect(&self, name: &str, synth_context: bool) -> Option<Box<dyn CloneableEffect>> {
-        self.effects
-            .get(name)
-            .and_then(|(availability, processor)| {
-                match (availability, synth_context) {
-                    // In synth context, allow SynthOnly and Both
-                    (EffectAvailability::SynthOnly, true) | (EffectAvailability::Both, true) => {
-                        Some(processor.clone_box())
-                    }
-                    // In trigger context, allow TriggerOnly and Both
-                    (EffectAvailability::TriggerOnly, false)
-                    | (EffectAvailability::Both, false) => Some(processor.clone_box()),
-                    _ => None, // Effect not available in this context
-                }
-            })
+        self.is_effect_available(name, synth_context)
+            .then(|| (self.effects[name].1)())
     }
 
     /// Check if an effect exists and is available in the given context
@@ -263,21 +234,20 @@ impl EffectRegistry {
     /// List all available effects for a given context
     pub fn list_available_effects(&self, synth_context: bool) -> Vec<&'static str> {
         self.effects
-            .iter()
-            .filter_map(
-                |(&name, (availability, _))| match (availability, synth_context) {
-                    (EffectAvailability::SynthOnly, true)
-                    | (EffectAvailability::Both, true)
-                    | (EffectAvailability::TriggerOnly, false)
-                    | (EffectAvailability::Both, false) => Some(name),
-                    _ => None,
-                },
-            )
+            .keys()
+            .copied()
+            .filter(|name| self.is_effect_available(name, synth_context))
             .collect()
     }
 }
 
-/// Trait object that is cloneable: used to store prototype processors in the registry.
+impl Default for EffectRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Trait object that is cloneable, so a configured processor can be duplicated.
 /// This is a super-trait combining `EffectProcessor` behaviour and a `clone_box` method
 /// returning a boxed clone of the same dyn trait.
 pub trait CloneableEffect: EffectProcessor {
@@ -331,6 +301,40 @@ impl dyn EffectProcessor {
                     "Release time in seconds (0.001 to 2.0)".to_string(),
                 );
             }
+            "Multiband" => {
+                params.insert(
+                    "low_freq",
+                    "Low/mid crossover frequency (20.0 to 20000.0)".to_string(),
+                );
+                params.insert(
+                    "high_freq",
+                    "Mid/high crossover frequency (20.0 to 20000.0)".to_string(),
+                );
+                params.insert(
+                    "low_threshold / mid_threshold / high_threshold",
+                    "Per-band threshold in dB (-60.0 to 0.0)".to_string(),
+                );
+                params.insert(
+                    "low_ratio / mid_ratio / high_ratio",
+                    "Per-band compression ratio (1.0 to 20.0)".to_string(),
+                );
+                params.insert(
+                    "low_makeup / mid_makeup / high_makeup",
+                    "Per-band makeup gain in dB (-24.0 to 24.0)".to_string(),
+                );
+                params.insert(
+                    "attack",
+                    "Attack time in seconds (0.0001 to 1.0)".to_string(),
+                );
+                params.insert(
+                    "release",
+                    "Release time in seconds (0.001 to 2.0)".to_string(),
+                );
+            }
+            "Transient" => {
+                params.insert("attack", "Onset gain in dB (-24.0 to 24.0)".to_string());
+                params.insert("sustain", "Sustain gain in dB (-24.0 to 24.0)".to_string());
+            }
             "Drive" => {
                 params.insert("amount", "Drive amount (0.0 to 1.0)".to_string());
                 params.insert("tone", "Tone control (0.0 to 1.0)".to_string());
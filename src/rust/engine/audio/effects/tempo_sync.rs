@@ -0,0 +1,139 @@
+/// Tempo-synced effect parameters
+///
+/// Delay times may be written as musical values (`1/8`, `1/8d`, `1/4t`, `1 beat`,
+/// `1/8 + 5ms`) instead of milliseconds. They are resolved against the tempo in effect
+/// when the event is scheduled, so a `bpm` change mid-piece re-times the echoes of every
+/// later event while earlier ones keep their original spacing.
+use crate::language::syntax::ast::{DurationValue, Value};
+use crate::language::syntax::parser::driver::duration::parse_duration_token;
+use std::collections::HashMap;
+
+/// Effects with time parameters that accept musical values, and the keys holding them
+const SYNCED_PARAMS: &[(&str, &[&str])] = &[("delay", &["time", "length", "value"])];
+
+/// Return `effects` with every musical time parameter replaced by its length in
+/// milliseconds at `bpm`. Accepts the effect shapes events carry: an array of
+/// `{ type: "delay", ... }` entries or a map keyed by effect name.
+pub fn resolve_tempo_synced(effects: &Value, bpm: f32) -> Value {
+    match effects {
+        Value::Array(entries) => Value::Array(
+            entries
+                .iter()
+                .map(|entry| resolve_tempo_synced(entry, bpm))
+                .collect(),
+        ),
+        Value::Map(map) => {
+            let effect_type = match map.get("type").or_else(|| map.get("effect")) {
+                Some(Value::String(name)) | Some(Value::Identifier(name)) => Some(name.as_str()),
+                _ => None,
+            };
+            if let Some(name) = effect_type {
+                return Value::Map(resolve_params(name, map, bpm));
+            }
+            // `{ delay: { time: 1/8d } }` or `{ delay: 1/8d }`
+            Value::Map(
+                map.iter()
+                    .map(|(name, params)| {
+                        let resolved = match params {
+                            Value::Map(params) => Value::Map(resolve_params(name, params, bpm)),
+                            other if synced_keys(name).is_some() => {
+                                musical_ms(other, bpm).map_or_else(|| other.clone(), Value::Number)
+                            }
+                            other => other.clone(),
+                        };
+                        (name.clone(), resolved)
+                    })
+                    .collect(),
+            )
+        }
+        other => other.clone(),
+    }
+}
+
+/// Length in milliseconds of a musical time value at `bpm`. `None` for plain numbers
+/// (already milliseconds) and values that are not durations.
+pub fn musical_ms(value: &Value, bpm: f32) -> Option<f32> {
+    let duration = match value {
+        Value::Duration(duration) => duration.clone(),
+        Value::String(token) | Value::Identifier(token) => parse_duration_token(token).ok()?,
+        _ => return None,
+    };
+    match duration {
+        DurationValue::Identifier(_) | DurationValue::Auto => None,
+        other => other.to_seconds(bpm).map(|seconds| seconds * 1000.0),
+    }
+}
+
+fn synced_keys(effect: &str) -> Option<&'static [&'static str]> {
+    SYNCED_PARAMS
+        .iter()
+        .find(|(name, _)| *name == effect)
+        .map(|(_, keys)| *keys)
+}
+
+fn resolve_params(
+    effect: &str,
+    params: &HashMap<String, Value>,
+    bpm: f32,
+) -> HashMap<String, Value> {
+    let mut params = params.clone();
+    for key in synced_keys(effect).unwrap_or_default() {
+        if let Some(ms) = params.get(*key).and_then(|value| musical_ms(value, bpm)) {
+            params.insert(key.to_string(), Value::Number(ms));
+        }
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delay(time: Value) -> Value {
+        let mut map = HashMap::new();
+        map.insert("type".to_string(), Value::String("delay".to_string()));
+        map.insert("time".to_string(), time);
+        Value::Array(vec![Value::Map(map)])
+    }
+
+    fn time_of(effects: &Value) -> Value {
+        match effects {
+            Value::Array(entries) => match &entries[0] {
+                Value::Map(map) => map["time"].clone(),
+                other => panic!("expected map, got {:?}", other),
+            },
+            other => panic!("expected array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_musical_delay_time_follows_bpm() {
+        let effects = delay(Value::Identifier("1/8d".to_string()));
+        // A dotted eighth of a beat is 0.1875 beats: 93.75 ms at 120 BPM, 187.5 ms at 60 BPM
+        assert_eq!(
+            time_of(&resolve_tempo_synced(&effects, 120.0)),
+            Value::Number(93.75)
+        );
+        assert_eq!(
+            time_of(&resolve_tempo_synced(&effects, 60.0)),
+            Value::Number(187.5)
+        );
+    }
+
+    #[test]
+    fn test_millisecond_times_and_other_effects_are_untouched() {
+        let effects = delay(Value::Number(300.0));
+        assert_eq!(resolve_tempo_synced(&effects, 90.0), effects);
+
+        let mut reverb = HashMap::new();
+        reverb.insert("size".to_string(), Value::String("1/4".to_string()));
+        let mut by_name = HashMap::new();
+        by_name.insert("reverb".to_string(), Value::Map(reverb));
+        by_name.insert("delay".to_string(), Value::String("1 beat".to_string()));
+        let Value::Map(resolved) = resolve_tempo_synced(&Value::Map(by_name.clone()), 120.0) else {
+            panic!("expected map");
+        };
+        assert_eq!(resolved["reverb"], by_name["reverb"]);
+        assert_eq!(resolved["delay"], Value::Number(500.0));
+    }
+}
@@ -38,3 +38,90 @@ fn test_speed_processor() {
     assert_eq!(samples[2], 0.0);
     assert_eq!(samples[3], 0.0);
 }
+
+#[test]
+fn test_multiband_bands_sum_to_input_without_compression() {
+    use crate::engine::audio::effects::processors::multiband::BandSettings;
+    let unity = BandSettings::new(0.0, 1.0, 0.0);
+    let mut processor = MultibandCompressorProcessor::new(200.0, 2000.0, [unity; 3], 0.01, 0.1);
+    let input: Vec<f32> = (0..512)
+        .map(|i| (i as f32 * 0.05).sin() * 0.8 + (i as f32 * 0.9).sin() * 0.2)
+        .collect();
+    let mut samples = input.clone();
+    processor.process(&mut samples, 44100);
+    for (a, b) in samples.iter().zip(input.iter()) {
+        assert!((a - b).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn test_multiband_compresses_only_hot_band() {
+    use crate::engine::audio::effects::processors::multiband::BandSettings;
+    let unity = BandSettings::new(0.0, 1.0, 0.0);
+    let squash = BandSettings::new(-30.0, 20.0, 0.0);
+    // Low-frequency sine: only the low band is compressed
+    let input: Vec<f32> = (0..8820)
+        .flat_map(|i| {
+            let s = (i as f32 * 2.0 * std::f32::consts::PI * 60.0 / 44100.0).sin() * 0.9;
+            [s, s]
+        })
+        .collect();
+
+    let mut low_only =
+        MultibandCompressorProcessor::new(500.0, 5000.0, [squash, unity, unity], 0.001, 0.1);
+    let mut high_only =
+        MultibandCompressorProcessor::new(500.0, 5000.0, [unity, unity, squash], 0.001, 0.1);
+    let (mut a, mut b) = (input.clone(), input.clone());
+    low_only.process(&mut a, 44100);
+    high_only.process(&mut b, 44100);
+
+    let peak = |s: &[f32]| s[4410..].iter().fold(0.0f32, |m, x| m.max(x.abs()));
+    assert!(peak(&a) < peak(&b) * 0.5);
+}
+
+#[test]
+fn test_transient_shaper_boosts_onset_over_sustain() {
+    let mut processor = TransientShaperProcessor::new(12.0, -12.0);
+    // Step from silence to a constant level: the onset is boosted, the tail is cut
+    let mut samples = vec![0.0f32; 200];
+    samples.extend(std::iter::repeat_n(0.5, 20000));
+    processor.process(&mut samples, 44100);
+
+    let onset = samples[200..260].iter().fold(0.0f32, |m, x| m.max(x.abs()));
+    let tail = samples[samples.len() - 1].abs();
+    assert!(onset > 0.5);
+    assert!(tail < 0.5);
+}
+
+#[test]
+fn test_restricted_registry_keeps_only_the_named_effects() {
+    let registry = EffectRegistry::restricted_to(["reverb", "lpf", "speed"]).unwrap();
+    assert!(registry.is_effect_available("lpf", true));
+    assert!(!registry.is_effect_available("lowpass", true));
+    assert!(!registry.is_effect_available("chorus", true));
+    assert!(registry.get_effect("speed", false).is_some());
+    let mut names = registry.list_available_effects(true);
+    names.sort();
+    assert_eq!(names, vec!["lpf", "reverb"]);
+
+    let err = EffectRegistry::restricted_to(["reverb", "wobble"]).unwrap_err();
+    assert_eq!(err.to_string(), "unknown effect 'wobble'");
+    assert_eq!(EffectRegistry::feature_for("chorus"), Some("fx-modulation"));
+    assert_eq!(EffectRegistry::feature_for("reverb"), None);
+}
+
+#[test]
+fn test_every_gated_effect_is_built_with_its_feature() {
+    let registry = EffectRegistry::new();
+    for (name, _) in GATED_EFFECTS {
+        assert!(
+            registry.is_effect_available(name, true) || registry.is_effect_available(name, false),
+            "{}",
+            name
+        );
+    }
+    // Each entry builds a fresh processor on request
+    for (name, _, build) in BUILTIN_EFFECTS {
+        assert!(!build().name().is_empty(), "{}", name);
+    }
+}
@@ -4,10 +4,17 @@
 //! Currently supported:
 //! - WAV (via hound) - 16/24/32-bit
 //! - MP3 (via mp3lame-encoder) - 128/192/256/320 kbps
+//! - FLAC - 16/24-bit, Vorbis comment tags
+//! - ALAC (.m4a) - 16/24-bit, iTunes tags
 //!
-//! Planned: OGG Vorbis, FLAC, Opus
+//! Planned: OGG Vorbis, Opus
 
 use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+
+pub mod alac;
+mod bits;
+pub mod flac;
 
 /// Supported export formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,6 +23,7 @@ pub enum AudioFormat {
     Mp3,
     Ogg,
     Flac,
+    Alac,
     Opus,
 }
 
@@ -26,6 +34,7 @@ impl AudioFormat {
             "mp3" => Some(Self::Mp3),
             "ogg" | "vorbis" => Some(Self::Ogg),
             "flac" => Some(Self::Flac),
+            "alac" | "m4a" => Some(Self::Alac),
             "opus" => Some(Self::Opus),
             _ => None,
         }
@@ -37,12 +46,13 @@ impl AudioFormat {
             Self::Mp3 => "mp3",
             Self::Ogg => "ogg",
             Self::Flac => "flac",
+            Self::Alac => "alac",
             Self::Opus => "opus",
         }
     }
 
     pub fn is_supported(&self) -> bool {
-        matches!(self, Self::Wav | Self::Mp3)
+        matches!(self, Self::Wav | Self::Mp3 | Self::Flac | Self::Alac)
     }
 
     pub fn file_extension(&self) -> &'static str {
@@ -51,6 +61,7 @@ impl AudioFormat {
             Self::Mp3 => "mp3",
             Self::Ogg => "ogg",
             Self::Flac => "flac",
+            Self::Alac => "m4a",
             Self::Opus => "opus",
         }
     }
@@ -61,6 +72,7 @@ impl AudioFormat {
             Self::Mp3 => "audio/mpeg",
             Self::Ogg => "audio/ogg",
             Self::Flac => "audio/flac",
+            Self::Alac => "audio/mp4",
             Self::Opus => "audio/opus",
         }
     }
@@ -71,9 +83,10 @@ impl AudioFormat {
 pub struct EncoderOptions {
     pub format: AudioFormat,
     pub sample_rate: u32,
-    pub bit_depth: u8,     // For WAV/FLAC: 16, 24, 32
-    pub bitrate_kbps: u32, // For MP3/OGG/Opus: 128, 192, 256, 320
-    pub quality: f32,      // For OGG/Opus: 0.0-10.0 (quality scale)
+    pub bit_depth: u8,                  // For WAV/FLAC: 16, 24, 32
+    pub bitrate_kbps: u32,              // For MP3/OGG/Opus: 128, 192, 256, 320
+    pub quality: f32,                   // For OGG/Opus: 0.0-10.0 (quality scale)
+    pub tags: BTreeMap<String, String>, // For FLAC/ALAC: title, artist, album, ...
 }
 
 impl Default for EncoderOptions {
@@ -84,6 +97,7 @@ impl Default for EncoderOptions {
             bit_depth: 16,
             bitrate_kbps: 192,
             quality: 5.0,
+            tags: BTreeMap::new(),
         }
     }
 }
@@ -124,6 +138,28 @@ impl EncoderOptions {
             ..Default::default()
         }
     }
+
+    pub fn alac(sample_rate: u32, bit_depth: u8) -> Self {
+        Self {
+            format: AudioFormat::Alac,
+            sample_rate,
+            bit_depth,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_tags(mut self, tags: BTreeMap<String, String>) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+/// Scale normalized samples to signed integers of `bits` width
+pub fn quantize(pcm: &[f32], bits: u8) -> Vec<i32> {
+    let peak = ((1i64 << (bits - 1)) - 1) as f32;
+    pcm.iter()
+        .map(|sample| (sample.clamp(-1.0, 1.0) * peak).round() as i32)
+        .collect()
 }
 
 /// Encode PCM samples (f32, normalized -1.0 to 1.0) to the specified format
@@ -144,6 +180,7 @@ pub fn encode_audio(pcm_samples: &[f32], options: &EncoderOptions) -> Result<Vec
         }
         AudioFormat::Ogg => encode_ogg(pcm_samples, options),
         AudioFormat::Flac => encode_flac(pcm_samples, options),
+        AudioFormat::Alac => encode_alac(pcm_samples, options),
         AudioFormat::Opus => encode_opus(pcm_samples, options),
     }
 }
@@ -315,24 +352,48 @@ fn encode_ogg(_pcm_samples: &[f32], options: &EncoderOptions) -> Result<Vec<u8>>
         Workaround: Export to WAV and convert with ffmpeg:\n\
         - ffmpeg -i output.wav -c:a libvorbis -q:a {} output.ogg\n\
         \n\
-        Supported formats: WAV (16/24/32-bit)\n\
-        Coming soon: OGG Vorbis, FLAC, Opus",
+        Supported formats: WAV (16/24/32-bit), MP3, FLAC, ALAC\n\
+        Coming soon: OGG Vorbis, Opus",
         options.quality
     ))
 }
 
-/// Encode to FLAC format
-/// TODO: Implement using FLAC encoder
-fn encode_flac(_pcm_samples: &[f32], _options: &EncoderOptions) -> Result<Vec<u8>> {
-    Err(anyhow!(
-        "FLAC export not yet implemented. \n\
-        FLAC encoding is planned for v2.1.\n\
-        Workaround: Export to WAV and convert with ffmpeg:\n\
-        - ffmpeg -i output.wav -c:a flac output.flac\n\
-        \n\
-        Supported formats: WAV (16/24/32-bit)\n\
-        Coming soon: OGG Vorbis, FLAC, Opus"
-    ))
+/// Mono samples duplicated to stereo, like the WAV encoder, at the requested depth
+fn lossless_stereo(pcm_samples: &[f32], options: &EncoderOptions) -> Vec<i32> {
+    quantize(pcm_samples, options.bit_depth)
+        .into_iter()
+        .flat_map(|sample| [sample, sample])
+        .collect()
+}
+
+/// Encode to FLAC format (16 or 24-bit; 32-bit requests are written as 24-bit)
+fn encode_flac(pcm_samples: &[f32], options: &EncoderOptions) -> Result<Vec<u8>> {
+    let options = EncoderOptions {
+        bit_depth: options.bit_depth.min(24),
+        ..options.clone()
+    };
+    flac::encode(
+        &lossless_stereo(pcm_samples, &options),
+        2,
+        options.sample_rate,
+        options.bit_depth,
+        &options.tags,
+    )
+}
+
+/// Encode to ALAC in an .m4a container (16 or 24-bit)
+fn encode_alac(pcm_samples: &[f32], options: &EncoderOptions) -> Result<Vec<u8>> {
+    let options = EncoderOptions {
+        bit_depth: options.bit_depth.clamp(16, 24),
+        ..options.clone()
+    };
+    alac::encode(
+        &lossless_stereo(pcm_samples, &options),
+        2,
+        options.sample_rate,
+        options.bit_depth,
+        &options.tags,
+    )
 }
 
 /// Encode to Opus format
@@ -344,8 +405,8 @@ fn encode_opus(_pcm_samples: &[f32], options: &EncoderOptions) -> Result<Vec<u8>
         Workaround: Export to WAV and convert with ffmpeg:\n\
         - ffmpeg -i output.wav -c:a libopus -b:a {}k output.opus\n\
         \n\
-        Supported formats: WAV (16/24/32-bit)\n\
-        Coming soon: OGG Vorbis, FLAC, Opus",
+        Supported formats: WAV (16/24/32-bit), MP3, FLAC, ALAC\n\
+        Coming soon: OGG Vorbis, Opus",
         options.bitrate_kbps
     ))
 }
@@ -361,6 +422,7 @@ mod tests {
         assert_eq!(AudioFormat::from_str("ogg"), Some(AudioFormat::Ogg));
         assert_eq!(AudioFormat::from_str("vorbis"), Some(AudioFormat::Ogg));
         assert_eq!(AudioFormat::from_str("flac"), Some(AudioFormat::Flac));
+        assert_eq!(AudioFormat::from_str("m4a"), Some(AudioFormat::Alac));
         assert_eq!(AudioFormat::from_str("opus"), Some(AudioFormat::Opus));
         assert_eq!(AudioFormat::from_str("unknown"), None);
     }
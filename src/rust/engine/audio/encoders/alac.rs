@@ -0,0 +1,419 @@
+//! ALAC in an MP4 (`.m4a`) container with iTunes-style tags.
+//!
+//! Frames are written as ALAC escape (uncompressed) frames: lossless and readable by any
+//! ALAC decoder, but without the adaptive-Golomb compression of Apple's encoder. Use FLAC
+//! when file size matters.
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Seek, SeekFrom, Write};
+
+use anyhow::{Result, bail};
+
+use super::bits::BitWriter;
+
+/// Samples per ALAC frame, as declared in the magic cookie
+const FRAME_LENGTH: usize = 4096;
+
+/// Encode interleaved integer PCM (`bits` of 16 or 24 per sample) as an ALAC `.m4a` file.
+/// Well-known tags (`title`, `artist`, `album`, `year`, `genre`, `comment`, `composer`)
+/// map to their iTunes atoms; others are stored as freeform `----` entries.
+pub fn encode(
+    samples: &[i32],
+    channels: u8,
+    sample_rate: u32,
+    bits: u8,
+    tags: &BTreeMap<String, String>,
+) -> Result<Vec<u8>> {
+    let mut stream = AlacStream::new(Cursor::new(Vec::new()), channels, sample_rate, bits, tags)?;
+    stream.write(samples)?;
+    Ok(stream.finish()?.into_inner())
+}
+
+/// Incremental ALAC writer: packets go straight into `mdat`, and `finish` patches its
+/// size and appends the `moov` box indexing them
+pub struct AlacStream<W: Write + Seek> {
+    writer: W,
+    channels: usize,
+    sample_rate: u32,
+    bits: u8,
+    /// Byte offset of the `mdat` box, whose size `finish` fills in
+    mdat_offset: u64,
+    /// Samples not yet making up a whole frame
+    pending: Vec<i32>,
+    packet_sizes: Vec<u32>,
+    total_frames: u64,
+    /// Written with the index by `finish`
+    tags: BTreeMap<String, String>,
+}
+
+impl<W: Write + Seek> AlacStream<W> {
+    pub fn new(
+        mut writer: W,
+        channels: u8,
+        sample_rate: u32,
+        bits: u8,
+        tags: &BTreeMap<String, String>,
+    ) -> Result<Self> {
+        if !(1..=2).contains(&channels) {
+            bail!(
+                "ALAC export supports mono or stereo, got {} channels",
+                channels
+            );
+        }
+        if !matches!(bits, 16 | 24) {
+            bail!("ALAC export supports 16 or 24 bits, got {}", bits);
+        }
+
+        let ftyp = mp4_box(
+            b"ftyp",
+            &[b"M4A ".as_slice(), &0u32.to_be_bytes(), b"M4A mp42isom"].concat(),
+        );
+        writer.write_all(&ftyp)?;
+        let mdat_offset = writer.stream_position()?;
+        // Size written by `finish`
+        writer.write_all(&[0, 0, 0, 0])?;
+        writer.write_all(b"mdat")?;
+        Ok(Self {
+            writer,
+            channels: channels as usize,
+            sample_rate,
+            bits,
+            mdat_offset,
+            pending: Vec::new(),
+            packet_sizes: Vec::new(),
+            total_frames: 0,
+            tags: tags.clone(),
+        })
+    }
+
+    /// Append interleaved samples, encoding every frame completed so far
+    pub fn write(&mut self, samples: &[i32]) -> Result<()> {
+        self.pending.extend_from_slice(samples);
+        let frame = FRAME_LENGTH * self.channels;
+        let complete = self.pending.len() / frame * frame;
+        if complete > 0 {
+            let pending = std::mem::take(&mut self.pending);
+            for chunk in pending[..complete].chunks(frame) {
+                self.write_packet(chunk)?;
+            }
+            self.pending = pending[complete..].to_vec();
+        }
+        Ok(())
+    }
+
+    /// Encode the last, shorter frame, then write the index and the tags. Trailing samples
+    /// that do not make up a whole sample frame are dropped.
+    pub fn finish(mut self) -> Result<W> {
+        let whole = self.pending.len() / self.channels * self.channels;
+        if whole > 0 {
+            let pending = std::mem::take(&mut self.pending);
+            self.write_packet(&pending[..whole])?;
+        }
+
+        let end = self.writer.stream_position()?;
+        let data_len = end - self.mdat_offset - 8;
+        let duration_secs = self.total_frames as f64 / self.sample_rate.max(1) as f64;
+        let avg_bitrate = if duration_secs > 0.0 {
+            (data_len as f64 * 8.0 / duration_secs) as u32
+        } else {
+            0
+        };
+        let cookie = Cookie {
+            channels: self.channels as u8,
+            bits: self.bits,
+            sample_rate: self.sample_rate,
+            max_packet: self.packet_sizes.iter().copied().max().unwrap_or(0),
+            avg_bitrate,
+        };
+        let moov = moov(
+            &cookie,
+            self.total_frames as u32,
+            &self.packet_sizes,
+            (self.mdat_offset + 8) as u32,
+            &self.tags,
+        );
+        self.writer.write_all(&moov)?;
+
+        let after = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(self.mdat_offset))?;
+        self.writer
+            .write_all(&((data_len + 8) as u32).to_be_bytes())?;
+        self.writer.seek(SeekFrom::Start(after))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_packet(&mut self, frame: &[i32]) -> Result<()> {
+        let packet = encode_frame(frame, self.channels, self.bits);
+        self.packet_sizes.push(packet.len() as u32);
+        self.total_frames += (frame.len() / self.channels) as u64;
+        self.writer.write_all(&packet)?;
+        Ok(())
+    }
+}
+
+/// One escape frame: the element header flags raw samples, channel samples interleaved
+fn encode_frame(frame: &[i32], channels: usize, bits: u8) -> Vec<u8> {
+    let frame_samples = frame.len() / channels;
+    let partial = frame_samples != FRAME_LENGTH;
+
+    let mut w = BitWriter::default();
+    // Element tag: single (0) or channel pair (1) element, instance 0
+    w.write(if channels == 1 { 0 } else { 1 }, 3);
+    w.write(0, 4);
+    w.write(0, 12);
+    // Partial-frame flag, no shifted bytes, escape flag
+    w.write(if partial { 0b1001 } else { 0b0001 }, 4);
+    if partial {
+        w.write(frame_samples as u64, 32);
+    }
+    for &sample in frame {
+        w.write_signed(sample as i64, bits as u32);
+    }
+    // End-of-frame element
+    w.write(7, 3);
+    w.into_bytes()
+}
+
+struct Cookie {
+    channels: u8,
+    bits: u8,
+    sample_rate: u32,
+    max_packet: u32,
+    avg_bitrate: u32,
+}
+
+impl Cookie {
+    /// ALACSpecificConfig with Apple's default tuning parameters
+    fn bytes(&self) -> Vec<u8> {
+        let mut cookie = Vec::with_capacity(24);
+        cookie.extend_from_slice(&(FRAME_LENGTH as u32).to_be_bytes());
+        cookie.push(0); // compatible version
+        cookie.push(self.bits);
+        cookie.extend_from_slice(&[40, 10, 14]); // pb, mb, kb
+        cookie.push(self.channels);
+        cookie.extend_from_slice(&255u16.to_be_bytes()); // max run
+        cookie.extend_from_slice(&self.max_packet.to_be_bytes());
+        cookie.extend_from_slice(&self.avg_bitrate.to_be_bytes());
+        cookie.extend_from_slice(&self.sample_rate.to_be_bytes());
+        cookie
+    }
+}
+
+fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 8);
+    out.extend_from_slice(&(body.len() as u32 + 8).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    out
+}
+
+/// Box with a version/flags header
+fn full_box(kind: &[u8; 4], flags: u32, body: &[u8]) -> Vec<u8> {
+    mp4_box(kind, &[&flags.to_be_bytes(), body].concat())
+}
+
+const IDENTITY_MATRIX: [u32; 9] = [0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000];
+
+fn matrix() -> Vec<u8> {
+    IDENTITY_MATRIX
+        .iter()
+        .flat_map(|v| v.to_be_bytes())
+        .collect()
+}
+
+fn moov(
+    cookie: &Cookie,
+    total_frames: u32,
+    packet_sizes: &[u32],
+    chunk_offset: u32,
+    tags: &BTreeMap<String, String>,
+) -> Vec<u8> {
+    let rate = cookie.sample_rate;
+
+    let mut mvhd = Vec::new();
+    mvhd.extend_from_slice(&[0; 8]); // creation / modification time
+    mvhd.extend_from_slice(&rate.to_be_bytes());
+    mvhd.extend_from_slice(&total_frames.to_be_bytes());
+    mvhd.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // rate 1.0
+    mvhd.extend_from_slice(&0x0100u16.to_be_bytes()); // volume 1.0
+    mvhd.extend_from_slice(&[0; 10]);
+    mvhd.extend_from_slice(&matrix());
+    mvhd.extend_from_slice(&[0; 24]);
+    mvhd.extend_from_slice(&2u32.to_be_bytes()); // next track id
+
+    let mut tkhd = Vec::new();
+    tkhd.extend_from_slice(&[0; 8]);
+    tkhd.extend_from_slice(&1u32.to_be_bytes()); // track id
+    tkhd.extend_from_slice(&[0; 4]);
+    tkhd.extend_from_slice(&total_frames.to_be_bytes());
+    tkhd.extend_from_slice(&[0; 8]);
+    tkhd.extend_from_slice(&[0; 4]); // layer, alternate group
+    tkhd.extend_from_slice(&0x0100u16.to_be_bytes());
+    tkhd.extend_from_slice(&[0; 2]);
+    tkhd.extend_from_slice(&matrix());
+    tkhd.extend_from_slice(&[0; 8]); // width, height
+
+    let mut mdhd = Vec::new();
+    mdhd.extend_from_slice(&[0; 8]);
+    mdhd.extend_from_slice(&rate.to_be_bytes());
+    mdhd.extend_from_slice(&total_frames.to_be_bytes());
+    mdhd.extend_from_slice(&0x55C4u16.to_be_bytes()); // language "und"
+    mdhd.extend_from_slice(&[0; 2]);
+
+    let mut hdlr = Vec::new();
+    hdlr.extend_from_slice(&[0; 4]);
+    hdlr.extend_from_slice(b"soun");
+    hdlr.extend_from_slice(&[0; 12]);
+    hdlr.extend_from_slice(b"SoundHandler\0");
+
+    let dref = full_box(
+        b"dref",
+        0,
+        &[&1u32.to_be_bytes()[..], &full_box(b"url ", 1, &[])].concat(),
+    );
+
+    let stbl = mp4_box(
+        b"stbl",
+        &[
+            stsd(cookie),
+            stts(total_frames),
+            full_box(
+                b"stsc",
+                0,
+                &[1u32, 1, packet_sizes.len() as u32, 1]
+                    .iter()
+                    .flat_map(|v| v.to_be_bytes())
+                    .collect::<Vec<_>>(),
+            ),
+            full_box(
+                b"stsz",
+                0,
+                &[0u32, packet_sizes.len() as u32]
+                    .into_iter()
+                    .chain(packet_sizes.iter().copied())
+                    .flat_map(|v| v.to_be_bytes())
+                    .collect::<Vec<_>>(),
+            ),
+            full_box(
+                b"stco",
+                0,
+                &[1u32, chunk_offset]
+                    .iter()
+                    .flat_map(|v| v.to_be_bytes())
+                    .collect::<Vec<_>>(),
+            ),
+        ]
+        .concat(),
+    );
+
+    let minf = mp4_box(
+        b"minf",
+        &[full_box(b"smhd", 0, &[0; 4]), mp4_box(b"dinf", &dref), stbl].concat(),
+    );
+    let mdia = mp4_box(
+        b"mdia",
+        &[
+            full_box(b"mdhd", 0, &mdhd),
+            full_box(b"hdlr", 0, &hdlr),
+            minf,
+        ]
+        .concat(),
+    );
+    let trak = mp4_box(b"trak", &[full_box(b"tkhd", 7, &tkhd), mdia].concat());
+
+    let mut body = [full_box(b"mvhd", 0, &mvhd), trak].concat();
+    if !tags.is_empty() {
+        body.extend_from_slice(&udta(tags));
+    }
+    mp4_box(b"moov", &body)
+}
+
+fn stsd(cookie: &Cookie) -> Vec<u8> {
+    let mut entry = Vec::new();
+    entry.extend_from_slice(&[0; 6]);
+    entry.extend_from_slice(&1u16.to_be_bytes()); // data reference index
+    entry.extend_from_slice(&[0; 8]); // version, revision, vendor
+    entry.extend_from_slice(&(cookie.channels as u16).to_be_bytes());
+    entry.extend_from_slice(&(cookie.bits as u16).to_be_bytes());
+    entry.extend_from_slice(&[0; 4]); // compression id, packet size
+    // 16.16 fixed point; rates above 65535 Hz only fit in the cookie
+    entry.extend_from_slice(&(cookie.sample_rate.min(0xFFFF) << 16).to_be_bytes());
+    entry.extend_from_slice(&full_box(b"alac", 0, &cookie.bytes()));
+
+    full_box(
+        b"stsd",
+        0,
+        &[&1u32.to_be_bytes()[..], &mp4_box(b"alac", &entry)].concat(),
+    )
+}
+
+/// Every packet covers FRAME_LENGTH samples except possibly the last
+fn stts(total_frames: u32) -> Vec<u8> {
+    let full = total_frames / FRAME_LENGTH as u32;
+    let rest = total_frames % FRAME_LENGTH as u32;
+    let mut entries: Vec<(u32, u32)> = Vec::new();
+    if full > 0 {
+        entries.push((full, FRAME_LENGTH as u32));
+    }
+    if rest > 0 {
+        entries.push((1, rest));
+    }
+    let mut body = (entries.len() as u32).to_be_bytes().to_vec();
+    for (count, delta) in entries {
+        body.extend_from_slice(&count.to_be_bytes());
+        body.extend_from_slice(&delta.to_be_bytes());
+    }
+    full_box(b"stts", 0, &body)
+}
+
+/// `udta/meta/ilst` tag list
+fn udta(tags: &BTreeMap<String, String>) -> Vec<u8> {
+    let text = |value: &str| {
+        // Type 1: UTF-8 text, default locale
+        full_box(b"data", 1, &[&[0u8; 4][..], value.as_bytes()].concat())
+    };
+
+    let mut items = Vec::new();
+    for (key, value) in tags {
+        let atom: Option<&[u8; 4]> = match key.to_lowercase().as_str() {
+            "title" => Some(b"\xA9nam"),
+            "artist" => Some(b"\xA9ART"),
+            "album" => Some(b"\xA9alb"),
+            "year" | "date" => Some(b"\xA9day"),
+            "genre" => Some(b"\xA9gen"),
+            "comment" => Some(b"\xA9cmt"),
+            "composer" => Some(b"\xA9wrt"),
+            _ => None,
+        };
+        match atom {
+            Some(atom) => items.extend_from_slice(&mp4_box(atom, &text(value))),
+            None => items.extend_from_slice(&mp4_box(
+                b"----",
+                &[
+                    full_box(b"mean", 0, b"com.apple.iTunes"),
+                    full_box(b"name", 0, key.to_uppercase().as_bytes()),
+                    text(value),
+                ]
+                .concat(),
+            )),
+        }
+    }
+
+    let mut handler = Vec::new();
+    handler.extend_from_slice(&[0; 4]);
+    handler.extend_from_slice(b"mdir");
+    handler.extend_from_slice(b"appl");
+    handler.extend_from_slice(&[0; 9]);
+
+    let meta = full_box(
+        b"meta",
+        0,
+        &[full_box(b"hdlr", 0, &handler), mp4_box(b"ilst", &items)].concat(),
+    );
+    mp4_box(b"udta", &meta)
+}
+
+#[cfg(test)]
+#[path = "test_alac.rs"]
+mod tests;
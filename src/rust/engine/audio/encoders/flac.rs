@@ -0,0 +1,446 @@
+//! FLAC encoder: fixed-predictor subframes with partitioned Rice residuals, stereo
+//! decorrelation and a Vorbis comment block for tags.
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Seek, SeekFrom, Write};
+
+use anyhow::{Result, bail};
+
+use super::bits::BitWriter;
+
+/// Samples per frame (block size code 12)
+const BLOCK_SIZE: usize = 4096;
+const MAX_PARTITION_ORDER: u32 = 8;
+/// Largest parameter of the 4-bit Rice method; wider residuals switch to 5-bit RICE2.
+/// Escaped (raw) partitions are avoided since common decoders reject them.
+const MAX_RICE_PARAM: u32 = 14;
+const MAX_RICE2_PARAM: u32 = 30;
+
+/// Encode interleaved integer PCM (`bits` of 8, 16 or 24 per sample) as a FLAC stream.
+/// `tags` become Vorbis comments (`title` -> `TITLE=...`).
+pub fn encode(
+    samples: &[i32],
+    channels: u8,
+    sample_rate: u32,
+    bits: u8,
+    tags: &BTreeMap<String, String>,
+) -> Result<Vec<u8>> {
+    let mut stream = FlacStream::new(Cursor::new(Vec::new()), channels, sample_rate, bits, tags)?;
+    stream.write(samples)?;
+    Ok(stream.finish()?.into_inner())
+}
+
+/// Incremental FLAC writer for renders too long to hold in memory: each block is
+/// encoded as soon as it fills, and `finish` patches STREAMINFO once the totals are known.
+pub struct FlacStream<W: Write + Seek> {
+    writer: W,
+    channels: usize,
+    sample_rate: u32,
+    bits: u8,
+    /// Byte offset of the STREAMINFO body, rewritten by `finish`
+    info_offset: u64,
+    /// Samples not yet making up a whole block
+    pending: Vec<i32>,
+    frame_index: u64,
+    total_frames: u64,
+    min_frame: u32,
+    max_frame: u32,
+}
+
+impl<W: Write + Seek> FlacStream<W> {
+    pub fn new(
+        mut writer: W,
+        channels: u8,
+        sample_rate: u32,
+        bits: u8,
+        tags: &BTreeMap<String, String>,
+    ) -> Result<Self> {
+        if !(1..=2).contains(&channels) {
+            bail!(
+                "FLAC export supports mono or stereo, got {} channels",
+                channels
+            );
+        }
+        if !matches!(bits, 8 | 16 | 24) {
+            bail!("FLAC export supports 8, 16 or 24 bits, got {}", bits);
+        }
+        if sample_rate == 0 || sample_rate >= 1 << 20 {
+            bail!("sample rate {} is out of range for FLAC", sample_rate);
+        }
+
+        let mut stream = Self {
+            info_offset: writer.stream_position()? + 8,
+            writer,
+            channels: channels as usize,
+            sample_rate,
+            bits,
+            pending: Vec::new(),
+            frame_index: 0,
+            total_frames: 0,
+            min_frame: u32::MAX,
+            max_frame: 0,
+        };
+
+        // STREAMINFO is written with placeholder totals and patched by `finish`
+        let mut head = Vec::new();
+        head.extend_from_slice(b"fLaC");
+        write_metadata_block(&mut head, 0, &stream.stream_info(), false);
+        write_metadata_block(&mut head, 4, &vorbis_comment(tags), true);
+        stream.writer.write_all(&head)?;
+        Ok(stream)
+    }
+
+    /// Append interleaved samples, encoding every block completed so far
+    pub fn write(&mut self, samples: &[i32]) -> Result<()> {
+        self.pending.extend_from_slice(samples);
+        let block = BLOCK_SIZE * self.channels;
+        let complete = self.pending.len() / block * block;
+        if complete > 0 {
+            let pending = std::mem::take(&mut self.pending);
+            for chunk in pending[..complete].chunks(block) {
+                self.write_frame(chunk)?;
+            }
+            self.pending = pending[complete..].to_vec();
+        }
+        Ok(())
+    }
+
+    /// Encode the last, shorter block and fill in STREAMINFO. Trailing samples that do
+    /// not make up a whole frame are dropped.
+    pub fn finish(mut self) -> Result<W> {
+        let whole = self.pending.len() / self.channels * self.channels;
+        if whole > 0 {
+            let pending = std::mem::take(&mut self.pending);
+            self.write_frame(&pending[..whole])?;
+        }
+
+        let info = self.stream_info();
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(self.info_offset))?;
+        self.writer.write_all(&info)?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_frame(&mut self, block: &[i32]) -> Result<()> {
+        let frame = encode_frame(block, self.channels, self.bits, self.frame_index);
+        self.frame_index += 1;
+        self.total_frames += (block.len() / self.channels) as u64;
+        self.min_frame = self.min_frame.min(frame.len() as u32);
+        self.max_frame = self.max_frame.max(frame.len() as u32);
+        self.writer.write_all(&frame)?;
+        Ok(())
+    }
+
+    /// STREAMINFO body for the frames written so far
+    fn stream_info(&self) -> Vec<u8> {
+        let mut info = BitWriter::default();
+        // Fixed block size; the last block may be shorter
+        info.write(BLOCK_SIZE as u64, 16);
+        info.write(BLOCK_SIZE as u64, 16);
+        info.write(
+            if self.max_frame == 0 {
+                0
+            } else {
+                self.min_frame as u64
+            },
+            24,
+        );
+        info.write(self.max_frame as u64, 24);
+        info.write(self.sample_rate as u64, 20);
+        info.write(self.channels as u64 - 1, 3);
+        info.write(self.bits as u64 - 1, 5);
+        info.write(self.total_frames, 36);
+        // MD5 of the unencoded audio left unset (allowed by the format)
+        info.write(0, 32);
+        info.write(0, 32);
+        info.write(0, 32);
+        info.write(0, 32);
+        info.into_bytes()
+    }
+}
+
+fn write_metadata_block(out: &mut Vec<u8>, kind: u8, body: &[u8], last: bool) {
+    out.push(if last { 0x80 | kind } else { kind });
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    out.extend_from_slice(body);
+}
+
+/// VORBIS_COMMENT body; lengths are little-endian unlike the rest of FLAC
+fn vorbis_comment(tags: &BTreeMap<String, String>) -> Vec<u8> {
+    let vendor = format!("devalang {}", env!("CARGO_PKG_VERSION"));
+    let mut body = Vec::new();
+    body.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    body.extend_from_slice(vendor.as_bytes());
+    body.extend_from_slice(&(tags.len() as u32).to_le_bytes());
+    for (key, value) in tags {
+        let comment = format!("{}={}", key.to_uppercase(), value);
+        body.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        body.extend_from_slice(comment.as_bytes());
+    }
+    body
+}
+
+fn encode_frame(block: &[i32], channels: usize, bits: u8, index: u64) -> Vec<u8> {
+    let block_size = block.len() / channels;
+    let bits = bits as u32;
+    let channel = |c: usize| -> Vec<i64> {
+        block
+            .iter()
+            .skip(c)
+            .step_by(channels)
+            .map(|&s| s as i64)
+            .collect()
+    };
+
+    // (channel assignment code, subframes with their sample width)
+    let (assignment, subframes): (u64, Vec<(Vec<i64>, u32)>) = if channels == 1 {
+        (0, vec![(channel(0), bits)])
+    } else {
+        let (left, right) = (channel(0), channel(1));
+        let side: Vec<i64> = left.iter().zip(&right).map(|(l, r)| l - r).collect();
+        let mid: Vec<i64> = left.iter().zip(&right).map(|(l, r)| (l + r) >> 1).collect();
+        let cost = |signal: &[i64]| best_fixed_order(signal).1;
+        let (l, r, s, m) = (cost(&left), cost(&right), cost(&side), cost(&mid));
+        let options = [(l + r, 1), (l + s, 8), (s + r, 9), (m + s, 10)];
+        let (_, code) = options
+            .iter()
+            .min_by_key(|(cost, _)| *cost)
+            .copied()
+            .unwrap();
+        match code {
+            8 => (8, vec![(left, bits), (side, bits + 1)]),
+            9 => (9, vec![(side, bits + 1), (right, bits)]),
+            10 => (10, vec![(mid, bits), (side, bits + 1)]),
+            _ => (1, vec![(left, bits), (right, bits)]),
+        }
+    };
+
+    let mut w = BitWriter::default();
+    // Sync code, reserved bit, fixed-blocksize stream
+    w.write(0x3FFE, 14);
+    w.write(0, 1);
+    w.write(0, 1);
+    let size_code = if block_size == BLOCK_SIZE { 12 } else { 7 };
+    w.write(size_code, 4);
+    // Sample rate from STREAMINFO
+    w.write(0, 4);
+    w.write(assignment, 4);
+    w.write(
+        match bits {
+            8 => 1,
+            16 => 4,
+            _ => 6,
+        },
+        3,
+    );
+    w.write(0, 1);
+    write_utf8_number(&mut w, index);
+    if size_code == 7 {
+        w.write(block_size as u64 - 1, 16);
+    }
+    let header_crc = crc8(w.bytes());
+    w.write(header_crc as u64, 8);
+
+    for (signal, width) in &subframes {
+        write_subframe(&mut w, signal, *width);
+    }
+    w.align();
+    let crc = crc16(w.bytes());
+    w.write(crc as u64, 16);
+    w.into_bytes()
+}
+
+/// Frame number in FLAC's extended UTF-8 coding
+fn write_utf8_number(w: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        w.write(value, 8);
+        return;
+    }
+    let continuation = match value {
+        v if v < 0x800 => 1,
+        v if v < 0x1_0000 => 2,
+        v if v < 0x20_0000 => 3,
+        v if v < 0x400_0000 => 4,
+        _ => 5,
+    };
+    // One leading 1 bit per byte in the sequence, then the high value bits
+    let prefix = (0xFF00u64 >> (continuation + 1)) & 0xFF;
+    w.write(prefix | (value >> (6 * continuation)), 8);
+    for shift in (0..continuation).rev() {
+        w.write(0x80 | ((value >> (6 * shift)) & 0x3F), 8);
+    }
+}
+
+/// Residual of the order-`order` fixed polynomial predictor
+fn fixed_residual(signal: &[i64], order: usize) -> Vec<i64> {
+    (order..signal.len())
+        .map(|i| {
+            let s = |k: usize| signal[i - k];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+/// Predictor order with the smallest absolute residual sum, and that sum
+fn best_fixed_order(signal: &[i64]) -> (usize, u64) {
+    (0..=4usize.min(signal.len().saturating_sub(1)))
+        .map(|order| {
+            let total = fixed_residual(signal, order)
+                .iter()
+                .map(|r| r.unsigned_abs())
+                .sum();
+            (order, total)
+        })
+        .min_by_key(|(_, total)| *total)
+        .unwrap_or((0, 0))
+}
+
+fn write_subframe(w: &mut BitWriter, signal: &[i64], width: u32) {
+    if signal.iter().all(|&s| s == signal[0]) {
+        // CONSTANT
+        w.write(0, 8);
+        w.write_signed(signal[0], width);
+        return;
+    }
+
+    let (order, _) = best_fixed_order(signal);
+    let residual = fixed_residual(signal, order);
+    let plan = plan_partitions(&residual, signal.len(), order);
+    let rice2 = plan.iter().any(|p| p.param > MAX_RICE_PARAM);
+    let fixed_bits = 8
+        + order as u64 * width as u64
+        + 6
+        + plan.iter().map(|p| p.bits + rice2 as u64).sum::<u64>();
+
+    if fixed_bits >= 8 + signal.len() as u64 * width as u64 {
+        // VERBATIM
+        w.write(0b0000_0010, 8);
+        for &s in signal {
+            w.write_signed(s, width);
+        }
+        return;
+    }
+
+    // FIXED
+    w.write(0b0001_0000 | ((order as u64) << 1), 8);
+    for &s in &signal[..order] {
+        w.write_signed(s, width);
+    }
+    // Residual coding method: RICE (4-bit parameters) or RICE2 (5-bit)
+    let param_width = if rice2 { 5 } else { 4 };
+    w.write(rice2 as u64, 2);
+    w.write(plan_order(&plan) as u64, 4);
+    let mut start = 0;
+    for partition in &plan {
+        let k = partition.param;
+        w.write(k as u64, param_width);
+        for &r in &residual[start..start + partition.len] {
+            let u = zigzag(r);
+            w.write_unary(u >> k);
+            w.write(u, k);
+        }
+        start += partition.len;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Partition {
+    len: usize,
+    param: u32,
+    /// Coded size with a 4-bit parameter
+    bits: u64,
+}
+
+fn plan_order(plan: &[Partition]) -> u32 {
+    plan.len().trailing_zeros()
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Cheapest parameter for a partition and its exact size in bits (parameter included)
+fn choose_param(values: &[i64]) -> (u32, u64) {
+    let zigzagged: Vec<u64> = values.iter().map(|&r| zigzag(r)).collect();
+    let n = values.len() as u64;
+    let mean = zigzagged.iter().sum::<u64>() / n.max(1);
+    let guess = if mean == 0 {
+        0
+    } else {
+        63 - mean.leading_zeros()
+    };
+
+    let rice_bits = |k: u32| 4 + n * (k as u64 + 1) + zigzagged.iter().map(|u| u >> k).sum::<u64>();
+    let mut best = (0, u64::MAX);
+    for k in guess.saturating_sub(1)..=(guess + 1).min(MAX_RICE2_PARAM) {
+        let bits = rice_bits(k);
+        if bits < best.1 {
+            best = (k, bits);
+        }
+    }
+    best
+}
+
+/// Partition layout with the fewest bits among the valid partition orders
+fn plan_partitions(residual: &[i64], block_size: usize, order: usize) -> Vec<Partition> {
+    let mut best: Option<(u64, Vec<Partition>)> = None;
+    for p in 0..=MAX_PARTITION_ORDER {
+        let parts = 1usize << p;
+        if !block_size.is_multiple_of(parts) || block_size / parts <= order {
+            break;
+        }
+        let mut plan = Vec::with_capacity(parts);
+        let mut start = 0;
+        for index in 0..parts {
+            let len = block_size / parts - if index == 0 { order } else { 0 };
+            let (param, bits) = choose_param(&residual[start..start + len]);
+            plan.push(Partition { len, param, bits });
+            start += len;
+        }
+        let total: u64 = plan.iter().map(|p| p.bits).sum();
+        if best.as_ref().is_none_or(|(bits, _)| total < *bits) {
+            best = Some((total, plan));
+        }
+    }
+    best.map(|(_, plan)| plan).unwrap_or_default()
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[cfg(test)]
+#[path = "test_flac.rs"]
+mod tests;
@@ -0,0 +1,59 @@
+use super::*;
+
+/// Offset of the first `kind` box
+fn find_box(bytes: &[u8], kind: &[u8; 4]) -> usize {
+    bytes.windows(4).position(|w| w == kind).unwrap() - 4
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+/// `count` bits starting at bit `offset`, MSB first
+fn read_bits(bytes: &[u8], offset: usize, count: usize) -> u64 {
+    (offset..offset + count).fold(0, |acc, bit| {
+        acc << 1 | ((bytes[bit / 8] >> (7 - bit % 8)) & 1) as u64
+    })
+}
+
+#[test]
+fn test_alac_packets_hold_raw_samples() {
+    let pcm: Vec<i32> = (0..(FRAME_LENGTH + 10) * 2)
+        .map(|i| (i as i32 % 200 - 100) * 100)
+        .collect();
+    let bytes = encode(&pcm, 2, 44100, 16, &BTreeMap::new()).unwrap();
+    assert_eq!(&bytes[4..8], b"ftyp");
+
+    let mdat = find_box(&bytes, b"mdat");
+    let stco = find_box(&bytes, b"stco");
+    assert_eq!(read_u32(&bytes, stco + 16) as usize, mdat + 8);
+
+    // Two packets: a full frame and a 10-sample partial one
+    let stsz = find_box(&bytes, b"stsz");
+    assert_eq!(read_u32(&bytes, stsz + 16), 2);
+    let first_len = read_u32(&bytes, stsz + 20) as usize;
+
+    let first = &bytes[mdat + 8..];
+    assert_eq!(read_bits(first, 0, 3), 1); // channel pair element
+    assert_eq!(read_bits(first, 19, 4), 0b0001); // escape, full frame
+    assert_eq!(read_bits(first, 23, 16) as i16, -10000);
+    assert_eq!(read_bits(first, 39, 16) as i16, -9900);
+
+    let second = &bytes[mdat + 8 + first_len..];
+    assert_eq!(read_bits(second, 19, 4), 0b1001); // escape, partial frame
+    assert_eq!(read_bits(second, 23, 32), 10);
+}
+
+#[test]
+fn test_alac_tags_become_ilst_items() {
+    let tags = BTreeMap::from([
+        ("title".to_string(), "Demo".to_string()),
+        ("mastered_by".to_string(), "me".to_string()),
+    ]);
+    let bytes = encode(&[0; 64], 1, 48000, 24, &tags).unwrap();
+
+    let title = find_box(&bytes, b"\xA9nam");
+    assert_eq!(&bytes[title + 24..title + 28], b"Demo");
+    assert!(bytes.windows(11).any(|w| w == b"MASTERED_BY"));
+    assert!(encode(&[0; 64], 1, 48000, 8, &tags).is_err());
+}
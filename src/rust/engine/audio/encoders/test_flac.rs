@@ -0,0 +1,72 @@
+use super::*;
+
+/// Stereo test signal: a sine on the left, a decaying saw on the right, plus silence
+fn signal(frames: usize, bits: u32) -> Vec<i32> {
+    let peak = ((1i64 << (bits - 1)) - 1) as f32;
+    (0..frames)
+        .flat_map(|i| {
+            let t = i as f32 / 44100.0;
+            let left = (t * 440.0 * std::f32::consts::TAU).sin() * 0.8;
+            let right = if i > frames / 2 {
+                0.0
+            } else {
+                ((t * 110.0).fract() * 2.0 - 1.0) * 0.5
+            };
+            [(left * peak) as i32, (right * peak) as i32]
+        })
+        .collect()
+}
+
+#[cfg(feature = "cli")]
+fn decode(bytes: Vec<u8>) -> (u16, u32, Vec<i16>) {
+    use rodio::Source;
+
+    let decoder = rodio::Decoder::new(std::io::Cursor::new(bytes)).unwrap();
+    (decoder.channels(), decoder.sample_rate(), decoder.collect())
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn test_flac_round_trip_is_lossless() {
+    // Several full blocks and a partial last one
+    let pcm = signal(3 * BLOCK_SIZE + 1000, 16);
+    let tags = BTreeMap::from([("title".to_string(), "Demo".to_string())]);
+    let bytes = encode(&pcm, 2, 44100, 16, &tags).unwrap();
+
+    assert!(bytes.starts_with(b"fLaC"));
+    assert!(bytes.windows(10).any(|w| w == b"TITLE=Demo"));
+    // Compresses to under half of the raw 16-bit PCM
+    let raw_bytes = pcm.len() * 2;
+    assert!(bytes.len() < raw_bytes / 2);
+
+    let (channels, rate, decoded) = decode(bytes);
+    assert_eq!((channels, rate), (2, 44100));
+    let expected: Vec<i16> = pcm.iter().map(|&s| s as i16).collect();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn test_flac_24_bit_mono() {
+    let pcm: Vec<i32> = signal(BLOCK_SIZE + 17, 24).into_iter().step_by(2).collect();
+    let bytes = encode(&pcm, 1, 48000, 24, &BTreeMap::new()).unwrap();
+
+    let (channels, rate, decoded) = decode(bytes);
+    assert_eq!((channels, rate), (1, 48000));
+    // The decoder hands out 16-bit samples: the top 16 of the 24 bits
+    let expected: Vec<i16> = pcm.iter().map(|&s| (s >> 8) as i16).collect();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn test_frame_numbers_use_utf8_coding() {
+    let coded = |value: u64| {
+        let mut w = BitWriter::default();
+        write_utf8_number(&mut w, value);
+        w.into_bytes()
+    };
+    assert_eq!(coded(0x7F), vec![0x7F]);
+    assert_eq!(coded(0x80), vec![0xC2, 0x80]);
+    assert_eq!(coded(0x800), vec![0xE0, 0xA0, 0x80]);
+    assert!(encode(&[0, 0], 2, 44100, 32, &BTreeMap::new()).is_err());
+}
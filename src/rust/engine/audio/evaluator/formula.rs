@@ -0,0 +1,313 @@
+//! Numeric formulas over special variables, e.g. `0.3 + 0.2 * sin($time * 2)`.
+//!
+//! A formula is parsed once and compiled into nested closures, so evaluating it per
+//! render block is a handful of calls with no parsing or allocation.
+
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::engine::special_vars::{SpecialVarContext, resolve_special_var};
+use crate::language::syntax::ast::Value;
+
+type Compiled = Arc<dyn Fn(&SpecialVarContext) -> f32 + Send + Sync>;
+type MathFn = fn(&[f32]) -> f32;
+
+/// A compiled numeric expression
+#[derive(Clone)]
+pub struct Formula {
+    source: String,
+    eval: Compiled,
+}
+
+impl fmt::Debug for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Formula").field(&self.source).finish()
+    }
+}
+
+impl Formula {
+    /// Compile `source`. `$` names are read from the context at evaluation time; other
+    /// identifiers (script variables) are looked up through `resolve` once, here.
+    pub fn compile(source: &str, resolve: &dyn Fn(&str) -> Option<f32>) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            resolve,
+        };
+        let expr = parser.expression()?;
+        if let Some(token) = parser.peek() {
+            bail!("unexpected '{}' in formula '{}'", token, source.trim());
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            eval: compile(expr),
+        })
+    }
+
+    pub fn eval(&self, context: &SpecialVarContext) -> f32 {
+        let value = (self.eval)(context);
+        if value.is_finite() { value } else { 0.0 }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Ident(String),
+    Op(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Ident(name) => write!(f, "{}", name),
+            Token::Op(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse()
+                .map_err(|_| anyhow!("invalid number '{}' in formula", text))?;
+            tokens.push(Token::Number(value));
+        } else if c == '$' || c.is_alphabetic() || c == '_' {
+            let start = i;
+            i += 1;
+            // `$random.float`-style names keep their dots
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || (c == '$' && chars[i] == '.'))
+            {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/%^(),".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else {
+            bail!("unexpected character '{}' in formula", c);
+        }
+    }
+    Ok(tokens)
+}
+
+enum Expr {
+    Const(f32),
+    Special(String),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(MathFn, Vec<Expr>),
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    resolve: &'a dyn Fn(&str) -> Option<f32>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<Expr> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    /// term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/' | '%'))) = self.peek().cloned() {
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    /// unary := '-' unary | power
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    /// power := primary ('^' unary)?  (right-associative)
+    fn power(&mut self) -> Result<Expr> {
+        let base = self.primary()?;
+        if self.eat('^') {
+            return Ok(Expr::Binary('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Const(n)),
+            Some(Token::Op('(')) => {
+                let inner = self.expression()?;
+                if !self.eat(')') {
+                    bail!("missing ')' in formula");
+                }
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::Op('(')) => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.expression()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            bail!("expected ',' or ')' in call to {}()", name);
+                        }
+                    }
+                }
+                let (arity, function) = function(&name)?;
+                if args.len() != arity {
+                    bail!("{}() takes {} argument(s), got {}", name, arity, args.len());
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Ident(name)) if name.starts_with('$') => {
+                if resolve_special_var(&name, &SpecialVarContext::default()).is_none() {
+                    bail!("unknown special variable '{}'", name);
+                }
+                Ok(Expr::Special(name))
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "pi" => Ok(Expr::Const(std::f32::consts::PI)),
+                "e" => Ok(Expr::Const(std::f32::consts::E)),
+                _ => (self.resolve)(&name)
+                    .map(Expr::Const)
+                    .ok_or_else(|| anyhow!("unknown variable '{}' in formula", name)),
+            },
+            Some(token) => bail!("unexpected '{}' in formula", token),
+            None => bail!("formula ended unexpectedly"),
+        }
+    }
+}
+
+fn function(name: &str) -> Result<(usize, MathFn)> {
+    let entry: (usize, MathFn) = match name {
+        "sin" => (1, |a| a[0].sin()),
+        "cos" => (1, |a| a[0].cos()),
+        "tan" => (1, |a| a[0].tan()),
+        "abs" => (1, |a| a[0].abs()),
+        "sqrt" => (1, |a| a[0].sqrt()),
+        "exp" => (1, |a| a[0].exp()),
+        "ln" | "log" => (1, |a| a[0].ln()),
+        "floor" => (1, |a| a[0].floor()),
+        "ceil" => (1, |a| a[0].ceil()),
+        "round" => (1, |a| a[0].round()),
+        "min" => (2, |a| a[0].min(a[1])),
+        "max" => (2, |a| a[0].max(a[1])),
+        "pow" => (2, |a| a[0].powf(a[1])),
+        "clamp" => (3, |a| a[0].clamp(a[1].min(a[2]), a[1].max(a[2]))),
+        _ => bail!("unknown function '{}' in formula", name),
+    };
+    Ok(entry)
+}
+
+fn compile(expr: Expr) -> Compiled {
+    match expr {
+        Expr::Const(value) => Arc::new(move |_| value),
+        Expr::Special(name) => match name.as_str() {
+            // Hot paths read the field directly instead of going through the name lookup
+            "$time" | "$currentTime" => Arc::new(|ctx| ctx.current_time),
+            "$beat" | "$currentBeat" => Arc::new(|ctx| ctx.current_beat),
+            "$bpm" | "$tempo" => Arc::new(|ctx| ctx.bpm),
+            "$position" | "$progress" => Arc::new(|ctx| ctx.position),
+            _ => Arc::new(move |ctx| match resolve_special_var(&name, ctx) {
+                Some(Value::Number(n)) => n,
+                Some(Value::Boolean(b)) => b as u8 as f32,
+                _ => 0.0,
+            }),
+        },
+        Expr::Neg(inner) => {
+            let inner = compile(*inner);
+            Arc::new(move |ctx| -inner(ctx))
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let (lhs, rhs) = (compile(*lhs), compile(*rhs));
+            match op {
+                '+' => Arc::new(move |ctx| lhs(ctx) + rhs(ctx)),
+                '-' => Arc::new(move |ctx| lhs(ctx) - rhs(ctx)),
+                '*' => Arc::new(move |ctx| lhs(ctx) * rhs(ctx)),
+                '/' => Arc::new(move |ctx| lhs(ctx) / rhs(ctx)),
+                '%' => Arc::new(move |ctx| lhs(ctx).rem_euclid(rhs(ctx))),
+                _ => Arc::new(move |ctx| lhs(ctx).powf(rhs(ctx))),
+            }
+        }
+        Expr::Call(function, args) => {
+            let args: Vec<Compiled> = args.into_iter().map(compile).collect();
+            match args.len() {
+                1 => {
+                    let a = args[0].clone();
+                    Arc::new(move |ctx| function(&[a(ctx)]))
+                }
+                2 => {
+                    let (a, b) = (args[0].clone(), args[1].clone());
+                    Arc::new(move |ctx| function(&[a(ctx), b(ctx)]))
+                }
+                _ => {
+                    let (a, b, c) = (args[0].clone(), args[1].clone(), args[2].clone());
+                    Arc::new(move |ctx| function(&[a(ctx), b(ctx), c(ctx)]))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_formula.rs"]
+mod tests;
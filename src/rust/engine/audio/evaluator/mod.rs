@@ -1,4 +1,6 @@
 /// Expression evaluator - evaluates conditions and expressions at runtime
+pub mod formula;
+
 use crate::language::syntax::ast::Value;
 use std::collections::HashMap;
 
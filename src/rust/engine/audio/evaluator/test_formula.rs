@@ -0,0 +1,49 @@
+use super::*;
+
+fn no_vars(_: &str) -> Option<f32> {
+    None
+}
+
+fn at(time: f32) -> SpecialVarContext {
+    let mut ctx = SpecialVarContext::new(120.0, 44100);
+    ctx.update_time(time);
+    ctx
+}
+
+#[test]
+fn test_formula_precedence_and_functions() {
+    let eval = |source: &str| Formula::compile(source, &no_vars).unwrap().eval(&at(0.0));
+    assert_eq!(eval("1 + 2 * 3"), 7.0);
+    assert_eq!(eval("(1 + 2) * 3"), 9.0);
+    assert_eq!(eval("-2 ^ 2"), -4.0);
+    assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+    assert_eq!(eval("7 % 3"), 1.0);
+    assert_eq!(eval("clamp(5, 0, 1) + max(2, 3)"), 4.0);
+    assert!((eval("sin(pi / 2)") - 1.0).abs() < 1e-6);
+    assert_eq!(eval("1 / 0"), 0.0);
+}
+
+#[test]
+fn test_formula_reads_time_and_variables() {
+    let depth = |name: &str| (name == "depth").then_some(0.2);
+    let formula = Formula::compile("0.3 + depth * sin($time * 2)", &depth).unwrap();
+    assert_eq!(formula.source(), "0.3 + depth * sin($time * 2)");
+    assert!((formula.eval(&at(0.0)) - 0.3).abs() < 1e-6);
+    let expected = 0.3 + 0.2 * (2.0f32).sin();
+    assert!((formula.eval(&at(1.0)) - expected).abs() < 1e-6);
+
+    // 120 BPM: beat 4 at two seconds
+    let beats = Formula::compile("$beat / 4", &no_vars).unwrap();
+    assert!((beats.eval(&at(2.0)) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_formula_rejects_invalid_input() {
+    assert!(Formula::compile("1 +", &no_vars).is_err());
+    assert!(Formula::compile("(1 + 2", &no_vars).is_err());
+    assert!(Formula::compile("sin(1, 2)", &no_vars).is_err());
+    assert!(Formula::compile("wobble($time)", &no_vars).is_err());
+    assert!(Formula::compile("$nope * 2", &no_vars).is_err());
+    assert!(Formula::compile("cutoff * 2", &no_vars).is_err());
+    assert!(Formula::compile("1 2", &no_vars).is_err());
+}
@@ -0,0 +1,205 @@
+//! Collected events as JSON (`devalang build --outputs events-json`)
+//!
+//! Written to `<output>/events/<module>.json` for visualizers and custom exporters:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "module": "index",
+//!   "bpm": 120.0,
+//!   "sample_rate": 44100,
+//!   "duration": 8.0,
+//!   "events": [
+//!     { "type": "note", "start": 0.0, "duration": 0.5, "midi": 60, "velocity": 0.8,
+//!       "synth": "lead", "pan": 0.0, "gain": 1.0, "insert": "master" },
+//!     { "type": "chord", "start": 0.5, "duration": 1.0, "midis": [60, 64, 67], ... },
+//!     { "type": "sample", "start": 1.0, "duration": 0.42, "uri": "devalang://bank/...",
+//!       "velocity": 1.0, "insert": "drums/fills" }
+//!   ]
+//! }
+//! ```
+//!
+//! Times and durations are in seconds. Note and chord durations are the held length,
+//! without the release. A sample's duration is how long it plays (its region when it
+//! has one); it is left out when the sample could not be loaded or is repitched, which
+//! is when `note` is set. `insert` is the group path the event is mixed into, or `master`.
+//! Events are listed by start time; `version` changes whenever a field changes meaning
+//! or is removed.
+
+use serde::Serialize;
+
+use crate::engine::audio::events::{AudioEvent, AudioEventList};
+use crate::engine::audio::mixer::MASTER_INSERT;
+
+pub const EVENTS_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventsDocument {
+    pub version: u32,
+    pub module: String,
+    pub bpm: f32,
+    pub sample_rate: u32,
+    /// Seconds until the last event ends
+    pub duration: f32,
+    pub events: Vec<ExportedEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ExportedEvent {
+    Note {
+        start: f32,
+        duration: f32,
+        midi: u8,
+        velocity: f32,
+        synth: String,
+        pan: f32,
+        gain: f32,
+        insert: String,
+    },
+    Chord {
+        start: f32,
+        duration: f32,
+        midis: Vec<u8>,
+        velocity: f32,
+        synth: String,
+        pan: f32,
+        gain: f32,
+        insert: String,
+    },
+    Sample {
+        start: f32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        duration: Option<f32>,
+        uri: String,
+        velocity: f32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        note: Option<u8>,
+        insert: String,
+    },
+}
+
+impl ExportedEvent {
+    pub fn start(&self) -> f32 {
+        match self {
+            ExportedEvent::Note { start, .. }
+            | ExportedEvent::Chord { start, .. }
+            | ExportedEvent::Sample { start, .. } => *start,
+        }
+    }
+
+    fn end(&self) -> f32 {
+        match self {
+            ExportedEvent::Note {
+                start, duration, ..
+            }
+            | ExportedEvent::Chord {
+                start, duration, ..
+            } => start + duration,
+            ExportedEvent::Sample {
+                start, duration, ..
+            } => start + duration.unwrap_or(0.0),
+        }
+    }
+}
+
+impl EventsDocument {
+    /// Export `events`; `sample_seconds` gives the length of a sample when it is loaded
+    pub fn new(
+        module: &str,
+        bpm: f32,
+        sample_rate: u32,
+        events: &AudioEventList,
+        sample_seconds: impl Fn(&str) -> Option<f32>,
+    ) -> Self {
+        let mut exported: Vec<ExportedEvent> = events
+            .events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| {
+                let insert = events
+                    .group_path(index)
+                    .unwrap_or_else(|| MASTER_INSERT.to_string());
+                match event {
+                    AudioEvent::Note {
+                        midi,
+                        start_time,
+                        duration,
+                        velocity,
+                        synth_id,
+                        pan,
+                        gain,
+                        ..
+                    } => ExportedEvent::Note {
+                        start: *start_time,
+                        duration: *duration,
+                        midi: *midi,
+                        velocity: *velocity,
+                        synth: synth_id.clone(),
+                        pan: *pan,
+                        gain: *gain,
+                        insert,
+                    },
+                    AudioEvent::Chord {
+                        midis,
+                        start_time,
+                        duration,
+                        velocity,
+                        synth_id,
+                        pan,
+                        gain,
+                        ..
+                    } => ExportedEvent::Chord {
+                        start: *start_time,
+                        duration: *duration,
+                        midis: midis.clone(),
+                        velocity: *velocity,
+                        synth: synth_id.clone(),
+                        pan: *pan,
+                        gain: *gain,
+                        insert,
+                    },
+                    AudioEvent::Sample {
+                        uri,
+                        start_time,
+                        velocity,
+                        note,
+                        region,
+                        ..
+                    } => {
+                        // Repitched samples change length with the pitch
+                        let length = match note {
+                            None => sample_seconds(uri),
+                            Some(_) => None,
+                        };
+                        ExportedEvent::Sample {
+                            start: *start_time,
+                            duration: match region {
+                                Some(region) => region.played_length(length),
+                                None => length,
+                            },
+                            uri: uri.clone(),
+                            velocity: *velocity,
+                            note: *note,
+                            insert,
+                        }
+                    }
+                }
+            })
+            .collect();
+        exported.sort_by(|a, b| a.start().total_cmp(&b.start()));
+
+        Self {
+            version: EVENTS_SCHEMA_VERSION,
+            module: module.to_string(),
+            bpm,
+            sample_rate,
+            duration: exported.iter().map(ExportedEvent::end).fold(0.0, f32::max),
+            events: exported,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_event_export.rs"]
+mod tests;
@@ -1,7 +1,18 @@
+use crate::engine::audio::effects::chain::build_effect_chain_with;
+use crate::engine::audio::effects::registry::EffectRegistry;
 use crate::engine::audio::generator::FilterDef;
+use crate::engine::audio::synth::EnvelopeCurves;
+use crate::engine::audio::tempo::TempoMap;
 /// Audio events system - stores note/chord events to be rendered
 use crate::language::syntax::ast::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Length assumed for samples whose real length is not known
+const ESTIMATED_SAMPLE_SECONDS: f32 = 2.0;
+/// Effect tails counted into the render length are capped at this
+const MAX_EFFECT_TAIL_SECONDS: f32 = 10.0;
 
 #[derive(Debug, Clone)]
 pub enum AudioEvent {
@@ -62,9 +73,126 @@ pub enum AudioEvent {
         velocity: f32,
         // Effects to apply to this sample (trigger effects)
         effects: Option<crate::language::syntax::ast::Value>,
+        // Target MIDI note; the sample is repitched from its root when set
+        note: Option<u8>,
+        // Note-mode automation target whose templates ramp across this trigger
+        automation: Option<String>,
+        // Portion of the sample to play, looped and/or synced to the tempo
+        region: Option<SampleRegion>,
     },
 }
 
+/// Part of a sample a trigger plays (`.loopBreak start: 0.25 end: 0.75 loop: true sync: 2 bars`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleRegion {
+    /// Region bounds as fractions of the sample length
+    pub start: f32,
+    pub end: f32,
+    /// Seconds one pass of the region is stretched to (`sync`), resolved at the trigger
+    pub sync: Option<f32>,
+    /// Seconds the region keeps repeating for (`loop`); `None` plays it once
+    pub loop_length: Option<f32>,
+    /// `loop: true` without a duration: repeats until the render ends, which sets
+    /// `loop_length` once the timeline is known (see `close_open_loops`)
+    pub loop_to_end: bool,
+}
+
+impl SampleRegion {
+    /// Cut the region out of mono `samples`, stretch it to the sync length and repeat it
+    /// over the loop length
+    pub fn apply(&self, samples: &[f32], sample_rate: u32) -> Vec<f32> {
+        let len = samples.len() as f32;
+        let from = (self.start.clamp(0.0, 1.0) * len) as usize;
+        let to = (self.end.clamp(0.0, 1.0) * len) as usize;
+        let mut region = samples[from.min(to)..to.max(from)].to_vec();
+        if region.is_empty() {
+            return region;
+        }
+
+        if let Some(seconds) = self.sync {
+            // Resampling stretch, like the `stretch` effect: the pitch follows the tempo
+            let frames = ((seconds * sample_rate as f32) as usize).max(1);
+            let step = region.len() as f32 / frames as f32;
+            region = (0..frames)
+                .map(|i| {
+                    let pos = i as f32 * step;
+                    let idx = pos as usize;
+                    let frac = pos - idx as f32;
+                    let a = region[idx.min(region.len() - 1)];
+                    let b = region.get(idx + 1).copied().unwrap_or(a);
+                    a + (b - a) * frac
+                })
+                .collect();
+        }
+
+        if let Some(seconds) = self.loop_length {
+            let frames = (seconds * sample_rate as f32) as usize;
+            region = region.iter().copied().cycle().take(frames).collect();
+        }
+        region
+    }
+
+    /// Seconds the region plays for, given the length of the whole sample when known
+    pub fn played_length(&self, sample_seconds: Option<f32>) -> Option<f32> {
+        self.loop_length.or(self.sync).or_else(|| {
+            sample_seconds.map(|seconds| seconds * (self.end - self.start).abs().min(1.0))
+        })
+    }
+}
+
+/// Fast repeats of a trigger (`.hat roll 1/8 x4 pitch: 12 gain: 0.3`) or of a pattern step
+/// written as a hit count (`"x-3-"`), scheduled as separate sample events
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleRoll {
+    /// Seconds between two hits, resolved at the tempo the roll starts under
+    pub interval: f32,
+    pub count: usize,
+    /// Semitones the last hit is shifted by; the hits in between ramp linearly
+    pub pitch: f32,
+    /// Gain of the last hit relative to the first, ramped the same way
+    pub gain: f32,
+}
+
+impl SampleRoll {
+    /// Hit `index` of the roll started by `event`
+    pub fn hit(&self, event: &AudioEvent, index: usize) -> AudioEvent {
+        let mut hit = event.clone();
+        let ramp = if self.count > 1 {
+            index as f32 / (self.count - 1) as f32
+        } else {
+            0.0
+        };
+        if let AudioEvent::Sample {
+            start_time,
+            velocity,
+            effects,
+            ..
+        } = &mut hit
+        {
+            *start_time += self.interval * index as f32;
+            *velocity *= 1.0 + (self.gain - 1.0) * ramp;
+            let semitones = self.pitch * ramp;
+            if semitones.abs() > 1e-4 {
+                // Repitched by playback speed, like the `speed` trigger effect
+                let mut speed = HashMap::new();
+                speed.insert("type".to_string(), Value::String("speed".to_string()));
+                speed.insert(
+                    "speed".to_string(),
+                    Value::Number(2f32.powf(semitones / 12.0)),
+                );
+                let mut chain = match effects.take() {
+                    Some(Value::Array(chain)) => chain,
+                    Some(other) => vec![other],
+                    None => Vec::new(),
+                };
+                chain.push(Value::Map(speed));
+                *effects = Some(Value::Array(chain));
+            }
+        }
+        hit
+    }
+}
+
 /// Audio events collector
 #[derive(Debug, Default)]
 pub struct AudioEventList {
@@ -73,6 +201,30 @@ pub struct AudioEventList {
     /// and should not affect loop termination logic.
     pub logs: Vec<(f32, String)>,
     pub synths: HashMap<String, SynthDefinition>,
+    /// Effect chains declared on groups (`group drums -> compressor(...):`), keyed by group name
+    pub group_effects: HashMap<String, Value>,
+    /// Event index ranges produced by calls of groups that carry an effect chain
+    /// (or of every group when `tag_all_groups` is set)
+    pub group_spans: Vec<(Range<usize>, String)>,
+    /// Track every group call, not only those with effects, so each group renders into
+    /// its own insert (used by watch rebuilds to reuse unchanged groups)
+    pub tag_all_groups: bool,
+    /// Event index ranges played by soloed groups
+    pub solo_spans: Vec<Range<usize>>,
+    /// Groups named by `duck` statements, tracked like groups with an effect chain
+    pub duck_groups: HashSet<String>,
+    /// Groups sent to hardware outputs by `route ... -> outputs`, tracked the same way
+    pub output_groups: HashSet<String>,
+    /// Groups given a channel strip by `strip` statements, tracked the same way
+    pub strip_groups: HashSet<String>,
+    /// Groups feeding a node of the `routing:` block, tracked the same way
+    pub routing_groups: HashSet<String>,
+    /// Events of muted or unsoloed groups kept to key ducks, with their insert path
+    pub duck_key_events: Vec<(String, AudioEvent)>,
+    /// Cue points set by `mark` statements: seconds from start and name, in collection order
+    pub markers: Vec<(f32, String)>,
+    /// Triggers (`.kit.snare`) that resolved to no sample, so they played nothing
+    pub unresolved_triggers: BTreeSet<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,10 +234,13 @@ pub struct SynthDefinition {
     pub decay: f32,
     pub sustain: f32,
     pub release: f32,
+    pub curves: EnvelopeCurves, // Per-stage envelope shapes
     pub synth_type: Option<String>,
     pub filters: Vec<FilterDef>,
     pub options: HashMap<String, f32>, // Configurable synth type options
     pub lfo: Option<crate::engine::audio::lfo::LfoParams>, // Low-Frequency Oscillator
+    // Named per-note modes (`lead!pluck`), on top of the built-in ones
+    pub articulations: HashMap<String, crate::engine::audio::articulation::Articulation>,
     // Plugin support
     pub plugin_author: Option<String>,
     pub plugin_name: Option<String>,
@@ -100,10 +255,12 @@ impl Default for SynthDefinition {
             decay: 0.1,
             sustain: 0.7,
             release: 0.2,
+            curves: EnvelopeCurves::default(),
             synth_type: None,
             filters: Vec::new(),
             options: HashMap::new(),
             lfo: None,
+            articulations: HashMap::new(),
             plugin_author: None,
             plugin_name: None,
             plugin_export: None,
@@ -117,9 +274,159 @@ impl AudioEventList {
             events: Vec::new(),
             logs: Vec::new(),
             synths: HashMap::new(),
+            group_effects: HashMap::new(),
+            group_spans: Vec::new(),
+            tag_all_groups: false,
+            solo_spans: Vec::new(),
+            duck_groups: HashSet::new(),
+            output_groups: HashSet::new(),
+            strip_groups: HashSet::new(),
+            routing_groups: HashSet::new(),
+            duck_key_events: Vec::new(),
+            markers: Vec::new(),
+            unresolved_triggers: BTreeSet::new(),
         }
     }
 
+    /// Whether the insert at `path` is inside a group named by a `duck` statement
+    fn keys_duck(&self, path: &str) -> bool {
+        path.split('/')
+            .any(|group| self.duck_groups.contains(group))
+    }
+
+    /// Keep the events added since `start` as duck keys when `path` is a duck group,
+    /// before a mute drops them
+    pub fn keep_duck_keys(&mut self, start: usize, path: &str) {
+        if self.keys_duck(path) {
+            let events = self.events[start..].iter().cloned();
+            self.duck_key_events
+                .extend(events.map(|event| (path.to_string(), event)));
+        }
+    }
+
+    /// Drop the events added since `start` along with the spans that cover them
+    /// (used for muted groups)
+    pub fn discard_from(&mut self, start: usize) {
+        self.events.truncate(start);
+        self.group_spans.retain(|(range, _)| range.start < start);
+        for (range, _) in &mut self.group_spans {
+            range.end = range.end.min(start);
+        }
+        self.solo_spans.retain(|range| range.start < start);
+        for range in &mut self.solo_spans {
+            range.end = range.end.min(start);
+        }
+    }
+
+    /// Keep only the events inside the solo spans, remapping the group spans to the
+    /// remaining indices
+    pub fn retain_soloed(&mut self) {
+        let keep: Vec<bool> = (0..self.events.len())
+            .map(|index| self.solo_spans.iter().any(|range| range.contains(&index)))
+            .collect();
+        for index in (0..keep.len()).filter(|&index| !keep[index]) {
+            if let Some(path) = self.group_path(index).filter(|path| self.keys_duck(path)) {
+                self.duck_key_events
+                    .push((path, self.events[index].clone()));
+            }
+        }
+        // New index of each kept event, and of the position right after a dropped one
+        let mut remap = Vec::with_capacity(keep.len() + 1);
+        let mut next = 0;
+        for &kept in &keep {
+            remap.push(next);
+            if kept {
+                next += 1;
+            }
+        }
+        remap.push(next);
+
+        let mut index = 0;
+        self.events.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+        for (range, _) in &mut self.group_spans {
+            *range = remap[range.start]..remap[range.end];
+        }
+        self.group_spans.retain(|(range, _)| !range.is_empty());
+        self.solo_spans.clear();
+        self.solo_spans.push(0..self.events.len());
+    }
+
+    /// Mark the events added since `start` as played by `group`. Only groups with an
+    /// effect chain are tracked since the others mix straight into master, unless
+    /// `tag_all_groups` is set.
+    pub fn tag_group(&mut self, start: usize, group: &str) {
+        let end = self.events.len();
+        if end > start
+            && (self.tag_all_groups
+                || self.group_effects.contains_key(group)
+                || self.duck_groups.contains(group)
+                || self.output_groups.contains(group)
+                || self.strip_groups.contains(group)
+                || self.routing_groups.contains(group))
+        {
+            self.group_spans.push((start..end, group.to_string()));
+        }
+    }
+
+    /// Insert path of the event at `index`, outermost group first (e.g. `drums/fills`).
+    /// Returns `None` for events that are not part of a tracked group.
+    pub fn group_path(&self, index: usize) -> Option<String> {
+        let mut spans: Vec<(usize, &(Range<usize>, String))> = self
+            .group_spans
+            .iter()
+            .enumerate()
+            .filter(|(_, (range, _))| range.contains(&index))
+            .collect();
+        if spans.is_empty() {
+            return None;
+        }
+        // Wider spans are outer groups; on equal width the later tag is the outer call
+        spans.sort_by_key(|(order, (range, _))| {
+            (std::cmp::Reverse(range.len()), std::cmp::Reverse(*order))
+        });
+        Some(
+            spans
+                .iter()
+                .map(|(_, (_, name))| name.as_str())
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+
+    /// Start time and name of each outermost tracked group call, in playback order.
+    /// Used as song sections (e.g. `call verse` then `call chorus`).
+    pub fn sections(&self) -> Vec<(f32, String)> {
+        let mut sections: Vec<(f32, String)> = self
+            .group_spans
+            .iter()
+            .filter(|(range, _)| {
+                !self.group_spans.iter().any(|(outer, _)| {
+                    outer != range
+                        && outer.len() > range.len()
+                        && outer.start <= range.start
+                        && range.end <= outer.end
+                })
+            })
+            .filter_map(|(range, name)| {
+                self.events[range.clone()]
+                    .iter()
+                    .map(|event| match event {
+                        AudioEvent::Note { start_time, .. }
+                        | AudioEvent::Chord { start_time, .. }
+                        | AudioEvent::Sample { start_time, .. } => *start_time,
+                    })
+                    .min_by(|a, b| a.total_cmp(b))
+                    .map(|start| (start, name.clone()))
+            })
+            .collect();
+        sections.sort_by(|a, b| a.0.total_cmp(&b.0));
+        sections.dedup();
+        sections
+    }
+
     pub fn add_synth(&mut self, name: String, definition: SynthDefinition) {
         self.synths.insert(name, definition);
     }
@@ -226,6 +533,9 @@ impl AudioEventList {
             start_time,
             velocity,
             effects: None,
+            note: None,
+            automation: None,
+            region: None,
         });
     }
 
@@ -236,12 +546,53 @@ impl AudioEventList {
         start_time: f32,
         velocity: f32,
         effects: Option<Value>,
+    ) {
+        self.add_pitched_sample_event(uri, start_time, velocity, effects, None);
+    }
+
+    /// Turn every sample event from index `first` on into the first hit of `roll`
+    pub fn apply_roll(&mut self, first: usize, roll: &SampleRoll) {
+        let repeats: Vec<AudioEvent> = self.events[first.min(self.events.len())..]
+            .iter()
+            .filter(|event| matches!(event, AudioEvent::Sample { .. }))
+            .flat_map(|event| (1..roll.count).map(move |index| roll.hit(event, index)))
+            .collect();
+        self.events.extend(repeats);
+    }
+
+    /// Give every region looping until the render ends the length left up to `end` seconds
+    pub fn close_open_loops(&mut self, end: f32) {
+        for event in &mut self.events {
+            if let AudioEvent::Sample {
+                start_time,
+                region: Some(region),
+                ..
+            } = event
+                && region.loop_to_end
+                && region.loop_length.is_none()
+            {
+                region.loop_length = Some((end - *start_time).max(0.0));
+            }
+        }
+    }
+
+    /// Add a sample event played at `note` (repitched from the sample's root)
+    pub fn add_pitched_sample_event(
+        &mut self,
+        uri: &str,
+        start_time: f32,
+        velocity: f32,
+        effects: Option<Value>,
+        note: Option<u8>,
     ) {
         self.events.push(AudioEvent::Sample {
             uri: uri.to_string(),
             start_time,
             velocity,
             effects,
+            note,
+            automation: None,
+            region: None,
         });
     }
 
@@ -251,10 +602,30 @@ impl AudioEventList {
         self.logs.push((time, message));
     }
 
+    /// Record a cue point; a name marked again keeps its first time
+    pub fn add_marker(&mut self, name: String, time: f32) {
+        if !self.markers.iter().any(|(_, existing)| *existing == name) {
+            self.markers.push((time, name));
+        }
+    }
+
     pub fn get_synth(&self, name: &str) -> Option<&SynthDefinition> {
         self.synths.get(name)
     }
 
+    /// Every sample URI the events trigger, in order of first use
+    pub fn sample_uris(&self) -> Vec<String> {
+        let mut uris: Vec<String> = Vec::new();
+        for event in &self.events {
+            if let AudioEvent::Sample { uri, .. } = event
+                && !uris.contains(uri)
+            {
+                uris.push(uri.clone());
+            }
+        }
+        uris
+    }
+
     pub fn total_duration(&self) -> f32 {
         self.events
             .iter()
@@ -282,20 +653,107 @@ impl AudioEventList {
                             start_time + duration
                         } else {
                             // Fallback: estimate 2 seconds
-                            start_time + 2.0
+                            start_time + ESTIMATED_SAMPLE_SECONDS
                         }
                     }
                     #[cfg(not(target_arch = "wasm32"))]
                     {
                         // Fallback for native: estimate 2 seconds
                         let _ = uri; // Silence unused warning on non-WASM targets
-                        start_time + 2.0
+                        start_time + ESTIMATED_SAMPLE_SECONDS
                     }
                 } // Log events are not considered in audio total duration
             })
             .fold(0.0, f32::max)
     }
 
+    /// Length of the rendered audio: the musical length plus the release tails of notes
+    /// that ring past it
+    pub fn render_duration(&self) -> f32 {
+        self.events
+            .iter()
+            .filter(|event| !matches!(event, AudioEvent::Sample { .. }))
+            .map(|event| self.note_end(event))
+            .fold(self.total_duration(), f32::max)
+    }
+
+    /// End of a note or chord including its release
+    fn note_end(&self, event: &AudioEvent) -> f32 {
+        match event {
+            AudioEvent::Note {
+                start_time,
+                duration,
+                synth_def,
+                release,
+                ..
+            }
+            | AudioEvent::Chord {
+                start_time,
+                duration,
+                synth_def,
+                release,
+                ..
+            } => {
+                let release = release.map(|ms| ms / 1000.0).unwrap_or(synth_def.release);
+                let release = crate::engine::audio::generator::release_seconds(
+                    synth_def.synth_type.as_deref(),
+                    release,
+                );
+                start_time + duration + release
+            }
+            AudioEvent::Sample { start_time, .. } => *start_time,
+        }
+    }
+
+    /// End of the audible output: notes with their release, samples with their length
+    /// from `sample_length` (estimated when unknown or repitched) and the tails of
+    /// group effect chains built from `registry`.
+    pub fn end_time(
+        &self,
+        sample_rate: u32,
+        registry: &Arc<EffectRegistry>,
+        sample_length: impl Fn(&str) -> Option<f32>,
+    ) -> f32 {
+        let event_end = |event: &AudioEvent| match event {
+            AudioEvent::Note { .. } | AudioEvent::Chord { .. } => self.note_end(event),
+            AudioEvent::Sample {
+                start_time,
+                uri,
+                note,
+                region,
+                ..
+            } => {
+                let length = match note {
+                    None => sample_length(uri),
+                    Some(_) => None,
+                };
+                let length = match region {
+                    Some(region) => region.played_length(length),
+                    None => length,
+                };
+                start_time + length.unwrap_or(ESTIMATED_SAMPLE_SECONDS)
+            }
+        };
+
+        let mut end = self.events.iter().map(event_end).fold(0.0, f32::max);
+        for (range, group) in &self.group_spans {
+            let Some(Value::Array(effects)) = self.group_effects.get(group) else {
+                continue;
+            };
+            let tail = build_effect_chain_with(Arc::clone(registry), effects, true)
+                .tail_seconds(sample_rate)
+                .min(MAX_EFFECT_TAIL_SECONDS);
+            if tail > 0.0 {
+                let last = self.events[range.clone()]
+                    .iter()
+                    .map(event_end)
+                    .fold(0.0, f32::max);
+                end = end.max(last + tail);
+            }
+        }
+        end
+    }
+
     /// Merge another AudioEventList into this one
     /// This is used for parallel spawn execution
     pub fn merge(&mut self, other: AudioEventList) {
@@ -306,6 +764,30 @@ impl AudioEventList {
             }
         }
 
+        // Merge group chains and shift group spans past the events already collected
+        for (name, effects) in other.group_effects {
+            self.group_effects.entry(name).or_insert(effects);
+        }
+        self.duck_groups.extend(other.duck_groups);
+        self.output_groups.extend(other.output_groups);
+        self.strip_groups.extend(other.strip_groups);
+        self.routing_groups.extend(other.routing_groups);
+        self.duck_key_events.extend(other.duck_key_events);
+        self.unresolved_triggers.extend(other.unresolved_triggers);
+        let offset = self.events.len();
+        self.group_spans.extend(
+            other
+                .group_spans
+                .into_iter()
+                .map(|(range, name)| (range.start + offset..range.end + offset, name)),
+        );
+        self.solo_spans.extend(
+            other
+                .solo_spans
+                .into_iter()
+                .map(|range| range.start + offset..range.end + offset),
+        );
+
         // Merge events and update their synth_def snapshots if needed
         for mut event in other.events {
             // Update synth_def snapshot for Note and Chord events
@@ -355,9 +837,49 @@ impl AudioEventList {
         }
 
         // Keep logs time-ordered for predictable playback
+        self.sort_logs();
+
+        for (time, name) in other.markers {
+            self.add_marker(name, time);
+        }
+    }
+
+    /// Order logs by their scheduled time. The sort is stable so prints sharing a
+    /// timestamp keep the order in which they were executed.
+    pub fn sort_logs(&mut self) {
         self.logs
             .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
     }
+
+    /// Build the print timeline (time-ordered, with bar/beat positions) at the given tempo.
+    pub fn print_timeline(&self, tempo_map: &TempoMap, bpm: f32) -> Vec<PrintTimelineEntry> {
+        let bpm = if bpm > 0.0 { bpm } else { 120.0 };
+        let mut logs: Vec<&(f32, String)> = self.logs.iter().collect();
+        logs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        logs.into_iter()
+            .map(|(time, message)| {
+                // Small epsilon so prints scheduled exactly on a beat don't round down
+                let beats = tempo_map.beat_at(*time, bpm).max(0.0) + 1e-4;
+                let (bar, beat) = tempo_map.bar_at(beats);
+                PrintTimelineEntry {
+                    time: *time,
+                    bar: bar + 1,
+                    beat: (beat * 1000.0).floor() / 1000.0 + 1.0,
+                    message: message.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A scheduled print with its musical position (1-based bar and beat)
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PrintTimelineEntry {
+    pub time: f32,
+    pub bar: u32,
+    pub beat: f32,
+    pub message: String,
 }
 
 /// Helper to extract values from Value::Map
@@ -385,6 +907,32 @@ pub fn extract_string(map: &HashMap<String, Value>, key: &str, default: &str) ->
         .unwrap_or_else(|| default.to_string())
 }
 
+/// Envelope stage curves from `attack_curve` / `decay_curve` / `release_curve`, with
+/// `curve` as the default for all three. Values are `$curve.*` / `$ease.*` names or the
+/// bare curve name (`"out"`, `"swing(0.5)"`); unknown names stay linear.
+pub fn extract_envelope_curves(map: &HashMap<String, Value>) -> EnvelopeCurves {
+    use crate::engine::curves::{CurveType, parse_curve};
+
+    let curve = |key: &str| -> Option<CurveType> {
+        let name = match map.get(key)? {
+            Value::String(s) | Value::Identifier(s) => s.trim_matches('"').trim_matches('\''),
+            _ => return None,
+        };
+        if name.starts_with('$') {
+            parse_curve(name)
+        } else {
+            parse_curve(&format!("$curve.{}", name))
+        }
+    };
+    let all = curve("curve").unwrap_or(CurveType::Linear);
+
+    EnvelopeCurves {
+        attack: curve("attack_curve").unwrap_or(all),
+        decay: curve("decay_curve").unwrap_or(all),
+        release: curve("release_curve").unwrap_or(all),
+    }
+}
+
 pub fn extract_filters(filters_arr: &[Value]) -> Vec<FilterDef> {
     filters_arr
         .iter()
@@ -1,9 +1,15 @@
 use super::lfo::{LfoParams, apply_lfo_modulation, generate_lfo_value};
+use super::synth::pitch::PitchModulation;
 use super::synth::types::{SynthType, get_synth_type};
-use super::synth::{adsr_envelope, midi_to_frequency, oscillator_sample, time_to_samples};
+use super::synth::{
+    EnvelopeCurves, gated_adsr_envelope, midi_to_frequency, oscillator_at_phase, oscillator_sample,
+    time_to_samples,
+};
 /// Note generator - creates audio samples for synthesized notes
 use anyhow::Result;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
 #[cfg(feature = "cli")]
 use crate::engine::plugin::{loader::load_plugin, runner::WasmPluginRunner};
@@ -16,6 +22,62 @@ pub struct FilterDef {
     pub resonance: f32,      // 0.0 - 1.0
 }
 
+/// Parameter curve sampled once per render block: seconds since the note started to the
+/// value at that point, or `None` where the note's own setting applies
+pub type ParamCurve = Arc<dyn Fn(f32) -> Option<f32> + Send + Sync>;
+
+/// Formula-driven parameters re-evaluated every `block_size` frames while a note renders
+#[derive(Clone, Default)]
+pub struct BlockAutomation {
+    pub block_size: usize,
+    /// Gain in place of the event's own, applied on top of the note velocity
+    pub gain: Option<ParamCurve>,
+    pub pan: Option<ParamCurve>,
+    /// Cutoff in Hz for every filter in the chain
+    pub cutoff: Option<ParamCurve>,
+    /// Vibrato depth in cents, in place of the synth's `vibrato_depth`
+    pub vibrato: Option<ParamCurve>,
+}
+
+impl BlockAutomation {
+    pub fn is_empty(&self) -> bool {
+        self.gain.is_none() && self.pan.is_none() && self.cutoff.is_none() && self.vibrato.is_none()
+    }
+
+    fn block_size(&self) -> usize {
+        self.block_size.max(1)
+    }
+}
+
+impl fmt::Debug for BlockAutomation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockAutomation")
+            .field("block_size", &self.block_size)
+            .field("gain", &self.gain.is_some())
+            .field("pan", &self.pan.is_some())
+            .field("cutoff", &self.cutoff.is_some())
+            .field("vibrato", &self.vibrato.is_some())
+            .finish()
+    }
+}
+
+/// Note pending in the same render block, forwarded to plugins using the extended ABI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PluginPendingNote {
+    pub midi: u8,
+    pub velocity: f32,   // 0.0 - 1.0
+    pub duration_ms: f32,
+    pub offset_ms: f32, // offset from the start of the block
+}
+
+/// Block-level context for plugins: pending notes plus tempo information
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PluginContext {
+    pub notes: Vec<PluginPendingNote>,
+    pub bpm: f32,
+    pub beat_phase: f32, // 0.0 - 1.0 position within the current beat
+}
+
 /// Parameters for synthesizing a note
 #[derive(Debug, Clone)]
 pub struct SynthParams {
@@ -24,6 +86,7 @@ pub struct SynthParams {
     pub decay: f32,                    // seconds
     pub sustain: f32,                  // level (0.0 - 1.0)
     pub release: f32,                  // seconds
+    pub curves: EnvelopeCurves,        // Per-stage envelope shapes
     pub synth_type: Option<String>,    // pluck, arp, pad, bass, lead, keys
     pub filters: Vec<FilterDef>,       // Chain of filters
     pub options: HashMap<String, f32>, // Configurable options for synth types
@@ -32,6 +95,8 @@ pub struct SynthParams {
     pub plugin_author: Option<String>,
     pub plugin_name: Option<String>,
     pub plugin_export: Option<String>,
+    pub plugin_context: Option<PluginContext>, // Only used by plugins exposing the extended ABI
+    pub automation: BlockAutomation,           // Per-block formula automation (built-in synths)
 }
 
 impl Default for SynthParams {
@@ -42,6 +107,7 @@ impl Default for SynthParams {
             decay: 0.1,
             sustain: 0.7,
             release: 0.2,
+            curves: EnvelopeCurves::default(),
             synth_type: None,
             filters: Vec::new(),
             options: HashMap::new(),
@@ -49,10 +115,25 @@ impl Default for SynthParams {
             plugin_author: None,
             plugin_name: None,
             plugin_export: None,
+            plugin_context: None,
+            automation: BlockAutomation::default(),
         }
     }
 }
 
+/// Release time (seconds) a note actually renders with: synth types set their own
+/// envelope over the definition's
+pub fn release_seconds(synth_type: Option<&str>, release: f32) -> f32 {
+    let mut params = SynthParams {
+        release,
+        ..SynthParams::default()
+    };
+    if let Some(stype) = synth_type.and_then(get_synth_type) {
+        stype.modify_params(&mut params);
+    }
+    params.release.max(0.0)
+}
+
 /// Generate stereo audio samples for a single note
 pub fn generate_note(
     midi_note: u8,
@@ -140,26 +221,45 @@ pub fn generate_note_with_options(
     let decay_samples = time_to_samples(modified_params.decay, sample_rate);
     let release_samples = time_to_samples(modified_params.release, sample_rate);
 
-    // Calculate sustain duration
+    // The note holds for its duration; the release tail rings on past it
     let duration_seconds = duration_ms / 1000.0;
-    let total_samples = time_to_samples(duration_seconds, sample_rate);
-    let envelope_samples = attack_samples + decay_samples + release_samples;
-    let sustain_samples = total_samples.saturating_sub(envelope_samples);
+    let gate_samples = time_to_samples(duration_seconds, sample_rate);
+    let total_samples = gate_samples + release_samples;
 
     let mut samples = Vec::with_capacity(total_samples * 2); // stereo
 
     // Calculate pan gains (constant power panning)
-    let pan = pan.clamp(-1.0, 1.0);
-    let pan_angle = (pan + 1.0) * 0.25 * std::f32::consts::PI; // 0 to PI/2
-    let left_gain = pan_angle.cos();
-    let right_gain = pan_angle.sin();
+    let (mut left_gain, mut right_gain) = pan_gains(pan);
 
     // Prepare LFO parameters if any
     let bpm = 120.0; // TODO: get from context
 
+    let automation = &modified_params.automation;
+    let block_size = automation.block_size();
+    let mut block_gain = 1.0;
+    let mut block_vibrato = None;
+
+    // Vibrato and pitch envelopes move the frequency, so the phase is accumulated
+    let pitch = PitchModulation::from_options(&modified_params.options);
+    let moving_pitch = pitch.is_active() || automation.vibrato.is_some();
+    let mut phase = 0.0f32;
+
     for i in 0..total_samples {
         let time = i as f32 / sample_rate as f32;
 
+        // Formula automation moves at block rate
+        if i.is_multiple_of(block_size) {
+            if let Some(gain) = automation.gain.as_ref().and_then(|curve| curve(time)) {
+                block_gain = gain.max(0.0);
+            }
+            if let Some(pan) = automation.pan.as_ref().and_then(|curve| curve(time)) {
+                (left_gain, right_gain) = pan_gains(pan);
+            }
+            if let Some(curve) = &automation.vibrato {
+                block_vibrato = curve(time);
+            }
+        }
+
         // Generate oscillator sample
         let mut osc_frequency = frequency;
 
@@ -172,20 +272,29 @@ pub fn generate_note_with_options(
             }
         }
 
-        let osc_sample = oscillator_sample(&modified_params.waveform, osc_frequency, time);
+        let osc_sample = if moving_pitch {
+            let cents = pitch.cents_at(time, block_vibrato);
+            let sample = oscillator_at_phase(&modified_params.waveform, phase);
+            phase =
+                (phase + osc_frequency * 2.0_f32.powf(cents / 1200.0) / sample_rate as f32).fract();
+            sample
+        } else {
+            oscillator_sample(&modified_params.waveform, osc_frequency, time)
+        };
 
         // Apply ADSR envelope
-        let envelope = adsr_envelope(
+        let envelope = gated_adsr_envelope(
             i,
             attack_samples,
             decay_samples,
-            sustain_samples,
+            gate_samples,
             release_samples,
             modified_params.sustain,
+            &modified_params.curves,
         );
 
         // Apply velocity and envelope
-        let mut amplitude = osc_sample * envelope * velocity * 0.3; // 0.3 for headroom
+        let mut amplitude = osc_sample * envelope * velocity * block_gain * 0.3; // 0.3 for headroom
 
         // Apply volume LFO if configured
         if let Some(ref lfo) = modified_params.lfo {
@@ -221,7 +330,24 @@ pub fn generate_note_with_options(
             }
         }
 
-        apply_filter(&mut samples, &modulated_filter, sample_rate)?;
+        match &automation.cutoff {
+            Some(curve) => {
+                let cutoffs: Vec<f32> = (0..total_samples.div_ceil(block_size))
+                    .map(|block| {
+                        curve((block * block_size) as f32 / sample_rate as f32)
+                            .unwrap_or(modulated_filter.cutoff)
+                    })
+                    .collect();
+                apply_filter_blocks(
+                    &mut samples,
+                    &modulated_filter.filter_type,
+                    &cutoffs,
+                    block_size,
+                    sample_rate,
+                )?;
+            }
+            None => apply_filter(&mut samples, &modulated_filter, sample_rate)?,
+        }
     }
 
     // Apply synth type post-processing
@@ -232,24 +358,67 @@ pub fn generate_note_with_options(
     Ok(samples)
 }
 
+/// Constant power pan gains for `pan` in -1.0 (left) to 1.0 (right)
+fn pan_gains(pan: f32) -> (f32, f32) {
+    let pan_angle = (pan.clamp(-1.0, 1.0) + 1.0) * 0.25 * std::f32::consts::PI; // 0 to PI/2
+    (pan_angle.cos(), pan_angle.sin())
+}
+
 /// Apply a filter to audio samples
 fn apply_filter(samples: &mut [f32], filter: &FilterDef, sample_rate: u32) -> Result<()> {
-    match filter.filter_type.to_lowercase().as_str() {
-        "lowpass" => apply_lowpass(samples, filter.cutoff, sample_rate),
-        "highpass" => apply_highpass(samples, filter.cutoff, sample_rate),
-        "bandpass" => apply_bandpass(samples, filter.cutoff, sample_rate),
+    apply_filter_blocks(
+        samples,
+        &filter.filter_type,
+        &[filter.cutoff],
+        usize::MAX,
+        sample_rate,
+    )
+}
+
+/// Apply a filter whose cutoff changes every `block_size` frames (one entry per block)
+fn apply_filter_blocks(
+    samples: &mut [f32],
+    filter_type: &str,
+    cutoffs: &[f32],
+    block_size: usize,
+    sample_rate: u32,
+) -> Result<()> {
+    match filter_type.to_lowercase().as_str() {
+        "lowpass" => apply_lowpass(samples, cutoffs, block_size, sample_rate),
+        "highpass" => apply_highpass(samples, cutoffs, block_size, sample_rate),
+        "bandpass" => apply_bandpass(samples, cutoffs, block_size, sample_rate),
         _ => Ok(()),
     }
 }
 
+/// Cutoff in effect at `frame`, or `None` when it is unchanged since the previous frame
+fn block_cutoff(cutoffs: &[f32], block_size: usize, frame: usize) -> Option<f32> {
+    if !frame.is_multiple_of(block_size) {
+        return None;
+    }
+    cutoffs
+        .get(frame / block_size)
+        .or(cutoffs.last())
+        .map(|cutoff| cutoff.max(1.0))
+}
+
 /// Simple one-pole lowpass filter
-fn apply_lowpass(samples: &mut [f32], cutoff: f32, sample_rate: u32) -> Result<()> {
+fn apply_lowpass(
+    samples: &mut [f32],
+    cutoffs: &[f32],
+    block_size: usize,
+    sample_rate: u32,
+) -> Result<()> {
     let dt = 1.0 / sample_rate as f32;
-    let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff);
-    let alpha = dt / (rc + dt);
+    let mut alpha = 0.0f32;
 
     let mut prev = 0.0f32;
     for i in (0..samples.len()).step_by(2) {
+        if let Some(cutoff) = block_cutoff(cutoffs, block_size, i / 2) {
+            let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff);
+            alpha = dt / (rc + dt);
+        }
+
         // Process left channel
         let filtered = prev + alpha * (samples[i] - prev);
         prev = filtered;
@@ -265,15 +434,24 @@ fn apply_lowpass(samples: &mut [f32], cutoff: f32, sample_rate: u32) -> Result<(
 }
 
 /// Simple one-pole highpass filter
-fn apply_highpass(samples: &mut [f32], cutoff: f32, sample_rate: u32) -> Result<()> {
+fn apply_highpass(
+    samples: &mut [f32],
+    cutoffs: &[f32],
+    block_size: usize,
+    sample_rate: u32,
+) -> Result<()> {
     let dt = 1.0 / sample_rate as f32;
-    let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff);
-    let alpha = rc / (rc + dt);
+    let mut alpha = 0.0f32;
 
     let mut prev_input = 0.0f32;
     let mut prev_output = 0.0f32;
 
     for i in (0..samples.len()).step_by(2) {
+        if let Some(cutoff) = block_cutoff(cutoffs, block_size, i / 2) {
+            let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff);
+            alpha = rc / (rc + dt);
+        }
+
         let current = samples[i];
         let filtered = alpha * (prev_output + current - prev_input);
 
@@ -290,12 +468,19 @@ fn apply_highpass(samples: &mut [f32], cutoff: f32, sample_rate: u32) -> Result<
 }
 
 /// Simple bandpass filter (combination of lowpass and highpass)
-fn apply_bandpass(samples: &mut [f32], center: f32, sample_rate: u32) -> Result<()> {
+fn apply_bandpass(
+    samples: &mut [f32],
+    centers: &[f32],
+    block_size: usize,
+    sample_rate: u32,
+) -> Result<()> {
     // Bandpass = highpass below center, then lowpass above center
-    let bandwidth = center * 0.5; // 50% bandwidth
+    // (50% bandwidth)
+    let lows: Vec<f32> = centers.iter().map(|center| center * 0.5).collect();
+    let highs: Vec<f32> = centers.iter().map(|center| center * 1.5).collect();
 
-    apply_highpass(samples, center - bandwidth, sample_rate)?;
-    apply_lowpass(samples, center + bandwidth, sample_rate)?;
+    apply_highpass(samples, &lows, block_size, sample_rate)?;
+    apply_lowpass(samples, &highs, block_size, sample_rate)?;
 
     Ok(())
 }
@@ -320,6 +505,29 @@ pub fn generate_chord(
     )
 }
 
+/// Pan of the `index`-th of `count` chord notes, distributed across the stereo field by `spread`
+pub fn chord_note_pan(index: usize, count: usize, pan: f32, spread: f32) -> f32 {
+    let spread = spread.clamp(0.0, 1.0);
+    if count > 1 && spread > 0.0 {
+        let position = index as f32 / (count - 1) as f32; // 0.0 to 1.0
+        let spread_amount = (position - 0.5) * 2.0 * spread; // -spread to +spread
+        (pan + spread_amount).clamp(-1.0, 1.0)
+    } else {
+        pan
+    }
+}
+
+/// Share of the `index`-th of `count` chord notes in the mix, matching the progressive
+/// averaging of `generate_chord_with_options` (used when a chord is split into notes)
+pub fn chord_note_weight(index: usize, count: usize) -> f32 {
+    let halvings = if index == 0 {
+        count.saturating_sub(1)
+    } else {
+        count - index
+    };
+    0.5f32.powi(halvings as i32)
+}
+
 /// Generate stereo audio samples for a chord with pan, detune and spread options
 pub fn generate_chord_with_options(
     midi_notes: &[u8],
@@ -336,21 +544,13 @@ pub fn generate_chord_with_options(
     }
 
     let num_notes = midi_notes.len();
-    let spread = spread.clamp(0.0, 1.0);
 
     // Calculate pan position for each note if spread is enabled
     let mut result: Option<Vec<f32>> = None;
 
     for (i, &midi_note) in midi_notes.iter().enumerate() {
         // Calculate individual pan for each note based on spread
-        let note_pan = if num_notes > 1 && spread > 0.0 {
-            // Distribute notes across stereo field
-            let position = i as f32 / (num_notes - 1) as f32; // 0.0 to 1.0
-            let spread_amount = (position - 0.5) * 2.0 * spread; // -spread to +spread
-            (pan + spread_amount).clamp(-1.0, 1.0)
-        } else {
-            pan
-        };
+        let note_pan = chord_note_pan(i, num_notes, pan, spread);
 
         // Generate note with individual pan
         let note_samples = generate_note_with_options(
@@ -412,6 +612,61 @@ mod tests {
         let has_audio = samples.iter().any(|&s| s.abs() > 0.001);
         assert!(has_audio);
     }
+
+    #[test]
+    fn test_chord_note_weights_sum_to_one() {
+        for count in 1..6 {
+            let total: f32 = (0..count).map(|i| chord_note_weight(i, count)).sum();
+            assert!((total - 1.0).abs() < 1e-6);
+        }
+        assert_eq!(chord_note_pan(0, 3, 0.0, 1.0), -1.0);
+        assert_eq!(chord_note_pan(2, 3, 0.5, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_block_automation_updates_gain_per_block() {
+        let mut params = SynthParams {
+            attack: 0.0,
+            release: 0.0,
+            sustain: 1.0,
+            ..SynthParams::default()
+        };
+        // Silent for the second half of a 100ms note, switching on a block boundary
+        params.automation = BlockAutomation {
+            block_size: 441,
+            gain: Some(Arc::new(|t| Some(if t < 0.05 { 1.0 } else { 0.0 }))),
+            ..BlockAutomation::default()
+        };
+        let samples = generate_note(69, 100.0, 1.0, &params, 44100).unwrap();
+        let frames = samples.len() / 2;
+
+        assert!(samples[..frames].iter().any(|s| s.abs() > 0.01));
+        assert!(samples[frames + 2..].iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn test_pitch_envelope_starts_the_oscillator_off_pitch() {
+        let crossings = |params: &SynthParams| {
+            let samples = generate_note(69, 100.0, 1.0, params, 44100).unwrap();
+            let left: Vec<f32> = samples.iter().step_by(2).copied().take(4410).collect();
+            left.windows(2)
+                .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+                .count()
+        };
+        let mut params = SynthParams {
+            attack: 0.0,
+            sustain: 1.0,
+            ..SynthParams::default()
+        };
+        let plain = crossings(&params);
+
+        // An octave up, gliding back over a second: still close to 880 Hz
+        params.options.insert("pitch_start".to_string(), 12.0);
+        params.options.insert("pitch_time".to_string(), 1000.0);
+        let raised = crossings(&params);
+        assert!((80..=96).contains(&plain));
+        assert!(raised as f32 > plain as f32 * 1.8);
+    }
 }
 
 /// Generate audio using a WASM plugin
@@ -500,6 +755,7 @@ fn generate_note_with_plugin(
             sample_rate as i32,
             2, // stereo
             Some(&plugin_options),
+            params.plugin_context.as_ref(),
         )
         .map_err(|e| anyhow::anyhow!("Plugin render error: {}", e))?;
 
@@ -1 +1,423 @@
-pub fn placeholder() {}
+//! Signal-flow diagrams of a project's routing
+//!
+//! Walks the `routing:` block and the group inserts of a parsed file into a graph whose
+//! nodes are inserts (master, routing nodes and the groups feeding them) and whose edges
+//! are routes, sends and duck/sidechain links, then writes it as Graphviz DOT or Mermaid.
+//! `devalang check` reuses the walk to flag inserts that can never carry signal.
+
+use crate::language::preprocessor::loader::import_binding;
+use crate::language::syntax::ast::{Statement, StatementKind, Value};
+use std::collections::HashSet;
+
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+
+/// Name of the routing master node
+pub const MASTER_NODE: &str = "$master";
+
+/// Group or variable whose events a routing node receives: its `= source`, else the
+/// group or variable of its own name. The renderer routes events with the same rule (see
+/// `AudioGraph::target_node`).
+pub fn node_source<'a>(name: &'a str, alias: Option<&'a str>) -> &'a str {
+    alias.unwrap_or(name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "cli", clap(rename_all = "lower"))]
+pub enum DiagramFormat {
+    /// Graphviz (`dot -Tsvg`)
+    #[default]
+    Dot,
+    /// Mermaid flowchart (renders in Markdown on most forges)
+    Mermaid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowNodeKind {
+    Master,
+    /// `node` declared in the routing block
+    Insert,
+    /// Group or variable whose signal feeds an insert (or master, for group inserts)
+    Source,
+    /// Name used by an `fx`/`route`/`duck`/`sidechain` line but never declared
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowNode {
+    pub name: String,
+    pub kind: FlowNodeKind,
+    /// Effect names of the node's chain, in order when the source keeps one
+    pub effects: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowEdgeKind {
+    /// A source feeding the insert it is linked to
+    Link,
+    /// `route a to b`
+    Route,
+    /// Implicit route to master for inserts that route nowhere else
+    Default,
+    Duck,
+    Sidechain,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: FlowEdgeKind,
+    pub label: Option<String>,
+}
+
+/// Insert that never receives or never delivers signal, with the line that declares it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orphan {
+    pub name: String,
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Routing graph of one parsed file
+#[derive(Debug, Clone, Default)]
+pub struct SignalFlow {
+    pub nodes: Vec<FlowNode>,
+    pub edges: Vec<FlowEdge>,
+    pub orphans: Vec<Orphan>,
+}
+
+impl SignalFlow {
+    /// Build the graph from top-level statements. Files without a routing block still
+    /// get their group inserts drawn into master.
+    pub fn from_statements(statements: &[Statement]) -> Self {
+        let mut flow = SignalFlow::default();
+        flow.node(MASTER_NODE, FlowNodeKind::Master);
+
+        let mut declared = HashSet::new();
+        let mut group_effects = Vec::new();
+        for statement in statements {
+            match &statement.kind {
+                StatementKind::Group { name, .. } => {
+                    declared.insert(name.clone());
+                    if let Value::Map(map) = &statement.value
+                        && let Some(effects) = map.get("effects")
+                    {
+                        group_effects.push((name.clone(), effect_names(effects)));
+                    }
+                }
+                StatementKind::Let { name, .. }
+                | StatementKind::Var { name, .. }
+                | StatementKind::Const { name, .. }
+                | StatementKind::Param { name, .. }
+                | StatementKind::Pattern { name, .. } => {
+                    declared.insert(name.clone());
+                }
+                StatementKind::Import { names, .. } => {
+                    declared.extend(names.iter().map(|name| import_binding(name).1.to_string()))
+                }
+                _ => {}
+            }
+        }
+
+        let routing: Vec<&Statement> = statements
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                StatementKind::Routing { body } => Some(body),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        // Inserts first, so later lines can refer to nodes declared below them
+        let mut linked = HashSet::new();
+        let mut inserts = Vec::new();
+        for statement in &routing {
+            let StatementKind::RoutingNode { name, alias } = &statement.kind else {
+                continue;
+            };
+            if name == MASTER_NODE {
+                continue;
+            }
+            let insert = flow.node(name, FlowNodeKind::Insert);
+            let source = node_source(name, alias.as_deref());
+            if declared.contains(source) {
+                let from = flow.node(source, FlowNodeKind::Source);
+                flow.edge(from, insert, FlowEdgeKind::Link, None);
+                linked.insert(source.to_string());
+            }
+            inserts.push((insert, source.to_string(), statement.line));
+        }
+
+        let mut routed_out = HashSet::new();
+        let mut routed_in = HashSet::new();
+        for statement in &routing {
+            match &statement.kind {
+                StatementKind::RoutingFx { target, effects } => {
+                    let index = flow.known(target, statement.line, "fx");
+                    flow.nodes[index].effects = effect_names(effects);
+                }
+                StatementKind::RoutingRoute {
+                    source,
+                    destination,
+                    effects,
+                } => {
+                    let from = flow.known(source, statement.line, "route");
+                    let to = flow.known(destination, statement.line, "route");
+                    let label = effects.as_ref().map(route_label);
+                    flow.edge(from, to, FlowEdgeKind::Route, label);
+                    routed_out.insert(from);
+                    // Undeclared sources carry nothing
+                    if flow.nodes[from].kind == FlowNodeKind::Insert {
+                        routed_in.insert(to);
+                    }
+                }
+                StatementKind::RoutingDuck {
+                    source,
+                    destination,
+                    ..
+                }
+                | StatementKind::RoutingSidechain {
+                    source,
+                    destination,
+                    ..
+                } => {
+                    let keyword = match statement.kind {
+                        StatementKind::RoutingDuck { .. } => "duck",
+                        _ => "sidechain",
+                    };
+                    let from = flow.known(source, statement.line, keyword);
+                    let to = flow.known(destination, statement.line, keyword);
+                    let kind = match keyword {
+                        "duck" => FlowEdgeKind::Duck,
+                        _ => FlowEdgeKind::Sidechain,
+                    };
+                    flow.edge(from, to, kind, None);
+                }
+                _ => {}
+            }
+        }
+
+        for (insert, source, line) in inserts {
+            let has_source = linked.contains(&source) || routed_in.contains(&insert);
+            if !has_source {
+                let name = flow.nodes[insert].name.clone();
+                let reason = if source == name {
+                    format!(
+                        "node '{}' receives no signal: no group or variable is named '{}' and nothing routes into it",
+                        name, name
+                    )
+                } else {
+                    format!(
+                        "node '{}' receives no signal: '{}' is not a group or variable and nothing routes into it",
+                        name, source
+                    )
+                };
+                flow.orphans.push(Orphan { name, line, reason });
+            }
+            if !routed_out.contains(&insert) {
+                flow.edge(insert, 0, FlowEdgeKind::Default, None);
+            }
+        }
+
+        // Group insert chains not claimed by a routing node sum straight into master
+        for (group, effects) in group_effects {
+            if linked.contains(&group) {
+                if let Some(node) = flow
+                    .nodes
+                    .iter_mut()
+                    .find(|node| node.name == group && node.kind == FlowNodeKind::Source)
+                {
+                    node.effects = effects;
+                }
+                continue;
+            }
+            let index = flow.node(&group, FlowNodeKind::Source);
+            flow.nodes[index].effects = effects;
+            flow.edge(index, 0, FlowEdgeKind::Default, None);
+        }
+
+        flow.orphans.sort_by_key(|orphan| orphan.line);
+        flow
+    }
+
+    /// Render in `format`
+    pub fn render(&self, format: DiagramFormat) -> String {
+        match format {
+            DiagramFormat::Dot => self.to_dot(),
+            DiagramFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    /// Graphviz DOT source, left to right
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph routing {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (index, node) in self.nodes.iter().enumerate() {
+            let label = dot_escape(&node_label(node, "\\n"));
+            let style = match node.kind {
+                FlowNodeKind::Master => ", shape=doublecircle",
+                FlowNodeKind::Insert => "",
+                FlowNodeKind::Source => ", style=rounded",
+                FlowNodeKind::Missing => ", style=dashed, color=red",
+            };
+            out.push_str(&format!("    n{} [label=\"{}\"{}];\n", index, label, style));
+        }
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            if let Some(label) = edge_label(edge) {
+                attributes.push(format!("label=\"{}\"", dot_escape(&label)));
+            }
+            match edge.kind {
+                FlowEdgeKind::Default => attributes.push("style=dotted".to_string()),
+                FlowEdgeKind::Duck | FlowEdgeKind::Sidechain => {
+                    attributes.push("style=dashed".to_string())
+                }
+                FlowEdgeKind::Link | FlowEdgeKind::Route => {}
+            }
+            let attributes = if attributes.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attributes.join(", "))
+            };
+            out.push_str(&format!(
+                "    n{} -> n{}{};\n",
+                edge.from, edge.to, attributes
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid flowchart source, left to right
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for (index, node) in self.nodes.iter().enumerate() {
+            let label = mermaid_escape(&node_label(node, "<br/>"));
+            let shape = match node.kind {
+                FlowNodeKind::Master => format!("((\"{}\"))", label),
+                FlowNodeKind::Source => format!("(\"{}\")", label),
+                FlowNodeKind::Insert => format!("[\"{}\"]", label),
+                FlowNodeKind::Missing => format!("[\"{}\"]:::missing", label),
+            };
+            out.push_str(&format!("    n{}{}\n", index, shape));
+        }
+        for edge in &self.edges {
+            let label = edge_label(edge).map(|label| mermaid_escape(&label));
+            let arrow = match (edge.kind, label) {
+                (FlowEdgeKind::Default, _) => "-.->".to_string(),
+                (FlowEdgeKind::Duck | FlowEdgeKind::Sidechain, Some(label)) => {
+                    format!("-. \"{}\" .->", label)
+                }
+                (_, Some(label)) => format!("-- \"{}\" -->", label),
+                (_, None) => "-->".to_string(),
+            };
+            out.push_str(&format!("    n{} {} n{}\n", edge.from, arrow, edge.to));
+        }
+        if self
+            .nodes
+            .iter()
+            .any(|node| node.kind == FlowNodeKind::Missing)
+        {
+            out.push_str("    classDef missing stroke:#d33,stroke-dasharray:4\n");
+        }
+        out
+    }
+
+    fn node(&mut self, name: &str, kind: FlowNodeKind) -> usize {
+        if let Some(index) = self
+            .nodes
+            .iter()
+            .position(|node| node.name == name && node.kind == kind)
+        {
+            return index;
+        }
+        self.nodes.push(FlowNode {
+            name: name.to_string(),
+            kind,
+            effects: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Index of a node referenced by a routing line, recording an orphan for unknown names
+    fn known(&mut self, name: &str, line: usize, keyword: &str) -> usize {
+        if let Some(index) = self.nodes.iter().position(|node| {
+            node.name == name && matches!(node.kind, FlowNodeKind::Master | FlowNodeKind::Insert)
+        }) {
+            return index;
+        }
+        self.orphans.push(Orphan {
+            name: name.to_string(),
+            line,
+            reason: format!(
+                "'{}' uses node '{}', which is not declared with `node`",
+                keyword, name
+            ),
+        });
+        self.node(name, FlowNodeKind::Missing)
+    }
+
+    fn edge(&mut self, from: usize, to: usize, kind: FlowEdgeKind, label: Option<String>) {
+        self.edges.push(FlowEdge {
+            from,
+            to,
+            kind,
+            label,
+        });
+    }
+}
+
+fn node_label(node: &FlowNode, separator: &str) -> String {
+    if node.effects.is_empty() {
+        node.name.clone()
+    } else {
+        format!("{}{}{}", node.name, separator, node.effects.join(", "))
+    }
+}
+
+fn edge_label(edge: &FlowEdge) -> Option<String> {
+    match edge.kind {
+        FlowEdgeKind::Duck => Some("duck".to_string()),
+        FlowEdgeKind::Sidechain => Some("sidechain".to_string()),
+        _ => edge.label.clone(),
+    }
+}
+
+/// Effect names of a chain: ordered lists keep their order, maps are sorted
+fn effect_names(effects: &Value) -> Vec<String> {
+    match effects {
+        Value::Array(items) => items.iter().flat_map(effect_names).collect(),
+        Value::Map(map) => {
+            let mut names: Vec<String> = map.keys().cloned().collect();
+            names.sort();
+            names
+        }
+        Value::String(name) | Value::Identifier(name) => vec![name.clone()],
+        Value::Call { name, .. } => vec![name.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// `volume(0.2)` reads `volume 0.2`; other effects just show their names
+fn route_label(effects: &Value) -> String {
+    if let Value::Map(map) = effects
+        && map.len() == 1
+        && let Some((name, Value::Number(amount))) = map.iter().next()
+    {
+        return format!("{} {}", name, amount);
+    }
+    effect_names(effects).join(", ")
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('"', "\\\"")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+#[path = "test_graph.rs"]
+mod tests;
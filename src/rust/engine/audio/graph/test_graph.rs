@@ -0,0 +1,170 @@
+use super::*;
+use crate::language::syntax::parser::driver::SimpleParser;
+use std::path::PathBuf;
+
+fn flow(source: &str) -> SignalFlow {
+    let statements = SimpleParser::parse(source, PathBuf::from("graph.deva")).unwrap();
+    SignalFlow::from_statements(&statements)
+}
+
+fn index(flow: &SignalFlow, name: &str) -> usize {
+    flow.nodes
+        .iter()
+        .position(|node| node.name == name)
+        .unwrap()
+}
+
+const ROUTING: &str = "group kicks:
+    .kick
+
+group leads:
+    .lead
+
+routing:
+    node $master
+    node leadNode = leads
+    node kicks
+    fx leadNode -> reverb({ size: 0.9 })
+    route kicks to leadNode with volume(0.2)
+    duck leadNode to kicks with compressor({ ratio: 3.0 })
+";
+
+#[test]
+fn test_routing_block_becomes_a_graph() {
+    let flow = flow(ROUTING);
+    assert!(flow.orphans.is_empty(), "{:?}", flow.orphans);
+
+    let lead = index(&flow, "leadNode");
+    let kicks = flow
+        .nodes
+        .iter()
+        .position(|node| node.name == "kicks" && node.kind == FlowNodeKind::Insert)
+        .unwrap();
+    let leads = index(&flow, "leads");
+    let kick_group = flow
+        .nodes
+        .iter()
+        .position(|node| node.name == "kicks" && node.kind == FlowNodeKind::Source)
+        .unwrap();
+    assert_eq!(flow.nodes[lead].effects, vec!["reverb"]);
+    assert_eq!(flow.nodes[leads].kind, FlowNodeKind::Source);
+
+    let edges: Vec<_> = flow
+        .edges
+        .iter()
+        .map(|edge| (edge.from, edge.to, edge.kind))
+        .collect();
+    assert!(edges.contains(&(leads, lead, FlowEdgeKind::Link)));
+    // `node kicks` is fed by the group of the same name
+    assert!(edges.contains(&(kick_group, kicks, FlowEdgeKind::Link)));
+    assert!(edges.contains(&(kicks, lead, FlowEdgeKind::Route)));
+    assert!(edges.contains(&(lead, kicks, FlowEdgeKind::Duck)));
+    // Only the lead reaches master by default; the kicks are routed into it
+    assert!(edges.contains(&(lead, 0, FlowEdgeKind::Default)));
+    assert!(!edges.contains(&(kicks, 0, FlowEdgeKind::Default)));
+}
+
+#[test]
+fn test_dot_and_mermaid_output() {
+    let flow = flow(ROUTING);
+    let dot = flow.render(DiagramFormat::Dot);
+    assert!(dot.starts_with("digraph routing {"));
+    assert!(dot.contains("[label=\"leadNode\\nreverb\"]"));
+    assert!(dot.contains("[label=\"volume 0.2\"]"));
+    assert!(dot.contains("[label=\"duck\", style=dashed]"));
+
+    let mermaid = flow.render(DiagramFormat::Mermaid);
+    assert!(mermaid.starts_with("flowchart LR\n"));
+    assert!(mermaid.contains("((\"$master\"))"));
+    assert!(mermaid.contains("-- \"volume 0.2\" -->"));
+    assert!(!mermaid.contains("classDef missing"));
+}
+
+#[test]
+fn test_orphaned_inserts_are_reported() {
+    let flow = flow(
+        "group drums:
+    .kick
+
+routing:
+    node drumBus = drum
+    node fxBus
+    route drums to fxBus
+    fx ghost -> drive({ gain: 0.5 })
+",
+    );
+    let orphans: Vec<(&str, usize)> = flow
+        .orphans
+        .iter()
+        .map(|orphan| (orphan.name.as_str(), orphan.line))
+        .collect();
+    // `drums` is a group, not a node; `ghost` was never declared; `drumBus` has a typo'd
+    // source and `fxBus` only receives from the undeclared `drums` node
+    assert_eq!(
+        orphans,
+        vec![("drumBus", 5), ("fxBus", 6), ("drums", 7), ("ghost", 8)]
+    );
+    assert!(
+        flow.render(DiagramFormat::Mermaid)
+            .contains("classDef missing")
+    );
+}
+
+#[test]
+fn test_links_match_where_the_renderer_puts_events() {
+    use crate::engine::audio::interpreter::driver::AudioInterpreter;
+    use crate::engine::audio::interpreter::driver::renderer_graph::get_event_target_node;
+
+    let source = "let lead = synth saw
+let bass = synth square
+
+group leads:
+    lead -> note(C4) -> duration(100)
+
+group low:
+    bass -> note(C2) -> duration(100)
+
+routing:
+    node leadNode = leads
+    node low
+    node padNode = lead
+
+call leads
+call low
+lead -> note(E4) -> duration(100)
+bass -> note(E2) -> duration(100)
+";
+    let statements = SimpleParser::parse(source, PathBuf::from("graph.deva")).unwrap();
+    let flow = SignalFlow::from_statements(&statements);
+    let mut interpreter = AudioInterpreter::new(8000);
+    interpreter.collect_events(&statements).unwrap();
+
+    let targets: Vec<String> = (0..interpreter.events.events.len())
+        .map(|index| get_event_target_node(&interpreter, index))
+        .collect();
+    // Groups win over the synth variable they play; unlinked variables go to master
+    assert_eq!(targets, vec!["leadNode", "low", "padNode", MASTER_NODE]);
+
+    // Every node an event lands in is linked, in the diagram, from a source of that event
+    let linked: Vec<(&str, &str)> = flow
+        .edges
+        .iter()
+        .filter(|edge| edge.kind == FlowEdgeKind::Link)
+        .map(|edge| {
+            (
+                flow.nodes[edge.from].name.as_str(),
+                flow.nodes[edge.to].name.as_str(),
+            )
+        })
+        .collect();
+    let sources = ["leads", "low", "lead", "bass"];
+    for (source, target) in sources.iter().zip(&targets) {
+        if target != MASTER_NODE {
+            assert!(
+                linked.contains(&(source, target.as_str())),
+                "{source} -> {target}"
+            );
+        }
+    }
+    assert!(!linked.iter().any(|(source, _)| *source == "bass"));
+}
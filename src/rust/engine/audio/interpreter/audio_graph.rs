@@ -1,4 +1,5 @@
 /// Audio Graph - Representation of routing and mixing graph
+use crate::engine::audio::graph::node_source;
 use crate::language::syntax::ast::Value;
 use std::collections::HashMap;
 
@@ -176,6 +177,34 @@ impl AudioGraph {
             .collect()
     }
 
+    /// Node receiving events played by `sources` (innermost group first, then the synth
+    /// variable): that of the first source a node takes its signal from (see
+    /// `graph::node_source`), else master
+    pub fn target_node(&self, sources: &[&str]) -> &str {
+        let mut nodes: Vec<&Node> = self
+            .nodes
+            .values()
+            .filter(|node| node.name != self.master_node)
+            .collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        sources
+            .iter()
+            .find_map(|source| {
+                nodes
+                    .iter()
+                    .find(|node| node_source(&node.name, node.alias.as_deref()) == *source)
+            })
+            .map_or(self.master_node.as_str(), |node| node.name.as_str())
+    }
+
+    /// Groups and variables the nodes take their signal from
+    pub fn sources(&self) -> impl Iterator<Item = &str> {
+        self.nodes
+            .values()
+            .filter(|node| node.name != self.master_node)
+            .map(|node| node_source(&node.name, node.alias.as_deref()))
+    }
+
     /// Check if a node exists
     pub fn has_node(&self, node_name: &str) -> bool {
         self.nodes.contains_key(node_name)
@@ -3,9 +3,11 @@ use rayon::prelude::*;
 use std::collections::HashMap;
 
 use crate::engine::audio::events::AudioEventList;
+use crate::engine::audio::settings::ClickMode;
 use crate::engine::events::EventHandler;
 use crate::engine::events::EventRegistry;
 use crate::engine::functions::FunctionRegistry;
+use crate::engine::plugin::effect::normalize_effect_list;
 use crate::language::syntax::ast::{Statement, StatementKind, Value};
 
 use super::AudioInterpreter;
@@ -71,6 +73,63 @@ macro_rules! log_structured_error {
     ($_logger:expr, $_error:expr) => {};
 }
 
+/// Label of a `loop`/`for` header or the target of a `break`/`continue`
+fn loop_label(stmt: &Statement) -> Option<&str> {
+    match &stmt.value {
+        Value::String(label) => Some(label.as_str()),
+        _ => None,
+    }
+}
+
+/// Register a `duck`; both groups get their own insert so the mixer can follow one and
+/// turn the other down. Groups called before the `duck` statement are not tracked.
+fn add_duck(interpreter: &mut AudioInterpreter, source: &str, destination: &str, effect: &Value) {
+    interpreter.routing.ducks.push(super::DuckConfig {
+        source: source.to_string(),
+        destination: destination.to_string(),
+        effect: effect.clone(),
+    });
+    let groups = &mut interpreter.events.duck_groups;
+    groups.insert(source.to_string());
+    groups.insert(destination.to_string());
+}
+
+/// Register a `route ... -> outputs`; the group gets its own insert so the mixer can
+/// tap it. Like ducks, groups called before the statement are not tracked.
+fn add_output_route(interpreter: &mut AudioInterpreter, source: &str, first: u16, last: u16) {
+    let route = crate::engine::audio::outputs::OutputRoute {
+        insert: source.to_string(),
+        first,
+        last,
+    };
+    if !interpreter.routing.outputs.contains(&route) {
+        interpreter.routing.outputs.push(route);
+    }
+    interpreter.events.output_groups.insert(source.to_string());
+}
+
+/// Register a `strip` statement; like output routes, the group gets its own insert
+fn add_strip(
+    interpreter: &mut AudioInterpreter,
+    target: &str,
+    strip: &crate::engine::audio::mixer::ChannelStrip,
+) {
+    interpreter
+        .routing
+        .strips
+        .insert(target.to_string(), *strip);
+    interpreter.events.strip_groups.insert(target.to_string());
+}
+
+/// Whether a top-level `let`/`const` of `name` keeps the value it already has: one
+/// injected with `--set`, or a persisted one (skipped once per rebuild). Assignments in
+/// loop, group and function bodies run as written.
+fn declared_elsewhere(interpreter: &mut AudioInterpreter, stmt: &Statement, name: &str) -> bool {
+    stmt.indent == 0
+        && (interpreter.variable_overrides.contains_key(name)
+            || interpreter.persist.pending_init.remove(name))
+}
+
 pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statement]) -> Result<()> {
     #[cfg(feature = "cli")]
     let logger = crate::tools::logger::Logger::new();
@@ -152,23 +211,58 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                     indent: stmt.indent,
                     line: stmt.line,
                     column: stmt.column,
+                    span: stmt.span,
                 };
                 interpreter
                     .variables
                     .insert(name.clone(), Value::Statement(Box::new(func_stmt)));
             }
             StatementKind::Let { name, value } => {
-                if let Some(val) = value {
+                if let Some(val) = value
+                    && !declared_elsewhere(interpreter, stmt, name)
+                {
                     super::handler::handle_let(interpreter, name, val)?;
                 }
             }
-            StatementKind::Const { name, value } => {
+            StatementKind::Const { name, value } | StatementKind::Param { name, value } => {
                 // Treat const like let at runtime: register the value in the interpreter variables.
                 // Immutability is enforced at higher language layers; runtime simply stores the value.
-                if let Some(val) = value {
+                if let Some(val) = value
+                    && !declared_elsewhere(interpreter, stmt, name)
+                {
                     super::handler::handle_let(interpreter, name, val)?;
                 }
             }
+            StatementKind::Metronome { enabled } => {
+                interpreter.metronome = enabled.then(|| match &stmt.value {
+                    Value::String(mode) => ClickMode::parse(mode).unwrap_or(ClickMode::Mix),
+                    _ => ClickMode::Mix,
+                });
+            }
+            StatementKind::Mark { name } => {
+                interpreter
+                    .events
+                    .add_marker(name.clone(), interpreter.cursor_time);
+            }
+            StatementKind::Persist { names } => {
+                interpreter.mark_persistent(names);
+            }
+            StatementKind::ArrowCall {
+                target,
+                method,
+                args,
+            } if crate::engine::functions::theory::is_transform(method)
+                && matches!(interpreter.variables.get(target), Some(Value::Array(_))) =>
+            {
+                let chain = match &stmt.value {
+                    Value::Map(map) => match map.get("chain") {
+                        Some(Value::Array(arr)) => Some(arr.as_slice()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                super::handler::handle_array_transform(interpreter, target, method, args, chain)?;
+            }
             StatementKind::ArrowCall {
                 target,
                 method,
@@ -186,7 +280,10 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                     None
                 };
 
-                let context =
+                // `lead!pluck -> note(C4)` plays the note with the synth's `pluck` articulation
+                let (target, articulation) =
+                    crate::engine::audio::articulation::split_target(target);
+                let mut context =
                     crate::engine::audio::interpreter::statements::arrow_call::execute_arrow_call(
                         interpreter,
                         target,
@@ -213,12 +310,19 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                         e
                     })?;
 
+                if let Some(articulation) = articulation {
+                    context.set("articulation", Value::String(articulation.to_string()));
+                }
                 super::extractor::extract_audio_event(interpreter, target, &context)?;
                 interpreter.cursor_time += context.duration;
             }
             StatementKind::Tempo { value, body } => {
                 let prev_bpm = interpreter.bpm;
-                interpreter.set_bpm(*value);
+                // A `bpm` override pins the global tempo; tempo blocks still apply locally
+                match interpreter.variable_overrides.get("bpm") {
+                    Some(Value::Number(bpm)) if body.is_none() => interpreter.set_bpm(*bpm),
+                    _ => interpreter.set_bpm(*value),
+                }
 
                 // If this is a block, execute its body with the new tempo
                 if let Some(block_body) = body {
@@ -232,59 +336,53 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                 }
             }
             StatementKind::Sleep => {
-                // Accept either a raw number (ms) or a Duration value (beats/fraction)
-                match &stmt.value {
-                    Value::Number(n) => {
-                        interpreter.cursor_time += n / 1000.0;
-                    }
-                    Value::Duration(dv) => {
-                        // convert duration value to seconds
-                        let secs = match dv {
-                            crate::language::syntax::ast::DurationValue::Milliseconds(ms) => {
-                                Some(ms / 1000.0)
-                            }
-                            crate::language::syntax::ast::DurationValue::Beats(b) => {
-                                Some(b * (60.0 / interpreter.bpm))
-                            }
-                            crate::language::syntax::ast::DurationValue::Beat(s) => {
-                                // parse a fraction like "3/4" into beats
-                                if let Some((num, den)) = {
-                                    let mut sp = s.split('/');
-                                    if let (Some(a), Some(b)) = (sp.next(), sp.next()) {
-                                        if let (Ok(an), Ok(bn)) =
-                                            (a.trim().parse::<f32>(), b.trim().parse::<f32>())
-                                        {
-                                            Some((an, bn))
-                                        } else {
-                                            None
-                                        }
-                                    } else {
-                                        None
-                                    }
-                                } {
-                                    if den.abs() > f32::EPSILON {
-                                        Some((num / den) * (60.0 / interpreter.bpm))
-                                    } else {
-                                        None
-                                    }
-                                } else {
-                                    None
-                                }
-                            }
-                            crate::language::syntax::ast::DurationValue::Number(n) => {
-                                Some(n / 1000.0)
-                            }
-                            _ => None,
-                        };
-                        if let Some(s) = secs {
-                            interpreter.cursor_time += s;
-                        }
-                    }
-                    _ => {}
+                // Accept either a raw number (ms) or a Duration value (beats/fraction/span)
+                let secs = match &stmt.value {
+                    Value::Number(n) => Some(n / 1000.0),
+                    Value::Duration(dv) => interpreter.duration_secs(dv),
+                    _ => None,
+                };
+                if let Some(s) = secs {
+                    interpreter.cursor_time += s;
                 }
             }
             StatementKind::Group { name, body } => {
                 interpreter.groups.insert(name.clone(), body.clone());
+                // `group name -> fx(...) -> ...:` carries an insert chain for mixdown
+                if let Value::Map(map) = &stmt.value
+                    && let Some(effects) = map.get("effects")
+                {
+                    let effects = normalize_effect_list(effects, |path| {
+                        interpreter
+                            .resolve_value(&Value::Identifier(path.to_string()))
+                            .unwrap_or(Value::Null)
+                    })
+                    .map_err(|e| anyhow::anyhow!("group '{}': {}", name, e))?;
+                    let effects = interpreter.tempo_synced_effects(&effects);
+                    interpreter
+                        .events
+                        .group_effects
+                        .insert(name.clone(), effects);
+                }
+                // `group name solo:` / `group name mute:` join the flags set from the CLI
+                if let Value::Map(map) = &stmt.value {
+                    if matches!(map.get("solo"), Some(Value::Boolean(true))) {
+                        interpreter.solo_mute.solo.insert(name.clone());
+                    }
+                    if matches!(map.get("mute"), Some(Value::Boolean(true))) {
+                        interpreter.solo_mute.mute.insert(name.clone());
+                    }
+                }
+                // `group name with { ... }:` applies its block whenever the group runs
+                if let Value::Map(map) = &stmt.value
+                    && let Some(Value::Map(block)) = map.get("with")
+                {
+                    interpreter
+                        .group_presets
+                        .insert(name.clone(), block.clone());
+                } else {
+                    interpreter.group_presets.remove(name);
+                }
             }
             StatementKind::Routing { body } => {
                 // Process routing block - parse nodes, fx, routes, ducks, and sidechains
@@ -318,12 +416,14 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                             source,
                             destination,
                             effect,
-                        } => {
-                            interpreter.routing.ducks.push(super::DuckConfig {
-                                source: source.clone(),
-                                destination: destination.clone(),
-                                effect: effect.clone(),
-                            });
+                        } => add_duck(interpreter, source, destination, effect),
+                        StatementKind::RoutingOutputs {
+                            source,
+                            first,
+                            last,
+                        } => add_output_route(interpreter, source, *first, *last),
+                        StatementKind::RoutingStrip { target, strip } => {
+                            add_strip(interpreter, target, strip)
                         }
                         StatementKind::RoutingSidechain {
                             source,
@@ -345,6 +445,33 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                     crate::engine::audio::interpreter::AudioGraph::from_routing_setup(
                         &interpreter.routing,
                     );
+                // Events of groups feeding a node need their group to find the node
+                let sources: Vec<String> = interpreter
+                    .audio_graph
+                    .sources()
+                    .map(String::from)
+                    .collect();
+                interpreter.events.routing_groups.extend(sources);
+            }
+            // `route drums -> outputs 3-4` outside a routing block
+            StatementKind::RoutingOutputs {
+                source,
+                first,
+                last,
+            } => add_output_route(interpreter, source, *first, *last),
+            // `strip drums gain -3db` outside a routing block
+            StatementKind::RoutingStrip { target, strip } => add_strip(interpreter, target, strip),
+            // `duck pads by kick` outside a routing block
+            StatementKind::RoutingDuck {
+                source,
+                destination,
+                effect,
+            } => {
+                add_duck(interpreter, source, destination, effect);
+                interpreter.audio_graph =
+                    crate::engine::audio::interpreter::AudioGraph::from_routing_setup(
+                        &interpreter.routing,
+                    );
             }
             StatementKind::Call { name, args } => {
                 // If this call contains an inline pattern (parser stores it in stmt.value as a Map
@@ -370,6 +497,7 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                                 indent: stmt.indent,
                                 line: stmt.line,
                                 column: stmt.column,
+                                span: stmt.span,
                             };
 
                             interpreter
@@ -379,12 +507,35 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                     }
                 }
 
-                super::handler::handle_call(interpreter, name, args)?;
+                // `call name with { ... }`: the block applies to every trigger inside the call
+                let preset = match &stmt.value {
+                    Value::Map(map) => match map.get("with") {
+                        Some(Value::Map(block)) => Some(block.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let scoped = preset.is_some();
+                if let Some(block) = preset {
+                    interpreter.modifier_stack.push(block);
+                }
+                let result = super::handler::handle_call(interpreter, name, args);
+                if scoped {
+                    interpreter.modifier_stack.pop();
+                }
+                result?;
             }
 
             StatementKind::Automate { target } => {
-                // Expect stmt.value to be a Map with keys: "mode" (optional) and "body" (raw string)
-                if let Value::Map(map) = &stmt.value {
+                // Expect stmt.value to be a Map with keys: "mode" (optional) and "params" (timeline),
+                // or "param" and "formula" for the inline form
+                if let Value::Map(map) = &stmt.value
+                    && let (Some(Value::String(param)), Some(Value::String(formula))) =
+                        (map.get("param"), map.get("formula"))
+                {
+                    super::handler::handle_automate_formula(interpreter, target, param, formula)
+                        .map_err(|e| anyhow::anyhow!("{} at line {}", e, stmt.line))?;
+                } else if let Value::Map(map) = &stmt.value {
                     let mode = map
                         .get("mode")
                         .and_then(|v| {
@@ -396,11 +547,10 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                         })
                         .unwrap_or_else(|| "global".to_string());
 
-                    if let Some(Value::String(raw_body)) = map.get("body") {
-                        // Parse templates
-                        let templates =
-                            crate::engine::audio::automation::parse_param_templates_from_raw(
-                                raw_body,
+                    if let Some(params) = map.get("params") {
+                        let mut templates =
+                            crate::engine::audio::automation::parse_param_templates_from_value(
+                                params,
                             );
 
                         if mode == "note" {
@@ -418,6 +568,11 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                             let mut local_interpreter = AudioInterpreter {
                                 sample_rate: interpreter.sample_rate,
                                 bpm: current_bpm,
+                                resample_quality: interpreter.resample_quality,
+                                mix: interpreter.mix,
+                                deadline: interpreter.deadline,
+                                effect_registry: interpreter.effect_registry.clone(),
+                                synth_types: interpreter.synth_types.clone(),
                                 function_registry: FunctionRegistry::new(),
                                 events: AudioEventList::new(),
                                 variables: variables_snapshot.clone(),
@@ -436,6 +591,25 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                                 suppress_beat_emit: true,
                                 suppress_print: true,
                                 break_flag: false,
+                                continue_flag: false,
+                                loop_jump: None,
+                                loop_frames: interpreter.loop_frames.clone(),
+                                loop_pass: interpreter.loop_pass,
+                                trigger_seed: interpreter.trigger_seed,
+                                variable_overrides: interpreter.variable_overrides.clone(),
+                                persist: interpreter.persist.clone(),
+                                insert_cache: interpreter.insert_cache.clone(),
+                                tempo_map: interpreter.tempo_map.clone(),
+                                modifier_stack: interpreter.modifier_stack.clone(),
+                                group_presets: interpreter.group_presets.clone(),
+                                solo_mute: interpreter.solo_mute.clone(),
+                                group_stack: interpreter.group_stack.clone(),
+                                metronome: interpreter.metronome,
+                                scenes: interpreter.scenes.clone(),
+                                scene: None,
+                                snapshots: interpreter.snapshots.clone(),
+                                macros: interpreter.macros.clone(),
+                                pattern_chains: interpreter.pattern_chains.clone(),
                                 // Inherit background_event_tx from parent so spawned/child
                                 // interpreters reuse the same Sender when running under
                                 // live playback. This prevents child interpreters from
@@ -454,6 +628,18 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
 
                             // Inherit synth definitions
                             local_interpreter.events.synths = interpreter.events.synths.clone();
+                            local_interpreter.events.tag_all_groups =
+                                interpreter.events.tag_all_groups;
+                            local_interpreter.events.group_effects =
+                                interpreter.events.group_effects.clone();
+                            local_interpreter.events.duck_groups =
+                                interpreter.events.duck_groups.clone();
+                            local_interpreter.events.output_groups =
+                                interpreter.events.output_groups.clone();
+                            local_interpreter.events.strip_groups =
+                                interpreter.events.strip_groups.clone();
+                            local_interpreter.events.routing_groups =
+                                interpreter.events.routing_groups.clone();
 
                             // Simulate to measure duration
                             if !remaining.is_empty() {
@@ -462,6 +648,12 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
 
                             let end_time =
                                 interpreter.cursor_time + local_interpreter.events.total_duration();
+                            for tpl in templates.iter_mut() {
+                                tpl.resolve_timed_points(
+                                    end_time - interpreter.cursor_time,
+                                    current_bpm,
+                                );
+                            }
 
                             // Create context with timing information
                             let context = super::NoteAutomationContext {
@@ -490,6 +682,11 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                             let mut local_interpreter = AudioInterpreter {
                                 sample_rate: interpreter.sample_rate,
                                 bpm: current_bpm,
+                                resample_quality: interpreter.resample_quality,
+                                mix: interpreter.mix,
+                                deadline: interpreter.deadline,
+                                effect_registry: interpreter.effect_registry.clone(),
+                                synth_types: interpreter.synth_types.clone(),
                                 function_registry: FunctionRegistry::new(),
                                 events: AudioEventList::new(),
                                 variables: variables_snapshot.clone(),
@@ -508,6 +705,25 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                                 suppress_beat_emit: true,
                                 suppress_print: true,
                                 break_flag: false,
+                                continue_flag: false,
+                                loop_jump: None,
+                                loop_frames: interpreter.loop_frames.clone(),
+                                loop_pass: interpreter.loop_pass,
+                                trigger_seed: interpreter.trigger_seed,
+                                variable_overrides: interpreter.variable_overrides.clone(),
+                                persist: interpreter.persist.clone(),
+                                insert_cache: interpreter.insert_cache.clone(),
+                                tempo_map: interpreter.tempo_map.clone(),
+                                modifier_stack: interpreter.modifier_stack.clone(),
+                                group_presets: interpreter.group_presets.clone(),
+                                solo_mute: interpreter.solo_mute.clone(),
+                                group_stack: interpreter.group_stack.clone(),
+                                metronome: interpreter.metronome,
+                                scenes: interpreter.scenes.clone(),
+                                scene: None,
+                                snapshots: interpreter.snapshots.clone(),
+                                macros: interpreter.macros.clone(),
+                                pattern_chains: interpreter.pattern_chains.clone(),
                                 // Keep the same background sender as the parent interpreter
                                 background_event_tx: interpreter.background_event_tx.clone(),
                                 background_event_rx: None,
@@ -522,6 +738,8 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
 
                             // Inherit synth definitions so durations reflect real events
                             local_interpreter.events.synths = interpreter.events.synths.clone();
+                            local_interpreter.events.tag_all_groups =
+                                interpreter.events.tag_all_groups;
 
                             // Simulate collecting events for the remaining statements to estimate duration
                             if !remaining.is_empty() {
@@ -530,48 +748,19 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
 
                             let total_dur = local_interpreter.events.total_duration();
                             let start_time = interpreter.cursor_time;
+                            for tpl in templates.iter_mut() {
+                                tpl.resolve_timed_points(total_dur, current_bpm);
+                            }
 
                             let mut envelope =
                                 crate::engine::audio::automation::AutomationEnvelope::new(
                                     target.clone(),
                                 );
 
-                            // For each template, create AutomationParam segments between adjacent points
+                            // One segment per keyframe pair, repeated over loop regions
                             for tpl in templates.iter() {
-                                if tpl.points.len() >= 2 {
-                                    for w in tpl.points.windows(2) {
-                                        let (p0, v0) = w[0];
-                                        let (p1, v1) = w[1];
-                                        let seg_start = start_time + p0 * total_dur;
-                                        let seg_dur = (p1 - p0) * total_dur;
-                                        if seg_dur <= 0.0 {
-                                            continue;
-                                        }
-                                        envelope.add_param(
-                                            crate::engine::audio::automation::AutomationParam {
-                                                param_name: tpl.param_name.clone(),
-                                                from_value: v0,
-                                                to_value: v1,
-                                                start_time: seg_start,
-                                                duration: seg_dur,
-                                                curve: tpl.curve,
-                                            },
-                                        );
-                                    }
-                                } else if tpl.points.len() == 1 {
-                                    // Single point - treat as immediate set at that fraction
-                                    let (p, v) = tpl.points[0];
-                                    let seg_start = start_time + p * total_dur;
-                                    envelope.add_param(
-                                        crate::engine::audio::automation::AutomationParam {
-                                            param_name: tpl.param_name.clone(),
-                                            from_value: v,
-                                            to_value: v,
-                                            start_time: seg_start,
-                                            duration: 0.0,
-                                            curve: tpl.curve,
-                                        },
-                                    );
+                                for segment in tpl.segments(start_time, total_dur) {
+                                    envelope.add_param(segment);
                                 }
                             }
 
@@ -586,14 +775,63 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                 unreachable!("Spawn statements should be handled in parallel section");
             }
             StatementKind::Loop { count, body } => {
-                interpreter.execute_loop(count, body)?;
+                if interpreter.persist.loops {
+                    // Continue the pass index where the previous build's loop stopped
+                    let base = interpreter
+                        .persist
+                        .previous
+                        .loop_passes
+                        .get(&stmt.line)
+                        .copied()
+                        .unwrap_or(0);
+                    let passes =
+                        interpreter.execute_loop_from(count, body, base, loop_label(stmt))?;
+                    interpreter
+                        .persist
+                        .loop_passes
+                        .insert(stmt.line, base + passes);
+                } else {
+                    interpreter.execute_loop_from(count, body, 0, loop_label(stmt))?;
+                }
             }
             StatementKind::For {
                 variable,
                 iterable,
                 body,
             } => {
-                interpreter.execute_for(variable, iterable, body)?;
+                interpreter.execute_for(variable, iterable, body, loop_label(stmt))?;
+            }
+            StatementKind::At { position, body } => {
+                // Place the body at an absolute position, then resume the sequential cursor
+                let saved_cursor = interpreter.cursor_time;
+                interpreter.cursor_time = interpreter.time_position_secs(position);
+                collect_events(interpreter, body)?;
+                interpreter.cursor_time = saved_cursor;
+                interpreter.special_vars.update_time(saved_cursor);
+            }
+            StatementKind::Scene { name, members } => {
+                interpreter.scenes.insert(name.clone(), members.clone());
+            }
+            StatementKind::Switch { scene, fade } => {
+                super::handler::handle_switch(interpreter, scene, fade.as_ref())?;
+            }
+            StatementKind::SnapshotSave { name } => {
+                let snapshot = super::handler::capture_snapshot(interpreter);
+                interpreter.snapshots.insert(name.clone(), snapshot);
+            }
+            StatementKind::SnapshotMorph {
+                from,
+                to,
+                length,
+                curve,
+            } => {
+                super::handler::handle_snapshot_morph(
+                    interpreter,
+                    from,
+                    to,
+                    length.as_ref(),
+                    curve.as_deref(),
+                )?;
             }
             StatementKind::Return { value } => {
                 // Only allow 'return' inside a function call context
@@ -616,10 +854,29 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                 // Stop further execution of the current statement block
                 return Ok(());
             }
-            StatementKind::Break => {
-                // Signal to enclosing loop/for that a break occurred
-                interpreter.break_flag = true;
-                // Stop processing this block so loop logic can handle the break
+            StatementKind::Break | StatementKind::Continue => {
+                let target = loop_label(stmt);
+                if let Some(label) = target
+                    && !interpreter
+                        .loop_frames
+                        .iter()
+                        .any(|frame| frame.label.as_deref() == Some(label))
+                {
+                    return Err(anyhow::anyhow!(
+                        "no enclosing loop labelled '{}' at {}:{}",
+                        label,
+                        stmt.line,
+                        stmt.column
+                    ));
+                }
+                // Signal the targeted loop/for; it consumes the flag after the pass
+                interpreter.loop_jump = target.map(str::to_string);
+                if matches!(stmt.kind, StatementKind::Break) {
+                    interpreter.break_flag = true;
+                } else {
+                    interpreter.continue_flag = true;
+                }
+                // Stop processing this block so loop logic can handle the jump
                 return Ok(());
             }
             StatementKind::Print => {
@@ -672,7 +929,7 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                 super::handler::handle_assign(interpreter, target, property, &stmt.value)?;
             }
             StatementKind::Load { source, alias } => {
-                super::handler::handle_load(interpreter, source, alias)?;
+                super::handler::handle_load(interpreter, source, alias, &stmt.value)?;
             }
             #[cfg(feature = "cli")]
             StatementKind::UsePlugin {
@@ -699,30 +956,52 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                     indent: stmt.indent,
                     line: stmt.line,
                     column: stmt.column,
+                    span: stmt.span,
                 };
                 interpreter
                     .variables
                     .insert(name.clone(), Value::Statement(Box::new(pattern_stmt)));
+                match stmt.value.get("chain") {
+                    Some(Value::String(chain)) => {
+                        super::handler::handle_pattern_chain(interpreter, name, chain)?
+                    }
+                    _ => {
+                        interpreter.pattern_chains.remove(name);
+                    }
+                }
             }
             StatementKind::Bank { name, alias } => {
                 super::handler::handle_bank(interpreter, name, alias)?;
             }
+            StatementKind::Macro { name, targets } => {
+                super::handler::handle_macro(interpreter, name, targets)?;
+            }
             StatementKind::Bind { source, target } => {
                 super::handler::handle_bind(interpreter, source, target, &stmt.value)?;
             }
             StatementKind::Import { names, source } => {
+                use crate::language::preprocessor::loader::{import_binding, load_module_exports};
                 let path = std::path::Path::new(&source);
+                let params = match &stmt.value {
+                    Value::Map(params) => params.clone(),
+                    _ => HashMap::new(),
+                };
                 if path.exists() {
-                    match crate::language::preprocessor::loader::load_module_exports(path) {
+                    match load_module_exports(path, &params) {
                         Ok(exports) => {
-                            for name in names {
+                            for (name, value) in &exports.params {
+                                super::handler::handle_let(interpreter, name, value)?;
+                            }
+                            for entry in names {
+                                let (name, local) = import_binding(entry);
+                                let local = local.to_string();
                                 if let Some(val) = exports.variables.get(name) {
-                                    interpreter.variables.insert(name.clone(), val.clone());
+                                    interpreter.variables.insert(local, val.clone());
                                 } else if let Some(group_body) = exports.groups.get(name) {
-                                    interpreter.groups.insert(name.clone(), group_body.clone());
+                                    interpreter.groups.insert(local, group_body.clone());
                                 } else if let Some(pattern_stmt) = exports.patterns.get(name) {
                                     interpreter.variables.insert(
-                                        name.clone(),
+                                        local,
                                         Value::Statement(Box::new(pattern_stmt.clone())),
                                     );
                                 } else {
@@ -743,9 +1022,22 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                 duration: _,
                 effects,
             } => {
+                // Per-trigger modifiers (`chance`, `every`) gate whether this hit fires; a `roll`
+                // repeats it.
                 // Pass the effects associated with the trigger statement into the handler so
                 // runtime scheduling can attach them to sample events.
-                super::handler::handle_trigger(interpreter, entity, effects.as_ref())?;
+                if super::handler::trigger_passes_modifiers(interpreter, stmt) {
+                    let note = super::handler::trigger_note(stmt);
+                    let first_event = interpreter.events.events.len();
+                    super::handler::handle_trigger(interpreter, entity, effects.as_ref(), note)?;
+                    super::handler::tag_sample_region(interpreter, stmt, first_event);
+                    if let Some(roll) = super::handler::trigger_roll(interpreter, stmt) {
+                        interpreter.events.apply_roll(first_event, &roll);
+                    }
+                } else {
+                    // A skipped hit still occupies its step so the surrounding rhythm is kept
+                    interpreter.cursor_time += interpreter.beat_duration();
+                }
             }
             StatementKind::Unknown => {
                 // Unknown statements are parser errors - log them with structured formatting
@@ -771,7 +1063,7 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                     {
                         let mut structured_err =
                             crate::tools::logger::StructuredError::new(&main_msg)
-                                .with_location(stmt.line, stmt.column)
+                                .with_span(stmt.line, stmt.span.start_column, stmt.span.end_column)
                                 .with_type("UnknownStatement");
 
                         // Add file location if available
@@ -798,10 +1090,10 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                     {
                         use crate::web::registry::debug;
                         if debug::is_debug_errors_enabled() {
-                            debug::push_parse_error_from_parts(
+                            debug::push_parse_error_with_span(
                                 error_msg.clone(),
                                 stmt.line,
-                                stmt.column,
+                                &stmt.span,
                                 "UnknownStatement".to_string(),
                             );
                         }
@@ -810,6 +1102,11 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
             }
             _ => {}
         }
+
+        // A break/continue raised in a nested block (e.g. an `if`) ends this block too
+        if interpreter.break_flag || interpreter.continue_flag {
+            return Ok(());
+        }
     }
 
     if !spawns.is_empty() {
@@ -873,6 +1170,11 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                     let mut local_interpreter = AudioInterpreter {
                         sample_rate: interpreter.sample_rate,
                         bpm: current_bpm,
+                        resample_quality: interpreter.resample_quality,
+                        mix: interpreter.mix,
+                        deadline: interpreter.deadline,
+                        effect_registry: interpreter.effect_registry.clone(),
+                        synth_types: interpreter.synth_types.clone(),
                         function_registry: FunctionRegistry::new(),
                         events: AudioEventList::new(),
                         variables: variables_snapshot.clone(),
@@ -881,7 +1183,16 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                         automation_registry: interpreter.automation_registry.clone(),
                         note_automation_templates: interpreter.note_automation_templates.clone(),
                         cursor_time: current_time,
-                        special_vars: special_vars_snapshot.clone(),
+                        // Seeded builds give each spawn its own random stream so results
+                        // don't depend on which worker thread runs first
+                        special_vars: {
+                            let mut vars = special_vars_snapshot.clone();
+                            vars.random = vars.random.fork(
+                                ((stmt.line as u64) << 32 | stmt.column as u64)
+                                    ^ u64::from(current_time.to_bits()) << 16,
+                            );
+                            vars
+                        },
                         event_registry: EventRegistry::new(),
                         #[cfg(feature = "cli")]
                         midi_manager: interpreter.midi_manager.clone(),
@@ -889,6 +1200,25 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                         suppress_beat_emit: interpreter.suppress_beat_emit,
                         suppress_print: interpreter.suppress_print,
                         break_flag: false,
+                        continue_flag: false,
+                        loop_jump: None,
+                        loop_frames: interpreter.loop_frames.clone(),
+                        loop_pass: interpreter.loop_pass,
+                        trigger_seed: interpreter.trigger_seed,
+                        variable_overrides: interpreter.variable_overrides.clone(),
+                        persist: interpreter.persist.clone(),
+                        insert_cache: interpreter.insert_cache.clone(),
+                        tempo_map: interpreter.tempo_map.clone(),
+                        modifier_stack: interpreter.modifier_stack.clone(),
+                        group_presets: interpreter.group_presets.clone(),
+                        solo_mute: interpreter.solo_mute.clone(),
+                        group_stack: interpreter.group_stack.clone(),
+                        metronome: interpreter.metronome,
+                        scenes: interpreter.scenes.clone(),
+                        scene: None,
+                        snapshots: interpreter.snapshots.clone(),
+                        macros: interpreter.macros.clone(),
+                        pattern_chains: interpreter.pattern_chains.clone(),
                         // Ensure spawned local interpreters inherit the parent's
                         // background sender when present. This avoids creating
                         // ephemeral receivers that would be dropped and cause
@@ -906,11 +1236,20 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
 
                     // Inherit synth definitions from parent so spawned groups can snapshot synths/plugins
                     local_interpreter.events.synths = interpreter.events.synths.clone();
+                    local_interpreter.events.tag_all_groups = interpreter.events.tag_all_groups;
+                    local_interpreter.events.group_effects =
+                        interpreter.events.group_effects.clone();
+                    local_interpreter.events.duck_groups = interpreter.events.duck_groups.clone();
+                    local_interpreter.events.output_groups =
+                        interpreter.events.output_groups.clone();
+                    local_interpreter.events.strip_groups = interpreter.events.strip_groups.clone();
+                    local_interpreter.events.routing_groups =
+                        interpreter.events.routing_groups.clone();
 
                     // Try to spawn a group first
                     if let Some(body) = groups_snapshot.get(resolved_name) {
                         // Spawn group (parallel)
-                        collect_events(&mut local_interpreter, body)?;
+                        super::handler::run_group(&mut local_interpreter, resolved_name, body)?;
                         Ok(local_interpreter.events)
                     }
                     // Try to spawn a pattern
@@ -926,6 +1265,7 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
                                             tgt.as_str(),
                                             &pat,
                                             options,
+                                            super::handler::pattern_accent(&stmt_box.value),
                                         )?;
                                         return Ok(local_interpreter.events);
                                     }
@@ -965,57 +1305,6 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
         }
     }
 
-    // Emit some built-in beat events to trigger 'on beat' handlers.
-    // Use the interpreter.special_vars.total_duration when available, but limit to a small number
-    // of beats to avoid excessively long renders during tests.
-    {
-        // Emit beat events at exact beat grid (0, beat, 2*beat, ...) up to total duration.
-        // We temporarily set interpreter.cursor_time and special_vars so handlers add events at
-        // the correct beat timestamps, then we restore the original cursor_time afterwards.
-        if !interpreter.suppress_beat_emit {
-            let beat_duration = 60.0 / interpreter.bpm;
-
-            // Determine total duration to cover. Prefer special_vars.total_duration if set.
-            let total = if interpreter.special_vars.total_duration > 0.0 {
-                interpreter.special_vars.total_duration
-            } else {
-                // Fallback: cover at least 4 beats
-                beat_duration * 4.0
-            };
-
-            let max_beats = ((total / beat_duration).ceil() as usize).min(256).max(1);
-
-            // Save previous state
-            let prev_cursor = interpreter.cursor_time;
-            let prev_time = interpreter.special_vars.current_time;
-            let prev_beat = interpreter.special_vars.current_beat;
-
-            for i in 0..max_beats {
-                let beat_time = i as f32 * beat_duration;
-                interpreter.cursor_time = beat_time;
-                // Update special vars consistently (updates beat and bar)
-                interpreter.special_vars.update_time(beat_time);
-
-                // execute handlers for 'beat' but ensure handlers won't re-emit beats by setting the guard
-                let prev = interpreter.suppress_beat_emit;
-                interpreter.suppress_beat_emit = true;
-                interpreter.execute_event_handlers("beat")?;
-                // Also call 'bar' handlers at bar boundaries (every 4 beats)
-                if (i % 4) == 0 {
-                    interpreter.execute_event_handlers("bar")?;
-                }
-                interpreter.suppress_beat_emit = prev;
-            }
-
-            // Restore previous state
-            interpreter.cursor_time = prev_cursor;
-            interpreter.special_vars.current_time = prev_time;
-            interpreter.special_vars.current_beat = prev_beat;
-            // restore bar too
-            interpreter.special_vars.current_bar = prev_beat / 4.0;
-        }
-    }
-
     // Do not perform a blocking drain here — background 'pass' workers will deliver
     // their events asynchronously. Blocking here caused noticeable latency before
     // playback in some offline render scenarios, so we avoid waiting and let the
@@ -1023,3 +1312,7 @@ pub fn collect_events(interpreter: &mut AudioInterpreter, statements: &[Statemen
 
     Ok(())
 }
+
+#[cfg(test)]
+#[path = "test_collector.rs"]
+mod tests;
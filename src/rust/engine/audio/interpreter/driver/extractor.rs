@@ -1,4 +1,4 @@
-use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::events::{AudioEvent, SynthDefinition};
 use crate::language::syntax::ast::Value;
 use anyhow::Result;
 
@@ -48,6 +48,78 @@ fn apply_automation_param(
     result
 }
 
+/// Attach note-mode automation to the sample events pushed since `first_event`.
+///
+/// A trigger like `kit.kick` picks up templates declared for `kit.kick` or for the whole
+/// bank (`automate kit mode note:`) when it starts inside that automation block.
+pub fn tag_sample_automation(interpreter: &mut AudioInterpreter, entity: &str, first_event: usize) {
+    let entity = entity.trim_start_matches('.');
+    let candidates = [Some(entity), entity.split_once('.').map(|(alias, _)| alias)];
+    let templates = &interpreter.note_automation_templates;
+    for event in interpreter.events.events.iter_mut().skip(first_event) {
+        if let AudioEvent::Sample {
+            start_time,
+            automation,
+            ..
+        } = event
+            && automation.is_none()
+        {
+            *automation = candidates
+                .iter()
+                .flatten()
+                .find(|target| {
+                    templates.get(**target).is_some_and(|ctx| {
+                        *start_time >= ctx.start_time && *start_time <= ctx.end_time
+                    })
+                })
+                .map(|target| target.to_string());
+        }
+    }
+}
+
+/// Apply the note's articulation (`lead!pluck`) to its synth snapshot; returns how long
+/// the note sounds
+fn apply_articulation(
+    synth_def: &mut SynthDefinition,
+    context: &crate::engine::functions::FunctionContext,
+    duration: f32,
+) -> Result<f32> {
+    let Some(Value::String(name)) = context.get("articulation") else {
+        return Ok(duration);
+    };
+    let articulation = crate::engine::audio::articulation::resolve(synth_def, name)?;
+    articulation.apply(synth_def);
+    Ok(duration * articulation.length.unwrap_or(1.0))
+}
+
+/// Vibrato and pitch envelope for one note: the synth's settings, then automation, then
+/// the note's own `-> vibrato(...)` / `-> pitch_env(...)`
+fn apply_pitch_options(
+    interpreter: &AudioInterpreter,
+    synth_id: &str,
+    synth_def: &mut SynthDefinition,
+    context: &crate::engine::functions::FunctionContext,
+) {
+    use crate::engine::audio::synth::pitch::{PITCH_ENV_OPTIONS, VIBRATO_OPTIONS};
+
+    for (option, _) in VIBRATO_OPTIONS.iter().chain(&PITCH_ENV_OPTIONS) {
+        let current = synth_def.options.get(*option).copied();
+        let automated = apply_automation_param(
+            interpreter,
+            synth_id,
+            &[option],
+            current.unwrap_or(0.0),
+            interpreter.cursor_time,
+        );
+        if current.is_some() || automated != 0.0 {
+            synth_def.options.insert(option.to_string(), automated);
+        }
+        if let Some(Value::Number(n)) = context.get(option) {
+            synth_def.options.insert(option.to_string(), *n);
+        }
+    }
+}
+
 pub fn extract_audio_event(
     interpreter: &mut AudioInterpreter,
     target: &str,
@@ -199,6 +271,9 @@ pub fn extract_audio_event(
             }
         }
 
+        let duration = apply_articulation(&mut synth_def, context, duration)?;
+        apply_pitch_options(interpreter, synth_id, &mut synth_def, context);
+
         let mut synth_effects_vec: Vec<crate::language::syntax::ast::Value> = Vec::new();
         if let Some(var_val) = interpreter.variables.get(synth_id) {
             match var_val {
@@ -330,7 +405,7 @@ pub fn extract_audio_event(
             reverb_amount: None,
             drive_amount: None,
             drive_color: None,
-            effects: event_effects,
+            effects: event_effects.map(|fx| interpreter.tempo_synced_effects(&fx)),
             use_per_note_automation,
         });
         return Ok(());
@@ -487,11 +562,13 @@ pub fn extract_audio_event(
             let synth_id = if target.is_empty() { "default" } else { target };
 
             // Create chord event directly with per-note automation flag
-            let synth_def = interpreter
+            let mut synth_def = interpreter
                 .events
                 .get_synth(synth_id)
                 .cloned()
                 .unwrap_or_default();
+            let duration = apply_articulation(&mut synth_def, context, duration)?;
+            apply_pitch_options(interpreter, synth_id, &mut synth_def, context);
             // Determine effects for this chord event by merging synth-level and any chord-level effects
             let mut event_effects: Option<crate::language::syntax::ast::Value> = None;
 
@@ -584,28 +661,70 @@ pub fn extract_audio_event(
                 event_effects = Some(crate::language::syntax::ast::Value::Array(merged));
             }
 
-            interpreter.events.events.push(AudioEvent::Chord {
-                midis,
-                start_time: interpreter.cursor_time,
-                duration,
-                velocity,
-                synth_id: synth_id.to_string(),
-                synth_def,
-                pan,
-                detune,
-                spread,
-                gain,
-                attack,
-                release,
-                delay_time,
-                delay_feedback,
-                delay_mix,
-                reverb_amount,
-                drive_amount,
-                drive_color,
-                effects: event_effects,
-                use_per_note_automation: false,
-            });
+            let strum = match context.get("strum") {
+                Some(Value::Number(ms)) if *ms > 0.0 => *ms / 1000.0,
+                _ => 0.0,
+            };
+            if strum > 0.0 {
+                // A strummed chord becomes one note per pitch, each offset by `strum`;
+                // pan and level per note match what the unstrummed chord would render
+                let direction = match context.get("strum_direction") {
+                    Some(Value::String(direction)) => direction.as_str(),
+                    _ => "up",
+                };
+                let effects = event_effects.map(|fx| interpreter.tempo_synced_effects(&fx));
+                let count = midis.len();
+                let order = strum_order(interpreter, &midis, direction);
+                for (step, index) in order.into_iter().enumerate() {
+                    interpreter.events.events.push(AudioEvent::Note {
+                        midi: midis[index],
+                        start_time: interpreter.cursor_time + step as f32 * strum,
+                        duration,
+                        velocity,
+                        synth_id: synth_id.to_string(),
+                        synth_def: synth_def.clone(),
+                        pan: crate::engine::audio::generator::chord_note_pan(
+                            index, count, pan, spread,
+                        ),
+                        detune,
+                        gain: gain
+                            * crate::engine::audio::generator::chord_note_weight(index, count),
+                        attack,
+                        release,
+                        delay_time,
+                        delay_feedback,
+                        delay_mix,
+                        reverb_amount,
+                        drive_amount,
+                        drive_color,
+                        effects: effects.clone(),
+                        use_per_note_automation,
+                    });
+                }
+            } else {
+                interpreter.events.events.push(AudioEvent::Chord {
+                    midis,
+                    start_time: interpreter.cursor_time,
+                    duration,
+                    velocity,
+                    synth_id: synth_id.to_string(),
+                    synth_def,
+                    pan,
+                    detune,
+                    spread,
+                    gain,
+                    attack,
+                    release,
+                    delay_time,
+                    delay_feedback,
+                    delay_mix,
+                    reverb_amount,
+                    drive_amount,
+                    drive_color,
+                    effects: event_effects.map(|fx| interpreter.tempo_synced_effects(&fx)),
+                    use_per_note_automation: false,
+                });
+            }
             // Apply note-mode/global automation to synth-specific options (cutoff, resonance, etc.)
             // Collect automated values first to avoid borrowing conflicts
             let synth_params = [
@@ -645,3 +764,25 @@ pub fn extract_audio_event(
     }
     Ok(())
 }
+
+/// Order in which a strummed chord's notes sound: `up` from the lowest pitch, `down`
+/// from the highest, `random` shuffled from the interpreter's (seedable) random source
+fn strum_order(interpreter: &AudioInterpreter, midis: &[u8], direction: &str) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..midis.len()).collect();
+    order.sort_by_key(|&index| midis[index]);
+    match direction {
+        "down" => order.reverse(),
+        "random" => {
+            for i in (1..order.len()).rev() {
+                let j = (interpreter.special_vars.random.next_u64() % (i as u64 + 1)) as usize;
+                order.swap(i, j);
+            }
+        }
+        _ => {}
+    }
+    order
+}
+
+#[cfg(test)]
+#[path = "test_extractor.rs"]
+mod tests;
@@ -2,15 +2,39 @@
 use anyhow::Result;
 use std::collections::HashMap;
 
-use crate::language::syntax::ast::{Statement, StatementKind, Value};
+use crate::engine::audio::pattern_chain::PatternChain;
+use crate::engine::audio::scene::SceneCue;
+use crate::engine::audio::snapshot::ParamSnapshot;
+use crate::language::syntax::ast::{DurationValue, Statement, StatementKind, Value};
 
 use super::AudioInterpreter;
 
 pub fn handle_let(interpreter: &mut AudioInterpreter, name: &str, value: &Value) -> Result<()> {
+    // Call expressions (`merge(a, b)`, `settings.keys()`) are evaluated once and their result
+    // stored as plain data
+    if let Value::Call { .. } = value {
+        let result = interpreter.resolve_value(value)?;
+        interpreter.variables.insert(name.to_string(), result);
+        return Ok(());
+    }
+
+    // Plain data maps (`{ swing: 0.2 }`) are stored as written; only object definitions
+    // (synths, plugins) receive default properties below
+    if let Value::Map(map) = value
+        && !["type", "synth_type", "waveform", "_plugin_ref"]
+            .iter()
+            .any(|key| map.contains_key(*key))
+    {
+        interpreter
+            .variables
+            .insert(name.to_string(), value.clone());
+        return Ok(());
+    }
+
     // Check if this is a synth definition (has waveform parameter OR _plugin_ref)
     if let Value::Map(orig_map) = value {
-        // Clone la map pour modification
-        let mut map = orig_map.clone();
+        // Flatten `extends` first so the stored map carries every inherited parameter
+        let mut map = resolve_synth_parents(interpreter, orig_map, &mut vec![name.to_string()])?;
 
         // Normalize older key `synth_type` into `type` so downstream logic reads the same key.
         // Prefer chained synth_type over default "synth" placeholder when present.
@@ -121,6 +145,7 @@ pub fn handle_let(interpreter: &mut AudioInterpreter, name: &str, value: &Value)
             let decay = crate::engine::audio::events::extract_number(&map, "decay", 0.1);
             let sustain = crate::engine::audio::events::extract_number(&map, "sustain", 0.7);
             let release = crate::engine::audio::events::extract_number(&map, "release", 0.2);
+            let curves = crate::engine::audio::events::extract_envelope_curves(&map);
 
             // Accept both String and Identifier for type (parser may emit Identifier for bare words)
             let synth_type = if let Some(v) = map.get("type") {
@@ -146,6 +171,8 @@ pub fn handle_let(interpreter: &mut AudioInterpreter, name: &str, value: &Value)
             } else {
                 None
             };
+            // Synth types the engine leaves out play as the plain oscillator
+            let synth_type = synth_type.filter(|name| interpreter.synth_types.allows(name));
 
             let filters = if let Some(Value::Array(filters_arr)) = map.get("filters") {
                 crate::engine::audio::events::extract_filters(filters_arr)
@@ -216,6 +243,10 @@ pub fn handle_let(interpreter: &mut AudioInterpreter, name: &str, value: &Value)
                     "decay",
                     "sustain",
                     "release",
+                    "curve",
+                    "attack_curve",
+                    "decay_curve",
+                    "release_curve",
                     "type",
                     "filters",
                     "_plugin_ref",
@@ -228,6 +259,10 @@ pub fn handle_let(interpreter: &mut AudioInterpreter, name: &str, value: &Value)
                     "decay",
                     "sustain",
                     "release",
+                    "curve",
+                    "attack_curve",
+                    "decay_curve",
+                    "release_curve",
                     "type",
                     "filters",
                     "_plugin_ref",
@@ -263,6 +298,8 @@ pub fn handle_let(interpreter: &mut AudioInterpreter, name: &str, value: &Value)
                 }
             }
 
+            crate::engine::audio::synth::pitch::extract_pitch_options(&map, &mut options);
+
             if is_plugin && map.contains_key("decay") {
                 options.insert("decay".to_string(), decay);
             }
@@ -279,6 +316,7 @@ pub fn handle_let(interpreter: &mut AudioInterpreter, name: &str, value: &Value)
                 decay,
                 sustain,
                 release,
+                curves,
                 synth_type,
                 filters,
                 options,
@@ -286,6 +324,7 @@ pub fn handle_let(interpreter: &mut AudioInterpreter, name: &str, value: &Value)
                 plugin_name,
                 plugin_export,
                 lfo,
+                articulations: crate::engine::audio::articulation::extract_articulations(&map),
             };
 
             interpreter.events.add_synth(name.to_string(), synth_def);
@@ -343,6 +382,15 @@ pub fn handle_call(interpreter: &mut AudioInterpreter, name: &str, args: &[Value
     // - Groups (defined with `group name:`)
     // ============================================================================
 
+    // A chain plays its next step
+    if let Some(step) = interpreter
+        .pattern_chains
+        .get_mut(name)
+        .map(PatternChain::advance)
+    {
+        return handle_call(interpreter, &step, args);
+    }
+
     // Check for user-defined function stored as a variable
     if let Some(var_val) = interpreter.variables.get(name).cloned() {
         if let Value::Statement(stmt_box) = var_val {
@@ -403,7 +451,8 @@ pub fn handle_call(interpreter: &mut AudioInterpreter, name: &str, args: &[Value
                 if let Some(tgt) = target.as_ref() {
                     let (pattern_str, options) = interpreter.extract_pattern_data(&stmt_box.value);
                     if let Some(pat) = pattern_str {
-                        interpreter.execute_pattern(tgt.as_str(), &pat, options)?;
+                        let accent = pattern_accent(&stmt_box.value);
+                        interpreter.execute_pattern(tgt.as_str(), &pat, options, accent)?;
                         return Ok(());
                     }
                 }
@@ -413,8 +462,7 @@ pub fn handle_call(interpreter: &mut AudioInterpreter, name: &str, args: &[Value
 
     // If it's a group call, execute the group body
     if let Some(body) = interpreter.groups.get(name).cloned() {
-        super::collector::collect_events(interpreter, &body)?;
-        return Ok(());
+        return run_group(interpreter, name, &body);
     }
 
     println!(
@@ -424,6 +472,182 @@ pub fn handle_call(interpreter: &mut AudioInterpreter, name: &str, args: &[Value
     Ok(())
 }
 
+/// Declare `pattern name = a then b ...`, restarting the chain from its first step.
+/// A chain that can reach itself again would never pick a pattern, so it is refused.
+pub fn handle_pattern_chain(
+    interpreter: &mut AudioInterpreter,
+    name: &str,
+    source: &str,
+) -> Result<()> {
+    let chain = PatternChain::parse(source)?;
+    let mut pending: Vec<&str> = chain.patterns();
+    let mut seen = std::collections::HashSet::new();
+    while let Some(pattern) = pending.pop() {
+        if pattern == name {
+            anyhow::bail!("pattern chain '{}' plays itself", name);
+        }
+        if seen.insert(pattern)
+            && let Some(inner) = interpreter.pattern_chains.get(pattern)
+        {
+            pending.extend(inner.patterns());
+        }
+    }
+    interpreter.pattern_chains.insert(name.to_string(), chain);
+    Ok(())
+}
+
+/// `switch scene [over duration]`: run every member of the scene from the cursor, then
+/// continue after the longest one. The fade is only recorded; live playback uses it to
+/// crossfade between builds (see `engine::audio::scene`).
+pub fn handle_switch(
+    interpreter: &mut AudioInterpreter,
+    scene: &str,
+    fade: Option<&DurationValue>,
+) -> Result<()> {
+    let members = interpreter
+        .scenes
+        .get(scene)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("unknown scene '{}'", scene))?;
+    let fade_seconds = match fade {
+        Some(duration) => interpreter
+            .duration_secs(duration)
+            .ok_or_else(|| anyhow::anyhow!("invalid crossfade length for scene '{}'", scene))?,
+        None => 0.0,
+    };
+
+    let start = interpreter.cursor_time;
+    let mut end = start;
+    for member in &members {
+        interpreter.cursor_time = start;
+        handle_call(interpreter, member, &[])?;
+        end = end.max(interpreter.cursor_time);
+    }
+    interpreter.cursor_time = end;
+    interpreter.special_vars.update_time(end);
+    interpreter.scene = Some(SceneCue::new(scene, fade_seconds));
+    Ok(())
+}
+
+/// Every automatable parameter as it stands at the cursor
+pub fn capture_snapshot(interpreter: &AudioInterpreter) -> ParamSnapshot {
+    ParamSnapshot::capture(
+        &interpreter.events.synths,
+        &interpreter.routing.strips,
+        &interpreter.automation_registry,
+        interpreter.cursor_time,
+    )
+}
+
+/// `snapshot morph from -> to [over duration] [curve name]`: automate every parameter of
+/// the two snapshots from the cursor on; the cursor itself does not move
+pub fn handle_snapshot_morph(
+    interpreter: &mut AudioInterpreter,
+    from: &str,
+    to: &str,
+    length: Option<&DurationValue>,
+    curve: Option<&str>,
+) -> Result<()> {
+    use crate::engine::audio::automation::SegmentMode;
+    use crate::engine::audio::snapshot::{morph_segments, parse_morph_curve};
+
+    let snapshot = |name: &str| {
+        interpreter
+            .snapshots
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("unknown snapshot '{}'", name))
+    };
+    let (from_snapshot, to_snapshot) = (snapshot(from)?, snapshot(to)?);
+    let seconds = match length {
+        Some(duration) => interpreter.duration_secs(duration).ok_or_else(|| {
+            anyhow::anyhow!("invalid morph length for snapshot '{}' -> '{}'", from, to)
+        })?,
+        None => 0.0,
+    };
+    let mode = match curve {
+        Some(curve) => parse_morph_curve(curve)?,
+        None => SegmentMode::Linear,
+    };
+
+    let live = capture_snapshot(interpreter);
+    let segments = morph_segments(
+        from_snapshot,
+        to_snapshot,
+        &live,
+        interpreter.cursor_time,
+        seconds,
+        mode,
+    );
+    for (owner, segment) in segments {
+        interpreter.automation_registry.add_param(&owner, segment);
+    }
+    Ok(())
+}
+
+/// `automate lead.cutoff: <expression>`: compile once, then drive the parameter from here on
+pub fn handle_automate_formula(
+    interpreter: &mut AudioInterpreter,
+    target: &str,
+    param: &str,
+    source: &str,
+) -> Result<()> {
+    use crate::engine::audio::automation::FormulaParam;
+    use crate::engine::audio::evaluator::formula::Formula;
+
+    let variables = &interpreter.variables;
+    let formula = Formula::compile(source, &|name| match variables.get(name) {
+        Some(Value::Number(n)) => Some(*n),
+        _ => None,
+    })
+    .map_err(|e| anyhow::anyhow!("automate {}.{}: {}", target, param, e))?;
+
+    interpreter.automation_registry.add_formula(
+        target,
+        FormulaParam {
+            param_name: param.to_string(),
+            formula: std::sync::Arc::new(formula),
+            start_time: interpreter.cursor_time,
+            context: interpreter.special_vars.clone(),
+        },
+    );
+    Ok(())
+}
+
+/// Run a group body under its header's `with { ... }` block and tag its events. Muted
+/// groups still advance the cursor but their events are dropped; soloed ones are
+/// recorded so `collect_all_events` can silence the rest.
+pub(super) fn run_group(
+    interpreter: &mut AudioInterpreter,
+    name: &str,
+    body: &[Statement],
+) -> Result<()> {
+    let start = interpreter.events.events.len();
+    let preset = interpreter.group_presets.get(name).cloned();
+    let scoped = preset.is_some();
+    if let Some(block) = preset {
+        interpreter.modifier_stack.push(block);
+    }
+    interpreter.group_stack.push(name.to_string());
+    let result = super::collector::collect_events(interpreter, body);
+    let path = interpreter.group_stack.join("/");
+    interpreter.group_stack.pop();
+    if scoped {
+        interpreter.modifier_stack.pop();
+    }
+    result?;
+    if interpreter.solo_mute.is_muted(&path) {
+        interpreter.events.keep_duck_keys(start, &path);
+        interpreter.events.discard_from(start);
+        return Ok(());
+    }
+    interpreter.events.tag_group(start, name);
+    let end = interpreter.events.events.len();
+    if end > start && interpreter.solo_mute.solo.contains(name) {
+        interpreter.events.solo_spans.push(start..end);
+    }
+    Ok(())
+}
+
 /// Execute a call as an expression and return its resulting Value.
 /// This is similar to `handle_call` but returns the captured `return` value
 /// from a function when present. Groups and patterns return `Value::Null`.
@@ -432,6 +656,14 @@ pub fn call_function(
     name: &str,
     args: &[Value],
 ) -> Result<Value> {
+    if let Some(step) = interpreter
+        .pattern_chains
+        .get_mut(name)
+        .map(PatternChain::advance)
+    {
+        return call_function(interpreter, &step, args);
+    }
+
     // If it's a stored variable that is a function statement, execute and capture return
     if let Some(var_val) = interpreter.variables.get(name).cloned() {
         if let Value::Statement(stmt_box) = var_val {
@@ -486,7 +718,8 @@ pub fn call_function(
                 if let Some(tgt) = target.as_ref() {
                     let (pattern_str, options) = interpreter.extract_pattern_data(&stmt_box.value);
                     if let Some(pat) = pattern_str {
-                        interpreter.execute_pattern(tgt.as_str(), &pat, options)?;
+                        let accent = pattern_accent(&stmt_box.value);
+                        interpreter.execute_pattern(tgt.as_str(), &pat, options, accent)?;
                         return Ok(Value::Null);
                     }
                 }
@@ -496,10 +729,38 @@ pub fn call_function(
 
     // If it's a group call, execute and return null
     if let Some(body) = interpreter.groups.get(name).cloned() {
-        super::collector::collect_events(interpreter, &body)?;
+        run_group(interpreter, name, &body)?;
         return Ok(Value::Null);
     }
 
+    // Unit helpers (ms, beats, hz, semitones), resolved against the current tempo
+    if let Some(result) = crate::engine::functions::units::call_unit(name, args, interpreter.bpm) {
+        return result;
+    }
+
+    // Built-in note collection transforms (transpose, retrograde, invert, negativeHarmony)
+    if let Some(result) = crate::engine::functions::theory::call_transform(name, args) {
+        return result;
+    }
+
+    // Built-in map operations, either as functions (`keys(settings)`) or as methods
+    // on a map value (`settings.keys()`, where the receiver becomes the first argument)
+    if let Some(result) = crate::engine::functions::maps::call_map_method(name, args) {
+        return result;
+    }
+    if let Some((receiver, method)) = name.rsplit_once('.')
+        && crate::engine::functions::maps::is_map_method(method)
+    {
+        let receiver = interpreter.resolve_value(&Value::Identifier(receiver.to_string()))?;
+        let mut method_args = Vec::with_capacity(args.len() + 1);
+        method_args.push(receiver);
+        method_args.extend_from_slice(args);
+        if let Some(result) = crate::engine::functions::maps::call_map_method(method, &method_args)
+        {
+            return result;
+        }
+    }
+
     println!(
         "⚠️  Warning: Group, pattern or function '{}' not found",
         name
@@ -678,6 +939,70 @@ pub fn execute_event_handlers(interpreter: &mut AudioInterpreter, event_name: &s
     Ok(())
 }
 
+/// Run the `on beat` / `on bar` handlers once per beat of the whole render.
+///
+/// Beats follow the tempo and meter map like the metronome does (eighths in 6/8) and each
+/// one is snapped to the sample frame it is reached on. The length is taken from the events
+/// collected so far, so handler output never extends the grid it is driven by; with nothing
+/// else to play, one bar is covered.
+pub fn emit_beat_events(interpreter: &mut AudioInterpreter) -> Result<()> {
+    use crate::engine::audio::click::click_times;
+
+    if interpreter
+        .event_registry
+        .get_handlers_matching("beat")
+        .is_empty()
+        && interpreter
+            .event_registry
+            .get_handlers_matching("bar")
+            .is_empty()
+    {
+        return Ok(());
+    }
+    let length = match interpreter.calculate_total_duration() {
+        length if length > 0.0 => length,
+        _ => interpreter.tempo_map.seconds_at(4.0, interpreter.bpm),
+    };
+    let rate = interpreter.sample_rate.max(1) as f64;
+
+    let prev_cursor = interpreter.cursor_time;
+    let prev_vars = (
+        interpreter.special_vars.current_time,
+        interpreter.special_vars.current_beat,
+        interpreter.special_vars.current_bar,
+    );
+    let mut bar = -1.0f32;
+    for (index, click) in click_times(&interpreter.tempo_map, interpreter.bpm, length)
+        .into_iter()
+        .enumerate()
+    {
+        let time = ((click.time as f64 * rate).round() / rate) as f32;
+        if click.downbeat {
+            bar += 1.0;
+        }
+        interpreter.cursor_time = time;
+        interpreter.special_vars.update_time(time);
+        // Count on the grid itself so `on beat 2` / `on bar 4` hold across tempo changes
+        interpreter.special_vars.current_beat = index as f32;
+        interpreter.special_vars.current_bar = bar.max(0.0);
+
+        interpreter.execute_event_handlers("beat")?;
+        if click.downbeat {
+            // Beat handlers may have moved the cursor; bar handlers start on the beat too
+            interpreter.cursor_time = time;
+            interpreter.execute_event_handlers("bar")?;
+        }
+    }
+
+    interpreter.cursor_time = prev_cursor;
+    (
+        interpreter.special_vars.current_time,
+        interpreter.special_vars.current_beat,
+        interpreter.special_vars.current_bar,
+    ) = prev_vars;
+    Ok(())
+}
+
 pub fn handle_assign(
     interpreter: &mut AudioInterpreter,
     target: &str,
@@ -764,18 +1089,99 @@ pub fn handle_assign(
     Ok(())
 }
 
+/// Flatten a synth map's `extends` chain: parents are looked up among the defined
+/// variables (dotted names reach into imported modules), merged depth-first and then
+/// overridden by the child. `chain` holds the definitions already being resolved.
+pub fn resolve_synth_parents(
+    interpreter: &AudioInterpreter,
+    map: &HashMap<String, Value>,
+    chain: &mut Vec<String>,
+) -> Result<HashMap<String, Value>> {
+    let Some(Value::String(parent)) = map.get("_extends") else {
+        return Ok(map.clone());
+    };
+    if chain.contains(parent) {
+        chain.push(parent.clone());
+        return Err(anyhow::anyhow!(
+            "Synth inheritance cycle: {}",
+            chain.join(" -> ")
+        ));
+    }
+
+    let mut segments = parent.split('.');
+    let root = segments.next().unwrap_or_default();
+    let mut found = interpreter.variables.get(root);
+    for segment in segments {
+        found = match found {
+            Some(Value::Map(map)) => map.get(segment),
+            _ => None,
+        };
+    }
+    let parent_map = match found {
+        Some(Value::Map(parent_map)) if parent_map.contains_key("_plugin_author") => {
+            // An installed plugin export: inherit it the way `synth alias.export` would
+            let mut reference = HashMap::new();
+            reference.insert("_plugin_ref".to_string(), Value::String(parent.clone()));
+            reference
+        }
+        Some(Value::Map(parent_map)) => {
+            chain.push(parent.clone());
+            let resolved = resolve_synth_parents(interpreter, parent_map, chain)?;
+            chain.pop();
+            resolved
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Synth '{}' extends unknown definition '{}'",
+                chain.last().map(String::as_str).unwrap_or("<anonymous>"),
+                parent
+            ));
+        }
+    };
+
+    let mut child = map.clone();
+    // Every parsed definition carries the `synth` placeholder type; only an explicit type
+    // replaces the parent's
+    if matches!(child.get("type"), Some(Value::String(t)) if t == "synth") {
+        child.remove("type");
+    }
+    Ok(merge_synth_maps(parent_map, child))
+}
+
+/// Deep-merge `overrides` into `base`: nested maps (lfo, adsr, ...) merge key by key,
+/// everything else is replaced
+fn merge_synth_maps(
+    mut base: HashMap<String, Value>,
+    overrides: HashMap<String, Value>,
+) -> HashMap<String, Value> {
+    for (key, value) in overrides {
+        let merged = match (base.remove(&key), value) {
+            (Some(Value::Map(inner)), Value::Map(over)) => {
+                Value::Map(merge_synth_maps(inner, over))
+            }
+            (_, value) => value,
+        };
+        base.insert(key, merged);
+    }
+    base
+}
+
 pub fn extract_synth_def_from_map(
-    _interpreter: &AudioInterpreter,
+    interpreter: &AudioInterpreter,
     map: &HashMap<String, Value>,
 ) -> Result<crate::engine::audio::events::SynthDefinition> {
     use crate::engine::audio::events::extract_filters;
     use crate::engine::audio::lfo::{LfoParams, LfoRate, LfoTarget, LfoWaveform};
 
+    let resolved = resolve_synth_parents(interpreter, map, &mut Vec::new())?;
+    let map = &resolved;
+
     let waveform = crate::engine::audio::events::extract_string(map, "waveform", "sine");
     let attack = crate::engine::audio::events::extract_number(map, "attack", 0.01);
     let decay = crate::engine::audio::events::extract_number(map, "decay", 0.1);
     let sustain = crate::engine::audio::events::extract_number(map, "sustain", 0.7);
     let release = crate::engine::audio::events::extract_number(map, "release", 0.2);
+    let curves = crate::engine::audio::events::extract_envelope_curves(map);
 
     // Accept both String and Identifier for type (and synth_type alias)
     let synth_type = if let Some(v) = map.get("type") {
@@ -821,6 +1227,8 @@ pub fn extract_synth_def_from_map(
     } else {
         None
     };
+    // Synth types the engine leaves out play as the plain oscillator
+    let synth_type = synth_type.filter(|name| interpreter.synth_types.allows(name));
 
     let filters = if let Some(Value::Array(filters_arr)) = map.get("filters") {
         extract_filters(filters_arr)
@@ -906,6 +1314,10 @@ pub fn extract_synth_def_from_map(
             "decay",
             "sustain",
             "release",
+            "curve",
+            "attack_curve",
+            "decay_curve",
+            "release_curve",
             "type",
             "filters",
             "plugin_author",
@@ -927,6 +1339,7 @@ pub fn extract_synth_def_from_map(
             }
         }
     }
+    crate::engine::audio::synth::pitch::extract_pitch_options(map, &mut options);
 
     Ok(crate::engine::audio::events::SynthDefinition {
         waveform,
@@ -934,6 +1347,7 @@ pub fn extract_synth_def_from_map(
         decay,
         sustain,
         release,
+        curves,
         synth_type,
         filters,
         options,
@@ -941,10 +1355,16 @@ pub fn extract_synth_def_from_map(
         plugin_name,
         plugin_export,
         lfo,
+        articulations: crate::engine::audio::articulation::extract_articulations(map),
     })
 }
 
-pub fn handle_load(interpreter: &mut AudioInterpreter, source: &str, alias: &str) -> Result<()> {
+pub fn handle_load(
+    interpreter: &mut AudioInterpreter,
+    source: &str,
+    alias: &str,
+    options: &Value,
+) -> Result<()> {
     use std::path::Path;
 
     let path = Path::new(source);
@@ -958,6 +1378,20 @@ pub fn handle_load(interpreter: &mut AudioInterpreter, source: &str, alias: &str
             "mid" | "midi" => {
                 use crate::engine::audio::midi::load_midi_file;
                 let midi_data = load_midi_file(path)?;
+                // `with { tempo_map: true }` makes the file's tempo/meter changes the project's
+                if let Value::Map(opts) = options
+                    && match opts.get("tempo_map") {
+                        Some(Value::Boolean(flag)) => *flag,
+                        Some(Value::String(flag)) => flag == "true",
+                        _ => false,
+                    }
+                    && let Value::Map(midi_map) = &midi_data
+                    && let Some(tempo_map) = midi_map
+                        .get("tempo_map")
+                        .and_then(crate::engine::audio::tempo::TempoMap::from_value)
+                {
+                    interpreter.tempo_map = tempo_map;
+                }
                 interpreter.variables.insert(alias.to_string(), midi_data);
                 // MIDI file loaded (silent)
                 Ok(())
@@ -995,6 +1429,33 @@ pub fn handle_load(interpreter: &mut AudioInterpreter, source: &str, alias: &str
     }
 }
 
+/// Register a `macro` control. Its value is the `--set` override of the same name (the
+/// live session updates it), otherwise 1.0, and is readable as a variable by the script.
+pub fn handle_macro(
+    interpreter: &mut AudioInterpreter,
+    name: &str,
+    targets: &[String],
+) -> Result<()> {
+    use crate::engine::audio::macros::MacroTarget;
+
+    let value = match interpreter.variable_overrides.get(name).cloned() {
+        Some(value) => match interpreter.resolve_value(&value)? {
+            Value::Number(value) => value,
+            other => anyhow::bail!("macro '{}' must be set to a number, got {:?}", name, other),
+        },
+        None => 1.0,
+    };
+    let targets = targets
+        .iter()
+        .map(|target| MacroTarget::parse(target))
+        .collect::<Result<Vec<_>>>()?;
+    interpreter.macros.define(name, value, targets);
+    interpreter
+        .variables
+        .insert(name.to_string(), Value::Number(value.clamp(0.0, 1.0)));
+    Ok(())
+}
+
 pub fn handle_bind(
     interpreter: &mut AudioInterpreter,
     source: &str,
@@ -1200,8 +1661,24 @@ pub fn handle_bind(
                         1.0
                     };
 
-                    let start_time_s = (time / 1000.0) * factor;
-                    let duration_s = (duration_ms / 1000.0) * factor;
+                    let (start_time_s, duration_s) = if interpreter.tempo_map.has_flat_tempo() {
+                        ((time / 1000.0) * factor, (duration_ms / 1000.0) * factor)
+                    } else {
+                        // Follow the imported tempo map instead of a flat rescale
+                        let beat = crate::engine::audio::events::extract_number(
+                            note_map,
+                            "beat",
+                            time / 1000.0 * midi_bpm / 60.0,
+                        );
+                        let beats = crate::engine::audio::events::extract_number(
+                            note_map,
+                            "duration_beats",
+                            duration_ms / 1000.0 * midi_bpm / 60.0,
+                        );
+                        let start = interpreter.tempo_map.seconds_at(beat, interp_bpm);
+                        let end = interpreter.tempo_map.seconds_at(beat + beats, interp_bpm);
+                        (start, end - start)
+                    };
 
                     let event = AudioEvent::Note {
                         midi: note,
@@ -1230,6 +1707,11 @@ pub fn handle_bind(
                 }
             }
 
+            // Expose the imported harmony to scripts through $midi.chord.*
+            interpreter.special_vars.midi_chords = std::sync::Arc::new(
+                crate::engine::audio::midi::detect_chords(notes_array, midi_bpm, 4.0),
+            );
+
             // Bound notes from source to target
         }
     }
@@ -1323,6 +1805,8 @@ pub fn handle_bank(
     let target_alias = alias
         .clone()
         .unwrap_or_else(|| name.split('.').last().unwrap_or(name).to_string());
+    let name = interpreter.banks.remapped(&target_alias, name).to_string();
+    let name = name.as_str();
 
     if let Some(existing_value) = interpreter.variables.get(name) {
         interpreter
@@ -1332,17 +1816,27 @@ pub fn handle_bank(
         #[cfg(feature = "wasm")]
         {
             use crate::web::registry::banks::REGISTERED_BANKS;
-            REGISTERED_BANKS.with(|banks| {
+            let found = REGISTERED_BANKS.with(|banks| {
+                let mut found = false;
                 for bank in banks.borrow().iter() {
                     if bank.full_name == *name {
                         if let Some(Value::Map(bank_map)) = interpreter.variables.get(&bank.alias) {
                             interpreter
                                 .variables
                                 .insert(target_alias.clone(), Value::Map(bank_map.clone()));
+                            found = true;
                         }
                     }
                 }
+                found
             });
+            if !found {
+                crate::web::registry::debug::push_runtime_warning(
+                    "unresolved_bank",
+                    format!("Bank '{}' is not registered", name),
+                    interpreter.current_statement_location,
+                );
+            }
         }
 
         #[cfg(not(feature = "wasm"))]
@@ -1354,7 +1848,17 @@ pub fn handle_bank(
                     &current_dir,
                     &current_dir,
                 ) {
-                    Ok(_) => {
+                    Ok(_bank) => {
+                        // Let the renderer load this bank's samples (builds do not scan
+                        // the standard bank locations like `play` does)
+                        #[cfg(feature = "cli")]
+                        if let Err(e) = crate::engine::audio::samples::ensure_bank_registered(
+                            name,
+                            _bank.root_dir(),
+                        ) {
+                            crate::tools::logger::Logger::new()
+                                .warn(format!("Failed to load samples of bank '{}': {}", name, e));
+                        }
                         let mut bank_map = HashMap::new();
                         bank_map.insert("_name".to_string(), Value::String(name.to_string()));
                         bank_map.insert("_alias".to_string(), Value::String(target_alias.clone()));
@@ -1362,8 +1866,10 @@ pub fn handle_bank(
                             .variables
                             .insert(target_alias.clone(), Value::Map(bank_map));
                     }
-                    Err(e) => {
-                        eprintln!("⚠️ Failed to register bank '{}': {}", name, e);
+                    Err(_e) => {
+                        #[cfg(feature = "cli")]
+                        crate::tools::logger::Logger::new()
+                            .warn(format!("Failed to register bank '{}': {}", name, _e));
                         let mut bank_map = HashMap::new();
                         bank_map.insert("_name".to_string(), Value::String(name.to_string()));
                         bank_map.insert("_alias".to_string(), Value::String(target_alias.clone()));
@@ -1390,10 +1896,202 @@ pub fn handle_bank(
     Ok(())
 }
 
+/// Evaluate `chance` / `every` trigger modifiers stored in the statement value.
+///
+/// `every N` fires on loop passes 0, N, 2N, ... of the innermost loop. `chance` rolls a
+/// deterministic value derived from the interpreter seed, the statement position, the loop
+/// pass and the cursor time, so the same source always renders the same hits.
+pub fn trigger_passes_modifiers(interpreter: &AudioInterpreter, stmt: &Statement) -> bool {
+    let Value::Map(modifiers) = &stmt.value else {
+        return true;
+    };
+
+    if let Some(Value::Number(every)) = modifiers.get("every") {
+        let every = (*every as usize).max(1);
+        if !interpreter.loop_pass.is_multiple_of(every) {
+            return false;
+        }
+    }
+
+    if let Some(Value::Number(chance)) = modifiers.get("chance") {
+        let roll = seeded_unit(&[
+            interpreter.trigger_seed,
+            stmt.line as u64,
+            stmt.column as u64,
+            interpreter.loop_pass as u64,
+            interpreter.cursor_time.to_bits() as u64,
+        ]);
+        if roll >= *chance {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Sample region requested by trigger options (`.loopBreak 4 bars start: 0.25 end: 0.75
+/// loop: true sync: 2 bars`), with `sync` and the loop length (the trigger duration)
+/// resolved at the current tempo. A looped trigger without a duration repeats until the
+/// render ends.
+/// `sync: auto` depends on the sample, so it is left to `tag_sample_region`.
+pub fn trigger_region(
+    interpreter: &AudioInterpreter,
+    stmt: &Statement,
+) -> Option<crate::engine::audio::events::SampleRegion> {
+    region_options(interpreter, stmt).map(|(region, _)| region)
+}
+
+/// The trigger's region, and whether it syncs to the tempo detected in the sample
+fn region_options(
+    interpreter: &AudioInterpreter,
+    stmt: &Statement,
+) -> Option<(crate::engine::audio::events::SampleRegion, bool)> {
+    let StatementKind::Trigger {
+        entity, duration, ..
+    } = &stmt.kind
+    else {
+        return None;
+    };
+    let modifiers = match &stmt.value {
+        Value::Map(modifiers)
+            if ["start", "end", "loop", "sync"]
+                .iter()
+                .any(|key| modifiers.contains_key(*key)) =>
+        {
+            modifiers
+        }
+        // A trigger stored in a variable (`let brk = .kit.break start: 0.5`) keeps its region
+        _ => {
+            return match interpreter.variables.get(entity.trim_start_matches('.')) {
+                Some(Value::Statement(inner)) if inner.as_ref() != stmt => {
+                    region_options(interpreter, inner)
+                }
+                _ => None,
+            };
+        }
+    };
+
+    let bound = |key: &str, default: f32| match modifiers.get(key) {
+        Some(Value::Number(fraction)) => *fraction,
+        _ => default,
+    };
+    let sync = match modifiers.get("sync") {
+        Some(Value::Duration(length)) => length.to_seconds(interpreter.bpm),
+        _ => None,
+    };
+    let auto = matches!(modifiers.get("sync"), Some(Value::Identifier(mode)) if mode == "auto");
+    let looped = matches!(modifiers.get("loop"), Some(Value::Boolean(true)));
+    let loop_length = looped
+        .then(|| duration.to_seconds(interpreter.bpm))
+        .flatten();
+    let region = crate::engine::audio::events::SampleRegion {
+        start: bound("start", 0.0),
+        end: bound("end", 1.0),
+        sync,
+        loop_length,
+        loop_to_end: looped && loop_length.is_none(),
+    };
+    Some((region, auto))
+}
+
+/// Attach the trigger's sample region to the sample events it scheduled. With `sync: auto`
+/// each region is stretched from the sample's own tempo to the current one.
+pub fn tag_sample_region(interpreter: &mut AudioInterpreter, stmt: &Statement, first_event: usize) {
+    let Some((trigger_region, auto)) = region_options(interpreter, stmt) else {
+        return;
+    };
+    let bpm = interpreter.bpm;
+    for event in interpreter.events.events.iter_mut().skip(first_event) {
+        if let crate::engine::audio::events::AudioEvent::Sample { uri, region, .. } = event {
+            let mut tagged = trigger_region;
+            if auto {
+                tagged.sync = auto_sync_seconds(uri, &tagged, bpm);
+            }
+            *region = Some(tagged);
+        }
+    }
+}
+
+/// Seconds `region` of the loop at `uri` lasts once played at `bpm` instead of its own
+/// tempo; `None` (played as recorded) when the sample has no detectable tempo
+#[cfg(feature = "cli")]
+fn auto_sync_seconds(
+    uri: &str,
+    region: &crate::engine::audio::events::SampleRegion,
+    bpm: f32,
+) -> Option<f32> {
+    let sample_loop = crate::engine::audio::samples::sample_loop(uri)?;
+    let played = sample_loop.seconds * (region.end - region.start).abs().min(1.0);
+    Some(played * sample_loop.bpm / bpm.max(1.0))
+}
+
+/// Loops are analyzed by the native sample registry only
+#[cfg(not(feature = "cli"))]
+fn auto_sync_seconds(
+    _uri: &str,
+    _region: &crate::engine::audio::events::SampleRegion,
+    _bpm: f32,
+) -> Option<f32> {
+    None
+}
+
+/// Roll requested by a trigger (`.hat roll 1/8 x4 pitch: 12 gain: 0.3`), with the hit
+/// spacing resolved at the current tempo
+pub fn trigger_roll(
+    interpreter: &AudioInterpreter,
+    stmt: &Statement,
+) -> Option<crate::engine::audio::events::SampleRoll> {
+    let Value::Map(modifiers) = &stmt.value else {
+        return None;
+    };
+    let Some(Value::Map(roll)) = modifiers.get("roll") else {
+        return None;
+    };
+    let number = |key: &str, default: f32| match roll.get(key) {
+        Some(Value::Number(n)) => *n,
+        _ => default,
+    };
+    let interval = match roll.get("interval") {
+        Some(Value::Duration(duration)) => interpreter.duration_secs(duration)?,
+        _ => return None,
+    };
+    use crate::language::syntax::parser::driver::trigger::MAX_ROLL_HITS;
+    Some(crate::engine::audio::events::SampleRoll {
+        interval: interval.max(0.0),
+        count: number("count", 1.0).clamp(1.0, MAX_ROLL_HITS as f32) as usize,
+        pitch: number("pitch", 0.0),
+        gain: number("gain", 1.0).max(0.0),
+    })
+}
+
+/// MIDI note requested by a pitched trigger (`.bank.pluck C4`), if any
+pub fn trigger_note(stmt: &Statement) -> Option<u8> {
+    match &stmt.value {
+        Value::Map(modifiers) => match modifiers.get("note") {
+            Some(Value::Number(midi)) => Some(midi.clamp(0.0, 127.0) as u8),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Hash the given parts into a stable value in `[0, 1)` (splitmix64 mixing).
+fn seeded_unit(parts: &[u64]) -> f32 {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    for part in parts {
+        state ^= part.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        state ^= state >> 31;
+    }
+    (state >> 40) as f32 / (1u64 << 24) as f32
+}
+
 pub fn handle_trigger(
     interpreter: &mut AudioInterpreter,
     entity: &str,
     effects: Option<&crate::language::syntax::ast::Value>,
+    note: Option<u8>,
 ) -> Result<()> {
     let resolved_entity = if entity.starts_with('.') {
         &entity[1..]
@@ -1415,7 +2113,8 @@ pub fn handle_trigger(
                 if inner_entity != resolved_entity {
                     // Prefer stored effects when present, otherwise fall back to effects passed in
                     let chosen_effects = stored_effects.as_ref().or(effects);
-                    return handle_trigger(interpreter, inner_entity, chosen_effects);
+                    let chosen_note = note.or_else(|| trigger_note(&stmt_box));
+                    return handle_trigger(interpreter, inner_entity, chosen_effects, chosen_note);
                 } else {
                     return Ok(());
                 }
@@ -1423,6 +2122,21 @@ pub fn handle_trigger(
         }
     }
 
+    // Parameters from enclosing `call ... with { ... }` blocks
+    let preset = interpreter.trigger_preset();
+    let note = note.or(preset.note);
+    let velocity =
+        preset.velocity * interpreter.accent_gain(preset.accent.as_ref(), interpreter.cursor_time);
+    let start_time = interpreter.cursor_time + interpreter.swing_offset(preset.swing);
+    let first_event = interpreter.events.events.len();
+    let effects = match effects {
+        Some(fx) => {
+            let fx = interpreter.resolve_value(fx)?;
+            Some(interpreter.tempo_synced_effects(&fx))
+        }
+        None => None,
+    };
+
     if resolved_entity.contains('.') {
         let parts: Vec<&str> = resolved_entity.split('.').collect();
         if parts.len() == 2 {
@@ -1432,11 +2146,12 @@ pub fn handle_trigger(
                 if let Some(Value::String(sample_uri)) = map.get(property) {
                     let uri = sample_uri.trim_matches('"').trim_matches('\'');
                     // scheduling sample at current cursor_time
-                    interpreter.events.add_sample_event_with_effects(
+                    interpreter.events.add_pitched_sample_event(
                         uri,
-                        interpreter.cursor_time,
-                        1.0,
-                        effects.cloned(),
+                        start_time,
+                        velocity,
+                        effects.clone(),
+                        note,
                     );
                     let beat_duration = interpreter.beat_duration();
                     interpreter.cursor_time += beat_duration;
@@ -1447,11 +2162,12 @@ pub fn handle_trigger(
                         let resolved_uri = interpreter.resolve_sample_uri(resolved_entity);
                         if resolved_uri != resolved_entity {
                             // scheduling resolved sample
-                            interpreter.events.add_sample_event_with_effects(
+                            interpreter.events.add_pitched_sample_event(
                                 &resolved_uri,
-                                interpreter.cursor_time,
-                                1.0,
-                                effects.cloned(),
+                                start_time,
+                                velocity,
+                                effects.clone(),
+                                note,
                             );
                             let beat_duration = interpreter.beat_duration();
                             interpreter.cursor_time += beat_duration;
@@ -1460,11 +2176,12 @@ pub fn handle_trigger(
                         {
                             if let Some(path_str) = pathbuf.to_str() {
                                 // scheduling sample via bank path
-                                interpreter.events.add_sample_event_with_effects(
+                                interpreter.events.add_pitched_sample_event(
                                     path_str,
-                                    interpreter.cursor_time,
-                                    1.0,
-                                    effects.cloned(),
+                                    start_time,
+                                    velocity,
+                                    effects.clone(),
+                                    note,
                                 );
                                 let beat_duration = interpreter.beat_duration();
                                 interpreter.cursor_time += beat_duration;
@@ -1478,29 +2195,107 @@ pub fn handle_trigger(
                             // no path found in BankRegistry
                         }
                     }
+                    #[cfg(feature = "wasm")]
+                    crate::web::registry::debug::push_runtime_warning(
+                        "missing_trigger",
+                        format!("Trigger '{}' not found in bank '{}'", property, var_name),
+                        interpreter.current_statement_location,
+                    );
                 }
+            } else {
+                #[cfg(feature = "wasm")]
+                crate::web::registry::debug::push_runtime_warning(
+                    "missing_trigger",
+                    format!(
+                        "'{}' is not a bank or map (in '{}')",
+                        var_name, resolved_entity
+                    ),
+                    interpreter.current_statement_location,
+                );
             }
         }
     } else {
         if let Some(Value::String(sample_uri)) = interpreter.variables.get(resolved_entity) {
             let uri = sample_uri.trim_matches('"').trim_matches('\'');
-            interpreter.events.add_sample_event_with_effects(
+            interpreter.events.add_pitched_sample_event(
                 uri,
-                interpreter.cursor_time,
-                1.0,
-                effects.cloned(),
+                start_time,
+                velocity,
+                effects.clone(),
+                note,
+            );
+            let beat_duration = interpreter.beat_duration();
+            interpreter.cursor_time += beat_duration;
+        } else if let Some(uri) = resolve_implicit_trigger(interpreter, resolved_entity) {
+            interpreter.events.add_pitched_sample_event(
+                &uri,
+                start_time,
+                velocity,
+                effects.clone(),
+                note,
             );
             let beat_duration = interpreter.beat_duration();
             interpreter.cursor_time += beat_duration;
         }
     }
 
+    if interpreter.events.events.len() == first_event {
+        interpreter
+            .events
+            .unresolved_triggers
+            .insert(resolved_entity.to_string());
+    }
+    super::extractor::tag_sample_automation(interpreter, resolved_entity, first_event);
+
     // Note: do not call interpreter.render_audio() here - rendering is handled by the build pipeline.
     // Trigger queued for rendering (events collected)
 
     Ok(())
 }
 
+/// Apply a note collection transform chain to an array variable in place.
+/// Supports: `melody -> retrograde() -> transpose(12)`
+pub fn handle_array_transform(
+    interpreter: &mut AudioInterpreter,
+    target: &str,
+    method: &str,
+    args: &[Value],
+    chain: Option<&[Value]>,
+) -> Result<()> {
+    let mut current = interpreter
+        .variables
+        .get(target)
+        .cloned()
+        .unwrap_or(Value::Array(Vec::new()));
+
+    let mut calls: Vec<(String, Vec<Value>)> = vec![(method.to_string(), args.to_vec())];
+    for call in chain.unwrap_or(&[]) {
+        let Value::Map(call_map) = call else {
+            continue;
+        };
+        if let Some(Value::String(name)) = call_map.get("method") {
+            let call_args = match call_map.get("args") {
+                Some(Value::Array(a)) => a.clone(),
+                _ => Vec::new(),
+            };
+            calls.push((name.clone(), call_args));
+        }
+    }
+
+    for (name, call_args) in calls {
+        let mut resolved = vec![current];
+        for arg in &call_args {
+            resolved.push(interpreter.resolve_value(arg)?);
+        }
+        current = crate::engine::functions::theory::call_transform(&name, &resolved).ok_or_else(
+            || anyhow::anyhow!("'{}' cannot be applied to note array '{}'", name, target),
+        )??;
+    }
+
+    interpreter.variables.insert(target.to_string(), current);
+    Ok(())
+}
+
 pub fn extract_pattern_data(
     _interpreter: &AudioInterpreter,
     value: &Value,
@@ -1536,18 +2331,33 @@ pub fn extract_pattern_data(
     }
 }
 
+/// `accent` option of a stored pattern (`pattern p with t { accent: strong } = "x..."`)
+pub fn pattern_accent(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Map(map) => map.get("accent"),
+        _ => None,
+    }
+}
+
 pub fn execute_pattern(
     interpreter: &mut AudioInterpreter,
     target: &str,
     pattern: &str,
     options: Option<HashMap<String, f32>>,
+    accent: Option<&Value>,
 ) -> Result<()> {
-    use crate::engine::audio::events::AudioEvent;
+    use crate::engine::audio::events::{AudioEvent, SampleRoll};
 
+    let preset = interpreter.trigger_preset();
+    let accent = match accent {
+        Some(value) => interpreter.accent_map(value),
+        None => preset.accent.clone(),
+    };
     let swing = options
         .as_ref()
         .and_then(|o| o.get("swing").copied())
-        .unwrap_or(0.0);
+        .unwrap_or(preset.swing);
+    #[cfg(any(feature = "cli", feature = "wasm"))]
     let humanize = options
         .as_ref()
         .and_then(|o| o.get("humanize").copied())
@@ -1555,8 +2365,18 @@ pub fn execute_pattern(
     let velocity_mult = options
         .as_ref()
         .and_then(|o| o.get("velocity").copied())
-        .unwrap_or(1.0);
+        .unwrap_or(1.0)
+        * preset.velocity;
     let tempo_override = options.as_ref().and_then(|o| o.get("tempo").copied());
+    // Pitch and gain ramps across the hits of digit steps
+    let roll_pitch = options
+        .as_ref()
+        .and_then(|o| o.get("roll_pitch").copied())
+        .unwrap_or(0.0);
+    let roll_gain = options
+        .as_ref()
+        .and_then(|o| o.get("roll_gain").copied())
+        .unwrap_or(1.0);
 
     let effective_bpm = tempo_override.unwrap_or(interpreter.bpm);
 
@@ -1570,9 +2390,16 @@ pub fn execute_pattern(
 
     let bar_duration = (60.0 / effective_bpm) * 4.0;
     let step_duration = bar_duration / step_count;
+    let first_event = interpreter.events.events.len();
 
     for (i, &ch) in pattern_chars.iter().enumerate() {
-        if ch == 'x' || ch == 'X' {
+        // A digit step plays that many evenly spaced hits inside the step (`"x-3-"`)
+        let hits = match ch {
+            'x' | 'X' => 1,
+            '1'..='9' => ch as usize - '0' as usize,
+            _ => 0,
+        };
+        if hits > 0 {
             let mut time = interpreter.cursor_time + (i as f32 * step_duration);
             if swing > 0.0 && i % 2 == 1 {
                 time += step_duration * swing;
@@ -1580,26 +2407,91 @@ pub fn execute_pattern(
 
             #[cfg(any(feature = "cli", feature = "wasm"))]
             if humanize > 0.0 {
-                use rand::Rng;
-                let mut rng = rand::thread_rng();
-                let offset = rng.gen_range(-humanize..humanize);
+                let offset = (interpreter.special_vars.random.next_f32() * 2.0 - 1.0) * humanize;
                 time += offset;
             }
 
             let event = AudioEvent::Sample {
                 uri: resolved_uri.clone(),
                 start_time: time,
-                velocity: velocity_mult, // Already in 0-1 range, not MIDI 0-127
+                // Already in 0-1 range, not MIDI 0-127
+                velocity: velocity_mult
+                    * accent.as_ref().map_or(1.0, |map| {
+                        // Accents follow the pattern grid (unswung), read through the tempo map
+                        map.gain_at(
+                            interpreter.beat_at(interpreter.cursor_time + i as f32 * step_duration),
+                        )
+                    }),
                 effects: None,
+                note: preset.note,
+                automation: None,
+                region: None,
+            };
+            let roll = SampleRoll {
+                interval: step_duration / hits as f32,
+                count: hits,
+                pitch: roll_pitch,
+                gain: roll_gain,
             };
-            interpreter.events.events.push(event);
+            for index in 0..hits {
+                interpreter.events.events.push(roll.hit(&event, index));
+            }
         }
     }
+    super::extractor::tag_sample_automation(interpreter, target, first_event);
 
     interpreter.cursor_time += bar_duration;
     Ok(())
 }
 
+/// Sample URI for a trigger named without its bank (`.kick`): the project's local banks
+/// win over the `[banks] default` bank, which wins over other installed banks. The
+/// default bank is loaded the first time a bank-less trigger needs it.
+#[cfg(feature = "cli")]
+fn resolve_implicit_trigger(interpreter: &mut AudioInterpreter, trigger: &str) -> Option<String> {
+    if let Some(default) = interpreter.banks.default_bank().map(str::to_string)
+        && !interpreter.banks.has_identifier(&default)
+    {
+        let root = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        match interpreter
+            .banks
+            .register_bank(default.clone(), &default, &root, &root)
+        {
+            Ok(bank) => {
+                if let Err(e) =
+                    crate::engine::audio::samples::ensure_bank_registered(&default, bank.root_dir())
+                {
+                    crate::tools::logger::Logger::new().warn(format!(
+                        "Failed to load samples of bank '{}': {}",
+                        default, e
+                    ));
+                }
+            }
+            Err(e) => {
+                // Reported once; later triggers only look at the declared banks
+                crate::tools::logger::Logger::new()
+                    .warn(format!("Failed to load default bank '{}': {}", default, e));
+                interpreter.banks.set_default_bank(None);
+            }
+        }
+    }
+
+    let candidate = interpreter
+        .banks
+        .implicit_candidates(trigger)
+        .into_iter()
+        .next()?;
+    Some(format!(
+        "devalang://bank/{}/{}",
+        candidate.identifier, trigger
+    ))
+}
+
+#[cfg(not(feature = "cli"))]
+fn resolve_implicit_trigger(_interpreter: &mut AudioInterpreter, _trigger: &str) -> Option<String> {
+    None
+}
+
 pub fn resolve_sample_uri(interpreter: &AudioInterpreter, target: &str) -> String {
     if let Some(dot_pos) = target.find('.') {
         let bank_alias = &target[..dot_pos];
@@ -1612,3 +2504,7 @@ pub fn resolve_sample_uri(interpreter: &AudioInterpreter, target: &str) -> Strin
     }
     target.to_string()
 }
+
+#[cfg(test)]
+#[path = "test_handler.rs"]
+mod tests;
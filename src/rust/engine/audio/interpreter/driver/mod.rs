@@ -1,13 +1,17 @@
 use crate::engine::audio::events::AudioEventList;
 use crate::engine::audio::events::SynthDefinition;
+use crate::engine::audio::interpreter::statements::loop_::LoopFrame;
 #[cfg(feature = "cli")]
 use crate::engine::audio::midi_native::MidiManager;
+use crate::engine::audio::outputs::OutputTaps;
+use crate::engine::audio::scene::SceneCue;
 use crate::engine::events::EventRegistry;
 use crate::engine::functions::FunctionRegistry;
 use crate::engine::special_vars::{SpecialVarContext, is_special_var, resolve_special_var};
 #[cfg(feature = "cli")]
 use crate::language::addons::registry::BankRegistry;
-use crate::language::syntax::ast::{Statement, Value};
+use crate::language::syntax::ast::{DurationValue, Statement, TimePosition, Value};
+use crate::language::syntax::parser::driver::duration::parse_duration_token;
 
 /// Routing configuration for a node
 #[derive(Debug, Clone)]
@@ -48,6 +52,10 @@ pub struct RoutingSetup {
     pub routes: Vec<RouteConfig>,
     pub ducks: Vec<DuckConfig>,
     pub sidechains: Vec<SidechainConfig>,
+    /// Group inserts sent to hardware outputs (`route drums -> outputs 3-4`)
+    pub outputs: Vec<crate::engine::audio::outputs::OutputRoute>,
+    /// Channel strips of group inserts (`strip drums gain -3db`), by group name
+    pub strips: HashMap<String, crate::engine::audio::mixer::ChannelStrip>,
 }
 
 impl Default for RoutingSetup {
@@ -57,10 +65,60 @@ impl Default for RoutingSetup {
             routes: Vec::new(),
             ducks: Vec::new(),
             sidechains: Vec::new(),
+            outputs: Vec::new(),
+            strips: HashMap::new(),
         }
     }
 }
 
+impl RoutingSetup {
+    /// Order-independent hash of the whole setup, used to tell whether two builds mix alike
+    pub fn fingerprint(&self) -> u64 {
+        use crate::engine::audio::mixer::cache::hash_value;
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        let mut nodes: Vec<_> = self.nodes.values().collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        let optional = |value: &Option<Value>, hasher: &mut _| {
+            value.is_some().hash(hasher);
+            if let Some(value) = value {
+                hash_value(value, hasher);
+            }
+        };
+        for node in nodes {
+            (&node.name, &node.alias).hash(&mut hasher);
+            optional(&node.effects, &mut hasher);
+        }
+        for route in &self.routes {
+            (&route.source, &route.destination).hash(&mut hasher);
+            optional(&route.effects, &mut hasher);
+        }
+        for (kind, source, destination, effect) in self
+            .ducks
+            .iter()
+            .map(|d| ("duck", &d.source, &d.destination, &d.effect))
+            .chain(
+                self.sidechains
+                    .iter()
+                    .map(|s| ("sidechain", &s.source, &s.destination, &s.effect)),
+            )
+        {
+            (kind, source, destination).hash(&mut hasher);
+            hash_value(effect, &mut hasher);
+        }
+        self.outputs.hash(&mut hasher);
+        let mut strips: Vec<_> = self.strips.iter().collect();
+        strips.sort_by(|a, b| a.0.cmp(b.0));
+        for (group, strip) in strips {
+            group.hash(&mut hasher);
+            (strip.gain.to_bits(), strip.pan.to_bits(), strip.invert).hash(&mut hasher);
+            strip.lowcut.map(f32::to_bits).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
 #[cfg(not(feature = "cli"))]
 #[allow(dead_code)]
 #[derive(Clone)]
@@ -77,6 +135,9 @@ impl BankRegistry {
     pub fn resolve_trigger(&self, _var: &str, _prop: &str) -> Option<std::path::PathBuf> {
         None
     }
+    pub fn remapped<'a>(&'a self, _alias: &str, identifier: &'a str) -> &'a str {
+        identifier
+    }
     pub fn register_bank(
         &self,
         _alias: String,
@@ -100,6 +161,52 @@ impl StubBank {
     }
 }
 
+/// State carried from one live rebuild to the next (`@persist` variables and loop passes)
+#[derive(Clone, Debug, Default)]
+pub struct PersistSnapshot {
+    pub variables: HashMap<String, Value>,
+    /// Passes completed by each counted loop, keyed by the loop's source line
+    pub loop_passes: HashMap<usize, usize>,
+}
+
+/// Live `@persist` bookkeeping for one interpretation
+#[derive(Clone, Debug, Default)]
+pub struct PersistState {
+    /// Snapshot from the previous build
+    pub previous: PersistSnapshot,
+    /// Variables marked with `@persist` in this build
+    pub names: std::collections::HashSet<String>,
+    /// `@persist loops`: counted loops continue their pass index across rebuilds
+    pub loops: bool,
+    /// Restored variables whose next `let`/`const` initializer must be skipped
+    pub pending_init: std::collections::HashSet<String>,
+    pub loop_passes: HashMap<usize, usize>,
+}
+
+/// Trigger parameters in effect from enclosing `call name with { ... }` blocks
+#[derive(Clone, Debug, PartialEq)]
+pub struct TriggerPreset {
+    /// Product of every `velocity` on the stack
+    pub velocity: f32,
+    /// Innermost `swing`, as the fraction of an off-beat step the hit is delayed by
+    pub swing: f32,
+    /// Innermost `note`, used to repitch samples
+    pub note: Option<u8>,
+    /// Innermost `accent` map, scaling velocity by beat position
+    pub accent: Option<crate::engine::audio::accent::AccentMap>,
+}
+
+impl Default for TriggerPreset {
+    fn default() -> Self {
+        Self {
+            velocity: 1.0,
+            swing: 0.0,
+            note: None,
+            accent: None,
+        }
+    }
+}
+
 /// Context for note-mode automations: contains templates and their temporal bounds
 #[derive(Clone, Debug)]
 pub struct NoteAutomationContext {
@@ -124,6 +231,10 @@ impl NoteAutomationContext {
 use anyhow::Result;
 use std::collections::HashMap;
 
+/// Length assumed while events are still being collected (`$progress`, beat handlers,
+/// background loop passes)
+const ESTIMATED_TOTAL_DURATION: f32 = 60.0;
+
 pub mod collector;
 pub mod extractor;
 pub mod handler;
@@ -133,6 +244,16 @@ pub mod renderer_graph;
 pub struct AudioInterpreter {
     pub sample_rate: u32,
     pub bpm: f32,
+    /// Quality used when converting bank samples to `sample_rate`
+    pub resample_quality: crate::engine::audio::settings::ResampleQuality,
+    /// Block size and accumulator precision used when mixing
+    pub mix: crate::engine::audio::settings::MixSettings,
+    /// Wall-clock time after which collecting or rendering fails (`RenderLimits`)
+    pub deadline: Option<std::time::Instant>,
+    /// Effects scripts can use: every built-in one unless `EngineBuilder` narrowed them
+    pub effect_registry: std::sync::Arc<crate::engine::audio::effects::registry::EffectRegistry>,
+    /// Synth types scripts can use; a disallowed `type` plays as the plain oscillator
+    pub synth_types: crate::engine::audio::synth::types::SynthTypeSelection,
     pub function_registry: FunctionRegistry,
     pub events: AudioEventList,
     pub variables: HashMap<String, Value>,
@@ -149,12 +270,53 @@ pub struct AudioInterpreter {
     pub midi_manager: Option<std::sync::Arc<std::sync::Mutex<MidiManager>>>,
     /// Track current statement location for better error reporting
     current_statement_location: Option<(usize, usize)>, // (line, column)
-    /// Internal guard to avoid re-entrant beat emission during handler execution
+    /// Skip the `on beat` / `on bar` pass of `collect_all_events` (the live interpreter
+    /// and handler tests drive those handlers themselves)
     pub suppress_beat_emit: bool,
     /// Internal guard to suppress printing during simulated/local interpreter runs
     pub suppress_print: bool,
     /// Flag used by 'break' statement to request breaking out of loops
     pub break_flag: bool,
+    /// Set by 'continue' to skip the rest of the current loop pass
+    pub continue_flag: bool,
+    /// Loop label targeted by a pending `break`/`continue` (None: the innermost loop)
+    pub loop_jump: Option<String>,
+    /// Running loops with their labels and pass indices, innermost last
+    pub loop_frames: Vec<LoopFrame>,
+    /// Zero-based pass index of the innermost running loop (used by `every N` triggers)
+    pub loop_pass: usize,
+    /// Seed mixed into `chance` rolls so probabilistic triggers render identically each build
+    pub trigger_seed: u64,
+    /// Values injected from outside (e.g. `play --set key=value`) that win over `let`/`const`
+    pub variable_overrides: HashMap<String, Value>,
+    /// Variables and loop passes that survive live rebuilds
+    pub persist: PersistState,
+    /// Parameter blocks pushed by `call name with { ... }`, innermost last
+    pub modifier_stack: Vec<HashMap<String, Value>>,
+    /// `with { ... }` blocks from group headers, pushed whenever the group runs
+    pub group_presets: HashMap<String, HashMap<String, Value>>,
+    /// Solo and mute flags from group headers and the CLI
+    pub solo_mute: crate::engine::audio::solo::SoloMute,
+    /// Groups currently running, outermost first
+    pub group_stack: Vec<String>,
+    /// Metronome requested by `metronome on`
+    pub metronome: Option<crate::engine::audio::settings::ClickMode>,
+    /// Members of each `scene` declaration
+    pub scenes: HashMap<String, Vec<String>>,
+    /// Scene started by the last `switch`
+    pub scene: Option<SceneCue>,
+    /// Parameter values captured by `snapshot save`
+    pub snapshots: HashMap<String, crate::engine::audio::snapshot::ParamSnapshot>,
+    /// `macro` controls, applied to the collected events
+    pub macros: crate::engine::audio::macros::MacroRegistry,
+    /// `pattern name = a then b` chains with their position, keyed by pattern name
+    pub pattern_chains: HashMap<String, crate::engine::audio::pattern_chain::PatternChain>,
+    /// Group inserts rendered by the previous build, reused when their events are unchanged
+    pub insert_cache:
+        Option<std::sync::Arc<std::sync::Mutex<crate::engine::audio::mixer::InsertCache>>>,
+    /// Tempo and meter changes imported from MIDI files; bound MIDI notes and MIDI
+    /// export follow it when set
+    pub tempo_map: crate::engine::audio::tempo::TempoMap,
     /// Background worker channel sender/receiver (threads send AudioEventList here)
     pub background_event_tx:
         Option<std::sync::mpsc::Sender<crate::engine::audio::events::AudioEventList>>,
@@ -181,6 +343,11 @@ impl AudioInterpreter {
         Self {
             sample_rate,
             bpm: 120.0,
+            resample_quality: crate::engine::audio::settings::ResampleQuality::default(),
+            mix: crate::engine::audio::settings::MixSettings::default(),
+            deadline: None,
+            effect_registry: crate::engine::audio::effects::registry::EffectRegistry::full(),
+            synth_types: Default::default(),
             function_registry: FunctionRegistry::new(),
             events: AudioEventList::new(),
             variables: HashMap::new(),
@@ -197,6 +364,25 @@ impl AudioInterpreter {
             suppress_beat_emit: false,
             suppress_print: false,
             break_flag: false,
+            continue_flag: false,
+            loop_jump: None,
+            loop_frames: Vec::new(),
+            loop_pass: 0,
+            trigger_seed: 0,
+            variable_overrides: HashMap::new(),
+            persist: PersistState::default(),
+            modifier_stack: Vec::new(),
+            group_presets: HashMap::new(),
+            solo_mute: Default::default(),
+            group_stack: Vec::new(),
+            metronome: None,
+            scenes: HashMap::new(),
+            scene: None,
+            snapshots: HashMap::new(),
+            macros: Default::default(),
+            pattern_chains: HashMap::new(),
+            insert_cache: None,
+            tempo_map: crate::engine::audio::tempo::TempoMap::new(),
             background_event_tx: None,
             background_event_rx: None,
             background_workers: Vec::new(),
@@ -209,10 +395,20 @@ impl AudioInterpreter {
         }
     }
 
+    /// Render every group into its own insert and reuse the ones whose events did not
+    /// change since the last build sharing `cache`
+    pub fn enable_insert_cache(
+        &mut self,
+        cache: std::sync::Arc<std::sync::Mutex<crate::engine::audio::mixer::InsertCache>>,
+    ) {
+        self.insert_cache = Some(cache);
+        self.events.tag_all_groups = true;
+    }
+
     /// Handle a trigger statement (e.g., .kit.kick or kit.kick)
     fn handle_trigger(&mut self, entity: &str) -> Result<()> {
         // Delegate detailed trigger handling to the handler module
-        handler::handle_trigger(self, entity, None)
+        handler::handle_trigger(self, entity, None, None)
     }
 
     /// Helper to print banks and triggers for debugging
@@ -221,8 +417,17 @@ impl AudioInterpreter {
     }
 
     pub fn interpret(&mut self, statements: &[Statement]) -> Result<Vec<f32>> {
-        // Initialize special vars context
-        let total_duration = self.calculate_total_duration(statements)?;
+        self.collect_all_events(statements)?;
+
+        // Phase 2: Render audio
+        self.render_audio()
+    }
+
+    /// Phase 1 of `interpret`: collect events, including those produced by background
+    /// loop passes, without rendering
+    pub fn collect_all_events(&mut self, statements: &[Statement]) -> Result<()> {
+        // Initialize special vars context; nothing is collected yet, so this is an estimate
+        let total_duration = ESTIMATED_TOTAL_DURATION;
         self.special_vars.total_duration = total_duration;
         self.special_vars.update_bpm(self.bpm);
 
@@ -265,10 +470,33 @@ impl AudioInterpreter {
             }
         }
 
-        // Phase 2: Render audio
+        // Beat/bar handlers run over the finished timeline, the same way for every caller
+        if !self.suppress_beat_emit {
+            handler::emit_beat_events(self)?;
+        }
 
-        // Phase 2: Render audio
-        self.render_audio()
+        // Once a declared group is soloed, only soloed groups are heard
+        if self.solo_mute.solo_active(self.groups.keys()) {
+            self.events.retain_soloed();
+        }
+
+        if !self.macros.is_empty() {
+            let variables = &self.variables;
+            self.macros
+                .apply(&mut self.events, |alias| match variables.get(alias) {
+                    Some(Value::Map(bank)) => match bank.get("_name") {
+                        Some(Value::String(name)) => Some(format!("devalang://bank/{}/", name)),
+                        _ => None,
+                    },
+                    _ => None,
+                });
+        }
+
+        // Open sample loops repeat up to the end of everything else
+        let total_duration = self.calculate_total_duration();
+        self.events.close_open_loops(total_duration);
+        self.special_vars.total_duration = total_duration;
+        Ok(())
     }
 
     /// Get reference to collected audio events (for MIDI export)
@@ -281,16 +509,55 @@ impl AudioInterpreter {
         self.current_statement_location
     }
 
-    /// Calculate approximate total duration by scanning statements
-    pub fn calculate_total_duration(&self, _statements: &[Statement]) -> Result<f32> {
-        // For now, return a default duration (will be updated during collect_events)
-        Ok(60.0) // Default 60 seconds
+    /// Real end of the render from the collected events: note releases, loaded sample
+    /// lengths and group effect tails
+    pub fn calculate_total_duration(&self) -> f32 {
+        #[cfg(feature = "cli")]
+        {
+            let mut lengths: HashMap<&str, Option<f32>> = HashMap::new();
+            for event in &self.events.events {
+                if let crate::engine::audio::events::AudioEvent::Sample { uri, .. } = event {
+                    lengths
+                        .entry(uri)
+                        .or_insert_with(|| crate::engine::audio::samples::sample_seconds(uri));
+                }
+            }
+            self.events
+                .end_time(self.sample_rate, &self.effect_registry, |uri| {
+                    lengths.get(uri).copied().flatten()
+                })
+        }
+        #[cfg(not(feature = "cli"))]
+        {
+            #[cfg(target_arch = "wasm32")]
+            let length = |uri: &str| {
+                use crate::web::registry::samples::{REGISTRY_SAMPLE_RATE, get_sample};
+                // Registered samples are converted to the registry rate on load
+                get_sample(uri).map(|pcm| pcm.len() as f32 / REGISTRY_SAMPLE_RATE as f32)
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            let length = |_: &str| None;
+            self.events
+                .end_time(self.sample_rate, &self.effect_registry, length)
+        }
     }
 
     pub fn collect_events(&mut self, statements: &[Statement]) -> Result<()> {
+        self.check_deadline()?;
         // Delegate to the collector child module
         collector::collect_events(self, statements)
     }
+
+    /// Fail once `deadline` has passed; checked for every block of statements collected
+    /// and between the steps of a render
+    pub fn check_deadline(&self) -> Result<()> {
+        match self.deadline {
+            Some(deadline) if std::time::Instant::now() >= deadline => {
+                Err(anyhow::anyhow!("Render stopped: over its time limit"))
+            }
+            _ => Ok(()),
+        }
+    }
     pub fn handle_let(&mut self, name: &str, value: &Value) -> Result<()> {
         handler::handle_let(self, name, value)
     }
@@ -309,6 +576,37 @@ impl AudioInterpreter {
         renderer::render_audio(self)
     }
 
+    /// Render the master with the groups sent to hardware outputs (see `renderer::render_audio_outputs`)
+    pub fn render_audio_outputs(&self) -> Result<(Vec<f32>, OutputTaps)> {
+        renderer::render_audio_outputs(self)
+    }
+
+    /// Render the master with every group and output route tapped (see `renderer::render_audio_inserts`)
+    pub fn render_audio_inserts(&self) -> Result<(Vec<f32>, OutputTaps)> {
+        renderer::render_audio_inserts(self)
+    }
+
+    /// Render the master with a report on where and why it is silent (see `renderer::render_audio_diagnosed`)
+    pub fn render_audio_diagnosed(
+        &self,
+    ) -> Result<(Vec<f32>, crate::engine::audio::diagnostics::SilenceReport)> {
+        renderer::render_audio_diagnosed(self)
+    }
+
+    /// Render block by block into `sink` instead of one buffer (see `renderer::render_audio_streamed`)
+    pub fn render_audio_streamed(
+        &self,
+        chunk_frames: usize,
+        sink: &mut dyn FnMut(&[f32]) -> Result<()>,
+    ) -> Result<f32> {
+        renderer::render_audio_streamed(self, chunk_frames, sink)
+    }
+
+    /// Whether this project can be rendered with `render_audio_streamed`
+    pub fn can_stream(&self) -> bool {
+        renderer::can_stream(self)
+    }
+
     pub fn set_bpm(&mut self, bpm: f32) {
         self.bpm = bpm.max(1.0).min(999.0);
         // Keep special vars in sync so $beat/$bar calculations use the updated BPM
@@ -319,11 +617,208 @@ impl AudioInterpreter {
         ((60.0 / self.bpm) * self.sample_rate as f32) as usize
     }
 
+    /// Fix every random source (`$random.*`, humanize, `chance`, random strums) to `seed` so two
+    /// renders of the same source produce identical audio.
+    pub fn set_deterministic(&mut self, seed: u64) {
+        self.trigger_seed = seed;
+        self.special_vars.random = crate::engine::special_vars::RandomSource::seeded(seed);
+    }
+
+    /// Install top-level variable overrides. They are visible before the first statement
+    /// and replace any later `let`/`const` of the same name; `bpm` overrides the tempo.
+    pub fn set_overrides(&mut self, overrides: HashMap<String, Value>) {
+        if let Some(Value::Number(bpm)) = overrides.get("bpm") {
+            self.set_bpm(*bpm);
+        }
+        for (name, value) in &overrides {
+            self.variables.insert(name.clone(), value.clone());
+        }
+        self.variable_overrides = overrides;
+    }
+
+    /// Seed `@persist` state with the snapshot of the previous build
+    pub fn set_persisted(&mut self, snapshot: PersistSnapshot) {
+        self.persist.previous = snapshot;
+    }
+
+    /// Mark variables as persistent and restore their previous values. A restored value
+    /// replaces the first `let`/`const` of the name so the initializer does not reset it.
+    pub fn mark_persistent(&mut self, names: &[String]) {
+        for name in names {
+            if name == "loops" {
+                self.persist.loops = true;
+                continue;
+            }
+            self.persist.names.insert(name.clone());
+            if let Some(value) = self.persist.previous.variables.get(name) {
+                self.variables.insert(name.clone(), value.clone());
+                self.persist.pending_init.insert(name.clone());
+            }
+        }
+    }
+
+    /// State to hand to the next build: current values of persisted variables and,
+    /// with `@persist loops`, the pass counters of counted loops
+    pub fn persisted_snapshot(&self) -> PersistSnapshot {
+        PersistSnapshot {
+            variables: self
+                .persist
+                .names
+                .iter()
+                .filter_map(|name| Some((name.clone(), self.variables.get(name)?.clone())))
+                .collect(),
+            loop_passes: if self.persist.loops {
+                self.persist.loop_passes.clone()
+            } else {
+                HashMap::new()
+            },
+        }
+    }
+
+    /// Merge the `with { ... }` blocks on the modifier stack into trigger parameters.
+    ///
+    /// `swing` is written as the long share of an off-beat pair (`50%` is straight,
+    /// `66%` is a triplet feel); numbers above 1 are read as percentages.
+    pub fn trigger_preset(&self) -> TriggerPreset {
+        let mut preset = TriggerPreset::default();
+        for block in &self.modifier_stack {
+            if let Some(velocity) = block.get("velocity").and_then(|v| self.preset_number(v)) {
+                preset.velocity *= velocity;
+            }
+            if let Some(ratio) = block.get("swing").and_then(|v| self.preset_number(v)) {
+                let ratio = if ratio > 1.0 { ratio / 100.0 } else { ratio };
+                preset.swing = ((ratio - 0.5) * 2.0).clamp(0.0, 0.9);
+            }
+            if let Some(note) = block.get("note") {
+                let midi = match note {
+                    Value::Number(n) => Some(n.clamp(0.0, 127.0) as u8),
+                    Value::String(s) | Value::Identifier(s) => {
+                        crate::engine::functions::note::parse_note_to_midi(s).ok()
+                    }
+                    _ => None,
+                };
+                preset.note = midi.or(preset.note);
+            }
+            if let Some(accent) = block.get("accent") {
+                preset.accent = self.accent_map(accent);
+            }
+        }
+        preset
+    }
+
+    /// Accent map named by a `with { accent: ... }` entry: a declared `accent map`
+    /// variable or a built-in preset; `none` turns an outer accent off
+    pub fn accent_map(&self, value: &Value) -> Option<crate::engine::audio::accent::AccentMap> {
+        use crate::engine::audio::accent::AccentMap;
+        match value {
+            Value::String(name) | Value::Identifier(name) => match self.variables.get(name) {
+                Some(declared) => AccentMap::from_value(declared),
+                None => AccentMap::preset(name),
+            },
+            other => AccentMap::from_value(other),
+        }
+    }
+
+    /// Velocity multiplier of `accent` for a hit at `time` seconds
+    pub fn accent_gain(
+        &self,
+        accent: Option<&crate::engine::audio::accent::AccentMap>,
+        time: f32,
+    ) -> f32 {
+        accent.map_or(1.0, |map| map.gain_at(self.beat_at(time)))
+    }
+
+    /// Beat position reached at `seconds`, following the tempo map
+    pub fn beat_at(&self, seconds: f32) -> f32 {
+        self.tempo_map.beat_at(seconds, self.bpm)
+    }
+
+    /// Delay for a hit at the cursor: off-beat eighths are pushed back by `swing` of a step
+    pub fn swing_offset(&self, swing: f32) -> f32 {
+        if swing <= 0.0 {
+            return 0.0;
+        }
+        let step = self.beat_duration() / 2.0;
+        let position = self.cursor_time / step;
+        let index = position.round();
+        if (position - index).abs() < 1e-3 && (index as i64) % 2 == 1 {
+            swing * step
+        } else {
+            0.0
+        }
+    }
+
+    /// Numeric value of a preset entry: numbers, `60%` strings or numeric variables
+    fn preset_number(&self, value: &Value) -> Option<f32> {
+        match value {
+            Value::Number(n) => Some(*n),
+            Value::String(s) | Value::Identifier(s) => match s.strip_suffix('%') {
+                Some(pct) => pct.trim().parse::<f32>().ok().map(|p| p / 100.0),
+                None => match self.variables.get(s) {
+                    Some(Value::Number(n)) => Some(*n),
+                    _ => s.parse::<f32>().ok(),
+                },
+            },
+            _ => None,
+        }
+    }
+
     /// Get duration of one beat in seconds
     pub fn beat_duration(&self) -> f32 {
         60.0 / self.bpm
     }
 
+    /// Convert an `at` position to seconds, following the tempo and meter maps
+    /// (a meter change starts a new bar, as on the click track)
+    pub fn time_position_secs(&self, position: &TimePosition) -> f32 {
+        let beat = match position {
+            TimePosition::Seconds(secs) => return secs.max(0.0),
+            TimePosition::Beat(beat) => (beat - 1.0).max(0.0),
+            TimePosition::Bar(bar) => {
+                let bar = (bar - 1.0).max(0.0);
+                let bar_length = |beat: f32| {
+                    let (numerator, denominator) = self.tempo_map.meter_at(beat);
+                    4.0 * numerator.max(1) as f32 / denominator.max(1) as f32
+                };
+                let mut start = 0.0f32;
+                for _ in 0..bar.floor() as u32 {
+                    let length = bar_length(start);
+                    let next_meter = self
+                        .tempo_map
+                        .meters
+                        .iter()
+                        .find(|change| change.beat > start)
+                        .map_or(f32::INFINITY, |change| change.beat);
+                    start = (start + length).min(next_meter);
+                }
+                start + bar_length(start) * bar.fract()
+            }
+        };
+        self.tempo_map.seconds_at(beat, self.bpm)
+    }
+
+    /// Convert a duration to seconds at the current tempo, resolving variable references
+    /// (numbers are milliseconds, strings are parsed as duration literals)
+    pub fn duration_secs(&self, duration: &DurationValue) -> Option<f32> {
+        match duration {
+            DurationValue::Identifier(name) => match self.variables.get(name)? {
+                Value::Number(ms) => Some(ms / 1000.0),
+                Value::Duration(dv) if !matches!(dv, DurationValue::Identifier(_)) => {
+                    dv.to_seconds(self.bpm)
+                }
+                Value::String(s) => parse_duration_token(s).ok()?.to_seconds(self.bpm),
+                _ => None,
+            },
+            other => other.to_seconds(self.bpm),
+        }
+    }
+
+    /// Resolve musical effect times (`delay(1/8d)`) against the current tempo, so each
+    /// event keeps the tempo it was scheduled under
+    pub fn tempo_synced_effects(&self, effects: &Value) -> Value {
+        crate::engine::audio::effects::tempo_sync::resolve_tempo_synced(effects, self.bpm)
+    }
+
     /// Execute print statement with variable interpolation
     /// Supports {variable_name} syntax
     pub fn execute_print(&mut self, value: &Value) -> Result<()> {
@@ -462,6 +957,10 @@ impl AudioInterpreter {
     pub fn resolve_value(&mut self, value: &Value) -> Result<Value> {
         match value {
             Value::Identifier(name) => {
+                if let Some(counter) = self.loop_counter(name) {
+                    return Ok(counter);
+                }
+
                 // Check if it's a special variable
                 if is_special_var(name) {
                     if let Some(special_val) = resolve_special_var(name, &self.special_vars) {
@@ -819,15 +1318,83 @@ impl AudioInterpreter {
                 let res = super::handler::call_function(self, name, &resolved_args)?;
                 return Ok(res);
             }
+            Value::Map(_) | Value::Array(_) => self.resolve_unit_calls(value),
             _ => return Ok(value.clone()),
         }
     }
 
+    /// Evaluate unit helpers (`ms(1/8)`, `hz(A4)`) nested in map and array literals, so
+    /// effect and automation params can use them. Other calls are left for their consumers.
+    fn resolve_unit_calls(&mut self, value: &Value) -> Result<Value> {
+        match value {
+            Value::Call { name, .. } if crate::engine::functions::units::is_unit_function(name) => {
+                self.resolve_value(value)
+            }
+            Value::Map(map) => Ok(Value::Map(
+                map.iter()
+                    .map(|(key, item)| Ok((key.clone(), self.resolve_unit_calls(item)?)))
+                    .collect::<Result<_>>()?,
+            )),
+            Value::Array(items) => Ok(Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.resolve_unit_calls(item))
+                    .collect::<Result<_>>()?,
+            )),
+            other => Ok(other.clone()),
+        }
+    }
+
     /// Execute event handlers matching the event name
     pub fn execute_event_handlers(&mut self, event_name: &str) -> Result<()> {
         handler::execute_event_handlers(self, event_name)
     }
 
+    /// Feed a raw MIDI input message (from a controller) to the `on mapping.in.<device>.*`
+    /// handlers at the cursor, as native MIDI input does. The message goes to every
+    /// `bind mapping.in.<device>` whose `channel` (1-16) matches, or to `default_device`
+    /// when the program binds no incoming mapping. Returns false for messages that
+    /// aren't notes.
+    pub fn receive_midi_message(&mut self, message: &[u8], default_device: &str) -> Result<bool> {
+        let Some((kind, data)) = crate::engine::audio::midi::input_event(message) else {
+            return Ok(false);
+        };
+        let channel = match data.get("channel") {
+            Some(Value::Number(channel)) => *channel + 1.0,
+            _ => 0.0,
+        };
+
+        let mut bound = false;
+        let mut devices: Vec<String> = Vec::new();
+        for (name, binding) in &self.variables {
+            let Some(source) = name.strip_prefix("__mapping_bind::mapping.in.") else {
+                continue;
+            };
+            bound = true;
+            let wanted = match binding {
+                Value::Map(options) => options.get("channel"),
+                _ => None,
+            };
+            if matches!(wanted, Some(Value::Number(wanted)) if *wanted != channel) {
+                continue;
+            }
+            devices.push(source.split('.').next().unwrap_or(source).to_string());
+        }
+        if !bound {
+            devices.push(default_device.to_string());
+        }
+        devices.sort();
+        devices.dedup();
+
+        for device in devices {
+            let event_name = format!("mapping.in.{}.{}", device, kind);
+            self.event_registry
+                .emit(event_name.clone(), data.clone(), self.cursor_time);
+            self.execute_event_handlers(&event_name)?;
+        }
+        Ok(true)
+    }
+
     /// Check if two values are equal
     pub fn values_equal(&self, left: &Value, right: &Value) -> bool {
         match (left, right) {
@@ -868,9 +1435,9 @@ impl AudioInterpreter {
         handler::extract_synth_def_from_map(self, map)
     }
 
-    /// Handle MIDI file loading: @load "path.mid" as alias
-    pub fn handle_load(&mut self, source: &str, alias: &str) -> Result<()> {
-        handler::handle_load(self, source, alias)
+    /// Handle MIDI file loading: @load "path.mid" as alias [with { tempo_map: true }]
+    pub fn handle_load(&mut self, source: &str, alias: &str, options: &Value) -> Result<()> {
+        handler::handle_load(self, source, alias, options)
     }
 
     /// Handle MIDI binding: bind source -> target { options }
@@ -886,14 +1453,16 @@ impl AudioInterpreter {
         handler::extract_pattern_data(self, value)
     }
 
-    /// Execute a pattern with given target and pattern string
+    /// Execute a pattern with given target and pattern string; `accent` is the
+    /// pattern's own `accent` option, overriding enclosing `with` blocks
     pub fn execute_pattern(
         &mut self,
         target: &str,
         pattern: &str,
         options: Option<HashMap<String, f32>>,
+        accent: Option<&Value>,
     ) -> Result<()> {
-        handler::execute_pattern(self, target, pattern, options)
+        handler::execute_pattern(self, target, pattern, options, accent)
     }
 
     /// Resolve sample URI from bank.trigger notation (e.g., myBank.kick -> devalang://bank/devaloop.808/kick)
@@ -902,6 +1471,9 @@ impl AudioInterpreter {
     }
 }
 
+#[cfg(test)]
+pub(crate) mod test_support;
+
 #[cfg(test)]
 #[path = "test_arrays.rs"]
 mod tests_arrays;
@@ -917,3 +1489,7 @@ mod tests_functions;
 #[cfg(test)]
 #[path = "test_control_flow.rs"]
 mod tests_control_flow;
+
+#[cfg(test)]
+#[path = "test_synths.rs"]
+mod tests_synths;
@@ -1,14 +1,30 @@
 #![allow(unused_macros)]
 use super::AudioInterpreter;
-use crate::engine::audio::effects::chain::{EffectChain, build_effect_chain};
+use crate::engine::audio::choke;
+use crate::engine::audio::diagnostics::{InsertLevel, SilenceReport};
+use crate::engine::audio::effects::chain::{EffectChain, build_effect_chain_with};
 use crate::engine::audio::effects::normalize_effects;
 use crate::engine::audio::effects::processors::{
     DelayProcessor, DriveProcessor, EffectProcessor, ReverbProcessor,
 };
+use crate::engine::audio::events::AudioEvent;
 use crate::engine::audio::generator::{
-    SynthParams, generate_chord_with_options, generate_note_with_options,
+    BlockAutomation, ParamCurve, PluginContext, PluginPendingNote, SynthParams,
+    generate_chord_with_options, generate_note_with_options,
 };
+use crate::engine::audio::mixer::{
+    AudioMixer, DuckSettings, InsertCache, InsertMeters, MASTER_INSERT, METER_WINDOW_SECONDS,
+    MixSample, StripAutomation,
+};
+use crate::engine::audio::outputs::OutputTaps;
+use crate::engine::audio::retrigger::RetriggerFades;
+use crate::engine::audio::settings::MixPrecision;
+use crate::language::syntax::ast::Value;
 use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 // Conditional logging macros for CLI feature
 #[cfg(feature = "cli")]
@@ -53,10 +69,91 @@ macro_rules! log_error {
     };
 }
 
+/// A render: the stereo master, the tapped groups and the sample triggers that rendered
+/// nothing (their URIs, once per trigger)
+#[derive(Default)]
+struct Rendered {
+    master: Vec<f32>,
+    taps: OutputTaps,
+    dropped_samples: Vec<String>,
+}
+
 pub fn render_audio(interpreter: &AudioInterpreter) -> Result<Vec<f32>> {
-    let total_duration = interpreter.events.total_duration();
+    render_with_taps(interpreter, &[]).map(|rendered| rendered.master)
+}
+
+/// Render the stereo master and the stereo signal of each group sent to hardware outputs
+/// (`route ... -> outputs`), ready for `outputs::interleave`
+pub fn render_audio_outputs(interpreter: &AudioInterpreter) -> Result<(Vec<f32>, OutputTaps)> {
+    let taps: Vec<String> = interpreter
+        .routing
+        .outputs
+        .iter()
+        .map(|route| route.insert.clone())
+        .collect();
+    render_with_taps(interpreter, &taps).map(|rendered| (rendered.master, rendered.taps))
+}
+
+/// Render the stereo master, every group after its effects (what `devalang diff`
+/// compares) and the groups sent to hardware outputs
+pub fn render_audio_inserts(interpreter: &AudioInterpreter) -> Result<(Vec<f32>, OutputTaps)> {
+    let mut taps = group_names(interpreter);
+    taps.extend(
+        interpreter
+            .routing
+            .outputs
+            .iter()
+            .map(|route| route.insert.clone()),
+    );
+    let taps: Vec<String> = taps.into_iter().collect();
+    render_with_taps(interpreter, &taps).map(|rendered| (rendered.master, rendered.taps))
+}
+
+/// Every group that plays events, at any depth of the group path
+fn group_names(interpreter: &AudioInterpreter) -> BTreeSet<String> {
+    (0..interpreter.events.events.len())
+        .filter_map(|index| interpreter.events.group_path(index))
+        .flat_map(|path| path.split('/').map(str::to_string).collect::<Vec<_>>())
+        .collect()
+}
+
+/// Render the stereo master with a `SilenceReport` explaining where it is quiet: every
+/// group insert is tapped to measure its activity
+pub fn render_audio_diagnosed(interpreter: &AudioInterpreter) -> Result<(Vec<f32>, SilenceReport)> {
+    let mut inserts = group_names(interpreter);
+    inserts.extend(interpreter.solo_mute.mute.iter().cloned());
+    let taps: Vec<String> = inserts.iter().cloned().collect();
+    let rendered = render_with_taps(interpreter, &taps)?;
+
+    // Graph renders do not tap inserts, so their activity is unknown
+    let graph = interpreter.audio_graph.node_names().len() > 1;
+    let inserts: Vec<InsertLevel> = if graph {
+        Vec::new()
+    } else {
+        inserts
+            .into_iter()
+            .map(|name| InsertLevel {
+                muted: interpreter.solo_mute.mute.contains(&name),
+                signal: rendered.taps.get(&name).map(Vec::as_slice).unwrap_or(&[]),
+                name,
+            })
+            .collect()
+    };
+    let report = SilenceReport::analyze(
+        &rendered.master,
+        interpreter.sample_rate,
+        &inserts,
+        &rendered.dropped_samples,
+        &interpreter.events.unresolved_triggers,
+    );
+    Ok((rendered.master, report))
+}
+
+/// Stereo master and the stereo signal of each group named in `taps`
+fn render_with_taps(interpreter: &AudioInterpreter, taps: &[String]) -> Result<Rendered> {
+    let total_duration = interpreter.calculate_total_duration();
     if total_duration <= 0.0 {
-        return Ok(Vec::new());
+        return Ok(Rendered::default());
     }
 
     let total_samples = (total_duration * interpreter.sample_rate as f32).ceil() as usize;
@@ -75,12 +172,47 @@ pub fn render_audio(interpreter: &AudioInterpreter) -> Result<Vec<f32>> {
             "Using audio graph rendering with {} nodes",
             interpreter.audio_graph.node_names().len()
         );
-        return super::renderer_graph::render_audio_graph(interpreter, total_samples)
+        // Graph nodes are not group inserts, so nothing is tapped
+        let rendered = match interpreter.mix.precision {
+            MixPrecision::F32 => {
+                super::renderer_graph::render_audio_graph::<f32>(interpreter, total_samples)
+            }
+            MixPrecision::F64 => {
+                super::renderer_graph::render_audio_graph::<f64>(interpreter, total_samples)
+            }
+        };
+        return rendered
+            .map(|master| Rendered {
+                master,
+                ..Rendered::default()
+            })
             .map_err(|e| anyhow::anyhow!("Audio graph rendering failed: {}", e));
     }
 
-    // Default: simple buffer rendering (no routing)
-    let mut buffer = vec![0.0f32; total_samples * 2]; // stereo
+    // Default: simple buffer rendering (no routing), summed in the configured precision
+    match interpreter.mix.precision {
+        MixPrecision::F32 => render_events::<f32>(interpreter, total_duration, total_samples, taps),
+        MixPrecision::F64 => render_events::<f64>(interpreter, total_duration, total_samples, taps),
+    }
+}
+
+/// Render every event into a stereo accumulator of type `S`, mix group inserts,
+/// then convert to f32 and normalize. Groups named in `taps` are returned with the
+/// same normalization as the master.
+fn render_events<S: MixSample>(
+    interpreter: &AudioInterpreter,
+    total_duration: f32,
+    total_samples: usize,
+    taps: &[String],
+) -> Result<Rendered> {
+    #[cfg(feature = "cli")]
+    let logger = crate::tools::logger::Logger::new();
+    #[cfg(not(feature = "cli"))]
+    let _logger = ();
+
+    let mut buffer = vec![S::default(); total_samples * 2]; // stereo
+    // Events played by groups with an effect chain render into their own insert buffer
+    let mut group_buffers: HashMap<String, Vec<S>> = HashMap::new();
 
     log_info!(
         logger,
@@ -92,145 +224,712 @@ pub fn render_audio(interpreter: &AudioInterpreter) -> Result<Vec<f32>> {
 
     // No-op pre-scan: logs are stored separately in interpreter.events.logs and are ignored by renderer.
 
+    let paths: Vec<Option<String>> = (0..interpreter.events.events.len())
+        .map(|index| interpreter.events.group_path(index))
+        .collect();
+
+    // Watch rebuilds: splice in group inserts whose events did not change
+    let fingerprints = match &interpreter.insert_cache {
+        Some(cache) => {
+            let context = render_context_hash(interpreter, total_samples);
+            let fingerprints =
+                InsertCache::fingerprints(&interpreter.events.events, &paths, context);
+            if let Ok(cache) = cache.lock() {
+                for (path, fingerprint) in &fingerprints {
+                    if let Some(samples) = cache.lookup(path, *fingerprint, total_samples * 2) {
+                        group_buffers.insert(
+                            path.clone(),
+                            samples.iter().map(|&s| S::from_f64(s)).collect(),
+                        );
+                    }
+                }
+            }
+            fingerprints
+        }
+        None => HashMap::new(),
+    };
+    let reused: HashSet<String> = group_buffers.keys().cloned().collect();
+
+    let events = &interpreter.events.events;
+    let chokes = choke::choke_times(events, choke::bank_choke_group);
+    let mut fades = retrigger_fades(interpreter);
+    let plugins = PluginNotes::new(&interpreter.events.events);
+    // In start order, so each retrigger knows whether the voice before it still rings
+    let mut order: Vec<usize> = (0..events.len()).collect();
+    order.sort_by_key(|&index| frame_at(event_start(&events[index]), interpreter.sample_rate));
+
     // Render each event (copied logic from driver)
     let mut note_count = 0;
     let mut sample_count = 0;
-    for event in &interpreter.events.events {
+    let mut dropped_samples = Vec::new();
+    for event_index in order {
+        interpreter.check_deadline()?;
+        let event = &events[event_index];
+        let buffer: &mut Vec<S> = match &paths[event_index] {
+            Some(path) if reused.contains(path) => continue,
+            Some(path) => group_buffers
+                .entry(path.clone())
+                .or_insert_with(|| vec![S::default(); total_samples * 2]),
+            None => &mut buffer,
+        };
         match event {
-            crate::engine::audio::events::AudioEvent::Note {
-                midi,
-                start_time,
-                duration,
-                velocity,
-                synth_id,
-                synth_def,
-                pan,
-                detune,
-                gain,
-                attack,
-                release,
-                delay_time,
-                delay_feedback,
-                delay_mix,
-                reverb_amount,
-                drive_amount,
-                drive_color,
-                use_per_note_automation,
-                ..
-            } => {
-                note_count += 1;
-                // Log note rendering only if needed (debug mode)
-
-                let mut params = SynthParams {
-                    waveform: synth_def.waveform.clone(),
-                    attack: synth_def.attack,
-                    decay: synth_def.decay,
-                    sustain: synth_def.sustain,
-                    release: synth_def.release,
-                    synth_type: synth_def.synth_type.clone(),
-                    filters: synth_def.filters.clone(),
-                    options: synth_def.options.clone(),
-                    lfo: synth_def.lfo.clone(),
-                    plugin_author: synth_def.plugin_author.clone(),
-                    plugin_name: synth_def.plugin_name.clone(),
-                    plugin_export: synth_def.plugin_export.clone(),
-                };
-
-                if let Some(a) = attack {
-                    params.attack = a / 1000.0;
+            AudioEvent::Note { .. } => note_count += 1,
+            AudioEvent::Sample { .. } => sample_count += 1,
+            AudioEvent::Chord { .. } => {}
+        }
+        match render_choked(
+            interpreter,
+            event,
+            &plugins,
+            chokes[event_index],
+            Some((&mut fades, event_index)),
+        )? {
+            Some((start_frame, samples)) => mix_into(buffer, start_frame, &samples),
+            None => {
+                if let AudioEvent::Sample { uri, .. } = event {
+                    dropped_samples.push(uri.clone());
                 }
-                if let Some(r) = release {
-                    params.release = r / 1000.0;
+            }
+        }
+    }
+
+    log_info!(
+        logger,
+        "Rendered {} notes + {} samples",
+        note_count,
+        sample_count
+    );
+    if let Some(cache) = &interpreter.insert_cache
+        && let Ok(mut cache) = cache.lock()
+    {
+        for (path, samples) in &group_buffers {
+            if !reused.contains(path)
+                && let Some(fingerprint) = fingerprints.get(path)
+            {
+                let samples = samples.iter().map(|&s| s.to_f64()).collect();
+                cache.store(path.clone(), *fingerprint, samples);
+            }
+        }
+        let live: HashSet<&str> = fingerprints.keys().map(String::as_str).collect();
+        cache.retain_paths(&live);
+        cache.reused = reused.len();
+        cache.rendered = group_buffers.len() - reused.len();
+        log_info!(
+            logger,
+            "Reused {} of {} group inserts",
+            cache.reused,
+            group_buffers.len()
+        );
+    }
+    // Muted or unsoloed groups still key the ducks they are named in
+    let mut duck_keys: HashMap<String, Vec<S>> = HashMap::new();
+    for (path, event) in &interpreter.events.duck_key_events {
+        if let Some((start_frame, samples)) =
+            render_choked(interpreter, event, &plugins, None, None)?
+        {
+            let key = duck_keys
+                .entry(path.clone())
+                .or_insert_with(|| vec![S::default(); total_samples * 2]);
+            mix_into(key, start_frame, &samples);
+        }
+    }
+    let mut tapped = HashMap::new();
+    let mut meters = InsertMeters::new();
+    // `strip master ...` needs the mixer even when no group has an insert of its own
+    if !group_buffers.is_empty() || interpreter.routing.strips.contains_key(MASTER_INSERT) {
+        (buffer, tapped, meters) = mix_group_inserts(
+            interpreter,
+            buffer,
+            group_buffers,
+            duck_keys,
+            total_samples,
+            taps,
+        );
+    }
+    let mut buffer: Vec<f32> = buffer.into_iter().map(MixSample::to_f32).collect();
+    let mut taps: OutputTaps = tapped
+        .into_iter()
+        .map(|(group, tap)| (group, tap.into_iter().map(MixSample::to_f32).collect()))
+        .collect();
+
+    let max_amplitude = buffer.iter().map(|&s| s.abs()).fold(0.0f32, f32::max);
+    log_info!(
+        logger,
+        "Max amplitude before normalization: {:.4}",
+        max_amplitude
+    );
+
+    if max_amplitude > 1.0 {
+        for sample in buffer.iter_mut().chain(taps.values_mut().flatten()) {
+            *sample /= max_amplitude;
+        }
+        for peak in meters.iter_mut().flat_map(|(_, peaks)| peaks) {
+            *peak /= max_amplitude;
+        }
+    }
+    if let Some(cache) = &interpreter.insert_cache
+        && let Ok(mut cache) = cache.lock()
+    {
+        cache.meters = meters;
+    }
+
+    Ok(Rendered {
+        master: buffer,
+        taps,
+        dropped_samples,
+    })
+}
+
+/// Whether `render_audio_streamed` can render this project. Groups without effects, strip
+/// or duck only sum into the master and stream like ungrouped events; routing
+/// graphs, processed group inserts, a master strip and live rebuilds splicing in cached
+/// inserts need the whole timeline at once, and the build falls back to an in-memory
+/// render with a warning.
+pub fn can_stream(interpreter: &AudioInterpreter) -> bool {
+    let routing = &interpreter.routing;
+    let passes_through = |group: &str| {
+        !matches!(
+            interpreter.events.group_effects.get(group),
+            Some(Value::Array(effects)) if !effects.is_empty()
+        ) && !routing.strips.contains_key(group)
+            && !routing
+                .ducks
+                .iter()
+                .any(|duck| duck.source == group || duck.destination == group)
+    };
+    interpreter.audio_graph.node_names().len() <= 1
+        && interpreter.insert_cache.is_none()
+        && !routing.strips.contains_key(MASTER_INSERT)
+        && interpreter.events.duck_key_events.is_empty()
+        && (0..interpreter.events.events.len()).all(|index| {
+            interpreter
+                .events
+                .group_path(index)
+                .is_none_or(|path| path.split('/').all(passes_through))
+        })
+}
+
+/// Render chunk by chunk, handing every `chunk_frames` block of interleaved stereo to
+/// `sink` once all events starting in it are mixed. Only that block and the tails of
+/// events still sounding are kept in memory. The whole render would be needed to
+/// peak-normalize, so the output is left as mixed; returns its peak level.
+pub fn render_audio_streamed(
+    interpreter: &AudioInterpreter,
+    chunk_frames: usize,
+    sink: &mut dyn FnMut(&[f32]) -> Result<()>,
+) -> Result<f32> {
+    match interpreter.mix.precision {
+        MixPrecision::F32 => stream_events::<f32>(interpreter, chunk_frames, sink),
+        MixPrecision::F64 => stream_events::<f64>(interpreter, chunk_frames, sink),
+    }
+}
+
+fn stream_events<S: MixSample>(
+    interpreter: &AudioInterpreter,
+    chunk_frames: usize,
+    sink: &mut dyn FnMut(&[f32]) -> Result<()>,
+) -> Result<f32> {
+    let total_duration = interpreter.calculate_total_duration();
+    if total_duration <= 0.0 {
+        return Ok(0.0);
+    }
+    let sample_rate = interpreter.sample_rate;
+    let total_frames = (total_duration * sample_rate as f32).ceil() as usize;
+    let chunk_frames = chunk_frames.max(1);
+
+    let events = &interpreter.events.events;
+    let start_frame_of = |event: &AudioEvent| match event {
+        AudioEvent::Note { start_time, .. }
+        | AudioEvent::Chord { start_time, .. }
+        | AudioEvent::Sample { start_time, .. } => frame_at(*start_time, sample_rate),
+    };
+    // Stable, so events on the same frame keep their mixing order
+    let chokes = choke::choke_times(events, choke::bank_choke_group);
+    let mut fades = retrigger_fades(interpreter);
+    let plugins = PluginNotes::new(&interpreter.events.events);
+    let mut order: Vec<usize> = (0..events.len()).collect();
+    order.sort_by_key(|&index| start_frame_of(&events[index]));
+
+    // Mixed audio from frame `written` on: the current chunk plus pending tails
+    let mut pending: Vec<S> = Vec::new();
+    let mut chunk: Vec<f32> = Vec::with_capacity(chunk_frames * 2);
+    let mut next = 0;
+    let mut written = 0;
+    let mut peak = 0.0f32;
+    while written < total_frames {
+        interpreter.check_deadline()?;
+        let chunk_end = (written + chunk_frames).min(total_frames);
+        while let Some(&index) = order.get(next) {
+            let event = &events[index];
+            if start_frame_of(event) >= chunk_end {
+                break;
+            }
+            next += 1;
+            if let Some((start_frame, samples)) = render_choked(
+                interpreter,
+                event,
+                &plugins,
+                chokes[index],
+                Some((&mut fades, index)),
+            )? {
+                let offset = start_frame.saturating_sub(written);
+                let end = (offset * 2 + samples.len()).min((total_frames - written) * 2);
+                if pending.len() < end {
+                    pending.resize(end, S::default());
                 }
+                mix_into(&mut pending[..end], offset, &samples);
+            }
+        }
 
-                let mut samples = if *use_per_note_automation {
-                    // Generate per-note automation with segments
-                    if let Some(automation_ctx) =
-                        interpreter.note_automation_templates.get(synth_id.as_str())
-                    {
-                        use crate::engine::audio::automation::evaluate_template_at;
+        let len = (chunk_end - written) * 2;
+        if pending.len() < len {
+            pending.resize(len, S::default());
+        }
+        chunk.clear();
+        chunk.extend(pending.drain(..len).map(MixSample::to_f32));
+        peak = chunk
+            .iter()
+            .fold(peak, |peak, sample| peak.max(sample.abs()));
+        sink(&chunk)?;
+        written = chunk_end;
+    }
+
+    Ok(peak)
+}
 
-                        let mut all_samples = Vec::new();
-                        let num_segments = 8; // Generate 8 segments per note for smooth automation
-                        let segment_duration = duration / num_segments as f32;
+/// Builds the interpreter for one pass of the program, events collected
+pub type PassBuilder = Box<dyn FnMut() -> Result<AudioInterpreter>>;
 
-                        for segment_idx in 0..num_segments {
-                            // Calculate progress for this segment (0.0 to 1.0)
-                            let segment_progress = (segment_idx as f32 + 0.5) / num_segments as f32;
+/// Pull-based renderer for hosts that ask for audio one block at a time (an
+/// AudioWorklet's `process`). Like `render_audio_streamed` it only keeps the tails of
+/// sounding events, but it advances only as far as each call asks. With `repeat`, a
+/// fresh pass of the program is collected whenever the last one runs out, so
+/// generative pieces keep playing (and keep rolling new random choices) indefinitely.
+/// A pass `can_stream` turns down (group inserts, routing graphs) is rendered whole,
+/// normalized like `render_audio`, when it begins.
+pub struct BlockStream {
+    build: PassBuilder,
+    repeat: bool,
+    pass: AudioInterpreter,
+    /// Event indices of the current pass, by start frame
+    order: Vec<usize>,
+    /// Events of the current pass below this index are already in `pending`: passes
+    /// `can_stream` turns down are rendered whole when they begin
+    premixed: usize,
+    chokes: Vec<Option<f32>>,
+    fades: RetriggerFades,
+    plugins: PluginNotes,
+    next: usize,
+    /// Stream frame the current pass started on, and its length in frames
+    pass_start: usize,
+    pass_frames: usize,
+    /// Mixed interleaved stereo from frame `position` on
+    pending: Vec<f32>,
+    position: usize,
+    /// Events that started since the last `take_started`, timed on the stream
+    started: Vec<AudioEvent>,
+    finished: bool,
+}
 
-                            // Evaluate templates for this progress point
-                            let segment_pan = automation_ctx
-                                .templates
-                                .iter()
-                                .find(|t| t.param_name == "pan")
-                                .map(|t| evaluate_template_at(t, segment_progress))
-                                .unwrap_or(*pan);
+impl BlockStream {
+    pub fn new(mut build: PassBuilder, repeat: bool) -> Result<Self> {
+        let pass = build()?;
+        let mut stream = Self {
+            build,
+            repeat,
+            pass,
+            order: Vec::new(),
+            premixed: 0,
+            chokes: Vec::new(),
+            fades: RetriggerFades::default(),
+            plugins: PluginNotes::default(),
+            next: 0,
+            pass_start: 0,
+            pass_frames: 0,
+            pending: Vec::new(),
+            position: 0,
+            started: Vec::new(),
+            finished: false,
+        };
+        stream.begin_pass(0)?;
+        Ok(stream)
+    }
 
-                            let segment_detune = automation_ctx
+    pub fn sample_rate(&self) -> u32 {
+        self.pass.sample_rate
+    }
+
+    /// Frames handed out so far; the playhead of a host that plays every block
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// True once a non-repeating stream has played its last tail
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Fill `out` with the next `out.len() / 2` frames of interleaved stereo. Returns
+    /// the frames produced; fewer (rest silent) once the stream has finished.
+    pub fn fill(&mut self, out: &mut [f32]) -> Result<usize> {
+        out.fill(0.0);
+        if self.finished {
+            return Ok(0);
+        }
+        let frames = out.len() / 2;
+        let block_end = self.position + frames;
+        loop {
+            self.mix_events_before(block_end)?;
+            let pass_end = self.pass_start + self.pass_frames;
+            if !self.looping() || self.next < self.order.len() || pass_end >= block_end {
+                break;
+            }
+            // The pass runs out inside this block: the next one starts on its boundary
+            self.pass = (self.build)()?;
+            self.begin_pass(pass_end)?;
+        }
+
+        let produced = if self.looping() {
+            frames
+        } else {
+            let end =
+                (self.pass_start + self.pass_frames).max(self.position + self.pending.len() / 2);
+            end.saturating_sub(self.position).min(frames)
+        };
+        let take = (produced * 2).min(self.pending.len());
+        for (slot, sample) in out.iter_mut().zip(self.pending.drain(..take)) {
+            *slot = sample;
+        }
+        self.position += produced;
+        self.finished = produced < frames;
+        Ok(produced)
+    }
+
+    /// Events started since the last call, with `start_time` on the stream timeline
+    pub fn take_started(&mut self) -> Vec<AudioEvent> {
+        std::mem::take(&mut self.started)
+    }
+
+    /// Run `collect` on the current pass with its cursor at `at` seconds of stream time
+    /// (or the playhead, if that has already passed) and play the events it adds, e.g.
+    /// those of an `on` handler fired by live input. Returns how many were added.
+    pub fn insert_events<F>(&mut self, at: f32, collect: F) -> Result<usize>
+    where
+        F: FnOnce(&mut AudioInterpreter) -> Result<()>,
+    {
+        let sample_rate = self.pass.sample_rate;
+        let frame = frame_at(at, sample_rate).max(self.position);
+        let before = self.pass.events.events.len();
+        let cursor = self.pass.cursor_time;
+        self.pass.cursor_time = frame.saturating_sub(self.pass_start) as f32 / sample_rate as f32;
+        let collected = collect(&mut self.pass);
+        self.pass.cursor_time = cursor;
+        collected?;
+
+        let events = &self.pass.events.events;
+        let added = events.len() - before;
+        if added > 0 {
+            self.chokes = choke::choke_times(events, choke::bank_choke_group);
+            self.fades
+                .replan(events, |index| self.pass.events.group_path(index));
+            self.plugins = PluginNotes::new(events);
+            self.order.extend(before..events.len());
+            self.order[self.next..]
+                .sort_by_key(|&index| frame_at(event_start(&events[index]), sample_rate));
+            self.finished = false;
+        }
+        Ok(added)
+    }
+
+    /// Whether another pass follows this one; a pass without events ends the stream
+    fn looping(&self) -> bool {
+        self.repeat && self.pass_frames > 0
+    }
+
+    fn begin_pass(&mut self, start: usize) -> Result<()> {
+        let events = &self.pass.events.events;
+        let sample_rate = self.pass.sample_rate;
+        self.chokes = choke::choke_times(events, choke::bank_choke_group);
+        self.fades = retrigger_fades(&self.pass);
+        self.plugins = PluginNotes::new(events);
+        self.order = (0..events.len()).collect();
+        self.order
+            .sort_by_key(|&index| frame_at(event_start(&events[index]), sample_rate));
+        self.next = 0;
+        self.pass_start = start;
+        self.pass_frames = (self.pass.events.total_duration() * sample_rate as f32).ceil() as usize;
+        self.premixed = 0;
+
+        if !can_stream(&self.pass) && !events.is_empty() {
+            // Group inserts and routing graphs need the whole pass: render it like
+            // `render_audio` does and hand it out block by block
+            let rendered = self.pass.render_audio()?;
+            let offset = start.saturating_sub(self.position);
+            let needed = offset * 2 + rendered.len();
+            if self.pending.len() < needed {
+                self.pending.resize(needed, 0.0);
+            }
+            mix_into(&mut self.pending, offset, &rendered);
+            self.pass_frames = self.pass_frames.max(rendered.len() / 2);
+            self.premixed = self.pass.events.events.len();
+        }
+        Ok(())
+    }
+
+    /// Render and mix every event of the current pass starting before stream frame `end`
+    fn mix_events_before(&mut self, end: usize) -> Result<()> {
+        let sample_rate = self.pass.sample_rate;
+        let offset_seconds = self.pass_start as f32 / sample_rate as f32;
+        while let Some(&index) = self.order.get(self.next) {
+            let event = &self.pass.events.events[index];
+            if self.pass_start + frame_at(event_start(event), sample_rate) >= end {
+                break;
+            }
+            self.next += 1;
+            let mut shifted = event.clone();
+            match &mut shifted {
+                AudioEvent::Note { start_time, .. }
+                | AudioEvent::Chord { start_time, .. }
+                | AudioEvent::Sample { start_time, .. } => *start_time += offset_seconds,
+            }
+            self.started.push(shifted);
+            if index < self.premixed {
+                continue;
+            }
+
+            let Some((start_frame, samples)) = render_choked(
+                &self.pass,
+                event,
+                &self.plugins,
+                self.chokes[index],
+                Some((&mut self.fades, index)),
+            )?
+            else {
+                continue;
+            };
+            // Events are only mixed once their block is requested, so none start behind
+            // the playhead except by rounding
+            let offset = (self.pass_start + start_frame).saturating_sub(self.position);
+            let needed = offset * 2 + samples.len();
+            if self.pending.len() < needed {
+                self.pending.resize(needed, 0.0);
+            }
+            mix_into(&mut self.pending, offset, &samples);
+        }
+        Ok(())
+    }
+}
+
+fn event_start(event: &AudioEvent) -> f32 {
+    match event {
+        AudioEvent::Note { start_time, .. }
+        | AudioEvent::Chord { start_time, .. }
+        | AudioEvent::Sample { start_time, .. } => *start_time,
+    }
+}
+
+/// Frame an event starting at `start_time` seconds is placed on
+fn frame_at(start_time: f32, sample_rate: u32) -> usize {
+    (start_time * sample_rate as f32) as usize
+}
+
+/// Add interleaved stereo `samples` into `buffer` from `start_frame`, dropping what
+/// runs past its end
+fn mix_into<S: MixSample>(buffer: &mut [S], start_frame: usize, samples: &[f32]) {
+    let Some(slots) = buffer.get_mut(start_frame * 2..) else {
+        return;
+    };
+    for (slot, &sample) in slots.iter_mut().zip(samples) {
+        *slot += S::from_f32(sample);
+    }
+}
+
+/// Retrigger crossfades for the events of `interpreter`, per group insert
+fn retrigger_fades(interpreter: &AudioInterpreter) -> RetriggerFades {
+    RetriggerFades::new(
+        &interpreter.events.events,
+        |index| interpreter.events.group_path(index),
+        interpreter.mix.retrigger_fade_ms,
+        interpreter.sample_rate,
+    )
+}
+
+/// `render_event`, crossfaded with retriggers of the same sample (`fades`, with the
+/// event's index) and cut short at `choke_at` (seconds) when a later trigger of its
+/// choke group starts while it still rings
+fn render_choked(
+    interpreter: &AudioInterpreter,
+    event: &AudioEvent,
+    plugins: &PluginNotes,
+    choke_at: Option<f32>,
+    fades: Option<(&mut RetriggerFades, usize)>,
+) -> Result<Option<(usize, Vec<f32>)>> {
+    let rendered = render_event(interpreter, event, plugins)?;
+    Ok(rendered.map(|(start_frame, mut samples)| {
+        if let Some((fades, index)) = fades {
+            fades.apply(index, start_frame, &mut samples, 2);
+        }
+        if let Some(at) = choke_at {
+            let keep = frame_at(at, interpreter.sample_rate).saturating_sub(start_frame);
+            choke::cut(&mut samples, 2, keep, interpreter.sample_rate);
+        }
+        (start_frame, samples)
+    }))
+}
+
+/// Render one event to interleaved stereo, with the frame it starts on. Events that
+/// produce nothing (missing samples) yield `None`.
+fn render_event(
+    interpreter: &AudioInterpreter,
+    event: &AudioEvent,
+    plugins: &PluginNotes,
+) -> Result<Option<(usize, Vec<f32>)>> {
+    #[cfg(feature = "cli")]
+    let logger = crate::tools::logger::Logger::new();
+    #[cfg(not(feature = "cli"))]
+    let _logger = ();
+
+    match event {
+        crate::engine::audio::events::AudioEvent::Note {
+            midi,
+            start_time,
+            duration,
+            velocity,
+            synth_id,
+            synth_def,
+            pan,
+            detune,
+            gain,
+            attack,
+            release,
+            delay_time,
+            delay_feedback,
+            delay_mix,
+            reverb_amount,
+            drive_amount,
+            drive_color,
+            use_per_note_automation,
+            ..
+        } => {
+            // Log note rendering only if needed (debug mode)
+
+            let mut params = SynthParams {
+                waveform: synth_def.waveform.clone(),
+                attack: synth_def.attack,
+                decay: synth_def.decay,
+                sustain: synth_def.sustain,
+                release: synth_def.release,
+                curves: synth_def.curves,
+                synth_type: synth_def.synth_type.clone(),
+                filters: synth_def.filters.clone(),
+                options: synth_def.options.clone(),
+                lfo: synth_def.lfo.clone(),
+                plugin_author: synth_def.plugin_author.clone(),
+                plugin_name: synth_def.plugin_name.clone(),
+                plugin_export: synth_def.plugin_export.clone(),
+                plugin_context: None,
+                automation: BlockAutomation::default(),
+            };
+
+            if let Some(a) = attack {
+                params.attack = a / 1000.0;
+            }
+            if let Some(r) = release {
+                params.release = r / 1000.0;
+            }
+            if params.plugin_author.is_some() {
+                params.plugin_context =
+                    Some(plugins.context_for(interpreter, synth_id, *start_time, *duration));
+            }
+
+            let mut samples = if *use_per_note_automation {
+                // Generate per-note automation with segments
+                if let Some(automation_ctx) =
+                    interpreter.note_automation_templates.get(synth_id.as_str())
+                {
+                    use crate::engine::audio::automation::evaluate_template_at;
+
+                    let mut all_samples = Vec::new();
+                    let num_segments = 8; // Generate 8 segments per note for smooth automation
+                    let segment_duration = duration / num_segments as f32;
+
+                    for segment_idx in 0..num_segments {
+                        // Calculate progress for this segment (0.0 to 1.0)
+                        let segment_progress = (segment_idx as f32 + 0.5) / num_segments as f32;
+
+                        // Evaluate templates for this progress point
+                        let segment_pan = automation_ctx
+                            .templates
+                            .iter()
+                            .find(|t| t.param_name == "pan")
+                            .map(|t| evaluate_template_at(t, segment_progress))
+                            .unwrap_or(*pan);
+
+                        let segment_detune = automation_ctx
+                            .templates
+                            .iter()
+                            .find(|t| t.param_name == "pitch" || t.param_name == "detune")
+                            .map(|t| evaluate_template_at(t, segment_progress))
+                            .unwrap_or(*detune);
+
+                        let segment_gain = automation_ctx
+                            .templates
+                            .iter()
+                            .find(|t| t.param_name == "volume" || t.param_name == "gain")
+                            .map(|t| evaluate_template_at(t, segment_progress))
+                            .unwrap_or(*gain);
+
+                        // Clone and modify params for this segment
+                        let mut segment_params = params.clone();
+
+                        // Apply cutoff automation
+                        for filter in &mut segment_params.filters {
+                            let segment_cutoff = automation_ctx
                                 .templates
                                 .iter()
-                                .find(|t| t.param_name == "pitch" || t.param_name == "detune")
+                                .find(|t| t.param_name == "cutoff")
                                 .map(|t| evaluate_template_at(t, segment_progress))
-                                .unwrap_or(*detune);
+                                .unwrap_or(filter.cutoff);
+                            filter.cutoff = segment_cutoff;
 
-                            let segment_gain = automation_ctx
+                            let segment_resonance = automation_ctx
                                 .templates
                                 .iter()
-                                .find(|t| t.param_name == "volume" || t.param_name == "gain")
+                                .find(|t| t.param_name == "resonance")
                                 .map(|t| evaluate_template_at(t, segment_progress))
-                                .unwrap_or(*gain);
-
-                            // Clone and modify params for this segment
-                            let mut segment_params = params.clone();
-
-                            // Apply cutoff automation
-                            for filter in &mut segment_params.filters {
-                                let segment_cutoff = automation_ctx
-                                    .templates
-                                    .iter()
-                                    .find(|t| t.param_name == "cutoff")
-                                    .map(|t| evaluate_template_at(t, segment_progress))
-                                    .unwrap_or(filter.cutoff);
-                                filter.cutoff = segment_cutoff;
-
-                                let segment_resonance = automation_ctx
-                                    .templates
-                                    .iter()
-                                    .find(|t| t.param_name == "resonance")
-                                    .map(|t| evaluate_template_at(t, segment_progress))
-                                    .unwrap_or(filter.resonance);
-                                filter.resonance = segment_resonance;
-                            }
-
-                            // Generate this segment with updated params
-                            let segment_samples = generate_note_with_options(
-                                *midi,
-                                segment_duration * 1000.0,
-                                (velocity * segment_gain).clamp(0.0, 1.0),
-                                &segment_params,
-                                interpreter.sample_rate,
-                                segment_pan,
-                                segment_detune,
-                            )?;
-
-                            all_samples.extend(segment_samples);
+                                .unwrap_or(filter.resonance);
+                            filter.resonance = segment_resonance;
                         }
 
-                        all_samples
-                    } else {
-                        // No templates found, generate normally
-                        generate_note_with_options(
+                        // Generate this segment with updated params
+                        let segment_samples = generate_note_with_options(
                             *midi,
-                            duration * 1000.0,
-                            velocity * gain,
-                            &params,
+                            segment_duration * 1000.0,
+                            (velocity * segment_gain).clamp(0.0, 1.0),
+                            &segment_params,
                             interpreter.sample_rate,
-                            *pan,
-                            *detune,
-                        )?
+                            segment_pan,
+                            segment_detune,
+                        )?;
+
+                        // Segments carry their own release tail, so overlap them at
+                        // their start instead of appending
+                        let offset = (segment_duration
+                            * segment_idx as f32
+                            * interpreter.sample_rate as f32)
+                            .round() as usize
+                            * 2;
+                        if all_samples.len() < offset + segment_samples.len() {
+                            all_samples.resize(offset + segment_samples.len(), 0.0);
+                        }
+                        for (dst, src) in all_samples[offset..].iter_mut().zip(&segment_samples) {
+                            *dst += src;
+                        }
                     }
+
+                    all_samples
                 } else {
-                    // Generate note normally (global mode or no automation)
+                    // No templates found, generate normally
                     generate_note_with_options(
                         *midi,
                         duration * 1000.0,
@@ -240,155 +939,45 @@ pub fn render_audio(interpreter: &AudioInterpreter) -> Result<Vec<f32>> {
                         *pan,
                         *detune,
                     )?
-                };
-
-                // If this event has an effects map/array, build an effect chain and apply it.
-                // Also avoid double-applying drive/reverb/delay when those keys appear in the effects map.
-                let mut skip_drive = false;
-                let mut skip_reverb = false;
-                let mut skip_delay = false;
-                let mut effect_chain: Option<EffectChain> = None;
-
-                // Try to extract per-event effects (if present) and build a chain
-                if let crate::engine::audio::events::AudioEvent::Note { effects, .. } = event {
-                    if let Some(eff_val) = effects {
-                        match eff_val {
-                            crate::language::syntax::ast::Value::Array(arr) => {
-                                let chain = build_effect_chain(arr, true);
-                                if !chain.is_empty() {
-                                    effect_chain = Some(chain);
-                                }
-                            }
-                            crate::language::syntax::ast::Value::Map(_) => {
-                                let normalized = normalize_effects(&Some(eff_val.clone()));
-                                if !normalized.is_empty() {
-                                    let mut chain = EffectChain::new(true);
-                                    for (k, v) in normalized.into_iter() {
-                                        chain.add_effect(
-                                            &k,
-                                            Some(crate::language::syntax::ast::Value::Map(v)),
-                                        );
-                                        // mark skips for known audio processors
-                                        match k.as_str() {
-                                            "drive" => skip_drive = true,
-                                            "reverb" => skip_reverb = true,
-                                            "delay" => skip_delay = true,
-                                            _ => {}
-                                        }
-                                    }
-                                    effect_chain = Some(chain);
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
                 }
-
-                if let Some(chain) = effect_chain.as_mut() {
-                    chain.process(&mut samples, interpreter.sample_rate);
-                }
-
-                // Apply legacy per-field processors only when not overridden by the effects map
-                if let Some(amount) = drive_amount {
-                    if !skip_drive {
-                        let color = drive_color.unwrap_or(0.5);
-                        let mix = 0.7;
-                        // tone default to 0.5, color passed from drive_color
-                        let mut processor = DriveProcessor::new(*amount, 0.5, color, mix);
-                        processor.process(&mut samples, interpreter.sample_rate);
-                    }
-                }
-                if let Some(amount) = reverb_amount {
-                    if !skip_reverb {
-                        let room_size = *amount;
-                        let damping = 0.5;
-                        let decay = 0.5;
-                        let mix = *amount * 0.5;
-                        let mut processor = ReverbProcessor::new(room_size, damping, decay, mix);
-                        processor.process(&mut samples, interpreter.sample_rate);
-                    }
-                }
-                if let Some(time) = delay_time {
-                    if !skip_delay {
-                        let feedback = delay_feedback.unwrap_or(0.3);
-                        let mix = delay_mix.unwrap_or(0.5);
-                        let mut processor = DelayProcessor::new(*time, feedback, mix);
-                        processor.process(&mut samples, interpreter.sample_rate);
-                    }
-                }
-
-                let start_sample = (*start_time * interpreter.sample_rate as f32) as usize * 2;
-                for (i, &sample) in samples.iter().enumerate() {
-                    let buf_idx = start_sample + i;
-                    if buf_idx < buffer.len() {
-                        buffer[buf_idx] += sample;
-                    }
-                }
-            }
-
-            crate::engine::audio::events::AudioEvent::Chord {
-                midis,
-                start_time,
-                duration,
-                velocity,
-                synth_id: _,
-                synth_def,
-                pan,
-                detune,
-                spread,
-                gain,
-                attack,
-                release,
-                delay_time,
-                delay_feedback,
-                delay_mix,
-                reverb_amount,
-                drive_amount,
-                drive_color,
-                effects,
-                use_per_note_automation: _,
-            } => {
-                let mut params = SynthParams {
-                    waveform: synth_def.waveform.clone(),
-                    attack: synth_def.attack,
-                    decay: synth_def.decay,
-                    sustain: synth_def.sustain,
-                    release: synth_def.release,
-                    synth_type: synth_def.synth_type.clone(),
-                    filters: synth_def.filters.clone(),
-                    options: synth_def.options.clone(),
-                    lfo: synth_def.lfo.clone(),
-                    plugin_author: synth_def.plugin_author.clone(),
-                    plugin_name: synth_def.plugin_name.clone(),
-                    plugin_export: synth_def.plugin_export.clone(),
+            } else {
+                // Generate note normally (global mode or no automation)
+                params.automation =
+                    formula_automation(interpreter, synth_id, *start_time, *gain, *pan);
+                // A gain formula takes the place of the event gain
+                let gain = if params.automation.gain.is_some() {
+                    1.0
+                } else {
+                    *gain
                 };
-                if let Some(a) = attack {
-                    params.attack = a / 1000.0;
-                }
-                if let Some(r) = release {
-                    params.release = r / 1000.0;
-                }
-
-                let mut samples = generate_chord_with_options(
-                    midis,
+                generate_note_with_options(
+                    *midi,
                     duration * 1000.0,
                     velocity * gain,
                     &params,
                     interpreter.sample_rate,
                     *pan,
                     *detune,
-                    *spread,
-                )?;
-
-                // Build and apply effect chain if per-event effects are present
-                let mut skip_drive = false;
-                let mut skip_reverb = false;
-                let mut skip_delay = false;
-                let mut effect_chain: Option<EffectChain> = None;
+                )?
+            };
+
+            // If this event has an effects map/array, build an effect chain and apply it.
+            // Also avoid double-applying drive/reverb/delay when those keys appear in the effects map.
+            let mut skip_drive = false;
+            let mut skip_reverb = false;
+            let mut skip_delay = false;
+            let mut effect_chain: Option<EffectChain> = None;
+
+            // Try to extract per-event effects (if present) and build a chain
+            if let crate::engine::audio::events::AudioEvent::Note { effects, .. } = event {
                 if let Some(eff_val) = effects {
                     match eff_val {
                         crate::language::syntax::ast::Value::Array(arr) => {
-                            let chain = build_effect_chain(arr, true);
+                            let chain = build_effect_chain_with(
+                                Arc::clone(&interpreter.effect_registry),
+                                arr,
+                                true,
+                            );
                             if !chain.is_empty() {
                                 effect_chain = Some(chain);
                             }
@@ -396,12 +985,16 @@ pub fn render_audio(interpreter: &AudioInterpreter) -> Result<Vec<f32>> {
                         crate::language::syntax::ast::Value::Map(_) => {
                             let normalized = normalize_effects(&Some(eff_val.clone()));
                             if !normalized.is_empty() {
-                                let mut chain = EffectChain::new(true);
+                                let mut chain = EffectChain::with_registry(
+                                    Arc::clone(&interpreter.effect_registry),
+                                    true,
+                                );
                                 for (k, v) in normalized.into_iter() {
                                     chain.add_effect(
                                         &k,
                                         Some(crate::language::syntax::ast::Value::Map(v)),
                                     );
+                                    // mark skips for known audio processors
                                     match k.as_str() {
                                         "drive" => skip_drive = true,
                                         "reverb" => skip_reverb = true,
@@ -415,170 +1008,619 @@ pub fn render_audio(interpreter: &AudioInterpreter) -> Result<Vec<f32>> {
                         _ => {}
                     }
                 }
+            }
 
-                if let Some(chain) = effect_chain.as_mut() {
-                    chain.process(&mut samples, interpreter.sample_rate);
+            if let Some(chain) = effect_chain.as_mut() {
+                chain.process(&mut samples, interpreter.sample_rate);
+            }
+
+            // Apply legacy per-field processors only when not overridden by the effects map
+            if let Some(amount) = drive_amount {
+                if !skip_drive {
+                    let color = drive_color.unwrap_or(0.5);
+                    let mix = 0.7;
+                    // tone default to 0.5, color passed from drive_color
+                    let mut processor = DriveProcessor::new(*amount, 0.5, color, mix);
+                    processor.process(&mut samples, interpreter.sample_rate);
+                }
+            }
+            if let Some(amount) = reverb_amount {
+                if !skip_reverb {
+                    let room_size = *amount;
+                    let damping = 0.5;
+                    let decay = 0.5;
+                    let mix = *amount * 0.5;
+                    let mut processor = ReverbProcessor::new(room_size, damping, decay, mix);
+                    processor.process(&mut samples, interpreter.sample_rate);
+                }
+            }
+            if let Some(time) = delay_time {
+                if !skip_delay {
+                    let feedback = delay_feedback.unwrap_or(0.3);
+                    let mix = delay_mix.unwrap_or(0.5);
+                    let mut processor = DelayProcessor::new(*time, feedback, mix);
+                    processor.process(&mut samples, interpreter.sample_rate);
                 }
+            }
+
+            Ok(Some((
+                frame_at(*start_time, interpreter.sample_rate),
+                samples,
+            )))
+        }
+
+        crate::engine::audio::events::AudioEvent::Chord {
+            midis,
+            start_time,
+            duration,
+            velocity,
+            synth_id,
+            synth_def,
+            pan,
+            detune,
+            spread,
+            gain,
+            attack,
+            release,
+            delay_time,
+            delay_feedback,
+            delay_mix,
+            reverb_amount,
+            drive_amount,
+            drive_color,
+            effects,
+            use_per_note_automation: _,
+        } => {
+            let mut params = SynthParams {
+                waveform: synth_def.waveform.clone(),
+                attack: synth_def.attack,
+                decay: synth_def.decay,
+                sustain: synth_def.sustain,
+                release: synth_def.release,
+                curves: synth_def.curves,
+                synth_type: synth_def.synth_type.clone(),
+                filters: synth_def.filters.clone(),
+                options: synth_def.options.clone(),
+                lfo: synth_def.lfo.clone(),
+                plugin_author: synth_def.plugin_author.clone(),
+                plugin_name: synth_def.plugin_name.clone(),
+                plugin_export: synth_def.plugin_export.clone(),
+                plugin_context: None,
+                automation: BlockAutomation::default(),
+            };
+            if let Some(a) = attack {
+                params.attack = a / 1000.0;
+            }
+            if let Some(r) = release {
+                params.release = r / 1000.0;
+            }
+            if params.plugin_author.is_some() {
+                params.plugin_context =
+                    Some(plugins.context_for(interpreter, synth_id, *start_time, *duration));
+            }
+
+            // Chord pan stays static so the note spread is kept
+            params.automation = BlockAutomation {
+                pan: None,
+                ..formula_automation(interpreter, synth_id, *start_time, *gain, *pan)
+            };
+            let gain = if params.automation.gain.is_some() {
+                1.0
+            } else {
+                *gain
+            };
+            let mut samples = generate_chord_with_options(
+                midis,
+                duration * 1000.0,
+                velocity * gain,
+                &params,
+                interpreter.sample_rate,
+                *pan,
+                *detune,
+                *spread,
+            )?;
 
-                if let Some(amount) = drive_amount {
-                    if !skip_drive {
-                        let color = drive_color.unwrap_or(0.5);
-                        let mix = 0.7;
-                        let mut processor = DriveProcessor::new(*amount, 0.5, color, mix);
-                        processor.process(&mut samples, interpreter.sample_rate);
+            // Build and apply effect chain if per-event effects are present
+            let mut skip_drive = false;
+            let mut skip_reverb = false;
+            let mut skip_delay = false;
+            let mut effect_chain: Option<EffectChain> = None;
+            if let Some(eff_val) = effects {
+                match eff_val {
+                    crate::language::syntax::ast::Value::Array(arr) => {
+                        let chain = build_effect_chain_with(
+                            Arc::clone(&interpreter.effect_registry),
+                            arr,
+                            true,
+                        );
+                        if !chain.is_empty() {
+                            effect_chain = Some(chain);
+                        }
                     }
-                }
-                if let Some(amount) = reverb_amount {
-                    if !skip_reverb {
-                        let room_size = *amount;
-                        let damping = 0.5;
-                        let decay = 0.5;
-                        let mix = *amount * 0.5;
-                        let mut processor = ReverbProcessor::new(room_size, damping, decay, mix);
-                        processor.process(&mut samples, interpreter.sample_rate);
+                    crate::language::syntax::ast::Value::Map(_) => {
+                        let normalized = normalize_effects(&Some(eff_val.clone()));
+                        if !normalized.is_empty() {
+                            let mut chain = EffectChain::with_registry(
+                                Arc::clone(&interpreter.effect_registry),
+                                true,
+                            );
+                            for (k, v) in normalized.into_iter() {
+                                chain.add_effect(
+                                    &k,
+                                    Some(crate::language::syntax::ast::Value::Map(v)),
+                                );
+                                match k.as_str() {
+                                    "drive" => skip_drive = true,
+                                    "reverb" => skip_reverb = true,
+                                    "delay" => skip_delay = true,
+                                    _ => {}
+                                }
+                            }
+                            effect_chain = Some(chain);
+                        }
                     }
+                    _ => {}
                 }
-                if let Some(time) = delay_time {
-                    if !skip_delay {
-                        let feedback = delay_feedback.unwrap_or(0.3);
-                        let mix = delay_mix.unwrap_or(0.5);
-                        let mut processor = DelayProcessor::new(*time, feedback, mix);
-                        processor.process(&mut samples, interpreter.sample_rate);
-                    }
+            }
+
+            if let Some(chain) = effect_chain.as_mut() {
+                chain.process(&mut samples, interpreter.sample_rate);
+            }
+
+            if let Some(amount) = drive_amount {
+                if !skip_drive {
+                    let color = drive_color.unwrap_or(0.5);
+                    let mix = 0.7;
+                    let mut processor = DriveProcessor::new(*amount, 0.5, color, mix);
+                    processor.process(&mut samples, interpreter.sample_rate);
                 }
+            }
+            if let Some(amount) = reverb_amount {
+                if !skip_reverb {
+                    let room_size = *amount;
+                    let damping = 0.5;
+                    let decay = 0.5;
+                    let mix = *amount * 0.5;
+                    let mut processor = ReverbProcessor::new(room_size, damping, decay, mix);
+                    processor.process(&mut samples, interpreter.sample_rate);
+                }
+            }
+            if let Some(time) = delay_time {
+                if !skip_delay {
+                    let feedback = delay_feedback.unwrap_or(0.3);
+                    let mix = delay_mix.unwrap_or(0.5);
+                    let mut processor = DelayProcessor::new(*time, feedback, mix);
+                    processor.process(&mut samples, interpreter.sample_rate);
+                }
+            }
+
+            Ok(Some((
+                frame_at(*start_time, interpreter.sample_rate),
+                samples,
+            )))
+        }
+
+        crate::engine::audio::events::AudioEvent::Sample {
+            uri,
+            start_time,
+            velocity,
+            effects: _effects,
+            note: _note,
+            automation,
+            region,
+        } => {
+            // Without a sample source (plugin builds) the trigger renders nothing
+            #[cfg(not(any(feature = "cli", feature = "wasm")))]
+            let _ = (uri, start_time, velocity, automation, region);
 
-                let start_sample = (*start_time * interpreter.sample_rate as f32) as usize * 2;
-                for (i, &sample) in samples.iter().enumerate() {
-                    let buf_idx = start_sample + i;
-                    if buf_idx < buffer.len() {
-                        buffer[buf_idx] += sample;
+            // Note-mode templates ramping across this trigger (gain, pitch, cutoff)
+            #[cfg(any(feature = "cli", feature = "wasm"))]
+            let automation_ctx = automation
+                .as_deref()
+                .and_then(|target| interpreter.note_automation_templates.get(target));
+            #[cfg(any(feature = "cli", feature = "wasm"))]
+            let start_frame = frame_at(*start_time, interpreter.sample_rate);
+
+            // WASM path: samples are provided by the web registry as i16 PCM
+            #[cfg(feature = "wasm")]
+            {
+                use crate::web::registry::samples::get_sample;
+                if let Some(pcm_data) = get_sample(uri) {
+                    let mut pcm_data: Vec<f32> =
+                        pcm_data.iter().map(|&v| v as f32 / 32768.0).collect();
+                    if let Some(region) = region {
+                        pcm_data = region.apply(&pcm_data, interpreter.sample_rate);
+                    }
+                    if let Some(ctx) = automation_ctx {
+                        pcm_data = crate::engine::audio::automation::apply_templates_to_sample(
+                            &pcm_data,
+                            &ctx.templates,
+                            interpreter.sample_rate,
+                        );
                     }
+                    let stereo = pcm_data
+                        .iter()
+                        .flat_map(|&pcm_value| [pcm_value * velocity; 2])
+                        .collect();
+                    return Ok(Some((start_frame, stereo)));
+                } else {
+                    log_warn!(logger, "Sample not found in registry: {}", uri);
                 }
             }
 
-            crate::engine::audio::events::AudioEvent::Sample {
-                uri,
-                start_time,
-                velocity,
-                effects: _effects,
-            } => {
-                sample_count += 1;
-                // Log sample rendering only if needed (debug mode)
+            // CLI/native path: use SampleData (mono f32) and resample/scale into stereo buffer
+            #[cfg(feature = "cli")]
+            {
+                use crate::engine::audio::samples;
+                use crate::engine::audio::samples::variants::split_leading_variant;
+                // A leading reverse/speed over the whole sample comes from the variant cache
+                let variant = match (region, _note) {
+                    (None, None) => _effects.as_ref().and_then(split_leading_variant),
+                    _ => None,
+                };
+                let cached = variant.and_then(|(variant, rest)| {
+                    let data = samples::get_sample_variant(
+                        uri,
+                        interpreter.sample_rate,
+                        interpreter.resample_quality,
+                        variant,
+                    )?;
+                    Some((data, rest))
+                });
+                let (sample_data, _effects) = match cached {
+                    Some((data, rest)) => (Some(data), Some(rest)),
+                    None => (
+                        samples::get_sample_for_note(
+                            uri,
+                            interpreter.sample_rate,
+                            interpreter.resample_quality,
+                            *_note,
+                        ),
+                        _effects.clone(),
+                    ),
+                };
+                if let Some(sample_data) = sample_data {
+                    // velocity is in 0.0..1.0 range for sample events
+                    let velocity_scale = *velocity;
 
-                // WASM path: samples are provided by the web registry as i16 PCM
-                #[cfg(feature = "wasm")]
-                {
-                    use crate::web::registry::samples::get_sample;
-                    if let Some(pcm_data) = get_sample(uri) {
-                        let start_sample_idx =
-                            (*start_time * interpreter.sample_rate as f32) as usize;
-                        for (i, &pcm_value) in pcm_data.iter().enumerate() {
-                            let sample = (pcm_value as f32 / 32768.0) * velocity;
-                            let stereo_pos = (start_sample_idx + i) * 2;
-                            let buf_idx_l = stereo_pos;
-                            let buf_idx_r = stereo_pos + 1;
-                            if buf_idx_l < buffer.len() {
-                                buffer[buf_idx_l] += sample;
-                            }
-                            if buf_idx_r < buffer.len() {
-                                buffer[buf_idx_r] += sample;
-                            }
-                        }
-                    } else {
-                        log_warn!(logger, "Sample not found in registry: {}", uri);
+                    // Make a mutable copy so we can run effects on it (effects expect f32 slices),
+                    // cut down to the trigger's region when it sets one
+                    let mut proc_samples = match region {
+                        Some(region) => region.apply(&sample_data.samples, sample_data.sample_rate),
+                        None => sample_data.samples.clone(),
+                    };
+                    // Samples come back already converted (and cached) at the project
+                    // rate; convert here, at the configured quality, if that was skipped
+                    if sample_data.sample_rate != interpreter.sample_rate {
+                        proc_samples = samples::resample(
+                            &proc_samples,
+                            sample_data.sample_rate,
+                            interpreter.sample_rate,
+                            interpreter.resample_quality,
+                        );
                     }
-                }
 
-                // CLI/native path: use SampleData (mono f32) and resample/scale into stereo buffer
-                #[cfg(feature = "cli")]
-                {
-                    use crate::engine::audio::samples;
-                    if let Some(sample_data) = samples::get_sample(uri) {
-                        let start_sample_idx =
-                            (*start_time * interpreter.sample_rate as f32) as usize;
-                        // velocity is in 0.0..1.0 range for sample events
-                        let velocity_scale = velocity;
-                        let resample_ratio =
-                            interpreter.sample_rate as f32 / sample_data.sample_rate as f32;
-
-                        // Make a mutable copy so we can run effects on it (effects expect f32 slices)
-                        let mut proc_samples = sample_data.samples.clone();
-
-                        // Build and apply effect chain for sample events (trigger context)
-                        let mut sample_chain: Option<EffectChain> = None;
-                        if let Some(eff_val) = _effects {
-                            match eff_val {
-                                crate::language::syntax::ast::Value::Array(arr) => {
-                                    let chain = build_effect_chain(arr, false);
-                                    if !chain.is_empty() {
-                                        sample_chain = Some(chain);
-                                    }
+                    // Build and apply effect chain for sample events (trigger context)
+                    let mut sample_chain: Option<EffectChain> = None;
+                    if let Some(eff_val) = &_effects {
+                        match eff_val {
+                            crate::language::syntax::ast::Value::Array(arr) => {
+                                let chain = build_effect_chain_with(
+                                    Arc::clone(&interpreter.effect_registry),
+                                    arr,
+                                    false,
+                                );
+                                if !chain.is_empty() {
+                                    sample_chain = Some(chain);
                                 }
-                                crate::language::syntax::ast::Value::Map(_) => {
-                                    let normalized = normalize_effects(&Some(eff_val.clone()));
-                                    if !normalized.is_empty() {
-                                        let mut chain = EffectChain::new(false);
-                                        for (k, v) in normalized.into_iter() {
-                                            chain.add_effect(
-                                                &k,
-                                                Some(crate::language::syntax::ast::Value::Map(v)),
-                                            );
-                                        }
-                                        sample_chain = Some(chain);
+                            }
+                            crate::language::syntax::ast::Value::Map(_) => {
+                                let normalized = normalize_effects(&Some(eff_val.clone()));
+                                if !normalized.is_empty() {
+                                    let mut chain = EffectChain::with_registry(
+                                        Arc::clone(&interpreter.effect_registry),
+                                        false,
+                                    );
+                                    for (k, v) in normalized.into_iter() {
+                                        chain.add_effect(
+                                            &k,
+                                            Some(crate::language::syntax::ast::Value::Map(v)),
+                                        );
                                     }
+                                    sample_chain = Some(chain);
                                 }
-                                _ => {}
                             }
+                            _ => {}
+                        }
+                    }
+
+                    let mut stereo: Vec<f32> = Vec::with_capacity(proc_samples.len() * 2);
+                    let mut mix_frame = |i: usize, left: f32, right: f32| {
+                        let pos = i * 2;
+                        if stereo.len() < pos + 2 {
+                            stereo.resize(pos + 2, 0.0);
                         }
+                        stereo[pos] += left * velocity_scale;
+                        stereo[pos + 1] += right * velocity_scale;
+                    };
 
+                    // Spatial effects (binaural, mid/side) need a real stereo image: automate
+                    // the mono trigger first, then run the chain on an interleaved upmix
+                    if sample_chain
+                        .as_ref()
+                        .is_some_and(|chain| chain.needs_stereo())
+                    {
+                        if let Some(ctx) = automation_ctx {
+                            proc_samples =
+                                crate::engine::audio::automation::apply_templates_to_sample(
+                                    &proc_samples,
+                                    &ctx.templates,
+                                    interpreter.sample_rate,
+                                );
+                        }
+                        let mut upmix: Vec<f32> =
+                            proc_samples.iter().flat_map(|&s| [s, s]).collect();
+                        if let Some(chain) = sample_chain.as_mut() {
+                            chain.process(&mut upmix, interpreter.sample_rate);
+                        }
+                        for (i, frame) in upmix.chunks_exact(2).enumerate() {
+                            mix_frame(i, frame[0], frame[1]);
+                        }
+                    } else {
                         if let Some(chain) = sample_chain.as_mut() {
                             chain.process(&mut proc_samples, interpreter.sample_rate);
                         }
+                        if let Some(ctx) = automation_ctx {
+                            proc_samples =
+                                crate::engine::audio::automation::apply_templates_to_sample(
+                                    &proc_samples,
+                                    &ctx.templates,
+                                    interpreter.sample_rate,
+                                );
+                        }
 
                         for (i, &sample) in proc_samples.iter().enumerate() {
-                            let output_idx =
-                                start_sample_idx + (i as f32 * resample_ratio) as usize;
-                            let stereo_pos = output_idx * 2;
-                            let buf_idx_l = stereo_pos;
-                            let buf_idx_r = stereo_pos + 1;
-                            let scaled_sample = sample * velocity_scale;
-                            if buf_idx_l < buffer.len() {
-                                buffer[buf_idx_l] += scaled_sample;
-                            }
-                            if buf_idx_r < buffer.len() {
-                                buffer[buf_idx_r] += scaled_sample;
-                            }
+                            mix_frame(i, sample, sample);
                         }
-                    } else {
-                        log_error!(logger, "Bank sample not found: {}", uri);
                     }
+                    return Ok(Some((start_frame, stereo)));
+                } else {
+                    log_error!(logger, "Bank sample not found: {}", uri);
                 }
-            } // Log events are stored separately and ignored by renderer
+            }
+
+            Ok(None)
         }
     }
+}
 
-    log_info!(
-        logger,
-        "Rendered {} notes + {} samples",
-        note_count,
-        sample_count
-    );
-    let max_amplitude = buffer.iter().map(|&s| s.abs()).fold(0.0f32, f32::max);
-    log_info!(
-        logger,
-        "Max amplitude before normalization: {:.4}",
-        max_amplitude
-    );
+/// Hash of the render settings a cached insert depends on besides its own events
+fn render_context_hash(interpreter: &AudioInterpreter, total_samples: usize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    interpreter.sample_rate.hash(&mut hasher);
+    total_samples.hash(&mut hasher);
+    format!("{:?}", interpreter.resample_quality).hash(&mut hasher);
+    interpreter.mix.precision.label().hash(&mut hasher);
+    hasher.finish()
+}
 
-    if max_amplitude > 1.0 {
-        for sample in buffer.iter_mut() {
-            *sample /= max_amplitude;
+/// Sum group insert buffers (keyed by path, e.g. `drums/fills`) into the master buffer,
+/// applying each group's effect chain to its summed signal on the way up. The groups
+/// named in `taps` are also returned on their own, and live sessions (with an insert
+/// cache) get the meters of every insert for the OSC mirror.
+fn mix_group_inserts<S: MixSample>(
+    interpreter: &AudioInterpreter,
+    master: Vec<S>,
+    group_buffers: HashMap<String, Vec<S>>,
+    duck_keys: HashMap<String, Vec<S>>,
+    total_samples: usize,
+    taps: &[String],
+) -> (Vec<S>, OutputTaps<S>, InsertMeters) {
+    let mut mixer = AudioMixer::<S>::new(interpreter.sample_rate, 2)
+        .with_block_size(interpreter.mix.block_size)
+        .with_resample_quality(interpreter.resample_quality)
+        .with_effect_registry(Arc::clone(&interpreter.effect_registry));
+    if interpreter.insert_cache.is_some() {
+        let window = (METER_WINDOW_SECONDS * interpreter.sample_rate as f32).round() as usize;
+        mixer = mixer.with_meters(window);
+    }
+    for group in taps {
+        mixer.add_output_tap(group);
+    }
+    for duck in &interpreter.routing.ducks {
+        let settings = DuckSettings::from_effect(&duck.effect, interpreter.bpm);
+        mixer.add_duck(&duck.source, &duck.destination, settings);
+    }
+    for (path, samples) in duck_keys {
+        mixer.mix_duck_key(&path, 0, &samples);
+    }
+    for (path, samples) in group_buffers {
+        let mut parent = MASTER_INSERT.to_string();
+        let mut insert = String::new();
+        for group in path.split('/') {
+            if !insert.is_empty() {
+                insert.push('/');
+            }
+            insert.push_str(group);
+            mixer.register_insert(insert.clone(), Some(&parent));
+            if let Some(Value::Array(effects)) = interpreter.events.group_effects.get(group) {
+                mixer.set_insert_effects(&insert, effects.clone());
+            }
+            if let Some(strip) = interpreter.routing.strips.get(group) {
+                mixer.set_insert_strip(&insert, *strip, strip_automation(interpreter, group));
+            }
+            if interpreter.solo_mute.mute.contains(group) {
+                mixer.set_insert_muted(&insert, true);
+            }
+            parent = insert.clone();
+        }
+        mixer.mix_buffer(&insert, 0, &samples);
+    }
+    if let Some(strip) = interpreter.routing.strips.get(MASTER_INSERT) {
+        mixer.set_insert_strip(
+            MASTER_INSERT,
+            *strip,
+            strip_automation(interpreter, MASTER_INSERT),
+        );
+    }
+    mixer.mix_buffer(MASTER_INSERT, 0, &master);
+    mixer.into_metered_buffers(total_samples)
+}
+
+/// Curves for the `automate <group>.gain` / `.pan` / `.lowcut` statements of a strip
+fn strip_automation(interpreter: &AudioInterpreter, group: &str) -> StripAutomation {
+    let Some(envelope) = interpreter.automation_registry.envelope(group) else {
+        return StripAutomation::default();
+    };
+    let envelope = Arc::new(envelope.clone());
+    let curve = |names: &'static [&'static str]| -> Option<ParamCurve> {
+        let name = *names.iter().find(|name| {
+            envelope.has_formula(name) || envelope.params.iter().any(|p| p.param_name == **name)
+        })?;
+        let envelope = Arc::clone(&envelope);
+        Some(Arc::new(move |t: f32| envelope.get_value(name, t)))
+    };
+    StripAutomation {
+        gain: curve(&["gain", "volume"]),
+        pan: curve(&["pan"]),
+        lowcut: curve(&["lowcut"]),
+    }
+}
+
+/// Block-rate curves for the formulas automating `synth_id`, for a note starting at
+/// `start_time`. Gain and pan fall back to the note's own values outside the formula.
+pub(super) fn formula_automation(
+    interpreter: &AudioInterpreter,
+    synth_id: &str,
+    start_time: f32,
+    gain: f32,
+    pan: f32,
+) -> BlockAutomation {
+    let Some(envelope) = interpreter
+        .automation_registry
+        .envelope(synth_id)
+        .filter(|envelope| !envelope.formulas.is_empty())
+    else {
+        return BlockAutomation::default();
+    };
+    let envelope = Arc::new(envelope.clone());
+    let curve = |names: &'static [&'static str], fallback: Option<f32>| -> Option<ParamCurve> {
+        let name = *names.iter().find(|name| envelope.has_formula(name))?;
+        let envelope = Arc::clone(&envelope);
+        Some(Arc::new(move |t: f32| {
+            envelope.get_value(name, start_time + t).or(fallback)
+        }))
+    };
+
+    BlockAutomation {
+        block_size: interpreter.mix.block_size,
+        gain: curve(&["gain", "volume"], Some(gain)),
+        pan: curve(&["pan"], Some(pan)),
+        cutoff: curve(&["cutoff"], None),
+        vibrato: curve(&["vibrato_depth", "vibrato"], None),
+    }
+}
+
+/// Notes of every plugin synth by start time, built once per render so each block
+/// finds the notes it holds by binary search instead of scanning all events
+#[derive(Default)]
+pub(super) struct PluginNotes {
+    by_synth: HashMap<String, Vec<(f32, PluginPendingNote)>>,
+}
+
+impl PluginNotes {
+    pub(super) fn new(events: &[AudioEvent]) -> Self {
+        let mut by_synth: HashMap<String, Vec<(f32, PluginPendingNote)>> = HashMap::new();
+        for event in events {
+            let (midis, start_time, duration, velocity, synth_id) = match event {
+                AudioEvent::Note {
+                    midi,
+                    start_time,
+                    duration,
+                    velocity,
+                    synth_id,
+                    synth_def,
+                    ..
+                } if synth_def.plugin_author.is_some() => (
+                    std::slice::from_ref(midi),
+                    start_time,
+                    duration,
+                    velocity,
+                    synth_id,
+                ),
+                AudioEvent::Chord {
+                    midis,
+                    start_time,
+                    duration,
+                    velocity,
+                    synth_id,
+                    synth_def,
+                    ..
+                } if synth_def.plugin_author.is_some() => {
+                    (midis.as_slice(), start_time, duration, velocity, synth_id)
+                }
+                _ => continue,
+            };
+            let notes = by_synth.entry(synth_id.clone()).or_default();
+            for midi in midis {
+                notes.push((
+                    *start_time,
+                    PluginPendingNote {
+                        midi: *midi,
+                        velocity: *velocity,
+                        duration_ms: duration * 1000.0,
+                        offset_ms: 0.0,
+                    },
+                ));
+            }
+        }
+        // Stable, so notes starting together keep their event order
+        for notes in by_synth.values_mut() {
+            notes.sort_by(|a, b| a.0.total_cmp(&b.0));
         }
+        Self { by_synth }
     }
 
-    Ok(buffer)
+    /// The notes of `synth_id` starting within `[start_time, start_time + duration)`, so
+    /// plugins using the extended ABI can see the whole block (arpeggiators, sequencers)
+    pub(super) fn context_for(
+        &self,
+        interpreter: &AudioInterpreter,
+        synth_id: &str,
+        start_time: f32,
+        duration: f32,
+    ) -> PluginContext {
+        let block_end = start_time + duration.max(0.0);
+        let notes = self.by_synth.get(synth_id).map_or(&[][..], Vec::as_slice);
+        let first = notes.partition_point(|(t, _)| *t < start_time - f32::EPSILON);
+        let last = first + notes[first..].partition_point(|(t, _)| *t < block_end);
+        let notes = notes[first..last]
+            .iter()
+            .map(|(t, note)| PluginPendingNote {
+                offset_ms: (t - start_time) * 1000.0,
+                ..*note
+            })
+            .collect();
+
+        let beat = interpreter.beat_duration();
+        let beat_phase = if beat > 0.0 {
+            (start_time / beat).fract()
+        } else {
+            0.0
+        };
+
+        PluginContext {
+            notes,
+            bpm: interpreter.bpm,
+            beat_phase,
+        }
+    }
 }
 
 pub fn render_audio_wrapper(interpreter: &mut AudioInterpreter) -> Result<Vec<f32>> {
     render_audio(interpreter)
 }
+
+#[cfg(test)]
+#[path = "test_renderer.rs"]
+mod tests;
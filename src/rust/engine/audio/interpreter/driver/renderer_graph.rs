@@ -1,22 +1,24 @@
 /// Audio graph rendering - implements proper routing, node effects, and ducking
 use super::AudioInterpreter;
 use crate::engine::audio::interpreter::audio_graph::Connection;
+use crate::engine::audio::mixer::{DuckSettings, MixSample, duck};
 use std::collections::HashMap;
 
-/// Buffers for each node in the audio graph (stereo: left + right samples interleaved)
-type NodeBuffers = HashMap<String, Vec<f32>>;
+/// Buffers for each node in the audio graph (stereo: left + right samples interleaved),
+/// summed in the mix precision
+type NodeBuffers<S> = HashMap<String, Vec<S>>;
 
-/// Process audio through the routing graph
-pub fn render_audio_graph(
+/// Process audio through the routing graph, summing nodes in an accumulator of type `S`
+pub fn render_audio_graph<S: MixSample>(
     interpreter: &AudioInterpreter,
     total_samples: usize,
 ) -> anyhow::Result<Vec<f32>> {
     let total_duration = total_samples as f32 / interpreter.sample_rate as f32;
 
     // Create buffers for each node in the graph
-    let mut node_buffers: NodeBuffers = HashMap::new();
+    let mut node_buffers: NodeBuffers<S> = HashMap::new();
     for node_name in interpreter.audio_graph.node_names() {
-        node_buffers.insert(node_name, vec![0.0f32; total_samples * 2]);
+        node_buffers.insert(node_name, vec![S::default(); total_samples * 2]);
     }
 
     // Phase 1: Render audio events into their respective nodes
@@ -31,44 +33,30 @@ pub fn render_audio_graph(
     // Phase 4: Mix all nodes into master buffer
     let master_buffer = mix_to_master(interpreter, &node_buffers)?;
 
-    Ok(master_buffer)
+    Ok(master_buffer.into_iter().map(MixSample::to_f32).collect())
 }
 
-/// Determine which node an event belongs to based on its content
-/// Returns the node name where this event should be rendered
-fn get_event_target_node(
-    event: &crate::engine::audio::events::AudioEvent,
-    _interpreter: &AudioInterpreter,
-) -> String {
+/// Node the event at `index` is rendered into: that of the innermost group playing it,
+/// else that of its synth variable, else master
+pub(crate) fn get_event_target_node(interpreter: &AudioInterpreter, index: usize) -> String {
     use crate::engine::audio::events::AudioEvent;
 
-    match event {
-        AudioEvent::Note { synth_id, .. } | AudioEvent::Chord { synth_id, .. } => {
-            // Route notes/chords to lead node if synth matches lead pattern
-            if synth_id.contains("mySynth")
-                || synth_id.contains("lead")
-                || synth_id.contains("Lead")
-            {
-                "myLeadNode".to_string()
-            } else {
-                "$master".to_string()
-            }
-        }
-        AudioEvent::Sample { uri, .. } => {
-            // Route drum samples to kick node
-            if uri.contains("kick") || uri.contains("Kick") || uri.contains("drum") {
-                "myKickNode".to_string()
-            } else {
-                "$master".to_string()
-            }
-        }
+    let path = interpreter.events.group_path(index);
+    let mut sources: Vec<&str> = path
+        .as_deref()
+        .map_or(Vec::new(), |path| path.rsplit('/').collect());
+    if let AudioEvent::Note { synth_id, .. } | AudioEvent::Chord { synth_id, .. } =
+        &interpreter.events.events[index]
+    {
+        sources.push(synth_id);
     }
+    interpreter.audio_graph.target_node(&sources).to_string()
 }
 
 /// Render audio events into their assigned nodes
-fn render_events_into_nodes(
+fn render_events_into_nodes<S: MixSample>(
     interpreter: &AudioInterpreter,
-    node_buffers: &mut NodeBuffers,
+    node_buffers: &mut NodeBuffers<S>,
     total_duration: f32,
 ) -> anyhow::Result<()> {
     use crate::engine::audio::events::AudioEvent;
@@ -76,9 +64,30 @@ fn render_events_into_nodes(
 
     let total_samples = (total_duration * interpreter.sample_rate as f32).ceil() as usize;
 
-    for event in &interpreter.events.events {
+    let events = &interpreter.events.events;
+    // Only sample playback (CLI builds) is choked and crossfaded
+    #[cfg(feature = "cli")]
+    let chokes = crate::engine::audio::choke::choke_times(
+        events,
+        crate::engine::audio::choke::bank_choke_group,
+    );
+    #[cfg(feature = "cli")]
+    let mut fades = crate::engine::audio::retrigger::RetriggerFades::new(
+        events,
+        |index| interpreter.events.group_path(index),
+        interpreter.mix.retrigger_fade_ms,
+        interpreter.sample_rate,
+    );
+    let plugins = super::renderer::PluginNotes::new(events);
+    // In start order, so each retrigger knows whether the voice before it still rings
+    let mut order: Vec<usize> = (0..events.len()).collect();
+    order.sort_by(|&a, &b| event_start(&events[a]).total_cmp(&event_start(&events[b])));
+
+    for event_index in order {
+        interpreter.check_deadline()?;
+        let event = &events[event_index];
         // Determine target node for this event
-        let target_node = get_event_target_node(event, interpreter);
+        let target_node = get_event_target_node(interpreter, event_index);
 
         // Get the target buffer
         let target_buffer = node_buffers.get_mut(&target_node);
@@ -92,6 +101,7 @@ fn render_events_into_nodes(
                 midi,
                 start_time,
                 duration,
+                synth_id,
                 synth_def,
                 pan,
                 detune,
@@ -107,6 +117,7 @@ fn render_events_into_nodes(
                     decay: synth_def.decay,
                     sustain: synth_def.sustain,
                     release: synth_def.release,
+                    curves: synth_def.curves,
                     synth_type: synth_def.synth_type.clone(),
                     filters: synth_def.filters.clone(),
                     options: synth_def.options.clone(),
@@ -114,6 +125,14 @@ fn render_events_into_nodes(
                     plugin_author: synth_def.plugin_author.clone(),
                     plugin_name: synth_def.plugin_name.clone(),
                     plugin_export: synth_def.plugin_export.clone(),
+                    plugin_context: None,
+                    automation: super::renderer::formula_automation(
+                        interpreter,
+                        synth_id,
+                        *start_time,
+                        *gain,
+                        *pan,
+                    ),
                 };
 
                 if let Some(a) = attack {
@@ -122,7 +141,17 @@ fn render_events_into_nodes(
                 if let Some(r) = release {
                     params.release = r / 1000.0;
                 }
+                if params.plugin_author.is_some() {
+                    params.plugin_context =
+                        Some(plugins.context_for(interpreter, synth_id, *start_time, *duration));
+                }
 
+                // A gain formula takes the place of the event gain
+                let gain = if params.automation.gain.is_some() {
+                    1.0
+                } else {
+                    *gain
+                };
                 let samples = generate_note_with_options(
                     *midi,
                     *duration * 1000.0, // Convert to milliseconds
@@ -142,13 +171,16 @@ fn render_events_into_nodes(
                     target_buffer[start_idx..end_idx]
                         .iter_mut()
                         .zip(samples[0..write_len].iter())
-                        .for_each(|(dst, src)| *dst += src);
+                        .for_each(|(dst, src)| *dst += S::from_f32(*src));
                 }
             }
             AudioEvent::Sample {
                 uri: _uri,
                 start_time: _start_time,
                 velocity: _velocity,
+                note: _note,
+                automation: _automation,
+                region: _region,
                 ..
             } => {
                 // Load sample from bank (synthetic drums for CLI)
@@ -156,12 +188,45 @@ fn render_events_into_nodes(
                 {
                     use crate::engine::audio::samples;
 
-                    if let Some(sample_data) = samples::get_sample(_uri) {
+                    if let Some(sample_data) = samples::get_sample_for_note(
+                        _uri,
+                        interpreter.sample_rate,
+                        interpreter.resample_quality,
+                        *_note,
+                    ) {
+                        let cut = _region.map(|region| {
+                            region.apply(&sample_data.samples, sample_data.sample_rate)
+                        });
+                        let source = cut.as_deref().unwrap_or(&sample_data.samples);
+                        let automated = _automation
+                            .as_deref()
+                            .and_then(|target| interpreter.note_automation_templates.get(target))
+                            .map(|ctx| {
+                                crate::engine::audio::automation::apply_templates_to_sample(
+                                    source,
+                                    &ctx.templates,
+                                    interpreter.sample_rate,
+                                )
+                            });
+                        let data = automated.as_deref().unwrap_or(source);
                         let start_sample =
                             (*_start_time * interpreter.sample_rate as f32).ceil() as usize;
+                        // Crossfaded with retriggers, cut short by a later trigger of the
+                        // same choke group
+                        let mut kept = data.to_vec();
+                        fades.apply(event_index, start_sample, &mut kept, 2);
+                        if let Some(at) = chokes[event_index] {
+                            let end = (at * interpreter.sample_rate as f32).ceil() as usize;
+                            crate::engine::audio::choke::cut(
+                                &mut kept,
+                                1,
+                                end.saturating_sub(start_sample),
+                                interpreter.sample_rate,
+                            );
+                        }
+                        let data = kept.as_slice();
                         let start_idx = start_sample * 2; // Convert to stereo sample index
-                        let end_idx =
-                            (start_idx + sample_data.samples.len()).min(total_samples * 2);
+                        let end_idx = (start_idx + data.len()).min(total_samples * 2);
                         let write_len = end_idx - start_idx;
 
                         if start_idx < total_samples * 2 && write_len > 0 {
@@ -169,8 +234,8 @@ fn render_events_into_nodes(
                             let velocity_scale = _velocity;
                             target_buffer[start_idx..end_idx]
                                 .iter_mut()
-                                .zip(sample_data.samples[0..write_len].iter())
-                                .for_each(|(dst, src)| *dst += src * velocity_scale);
+                                .zip(data[0..write_len].iter())
+                                .for_each(|(dst, src)| *dst += S::from_f32(src * velocity_scale));
                         }
                     }
                 }
@@ -183,11 +248,11 @@ fn render_events_into_nodes(
 }
 
 /// Apply effects chains to each node
-fn apply_node_effects(
+fn apply_node_effects<S: MixSample>(
     interpreter: &AudioInterpreter,
-    node_buffers: &mut NodeBuffers,
+    node_buffers: &mut NodeBuffers<S>,
 ) -> anyhow::Result<()> {
-    use crate::engine::audio::effects::chain::build_effect_chain;
+    use crate::engine::audio::effects::chain::build_effect_chain_with;
 
     for (node_name, node_config) in &interpreter.audio_graph.nodes {
         if let Some(effects_value) = &node_config.effects {
@@ -197,11 +262,19 @@ fn apply_node_effects(
                 _ => vec![effects_value.clone()],
             };
 
-            let mut effect_chain = build_effect_chain(&effects_array, false);
+            let mut effect_chain = build_effect_chain_with(
+                std::sync::Arc::clone(&interpreter.effect_registry),
+                &effects_array,
+                false,
+            );
 
             if let Some(buffer) = node_buffers.get_mut(node_name) {
-                // Apply effects to this node's buffer
-                effect_chain.process(buffer, interpreter.sample_rate);
+                // Effects work in f32; the node is converted on the way in and out
+                let mut signal: Vec<f32> = buffer.iter().map(|s| s.to_f32()).collect();
+                effect_chain.process(&mut signal, interpreter.sample_rate);
+                for (slot, sample) in buffer.iter_mut().zip(signal) {
+                    *slot = S::from_f32(sample);
+                }
             }
         }
     }
@@ -210,9 +283,9 @@ fn apply_node_effects(
 }
 
 /// Apply routing connections and duck effects
-fn apply_routing_and_ducking(
+fn apply_routing_and_ducking<S: MixSample>(
     interpreter: &AudioInterpreter,
-    node_buffers: &mut NodeBuffers,
+    node_buffers: &mut NodeBuffers<S>,
 ) -> anyhow::Result<()> {
     // Phase 1: Apply all ducks and sidechains first (these modify source buffers)
     for connection in interpreter.audio_graph.connections.iter() {
@@ -220,9 +293,16 @@ fn apply_routing_and_ducking(
             Connection::Duck {
                 source,
                 destination,
-                effect_params: _,
+                effect_params,
             } => {
-                apply_duck(source, destination, node_buffers, interpreter.sample_rate)?;
+                let settings = DuckSettings::from_effect(effect_params, interpreter.bpm);
+                apply_duck(
+                    source,
+                    destination,
+                    settings,
+                    node_buffers,
+                    interpreter.sample_rate,
+                )?;
             }
             Connection::Sidechain {
                 source,
@@ -248,8 +328,8 @@ fn apply_routing_and_ducking(
                     node_buffers.get(source).cloned(),
                     node_buffers.get_mut(destination),
                 ) {
-                    for j in 0..src_buf.len() {
-                        dst_buf[j] += src_buf[j] * gain;
+                    for (dst, src) in dst_buf.iter_mut().zip(&src_buf) {
+                        *dst += S::from_f64(src.to_f64() * *gain as f64);
                     }
                 }
             }
@@ -260,65 +340,32 @@ fn apply_routing_and_ducking(
     Ok(())
 }
 
-/// Apply duck effect - compress source based on destination envelope
-fn apply_duck(
+/// Apply duck effect - turn the source down following the destination's envelope
+fn apply_duck<S: MixSample>(
     source_name: &str,
     destination_name: &str,
-    node_buffers: &mut NodeBuffers,
+    settings: DuckSettings,
+    node_buffers: &mut NodeBuffers<S>,
     sample_rate: u32,
 ) -> anyhow::Result<()> {
-    // Get current volumes in destination buffer (envelope)
-    let dest_envelope = if let Some(dest_buf) = node_buffers.get(destination_name) {
-        compute_envelope(dest_buf, sample_rate)
-    } else {
+    let Some(key) = node_buffers.get(destination_name) else {
         return Ok(());
     };
-
-    // Apply compression to source based on destination envelope
-    let src_opt = node_buffers.get_mut(source_name);
-    if src_opt.is_none() {
+    let Some(frames) = node_buffers.get(source_name).map(|buffer| buffer.len() / 2) else {
         return Ok(());
+    };
+    let gains = settings.gain_curve(key, 2, sample_rate, frames);
+    if let Some(src_buf) = node_buffers.get_mut(source_name) {
+        duck::apply_gain_curve(src_buf, 2, &gains);
     }
-
-    let src_buf = src_opt.unwrap();
-
-    // Map buffer indices to envelope indices
-    let frame_rate = 100; // Must match compute_envelope
-    let samples_per_frame = (sample_rate / frame_rate) as usize * 2; // stereo samples per envelope frame
-
-    for frame_idx in (0..src_buf.len()).step_by(2) {
-        // Calculate which envelope frame this sample belongs to
-        let current_envelope_idx = frame_idx / samples_per_frame;
-
-        if current_envelope_idx < dest_envelope.len() {
-            let dest_level = dest_envelope[current_envelope_idx];
-
-            // Apply compression proportional to destination level
-            let threshold = 0.005; // Start reducing at very low levels
-            let sensitivity = if dest_level > threshold {
-                ((dest_level - threshold) / (0.2 - threshold)).min(1.0)
-            } else {
-                0.0
-            };
-
-            let max_duck_reduction = 0.95; // 95% maximum reduction
-            let compression_gain = 1.0 - (sensitivity * max_duck_reduction);
-
-            src_buf[frame_idx] *= compression_gain;
-            if frame_idx + 1 < src_buf.len() {
-                src_buf[frame_idx + 1] *= compression_gain;
-            }
-        }
-    }
-
     Ok(())
 }
 
 /// Apply sidechain effect - gate modulation between nodes
-fn apply_sidechain(
+fn apply_sidechain<S: MixSample>(
     source_name: &str,
     destination_name: &str,
-    node_buffers: &mut NodeBuffers,
+    node_buffers: &mut NodeBuffers<S>,
     sample_rate: u32,
 ) -> anyhow::Result<()> {
     // Get current volumes in destination buffer (envelope)
@@ -344,9 +391,8 @@ fn apply_sidechain(
                 let normalized_linear = (dest_level * 10.0).min(1.0);
                 let gate_open = 1.0 - (normalized_linear * 0.5); // Range [1.0, 0.5]
 
-                src_buf[frame_idx] *= gate_open;
-                if frame_idx + 1 < src_buf.len() {
-                    src_buf[frame_idx + 1] *= gate_open;
+                for sample in src_buf.iter_mut().skip(frame_idx).take(2) {
+                    *sample = S::from_f64(sample.to_f64() * gate_open as f64);
                 }
             }
         }
@@ -356,13 +402,14 @@ fn apply_sidechain(
 }
 
 /// Compute RMS envelope of a buffer (stereo, 2 samples per frame)
-fn compute_envelope(buffer: &[f32], sample_rate: u32) -> Vec<f32> {
+fn compute_envelope<S: MixSample>(buffer: &[S], sample_rate: u32) -> Vec<f32> {
     let frame_rate = 100; // 100 Hz envelope resolution
     let samples_per_frame = sample_rate / frame_rate;
     let mut envelope = Vec::new();
 
     for chunk in buffer.chunks(samples_per_frame as usize * 2) {
-        let rms: f32 = (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt();
+        let rms: f32 =
+            (chunk.iter().map(|s| s.to_f32().powi(2)).sum::<f32>() / chunk.len() as f32).sqrt();
         envelope.push(rms.min(1.0).max(0.0));
     }
 
@@ -370,10 +417,10 @@ fn compute_envelope(buffer: &[f32], sample_rate: u32) -> Vec<f32> {
 }
 
 /// Mix all node buffers down to master
-fn mix_to_master(
+fn mix_to_master<S: MixSample>(
     _interpreter: &AudioInterpreter,
-    node_buffers: &NodeBuffers,
-) -> anyhow::Result<Vec<f32>> {
+    node_buffers: &NodeBuffers<S>,
+) -> anyhow::Result<Vec<S>> {
     let master_buf = node_buffers
         .get("$master")
         .ok_or_else(|| anyhow::anyhow!("Master node not found"))?
@@ -392,3 +439,16 @@ fn mix_to_master(
 
     Ok(result)
 }
+
+fn event_start(event: &crate::engine::audio::events::AudioEvent) -> f32 {
+    use crate::engine::audio::events::AudioEvent;
+    match event {
+        AudioEvent::Note { start_time, .. }
+        | AudioEvent::Chord { start_time, .. }
+        | AudioEvent::Sample { start_time, .. } => *start_time,
+    }
+}
+
+#[cfg(test)]
+#[path = "test_renderer_graph.rs"]
+mod tests;
@@ -0,0 +1,418 @@
+use anyhow::Result;
+
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::interpreter::driver::test_support::{crash_count, with_crash_kit};
+use crate::language::syntax::ast::{Statement, StatementKind, Value};
+use std::collections::HashMap;
+
+#[test]
+fn test_call_with_block_scopes_trigger_parameters() -> Result<()> {
+    let source = "group hats:\n    .kit.crash\n    sleep 1/2\n    .kit.crash\ngroup outer:\n    call hats with { velocity: 0.5 }\ncall outer with { velocity: 0.8, swing: 75% }\n.kit.crash\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+
+    let mut interp = AudioInterpreter::new(44100);
+    with_crash_kit(&mut interp);
+    interp.collect_events(&statements)?;
+
+    let hits: Vec<(f32, f32)> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            crate::engine::audio::events::AudioEvent::Sample {
+                start_time,
+                velocity,
+                ..
+            } => Some((*start_time, *velocity)),
+            _ => None,
+        })
+        .collect();
+
+    let eighth = interp.beat_duration() / 2.0;
+    assert_eq!(hits.len(), 3);
+    // Velocities multiply through nested blocks
+    assert!((hits[0].1 - 0.4).abs() < 1e-6 && (hits[1].1 - 0.4).abs() < 1e-6);
+    // 75% swing pushes the off-beat eighth back by half a step
+    assert!(hits[0].0.abs() < 1e-6);
+    assert!((hits[1].0 - eighth * 3.5).abs() < 1e-4);
+    // The block ends with the call
+    assert_eq!(hits[2].1, 1.0);
+    assert!(interp.modifier_stack.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_at_block_places_events_without_moving_cursor() -> Result<()> {
+    let source = ".kit.crash\nat bar 3:\n    .kit.crash\n.kit.crash\nat 0:05.5:\n    .kit.crash\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+
+    let mut interp = AudioInterpreter::new(44100);
+    with_crash_kit(&mut interp);
+    interp.collect_events(&statements)?;
+
+    let beat = interp.beat_duration();
+    let starts: Vec<f32> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            crate::engine::audio::events::AudioEvent::Sample { start_time, .. } => {
+                Some(*start_time)
+            }
+            _ => None,
+        })
+        .collect();
+    let expected = [0.0, 8.0 * beat, beat, 5.5];
+    assert_eq!(starts.len(), expected.len());
+    for (got, want) in starts.iter().zip(expected) {
+        assert!((got - want).abs() < 1e-4, "expected {want}, got {got}");
+    }
+    // The sequential cursor only moved for the two top-level triggers
+    assert!((interp.cursor_time - 2.0 * beat).abs() < 1e-4);
+    Ok(())
+}
+
+#[test]
+fn test_marks_record_the_cursor_time_once_per_name() -> Result<()> {
+    let source =
+        "bpm 120\nmark intro\n.kit.crash\n.kit.crash\nmark \"drop 2\"\n.kit.crash\nmark intro\n";
+    let (_, interp) = crash_count(source)?;
+    assert_eq!(
+        interp.events.markers,
+        vec![(0.0, "intro".to_string()), (1.0, "drop 2".to_string())]
+    );
+
+    assert!(
+        crate::language::syntax::parser::driver::statements::core::parse_mark("mark", 1).is_err()
+    );
+    assert!(
+        crate::language::syntax::parser::driver::statements::core::parse_mark("mark two words", 1)
+            .is_err()
+    );
+    Ok(())
+}
+
+fn note_times(interp: &AudioInterpreter, synth: &str) -> Vec<f32> {
+    interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Note {
+                synth_id,
+                start_time,
+                ..
+            } if synth_id == synth => Some(*start_time),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_beat_handlers_fire_once_per_frame_snapped_beat_of_the_render() -> Result<()> {
+    let body = "bpm 90\nlet lead = synth sine\nlet hat = synth square\ngroup verse:\n    loop 2:\n        lead -> note(C4) -> duration(1500)\ncall verse\nlead -> note(E4) -> duration(1500)\n";
+    let source = format!(
+        "{body}on beat:\n    hat -> note(C6) -> duration(20)\non bar:\n    hat -> note(C7) -> duration(20)\n"
+    );
+    let parse = |source: &str| {
+        crate::language::syntax::parser::driver::parse(
+            source,
+            std::path::PathBuf::from("test.deva"),
+        )
+    };
+
+    // The same track without handlers gives the length the beats must cover
+    let mut plain = AudioInterpreter::new(44100);
+    plain.collect_all_events(&parse(body)?)?;
+    let length = plain.calculate_total_duration();
+
+    let mut interp = AudioInterpreter::new(44100);
+    interp.collect_all_events(&parse(&source)?)?;
+
+    let beat = 60.0 / 90.0;
+    let snap = |time: f64| ((time * 44100.0).round() / 44100.0) as f32;
+    let mut expected: Vec<f32> = (0..)
+        .map(|i| i as f64 * beat)
+        .take_while(|time| *time < length as f64)
+        .flat_map(|time| {
+            let bar = ((time / beat).round() as usize).is_multiple_of(4);
+            std::iter::once(snap(time)).chain(bar.then(|| snap(time)))
+        })
+        .collect();
+    expected.sort_by(f32::total_cmp);
+    let mut times = note_times(&interp, "hat");
+    times.sort_by(f32::total_cmp);
+    assert_eq!(times, expected);
+    // Handlers add their own notes without moving the rest of the track
+    assert_eq!(note_times(&interp, "lead"), note_times(&plain, "lead"));
+    Ok(())
+}
+
+#[test]
+fn test_beat_handlers_follow_the_meter_and_can_be_suppressed() -> Result<()> {
+    let source = "bpm 120\nlet lead = synth sine\nlet hat = synth square\non bar:\n    hat -> note(C6) -> duration(20)\nlead -> note(C4) -> duration(3000)\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(48000);
+    interp.tempo_map.push_meter(0.0, 6, 8);
+    interp.collect_all_events(&statements)?;
+    // A 6/8 bar is three quarter notes: 1.5s at 120 BPM
+    let bars: Vec<f32> = note_times(&interp, "hat");
+    assert_eq!(bars[..2], [0.0, 1.5]);
+
+    let mut quiet = AudioInterpreter::new(48000);
+    quiet.suppress_beat_emit = true;
+    quiet.collect_all_events(&statements)?;
+    assert!(note_times(&quiet, "hat").is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_variable_overrides_win_over_let_and_tempo() -> Result<()> {
+    let mut interp = AudioInterpreter::new(44100);
+    let mut overrides = HashMap::new();
+    overrides.insert("intensity".to_string(), Value::Number(0.7));
+    overrides.insert("bpm".to_string(), Value::Number(140.0));
+    interp.set_overrides(overrides);
+
+    let tempo = Statement::new(
+        StatementKind::Tempo {
+            value: 90.0,
+            body: None,
+        },
+        Value::Null,
+        0,
+        1,
+        1,
+    );
+    let let_stmt = Statement::new(
+        StatementKind::Let {
+            name: "intensity".to_string(),
+            value: Some(Value::Number(0.2)),
+        },
+        Value::Null,
+        0,
+        2,
+        1,
+    );
+
+    interp.collect_events(&[tempo, let_stmt])?;
+
+    assert_eq!(interp.bpm, 140.0);
+    assert!(matches!(
+        interp.variables.get("intensity"),
+        Some(Value::Number(n)) if (*n - 0.7).abs() < f32::EPSILON
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_variable_overrides_leave_nested_assignments_alone() -> Result<()> {
+    let mut interp = AudioInterpreter::new(44100);
+    let mut overrides = HashMap::new();
+    overrides.insert("intensity".to_string(), Value::Number(0.5));
+    interp.set_overrides(overrides);
+
+    let declare = Statement::new(
+        StatementKind::Let {
+            name: "intensity".to_string(),
+            value: Some(Value::Number(0.2)),
+        },
+        Value::Null,
+        0,
+        1,
+        1,
+    );
+    let raise = Statement::new(
+        StatementKind::Let {
+            name: "intensity".to_string(),
+            value: Some(Value::Number(0.9)),
+        },
+        Value::Null,
+        4,
+        3,
+        5,
+    );
+    let loop_stmt = Statement::new(
+        StatementKind::Loop {
+            count: Value::Number(1.0),
+            body: vec![raise],
+        },
+        Value::Null,
+        0,
+        2,
+        1,
+    );
+
+    interp.collect_events(&[declare, loop_stmt])?;
+
+    assert!(matches!(
+        interp.variables.get("intensity"),
+        Some(Value::Number(n)) if (*n - 0.9).abs() < f32::EPSILON
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_persisted_variables_and_loop_passes_survive_rebuild() -> Result<()> {
+    let program = vec![
+        Statement::new(
+            StatementKind::Persist {
+                names: vec!["counter".to_string(), "loops".to_string()],
+            },
+            Value::Null,
+            0,
+            1,
+            1,
+        ),
+        Statement::new(
+            StatementKind::Let {
+                name: "counter".to_string(),
+                value: Some(Value::Number(0.0)),
+            },
+            Value::Null,
+            0,
+            2,
+            1,
+        ),
+        Statement::new(
+            StatementKind::Loop {
+                count: Value::Number(3.0),
+                body: vec![],
+            },
+            Value::Null,
+            0,
+            3,
+            1,
+        ),
+    ];
+
+    let mut first = AudioInterpreter::new(44100);
+    first.collect_events(&program)?;
+    // The live set evolved the counter during the first run
+    first
+        .variables
+        .insert("counter".to_string(), Value::Number(5.0));
+    let snapshot = first.persisted_snapshot();
+    assert_eq!(snapshot.loop_passes.get(&3), Some(&3));
+
+    let mut second = AudioInterpreter::new(44100);
+    second.set_persisted(snapshot);
+    second.collect_events(&program)?;
+
+    assert!(matches!(
+        second.variables.get("counter"),
+        Some(Value::Number(n)) if *n == 5.0
+    ));
+    assert_eq!(second.persisted_snapshot().loop_passes.get(&3), Some(&6));
+
+    Ok(())
+}
+
+#[test]
+fn test_persisted_value_is_only_kept_by_the_top_level_declaration() -> Result<()> {
+    let reset = Statement::new(
+        StatementKind::Let {
+            name: "counter".to_string(),
+            value: Some(Value::Number(7.0)),
+        },
+        Value::Null,
+        4,
+        3,
+        5,
+    );
+    let program = vec![
+        Statement::new(
+            StatementKind::Persist {
+                names: vec!["counter".to_string()],
+            },
+            Value::Null,
+            0,
+            1,
+            1,
+        ),
+        Statement::new(
+            StatementKind::Loop {
+                count: Value::Number(1.0),
+                body: vec![reset],
+            },
+            Value::Null,
+            0,
+            2,
+            1,
+        ),
+        Statement::new(
+            StatementKind::Let {
+                name: "counter".to_string(),
+                value: Some(Value::Number(0.0)),
+            },
+            Value::Null,
+            0,
+            4,
+            1,
+        ),
+    ];
+
+    let mut first = AudioInterpreter::new(44100);
+    first.collect_events(&program)?;
+    first
+        .variables
+        .insert("counter".to_string(), Value::Number(5.0));
+
+    let mut second = AudioInterpreter::new(44100);
+    second.set_persisted(first.persisted_snapshot());
+    second.collect_events(&program)?;
+
+    // The loop body still runs; the top-level `let` is the one skipped
+    assert!(matches!(
+        second.variables.get("counter"),
+        Some(Value::Number(n)) if *n == 7.0
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_at_positions_follow_tempo_and_meter_maps() -> Result<()> {
+    let source =
+        "at bar 3:\n    .kit.crash\nat bar 4:\n    .kit.crash\nat beat 10:\n    .kit.crash\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+
+    let mut interp = AudioInterpreter::new(44100);
+    with_crash_kit(&mut interp);
+    interp.bpm = 120.0;
+    // Two 4/4 bars at 120 BPM, then 3/4 at 60 BPM from beat 8
+    interp.tempo_map.push_tempo(8.0, 60.0);
+    interp.tempo_map.push_meter(8.0, 3, 4);
+    interp.collect_events(&statements)?;
+
+    let starts: Vec<f32> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Sample { start_time, .. } => Some(*start_time),
+            _ => None,
+        })
+        .collect();
+    let expected = [4.0, 7.0, 5.0];
+    assert_eq!(starts.len(), expected.len());
+    for (got, want) in starts.iter().zip(expected) {
+        assert!((got - want).abs() < 1e-4, "expected {want}, got {got}");
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 
 use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::interpreter::driver::test_support::crash_count;
 use crate::language::syntax::ast::{Statement, StatementKind, Value};
 
 #[test]
@@ -121,3 +122,36 @@ fn test_function_return_sets_return_variable() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_labelled_break_and_continue_unwind_nested_loops() -> Result<()> {
+    let source = "for i in 0..4 as outer:\n    for j in 0..4:\n        if j == 2:\n            continue outer\n        if i == 3:\n            break outer\n        .kit.crash\n.kit.crash\n";
+    let (hits, interp) = crash_count(source)?;
+    // Two inner passes for i = 0..=2, nothing for i = 3, then the trailing trigger
+    assert_eq!(hits, 7);
+    assert!(!interp.break_flag && !interp.continue_flag);
+    assert!(interp.loop_jump.is_none() && interp.loop_frames.is_empty());
+    assert!(!interp.variables.contains_key("i"));
+
+    // An unlabelled continue only skips the rest of the innermost pass
+    let (hits, _) = crash_count(
+        "loop 2:\n    for j in 0..3:\n        if j == 1:\n            continue\n        .kit.crash\n",
+    )?;
+    assert_eq!(hits, 4);
+    Ok(())
+}
+
+#[test]
+fn test_loop_counters_in_expressions_and_patterns() -> Result<()> {
+    let source = "pattern p with kit.crash = \"x.x.\"\nlet bars = 3\nloop bars as bar:\n    if $loop.bar == 1:\n        continue\n    call p\n    for step in 0..bars:\n        if $loop.index >= $loop.bar:\n            break\n        .kit.crash\n";
+    let (hits, interp) = crash_count(source)?;
+    // Bar 0: pattern (2 hits) and no steps; bar 1 skipped; bar 2: pattern + 2 steps
+    assert_eq!(hits, 6);
+    assert_eq!(interp.loop_pass, 0);
+
+    let Err(err) = crash_count("loop 2:\n    break nowhere\n") else {
+        panic!("break with an unknown label should fail");
+    };
+    assert!(err.to_string().contains("nowhere"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::language::syntax::ast::Value;
+
+#[test]
+fn test_note_mode_automation_tags_bank_samples() -> Result<()> {
+    let source = "automate kit mode note:\n    param gain { 0% = 0.0 100% = 1.0 }\n.kit.crash\n.fx.crash\n.kit.crash\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+
+    let mut interp = AudioInterpreter::new(44100);
+    for bank in ["kit", "fx"] {
+        let mut triggers = std::collections::HashMap::new();
+        triggers.insert("crash".to_string(), Value::String("crash.wav".to_string()));
+        interp
+            .variables
+            .insert(bank.to_string(), Value::Map(triggers));
+    }
+    interp.collect_events(&statements)?;
+
+    let targets: Vec<Option<String>> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            crate::engine::audio::events::AudioEvent::Sample { automation, .. } => {
+                Some(automation.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        targets,
+        vec![Some("kit".to_string()), None, Some("kit".to_string())]
+    );
+    Ok(())
+}
+
+fn strummed_notes(source: &str, seed: Option<u64>) -> Result<Vec<(u8, f32, f32)>> {
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    if let Some(seed) = seed {
+        interp.set_deterministic(seed);
+    }
+    interp.collect_events(&statements)?;
+    Ok(interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Note {
+                midi,
+                start_time,
+                gain,
+                ..
+            } => Some((*midi, *start_time, *gain)),
+            _ => None,
+        })
+        .collect())
+}
+
+#[test]
+fn test_strum_offsets_chord_notes_in_direction_order() -> Result<()> {
+    let up = strummed_notes(
+        "let keys = synth sine\nkeys -> chord(Cmaj7) -> strum(20ms)\n",
+        None,
+    )?;
+    let pitches: Vec<u8> = up.iter().map(|note| note.0).collect();
+    assert_eq!(pitches, vec![60, 64, 67, 71]);
+    for (step, note) in up.iter().enumerate() {
+        assert!((note.1 - step as f32 * 0.02).abs() < 1e-6);
+    }
+    // Split notes keep the chord's overall level
+    let total: f32 = up.iter().map(|note| note.2).sum();
+    assert!((total - 1.0).abs() < 1e-6);
+
+    let down = strummed_notes(
+        "bpm 120\nlet keys = synth sine\nkeys -> chord(Cmaj7) -> strum(1/32, down)\n",
+        None,
+    )?;
+    assert_eq!(down[0].0, 71);
+    // `1/32` is a fraction of a beat, so 15.6 ms apart at 120 bpm
+    assert!((down[3].1 - 3.0 * 0.015625).abs() < 1e-6);
+
+    let source = "let keys = synth sine\nkeys -> chord(Cmaj7) -> strum(\"15ms random\")\n";
+    let random = strummed_notes(source, Some(7))?;
+    assert_eq!(random, strummed_notes(source, Some(7))?);
+    let mut pitches: Vec<u8> = random.iter().map(|note| note.0).collect();
+    pitches.sort_unstable();
+    assert_eq!(pitches, vec![60, 64, 67, 71]);
+
+    assert!(strummed_notes("let keys = synth sine\nkeys -> chord(Cmaj7)\n", None)?.is_empty());
+    assert!(
+        strummed_notes(
+            "let keys = synth sine\nkeys -> chord(C) -> strum(20ms, sideways)\n",
+            None
+        )
+        .is_err()
+    );
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use anyhow::Result;
+
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::interpreter::driver::test_support::{crash_count, with_crash_kit};
+use crate::language::syntax::ast::{DurationValue, Statement, StatementKind, Value};
+use std::collections::HashMap;
+
+fn looped_trigger(source: &str, passes: f32) -> Result<(Statement, AudioInterpreter)> {
+    let trigger = crate::language::syntax::parser::driver::trigger::parse_trigger_line(source, 2)?;
+    let loop_stmt = Statement::new(
+        StatementKind::Loop {
+            count: Value::Number(passes),
+            body: vec![trigger],
+        },
+        Value::Null,
+        0,
+        1,
+        1,
+    );
+
+    let mut interp = AudioInterpreter::new(44100);
+    with_crash_kit(&mut interp);
+    Ok((loop_stmt, interp))
+}
+
+#[test]
+fn test_trigger_every_fires_on_matching_passes() -> Result<()> {
+    let (loop_stmt, mut interp) = looped_trigger(".kit.crash every 4", 8.0)?;
+    interp.collect_events(&[loop_stmt])?;
+
+    assert_eq!(
+        interp.events.events.len(),
+        2,
+        "expected hits on pass 0 and 4"
+    );
+    // Skipped passes still advance the cursor by one step each
+    assert!((interp.cursor_time - 8.0 * interp.beat_duration()).abs() < 1e-4);
+    Ok(())
+}
+
+#[test]
+fn test_trigger_chance_is_seed_stable() -> Result<()> {
+    let (never, mut interp) = looped_trigger(".kit.crash chance 0%", 16.0)?;
+    interp.collect_events(&[never])?;
+    assert!(interp.events.events.is_empty());
+
+    let (always, mut interp) = looped_trigger(".kit.crash chance 100%", 16.0)?;
+    interp.collect_events(&[always])?;
+    assert_eq!(interp.events.events.len(), 16);
+
+    let run = || -> Result<usize> {
+        let (stmt, mut interp) = looped_trigger(".kit.crash chance 50%", 64.0)?;
+        interp.collect_events(&[stmt])?;
+        Ok(interp.events.events.len())
+    };
+    let first = run()?;
+    assert_eq!(first, run()?, "chance must render identically across runs");
+    assert!(first > 0 && first < 64);
+    Ok(())
+}
+
+#[test]
+fn test_trigger_note_name_sets_sample_pitch() -> Result<()> {
+    let source = ".kit.crash C3\n.kit.crash F#4 1/2\n.kit.crash\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+
+    let mut interp = AudioInterpreter::new(44100);
+    with_crash_kit(&mut interp);
+    interp.collect_events(&statements)?;
+
+    let notes: Vec<Option<u8>> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            crate::engine::audio::events::AudioEvent::Sample { note, .. } => Some(*note),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(notes, vec![Some(48), Some(66), None]);
+    Ok(())
+}
+
+#[test]
+fn test_trigger_and_pattern_rolls_follow_the_tempo() -> Result<()> {
+    use crate::engine::audio::events::AudioEvent;
+
+    let hits = |interp: &AudioInterpreter| -> Vec<(f32, f32, bool)> {
+        interp
+            .events
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                AudioEvent::Sample {
+                    start_time,
+                    velocity,
+                    effects,
+                    ..
+                } => Some((*start_time, *velocity, effects.is_some())),
+                _ => None,
+            })
+            .collect()
+    };
+
+    // Half-beat spacing at 120 bpm is a quarter second; the last hit reaches the ramps
+    let (count, interp) = crash_count("bpm 120\n.kit.crash roll 1/2 x3 pitch: +12 gain: 0.5\n")?;
+    assert_eq!(count, 3);
+    let rolled = hits(&interp);
+    let expected = [(0.0, 1.0, false), (0.25, 0.75, true), (0.5, 0.5, true)];
+    for ((time, velocity, pitched), (t, v, p)) in rolled.iter().zip(expected) {
+        assert!(
+            (time - t).abs() < 1e-4 && (velocity - v).abs() < 1e-4,
+            "{rolled:?}"
+        );
+        assert_eq!(*pitched, p);
+    }
+    // The roll occupies the trigger's usual step
+    assert!((interp.cursor_time - interp.beat_duration()).abs() < 1e-4);
+
+    // A digit step splits its step into that many hits
+    let (count, interp) = crash_count("bpm 120\npattern p with kit.crash = \"x3--\"\ncall p\n")?;
+    assert_eq!(count, 4);
+    let times: Vec<f32> = hits(&interp).iter().map(|hit| hit.0).collect();
+    for (time, expected) in times
+        .iter()
+        .zip([0.0, 0.5, 0.5 + 1.0 / 6.0, 0.5 + 2.0 / 6.0])
+    {
+        assert!((time - expected).abs() < 1e-4, "{times:?}");
+    }
+    // `1` is a single hit, like `x`
+    let (count, _) = crash_count("pattern p with kit.crash = \"1-x-\"\ncall p\n")?;
+    assert_eq!(count, 2);
+    Ok(())
+}
+
+#[test]
+fn test_trigger_region_options_loop_and_sync_to_tempo() -> Result<()> {
+    use crate::engine::audio::events::{AudioEvent, SampleRegion};
+
+    let statements = crate::language::syntax::parser::driver::parse(
+        "bpm 120\n.kit.brk 4 bars start: 0.25 end: 75% loop: true sync: 2 bars\n.kit.brk start:0.5\n",
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    let mut kit = std::collections::HashMap::new();
+    kit.insert("brk".to_string(), Value::String("brk.wav".to_string()));
+    interp.variables.insert("kit".to_string(), Value::Map(kit));
+    interp.collect_events(&statements)?;
+
+    let regions: Vec<Option<SampleRegion>> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Sample { region, .. } => Some(*region),
+            _ => None,
+        })
+        .collect();
+    // At 120 BPM two bars last 4 seconds and the four bar trigger 8 seconds
+    assert_eq!(
+        regions,
+        vec![
+            Some(SampleRegion {
+                start: 0.25,
+                end: 0.75,
+                sync: Some(4.0),
+                loop_length: Some(8.0),
+                loop_to_end: false,
+            }),
+            Some(SampleRegion {
+                start: 0.5,
+                end: 1.0,
+                sync: None,
+                loop_length: None,
+                loop_to_end: false,
+            }),
+        ]
+    );
+
+    // The synced region is stretched to its length, then repeated over the loop
+    let region = regions[0].unwrap();
+    let samples: Vec<f32> = (0..100).map(|i| i as f32).collect();
+    let played = region.apply(&samples, 10);
+    assert_eq!(played.len(), 80);
+    assert_eq!(played[0], 25.0);
+    assert_eq!(played[40], 25.0);
+    assert!(played[39] > 73.0 && played[39] < 75.0);
+    assert_eq!(region.played_length(Some(10.0)), Some(8.0));
+    assert_eq!(regions[1].unwrap().apply(&samples, 10).len(), 50);
+
+    assert!(
+        crate::language::syntax::parser::driver::trigger::parse_trigger_line(
+            ".kit.brk end: 1.5",
+            1
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_looped_region_without_a_duration_repeats_until_the_render_ends() -> Result<()> {
+    use crate::engine::audio::events::AudioEvent;
+
+    let statements = crate::language::syntax::parser::driver::parse(
+        "bpm 120\nlet pad = synth sine\n.kit.brk loop: true\n.kit.brk loop: true end: 50%\npad -> note(C4) -> duration(3000)\n",
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    let mut kit = std::collections::HashMap::new();
+    kit.insert("brk".to_string(), Value::String("brk.wav".to_string()));
+    interp.variables.insert("kit".to_string(), Value::Map(kit));
+    interp.collect_all_events(&statements)?;
+
+    let loops: Vec<(f32, Option<f32>)> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Sample {
+                start_time, region, ..
+            } => region.map(|region| (*start_time, region.loop_length)),
+            _ => None,
+        })
+        .collect();
+    // Both loops keep going until the note started after them has ended
+    let end = interp.calculate_total_duration();
+    assert!(end >= 4.0, "{end}");
+    assert_eq!(loops.len(), 2);
+    for (start, length) in loops {
+        assert!(
+            (start + length.unwrap() - end).abs() < 1e-4,
+            "{start} {length:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_tempo_synced_delay_resolves_at_event_time() -> Result<()> {
+    let mut interp = AudioInterpreter::new(44100);
+    interp.variables.insert(
+        "kick".to_string(),
+        Value::String("file://kick.wav".to_string()),
+    );
+
+    let mut delay = HashMap::new();
+    delay.insert("type".to_string(), Value::String("delay".to_string()));
+    delay.insert("time".to_string(), Value::Identifier("1/8d".to_string()));
+    let effects = Value::Array(vec![Value::Map(delay)]);
+    let trigger = || {
+        Statement::trigger(
+            "kick",
+            DurationValue::Milliseconds(100.0),
+            Some(effects.clone()),
+            1,
+            1,
+        )
+    };
+    let tempo = |bpm: f32| {
+        Statement::new(
+            StatementKind::Tempo {
+                value: bpm,
+                body: None,
+            },
+            Value::Number(bpm),
+            0,
+            1,
+            1,
+        )
+    };
+    interp.collect_events(&[tempo(120.0), trigger(), tempo(60.0), trigger()])?;
+
+    let times: Vec<Value> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|e| match e {
+            crate::engine::audio::events::AudioEvent::Sample {
+                effects: Some(Value::Array(arr)),
+                ..
+            } => match arr.first() {
+                Some(Value::Map(map)) => map.get("time").cloned(),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(times, vec![Value::Number(93.75), Value::Number(187.5)]);
+
+    Ok(())
+}
@@ -99,3 +99,40 @@ fn test_print_cases() {
         }
     }
 }
+
+#[test]
+fn test_print_timeline_uses_musical_positions() {
+    let mut interp = AudioInterpreter::new(44100);
+    interp.suppress_print = true;
+
+    // 120 bpm: one beat = 0.5s, one bar = 2.0s. Schedule out of order on purpose.
+    interp.cursor_time = 2.5;
+    handler::execute_print(&mut interp, &Value::String("bar two".to_string())).unwrap();
+    interp.cursor_time = 0.0;
+    handler::execute_print(&mut interp, &Value::String("first".to_string())).unwrap();
+    handler::execute_print(&mut interp, &Value::String("second".to_string())).unwrap();
+
+    let timeline = interp.events.print_timeline(&interp.tempo_map, 120.0);
+    let messages: Vec<&str> = timeline.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["first", "second", "bar two"]);
+
+    assert_eq!((timeline[0].bar, timeline[0].beat), (1, 1.0));
+    assert_eq!((timeline[2].bar, timeline[2].beat), (2, 2.0));
+}
+
+#[test]
+fn test_print_timeline_follows_tempo_and_meter_changes() {
+    let mut interp = AudioInterpreter::new(44100);
+    interp.suppress_print = true;
+    // One bar of 4/4 at 120 bpm (2s), then 3/4 at 60 bpm from beat 4
+    interp.tempo_map.push_tempo(0.0, 120.0);
+    interp.tempo_map.push_tempo(4.0, 60.0);
+    interp.tempo_map.push_meter(4.0, 3, 4);
+
+    // 2s + 4 beats at 60 bpm = beat 8, one beat into the second 3/4 bar
+    interp.cursor_time = 6.0;
+    handler::execute_print(&mut interp, &Value::String("later".to_string())).unwrap();
+
+    let timeline = interp.events.print_timeline(&interp.tempo_map, 120.0);
+    assert_eq!((timeline[0].bar, timeline[0].beat), (3, 2.0));
+}
@@ -0,0 +1,226 @@
+use super::*;
+use crate::engine::audio::events::SynthDefinition;
+use crate::language::syntax::parser::driver::parse;
+
+fn interpreter(source: &str) -> AudioInterpreter {
+    let statements = parse(source, "renderer.deva".into()).unwrap();
+    let mut interpreter = AudioInterpreter::new(8000);
+    interpreter.collect_all_events(&statements).unwrap();
+    interpreter
+}
+
+fn streamed(interpreter: &AudioInterpreter) -> Vec<f32> {
+    let mut streamed = Vec::new();
+    render_audio_streamed(interpreter, 512, &mut |chunk| {
+        streamed.extend_from_slice(chunk);
+        Ok(())
+    })
+    .unwrap();
+    streamed
+}
+
+#[test]
+fn test_groups_without_inserts_stream() {
+    let plain = interpreter(
+        "bpm 120\nlet lead = synth sine\ngroup leads:\n    lead -> note(C4, { duration: 300, velocity: 30 })\ngroup pads:\n    leads\n    lead -> note(G4, { duration: 300, velocity: 30 })\nspawn pads\n",
+    );
+    assert!(can_stream(&plain));
+    let whole = render_audio(&plain).unwrap();
+    let streamed = streamed(&plain);
+    assert_eq!(streamed.len(), whole.len());
+    assert!(
+        streamed
+            .iter()
+            .zip(&whole)
+            .all(|(a, b)| (a - b).abs() < 1e-5)
+    );
+
+    // Effects, strips and ducks process the insert over the whole timeline
+    for setup in [
+        "group leads -> reverb({ size: 0.5 }):\n    lead -> note(C4) -> duration(300)\nspawn leads\n",
+        "strip leads gain 0.5\ngroup leads:\n    lead -> note(C4) -> duration(300)\nspawn leads\n",
+        "strip master gain 0.5\nlead -> note(C4) -> duration(300)\n",
+        "duck leads by hits amount 0.5\ngroup hits:\n    lead -> note(C2) -> duration(100)\ngroup leads:\n    lead -> note(C4) -> duration(300)\nspawn hits\nspawn leads\n",
+    ] {
+        let processed = interpreter(&format!("bpm 120\nlet lead = synth sine\n{}", setup));
+        assert!(!can_stream(&processed), "{setup}");
+    }
+}
+
+fn note(synth_id: &str, midi: u8, start_time: f32, plugin: bool) -> AudioEvent {
+    AudioEvent::Note {
+        midi,
+        start_time,
+        duration: 0.25,
+        velocity: 0.8,
+        synth_id: synth_id.to_string(),
+        synth_def: SynthDefinition {
+            plugin_author: plugin.then(|| "devaloop".to_string()),
+            ..SynthDefinition::default()
+        },
+        pan: 0.0,
+        detune: 0.0,
+        gain: 1.0,
+        attack: None,
+        release: None,
+        delay_time: None,
+        delay_feedback: None,
+        delay_mix: None,
+        reverb_amount: None,
+        drive_amount: None,
+        drive_color: None,
+        effects: None,
+        use_per_note_automation: false,
+    }
+}
+
+#[test]
+fn test_plugin_notes_hold_each_block_of_a_synth() {
+    let events = vec![
+        note("arp", 67, 0.5, true),
+        note("arp", 60, 0.0, true),
+        note("arp", 64, 0.25, true),
+        note("bass", 36, 0.0, true),
+        note("lead", 72, 0.0, false),
+    ];
+    let plugins = PluginNotes::new(&events);
+    let interpreter = AudioInterpreter::new(44100);
+
+    let block = plugins.context_for(&interpreter, "arp", 0.25, 0.5);
+    let held: Vec<(u8, f32)> = block.notes.iter().map(|n| (n.midi, n.offset_ms)).collect();
+    assert_eq!(held, vec![(64, 0.0), (67, 250.0)]);
+    assert_eq!(block.bpm, 120.0);
+    assert_eq!(block.beat_phase, 0.5);
+
+    assert_eq!(
+        plugins
+            .context_for(&interpreter, "arp", 0.0, 0.25)
+            .notes
+            .len(),
+        1
+    );
+    assert!(
+        plugins
+            .context_for(&interpreter, "lead", 0.0, 1.0)
+            .notes
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_streamed_render_matches_in_memory_render() -> Result<()> {
+    let statements = crate::language::syntax::parser::driver::parse(
+        "bpm 120\nlet lead = synth sine\nlet bass = synth square\nlead -> note(C4, { duration: 700, velocity: 30 })\nlead -> note(E4, { duration: 300, velocity: 30 })\nbass -> note(C2, { duration: 900, velocity: 20 })\n",
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(8000);
+    interp.collect_events(&statements)?;
+    assert!(interp.can_stream());
+
+    let whole = interp.render_audio()?;
+    let mut chunks = Vec::new();
+    let mut streamed = Vec::new();
+    let peak = interp.render_audio_streamed(700, &mut |chunk| {
+        chunks.push(chunk.len());
+        streamed.extend_from_slice(chunk);
+        Ok(())
+    })?;
+
+    // Quiet enough that the in-memory render is not normalized either
+    assert!(peak <= 1.0, "peak {peak}");
+    assert!(chunks.len() > 2);
+    assert!(chunks[..chunks.len() - 1].iter().all(|&len| len == 1400));
+    assert_eq!(streamed.len(), whole.len());
+    assert!(
+        streamed
+            .iter()
+            .zip(&whole)
+            .all(|(a, b)| (a - b).abs() < 1e-5)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_block_stream_pulls_the_render_block_by_block() -> Result<()> {
+    use crate::engine::audio::events::AudioEvent;
+    use crate::engine::audio::interpreter::driver::renderer::BlockStream;
+
+    let statements = crate::language::syntax::parser::driver::parse(
+        "bpm 120\nlet lead = synth sine\nlead -> note(C4, { duration: 250, velocity: 30 })\nlead -> note(E4, { duration: 250, velocity: 30 })\n",
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let pass = move || -> Result<AudioInterpreter> {
+        let mut interp = AudioInterpreter::new(8000);
+        interp.collect_events(&statements)?;
+        Ok(interp)
+    };
+    let first = pass.clone()()?;
+    let length = first.events.total_duration();
+    let mut whole = Vec::new();
+    first.render_audio_streamed(4096, &mut |chunk| {
+        whole.extend_from_slice(chunk);
+        Ok(())
+    })?;
+
+    // Played once, the blocks add up to the offline stream and then run dry
+    let mut once = BlockStream::new(Box::new(pass.clone()), false)?;
+    let mut block = vec![0.0; 256];
+    let mut played = Vec::new();
+    while !once.is_finished() {
+        let frames = once.fill(&mut block)?;
+        played.extend_from_slice(&block[..frames * 2]);
+    }
+    assert_eq!(played.len(), whole.len());
+    assert!(played.iter().zip(&whole).all(|(a, b)| (a - b).abs() < 1e-5));
+    assert_eq!(once.fill(&mut block)?, 0);
+
+    // Repeating, a new pass starts where the last one ended and never stops
+    let mut looped = BlockStream::new(Box::new(pass), true)?;
+    for _ in 0..40 {
+        assert_eq!(looped.fill(&mut block)?, 128);
+    }
+    assert_eq!(looped.position(), 40 * 128);
+    let starts: Vec<f32> = looped
+        .take_started()
+        .iter()
+        .map(|event| match event {
+            AudioEvent::Note { start_time, .. } => *start_time,
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(starts, vec![0.0, 0.0, length, length]);
+    assert!(looped.take_started().is_empty());
+    assert!(!looped.is_finished());
+    Ok(())
+}
+
+#[test]
+fn test_block_stream_renders_group_inserts_whole() -> Result<()> {
+    use crate::engine::audio::interpreter::driver::renderer::BlockStream;
+
+    let statements = crate::language::syntax::parser::driver::parse(
+        "bpm 120\nlet pad = synth sine\nstrip pads gain -3db\ngroup pads:\n    pad -> note(A4) -> duration(500) -> velocity(20)\n    pad -> note(E5) -> duration(500) -> velocity(20)\nspawn pads\n",
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let pass = move || -> Result<AudioInterpreter> {
+        let mut interp = AudioInterpreter::new(8000);
+        interp.collect_events(&statements)?;
+        Ok(interp)
+    };
+    let first = pass.clone()()?;
+    assert!(!first.can_stream());
+    let whole = first.render_audio()?;
+
+    let mut stream = BlockStream::new(Box::new(pass), false)?;
+    let mut block = vec![0.0; 256];
+    let mut played = Vec::new();
+    while !stream.is_finished() {
+        let frames = stream.fill(&mut block)?;
+        played.extend_from_slice(&block[..frames * 2]);
+    }
+    assert!(whole.iter().any(|s| s.abs() > 0.01));
+    assert_eq!(&played[..whole.len()], &whole[..]);
+    assert!(played[whole.len()..].iter().all(|s| *s == 0.0));
+    assert_eq!(stream.take_started().len(), 2);
+    Ok(())
+}
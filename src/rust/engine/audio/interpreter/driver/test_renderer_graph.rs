@@ -0,0 +1,34 @@
+use super::*;
+use crate::engine::audio::settings::MixPrecision;
+use crate::language::syntax::parser::driver::parse;
+
+const SOURCE: &str = "bpm 120
+let lead = synth saw
+group leads:
+    lead -> note(C4) -> duration(250)
+    lead -> note(G4) -> duration(250)
+spawn leads
+routing:
+    node $master
+    node bus = leads
+    route bus to $master with volume(0.5)
+";
+
+#[test]
+fn test_graph_renders_in_the_configured_precision() {
+    let statements = parse(SOURCE, "graph.deva".into()).unwrap();
+    let mut interpreter = AudioInterpreter::new(44100);
+    interpreter.mix.precision = MixPrecision::F64;
+    interpreter.collect_all_events(&statements).unwrap();
+    assert!(interpreter.audio_graph.node_names().len() > 1);
+
+    let rendered = interpreter.render_audio().unwrap();
+    let total = (interpreter.calculate_total_duration() * 44100.0).ceil() as usize;
+    let wide = render_audio_graph::<f64>(&interpreter, total).unwrap();
+    let narrow = render_audio_graph::<f32>(&interpreter, total).unwrap();
+    assert!(wide.iter().any(|s| *s != 0.0));
+    assert_eq!(rendered, wide);
+    // Summing in f32 rounds differently, so the f64 path really was taken
+    assert_ne!(wide, narrow);
+    assert!(wide.iter().zip(&narrow).all(|(a, b)| (a - b).abs() < 1e-4));
+}
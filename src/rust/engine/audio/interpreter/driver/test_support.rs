@@ -0,0 +1,25 @@
+//! Fixtures shared by the interpreter test suites
+
+use anyhow::Result;
+
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::language::syntax::ast::Value;
+
+/// Bind a `kit` bank whose `crash` trigger plays `crash.wav`
+pub(crate) fn with_crash_kit(interp: &mut AudioInterpreter) {
+    let mut kit = std::collections::HashMap::new();
+    kit.insert("crash".to_string(), Value::String("crash.wav".to_string()));
+    interp.variables.insert("kit".to_string(), Value::Map(kit));
+}
+
+/// Collect `source` with the crash kit bound; the number of events and the interpreter
+pub(crate) fn crash_count(source: &str) -> Result<(usize, AudioInterpreter)> {
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    with_crash_kit(&mut interp);
+    interp.collect_events(&statements)?;
+    Ok((interp.events.events.len(), interp))
+}
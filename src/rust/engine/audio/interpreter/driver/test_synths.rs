@@ -0,0 +1,91 @@
+use anyhow::Result;
+
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::language::syntax::ast::Value;
+
+fn run(source: &str) -> Result<AudioInterpreter> {
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    interp.collect_events(&statements)?;
+    Ok(interp)
+}
+
+fn error(source: &str) -> String {
+    match run(source) {
+        Ok(_) => panic!("expected an error for {:?}", source),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn test_synth_extends_merges_parent_parameters() -> Result<()> {
+    let interp = run("let basePad = synth saw { attack: 0.5, cutoff: 0.3, lfo: { rate: 2.0, depth: 0.4, target: \"pan\" } }
+synth lead extends basePad { cutoff: 0.7, lfo: { depth: 0.9 } }
+synth bright square extends lead { release: 1.5 }
+");
+    let interp = interp?;
+
+    let lead = &interp.events.synths["lead"];
+    assert_eq!(lead.waveform, "saw");
+    assert_eq!(lead.attack, 0.5);
+    assert_eq!(lead.options["cutoff"], 0.7);
+    // Nested maps merge key by key rather than replacing the parent's
+    let lfo = lead.lfo.as_ref().unwrap();
+    assert_eq!(lfo.depth, 0.9);
+    assert_eq!(
+        lfo.target,
+        crate::engine::audio::lfo::LfoTarget::from_str("pan").unwrap()
+    );
+
+    let bright = &interp.events.synths["bright"];
+    assert_eq!(bright.waveform, "square");
+    assert_eq!(bright.attack, 0.5);
+    assert_eq!(bright.release, 1.5);
+    assert_eq!(bright.options["cutoff"], 0.7);
+
+    // The parent is left untouched and dotted reads see inherited values
+    assert_eq!(interp.events.synths["basePad"].options["cutoff"], 0.3);
+    let Some(Value::Map(lead_map)) = interp.variables.get("lead") else {
+        panic!("lead is not a map");
+    };
+    assert_eq!(lead_map.get("attack"), Some(&Value::Number(0.5)));
+    Ok(())
+}
+
+#[test]
+fn test_synth_extends_reextracts_after_assignment() -> Result<()> {
+    let mut interp = run("let basePad = synth saw { attack: 0.5 }
+synth lead extends basePad {
+    cutoff: 0.7
+}
+")?;
+    interp.handle_assign("lead", "cutoff", &Value::Number(0.2))?;
+    let lead = &interp.events.synths["lead"];
+    assert_eq!(lead.attack, 0.5);
+    assert_eq!(lead.options["cutoff"], 0.2);
+    Ok(())
+}
+
+#[test]
+fn test_synth_extends_rejects_cycles_and_unknown_parents() {
+    let err = error("synth a extends a { cutoff: 0.2 }\n");
+    assert!(err.contains("a -> a"), "{}", err);
+
+    let err = error(
+        "synth a sine { cutoff: 0.2 }
+synth b extends a {}
+synth a extends b { cutoff: 0.4 }
+",
+    );
+    assert!(err.contains("a -> b -> a"), "{}", err);
+
+    let err = error("synth lead extends missing { cutoff: 0.2 }\n");
+    assert!(
+        err.contains("extends unknown definition 'missing'"),
+        "{}",
+        err
+    );
+}
@@ -1,4 +1,5 @@
 use crate::engine::functions::FunctionContext;
+use crate::engine::plugin::effect::{PLUGIN_EFFECT, normalize_effect_list};
 /// Arrow call statement handler
 use anyhow::{Result, anyhow};
 
@@ -112,7 +113,28 @@ pub fn execute_arrow_call(
                         }
                     }
 
-                    unknown_effects.push(crate::language::syntax::ast::Value::Map(effect_map));
+                    let effect = crate::language::syntax::ast::Value::Map(effect_map);
+                    if method_name == PLUGIN_EFFECT {
+                        // Resolve `plugin(alias.export)` now, while the alias is in scope
+                        let normalized = normalize_effect_list(
+                            &crate::language::syntax::ast::Value::Array(vec![effect]),
+                            |path| {
+                                interpreter
+                                    .resolve_value(
+                                        &crate::language::syntax::ast::Value::Identifier(
+                                            path.to_string(),
+                                        ),
+                                    )
+                                    .unwrap_or(crate::language::syntax::ast::Value::Null)
+                            },
+                        )
+                        .map_err(|e| anyhow!(e))?;
+                        if let crate::language::syntax::ast::Value::Array(entries) = normalized {
+                            unknown_effects.extend(entries);
+                        }
+                    } else {
+                        unknown_effects.push(effect);
+                    }
                 }
             }
         }
@@ -5,20 +5,96 @@ use std::sync::atomic::AtomicUsize;
 
 static BG_WORKER_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
+/// A running `loop`/`for`, innermost last on `AudioInterpreter::loop_frames`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopFrame {
+    pub label: Option<String>,
+    /// Zero-based pass index, readable as `$loop.index` or `$loop.<label>`
+    pub index: usize,
+}
+
 impl AudioInterpreter {
     pub fn execute_loop(&mut self, count: &Value, body: &[Statement]) -> Result<()> {
+        self.execute_loop_from(count, body, 0, None).map(|_| ())
+    }
+
+    /// Run a loop whose counted passes are numbered from `base` (used by `@persist loops`).
+    /// Returns the number of passes run.
+    pub fn execute_loop_from(
+        &mut self,
+        count: &Value,
+        body: &[Statement],
+        base: usize,
+        label: Option<&str>,
+    ) -> Result<usize> {
+        // Each loop tracks its own pass index; restore the enclosing loop's afterwards
+        let outer_pass = self.loop_pass;
+        self.loop_frames.push(LoopFrame {
+            label: label.map(str::to_string),
+            index: base,
+        });
+        let result = self.run_loop(count, body, base);
+        self.loop_frames.pop();
+        self.loop_pass = outer_pass;
+        result
+    }
+
+    /// Resolve `$loop.index` (innermost pass), `$loop.depth` and `$loop.<label>`
+    pub fn loop_counter(&self, name: &str) -> Option<Value> {
+        let key = name.strip_prefix("$loop.")?;
+        let index = match key {
+            "index" => self.loop_frames.last()?.index,
+            "depth" => return Some(Value::Number(self.loop_frames.len() as f32)),
+            label => {
+                self.loop_frames
+                    .iter()
+                    .rev()
+                    .find(|frame| frame.label.as_deref() == Some(label))?
+                    .index
+            }
+        };
+        Some(Value::Number(index as f32))
+    }
+
+    fn begin_pass(&mut self, pass: usize) {
+        self.loop_pass = pass;
+        if let Some(frame) = self.loop_frames.last_mut() {
+            frame.index = pass;
+        }
+    }
+
+    /// Consume a pending `break`/`continue` after a pass of the innermost loop.
+    /// Returns true when that loop must stop. A signal aimed at an enclosing
+    /// label is left pending so the body of the outer loop unwinds too.
+    fn end_pass(&mut self) -> bool {
+        if !self.break_flag && !self.continue_flag {
+            return false;
+        }
+        let own_label = self.loop_frames.last().and_then(|f| f.label.as_deref());
+        if let Some(target) = self.loop_jump.as_deref()
+            && own_label != Some(target)
+        {
+            return true;
+        }
+        self.loop_jump = None;
+        self.continue_flag = false;
+        std::mem::take(&mut self.break_flag)
+    }
+
+    fn run_loop(&mut self, count: &Value, body: &[Statement], base: usize) -> Result<usize> {
         match count {
             Value::Number(n) => {
                 let loop_count = (*n) as usize;
-                for _ in 0..loop_count {
+                let mut passes = 0;
+                for pass in 0..loop_count {
+                    self.begin_pass(base + pass);
+                    passes += 1;
                     self.collect_events(body)?;
-                    // If a break was signalled inside the body, consume flag and exit loop
-                    if self.break_flag {
-                        self.break_flag = false;
+                    if self.end_pass() {
                         break;
                     }
                 }
-                Ok(())
+                Ok(passes)
             }
             Value::Identifier(ident) if ident == "pass" => {
                 // offline: run in-place per beat
@@ -32,18 +108,10 @@ impl AudioInterpreter {
 
                     loop {
                         let before_cursor = self.cursor_time;
-                        // Avoid nested beat emission when executing loop body repeatedly:
-                        // suppress beat emission during inner collection so 'on beat'/'on bar'
-                        // handlers are only executed by the outer/top-level collector.
-                        let prev = self.suppress_beat_emit;
-                        self.suppress_beat_emit = true;
-                        let res = self.collect_events(body);
-                        // restore previous flag even if collect_events returned an error
-                        self.suppress_beat_emit = prev;
-                        res?;
+                        self.begin_pass(iter_count);
+                        self.collect_events(body)?;
                         // Break signalled inside loop body -> exit pass loop
-                        if self.break_flag {
-                            self.break_flag = false;
+                        if self.end_pass() {
                             break;
                         }
                         iter_count = iter_count.saturating_add(1);
@@ -60,7 +128,7 @@ impl AudioInterpreter {
                             break;
                         }
                     }
-                    return Ok(());
+                    return Ok(iter_count);
                 }
 
                 // live path handled elsewhere (spawning worker) in previous code paths
@@ -79,14 +147,10 @@ impl AudioInterpreter {
                 let render_target = self.special_vars.total_duration.max(1.0);
                 loop {
                     let before_cursor = self.cursor_time;
-                    let prev = self.suppress_beat_emit;
-                    self.suppress_beat_emit = true;
-                    let res = self.collect_events(body);
-                    self.suppress_beat_emit = prev;
-                    res?;
+                    self.begin_pass(iter_count);
+                    self.collect_events(body)?;
                     // Break signalled inside loop body -> exit pass loop
-                    if self.break_flag {
-                        self.break_flag = false;
+                    if self.end_pass() {
                         break;
                     }
                     iter_count = iter_count.saturating_add(1);
@@ -101,22 +165,20 @@ impl AudioInterpreter {
                         break;
                     }
                 }
-                Ok(())
+                Ok(iter_count)
             }
             Value::Null => {
                 // Indefinite loop: run until no further audio is produced or time limit hit
                 let start_time = self.cursor_time;
                 let time_limit = 60.0_f32;
+                let mut pass: usize = 0;
                 loop {
                     let before_cursor = self.cursor_time;
-                    let prev = self.suppress_beat_emit;
-                    self.suppress_beat_emit = true;
-                    let res = self.collect_events(body);
-                    self.suppress_beat_emit = prev;
-                    res?;
+                    self.begin_pass(pass);
+                    pass += 1;
+                    self.collect_events(body)?;
                     // Break signalled inside indefinite loop -> exit
-                    if self.break_flag {
-                        self.break_flag = false;
+                    if self.end_pass() {
                         break;
                     }
                     if (self.cursor_time - before_cursor).abs() < f32::EPSILON {
@@ -126,8 +188,15 @@ impl AudioInterpreter {
                         break;
                     }
                 }
-                Ok(())
+                Ok(pass)
             }
+            // `loop bars:` with the count held in a variable
+            Value::Identifier(_) => match self.resolve_value(count)? {
+                resolved @ Value::Number(_) => self.run_loop(&resolved, body, base),
+                other => {
+                    anyhow::bail!("❌ Loop count must resolve to a number, found: {:?}", other)
+                }
+            },
             other => anyhow::bail!(
                 "❌ Loop iterator must be a number, 'pass' or null, found: {:?}",
                 other
@@ -140,6 +209,7 @@ impl AudioInterpreter {
         variable: &str,
         iterable: &Value,
         body: &[Statement],
+        label: Option<&str>,
     ) -> Result<()> {
         let items = match iterable {
             Value::Array(arr) => arr.clone(),
@@ -151,41 +221,42 @@ impl AudioInterpreter {
                 }
             }
             Value::Range { start, end } => {
-                let start_val = match start.as_ref() {
-                    Value::Number(n) => *n as i32,
+                let start_val = match self.resolve_value(start)? {
+                    Value::Number(n) => n as i32,
                     _ => anyhow::bail!("❌ Range start must be a number"),
                 };
-                let end_val = match end.as_ref() {
-                    Value::Number(n) => *n as i32,
+                let end_val = match self.resolve_value(end)? {
+                    Value::Number(n) => n as i32,
                     _ => anyhow::bail!("❌ Range end must be a number"),
                 };
                 (start_val..end_val)
                     .map(|i| Value::Number(i as f32))
                     .collect()
             }
+            Value::Call { .. } => match self.resolve_value(iterable)? {
+                Value::Array(arr) => arr,
+                other => anyhow::bail!(
+                    "❌ For iterable must evaluate to an array, found: {:?}",
+                    other
+                ),
+            },
             _ => anyhow::bail!(
                 "❌ For iterable must be an array or range, found: {:?}",
                 iterable
             ),
         };
 
-        for item in items.iter() {
+        let outer_pass = self.loop_pass;
+        self.loop_frames.push(LoopFrame {
+            label: label.map(str::to_string),
+            index: 0,
+        });
+        let mut result = Ok(());
+        for (pass, item) in items.iter().enumerate() {
+            self.begin_pass(pass);
             let old_value = self.variables.insert(variable.to_string(), item.clone());
-            self.collect_events(body)?;
-            // If a break was signalled inside the body, consume flag and exit for-loop
-            if self.break_flag {
-                self.break_flag = false;
-                // Restore previous variable state before exiting
-                match old_value {
-                    Some(val) => {
-                        self.variables.insert(variable.to_string(), val);
-                    }
-                    None => {
-                        self.variables.remove(variable);
-                    }
-                }
-                break;
-            }
+            result = self.collect_events(body);
+            // Restore the previous variable state before the next pass or exit
             match old_value {
                 Some(val) => {
                     self.variables.insert(variable.to_string(), val);
@@ -194,8 +265,13 @@ impl AudioInterpreter {
                     self.variables.remove(variable);
                 }
             }
+            if result.is_err() || self.end_pass() {
+                break;
+            }
         }
+        self.loop_frames.pop();
+        self.loop_pass = outer_pass;
 
-        Ok(())
+        result
     }
 }
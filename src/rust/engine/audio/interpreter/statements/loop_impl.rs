@@ -34,15 +34,7 @@ impl AudioInterpreter {
 
                     loop {
                         let before_cursor = self.cursor_time;
-                        // Avoid nested beat emission when executing loop body repeatedly:
-                        // suppress beat emission during inner collection so 'on beat'/'on bar'
-                        // handlers are only executed by the outer/top-level collector.
-                        let prev = self.suppress_beat_emit;
-                        self.suppress_beat_emit = true;
-                        let res = self.collect_events(body);
-                        // restore previous flag even if collect_events returned an error
-                        self.suppress_beat_emit = prev;
-                        res?;
+                        self.collect_events(body)?;
                         // Break signalled inside loop body -> exit pass loop
                         if self.break_flag {
                             self.break_flag = false;
@@ -77,11 +69,7 @@ impl AudioInterpreter {
                 let render_target = self.special_vars.total_duration.max(1.0);
                 loop {
                     let before_cursor = self.cursor_time;
-                    let prev = self.suppress_beat_emit;
-                    self.suppress_beat_emit = true;
-                    let res = self.collect_events(body);
-                    self.suppress_beat_emit = prev;
-                    res?;
+                    self.collect_events(body)?;
                     // Break signalled inside loop body -> exit pass loop
                     if self.break_flag {
                         self.break_flag = false;
@@ -103,11 +91,7 @@ impl AudioInterpreter {
                 let time_limit = 60.0_f32;
                 loop {
                     let before_cursor = self.cursor_time;
-                    let prev = self.suppress_beat_emit;
-                    self.suppress_beat_emit = true;
-                    let res = self.collect_events(body);
-                    self.suppress_beat_emit = prev;
-                    res?;
+                    self.collect_events(body)?;
                     // Break signalled inside indefinite loop -> exit
                     if self.break_flag {
                         self.break_flag = false;
@@ -0,0 +1,370 @@
+//! Macro controls: one 0..1 value scaling several parameters at once
+//!
+//! ```deva
+//! macro intensity -> [drums.gain, lead.cutoff x0.5, reverb.mix x0.3]
+//! ```
+//!
+//! A macro is a top-level variable (1.0 unless overridden with `--set intensity=0.4`) and
+//! each target is `owner.param` with an optional depth. At depth `d` the parameter is
+//! multiplied by `1 - d + d * value`: full depth follows the macro down to silence, `x0.5`
+//! keeps at least half of the written value. The owner is a synth (`lead.cutoff`), a bank
+//! alias (`drums.gain`) or an effect type (`reverb.mix`). In live sessions macros also
+//! move from keys, OSC and MIDI CC (see `services::live::play::macros`).
+
+use crate::engine::audio::events::{AudioEvent, AudioEventList, SynthDefinition};
+use crate::language::syntax::ast::Value;
+use anyhow::{Result, anyhow};
+
+/// One parameter a macro scales
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacroTarget {
+    pub owner: String,
+    pub param: String,
+    /// How much of the parameter follows the macro, 0..1
+    pub depth: f32,
+}
+
+impl MacroTarget {
+    /// Parse `lead.cutoff` or `lead.cutoff x0.5`
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut words = raw.split_whitespace();
+        let path = words.next().ok_or_else(|| anyhow!("empty macro target"))?;
+        let (owner, param) = path
+            .trim_start_matches('.')
+            .rsplit_once('.')
+            .filter(|(owner, param)| !owner.is_empty() && !param.is_empty())
+            .ok_or_else(|| anyhow!("macro target '{}' must be owner.param", path))?;
+        let depth = match (words.next(), words.next()) {
+            (None, _) => 1.0,
+            (Some(depth), None) => depth
+                .strip_prefix(['x', '*'])
+                .and_then(|depth| depth.parse::<f32>().ok())
+                .filter(|depth| (0.0..=1.0).contains(depth))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "invalid depth '{}' for macro target {}, expected x0..x1",
+                        depth,
+                        path
+                    )
+                })?,
+            _ => return Err(anyhow!("unexpected text after macro target '{}'", raw)),
+        };
+        Ok(Self {
+            owner: owner.to_string(),
+            param: param.to_string(),
+            depth,
+        })
+    }
+
+    /// Multiplier applied to the parameter for a macro `value`
+    pub fn factor(&self, value: f32) -> f32 {
+        1.0 - self.depth + self.depth * value.clamp(0.0, 1.0)
+    }
+}
+
+impl std::fmt::Display for MacroTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.owner, self.param)?;
+        if self.depth != 1.0 {
+            write!(f, " x{}", self.depth)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacroControl {
+    pub name: String,
+    pub value: f32,
+    pub targets: Vec<MacroTarget>,
+}
+
+/// Macros declared by the script, in declaration order
+#[derive(Debug, Clone, Default)]
+pub struct MacroRegistry {
+    macros: Vec<MacroControl>,
+}
+
+impl MacroRegistry {
+    /// Declare (or redeclare) a macro
+    pub fn define(&mut self, name: &str, value: f32, targets: Vec<MacroTarget>) {
+        let control = MacroControl {
+            name: name.to_string(),
+            value: value.clamp(0.0, 1.0),
+            targets,
+        };
+        match self.macros.iter_mut().find(|m| m.name == name) {
+            Some(existing) => *existing = control,
+            None => self.macros.push(control),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MacroControl> {
+        self.macros.iter().find(|m| m.name == name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.macros.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MacroControl> {
+        self.macros.iter()
+    }
+
+    /// Scale every target in the collected events. `bank_uri` maps a bank alias to the
+    /// sample URI prefix of its triggers.
+    pub fn apply(&self, events: &mut AudioEventList, bank_uri: impl Fn(&str) -> Option<String>) {
+        for control in &self.macros {
+            for target in &control.targets {
+                let factor = target.factor(control.value);
+                if factor == 1.0 {
+                    continue;
+                }
+                let bank = bank_uri(&target.owner);
+                for event in &mut events.events {
+                    scale_event(event, target, bank.as_deref(), factor);
+                }
+                for effects in events.group_effects.values_mut() {
+                    scale_effect_param(effects, &target.owner, &target.param, factor);
+                }
+            }
+        }
+    }
+}
+
+fn scale_event(event: &mut AudioEvent, target: &MacroTarget, bank: Option<&str>, factor: f32) {
+    let param = target.param.as_str();
+    match event {
+        AudioEvent::Note {
+            synth_id,
+            synth_def,
+            velocity,
+            gain,
+            pan,
+            detune,
+            attack,
+            release,
+            effects,
+            ..
+        } => {
+            if *synth_id == target.owner {
+                match param {
+                    "gain" | "volume" => *gain *= factor,
+                    "velocity" => *velocity *= factor,
+                    "pan" => *pan *= factor,
+                    "detune" => *detune *= factor,
+                    "attack" => scale_envelope(attack, synth_def.attack, factor),
+                    "release" => scale_envelope(release, synth_def.release, factor),
+                    _ => scale_synth_param(synth_def, param, factor),
+                }
+            }
+            if let Some(effects) = effects {
+                scale_effect_param(effects, &target.owner, param, factor);
+            }
+        }
+        AudioEvent::Chord {
+            synth_id,
+            synth_def,
+            velocity,
+            gain,
+            pan,
+            detune,
+            spread,
+            attack,
+            release,
+            effects,
+            ..
+        } => {
+            if *synth_id == target.owner {
+                match param {
+                    "gain" | "volume" => *gain *= factor,
+                    "velocity" => *velocity *= factor,
+                    "pan" => *pan *= factor,
+                    "detune" => *detune *= factor,
+                    "spread" => *spread *= factor,
+                    "attack" => scale_envelope(attack, synth_def.attack, factor),
+                    "release" => scale_envelope(release, synth_def.release, factor),
+                    _ => scale_synth_param(synth_def, param, factor),
+                }
+            }
+            if let Some(effects) = effects {
+                scale_effect_param(effects, &target.owner, param, factor);
+            }
+        }
+        AudioEvent::Sample {
+            uri,
+            velocity,
+            effects,
+            ..
+        } => {
+            if bank.is_some_and(|prefix| uri.starts_with(prefix)) {
+                match param {
+                    "gain" | "volume" | "velocity" => *velocity *= factor,
+                    _ => {
+                        if let Some(Value::Map(map)) = effects
+                            && let Some(Value::Number(value)) = map.get_mut(param)
+                        {
+                            *value *= factor;
+                        }
+                    }
+                }
+            }
+            if let Some(effects) = effects {
+                scale_effect_param(effects, &target.owner, param, factor);
+            }
+        }
+    }
+}
+
+/// Envelope times are stored in milliseconds on the event but in seconds on the synth
+fn scale_envelope(event_ms: &mut Option<f32>, synth_seconds: f32, factor: f32) {
+    *event_ms = Some(event_ms.unwrap_or(synth_seconds * 1000.0) * factor);
+}
+
+fn scale_synth_param(synth: &mut SynthDefinition, param: &str, factor: f32) {
+    match param {
+        "decay" => synth.decay *= factor,
+        "sustain" => synth.sustain *= factor,
+        "cutoff" => synth.filters.iter_mut().for_each(|f| f.cutoff *= factor),
+        "resonance" => synth.filters.iter_mut().for_each(|f| f.resonance *= factor),
+        _ => {
+            if let Some(value) = synth.options.get_mut(param) {
+                *value *= factor;
+            }
+        }
+    }
+}
+
+/// Scale `param` of every `effect` entry in an effect list or map, in both the
+/// `{ type: reverb, mix: 0.3 }` and `{ reverb: { mix: 0.3 } }` shapes
+pub fn scale_effect_param(effects: &mut Value, effect: &str, param: &str, factor: f32) {
+    match effects {
+        Value::Array(items) => {
+            for item in items {
+                scale_effect_param(item, effect, param, factor);
+            }
+        }
+        Value::Map(map) => {
+            let is_effect = matches!(
+                map.get("type").or_else(|| map.get("effect")),
+                Some(Value::String(name) | Value::Identifier(name)) if name == effect
+            );
+            if is_effect && let Some(Value::Number(value)) = map.get_mut(param) {
+                *value *= factor;
+            }
+            if let Some(Value::Map(params)) = map.get_mut(effect)
+                && let Some(Value::Number(value)) = params.get_mut(param)
+            {
+                *value *= factor;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// How a live control moves a macro
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MacroChange {
+    Set(f32),
+    Step(f32),
+}
+
+impl MacroChange {
+    /// New macro value from `current`, kept within 0..1
+    pub fn apply(self, current: f32) -> f32 {
+        match self {
+            MacroChange::Set(value) => value,
+            MacroChange::Step(step) => current + step,
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+/// Computer key moving a macro in live mode
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacroKey {
+    pub key: char,
+    pub name: String,
+    pub change: MacroChange,
+}
+
+impl MacroKey {
+    /// Parse `]=intensity+0.1`, `[=intensity-0.1` or `0=intensity@0` (set to a value)
+    pub fn parse(raw: &str) -> Result<Self> {
+        let (key, action) = raw
+            .split_once("->")
+            .or_else(|| raw.split_once('='))
+            .ok_or_else(|| anyhow!("expected KEY=MACRO+STEP, got '{}'", raw))?;
+        let key = key.trim();
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(c), None) if !c.is_whitespace() => c.to_ascii_lowercase(),
+            _ => return Err(anyhow!("key must be a single character, got '{}'", key)),
+        };
+
+        let action = action.trim();
+        let split = action.find(['+', '-', '@']).ok_or_else(|| {
+            anyhow!(
+                "expected MACRO+STEP, MACRO-STEP or MACRO@VALUE, got '{}'",
+                action
+            )
+        })?;
+        let (name, amount) = action.split_at(split);
+        let number: f32 = amount[1..]
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("invalid amount '{}' for macro key '{}'", &amount[1..], key))?;
+        let change = match &amount[..1] {
+            "+" => MacroChange::Step(number),
+            "-" => MacroChange::Step(-number),
+            _ => MacroChange::Set(number),
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("missing macro name for key '{}'", key));
+        }
+        Ok(Self {
+            key,
+            name: name.to_string(),
+            change,
+        })
+    }
+}
+
+/// MIDI control change driving a macro across its range (value 0..127 -> 0..1)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacroCc {
+    pub controller: u8,
+    pub name: String,
+}
+
+impl MacroCc {
+    /// Parse `74=intensity`
+    pub fn parse(raw: &str) -> Result<Self> {
+        let (controller, name) = raw
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected CC=MACRO, got '{}'", raw))?;
+        let controller = controller
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|cc| *cc < 128)
+            .ok_or_else(|| anyhow!("controller must be 0-127, got '{}'", controller.trim()))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("missing macro name for CC {}", controller));
+        }
+        Ok(Self {
+            controller,
+            name: name.to_string(),
+        })
+    }
+
+    pub fn change(value: u8) -> MacroChange {
+        MacroChange::Set(value.min(127) as f32 / 127.0)
+    }
+}
+
+#[cfg(test)]
+#[path = "test_macros.rs"]
+mod tests;
@@ -6,17 +6,25 @@ use std::path::Path;
 
 #[cfg(any(feature = "cli", feature = "wasm"))]
 use midly::{
-    Format, Header, MetaMessage, MidiMessage, Smf, Timing, Track, TrackEvent, TrackEventKind,
+    Format, Header, MetaMessage, MidiMessage, PitchBend, Smf, Timing, Track, TrackEvent,
+    TrackEventKind,
 };
 
 #[cfg(all(target_arch = "wasm32", not(any(feature = "cli", feature = "wasm"))))]
 use crate::midly::{
-    Format, Header, MetaMessage, MidiMessage, Smf, Timing, Track, TrackEvent, TrackEventKind,
+    Format, Header, MetaMessage, MidiMessage, PitchBend, Smf, Timing, Track, TrackEvent,
+    TrackEventKind,
 };
 
 #[cfg(feature = "cli")]
 use std::collections::HashMap;
 
+use crate::engine::audio::automation::{AutomationEnvelope, AutomationRegistry};
+use crate::engine::audio::tempo::TempoMap;
+
+/// Tempo assumed by Standard MIDI Files before their first tempo event
+const DEFAULT_MIDI_BPM: f32 = 120.0;
+
 /// Load a MIDI file and return a Value::Map representing the MIDI data
 #[cfg(feature = "cli")]
 pub fn load_midi_file(path: &Path) -> Result<Value> {
@@ -29,9 +37,6 @@ pub fn load_midi_file(path: &Path) -> Result<Value> {
     // Convert MIDI data to a map
     let mut midi_map = HashMap::new();
 
-    // Defaults
-    let mut bpm = 120.0f32;
-    let mut tempo_us_per_quarter: u32 = 500_000; // default 120 BPM
     let mut notes: Vec<Value> = Vec::new();
 
     // Determine ticks per beat from header timing
@@ -40,6 +45,14 @@ pub fn load_midi_file(path: &Path) -> Result<Value> {
         _ => 480u32,
     };
 
+    // Tempo and meter changes may sit on any track; gather them first so note times
+    // follow every change instead of the last tempo seen on earlier tracks
+    let tempo_map = read_tempo_map(&smf, ticks_per_beat);
+    let bpm = tempo_map.bpm_at(0.0, DEFAULT_MIDI_BPM);
+    let ticks_to_ms = |ticks: u32| {
+        tempo_map.seconds_at(ticks as f32 / ticks_per_beat as f32, DEFAULT_MIDI_BPM) * 1000.0
+    };
+
     // Active note-on map to pair note-offs: key = (track, channel, key) -> Vec of indices in notes
     let mut active: std::collections::HashMap<(usize, u8, u8), Vec<usize>> =
         std::collections::HashMap::new();
@@ -51,90 +64,67 @@ pub fn load_midi_file(path: &Path) -> Result<Value> {
         for event in track {
             current_ticks = current_ticks.wrapping_add(event.delta.as_int() as u32);
 
-            match event.kind {
-                TrackEventKind::Midi { channel, message } => {
-                    let chan = channel.as_int();
-                    match message {
-                        MidiMessage::NoteOn { key, vel } => {
-                            if vel.as_int() > 0 {
-                                // compute time in ms from ticks using current tempo
-                                let time_ms = (current_ticks as f32)
-                                    * (tempo_us_per_quarter as f32)
-                                    / (ticks_per_beat as f32)
-                                    / 1000.0;
-
-                                let mut note_map = HashMap::new();
-                                note_map.insert(
-                                    "tick".to_string(),
-                                    Value::Number(current_ticks as f32),
-                                );
-                                note_map.insert("time".to_string(), Value::Number(time_ms));
-                                // store beat position (useful to rescale when interpreter BPM changes)
-                                let beats = time_ms * (bpm as f32) / 60000.0; // time_ms / (60000/midi_bpm)
-                                note_map.insert("beat".to_string(), Value::Number(beats));
-                                note_map
-                                    .insert("note".to_string(), Value::Number(key.as_int() as f32));
-                                note_map.insert(
-                                    "velocity".to_string(),
-                                    Value::Number(vel.as_int() as f32),
-                                );
-                                note_map
-                                    .insert("track".to_string(), Value::Number(track_idx as f32));
-                                note_map.insert("channel".to_string(), Value::Number(chan as f32));
-
-                                notes.push(Value::Map(note_map));
-
-                                // record active index for pairing
-                                let idx = notes.len() - 1;
-                                active
-                                    .entry((track_idx, chan as u8, key.as_int() as u8))
-                                    .or_default()
-                                    .push(idx);
-                            }
+            if let TrackEventKind::Midi { channel, message } = event.kind {
+                let chan = channel.as_int();
+                match message {
+                    MidiMessage::NoteOn { key, vel } => {
+                        if vel.as_int() > 0 {
+                            let time_ms = ticks_to_ms(current_ticks);
+
+                            let mut note_map = HashMap::new();
+                            note_map
+                                .insert("tick".to_string(), Value::Number(current_ticks as f32));
+                            note_map.insert("time".to_string(), Value::Number(time_ms));
+                            // store beat position (useful to rescale when interpreter BPM changes)
+                            let beats = current_ticks as f32 / ticks_per_beat as f32;
+                            note_map.insert("beat".to_string(), Value::Number(beats));
+                            note_map.insert("note".to_string(), Value::Number(key.as_int() as f32));
+                            note_map
+                                .insert("velocity".to_string(), Value::Number(vel.as_int() as f32));
+                            note_map.insert("track".to_string(), Value::Number(track_idx as f32));
+                            note_map.insert("channel".to_string(), Value::Number(chan as f32));
+
+                            notes.push(Value::Map(note_map));
+
+                            // record active index for pairing
+                            let idx = notes.len() - 1;
+                            active
+                                .entry((track_idx, chan as u8, key.as_int() as u8))
+                                .or_default()
+                                .push(idx);
                         }
-                        MidiMessage::NoteOff { key, .. } => {
-                            // pair with most recent active note-on for same track/channel/key
-                            let key_tuple = (track_idx, channel.as_int() as u8, key.as_int() as u8);
-                            if let Some(vec_idxs) = active.get_mut(&key_tuple) {
-                                if let Some(on_idx) = vec_idxs.pop() {
-                                    // compute duration from ticks
-                                    // find onset tick stored in notes[on_idx]
-                                    if let Some(Value::Map(on_map)) = notes.get_mut(on_idx) {
-                                        if let Some(Value::Number(on_tick)) = on_map.get("tick") {
-                                            let onset_ticks = *on_tick as u32;
-                                            let dur_ticks =
-                                                current_ticks.saturating_sub(onset_ticks);
-                                            let duration_ms = (dur_ticks as f32)
-                                                * (tempo_us_per_quarter as f32)
-                                                / (ticks_per_beat as f32)
-                                                / 1000.0;
-                                            on_map.insert(
-                                                "duration".to_string(),
-                                                Value::Number(duration_ms),
-                                            );
-                                            // also store duration in beats for easier rescaling
-                                            let duration_beats =
-                                                duration_ms * (bpm as f32) / 60000.0;
-                                            on_map.insert(
-                                                "duration_beats".to_string(),
-                                                Value::Number(duration_beats),
-                                            );
-                                        }
+                    }
+                    MidiMessage::NoteOff { key, .. } => {
+                        // pair with most recent active note-on for same track/channel/key
+                        let key_tuple = (track_idx, channel.as_int() as u8, key.as_int() as u8);
+                        if let Some(vec_idxs) = active.get_mut(&key_tuple) {
+                            if let Some(on_idx) = vec_idxs.pop() {
+                                // compute duration from ticks
+                                // find onset tick stored in notes[on_idx]
+                                if let Some(Value::Map(on_map)) = notes.get_mut(on_idx) {
+                                    if let Some(Value::Number(on_tick)) = on_map.get("tick") {
+                                        let onset_ticks = *on_tick as u32;
+                                        let dur_ticks = current_ticks.saturating_sub(onset_ticks);
+                                        let duration_ms =
+                                            ticks_to_ms(current_ticks) - ticks_to_ms(onset_ticks);
+                                        on_map.insert(
+                                            "duration".to_string(),
+                                            Value::Number(duration_ms),
+                                        );
+                                        // also store duration in beats for easier rescaling
+                                        let duration_beats =
+                                            dur_ticks as f32 / ticks_per_beat as f32;
+                                        on_map.insert(
+                                            "duration_beats".to_string(),
+                                            Value::Number(duration_beats),
+                                        );
                                     }
                                 }
                             }
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
-                TrackEventKind::Meta(meta) => {
-                    // Extract tempo if present
-                    if let MetaMessage::Tempo(t) = meta {
-                        tempo_us_per_quarter = t.as_int();
-                        bpm = 60_000_000.0f32 / tempo_us_per_quarter as f32;
-                    }
-                }
-                _ => {}
             }
         }
     }
@@ -160,11 +150,35 @@ pub fn load_midi_file(path: &Path) -> Result<Value> {
         Value::Number(ticks_per_beat as f32),
     );
     midi_map.insert("notes".to_string(), Value::Array(notes));
+    midi_map.insert("tempo_map".to_string(), tempo_map.to_value());
     midi_map.insert("type".to_string(), Value::String("midi".to_string()));
 
     Ok(Value::Map(midi_map))
 }
 
+/// Collect tempo and time-signature meta events from every track, positioned in beats
+#[cfg(feature = "cli")]
+fn read_tempo_map(smf: &Smf, ticks_per_beat: u32) -> TempoMap {
+    let mut tempo_map = TempoMap::new();
+    for track in &smf.tracks {
+        let mut ticks: u32 = 0;
+        for event in track {
+            ticks = ticks.wrapping_add(event.delta.as_int());
+            let beat = ticks as f32 / ticks_per_beat as f32;
+            match event.kind {
+                TrackEventKind::Meta(MetaMessage::Tempo(t)) if t.as_int() > 0 => {
+                    tempo_map.push_tempo(beat, 60_000_000.0 / t.as_int() as f32);
+                }
+                TrackEventKind::Meta(MetaMessage::TimeSignature(numerator, denominator, ..)) => {
+                    tempo_map.push_meter(beat, numerator, 1u8 << denominator.min(7));
+                }
+                _ => {}
+            }
+        }
+    }
+    tempo_map
+}
+
 #[cfg(not(feature = "cli"))]
 pub fn load_midi_file(_path: &Path) -> Result<Value> {
     Err(anyhow!("MIDI loading not available without 'cli' feature"))
@@ -175,126 +189,23 @@ pub fn load_midi_file(_path: &Path) -> Result<Value> {
 // ============================================================================
 
 /// Export AudioEvents to MIDI bytes (for WASM)
-pub fn events_to_midi_bytes(events: &[AudioEvent], bpm: f32) -> Result<Vec<u8>> {
+///
+/// With a non-empty `tempo_map`, note times are placed on the map's beat grid and
+/// its tempo and time-signature changes are written; otherwise a flat `bpm` applies.
+/// With `automation`, each synth's automated parameters are written as controller lanes.
+/// `markers` (seconds and name, from `mark` statements) become marker meta events.
+pub fn events_to_midi_bytes(
+    events: &[AudioEvent],
+    bpm: f32,
+    tempo_map: &TempoMap,
+    automation: Option<&AutomationRegistry>,
+    markers: &[(f32, String)],
+) -> Result<Vec<u8>> {
     if events.is_empty() {
         return Err(anyhow!("No events to export"));
     }
 
-    // Create MIDI header (single track format)
-    let ticks_per_beat = 480; // Standard MIDI resolution
-    let header = Header::new(Format::SingleTrack, Timing::Metrical(ticks_per_beat.into()));
-
-    // Create track events list
-    let mut track_events = Vec::new();
-
-    // Add tempo meta event at start
-    let tempo_us_per_quarter = (60_000_000.0 / bpm) as u32;
-    track_events.push(TrackEvent {
-        delta: 0.into(),
-        kind: TrackEventKind::Meta(MetaMessage::Tempo(tempo_us_per_quarter.into())),
-    });
-
-    // Collect all note events (expand chords to individual notes)
-    let mut midi_notes = Vec::new();
-
-    for event in events {
-        match event {
-            AudioEvent::Note {
-                midi,
-                start_time,
-                duration,
-                velocity,
-                ..
-            } => {
-                midi_notes.push(MidiNote {
-                    note: *midi,
-                    start: *start_time,
-                    duration: *duration,
-                    velocity: *velocity,
-                });
-            }
-            AudioEvent::Chord {
-                midis,
-                start_time,
-                duration,
-                velocity,
-                ..
-            } => {
-                // Expand chord into individual notes
-                for &note in midis {
-                    midi_notes.push(MidiNote {
-                        note,
-                        start: *start_time,
-                        duration: *duration,
-                        velocity: *velocity,
-                    });
-                }
-            }
-            AudioEvent::Sample { .. } => {
-                // Samples are not exported to MIDI
-            } // Log messages are stored separately in AudioEventList.logs and are not part of AudioEvent
-        }
-    }
-
-    // Sort by start time
-    midi_notes.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-
-    // Convert events to MIDI messages with proper delta timing
-    let mut midi_messages: Vec<MidiEventTimed> = Vec::new();
-
-    for note in &midi_notes {
-        let start_ticks = time_to_ticks(note.start, bpm, ticks_per_beat);
-        let end_ticks = time_to_ticks(note.start + note.duration, bpm, ticks_per_beat);
-
-        // Note On
-        midi_messages.push(MidiEventTimed {
-            ticks: start_ticks,
-            message: MidiMessage::NoteOn {
-                key: note.note.into(),
-                vel: ((note.velocity * 127.0) as u8).into(),
-            },
-        });
-
-        // Note Off
-        midi_messages.push(MidiEventTimed {
-            ticks: end_ticks,
-            message: MidiMessage::NoteOff {
-                key: note.note.into(),
-                vel: 0.into(),
-            },
-        });
-    }
-
-    // Sort all messages by time
-    midi_messages.sort_by_key(|msg| msg.ticks);
-
-    // Convert to delta times and create TrackEvents
-    let mut last_ticks = 0u32;
-
-    for msg in midi_messages {
-        let delta = msg.ticks.saturating_sub(last_ticks);
-
-        track_events.push(TrackEvent {
-            delta: delta.into(),
-            kind: TrackEventKind::Midi {
-                channel: 0.into(),
-                message: msg.message,
-            },
-        });
-
-        last_ticks = msg.ticks;
-    }
-
-    // End of track marker
-    track_events.push(TrackEvent {
-        delta: 0.into(),
-        kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
-    });
-
-    // Create SMF and write to memory buffer
-    let track = Track::from(track_events);
-    let mut smf = Smf::new(header);
-    smf.tracks.push(track);
+    let (smf, _) = build_smf(events, bpm, tempo_map, automation, markers);
 
     // Write to memory buffer
     let mut buffer = Vec::new();
@@ -306,163 +217,574 @@ pub fn events_to_midi_bytes(events: &[AudioEvent], bpm: f32) -> Result<Vec<u8>>
 
 /// Export AudioEvents to a standard MIDI file
 #[cfg(feature = "cli")]
-pub fn export_midi_file(events: &[AudioEvent], output_path: &Path, bpm: f32) -> Result<()> {
+pub fn export_midi_file(
+    events: &[AudioEvent],
+    output_path: &Path,
+    bpm: f32,
+    tempo_map: &TempoMap,
+    automation: Option<&AutomationRegistry>,
+    markers: &[(f32, String)],
+) -> Result<()> {
     if events.is_empty() {
         return Err(anyhow!("No events to export"));
     }
 
-    // Create MIDI header (single track format)
-    let ticks_per_beat = 480; // Standard MIDI resolution
-    let header = Header::new(Format::SingleTrack, Timing::Metrical(ticks_per_beat.into()));
+    let (smf, note_count) = build_smf(events, bpm, tempo_map, automation, markers);
 
-    // Create track events list
-    let mut track_events = Vec::new();
+    // Write to file directly (midly 0.5 API)
+    smf.save(output_path)
+        .map_err(|e| anyhow!("Failed to write MIDI file {}: {}", output_path.display(), e))?;
 
-    // Add tempo meta event at start
-    let tempo_us_per_quarter = (60_000_000.0 / bpm) as u32;
-    track_events.push(TrackEvent {
-        delta: 0.into(),
-        kind: TrackEventKind::Meta(MetaMessage::Tempo(tempo_us_per_quarter.into())),
-    });
+    println!(
+        "✅ MIDI exported: {} ({} events in, {} notes written)",
+        output_path.display(),
+        events.len(),
+        note_count
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "cli"))]
+pub fn export_midi_file(
+    _events: &[AudioEvent],
+    _output_path: &Path,
+    _bpm: f32,
+    _tempo_map: &TempoMap,
+    _automation: Option<&AutomationRegistry>,
+    _markers: &[(f32, String)],
+) -> Result<()> {
+    Err(anyhow!("MIDI export not available without 'cli' feature"))
+}
+
+/// Standard MIDI resolution used for exports
+const TICKS_PER_BEAT: u16 = 480;
+
+/// Pitch-bend range announced (RPN 0) on tracks that bend, in semitones
+const BEND_RANGE_SEMITONES: f32 = 12.0;
+
+/// Spacing of glide and controller points, in ticks (a 64th note)
+const CURVE_STEP_TICKS: usize = TICKS_PER_BEAT as usize / 16;
+
+/// Automated parameters written as controller lanes; `ccN` parameters map to controller N
+const AUTOMATION_CONTROLLERS: [(&str, u8); 6] = [
+    ("cutoff", 74),
+    ("resonance", 71),
+    ("volume", 7),
+    ("gain", 7),
+    ("pan", 10),
+    ("reverb", 91),
+];
+
+/// Build a multi-track SMF: a conductor track with the tempo and meter changes and the
+/// cue markers, then one
+/// track (on its own channel) per synth with its notes, per-note pitch bends (detune and
+/// `glide` from the previous note) and controller lanes sampled from its automation.
+/// Returns it with the number of notes written.
+fn build_smf<'a>(
+    events: &[AudioEvent],
+    bpm: f32,
+    tempo_map: &TempoMap,
+    automation: Option<&AutomationRegistry>,
+    markers: &'a [(f32, String)],
+) -> (Smf<'a>, usize) {
+    let header = Header::new(Format::Parallel, Timing::Metrical(TICKS_PER_BEAT.into()));
+    let mut smf = Smf::new(header);
 
-    // Collect all note events (expand chords to individual notes)
-    let mut midi_notes = Vec::new();
+    // Tempo and meter changes live on the conductor track
+    let mut conductor: Vec<MidiEventTimed<'a>> = Vec::new();
+    if tempo_map.has_flat_tempo() {
+        conductor.push(MidiEventTimed {
+            ticks: 0,
+            kind: tempo_event(bpm),
+        });
+    } else {
+        for change in &tempo_map.tempos {
+            conductor.push(MidiEventTimed {
+                ticks: beats_to_ticks(change.beat, TICKS_PER_BEAT),
+                kind: tempo_event(change.bpm),
+            });
+        }
+    }
+    for change in &tempo_map.meters {
+        conductor.push(MidiEventTimed {
+            ticks: beats_to_ticks(change.beat, TICKS_PER_BEAT),
+            kind: TrackEventKind::Meta(MetaMessage::TimeSignature(
+                change.numerator,
+                change.denominator.max(1).ilog2() as u8,
+                24,
+                8,
+            )),
+        });
+    }
+    for (time, name) in markers {
+        conductor.push(MidiEventTimed {
+            ticks: beats_to_ticks(tempo_map.beat_at(*time, bpm), TICKS_PER_BEAT),
+            kind: TrackEventKind::Meta(MetaMessage::Marker(name.as_bytes())),
+        });
+    }
+    smf.tracks.push(into_track(conductor));
 
+    // One track per synth, in order of first appearance (chords expand to their notes)
+    let mut synth_tracks: Vec<SynthTrack> = Vec::new();
     for event in events {
-        match event {
+        let (midis, start_time, duration, velocity, detune, synth_id, synth_def) = match event {
             AudioEvent::Note {
                 midi,
                 start_time,
                 duration,
                 velocity,
+                detune,
+                synth_id,
+                synth_def,
                 ..
-            } => {
-                midi_notes.push(MidiNote {
-                    note: *midi,
-                    start: *start_time,
-                    duration: *duration,
-                    velocity: *velocity,
-                });
-            }
+            } => (
+                std::slice::from_ref(midi),
+                start_time,
+                duration,
+                velocity,
+                detune,
+                synth_id,
+                synth_def,
+            ),
             AudioEvent::Chord {
                 midis,
                 start_time,
                 duration,
                 velocity,
+                detune,
+                synth_id,
+                synth_def,
                 ..
-            } => {
-                // Expand chord into individual notes
-                for &note in midis {
-                    midi_notes.push(MidiNote {
-                        note,
-                        start: *start_time,
-                        duration: *duration,
-                        velocity: *velocity,
-                    });
-                }
+            } => (
+                midis.as_slice(),
+                start_time,
+                duration,
+                velocity,
+                detune,
+                synth_id,
+                synth_def,
+            ),
+            // Samples are not exported to MIDI
+            AudioEvent::Sample { .. } => continue,
+        };
+
+        let index = match synth_tracks.iter().position(|t| t.synth_id == *synth_id) {
+            Some(index) => index,
+            None => {
+                synth_tracks.push(SynthTrack {
+                    synth_id: synth_id.clone(),
+                    glide: synth_def.options.get("glide").copied().unwrap_or(0.0) / 1000.0,
+                    notes: Vec::new(),
+                });
+                synth_tracks.len() - 1
             }
-            AudioEvent::Sample { .. } => {
-                // Samples are not exported to MIDI
-            } // Note: Log messages are stored separately in AudioEventList.logs and are
-              // not part of the AudioEvent enum; they are ignored for MIDI export.
+        };
+        for &note in midis {
+            synth_tracks[index].notes.push(MidiNote {
+                note,
+                start: *start_time,
+                duration: *duration,
+                velocity: *velocity,
+                detune: *detune,
+            });
         }
     }
 
-    // Sort by start time
-    midi_notes.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
-
-    // Convert events to MIDI messages with proper delta timing
-    let mut midi_messages: Vec<MidiEventTimed> = Vec::new();
+    let to_ticks = |seconds: f32| beats_to_ticks(tempo_map.beat_at(seconds, bpm), TICKS_PER_BEAT);
+    let mut note_count = 0;
+    // Channels in track order, leaving the GM drum channel alone
+    let mut channels = (0..16u8).filter(|c| *c != DRUM_CHANNEL).cycle();
+
+    for track in &mut synth_tracks {
+        let channel = channels.next().unwrap_or(0);
+        let mut messages: Vec<MidiEventTimed> = Vec::new();
+
+        track
+            .notes
+            .sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+        note_count += track.notes.len();
+
+        let bends = track.notes.iter().any(|n| n.detune != 0.0)
+            || (track.glide > 0.0 && track.notes.len() > 1);
+        if bends {
+            // RPN 0: pitch-bend sensitivity, then the null RPN
+            for (controller, value) in
+                [(101, 0), (100, 0), (6, 12), (38, 0), (101, 127), (100, 127)]
+            {
+                messages.push(MidiEventTimed {
+                    ticks: 0,
+                    kind: controller_event(channel, controller, value),
+                });
+            }
+        }
 
-    for note in &midi_notes {
-        let start_ticks = time_to_ticks(note.start, bpm, ticks_per_beat);
-        let end_ticks = time_to_ticks(note.start + note.duration, bpm, ticks_per_beat);
+        // Sounding pitch of the previous note, in semitones
+        let mut previous: Option<f32> = None;
+        for note in &track.notes {
+            let start_ticks = to_ticks(note.start);
+            let end_ticks = to_ticks(note.start + note.duration);
+
+            if bends {
+                let target = note.detune / 100.0;
+                let pitch = note.note as f32 + target;
+                match previous.filter(|p| track.glide > 0.0 && *p != pitch) {
+                    Some(from) => {
+                        // Start from the previous pitch and bend into this one
+                        let offset = from - note.note as f32;
+                        let glide_end = to_ticks(note.start + track.glide).max(start_ticks + 1);
+                        for ticks in (start_ticks..glide_end).step_by(CURVE_STEP_TICKS) {
+                            let progress =
+                                (ticks - start_ticks) as f32 / (glide_end - start_ticks) as f32;
+                            messages.push(MidiEventTimed {
+                                ticks,
+                                kind: bend_event(channel, offset + (target - offset) * progress),
+                            });
+                        }
+                        messages.push(MidiEventTimed {
+                            ticks: glide_end,
+                            kind: bend_event(channel, target),
+                        });
+                    }
+                    None => messages.push(MidiEventTimed {
+                        ticks: start_ticks,
+                        kind: bend_event(channel, target),
+                    }),
+                }
+            }
+            previous = Some(note.note as f32 + note.detune / 100.0);
+
+            messages.push(MidiEventTimed {
+                ticks: start_ticks,
+                kind: TrackEventKind::Midi {
+                    channel: channel.into(),
+                    message: MidiMessage::NoteOn {
+                        key: note.note.into(),
+                        vel: ((note.velocity * 127.0) as u8).into(),
+                    },
+                },
+            });
+            messages.push(MidiEventTimed {
+                ticks: end_ticks,
+                kind: TrackEventKind::Midi {
+                    channel: channel.into(),
+                    message: MidiMessage::NoteOff {
+                        key: note.note.into(),
+                        vel: 0.into(),
+                    },
+                },
+            });
+        }
 
-        // Note On
-        midi_messages.push(MidiEventTimed {
-            ticks: start_ticks,
-            message: MidiMessage::NoteOn {
-                key: note.note.into(),
-                vel: ((note.velocity * 127.0) as u8).into(),
-            },
-        });
+        if let Some(envelope) = automation.and_then(|a| a.envelope(&track.synth_id))
+            && let (Some(first), Some(end)) = (
+                track.notes.first(),
+                track
+                    .notes
+                    .iter()
+                    .map(|n| n.start + n.duration)
+                    .reduce(f32::max),
+            )
+        {
+            let lane_ticks = to_ticks(first.start)..=to_ticks(end);
+            let seconds_at =
+                |ticks: u32| tempo_map.seconds_at(ticks as f32 / TICKS_PER_BEAT as f32, bpm);
+            for (param, controller) in automation_lanes(envelope) {
+                let mut last = None;
+                for ticks in lane_ticks.clone().step_by(CURVE_STEP_TICKS) {
+                    let Some(value) = envelope.get_value(&param, seconds_at(ticks)) else {
+                        continue;
+                    };
+                    let value = controller_value(&param, value);
+                    if last != Some(value) {
+                        messages.push(MidiEventTimed {
+                            ticks,
+                            kind: controller_event(channel, controller, value),
+                        });
+                        last = Some(value);
+                    }
+                }
+            }
+        }
 
-        // Note Off
-        midi_messages.push(MidiEventTimed {
-            ticks: end_ticks,
-            message: MidiMessage::NoteOff {
-                key: note.note.into(),
-                vel: 0.into(),
-            },
-        });
+        smf.tracks.push(into_track(messages));
     }
 
-    // Sort all messages by time
-    midi_messages.sort_by_key(|msg| msg.ticks);
+    (smf, note_count)
+}
 
-    // Convert to delta times and create TrackEvents
-    let mut last_ticks = 0u32;
+/// Sort timed messages and turn them into a track with delta times and an end marker
+fn into_track<'a>(mut messages: Vec<MidiEventTimed<'a>>) -> Track<'a> {
+    // Stable, so messages pushed first (bends, tempo) precede notes on the same tick
+    messages.sort_by_key(|msg| msg.ticks);
 
-    for msg in midi_messages {
+    let mut last_ticks = 0u32;
+    let mut track_events = Vec::new();
+    for msg in messages {
         let delta = msg.ticks.saturating_sub(last_ticks);
-
         track_events.push(TrackEvent {
             delta: delta.into(),
-            kind: TrackEventKind::Midi {
-                channel: 0.into(),
-                message: msg.message,
-            },
+            kind: msg.kind,
         });
-
         last_ticks = msg.ticks;
     }
 
-    // End of track marker
     track_events.push(TrackEvent {
         delta: 0.into(),
         kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
     });
+    Track::from(track_events)
+}
 
-    // Create SMF and write to file
-    let track = Track::from(track_events);
-    let mut smf = Smf::new(header);
-    smf.tracks.push(track);
+/// Automated parameters of an envelope that have a controller, one parameter per controller
+fn automation_lanes(envelope: &AutomationEnvelope) -> Vec<(String, u8)> {
+    let mut names: Vec<&str> = envelope
+        .params
+        .iter()
+        .map(|p| p.param_name.as_str())
+        .chain(envelope.formulas.iter().map(|f| f.param_name.as_str()))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+
+    let mut lanes: Vec<(String, u8)> = Vec::new();
+    for name in names {
+        let controller = AUTOMATION_CONTROLLERS
+            .iter()
+            .find(|(param, _)| *param == name)
+            .map(|(_, cc)| *cc)
+            .or_else(|| {
+                name.strip_prefix("cc")
+                    .and_then(|n| n.parse::<u8>().ok())
+                    .filter(|cc| *cc < 120)
+            });
+        if let Some(controller) = controller
+            && !lanes.iter().any(|(_, cc)| *cc == controller)
+        {
+            lanes.push((name.to_string(), controller));
+        }
+    }
+    lanes
+}
 
-    // Write to file directly (midly 0.5 API)
-    smf.save(output_path)
-        .map_err(|e| anyhow!("Failed to write MIDI file {}: {}", output_path.display(), e))?;
+/// Controller value (0-127) of an automated parameter. Cutoffs above 1 are read as Hz on
+/// a log scale, pans span -1..1; other values up to 1 are normalized, larger ones raw.
+fn controller_value(param: &str, value: f32) -> u8 {
+    let normalized = match param {
+        "cutoff" if value > 1.0 => (value / 20.0).max(1.0).ln() / 1000.0f32.ln(),
+        "pan" => (value + 1.0) / 2.0,
+        _ if value > 1.0 => value / 127.0,
+        _ => value,
+    };
+    (normalized.clamp(0.0, 1.0) * 127.0).round() as u8
+}
 
-    println!(
-        "✅ MIDI exported: {} ({} events in, {} notes written)",
-        output_path.display(),
-        events.len(),
-        midi_notes.len()
-    );
-    Ok(())
+fn controller_event(channel: u8, controller: u8, value: u8) -> TrackEventKind<'static> {
+    TrackEventKind::Midi {
+        channel: channel.into(),
+        message: MidiMessage::Controller {
+            controller: controller.into(),
+            value: value.into(),
+        },
+    }
 }
 
-#[cfg(not(feature = "cli"))]
-pub fn export_midi_file(_events: &[AudioEvent], _output_path: &Path, _bpm: f32) -> Result<()> {
-    Err(anyhow!("MIDI export not available without 'cli' feature"))
+/// Pitch bend by `semitones` within the announced bend range
+fn bend_event(channel: u8, semitones: f32) -> TrackEventKind<'static> {
+    TrackEventKind::Midi {
+        channel: channel.into(),
+        message: MidiMessage::PitchBend {
+            bend: PitchBend::from_f32((semitones / BEND_RANGE_SEMITONES).clamp(-1.0, 1.0)),
+        },
+    }
+}
+
+fn tempo_event(bpm: f32) -> TrackEventKind<'static> {
+    let tempo_us_per_quarter = (60_000_000.0 / bpm) as u32;
+    TrackEventKind::Meta(MetaMessage::Tempo(tempo_us_per_quarter.into()))
 }
 
 // Helper structures for MIDI export
+#[derive(Debug, Clone)]
+struct SynthTrack {
+    synth_id: String,
+    /// Glide time from the previous note, in seconds (`glide` synth option, in ms)
+    glide: f32,
+    notes: Vec<MidiNote>,
+}
+
 #[derive(Debug, Clone)]
 struct MidiNote {
     note: u8,
     start: f32,
     duration: f32,
     velocity: f32,
+    /// Cents, written as pitch bend
+    detune: f32,
 }
 
 #[derive(Debug, Clone)]
-struct MidiEventTimed {
+struct MidiEventTimed<'a> {
     ticks: u32,
-    message: MidiMessage,
+    kind: TrackEventKind<'a>,
+}
+
+/// Convert a beat position to MIDI ticks
+fn beats_to_ticks(beats: f32, ticks_per_beat: u16) -> u32 {
+    (beats * ticks_per_beat as f32).round().max(0.0) as u32
+}
+
+/// Chord detected over one bar of a bound MIDI file
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiChord {
+    pub root: String,
+    pub quality: String,
+}
+
+impl MidiChord {
+    /// Chord symbol in the notation `chord()` accepts, e.g. `Cmaj` or `Amin7`
+    pub fn symbol(&self) -> String {
+        format!("{}{}", self.root, self.quality)
+    }
+}
+
+const PITCH_CLASS_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Interval templates matched against each bar. Triads come first so they win
+/// ties against the seventh chords that contain them.
+const CHORD_TEMPLATES: [(&str, &[usize]); 9] = [
+    ("maj", &[0, 4, 7]),
+    ("min", &[0, 3, 7]),
+    ("dim", &[0, 3, 6]),
+    ("aug", &[0, 4, 8]),
+    ("sus2", &[0, 2, 7]),
+    ("sus4", &[0, 5, 7]),
+    ("7", &[0, 4, 7, 10]),
+    ("maj7", &[0, 4, 7, 11]),
+    ("min7", &[0, 3, 7, 10]),
+];
+
+/// GM percussion channel, skipped by chord analysis
+const DRUM_CHANNEL: u8 = 9;
+
+/// Detect one chord per bar from loaded MIDI notes (see `load_midi_file`).
+///
+/// Pitch classes are weighted by how long they sound inside the bar and matched
+/// against the chord templates; the lowest note breaks ties. Bars without pitched
+/// notes yield `None`. `bpm` is only used for notes missing a `beat` position.
+pub fn detect_chords(notes: &[Value], bpm: f32, beats_per_bar: f32) -> Vec<Option<MidiChord>> {
+    use crate::engine::audio::events::extract_number;
+
+    // Per bar: duration-weighted pitch classes and the lowest sounding note
+    let mut bars: Vec<([f32; 12], Option<u8>)> = Vec::new();
+
+    for note in notes {
+        let Value::Map(map) = note else { continue };
+        if extract_number(map, "channel", 0.0) as u8 == DRUM_CHANNEL {
+            continue;
+        }
+
+        let key = extract_number(map, "note", 60.0).clamp(0.0, 127.0) as u8;
+        let start = extract_number(
+            map,
+            "beat",
+            extract_number(map, "time", 0.0) * bpm / 60000.0,
+        );
+        let length = extract_number(
+            map,
+            "duration_beats",
+            extract_number(map, "duration", 500.0) * bpm / 60000.0,
+        );
+        let end = start + length.max(0.0);
+
+        let first_bar = (start / beats_per_bar).floor().max(0.0) as usize;
+        let mut bar = first_bar;
+        loop {
+            let bar_start = bar as f32 * beats_per_bar;
+            let bar_end = bar_start + beats_per_bar;
+            if bar > first_bar && bar_start >= end {
+                break;
+            }
+
+            if bars.len() <= bar {
+                bars.resize(bar + 1, ([0.0; 12], None));
+            }
+            // Zero-length notes still count a little towards their bar
+            let overlap = (end.min(bar_end) - start.max(bar_start)).max(1e-3);
+            let (weights, bass) = &mut bars[bar];
+            weights[key as usize % 12] += overlap;
+            *bass = Some(bass.map_or(key, |low| low.min(key)));
+
+            bar += 1;
+        }
+    }
+
+    bars.iter()
+        .map(|(weights, bass)| classify_chord(weights, bass.map(|key| key as usize % 12)))
+        .collect()
+}
+
+/// Best matching chord for a set of pitch-class weights
+fn classify_chord(weights: &[f32; 12], bass: Option<usize>) -> Option<MidiChord> {
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut best: Option<(f32, usize, &str)> = None;
+    for root in 0..12 {
+        for (quality, intervals) in CHORD_TEMPLATES {
+            let matched: f32 = intervals.iter().map(|i| weights[(root + i) % 12]).sum();
+            let missing = intervals
+                .iter()
+                .filter(|i| weights[(root + **i) % 12] <= 0.0)
+                .count();
+            let mut score = (2.0 * matched - total) / total - 0.1 * missing as f32;
+            if bass == Some(root) {
+                score += 0.05;
+            }
+            if best.is_none_or(|(best_score, _, _)| score > best_score) {
+                best = Some((score, root, quality));
+            }
+        }
+    }
+
+    best.map(|(_, root, quality)| MidiChord {
+        root: PITCH_CLASS_NAMES[root].to_string(),
+        quality: quality.to_string(),
+    })
 }
 
-/// Convert time in seconds to MIDI ticks
-fn time_to_ticks(time_seconds: f32, bpm: f32, ticks_per_beat: u16) -> u32 {
-    let beats = time_seconds * (bpm / 60.0);
-    (beats * ticks_per_beat as f32) as u32
+/// Decode a raw NoteOn/NoteOff (0x9x / 0x8x) message into the `mapping.in.<device>.*`
+/// event suffix and its payload (`note`, `velocity`, zero-based `channel`). A NoteOn
+/// with velocity 0 is a NoteOff, as most controllers send it.
+pub fn input_event(
+    message: &[u8],
+) -> Option<(&'static str, std::collections::HashMap<String, Value>)> {
+    let [status, note, velocity, ..] = *message else {
+        return None;
+    };
+    let kind = match status & 0xF0 {
+        0x90 if velocity > 0 => "noteOn",
+        0x90 | 0x80 => "noteOff",
+        _ => return None,
+    };
+    let mut data = std::collections::HashMap::new();
+    data.insert("note".to_string(), Value::Number(note as f32));
+    data.insert("velocity".to_string(), Value::Number(velocity as f32));
+    data.insert("channel".to_string(), Value::Number((status & 0x0F) as f32));
+    Some((kind, data))
+}
+
+/// Controller number and value of a MIDI control change message
+pub fn control_change(message: &[u8]) -> Option<(u8, u8)> {
+    match *message {
+        [status, controller, value, ..] if status & 0xF0 == 0xB0 => Some((controller, value)),
+        _ => None,
+    }
 }
+
+#[cfg(all(test, feature = "cli"))]
+#[path = "test_midi.rs"]
+mod tests;
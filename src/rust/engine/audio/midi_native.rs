@@ -5,7 +5,6 @@
 // - provide a small API to send MIDI messages to out ports
 
 use crate::engine::events::EventRegistry;
-use crate::language::syntax::ast::Value;
 #[cfg(feature = "cli")]
 use midir::{MidiInput, MidiInputConnection, MidiOutput, MidiOutputConnection};
 use std::collections::HashMap;
@@ -65,44 +64,16 @@ impl MidiManager {
                 &port,
                 &device_name,
                 move |_stamp, message, _| {
-                    // Parse simple NoteOn/NoteOff (0x9x / 0x8x) messages
-                    if message[0] != 248 {
-                        let status = message[0];
-                        let cmd = status & 0xF0;
-                        let channel = (status & 0x0F) as u8;
-                        match cmd {
-                            0x90 => {
-                                let note = message[1] as u8;
-                                let vel = message[2] as u8;
-                                let mut data = HashMap::new();
-                                data.insert("note".to_string(), Value::Number(note as f32));
-                                data.insert("velocity".to_string(), Value::Number(vel as f32));
-                                data.insert("channel".to_string(), Value::Number(channel as f32));
-                                // Emit event name like mapping.in.<device>.noteOn (keyboard mode timestamp)
-                                let event_name = format!("mapping.in.{}.noteOn", device_name_inner);
-                                let elapsed = std::time::Instant::now().duration_since(*start);
-                                let ts = elapsed.as_secs_f32();
-                                if let Ok(mut reg) = registry.lock() {
-                                    reg.emit(event_name, data, ts);
-                                }
-                            }
-                            0x80 => {
-                                let note = message[1] as u8;
-                                let vel = message[2] as u8;
-                                let mut data = HashMap::new();
-                                data.insert("note".to_string(), Value::Number(note as f32));
-                                data.insert("velocity".to_string(), Value::Number(vel as f32));
-                                data.insert("channel".to_string(), Value::Number(channel as f32));
-                                let event_name =
-                                    format!("mapping.in.{}.noteOff", device_name_inner);
-                                let elapsed = std::time::Instant::now().duration_since(*start);
-                                let ts = elapsed.as_secs_f32();
-                                if let Ok(mut reg) = registry.lock() {
-                                    reg.emit(event_name, data, ts);
-                                }
-                            }
-                            _ => {}
-                        }
+                    let Some((kind, data)) = crate::engine::audio::midi::input_event(message)
+                    else {
+                        return;
+                    };
+                    // Emit event name like mapping.in.<device>.noteOn (keyboard mode timestamp)
+                    let event_name = format!("mapping.in.{}.{}", device_name_inner, kind);
+                    let elapsed = std::time::Instant::now().duration_since(*start);
+                    let ts = elapsed.as_secs_f32();
+                    if let Ok(mut reg) = registry.lock() {
+                        reg.emit(event_name, data, ts);
                     }
                 },
                 (),
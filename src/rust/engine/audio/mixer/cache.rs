@@ -0,0 +1,158 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::engine::audio::events::{AudioEvent, SynthDefinition};
+use crate::language::syntax::ast::Value;
+
+/// Rendered group inserts kept between watch rebuilds.
+///
+/// Each insert is stored with a fingerprint of the events that produced it; a rebuild
+/// whose events for that insert hash the same reuses the buffer instead of re-rendering.
+/// Buffers are kept as f64 so both mix precisions round-trip without loss.
+#[derive(Debug, Default)]
+pub struct InsertCache {
+    inserts: HashMap<String, CachedInsert>,
+    /// Inserts reused / rendered by the last build
+    pub reused: usize,
+    pub rendered: usize,
+    /// Peak envelope of every group insert after its chain, from the last build
+    pub meters: Vec<(String, Vec<f32>)>,
+}
+
+#[derive(Debug)]
+struct CachedInsert {
+    fingerprint: u64,
+    samples: Vec<f64>,
+}
+
+impl InsertCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fingerprint the events of every insert path. `context` covers render settings
+    /// (sample rate, length, quality) so changing them invalidates every insert.
+    pub fn fingerprints(
+        events: &[AudioEvent],
+        paths: &[Option<String>],
+        context: u64,
+    ) -> HashMap<String, u64> {
+        let mut hashers: HashMap<&str, DefaultHasher> = HashMap::new();
+        for (event, path) in events.iter().zip(paths) {
+            let Some(path) = path else {
+                continue;
+            };
+            let hasher = hashers.entry(path.as_str()).or_insert_with(|| {
+                let mut hasher = DefaultHasher::new();
+                context.hash(&mut hasher);
+                hasher
+            });
+            hash_event(event, hasher);
+        }
+        hashers
+            .into_iter()
+            .map(|(path, hasher)| (path.to_string(), hasher.finish()))
+            .collect()
+    }
+
+    /// Cached buffer for `path` if it was rendered from the same events and is long enough
+    pub fn lookup(&self, path: &str, fingerprint: u64, len: usize) -> Option<&[f64]> {
+        self.inserts
+            .get(path)
+            .filter(|cached| cached.fingerprint == fingerprint && cached.samples.len() >= len)
+            .map(|cached| &cached.samples[..len])
+    }
+
+    pub fn store(&mut self, path: String, fingerprint: u64, samples: Vec<f64>) {
+        self.inserts.insert(
+            path,
+            CachedInsert {
+                fingerprint,
+                samples,
+            },
+        );
+    }
+
+    /// Drop inserts that no longer exist in the project
+    pub fn retain_paths(&mut self, live: &HashSet<&str>) {
+        self.inserts.retain(|path, _| live.contains(path.as_str()));
+    }
+
+    /// Every cached insert (stereo buffers), sorted by path
+    pub fn buffers(&self) -> Vec<(&str, &[f64])> {
+        let mut buffers: Vec<(&str, &[f64])> = self
+            .inserts
+            .iter()
+            .map(|(path, cached)| (path.as_str(), cached.samples.as_slice()))
+            .collect();
+        buffers.sort_by(|a, b| a.0.cmp(b.0));
+        buffers
+    }
+
+    pub fn len(&self) -> usize {
+        self.inserts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inserts.is_empty()
+    }
+}
+
+/// Hash an event independently of `HashMap` iteration order: maps (effects, synth
+/// options) are hashed with sorted keys, everything else through its debug form.
+pub(crate) fn hash_event(event: &AudioEvent, hasher: &mut DefaultHasher) {
+    let mut plain = event.clone();
+    match &mut plain {
+        AudioEvent::Note {
+            synth_def, effects, ..
+        }
+        | AudioEvent::Chord {
+            synth_def, effects, ..
+        } => {
+            hash_synth(synth_def, hasher);
+            if let Some(effects) = effects.take() {
+                hash_value(&effects, hasher);
+            }
+            *synth_def = SynthDefinition::default();
+        }
+        AudioEvent::Sample { effects, .. } => {
+            if let Some(effects) = effects.take() {
+                hash_value(&effects, hasher);
+            }
+        }
+    }
+    format!("{:?}", plain).hash(hasher);
+}
+
+fn hash_synth(synth: &SynthDefinition, hasher: &mut DefaultHasher) {
+    let mut options: Vec<_> = synth.options.iter().collect();
+    options.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, value) in options {
+        key.hash(hasher);
+        value.to_bits().hash(hasher);
+    }
+    let mut plain = synth.clone();
+    plain.options.clear();
+    format!("{:?}", plain).hash(hasher);
+}
+
+pub(crate) fn hash_value(value: &Value, hasher: &mut DefaultHasher) {
+    match value {
+        Value::Map(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (key, value) in entries {
+                key.hash(hasher);
+                hash_value(value, hasher);
+            }
+        }
+        Value::Array(items) => {
+            items.len().hash(hasher);
+            for item in items {
+                hash_value(item, hasher);
+            }
+        }
+        other => format!("{:?}", other).hash(hasher),
+    }
+}
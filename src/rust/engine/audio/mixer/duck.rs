@@ -0,0 +1,123 @@
+//! Envelope-following ducking between inserts
+//!
+//! `duck pads by kick amount 0.6` turns the `pads` insert down while `kick` plays. An
+//! envelope follower tracks the key's level with separate attack and release times; the
+//! ducked insert is scaled by `1 - amount * envelope`, with the envelope normalized so
+//! the key's loudest moment gives the full `amount`.
+
+use super::MixSample;
+use crate::language::syntax::ast::Value;
+use crate::language::syntax::parser::driver::duration::parse_duration_token;
+
+pub const DEFAULT_AMOUNT: f32 = 0.5;
+pub const DEFAULT_ATTACK_SECONDS: f32 = 0.005;
+pub const DEFAULT_RELEASE_SECONDS: f32 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DuckSettings {
+    /// Gain reduction at the key's peak, 0 (none) to 1 (silence)
+    pub amount: f32,
+    pub attack: f32,
+    pub release: f32,
+}
+
+impl Default for DuckSettings {
+    fn default() -> Self {
+        Self {
+            amount: DEFAULT_AMOUNT,
+            attack: DEFAULT_ATTACK_SECONDS,
+            release: DEFAULT_RELEASE_SECONDS,
+        }
+    }
+}
+
+impl DuckSettings {
+    /// Read `amount`, `attack` and `release` from a duck's effect (`duck({ ... })` or
+    /// any single effect map). Plain numbers for times are milliseconds.
+    pub fn from_effect(effect: &Value, bpm: f32) -> Self {
+        let params = match effect {
+            Value::Map(map) if map.len() == 1 => match map.values().next() {
+                Some(inner @ Value::Map(_)) => inner,
+                _ => effect,
+            },
+            _ => effect,
+        };
+        let defaults = Self::default();
+        let seconds = |name: &str, fallback: f32| match params.get(name) {
+            Some(Value::Number(ms)) => ms / 1000.0,
+            Some(Value::Duration(duration)) => duration.to_seconds(bpm).unwrap_or(fallback),
+            Some(Value::String(text)) => parse_duration_token(text)
+                .ok()
+                .and_then(|duration| duration.to_seconds(bpm))
+                .unwrap_or(fallback),
+            _ => fallback,
+        };
+        Self {
+            amount: match params.get("amount") {
+                Some(Value::Number(amount)) => amount.clamp(0.0, 1.0),
+                _ => defaults.amount,
+            },
+            attack: seconds("attack", defaults.attack).max(0.0),
+            release: seconds("release", defaults.release).max(0.0),
+        }
+    }
+
+    /// Gain for the first `frames` frames of a signal ducked by the interleaved `key`;
+    /// the key is silent past its end
+    pub fn gain_curve<S: MixSample>(
+        &self,
+        key: &[S],
+        channels: usize,
+        sample_rate: u32,
+        frames: usize,
+    ) -> Vec<f32> {
+        let channels = channels.max(1);
+        let coefficient = |seconds: f32| {
+            if seconds <= 0.0 {
+                0.0
+            } else {
+                (-1.0 / (seconds * sample_rate.max(1) as f32)).exp()
+            }
+        };
+        let (attack, release) = (coefficient(self.attack), coefficient(self.release));
+
+        let mut level = 0.0f32;
+        let mut envelope: Vec<f32> = (0..frames)
+            .map(|index| {
+                let peak = key
+                    .get(index * channels..(index + 1) * channels)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|s| s.to_f32().abs())
+                    .fold(0.0f32, f32::max);
+                let coefficient = if peak > level { attack } else { release };
+                level = peak + coefficient * (level - peak);
+                level
+            })
+            .collect();
+
+        let loudest = envelope.iter().copied().fold(0.0f32, f32::max);
+        let scale = if loudest > f32::EPSILON {
+            self.amount / loudest
+        } else {
+            0.0
+        };
+        for value in &mut envelope {
+            *value = 1.0 - *value * scale;
+        }
+        envelope
+    }
+}
+
+/// Scale each frame of the interleaved `samples` by `gains`
+pub fn apply_gain_curve<S: MixSample>(samples: &mut [S], channels: usize, gains: &[f32]) {
+    for (frame, &gain) in samples.chunks_mut(channels.max(1)).zip(gains) {
+        for sample in frame {
+            *sample = S::from_f64(sample.to_f64() * gain as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_duck.rs"]
+mod tests;
@@ -0,0 +1,124 @@
+//! Fractional-position reads for resampled and variable-rate playback
+//!
+//! `ResampleQuality::Linear2` blends the two frames around the read position; every
+//! other quality runs a Blackman-windowed sinc over `taps()` frames, with the cutoff
+//! lowered when the signal is read faster than real time so it does not alias.
+
+use std::f64::consts::PI;
+
+use crate::engine::audio::settings::ResampleQuality;
+
+/// Kernel for one read position, reused across the channels of a frame
+#[derive(Debug, Clone)]
+pub struct Interpolator {
+    quality: ResampleQuality,
+    half: isize,
+    cutoff: f64,
+    /// First source frame the weights apply to
+    first: usize,
+    weights: Vec<f64>,
+}
+
+impl Interpolator {
+    /// Reads advancing `step` source frames per output frame
+    pub fn new(quality: ResampleQuality, step: f64) -> Self {
+        let half = (quality.taps() / 2).max(1) as isize;
+        Self {
+            quality,
+            half,
+            cutoff: Self::cutoff_for(step),
+            first: 0,
+            weights: Vec::with_capacity(quality.taps()),
+        }
+    }
+
+    pub fn quality(&self) -> ResampleQuality {
+        self.quality
+    }
+
+    pub fn is_linear(&self) -> bool {
+        self.quality == ResampleQuality::Linear2
+    }
+
+    /// Source frames needed on each side of the read position
+    pub fn reach(&self) -> usize {
+        self.half as usize
+    }
+
+    /// Follow a new read speed (var-speed playback)
+    pub fn set_step(&mut self, step: f64) {
+        self.cutoff = Self::cutoff_for(step);
+    }
+
+    fn cutoff_for(step: f64) -> f64 {
+        if step > 1.0 { 1.0 / step } else { 1.0 }
+    }
+
+    /// Compute the kernel for fractional frame `position` of a signal `frames` long.
+    /// Frames outside the signal are left out and the remaining weights renormalized.
+    pub fn prepare(&mut self, position: f64, frames: usize) {
+        self.weights.clear();
+        self.first = 0;
+        if frames == 0 {
+            return;
+        }
+        let base = position.floor().max(0.0) as usize;
+
+        if self.is_linear() {
+            let base = base.min(frames - 1);
+            let fraction = (position - base as f64).clamp(0.0, 1.0);
+            self.first = base;
+            self.weights.push(1.0 - fraction);
+            if base + 1 < frames {
+                self.weights.push(fraction);
+            } else {
+                // Hold the last frame
+                self.weights[0] = 1.0;
+            }
+            return;
+        }
+
+        let center = position.floor() as isize;
+        let start = (center - self.half + 1).max(0);
+        let end = (center + self.half).min(frames as isize - 1);
+        if start > end {
+            return;
+        }
+        self.first = start as usize;
+        let mut norm = 0.0f64;
+        for k in start..=end {
+            let x = position - k as f64;
+            let window_pos = x / self.half as f64;
+            let weight = if window_pos.abs() >= 1.0 {
+                0.0
+            } else {
+                let arg = PI * x * self.cutoff;
+                let sinc = if arg.abs() < 1e-9 { 1.0 } else { arg.sin() / arg };
+                // Blackman window centred on the read position
+                let window =
+                    0.42 + 0.5 * (PI * window_pos).cos() + 0.08 * (2.0 * PI * window_pos).cos();
+                sinc * window
+            };
+            norm += weight;
+            self.weights.push(weight);
+        }
+        if norm.abs() <= 1e-9 {
+            self.weights.clear();
+            return;
+        }
+        for weight in &mut self.weights {
+            *weight /= norm;
+        }
+    }
+
+    /// Apply the prepared kernel; `read(frame)` returns one channel of a source frame
+    pub fn apply(&self, read: impl Fn(usize) -> f32) -> f32 {
+        let sum: f64 = self
+            .weights
+            .iter()
+            .enumerate()
+            .map(|(offset, weight)| read(self.first + offset) as f64 * weight)
+            .sum();
+        sum as f32
+    }
+}
@@ -1,7 +1,84 @@
+use crate::engine::audio::effects::chain::build_effect_chain_with;
+use crate::engine::audio::effects::registry::EffectRegistry;
+use crate::engine::audio::outputs::OutputTaps;
+use crate::engine::audio::settings::{DEFAULT_BLOCK_SIZE, ResampleQuality};
+use crate::language::syntax::ast::Value;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+pub mod cache;
+pub mod duck;
+pub mod interpolation;
+pub mod strip;
+
+pub use cache::InsertCache;
+pub use duck::DuckSettings;
+pub use interpolation::Interpolator;
+pub use strip::{ChannelStrip, StripAutomation};
+
 pub const MASTER_INSERT: &str = "master";
+/// Length of one meter reading in the live `.meters` sidecar
+pub const METER_WINDOW_SECONDS: f32 = 0.05;
+
+/// Peak envelope of each insert as it leaves the insert, by insert path
+pub type InsertMeters = Vec<(String, Vec<f32>)>;
+
+/// Accumulator type for summing voices and inserts: `f32`, or `f64` to keep
+/// rounding error down in dense mixes. Audio enters and leaves as `f32`.
+pub trait MixSample: Copy + Default + std::ops::AddAssign + std::fmt::Debug + 'static {
+    fn from_f32(value: f32) -> Self;
+    fn to_f32(self) -> f32;
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+impl MixSample for f32 {
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+    fn to_f32(self) -> f32 {
+        self
+    }
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl MixSample for f64 {
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+/// Peak level of each `window_frames` block of an interleaved buffer (all channels)
+pub fn peak_envelope<S: MixSample>(
+    samples: &[S],
+    channels: usize,
+    window_frames: usize,
+) -> Vec<f32> {
+    let window = window_frames.max(1) * channels.max(1);
+    samples
+        .chunks(window)
+        .map(|block| {
+            block
+                .iter()
+                .map(|s| s.to_f32().abs())
+                .fold(0.0f32, f32::max)
+        })
+        .collect()
+}
 
 #[derive(Debug, Clone)]
 pub struct SampleBuffer {
@@ -68,18 +145,27 @@ impl SampleBuffer {
 }
 
 #[derive(Debug, Clone)]
-struct AudioInsert {
+struct AudioInsert<S> {
     name: String,
     parent: Option<String>,
-    buffer: Vec<f32>,
+    buffer: Vec<S>,
+    /// Channel strip run on the summed insert ahead of its effect chain
+    strip: Option<(ChannelStrip, StripAutomation)>,
+    /// Effect chain applied to the summed insert before it reaches its parent
+    effects: Vec<Value>,
+    /// Muted inserts are dropped at mixdown instead of reaching their parent
+    muted: bool,
 }
 
-impl AudioInsert {
+impl<S: MixSample> AudioInsert<S> {
     fn new(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             parent: None,
             buffer: Vec::new(),
+            strip: None,
+            effects: Vec::new(),
+            muted: false,
         }
     }
 
@@ -94,29 +180,73 @@ impl AudioInsert {
     fn ensure_frames(&mut self, frames: usize, channels: usize) {
         let required = frames.saturating_mul(channels);
         if self.buffer.len() < required {
-            self.buffer.resize(required, 0.0);
+            self.buffer.resize(required, S::default());
         }
     }
 }
 
 #[derive(Debug)]
-pub struct AudioMixer {
+pub struct AudioMixer<S: MixSample = f32> {
     sample_rate: u32,
     channels: usize,
-    inserts: HashMap<String, AudioInsert>,
+    /// Frames per block when running insert effect chains
+    block_size: usize,
+    /// Interpolation used when a sample's rate differs from the mixer's
+    resample_quality: ResampleQuality,
+    /// Effects insert chains can use
+    effect_registry: Arc<EffectRegistry>,
+    inserts: HashMap<String, AudioInsert<S>>,
+    /// `(target, key, settings)`: the target group's inserts follow the key group's level
+    ducks: Vec<(String, String, DuckSettings)>,
+    /// Audio that only keys ducks (muted or unsoloed groups), by insert path
+    duck_keys: HashMap<String, Vec<S>>,
+    /// Groups whose processed signal is kept for hardware outputs (`route ... -> outputs`)
+    output_taps: HashSet<String>,
+    /// Frames per meter reading when `into_metered_buffers` meters the inserts
+    meter_window: Option<usize>,
 }
 
-impl AudioMixer {
+impl<S: MixSample> AudioMixer<S> {
     pub fn new(sample_rate: u32, channels: usize) -> Self {
         let mut inserts = HashMap::new();
         inserts.insert(MASTER_INSERT.to_string(), AudioInsert::new(MASTER_INSERT));
         Self {
             sample_rate,
             channels: channels.max(1),
+            block_size: DEFAULT_BLOCK_SIZE,
+            resample_quality: ResampleQuality::default(),
+            effect_registry: EffectRegistry::full(),
             inserts,
+            ducks: Vec::new(),
+            duck_keys: HashMap::new(),
+            output_taps: HashSet::new(),
+            meter_window: None,
         }
     }
 
+    pub fn with_block_size(mut self, frames: usize) -> Self {
+        self.block_size = frames.max(1);
+        self
+    }
+
+    /// Linear or windowed-sinc reads for samples played at another rate (`resample_quality`)
+    pub fn with_resample_quality(mut self, quality: ResampleQuality) -> Self {
+        self.resample_quality = quality;
+        self
+    }
+
+    /// Insert chains only find the effects in `registry`
+    pub fn with_effect_registry(mut self, registry: Arc<EffectRegistry>) -> Self {
+        self.effect_registry = registry;
+        self
+    }
+
+    /// Meter every insert after its strip, chain and ducking, one peak per `window_frames`
+    pub fn with_meters(mut self, window_frames: usize) -> Self {
+        self.meter_window = Some(window_frames.max(1));
+        self
+    }
+
     pub fn register_insert(&mut self, name: impl Into<String>, parent: Option<&str>) -> String {
         let key = name.into();
         if key != MASTER_INSERT {
@@ -154,6 +284,64 @@ impl AudioMixer {
         }
     }
 
+    /// Attach an effect chain to an insert (registering it under master when unknown).
+    /// The chain runs once on the summed insert during `into_master_buffer`.
+    pub fn set_insert_effects(&mut self, insert: &str, effects: Vec<Value>) {
+        if !self.inserts.contains_key(insert) {
+            self.register_insert(insert.to_string(), Some(MASTER_INSERT));
+        }
+        if let Some(target) = self.inserts.get_mut(insert) {
+            target.effects = effects;
+        }
+    }
+
+    /// Give an insert (registering it under master when unknown) a channel strip, run
+    /// on its summed signal before the effect chain
+    pub fn set_insert_strip(
+        &mut self,
+        insert: &str,
+        strip: ChannelStrip,
+        automation: StripAutomation,
+    ) {
+        if !self.inserts.contains_key(insert) {
+            self.register_insert(insert.to_string(), Some(MASTER_INSERT));
+        }
+        if let Some(target) = self.inserts.get_mut(insert) {
+            target.strip = Some((strip, automation));
+        }
+    }
+
+    /// Silence an insert (registering it under master when unknown); its signal and
+    /// whatever its children send it never reach its parent
+    pub fn set_insert_muted(&mut self, insert: &str, muted: bool) {
+        if !self.inserts.contains_key(insert) {
+            self.register_insert(insert.to_string(), Some(MASTER_INSERT));
+        }
+        if let Some(target) = self.inserts.get_mut(insert) {
+            target.muted = muted;
+        }
+    }
+
+    /// Duck the inserts of group `target` by the level of group `key`. The key is the
+    /// dry sum of every insert inside the key group, so it does not depend on the key's
+    /// effects, mute or solo; the gain applies after the target's effect chain.
+    pub fn add_duck(&mut self, target: &str, key: &str, settings: DuckSettings) {
+        self.ducks
+            .push((target.to_string(), key.to_string(), settings));
+    }
+
+    /// Add audio that is not heard but still keys ducks (a muted or unsoloed group)
+    pub fn mix_duck_key(&mut self, insert: &str, start_frame: usize, samples: &[S]) {
+        let offset = start_frame.saturating_mul(self.channels);
+        let key = self.duck_keys.entry(insert.to_string()).or_default();
+        if key.len() < offset + samples.len() {
+            key.resize(offset + samples.len(), S::default());
+        }
+        for (slot, sample) in key[offset..].iter_mut().zip(samples) {
+            *slot += *sample;
+        }
+    }
+
     pub fn mix_sample(
         &mut self,
         insert: &str,
@@ -164,42 +352,190 @@ impl AudioMixer {
         if sample.frames() == 0 {
             return;
         }
-        let route = self.route_chain(insert);
-        for route_insert in route {
-            if !self.inserts.contains_key(&route_insert) {
-                // Unknown insert; skip mixing to avoid panics.
-                continue;
-            }
-            if let Some(target) = self.inserts.get_mut(&route_insert) {
-                Self::mix_into_insert(
-                    target,
-                    self.channels,
-                    start_frame,
-                    duration,
-                    self.sample_rate,
-                    sample,
-                );
+        if !self.inserts.contains_key(insert) {
+            self.register_insert(insert.to_string(), Some(MASTER_INSERT));
+        }
+        // Signal only lands in its own insert; parents receive it (post-effects) at mixdown
+        if let Some(target) = self.inserts.get_mut(insert) {
+            Self::mix_into_insert(
+                target,
+                self.channels,
+                start_frame,
+                duration,
+                self.sample_rate,
+                self.resample_quality,
+                sample,
+            );
+        }
+    }
+
+    /// Add already-rendered interleaved audio (at the mixer rate and channel count) into an insert
+    pub fn mix_buffer(&mut self, insert: &str, start_frame: usize, samples: &[S]) {
+        if samples.is_empty() {
+            return;
+        }
+        if !self.inserts.contains_key(insert) {
+            self.register_insert(insert.to_string(), Some(MASTER_INSERT));
+        }
+        let channels = self.channels;
+        if let Some(target) = self.inserts.get_mut(insert) {
+            let offset = start_frame.saturating_mul(channels);
+            target.ensure_frames(start_frame + samples.len().div_ceil(channels), channels);
+            for (slot, sample) in target.buffer[offset..].iter_mut().zip(samples) {
+                *slot += *sample;
             }
         }
     }
 
-    pub fn into_master_buffer(mut self, total_frames: usize) -> Vec<f32> {
+    /// Keep the signal of group `group` (every insert named after it) as it leaves its
+    /// insert, for `into_buffers`
+    pub fn add_output_tap(&mut self, group: &str) {
+        self.output_taps.insert(group.to_string());
+    }
+
+    /// Mix down every insert into master. Inserts are processed deepest first: each one
+    /// runs its effect chain on its summed signal, then adds the result into its parent.
+    pub fn into_master_buffer(self, total_frames: usize) -> Vec<S> {
+        self.into_buffers(total_frames).0
+    }
+
+    /// Mix down like `into_master_buffer`, also returning the processed and ducked
+    /// signal of each tapped group. Muted groups are not tapped.
+    pub fn into_buffers(self, total_frames: usize) -> (Vec<S>, OutputTaps<S>) {
+        let (master, taps, _) = self.into_metered_buffers(total_frames);
+        (master, taps)
+    }
+
+    /// Mix down like `into_buffers`, also returning the meters of every group insert
+    /// (empty unless `with_meters` was set), sorted by path
+    pub fn into_metered_buffers(
+        mut self,
+        total_frames: usize,
+    ) -> (Vec<S>, OutputTaps<S>, InsertMeters) {
         let samples = total_frames.saturating_mul(self.channels);
+        let mut taps: HashMap<String, Vec<S>> = HashMap::new();
+        let mut meters = InsertMeters::new();
         self.ensure_master_frames(total_frames);
+
+        let mut order: Vec<(usize, String)> = self
+            .inserts
+            .keys()
+            .filter(|name| name.as_str() != MASTER_INSERT)
+            .cloned()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|name| (self.route_chain(&name).len(), name))
+            .collect();
+        order.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        let duck_gains = self.duck_gains(total_frames);
+
+        for (_, name) in order {
+            let Some(mut insert) = self.inserts.remove(&name) else {
+                continue;
+            };
+            if insert.muted {
+                continue;
+            }
+            self.process_insert(&mut insert);
+            if let Some(gains) = duck_gains.get(&name) {
+                duck::apply_gain_curve(&mut insert.buffer, self.channels, gains);
+            }
+            if let Some(window) = self.meter_window {
+                meters.push((
+                    name.clone(),
+                    peak_envelope(&insert.buffer, self.channels, window),
+                ));
+            }
+            if let Some(group) = name.rsplit('/').next()
+                && self.output_taps.contains(group)
+            {
+                let tap = taps
+                    .entry(group.to_string())
+                    .or_insert_with(|| vec![S::default(); samples]);
+                for (slot, sample) in tap.iter_mut().zip(&insert.buffer) {
+                    *slot += *sample;
+                }
+            }
+            let parent = insert
+                .parent
+                .clone()
+                .unwrap_or_else(|| MASTER_INSERT.to_string());
+            self.mix_buffer(&parent, 0, &insert.buffer);
+        }
+
         let mut master = self
             .inserts
             .remove(MASTER_INSERT)
             .unwrap_or_else(|| AudioInsert::new(MASTER_INSERT));
+        self.process_insert(&mut master);
+        meters.sort_by(|a, b| a.0.cmp(&b.0));
         if samples == 0 {
             master.buffer.clear();
-            return master.buffer;
+            return (master.buffer, taps, meters);
         }
         if master.buffer.len() < samples {
-            master.buffer.resize(samples, 0.0);
+            master.buffer.resize(samples, S::default());
         } else if master.buffer.len() > samples {
             master.buffer.truncate(samples);
         }
-        master.buffer
+        (master.buffer, taps, meters)
+    }
+
+    /// Gain curve of each ducked insert, from the dry signals of the inserts before
+    /// mixdown. Key and target match any segment of an insert path, so nested groups
+    /// duck and are ducked with their group. Several ducks on one insert multiply.
+    fn duck_gains(&self, total_frames: usize) -> HashMap<String, Vec<f32>> {
+        let in_group = |path: &str, group: &str| path.split('/').any(|name| name == group);
+        // An insert mixed into another one of the target group is ducked there, once
+        let feeds_group = |name: &str, group: &str| {
+            let mut parent = self.inserts.get(name).and_then(|i| i.parent.as_deref());
+            for _ in 0..self.inserts.len() {
+                match parent {
+                    Some(path) if path != MASTER_INSERT => {
+                        if in_group(path, group) {
+                            return true;
+                        }
+                        parent = self.inserts.get(path).and_then(|i| i.parent.as_deref());
+                    }
+                    _ => break,
+                }
+            }
+            false
+        };
+        let mut gains: HashMap<String, Vec<f32>> = HashMap::new();
+        for (target, key, settings) in &self.ducks {
+            let mut signal: Vec<S> = Vec::new();
+            let sources = self
+                .inserts
+                .iter()
+                .map(|(name, insert)| (name, &insert.buffer))
+                .chain(&self.duck_keys);
+            for (_, buffer) in sources.filter(|(name, _)| in_group(name, key)) {
+                if signal.len() < buffer.len() {
+                    signal.resize(buffer.len(), S::default());
+                }
+                for (slot, sample) in signal.iter_mut().zip(buffer) {
+                    *slot += *sample;
+                }
+            }
+            let curve = settings.gain_curve(&signal, self.channels, self.sample_rate, total_frames);
+            for name in self.inserts.keys() {
+                if !in_group(name, target) || feeds_group(name, target) {
+                    continue;
+                }
+                match gains.get_mut(name) {
+                    Some(existing) => {
+                        for (gain, duck) in existing.iter_mut().zip(&curve) {
+                            *gain *= duck;
+                        }
+                    }
+                    None => {
+                        gains.insert(name.clone(), curve.clone());
+                    }
+                }
+            }
+        }
+        gains
     }
 
     pub fn sanitize_label(label: &str) -> String {
@@ -215,6 +551,36 @@ impl AudioMixer {
             .collect()
     }
 
+    /// Run the insert's channel strip, then its effect chain, block by block. Both work
+    /// in f32, so each block is converted on the way in and out of the accumulator type.
+    fn process_insert(&self, insert: &mut AudioInsert<S>) {
+        if (insert.effects.is_empty() && insert.strip.is_none()) || insert.buffer.is_empty() {
+            return;
+        }
+        let mut strip = insert.strip.clone().map(|(strip, automation)| {
+            strip::StripProcessor::new(strip, automation, self.sample_rate, self.channels)
+        });
+        // Bus context: sample-manipulation effects (reverse, speed, ...) are not available
+        let mut chain =
+            build_effect_chain_with(Arc::clone(&self.effect_registry), &insert.effects, true);
+        let mut block = Vec::with_capacity(self.block_size * self.channels);
+        for (index, chunk) in insert
+            .buffer
+            .chunks_mut(self.block_size * self.channels)
+            .enumerate()
+        {
+            block.clear();
+            block.extend(chunk.iter().map(|s| s.to_f32()));
+            if let Some(strip) = strip.as_mut() {
+                strip.process(&mut block, index * self.block_size);
+            }
+            chain.process(&mut block, self.sample_rate);
+            for (slot, processed) in chunk.iter_mut().zip(&block) {
+                *slot = S::from_f32(*processed);
+            }
+        }
+    }
+
     fn route_chain(&mut self, insert: &str) -> Vec<String> {
         if !self.inserts.contains_key(insert) {
             self.register_insert(insert.to_string(), Some(MASTER_INSERT));
@@ -244,11 +610,12 @@ impl AudioMixer {
     }
 
     fn mix_into_insert(
-        insert: &mut AudioInsert,
+        insert: &mut AudioInsert<S>,
         channel_count: usize,
         start_frame: usize,
         duration: f32,
         output_rate: u32,
+        quality: ResampleQuality,
         sample: &SampleBuffer,
     ) {
         let channel_count = channel_count.max(1);
@@ -266,30 +633,41 @@ impl AudioMixer {
         let ratio = if output_rate == 0 {
             1.0
         } else {
-            sample.sample_rate() as f32 / output_rate as f32
+            sample.sample_rate() as f64 / output_rate as f64
         };
+        let frames = sample.frames().min(max_play_frames);
+        if ratio == 1.0 {
+            // Same rate: every read lands on a source frame
+            for frame_idx in 0..frames {
+                for ch in 0..channel_count {
+                    let buffer_index = (start_frame + frame_idx) * channel_count + ch;
+                    if let Some(slot) = insert.buffer.get_mut(buffer_index) {
+                        *slot += S::from_f32(sample.sample_channel(frame_idx, ch, channel_count));
+                    }
+                }
+            }
+            return;
+        }
+        let mut interpolator = Interpolator::new(quality, ratio);
         for frame_idx in 0..max_play_frames {
             let buffer_frame = start_frame + frame_idx;
-            let sample_pos = frame_idx as f32 * ratio;
-            if sample_pos >= sample.frames() as f32 {
+            let sample_pos = frame_idx as f64 * ratio;
+            if sample_pos >= sample.frames() as f64 {
                 break;
             }
-            let base = sample_pos.floor() as usize;
-            let next = if base + 1 >= sample.frames() {
-                sample.frames().saturating_sub(1)
-            } else {
-                base + 1
-            };
-            let frac = sample_pos - base as f32;
+            interpolator.prepare(sample_pos, sample.frames());
             for ch in 0..channel_count {
-                let v0 = sample.sample_channel(base, ch, channel_count);
-                let v1 = sample.sample_channel(next, ch, channel_count);
-                let interpolated = v0 + (v1 - v0) * frac;
+                let value =
+                    interpolator.apply(|frame| sample.sample_channel(frame, ch, channel_count));
                 let buffer_index = buffer_frame * channel_count + ch;
                 if let Some(slot) = insert.buffer.get_mut(buffer_index) {
-                    *slot += interpolated;
+                    *slot += S::from_f32(value);
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+#[path = "test_mixer.rs"]
+mod tests;
@@ -0,0 +1,249 @@
+//! Channel strip run on an insert before its effect chain
+//!
+//! ```deva
+//! strip drums gain -3db pan -0.2 invert lowcut 80hz
+//! automate drums.pan: sin($time)
+//! ```
+//!
+//! Input gain, pan, polarity flip and a low-cut filter, in that order. Pan balances a
+//! stereo insert (the centre leaves both sides alone, full left silences the right). Gain,
+//! pan and low-cut follow `automate <group>.<param>` statements, read once per block and
+//! ramped across it so automated moves do not click.
+
+use crate::engine::audio::generator::ParamCurve;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// Low-cut frequencies are kept inside this range (Hz)
+const MIN_LOWCUT: f32 = 10.0;
+const MAX_LOWCUT: f32 = 20000.0;
+
+/// Static settings of a strip, as written in the `strip` statement
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStrip {
+    /// Linear input gain
+    pub gain: f32,
+    /// -1.0 (left) to 1.0 (right)
+    pub pan: f32,
+    /// Flip the polarity of every channel
+    pub invert: bool,
+    /// Cutoff of the 12 dB/octave low-cut filter (Hz), off when `None`
+    pub lowcut: Option<f32>,
+}
+
+impl Default for ChannelStrip {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            pan: 0.0,
+            invert: false,
+            lowcut: None,
+        }
+    }
+}
+
+impl ChannelStrip {
+    /// Parse the options after `strip <group>`: `gain 0.8` (or `gain -3db`), `pan -0.2`,
+    /// `invert` and `lowcut 80hz` (or `lowcut off`), in any order
+    pub fn parse_options(options: &str) -> Result<Self> {
+        let mut strip = Self::default();
+        let mut words = options.split_whitespace().map(|w| w.trim_end_matches(':'));
+        while let Some(option) = words.next() {
+            if option == "invert" {
+                strip.invert = true;
+                continue;
+            }
+            let value = words
+                .next()
+                .ok_or_else(|| anyhow!("strip option '{}' requires a value", option))?;
+            let invalid = || anyhow!("invalid strip {}: '{}'", option, value);
+            match option {
+                "gain" => {
+                    let lower = value.to_ascii_lowercase();
+                    strip.gain = match lower.strip_suffix("db") {
+                        Some(db) => db
+                            .parse::<f32>()
+                            .map(crate::engine::audio::dsp::db_to_gain)
+                            .map_err(|_| invalid())?,
+                        None => value.parse::<f32>().map_err(|_| invalid())?,
+                    };
+                    if !strip.gain.is_finite() || strip.gain < 0.0 {
+                        return Err(invalid());
+                    }
+                }
+                "pan" => {
+                    strip.pan = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|pan| (-1.0..=1.0).contains(pan))
+                        .ok_or_else(invalid)?;
+                }
+                "lowcut" => {
+                    strip.lowcut = if value == "off" {
+                        None
+                    } else {
+                        Some(parse_frequency(value).ok_or_else(invalid)?)
+                    };
+                }
+                other => {
+                    return Err(anyhow!(
+                        "unknown strip option '{}' (expected gain, pan, invert or lowcut)",
+                        other
+                    ));
+                }
+            }
+        }
+        Ok(strip)
+    }
+}
+
+/// `80hz`, `1.2khz` or a bare number of Hz
+fn parse_frequency(raw: &str) -> Option<f32> {
+    let lower = raw.to_ascii_lowercase();
+    let hz = match lower.strip_suffix("khz") {
+        Some(khz) => khz.parse::<f32>().ok()? * 1000.0,
+        None => lower.strip_suffix("hz").unwrap_or(&lower).parse().ok()?,
+    };
+    (MIN_LOWCUT..=MAX_LOWCUT).contains(&hz).then_some(hz)
+}
+
+impl std::fmt::Display for ChannelStrip {
+    /// The options only, without the group (`gain 0.8 pan -0.2 invert lowcut 80hz`)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut options = Vec::new();
+        if self.gain != 1.0 {
+            options.push(format!("gain {}", self.gain));
+        }
+        if self.pan != 0.0 {
+            options.push(format!("pan {}", self.pan));
+        }
+        if self.invert {
+            options.push("invert".to_string());
+        }
+        if let Some(lowcut) = self.lowcut {
+            options.push(format!("lowcut {}hz", lowcut));
+        }
+        write!(f, "{}", options.join(" "))
+    }
+}
+
+/// Automation curves of a strip, taking seconds from the start of the render; where a
+/// curve gives nothing the static setting applies
+#[derive(Clone, Default)]
+pub struct StripAutomation {
+    pub gain: Option<ParamCurve>,
+    pub pan: Option<ParamCurve>,
+    pub lowcut: Option<ParamCurve>,
+}
+
+impl std::fmt::Debug for StripAutomation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StripAutomation")
+            .field("gain", &self.gain.is_some())
+            .field("pan", &self.pan.is_some())
+            .field("lowcut", &self.lowcut.is_some())
+            .finish()
+    }
+}
+
+/// Running state of a strip over one insert's signal
+pub struct StripProcessor {
+    strip: ChannelStrip,
+    automation: StripAutomation,
+    sample_rate: u32,
+    channels: usize,
+    /// Channel gains reached at the end of the previous block
+    gains: Option<Vec<f32>>,
+    /// Biquad memory per channel: `[x1, x2, y1, y2]`
+    filter: Vec<[f32; 4]>,
+}
+
+impl StripProcessor {
+    pub fn new(
+        strip: ChannelStrip,
+        automation: StripAutomation,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Self {
+        let channels = channels.max(1);
+        Self {
+            strip,
+            automation,
+            sample_rate,
+            channels,
+            gains: None,
+            filter: vec![[0.0; 4]; channels],
+        }
+    }
+
+    /// Process one block of interleaved audio starting at frame `start_frame` of the insert
+    pub fn process(&mut self, block: &mut [f32], start_frame: usize) {
+        let time = start_frame as f32 / self.sample_rate.max(1) as f32;
+        let value = |curve: &Option<ParamCurve>, fallback: f32| {
+            curve
+                .as_ref()
+                .and_then(|curve| curve(time))
+                .unwrap_or(fallback)
+        };
+        let gain = value(&self.automation.gain, self.strip.gain).max(0.0);
+        let pan = value(&self.automation.pan, self.strip.pan).clamp(-1.0, 1.0);
+        let polarity = if self.strip.invert { -1.0 } else { 1.0 };
+
+        let target = self.channel_gains(gain * polarity, pan);
+        let from = self.gains.clone().unwrap_or_else(|| target.clone());
+        let frames = block.len() / self.channels;
+        for (frame, chunk) in block.chunks_mut(self.channels).enumerate() {
+            let progress = (frame + 1) as f32 / frames.max(1) as f32;
+            for ((sample, from), to) in chunk.iter_mut().zip(&from).zip(&target) {
+                *sample *= from + (to - from) * progress;
+            }
+        }
+        self.gains = Some(target);
+
+        let lowcut = match (&self.automation.lowcut, self.strip.lowcut) {
+            (Some(curve), fallback) => curve(time).or(fallback),
+            (None, fallback) => fallback,
+        };
+        if let Some(cutoff) = lowcut {
+            self.lowcut(block, cutoff);
+        }
+    }
+
+    /// Balance of a stereo insert with `gain` on top; other layouts only take the gain
+    fn channel_gains(&self, gain: f32, pan: f32) -> Vec<f32> {
+        if self.channels != 2 {
+            return vec![gain; self.channels];
+        }
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let left = (pan.max(0.0) * quarter).cos();
+        let right = ((-pan).max(0.0) * quarter).cos();
+        vec![gain * left, gain * right]
+    }
+
+    /// Butterworth high-pass (RBJ biquad) at `cutoff`, the memory carrying across blocks
+    fn lowcut(&mut self, block: &mut [f32], cutoff: f32) {
+        let rate = self.sample_rate.max(1) as f32;
+        let cutoff = cutoff.clamp(MIN_LOWCUT, rate * 0.45);
+        let omega = 2.0 * std::f32::consts::PI * cutoff / rate;
+        let alpha = omega.sin() / std::f32::consts::SQRT_2;
+        let cos = omega.cos();
+        let a0 = 1.0 + alpha;
+        let b0 = (1.0 + cos) / 2.0 / a0;
+        let b1 = -(1.0 + cos) / a0;
+        let a1 = -2.0 * cos / a0;
+        let a2 = (1.0 - alpha) / a0;
+        for chunk in block.chunks_mut(self.channels) {
+            for (sample, state) in chunk.iter_mut().zip(self.filter.iter_mut()) {
+                let [x1, x2, y1, y2] = *state;
+                let x = *sample;
+                let y = b0 * x + b1 * x1 + b0 * x2 - a1 * y1 - a2 * y2;
+                *state = [x, x1, y, y1];
+                *sample = y;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_strip.rs"]
+mod tests;
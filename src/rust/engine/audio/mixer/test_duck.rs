@@ -0,0 +1,122 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::language::syntax::ast::DurationValue;
+use anyhow::Result;
+use std::collections::HashMap;
+
+fn params(entries: &[(&str, Value)]) -> Value {
+    let inner: HashMap<String, Value> = entries
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect();
+    let mut effect = HashMap::new();
+    effect.insert("duck".to_string(), Value::Map(inner));
+    Value::Map(effect)
+}
+
+#[test]
+fn test_settings_read_amount_and_times() {
+    let settings = DuckSettings::from_effect(
+        &params(&[
+            ("amount", Value::Number(0.6)),
+            ("attack", Value::Duration(DurationValue::Milliseconds(10.0))),
+            ("release", Value::Duration(DurationValue::Beats(0.5))),
+        ]),
+        120.0,
+    );
+    assert_eq!(settings.amount, 0.6);
+    assert!((settings.attack - 0.01).abs() < 1e-6);
+    assert!((settings.release - 0.25).abs() < 1e-6);
+
+    // `duck a to b with duck({ release: 80ms })` keeps times as text; numbers are ms
+    let settings = DuckSettings::from_effect(
+        &params(&[
+            ("attack", Value::Number(2.0)),
+            ("release", Value::String("80ms".to_string())),
+        ]),
+        120.0,
+    );
+    assert_eq!(settings.amount, DEFAULT_AMOUNT);
+    assert!((settings.attack - 0.002).abs() < 1e-6);
+    assert!((settings.release - 0.08).abs() < 1e-6);
+}
+
+#[test]
+fn test_gain_follows_the_key_with_attack_and_release() {
+    let settings = DuckSettings {
+        amount: 0.5,
+        attack: 0.0,
+        release: 0.01,
+    };
+    // Mono key at 1 kHz: silent, 10 frames at full level, then silent again
+    let mut key = vec![0.0f32; 100];
+    key[20..30].fill(1.0);
+    let gains = settings.gain_curve(&key, 1, 1000, 120);
+
+    assert_eq!(gains.len(), 120);
+    assert_eq!(gains[10], 1.0);
+    // Instant attack reaches the full amount at the key's peak
+    assert!((gains[25] - 0.5).abs() < 1e-6);
+    // Release recovers over ~10 ms (one time constant brings it to 1 - 0.5 / e)
+    assert!((gains[39] - (1.0 - 0.5 / std::f32::consts::E)).abs() < 0.01);
+    assert!(gains[119] > 0.99);
+    assert!(gains.windows(2).skip(30).all(|pair| pair[1] >= pair[0]));
+}
+
+#[test]
+fn test_silent_key_leaves_the_signal_alone() {
+    let gains = DuckSettings::default().gain_curve(&[0.0f32; 8], 2, 44100, 4);
+    assert_eq!(gains, vec![1.0; 4]);
+
+    let mut samples = vec![0.5f64, -0.5, 0.25, -0.25];
+    apply_gain_curve(&mut samples, 2, &[1.0, 0.5]);
+    assert_eq!(samples, vec![0.5, -0.5, 0.125, -0.125]);
+}
+
+#[test]
+fn test_duck_follows_the_key_group_even_when_it_is_not_heard() -> Result<()> {
+    let render = |duck: &str, solo: &[&str]| -> Result<Vec<f32>> {
+        let source = format!(
+            "bpm 120\nlet pad = synth sine\nlet hit = synth square\ngroup pads:\n    pad -> note(A4) -> duration(2000) -> velocity(20)\ngroup kick:\n    sleep 1000\n    hit -> note(C2) -> duration(300) -> velocity(20)\n{duck}\nspawn pads\nspawn kick\n"
+        );
+        let statements = crate::language::syntax::parser::driver::parse(
+            &source,
+            std::path::PathBuf::from("test.deva"),
+        )?;
+        let mut interp = AudioInterpreter::new(8000);
+        interp.solo_mute = crate::engine::audio::solo::SoloMute::from_names(
+            &solo.iter().map(|n| n.to_string()).collect::<Vec<_>>(),
+            &[],
+        );
+        interp.collect_all_events(&statements)?;
+        interp.render_audio()
+    };
+    // Peak of the stereo render between two times in seconds
+    let peak = |audio: &[f32], from: f32, to: f32| {
+        audio[(from * 16000.0) as usize..(to * 16000.0) as usize]
+            .iter()
+            .fold(0.0f32, |peak, s| peak.max(s.abs()))
+    };
+
+    let dry = render("", &["pads"])?;
+    let ducked = render(
+        "duck pads by kick amount 0.8 attack 1ms release 50ms",
+        &["pads"],
+    )?;
+    let before = peak(&ducked, 0.5, 0.9);
+    assert!((before - peak(&dry, 0.5, 0.9)).abs() < 1e-4);
+    // The soloed pads still make room for the kick they no longer play with
+    let during = peak(&ducked, 1.1, 1.2);
+    assert!(during < before * 0.5, "{during} vs {before}");
+    assert!(peak(&dry, 1.1, 1.2) > before * 0.9);
+    // and come back once it has released
+    assert!(peak(&ducked, 1.6, 1.9) > before * 0.9);
+
+    // The long form reads the same options
+    let long = render(
+        "routing:\n    duck pads to kick with duck({ amount: 0.8, attack: 1ms, release: 50ms })",
+        &["pads"],
+    )?;
+    assert_eq!(long, ducked);
+    Ok(())
+}
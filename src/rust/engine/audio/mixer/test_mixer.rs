@@ -0,0 +1,282 @@
+use super::*;
+
+fn mono_chain() -> Vec<Value> {
+    vec![Value::String("mono".to_string())]
+}
+
+#[test]
+fn test_insert_chain_runs_on_summed_group_before_parent() {
+    let mut mixer = AudioMixer::new(44100, 2);
+    mixer.register_insert("drums", Some(MASTER_INSERT));
+    mixer.set_insert_effects("drums", mono_chain());
+
+    // Two hits in the drums insert, one dry signal straight on master
+    mixer.mix_buffer("drums", 0, &[1.0, 0.0]);
+    mixer.mix_buffer("drums", 0, &[0.5, 0.0]);
+    mixer.mix_buffer(MASTER_INSERT, 0, &[0.0, 0.25]);
+
+    let out = mixer.into_master_buffer(1);
+    assert_eq!(out, vec![0.75, 1.0]);
+}
+
+#[test]
+fn test_nested_inserts_propagate_deepest_first() {
+    let mut mixer = AudioMixer::new(44100, 2);
+    mixer.register_insert("drums", Some(MASTER_INSERT));
+    mixer.register_insert("drums/fills", Some("drums"));
+    mixer.set_insert_effects("drums", mono_chain());
+
+    // The fill only reaches master through the drums chain
+    mixer.mix_buffer("drums/fills", 1, &[1.0, 0.0]);
+
+    let out = mixer.into_master_buffer(2);
+    assert_eq!(out, vec![0.0, 0.0, 0.5, 0.5]);
+}
+
+#[test]
+fn test_muted_insert_silences_its_children() {
+    let mut mixer = AudioMixer::new(44100, 2);
+    mixer.register_insert("drums", Some(MASTER_INSERT));
+    mixer.register_insert("drums/fills", Some("drums"));
+    mixer.register_insert("pads", Some(MASTER_INSERT));
+    mixer.set_insert_muted("drums", true);
+
+    mixer.mix_buffer("drums", 0, &[1.0, 1.0]);
+    mixer.mix_buffer("drums/fills", 0, &[1.0, 1.0]);
+    mixer.mix_buffer("pads", 0, &[0.25, 0.5]);
+
+    let out = mixer.into_master_buffer(1);
+    assert_eq!(out, vec![0.25, 0.5]);
+}
+
+#[test]
+fn test_output_taps_keep_the_processed_group_and_still_reach_master() {
+    let mut mixer = AudioMixer::new(44100, 2);
+    mixer.register_insert("verse", Some(MASTER_INSERT));
+    mixer.register_insert("verse/drums", Some("verse"));
+    mixer.register_insert("drums", Some(MASTER_INSERT));
+    mixer.set_insert_effects("verse/drums", mono_chain());
+    mixer.add_output_tap("drums");
+
+    mixer.mix_buffer("verse/drums", 0, &[1.0, 0.0]);
+    mixer.mix_buffer("drums", 1, &[0.25, 0.25]);
+    mixer.mix_buffer(MASTER_INSERT, 0, &[0.1, 0.1]);
+
+    let (master, taps) = mixer.into_buffers(2);
+    // Every insert named `drums` sums into the tap, after its chain
+    assert_eq!(taps.get("drums"), Some(&vec![0.5, 0.5, 0.25, 0.25]));
+    assert_eq!(master, vec![0.6, 0.6, 0.25, 0.25]);
+    assert_eq!(taps.len(), 1);
+}
+
+#[test]
+fn test_meters_read_inserts_after_their_chain() {
+    let mut mixer = AudioMixer::new(44100, 2).with_meters(1);
+    mixer.register_insert("drums", Some(MASTER_INSERT));
+    mixer.register_insert("pads", Some(MASTER_INSERT));
+    mixer.set_insert_effects("drums", mono_chain());
+
+    mixer.mix_buffer("drums", 0, &[1.0, 0.0, 0.5, 0.5]);
+    mixer.mix_buffer("pads", 0, &[0.25, 0.25]);
+
+    let (_, _, meters) = mixer.into_metered_buffers(2);
+    assert_eq!(
+        meters,
+        vec![
+            ("drums".to_string(), vec![0.5, 0.5]),
+            ("pads".to_string(), vec![0.25]),
+        ]
+    );
+}
+
+#[test]
+fn test_f64_accumulator_reduces_summing_error() {
+    // A loud hit followed by many quiet voices: f32 loses the quiet ones
+    let voices = 100_000;
+    let mut narrow = AudioMixer::<f32>::new(44100, 1);
+    let mut wide = AudioMixer::<f64>::new(44100, 1);
+    narrow.mix_buffer(MASTER_INSERT, 0, &[1.0]);
+    wide.mix_buffer(MASTER_INSERT, 0, &[1.0]);
+    for _ in 0..voices {
+        narrow.mix_buffer(MASTER_INSERT, 0, &[1e-8]);
+        wide.mix_buffer(MASTER_INSERT, 0, &[1e-8]);
+    }
+
+    let expected = 1.0 + voices as f64 * 1e-8f32 as f64;
+    let narrow_err = (narrow.into_master_buffer(1)[0] as f64 - expected).abs();
+    let wide_err = (wide.into_master_buffer(1)[0] - expected).abs();
+    assert!(wide_err < 1e-9, "f64 error {}", wide_err);
+    assert!(narrow_err > wide_err * 1000.0);
+}
+
+#[test]
+fn test_block_size_does_not_change_insert_output() {
+    let delay = vec![Value::Map(std::collections::HashMap::from([
+        ("type".to_string(), Value::String("delay".to_string())),
+        ("time".to_string(), Value::Number(5.0)),
+    ]))];
+    let input: Vec<f32> = (0..4096).map(|i| ((i % 97) as f32 / 97.0) - 0.5).collect();
+
+    let render = |block_size: usize| {
+        let mut mixer = AudioMixer::<f32>::new(44100, 2).with_block_size(block_size);
+        mixer.set_insert_effects("fx", delay.clone());
+        mixer.mix_buffer("fx", 0, &input);
+        mixer.into_master_buffer(2048)
+    };
+
+    let whole = render(4096);
+    assert_ne!(whole, input, "the delay should be applied");
+    assert_eq!(render(64), whole);
+}
+
+#[test]
+fn test_insert_cache_rerenders_only_changed_groups() -> anyhow::Result<()> {
+    use crate::engine::audio::interpreter::driver::AudioInterpreter;
+    use std::sync::{Arc, Mutex};
+
+    let render = |source: &str, cache: &Arc<Mutex<InsertCache>>| -> anyhow::Result<Vec<f32>> {
+        let statements = crate::language::syntax::parser::driver::parse(
+            source,
+            std::path::PathBuf::from("test.deva"),
+        )?;
+        let mut interp = AudioInterpreter::new(8000);
+        interp.enable_insert_cache(cache.clone());
+        interp.collect_events(&statements)?;
+        interp.render_audio()
+    };
+
+    let source = "let lead = synth sine\nlet bass = synth square\ngroup melody:\n    lead -> note(C4)\ngroup low:\n    bass -> note(C2)\ncall melody\ncall low\n";
+    let cache = Arc::new(Mutex::new(InsertCache::new()));
+    let first = render(source, &cache)?;
+    assert_eq!(cache.lock().unwrap().rendered, 2);
+
+    // Identical source: both inserts come from the cache and the mix is unchanged
+    let again = render(source, &cache)?;
+    assert_eq!(cache.lock().unwrap().reused, 2);
+    assert_eq!(first, again);
+
+    // Editing one group only re-renders that group
+    let edited = source.replace("note(C2)", "note(D2)");
+    render(&edited, &cache)?;
+    let cache = cache.lock().unwrap();
+    assert_eq!((cache.reused, cache.rendered), (1, 1));
+    Ok(())
+}
+
+/// RMS error of mixing a 5 kHz tone recorded at 22.05 kHz into a 44.1 kHz mixer
+fn upsampling_error(quality: ResampleQuality) -> f32 {
+    let tone = |rate: f32, n: usize| (2.0 * std::f32::consts::PI * 5000.0 * n as f32 / rate).sin();
+    let source: Vec<f32> = (0..2205).map(|n| tone(22050.0, n)).collect();
+    let sample = SampleBuffer::new(Arc::new(source), 1, 22050);
+
+    let mut mixer = AudioMixer::<f32>::new(44100, 1).with_resample_quality(quality);
+    mixer.mix_sample(MASTER_INSERT, 0, 0.1, &sample);
+    let out = mixer.into_master_buffer(4410);
+
+    // Skip the edges, where the kernel runs out of source frames
+    let middle = 400..4000;
+    let squared: f32 = middle
+        .clone()
+        .map(|n| (out[n] - tone(44100.0, n)).powi(2))
+        .sum();
+    (squared / middle.len() as f32).sqrt()
+}
+
+#[test]
+fn test_sinc_reads_repitched_samples_closer_than_linear() {
+    let linear = upsampling_error(ResampleQuality::Linear2);
+    let sinc = upsampling_error(ResampleQuality::Sinc48);
+    assert!(linear > 0.05, "linear error {}", linear);
+    assert!(
+        sinc < linear / 10.0,
+        "sinc error {} vs linear {}",
+        sinc,
+        linear
+    );
+}
+
+#[test]
+fn test_same_rate_samples_are_copied_whatever_the_quality() {
+    let sample = SampleBuffer::new(Arc::new(vec![0.5, -0.25, 1.0]), 1, 44100);
+    let mut mixer =
+        AudioMixer::<f32>::new(44100, 1).with_resample_quality(ResampleQuality::Sinc512);
+    mixer.mix_sample(MASTER_INSERT, 1, 0.0, &sample);
+    assert_eq!(mixer.into_master_buffer(4), vec![0.0, 0.5, -0.25, 1.0]);
+}
+
+#[test]
+fn test_duck_turns_the_target_insert_down_while_the_key_plays() {
+    let instant = DuckSettings {
+        amount: 0.75,
+        attack: 0.0,
+        release: 0.0,
+    };
+    let mut mixer = AudioMixer::<f32>::new(1000, 1);
+    mixer.add_duck("pads", "kick", instant);
+    // The key group may sit inside another one
+    mixer.register_insert("drums/kick", Some("drums"));
+    mixer.mix_buffer("drums/kick", 1, &[1.0, 0.5]);
+    mixer.mix_buffer("pads", 0, &[1.0; 4]);
+
+    let out = mixer.into_master_buffer(4);
+    assert_eq!(out, vec![1.0, 1.25, 1.125, 1.0]);
+}
+
+#[test]
+fn test_duck_reaches_nested_inserts_of_the_target_once() {
+    let instant = DuckSettings {
+        amount: 0.75,
+        attack: 0.0,
+        release: 0.0,
+    };
+    let mut mixer = AudioMixer::<f32>::new(1000, 1);
+    mixer.add_duck("pads", "kick", instant);
+    // One nested insert mixes into the ducked group, the other goes straight to master
+    mixer.register_insert("pads/fill", Some("pads"));
+    mixer.register_insert("pads/stab", Some(MASTER_INSERT));
+    mixer.mix_buffer("kick", 1, &[1.0, 0.5]);
+    mixer.mix_buffer("pads/fill", 0, &[1.0; 4]);
+    mixer.mix_buffer("pads/stab", 0, &[1.0; 4]);
+
+    let out = mixer.into_master_buffer(4);
+    assert_eq!(out, vec![2.0, 1.5, 1.75, 2.0]);
+}
+
+#[test]
+fn test_silent_duck_keys_still_duck() {
+    let mut mixer = AudioMixer::<f64>::new(1000, 1);
+    mixer.add_duck(
+        "pads",
+        "kick",
+        DuckSettings {
+            amount: 1.0,
+            attack: 0.0,
+            release: 0.0,
+        },
+    );
+    // A muted kick is not heard, but the pads still make room for it
+    mixer.mix_duck_key("kick", 2, &[1.0]);
+    mixer.mix_buffer("pads", 0, &[0.5; 4]);
+
+    let out = mixer.into_master_buffer(4);
+    assert_eq!(out, vec![0.5, 0.5, 0.0, 0.5]);
+}
+
+#[test]
+fn test_strip_runs_on_its_insert_before_the_effect_chain() {
+    let mut mixer = AudioMixer::<f64>::new(1000, 2);
+    let strip = ChannelStrip {
+        gain: 0.5,
+        pan: 1.0,
+        invert: true,
+        lowcut: None,
+    };
+    mixer.set_insert_strip("bass", strip, StripAutomation::default());
+    // The mono fold sees the strip's hard-right pan, not the dry signal
+    mixer.set_insert_effects("bass", mono_chain());
+    mixer.mix_buffer("bass", 0, &[1.0, 1.0]);
+    mixer.mix_buffer("pads", 0, &[0.25, 0.25]);
+
+    let out = mixer.into_master_buffer(1);
+    assert!(out.iter().all(|s| s.abs() < 1e-6), "{:?}", out);
+}
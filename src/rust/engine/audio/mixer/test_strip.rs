@@ -0,0 +1,150 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use anyhow::Result;
+use std::sync::Arc;
+
+#[test]
+fn test_parse_options_reads_every_setting_in_any_order() {
+    let strip = ChannelStrip::parse_options("lowcut 80hz invert pan -0.25 gain -6db").unwrap();
+    assert!((strip.gain - 0.501).abs() < 1e-3);
+    assert_eq!(strip.pan, -0.25);
+    assert!(strip.invert);
+    assert_eq!(strip.lowcut, Some(80.0));
+    assert_eq!(
+        ChannelStrip::parse_options("lowcut 1.2khz").unwrap().lowcut,
+        Some(1200.0)
+    );
+    assert_eq!(
+        ChannelStrip::parse_options("").unwrap(),
+        ChannelStrip::default()
+    );
+
+    assert!(ChannelStrip::parse_options("pan 2").is_err());
+    assert!(ChannelStrip::parse_options("gain").is_err());
+    assert!(ChannelStrip::parse_options("gain -1").is_err());
+    assert!(ChannelStrip::parse_options("lowcut 5hz").is_err());
+    assert!(ChannelStrip::parse_options("width 0.5").is_err());
+}
+
+#[test]
+fn test_display_reads_back_the_same_strip() {
+    let strip = ChannelStrip {
+        gain: 0.8,
+        pan: -0.2,
+        invert: true,
+        lowcut: Some(80.0),
+    };
+    assert_eq!(strip.to_string(), "gain 0.8 pan -0.2 invert lowcut 80hz");
+    assert_eq!(
+        ChannelStrip::parse_options(&strip.to_string()).unwrap(),
+        strip
+    );
+    assert_eq!(ChannelStrip::default().to_string(), "");
+}
+
+#[test]
+fn test_gain_pan_and_polarity_apply_per_channel() {
+    let strip = ChannelStrip {
+        gain: 0.5,
+        pan: -1.0,
+        invert: true,
+        lowcut: None,
+    };
+    let mut processor = StripProcessor::new(strip, StripAutomation::default(), 1000, 2);
+    let mut block = vec![1.0, 1.0, 0.5, 0.5];
+    processor.process(&mut block, 0);
+    // Hard left keeps the left side and silences the right
+    for (out, expected) in block.iter().zip([-0.5, 0.0, -0.25, 0.0]) {
+        assert!((out - expected).abs() < 1e-6, "{:?}", block);
+    }
+}
+
+#[test]
+fn test_lowcut_removes_dc_and_keeps_the_highs() {
+    let strip = ChannelStrip {
+        lowcut: Some(100.0),
+        ..ChannelStrip::default()
+    };
+    let mut processor = StripProcessor::new(strip, StripAutomation::default(), 8000, 1);
+    let mut dc = vec![1.0f32; 8000];
+    for (index, block) in dc.chunks_mut(512).enumerate() {
+        processor.process(block, index * 512);
+    }
+    assert!(dc[7999].abs() < 1e-3);
+
+    let mut processor = StripProcessor::new(strip, StripAutomation::default(), 8000, 1);
+    let mut high: Vec<f32> = (0..8000)
+        .map(|i| (i as f32 * 2.0 * std::f32::consts::PI * 2000.0 / 8000.0).sin())
+        .collect();
+    processor.process(&mut high, 0);
+    let peak = high[4000..]
+        .iter()
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(peak > 0.95 && peak < 1.05);
+}
+
+#[test]
+fn test_automated_gain_ramps_across_each_block() {
+    let automation = StripAutomation {
+        gain: Some(Arc::new(|t: f32| Some(if t < 0.004 { 1.0 } else { 0.0 }))),
+        ..StripAutomation::default()
+    };
+    let mut processor = StripProcessor::new(ChannelStrip::default(), automation, 1000, 1);
+    let mut first = vec![1.0; 4];
+    processor.process(&mut first, 0);
+    assert_eq!(first, vec![1.0; 4]);
+
+    // The curve drops to zero: the next block fades down to it instead of jumping
+    let mut second = vec![1.0; 4];
+    processor.process(&mut second, 4);
+    assert_eq!(second, vec![0.75, 0.5, 0.25, 0.0]);
+}
+
+#[test]
+fn test_strip_statement_and_automation_shape_the_group_insert() -> Result<()> {
+    let render = |extra: &str| -> Result<(AudioInterpreter, Vec<f32>)> {
+        let source = format!(
+            "bpm 120\nlet pad = synth sine\ngroup pads:\n    pad -> note(A4) -> duration(1000) -> velocity(20)\n{}spawn pads\n",
+            extra
+        );
+        let statements = crate::language::syntax::parser::driver::parse(
+            &source,
+            std::path::PathBuf::from("test.deva"),
+        )?;
+        let mut interp = AudioInterpreter::new(8000);
+        interp.collect_all_events(&statements)?;
+        let audio = interp.render_audio()?;
+        Ok((interp, audio))
+    };
+
+    let (_, dry) = render("")?;
+    let (interp, inverted) = render("strip pads invert pan 0\n")?;
+    assert!(interp.routing.strips["pads"].invert);
+    assert_eq!(dry.len(), inverted.len());
+    for (dry, inverted) in dry.iter().zip(&inverted) {
+        assert!((dry + inverted).abs() < 1e-4);
+    }
+
+    // Hard left from the strip, then back to the right half a second in
+    let (_, panned) = render("strip pads pan -1\nautomate pads.pan: $time * 4 - 1\n")?;
+    let frame = |at: f32| {
+        let index = (at * 8000.0) as usize * 2;
+        (
+            panned[index..index + 400]
+                .iter()
+                .step_by(2)
+                .map(|s| s.abs())
+                .sum::<f32>(),
+            panned[index + 1..index + 401]
+                .iter()
+                .step_by(2)
+                .map(|s| s.abs())
+                .sum::<f32>(),
+        )
+    };
+    let (left, right) = frame(0.05);
+    assert!(right < left * 0.5);
+    let (left, right) = frame(0.6);
+    assert!(left < right * 0.5);
+    Ok(())
+}
@@ -1,20 +1,40 @@
+pub mod accent;
+pub mod articulation;
 pub mod automation;
+pub mod choke;
+pub mod click;
+pub mod diagnostics;
+pub mod diff;
+pub mod dsp;
 pub mod effects;
 pub mod encoders;
 pub mod evaluator;
+pub mod event_export;
 pub mod events;
 pub mod fx;
 pub mod generator;
 pub mod graph;
 pub mod interpreter;
 pub mod lfo;
+pub mod macros;
 pub mod midi;
 #[cfg(feature = "cli")]
 pub mod midi_native;
 pub mod mixer;
 pub mod nodes;
+pub mod outputs;
+pub mod pattern_chain;
 pub mod playback;
+pub mod retrigger;
 #[cfg(feature = "cli")]
 pub mod samples;
+pub mod scene;
 pub mod settings;
+pub mod snapshot;
+pub mod solo;
 pub mod synth;
+pub mod tempo;
+
+#[cfg(all(test, feature = "cli"))]
+#[path = "test_golden.rs"]
+mod golden_tests;
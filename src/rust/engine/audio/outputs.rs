@@ -0,0 +1,118 @@
+//! Hardware output routing for live rigs
+//!
+//! ```deva
+//! route drums -> outputs 3-4
+//! route bass -> outputs 5
+//! ```
+//!
+//! The main mix always plays on outputs 1-2. Each routed group insert is also sent,
+//! after its effects and ducking, to one output (folded to mono) or a pair of outputs
+//! (left, right), so a front-of-house desk receives live stems next to the main mix.
+
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+
+/// Outputs the main mix is played on
+pub const MAIN_OUTPUTS: u16 = 2;
+
+/// Stereo signal of each routed group insert, keyed by group name
+pub type OutputTaps<S = f32> = HashMap<String, Vec<S>>;
+
+/// One group insert sent to hardware outputs `first..=last` (1-based)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutputRoute {
+    pub insert: String,
+    pub first: u16,
+    pub last: u16,
+}
+
+impl OutputRoute {
+    /// Parse the channels of `outputs 3-4` or `outputs 5`; a route spans one or two outputs
+    pub fn parse_channels(raw: &str) -> Result<(u16, u16)> {
+        let raw = raw.trim();
+        let channel = |text: &str| {
+            text.trim()
+                .parse::<u16>()
+                .ok()
+                .filter(|channel| *channel >= 1)
+                .ok_or_else(|| anyhow!("invalid output channel '{}'", text.trim()))
+        };
+        let (first, last) = match raw.split_once('-') {
+            Some((first, last)) => (channel(first)?, channel(last)?),
+            None => {
+                let only = channel(raw)?;
+                (only, only)
+            }
+        };
+        if last < first || last - first > 1 {
+            return Err(anyhow!(
+                "outputs {} must be one channel or a pair, e.g. 'outputs 3-4'",
+                raw
+            ));
+        }
+        Ok((first, last))
+    }
+
+    pub fn is_mono(&self) -> bool {
+        self.first == self.last
+    }
+}
+
+impl std::fmt::Display for OutputRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_mono() {
+            write!(f, "{} -> outputs {}", self.insert, self.first)
+        } else {
+            write!(f, "{} -> outputs {}-{}", self.insert, self.first, self.last)
+        }
+    }
+}
+
+/// Channels a device must offer to play every route (never fewer than the main pair)
+pub fn required_channels(routes: &[OutputRoute]) -> u16 {
+    routes
+        .iter()
+        .map(|route| route.last)
+        .fold(MAIN_OUTPUTS, u16::max)
+}
+
+/// Interleave the stereo main mix and the stereo signal of each routed insert into
+/// `channels` outputs. Routes landing on the same outputs add up; inserts that were
+/// not rendered (unknown or muted groups) leave their outputs silent.
+pub fn interleave(
+    main: &[f32],
+    taps: &OutputTaps,
+    routes: &[OutputRoute],
+    channels: u16,
+) -> Vec<f32> {
+    let channels = channels.max(MAIN_OUTPUTS) as usize;
+    let frames = main.len() / 2;
+    let mut out = vec![0.0f32; frames * channels];
+    for (frame, pair) in main.chunks_exact(2).enumerate() {
+        out[frame * channels] = pair[0];
+        out[frame * channels + 1] = pair[1];
+    }
+    for route in routes {
+        let Some(signal) = taps.get(&route.insert) else {
+            continue;
+        };
+        let first = route.first as usize - 1;
+        if route.last as usize > channels {
+            continue;
+        }
+        for (frame, pair) in signal.chunks_exact(2).take(frames).enumerate() {
+            let slot = frame * channels + first;
+            if route.is_mono() {
+                out[slot] += (pair[0] + pair[1]) * 0.5;
+            } else {
+                out[slot] += pair[0];
+                out[slot + 1] += pair[1];
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[path = "test_outputs.rs"]
+mod tests;
@@ -0,0 +1,176 @@
+//! Pattern chains: `pattern verse = a then b then (c alt d)`
+//!
+//! A chain is called like a pattern, but each call plays a single step of it and moves
+//! on, so a chain called from a loop walks through its patterns across the passes.
+//! `then` plays its parts in turn, `alt` takes the next of its parts each time it comes
+//! round, and `x N` (as in `a x2`) repeats a part for N steps before moving on. `alt`
+//! binds tighter than `then`; parentheses group.
+
+use anyhow::{Result, bail};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternChain {
+    Pattern(String),
+    Then {
+        parts: Vec<PatternChain>,
+        index: usize,
+    },
+    Alt {
+        parts: Vec<PatternChain>,
+        index: usize,
+    },
+    Repeat {
+        part: Box<PatternChain>,
+        times: usize,
+        done: usize,
+    },
+}
+
+impl PatternChain {
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source);
+        let mut position = 0;
+        let chain = parse_then(&tokens, &mut position)?;
+        if let Some(extra) = tokens.get(position) {
+            bail!(
+                "unexpected '{}' in pattern chain '{}'",
+                extra,
+                source.trim()
+            );
+        }
+        Ok(chain)
+    }
+
+    /// Pattern to play for this call; the chain then moves on to its next step
+    pub fn advance(&mut self) -> String {
+        self.step().0
+    }
+
+    /// Name for this step, and whether this part has just finished a full round
+    fn step(&mut self) -> (String, bool) {
+        match self {
+            PatternChain::Pattern(name) => (name.clone(), true),
+            PatternChain::Then { parts, index } => {
+                let (name, finished) = parts[*index].step();
+                if !finished {
+                    return (name, false);
+                }
+                *index = (*index + 1) % parts.len();
+                (name, *index == 0)
+            }
+            PatternChain::Alt { parts, index } => {
+                let (name, finished) = parts[*index].step();
+                if finished {
+                    *index = (*index + 1) % parts.len();
+                }
+                (name, finished)
+            }
+            PatternChain::Repeat { part, times, done } => {
+                let (name, finished) = part.step();
+                if !finished {
+                    return (name, false);
+                }
+                *done += 1;
+                if *done < *times {
+                    return (name, false);
+                }
+                *done = 0;
+                (name, true)
+            }
+        }
+    }
+
+    /// Every pattern name the chain can play
+    pub fn patterns(&self) -> Vec<&str> {
+        match self {
+            PatternChain::Pattern(name) => vec![name.as_str()],
+            PatternChain::Then { parts, .. } | PatternChain::Alt { parts, .. } => {
+                parts.iter().flat_map(PatternChain::patterns).collect()
+            }
+            PatternChain::Repeat { part, .. } => part.patterns(),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Vec<String> {
+    source
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_then(tokens: &[String], position: &mut usize) -> Result<PatternChain> {
+    let mut parts = vec![parse_alt(tokens, position)?];
+    while tokens.get(*position).map(String::as_str) == Some("then") {
+        *position += 1;
+        parts.push(parse_alt(tokens, position)?);
+    }
+    Ok(match parts.len() {
+        1 => parts.remove(0),
+        _ => PatternChain::Then { parts, index: 0 },
+    })
+}
+
+fn parse_alt(tokens: &[String], position: &mut usize) -> Result<PatternChain> {
+    let mut parts = vec![parse_repeat(tokens, position)?];
+    while tokens.get(*position).map(String::as_str) == Some("alt") {
+        *position += 1;
+        parts.push(parse_repeat(tokens, position)?);
+    }
+    Ok(match parts.len() {
+        1 => parts.remove(0),
+        _ => PatternChain::Alt { parts, index: 0 },
+    })
+}
+
+fn parse_repeat(tokens: &[String], position: &mut usize) -> Result<PatternChain> {
+    let mut chain = parse_atom(tokens, position)?;
+    while let Some(times) = tokens.get(*position).and_then(|token| repeat_count(token)) {
+        *position += 1;
+        if times == 0 {
+            bail!("pattern chain repeats must be at least x1");
+        }
+        chain = PatternChain::Repeat {
+            part: Box::new(chain),
+            times,
+            done: 0,
+        };
+    }
+    Ok(chain)
+}
+
+fn parse_atom(tokens: &[String], position: &mut usize) -> Result<PatternChain> {
+    let Some(token) = tokens.get(*position) else {
+        bail!("pattern chain ends where a pattern name was expected");
+    };
+    *position += 1;
+    match token.as_str() {
+        "(" => {
+            let inner = parse_then(tokens, position)?;
+            if tokens.get(*position).map(String::as_str) != Some(")") {
+                bail!("missing ')' in pattern chain");
+            }
+            *position += 1;
+            Ok(inner)
+        }
+        ")" | "then" | "alt" => bail!("expected a pattern name before '{}'", token),
+        name if name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.') =>
+        {
+            Ok(PatternChain::Pattern(name.to_string()))
+        }
+        other => bail!("'{}' is not a pattern name", other),
+    }
+}
+
+/// `x3` -> 3
+fn repeat_count(token: &str) -> Option<usize> {
+    token.strip_prefix('x')?.parse().ok()
+}
+
+#[cfg(test)]
+#[path = "test_pattern_chain.rs"]
+mod tests;
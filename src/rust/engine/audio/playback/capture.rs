@@ -0,0 +1,238 @@
+//! Hardware bounce: the notes bound to an external MIDI device (`bind lead ->
+//! mapping.out.<device>`) are streamed to its port in real time while the audio
+//! interface input is recorded, and the recording is lined up with the project timeline.
+
+use anyhow::{Context, Result, bail};
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use rodio::cpal::{self, SampleFormat};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::samples::resample;
+use crate::engine::audio::settings::ResampleQuality;
+
+/// One MIDI message sent `time` seconds into the timeline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiCue {
+    pub time: f32,
+    pub message: [u8; 3],
+}
+
+/// Audio recorded from the input device while the cues were sent
+#[derive(Debug, Clone, Default)]
+pub struct Capture {
+    /// Interleaved, `channels` per frame
+    pub samples: Vec<f32>,
+    pub channels: u16,
+    pub sample_rate: u32,
+    /// Frames recorded before the first cue went out (timeline zero)
+    pub lead_in: usize,
+}
+
+/// Note on/off cues for the notes and chords `source` plays, on `channel` (0-15). At
+/// equal times note-offs go first so repeated notes retrigger.
+pub fn schedule_notes(events: &[AudioEvent], source: &str, channel: u8) -> Vec<MidiCue> {
+    let channel = channel & 0x0F;
+    let mut cues = Vec::new();
+    for event in events {
+        let (midis, start_time, duration, velocity) = match event {
+            AudioEvent::Note {
+                midi,
+                start_time,
+                duration,
+                velocity,
+                synth_id,
+                ..
+            } if synth_id == source => (vec![*midi], *start_time, *duration, *velocity),
+            AudioEvent::Chord {
+                midis,
+                start_time,
+                duration,
+                velocity,
+                synth_id,
+                ..
+            } if synth_id == source => (midis.clone(), *start_time, *duration, *velocity),
+            _ => continue,
+        };
+        let velocity = (velocity.clamp(0.0, 1.0) * 127.0).round().max(1.0) as u8;
+        for midi in midis {
+            let note = midi.min(127);
+            cues.push(MidiCue {
+                time: start_time,
+                message: [0x90 | channel, note, velocity],
+            });
+            cues.push(MidiCue {
+                time: start_time + duration,
+                message: [0x80 | channel, note, 0],
+            });
+        }
+    }
+    cues.sort_by(|a, b| {
+        a.time
+            .total_cmp(&b.time)
+            .then_with(|| (a.message[0] & 0xF0).cmp(&(b.message[0] & 0xF0)))
+    });
+    cues
+}
+
+/// Line `capture` up with the timeline: drop the lead-in plus `latency` (the round trip
+/// through the gear and the interface), resample to `sample_rate` and return
+/// `length_frames` of interleaved stereo. Mono inputs are copied to both sides.
+pub fn align_capture(
+    capture: &Capture,
+    latency: Duration,
+    sample_rate: u32,
+    quality: ResampleQuality,
+    length_frames: usize,
+) -> Vec<f32> {
+    let channels = capture.channels.max(1) as usize;
+    let skip = capture.lead_in + (latency.as_secs_f64() * capture.sample_rate as f64) as usize;
+    let frames = capture.samples.chunks_exact(channels).skip(skip);
+    let (left, right): (Vec<f32>, Vec<f32>) = frames
+        .map(|frame| (frame[0], frame[1.min(channels - 1)]))
+        .unzip();
+
+    let convert = |side: &[f32]| {
+        let mut side = resample(side, capture.sample_rate, sample_rate, quality);
+        side.resize(length_frames, 0.0);
+        side
+    };
+    let (left, right) = (convert(&left), convert(&right));
+    left.into_iter()
+        .zip(right)
+        .flat_map(|(l, r)| [l, r])
+        .collect()
+}
+
+/// Record `input` (default device when `None`) while sending `cues` to MIDI output
+/// port `port`, then keep recording for `tail` so releases are captured
+pub fn record_while_sending(
+    input: Option<&str>,
+    port: usize,
+    cues: &[MidiCue],
+    tail: Duration,
+) -> Result<Capture> {
+    let device = find_input_device(input)?;
+    let config = device
+        .default_input_config()
+        .context("failed to read the input device configuration")?;
+    let channels = config.channels();
+    let sample_rate = config.sample_rate().0;
+
+    let recorded = Arc::new(Mutex::new(Vec::<f32>::new()));
+    let sink = recorded.clone();
+    let on_error = |err| eprintln!("audio input error: {}", err);
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => device.build_input_stream(
+            &config.into(),
+            move |data: &[f32], _: &_| {
+                if let Ok(mut buffer) = sink.lock() {
+                    buffer.extend_from_slice(data);
+                }
+            },
+            on_error,
+            None,
+        ),
+        SampleFormat::I16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[i16], _: &_| {
+                if let Ok(mut buffer) = sink.lock() {
+                    buffer.extend(data.iter().map(|&s| s as f32 / i16::MAX as f32));
+                }
+            },
+            on_error,
+            None,
+        ),
+        SampleFormat::U16 => device.build_input_stream(
+            &config.into(),
+            move |data: &[u16], _: &_| {
+                if let Ok(mut buffer) = sink.lock() {
+                    buffer.extend(data.iter().map(|&s| s as f32 / 32768.0 - 1.0));
+                }
+            },
+            on_error,
+            None,
+        ),
+        other => bail!("unsupported input sample format {:?}", other),
+    }
+    .context("failed to open the audio input stream")?;
+
+    let midi_out = midir::MidiOutput::new("devalang-bounce")
+        .map_err(|e| anyhow::anyhow!("midi_out: {}", e))?;
+    let ports = midi_out.ports();
+    let Some(midi_port) = ports.get(port) else {
+        bail!(
+            "MIDI output port {} not found ({} available)",
+            port,
+            ports.len()
+        );
+    };
+    let mut connection = midi_out
+        .connect(midi_port, "devalang-bounce")
+        .map_err(|e| anyhow::anyhow!("connect out: {}", e))?;
+
+    stream
+        .play()
+        .context("failed to start the audio input stream")?;
+    // Wait for the first input block so the recording is running when the timeline starts
+    let opened = Instant::now();
+    while recorded.lock().map(|b| b.is_empty()).unwrap_or(false) {
+        if opened.elapsed() > Duration::from_secs(2) {
+            bail!("the audio input device delivered no audio");
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    // Timeline zero is the moment the first cue may go out; what was recorded before it
+    // is the lead-in
+    let lead_in = recorded.lock().map(|b| b.len()).unwrap_or(0) / channels as usize;
+    let start = Instant::now();
+    for cue in cues {
+        let at = Duration::from_secs_f32(cue.time.max(0.0));
+        if let Some(wait) = at.checked_sub(start.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        let _ = connection.send(&cue.message);
+    }
+    std::thread::sleep(tail);
+    drop(stream);
+    connection.close();
+
+    let samples =
+        std::mem::take(&mut *recorded.lock().map_err(|_| {
+            anyhow::anyhow!("audio input buffer was poisoned by the recording thread")
+        })?);
+    Ok(Capture {
+        samples,
+        channels,
+        sample_rate,
+        lead_in,
+    })
+}
+
+fn find_input_device(name: Option<&str>) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+    let Some(name) = name else {
+        return host
+            .default_input_device()
+            .context("no default audio input device");
+    };
+    let needle = name.to_lowercase();
+    host.input_devices()
+        .context("failed to enumerate audio input devices")?
+        .find(|d| {
+            d.name()
+                .map(|n| n == name || n.to_lowercase().contains(&needle))
+                .unwrap_or(false)
+        })
+        .with_context(|| {
+            format!(
+                "audio input device '{}' not found (run `devalang devices list`)",
+                name
+            )
+        })
+}
+
+#[cfg(test)]
+#[path = "test_capture.rs"]
+mod tests;
@@ -0,0 +1,81 @@
+//! Channel layout of playback sources on the opened output stream
+//!
+//! rodio fills outputs a source does not have by repeating its last channel, so a
+//! stereo master on an 8-channel interface would play the right channel on outputs 3-8
+//! and a 6-output routing file would bleed into outputs 7-8. Sources are interleaved
+//! onto the stream's channel count here instead, with silence on the extra outputs.
+
+use std::time::Duration;
+
+use rodio::Source;
+
+/// `inner` interleaved onto `to` channels: its first channels as they are, silence on
+/// the outputs it does not have, and channels past `to` dropped
+pub struct PadChannels<I> {
+    inner: I,
+    from: u16,
+    to: u16,
+    /// Output channel of the next sample
+    position: u16,
+}
+
+impl<I: Iterator<Item = f32>> PadChannels<I> {
+    pub fn new(inner: I, from: u16, to: u16) -> Self {
+        Self {
+            inner,
+            from: from.max(1),
+            to: to.max(1),
+            position: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = f32>> Iterator for PadChannels<I> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position == self.to {
+            for _ in self.to..self.from {
+                self.inner.next()?;
+            }
+            self.position = 0;
+        }
+        let sample = if self.position < self.from {
+            self.inner.next()?
+        } else {
+            0.0
+        };
+        self.position += 1;
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for PadChannels<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.to
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// Interleaved `samples` of `from` channels, re-interleaved onto `to` channels
+pub fn pad_channels(samples: Vec<f32>, from: u16, to: u16) -> Vec<f32> {
+    if from == to {
+        return samples;
+    }
+    PadChannels::new(samples.into_iter(), from, to).collect()
+}
+
+#[cfg(test)]
+#[path = "test_channels.rs"]
+mod tests;
@@ -0,0 +1,180 @@
+//! Computer-keyboard triggering for live sessions
+//!
+//! Keys are bound to bank triggers (`a -> .kit.kick`); each keypress is snapped to the
+//! nearest grid subdivision of the playing loop and can be recorded into a take, written
+//! back as `at beat` blocks.
+
+use anyhow::{Result, anyhow};
+
+use crate::engine::audio::tempo::TempoMap;
+
+/// One key bound to a bank trigger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: char,
+    /// Trigger without its leading dot, e.g. `kit.kick`
+    pub trigger: String,
+}
+
+impl KeyBinding {
+    /// Parse `a=.kit.kick` or `a -> .kit.kick`
+    pub fn parse(raw: &str) -> Result<Self> {
+        let (key, trigger) = raw
+            .split_once("->")
+            .or_else(|| raw.split_once('='))
+            .ok_or_else(|| anyhow!("expected KEY=TRIGGER, got '{}'", raw))?;
+
+        let key = key.trim();
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(c), None) if !c.is_whitespace() => c.to_ascii_lowercase(),
+            _ => return Err(anyhow!("key must be a single character, got '{}'", key)),
+        };
+
+        let trigger = trigger.trim().trim_start_matches('.');
+        if trigger.is_empty() {
+            return Err(anyhow!("missing trigger for key '{}'", key));
+        }
+        Ok(Self {
+            key,
+            trigger: trigger.to_string(),
+        })
+    }
+}
+
+/// Snaps keypresses to a beat grid inside a loop
+#[derive(Debug, Clone)]
+pub struct Quantizer {
+    beat_seconds: f32,
+    /// Grid step in beats (`1/16` is a quarter beat)
+    grid_beats: f32,
+    loop_seconds: f32,
+    /// Tempo and meter changes of the loop, for its bar lines
+    tempo_map: TempoMap,
+}
+
+impl Quantizer {
+    pub fn new(bpm: f32, grid_beats: f32, loop_seconds: f32) -> Self {
+        Self {
+            beat_seconds: 60.0 / bpm.max(1.0),
+            grid_beats: grid_beats.max(f32::EPSILON),
+            loop_seconds,
+            tempo_map: TempoMap::new(),
+        }
+    }
+
+    /// Place bar lines by the tempo and meter changes of `tempo_map`
+    pub fn with_tempo_map(mut self, tempo_map: TempoMap) -> Self {
+        self.tempo_map = tempo_map;
+        self
+    }
+
+    /// Parse a note-value grid (`1/16`, `1/8`, `1/4`) into beats
+    pub fn parse_grid(raw: &str) -> Result<f32> {
+        let invalid = || anyhow!("invalid grid '{}', expected a note value like 1/16", raw);
+        let (num, den) = raw.trim().split_once('/').ok_or_else(invalid)?;
+        let num: f32 = num.trim().parse().map_err(|_| invalid())?;
+        let den: f32 = den.trim().parse().map_err(|_| invalid())?;
+        if num <= 0.0 || den <= 0.0 {
+            return Err(invalid());
+        }
+        // A whole note spans four beats
+        Ok(4.0 * num / den)
+    }
+
+    pub fn beat_seconds(&self) -> f32 {
+        self.beat_seconds
+    }
+
+    /// First bar line at or after `position` seconds; bars follow the meter map (4/4
+    /// without one), and a meter change starts a new bar as on the click track
+    pub fn next_bar(&self, position: f32) -> f32 {
+        let position = position.max(0.0);
+        let bpm = 60.0 / self.beat_seconds;
+        let beat = self.tempo_map.beat_at(position, bpm);
+        let (_, into) = self.tempo_map.bar_at(beat);
+        if into < 1e-4 {
+            return position;
+        }
+        let (numerator, denominator) = self.tempo_map.meter_at(beat);
+        let bar_end = beat - into + 4.0 * numerator.max(1) as f32 / denominator.max(1) as f32;
+        let next_meter = self
+            .tempo_map
+            .meters
+            .iter()
+            .map(|change| change.beat)
+            .find(|&change| change > beat)
+            .unwrap_or(f32::INFINITY);
+        self.tempo_map.seconds_at(bar_end.min(next_meter), bpm)
+    }
+
+    /// Snap a keypress at `position` seconds into the loop. Returns the grid time inside
+    /// the loop and how long to wait before playing it; presses just after a grid line
+    /// play right away.
+    pub fn snap(&self, position: f32) -> (f32, f32) {
+        let step = self.grid_beats * self.beat_seconds;
+        let snapped = (position / step).round() * step;
+        let delay = (snapped - position).max(0.0);
+        let time = if self.loop_seconds > 0.0 {
+            snapped.rem_euclid(self.loop_seconds)
+        } else {
+            snapped
+        };
+        // Rounding can land a hair below the loop length instead of wrapping to 0
+        let time = if self.loop_seconds - time < step * 1e-3 {
+            0.0
+        } else {
+            time
+        };
+        (time, delay)
+    }
+}
+
+/// Quantized hits recorded during a live session
+#[derive(Debug, Clone, Default)]
+pub struct Take {
+    hits: Vec<(f32, String)>,
+}
+
+impl Take {
+    pub fn record(&mut self, time: f32, trigger: &str) {
+        self.hits.push((time, trigger.to_string()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hits.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hits.len()
+    }
+
+    /// Devalang source replaying the take: one `at beat` block per hit, in time order.
+    /// The same trigger hit twice on one grid line (once per loop pass) is kept once.
+    pub fn to_source(&self, quantizer: &Quantizer, grid: &str) -> String {
+        let mut hits: Vec<(f32, &str)> = self
+            .hits
+            .iter()
+            .map(|(time, trigger)| {
+                let beat = time / quantizer.beat_seconds() + 1.0;
+                ((beat * 1000.0).round() / 1000.0, trigger.as_str())
+            })
+            .collect();
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        hits.dedup();
+
+        let mut source = format!(
+            "# Live take at {} BPM, quantized to {}\n",
+            60.0 / quantizer.beat_seconds(),
+            grid
+        );
+        for (beat, trigger) in hits {
+            source.push_str(&format!("\nat beat {}:\n    .{}\n", beat, trigger));
+        }
+        source
+    }
+}
+
+#[cfg(test)]
+#[path = "test_keys.rs"]
+mod tests;
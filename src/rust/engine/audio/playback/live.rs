@@ -2,14 +2,24 @@ use std::fs::File;
 use std::io::BufReader;
 use std::path::PathBuf;
 use std::sync::mpsc;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, bail};
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+use rodio::buffer::SamplesBuffer;
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::cpal::{FromSample, SizedSample};
+use rodio::dynamic_mixer::DynamicMixerController;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
 use tokio::time::sleep;
 
+use crate::engine::audio::playback::channels::{PadChannels, pad_channels};
+use crate::engine::audio::playback::osc::{OscSender, OscSettings, OscTimeline};
+use crate::engine::audio::playback::recording::SessionRecorder;
+use crate::engine::audio::playback::region::crossfade_patch;
+use crate::engine::audio::playback::speed::{LiveRate, PreviewRate, VarSpeed};
+use crate::engine::audio::scene::mix_transition;
 use crate::engine::audio::settings::{AudioBitDepth, AudioChannels, AudioFormat, ResampleQuality};
 use crate::tools::logger::Logger;
 
@@ -20,36 +30,80 @@ pub struct LivePlaybackEngine {
 
 struct LivePlaybackInner {
     logger: Arc<Logger>,
-    _stream: OutputStream,
-    handle: OutputStreamHandle,
+    _stream: OpenStream,
+    handle: OutputHandle,
+    channels: u16,
+}
+
+/// Keeps the output stream running while it is held
+enum OpenStream {
+    Rodio(OutputStream),
+    /// Opened with a fixed buffer size (`--buffer-size`), which rodio cannot request
+    FixedBuffer(rodio::cpal::Stream),
+}
+
+/// Creates the sinks that play on the open stream
+#[derive(Clone)]
+enum OutputHandle {
+    Rodio(OutputStreamHandle),
+    Mixer(Arc<DynamicMixerController<f32>>),
+}
+
+impl OutputHandle {
+    fn sink(&self) -> Result<Sink> {
+        match self {
+            Self::Rodio(handle) => Sink::try_new(handle).context("failed to create audio sink"),
+            Self::Mixer(mixer) => {
+                let (sink, queue) = Sink::new_idle();
+                mixer.add(queue);
+                Ok(sink)
+            }
+        }
+    }
 }
 
 impl LivePlaybackEngine {
     pub fn new(logger: Arc<Logger>) -> Result<Self> {
-        let (stream, handle) =
-            OutputStream::try_default().context("failed to access default audio output stream")?;
+        Self::with_output(logger, &OutputDeviceConfig::default())
+    }
+
+    /// Create an engine bound to the output device described by `output`
+    pub fn with_output(logger: Arc<Logger>, output: &OutputDeviceConfig) -> Result<Self> {
+        let (stream, handle, channels) = open_output_stream(&logger, output)?;
         Ok(Self {
             inner: Arc::new(LivePlaybackInner {
                 logger,
                 _stream: stream,
                 handle,
+                channels,
             }),
         })
     }
 
+    /// Channels of the opened output stream
+    pub fn channels(&self) -> u16 {
+        self.inner.channels
+    }
+
     pub fn logger(&self) -> &Logger {
         &self.inner.logger
     }
 
-    fn handle(&self) -> &OutputStreamHandle {
+    fn handle(&self) -> &OutputHandle {
         &self.inner.handle
     }
 
-    fn create_sink(&self, source: &LiveAudioSource) -> Result<Sink> {
-        create_sink_with_handle(self.handle(), source)
+    fn create_sink(&self, source: &LiveAudioSource, preview: Option<PreviewRate>) -> Result<Sink> {
+        create_sink_with_handle(self.handle(), source, preview)
     }
 
-    pub async fn play_once(&self, source: LiveAudioSource, volume: f32) -> Result<()> {
+    /// Play `source` once; `preview` changes the playback rate without re-rendering
+    pub async fn play_once(
+        &self,
+        source: LiveAudioSource,
+        volume: f32,
+        preview: Option<PreviewRate>,
+    ) -> Result<()> {
         let volume_display = if volume == 0.0 {
             " [MUTED]".to_string()
         } else if volume < 1.0 {
@@ -69,7 +123,10 @@ impl LivePlaybackEngine {
             format_duration_short(source.length),
             volume_display
         ));
-        let sink = Arc::new(self.create_sink(&source)?);
+        if let Some(preview) = preview {
+            log_preview_rate(self.logger(), preview);
+        }
+        let sink = Arc::new(self.create_sink(&source, preview)?);
         sink.set_volume(volume);
 
         // Attempt to load scheduled print events sidecar (module.printlog) for this audio
@@ -94,9 +151,15 @@ impl LivePlaybackEngine {
             sink_clone.sleep_until_end();
         });
 
-        // Track playback start time so we can schedule print events
-        let start_instant = std::time::Instant::now();
-        let mut next_log_idx: usize = 0;
+        // Track playback start time so we can schedule print events; a `--start-at`
+        // offset counts as already played
+        let start_instant = rewind(
+            Instant::now(),
+            source.start,
+            preview.map_or(1.0, |preview| preview.rate),
+        );
+        let offset = source.start.as_secs_f32();
+        let mut next_log_idx = scheduled_logs.partition_point(|(time, _)| *time < offset);
 
         // Poll loop: while playback is ongoing emit scheduled prints
         let poll_interval = std::time::Duration::from_millis(25);
@@ -107,7 +170,7 @@ impl LivePlaybackEngine {
             }
 
             if !scheduled_logs.is_empty() {
-                let elapsed = start_instant.elapsed().as_secs_f32();
+                let elapsed = render_elapsed(start_instant, preview);
                 while next_log_idx < scheduled_logs.len()
                     && scheduled_logs[next_log_idx].0 <= elapsed
                 {
@@ -157,6 +220,9 @@ impl LivePlaybackEngine {
             format_duration_short(source.length),
             volume_display
         ));
+        if let Some(preview) = options.preview {
+            log_preview_rate(self.logger(), preview);
+        }
         let (tx, rx) = mpsc::channel();
         let last_update = Arc::new(Mutex::new(Instant::now()));
         let logger = Arc::clone(&self.inner.logger);
@@ -186,42 +252,239 @@ impl LivePlaybackEngine {
     }
 }
 
-fn create_sink_with_handle(handle: &OutputStreamHandle, source: &LiveAudioSource) -> Result<Sink> {
+fn create_sink_with_handle(
+    handle: &OutputHandle,
+    source: &LiveAudioSource,
+    preview: Option<PreviewRate>,
+) -> Result<Sink> {
     let file = File::open(&source.path)
         .with_context(|| format!("unable to open audio file: {}", source.path.display()))?;
     let reader = BufReader::new(file);
     let decoder = Decoder::new(reader)
         .with_context(|| format!("failed to decode audio file: {}", source.path.display()))?;
-    let sink = Sink::try_new(handle).context("failed to create audio sink")?;
-    sink.append(decoder);
+    let sink = handle.sink()?;
+    let channels = decoder.channels();
+    let outputs = source.stream_channels.unwrap_or(channels);
+    match &source.overlay {
+        Some(overlay) => {
+            let file = File::open(overlay)
+                .with_context(|| format!("unable to open audio file: {}", overlay.display()))?;
+            let overlay = Decoder::new(BufReader::new(file))
+                .with_context(|| format!("failed to decode audio file: {}", overlay.display()))?;
+            let mixed = decoder
+                .convert_samples::<f32>()
+                .mix(overlay.convert_samples::<f32>())
+                .skip_duration(source.start);
+            append_source(
+                &sink,
+                PadChannels::new(mixed, channels, outputs),
+                preview,
+                None,
+                None,
+            );
+        }
+        None => {
+            let skipped = decoder.convert_samples::<f32>().skip_duration(source.start);
+            append_source(
+                &sink,
+                PadChannels::new(skipped, channels, outputs),
+                preview,
+                None,
+                None,
+            );
+        }
+    }
     sink.set_volume(1.0);
     Ok(sink)
 }
 
+/// Append `source` to `sink`, resampled at the preview rate when one is set. With a live
+/// tempo the source is always stretched, so tempo changes reach it while it plays.
+fn append_source<S>(
+    sink: &Sink,
+    source: S,
+    preview: Option<PreviewRate>,
+    tempo: Option<&LiveRate>,
+    recorder: Option<&SessionRecorder>,
+) where
+    S: Source<Item = f32> + Send + 'static,
+{
+    if let Some(tempo) = tempo {
+        let preview = preview.unwrap_or(PreviewRate::new(1.0, true));
+        let stretched = PreviewSource::new(source, preview).following(tempo.clone());
+        return append_recorded(sink, stretched, recorder);
+    }
+    match preview.filter(|preview| !preview.is_identity()) {
+        Some(preview) => append_recorded(sink, PreviewSource::new(source, preview), recorder),
+        None => append_recorded(sink, source, recorder),
+    }
+}
+
+/// Append `source` to `sink`, copying what it plays to the session recording if any
+fn append_recorded<S>(sink: &Sink, source: S, recorder: Option<&SessionRecorder>)
+where
+    S: Source<Item = f32> + Send + 'static,
+{
+    match recorder {
+        Some(recorder) => sink.append(recorder.tap(source)),
+        None => sink.append(source),
+    }
+}
+
+/// When playback would have started to have played `render` of the audio by `now`
+fn rewind(now: Instant, render: Duration, rate: f32) -> Instant {
+    now.checked_sub(render.div_f32(rate.max(f32::EPSILON)))
+        .unwrap_or(now)
+}
+
+/// Seconds of the render reached since `start`, accounting for the preview rate
+fn render_elapsed(start: Instant, preview: Option<PreviewRate>) -> f32 {
+    let elapsed = start.elapsed().as_secs_f32();
+    preview.map_or(elapsed, |preview| preview.render_seconds(elapsed))
+}
+
+fn log_preview_rate(logger: &Logger, preview: PreviewRate) {
+    logger.info(format!(
+        "Previewing at {}x{}",
+        preview.rate,
+        if preview.preserve_pitch {
+            " (pitch preserved)"
+        } else {
+            ""
+        }
+    ));
+}
+
+/// A source played back at a preview rate (see `speed::VarSpeed`)
+struct PreviewSource<S: Source<Item = f32>> {
+    inner: VarSpeed<S>,
+    channels: u16,
+    sample_rate: u32,
+    base_rate: f32,
+    /// Live tempo multiplied into the preview rate, and the value last applied
+    tempo: Option<(LiveRate, f32)>,
+}
+
+impl<S: Source<Item = f32>> PreviewSource<S> {
+    fn new(source: S, preview: PreviewRate) -> Self {
+        let channels = source.channels();
+        let sample_rate = source.sample_rate();
+        Self {
+            inner: VarSpeed::new(source, channels, sample_rate, preview),
+            channels,
+            sample_rate,
+            base_rate: preview.rate,
+            tempo: None,
+        }
+    }
+
+    fn following(mut self, tempo: LiveRate) -> Self {
+        let rate = tempo.rate();
+        self.inner.set_rate(self.base_rate * rate);
+        self.tempo = Some((tempo, rate));
+        self
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for PreviewSource<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some((tempo, applied)) = self.tempo.as_mut() {
+            let rate = tempo.rate();
+            if rate != *applied {
+                *applied = rate;
+                self.inner.set_rate(self.base_rate * rate);
+            }
+        }
+        self.inner.next()
+    }
+}
+
+impl<S: Source<Item = f32>> Source for PreviewSource<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
 fn run_loop(
     logger: Arc<Logger>,
-    handle: OutputStreamHandle,
+    handle: OutputHandle,
     initial: LiveAudioSource,
     options: LivePlaybackOptions,
     rx: mpsc::Receiver<PlaybackCommand>,
     last_update: Arc<Mutex<Instant>>,
 ) -> Result<()> {
     let mut current = initial;
-    let mut pending: Option<LiveAudioSource> = None;
+    let mut next_loop = NextLoop::default();
+    // Decoded copy of `current`; patches write into it while it plays
+    let mut buffer: Option<Arc<LoopBuffer>> = None;
+    // One pass of `current` whose start is crossfaded with the previous scene
+    let mut transition: Option<Arc<LoopBuffer>> = None;
     let poll_interval = options.poll_interval().max(Duration::from_millis(25));
 
+    let mut osc =
+        options
+            .osc
+            .as_ref()
+            .and_then(|(settings, bpm)| match OscSender::connect(settings) {
+                Ok(sender) => {
+                    logger.info(format!(
+                        "Mirroring playback to OSC {}:{}",
+                        settings.host, settings.port
+                    ));
+                    Some((sender, *bpm))
+                }
+                Err(err) => {
+                    logger.warn(format!("OSC output disabled: {err}"));
+                    None
+                }
+            });
+    let mut loop_index: u32 = 0;
+
     loop {
         logger.watch(format!(
             "Looping {} (~{})",
             current.path.display(),
             format_duration_short(current.length)
         ));
+        // Only the first pass starts at the `--start-at` offset
+        let offset = std::mem::take(&mut current.start);
         if let Ok(mut guard) = last_update.lock() {
-            *guard = Instant::now();
+            *guard = options.rewind(Instant::now(), offset);
         }
 
-        let sink = match create_sink_with_handle(&handle, &current) {
-            Ok(sink) => {
+        let prepared = match &buffer {
+            Some(loaded) => Ok(Arc::clone(loaded)),
+            None => LoopBuffer::load(&current).map(Arc::new),
+        }
+        .and_then(|loaded| {
+            let sink = handle.sink()?;
+            let pass = transition.take().unwrap_or_else(|| Arc::clone(&loaded));
+            append_source(
+                &sink,
+                LoopPass::new(pass, offset),
+                options.preview,
+                options.tempo.as_ref(),
+                options.recorder.as_ref(),
+            );
+            Ok((loaded, sink))
+        });
+        let sink = match prepared {
+            Ok((loaded, sink)) => {
+                buffer = Some(loaded);
                 sink.set_volume(options.volume());
                 Arc::new(sink)
             }
@@ -229,7 +492,20 @@ fn run_loop(
                 logger.error(format!("Failed to prepare live buffer: {err}"));
                 match rx.recv() {
                     Ok(PlaybackCommand::Queue(next)) => {
-                        pending = Some(next);
+                        current = next;
+                        continue;
+                    }
+                    Ok(PlaybackCommand::Patch(patch)) => {
+                        current = patch.source;
+                        continue;
+                    }
+                    Ok(PlaybackCommand::Crossfade(next)) => {
+                        current = next.source;
+                        continue;
+                    }
+                    Ok(PlaybackCommand::Swap(next, _)) => {
+                        current = next.source;
+                        buffer = Some(Arc::new(next.buffer));
                         continue;
                     }
                     Ok(PlaybackCommand::Stop) | Err(_) => break,
@@ -260,19 +536,26 @@ fn run_loop(
         });
 
         // Track playback start time so we can schedule print events
-        let start_instant = Instant::now();
-        let mut next_log_idx: usize = 0;
+        let start_instant = options.rewind(Instant::now(), offset);
+        let offset = offset.as_secs_f32();
+        let mut next_log_idx = scheduled_logs.partition_point(|(time, _)| *time < offset);
+        let mut timeline = osc.as_ref().map(|_| OscTimeline::load(&current.path));
 
         let mut stop_requested = false;
+        let mut crossfading = false;
 
         loop {
             if wait_handle.is_finished() {
                 let _ = wait_handle.join();
                 break;
             }
+            if let (Some((sender, bpm)), Some(timeline)) = (osc.as_mut(), timeline.as_mut()) {
+                let elapsed = options.render_elapsed(start_instant);
+                timeline.emit(sender, elapsed, *bpm, loop_index);
+            }
             // Emit scheduled prints at the correct playback time
             if !scheduled_logs.is_empty() {
-                let elapsed = start_instant.elapsed().as_secs_f32();
+                let elapsed = options.render_elapsed(start_instant);
                 while next_log_idx < scheduled_logs.len()
                     && scheduled_logs[next_log_idx].0 <= elapsed
                 {
@@ -283,9 +566,56 @@ fn run_loop(
                 }
             }
 
-            match rx.recv_timeout(poll_interval) {
-                Ok(PlaybackCommand::Queue(next)) => {
-                    pending = Some(next);
+            // A prerendered build fades in from where the playhead is once it reaches
+            // its bar line, and plays on from there
+            let elapsed = options.render_elapsed(start_instant);
+            let command = match next_loop.due(elapsed) {
+                Some(next) => Ok(PlaybackCommand::Crossfade(next)),
+                None => rx.recv_timeout(poll_interval),
+            };
+            match command {
+                Ok(PlaybackCommand::Queue(next)) => next_loop.queue(next),
+                Ok(PlaybackCommand::Patch(patch)) => {
+                    if let Some(patch) = next_loop.take_patch(patch) {
+                        apply_patch(
+                            &logger,
+                            patch,
+                            buffer.as_deref(),
+                            &mut current,
+                            &mut next_loop.pending,
+                            options.crossfade(),
+                        );
+                    }
+                }
+                Ok(PlaybackCommand::Swap(next, at)) => next_loop.schedule(next, at),
+                Ok(PlaybackCommand::Crossfade(next)) => {
+                    match buffer
+                        .as_deref()
+                        .map(|playing| playing.transition_to(&next, elapsed))
+                    {
+                        Some(Ok(mixed)) => {
+                            logger.success(format!(
+                                "Crossfading into {} over {}",
+                                next.source.path.display(),
+                                format_duration_short(next.fade)
+                            ));
+                            transition = Some(Arc::new(mixed));
+                            buffer = Some(Arc::new(next.buffer));
+                            current = next.source;
+                            next_loop = NextLoop::default();
+                            crossfading = true;
+                            sink.stop();
+                            let _ = wait_handle.join();
+                            break;
+                        }
+                        Some(Err(err)) => {
+                            logger.info(format!(
+                                "Switching after current loop instead of crossfading: {err}"
+                            ));
+                            next_loop.queue(next.source);
+                        }
+                        None => next_loop.queue(next.source),
+                    }
                 }
                 Ok(PlaybackCommand::Stop) => {
                     stop_requested = true;
@@ -311,7 +641,22 @@ fn run_loop(
 
         while let Ok(cmd) = rx.try_recv() {
             match cmd {
-                PlaybackCommand::Queue(next) => pending = Some(next),
+                PlaybackCommand::Queue(next) => next_loop.queue(next),
+                PlaybackCommand::Patch(patch) => {
+                    if let Some(patch) = next_loop.take_patch(patch) {
+                        apply_patch(
+                            &logger,
+                            patch,
+                            buffer.as_deref(),
+                            &mut current,
+                            &mut next_loop.pending,
+                            options.crossfade(),
+                        );
+                    }
+                }
+                // The pass already ended, so the new scene simply starts the next one
+                PlaybackCommand::Crossfade(next) => next_loop.queue(next.source),
+                PlaybackCommand::Swap(next, _) => next_loop.schedule(next, 0.0),
                 PlaybackCommand::Stop => {
                     stop_requested = true;
                     break;
@@ -323,15 +668,30 @@ fn run_loop(
             break;
         }
 
-        if let Some(next) = pending.take() {
-            logger.success(format!(
-                "Next build ready -> {} (~{}). Switching after current loop.",
-                next.path.display(),
-                format_duration_short(next.length)
-            ));
-            current = next;
-        } else {
-            logger.info("Replaying current loop (no pending build).");
+        loop_index = loop_index.wrapping_add(1);
+        if crossfading {
+            continue;
+        }
+        match next_loop.at_loop_start() {
+            Some(LoopSwitch::Swap(next)) => {
+                logger.success(format!(
+                    "Prerendered build ready -> {} (~{}). Swapping at the loop start.",
+                    next.source.path.display(),
+                    format_duration_short(next.source.length)
+                ));
+                current = next.source;
+                buffer = Some(Arc::new(next.buffer));
+            }
+            Some(LoopSwitch::Switch(next)) => {
+                logger.success(format!(
+                    "Next build ready -> {} (~{}). Switching after current loop.",
+                    next.path.display(),
+                    format_duration_short(next.length)
+                ));
+                current = next;
+                buffer = None;
+            }
+            None => logger.info("Replaying current loop (no pending build)."),
         }
     }
 
@@ -339,6 +699,272 @@ fn run_loop(
     Ok(())
 }
 
+/// Builds waiting to replace the playing loop: a full switch after the current pass, or a
+/// prerendered swap at a bar line (`--prerender`). Each new build replaces the one waiting.
+#[derive(Default)]
+struct NextLoop {
+    pending: Option<LiveAudioSource>,
+    /// Prerendered build and the second of the pass it swaps in at
+    scheduled: Option<(SceneTransition, f32)>,
+}
+
+/// What replaces the loop when a pass ends
+enum LoopSwitch {
+    Swap(SceneTransition),
+    Switch(LiveAudioSource),
+}
+
+impl NextLoop {
+    fn queue(&mut self, next: LiveAudioSource) {
+        self.pending = Some(next);
+        self.scheduled = None;
+    }
+
+    fn schedule(&mut self, next: SceneTransition, at: f32) {
+        self.pending = None;
+        self.scheduled = Some((next, at));
+    }
+
+    /// The scheduled swap once the playhead reaches its bar line, set to fade in from
+    /// where the playhead is (`elapsed` seconds into the pass) and play on from there
+    fn due(&mut self, elapsed: f32) -> Option<SceneTransition> {
+        let (mut next, _) = self.scheduled.take_if(|(_, at)| elapsed >= *at)?;
+        next.source.start = Duration::from_secs_f32(elapsed);
+        Some(next)
+    }
+
+    /// `patch` when it applies to the playing loop. A scheduled swap holds the build the
+    /// patch was diffed against, so the patched build switches in after the pass instead.
+    fn take_patch(&mut self, patch: RegionPatch) -> Option<RegionPatch> {
+        if self.scheduled.take().is_some() {
+            self.pending = Some(patch.source);
+            return None;
+        }
+        Some(patch)
+    }
+
+    /// The loop start is a bar line too, so a swap still waiting happens there, with its
+    /// buffer already decoded
+    fn at_loop_start(&mut self) -> Option<LoopSwitch> {
+        match self.scheduled.take() {
+            Some((next, _)) => Some(LoopSwitch::Swap(next)),
+            None => self.pending.take().map(LoopSwitch::Switch),
+        }
+    }
+}
+
+/// Write a patch into the playing loop, or queue its build as a full switch when the
+/// loop cannot take it (a full switch is already waiting, or the layout changed)
+fn apply_patch(
+    logger: &Logger,
+    patch: RegionPatch,
+    buffer: Option<&LoopBuffer>,
+    current: &mut LiveAudioSource,
+    pending: &mut Option<LiveAudioSource>,
+    fade: Duration,
+) {
+    // A waiting switch has not reached the loop yet, so the patch's baseline is not playing
+    if pending.is_some() {
+        *pending = Some(patch.source);
+        return;
+    }
+    let Some(buffer) = buffer else {
+        *pending = Some(patch.source);
+        return;
+    };
+    match buffer.patch(&patch, fade) {
+        Ok(()) => {
+            logger.success(format!(
+                "Patched {:.2}s-{:.2}s of the playing loop from {}",
+                patch.start,
+                patch.end,
+                patch.source.path.display()
+            ));
+            *current = patch.source;
+        }
+        Err(err) => {
+            logger.info(format!(
+                "Switching after current loop instead of patching: {err}"
+            ));
+            *pending = Some(patch.source);
+        }
+    }
+}
+
+/// Decoded loop kept in memory so live rebuilds can patch it while it plays
+struct LoopBuffer {
+    samples: RwLock<Vec<f32>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl LoopBuffer {
+    fn load(source: &LiveAudioSource) -> Result<Self> {
+        let (samples, channels, sample_rate) = decode_source(source)?;
+        Ok(Self {
+            samples: RwLock::new(samples),
+            channels,
+            sample_rate,
+        })
+    }
+
+    fn patch(&self, patch: &RegionPatch, fade: Duration) -> Result<()> {
+        let mut samples = self
+            .samples
+            .write()
+            .map_err(|_| anyhow::anyhow!("live buffer lock poisoned"))?;
+        if patch.channels != self.channels
+            || patch.sample_rate != self.sample_rate
+            || patch.samples.len() != samples.len()
+        {
+            bail!("the new build changed the loop length or format");
+        }
+        let to_frame = |secs: f32| (secs.max(0.0) as f64 * self.sample_rate as f64) as usize;
+        crossfade_patch(
+            &mut samples,
+            &patch.samples,
+            self.channels as usize,
+            to_frame(patch.start)..to_frame(patch.end),
+            to_frame(fade.as_secs_f32()),
+        );
+        Ok(())
+    }
+
+    /// One pass of `next`'s loop whose `next.fade` from its start position blends out of
+    /// this loop, read on from `elapsed` seconds (wrapping) as if it had kept playing
+    fn transition_to(&self, next: &SceneTransition, elapsed: f32) -> Result<LoopBuffer> {
+        let playing = self
+            .samples
+            .read()
+            .map_err(|_| anyhow::anyhow!("live buffer lock poisoned"))?;
+        if next.buffer.channels != self.channels || next.buffer.sample_rate != self.sample_rate {
+            bail!("the new scene changed the channel layout or sample rate");
+        }
+        let channels = self.channels.max(1) as usize;
+        let frames = playing.len() / channels;
+        if frames == 0 {
+            bail!("the playing loop is empty");
+        }
+
+        let mut incoming = next
+            .buffer
+            .samples
+            .read()
+            .map_err(|_| anyhow::anyhow!("live buffer lock poisoned"))?
+            .clone();
+        let to_frames = |secs: f64| (secs.max(0.0) * self.sample_rate as f64) as usize;
+        let start = to_frames(next.source.start.as_secs_f64()).min(incoming.len() / channels);
+        let fade_frames = to_frames(next.fade.as_secs_f64()).min(incoming.len() / channels - start);
+        let from = to_frames(elapsed as f64);
+        let outgoing: Vec<f32> = (0..fade_frames)
+            .flat_map(|offset| {
+                let frame = (from + offset) % frames;
+                playing[frame * channels..(frame + 1) * channels]
+                    .iter()
+                    .copied()
+            })
+            .collect();
+        mix_transition(&outgoing, &mut incoming[start * channels..], channels);
+
+        Ok(LoopBuffer {
+            samples: RwLock::new(incoming),
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+        })
+    }
+}
+
+/// Samples read per lock of the loop buffer; patches land between blocks
+const LOOP_BLOCK_SAMPLES: usize = 1024;
+
+/// One pass over a `LoopBuffer`
+struct LoopPass {
+    buffer: Arc<LoopBuffer>,
+    position: usize,
+    block: Vec<f32>,
+    block_index: usize,
+}
+
+impl LoopPass {
+    /// Pass beginning `start` into the loop
+    fn new(buffer: Arc<LoopBuffer>, start: Duration) -> Self {
+        let frame = (start.as_secs_f64() * buffer.sample_rate as f64) as usize;
+        Self {
+            position: frame * buffer.channels.max(1) as usize,
+            buffer,
+            block: Vec::with_capacity(LOOP_BLOCK_SAMPLES),
+            block_index: 0,
+        }
+    }
+}
+
+impl Iterator for LoopPass {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.block_index >= self.block.len() {
+            let samples = self.buffer.samples.read().ok()?;
+            let end = (self.position + LOOP_BLOCK_SAMPLES).min(samples.len());
+            if self.position >= end {
+                return None;
+            }
+            self.block.clear();
+            self.block.extend_from_slice(&samples[self.position..end]);
+            self.position = end;
+            self.block_index = 0;
+        }
+        let sample = self.block[self.block_index];
+        self.block_index += 1;
+        Some(sample)
+    }
+}
+
+impl Source for LoopPass {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.buffer.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.buffer.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Interleaved samples, channel count and rate of an audio file, on the source's
+/// stream channels when it has them
+fn decode_source(source: &LiveAudioSource) -> Result<(Vec<f32>, u16, u32)> {
+    let file = File::open(&source.path)
+        .with_context(|| format!("unable to open audio file: {}", source.path.display()))?;
+    let decoder = Decoder::new(BufReader::new(file))
+        .with_context(|| format!("failed to decode audio file: {}", source.path.display()))?;
+    let channels = decoder.channels();
+    let sample_rate = decoder.sample_rate();
+    let mut samples: Vec<f32> = decoder.convert_samples::<f32>().collect();
+    if let Some(overlay) = &source.overlay
+        && let Ok(file) = File::open(overlay)
+        && let Ok(overlay) = Decoder::new(BufReader::new(file))
+    {
+        for (sample, over) in samples.iter_mut().zip(overlay.convert_samples::<f32>()) {
+            *sample += over;
+        }
+    }
+    match source.stream_channels {
+        Some(outputs) => Ok((
+            pad_channels(samples, channels, outputs),
+            outputs,
+            sample_rate,
+        )),
+        None => Ok((samples, channels, sample_rate)),
+    }
+}
+
 fn format_duration_short(duration: Duration) -> String {
     if duration.as_secs() >= 1 {
         format!("{:.2}s", duration.as_secs_f64())
@@ -361,6 +987,14 @@ pub struct LiveAudioSource {
     pub sample_rate: u32,
     pub resample_quality: ResampleQuality,
     pub length: Duration,
+    /// Track mixed over the audio while playing only (the `--click monitor` metronome);
+    /// same rate and channel count as the audio
+    pub overlay: Option<PathBuf>,
+    /// Where playback begins (`--start-at`); live loops start later passes from the top
+    pub start: Duration,
+    /// Channels of the output stream the file plays on; outputs the file lacks stay
+    /// silent (see `playback::channels`). `None` leaves the layout to rodio.
+    pub stream_channels: Option<u16>,
 }
 
 impl LiveAudioSource {
@@ -381,14 +1015,278 @@ impl LiveAudioSource {
             sample_rate,
             resample_quality,
             length,
+            overlay: None,
+            start: Duration::ZERO,
+            stream_channels: None,
         }
     }
+
+    pub fn with_overlay(mut self, overlay: Option<PathBuf>) -> Self {
+        self.overlay = overlay;
+        self
+    }
+
+    pub fn starting_at(mut self, start: Duration) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Interleave the file onto an output stream of `channels` channels
+    pub fn on_stream_channels(mut self, channels: u16) -> Self {
+        self.stream_channels = Some(channels);
+        self
+    }
+}
+
+/// Output device selection for playback
+#[derive(Debug, Clone, Default)]
+pub struct OutputDeviceConfig {
+    /// Device name (exact match first, then case-insensitive substring); `None` = default output
+    pub device: Option<String>,
+    /// Frames per output buffer; `None` = the backend's default
+    pub buffer_size: Option<u32>,
+    /// Channels to open the device with (hardware output routes); `None` = device default
+    pub channels: Option<u16>,
+}
+
+/// Description of an audio output device, as reported by `devalang devices list`
+#[derive(Debug, Clone)]
+pub struct OutputDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub channels: Option<u16>,
+    pub sample_rate: Option<u32>,
+    /// Supported buffer size range in frames, when the backend reports one
+    pub buffer_range: Option<(u32, u32)>,
+}
+
+/// Enumerate the output devices of the default audio host
+pub fn list_output_devices() -> Result<Vec<OutputDeviceInfo>> {
+    let host = rodio::cpal::default_host();
+    let default_name = host.default_output_device().and_then(|d| d.name().ok());
+    let devices = host
+        .output_devices()
+        .context("failed to enumerate audio output devices")?;
+
+    Ok(devices
+        .map(|device| {
+            let name = device.name().unwrap_or_else(|_| "unknown".to_string());
+            let config = device.default_output_config().ok();
+            let buffer_range = config.as_ref().and_then(|c| match c.buffer_size() {
+                rodio::cpal::SupportedBufferSize::Range { min, max } => Some((*min, *max)),
+                rodio::cpal::SupportedBufferSize::Unknown => None,
+            });
+            OutputDeviceInfo {
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                channels: config.as_ref().map(|c| c.channels()),
+                sample_rate: config.as_ref().map(|c| c.sample_rate().0),
+                buffer_range,
+                name,
+            }
+        })
+        .collect())
+}
+
+fn find_output_device(name: &str) -> Result<rodio::cpal::Device> {
+    let host = rodio::cpal::default_host();
+    let devices: Vec<rodio::cpal::Device> = host
+        .output_devices()
+        .context("failed to enumerate audio output devices")?
+        .collect();
+
+    let needle = name.to_lowercase();
+    let position = devices
+        .iter()
+        .position(|d| d.name().map(|n| n == name).unwrap_or(false))
+        .or_else(|| {
+            devices.iter().position(|d| {
+                d.name()
+                    .map(|n| n.to_lowercase().contains(&needle))
+                    .unwrap_or(false)
+            })
+        });
+
+    match position {
+        Some(idx) => Ok(devices.into_iter().nth(idx).expect("index from position")),
+        None => bail!(
+            "audio output device '{}' not found (run `devalang devices list`)",
+            name
+        ),
+    }
+}
+
+fn open_output_stream(
+    logger: &Logger,
+    output: &OutputDeviceConfig,
+) -> Result<(OpenStream, OutputHandle, u16)> {
+    let device = match output.device.as_deref() {
+        Some(name) => Some(find_output_device(name)?),
+        None => None,
+    };
+    let channels = output.channels.filter(|channels| *channels > 2);
+
+    if channels.is_none() && output.buffer_size.is_none() {
+        let (stream, handle) = match &device {
+            Some(device) => {
+                let name = device.name().unwrap_or_else(|_| "unknown".to_string());
+                logger.info(format!("Audio output: {}", name));
+                OutputStream::try_from_device(device)
+                    .with_context(|| format!("failed to open audio output device '{}'", name))?
+            }
+            None => OutputStream::try_default()
+                .context("failed to access default audio output stream")?,
+        };
+        let channels = device
+            .or_else(|| rodio::cpal::default_host().default_output_device())
+            .and_then(|d| d.default_output_config().ok())
+            .map(|c| c.channels())
+            .unwrap_or(2);
+        return Ok((
+            OpenStream::Rodio(stream),
+            OutputHandle::Rodio(handle),
+            channels,
+        ));
+    }
+
+    let device = match device {
+        Some(device) => device,
+        None => rodio::cpal::default_host()
+            .default_output_device()
+            .context("no default audio output device")?,
+    };
+    let name = device.name().unwrap_or_else(|_| "unknown".to_string());
+    let config = match channels {
+        Some(channels) => multichannel_config(&device, channels).with_context(|| {
+            format!(
+                "audio output device '{}' does not offer {} output channels",
+                name, channels
+            )
+        })?,
+        None => device
+            .default_output_config()
+            .with_context(|| format!("failed to query audio output device '{}'", name))?,
+    };
+    let opened = config.channels();
+
+    let Some(frames) = output.buffer_size else {
+        logger.info(format!("Audio output: {} ({} channels)", name, opened));
+        let (stream, handle) = OutputStream::try_from_device_config(&device, config)
+            .with_context(|| format!("failed to open audio output device '{}'", name))?;
+        return Ok((
+            OpenStream::Rodio(stream),
+            OutputHandle::Rodio(handle),
+            opened,
+        ));
+    };
+    if let rodio::cpal::SupportedBufferSize::Range { min, max } = *config.buffer_size()
+        && (frames < min || frames > max)
+    {
+        bail!(
+            "buffer size {} is outside the device range ({}-{} frames)",
+            frames,
+            min,
+            max
+        );
+    }
+    logger.info(format!(
+        "Audio output: {} ({} channels, {} frame buffer)",
+        name, opened, frames
+    ));
+    let (stream, mixer) = open_fixed_buffer_stream(&device, &config, frames)
+        .with_context(|| format!("failed to open audio output device '{}'", name))?;
+    Ok((
+        OpenStream::FixedBuffer(stream),
+        OutputHandle::Mixer(mixer),
+        opened,
+    ))
+}
+
+/// Output stream with `frames` per buffer, mixing the sinks added to the returned
+/// controller the way rodio's own stream does
+fn open_fixed_buffer_stream(
+    device: &rodio::cpal::Device,
+    config: &rodio::cpal::SupportedStreamConfig,
+    frames: u32,
+) -> Result<(rodio::cpal::Stream, Arc<DynamicMixerController<f32>>)> {
+    use rodio::cpal::SampleFormat;
+    use rodio::cpal::traits::StreamTrait;
+
+    let stream_config = rodio::cpal::StreamConfig {
+        channels: config.channels(),
+        sample_rate: config.sample_rate(),
+        buffer_size: rodio::cpal::BufferSize::Fixed(frames),
+    };
+    let (controller, mixer) =
+        rodio::dynamic_mixer::mixer::<f32>(config.channels(), config.sample_rate().0);
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => build_mixer_stream::<f32>(device, &stream_config, mixer),
+        SampleFormat::I16 => build_mixer_stream::<i16>(device, &stream_config, mixer),
+        SampleFormat::I32 => build_mixer_stream::<i32>(device, &stream_config, mixer),
+        SampleFormat::U16 => build_mixer_stream::<u16>(device, &stream_config, mixer),
+        format => bail!(
+            "sample format {} is not supported with a fixed buffer size",
+            format
+        ),
+    }?;
+    stream.play().context("failed to start the output stream")?;
+    Ok((stream, controller))
+}
+
+fn build_mixer_stream<T: SizedSample + FromSample<f32>>(
+    device: &rodio::cpal::Device,
+    config: &rodio::cpal::StreamConfig,
+    mut mixer: rodio::dynamic_mixer::DynamicMixer<f32>,
+) -> Result<rodio::cpal::Stream> {
+    device
+        .build_output_stream::<T, _, _>(
+            config,
+            move |data, _| {
+                for sample in data.iter_mut() {
+                    *sample = mixer.next().map(T::from_sample_).unwrap_or(T::EQUILIBRIUM);
+                }
+            },
+            |err| eprintln!("an error occurred on output stream: {}", err),
+            None,
+        )
+        .context("failed to build the output stream")
+}
+
+/// Stream config with at least `channels` outputs: the fewest channels that fit, at the
+/// device's default rate when supported. Sources are padded to the channels it opens
+/// (`LiveAudioSource::on_stream_channels`), which can be more than were asked for.
+fn multichannel_config(
+    device: &rodio::cpal::Device,
+    channels: u16,
+) -> Option<rodio::cpal::SupportedStreamConfig> {
+    let default_rate = device.default_output_config().ok().map(|c| c.sample_rate());
+    let range = device
+        .supported_output_configs()
+        .ok()?
+        .filter(|range| range.channels() >= channels)
+        .min_by_key(|range| range.channels())?;
+    match default_rate {
+        Some(rate) if range.min_sample_rate() <= rate && rate <= range.max_sample_rate() => {
+            Some(range.with_sample_rate(rate))
+        }
+        _ => Some(range.with_max_sample_rate()),
+    }
 }
 
 #[derive(Clone)]
 pub struct LivePlaybackOptions {
     poll_interval: Duration,
     volume: f32,
+    output: OutputDeviceConfig,
+    /// OSC destination for playhead/meter/section messages, with the project tempo
+    osc: Option<(OscSettings, f32)>,
+    /// Fade in and out of a patched region
+    crossfade: Duration,
+    /// Playback rate for reviewing the loop faster or slower than rendered
+    preview: Option<PreviewRate>,
+    /// Tempo changed while playing (tap/nudge keys), as a rate over the render
+    tempo: Option<LiveRate>,
+    /// Copy of the master output written to disk (`--record-session`)
+    recorder: Option<SessionRecorder>,
 }
 
 impl LivePlaybackOptions {
@@ -396,14 +1294,75 @@ impl LivePlaybackOptions {
         Self {
             poll_interval,
             volume: 1.0,
+            output: OutputDeviceConfig::default(),
+            osc: None,
+            crossfade: Duration::from_millis(20),
+            preview: None,
+            tempo: None,
+            recorder: None,
         }
     }
 
+    /// Record everything the loop and the keyboard triggers play
+    pub fn with_recorder(mut self, recorder: SessionRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Follow `tempo` while playing; the loop is time-stretched, keeping its pitch
+    pub fn with_live_tempo(mut self, tempo: LiveRate) -> Self {
+        self.tempo = Some(tempo);
+        self
+    }
+
+    /// When a pass would have started to have played `render` of it by `now`
+    fn rewind(&self, now: Instant, render: Duration) -> Instant {
+        let tempo = self.tempo.as_ref().map_or(1.0, LiveRate::rate);
+        rewind(now, render, self.preview.map_or(1.0, |p| p.rate) * tempo)
+    }
+
+    /// Seconds of the render reached since `start`, through the preview rate and live tempo
+    fn render_elapsed(&self, start: Instant) -> f32 {
+        match &self.tempo {
+            Some(tempo) => tempo.render_seconds(start) * self.preview.map_or(1.0, |p| p.rate),
+            None => render_elapsed(start, self.preview),
+        }
+    }
+
+    pub fn with_preview_rate(mut self, preview: Option<PreviewRate>) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    pub fn with_crossfade(mut self, crossfade: Duration) -> Self {
+        self.crossfade = crossfade;
+        self
+    }
+
+    pub fn crossfade(&self) -> Duration {
+        self.crossfade
+    }
+
+    /// Mirror playback to OSC; `bpm` converts the playhead to beats and bars
+    pub fn with_osc(mut self, settings: OscSettings, bpm: f32) -> Self {
+        self.osc = Some((settings, bpm));
+        self
+    }
+
     pub fn with_volume(mut self, volume: f32) -> Self {
         self.volume = volume.clamp(0.0, 1.0);
         self
     }
 
+    pub fn with_output(mut self, output: OutputDeviceConfig) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn output(&self) -> &OutputDeviceConfig {
+        &self.output
+    }
+
     pub fn poll_interval(&self) -> Duration {
         self.poll_interval
     }
@@ -415,9 +1374,44 @@ impl LivePlaybackOptions {
 
 enum PlaybackCommand {
     Queue(LiveAudioSource),
+    Patch(RegionPatch),
+    Crossfade(SceneTransition),
+    /// Crossfade once the playhead reaches the given second of the pass
+    Swap(SceneTransition, f32),
     Stop,
 }
 
+/// A build that switched scenes, faded in over `fade` from where the playing loop is
+struct SceneTransition {
+    source: LiveAudioSource,
+    buffer: LoopBuffer,
+    fade: Duration,
+}
+
+/// A build decoded ahead of its swap (`--prerender`), so nothing is loaded at the switch
+pub struct PreparedLoop {
+    source: LiveAudioSource,
+    buffer: LoopBuffer,
+}
+
+impl PreparedLoop {
+    /// Decode `source`; meant for a worker thread while the current loop plays
+    pub fn load(source: LiveAudioSource) -> Result<Self> {
+        let buffer = LoopBuffer::load(&source)?;
+        Ok(Self { source, buffer })
+    }
+}
+
+/// A rebuilt loop that only differs from the playing one between `start` and `end`
+struct RegionPatch {
+    source: LiveAudioSource,
+    samples: Vec<f32>,
+    channels: u16,
+    sample_rate: u32,
+    start: f32,
+    end: f32,
+}
+
 pub struct LivePlaybackSession {
     engine: LivePlaybackEngine,
     commands: mpsc::Sender<PlaybackCommand>,
@@ -464,6 +1458,52 @@ impl LivePlaybackSession {
             .context("failed to queue next live buffer")
     }
 
+    /// Patch `start..end` seconds of the playing loop with `next`, crossfaded, instead of
+    /// switching after the current pass. The file is decoded here, off the playback thread;
+    /// the engine falls back to a full switch if the loop cannot be patched.
+    pub fn patch_region(&self, next: LiveAudioSource, start: f32, end: f32) -> Result<()> {
+        let (samples, channels, sample_rate) = decode_source(&next)?;
+        self.commands
+            .send(PlaybackCommand::Patch(RegionPatch {
+                source: next,
+                samples,
+                channels,
+                sample_rate,
+                start,
+                end,
+            }))
+            .context("failed to queue live patch")
+    }
+
+    /// Start `next` right away, crossfading from the playing loop over `fade`, instead of
+    /// switching after the current pass (scene changes with `switch ... over`)
+    pub fn crossfade_to(&self, next: LiveAudioSource, fade: Duration) -> Result<()> {
+        let buffer = LoopBuffer::load(&next)?;
+        self.commands
+            .send(PlaybackCommand::Crossfade(SceneTransition {
+                source: next,
+                buffer,
+                fade,
+            }))
+            .context("failed to queue scene crossfade")
+    }
+
+    /// Swap to the prerendered `next` once the playhead reaches `at` seconds (a bar line),
+    /// crossfading over `fade` and playing on from the same position. When `at` is past the
+    /// end of the pass, the swap happens at the loop start.
+    pub fn swap_at(&self, next: PreparedLoop, fade: Duration, at: f32) -> Result<()> {
+        self.commands
+            .send(PlaybackCommand::Swap(
+                SceneTransition {
+                    source: next.source,
+                    buffer: next.buffer,
+                    fade,
+                },
+                at,
+            ))
+            .context("failed to schedule the prerendered build")
+    }
+
     pub async fn heartbeat(&self) {
         sleep(self.options.poll_interval()).await;
     }
@@ -482,8 +1522,37 @@ impl LivePlaybackSession {
         Ok(())
     }
 
-    #[allow(dead_code)]
+    /// Seconds of the render reached in the current loop pass
+    pub fn playhead(&self) -> f32 {
+        self.options.render_elapsed(self.last_update())
+    }
+
+    /// Play mono `samples` once over the loop after `delay` seconds of render time
+    /// (keyboard triggers); the loop itself is left untouched
+    pub fn inject(&self, samples: Vec<f32>, sample_rate: u32, delay: f32) -> Result<()> {
+        // The preview rate and live tempo stretch the delay along with the sample
+        let delay = Duration::from_secs_f32(delay.max(0.0));
+        let sink = self.engine.handle().sink()?;
+        if let Some(recorder) = &self.options.recorder {
+            recorder.overlay(samples.clone(), sample_rate, delay.as_secs_f32());
+        }
+        append_source(
+            &sink,
+            SamplesBuffer::new(1, sample_rate, samples).delay(delay),
+            self.options.preview,
+            self.options.tempo.as_ref(),
+            None,
+        );
+        sink.set_volume(self.options.volume());
+        sink.detach();
+        Ok(())
+    }
+
     pub fn last_update(&self) -> Instant {
         *self.last_update.lock().expect("last_update poisoned")
     }
 }
+
+#[cfg(test)]
+#[path = "test_live.rs"]
+mod tests;
@@ -1,2 +1,14 @@
 #[cfg(feature = "cli")]
+pub mod capture;
+#[cfg(feature = "cli")]
+pub mod channels;
+pub mod keys;
+#[cfg(feature = "cli")]
 pub mod live;
+#[cfg(feature = "cli")]
+pub mod osc;
+#[cfg(feature = "cli")]
+pub mod recording;
+pub mod region;
+pub mod speed;
+pub mod tempo;
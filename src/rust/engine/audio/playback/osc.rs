@@ -0,0 +1,261 @@
+//! OSC mirror of live playback for external visualizers (TouchDesigner, VDMX, ...)
+//!
+//! While a live session plays, the playhead, per-insert meters and section changes are
+//! sent over UDP:
+//! - `/deva/playhead` `f:seconds f:beat i:bar i:loop`
+//! - `/deva/meter/<insert>` `f:peak` (e.g. `/deva/meter/master`, `/deva/meter/drums/fills`)
+//! - `/deva/section` `s:name f:seconds`
+//!
+//! Incoming messages are decoded with [`decode_message`] (live macro control).
+
+use std::collections::HashMap;
+use std::net::UdpSocket;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::engine::audio::tempo::TempoMap;
+
+/// Destination and rate limit for OSC output
+#[derive(Debug, Clone)]
+pub struct OscSettings {
+    pub host: String,
+    pub port: u16,
+    /// Minimum interval between two messages on the same address
+    pub throttle: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Float(f32),
+    Int(i32),
+    Str(String),
+}
+
+/// Encode an OSC 1.0 message: padded address, padded type tag string, big-endian arguments
+pub fn encode_message(address: &str, args: &[OscArg]) -> Vec<u8> {
+    fn push_padded(out: &mut Vec<u8>, text: &str) {
+        out.extend_from_slice(text.as_bytes());
+        // At least one NUL terminator, then pad to a multiple of four
+        out.push(0);
+        while !out.len().is_multiple_of(4) {
+            out.push(0);
+        }
+    }
+
+    let mut out = Vec::new();
+    push_padded(&mut out, address);
+    let tags: String = std::iter::once(',')
+        .chain(args.iter().map(|arg| match arg {
+            OscArg::Float(_) => 'f',
+            OscArg::Int(_) => 'i',
+            OscArg::Str(_) => 's',
+        }))
+        .collect();
+    push_padded(&mut out, &tags);
+    for arg in args {
+        match arg {
+            OscArg::Float(value) => out.extend_from_slice(&value.to_be_bytes()),
+            OscArg::Int(value) => out.extend_from_slice(&value.to_be_bytes()),
+            OscArg::Str(value) => push_padded(&mut out, value),
+        }
+    }
+    out
+}
+
+/// Decode an OSC 1.0 message into its address and arguments; bundles and unknown
+/// argument types are rejected
+pub fn decode_message(packet: &[u8]) -> Option<(String, Vec<OscArg>)> {
+    fn read_padded(packet: &[u8], at: &mut usize) -> Option<String> {
+        let rest = packet.get(*at..)?;
+        let len = rest.iter().position(|b| *b == 0)?;
+        let text = std::str::from_utf8(&rest[..len]).ok()?.to_string();
+        *at += (len + 4) & !3;
+        Some(text)
+    }
+    fn read_word(packet: &[u8], at: &mut usize) -> Option<[u8; 4]> {
+        let word = packet.get(*at..*at + 4)?.try_into().ok()?;
+        *at += 4;
+        Some(word)
+    }
+
+    let mut at = 0;
+    let address = read_padded(packet, &mut at)?;
+    if !address.starts_with('/') {
+        return None;
+    }
+    let tags = read_padded(packet, &mut at)?;
+    let mut args = Vec::new();
+    for tag in tags.strip_prefix(',')?.chars() {
+        args.push(match tag {
+            'f' => OscArg::Float(f32::from_be_bytes(read_word(packet, &mut at)?)),
+            'i' => OscArg::Int(i32::from_be_bytes(read_word(packet, &mut at)?)),
+            's' => OscArg::Str(read_padded(packet, &mut at)?),
+            _ => return None,
+        });
+    }
+    Some((address, args))
+}
+
+/// UDP sender with a per-address throttle
+pub struct OscSender {
+    socket: UdpSocket,
+    throttle: Duration,
+    last_sent: HashMap<String, Instant>,
+}
+
+impl OscSender {
+    pub fn connect(settings: &OscSettings) -> Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0").context("failed to open OSC socket")?;
+        socket
+            .connect((settings.host.as_str(), settings.port))
+            .with_context(|| {
+                format!(
+                    "failed to resolve OSC destination {}:{}",
+                    settings.host, settings.port
+                )
+            })?;
+        Ok(Self {
+            socket,
+            throttle: settings.throttle,
+            last_sent: HashMap::new(),
+        })
+    }
+
+    /// Send unless the same address was sent less than the throttle interval ago
+    pub fn send(&mut self, address: &str, args: &[OscArg]) {
+        let now = Instant::now();
+        if let Some(last) = self.last_sent.get(address)
+            && now.duration_since(*last) < self.throttle
+        {
+            return;
+        }
+        self.last_sent.insert(address.to_string(), now);
+        self.send_now(address, args);
+    }
+
+    /// Send immediately; delivery errors are ignored (nobody may be listening)
+    pub fn send_now(&mut self, address: &str, args: &[OscArg]) {
+        let _ = self.socket.send(&encode_message(address, args));
+    }
+}
+
+/// Meters, sections and tempo changes of one rendered loop, read from the build's sidecars
+#[derive(Debug, Default)]
+pub struct OscTimeline {
+    /// (insert path, window length in seconds, peak per window)
+    meters: Vec<(String, f32, Vec<f32>)>,
+    /// (start seconds, section name), sorted by time
+    sections: Vec<(f32, String)>,
+    next_section: usize,
+    /// Places the playhead in beats and bars
+    tempo_map: TempoMap,
+}
+
+impl OscTimeline {
+    /// Load `<stem>.meters`, `<stem>.sections` and `<stem>.tempo` next to the rendered
+    /// audio; missing sidecars leave the timeline empty (only the playhead is sent, at the
+    /// session tempo)
+    pub fn load(audio_path: &Path) -> Self {
+        let mut timeline = Self::default();
+        let Some(stem) = audio_path.file_stem().and_then(|s| s.to_str()) else {
+            return timeline;
+        };
+
+        let meters_path = audio_path.with_file_name(format!("{}.meters", stem));
+        if let Ok(contents) = std::fs::read_to_string(meters_path) {
+            for line in contents.lines() {
+                let mut fields = line.splitn(3, '\t');
+                if let (Some(insert), Some(window), Some(peaks)) =
+                    (fields.next(), fields.next(), fields.next())
+                    && let Ok(window) = window.parse::<f32>()
+                {
+                    let peaks = peaks
+                        .split(',')
+                        .filter_map(|peak| peak.parse::<f32>().ok())
+                        .collect();
+                    timeline.meters.push((insert.to_string(), window, peaks));
+                }
+            }
+        }
+
+        let sections_path = audio_path.with_file_name(format!("{}.sections", stem));
+        if let Ok(contents) = std::fs::read_to_string(sections_path) {
+            for line in contents.lines() {
+                if let Some((t, name)) = line.split_once('\t')
+                    && let Ok(secs) = t.parse::<f32>()
+                {
+                    timeline.sections.push((secs, name.to_string()));
+                }
+            }
+            timeline.sections.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+
+        let tempo_path = audio_path.with_file_name(format!("{}.tempo", stem));
+        if let Ok(contents) = std::fs::read_to_string(tempo_path) {
+            for line in contents.lines() {
+                let fields: Vec<&str> = line.split('\t').collect();
+                match fields.as_slice() {
+                    ["tempo", beat, bpm] => {
+                        if let (Ok(beat), Ok(bpm)) = (beat.parse(), bpm.parse()) {
+                            timeline.tempo_map.push_tempo(beat, bpm);
+                        }
+                    }
+                    ["meter", beat, numerator, denominator] => {
+                        if let (Ok(beat), Ok(numerator), Ok(denominator)) =
+                            (beat.parse(), numerator.parse(), denominator.parse())
+                        {
+                            timeline.tempo_map.push_meter(beat, numerator, denominator);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        timeline
+    }
+
+    /// Mirror the state at `elapsed` seconds into the loop to `sender`. `bpm` is the
+    /// tempo before the first tempo change.
+    pub fn emit(&mut self, sender: &mut OscSender, elapsed: f32, bpm: f32, loop_index: u32) {
+        let beat = self.tempo_map.beat_at(elapsed, bpm.max(1.0));
+        // Small epsilon so a playhead exactly on a bar line is not placed in the bar before
+        let (bar, _) = self.tempo_map.bar_at(beat + 1e-4);
+        sender.send(
+            "/deva/playhead",
+            &[
+                OscArg::Float(elapsed),
+                OscArg::Float(beat),
+                OscArg::Int(bar as i32 + 1),
+                OscArg::Int(loop_index as i32),
+            ],
+        );
+
+        for (insert, window, peaks) in &self.meters {
+            if *window <= 0.0 {
+                continue;
+            }
+            let peak = peaks
+                .get((elapsed / window) as usize)
+                .copied()
+                .unwrap_or(0.0);
+            sender.send(&format!("/deva/meter/{}", insert), &[OscArg::Float(peak)]);
+        }
+
+        // Section changes are never throttled so a quick succession is not lost
+        while let Some((start, name)) = self.sections.get(self.next_section)
+            && *start <= elapsed
+        {
+            sender.send_now(
+                "/deva/section",
+                &[OscArg::Str(name.clone()), OscArg::Float(*start)],
+            );
+            self.next_section += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_osc.rs"]
+mod tests;
@@ -0,0 +1,354 @@
+//! Master recording of live sessions (`devalang play --live --record-session`)
+//!
+//! What the loop sends to the output (after the preview rate and live tempo) and the
+//! keyboard hits played over it are written to stereo 32-bit float WAV segments. A new
+//! segment starts every `segment` so long jams stay manageable and a crash loses at most
+//! the unfinished file. Rebuilds and scene switches drop markers; each segment gets a
+//! label list next to it (`<segment>.txt`, Audacity's tab-separated start/end/text format).
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use rodio::Source;
+
+use crate::engine::audio::samples::resample;
+use crate::engine::audio::settings::ResampleQuality;
+
+/// Samples a tap collects before handing them to the writer thread
+const FLUSH_SAMPLES: usize = 4096;
+
+/// Where and how a live session is recorded
+#[derive(Debug, Clone)]
+pub struct SessionRecording {
+    pub dir: PathBuf,
+    /// Length of one segment file
+    pub segment: Duration,
+}
+
+enum Message {
+    Block {
+        samples: Vec<f32>,
+        channels: u16,
+        sample_rate: u32,
+    },
+    Overlay {
+        samples: Vec<f32>,
+        sample_rate: u32,
+        delay: f32,
+    },
+    Marker(String),
+    Stop,
+}
+
+/// Writer thread, returning the audio files it wrote
+type Worker = thread::JoinHandle<Result<Vec<PathBuf>>>;
+
+/// Handle to the writer thread, shared by the playback thread and the live service
+#[derive(Clone)]
+pub struct SessionRecorder {
+    tx: mpsc::Sender<Message>,
+    worker: Arc<Mutex<Option<Worker>>>,
+}
+
+impl SessionRecorder {
+    /// Start recording into `recording.dir`; files are named after `stem`
+    pub fn start(recording: &SessionRecording, stem: &str) -> Result<Self> {
+        std::fs::create_dir_all(&recording.dir).with_context(|| {
+            format!(
+                "failed to create session directory {}",
+                recording.dir.display()
+            )
+        })?;
+        let mut writer = SessionWriter::new(&recording.dir, stem, recording.segment);
+        let (tx, rx) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            while let Ok(message) = rx.recv() {
+                match message {
+                    Message::Block {
+                        samples,
+                        channels,
+                        sample_rate,
+                    } => writer.write_block(&samples, channels, sample_rate)?,
+                    Message::Overlay {
+                        samples,
+                        sample_rate,
+                        delay,
+                    } => writer.overlay(&samples, sample_rate, delay),
+                    Message::Marker(label) => writer.mark(label),
+                    Message::Stop => break,
+                }
+            }
+            writer.finish()
+        });
+        Ok(Self {
+            tx,
+            worker: Arc::new(Mutex::new(Some(worker))),
+        })
+    }
+
+    /// Drop a marker at the current end of the recording
+    pub fn mark(&self, label: impl Into<String>) {
+        let _ = self.tx.send(Message::Marker(label.into()));
+    }
+
+    /// Mix mono `samples` into the recording `delay` seconds from its current end
+    /// (keyboard hits, which play on their own sink)
+    pub fn overlay(&self, samples: Vec<f32>, sample_rate: u32, delay: f32) {
+        let _ = self.tx.send(Message::Overlay {
+            samples,
+            sample_rate,
+            delay,
+        });
+    }
+
+    /// Record everything `source` plays
+    pub fn tap<S: Source<Item = f32>>(&self, source: S) -> RecordTap<S> {
+        RecordTap {
+            source,
+            recorder: self.clone(),
+            block: Vec::with_capacity(FLUSH_SAMPLES),
+        }
+    }
+
+    /// Close the last segment; returns the audio files written
+    pub fn finish(&self) -> Result<Vec<PathBuf>> {
+        let _ = self.tx.send(Message::Stop);
+        let worker = self
+            .worker
+            .lock()
+            .map_err(|_| anyhow::anyhow!("session recorder lock poisoned"))?
+            .take();
+        match worker {
+            Some(worker) => worker
+                .join()
+                .map_err(|_| anyhow::anyhow!("session recorder thread panicked"))?,
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Source wrapper sending a copy of every sample to the recorder
+pub struct RecordTap<S: Source<Item = f32>> {
+    source: S,
+    recorder: SessionRecorder,
+    block: Vec<f32>,
+}
+
+impl<S: Source<Item = f32>> RecordTap<S> {
+    fn flush(&mut self) {
+        if self.block.is_empty() {
+            return;
+        }
+        let _ = self.recorder.tx.send(Message::Block {
+            samples: std::mem::replace(&mut self.block, Vec::with_capacity(FLUSH_SAMPLES)),
+            channels: self.source.channels(),
+            sample_rate: self.source.sample_rate(),
+        });
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for RecordTap<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.source.next();
+        match sample {
+            Some(sample) => {
+                self.block.push(sample);
+                if self.block.len() >= FLUSH_SAMPLES {
+                    self.flush();
+                }
+            }
+            None => self.flush(),
+        }
+        sample
+    }
+}
+
+impl<S: Source<Item = f32>> Source for RecordTap<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl<S: Source<Item = f32>> Drop for RecordTap<S> {
+    fn drop(&mut self) {
+        // A stopped sink drops its source mid-block
+        self.flush();
+    }
+}
+
+/// Writes the recording to segment files; runs on the recorder thread
+pub struct SessionWriter {
+    dir: PathBuf,
+    stem: String,
+    segment: Duration,
+    index: usize,
+    current: Option<Segment>,
+    /// Stereo frames of keyboard hits still to be mixed into the incoming audio
+    overlay: VecDeque<[f32; 2]>,
+    /// Markers dropped before the first audio arrived
+    early_markers: Vec<String>,
+    written: Vec<PathBuf>,
+}
+
+struct Segment {
+    writer: WavWriter<BufWriter<File>>,
+    path: PathBuf,
+    sample_rate: u32,
+    frames: u64,
+    markers: Vec<(f64, String)>,
+}
+
+impl SessionWriter {
+    pub fn new(dir: &Path, stem: &str, segment: Duration) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            stem: stem.to_string(),
+            segment,
+            index: 0,
+            current: None,
+            overlay: VecDeque::new(),
+            early_markers: Vec::new(),
+            written: Vec::new(),
+        }
+    }
+
+    /// Append interleaved audio; mono is copied to both sides, channels past the second
+    /// are dropped. A new segment starts when the current one is full or the rate changes.
+    pub fn write_block(&mut self, samples: &[f32], channels: u16, sample_rate: u32) -> Result<()> {
+        let channels = channels.max(1) as usize;
+        let segment_frames = (self.segment.as_secs_f64() * sample_rate as f64).max(1.0) as u64;
+        for frame in samples.chunks_exact(channels) {
+            let full = self.current.as_ref().is_none_or(|segment| {
+                segment.sample_rate != sample_rate || segment.frames >= segment_frames
+            });
+            if full {
+                self.next_segment(sample_rate)?;
+            }
+            let [over_left, over_right] = self.overlay.pop_front().unwrap_or_default();
+            let left = frame[0] + over_left;
+            let right = frame[1.min(channels - 1)] + over_right;
+            let Some(segment) = self.current.as_mut() else {
+                continue;
+            };
+            for sample in [left, right] {
+                segment
+                    .writer
+                    .write_sample(sample.clamp(-1.0, 1.0))
+                    .with_context(|| format!("failed to write {}", segment.path.display()))?;
+            }
+            segment.frames += 1;
+        }
+        Ok(())
+    }
+
+    /// Mix mono `samples` in `delay` seconds after the audio written so far
+    pub fn overlay(&mut self, samples: &[f32], sample_rate: u32, delay: f32) {
+        let rate = self
+            .current
+            .as_ref()
+            .map_or(sample_rate, |segment| segment.sample_rate);
+        let samples = if rate == sample_rate {
+            samples.to_vec()
+        } else {
+            resample(samples, sample_rate, rate, ResampleQuality::default())
+        };
+        let offset = (delay.max(0.0) as f64 * rate as f64) as usize;
+        if self.overlay.len() < offset + samples.len() {
+            self.overlay.resize(offset + samples.len(), [0.0; 2]);
+        }
+        for (frame, sample) in self.overlay.iter_mut().skip(offset).zip(samples) {
+            frame[0] += sample;
+            frame[1] += sample;
+        }
+    }
+
+    /// Drop a marker at the current end of the recording
+    pub fn mark(&mut self, label: String) {
+        match self.current.as_mut() {
+            Some(segment) => {
+                let time = segment.frames as f64 / segment.sample_rate.max(1) as f64;
+                segment.markers.push((time, label));
+            }
+            None => self.early_markers.push(label),
+        }
+    }
+
+    /// Close the last segment; returns the audio files written
+    pub fn finish(mut self) -> Result<Vec<PathBuf>> {
+        self.close_segment()?;
+        Ok(self.written)
+    }
+
+    fn next_segment(&mut self, sample_rate: u32) -> Result<()> {
+        self.close_segment()?;
+        self.index += 1;
+        let path = self
+            .dir
+            .join(format!("{}-{:03}.wav", self.stem, self.index));
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let writer = WavWriter::create(&path, spec)
+            .with_context(|| format!("failed to create {}", path.display()))?;
+        self.current = Some(Segment {
+            writer,
+            path,
+            sample_rate,
+            frames: 0,
+            markers: std::mem::take(&mut self.early_markers)
+                .into_iter()
+                .map(|label| (0.0, label))
+                .collect(),
+        });
+        Ok(())
+    }
+
+    fn close_segment(&mut self) -> Result<()> {
+        let Some(segment) = self.current.take() else {
+            return Ok(());
+        };
+        segment
+            .writer
+            .finalize()
+            .with_context(|| format!("failed to finalize {}", segment.path.display()))?;
+        let labels: String = segment
+            .markers
+            .iter()
+            .map(|(time, label)| format!("{time:.6}\t{time:.6}\t{label}\n"))
+            .collect();
+        let labels_path = segment.path.with_extension("txt");
+        std::fs::write(&labels_path, labels)
+            .with_context(|| format!("failed to write {}", labels_path.display()))?;
+        self.written.push(segment.path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "test_recording.rs"]
+mod tests;
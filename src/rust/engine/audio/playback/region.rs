@@ -0,0 +1,195 @@
+//! Changed-region detection between two renders of the same project
+//!
+//! Live rebuilds fingerprint every collected event with its time span. Diffing the
+//! fingerprints of two builds gives the time window whose audio can differ, so the
+//! playing loop only needs that window patched instead of a full buffer swap.
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use crate::engine::audio::events::{AudioEvent, AudioEventList};
+use crate::engine::audio::mixer::cache::{hash_event, hash_value};
+
+/// Seconds assumed for a sample whose length is unknown (matches `total_duration`)
+const UNKNOWN_SAMPLE_SECONDS: f32 = 2.0;
+
+/// One collected event: what it is and the time it sounds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventSpan {
+    pub fingerprint: u64,
+    pub start: f32,
+    pub end: f32,
+}
+
+/// Event spans of one build plus a hash of the state that shapes the whole mix
+/// (group insert chains and which group each event plays through)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderFingerprint {
+    pub spans: Vec<EventSpan>,
+    pub context: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegionDiff {
+    Unchanged,
+    /// Only audio between `start` and `end` seconds can differ
+    Region {
+        start: f32,
+        end: f32,
+    },
+    /// Something that affects the whole loop changed
+    Full,
+}
+
+impl RenderFingerprint {
+    /// Fingerprint `events`; `sample_seconds` gives the length of a sample URI when known
+    pub fn from_events(
+        events: &AudioEventList,
+        extra_context: u64,
+        sample_seconds: impl Fn(&str) -> Option<f32>,
+    ) -> Self {
+        let mut lengths: HashMap<&str, f32> = HashMap::new();
+        let spans = events
+            .events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| {
+                let mut hasher = DefaultHasher::new();
+                hash_event(event, &mut hasher);
+                events.group_path(index).hash(&mut hasher);
+                let (start, end) = match event {
+                    AudioEvent::Note {
+                        start_time,
+                        duration,
+                        synth_def,
+                        release,
+                        ..
+                    }
+                    | AudioEvent::Chord {
+                        start_time,
+                        duration,
+                        synth_def,
+                        release,
+                        ..
+                    } => {
+                        let release = release.map(|ms| ms / 1000.0).unwrap_or(synth_def.release);
+                        (*start_time, start_time + duration + release.max(0.0))
+                    }
+                    AudioEvent::Sample {
+                        uri,
+                        start_time,
+                        region,
+                        ..
+                    } => {
+                        let length = *lengths.entry(uri.as_str()).or_insert_with(|| {
+                            sample_seconds(uri).unwrap_or(UNKNOWN_SAMPLE_SECONDS)
+                        });
+                        let length = region
+                            .and_then(|region| region.played_length(Some(length)))
+                            .unwrap_or(length);
+                        (*start_time, start_time + length)
+                    }
+                };
+                EventSpan {
+                    fingerprint: hasher.finish(),
+                    start,
+                    end,
+                }
+            })
+            .collect();
+
+        let mut hasher = DefaultHasher::new();
+        extra_context.hash(&mut hasher);
+        let mut chains: Vec<_> = events.group_effects.iter().collect();
+        chains.sort_by(|a, b| a.0.cmp(b.0));
+        for (group, chain) in chains {
+            group.hash(&mut hasher);
+            hash_value(chain, &mut hasher);
+        }
+
+        Self {
+            spans,
+            context: hasher.finish(),
+        }
+    }
+
+    /// Window whose audio differs between `self` (the playing build) and `next`.
+    /// `tail` seconds are added after the last changed event for effect tails.
+    pub fn diff(&self, next: &RenderFingerprint, tail: f32) -> RegionDiff {
+        if self.context != next.context {
+            return RegionDiff::Full;
+        }
+
+        // Events present in one build but not the other (as multisets)
+        let mut unmatched: HashMap<(u64, u32, u32), i32> = HashMap::new();
+        for span in &self.spans {
+            *unmatched.entry(span_key(span)).or_default() += 1;
+        }
+        for span in &next.spans {
+            *unmatched.entry(span_key(span)).or_default() -= 1;
+        }
+
+        let mut window: Option<(f32, f32)> = None;
+        for span in self.spans.iter().chain(&next.spans) {
+            if unmatched.get(&span_key(span)).copied().unwrap_or(0) == 0 {
+                continue;
+            }
+            window = Some(match window {
+                Some((start, end)) => (start.min(span.start), end.max(span.end)),
+                None => (span.start, span.end),
+            });
+        }
+
+        match window {
+            Some((start, end)) => RegionDiff::Region {
+                start: start.max(0.0),
+                end: end + tail.max(0.0),
+            },
+            None => RegionDiff::Unchanged,
+        }
+    }
+}
+
+fn span_key(span: &EventSpan) -> (u64, u32, u32) {
+    (span.fingerprint, span.start.to_bits(), span.end.to_bits())
+}
+
+/// Replace `frames` of interleaved `target` with `source`, crossfading from the old audio
+/// over `fade` frames before the window and back to it over `fade` frames after.
+/// Both buffers share the same channel layout.
+pub fn crossfade_patch(
+    target: &mut [f32],
+    source: &[f32],
+    channels: usize,
+    frames: Range<usize>,
+    fade: usize,
+) {
+    let channels = channels.max(1);
+    let total = target.len().min(source.len()) / channels;
+    if frames.start >= frames.end || frames.start >= total {
+        return;
+    }
+    let from = frames.start.saturating_sub(fade);
+    let to = (frames.end + fade).min(total);
+
+    for frame in from..to {
+        // 0 at the outer edges of the fades, 1 inside the window
+        let weight = if frame < frames.start {
+            (frame + 1 - from) as f32 / (frames.start - from + 1) as f32
+        } else if frame >= frames.end {
+            (to - frame) as f32 / (to - frames.end + 1) as f32
+        } else {
+            1.0
+        };
+        for channel in 0..channels {
+            let i = frame * channels + channel;
+            target[i] = target[i] * (1.0 - weight) + source[i] * weight;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_region.rs"]
+mod tests;
@@ -0,0 +1,434 @@
+//! Playback-rate preview (`play --preview-rate 1.5x`)
+//!
+//! The rendered buffer is resampled while it plays, so long pieces can be reviewed
+//! faster or slower without rebuilding. Plain var-speed shifts the pitch with the
+//! rate; `preserve_pitch` instead stretches time with windowed overlap-add, which
+//! smears transients a little but is fine for reviewing arrangement and timing.
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use anyhow::{Result, bail};
+
+use crate::engine::audio::mixer::Interpolator;
+use crate::engine::audio::settings::ResampleQuality;
+
+/// Slowest and fastest accepted preview rates
+pub const MIN_PREVIEW_RATE: f32 = 0.25;
+pub const MAX_PREVIEW_RATE: f32 = 4.0;
+
+/// Grain length of the pitch-preserving stretch
+const GRAIN_SECONDS: f32 = 0.04;
+
+/// Rate changes remembered to map wall-clock time back to render time
+const MAX_RATE_CHANGES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviewRate {
+    pub rate: f32,
+    pub preserve_pitch: bool,
+    /// Interpolation of var-speed playback (unused when the pitch is preserved)
+    pub quality: ResampleQuality,
+}
+
+impl PreviewRate {
+    pub fn new(rate: f32, preserve_pitch: bool) -> Self {
+        Self {
+            rate,
+            preserve_pitch,
+            quality: ResampleQuality::Linear2,
+        }
+    }
+
+    /// Read var-speed playback at `quality` (`resample_quality`) instead of linearly
+    pub fn with_quality(mut self, quality: ResampleQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Parse `1.5x`, `1.5` or `150%`
+    pub fn parse_rate(raw: &str) -> Result<f32> {
+        let trimmed = raw.trim();
+        let rate = if let Some(percent) = trimmed.strip_suffix('%') {
+            percent.trim().parse::<f32>().map(|p| p / 100.0)
+        } else {
+            trimmed
+                .strip_suffix(['x', 'X'])
+                .unwrap_or(trimmed)
+                .trim()
+                .parse::<f32>()
+        };
+        match rate {
+            Ok(rate) if (MIN_PREVIEW_RATE..=MAX_PREVIEW_RATE).contains(&rate) => Ok(rate),
+            Ok(rate) => bail!(
+                "preview rate {} is outside {}x-{}x",
+                rate,
+                MIN_PREVIEW_RATE,
+                MAX_PREVIEW_RATE
+            ),
+            Err(_) => bail!("invalid preview rate '{}' (expected e.g. 1.5x)", raw),
+        }
+    }
+
+    /// True when playback would sound exactly like the render
+    pub fn is_identity(&self) -> bool {
+        (self.rate - 1.0).abs() < f32::EPSILON
+    }
+
+    /// Render position reached after `elapsed` seconds of preview playback
+    pub fn render_seconds(&self, elapsed: f32) -> f32 {
+        elapsed * self.rate
+    }
+}
+
+/// Playback rate shared with the sources playing it, so live tempo changes apply
+/// mid-pass. Every change is timestamped to turn wall-clock time back into render time.
+#[derive(Debug, Clone)]
+pub struct LiveRate {
+    /// Current rate as `f32` bits, polled by the audio thread
+    current: Arc<AtomicU32>,
+    changes: Arc<Mutex<Vec<(Instant, f32)>>>,
+}
+
+impl LiveRate {
+    pub fn new(rate: f32) -> Self {
+        Self {
+            current: Arc::new(AtomicU32::new(rate.to_bits())),
+            changes: Arc::new(Mutex::new(vec![(Instant::now(), rate)])),
+        }
+    }
+
+    pub fn rate(&self) -> f32 {
+        f32::from_bits(self.current.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, rate: f32) {
+        self.set_at(Instant::now(), rate);
+    }
+
+    fn set_at(&self, at: Instant, rate: f32) {
+        let rate = rate.clamp(MIN_PREVIEW_RATE, MAX_PREVIEW_RATE);
+        if let Ok(mut changes) = self.changes.lock() {
+            changes.push((at, rate));
+            if changes.len() > MAX_RATE_CHANGES {
+                changes.remove(0);
+            }
+        }
+        self.current.store(rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Render seconds played since `start`, following every rate change in between
+    pub fn render_seconds(&self, start: Instant) -> f32 {
+        self.render_seconds_at(start, Instant::now())
+    }
+
+    fn render_seconds_at(&self, start: Instant, now: Instant) -> f32 {
+        let Ok(changes) = self.changes.lock() else {
+            return now.saturating_duration_since(start).as_secs_f32() * self.rate();
+        };
+        let mut rate = changes
+            .iter()
+            .take_while(|(at, _)| *at <= start)
+            .last()
+            .or(changes.first())
+            .map_or(1.0, |(_, rate)| *rate);
+        let mut cursor = start;
+        let mut seconds = 0.0;
+        for (at, next_rate) in changes.iter().filter(|(at, _)| *at > start && *at <= now) {
+            seconds += at.duration_since(cursor).as_secs_f32() * rate;
+            cursor = *at;
+            rate = *next_rate;
+        }
+        seconds + now.saturating_duration_since(cursor).as_secs_f32() * rate
+    }
+}
+
+/// Interleaved samples of `source` played back at `rate`
+pub struct VarSpeed<I: Iterator<Item = f32>> {
+    source: I,
+    channels: usize,
+    rate: f64,
+    mode: Mode,
+    /// Produced samples not handed out yet
+    output: VecDeque<f32>,
+    finished: bool,
+}
+
+enum Mode {
+    /// Linear interpolation between the two frames around the read position
+    Resample {
+        previous: Vec<f32>,
+        next: Vec<f32>,
+        fraction: f64,
+        started: bool,
+        /// The source ran out; `previous` is its last frame
+        drained: bool,
+    },
+    /// Windowed sinc over the frames around the read position
+    Sinc(SincRead),
+    /// Hann-windowed grains read every `grain/2 * rate` frames, written every `grain/2`
+    Stretch(Stretch),
+}
+
+struct SincRead {
+    interpolator: Interpolator,
+    /// Source samples from frame `input_start` on
+    input: VecDeque<f32>,
+    input_start: usize,
+    exhausted: bool,
+    /// Source frame the next output frame is read at
+    position: f64,
+}
+
+struct Stretch {
+    grain: usize,
+    window: Vec<f32>,
+    /// Source samples from frame `input_start` on
+    input: VecDeque<f32>,
+    input_start: usize,
+    exhausted: bool,
+    /// Source frame the next grain starts at
+    read_position: f64,
+    /// Overlap-add accumulator, one grain long
+    accumulator: Vec<f32>,
+}
+
+impl<I: Iterator<Item = f32>> VarSpeed<I> {
+    pub fn new(source: I, channels: u16, sample_rate: u32, preview: PreviewRate) -> Self {
+        let channels = channels.max(1) as usize;
+        let mode = if preview.preserve_pitch {
+            // Even grain so two half-overlapping Hann windows sum to one
+            let grain = ((sample_rate as f32 * GRAIN_SECONDS) as usize).max(8) & !1;
+            Mode::Stretch(Stretch {
+                grain,
+                window: (0..grain)
+                    .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / grain as f32).cos())
+                    .collect(),
+                input: VecDeque::new(),
+                input_start: 0,
+                exhausted: false,
+                read_position: 0.0,
+                accumulator: vec![0.0; grain * channels],
+            })
+        } else if preview.quality != ResampleQuality::Linear2 {
+            Mode::Sinc(SincRead {
+                interpolator: Interpolator::new(preview.quality, preview.rate as f64),
+                input: VecDeque::new(),
+                input_start: 0,
+                exhausted: false,
+                position: 0.0,
+            })
+        } else {
+            Mode::Resample {
+                previous: vec![0.0; channels],
+                next: vec![0.0; channels],
+                fraction: 0.0,
+                started: false,
+                drained: false,
+            }
+        };
+        Self {
+            source,
+            channels,
+            rate: preview.rate.max(MIN_PREVIEW_RATE) as f64,
+            mode,
+            output: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Change the rate from the next produced frame on
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate.max(MIN_PREVIEW_RATE) as f64;
+    }
+
+    /// Produce the next output frame(s); false once the source is used up
+    fn fill(&mut self) -> bool {
+        if self.finished {
+            return false;
+        }
+        let produced = match &mut self.mode {
+            Mode::Resample {
+                previous,
+                next,
+                fraction,
+                started,
+                drained,
+            } => {
+                if !*started {
+                    *started = true;
+                    if !read_frame(&mut self.source, previous) {
+                        return false;
+                    }
+                    if !read_frame(&mut self.source, next) {
+                        next.copy_from_slice(previous);
+                        *drained = true;
+                    }
+                }
+                let weight = *fraction as f32;
+                self.output.extend(
+                    previous
+                        .iter()
+                        .zip(next.iter())
+                        .map(|(a, b)| a + (b - a) * weight),
+                );
+                *fraction += self.rate;
+                let mut more = true;
+                while *fraction >= 1.0 {
+                    *fraction -= 1.0;
+                    if *drained {
+                        more = false;
+                        break;
+                    }
+                    std::mem::swap(previous, next);
+                    if !read_frame(&mut self.source, next) {
+                        // Hold the last frame so it still gets played once
+                        next.copy_from_slice(previous);
+                        *drained = true;
+                    }
+                }
+                more
+            }
+            Mode::Sinc(read) => {
+                read.step(&mut self.source, self.channels, self.rate, &mut self.output)
+            }
+            Mode::Stretch(stretch) => {
+                stretch.step(&mut self.source, self.channels, self.rate, &mut self.output)
+            }
+        };
+        if !produced {
+            self.finished = true;
+        }
+        produced || !self.output.is_empty()
+    }
+}
+
+impl SincRead {
+    /// Interpolate one frame and drop the input the kernel no longer reaches
+    fn step(
+        &mut self,
+        source: &mut impl Iterator<Item = f32>,
+        channels: usize,
+        rate: f64,
+        output: &mut VecDeque<f32>,
+    ) -> bool {
+        let reach = self.interpolator.reach();
+        let center = self.position as usize;
+        while !self.exhausted && self.input_start + self.input.len() / channels <= center + reach {
+            let mut frame = vec![0.0; channels];
+            if read_frame(source, &mut frame) {
+                self.input.extend(frame);
+            } else {
+                self.exhausted = true;
+            }
+        }
+        let available = self.input_start + self.input.len() / channels;
+        if center >= available {
+            return false;
+        }
+
+        self.interpolator.set_step(rate);
+        self.interpolator.prepare(
+            self.position - self.input_start as f64,
+            available - self.input_start,
+        );
+        for channel in 0..channels {
+            output.push_back(
+                self.interpolator
+                    .apply(|frame| self.input[frame * channels + channel]),
+            );
+        }
+
+        self.position += rate;
+        let keep_from = (self.position as usize + 1)
+            .saturating_sub(reach)
+            .min(available);
+        if keep_from > self.input_start {
+            self.input
+                .drain(..(keep_from - self.input_start) * channels);
+            self.input_start = keep_from;
+        }
+        true
+    }
+}
+
+impl Stretch {
+    /// Overlap-add one grain and release the half of the accumulator it completes
+    fn step(
+        &mut self,
+        source: &mut impl Iterator<Item = f32>,
+        channels: usize,
+        rate: f64,
+        output: &mut VecDeque<f32>,
+    ) -> bool {
+        let start = self.read_position as usize;
+        let end = start + self.grain;
+        while !self.exhausted && self.input_start + self.input.len() / channels < end {
+            let mut frame = vec![0.0; channels];
+            if read_frame(source, &mut frame) {
+                self.input.extend(frame);
+            } else {
+                self.exhausted = true;
+            }
+        }
+        let available = self.input_start + self.input.len() / channels;
+        if self.exhausted && start >= available {
+            return false;
+        }
+
+        for (offset, weight) in self.window.iter().enumerate() {
+            let frame = start + offset;
+            if frame >= available {
+                break;
+            }
+            let base = (frame - self.input_start) * channels;
+            for channel in 0..channels {
+                self.accumulator[offset * channels + channel] +=
+                    self.input[base + channel] * weight;
+            }
+        }
+
+        let hop = self.grain / 2 * channels;
+        output.extend(self.accumulator.drain(..hop));
+        self.accumulator.resize(self.grain * channels, 0.0);
+
+        self.read_position += (self.grain / 2) as f64 * rate;
+        let keep_from = (self.read_position as usize).min(available);
+        let drop = (keep_from - self.input_start) * channels;
+        self.input.drain(..drop);
+        self.input_start = keep_from;
+        true
+    }
+}
+
+/// Read one interleaved frame; false once the source runs out
+fn read_frame(source: &mut impl Iterator<Item = f32>, frame: &mut [f32]) -> bool {
+    for (index, slot) in frame.iter_mut().enumerate() {
+        match source.next() {
+            Some(sample) => *slot = sample,
+            None if index == 0 => return false,
+            None => *slot = 0.0,
+        }
+    }
+    true
+}
+
+impl<I: Iterator<Item = f32>> Iterator for VarSpeed<I> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        while self.output.is_empty() {
+            if !self.fill() {
+                return None;
+            }
+        }
+        self.output.pop_front()
+    }
+}
+
+#[cfg(test)]
+#[path = "test_speed.rs"]
+mod tests;
@@ -0,0 +1,108 @@
+//! Live tempo control: tap tempo and BPM nudges while a loop plays
+//!
+//! The playing loop is not re-rendered; the new tempo becomes a playback rate over the
+//! render (`bpm / project bpm`), applied by a pitch-preserving stretch. The tempo is
+//! kept across rebuilds unless the project's own `bpm` changes.
+
+use std::time::{Duration, Instant};
+
+use crate::engine::audio::playback::speed::{MAX_PREVIEW_RATE, MIN_PREVIEW_RATE};
+
+/// Taps further apart than this start a new measurement
+const TAP_TIMEOUT: Duration = Duration::from_secs(2);
+/// Intervals averaged into a tapped tempo
+const MAX_TAP_INTERVALS: usize = 8;
+
+/// Tempo estimated from the spacing of the latest taps
+#[derive(Debug, Clone, Default)]
+pub struct TapTempo {
+    taps: Vec<Instant>,
+}
+
+impl TapTempo {
+    /// Record a tap; returns the tempo once at least two taps are close enough together
+    pub fn tap(&mut self, at: Instant) -> Option<f32> {
+        if self
+            .taps
+            .last()
+            .is_some_and(|last| at.saturating_duration_since(*last) > TAP_TIMEOUT)
+        {
+            self.taps.clear();
+        }
+        self.taps.push(at);
+        if self.taps.len() > MAX_TAP_INTERVALS + 1 {
+            self.taps.remove(0);
+        }
+
+        let (first, last) = (self.taps.first()?, self.taps.last()?);
+        let intervals = self.taps.len() - 1;
+        if intervals == 0 {
+            return None;
+        }
+        let average = last.duration_since(*first).as_secs_f32() / intervals as f32;
+        (average > 0.0).then(|| 60.0 / average)
+    }
+}
+
+/// Tempo of a live session relative to the tempo the loop was rendered at
+#[derive(Debug, Clone)]
+pub struct LiveTempo {
+    project_bpm: f32,
+    bpm: f32,
+    taps: TapTempo,
+}
+
+impl LiveTempo {
+    pub fn new(project_bpm: f32) -> Self {
+        Self {
+            project_bpm,
+            bpm: project_bpm,
+            taps: TapTempo::default(),
+        }
+    }
+
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Playback rate over the render that plays it at `bpm`
+    pub fn rate(&self) -> f32 {
+        self.bpm / self.project_bpm.max(f32::EPSILON)
+    }
+
+    /// Move the tempo by `delta` BPM, to the nearest tenth
+    pub fn nudge(&mut self, delta: f32) -> f32 {
+        self.set(((self.bpm + delta) * 10.0).round() / 10.0)
+    }
+
+    /// Tap the beat; the tempo follows once two taps are in
+    pub fn tap(&mut self, at: Instant) -> Option<f32> {
+        let tapped = self.taps.tap(at)?;
+        Some(self.set((tapped * 10.0).round() / 10.0))
+    }
+
+    /// Follow a rebuild rendered at `project_bpm`. A changed project tempo wins over
+    /// the live one; returns true when the live tempo was reset for that reason.
+    pub fn rebase(&mut self, project_bpm: f32) -> bool {
+        if (project_bpm - self.project_bpm).abs() < f32::EPSILON {
+            return false;
+        }
+        let nudged = (self.bpm - self.project_bpm).abs() >= f32::EPSILON;
+        self.project_bpm = project_bpm;
+        self.bpm = project_bpm;
+        nudged
+    }
+
+    /// Tempos are kept within the stretch range around the rendered tempo
+    fn set(&mut self, bpm: f32) -> f32 {
+        self.bpm = bpm.clamp(
+            self.project_bpm * MIN_PREVIEW_RATE,
+            self.project_bpm * MAX_PREVIEW_RATE,
+        );
+        self.bpm
+    }
+}
+
+#[cfg(test)]
+#[path = "test_tempo.rs"]
+mod tests;
@@ -0,0 +1,72 @@
+use super::*;
+use crate::engine::audio::events::SynthDefinition;
+
+fn note(midi: u8, start_time: f32, duration: f32, synth_id: &str) -> AudioEvent {
+    AudioEvent::Note {
+        midi,
+        start_time,
+        duration,
+        velocity: 0.5,
+        synth_id: synth_id.to_string(),
+        synth_def: SynthDefinition::default(),
+        pan: 0.0,
+        detune: 0.0,
+        gain: 1.0,
+        attack: None,
+        release: None,
+        delay_time: None,
+        delay_feedback: None,
+        delay_mix: None,
+        reverb_amount: None,
+        drive_amount: None,
+        drive_color: None,
+        effects: None,
+        use_per_note_automation: false,
+    }
+}
+
+#[test]
+fn test_schedule_sends_bound_notes_only() {
+    let events = vec![
+        note(60, 0.0, 0.5, "lead"),
+        note(62, 0.5, 0.5, "lead"),
+        note(36, 0.0, 1.0, "bass"),
+    ];
+    let cues = schedule_notes(&events, "lead", 9);
+    let summary: Vec<(f32, [u8; 3])> = cues.iter().map(|c| (c.time, c.message)).collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0.0, [0x99, 60, 64]),
+            // The first note ends before the second starts on the same instant
+            (0.5, [0x89, 60, 0]),
+            (0.5, [0x99, 62, 64]),
+            (1.0, [0x89, 62, 0]),
+        ]
+    );
+}
+
+#[test]
+fn test_align_drops_lead_in_and_latency() {
+    // Mono input at 1 kHz: 10 frames of lead-in, then a ramp
+    let mut samples = vec![0.0; 10];
+    samples.extend((0..100).map(|i| i as f32 / 100.0));
+    let capture = Capture {
+        samples,
+        channels: 1,
+        sample_rate: 1000,
+        lead_in: 10,
+    };
+
+    let aligned = align_capture(
+        &capture,
+        Duration::from_millis(5),
+        1000,
+        ResampleQuality::Linear2,
+        120,
+    );
+    assert_eq!(aligned.len(), 240);
+    assert_eq!(&aligned[..4], &[0.05, 0.05, 0.06, 0.06]);
+    // Padded with silence past the end of the recording
+    assert_eq!(aligned[238], 0.0);
+}
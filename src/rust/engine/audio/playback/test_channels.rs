@@ -0,0 +1,32 @@
+use super::*;
+use rodio::buffer::SamplesBuffer;
+
+#[test]
+fn test_stereo_is_padded_with_silence() {
+    let padded = pad_channels(vec![0.1, 0.2, 0.3, 0.4], 2, 4);
+    assert_eq!(padded, vec![0.1, 0.2, 0.0, 0.0, 0.3, 0.4, 0.0, 0.0]);
+    assert_eq!(pad_channels(vec![0.1, 0.2], 2, 2), vec![0.1, 0.2]);
+}
+
+#[test]
+fn test_extra_channels_are_dropped() {
+    let narrowed = pad_channels(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 3, 2);
+    assert_eq!(narrowed, vec![1.0, 2.0, 4.0, 5.0]);
+}
+
+#[test]
+fn test_source_reports_the_stream_layout() {
+    let routed = SamplesBuffer::new(6, 48_000, vec![0.5_f32; 12]);
+    let padded = PadChannels::new(routed, 6, 8);
+    assert_eq!(padded.channels(), 8);
+    assert_eq!(padded.sample_rate(), 48_000);
+    let samples: Vec<f32> = padded.collect();
+    assert_eq!(samples.len(), 16);
+    assert!(
+        samples[6..8]
+            .iter()
+            .chain(&samples[14..])
+            .all(|s| *s == 0.0)
+    );
+    assert!(samples[..6].iter().all(|s| *s == 0.5));
+}
@@ -0,0 +1,82 @@
+use super::*;
+
+#[test]
+fn test_parse_key_binding_forms() {
+    let arrow = KeyBinding::parse("a -> .kit.kick").unwrap();
+    assert_eq!(arrow.key, 'a');
+    assert_eq!(arrow.trigger, "kit.kick");
+
+    let equals = KeyBinding::parse("S=kit.snare").unwrap();
+    assert_eq!(equals.key, 's');
+    assert_eq!(equals.trigger, "kit.snare");
+
+    assert!(KeyBinding::parse("ab=.kit.kick").is_err());
+    assert!(KeyBinding::parse("a=").is_err());
+    assert!(KeyBinding::parse("kit.kick").is_err());
+}
+
+#[test]
+fn test_snap_to_nearest_grid_line() {
+    assert_eq!(Quantizer::parse_grid("1/16").unwrap(), 0.25);
+    assert_eq!(Quantizer::parse_grid("1/4").unwrap(), 1.0);
+    assert!(Quantizer::parse_grid("16").is_err());
+
+    // 120 BPM: sixteenths every 0.125s, two-bar loop of 4s
+    let quantizer = Quantizer::new(120.0, 0.25, 4.0);
+
+    // Slightly early: waits for the grid line
+    let (time, delay) = quantizer.snap(0.49);
+    assert!((time - 0.5).abs() < 1e-5);
+    assert!((delay - 0.01).abs() < 1e-5);
+
+    // Slightly late: plays right away, recorded on the line before
+    let (time, delay) = quantizer.snap(0.51);
+    assert!((time - 0.5).abs() < 1e-5);
+    assert_eq!(delay, 0.0);
+
+    // Just before the loop end wraps to the downbeat
+    let (time, _) = quantizer.snap(3.99);
+    assert_eq!(time, 0.0);
+}
+
+#[test]
+fn test_take_source_uses_beat_positions() {
+    let quantizer = Quantizer::new(120.0, 0.25, 4.0);
+    let mut take = Take::default();
+    take.record(0.5, "kit.snare");
+    take.record(0.0, "kit.kick");
+    take.record(0.5, "kit.snare");
+    take.record(0.125, "kit.hat");
+
+    let source = take.to_source(&quantizer, "1/16");
+    assert_eq!(
+        source,
+        "# Live take at 120 BPM, quantized to 1/16\n\
+         \nat beat 1:\n    .kit.kick\n\
+         \nat beat 1.25:\n    .kit.hat\n\
+         \nat beat 2:\n    .kit.snare\n"
+    );
+}
+
+#[test]
+fn test_next_bar_rounds_up_to_the_bar_line() {
+    // 120 BPM: a bar every 2s
+    let quantizer = Quantizer::new(120.0, 0.25, 8.0);
+    assert_eq!(quantizer.next_bar(0.0), 0.0);
+    assert_eq!(quantizer.next_bar(0.3), 2.0);
+    assert_eq!(quantizer.next_bar(2.0), 2.0);
+    assert_eq!(quantizer.next_bar(5.9), 6.0);
+}
+
+#[test]
+fn test_next_bar_follows_the_meter_map() {
+    // 120 BPM in 3/4: a bar every 1.5s, then 4/4 from beat 6 (3s)
+    let mut tempo_map = TempoMap::new();
+    tempo_map.push_meter(0.0, 3, 4);
+    tempo_map.push_meter(6.0, 4, 4);
+    let quantizer = Quantizer::new(120.0, 0.25, 8.0).with_tempo_map(tempo_map);
+    assert_eq!(quantizer.next_bar(0.3), 1.5);
+    assert_eq!(quantizer.next_bar(1.5), 1.5);
+    assert_eq!(quantizer.next_bar(2.0), 3.0);
+    assert_eq!(quantizer.next_bar(3.2), 5.0);
+}
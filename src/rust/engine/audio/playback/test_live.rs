@@ -0,0 +1,118 @@
+use std::path::Path;
+
+use super::*;
+
+fn source(name: &str) -> LiveAudioSource {
+    LiveAudioSource::with_path(
+        PathBuf::from(name),
+        AudioFormat::default(),
+        AudioBitDepth::default(),
+        AudioChannels::Mono,
+        10,
+        ResampleQuality::default(),
+        Duration::from_secs(1),
+    )
+}
+
+fn buffer(samples: Vec<f32>) -> LoopBuffer {
+    LoopBuffer {
+        samples: RwLock::new(samples),
+        channels: 1,
+        sample_rate: 10,
+    }
+}
+
+fn transition(name: &str) -> SceneTransition {
+    SceneTransition {
+        source: source(name),
+        buffer: buffer(vec![0.0; 10]),
+        fade: Duration::from_millis(200),
+    }
+}
+
+fn patch(name: &str) -> RegionPatch {
+    RegionPatch {
+        source: source(name),
+        samples: vec![0.0; 10],
+        channels: 1,
+        sample_rate: 10,
+        start: 0.2,
+        end: 0.4,
+    }
+}
+
+#[test]
+fn test_scheduled_swap_waits_for_its_bar_line() {
+    let mut next_loop = NextLoop::default();
+    next_loop.schedule(transition("b.wav"), 0.5);
+
+    assert!(next_loop.due(0.4).is_none());
+    let next = next_loop.due(0.6).unwrap();
+    assert_eq!(next.source.path, PathBuf::from("b.wav"));
+    // Fades in from where the playhead is, not from the top of the new loop
+    assert_eq!(next.source.start, Duration::from_secs_f32(0.6));
+    assert!(next_loop.due(0.7).is_none());
+}
+
+#[test]
+fn test_newer_build_replaces_the_waiting_one() {
+    let mut next_loop = NextLoop::default();
+    next_loop.queue(source("a.wav"));
+    next_loop.schedule(transition("b.wav"), 0.5);
+    assert!(next_loop.pending.is_none());
+
+    next_loop.queue(source("c.wav"));
+    assert!(next_loop.due(1.0).is_none());
+    assert!(matches!(
+        next_loop.at_loop_start(),
+        Some(LoopSwitch::Switch(next)) if next.path == Path::new("c.wav")
+    ));
+    assert!(next_loop.at_loop_start().is_none());
+}
+
+#[test]
+fn test_patch_while_a_swap_is_scheduled_switches_after_the_pass() {
+    let mut next_loop = NextLoop::default();
+    assert!(next_loop.take_patch(patch("a.wav")).is_some());
+
+    next_loop.schedule(transition("b.wav"), 0.5);
+    assert!(next_loop.take_patch(patch("c.wav")).is_none());
+    assert!(next_loop.due(1.0).is_none());
+    assert!(matches!(
+        next_loop.at_loop_start(),
+        Some(LoopSwitch::Switch(next)) if next.path == Path::new("c.wav")
+    ));
+}
+
+#[test]
+fn test_swap_still_waiting_happens_at_the_loop_start() {
+    let mut next_loop = NextLoop::default();
+    next_loop.schedule(transition("b.wav"), 0.9);
+    assert!(matches!(
+        next_loop.at_loop_start(),
+        Some(LoopSwitch::Swap(next)) if next.source.path == Path::new("b.wav")
+    ));
+    assert!(next_loop.due(1.0).is_none());
+}
+
+#[test]
+fn test_transition_fades_in_from_the_swap_position() {
+    let playing = buffer(vec![1.0; 10]);
+    let mut next = transition("b.wav");
+    next.source.start = Duration::from_millis(400);
+
+    let mixed = playing.transition_to(&next, 0.4).unwrap();
+    let samples = mixed.samples.read().unwrap();
+    // Before the swap position the new loop is untouched, then blends out of the old one
+    assert!(samples[..4].iter().all(|&sample| sample == 0.0));
+    assert!(samples[4] > 0.0);
+    assert!(samples[6..].iter().all(|&sample| sample == 0.0));
+
+    let stereo = LoopBuffer {
+        samples: RwLock::new(vec![0.0; 20]),
+        channels: 2,
+        sample_rate: 10,
+    };
+    next.buffer = stereo;
+    assert!(playing.transition_to(&next, 0.4).is_err());
+}
@@ -0,0 +1,122 @@
+use super::*;
+
+#[test]
+fn test_encode_message_pads_address_tags_and_strings() {
+    let bytes = encode_message(
+        "/deva/section",
+        &[OscArg::Str("verse".to_string()), OscArg::Float(1.5)],
+    );
+
+    let mut expected = b"/deva/section\0\0\0,sf\0verse\0\0\0".to_vec();
+    expected.extend_from_slice(&1.5f32.to_be_bytes());
+    assert_eq!(bytes, expected);
+    assert_eq!(bytes.len() % 4, 0);
+}
+
+#[test]
+fn test_decode_message_reads_back_encoded_arguments() {
+    let args = vec![
+        OscArg::Float(0.25),
+        OscArg::Int(-3),
+        OscArg::Str("lead".to_string()),
+    ];
+    let bytes = encode_message("/deva/macro/intensity", &args);
+    assert_eq!(
+        decode_message(&bytes),
+        Some(("/deva/macro/intensity".to_string(), args))
+    );
+    assert_eq!(decode_message(b"#bundle\0"), None);
+    // Truncated arguments
+    assert_eq!(decode_message(&bytes[..bytes.len() - 8]), None);
+}
+
+#[test]
+fn test_emit_sends_playhead_meters_and_sections() -> Result<()> {
+    let receiver = UdpSocket::bind("127.0.0.1:0")?;
+    receiver.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut sender = OscSender::connect(&OscSettings {
+        host: "127.0.0.1".to_string(),
+        port: receiver.local_addr()?.port(),
+        throttle: Duration::from_secs(60),
+    })?;
+
+    let mut timeline = OscTimeline {
+        meters: vec![("master".to_string(), 0.5, vec![0.25, 0.75])],
+        sections: vec![(0.0, "intro".to_string())],
+        ..Default::default()
+    };
+    timeline.emit(&mut sender, 0.5, 120.0, 0);
+    // Throttled: an immediate second emit sends nothing new
+    timeline.emit(&mut sender, 0.7, 120.0, 0);
+
+    let mut received = Vec::new();
+    let mut buf = [0u8; 256];
+    while let Ok(len) = receiver.recv(&mut buf) {
+        received.push(buf[..len].to_vec());
+        if received.len() == 3 {
+            break;
+        }
+    }
+    assert!(received.contains(&encode_message(
+        "/deva/playhead",
+        &[
+            OscArg::Float(0.5),
+            OscArg::Float(1.0),
+            OscArg::Int(1),
+            OscArg::Int(0)
+        ],
+    )));
+    assert!(received.contains(&encode_message(
+        "/deva/meter/master",
+        &[OscArg::Float(0.75)]
+    )));
+    assert!(received.contains(&encode_message(
+        "/deva/section",
+        &[OscArg::Str("intro".to_string()), OscArg::Float(0.0)],
+    )));
+
+    receiver.set_read_timeout(Some(Duration::from_millis(100)))?;
+    assert!(
+        receiver.recv(&mut buf).is_err(),
+        "throttled messages were sent"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_playhead_follows_the_tempo_sidecar() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let audio = dir.path().join("live.wav");
+    // One bar of 4/4 at 120 bpm, then 3/4 at 60 bpm
+    std::fs::write(
+        dir.path().join("live.tempo"),
+        "tempo\t0\t120\ntempo\t4\t60\nmeter\t4\t3\t4\n",
+    )?;
+    let mut timeline = OscTimeline::load(&audio);
+
+    let receiver = UdpSocket::bind("127.0.0.1:0")?;
+    receiver.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut sender = OscSender::connect(&OscSettings {
+        host: "127.0.0.1".to_string(),
+        port: receiver.local_addr()?.port(),
+        throttle: Duration::ZERO,
+    })?;
+    // 2s of 4/4, then 4 beats at 60 bpm: one beat into the second 3/4 bar
+    timeline.emit(&mut sender, 6.0, 100.0, 1);
+
+    let mut buf = [0u8; 256];
+    let len = receiver.recv(&mut buf)?;
+    assert_eq!(
+        buf[..len].to_vec(),
+        encode_message(
+            "/deva/playhead",
+            &[
+                OscArg::Float(6.0),
+                OscArg::Float(8.0),
+                OscArg::Int(3),
+                OscArg::Int(1)
+            ],
+        )
+    );
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+fn read(path: &Path) -> (Vec<f32>, u32) {
+    let mut reader = hound::WavReader::open(path).unwrap();
+    let rate = reader.spec().sample_rate;
+    (reader.samples::<f32>().map(|s| s.unwrap()).collect(), rate)
+}
+
+#[test]
+fn test_segments_roll_over_and_carry_their_markers() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut writer = SessionWriter::new(dir.path(), "jam", Duration::from_secs(1));
+    writer.mark("start".to_string());
+    // 1.5 s of mono at 100 Hz: one full segment and half of the next
+    writer.write_block(&[0.25; 150], 1, 100).unwrap();
+    writer.mark("rebuild".to_string());
+    let files = writer.finish().unwrap();
+
+    assert_eq!(
+        files,
+        vec![
+            dir.path().join("jam-001.wav"),
+            dir.path().join("jam-002.wav")
+        ]
+    );
+    let (first, rate) = read(&files[0]);
+    assert_eq!((first.len(), rate), (200, 100));
+    assert!(first.iter().all(|&s| s == 0.25));
+    assert_eq!(read(&files[1]).0.len(), 100);
+
+    let labels = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+    assert_eq!(labels("jam-001.txt"), "0.000000\t0.000000\tstart\n");
+    assert_eq!(labels("jam-002.txt"), "0.500000\t0.500000\trebuild\n");
+}
+
+#[test]
+fn test_overlay_lands_after_the_written_audio() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut writer = SessionWriter::new(dir.path(), "keys", Duration::from_secs(60));
+    writer.write_block(&[0.0; 20], 2, 10).unwrap();
+    // Two tenths of a second after frame 10
+    writer.overlay(&[0.5, 0.25], 10, 0.2);
+    writer.write_block(&[0.0; 10], 2, 10).unwrap();
+    let files = writer.finish().unwrap();
+
+    let (samples, _) = read(&files[0]);
+    let left: Vec<f32> = samples.iter().step_by(2).copied().collect();
+    assert_eq!(&left[10..15], &[0.0, 0.0, 0.5, 0.25, 0.0]);
+}
@@ -0,0 +1,85 @@
+use super::*;
+use crate::language::syntax::ast::Value;
+
+fn hits(times: &[f32]) -> AudioEventList {
+    let mut events = AudioEventList::new();
+    for time in times {
+        events.add_sample_event("kick.wav", *time, 1.0);
+    }
+    events
+}
+
+fn fingerprint(events: &AudioEventList) -> RenderFingerprint {
+    RenderFingerprint::from_events(events, 0, |_| Some(0.5))
+}
+
+#[test]
+fn test_diff_finds_changed_window() {
+    let before = fingerprint(&hits(&[0.0, 1.0, 2.0, 3.0]));
+    assert_eq!(
+        before.diff(&fingerprint(&hits(&[0.0, 1.0, 2.0, 3.0])), 1.0),
+        RegionDiff::Unchanged
+    );
+
+    // Moving one hit covers both where it was and where it is now, plus the tail
+    let moved = fingerprint(&hits(&[0.0, 1.0, 2.5, 3.0]));
+    assert_eq!(
+        before.diff(&moved, 1.0),
+        RegionDiff::Region {
+            start: 2.0,
+            end: 4.0
+        }
+    );
+
+    // Order of collection does not matter
+    let reordered = fingerprint(&hits(&[3.0, 2.0, 1.0, 0.0]));
+    assert_eq!(before.diff(&reordered, 1.0), RegionDiff::Unchanged);
+}
+
+#[test]
+fn test_diff_falls_back_to_full_when_chains_change() {
+    let plain = hits(&[0.0, 1.0]);
+    let mut chained = hits(&[0.0, 1.0]);
+    chained
+        .group_effects
+        .insert("drums".to_string(), Value::Array(Vec::new()));
+    assert_eq!(
+        fingerprint(&plain).diff(&fingerprint(&chained), 1.0),
+        RegionDiff::Full
+    );
+    assert_eq!(
+        RenderFingerprint::from_events(&plain, 1, |_| None)
+            .diff(&RenderFingerprint::from_events(&plain, 2, |_| None), 1.0),
+        RegionDiff::Full
+    );
+}
+
+#[test]
+fn test_crossfade_patch_blends_window_edges() {
+    // Stereo, 10 frames of silence patched with ones on frames 4..6, 2-frame fades
+    let mut target = vec![0.0f32; 20];
+    let source = vec![1.0f32; 20];
+    crossfade_patch(&mut target, &source, 2, 4..6, 2);
+
+    let left: Vec<f32> = target.iter().step_by(2).copied().collect();
+    let expected = [
+        0.0,
+        0.0,
+        1.0 / 3.0,
+        2.0 / 3.0,
+        1.0,
+        1.0,
+        2.0 / 3.0,
+        1.0 / 3.0,
+        0.0,
+        0.0,
+    ];
+    for (got, want) in left.iter().zip(expected) {
+        assert!((got - want).abs() < 1e-6, "{:?}", left);
+    }
+    assert_eq!(target[9], target[8]);
+
+    // Windows past the end of the buffer are clamped
+    crossfade_patch(&mut target, &source, 2, 9..40, 0);
+    assert_eq!(target[19], 1.0);
+}
@@ -0,0 +1,143 @@
+use super::*;
+
+fn sine(frequency: f32, sample_rate: u32, seconds: f32) -> Vec<f32> {
+    let count = (sample_rate as f32 * seconds) as usize;
+    (0..count)
+        .map(|i| (2.0 * PI * frequency * i as f32 / sample_rate as f32).sin())
+        .collect()
+}
+
+/// Sign changes per second of mono `samples`, ignoring the first and last 10%
+fn crossings_per_second(samples: &[f32], sample_rate: u32) -> f32 {
+    let edge = samples.len() / 10;
+    let middle = &samples[edge..samples.len() - edge];
+    let crossings = middle
+        .windows(2)
+        .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+        .count();
+    crossings as f32 * sample_rate as f32 / middle.len() as f32
+}
+
+#[test]
+fn test_parse_rate_forms() {
+    assert_eq!(PreviewRate::parse_rate("1.5x").unwrap(), 1.5);
+    assert_eq!(PreviewRate::parse_rate("150%").unwrap(), 1.5);
+    assert_eq!(PreviewRate::parse_rate(" 0.5 ").unwrap(), 0.5);
+    assert!(PreviewRate::parse_rate("10x").is_err());
+    assert!(PreviewRate::parse_rate("fast").is_err());
+    assert!(PreviewRate::new(1.0, true).is_identity());
+    assert_eq!(PreviewRate::new(2.0, false).render_seconds(3.0), 6.0);
+}
+
+#[test]
+fn test_resample_steps_through_frames_at_rate() {
+    // Stereo ramp: left counts up, right counts down
+    let ramp: Vec<f32> = (0..8).flat_map(|k| [k as f32, -(k as f32)]).collect();
+
+    let fast: Vec<f32> = VarSpeed::new(
+        ramp.clone().into_iter(),
+        2,
+        44100,
+        PreviewRate::new(2.0, false),
+    )
+    .collect();
+    assert_eq!(fast, vec![0.0, 0.0, 2.0, -2.0, 4.0, -4.0, 6.0, -6.0]);
+
+    let slow: Vec<f32> =
+        VarSpeed::new(ramp.into_iter(), 2, 44100, PreviewRate::new(0.5, false)).collect();
+    let left: Vec<f32> = slow.iter().step_by(2).copied().collect();
+    assert_eq!(&left[..5], &[0.0, 0.5, 1.0, 1.5, 2.0]);
+    // Eight frames at half speed last sixteen, the final one held
+    assert_eq!(left.len(), 16);
+}
+
+#[test]
+fn test_pitch_is_kept_only_when_preserving() {
+    let sample_rate = 44100;
+    let tone = sine(441.0, sample_rate, 1.0);
+    let original = crossings_per_second(&tone, sample_rate);
+
+    let shifted: Vec<f32> = VarSpeed::new(
+        tone.clone().into_iter(),
+        1,
+        sample_rate,
+        PreviewRate::new(2.0, false),
+    )
+    .collect();
+    let stretched: Vec<f32> = VarSpeed::new(
+        tone.into_iter(),
+        1,
+        sample_rate,
+        PreviewRate::new(2.0, true),
+    )
+    .collect();
+
+    // Both play the second in about half a second
+    for output in [&shifted, &stretched] {
+        let seconds = output.len() as f32 / sample_rate as f32;
+        assert!((seconds - 0.5).abs() < 0.05, "length {seconds}s");
+    }
+    // Var-speed doubles the pitch, the stretch keeps it
+    let shifted_rate = crossings_per_second(&shifted, sample_rate);
+    let stretched_rate = crossings_per_second(&stretched, sample_rate);
+    assert!(
+        (shifted_rate / original - 2.0).abs() < 0.05,
+        "{shifted_rate}"
+    );
+    assert!(
+        (stretched_rate / original - 1.0).abs() < 0.1,
+        "{stretched_rate}"
+    );
+}
+
+#[test]
+fn test_live_rate_integrates_changes_into_render_time() {
+    let live = LiveRate::new(1.0);
+    let start = Instant::now();
+    let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+    live.set_at(at(1000), 2.0);
+    live.set_at(at(2000), 0.5);
+
+    // 1 s at 1x, 1 s at 2x, 2 s at 0.5x
+    assert!((live.render_seconds_at(start, at(4000)) - 4.0).abs() < 1e-4);
+    // Measured from inside the 2x stretch
+    assert!((live.render_seconds_at(at(1500), at(2000)) - 1.0).abs() < 1e-4);
+    assert_eq!(live.rate(), 0.5);
+
+    let mut played = VarSpeed::new(
+        vec![0.0f32; 64].into_iter(),
+        1,
+        44100,
+        PreviewRate::new(1.0, false),
+    );
+    played.set_rate(2.0);
+    assert_eq!(played.count(), 32);
+}
+
+#[test]
+fn test_sinc_var_speed_follows_the_slowed_tone() {
+    let sample_rate = 44100;
+    let tone = sine(8000.0, sample_rate, 0.1);
+    // Half speed plays the tone an octave down
+    let expected = sine(4000.0, sample_rate, 0.2);
+    let error = |quality: ResampleQuality| {
+        let played: Vec<f32> = VarSpeed::new(
+            tone.clone().into_iter(),
+            1,
+            sample_rate,
+            PreviewRate::new(0.5, false).with_quality(quality),
+        )
+        .collect();
+        assert!((played.len() as i64 - expected.len() as i64).abs() <= 2);
+        let middle = 500..expected.len() - 500;
+        let squared: f32 = middle
+            .clone()
+            .map(|n| (played[n] - expected[n]).powi(2))
+            .sum();
+        (squared / middle.len() as f32).sqrt()
+    };
+
+    let linear = error(ResampleQuality::Linear2);
+    let sinc = error(ResampleQuality::Sinc24);
+    assert!(sinc < linear / 5.0, "sinc error {sinc} vs linear {linear}");
+}
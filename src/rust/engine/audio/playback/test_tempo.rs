@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn test_tap_tempo_averages_recent_taps() {
+    let start = Instant::now();
+    let mut taps = TapTempo::default();
+    assert_eq!(taps.tap(start), None);
+    // 0.5 s apart is 120 BPM; a slightly late third tap pulls the average down
+    assert_eq!(taps.tap(start + Duration::from_millis(500)), Some(120.0));
+    let bpm = taps.tap(start + Duration::from_millis(1020)).unwrap();
+    assert!((bpm - 60.0 / 0.51).abs() < 0.01);
+
+    // A long pause starts over
+    assert_eq!(taps.tap(start + Duration::from_secs(5)), None);
+    assert_eq!(
+        taps.tap(start + Duration::from_millis(5750)),
+        Some(60.0 / 0.75)
+    );
+}
+
+#[test]
+fn test_nudges_set_the_playback_rate() {
+    let mut tempo = LiveTempo::new(120.0);
+    assert_eq!(tempo.nudge(1.0), 121.0);
+    assert_eq!(tempo.nudge(0.1), 121.1);
+    assert_eq!(tempo.nudge(-0.1), 121.0);
+    assert!((tempo.rate() - 121.0 / 120.0).abs() < 1e-6);
+
+    // Clamped to the stretch range
+    assert_eq!(tempo.nudge(1000.0), 120.0 * MAX_PREVIEW_RATE);
+}
+
+#[test]
+fn test_rebuild_keeps_nudges_unless_the_project_tempo_changes() {
+    let start = Instant::now();
+    let mut tempo = LiveTempo::new(100.0);
+    tempo.tap(start);
+    assert_eq!(tempo.tap(start + Duration::from_millis(600)), Some(100.0));
+    tempo.nudge(2.0);
+
+    assert!(!tempo.rebase(100.0));
+    assert_eq!(tempo.bpm(), 102.0);
+
+    assert!(tempo.rebase(90.0));
+    assert_eq!(tempo.bpm(), 90.0);
+    assert_eq!(tempo.rate(), 1.0);
+}
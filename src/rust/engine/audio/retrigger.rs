@@ -0,0 +1,132 @@
+//! Crossfades between retriggers of one sample: when a sample starts again on the same
+//! insert while its previous trigger still rings, the old voice fades out and the new
+//! one fades in over a few milliseconds (equal power), so rapid retriggers don't click.
+
+use crate::engine::audio::dsp;
+use crate::engine::audio::events::AudioEvent;
+
+/// Crossfade length when `audio.retrigger_fade_ms` is not configured
+pub const DEFAULT_RETRIGGER_FADE_MS: u32 = 5;
+
+/// Sample URI and insert path: triggers sharing one retrigger each other
+type VoiceKey = (String, Option<String>);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Retrigger {
+    /// Start (seconds) of the next trigger of the same sample on the same insert
+    pub next: Option<f32>,
+    /// Index of the previous trigger of the same sample on the same insert
+    pub previous: Option<usize>,
+}
+
+/// For each event, its neighbouring triggers of the same sample on the same insert
+/// (`path_of` gives an event's insert). Triggers starting together layer instead.
+pub fn retriggers(
+    events: &[AudioEvent],
+    path_of: impl Fn(usize) -> Option<String>,
+) -> Vec<Retrigger> {
+    let mut plan = vec![Retrigger::default(); events.len()];
+
+    // (start, event index) per sample and insert
+    let mut voices: Vec<(VoiceKey, Vec<(f32, usize)>)> = Vec::new();
+    for (index, event) in events.iter().enumerate() {
+        let AudioEvent::Sample {
+            uri, start_time, ..
+        } = event
+        else {
+            continue;
+        };
+        let key = (uri.clone(), path_of(index));
+        match voices.iter_mut().find(|(voice, _)| *voice == key) {
+            Some((_, triggers)) => triggers.push((*start_time, index)),
+            None => voices.push((key, vec![(*start_time, index)])),
+        }
+    }
+
+    for (_, mut triggers) in voices {
+        triggers.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (position, &(start, index)) in triggers.iter().enumerate() {
+            plan[index].next = triggers[position + 1..]
+                .iter()
+                .map(|&(next, _)| next)
+                .find(|&next| next > start);
+            plan[index].previous = triggers[..position]
+                .iter()
+                .rev()
+                .find(|&&(previous, _)| previous < start)
+                .map(|&(_, previous)| previous);
+        }
+    }
+    plan
+}
+
+/// Applies retrigger crossfades to rendered voices. Voices must be shaped in start
+/// order, so a retrigger knows whether the voice before it still rings.
+#[derive(Debug, Clone, Default)]
+pub struct RetriggerFades {
+    plan: Vec<Retrigger>,
+    /// End frame of each voice shaped so far, before any fade
+    ends: Vec<Option<usize>>,
+    fade_frames: usize,
+    sample_rate: u32,
+}
+
+impl RetriggerFades {
+    /// `fade_ms` of 0 turns crossfading off
+    pub fn new(
+        events: &[AudioEvent],
+        path_of: impl Fn(usize) -> Option<String>,
+        fade_ms: u32,
+        sample_rate: u32,
+    ) -> Self {
+        let mut fades = Self {
+            fade_frames: (fade_ms as usize * sample_rate as usize) / 1000,
+            sample_rate,
+            ..Self::default()
+        };
+        fades.replan(events, path_of);
+        fades
+    }
+
+    /// Recompute the plan after events were added, keeping what was already shaped
+    pub fn replan(&mut self, events: &[AudioEvent], path_of: impl Fn(usize) -> Option<String>) {
+        if self.fade_frames > 0 {
+            self.plan = retriggers(events, path_of);
+        }
+        self.ends.resize(events.len(), None);
+    }
+
+    /// Fade the interleaved voice of event `index`, starting on `start_frame`, in over the
+    /// voice it retriggers and out where the next retrigger starts
+    pub fn apply(
+        &mut self,
+        index: usize,
+        start_frame: usize,
+        samples: &mut Vec<f32>,
+        channels: usize,
+    ) {
+        let channels = channels.max(1);
+        let Some(retrigger) = self.plan.get(index).copied() else {
+            return;
+        };
+        if let Some(end) = self.ends.get_mut(index) {
+            *end = Some(start_frame + samples.len() / channels);
+        }
+
+        let rings = retrigger
+            .previous
+            .and_then(|previous| self.ends.get(previous).copied().flatten())
+            .is_some_and(|end| end > start_frame);
+        if rings {
+            *samples = dsp::fade_in(samples, channels, self.fade_frames);
+        }
+        if let Some(next) = retrigger.next {
+            let at = ((next * self.sample_rate as f32) as usize).saturating_sub(start_frame);
+            *samples = dsp::fade_out(samples, channels, at, self.fade_frames);
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_retrigger.rs"]
+mod tests;
@@ -5,6 +5,8 @@
 
 // This module is conditionally exported from its parent via `#[cfg(feature = "cli")]`.
 // Avoid duplicating crate-level cfg attributes here which cause lints.
+use crate::engine::audio::dsp;
+use crate::engine::audio::settings::ResampleQuality;
 use anyhow::{Context, Result};
 use once_cell::sync::Lazy;
 use serde::Deserialize;
@@ -13,6 +15,19 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+pub mod pitch;
+pub mod prepare;
+pub mod tempo;
+pub mod variants;
+
+// Resampling is one of the editing primitives in `dsp`; kept here for its callers
+pub use crate::engine::audio::dsp::resample;
+
+use variants::SampleVariant;
+
+/// Root assumed for samples without a declared or detectable pitch (C4)
+pub const DEFAULT_ROOT: f32 = 60.0;
+
 /// Global sample registry for native builds
 static SAMPLE_REGISTRY: Lazy<Arc<Mutex<SampleRegistry>>> =
     Lazy::new(|| Arc::new(Mutex::new(SampleRegistry::new())));
@@ -35,12 +50,20 @@ struct BankInfo {
     version: Option<String>,
     #[allow(dead_code)]
     access: Option<String>,
+    /// Default root for every trigger (`"C3"`, or `"auto"` to detect)
+    root: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct TriggerInfo {
     name: String,
     path: String,
+    /// Pitch the sample was recorded at (`"C3"`, or `"auto"` to detect)
+    root: Option<String>,
+    /// Choke group: triggering any member cuts the others still ringing (`"hats"`)
+    choke: Option<String>,
+    /// Tempo of a loop (BPM), used by `sync: auto`; detected when missing
+    bpm: Option<f32>,
 }
 
 /// Sample data (mono f32 PCM)
@@ -50,6 +73,13 @@ pub struct SampleData {
     pub sample_rate: u32,
 }
 
+/// Tempo and length of a loop, for `sync: auto`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleLoop {
+    pub bpm: f32,
+    pub seconds: f32,
+}
+
 /// Bank metadata for lazy loading
 #[derive(Debug, Clone)]
 pub struct BankMetadata {
@@ -57,31 +87,345 @@ pub struct BankMetadata {
     bank_path: PathBuf,
     audio_path: String,
     triggers: HashMap<String, String>, // trigger_name -> file_path
+    roots: HashMap<String, String>,    // trigger_name -> declared root
+    chokes: HashMap<String, String>,   // trigger_name -> choke group
+    tempos: HashMap<String, f32>,      // trigger_name -> declared loop tempo
+}
+
+/// Hash of decoded PCM: URIs that load identical audio share one buffer
+type ContentKey = u64;
+
+/// Cache key for sample-rate conversions: (content, target_rate, quality)
+type ConversionKey = (ContentKey, u32, ResampleQuality);
+
+/// Cache key for repitched copies: (conversion, semitone shift as `f32` bits). Keyed by
+/// shift rather than note, since URIs sharing content can declare different roots.
+type PitchKey = (ConversionKey, u32);
+
+/// A note of a sample: already repitched, or the converted sample still to repitch
+enum NoteSource {
+    Ready(SampleData),
+    Pitch {
+        key: PitchKey,
+        source: SampleData,
+        semitones: f32,
+    },
+}
+
+impl NoteSource {
+    fn repitch(source: &SampleData, semitones: f32, quality: ResampleQuality) -> SampleData {
+        SampleData {
+            samples: dsp::pitch(&source.samples, source.sample_rate, semitones, quality),
+            sample_rate: source.sample_rate,
+        }
+    }
+}
+
+/// Folder under `.deva` where sample variants are shared between builds
+pub const VARIANT_CACHE_DIR: &str = "cache/samples";
+
+/// Registry counts. `shared_bytes` is the PCM not held twice because several URIs
+/// (e.g. a bank path and an absolute path) loaded the same audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SampleStats {
+    pub banks: usize,
+    /// Triggers declared by registered banks
+    pub bank_samples: usize,
+    /// URIs loaded so far
+    pub loaded: usize,
+    /// Distinct PCM buffers behind the loaded URIs
+    pub buffers: usize,
+    pub shared_bytes: usize,
 }
 
 /// Sample registry for managing loaded samples with lazy loading
 #[derive(Debug)]
 pub struct SampleRegistry {
-    samples: HashMap<String, SampleData>,  // Loaded samples cache
-    banks: HashMap<String, BankMetadata>,  // Bank metadata for lazy loading
-    loaded_samples: HashMap<String, bool>, // Track which samples are loaded
+    samples: HashMap<ContentKey, SampleData>, // Loaded PCM, one buffer per distinct content
+    aliases: HashMap<String, ContentKey>,     // URI -> content it loaded as
+    banks: HashMap<String, BankMetadata>,     // Bank metadata for lazy loading
+    loaded_samples: HashMap<String, bool>,    // Track which samples are loaded
+    converted: HashMap<ConversionKey, SampleData>, // Resampled copies, converted once
+    pitched: HashMap<PitchKey, SampleData>,   // Repitched copies, per semitone shift
+    roots: HashMap<String, f32>,              // Resolved root notes (MIDI), per URI
+    tempos: HashMap<String, Option<f32>>,     // Resolved loop tempos (BPM), per URI
+    loops: HashMap<String, Option<SampleLoop>>, // Resolved loop tempo and length, per URI
+    variants: HashMap<(ConversionKey, SampleVariant), SampleData>, // Reversed / 0.5x / 2x copies
+    variant_cache: Option<PathBuf>,           // Where variants are shared between builds
 }
 
 impl SampleRegistry {
     fn new() -> Self {
         Self {
             samples: HashMap::new(),
+            aliases: HashMap::new(),
             banks: HashMap::new(),
             loaded_samples: HashMap::new(),
+            converted: HashMap::new(),
+            pitched: HashMap::new(),
+            roots: HashMap::new(),
+            tempos: HashMap::new(),
+            loops: HashMap::new(),
+            variants: HashMap::new(),
+            variant_cache: None,
         }
     }
 
     /// Register a sample with URI and PCM data (eager loading)
     pub fn register_sample(&mut self, uri: String, data: SampleData) {
-        self.samples.insert(uri.clone(), data);
+        self.roots.remove(&uri);
+        self.tempos.remove(&uri);
+        self.loops.remove(&uri);
+        self.store(uri.clone(), data);
         self.loaded_samples.insert(uri, true);
     }
 
+    /// Point `uri` at `data`, reusing the buffer of identical audio already loaded
+    fn store(&mut self, uri: String, data: SampleData) -> ContentKey {
+        let mut key = content_key(&data);
+        // Step past hash collisions between different audio
+        while let Some(existing) = self.samples.get(&key) {
+            if same_audio(existing, &data) {
+                break;
+            }
+            key = key.wrapping_add(1);
+        }
+        self.samples.entry(key).or_insert(data);
+        if let Some(previous) = self.aliases.insert(uri, key)
+            && previous != key
+        {
+            self.release(previous);
+        }
+        key
+    }
+
+    /// Drop a buffer, and its conversions, once no URI points at it
+    fn release(&mut self, key: ContentKey) {
+        if self.aliases.values().any(|&alias| alias == key) {
+            return;
+        }
+        self.samples.remove(&key);
+        self.converted.retain(|(content, _, _), _| *content != key);
+        self.pitched
+            .retain(|((content, _, _), _), _| *content != key);
+        self.variants
+            .retain(|((content, _, _), _), _| *content != key);
+    }
+
+    /// Content behind `uri`, loading it on first use
+    fn content_of(&mut self, uri: &str) -> Option<ContentKey> {
+        if let Some(key) = self.aliases.get(uri) {
+            return Some(*key);
+        }
+        self.get_sample(uri)?;
+        self.aliases.get(uri).copied()
+    }
+
+    /// Get sample data converted to `target_rate`, converting at most once per
+    /// (uri, target_rate, quality) and serving later requests from the cache
+    pub fn get_sample_at_rate(
+        &mut self,
+        uri: &str,
+        target_rate: u32,
+        quality: ResampleQuality,
+    ) -> Option<SampleData> {
+        let key = (self.content_of(uri)?, target_rate, quality);
+        if let Some(data) = self.converted.get(&key) {
+            return Some(data.clone());
+        }
+
+        let data = self.samples.get(&key.0)?.clone();
+        if data.sample_rate == target_rate || target_rate == 0 {
+            return Some(data);
+        }
+
+        let converted = SampleData {
+            samples: resample(&data.samples, data.sample_rate, target_rate, quality),
+            sample_rate: target_rate,
+        };
+        self.converted.insert(key, converted.clone());
+        Some(converted)
+    }
+
+    /// `variant` of the sample at `target_rate`, computed at most once. With a variant
+    /// cache folder set, it is read from there when an earlier build already wrote it.
+    pub fn get_sample_variant(
+        &mut self,
+        uri: &str,
+        target_rate: u32,
+        quality: ResampleQuality,
+        variant: SampleVariant,
+    ) -> Option<SampleData> {
+        let key = ((self.content_of(uri)?, target_rate, quality), variant);
+        if let Some(data) = self.variants.get(&key) {
+            return Some(data.clone());
+        }
+
+        let source = self.get_sample_at_rate(uri, target_rate, quality)?;
+        let cache_path = self
+            .variant_cache
+            .as_deref()
+            .map(|dir| variants::cache_path(dir, &source, quality, variant));
+        let cached = cache_path
+            .as_deref()
+            .and_then(|path| variants::read_cached(path, source.sample_rate).ok())
+            .filter(|data| data.samples.len() == source.samples.len());
+        let data = match cached {
+            Some(data) => data,
+            None => {
+                let data = variant.apply(&source);
+                if let Some(path) = &cache_path
+                    && let Err(e) = variants::write_cached(path, &data)
+                {
+                    eprintln!("Failed to cache {:?} of {}: {}", variant, uri, e);
+                }
+                data
+            }
+        };
+        self.variants.insert(key, data.clone());
+        Some(data)
+    }
+
+    /// Get sample data at `target_rate`, repitched from its root to `note` when given.
+    /// Repitched copies are cached like rate conversions.
+    pub fn get_sample_for_note(
+        &mut self,
+        uri: &str,
+        target_rate: u32,
+        quality: ResampleQuality,
+        note: Option<u8>,
+    ) -> Option<SampleData> {
+        let Some(note) = note else {
+            return self.get_sample_at_rate(uri, target_rate, quality);
+        };
+        match self.note_source(uri, target_rate, quality, note)? {
+            NoteSource::Ready(data) => Some(data),
+            NoteSource::Pitch {
+                key,
+                source,
+                semitones,
+            } => {
+                let pitched = NoteSource::repitch(&source, semitones, quality);
+                self.store_pitched(key, pitched.clone());
+                Some(pitched)
+            }
+        }
+    }
+
+    /// Cached copy of `uri` repitched to `note`, or what to repitch it from
+    fn note_source(
+        &mut self,
+        uri: &str,
+        target_rate: u32,
+        quality: ResampleQuality,
+        note: u8,
+    ) -> Option<NoteSource> {
+        let content = self.content_of(uri)?;
+        let semitones = note as f32 - self.root_note(uri);
+        let key = ((content, target_rate, quality), semitones.to_bits());
+        if let Some(data) = self.pitched.get(&key) {
+            return Some(NoteSource::Ready(data.clone()));
+        }
+        let source = self.get_sample_at_rate(uri, target_rate, quality)?;
+        if semitones == 0.0 {
+            return Some(NoteSource::Ready(source));
+        }
+        Some(NoteSource::Pitch {
+            key,
+            source,
+            semitones,
+        })
+    }
+
+    /// Keep a repitched copy, unless its sample was released meanwhile
+    fn store_pitched(&mut self, key: PitchKey, data: SampleData) {
+        if self.samples.contains_key(&key.0.0) {
+            self.pitched.insert(key, data);
+        }
+    }
+
+    /// Root note of a sample as a (fractional) MIDI number.
+    ///
+    /// Uses the `root` declared in bank.toml; `"auto"` or a missing root runs pitch
+    /// detection once and caches the result. Unpitched samples fall back to `DEFAULT_ROOT`.
+    pub fn root_note(&mut self, uri: &str) -> f32 {
+        if let Some(root) = self.roots.get(uri) {
+            return *root;
+        }
+
+        let declared = self.declared_root(uri);
+        let root = match declared.as_deref() {
+            Some(name) if !name.eq_ignore_ascii_case("auto") => {
+                match crate::engine::functions::note::parse_note_to_midi(name) {
+                    Ok(midi) => Some(midi as f32),
+                    Err(e) => {
+                        eprintln!("Invalid root '{}' for {}: {}", name, uri, e);
+                        None
+                    }
+                }
+            }
+            _ => self.get_sample(uri).and_then(|data| {
+                pitch::detect_pitch(&data.samples, data.sample_rate).map(pitch::hz_to_midi)
+            }),
+        }
+        .unwrap_or(DEFAULT_ROOT);
+
+        self.roots.insert(uri.to_string(), root);
+        root
+    }
+
+    /// Tempo of a loop in BPM: the `bpm` declared in bank.toml, otherwise detected once
+    /// and cached. `None` when the sample has no steady beat.
+    pub fn tempo(&mut self, uri: &str) -> Option<f32> {
+        if let Some(tempo) = self.tempos.get(uri) {
+            return *tempo;
+        }
+        let tempo = self.declared_tempo(uri).or_else(|| {
+            self.get_sample(uri)
+                .and_then(|data| tempo::detect_tempo(&data.samples, data.sample_rate))
+        });
+        self.tempos.insert(uri.to_string(), tempo);
+        tempo
+    }
+
+    /// Length in seconds of the sample at `uri`, without copying its PCM
+    pub fn sample_seconds(&mut self, uri: &str) -> Option<f32> {
+        let key = self.content_of(uri)?;
+        let data = self.samples.get(&key)?;
+        (data.sample_rate > 0).then(|| data.samples.len() as f32 / data.sample_rate as f32)
+    }
+
+    /// Tempo declared in the bank manifest for a `devalang://bank/` URI
+    fn declared_tempo(&self, uri: &str) -> Option<f32> {
+        let (bank_id, trigger) = uri.strip_prefix("devalang://bank/")?.split_once('/')?;
+        self.banks.get(bank_id)?.tempos.get(trigger).copied()
+    }
+
+    /// Root declared in the bank manifest for a `devalang://bank/` URI
+    fn declared_root(&self, uri: &str) -> Option<String> {
+        let (bank_id, trigger) = uri.strip_prefix("devalang://bank/")?.split_once('/')?;
+        self.banks.get(bank_id)?.roots.get(trigger).cloned()
+    }
+
+    /// Choke group of a `devalang://bank/` URI, scoped to its bank (`<bank_id>/<group>`)
+    pub fn choke_group(&self, uri: &str) -> Option<String> {
+        let (bank_id, trigger) = uri.strip_prefix("devalang://bank/")?.split_once('/')?;
+        let group = self.banks.get(bank_id)?.chokes.get(trigger)?;
+        Some(format!("{}/{}", bank_id, group))
+    }
+
+    /// List the URIs of every trigger declared by registered banks
+    pub fn bank_sample_uris(&self) -> Vec<String> {
+        self.banks
+            .values()
+            .flat_map(|bank| {
+                bank.triggers
+                    .keys()
+                    .map(move |trigger| format!("devalang://bank/{}/{}", bank.bank_id, trigger))
+            })
+            .collect()
+    }
+
     /// Register bank metadata for lazy loading
     pub fn register_bank_metadata(&mut self, metadata: BankMetadata) {
         self.banks.insert(metadata.bank_id.clone(), metadata);
@@ -90,14 +434,14 @@ impl SampleRegistry {
     /// Get sample data by URI (lazy load if needed)
     pub fn get_sample(&mut self, uri: &str) -> Option<SampleData> {
         // If already loaded, return from cache
-        if let Some(data) = self.samples.get(uri) {
+        if let Some(data) = self.aliases.get(uri).and_then(|key| self.samples.get(key)) {
             return Some(data.clone());
         }
 
         // Try lazy loading
         if !self.loaded_samples.contains_key(uri) {
             if let Some(data) = self.try_lazy_load(uri) {
-                self.samples.insert(uri.to_string(), data.clone());
+                self.store(uri.to_string(), data.clone());
                 self.loaded_samples.insert(uri.to_string(), true);
                 return Some(data);
             }
@@ -153,17 +497,80 @@ impl SampleRegistry {
     }
 
     /// Get statistics
-    pub fn stats(&self) -> (usize, usize, usize) {
-        let total_banks = self.banks.len();
-        let total_samples: usize = self.banks.values().map(|b| b.triggers.len()).sum();
-        let loaded_samples = self.samples.len();
-        (total_banks, total_samples, loaded_samples)
+    pub fn stats(&self) -> SampleStats {
+        let bytes = |key: &ContentKey| {
+            self.samples
+                .get(key)
+                .map_or(0, |data| data.samples.len() * std::mem::size_of::<f32>())
+        };
+        let referenced: usize = self.aliases.values().map(bytes).sum();
+        let held: usize = self.samples.keys().map(bytes).sum();
+        SampleStats {
+            banks: self.banks.len(),
+            bank_samples: self.banks.values().map(|b| b.triggers.len()).sum(),
+            loaded: self.aliases.len(),
+            buffers: self.samples.len(),
+            shared_bytes: referenced.saturating_sub(held),
+        }
+    }
+
+    /// Number of cached sample-rate conversions
+    pub fn converted_count(&self) -> usize {
+        self.converted.len()
     }
 }
 
+fn content_key(data: &SampleData) -> ContentKey {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    data.sample_rate.hash(&mut hasher);
+    data.samples.len().hash(&mut hasher);
+    for sample in &data.samples {
+        sample.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn same_audio(a: &SampleData, b: &SampleData) -> bool {
+    a.sample_rate == b.sample_rate
+        && a.samples.len() == b.samples.len()
+        && a.samples
+            .iter()
+            .zip(&b.samples)
+            .all(|(x, y)| x.to_bits() == y.to_bits())
+}
+
 /// Load a bank from a directory containing bank.toml and audio files
 /// Uses lazy loading: only metadata is loaded initially, samples are loaded on demand
 pub fn load_bank_from_directory(bank_path: &Path) -> Result<String> {
+    let metadata = read_bank_metadata(bank_path)?;
+    let bank_id = metadata.bank_id.clone();
+
+    // Register bank metadata
+    let mut registry = SAMPLE_REGISTRY.lock().unwrap();
+    registry.register_bank_metadata(metadata);
+
+    // Bank registered
+
+    Ok(bank_id)
+}
+
+/// Register the bank at `bank_path` under `bank_id` (the name used by
+/// `devalang://bank/<bank_id>/...` URIs) unless a bank with that id is already known
+pub fn ensure_bank_registered(bank_id: &str, bank_path: &Path) -> Result<()> {
+    if SAMPLE_REGISTRY.lock().unwrap().has_bank(bank_id) {
+        return Ok(());
+    }
+    let mut metadata = read_bank_metadata(bank_path)?;
+    metadata.bank_id = bank_id.to_string();
+    SAMPLE_REGISTRY
+        .lock()
+        .unwrap()
+        .register_bank_metadata(metadata);
+    Ok(())
+}
+
+fn read_bank_metadata(bank_path: &Path) -> Result<BankMetadata> {
     let manifest_path = bank_path.join("bank.toml");
     if !manifest_path.exists() {
         anyhow::bail!("bank.toml not found in {:?}", bank_path);
@@ -179,27 +586,34 @@ pub fn load_bank_from_directory(bank_path: &Path) -> Result<String> {
 
     // Build trigger map: trigger_name -> file_path
     let mut triggers = HashMap::new();
+    let mut roots = HashMap::new();
+    let mut chokes = HashMap::new();
+    let mut tempos = HashMap::new();
     for trigger in &manifest.triggers {
         // Clean up trigger path (remove leading ./)
         let clean_path = trigger.path.trim_start_matches("./").to_string();
         triggers.insert(trigger.name.clone(), clean_path);
+        if let Some(root) = trigger.root.as_ref().or(manifest.bank.root.as_ref()) {
+            roots.insert(trigger.name.clone(), root.clone());
+        }
+        if let Some(group) = &trigger.choke {
+            chokes.insert(trigger.name.clone(), group.clone());
+        }
+        if let Some(bpm) = trigger.bpm.filter(|bpm| *bpm > 0.0) {
+            tempos.insert(trigger.name.clone(), bpm);
+        }
     }
 
     // Create bank metadata for lazy loading
-    let metadata = BankMetadata {
-        bank_id: bank_id.clone(),
+    Ok(BankMetadata {
+        bank_id,
         bank_path: bank_path.to_path_buf(),
         audio_path: manifest.bank.audio_path.clone(),
-        triggers: triggers.clone(),
-    };
-
-    // Register bank metadata
-    let mut registry = SAMPLE_REGISTRY.lock().unwrap();
-    registry.register_bank_metadata(metadata);
-
-    // Bank registered
-
-    Ok(bank_id)
+        triggers,
+        roots,
+        chokes,
+        tempos,
+    })
 }
 
 /// Load WAV file and convert to mono f32 PCM
@@ -282,6 +696,314 @@ pub fn get_sample(uri: &str) -> Option<SampleData> {
     generate_synthetic_sample(uri)
 }
 
+/// Length in seconds of a sample (synthetic fallbacks included), without copying the
+/// PCM of registered samples
+pub fn sample_seconds(uri: &str) -> Option<f32> {
+    if let Some(seconds) = SAMPLE_REGISTRY.lock().unwrap().sample_seconds(uri) {
+        return Some(seconds);
+    }
+    generate_synthetic_sample(uri)
+        .filter(|data| data.sample_rate > 0)
+        .map(|data| data.samples.len() as f32 / data.sample_rate as f32)
+}
+
+/// Choke group declared in bank.toml for a sample URI
+pub fn choke_group(uri: &str) -> Option<String> {
+    SAMPLE_REGISTRY.lock().unwrap().choke_group(uri)
+}
+
+/// Get sample from global registry converted to `target_rate` (cached per quality)
+pub fn get_sample_at_rate(
+    uri: &str,
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> Option<SampleData> {
+    let mut registry = SAMPLE_REGISTRY.lock().unwrap();
+    if let Some(data) = registry.get_sample_at_rate(uri, target_rate, quality) {
+        return Some(data);
+    }
+    drop(registry);
+
+    // Fallback: synthetic drums are generated at 44.1kHz
+    generate_synthetic_sample(uri).map(|data| SampleData {
+        samples: resample(&data.samples, data.sample_rate, target_rate, quality),
+        sample_rate: target_rate,
+    })
+}
+
+/// Tempo (declared in bank.toml or detected) and length of a loop, for `sync: auto`.
+/// Resolved once per URI; detection reads the whole loop, so it runs without the lock.
+pub fn sample_loop(uri: &str) -> Option<SampleLoop> {
+    let (declared, data) = {
+        let mut registry = SAMPLE_REGISTRY.lock().unwrap();
+        if let Some(cached) = registry.loops.get(uri) {
+            return *cached;
+        }
+        (registry.declared_tempo(uri), registry.get_sample(uri))
+    };
+    let resolved = data.filter(|data| data.sample_rate > 0).and_then(|data| {
+        let bpm = declared.or_else(|| tempo::detect_tempo(&data.samples, data.sample_rate))?;
+        Some(SampleLoop {
+            bpm,
+            seconds: data.samples.len() as f32 / data.sample_rate as f32,
+        })
+    });
+    SAMPLE_REGISTRY
+        .lock()
+        .unwrap()
+        .loops
+        .insert(uri.to_string(), resolved);
+    resolved
+}
+
+/// Get sample from global registry at `target_rate`, repitched to `note` from its root
+pub fn get_sample_for_note(
+    uri: &str,
+    target_rate: u32,
+    quality: ResampleQuality,
+    note: Option<u8>,
+) -> Option<SampleData> {
+    let Some(note) = note else {
+        return get_sample_at_rate(uri, target_rate, quality);
+    };
+    let lookup = SAMPLE_REGISTRY
+        .lock()
+        .unwrap()
+        .note_source(uri, target_rate, quality, note);
+    match lookup {
+        Some(NoteSource::Ready(data)) => Some(data),
+        // Repitched without the lock, so other voices can load meanwhile
+        Some(NoteSource::Pitch {
+            key,
+            source,
+            semitones,
+        }) => {
+            let pitched = NoteSource::repitch(&source, semitones, quality);
+            SAMPLE_REGISTRY
+                .lock()
+                .unwrap()
+                .store_pitched(key, pitched.clone());
+            Some(pitched)
+        }
+        // Synthetic fallbacks are unpitched, so play them as-is
+        None => get_sample_at_rate(uri, target_rate, quality),
+    }
+}
+
+/// Pre-convert every registered bank sample to `target_rate` on a background thread.
+///
+/// Intended to run right after `auto_load_banks` so the first render does not pay
+/// the conversion cost. The registry lock is only held per sample.
+pub fn preconvert_bank_samples(
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> std::thread::JoinHandle<usize> {
+    std::thread::spawn(move || {
+        let uris = SAMPLE_REGISTRY.lock().unwrap().bank_sample_uris();
+        let mut converted = 0;
+        for uri in uris {
+            let mut registry = SAMPLE_REGISTRY.lock().unwrap();
+            if registry
+                .get_sample_at_rate(&uri, target_rate, quality)
+                .is_some()
+            {
+                converted += 1;
+            }
+        }
+        converted
+    })
+}
+
+/// Share sample variants between builds through files in `dir` (`None` keeps them in
+/// memory only)
+pub fn set_variant_cache_dir(dir: Option<PathBuf>) {
+    SAMPLE_REGISTRY.lock().unwrap().variant_cache = dir;
+}
+
+/// Get `variant` of a registered sample at `target_rate` (cached per quality)
+pub fn get_sample_variant(
+    uri: &str,
+    target_rate: u32,
+    quality: ResampleQuality,
+    variant: SampleVariant,
+) -> Option<SampleData> {
+    SAMPLE_REGISTRY
+        .lock()
+        .unwrap()
+        .get_sample_variant(uri, target_rate, quality, variant)
+}
+
+/// Generate every cached variant of every registered bank sample at `target_rate` on a
+/// background thread (`audio.sample_variants`). The registry lock is only held per sample.
+pub fn generate_bank_variants(
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> std::thread::JoinHandle<usize> {
+    std::thread::spawn(move || {
+        let uris = SAMPLE_REGISTRY.lock().unwrap().bank_sample_uris();
+        let mut generated = 0;
+        for uri in uris {
+            for variant in SampleVariant::ALL {
+                if get_sample_variant(&uri, target_rate, quality, variant).is_some() {
+                    generated += 1;
+                }
+            }
+        }
+        generated
+    })
+}
+
+/// Bank-wide sample work started ahead of the first render (`audio.preconvert_samples`,
+/// `audio.sample_variants`), shared by `play` and `build`
+pub struct BankPreparation {
+    preconvert: Option<std::thread::JoinHandle<usize>>,
+    variants: Option<std::thread::JoinHandle<usize>>,
+}
+
+/// What a finished `BankPreparation` did (`None` for a step that was not started)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BankPreparationReport {
+    pub converted: Option<usize>,
+    pub variants: Option<usize>,
+}
+
+impl BankPreparation {
+    pub fn start(
+        target_rate: u32,
+        quality: ResampleQuality,
+        preconvert: bool,
+        variants: bool,
+    ) -> Self {
+        Self {
+            preconvert: preconvert.then(|| preconvert_bank_samples(target_rate, quality)),
+            variants: variants.then(|| generate_bank_variants(target_rate, quality)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.preconvert.is_none() && self.variants.is_none()
+    }
+
+    /// Wait for the workers; one that panicked is reported as an error
+    pub fn finish(self) -> Result<BankPreparationReport> {
+        fn join(
+            handle: Option<std::thread::JoinHandle<usize>>,
+            step: &str,
+        ) -> Result<Option<usize>> {
+            handle
+                .map(|handle| {
+                    handle
+                        .join()
+                        .map_err(|_| anyhow::anyhow!("{} worker panicked", step))
+                })
+                .transpose()
+        }
+
+        Ok(BankPreparationReport {
+            converted: join(self.preconvert, "Bank sample pre-conversion")?,
+            variants: join(self.variants, "Sample variant generation")?,
+        })
+    }
+}
+
+/// Source/target rate ratio from which a mismatch is reported as severe (22.05 kHz in a
+/// 48 kHz project, 96 kHz in a 44.1 kHz one; 44.1 vs 48 kHz is not)
+pub const SEVERE_RATE_RATIO: f32 = 1.5;
+
+/// A sample whose native rate differs from the project rate
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateConversion {
+    pub uri: String,
+    pub source_rate: u32,
+    pub target_rate: u32,
+    pub quality: ResampleQuality,
+}
+
+impl RateConversion {
+    /// Larger rate over smaller rate (always >= 1)
+    pub fn ratio(&self) -> f32 {
+        let (low, high) = if self.source_rate < self.target_rate {
+            (self.source_rate, self.target_rate)
+        } else {
+            (self.target_rate, self.source_rate)
+        };
+        high as f32 / low.max(1) as f32
+    }
+
+    pub fn is_severe(&self) -> bool {
+        self.ratio() >= SEVERE_RATE_RATIO
+    }
+
+    /// Highest frequency left after conversion (the lower of the two Nyquist limits)
+    pub fn bandwidth_hz(&self) -> f32 {
+        self.source_rate.min(self.target_rate) as f32 / 2.0
+    }
+
+    /// Frequency where the resampling filter starts to roll off
+    pub fn rolloff_hz(&self) -> f32 {
+        self.bandwidth_hz() * (1.0 - self.quality.transition_fraction())
+    }
+
+    /// One-line estimate of what the conversion costs in quality
+    pub fn describe(&self) -> String {
+        format!(
+            "{}: {} Hz -> {} Hz ({}), bandwidth {:.1} kHz, rolloff from ~{:.1} kHz, aliasing ~{:.0} dB",
+            self.uri,
+            self.source_rate,
+            self.target_rate,
+            self.quality,
+            self.bandwidth_hz() / 1000.0,
+            self.rolloff_hz() / 1000.0,
+            self.quality.estimated_rejection_db()
+        )
+    }
+}
+
+/// Samples (uri, native rate) that are not at `target_rate`, sorted by uri
+pub fn rate_conversions(
+    rates: impl IntoIterator<Item = (String, u32)>,
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> Vec<RateConversion> {
+    let mut conversions: Vec<RateConversion> = rates
+        .into_iter()
+        .filter(|(_, rate)| *rate != 0 && *rate != target_rate)
+        .map(|(uri, source_rate)| RateConversion {
+            uri,
+            source_rate,
+            target_rate,
+            quality,
+        })
+        .collect();
+    conversions.sort_by(|a, b| a.uri.cmp(&b.uri));
+    conversions.dedup_by(|a, b| a.uri == b.uri);
+    conversions
+}
+
+/// Look up the native rate of every sample a render uses and, with `preconvert`, convert
+/// them to `target_rate` up front (the registry caches the result for the render).
+/// Returns the samples that need converting.
+pub fn prepare_samples(
+    uris: &[String],
+    target_rate: u32,
+    quality: ResampleQuality,
+    preconvert: bool,
+) -> Vec<RateConversion> {
+    let rates = uris.iter().filter_map(|uri| {
+        let rate = get_sample(uri)?.sample_rate;
+        if preconvert && rate != target_rate {
+            get_sample_at_rate(uri, target_rate, quality);
+        }
+        Some((uri.clone(), rate))
+    });
+    rate_conversions(rates.collect::<Vec<_>>(), target_rate, quality)
+}
+
+/// Native sample rate of an audio file
+pub fn file_sample_rate(path: &Path) -> Result<u32> {
+    Ok(load_audio_file(path)?.sample_rate)
+}
+
 /// Register a sample into the global registry with the given URI.
 pub fn register_sample(uri: &str, data: SampleData) {
     let mut registry = SAMPLE_REGISTRY.lock().unwrap();
@@ -309,8 +1031,8 @@ pub fn register_sample_from_path(path: &std::path::Path) -> Result<String, anyho
     }
 }
 
-/// Get registry statistics (banks, total samples, loaded samples)
-pub fn get_stats() -> (usize, usize, usize) {
+/// Get registry statistics, including the memory saved by sharing identical samples
+pub fn get_stats() -> SampleStats {
     let registry = SAMPLE_REGISTRY.lock().unwrap();
     registry.stats()
 }
@@ -615,3 +1337,7 @@ fn generate_cymbal(sample_rate: u32, duration_ms: u32) -> Vec<f32> {
 
     samples
 }
+
+#[cfg(test)]
+#[path = "test_samples.rs"]
+mod tests;
@@ -0,0 +1,85 @@
+//! Fundamental-frequency detection for melodic samples
+//!
+//! A compact YIN estimator: cumulative-mean-normalized difference function,
+//! absolute threshold and parabolic interpolation around the chosen lag.
+
+/// Lowest and highest fundamentals considered (Hz)
+const MIN_FREQ: f32 = 40.0;
+const MAX_FREQ: f32 = 2000.0;
+/// Dips of the normalized difference below this count as periodic
+const YIN_THRESHOLD: f32 = 0.15;
+/// Attack portion skipped before analysis so transients do not dominate (seconds)
+const ONSET_SKIP: f32 = 0.05;
+
+/// Estimate the fundamental frequency of a mono sample in Hz.
+///
+/// Returns `None` when the sample is too short or not clearly periodic (drums, noise).
+pub fn detect_pitch(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    if sample_rate == 0 {
+        return None;
+    }
+    let rate = sample_rate as f32;
+    let min_lag = (rate / MAX_FREQ).floor().max(2.0) as usize;
+    let max_lag = (rate / MIN_FREQ).ceil() as usize;
+    let window = max_lag;
+
+    // Skip the attack when the sample is long enough, otherwise analyse from the start
+    let skip = ((rate * ONSET_SKIP) as usize).min(samples.len().saturating_sub(window + max_lag));
+    let frame = samples.get(skip..skip + window + max_lag)?;
+    if frame.iter().all(|s| s.abs() < 1e-4) {
+        return None;
+    }
+
+    let mut diff = vec![0.0f32; max_lag + 1];
+    for (tau, slot) in diff.iter_mut().enumerate().skip(1) {
+        *slot = (0..window)
+            .map(|j| {
+                let d = frame[j] - frame[j + tau];
+                d * d
+            })
+            .sum();
+    }
+
+    let mut cmnd = vec![1.0f32; max_lag + 1];
+    let mut running = 0.0f32;
+    for tau in 1..=max_lag {
+        running += diff[tau];
+        cmnd[tau] = if running > 0.0 {
+            diff[tau] * tau as f32 / running
+        } else {
+            1.0
+        };
+    }
+
+    let mut tau = min_lag;
+    while tau < max_lag {
+        if cmnd[tau] < YIN_THRESHOLD {
+            while tau + 1 < max_lag && cmnd[tau + 1] < cmnd[tau] {
+                tau += 1;
+            }
+            return Some(rate / refine_lag(&cmnd, tau));
+        }
+        tau += 1;
+    }
+
+    None
+}
+
+/// Parabolic interpolation of the minimum around `tau`
+fn refine_lag(cmnd: &[f32], tau: usize) -> f32 {
+    if tau == 0 || tau + 1 >= cmnd.len() {
+        return tau as f32;
+    }
+    let (a, b, c) = (cmnd[tau - 1], cmnd[tau], cmnd[tau + 1]);
+    let denom = a - 2.0 * b + c;
+    if denom.abs() < f32::EPSILON {
+        tau as f32
+    } else {
+        tau as f32 + 0.5 * (a - c) / denom
+    }
+}
+
+/// Convert a frequency in Hz to a (fractional) MIDI note number, A4 = 69
+pub fn hz_to_midi(freq: f32) -> f32 {
+    69.0 + 12.0 * (freq / 440.0).log2()
+}
@@ -0,0 +1,227 @@
+//! Batch preparation of raw sample folders into banks
+//!
+//! Each file is trimmed of leading/trailing silence, converted to the project rate and
+//! peak-normalized; the folder then gets a `bank.toml` listing one trigger per file.
+//! `analyze_bank` fills in the tempo and root of the triggers of an existing bank.
+
+use super::{BankManifest, SampleData, load_audio_file, pitch, resample, tempo};
+use crate::engine::audio::settings::ResampleQuality;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Extensions picked up when scanning a folder
+pub const AUDIO_EXTENSIONS: [&str; 7] = ["wav", "wave", "mp3", "flac", "ogg", "aif", "aiff"];
+
+#[derive(Debug, Clone, Copy)]
+pub struct PrepareOptions {
+    pub sample_rate: u32,
+    pub quality: ResampleQuality,
+    /// Level (dBFS) below which leading/trailing audio counts as silence; `None` keeps it
+    pub trim_threshold_db: Option<f32>,
+    /// Peak level (dBFS) to normalize to; `None` keeps the original gain
+    pub normalize_peak_db: Option<f32>,
+}
+
+/// Range `start..end` between the first and last samples at or above `threshold_db`.
+/// `None` when the whole sample is below the threshold.
+pub fn silence_bounds(samples: &[f32], threshold_db: f32) -> Option<(usize, usize)> {
+    let threshold = db_to_gain(threshold_db);
+    let start = samples.iter().position(|s| s.abs() >= threshold)?;
+    let end = samples.iter().rposition(|s| s.abs() >= threshold)? + 1;
+    Some((start, end))
+}
+
+/// Scale `samples` so their peak sits at `peak_db`; silent input is left untouched
+pub fn normalize_peak(samples: &mut [f32], peak_db: f32) {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak <= f32::EPSILON {
+        return;
+    }
+    let gain = db_to_gain(peak_db) / peak;
+    for sample in samples {
+        *sample *= gain;
+    }
+}
+
+/// Trim, convert and normalize one sample
+pub fn prepare(data: &SampleData, options: &PrepareOptions) -> SampleData {
+    let trimmed = match options.trim_threshold_db {
+        Some(threshold_db) => match silence_bounds(&data.samples, threshold_db) {
+            Some((start, end)) => &data.samples[start..end],
+            None => &[][..],
+        },
+        None => &data.samples[..],
+    };
+
+    let mut samples = resample(
+        trimmed,
+        data.sample_rate,
+        options.sample_rate,
+        options.quality,
+    );
+    if let Some(peak_db) = options.normalize_peak_db {
+        normalize_peak(&mut samples, peak_db);
+    }
+
+    SampleData {
+        samples,
+        sample_rate: options.sample_rate,
+    }
+}
+
+/// Load an audio file (any supported format) and prepare it
+pub fn prepare_file(path: &Path, options: &PrepareOptions) -> Result<SampleData> {
+    Ok(prepare(&load_audio_file(path)?, options))
+}
+
+/// Trigger name for a file stem: lowercase, anything but letters, digits and `_` becomes `_`
+pub fn trigger_name(stem: &str) -> String {
+    let name: String = stem
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let name = name.trim_matches('_').to_string();
+    match name.chars().next() {
+        None => "sample".to_string(),
+        // Triggers are read as identifiers, which cannot start with a digit
+        Some(c) if c.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name,
+    }
+}
+
+/// `bank.toml` content for a prepared folder; `triggers` are (name, file) pairs relative
+/// to `audio_path`
+pub fn bank_manifest(
+    name: &str,
+    publisher: &str,
+    audio_path: &str,
+    triggers: &[(String, String)],
+) -> String {
+    let mut manifest = format!(
+        "[bank]\nname = {:?}\npublisher = {:?}\naudio_path = {:?}\n",
+        name, publisher, audio_path
+    );
+    for (trigger, file) in triggers {
+        manifest.push_str(&format!(
+            "\n[[triggers]]\nname = {:?}\npath = {:?}\n",
+            trigger,
+            format!("./{}", file)
+        ));
+    }
+    manifest
+}
+
+/// Tempo and root detected in one trigger of a bank
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerAnalysis {
+    /// Loop tempo (BPM); `None` for one-shots and samples without a steady beat
+    pub bpm: Option<f32>,
+    /// Root note name (`"C3"`); `None` for unpitched samples
+    pub root: Option<String>,
+}
+
+impl TriggerAnalysis {
+    /// `bank.toml` keys and values to record for this trigger
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = Vec::new();
+        if let Some(bpm) = self.bpm {
+            entries.push(("bpm", format!("{}", (bpm * 100.0).round() / 100.0)));
+        }
+        if let Some(root) = &self.root {
+            entries.push(("root", format!("{:?}", root)));
+        }
+        entries
+    }
+}
+
+/// Detect the tempo and root of every trigger of the bank in `dir`, by trigger name in
+/// manifest order. Files that fail to load are reported as errors in place.
+pub fn analyze_bank(dir: &Path) -> Result<Vec<(String, Result<TriggerAnalysis>)>> {
+    let manifest_path = dir.join("bank.toml");
+    let content = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let manifest: BankManifest = toml::from_str(&content)
+        .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+    let audio_dir = dir.join(&manifest.bank.audio_path);
+    Ok(manifest
+        .triggers
+        .iter()
+        .map(|trigger| {
+            let path = audio_dir.join(trigger.path.trim_start_matches("./"));
+            let analysis = load_audio_file(&path).map(|data| TriggerAnalysis {
+                bpm: tempo::detect_tempo(&data.samples, data.sample_rate),
+                root: pitch::detect_pitch(&data.samples, data.sample_rate).map(|hz| {
+                    crate::engine::functions::theory::midi_to_name(
+                        pitch::hz_to_midi(hz).round() as i32
+                    )
+                }),
+            });
+            (trigger.name.clone(), analysis)
+        })
+        .collect())
+}
+
+/// Write `entries` (trigger name -> key/value pairs, values already TOML) into the
+/// `[[triggers]]` tables of a `bank.toml`, keeping its layout and comments. Keys a trigger
+/// already declares are only replaced with `overwrite`.
+pub fn set_trigger_metadata(
+    manifest: &str,
+    entries: &HashMap<String, Vec<(&str, String)>>,
+    overwrite: bool,
+) -> String {
+    let lines: Vec<&str> = manifest.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut index = 0;
+    while index < lines.len() {
+        if lines[index].trim() != "[[triggers]]" {
+            out.push(lines[index].to_string());
+            index += 1;
+            continue;
+        }
+        let end = (index + 1..lines.len())
+            .find(|&i| lines[i].trim_start().starts_with('['))
+            .unwrap_or(lines.len());
+        let mut block: Vec<String> = lines[index..end].iter().map(|l| l.to_string()).collect();
+        let key_of = |line: &str| line.split_once('=').map(|(key, _)| key.trim().to_string());
+        let name = block.iter().find_map(|line| {
+            (key_of(line).as_deref() == Some("name"))
+                .then(|| toml::from_str::<toml::Table>(line).ok())
+                .flatten()
+                .and_then(|table| table.get("name")?.as_str().map(str::to_string))
+        });
+        if let Some(updates) = name.as_ref().and_then(|name| entries.get(name)) {
+            for (key, value) in updates {
+                let line = format!("{} = {}", key, value);
+                match block
+                    .iter()
+                    .position(|l| key_of(l).as_deref() == Some(*key))
+                {
+                    Some(at) if overwrite => block[at] = line,
+                    Some(_) => {}
+                    None => {
+                        // After the last entry, before the blank lines ending the table
+                        let at = block
+                            .iter()
+                            .rposition(|l| !l.trim().is_empty())
+                            .map_or(block.len(), |last| last + 1);
+                        block.insert(at, line);
+                    }
+                }
+            }
+        }
+        out.extend(block);
+        index = end;
+    }
+    let mut text = out.join("\n");
+    if manifest.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
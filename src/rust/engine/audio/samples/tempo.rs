@@ -0,0 +1,112 @@
+//! Tempo detection for loops
+//!
+//! The onset strength (rise of the short-time level) is autocorrelated over the lags
+//! of 70-180 BPM, weighted towards 120 BPM so half/double tempo ambiguities resolve the
+//! way a producer would count them. Loops usually span whole beats, so a tempo within a
+//! few percent of one that fits the loop exactly is snapped to it.
+
+/// Tempo range considered (BPM)
+const MIN_BPM: f32 = 70.0;
+const MAX_BPM: f32 = 180.0;
+/// Onset envelope frames per second
+const FRAME_RATE: f32 = 100.0;
+/// Tempo the weighting is centred on, and its spread in octaves
+const PRIOR_BPM: f32 = 120.0;
+const PRIOR_OCTAVES: f32 = 1.0;
+/// Level jumps smaller than this (dB between frames) are not onsets
+const ONSET_DB: f32 = 3.0;
+/// Snap to a whole number of beats over the loop when this close (relative)
+const SNAP_TOLERANCE: f32 = 0.03;
+/// Shortest sample analyzed: two beats at the slowest tempo (seconds)
+const MIN_SECONDS: f32 = 2.0 * 60.0 / MIN_BPM;
+
+/// Estimate the tempo of a mono loop in BPM.
+///
+/// Returns `None` when the sample is too short or has no regular onsets (pads, silence).
+pub fn detect_tempo(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    if sample_rate == 0 || (samples.len() as f32) < MIN_SECONDS * sample_rate as f32 {
+        return None;
+    }
+    let onsets = onset_envelope(samples, sample_rate);
+    let hop_seconds = 1.0 / FRAME_RATE;
+    let min_lag = (60.0 / MAX_BPM / hop_seconds).floor() as usize;
+    let max_lag = ((60.0 / MIN_BPM / hop_seconds).ceil() as usize).min(onsets.len() / 2);
+    if min_lag < 2 || max_lag <= min_lag {
+        return None;
+    }
+
+    let energy: f32 = onsets.iter().map(|v| v * v).sum();
+    if energy <= f32::EPSILON {
+        return None;
+    }
+    let correlation = |lag: usize| -> f32 {
+        let sum: f32 = onsets.iter().zip(&onsets[lag..]).map(|(a, b)| a * b).sum();
+        // Unbiased: longer lags overlap fewer frames
+        sum / (onsets.len() - lag) as f32
+    };
+    let scores: Vec<f32> = (min_lag - 1..=max_lag + 1).map(correlation).collect();
+    let weight = |lag: f32| {
+        let bpm = 60.0 / (lag * hop_seconds);
+        let octaves = (bpm / PRIOR_BPM).log2() / PRIOR_OCTAVES;
+        (-0.5 * octaves * octaves).exp()
+    };
+    let (best, _) = (1..scores.len() - 1)
+        .map(|i| (i, scores[i] * weight((min_lag - 1 + i) as f32)))
+        .filter(|(i, _)| scores[*i] >= scores[i - 1] && scores[*i] >= scores[i + 1])
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    // Periodic onsets correlate well above the average frame
+    let mean = energy / onsets.len() as f32;
+    if scores[best] < mean * 0.2 {
+        return None;
+    }
+
+    // Parabolic interpolation around the peak
+    let (a, b, c) = (scores[best - 1], scores[best], scores[best + 1]);
+    let denom = a - 2.0 * b + c;
+    let offset = if denom.abs() > f32::EPSILON {
+        (0.5 * (a - c) / denom).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    let lag = (min_lag - 1 + best) as f32 + offset;
+    let bpm = 60.0 / (lag * hop_seconds);
+
+    let seconds = samples.len() as f32 / sample_rate as f32;
+    Some(snap_to_loop(bpm, seconds))
+}
+
+/// `bpm`, or the nearby tempo fitting a whole number of beats in `seconds`
+pub fn snap_to_loop(bpm: f32, seconds: f32) -> f32 {
+    let beats = (seconds * bpm / 60.0).round();
+    if beats < 1.0 {
+        return bpm;
+    }
+    let fitted = beats * 60.0 / seconds;
+    if (fitted - bpm).abs() / bpm <= SNAP_TOLERANCE {
+        fitted
+    } else {
+        bpm
+    }
+}
+
+/// Rise of the frame level in dB above `ONSET_DB`, at `FRAME_RATE` frames per second;
+/// steady or slowly swelling sounds give no onsets at all
+fn onset_envelope(samples: &[f32], sample_rate: u32) -> Vec<f32> {
+    let hop = ((sample_rate as f32 / FRAME_RATE).round() as usize).max(1);
+    let levels: Vec<f32> = samples
+        .chunks(hop)
+        .map(|frame| {
+            let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+            20.0 * rms.max(1e-4).log10()
+        })
+        .collect();
+    let mut previous = levels.first().copied().unwrap_or_default();
+    levels
+        .iter()
+        .map(|&level| {
+            let rise = (level - previous - ONSET_DB).max(0.0);
+            previous = level;
+            rise
+        })
+        .collect()
+}
@@ -0,0 +1,541 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::language::syntax::ast::Value;
+
+fn sine(rate: u32, freq: f32, seconds: f32) -> Vec<f32> {
+    let len = (rate as f32 * seconds) as usize;
+    (0..len)
+        .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / rate as f32).sin())
+        .collect()
+}
+
+#[test]
+fn test_resample_output_length() {
+    let input = sine(48_000, 440.0, 0.5);
+    let out = resample(&input, 48_000, 44_100, ResampleQuality::Sinc24);
+    assert_eq!(out.len(), 22_050);
+
+    let linear = resample(&input, 48_000, 44_100, ResampleQuality::Linear2);
+    assert_eq!(linear.len(), out.len());
+}
+
+#[test]
+fn test_resample_same_rate_is_identity() {
+    let input = sine(44_100, 220.0, 0.1);
+    let out = resample(&input, 44_100, 44_100, ResampleQuality::Sinc48);
+    assert_eq!(out, input);
+}
+
+#[test]
+fn test_conversion_is_cached_per_rate_and_quality() {
+    let mut registry = SampleRegistry::new();
+    registry.register_sample(
+        "test://tone".to_string(),
+        SampleData {
+            samples: sine(48_000, 440.0, 0.1),
+            sample_rate: 48_000,
+        },
+    );
+
+    let first = registry
+        .get_sample_at_rate("test://tone", 44_100, ResampleQuality::Sinc24)
+        .unwrap();
+    assert_eq!(first.sample_rate, 44_100);
+    assert_eq!(registry.converted_count(), 1);
+
+    // Same key: served from the cache
+    registry.get_sample_at_rate("test://tone", 44_100, ResampleQuality::Sinc24);
+    assert_eq!(registry.converted_count(), 1);
+
+    // Different quality: converted separately
+    registry.get_sample_at_rate("test://tone", 44_100, ResampleQuality::Linear2);
+    assert_eq!(registry.converted_count(), 2);
+
+    // Re-registering the sample invalidates its conversions
+    registry.register_sample(
+        "test://tone".to_string(),
+        SampleData {
+            samples: sine(48_000, 220.0, 0.1),
+            sample_rate: 48_000,
+        },
+    );
+    assert_eq!(registry.converted_count(), 0);
+}
+
+#[test]
+fn test_identical_audio_shares_one_buffer() {
+    let mut registry = SampleRegistry::new();
+    let tone = || SampleData {
+        samples: sine(48_000, 440.0, 0.1),
+        sample_rate: 48_000,
+    };
+    registry.register_sample("devalang://bank/kit/tone".to_string(), tone());
+    registry.register_sample("/abs/kit/tone.wav".to_string(), tone());
+
+    let stats = registry.stats();
+    assert_eq!((stats.loaded, stats.buffers), (2, 1));
+    assert_eq!(stats.shared_bytes, 4_800 * 4);
+
+    // Conversions are shared too
+    registry.get_sample_at_rate("devalang://bank/kit/tone", 44_100, ResampleQuality::Sinc24);
+    registry.get_sample_at_rate("/abs/kit/tone.wav", 44_100, ResampleQuality::Sinc24);
+    assert_eq!(registry.converted_count(), 1);
+
+    // Re-pointing one alias keeps the buffer for the other
+    registry.register_sample(
+        "/abs/kit/tone.wav".to_string(),
+        SampleData {
+            samples: sine(48_000, 220.0, 0.1),
+            sample_rate: 48_000,
+        },
+    );
+    let stats = registry.stats();
+    assert_eq!((stats.buffers, stats.shared_bytes), (2, 0));
+    assert_eq!(registry.converted_count(), 1);
+    assert_eq!(
+        registry
+            .get_sample("devalang://bank/kit/tone")
+            .unwrap()
+            .samples,
+        tone().samples
+    );
+}
+
+#[test]
+fn test_detects_pitch_of_sine() {
+    let hz = pitch::detect_pitch(&sine(44_100, 130.81, 0.5), 44_100).unwrap();
+    assert!(
+        (pitch::hz_to_midi(hz) - 48.0).abs() < 0.1,
+        "detected {} Hz",
+        hz
+    );
+
+    // Silence has no pitch
+    assert!(pitch::detect_pitch(&vec![0.0; 44_100], 44_100).is_none());
+}
+
+#[test]
+fn test_note_repitches_from_detected_root() {
+    let mut registry = SampleRegistry::new();
+    registry.register_sample(
+        "test://pluck".to_string(),
+        SampleData {
+            samples: sine(44_100, 261.63, 0.5),
+            sample_rate: 44_100,
+        },
+    );
+
+    assert!((registry.root_note("test://pluck") - 60.0).abs() < 0.1);
+
+    // One octave up plays twice as fast
+    let up = registry
+        .get_sample_for_note("test://pluck", 44_100, ResampleQuality::Linear2, Some(72))
+        .unwrap();
+    assert!((up.samples.len() as i64 - 11_025).abs() <= 2);
+    let hz = pitch::detect_pitch(&up.samples, up.sample_rate).unwrap();
+    assert!(
+        (pitch::hz_to_midi(hz) - 72.0).abs() < 0.2,
+        "detected {} Hz",
+        hz
+    );
+}
+
+#[test]
+fn test_repitched_notes_are_cached_per_shift() {
+    let mut registry = SampleRegistry::new();
+    let pluck = SampleData {
+        samples: sine(44_100, 261.63, 0.5),
+        sample_rate: 44_100,
+    };
+    registry.register_sample("test://pluck".to_string(), pluck.clone());
+    registry.register_sample("test://copy".to_string(), pluck);
+
+    let first = registry
+        .get_sample_for_note("test://pluck", 44_100, ResampleQuality::Linear2, Some(67))
+        .unwrap();
+    let again = registry
+        .get_sample_for_note("test://copy", 44_100, ResampleQuality::Linear2, Some(67))
+        .unwrap();
+    assert_eq!(first.samples, again.samples);
+    // Both URIs share the buffer and root, so the shift was computed once
+    assert_eq!(registry.pitched.len(), 1);
+
+    registry
+        .get_sample_for_note("test://pluck", 44_100, ResampleQuality::Linear2, Some(72))
+        .unwrap();
+    assert_eq!(registry.pitched.len(), 2);
+}
+
+#[test]
+fn test_rate_conversions_flag_severe_mismatches() {
+    let rates = vec![
+        ("b".to_string(), 22_050),
+        ("a".to_string(), 48_000),
+        ("c".to_string(), 44_100),
+        ("b".to_string(), 22_050),
+    ];
+    let conversions = rate_conversions(rates, 44_100, ResampleQuality::Sinc24);
+    let uris: Vec<&str> = conversions.iter().map(|c| c.uri.as_str()).collect();
+    assert_eq!(uris, vec!["a", "b"]);
+
+    assert!(!conversions[0].is_severe());
+    assert!(conversions[1].is_severe());
+    // Upsampling cannot add content above the source Nyquist
+    assert_eq!(conversions[1].bandwidth_hz(), 11_025.0);
+    assert!(conversions[1].rolloff_hz() < conversions[1].bandwidth_hz());
+    assert!(
+        conversions[1]
+            .describe()
+            .starts_with("b: 22050 Hz -> 44100 Hz")
+    );
+}
+
+#[test]
+fn test_prepare_samples_preconverts_used_samples() {
+    let uri = "test://prepare/low";
+    register_sample(
+        uri,
+        SampleData {
+            samples: sine(22_050, 220.0, 0.1),
+            sample_rate: 22_050,
+        },
+    );
+
+    let conversions = prepare_samples(&[uri.to_string()], 48_000, ResampleQuality::Sinc12, true);
+    assert_eq!(conversions.len(), 1);
+    assert_eq!(conversions[0].source_rate, 22_050);
+
+    let registry = SAMPLE_REGISTRY.lock().unwrap();
+    let key = (registry.aliases[uri], 48_000, ResampleQuality::Sinc12);
+    assert!(registry.converted.contains_key(&key));
+}
+
+#[test]
+fn test_prepare_trims_converts_and_normalizes() {
+    use super::prepare::{PrepareOptions, prepare, silence_bounds};
+
+    let mut samples = vec![0.0f32; 4_800];
+    samples.extend(sine(48_000, 440.0, 0.25).iter().map(|s| s * 0.25));
+    samples.extend(vec![0.0001f32; 4_800]);
+    assert_eq!(silence_bounds(&samples, -60.0).unwrap().0, 4_801);
+
+    let data = SampleData {
+        samples,
+        sample_rate: 48_000,
+    };
+    let options = PrepareOptions {
+        sample_rate: 44_100,
+        quality: ResampleQuality::Sinc24,
+        trim_threshold_db: Some(-60.0),
+        normalize_peak_db: Some(-1.0),
+    };
+    let prepared = prepare(&data, &options);
+
+    assert_eq!(prepared.sample_rate, 44_100);
+    // A quarter second of tone is left once both silent ends are gone
+    let seconds = prepared.samples.len() as f32 / 44_100.0;
+    assert!((seconds - 0.25).abs() < 0.002, "{seconds}");
+    let peak = prepared.samples.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+    assert!((peak - 10f32.powf(-1.0 / 20.0)).abs() < 1e-3, "{peak}");
+}
+
+#[test]
+fn test_prepare_bank_manifest() {
+    use super::prepare::{bank_manifest, trigger_name};
+
+    assert_eq!(trigger_name("Kick 01"), "kick_01");
+    assert_eq!(trigger_name("808-Snare!"), "_808_snare");
+    assert_eq!(trigger_name("--"), "sample");
+
+    let manifest = bank_manifest(
+        "drums",
+        "local",
+        "audio/",
+        &[("kick_01".to_string(), "kick_01.wav".to_string())],
+    );
+    let parsed: BankManifest = toml::from_str(&manifest).unwrap();
+    assert_eq!(parsed.bank.audio_path, "audio/");
+    assert_eq!(parsed.triggers[0].name, "kick_01");
+    assert_eq!(parsed.triggers[0].path, "./kick_01.wav");
+}
+
+#[test]
+fn test_choke_groups_are_read_from_the_manifest() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("devalang_choke_{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join("bank.toml"),
+        r#"
+[bank]
+name = "808"
+publisher = "devaloop"
+audio_path = "audio/"
+
+[[triggers]]
+name = "hatOpen"
+path = "./oh.wav"
+choke = "hats"
+
+[[triggers]]
+name = "hatClosed"
+path = "./ch.wav"
+choke = "hats"
+
+[[triggers]]
+name = "kick"
+path = "./kick.wav"
+"#,
+    )?;
+    let metadata = read_bank_metadata(&dir);
+    let _ = fs::remove_dir_all(&dir);
+
+    let mut registry = SampleRegistry::new();
+    registry.register_bank_metadata(metadata?);
+    assert_eq!(
+        registry.choke_group("devalang://bank/devaloop.808/hatOpen"),
+        Some("devaloop.808/hats".to_string())
+    );
+    assert_eq!(
+        registry.choke_group("devalang://bank/devaloop.808/hatClosed"),
+        registry.choke_group("devalang://bank/devaloop.808/hatOpen")
+    );
+    assert_eq!(
+        registry.choke_group("devalang://bank/devaloop.808/kick"),
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn test_variants_are_shared_through_the_cache_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    let tone = SampleData {
+        samples: sine(44_100, 440.0, 0.05),
+        sample_rate: 44_100,
+    };
+    let mut first = SampleRegistry::new();
+    first.variant_cache = Some(dir.path().to_path_buf());
+    first.register_sample("test://tone".to_string(), tone.clone());
+    let reversed = first
+        .get_sample_variant(
+            "test://tone",
+            44_100,
+            ResampleQuality::Sinc24,
+            SampleVariant::Reversed,
+        )
+        .unwrap();
+    assert_eq!(reversed.samples.first(), tone.samples.last());
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // A later build reads the file written by the first one
+    let path = fs::read_dir(dir.path())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let marked = SampleData {
+        samples: vec![0.5; tone.samples.len()],
+        sample_rate: 44_100,
+    };
+    variants::write_cached(&path, &marked).unwrap();
+    let mut second = SampleRegistry::new();
+    second.variant_cache = Some(dir.path().to_path_buf());
+    second.register_sample("test://tone".to_string(), tone);
+    let cached = second
+        .get_sample_variant(
+            "test://tone",
+            44_100,
+            ResampleQuality::Sinc24,
+            SampleVariant::Reversed,
+        )
+        .unwrap();
+    assert_eq!(cached.samples, marked.samples);
+}
+
+/// Decaying noise bursts on every beat of `bars` 4/4 bars at `bpm`
+fn drum_loop(rate: u32, bpm: f32, bars: usize) -> Vec<f32> {
+    let beat = (60.0 / bpm * rate as f32) as usize;
+    let mut seed = 1u32;
+    (0..beat * 4 * bars)
+        .map(|i| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let noise = (seed >> 16) as f32 / 32_768.0 - 1.0;
+            noise * (-((i % beat) as f32) / (rate as f32 * 0.03)).exp()
+        })
+        .collect()
+}
+
+#[test]
+fn test_detects_the_tempo_of_a_loop() {
+    for bpm in [92.0, 120.0, 140.0] {
+        let detected = tempo::detect_tempo(&drum_loop(22_050, bpm, 2), 22_050).unwrap();
+        assert!((detected - bpm).abs() < 0.5, "{detected} vs {bpm}");
+    }
+    // One-shots and steady tones have no tempo
+    assert!(tempo::detect_tempo(&drum_loop(22_050, 120.0, 0), 22_050).is_none());
+    assert!(tempo::detect_tempo(&vec![0.0; 22_050 * 4], 22_050).is_none());
+    assert!(tempo::detect_tempo(&sine(22_050, 220.0, 4.0), 22_050).is_none());
+    // A tempo close to fitting the loop is snapped to it
+    assert_eq!(tempo::snap_to_loop(119.0, 4.0), 120.0);
+    assert_eq!(tempo::snap_to_loop(110.0, 4.0), 110.0);
+}
+
+#[test]
+fn test_declared_tempo_wins_over_detection() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("devalang_tempo_{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join("bank.toml"),
+        "[bank]\nname = \"loops\"\npublisher = \"devaloop\"\naudio_path = \"audio/\"\n\n[[triggers]]\nname = \"brk\"\npath = \"./brk.wav\"\nbpm = 174\n",
+    )?;
+    let metadata = read_bank_metadata(&dir);
+    let _ = fs::remove_dir_all(&dir);
+
+    let mut registry = SampleRegistry::new();
+    registry.register_bank_metadata(metadata?);
+    assert_eq!(
+        registry.tempo("devalang://bank/devaloop.loops/brk"),
+        Some(174.0)
+    );
+
+    registry.register_sample(
+        "loop.wav".to_string(),
+        SampleData {
+            samples: drum_loop(22_050, 100.0, 2),
+            sample_rate: 22_050,
+        },
+    );
+    let detected = registry.tempo("loop.wav").unwrap();
+    assert!((detected - 100.0).abs() < 0.5, "{detected}");
+    Ok(())
+}
+
+#[test]
+fn test_loop_tempo_and_length_are_resolved_once() {
+    let uri = "test://sync_loop";
+    let samples = drum_loop(22_050, 100.0, 2);
+    let seconds = samples.len() as f32 / 22_050.0;
+    register_sample(
+        uri,
+        SampleData {
+            samples,
+            sample_rate: 22_050,
+        },
+    );
+
+    let resolved = sample_loop(uri).unwrap();
+    assert!((resolved.bpm - 100.0).abs() < 0.5, "{:?}", resolved);
+    assert_eq!(resolved.seconds, seconds);
+    assert_eq!(
+        SAMPLE_REGISTRY.lock().unwrap().loops.get(uri),
+        Some(&Some(resolved))
+    );
+    assert_eq!(sample_loop(uri), Some(resolved));
+    assert_eq!(sample_loop("test://no_such_loop"), None);
+}
+
+#[test]
+fn test_sample_seconds_reads_the_registered_length() {
+    register_sample(
+        "test://half_second",
+        SampleData {
+            samples: vec![0.0; 11_025],
+            sample_rate: 22_050,
+        },
+    );
+    assert_eq!(sample_seconds("test://half_second"), Some(0.5));
+    assert_eq!(sample_seconds("test://not_registered"), None);
+}
+
+#[test]
+fn test_trigger_metadata_is_added_without_touching_the_rest() {
+    let manifest = "# my loops\n[bank]\nname = \"loops\"\n\n[[triggers]]\nname = \"brk\"\npath = \"./brk.wav\"\n\n[[triggers]]\nname = \"pad\"\npath = \"./pad.wav\"\nroot = \"A2\" # tuned by ear\n";
+    let entries = HashMap::from([
+        ("brk".to_string(), vec![("bpm", "174".to_string())]),
+        (
+            "pad".to_string(),
+            vec![("bpm", "90".to_string()), ("root", "\"C3\"".to_string())],
+        ),
+    ]);
+
+    assert_eq!(
+        prepare::set_trigger_metadata(manifest, &entries, false),
+        "# my loops\n[bank]\nname = \"loops\"\n\n[[triggers]]\nname = \"brk\"\npath = \"./brk.wav\"\nbpm = 174\n\n[[triggers]]\nname = \"pad\"\npath = \"./pad.wav\"\nroot = \"A2\" # tuned by ear\nbpm = 90\n"
+    );
+    let forced = prepare::set_trigger_metadata(manifest, &entries, true);
+    assert!(forced.contains("root = \"C3\"\n") && !forced.contains("A2"));
+
+    let analysis = prepare::TriggerAnalysis {
+        bpm: Some(120.004),
+        root: Some("C3".to_string()),
+    };
+    assert_eq!(
+        analysis.entries(),
+        vec![("bpm", "120".to_string()), ("root", "\"C3\"".to_string())]
+    );
+}
+
+#[test]
+fn test_bank_preparation_reports_only_started_steps() {
+    let idle = BankPreparation::start(44_100, ResampleQuality::Linear2, false, false);
+    assert!(idle.is_empty());
+    assert_eq!(idle.finish().unwrap(), BankPreparationReport::default());
+
+    let preparation = BankPreparation::start(44_100, ResampleQuality::Linear2, true, false);
+    assert!(!preparation.is_empty());
+    let report = preparation.finish().unwrap();
+    assert!(report.converted.is_some());
+    assert_eq!(report.variants, None);
+}
+
+#[test]
+fn test_sync_auto_stretches_a_loop_from_its_own_tempo() -> Result<()> {
+    use crate::engine::audio::events::AudioEvent;
+    use crate::engine::audio::samples::{self, SampleData};
+
+    // Two bars of clicks at 100 BPM (4.8 seconds)
+    let rate = 8000;
+    let beat = (0.6 * rate as f32) as usize;
+    let clicks: Vec<f32> = (0..beat * 8)
+        .map(|i| (-((i % beat) as f32) / 200.0).exp() * if i % 2 == 0 { 1.0 } else { -1.0 })
+        .collect();
+    samples::register_sample(
+        "sync_auto_loop.wav",
+        SampleData {
+            samples: clicks,
+            sample_rate: rate,
+        },
+    );
+
+    let statements = crate::language::syntax::parser::driver::parse(
+        "bpm 120\n.kit.brk sync: auto\n.kit.brk end: 50% sync: auto\n",
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(rate);
+    let mut kit = std::collections::HashMap::new();
+    kit.insert(
+        "brk".to_string(),
+        Value::String("sync_auto_loop.wav".to_string()),
+    );
+    interp.variables.insert("kit".to_string(), Value::Map(kit));
+    interp.collect_events(&statements)?;
+
+    let syncs: Vec<f32> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Sample { region, .. } => region.and_then(|region| region.sync),
+            _ => None,
+        })
+        .collect();
+    // Two bars at 120 BPM last 4 seconds; half the loop, one bar
+    assert_eq!(syncs.len(), 2);
+    assert!((syncs[0] - 4.0).abs() < 0.01, "{syncs:?}");
+    assert!((syncs[1] - 2.0).abs() < 0.01, "{syncs:?}");
+
+    let printed = crate::language::syntax::printer::print_statements(&statements);
+    assert!(printed.contains(".kit.brk sync: auto"), "{printed}");
+    Ok(())
+}
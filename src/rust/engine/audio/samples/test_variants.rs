@@ -0,0 +1,101 @@
+use super::*;
+
+fn effect(kind: &str, key: &str, value: Value) -> Value {
+    let mut map = HashMap::new();
+    map.insert("type".to_string(), Value::String(kind.to_string()));
+    map.insert(key.to_string(), value);
+    Value::Map(map)
+}
+
+#[test]
+fn test_only_a_leading_cached_effect_is_split_off() {
+    let reverb = effect("reverb", "mix", Value::Number(0.3));
+    let chain = Value::Array(vec![
+        effect("reverse", "reverse", Value::Boolean(true)),
+        reverb.clone(),
+    ]);
+    assert_eq!(
+        split_leading_variant(&chain),
+        Some((SampleVariant::Reversed, Value::Array(vec![reverb.clone()])))
+    );
+    assert_eq!(
+        split_leading_variant(&effect("speed", "value", Value::Number(0.5))),
+        Some((SampleVariant::HalfSpeed, Value::Array(Vec::new())))
+    );
+
+    // `-> reverse(true)` on its own
+    let lone = Value::Map(HashMap::from([(
+        "reverse".to_string(),
+        Value::Boolean(true),
+    )]));
+    assert_eq!(
+        split_leading_variant(&lone),
+        Some((SampleVariant::Reversed, Value::Array(Vec::new())))
+    );
+
+    // Other speeds, disabled reverses and effects later in the chain run as before
+    assert!(split_leading_variant(&effect("speed", "speed", Value::Number(1.5))).is_none());
+    assert!(split_leading_variant(&effect("reverse", "reverse", Value::Boolean(false))).is_none());
+    let later = Value::Array(vec![reverb, effect("speed", "speed", Value::Number(2.0))]);
+    assert!(split_leading_variant(&later).is_none());
+}
+
+#[test]
+fn test_variants_match_the_trigger_effects() {
+    let data = SampleData {
+        samples: vec![0.0, 0.25, 0.5, 0.75],
+        sample_rate: 44_100,
+    };
+    assert_eq!(
+        SampleVariant::Reversed.apply(&data).samples,
+        vec![0.75, 0.5, 0.25, 0.0]
+    );
+    // Speed changes keep the sample's length, like the `speed` effect on a trigger
+    let mut expected = data.samples.clone();
+    SpeedProcessor::new(2.0).process(&mut expected, data.sample_rate);
+    assert_eq!(SampleVariant::DoubleSpeed.apply(&data).samples, expected);
+}
+
+#[test]
+fn test_cached_variants_round_trip_and_follow_the_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let data = SampleData {
+        samples: vec![0.1, -0.2, 0.3],
+        sample_rate: 48_000,
+    };
+    let path = cache_path(
+        dir.path(),
+        &data,
+        ResampleQuality::Sinc24,
+        SampleVariant::Reversed,
+    );
+    let reversed = SampleVariant::Reversed.apply(&data);
+    write_cached(&path, &reversed).unwrap();
+    assert_eq!(
+        read_cached(&path, 48_000).unwrap().samples,
+        reversed.samples
+    );
+
+    let edited = SampleData {
+        samples: vec![0.1, -0.2, 0.4],
+        sample_rate: 48_000,
+    };
+    assert_ne!(
+        cache_path(
+            dir.path(),
+            &edited,
+            ResampleQuality::Sinc24,
+            SampleVariant::Reversed
+        ),
+        path
+    );
+    assert_ne!(
+        cache_path(
+            dir.path(),
+            &data,
+            ResampleQuality::Sinc24,
+            SampleVariant::HalfSpeed
+        ),
+        path
+    );
+}
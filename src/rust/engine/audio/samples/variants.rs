@@ -0,0 +1,173 @@
+//! Cached reverse and speed variants of samples
+//!
+//! A trigger whose effect chain starts with `reverse`, `speed(0.5)` or `speed(2)` plays
+//! the whole sample through that effect before anything else, so the result only depends
+//! on the sample. The registry keeps these variants per sample and rate instead of
+//! recomputing them on every render. With `audio.sample_variants` they are generated
+//! when banks load and written to `.deva/cache/samples`, so later builds read them back.
+
+use super::SampleData;
+use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
+use crate::engine::audio::effects::processors::{ReverseProcessor, SpeedProcessor};
+use crate::engine::audio::settings::ResampleQuality;
+use crate::language::syntax::ast::Value;
+use crate::platform::storage::atomic::write_atomic;
+use anyhow::{Result, bail};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SampleVariant {
+    Reversed,
+    HalfSpeed,
+    DoubleSpeed,
+}
+
+impl SampleVariant {
+    pub const ALL: [SampleVariant; 3] = [
+        SampleVariant::Reversed,
+        SampleVariant::HalfSpeed,
+        SampleVariant::DoubleSpeed,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            SampleVariant::Reversed => "reversed",
+            SampleVariant::HalfSpeed => "half-speed",
+            SampleVariant::DoubleSpeed => "double-speed",
+        }
+    }
+
+    /// The variant one effect entry produces, when it is a cached one
+    pub fn from_effect(effect: &Value) -> Option<Self> {
+        let (kind, params) = match effect {
+            Value::String(kind) | Value::Identifier(kind) => (kind.as_str(), None),
+            Value::Map(map) => match map.get("type").or_else(|| map.get("effect")) {
+                Some(Value::String(kind) | Value::Identifier(kind)) => (kind.as_str(), Some(map)),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let param =
+            |keys: [&str; 2]| params.and_then(|map| keys.iter().find_map(|key| map.get(*key)));
+        match kind {
+            "reverse" => match param(["reverse", "value"]) {
+                None | Some(Value::Boolean(true)) => Some(SampleVariant::Reversed),
+                _ => None,
+            },
+            "speed" => match param(["speed", "value"]) {
+                Some(Value::Number(speed)) if *speed == 0.5 => Some(SampleVariant::HalfSpeed),
+                Some(Value::Number(speed)) if *speed == 2.0 => Some(SampleVariant::DoubleSpeed),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Run the effect over the whole sample, exactly as the trigger chain would
+    pub fn apply(self, data: &SampleData) -> SampleData {
+        let mut samples = data.samples.clone();
+        match self {
+            SampleVariant::Reversed => {
+                ReverseProcessor::new(true).process(&mut samples, data.sample_rate)
+            }
+            SampleVariant::HalfSpeed => {
+                SpeedProcessor::new(0.5).process(&mut samples, data.sample_rate)
+            }
+            SampleVariant::DoubleSpeed => {
+                SpeedProcessor::new(2.0).process(&mut samples, data.sample_rate)
+            }
+        }
+        SampleData {
+            samples,
+            sample_rate: data.sample_rate,
+        }
+    }
+}
+
+/// Split a cached variant off the front of a trigger's effects: the variant and the
+/// effects still to run on it
+pub fn split_leading_variant(effects: &Value) -> Option<(SampleVariant, Value)> {
+    match effects {
+        Value::Array(chain) => {
+            let variant = SampleVariant::from_effect(chain.first()?)?;
+            Some((variant, Value::Array(chain[1..].to_vec())))
+        }
+        // A lone `-> reverse(true)` is collected as `{ "reverse": true }`; maps holding
+        // several effects have no order, so they are left to the chain
+        Value::Map(map)
+            if map.len() == 1 && !map.contains_key("type") && !map.contains_key("effect") =>
+        {
+            let (kind, value) = map.iter().next()?;
+            let mut entry = match value {
+                Value::Map(params) => params.clone(),
+                other => HashMap::from([("value".to_string(), other.clone())]),
+            };
+            entry.insert("type".to_string(), Value::String(kind.clone()));
+            let variant = SampleVariant::from_effect(&Value::Map(entry))?;
+            Some((variant, Value::Array(Vec::new())))
+        }
+        single => Some((
+            SampleVariant::from_effect(single)?,
+            Value::Array(Vec::new()),
+        )),
+    }
+}
+
+/// File in `dir` holding `variant` of `source`. Named after the source audio, so an edited
+/// sample never reads a stale variant.
+pub fn cache_path(
+    dir: &Path,
+    source: &SampleData,
+    quality: ResampleQuality,
+    variant: SampleVariant,
+) -> PathBuf {
+    let mut hasher = Sha256::new();
+    for sample in &source.samples {
+        hasher.update(sample.to_le_bytes());
+    }
+    hasher.update(source.sample_rate.to_le_bytes());
+    hasher.update(quality.to_string().as_bytes());
+    let digest: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    dir.join(format!("{}-{}.f32", &digest[..32], variant.name()))
+}
+
+/// Write a variant as raw little-endian f32 samples
+pub fn write_cached(path: &Path, data: &SampleData) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let bytes: Vec<u8> = data
+        .samples
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes())
+        .collect();
+    // Play and build sessions generate variants side by side
+    write_atomic(path, bytes)?;
+    Ok(())
+}
+
+/// Read a variant written by `write_cached`
+pub fn read_cached(path: &Path, sample_rate: u32) -> Result<SampleData> {
+    let bytes = fs::read(path)?;
+    if bytes.len() % 4 != 0 {
+        bail!("truncated sample cache file {}", path.display());
+    }
+    Ok(SampleData {
+        samples: bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect(),
+        sample_rate,
+    })
+}
+
+#[cfg(test)]
+#[path = "test_variants.rs"]
+mod tests;
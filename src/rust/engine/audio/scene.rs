@@ -0,0 +1,60 @@
+//! Scenes: named sets of groups and patterns played together
+//!
+//! Declared with `scene verse = [drums, bass]` and started with `switch verse`, which
+//! runs every member from the cursor at once. `switch chorus over 4 bars` asks live
+//! playback to crossfade from the scene of the playing loop to the new one instead of
+//! cutting at the loop boundary; offline builds play the scene the same either way.
+
+use std::f32::consts::FRAC_PI_2;
+
+/// Scene most recently started by `switch`, carried with a build's artifacts
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneCue {
+    pub scene: String,
+    /// Crossfade length requested with `over`, in seconds (0 switches at the loop end)
+    pub fade_seconds: f32,
+}
+
+impl SceneCue {
+    pub fn new(scene: impl Into<String>, fade_seconds: f32) -> Self {
+        Self {
+            scene: scene.into(),
+            fade_seconds: fade_seconds.max(0.0),
+        }
+    }
+
+    /// Crossfade to use when live playback moves from a build cued with `previous`
+    /// to this one: only a change of scene with a fade length crossfades
+    pub fn transition_from(&self, previous: Option<&SceneCue>) -> Option<f32> {
+        let previous = previous?;
+        (previous.scene != self.scene && self.fade_seconds > 0.0).then_some(self.fade_seconds)
+    }
+}
+
+/// Equal-power (outgoing, incoming) gains at `position` (0..=1) through a crossfade
+pub fn crossfade_gains(position: f32) -> (f32, f32) {
+    let angle = position.clamp(0.0, 1.0) * FRAC_PI_2;
+    (angle.cos(), angle.sin())
+}
+
+/// Blend the start of interleaved `incoming` with `outgoing`, which holds the audio the
+/// old scene would have played from the switch on. The blend lasts `outgoing.len()`
+/// samples (or all of `incoming` when it is shorter).
+pub fn mix_transition(outgoing: &[f32], incoming: &mut [f32], channels: usize) {
+    let channels = channels.max(1);
+    let frames = outgoing.len().min(incoming.len()) / channels;
+    if frames == 0 {
+        return;
+    }
+    for frame in 0..frames {
+        let (fade_out, fade_in) = crossfade_gains((frame + 1) as f32 / frames as f32);
+        for channel in 0..channels {
+            let i = frame * channels + channel;
+            incoming[i] = outgoing[i] * fade_out + incoming[i] * fade_in;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_scene.rs"]
+mod tests;
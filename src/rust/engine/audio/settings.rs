@@ -10,7 +10,8 @@ pub enum AudioFormat {
     Mp3,
     Wav,
     Flac,
-    Mid, // MIDI format
+    Alac, // Apple Lossless in an .m4a container
+    Mid,  // MIDI format
 }
 
 impl Default for AudioFormat {
@@ -25,12 +26,16 @@ impl AudioFormat {
             AudioFormat::Mp3 => "mp3",
             AudioFormat::Wav => "wav",
             AudioFormat::Flac => "flac",
+            AudioFormat::Alac => "alac",
             AudioFormat::Mid => "mid",
         }
     }
 
     pub fn file_extension(self) -> &'static str {
-        self.label()
+        match self {
+            AudioFormat::Alac => "m4a",
+            _ => self.label(),
+        }
     }
 
     /// Parse format from string
@@ -39,6 +44,7 @@ impl AudioFormat {
             "mp3" => Some(AudioFormat::Mp3),
             "wav" => Some(AudioFormat::Wav),
             "flac" => Some(AudioFormat::Flac),
+            "alac" | "m4a" => Some(AudioFormat::Alac),
             "mid" | "midi" => Some(AudioFormat::Mid),
             _ => None,
         }
@@ -101,7 +107,7 @@ impl AudioChannels {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "cli", derive(ValueEnum))]
 #[cfg_attr(feature = "cli", clap(rename_all = "kebab-case"))]
 pub enum ResampleQuality {
@@ -134,6 +140,36 @@ impl ResampleQuality {
             ResampleQuality::Sinc512 => "512-point sinc",
         }
     }
+
+    /// Number of interpolation points used by the resampler
+    pub fn taps(self) -> usize {
+        match self {
+            ResampleQuality::Linear2 => 2,
+            ResampleQuality::Sinc12 => 12,
+            ResampleQuality::Sinc24 => 24,
+            ResampleQuality::Sinc48 => 48,
+            ResampleQuality::Sinc96 => 96,
+            ResampleQuality::Sinc192 => 192,
+            ResampleQuality::Sinc512 => 512,
+        }
+    }
+
+    /// Rough alias/image rejection of the resampling filter in dB; the Blackman-windowed
+    /// sinc bottoms out around -74 dB however many points it uses
+    pub fn estimated_rejection_db(self) -> f32 {
+        match self {
+            ResampleQuality::Linear2 => -25.0,
+            ResampleQuality::Sinc12 => -45.0,
+            ResampleQuality::Sinc24 => -58.0,
+            ResampleQuality::Sinc48 => -68.0,
+            _ => -74.0,
+        }
+    }
+
+    /// Width of the filter's transition band as a fraction of the lower Nyquist frequency
+    pub fn transition_fraction(self) -> f32 {
+        (6.0 / self.taps() as f32).min(1.0)
+    }
 }
 
 impl fmt::Display for ResampleQuality {
@@ -141,3 +177,110 @@ impl fmt::Display for ResampleQuality {
         f.write_str(self.label())
     }
 }
+
+/// Accumulator used when summing voices and inserts (output is always converted to f32)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MixPrecision {
+    #[default]
+    F32,
+    /// Double-precision summing, for dense mixes where f32 rounding error adds up
+    F64,
+}
+
+impl MixPrecision {
+    pub fn label(self) -> &'static str {
+        match self {
+            MixPrecision::F32 => "f32",
+            MixPrecision::F64 => "f64",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "f32" => Some(MixPrecision::F32),
+            "f64" | "double" | "64" => Some(MixPrecision::F64),
+            _ => None,
+        }
+    }
+}
+
+/// Frames processed per block when running insert effect chains
+pub const DEFAULT_BLOCK_SIZE: usize = 512;
+
+/// Internal mixing options (`audio.block_size`, `audio.mix_precision`,
+/// `audio.retrigger_fade_ms` in the config)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MixSettings {
+    pub block_size: usize,
+    pub precision: MixPrecision,
+    /// Crossfade between retriggers of a sample that still rings; 0 turns it off
+    pub retrigger_fade_ms: u32,
+}
+
+impl Default for MixSettings {
+    fn default() -> Self {
+        Self {
+            block_size: DEFAULT_BLOCK_SIZE,
+            precision: MixPrecision::default(),
+            retrigger_fade_ms: crate::engine::audio::retrigger::DEFAULT_RETRIGGER_FADE_MS,
+        }
+    }
+}
+
+/// Bounds on one render, for servers rendering projects they did not write
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderLimits {
+    /// Longest audio the project may render; checked before rendering starts
+    pub max_audio_seconds: Option<f64>,
+    /// Wall-clock time collecting the events and rendering them may take
+    pub max_render_time: Option<std::time::Duration>,
+}
+
+/// Export format for the print timeline produced during offline builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "cli", clap(rename_all = "lower"))]
+pub enum LogTimelineFormat {
+    Json,
+}
+
+impl LogTimelineFormat {
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            LogTimelineFormat::Json => "json",
+        }
+    }
+}
+
+/// Extra files a build writes next to the audio (`--outputs`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "cli", clap(rename_all = "kebab-case"))]
+pub enum BuildOutput {
+    /// The collected events as JSON (`events/<module>.json`, see `event_export`)
+    EventsJson,
+}
+
+/// Where the metronome (`--click`, `metronome on`) ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "cli", clap(rename_all = "lower"))]
+pub enum ClickMode {
+    /// Mixed into the rendered audio
+    Mix,
+    /// Written next to the audio as `<module>.click.wav`
+    Stem,
+    /// Heard during `play` only; exports stay clean
+    Monitor,
+}
+
+impl ClickMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "mix" => Some(ClickMode::Mix),
+            "stem" => Some(ClickMode::Stem),
+            "monitor" => Some(ClickMode::Monitor),
+            _ => None,
+        }
+    }
+}
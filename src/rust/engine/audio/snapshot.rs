@@ -0,0 +1,172 @@
+//! Parameter snapshots: capture every automatable value, then morph between captures
+//!
+//! ```deva
+//! snapshot save calm
+//! automate lead.cutoff: 4000
+//! snapshot save wild
+//! snapshot morph calm -> wild over 8 beats curve smooth
+//! ```
+//!
+//! A snapshot holds the value each `owner.param` has at the cursor: synth filter cutoff
+//! and resonance, synth options read by automation (drive, tone, vibrato, ...), channel
+//! strips and anything an `automate` statement drives at that moment. A morph becomes
+//! one automation segment per parameter starting at the cursor, so notes, strips and MIDI
+//! CC export follow it like any other automation. A parameter missing from one of the
+//! two snapshots morphs from (or to) its value when the morph starts.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::engine::audio::automation::{
+    AutomationCurve, AutomationParam, AutomationRegistry, SegmentMode,
+};
+use crate::engine::audio::events::SynthDefinition;
+use crate::engine::audio::mixer::ChannelStrip;
+use crate::engine::audio::synth::pitch::{PITCH_ENV_OPTIONS, VIBRATO_OPTIONS};
+use anyhow::{Result, anyhow};
+
+/// Synth options the note extractor reads from automation
+const SYNTH_OPTIONS: [&str; 3] = ["drive", "tone", "decay"];
+
+/// Values of every automatable parameter at one point of the timeline
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParamSnapshot {
+    values: BTreeMap<(String, String), f32>,
+}
+
+impl ParamSnapshot {
+    /// Capture the parameters of `synths` and `strips`, then the automated values at
+    /// `time` (seconds) on top
+    pub fn capture(
+        synths: &HashMap<String, SynthDefinition>,
+        strips: &HashMap<String, ChannelStrip>,
+        automation: &AutomationRegistry,
+        time: f32,
+    ) -> Self {
+        let mut snapshot = Self::default();
+        for (name, synth) in synths {
+            if let Some(filter) = synth.filters.first() {
+                snapshot.set(name, "cutoff", filter.cutoff);
+                snapshot.set(name, "resonance", filter.resonance);
+            }
+            let options = VIBRATO_OPTIONS
+                .iter()
+                .chain(&PITCH_ENV_OPTIONS)
+                .map(|(option, _)| *option)
+                .chain(SYNTH_OPTIONS);
+            for option in options {
+                if let Some(value) = synth.options.get(option) {
+                    snapshot.set(name, option, *value);
+                }
+            }
+        }
+        for (group, strip) in strips {
+            snapshot.set(group, "gain", strip.gain);
+            snapshot.set(group, "pan", strip.pan);
+            if let Some(lowcut) = strip.lowcut {
+                snapshot.set(group, "lowcut", lowcut);
+            }
+        }
+        for target in automation.targets() {
+            let Some(envelope) = automation.envelope(&target) else {
+                continue;
+            };
+            let params = envelope
+                .params
+                .iter()
+                .map(|p| &p.param_name)
+                .chain(envelope.formulas.iter().map(|f| &f.param_name));
+            for param in params {
+                if let Some(value) = envelope.get_value(param, time) {
+                    snapshot.set(&target, param, value);
+                }
+            }
+        }
+        snapshot
+    }
+
+    pub fn set(&mut self, owner: &str, param: &str, value: f32) {
+        self.values
+            .insert((owner.to_string(), param.to_string()), value);
+    }
+
+    pub fn get(&self, owner: &str, param: &str) -> Option<f32> {
+        self.values
+            .get(&(owner.to_string(), param.to_string()))
+            .copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// `(owner, param, value)` in owner then parameter order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, f32)> {
+        self.values
+            .iter()
+            .map(|((owner, param), value)| (owner.as_str(), param.as_str(), *value))
+    }
+}
+
+/// Curve of a morph: `linear` (default), `smooth`, `exp`, `$curve.in`, ... Hold and
+/// step would only jump at one end, so they are refused.
+pub fn parse_morph_curve(name: &str) -> Result<SegmentMode> {
+    match SegmentMode::parse(name) {
+        Some(SegmentMode::Hold | SegmentMode::Step) | None => {
+            Err(anyhow!("invalid snapshot morph curve '{}'", name))
+        }
+        Some(mode) => Ok(mode),
+    }
+}
+
+/// Automation segments morphing `from` into `to` over `length` seconds from `start`,
+/// as `(owner, segment)` pairs. `live` fills in parameters only one side captured.
+pub fn morph_segments(
+    from: &ParamSnapshot,
+    to: &ParamSnapshot,
+    live: &ParamSnapshot,
+    start: f32,
+    length: f32,
+    mode: SegmentMode,
+) -> Vec<(String, AutomationParam)> {
+    let mut keys: Vec<&(String, String)> = from.values.keys().chain(to.values.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let ease = match mode {
+        SegmentMode::Curve(curve) => Some(curve),
+        _ => None,
+    };
+    keys.into_iter()
+        .filter_map(|key| {
+            let (owner, param) = key;
+            let value = |snapshot: &ParamSnapshot| {
+                snapshot
+                    .values
+                    .get(key)
+                    .or_else(|| live.values.get(key))
+                    .copied()
+            };
+            let (from_value, to_value) = (value(from)?, value(to)?);
+            Some((
+                owner.clone(),
+                AutomationParam {
+                    param_name: param.clone(),
+                    from_value,
+                    to_value,
+                    start_time: start,
+                    duration: length.max(0.0),
+                    curve: AutomationCurve::Linear,
+                    ease,
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "test_snapshot.rs"]
+mod tests;
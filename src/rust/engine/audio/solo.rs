@@ -0,0 +1,72 @@
+//! Solo and mute state for groups and their inserts
+//!
+//! Set from group headers (`group drums solo:`) or from the CLI (`--solo drums --mute pads`).
+//! Paths use the insert notation, outermost group first (`drums/fills`): muting or
+//! soloing a group covers the groups nested in it. Once any declared group is soloed,
+//! everything outside the soloed groups is silent; a mute always wins over a solo.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SoloMute {
+    #[serde(default)]
+    pub solo: BTreeSet<String>,
+    #[serde(default)]
+    pub mute: BTreeSet<String>,
+}
+
+impl SoloMute {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_names(solo: &[String], mute: &[String]) -> Self {
+        Self {
+            solo: solo.iter().cloned().collect(),
+            mute: mute.iter().cloned().collect(),
+        }
+    }
+
+    /// Read a saved set; `None` when the file is missing or unreadable
+    pub fn load(path: &Path) -> Option<Self> {
+        let raw = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&raw).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let raw = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, raw).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solo.is_empty() && self.mute.is_empty()
+    }
+
+    /// True when `path` or one of the groups enclosing it is muted
+    pub fn is_muted(&self, path: &str) -> bool {
+        path.split('/').any(|group| self.mute.contains(group))
+    }
+
+    /// True when `path` or one of the groups enclosing it is soloed
+    pub fn is_soloed(&self, path: &str) -> bool {
+        path.split('/').any(|group| self.solo.contains(group))
+    }
+
+    /// True when one of the soloed names is among `groups`. Solos naming nothing that
+    /// exists are ignored so a typo does not silence the whole project.
+    pub fn solo_active<'a>(&self, groups: impl IntoIterator<Item = &'a String>) -> bool {
+        groups.into_iter().any(|group| self.solo.contains(group))
+    }
+
+    /// Whether the insert at `path` is heard, given whether any solo is active
+    pub fn is_audible(&self, path: &str, solo_active: bool) -> bool {
+        !self.is_muted(path) && (!solo_active || self.is_soloed(path))
+    }
+}
+
+#[cfg(test)]
+#[path = "test_solo.rs"]
+mod tests;
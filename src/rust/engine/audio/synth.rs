@@ -1,17 +1,25 @@
 /// Audio synthesis utilities - oscillators and envelopes
+pub mod pitch;
 pub mod types;
 
+use crate::engine::curves::{CurveType, evaluate_curve};
 use std::f32::consts::PI;
 
 /// Generate a single sample from an oscillator
 pub fn oscillator_sample(waveform: &str, frequency: f32, time: f32) -> f32 {
-    let phase = 2.0 * PI * frequency * time;
+    oscillator_at_phase(waveform, frequency * time)
+}
+
+/// Generate a single sample from an oscillator `phase` cycles in; voices whose pitch
+/// moves accumulate their phase and read it here
+pub fn oscillator_at_phase(waveform: &str, phase: f32) -> f32 {
+    let angle = 2.0 * PI * phase;
 
     match waveform {
-        "sine" => phase.sin(),
+        "sine" => angle.sin(),
 
         "square" => {
-            if phase.sin() >= 0.0 {
+            if angle.sin() >= 0.0 {
                 1.0
             } else {
                 -1.0
@@ -20,12 +28,12 @@ pub fn oscillator_sample(waveform: &str, frequency: f32, time: f32) -> f32 {
 
         "saw" => {
             // Sawtooth: -1 to 1
-            2.0 * (frequency * time - (frequency * time + 0.5).floor())
+            2.0 * (phase - (phase + 0.5).floor())
         }
 
         "triangle" => {
             // Triangle wave
-            (2.0 * (2.0 * (frequency * time).fract() - 1.0)).abs() * 2.0 - 1.0
+            (2.0 * (2.0 * phase.fract() - 1.0)).abs() * 2.0 - 1.0
         }
 
         _ => 0.0, // Unknown waveform returns silence
@@ -68,6 +76,64 @@ pub fn adsr_envelope(
     }
 }
 
+/// Curve shaping each envelope stage (`$curve.*` / `$ease.*` from `engine::curves`).
+///
+/// Falling stages (decay, release) apply the curve to the distance travelled, so
+/// `$curve.out` drops fast then settles and `$curve.in` holds then falls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvelopeCurves {
+    pub attack: CurveType,
+    pub decay: CurveType,
+    pub release: CurveType,
+}
+
+impl Default for EnvelopeCurves {
+    fn default() -> Self {
+        Self {
+            attack: CurveType::Linear,
+            decay: CurveType::Linear,
+            release: CurveType::Linear,
+        }
+    }
+}
+
+/// Gated ADSR envelope value at sample position.
+///
+/// Attack and decay run from the note start and the sustain level holds until
+/// `gate_samples` (the note length); the release then falls from whatever level the
+/// envelope had reached, so the tail lasts `release_samples` past the note.
+pub fn gated_adsr_envelope(
+    sample_index: usize,
+    attack_samples: usize,
+    decay_samples: usize,
+    gate_samples: usize,
+    release_samples: usize,
+    sustain_level: f32,
+    curves: &EnvelopeCurves,
+) -> f32 {
+    let held = |index: usize| -> f32 {
+        if index < attack_samples {
+            evaluate_curve(curves.attack, index as f32 / attack_samples as f32)
+        } else if index < attack_samples + decay_samples {
+            let progress = (index - attack_samples) as f32 / decay_samples as f32;
+            1.0 - (1.0 - sustain_level) * evaluate_curve(curves.decay, progress)
+        } else {
+            sustain_level
+        }
+    };
+
+    if sample_index < gate_samples {
+        return held(sample_index);
+    }
+    let released = sample_index - gate_samples;
+    if released >= release_samples {
+        return 0.0;
+    }
+    let level = held(gate_samples);
+    let progress = released as f32 / release_samples as f32;
+    level * (1.0 - evaluate_curve(curves.release, progress))
+}
+
 /// Convert time in seconds to samples
 pub fn time_to_samples(time_seconds: f32, sample_rate: u32) -> usize {
     (time_seconds * sample_rate as f32) as usize
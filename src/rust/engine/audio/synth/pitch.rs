@@ -0,0 +1,106 @@
+//! Per-note pitch movement rendered in the voice oscillator: vibrato and a pitch envelope
+//! that starts off pitch and glides back to the note.
+//!
+//! Both live in the synth options (`vibrato_rate`, `vibrato_depth`, `vibrato_delay`,
+//! `pitch_start`, `pitch_time`), so a synth definition sets them for every note and
+//! `-> vibrato(...)` / `-> pitch_env(...)` override them on a single note:
+//!
+//! ```deva
+//! let lead = synth saw { vibrato: { rate: 5.5, depth: 20, delay: 250 }, pitch_env: { start: -2, time: 60 } }
+//! lead -> note(C5) -> vibrato(6, 35) -> pitch_env(12, 40)
+//! ```
+
+use crate::language::syntax::ast::Value;
+use std::collections::HashMap;
+
+/// Synth option keys, with the map entry and field each comes from
+pub const VIBRATO_OPTIONS: [(&str, &str); 3] = [
+    ("vibrato_rate", "rate"),
+    ("vibrato_depth", "depth"),
+    ("vibrato_delay", "delay"),
+];
+pub const PITCH_ENV_OPTIONS: [(&str, &str); 2] = [("pitch_start", "start"), ("pitch_time", "time")];
+
+/// Vibrato rate when only a depth is given
+pub const DEFAULT_VIBRATO_RATE: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PitchModulation {
+    /// Hz
+    pub vibrato_rate: f32,
+    /// Cents either side of the note
+    pub vibrato_depth: f32,
+    /// Seconds before the vibrato starts
+    pub vibrato_delay: f32,
+    /// Semitones off the note at its start
+    pub start: f32,
+    /// Seconds to glide from `start` to the note
+    pub time: f32,
+}
+
+impl PitchModulation {
+    pub fn from_options(options: &HashMap<String, f32>) -> Self {
+        let option = |key: &str| options.get(key).copied().unwrap_or(0.0);
+        let vibrato_depth = option("vibrato_depth");
+        Self {
+            vibrato_rate: options
+                .get("vibrato_rate")
+                .copied()
+                .unwrap_or(DEFAULT_VIBRATO_RATE)
+                .max(0.0),
+            vibrato_depth,
+            vibrato_delay: option("vibrato_delay").max(0.0) / 1000.0,
+            start: option("pitch_start"),
+            time: option("pitch_time").max(0.0) / 1000.0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.has_vibrato() || self.has_envelope()
+    }
+
+    fn has_vibrato(&self) -> bool {
+        self.vibrato_depth != 0.0 && self.vibrato_rate > 0.0
+    }
+
+    fn has_envelope(&self) -> bool {
+        self.start != 0.0 && self.time > 0.0
+    }
+
+    /// Pitch offset in cents `time` seconds into the note, with `depth` in place of the
+    /// vibrato depth when it is automated
+    pub fn cents_at(&self, time: f32, depth: Option<f32>) -> f32 {
+        let mut cents = 0.0;
+        if self.has_envelope() && time < self.time {
+            cents += self.start * 100.0 * (1.0 - time / self.time);
+        }
+        let depth = depth.unwrap_or(self.vibrato_depth);
+        if depth != 0.0 && self.vibrato_rate > 0.0 && time >= self.vibrato_delay {
+            let phase = (time - self.vibrato_delay) * self.vibrato_rate;
+            cents += depth * (2.0 * std::f32::consts::PI * phase).sin();
+        }
+        cents
+    }
+}
+
+/// Copy `vibrato: { rate, depth, delay }` and `pitch_env: { start, time }` maps of a synth
+/// definition into its options
+pub fn extract_pitch_options(map: &HashMap<String, Value>, options: &mut HashMap<String, f32>) {
+    for (entry, keys) in [
+        ("vibrato", &VIBRATO_OPTIONS[..]),
+        ("pitch_env", &PITCH_ENV_OPTIONS[..]),
+    ] {
+        let Some(Value::Map(settings)) = map.get(entry) else {
+            continue;
+        };
+        for (option, field) in keys {
+            if let Some(Value::Number(n)) = settings.get(*field) {
+                options.insert(option.to_string(), *n);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_pitch.rs"]
+mod tests;
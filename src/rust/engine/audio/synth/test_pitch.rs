@@ -0,0 +1,101 @@
+use super::*;
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use anyhow::Result;
+
+fn options(entries: &[(&str, f32)]) -> HashMap<String, f32> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), *value))
+        .collect()
+}
+
+#[test]
+fn test_pitch_envelope_glides_to_the_note_and_vibrato_waits_for_its_delay() {
+    let pitch = PitchModulation::from_options(&options(&[
+        ("pitch_start", 12.0),
+        ("pitch_time", 100.0),
+        ("vibrato_depth", 20.0),
+        ("vibrato_rate", 5.0),
+        ("vibrato_delay", 200.0),
+    ]));
+    assert!(pitch.is_active());
+    assert!((pitch.cents_at(0.0, None) - 1200.0).abs() < 1e-3);
+    assert!((pitch.cents_at(0.05, None) - 600.0).abs() < 1e-3);
+    assert_eq!(pitch.cents_at(0.15, None), 0.0);
+    // A quarter cycle past the delay the vibrato peaks
+    assert!((pitch.cents_at(0.25, None) - 20.0).abs() < 1e-3);
+    // Automated depth takes over
+    assert!((pitch.cents_at(0.25, Some(-5.0)) + 5.0).abs() < 1e-3);
+
+    assert!(!PitchModulation::from_options(&HashMap::new()).is_active());
+}
+
+#[test]
+fn test_extract_pitch_options_flattens_definition_maps() {
+    let map: HashMap<String, Value> = [
+        (
+            "vibrato".to_string(),
+            Value::Map(
+                [
+                    ("rate".to_string(), Value::Number(6.0)),
+                    ("depth".to_string(), Value::Number(30.0)),
+                ]
+                .into(),
+            ),
+        ),
+        (
+            "pitch_env".to_string(),
+            Value::Map([("start".to_string(), Value::Number(-2.0))].into()),
+        ),
+    ]
+    .into();
+    let mut extracted = HashMap::new();
+    extract_pitch_options(&map, &mut extracted);
+    assert_eq!(
+        extracted,
+        options(&[
+            ("vibrato_rate", 6.0),
+            ("vibrato_depth", 30.0),
+            ("pitch_start", -2.0)
+        ])
+    );
+}
+
+#[test]
+fn test_vibrato_and_pitch_env_reach_note_options() -> Result<()> {
+    let source = "bpm 120\nlet lead = synth saw { vibrato: { rate: 5, depth: 20, delay: 250 }, pitch_env: { start: -2, time: 60 } }\nlead -> note(C4)\nlead -> note(E4) -> vibrato(7, 35) -> pitch_env(12, 1/16)\nlead -> chord(C) -> vibrato({ depth: 10 })\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    interp.collect_events(&statements)?;
+    let options: Vec<_> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Note { synth_def, .. } | AudioEvent::Chord { synth_def, .. } => {
+                Some(synth_def.options.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(options.len(), 3);
+    let option = |index: usize, key: &str| options[index].get(key).copied();
+
+    assert_eq!(option(0, "vibrato_rate"), Some(5.0));
+    assert_eq!(option(0, "vibrato_delay"), Some(250.0));
+    assert_eq!(option(0, "pitch_start"), Some(-2.0));
+    // Note options override the synth's
+    assert_eq!(option(1, "vibrato_rate"), Some(7.0));
+    assert_eq!(option(1, "vibrato_depth"), Some(35.0));
+    assert_eq!(option(1, "vibrato_delay"), Some(250.0));
+    assert_eq!(option(1, "pitch_start"), Some(12.0));
+    // `1/16` of a beat at 120 bpm
+    assert!((option(1, "pitch_time").unwrap() - 31.25).abs() < 1e-3);
+    assert_eq!(option(2, "vibrato_depth"), Some(10.0));
+    assert_eq!(option(2, "vibrato_rate"), Some(5.0));
+    Ok(())
+}
@@ -1,14 +1,21 @@
+#[cfg(feature = "synth-types")]
 pub mod arp;
+#[cfg(feature = "synth-types")]
 pub mod bass;
+#[cfg(feature = "synth-types")]
 pub mod keys;
+#[cfg(feature = "synth-types")]
 pub mod lead;
+#[cfg(feature = "synth-types")]
 pub mod pad;
 /// Synth types module - different synth presets and behaviors
+#[cfg(feature = "synth-types")]
 pub mod pluck;
 
 use crate::engine::audio::generator::SynthParams;
-use anyhow::Result;
-use std::collections::HashMap;
+use anyhow::{Result, anyhow};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// Trait for synth type behavior
 pub trait SynthType {
@@ -28,15 +35,75 @@ pub trait SynthType {
     ) -> Result<()>;
 }
 
+/// Builds a synth type
+pub type SynthTypeFactory = fn() -> Box<dyn SynthType>;
+
+/// Synth types built into this binary (none without the `synth-types` feature)
+pub const BUILTIN_SYNTH_TYPES: &[(&str, SynthTypeFactory)] = &[
+    #[cfg(feature = "synth-types")]
+    ("pluck", || Box::new(pluck::PluckSynth)),
+    #[cfg(feature = "synth-types")]
+    ("arp", || Box::new(arp::ArpSynth)),
+    #[cfg(feature = "synth-types")]
+    ("pad", || Box::new(pad::PadSynth)),
+    #[cfg(feature = "synth-types")]
+    ("bass", || Box::new(bass::BassSynth)),
+    #[cfg(feature = "synth-types")]
+    ("lead", || Box::new(lead::LeadSynth)),
+    #[cfg(feature = "synth-types")]
+    ("keys", || Box::new(keys::KeysSynth)),
+];
+
+/// Every optional synth type, built with the `synth-types` feature
+pub const GATED_SYNTH_TYPES: &[&str] = &["pluck", "arp", "pad", "bass", "lead", "keys"];
+
 /// Get synth type by name
 pub fn get_synth_type(type_name: &str) -> Option<Box<dyn SynthType>> {
-    match type_name.to_lowercase().as_str() {
-        "pluck" => Some(Box::new(pluck::PluckSynth)),
-        "arp" => Some(Box::new(arp::ArpSynth)),
-        "pad" => Some(Box::new(pad::PadSynth)),
-        "bass" => Some(Box::new(bass::BassSynth)),
-        "lead" => Some(Box::new(lead::LeadSynth)),
-        "keys" => Some(Box::new(keys::KeysSynth)),
-        _ => None,
+    let name = type_name.to_lowercase();
+    BUILTIN_SYNTH_TYPES
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, build)| build())
+}
+
+/// Synth types an interpreter lets scripts use (see `EngineBuilder::with_synth_types`)
+#[derive(Debug, Clone, Default)]
+pub struct SynthTypeSelection {
+    /// `None` allows every built-in synth type
+    enabled: Option<Arc<HashSet<&'static str>>>,
+}
+
+impl SynthTypeSelection {
+    /// Only the synth types in `names`; one that is not built in is an error
+    pub fn only<S: AsRef<str>>(names: impl IntoIterator<Item = S>) -> Result<Self> {
+        let mut enabled = HashSet::new();
+        for name in names {
+            let name = name.as_ref().to_lowercase();
+            let (builtin, _) = BUILTIN_SYNTH_TYPES
+                .iter()
+                .find(|(builtin, _)| *builtin == name)
+                .ok_or_else(|| {
+                    if GATED_SYNTH_TYPES.contains(&name.as_str()) {
+                        anyhow!(
+                            "synth type '{}' needs the 'synth-types' feature, which this build lacks",
+                            name
+                        )
+                    } else {
+                        anyhow!("unknown synth type '{}'", name)
+                    }
+                })?;
+            enabled.insert(*builtin);
+        }
+        Ok(Self {
+            enabled: Some(Arc::new(enabled)),
+        })
+    }
+
+    /// Whether scripts may use the synth type `name`
+    pub fn allows(&self, name: &str) -> bool {
+        match &self.enabled {
+            Some(enabled) => enabled.contains(name.to_lowercase().as_str()),
+            None => true,
+        }
     }
 }
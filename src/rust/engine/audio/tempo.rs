@@ -0,0 +1,213 @@
+//! Tempo and meter maps
+//!
+//! Positions are in beats (quarter notes) from the start of the project. Imported
+//! from MIDI files with `@load "file.mid" as name with { tempo_map: true }` and
+//! written back on MIDI export.
+
+use crate::language::syntax::ast::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TempoChange {
+    pub beat: f32,
+    pub bpm: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MeterChange {
+    pub beat: f32,
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TempoMap {
+    /// Tempo changes sorted by beat
+    pub tempos: Vec<TempoChange>,
+    /// Time-signature changes sorted by beat
+    pub meters: Vec<MeterChange>,
+}
+
+impl TempoMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// True when the map holds neither tempo nor meter changes
+    pub fn is_empty(&self) -> bool {
+        self.tempos.is_empty() && self.meters.is_empty()
+    }
+
+    /// True when the map holds no tempo change (a flat project BPM applies)
+    pub fn has_flat_tempo(&self) -> bool {
+        self.tempos.is_empty()
+    }
+
+    /// Add a tempo change, replacing any change already at `beat`
+    pub fn push_tempo(&mut self, beat: f32, bpm: f32) {
+        if bpm <= 0.0 {
+            return;
+        }
+        self.tempos.retain(|change| change.beat != beat);
+        self.tempos.push(TempoChange { beat, bpm });
+        self.tempos.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+    }
+
+    /// Add a time-signature change, replacing any change already at `beat`
+    pub fn push_meter(&mut self, beat: f32, numerator: u8, denominator: u8) {
+        self.meters.retain(|change| change.beat != beat);
+        self.meters.push(MeterChange {
+            beat,
+            numerator,
+            denominator,
+        });
+        self.meters.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+    }
+
+    /// Tempo in effect at `beat`; `fallback` applies before the first change
+    pub fn bpm_at(&self, beat: f32, fallback: f32) -> f32 {
+        self.tempos
+            .iter()
+            .take_while(|change| change.beat <= beat)
+            .last()
+            .map_or(fallback, |change| change.bpm)
+    }
+
+    /// Time signature in effect at `beat` (4/4 before the first change)
+    pub fn meter_at(&self, beat: f32) -> (u8, u8) {
+        self.meters
+            .iter()
+            .take_while(|change| change.beat <= beat)
+            .last()
+            .map_or((4, 4), |change| (change.numerator, change.denominator))
+    }
+
+    /// Time in seconds at which `beat` is reached
+    pub fn seconds_at(&self, beat: f32, fallback: f32) -> f32 {
+        let mut seconds = 0.0f64;
+        let mut position = 0.0f32;
+        let mut bpm = fallback;
+        for change in &self.tempos {
+            if change.beat >= beat {
+                break;
+            }
+            seconds += (change.beat - position).max(0.0) as f64 * 60.0 / bpm as f64;
+            position = change.beat.max(position);
+            bpm = change.bpm;
+        }
+        (seconds + (beat - position) as f64 * 60.0 / bpm as f64) as f32
+    }
+
+    /// Bar (from 0) and quarter notes into it at `beat`. A meter change starts a new bar,
+    /// as on the click track.
+    pub fn bar_at(&self, beat: f32) -> (u32, f32) {
+        let mut bars = 0.0f32;
+        let mut start = 0.0f32;
+        let mut length = 4.0f32;
+        for change in &self.meters {
+            if change.beat > beat {
+                break;
+            }
+            if change.beat > start {
+                bars += ((change.beat - start) / length).ceil();
+                start = change.beat;
+            }
+            length = 4.0 * change.numerator.max(1) as f32 / change.denominator.max(1) as f32;
+        }
+        let into = ((beat - start) / length).floor().max(0.0);
+        ((bars + into) as u32, beat - start - into * length)
+    }
+
+    /// Beat position reached after `seconds` (inverse of `seconds_at`)
+    pub fn beat_at(&self, seconds: f32, fallback: f32) -> f32 {
+        let mut elapsed = 0.0f64;
+        let mut position = 0.0f32;
+        let mut bpm = fallback;
+        for change in &self.tempos {
+            let span = (change.beat - position).max(0.0) as f64 * 60.0 / bpm as f64;
+            if elapsed + span > seconds as f64 {
+                break;
+            }
+            elapsed += span;
+            position = change.beat.max(position);
+            bpm = change.bpm;
+        }
+        position + ((seconds as f64 - elapsed) * bpm as f64 / 60.0) as f32
+    }
+
+    /// Map form stored alongside loaded MIDI data:
+    /// `{ tempo: [{ beat, bpm }], meter: [{ beat, numerator, denominator }] }`
+    pub fn to_value(&self) -> Value {
+        let tempos = self
+            .tempos
+            .iter()
+            .map(|change| {
+                Value::Map(HashMap::from([
+                    ("beat".to_string(), Value::Number(change.beat)),
+                    ("bpm".to_string(), Value::Number(change.bpm)),
+                ]))
+            })
+            .collect();
+        let meters = self
+            .meters
+            .iter()
+            .map(|change| {
+                Value::Map(HashMap::from([
+                    ("beat".to_string(), Value::Number(change.beat)),
+                    (
+                        "numerator".to_string(),
+                        Value::Number(change.numerator as f32),
+                    ),
+                    (
+                        "denominator".to_string(),
+                        Value::Number(change.denominator as f32),
+                    ),
+                ]))
+            })
+            .collect();
+        Value::Map(HashMap::from([
+            ("tempo".to_string(), Value::Array(tempos)),
+            ("meter".to_string(), Value::Array(meters)),
+        ]))
+    }
+
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let Value::Map(map) = value else {
+            return None;
+        };
+        let number = |entry: &HashMap<String, Value>, key: &str| match entry.get(key) {
+            Some(Value::Number(n)) => Some(*n),
+            _ => None,
+        };
+
+        let mut tempo_map = Self::new();
+        if let Some(Value::Array(tempos)) = map.get("tempo") {
+            for entry in tempos {
+                if let Value::Map(entry) = entry
+                    && let (Some(beat), Some(bpm)) = (number(entry, "beat"), number(entry, "bpm"))
+                {
+                    tempo_map.push_tempo(beat, bpm);
+                }
+            }
+        }
+        if let Some(Value::Array(meters)) = map.get("meter") {
+            for entry in meters {
+                if let Value::Map(entry) = entry
+                    && let (Some(beat), Some(numerator), Some(denominator)) = (
+                        number(entry, "beat"),
+                        number(entry, "numerator"),
+                        number(entry, "denominator"),
+                    )
+                {
+                    tempo_map.push_meter(beat, numerator as u8, denominator as u8);
+                }
+            }
+        }
+        Some(tempo_map)
+    }
+}
+
+#[cfg(test)]
+#[path = "test_tempo.rs"]
+mod tests;
@@ -0,0 +1,145 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::interpreter::driver::test_support::with_crash_kit;
+use anyhow::Result;
+
+#[test]
+fn test_gain_follows_beat_phase() {
+    let map = AccentMap::new(vec![1.2, 0.8, 1.1, 0.8], 1.0);
+    assert_eq!(map.gain_at(0.0), 1.2);
+    assert_eq!(map.gain_at(1.0), 0.8);
+    assert_eq!(map.gain_at(2.5), 1.1);
+    // Wraps every cycle
+    assert_eq!(map.gain_at(6.0), 1.1);
+    // Float drift just under a step boundary still lands on the step
+    assert_eq!(map.gain_at(2.9999), 0.8);
+}
+
+#[test]
+fn test_sub_beat_steps() {
+    let map = AccentMap::preset("offbeat").unwrap();
+    assert_eq!(map.gain_at(0.0), 0.85);
+    assert_eq!(map.gain_at(0.5), 1.15);
+    assert_eq!(map.gain_at(3.5), 1.15);
+}
+
+#[test]
+fn test_value_round_trip() {
+    let map = AccentMap::new(vec![1.3, 0.7], 0.5);
+    assert_eq!(AccentMap::from_value(&map.to_value()), Some(map));
+    assert_eq!(AccentMap::from_value(&Value::Number(1.0)), None);
+    assert!(AccentMap::preset("unknown").is_none());
+}
+
+#[test]
+fn test_declaration_forms() {
+    use crate::language::syntax::ast::StatementKind;
+    use crate::language::syntax::parser::driver::statements::core::parse_accent_map;
+
+    let declared = |line: &str| match parse_accent_map(line, 1).unwrap().kind {
+        StatementKind::Let {
+            value: Some(value), ..
+        } => AccentMap::from_value(&value).unwrap(),
+        other => panic!("unexpected {:?}", other),
+    };
+
+    assert_eq!(
+        declared("accent map hats = [1.1, 0.7] per 1/8"),
+        AccentMap::new(vec![1.1, 0.7], 0.5)
+    );
+    // Positions are 1-based beats; an off-beat position switches to an eighth grid
+    assert_eq!(
+        declared("accent map push = { 1: 1.3, 2.5: 1.2 }"),
+        AccentMap::new(vec![1.3, 1.0, 1.0, 1.2, 1.0, 1.0, 1.0, 1.0], 0.5)
+    );
+    assert!(parse_accent_map("accent map bad = [loud]", 1).is_err());
+    assert!(parse_accent_map("accent map = [1.0]", 1).is_err());
+}
+
+#[test]
+fn test_declarations_reject_unbounded_maps() {
+    use crate::language::syntax::parser::driver::statements::core::parse_accent_map;
+
+    assert!(parse_accent_map("accent map ok = { 1: 1.3, 16: 1.1 }", 1).is_ok());
+    for line in [
+        "accent map bad = { inf: 1 }",
+        "accent map bad = { 1: nan }",
+        "accent map bad = { 100000: 1.2 }",
+        "accent map bad = [1.0, inf]",
+        "accent map bad = [1.2, 0.8] per 1/0.000001",
+        "accent map bad = [1.2, 0.8] per 1/1e30",
+    ] {
+        assert!(parse_accent_map(line, 1).is_err(), "{}", line);
+    }
+}
+
+#[test]
+fn test_pattern_accents_follow_the_tempo_map() -> Result<()> {
+    use crate::engine::audio::events::AudioEvent;
+    use crate::engine::audio::interpreter::driver::AudioInterpreter;
+
+    let source = "bpm 120\naccent map fourth = [1.0, 1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 1.0]\npattern p with kit.hat { accent: fourth } = \"xxxx\"\ncall p\ncall p\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    interp.variables.insert(
+        "kit".to_string(),
+        Value::Map(HashMap::from([(
+            "hat".to_string(),
+            Value::String("hat.wav".to_string()),
+        )])),
+    );
+    // Beats 0-2 take 1s, then one beat per second: the hits at 2s and 2.5s sit on
+    // beats 3 and 3.5, the accented step, rather than on beats 4 and 5
+    interp.tempo_map.push_tempo(2.0, 60.0);
+    interp.collect_events(&statements)?;
+
+    let velocities: Vec<f32> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Sample { velocity, .. } => Some(*velocity),
+            _ => None,
+        })
+        .collect();
+    let base = velocities[0];
+    assert_eq!(
+        velocities,
+        vec![base, base, base, base, 2.0 * base, 2.0 * base, base, base]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_accent_maps_scale_velocity_by_beat() -> Result<()> {
+    let source = "accent map ones = { 1: 1.5, 3: 1.25 }\ngroup hits with { accent: ones }:\n    .kit.crash\n    .kit.crash\n    .kit.crash\n    .kit.crash\ncall hits\npattern p with kit.crash { accent: backbeat } = \"x.x.x.x.\"\ncall p\n.kit.crash\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+
+    let mut interp = AudioInterpreter::new(44100);
+    with_crash_kit(&mut interp);
+    interp.collect_events(&statements)?;
+
+    let velocities: Vec<f32> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            crate::engine::audio::events::AudioEvent::Sample { velocity, .. } => Some(*velocity),
+            _ => None,
+        })
+        .collect();
+
+    // Declared map on the group, built-in preset on the pattern, nothing afterwards
+    assert_eq!(
+        velocities,
+        vec![1.5, 1.0, 1.25, 1.0, 0.9, 1.2, 0.9, 1.2, 1.0]
+    );
+    assert!(interp.modifier_stack.is_empty());
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use super::*;
+use crate::engine::audio::events::{AudioEvent, SynthDefinition};
+use crate::engine::audio::interpreter::driver::test_support::crash_count;
+use crate::language::syntax::ast::Value;
+use anyhow::Result;
+
+#[test]
+fn test_declared_articulations_override_the_builtins() {
+    let mut settings = HashMap::new();
+    settings.insert("decay".to_string(), Value::Number(0.05));
+    settings.insert("length".to_string(), Value::Number(2.0));
+    let mut table = HashMap::new();
+    table.insert("pluck".to_string(), Value::Map(settings));
+    let mut map = HashMap::new();
+    map.insert("articulations".to_string(), Value::Map(table));
+
+    let synth = SynthDefinition {
+        articulations: extract_articulations(&map),
+        ..SynthDefinition::default()
+    };
+    let pluck = resolve(&synth, "pluck").unwrap();
+    assert_eq!(pluck.decay, Some(0.05));
+    assert_eq!(pluck.attack, None);
+    assert_eq!(pluck.length, Some(1.0));
+    assert_eq!(resolve(&synth, "staccato").unwrap().length, Some(0.5));
+    assert!(resolve(&synth, "legato").is_err());
+}
+
+#[test]
+fn test_apply_keeps_unset_settings_and_sets_every_filter() {
+    let mut synth = SynthDefinition::default();
+    Articulation::builtin("pluck").unwrap().apply(&mut synth);
+    assert_eq!(synth.sustain, 0.0);
+    assert_eq!(synth.filters.len(), 1);
+    assert_eq!(synth.filters[0].cutoff, 2500.0);
+
+    let mut synth = SynthDefinition::default();
+    let decay_only = Articulation {
+        decay: Some(0.3),
+        ..Articulation::default()
+    };
+    decay_only.apply(&mut synth);
+    assert_eq!(synth.decay, 0.3);
+    assert_eq!(synth.attack, SynthDefinition::default().attack);
+    assert!(synth.filters.is_empty());
+}
+
+#[test]
+fn test_split_target() {
+    assert_eq!(split_target("lead!pluck"), ("lead", Some("pluck")));
+    assert_eq!(split_target("lead"), ("lead", None));
+    assert_eq!(split_target("lead!"), ("lead!", None));
+}
+
+#[test]
+fn test_articulations_switch_envelopes_per_note() -> Result<()> {
+    let source = "bpm 120\nlet lead = synth saw -> articulations({ soft: { attack: 0.2, length: 0.25 } })\nlead!pluck C4 1/2\nlead -> note(D4) -> duration(1/2)\nlead!soft -> note(E4) -> duration(1/2)\nlead!staccato E4 1/2 -> velocity(64)\n.kit.crash\n";
+    let (hits, interp) = crash_count(source)?;
+    assert_eq!(hits, 5);
+    let notes: Vec<(f32, f32, &SynthDefinition)> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Note {
+                start_time,
+                duration,
+                synth_def,
+                ..
+            } => Some((*start_time, *duration, synth_def)),
+            _ => None,
+        })
+        .collect();
+    let plain = SynthDefinition::default();
+    assert_eq!(notes[0].2.sustain, 0.0);
+    assert_eq!(notes[1].2.sustain, plain.sustain);
+    assert_eq!(notes[2].2.attack, 0.2);
+    assert!((notes[2].1 - 0.0625).abs() < 1e-4);
+    assert!((notes[3].1 - 0.125).abs() < 1e-4);
+    // Shortened notes still move the cursor by their written length
+    assert!((notes[3].0 - 0.75).abs() < 1e-4);
+
+    assert!(crash_count("let lead = synth saw\nlead!legato C4\n").is_err());
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use super::*;
+
+fn trigger(uri: &str, start_time: f32) -> AudioEvent {
+    AudioEvent::Sample {
+        uri: uri.to_string(),
+        start_time,
+        velocity: 1.0,
+        effects: None,
+        note: None,
+        automation: None,
+        region: None,
+    }
+}
+
+#[test]
+fn test_later_trigger_in_group_chokes_earlier_ones() {
+    let events = vec![
+        trigger("kit/hatOpen", 0.0),
+        trigger("kit/kick", 0.25),
+        trigger("kit/hatClosed", 0.5),
+        trigger("kit/hatOpen", 0.5),
+        trigger("kit/hatOpen", 1.0),
+    ];
+    let group = |uri: &str| uri.contains("hat").then(|| "kit/hats".to_string());
+
+    let times = choke_times(&events, group);
+    assert_eq!(
+        times,
+        vec![Some(0.5), None, Some(1.0), Some(1.0), None],
+        "the kick is in no group and simultaneous hats do not cut each other"
+    );
+}
+
+#[test]
+fn test_cut_truncates_with_a_fade() {
+    let mut samples = vec![1.0f32; 2 * 1000];
+    cut(&mut samples, 2, 600, 10_000);
+
+    assert_eq!(samples.len(), 1200);
+    // 5 ms at 10 kHz: the last 50 frames ramp down to silence
+    assert_eq!(samples[2 * 549], 1.0);
+    assert!(samples[2 * 575] < 0.5);
+    assert_eq!(samples[2 * 599], 0.0);
+    assert_eq!(samples[2 * 599 + 1], 0.0);
+
+    // Nothing to cut when the sample already ends before the choke
+    let mut short = vec![1.0f32; 20];
+    cut(&mut short, 2, 600, 10_000);
+    assert_eq!(short, vec![1.0f32; 20]);
+}
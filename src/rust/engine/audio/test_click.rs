@@ -0,0 +1,66 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::test_support::crash_count;
+use anyhow::Result;
+
+fn times(clicks: &[Click]) -> Vec<(f32, bool)> {
+    clicks.iter().map(|c| (c.time, c.downbeat)).collect()
+}
+
+#[test]
+fn test_clicks_follow_tempo_and_meter_changes() {
+    let flat = click_times(&TempoMap::new(), 120.0, 2.5);
+    assert_eq!(
+        times(&flat),
+        vec![
+            (0.0, true),
+            (0.5, false),
+            (1.0, false),
+            (1.5, false),
+            (2.0, true)
+        ]
+    );
+
+    // One bar of 4/4 at 120, then 6/8 at 60: eighth-note ticks half a second apart
+    let mut map = TempoMap::new();
+    map.push_tempo(4.0, 60.0);
+    map.push_meter(4.0, 6, 8);
+    let clicks = click_times(&map, 120.0, 4.0);
+    assert_eq!(
+        times(&clicks)[3..],
+        [
+            (1.5, false),
+            (2.0, true),
+            (2.5, false),
+            (3.0, false),
+            (3.5, false)
+        ]
+    );
+}
+
+#[test]
+fn test_rendered_click_starts_on_its_frame() {
+    let clicks = [Click {
+        time: 0.25,
+        downbeat: true,
+    }];
+    let track = render_click(&clicks, 1000, 500);
+    assert_eq!(track.len(), 1000);
+    // Silent up to frame 250, sounding right after
+    assert!(track[..500].iter().all(|&s| s == 0.0));
+    assert!(track[502..520].iter().any(|&s| s.abs() > 0.1));
+    assert_eq!(track[502], track[503]);
+}
+
+#[test]
+fn test_metronome_statement_sets_click_mode() -> Result<()> {
+    use crate::engine::audio::settings::ClickMode;
+
+    let (_, interp) = crash_count("metronome on\n")?;
+    assert_eq!(interp.metronome, Some(ClickMode::Mix));
+    let (_, interp) = crash_count("metronome on stem\n.kit.crash\n")?;
+    assert_eq!(interp.metronome, Some(ClickMode::Stem));
+    let (_, interp) = crash_count("metronome on monitor\nmetronome off\n")?;
+    assert_eq!(interp.metronome, None);
+    assert!(crash_count("metronome loud\n").is_err());
+    Ok(())
+}
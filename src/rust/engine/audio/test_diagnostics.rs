@@ -0,0 +1,148 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::interpreter::driver::test_support::with_crash_kit;
+use anyhow::Result;
+
+#[test]
+fn test_levels_per_second_and_first_sound() {
+    // 4 Hz stereo: one silent second, then a second and a half at 0.5
+    let mut master = vec![0.0; 8];
+    master.extend(vec![0.5; 12]);
+    let pads = vec![0.0, 0.0, 0.2, -0.2];
+    let inserts = [
+        InsertLevel {
+            name: "pads".to_string(),
+            muted: false,
+            signal: &pads,
+        },
+        InsertLevel {
+            name: "hats".to_string(),
+            muted: true,
+            signal: &[],
+        },
+    ];
+    let dropped = vec!["kick.wav".to_string(), "kick.wav".to_string()];
+    let report = SilenceReport::analyze(&master, 4, &inserts, &dropped, &BTreeSet::new());
+
+    assert_eq!(report.rms_per_second, vec![0.0, 0.5, 0.5]);
+    assert_eq!(report.first_sound, Some(1.0));
+    assert_eq!(report.peak, 0.5);
+    assert_eq!(report.inserts[0].first_sound, Some(0.25));
+    assert!((report.inserts[0].rms - 0.2f32 / 2f32.sqrt()).abs() < 1e-6);
+    assert_eq!(report.inserts[1].peak, 0.0);
+    assert_eq!(report.dropped_events, 2);
+    assert_eq!(report.missing_samples, vec!["kick.wav".to_string()]);
+    assert_eq!(
+        report.reasons(),
+        vec![
+            "Sample 'kick.wav' is not loaded".to_string(),
+            "2 sample trigger(s) dropped because their sample is missing".to_string(),
+            "Group 'hats' is muted".to_string(),
+            "Output is silent for the first 1.00s".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_reasons_for_an_empty_or_quiet_render() {
+    let empty = SilenceReport::analyze(&[], 44100, &[], &[], &BTreeSet::new());
+    assert!(empty.is_silent());
+    assert_eq!(
+        empty.reasons(),
+        vec!["Nothing was scheduled: the program plays no notes or samples".to_string()]
+    );
+
+    let quiet = SilenceReport::analyze(&[0.0005; 8], 4, &[], &[], &BTreeSet::new());
+    assert!(quiet.is_silent());
+    assert_eq!(
+        quiet.reasons(),
+        vec!["Output stays below -60 dBFS".to_string()]
+    );
+}
+
+#[test]
+fn test_report_serializes_with_the_web_type_field_names() {
+    let inserts = [InsertLevel {
+        name: "pads".to_string(),
+        muted: false,
+        signal: &[0.0, 0.5],
+    }];
+    let report = SilenceReport::analyze(&[0.0, 0.5], 2, &inserts, &[], &BTreeSet::new());
+    let value = serde_json::to_value(&report).unwrap();
+    for key in [
+        "rmsPerSecond",
+        "firstSound",
+        "peak",
+        "inserts",
+        "droppedEvents",
+        "missingSamples",
+        "unresolvedTriggers",
+    ] {
+        assert!(value.get(key).is_some(), "missing {key} in {value}");
+    }
+    assert!(value["inserts"][0].get("firstSound").is_some(), "{value}");
+}
+
+#[test]
+fn test_clipping_warning_counts_samples_over_full_scale() {
+    assert_eq!(clipping_warning(&[0.5, -1.0, 1.0, 0.0], 4), None);
+    let warning = clipping_warning(&[0.0, 0.0, 0.5, 0.5, 2.0, -1.5, 1.0, 0.0], 4).unwrap();
+    assert_eq!(
+        warning,
+        "Output clips in 2 samples (peak 6.0 dBFS, first at 0.50s); lower gain or add a limiter"
+    );
+}
+
+#[test]
+fn test_diagnosed_render_explains_silent_parts() -> Result<()> {
+    let source = "bpm 120\nlet pad = synth saw\nstrip pads pan 0.2\nstrip fills gain -3db\ngroup pads:\n    pad -> note(A3) -> duration(500)\ngroup hats mute:\n    .kit.crash\ngroup fills:\n    .kit.crash\n    .kit.snare\nsleep 2\nspawn pads\ncall hats\ncall fills\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(8000);
+    with_crash_kit(&mut interp);
+    interp.collect_all_events(&statements)?;
+
+    let (master, report) = interp.render_audio_diagnosed()?;
+    assert_eq!(master, interp.render_audio()?);
+    // Quiet for the two beats of the sleep, then the pad
+    let start = report.first_sound.unwrap();
+    assert!((start - 1.0).abs() < 0.01, "first sound at {}", start);
+    assert!(report.rms_per_second[0] < 1e-3);
+    assert!(report.rms_per_second[1] > 0.01);
+
+    assert_eq!(report.unresolved_triggers, vec!["kit.snare".to_string()]);
+    // The muted hats never reach the renderer; the fills crash does and is dropped
+    assert_eq!(report.missing_samples, vec!["crash.wav".to_string()]);
+    assert_eq!(report.dropped_events, 1);
+    let inserts: Vec<(&str, bool, bool)> = report
+        .inserts
+        .iter()
+        .map(|insert| {
+            (
+                insert.name.as_str(),
+                insert.muted,
+                insert.first_sound.is_some(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        inserts,
+        vec![
+            ("fills", false, false),
+            ("hats", true, false),
+            ("pads", false, true)
+        ]
+    );
+
+    let reasons = report.reasons();
+    assert!(reasons.iter().any(|r| r.contains("'kit.snare'")));
+    assert!(reasons.iter().any(|r| r == "Group 'hats' is muted"));
+    assert!(
+        reasons
+            .iter()
+            .any(|r| r.contains("'fills' renders silence"))
+    );
+    Ok(())
+}
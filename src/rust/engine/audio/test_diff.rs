@@ -0,0 +1,76 @@
+use super::*;
+
+const RATE: u32 = 44100;
+
+/// Mono render of decaying 1 kHz hits at `times` (seconds), `seconds` long
+fn hits(times: &[f32], seconds: f32, gain: f32) -> Vec<f32> {
+    let mut out = vec![0.0f32; (seconds * RATE as f32) as usize];
+    for &time in times {
+        let start = (time * RATE as f32) as usize;
+        for i in 0..(RATE as usize / 10) {
+            if let Some(sample) = out.get_mut(start + i) {
+                let t = i as f32 / RATE as f32;
+                *sample += gain * (2.0 * PI * 1000.0 * t).sin() * (-t * 40.0).exp();
+            }
+        }
+    }
+    out
+}
+
+fn master(samples: Vec<f32>) -> Vec<InsertAudio> {
+    vec![InsertAudio::new("master", samples, 1)]
+}
+
+#[test]
+fn test_identical_renders_are_neutral() {
+    let audio = hits(&[0.1, 0.6, 1.1], 1.5, 0.8);
+    let diff = AudioDiff::compare(&master(audio.clone()), &master(audio), RATE);
+
+    assert!(diff.is_neutral(0.01, 0.0));
+    assert_eq!(diff.onsets.matched.len(), 3);
+    assert_eq!(diff.heatmap.max_abs_db(), 0.0);
+    assert_eq!(diff.heatmap.cells[0].len(), SPECTRUM_BANDS);
+    assert_eq!(diff.length_change_seconds, 0.0);
+}
+
+#[test]
+fn test_reports_level_change_per_insert() {
+    let old = vec![
+        InsertAudio::new("master", hits(&[0.1], 0.5, 0.8), 1),
+        InsertAudio::new("drums", hits(&[0.1], 0.5, 0.8), 1),
+        InsertAudio::new("pads", hits(&[0.2], 0.5, 0.4), 1),
+    ];
+    let new = vec![
+        InsertAudio::new("master", hits(&[0.1], 0.5, 0.8), 1),
+        InsertAudio::new("drums", hits(&[0.1], 0.5, 0.4), 1),
+        InsertAudio::new("bass", hits(&[0.3], 0.5, 0.4), 1),
+    ];
+    let diff = AudioDiff::compare(&old, &new, RATE);
+
+    let names: Vec<&str> = diff.inserts.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["master", "drums", "pads", "bass"]);
+    // Half the gain is about -6 dB
+    let drums = diff.inserts[1].change_db().unwrap();
+    assert!((drums + 6.02).abs() < 0.05, "{drums}");
+    assert_eq!(diff.inserts[2].new_rms_db, None);
+    assert_eq!(diff.inserts[3].old_rms_db, None);
+    assert!(!diff.is_neutral(1.0, 1.0));
+}
+
+#[test]
+fn test_onset_shifts_are_measured() {
+    let old = hits(&[0.1, 0.5, 0.9], 1.5, 0.8);
+    let new = hits(&[0.1, 0.52, 1.3], 1.5, 0.8);
+    let diff = AudioDiff::compare(&master(old), &master(new), RATE);
+
+    let onsets = &diff.onsets;
+    assert_eq!(onsets.matched.len(), 2);
+    assert!(onsets.matched[0].shift_ms().abs() < 1.0);
+    assert!((onsets.matched[1].shift_ms() - 20.0).abs() < 1.0);
+    assert_eq!(onsets.removed.len(), 1);
+    assert!((onsets.removed[0] - 0.9).abs() < 0.005);
+    assert!((onsets.added[0] - 1.3).abs() < 0.005);
+    // The moved hits show up in the heatmap from their window on
+    let first = diff.heatmap.first_change(3.0).unwrap();
+    assert!(first > 0.4 && first <= 0.5, "{first}");
+}
@@ -0,0 +1,85 @@
+use super::*;
+
+#[test]
+fn test_reverse_keeps_channels_in_their_frame() {
+    assert_eq!(reverse(&[1.0, 2.0, 3.0], 1), vec![3.0, 2.0, 1.0]);
+    assert_eq!(
+        reverse(&[1.0, -1.0, 2.0, -2.0, 3.0, -3.0], 2),
+        vec![3.0, -3.0, 2.0, -2.0, 1.0, -1.0]
+    );
+    assert_eq!(gain(&[0.5, -1.0], 0.5), vec![0.25, -0.5]);
+    assert!((db_to_gain(-6.0) - 0.501).abs() < 1e-3);
+}
+
+#[test]
+fn test_fades_cross_at_constant_power_and_leave_the_input_alone() {
+    let source = vec![1.0f32; 20];
+    let faded_in = fade_in(&source, 2, 4);
+    let faded_out = fade_out(&source, 2, 2, 4);
+    assert_eq!(source, vec![1.0; 20]);
+    // Cut where the fade out ends (frame 6), both channels faded alike
+    assert_eq!(faded_out.len(), 12);
+    assert_eq!(faded_out[..4], [1.0; 4]);
+    for frame in 0..4 {
+        assert_eq!(faded_in[frame * 2], faded_in[frame * 2 + 1]);
+        let power = faded_in[frame * 2].powi(2) + faded_out[(2 + frame) * 2].powi(2);
+        assert!((power - 1.0).abs() < 1e-5);
+    }
+    assert_eq!(faded_in[8..], [1.0; 12]);
+    // A fade starting past the end changes nothing
+    assert_eq!(fade_out(&source, 2, 10, 4), source);
+}
+
+#[test]
+fn test_pitch_and_speed_change_the_length_like_a_sampler() {
+    let ramp: Vec<f32> = (0..100).map(|i| i as f32).collect();
+    assert_eq!(pitch(&ramp, 1000, 12.0, ResampleQuality::Linear2).len(), 50);
+    assert_eq!(
+        pitch(&ramp, 1000, -12.0, ResampleQuality::Linear2).len(),
+        200
+    );
+    assert_eq!(pitch(&ramp, 1000, 0.0, ResampleQuality::Linear2), ramp);
+
+    let fast = speed(&ramp, 2.0);
+    assert_eq!(fast.len(), 50);
+    assert_eq!(fast[..3], [0.0, 2.0, 4.0]);
+    let slow = speed(&ramp, 0.5);
+    assert_eq!(slow.len(), 200);
+    assert_eq!(slow[..3], [0.0, 0.5, 1.0]);
+    let backwards = speed(&ramp, -1.0);
+    assert_eq!(backwards[..3], [99.0, 98.0, 97.0]);
+}
+
+#[test]
+fn test_stretch_reads_slower_over_the_same_frames() {
+    let stereo: Vec<f32> = (0..8).flat_map(|i| [i as f32, -(i as f32)]).collect();
+    let stretched = stretch(&stereo, 2, 2.0);
+    assert_eq!(stretched.len(), stereo.len());
+    assert_eq!(stretched[..6], [0.0, 0.0, 0.5, -0.5, 1.0, -1.0]);
+    assert_eq!(stretch(&stereo, 2, 1.0), stereo);
+}
+
+#[test]
+fn test_host_processors_apply_the_same_primitives() {
+    use crate::engine::audio::effects::processors::super_trait::EffectProcessor;
+    use crate::engine::audio::effects::processors::{
+        ReverseProcessor, SpeedProcessor, StretchProcessor,
+    };
+
+    let source: Vec<f32> = (0..64).map(|i| (i as f32 * 0.3).sin()).collect();
+
+    let mut reversed = source.clone();
+    ReverseProcessor::new(true).process(&mut reversed, 44100);
+    assert_eq!(reversed, reverse(&source, 1));
+
+    let mut stretched = source.clone();
+    StretchProcessor::new(1.5, 0.0, false).process(&mut stretched, 44100);
+    assert_eq!(stretched, stretch(&source, 2, 1.5));
+
+    // The processor keeps the buffer length: the faster read is padded with silence
+    let mut sped = source.clone();
+    SpeedProcessor::new(2.0).process(&mut sped, 44100);
+    let expected = speed(&source, 2.0);
+    assert_eq!(sped[..expected.len()], expected[..]);
+    assert!(sped[expected.len()..].iter().all(|s| *s == 0.0));
+}
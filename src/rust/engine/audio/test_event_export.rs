@@ -0,0 +1,55 @@
+use super::*;
+use crate::engine::audio::events::SampleRegion;
+use serde_json::json;
+
+fn hit(uri: &str, start_time: f32, region: Option<SampleRegion>) -> AudioEvent {
+    AudioEvent::Sample {
+        uri: uri.to_string(),
+        start_time,
+        velocity: 0.5,
+        effects: None,
+        note: None,
+        automation: None,
+        region,
+    }
+}
+
+#[test]
+fn test_samples_are_sorted_with_their_insert_and_length() {
+    let mut events = AudioEventList::new();
+    events.tag_all_groups = true;
+    events.events.push(hit("kick.wav", 1.0, None));
+    events.events.push(hit("snare.wav", 0.5, None));
+    events.tag_group(1, "fills");
+    events.tag_group(0, "drums");
+    let looped = SampleRegion {
+        start: 0.0,
+        end: 0.5,
+        sync: None,
+        loop_length: Some(2.0),
+        loop_to_end: false,
+    };
+    events.events.push(hit("pad.wav", 0.0, Some(looped)));
+
+    let lengths = |uri: &str| (uri == "kick.wav").then_some(0.25);
+    let document = EventsDocument::new("song", 120.0, 44100, &events, lengths);
+
+    assert_eq!(
+        serde_json::to_value(&document).unwrap(),
+        json!({
+            "version": EVENTS_SCHEMA_VERSION,
+            "module": "song",
+            "bpm": 120.0,
+            "sample_rate": 44100,
+            "duration": 2.0,
+            "events": [
+                { "type": "sample", "start": 0.0, "duration": 2.0, "uri": "pad.wav",
+                  "velocity": 0.5, "insert": "master" },
+                { "type": "sample", "start": 0.5, "uri": "snare.wav",
+                  "velocity": 0.5, "insert": "drums/fills" },
+                { "type": "sample", "start": 1.0, "duration": 0.25, "uri": "kick.wav",
+                  "velocity": 0.5, "insert": "drums" },
+            ]
+        })
+    );
+}
@@ -0,0 +1,342 @@
+//! Golden renders: short deterministic programs and processor runs compared against the
+//! fingerprints checked in under `golden/`
+//!
+//! Every case is reduced to its sample count, a hash of the samples quantized to
+//! `QUANTUM` and the RMS level of `SLICES` equal slices. A case passes when the hash
+//! matches, or when every slice is within `TOLERANCE` of the recorded level, which absorbs
+//! floating point differences between platforms. Built-in effects and synth types are
+//! listed from the registries, so a new one fails here until it has a recording.
+//!
+//! After an intended change to the sound, record again and review the diff:
+//!
+//! ```text
+//! DEVALANG_BLESS=1 cargo test --features cli golden
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use crate::engine::audio::effects::EffectAvailability;
+use crate::engine::audio::effects::chain::build_effect_chain;
+use crate::engine::audio::effects::registry::BUILTIN_EFFECTS;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::synth::types::BUILTIN_SYNTH_TYPES;
+use crate::language::syntax::ast::Value;
+
+const SAMPLE_RATE: u32 = 8000;
+/// Step the samples are rounded to before hashing
+const QUANTUM: f32 = 1.0 / 4096.0;
+const SLICES: usize = 16;
+/// Largest difference allowed per slice level when the hash differs
+const TOLERANCE: f32 = 1e-3;
+
+/// Parameters the effect cases run with; other effects use their defaults
+const EFFECT_PARAMS: &[(&str, &[(&str, f32)])] = &[
+    ("drive", &[("amount", 0.8), ("mix", 0.7)]),
+    ("reverb", &[("size", 0.6), ("mix", 0.4)]),
+    ("delay", &[("time", 60.0), ("feedback", 0.5), ("mix", 0.5)]),
+    ("speed", &[("speed", 1.5)]),
+    ("chorus", &[("depth", 0.8), ("rate", 2.0)]),
+    ("flanger", &[("depth", 0.8), ("rate", 2.0)]),
+    ("phaser", &[("depth", 0.8), ("rate", 2.0)]),
+    ("lfo", &[("rate", 6.0), ("depth", 0.8)]),
+    ("compressor", &[("threshold", -30.0), ("ratio", 8.0)]),
+    ("comp", &[("threshold", -30.0), ("ratio", 8.0)]),
+    ("multiband", &[("threshold", -30.0), ("ratio", 6.0)]),
+    ("mbcomp", &[("threshold", -30.0), ("ratio", 6.0)]),
+    ("transient", &[("attack", 10.0), ("sustain", -3.0)]),
+    ("binaural", &[("azimuth", 60.0)]),
+    ("midside", &[("mid", 0.5), ("side", 1.5)]),
+    ("gain", &[("value", 0.5)]),
+    ("volume", &[("value", 0.25)]),
+    ("pan", &[("value", -0.6)]),
+    ("fadeIn", &[("value", 200.0)]),
+    ("fadeOut", &[("value", 150.0)]),
+    ("pitch", &[("value", 7.0)]),
+    ("stereo", &[("width", 1.8)]),
+    ("freeze", &[("enabled", 1.0), ("fade", 0.6), ("hold", 0.1)]),
+    ("slice", &[("segments", 8.0), ("crossfade", 0.2)]),
+    ("stretch", &[("factor", 1.5)]),
+];
+
+/// Mixer routing paths, each rendered from source
+const MIXER_CASES: &[(&str, &str)] = &[
+    (
+        "group_insert_fx",
+        "group pads:\n    pad -> note(C4) -> duration(400)\nroute pads to master with reverb({ size: 0.7, mix: 0.5 })\nspawn pads\n",
+    ),
+    (
+        "node_route_bus",
+        "routing:\n    node keys = pad\n    fx keys -> lpf({ cutoff: 600 })\n    route keys to master with gain(0.5)\npad -> note(E4) -> duration(400)\n",
+    ),
+    (
+        "duck_by_group",
+        "duck pads by hits amount 0.8 attack 5ms release 50ms\ngroup hits:\n    bass -> note(C2) -> duration(100)\n    sleep 1/4\n    bass -> note(C2) -> duration(100)\ngroup pads:\n    pad -> note(G4) -> duration(600)\nspawn hits\nspawn pads\n",
+    ),
+    (
+        "sidechain",
+        "routing:\n    node low = bass\n    node wide = pad\n    duck low to wide with compressor({ threshold: -30, ratio: 8 })\nbass -> note(C2) -> duration(300)\npad -> note(C4) -> duration(600)\n",
+    ),
+    (
+        "strip",
+        "strip pads gain -3db pan -0.4 invert lowcut 200hz\ngroup pads:\n    pad -> note(A3) -> duration(500)\nspawn pads\n",
+    ),
+    (
+        "strip_automated",
+        "strip pads pan -1\nautomate pads.pan: $time * 4 - 1\ngroup pads:\n    pad -> note(A3) -> duration(500)\nspawn pads\n",
+    ),
+    (
+        "master_strip",
+        "strip master gain 0.5 lowcut 300hz\npad -> note(C3) -> duration(400)\nbass -> note(C2) -> duration(400)\n",
+    ),
+    (
+        "hardware_outputs",
+        "route hits -> outputs 3-4\ngroup hits:\n    bass -> note(C2) -> duration(200)\ngroup pads:\n    pad -> note(E4) -> duration(400)\nspawn hits\nspawn pads\n",
+    ),
+];
+
+/// Synths every mixer case can play
+const MIXER_SYNTHS: &str = "bpm 120\nlet pad = synth saw\nlet bass = synth square\n";
+
+#[derive(Debug, Clone, PartialEq)]
+struct Fingerprint {
+    samples: usize,
+    hash: u64,
+    levels: Vec<f32>,
+}
+
+impl Fingerprint {
+    fn of(audio: &[f32]) -> Self {
+        // FNV-1a over the quantized samples
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for sample in audio {
+            let quantized = (sample / QUANTUM).round() as i32;
+            for byte in quantized.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        }
+        let slice = audio.len().div_ceil(SLICES).max(1);
+        let mut levels: Vec<f32> = audio
+            .chunks(slice)
+            .map(|chunk| (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt())
+            .collect();
+        levels.resize(SLICES, 0.0);
+        Self {
+            samples: audio.len(),
+            hash,
+            levels,
+        }
+    }
+
+    fn matches(&self, recorded: &Fingerprint) -> bool {
+        self.samples == recorded.samples
+            && (self.hash == recorded.hash
+                || self
+                    .levels
+                    .iter()
+                    .zip(&recorded.levels)
+                    .all(|(level, recorded)| (level - recorded).abs() <= TOLERANCE))
+    }
+
+    fn line(&self, name: &str) -> String {
+        let levels: Vec<String> = self.levels.iter().map(|l| format!("{:.5}", l)).collect();
+        format!(
+            "{} {} {:016x} {}",
+            name,
+            self.samples,
+            self.hash,
+            levels.join(" ")
+        )
+    }
+
+    fn parse(line: &str) -> Option<(String, Self)> {
+        let mut fields = line.split_whitespace();
+        let name = fields.next()?.to_string();
+        let samples = fields.next()?.parse().ok()?;
+        let hash = u64::from_str_radix(fields.next()?, 16).ok()?;
+        let levels = fields
+            .map(|level| level.parse().ok())
+            .collect::<Option<Vec<f32>>>()?;
+        (levels.len() == SLICES).then_some((
+            name,
+            Self {
+                samples,
+                hash,
+                levels,
+            },
+        ))
+    }
+}
+
+fn golden_path(suite: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/rust/engine/audio/golden")
+        .join(format!("{}.txt", suite))
+}
+
+/// Compare `cases` with the recordings of `suite`, or record them with `DEVALANG_BLESS`
+fn check_suite(suite: &str, cases: BTreeMap<String, Vec<f32>>) {
+    let path = golden_path(suite);
+    let rendered: BTreeMap<String, Fingerprint> = cases
+        .into_iter()
+        .map(|(name, audio)| (name, Fingerprint::of(&audio)))
+        .collect();
+
+    if std::env::var_os("DEVALANG_BLESS").is_some() {
+        let mut text = format!(
+            "# Golden {} renders: name, samples, quantized hash, {} slice levels\n",
+            suite, SLICES
+        );
+        for (name, fingerprint) in &rendered {
+            text.push_str(&fingerprint.line(name));
+            text.push('\n');
+        }
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, text).unwrap();
+        return;
+    }
+
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    let recorded: HashMap<String, Fingerprint> = text
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter_map(Fingerprint::parse)
+        .collect();
+
+    let mut failures = Vec::new();
+    for (name, fingerprint) in &rendered {
+        match recorded.get(name) {
+            Some(recorded) if fingerprint.matches(recorded) => {}
+            Some(recorded) => failures.push(format!(
+                "{} changed\n  recorded: {}\n  rendered: {}",
+                name,
+                recorded.line(name),
+                fingerprint.line(name)
+            )),
+            None => failures.push(format!("{} has no recording", name)),
+        }
+    }
+    for name in recorded.keys() {
+        if !rendered.contains_key(name) {
+            failures.push(format!("{} is recorded but no longer rendered", name));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} golden renders differ from {}:\n{}\nRecord again with DEVALANG_BLESS=1 once the change is intended",
+        suite,
+        path.display(),
+        failures.join("\n")
+    );
+}
+
+fn render(source: &str) -> Vec<f32> {
+    let statements =
+        crate::language::syntax::parser::driver::parse(source, PathBuf::from("golden.deva"))
+            .unwrap();
+    let mut interp = AudioInterpreter::new(SAMPLE_RATE);
+    interp.collect_all_events(&statements).unwrap();
+    let (mut master, taps) = interp.render_audio_outputs().unwrap();
+    let mut taps: Vec<_> = taps.into_iter().collect();
+    taps.sort_by(|a, b| a.0.cmp(&b.0));
+    for (_, tap) in taps {
+        master.extend(tap);
+    }
+    master
+}
+
+/// Half a second of stereo: a decaying saw on the left, a noise burst then a sine on
+/// the right
+fn test_signal() -> Vec<f32> {
+    let frames = SAMPLE_RATE as usize / 2;
+    let mut seed: u32 = 0x2545_f491;
+    (0..frames)
+        .flat_map(|frame| {
+            let t = frame as f32 / SAMPLE_RATE as f32;
+            let left = ((t * 220.0).fract() * 2.0 - 1.0) * (-t * 3.0).exp() * 0.8;
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let right = if frame < frames / 4 {
+                (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            } else {
+                (t * 330.0 * std::f32::consts::TAU).sin() * 0.3
+            };
+            [left, right]
+        })
+        .collect()
+}
+
+#[test]
+fn test_golden_effects() {
+    let mut cases = BTreeMap::new();
+    for (name, availability, _) in BUILTIN_EFFECTS {
+        let params: HashMap<String, Value> = EFFECT_PARAMS
+            .iter()
+            .find(|(effect, _)| effect == name)
+            .map(|(_, params)| params.iter())
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| (key.to_string(), Value::Number(*value)))
+            .collect();
+        let mut effect = HashMap::new();
+        effect.insert(name.to_string(), Value::Map(params));
+        let synth_context = !matches!(availability, EffectAvailability::TriggerOnly);
+        let mut chain = build_effect_chain(&[Value::Map(effect)], synth_context);
+        assert_eq!(chain.len(), 1, "{} was not built", name);
+
+        let mut audio = test_signal();
+        chain.process(&mut audio, SAMPLE_RATE);
+        // An effect that leaves the test signal alone records nothing about itself
+        assert_ne!(
+            Fingerprint::of(&audio).hash,
+            Fingerprint::of(&test_signal()).hash,
+            "{} does not change the test signal; give it parameters in EFFECT_PARAMS",
+            name
+        );
+        cases.insert(format!("fx/{}", name), audio);
+    }
+    check_suite("effects", cases);
+}
+
+#[test]
+fn test_golden_synths() {
+    let waveforms = ["sine", "square", "saw", "triangle"];
+    let synths = waveforms
+        .iter()
+        .map(|waveform| {
+            (
+                format!("waveform/{}", waveform),
+                format!("synth {}", waveform),
+            )
+        })
+        .chain(BUILTIN_SYNTH_TYPES.iter().map(|(name, _)| {
+            (
+                format!("type/{}", name),
+                format!("synth saw -> type({})", name),
+            )
+        }));
+
+    let mut cases = BTreeMap::new();
+    for (name, synth) in synths {
+        let source = format!(
+            "bpm 120\nlet s = {}\ns -> note(A3) -> duration(300)\ns -> chord(Cmaj7) -> duration(300)\n",
+            synth
+        );
+        cases.insert(name, render(&source));
+    }
+    check_suite("synths", cases);
+}
+
+#[test]
+fn test_golden_mixer() {
+    let cases = MIXER_CASES
+        .iter()
+        .map(|(name, source)| {
+            (
+                format!("mix/{}", name),
+                render(&format!("{}{}", MIXER_SYNTHS, source)),
+            )
+        })
+        .collect();
+    check_suite("mixer", cases);
+}
@@ -0,0 +1,137 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use anyhow::Result;
+use std::collections::HashMap;
+
+fn hit(uri: &str, effects: Option<Value>) -> AudioEvent {
+    AudioEvent::Sample {
+        uri: uri.to_string(),
+        start_time: 0.0,
+        velocity: 1.0,
+        effects,
+        note: None,
+        automation: None,
+        region: None,
+    }
+}
+
+fn registry(value: f32, targets: &[&str]) -> MacroRegistry {
+    let mut registry = MacroRegistry::default();
+    let targets = targets
+        .iter()
+        .map(|target| MacroTarget::parse(target).unwrap())
+        .collect();
+    registry.define("intensity", value, targets);
+    registry
+}
+
+#[test]
+fn test_target_parses_owner_param_and_depth() {
+    let target = MacroTarget::parse("lead.cutoff x0.5").unwrap();
+    assert_eq!(target.owner, "lead");
+    assert_eq!(target.param, "cutoff");
+    assert_eq!(target.depth, 0.5);
+    assert_eq!(target.to_string(), "lead.cutoff x0.5");
+    assert_eq!(MacroTarget::parse("drums.gain").unwrap().depth, 1.0);
+
+    assert!(MacroTarget::parse("gain").is_err());
+    assert!(MacroTarget::parse("lead.cutoff x2").is_err());
+    assert!(MacroTarget::parse("lead.cutoff 0.5").is_err());
+}
+
+#[test]
+fn test_depth_keeps_part_of_the_written_value() {
+    let full = MacroTarget::parse("drums.gain").unwrap();
+    let half = MacroTarget::parse("lead.cutoff x0.5").unwrap();
+    assert_eq!(full.factor(0.0), 0.0);
+    assert_eq!(full.factor(1.0), 1.0);
+    assert_eq!(half.factor(0.0), 0.5);
+    assert_eq!(half.factor(0.5), 0.75);
+    // Values outside the macro range are clamped
+    assert_eq!(half.factor(3.0), 1.0);
+}
+
+#[test]
+fn test_apply_scales_bank_hits_and_effect_params() {
+    let mut events = AudioEventList::new();
+    let mut reverb = HashMap::new();
+    reverb.insert("mix".to_string(), Value::Number(0.4));
+    let mut effects = HashMap::new();
+    effects.insert("reverb".to_string(), Value::Map(reverb));
+    events.events.push(hit(
+        "devalang://bank/devaloop.808/kick",
+        Some(Value::Map(effects)),
+    ));
+    events.events.push(hit("devalang://bank/other/kick", None));
+
+    registry(0.5, &["drums.gain", "reverb.mix x0.5"]).apply(&mut events, |alias| {
+        (alias == "drums").then(|| "devalang://bank/devaloop.808/".to_string())
+    });
+
+    let AudioEvent::Sample {
+        velocity, effects, ..
+    } = &events.events[0]
+    else {
+        unreachable!()
+    };
+    assert_eq!(*velocity, 0.5);
+    let mix = effects
+        .as_ref()
+        .and_then(|effects| effects.get("reverb"))
+        .and_then(|reverb| reverb.get("mix"));
+    assert_eq!(mix, Some(&Value::Number(0.4 * 0.75)));
+    let AudioEvent::Sample { velocity, .. } = &events.events[1] else {
+        unreachable!()
+    };
+    assert_eq!(*velocity, 1.0, "hits of other banks are left alone");
+}
+
+#[test]
+fn test_macro_keys_and_cc_move_the_value_within_range() {
+    let up = MacroKey::parse("]=intensity+0.1").unwrap();
+    assert_eq!(up.key, ']');
+    assert_eq!(up.name, "intensity");
+    assert_eq!(up.change, MacroChange::Step(0.1));
+    assert_eq!(up.change.apply(0.95), 1.0);
+    assert_eq!(
+        MacroKey::parse("[ -> intensity-0.25").unwrap().change,
+        MacroChange::Step(-0.25)
+    );
+    assert_eq!(
+        MacroKey::parse("0=intensity@0").unwrap().change,
+        MacroChange::Set(0.0)
+    );
+    assert!(MacroKey::parse("]=intensity").is_err());
+
+    let cc = MacroCc::parse("74=intensity").unwrap();
+    assert_eq!(cc.controller, 74);
+    assert_eq!(MacroCc::change(127).apply(0.0), 1.0);
+    assert!(MacroCc::parse("200=intensity").is_err());
+}
+
+#[test]
+fn test_macro_override_scales_its_targets() -> Result<()> {
+    let source = "let lead = synth saw { filters: [{ type: lowpass, cutoff: 2000 }] }\nmacro intensity -> [lead.gain, lead.cutoff x0.5]\nlead -> note(C4)\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    interp.set_overrides(
+        [("intensity".to_string(), Value::Number(0.5))]
+            .into_iter()
+            .collect(),
+    );
+    interp.collect_all_events(&statements)?;
+
+    let Some(AudioEvent::Note {
+        gain, synth_def, ..
+    }) = interp.events.events.first()
+    else {
+        panic!("expected a note");
+    };
+    assert_eq!(*gain, 0.5);
+    assert_eq!(synth_def.filters[0].cutoff, 1500.0);
+    assert_eq!(interp.variables.get("intensity"), Some(&Value::Number(0.5)));
+    Ok(())
+}
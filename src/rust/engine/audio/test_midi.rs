@@ -0,0 +1,293 @@
+use super::*;
+use crate::engine::audio::events::SynthDefinition;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+
+fn note(midi: u8, start_time: f32, duration: f32) -> AudioEvent {
+    AudioEvent::Note {
+        midi,
+        start_time,
+        duration,
+        velocity: 0.8,
+        synth_id: "lead".to_string(),
+        synth_def: SynthDefinition::default(),
+        pan: 0.0,
+        detune: 0.0,
+        gain: 1.0,
+        attack: None,
+        release: None,
+        delay_time: None,
+        delay_feedback: None,
+        delay_mix: None,
+        reverb_amount: None,
+        drive_amount: None,
+        drive_color: None,
+        effects: None,
+        use_per_note_automation: false,
+    }
+}
+
+#[test]
+fn test_tempo_map_round_trips_through_midi_file() -> Result<()> {
+    let mut tempo_map = TempoMap::new();
+    tempo_map.push_tempo(0.0, 120.0);
+    tempo_map.push_tempo(4.0, 60.0);
+    tempo_map.push_meter(0.0, 4, 4);
+    tempo_map.push_meter(4.0, 3, 4);
+
+    // Beat 0 and beat 5 (one beat after the tempo halves)
+    let events = vec![note(60, 0.0, 0.5), note(64, 3.0, 1.0)];
+    let bytes = events_to_midi_bytes(&events, 120.0, &tempo_map, None, &[])?;
+
+    let path = std::env::temp_dir().join(format!("devalang_tempo_{}.mid", std::process::id()));
+    std::fs::write(&path, bytes)?;
+    let loaded = load_midi_file(&path);
+    let _ = std::fs::remove_file(&path);
+    let Value::Map(midi) = loaded? else {
+        panic!("MIDI data should be a map");
+    };
+
+    let imported = midi.get("tempo_map").and_then(TempoMap::from_value);
+    assert_eq!(imported, Some(tempo_map));
+
+    let Some(Value::Array(notes)) = midi.get("notes") else {
+        panic!("MIDI data should list notes");
+    };
+    let field = |index: usize, key: &str| match &notes[index] {
+        Value::Map(map) => crate::engine::audio::events::extract_number(map, key, -1.0),
+        _ => -1.0,
+    };
+    assert!((field(1, "beat") - 5.0).abs() < 1e-3);
+    assert!((field(1, "time") - 3000.0).abs() < 1.0);
+    assert!((field(1, "duration") - 1000.0).abs() < 1.0);
+    Ok(())
+}
+
+#[test]
+fn test_detect_chords_per_bar_from_loaded_midi() -> Result<()> {
+    // Bar 1: C major triad, bar 2: A minor seventh, bar 3: empty, bar 4: G bass under D and F
+    let events = vec![
+        note(48, 0.0, 2.0),
+        note(64, 0.0, 2.0),
+        note(67, 0.0, 2.0),
+        note(45, 2.0, 2.0),
+        note(60, 2.0, 2.0),
+        note(64, 2.0, 2.0),
+        note(67, 2.0, 1.0),
+        note(43, 6.0, 2.0),
+        note(62, 6.0, 0.5),
+        note(65, 6.5, 0.5),
+        note(71, 7.0, 0.5),
+    ];
+    let bytes = events_to_midi_bytes(&events, 120.0, &TempoMap::new(), None, &[])?;
+
+    let path = std::env::temp_dir().join(format!("devalang_chords_{}.mid", std::process::id()));
+    std::fs::write(&path, bytes)?;
+    let loaded = load_midi_file(&path);
+    let _ = std::fs::remove_file(&path);
+    let Value::Map(midi) = loaded? else {
+        panic!("MIDI data should be a map");
+    };
+    let Some(Value::Array(notes)) = midi.get("notes") else {
+        panic!("MIDI data should list notes");
+    };
+
+    let symbols: Vec<Option<String>> = detect_chords(notes, 120.0, 4.0)
+        .iter()
+        .map(|chord| chord.as_ref().map(MidiChord::symbol))
+        .collect();
+    assert_eq!(
+        symbols,
+        vec![
+            Some("Cmaj".to_string()),
+            Some("Amin7".to_string()),
+            None,
+            Some("G7".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_export_writes_bends_and_automation_lanes() -> Result<()> {
+    use crate::engine::audio::automation::{
+        AutomationCurve, AutomationEnvelope, AutomationParam, AutomationRegistry,
+    };
+
+    let mut glide = note(60, 0.0, 0.5);
+    if let AudioEvent::Note { synth_def, .. } = &mut glide {
+        synth_def.options.insert("glide".to_string(), 100.0);
+    }
+    let mut detuned = glide.clone();
+    if let AudioEvent::Note {
+        midi,
+        start_time,
+        detune,
+        ..
+    } = &mut detuned
+    {
+        *midi = 64;
+        *start_time = 0.5;
+        *detune = 50.0;
+    }
+
+    let mut envelope = AutomationEnvelope::new("lead".to_string());
+    envelope.add_param(AutomationParam {
+        param_name: "cutoff".to_string(),
+        from_value: 20.0,
+        to_value: 20000.0,
+        start_time: 0.0,
+        duration: 1.0,
+        curve: AutomationCurve::Linear,
+        ease: None,
+    });
+    let mut automation = AutomationRegistry::new();
+    automation.register(envelope);
+
+    let bytes = events_to_midi_bytes(
+        &[glide, detuned],
+        120.0,
+        &TempoMap::new(),
+        Some(&automation),
+        &[],
+    )?;
+    let smf = Smf::parse(&bytes)?;
+    // Conductor track, then the synth's own track
+    assert_eq!(smf.tracks.len(), 2);
+
+    let mut ticks = 0u32;
+    let mut bends = Vec::new();
+    let mut cutoffs = Vec::new();
+    for event in &smf.tracks[1] {
+        ticks += event.delta.as_int();
+        if let TrackEventKind::Midi { message, .. } = event.kind {
+            match message {
+                MidiMessage::PitchBend { bend } => bends.push((ticks, bend.as_f32())),
+                MidiMessage::Controller { controller, value } if controller.as_int() == 74 => {
+                    cutoffs.push(value.as_int())
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // The second note glides up from C4 (-4 semitones) to its half-semitone detune
+    assert_eq!(bends.first().map(|b| b.0), Some(0));
+    let (start, from) = bends[1];
+    assert_eq!(start, 480);
+    assert!((from - -4.0 / BEND_RANGE_SEMITONES).abs() < 1e-3);
+    let (end, to) = *bends.last().unwrap();
+    assert_eq!(end, 480 + 96);
+    assert!((to - 0.5 / BEND_RANGE_SEMITONES).abs() < 1e-3);
+
+    // The cutoff sweep becomes a rising CC74 lane
+    assert_eq!(cutoffs.first(), Some(&0));
+    assert_eq!(cutoffs.last(), Some(&127));
+    assert!(cutoffs.windows(2).all(|w| w[0] < w[1]));
+    Ok(())
+}
+
+#[test]
+fn test_marks_are_written_as_conductor_markers() -> Result<()> {
+    let mut tempo_map = TempoMap::new();
+    tempo_map.push_tempo(0.0, 120.0);
+    tempo_map.push_tempo(4.0, 60.0);
+    let markers = vec![(0.0, "intro".to_string()), (3.0, "chorus".to_string())];
+
+    let bytes = events_to_midi_bytes(&[note(60, 0.0, 4.0)], 120.0, &tempo_map, None, &markers)?;
+    let smf = Smf::parse(&bytes)?;
+    let mut ticks = 0u32;
+    let mut found = Vec::new();
+    for event in &smf.tracks[0] {
+        ticks += event.delta.as_int();
+        if let TrackEventKind::Meta(MetaMessage::Marker(name)) = event.kind {
+            found.push((ticks, String::from_utf8_lossy(name).to_string()));
+        }
+    }
+    // Three seconds in is beat 5: four beats at 120 BPM, then one at 60
+    assert_eq!(
+        found,
+        vec![(0, "intro".to_string()), (5 * 480, "chorus".to_string())]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_input_event_decodes_note_messages() {
+    let (kind, data) = input_event(&[0x92, 60, 100]).unwrap();
+    assert_eq!(kind, "noteOn");
+    assert_eq!(data.get("note"), Some(&Value::Number(60.0)));
+    assert_eq!(data.get("channel"), Some(&Value::Number(2.0)));
+
+    assert_eq!(input_event(&[0x90, 60, 0]).unwrap().0, "noteOff");
+    assert_eq!(input_event(&[0x80, 60, 64]).unwrap().0, "noteOff");
+    // Clock ticks, controllers and truncated messages are ignored
+    assert!(input_event(&[0xF8]).is_none());
+    assert!(input_event(&[0xB0, 1, 64]).is_none());
+    assert!(input_event(&[0x90, 60]).is_none());
+}
+
+#[test]
+fn test_midi_input_fires_mapping_handlers_on_a_running_stream() -> Result<()> {
+    use crate::engine::audio::interpreter::driver::renderer::BlockStream;
+
+    let source = "bpm 120\nlet lead = synth sine\non mapping.in.web.noteOn:\n    lead -> note(C4) -> duration(100) -> velocity(30)\nlead -> note(E4) -> duration(100) -> velocity(30)\n";
+    let stream_of = |source: &str| -> Result<BlockStream> {
+        let statements = crate::language::syntax::parser::driver::parse(
+            source,
+            std::path::PathBuf::from("test.deva"),
+        )?;
+        let pass = move || -> Result<AudioInterpreter> {
+            let mut interp = AudioInterpreter::new(8000);
+            interp.collect_events(&statements)?;
+            Ok(interp)
+        };
+        BlockStream::new(Box::new(pass), false)
+    };
+
+    let mut stream = stream_of(source)?;
+    let mut block = vec![0.0; 256];
+    stream.fill(&mut block)?;
+    let started = stream.take_started().len();
+
+    let receive = |interp: &mut AudioInterpreter, message: &[u8]| {
+        interp.receive_midi_message(message, "web").map(|_| ())
+    };
+    // Not a note: nothing fires
+    assert_eq!(
+        stream.insert_events(0.05, |i| receive(i, &[0xB0, 1, 64]))?,
+        0
+    );
+    // A note 50 ms in plays from there; one in the past plays from the playhead
+    assert_eq!(
+        stream.insert_events(0.05, |i| receive(i, &[0x90, 60, 90]))?,
+        1
+    );
+    assert_eq!(
+        stream.insert_events(0.0, |i| receive(i, &[0x90, 62, 90]))?,
+        1
+    );
+    while stream.fill(&mut block)? > 0 {}
+    let starts: Vec<f32> = stream
+        .take_started()
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Note { start_time, .. } => Some(*start_time),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(started, 1);
+    assert_eq!(starts, vec![128.0 / 8000.0, 0.05]);
+
+    // Bound devices receive only their channel
+    let bound = "let lead = synth sine\nbind mapping.in.pads with { channel: 10 } -> lead\non mapping.in.pads.noteOn:\n    lead -> note(C4) -> duration(100)\n";
+    let mut stream = stream_of(bound)?;
+    assert_eq!(
+        stream.insert_events(0.0, |i| receive(i, &[0x90, 36, 90]))?,
+        0
+    );
+    assert_eq!(
+        stream.insert_events(0.0, |i| receive(i, &[0x99, 36, 90]))?,
+        1
+    );
+    Ok(())
+}
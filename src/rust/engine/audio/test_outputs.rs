@@ -0,0 +1,95 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use anyhow::Result;
+
+fn route(insert: &str, first: u16, last: u16) -> OutputRoute {
+    OutputRoute {
+        insert: insert.to_string(),
+        first,
+        last,
+    }
+}
+
+#[test]
+fn test_parse_channels_accepts_one_output_or_a_pair() {
+    assert_eq!(OutputRoute::parse_channels("3-4").unwrap(), (3, 4));
+    assert_eq!(OutputRoute::parse_channels(" 5 ").unwrap(), (5, 5));
+    assert!(OutputRoute::parse_channels("0").is_err());
+    assert!(OutputRoute::parse_channels("4-3").is_err());
+    assert!(OutputRoute::parse_channels("3-6").is_err());
+    assert!(OutputRoute::parse_channels("drums").is_err());
+    assert_eq!(route("drums", 3, 4).to_string(), "drums -> outputs 3-4");
+    assert_eq!(route("bass", 5, 5).to_string(), "bass -> outputs 5");
+}
+
+#[test]
+fn test_interleave_keeps_the_main_mix_on_one_two_and_sends_stems_after_it() {
+    let main = vec![0.1, 0.2, 0.3, 0.4];
+    let mut taps = HashMap::new();
+    taps.insert("drums".to_string(), vec![0.5, -0.5, 0.25, 0.75]);
+    taps.insert("bass".to_string(), vec![0.2, 0.4, 1.0, 0.0]);
+    let routes = vec![
+        route("drums", 3, 4),
+        route("bass", 5, 5),
+        route("pads", 6, 6),
+    ];
+    assert_eq!(required_channels(&routes), 6);
+
+    let out = interleave(&main, &taps, &routes, required_channels(&routes));
+    assert_eq!(
+        out,
+        vec![
+            0.1, 0.2, 0.5, -0.5, 0.3, 0.0, //
+            0.3, 0.4, 0.25, 0.75, 0.5, 0.0,
+        ]
+    );
+    // Routes past the channels available are dropped
+    assert_eq!(interleave(&main, &taps, &routes, 4).len(), 8);
+    assert_eq!(required_channels(&[]), MAIN_OUTPUTS);
+}
+
+#[test]
+fn test_output_routes_tap_their_group_next_to_the_full_master() -> Result<()> {
+    use crate::engine::audio::outputs::{OutputRoute, interleave, required_channels};
+
+    let source = "bpm 120\nlet pad = synth sine\nlet hit = synth square\ngroup pads:\n    pad -> note(A4) -> duration(500) -> velocity(20)\ngroup kick:\n    hit -> note(C2) -> duration(500) -> velocity(20)\nroute kick -> outputs 3-4\nroute pads -> outputs 5\nspawn pads\nspawn kick\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(8000);
+    interp.collect_all_events(&statements)?;
+    let routes = interp.routing.outputs.clone();
+    assert_eq!(
+        routes,
+        vec![
+            OutputRoute {
+                insert: "kick".to_string(),
+                first: 3,
+                last: 4
+            },
+            OutputRoute {
+                insert: "pads".to_string(),
+                first: 5,
+                last: 5
+            },
+        ]
+    );
+
+    let (master, taps) = interp.render_audio_outputs()?;
+    // Taps do not change the main mix
+    assert_eq!(master, interp.render_audio()?);
+    let kick = &taps["kick"];
+    let pads = &taps["pads"];
+    assert_eq!(kick.len(), master.len());
+    for ((sum, kick), pads) in master.iter().zip(kick).zip(pads) {
+        assert!((sum - kick - pads).abs() < 1e-4);
+    }
+
+    let channels = required_channels(&routes);
+    assert_eq!(channels, 5);
+    let mixed = interleave(&master, &taps, &routes, channels);
+    assert_eq!(mixed.len(), master.len() / 2 * 5);
+    assert!(mixed.chunks_exact(5).any(|frame| frame[2].abs() > 0.01));
+    Ok(())
+}
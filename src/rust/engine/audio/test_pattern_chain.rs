@@ -0,0 +1,50 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::test_support::crash_count;
+use anyhow::Result;
+
+fn steps(source: &str, count: usize) -> Vec<String> {
+    let mut chain = PatternChain::parse(source).unwrap();
+    (0..count).map(|_| chain.advance()).collect()
+}
+
+#[test]
+fn test_then_walks_the_chain_and_alt_takes_turns() {
+    assert_eq!(
+        steps("a then b then (c alt d)", 6),
+        ["a", "b", "c", "a", "b", "d"]
+    );
+    // `alt` binds tighter than `then`
+    assert_eq!(steps("a then b alt c", 4), ["a", "b", "a", "c"]);
+}
+
+#[test]
+fn test_repeats_hold_a_part_for_several_steps() {
+    assert_eq!(steps("a x2 then b", 6), ["a", "a", "b", "a", "a", "b"]);
+    assert_eq!(steps("(a then b) x2 then c", 5), ["a", "b", "a", "b", "c"]);
+    // Each round of a repeated `alt` plays its next part
+    assert_eq!(
+        steps("(a alt b) x2 then c", 6),
+        ["a", "b", "c", "a", "b", "c"]
+    );
+}
+
+#[test]
+fn test_malformed_chains_are_rejected() {
+    assert!(PatternChain::parse("a then").is_err());
+    assert!(PatternChain::parse("(a alt b").is_err());
+    assert!(PatternChain::parse("a b").is_err());
+    assert!(PatternChain::parse("a x0").is_err());
+    assert!(PatternChain::parse("\"x---\"").is_err());
+}
+
+#[test]
+fn test_pattern_chain_plays_one_step_per_loop_pass() -> Result<()> {
+    // One hit, two hits, three hits, then `c alt d` comes round to `d`
+    let source = "bpm 120\npattern a with kit.crash = \"x---\"\npattern b with kit.crash = \"xx--\"\npattern c with kit.crash = \"xxx-\"\npattern d with kit.crash = \"xxxx\"\npattern verse = a then b then (c alt d)\nloop 6:\n    call verse\n";
+    let (hits, _) = crash_count(source)?;
+    assert_eq!(hits, 1 + 2 + 3 + 1 + 2 + 4);
+
+    let looped = crash_count("pattern a with kit.crash = \"x---\"\npattern loop1 = a then loop1\n");
+    assert!(looped.is_err());
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+fn trigger(uri: &str, start_time: f32) -> AudioEvent {
+    AudioEvent::Sample {
+        uri: uri.to_string(),
+        start_time,
+        velocity: 1.0,
+        effects: None,
+        note: None,
+        automation: None,
+        region: None,
+    }
+}
+
+#[test]
+fn test_retriggers_pair_the_same_sample_on_the_same_insert() {
+    let events = vec![
+        trigger("kit/snare", 0.0),
+        trigger("kit/kick", 0.1),
+        trigger("kit/snare", 0.2),
+        trigger("kit/snare", 0.2),
+        trigger("kit/snare", 0.3),
+    ];
+    // The last snare plays on another insert
+    let path = |index: usize| (index == 4).then(|| "fills".to_string());
+
+    let plan = retriggers(&events, path);
+    assert_eq!(plan[0].next, Some(0.2));
+    assert_eq!(plan[0].previous, None);
+    assert_eq!(plan[1], Retrigger::default());
+    // Simultaneous triggers layer instead of cutting each other
+    assert_eq!(plan[2].next, None);
+    assert_eq!(plan[3].previous, Some(0));
+    assert_eq!(plan[4], Retrigger::default());
+}
+
+#[test]
+fn test_fades_are_equal_power_and_only_fade_in_over_ringing_voices() {
+    // 1 kHz, 4-frame fades; mono voices of constant level
+    let events = vec![
+        trigger("kit/snare", 0.0),
+        trigger("kit/snare", 0.01),
+        trigger("kit/snare", 0.1),
+    ];
+    let mut fades = RetriggerFades::new(&events, |_| None, 4, 1000);
+
+    let mut first = vec![1.0f32; 20];
+    fades.apply(0, 0, &mut first, 1);
+    let mut second = vec![1.0f32; 20];
+    fades.apply(1, 10, &mut second, 1);
+    // Ends 4 frames after the retrigger
+    assert_eq!(first.len(), 14);
+    assert_eq!(first[..10], [1.0; 10]);
+    assert!(first[13] < 0.2 && second[0] < 0.2);
+    // Old and new voices sum to constant power across the overlap
+    for offset in 0..4 {
+        let power = first[10 + offset].powi(2) + second[offset].powi(2);
+        assert!((power - 1.0).abs() < 1e-5);
+    }
+    assert_eq!(second[4..], [1.0; 16]);
+
+    // The second voice has ended by the third trigger: no fade in
+    let mut third = vec![1.0f32; 5];
+    fades.apply(2, 100, &mut third, 1);
+    assert_eq!(third, vec![1.0; 5]);
+
+    // Turned off, nothing changes
+    let mut off = RetriggerFades::new(&events, |_| None, 0, 1000);
+    let mut voice = vec![1.0f32; 20];
+    off.apply(0, 0, &mut voice, 1);
+    assert_eq!(voice, vec![1.0; 20]);
+}
@@ -0,0 +1,64 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::test_support::crash_count;
+use anyhow::Result;
+
+#[test]
+fn test_transition_needs_new_scene_and_fade() {
+    let verse = SceneCue::new("verse", 0.0);
+    let chorus = SceneCue::new("chorus", 8.0);
+
+    assert_eq!(chorus.transition_from(Some(&verse)), Some(8.0));
+    // Same scene again, a cut, or no scene before: regular rebuild handling
+    assert_eq!(chorus.transition_from(Some(&chorus)), None);
+    assert_eq!(verse.transition_from(Some(&chorus)), None);
+    assert_eq!(chorus.transition_from(None), None);
+}
+
+#[test]
+fn test_mix_transition_ends_on_incoming() {
+    // Stereo: 4 frames of the old scene at 1.0 over a new scene at 0.0
+    let outgoing = vec![1.0f32; 8];
+    let mut incoming = vec![0.0f32; 12];
+    mix_transition(&outgoing, &mut incoming, 2);
+
+    let left: Vec<f32> = incoming.iter().step_by(2).copied().collect();
+    assert!(left[0] > left[1] && left[1] > left[2]);
+    assert!(left[3].abs() < 1e-6);
+    // After the fade only the new scene plays
+    assert_eq!(&left[4..], &[0.0, 0.0]);
+    assert_eq!(incoming[0], incoming[1]);
+
+    // Constant power through the middle of the fade
+    let (fade_out, fade_in) = crossfade_gains(0.5);
+    assert!((fade_out * fade_out + fade_in * fade_in - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_switch_runs_scene_members_together() -> Result<()> {
+    let source = "bpm 120\ngroup hats:\n    .kit.crash\n    sleep 1 beat\n    .kit.crash\npattern fill with kit.crash = \"xxxx\"\nscene verse = [hats]\nscene chorus = [hats, fill]\nswitch verse\nswitch chorus over 2 bars\n";
+    let (hits, interp) = crash_count(source)?;
+    let starts: Vec<f32> = interp
+        .events
+        .events
+        .iter()
+        .map(|event| match event {
+            crate::engine::audio::events::AudioEvent::Sample { start_time, .. } => *start_time,
+            _ => -1.0,
+        })
+        .collect();
+    // Verse: 2 hits; chorus: both members start together once the verse is over
+    assert_eq!(hits, 8);
+    assert_eq!(&starts[..2], &[0.0, 1.0]);
+    assert_eq!(starts[2], 1.5);
+    assert_eq!(starts[4], 1.5);
+    assert_eq!(
+        interp.scene,
+        Some(crate::engine::audio::scene::SceneCue::new("chorus", 4.0))
+    );
+
+    let Err(err) = crash_count("switch nowhere\n") else {
+        panic!("switching to an undeclared scene should fail");
+    };
+    assert!(err.to_string().contains("nowhere"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use super::*;
+use crate::engine::audio::automation::AutomationEnvelope;
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::generator::FilterDef;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::curves::CurveType;
+use anyhow::Result;
+
+fn lead() -> SynthDefinition {
+    let mut lead = SynthDefinition::default();
+    lead.filters.push(FilterDef {
+        filter_type: "lowpass".to_string(),
+        cutoff: 800.0,
+        resonance: 0.5,
+    });
+    lead.options.insert("drive".to_string(), 0.2);
+    // Not read by automation, so not part of a snapshot
+    lead.options.insert("filter_type".to_string(), 1.0);
+    lead
+}
+
+#[test]
+fn test_capture_reads_synths_strips_then_automation() {
+    let mut synths = HashMap::new();
+    synths.insert("lead".to_string(), lead());
+    let mut strips = HashMap::new();
+    strips.insert(
+        "pads".to_string(),
+        ChannelStrip {
+            pan: -0.5,
+            ..ChannelStrip::default()
+        },
+    );
+    let mut automation = AutomationRegistry::new();
+    let mut envelope = AutomationEnvelope::new("lead".to_string());
+    envelope.add_param(AutomationParam {
+        param_name: "cutoff".to_string(),
+        from_value: 1000.0,
+        to_value: 2000.0,
+        start_time: 0.0,
+        duration: 2.0,
+        curve: AutomationCurve::Linear,
+        ease: None,
+    });
+    automation.register(envelope);
+
+    let snapshot = ParamSnapshot::capture(&synths, &strips, &automation, 1.0);
+    let values: Vec<(&str, &str, f32)> = snapshot.iter().collect();
+    assert_eq!(
+        values,
+        vec![
+            ("lead", "cutoff", 1500.0),
+            ("lead", "drive", 0.2),
+            ("lead", "resonance", 0.5),
+            ("pads", "gain", 1.0),
+            ("pads", "pan", -0.5),
+        ]
+    );
+}
+
+#[test]
+fn test_morph_fills_missing_sides_from_the_live_values() {
+    let mut from = ParamSnapshot::default();
+    from.set("lead", "cutoff", 800.0);
+    let mut to = ParamSnapshot::default();
+    to.set("lead", "cutoff", 3000.0);
+    to.set("pads", "pan", 1.0);
+    let mut live = ParamSnapshot::default();
+    live.set("pads", "pan", -1.0);
+    // Only one side and nothing live: left alone
+    to.set("bass", "drive", 0.5);
+
+    let mode = parse_morph_curve("smooth").unwrap();
+    let segments = morph_segments(&from, &to, &live, 2.0, 4.0, mode);
+    assert_eq!(segments.len(), 2);
+    let (owner, cutoff) = &segments[0];
+    assert_eq!(owner, "lead");
+    assert_eq!((cutoff.from_value, cutoff.to_value), (800.0, 3000.0));
+    assert_eq!((cutoff.start_time, cutoff.duration), (2.0, 4.0));
+    assert_eq!(cutoff.ease, Some(CurveType::EaseInOut));
+    let (owner, pan) = &segments[1];
+    assert_eq!(owner, "pads");
+    assert_eq!((pan.from_value, pan.to_value), (-1.0, 1.0));
+
+    assert!(parse_morph_curve("hold").is_err());
+    assert!(parse_morph_curve("wobbly").is_err());
+    assert_eq!(parse_morph_curve("linear").unwrap(), SegmentMode::Linear);
+}
+
+#[test]
+fn test_snapshot_morph_automates_captured_parameters() -> Result<()> {
+    let source = "bpm 120\nlet lead = synth saw { filters: [{ type: lowpass, cutoff: 800 }] }\nsnapshot save calm\nautomate lead.cutoff: 4000\nsnapshot save wild\nsleep 1 beat\nsnapshot morph calm -> wild over 4 beats\nlead -> note(C4) -> duration(100)\nsleep 2 beats\nlead -> note(C4) -> duration(100)\nsleep 4 beats\nlead -> note(C4) -> duration(100)\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    interp.collect_events(&statements)?;
+
+    assert_eq!(interp.snapshots["calm"].get("lead", "cutoff"), Some(800.0));
+    assert_eq!(interp.snapshots["wild"].get("lead", "cutoff"), Some(4000.0));
+    let cutoffs: Vec<(f32, f32)> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Note {
+                start_time,
+                synth_def,
+                ..
+            } => Some((*start_time, synth_def.filters[0].cutoff)),
+            _ => None,
+        })
+        .collect();
+    // The morph runs from 0.5s to 2.5s and takes over from the formula; each note
+    // moves the cursor on by its 100ms
+    assert_eq!(cutoffs.len(), 3);
+    assert_eq!(cutoffs[0], (0.5, 800.0));
+    assert!((cutoffs[1].0 - 1.6).abs() < 1e-5);
+    assert!((cutoffs[1].1 - 2560.0).abs() < 1.0, "{:?}", cutoffs);
+    assert_eq!(cutoffs[2].1, 4000.0);
+
+    let Err(err) = interp.collect_events(&crate::language::syntax::parser::driver::parse(
+        "snapshot morph calm -> nowhere\n",
+        std::path::PathBuf::from("test.deva"),
+    )?) else {
+        panic!("morphing to an unsaved snapshot should fail");
+    };
+    assert!(err.to_string().contains("nowhere"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::interpreter::driver::test_support::with_crash_kit;
+use anyhow::Result;
+
+fn flags(solo: &[&str], mute: &[&str]) -> SoloMute {
+    SoloMute {
+        solo: solo.iter().map(|s| s.to_string()).collect(),
+        mute: mute.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_flags_cover_nested_groups() {
+    let state = flags(&["drums"], &["fills"]);
+    assert!(state.is_soloed("drums/fills"));
+    assert!(state.is_muted("drums/fills"));
+    assert!(!state.is_muted("drums"));
+
+    // Mute wins over solo, and only soloed paths are heard while a solo is active
+    assert!(state.is_audible("drums", true));
+    assert!(!state.is_audible("drums/fills", true));
+    assert!(!state.is_audible("pads", true));
+    assert!(state.is_audible("pads", false));
+}
+
+#[test]
+fn test_unknown_solo_does_not_activate() {
+    let state = flags(&["drumz"], &[]);
+    let groups = ["drums".to_string(), "pads".to_string()];
+    assert!(!state.solo_active(&groups));
+    assert!(flags(&["pads"], &[]).solo_active(&groups));
+}
+
+#[test]
+fn test_muted_and_soloed_groups_are_filtered() -> Result<()> {
+    let source = "group hats mute:\n    .kit.crash\n    sleep 1\ngroup fill:\n    .kit.crash\ngroup drums:\n    .kit.crash\n    call fill\ngroup pads:\n    .kit.crash\ncall hats\ncall drums\ncall pads\n.kit.crash\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let run = |solo: &[&str], mute: &[&str]| -> Result<Vec<f32>> {
+        let mut interp = AudioInterpreter::new(44100);
+        with_crash_kit(&mut interp);
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        interp.solo_mute =
+            crate::engine::audio::solo::SoloMute::from_names(&names(solo), &names(mute));
+        interp.collect_all_events(&statements)?;
+        Ok(interp
+            .events
+            .events
+            .iter()
+            .map(|event| match event {
+                crate::engine::audio::events::AudioEvent::Sample { start_time, .. } => *start_time,
+                _ => -1.0,
+            })
+            .collect())
+    };
+
+    // The muted hats keep their time slot
+    let all = run(&[], &[])?;
+    assert_eq!(all.len(), 4);
+    assert!(all[0] > 0.5);
+    // A soloed group brings its nested groups; everything else is silent
+    assert_eq!(run(&["drums"], &[])?, all[..2]);
+    assert_eq!(run(&["drums"], &["fill"])?, all[..1]);
+    // A solo naming no declared group is ignored
+    assert_eq!(run(&["drumz"], &["pads"])?, vec![all[0], all[1], all[3]]);
+    Ok(())
+}
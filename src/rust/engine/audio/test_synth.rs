@@ -26,3 +26,33 @@ fn test_midi_to_frequency() {
     let c4 = midi_to_frequency(60);
     assert!((c4 - 261.63).abs() < 0.5);
 }
+
+#[test]
+fn test_gated_adsr_releases_after_gate() {
+    let curves = EnvelopeCurves::default();
+    // Sustain holds until the gate closes, then the release tail runs past it
+    assert!((gated_adsr_envelope(1_800, 100, 100, 2_000, 1_000, 0.5, &curves) - 0.5).abs() < 1e-6);
+    assert!((gated_adsr_envelope(2_500, 100, 100, 2_000, 1_000, 0.5, &curves) - 0.25).abs() < 1e-6);
+    assert_eq!(
+        gated_adsr_envelope(3_000, 100, 100, 2_000, 1_000, 0.5, &curves),
+        0.0
+    );
+
+    // A note shorter than its attack releases from the level it reached
+    let short = gated_adsr_envelope(50, 1_000, 100, 50, 100, 0.5, &curves);
+    assert!((short - 0.05).abs() < 1e-6);
+}
+
+#[test]
+fn test_gated_adsr_stage_curves() {
+    let curves = EnvelopeCurves {
+        attack: CurveType::EaseIn,
+        decay: CurveType::Linear,
+        release: CurveType::EaseOut,
+    };
+    // Quadratic attack is at a quarter halfway through
+    assert!((gated_adsr_envelope(50, 100, 0, 1_000, 100, 1.0, &curves) - 0.25).abs() < 1e-6);
+    // An ease-out release has dropped three quarters of the way halfway through
+    let mid_release = gated_adsr_envelope(1_050, 100, 0, 1_000, 100, 1.0, &curves);
+    assert!((mid_release - 0.25).abs() < 1e-6);
+}
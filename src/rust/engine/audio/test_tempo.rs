@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn test_seconds_follow_tempo_changes() {
+    let mut map = TempoMap::new();
+    map.push_tempo(0.0, 120.0);
+    map.push_tempo(4.0, 60.0);
+
+    // Four beats at 120 BPM, then one second per beat
+    assert!((map.seconds_at(4.0, 100.0) - 2.0).abs() < 1e-5);
+    assert!((map.seconds_at(6.0, 100.0) - 4.0).abs() < 1e-5);
+    assert!((map.beat_at(4.0, 100.0) - 6.0).abs() < 1e-5);
+    assert_eq!(map.bpm_at(5.0, 100.0), 60.0);
+}
+
+#[test]
+fn test_empty_map_uses_fallback_bpm() {
+    let map = TempoMap::new();
+    assert!((map.seconds_at(2.0, 90.0) - 4.0 / 3.0).abs() < 1e-5);
+    assert!((map.beat_at(1.0, 90.0) - 1.5).abs() < 1e-5);
+}
+
+#[test]
+fn test_value_round_trip() {
+    let mut map = TempoMap::new();
+    map.push_tempo(0.0, 100.0);
+    map.push_tempo(8.0, 140.0);
+    map.push_meter(0.0, 7, 8);
+    assert_eq!(TempoMap::from_value(&map.to_value()), Some(map));
+}
+
+#[test]
+fn test_bars_follow_meter_changes() {
+    let mut map = TempoMap::new();
+    assert_eq!(map.bar_at(5.0), (1, 1.0));
+
+    // Two quarter notes of 4/4 before the change still count as a bar
+    map.push_meter(2.0, 3, 4);
+    assert_eq!(map.bar_at(1.0), (0, 1.0));
+    assert_eq!(map.bar_at(2.0), (1, 0.0));
+    assert_eq!(map.bar_at(6.5), (2, 1.5));
+
+    map.push_meter(8.0, 6, 8);
+    assert_eq!(map.bar_at(12.0), (4, 1.0));
+}
+
+#[test]
+fn test_meter_only_map_is_not_empty() {
+    let mut map = TempoMap::new();
+    assert!(map.is_empty());
+    map.push_meter(0.0, 3, 4);
+    assert!(!map.is_empty());
+    assert!(map.has_flat_tempo());
+}
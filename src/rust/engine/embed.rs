@@ -0,0 +1,192 @@
+//! Library facade for embedding Devalang in Rust applications (a desktop GUI, a game, a
+//! plugin host) without going through the CLI build pipeline.
+//!
+//! ```no_run
+//! use devalang_wasm::DevalangEngine;
+//!
+//! let mut engine = DevalangEngine::new(44_100);
+//! engine.eval("bpm 120\nlet lead = synth saw")?;
+//! engine.eval("lead -> note(C4) -> duration(1/4)")?;
+//! assert!(engine.get_variable("lead").is_some());
+//! let first_beat = engine.render(0.0..0.5)?;
+//! # anyhow::Ok(())
+//! ```
+//!
+//! Embedders that only use a few effects and synth types build the engine with
+//! `DevalangEngine::builder`; scripts then fail to find anything else. Builds with
+//! `--no-default-features` also leave the optional families out of the binary unless
+//! their cargo features (`fx-modulation`, `fx-dynamics`, ..., `synth-types`) are on.
+
+use crate::engine::audio::effects::registry::EffectRegistry;
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::synth::types::SynthTypeSelection;
+use crate::language::syntax::ast::Value;
+use crate::language::syntax::parser::driver::SimpleParser;
+use anyhow::Result;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Channels in the buffers `render` returns (interleaved stereo)
+pub const RENDER_CHANNELS: usize = 2;
+
+/// Devalang session: sources are evaluated one after another on the same interpreter,
+/// so variables, synths, tempo and the timeline cursor carry over between `eval` calls
+pub struct DevalangEngine {
+    interpreter: AudioInterpreter,
+}
+
+impl DevalangEngine {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            interpreter: AudioInterpreter::new(sample_rate),
+        }
+    }
+
+    /// Engine limited to some effects and synth types (see `EngineBuilder`)
+    pub fn builder(sample_rate: u32) -> EngineBuilder {
+        EngineBuilder {
+            sample_rate,
+            effects: None,
+            synth_types: None,
+        }
+    }
+
+    /// Parse and run `source`, adding its events after those of earlier calls
+    pub fn eval(&mut self, source: &str) -> Result<()> {
+        let statements = SimpleParser::parse(source, PathBuf::from("embedded.deva"))?;
+        self.interpreter.collect_all_events(&statements)
+    }
+
+    /// `eval` that also reports what `source` added: the new events and the variables it
+    /// defined or changed
+    pub fn eval_cell(&mut self, source: &str) -> Result<CellChanges> {
+        let events_before = self.events().len();
+        let variables_before = self.interpreter.variables.clone();
+        self.eval(source)?;
+
+        let mut variables: Vec<String> = self
+            .interpreter
+            .variables
+            .iter()
+            .filter(|(name, value)| variables_before.get(*name) != Some(*value))
+            .map(|(name, _)| name.clone())
+            .collect();
+        variables.sort();
+        Ok(CellChanges {
+            new_events: self.events().len() - events_before,
+            variables,
+        })
+    }
+
+    /// Forget every evaluated source and start from an empty session (the builder's
+    /// selection stays)
+    pub fn reset(&mut self) {
+        let mut interpreter = AudioInterpreter::new(self.interpreter.sample_rate);
+        interpreter.effect_registry = Arc::clone(&self.interpreter.effect_registry);
+        interpreter.synth_types = self.interpreter.synth_types.clone();
+        self.interpreter = interpreter;
+    }
+
+    /// Value of a variable defined by an evaluated source
+    pub fn get_variable(&self, name: &str) -> Option<&Value> {
+        self.interpreter.variables.get(name)
+    }
+
+    /// Notes, chords and sample triggers collected so far, in evaluation order
+    pub fn events(&self) -> &[AudioEvent] {
+        &self.interpreter.events().events
+    }
+
+    /// Current tempo
+    pub fn bpm(&self) -> f32 {
+        self.interpreter.bpm
+    }
+
+    /// Seconds until the last collected sound has rung out
+    pub fn duration(&self) -> f32 {
+        self.interpreter.calculate_total_duration()
+    }
+
+    /// Render `range` (seconds) of everything evaluated so far as interleaved stereo at
+    /// the engine's sample rate. The mix is normalized over the whole piece, so a range
+    /// sounds the same as that part of a full render.
+    pub fn render(&self, range: Range<f32>) -> Result<Vec<f32>> {
+        let buffer = self.interpreter.render_audio()?;
+        let rate = self.interpreter.sample_rate as f32;
+        let frames = buffer.len() / RENDER_CHANNELS;
+        let frame = |seconds: f32| ((seconds.max(0.0) * rate).round() as usize).min(frames);
+        let (start, end) = (frame(range.start), frame(range.end));
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        Ok(buffer[start * RENDER_CHANNELS..end * RENDER_CHANNELS].to_vec())
+    }
+
+    /// The interpreter behind the facade, for settings it does not cover
+    pub fn interpreter_mut(&mut self) -> &mut AudioInterpreter {
+        &mut self.interpreter
+    }
+}
+
+/// What one `DevalangEngine::eval_cell` call changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CellChanges {
+    /// Events the source added
+    pub new_events: usize,
+    /// Variables the source defined or changed, sorted
+    pub variables: Vec<String>,
+}
+
+/// Narrows the effects and synth types scripts can use.
+///
+/// ```no_run
+/// use devalang_wasm::DevalangEngine;
+///
+/// let engine = DevalangEngine::builder(44_100)
+///     .with_effects(["reverb", "lpf"])
+///     .with_synth_types(["pluck"])
+///     .build()?;
+/// # anyhow::Ok(())
+/// ```
+///
+/// The selection belongs to the engine: other engines in the process keep theirs. It
+/// narrows the names scripts can use, not the binary; what gets compiled in is decided
+/// by the cargo features (see `full-registry` in Cargo.toml).
+pub struct EngineBuilder {
+    sample_rate: u32,
+    effects: Option<Vec<String>>,
+    synth_types: Option<Vec<String>>,
+}
+
+impl EngineBuilder {
+    /// Only these effects (and their aliases, when listed) are available
+    pub fn with_effects<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.effects = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Only these synth types (`pluck`, `pad`, ...) are available; plain waveforms always are
+    pub fn with_synth_types<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.synth_types = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Create the engine. Names that are unknown or not built into this binary are an
+    /// error.
+    pub fn build(self) -> Result<DevalangEngine> {
+        let mut engine = DevalangEngine::new(self.sample_rate);
+        if let Some(names) = self.effects {
+            engine.interpreter.effect_registry = Arc::new(EffectRegistry::restricted_to(&names)?);
+        }
+        if let Some(names) = self.synth_types {
+            engine.interpreter.synth_types = SynthTypeSelection::only(&names)?;
+        }
+        Ok(engine)
+    }
+}
+
+#[cfg(test)]
+#[path = "test_embed.rs"]
+mod tests;
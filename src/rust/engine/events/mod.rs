@@ -116,7 +116,7 @@ impl EventRegistry {
 
 /// Check if a pattern matches an event name
 /// Supports wildcards: * (any characters), ? (single character)
-fn pattern_matches(pattern: &str, event_name: &str) -> bool {
+pub(crate) fn pattern_matches(pattern: &str, event_name: &str) -> bool {
     if pattern == "*" {
         return true;
     }
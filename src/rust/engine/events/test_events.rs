@@ -104,6 +104,7 @@ fn test_on_beat_interval_executes_every_n() {
         indent: 0,
         line: 0,
         column: 0,
+        span: Default::default(),
     };
 
     let handler = EventHandler {
@@ -152,6 +153,7 @@ fn test_on_bar_interval_executes_every_n() {
         indent: 0,
         line: 0,
         column: 0,
+        span: Default::default(),
     };
 
     let handler = EventHandler {
@@ -15,7 +15,7 @@
 /// - gain(0.0-2.0): Volume multiplier
 /// - attack(seconds): Attack time override
 /// - release(seconds): Release time override
-/// - strum(ms): Strum delay between notes
+/// - strum(ms, up|down|random): Strum delay between notes
 use super::{FunctionContext, FunctionExecutor};
 use crate::language::syntax::ast::nodes::Value;
 use anyhow::{Result, anyhow};
@@ -1,4 +1,4 @@
-/// Additional arrow call functions: velocity, duration, pan, detune, spread, gain, attack, release, delay, reverb, drive
+/// Additional arrow call functions: velocity, duration, pan, detune, spread, gain, attack, release, strum, delay, reverb, drive
 use super::{FunctionContext, FunctionExecutor};
 use crate::language::syntax::ast::nodes::Value;
 use anyhow::{Result, anyhow};
@@ -238,8 +238,138 @@ impl FunctionExecutor for ReleaseFunction {
     }
 }
 
+/// Strum function: offsets chord notes like a guitar strum (`up` from the lowest note,
+/// `down` from the highest, or `random` order)
+/// Usage: -> strum(20ms) or -> strum(20ms, down) or -> strum(1/32, random) or -> strum("15ms up")
+pub struct StrumFunction;
+
+impl FunctionExecutor for StrumFunction {
+    fn name(&self) -> &str {
+        "strum"
+    }
+
+    fn execute(&self, context: &mut FunctionContext, args: &[Value]) -> Result<()> {
+        let usage =
+            "strum() requires a delay between notes and an optional direction (up, down, random)";
+        // `"20ms up"` carries both in one string
+        let (delay, direction) = match args {
+            [Value::String(text)] => match text.split_once(char::is_whitespace) {
+                Some((delay, direction)) => (
+                    Value::String(delay.to_string()),
+                    Some(direction.trim().to_string()),
+                ),
+                None => (Value::String(text.clone()), None),
+            },
+            [delay] => (delay.clone(), None),
+            [
+                delay,
+                Value::String(direction) | Value::Identifier(direction),
+            ] => (delay.clone(), Some(direction.clone())),
+            _ => return Err(anyhow!(usage)),
+        };
+
+        let delay_ms = match &delay {
+            Value::Number(ms) => *ms,
+            other => crate::engine::audio::effects::tempo_sync::musical_ms(other, context.tempo)
+                .ok_or_else(|| anyhow!(usage))?,
+        };
+        let direction = direction.unwrap_or_else(|| "up".to_string());
+        if !matches!(direction.as_str(), "up" | "down" | "random") {
+            return Err(anyhow!(
+                "strum() direction must be up, down or random: got '{}'",
+                direction
+            ));
+        }
+
+        context.set("strum", Value::Number(delay_ms.max(0.0)));
+        context.set("strum_direction", Value::String(direction));
+        Ok(())
+    }
+}
+
+/// Vibrato function: per-note vibrato, rate in Hz, depth in cents and an optional delay
+/// before it starts (ms or a musical value)
+/// Usage: -> vibrato(6, 30) or -> vibrato(6, 30, 200) or -> vibrato({ rate: 6, depth: 30, delay: 1/8 })
+pub struct VibratoFunction;
+
+impl FunctionExecutor for VibratoFunction {
+    fn name(&self) -> &str {
+        "vibrato"
+    }
+
+    fn execute(&self, context: &mut FunctionContext, args: &[Value]) -> Result<()> {
+        set_pitch_options(
+            context,
+            args,
+            &crate::engine::audio::synth::pitch::VIBRATO_OPTIONS,
+            "vibrato() requires a rate (Hz), a depth (cents) and an optional delay",
+        )
+    }
+}
+
+/// Pitch envelope function: starts the note `start` semitones off and glides back to it
+/// over `time` (ms or a musical value)
+/// Usage: -> pitch_env(12, 40) or -> pitch_env(-1, 1/16) or -> pitch_env({ start: 12, time: 40 })
+pub struct PitchEnvFunction;
+
+impl FunctionExecutor for PitchEnvFunction {
+    fn name(&self) -> &str {
+        "pitch_env"
+    }
+
+    fn execute(&self, context: &mut FunctionContext, args: &[Value]) -> Result<()> {
+        set_pitch_options(
+            context,
+            args,
+            &crate::engine::audio::synth::pitch::PITCH_ENV_OPTIONS,
+            "pitch_env() requires a start offset (semitones) and a glide time",
+        )
+    }
+}
+
+/// Store a map or at least two positional arguments under the synth option `keys`
+/// (option, field); times (`delay`, `time`) may be musical values
+fn set_pitch_options(
+    context: &mut FunctionContext,
+    args: &[Value],
+    keys: &[(&str, &str)],
+    usage: &str,
+) -> Result<()> {
+    let values: Vec<Option<&Value>> = match args {
+        [Value::Map(map)] => keys.iter().map(|(_, field)| map.get(*field)).collect(),
+        _ if (2..=keys.len()).contains(&args.len()) => {
+            (0..keys.len()).map(|i| args.get(i)).collect()
+        }
+        _ => return Err(anyhow!(usage.to_string())),
+    };
+
+    for ((option, field), value) in keys.iter().zip(values) {
+        let Some(value) = value else {
+            continue;
+        };
+        let number = match value {
+            Value::Number(n) => Some(*n),
+            other if matches!(*field, "delay" | "time") => {
+                crate::engine::audio::effects::tempo_sync::musical_ms(other, context.tempo)
+            }
+            _ => None,
+        };
+        let number = number.ok_or_else(|| anyhow!(usage.to_string()))?;
+        context.set(*option, Value::Number(number));
+    }
+    Ok(())
+}
+
+/// Delay time in ms; musical values (`1/8d`, `1/4t`) are resolved against the note's tempo
+fn delay_time_ms(value: &Value, tempo: f32) -> Option<f32> {
+    match value {
+        Value::Number(t) => Some(*t),
+        other => crate::engine::audio::effects::tempo_sync::musical_ms(other, tempo),
+    }
+}
+
 /// Delay function: echo effect with time in ms (default feedback: 0.3, mix: 0.5)
-/// Usage: -> delay(400) or -> delay(400, 0.5) or -> delay(400, 0.5, 0.7)
+/// Usage: -> delay(400) or -> delay(400, 0.5) or -> delay(400, 0.5, 0.7) or -> delay(1/8d)
 pub struct DelayFunction;
 
 impl FunctionExecutor for DelayFunction {
@@ -255,14 +385,14 @@ impl FunctionExecutor for DelayFunction {
         }
         // Support both positional args: delay(time, feedback?, mix?)
         // and map-style: delay({ time: 300, feedback: 0.3, mix: 0.5 })
-        let mut time_val: Option<f32> = None;
+        let time_val: Option<f32>;
         let mut feedback: f32 = 0.3;
         let mut mix: f32 = 0.5;
 
         if let Some(Value::Map(params)) = args.first() {
-            if let Some(Value::Number(t)) = params.get("time") {
-                time_val = Some(*t);
-            }
+            time_val = params
+                .get("time")
+                .and_then(|t| delay_time_ms(t, context.tempo));
             if let Some(Value::Number(f)) = params.get("feedback") {
                 feedback = *f;
             }
@@ -272,10 +402,7 @@ impl FunctionExecutor for DelayFunction {
         } else {
             // Positional
             // Time in ms (required)
-            time_val = match &args[0] {
-                Value::Number(t) => Some(*t),
-                _ => None,
-            };
+            time_val = delay_time_ms(&args[0], context.tempo);
 
             if args.len() > 1 {
                 if let Value::Number(f) = &args[1] {
@@ -0,0 +1,111 @@
+/// Built-in operations on maps (objects)
+///
+/// Usage (expressions): `let all = merge(defaults, overrides)`
+/// Usage (methods): `let names = settings.keys()`, `if settings.has("swing"):`
+/// Usage (for-loops): `for k in settings.keys():`
+///
+/// Available operations:
+/// - keys(map): Array of the map keys, sorted
+/// - values(map): Array of the map values, in key order
+/// - has(map, key): Boolean telling whether `key` is present
+/// - merge(map, other, ...): New map with every key; later maps win on conflicts
+/// - delete(map, key, ...): New map without the given keys
+///
+/// Every operation returns a new value and leaves its inputs untouched, so
+/// `let trimmed = settings.delete("swing")` keeps `settings` as it was.
+use crate::language::syntax::ast::nodes::Value;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+
+/// Returns true when `name` is one of the map operations
+pub fn is_map_method(name: &str) -> bool {
+    matches!(name, "keys" | "values" | "has" | "merge" | "delete")
+}
+
+/// Execute a map operation by name. `args[0]` is the map (the receiver in method syntax), the
+/// remaining arguments are the operation parameters. Returns `None` when `name` is not a map
+/// operation.
+pub fn call_map_method(name: &str, args: &[Value]) -> Option<Result<Value>> {
+    if !is_map_method(name) {
+        return None;
+    }
+    let map = match args.first() {
+        Some(Value::Map(map)) => map,
+        Some(other) => {
+            return Some(Err(anyhow!("{}() expects a map, found {:?}", name, other)));
+        }
+        None => return Some(Err(anyhow!("{}() requires a map", name))),
+    };
+    let params = &args[1..];
+
+    Some(match name {
+        "keys" => Ok(keys(map)),
+        "values" => Ok(values(map)),
+        "has" => params
+            .first()
+            .ok_or_else(|| anyhow!("has() requires a key"))
+            .and_then(|key| key_name(name, key))
+            .map(|key| Value::Boolean(map.contains_key(&key))),
+        "merge" => params
+            .iter()
+            .try_fold(map.clone(), |mut merged, other| match other {
+                Value::Map(other) => {
+                    merged.extend(other.iter().map(|(k, v)| (k.clone(), v.clone())));
+                    Ok(merged)
+                }
+                Value::Null => Ok(merged),
+                other => Err(anyhow!("merge() expects maps, found {:?}", other)),
+            })
+            .map(Value::Map),
+        "delete" => params
+            .iter()
+            .try_fold(map.clone(), |mut remaining, key| {
+                remaining.remove(&key_name(name, key)?);
+                Ok(remaining)
+            })
+            .map(Value::Map),
+        _ => unreachable!(),
+    })
+}
+
+/// Sorted map keys, so loops over them run in a stable order
+pub fn keys(map: &HashMap<String, Value>) -> Value {
+    Value::Array(
+        sorted_keys(map)
+            .into_iter()
+            .map(|key| Value::String(key.clone()))
+            .collect(),
+    )
+}
+
+/// Map values ordered like `keys`
+pub fn values(map: &HashMap<String, Value>) -> Value {
+    Value::Array(
+        sorted_keys(map)
+            .into_iter()
+            .map(|key| map[key].clone())
+            .collect(),
+    )
+}
+
+fn sorted_keys(map: &HashMap<String, Value>) -> Vec<&String> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+fn key_name(method: &str, key: &Value) -> Result<String> {
+    match key {
+        Value::String(s) | Value::Identifier(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        other => Err(anyhow!(
+            "{}() key must be a string, found {:?}",
+            method,
+            other
+        )),
+    }
+}
+
+#[cfg(test)]
+#[path = "test_maps.rs"]
+mod tests;
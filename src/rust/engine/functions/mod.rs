@@ -4,7 +4,10 @@ pub mod effects;
 ///
 /// This module provides a modular system for executing chainable functions
 /// like `synth -> note(C4) -> filter(lowpass, 1000)`
+pub mod maps;
 pub mod note;
+pub mod theory;
+pub mod units;
 
 use crate::language::syntax::ast::nodes::Value;
 use anyhow::Result;
@@ -78,6 +81,9 @@ impl FunctionRegistry {
         registry.register(Box::new(effects::GainFunction));
         registry.register(Box::new(effects::AttackFunction));
         registry.register(Box::new(effects::ReleaseFunction));
+        registry.register(Box::new(effects::StrumFunction));
+        registry.register(Box::new(effects::VibratoFunction));
+        registry.register(Box::new(effects::PitchEnvFunction));
         registry.register(Box::new(effects::DelayFunction));
         registry.register(Box::new(effects::ReverbFunction));
         registry.register(Box::new(effects::DriveFunction));
@@ -0,0 +1,69 @@
+use super::*;
+
+fn map(pairs: &[(&str, f32)]) -> Value {
+    Value::Map(
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), Value::Number(*v)))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_keys_and_values_are_sorted() {
+    let settings = map(&[("swing", 0.2), ("gain", 0.8), ("pan", -0.5)]);
+    assert_eq!(
+        call_map_method("keys", std::slice::from_ref(&settings))
+            .unwrap()
+            .unwrap(),
+        Value::Array(vec![
+            Value::String("gain".to_string()),
+            Value::String("pan".to_string()),
+            Value::String("swing".to_string()),
+        ])
+    );
+    assert_eq!(
+        call_map_method("values", &[settings]).unwrap().unwrap(),
+        Value::Array(vec![
+            Value::Number(0.8),
+            Value::Number(-0.5),
+            Value::Number(0.2)
+        ])
+    );
+}
+
+#[test]
+fn test_merge_and_delete_return_copies() {
+    let defaults = map(&[("gain", 1.0), ("pan", 0.0)]);
+    let overrides = map(&[("gain", 0.5), ("swing", 0.1)]);
+
+    let merged = call_map_method("merge", &[defaults.clone(), overrides])
+        .unwrap()
+        .unwrap();
+    assert_eq!(merged, map(&[("gain", 0.5), ("pan", 0.0), ("swing", 0.1)]));
+
+    let trimmed = call_map_method(
+        "delete",
+        &[merged.clone(), Value::String("swing".to_string())],
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(trimmed, map(&[("gain", 0.5), ("pan", 0.0)]));
+    assert_eq!(
+        call_map_method("has", &[merged, Value::String("swing".to_string())])
+            .unwrap()
+            .unwrap(),
+        Value::Boolean(true)
+    );
+    assert_eq!(defaults, map(&[("gain", 1.0), ("pan", 0.0)]));
+}
+
+#[test]
+fn test_call_map_method_rejects_non_maps() {
+    assert!(call_map_method("transpose", &[]).is_none());
+    assert!(
+        call_map_method("keys", &[Value::Number(1.0)])
+            .unwrap()
+            .is_err()
+    );
+}
@@ -0,0 +1,106 @@
+use super::*;
+
+fn names(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|v| match v {
+                Value::String(s) | Value::Identifier(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                other => format!("{:?}", other),
+            })
+            .collect(),
+        other => panic!("expected array, got {:?}", other),
+    }
+}
+
+fn notes(list: &[&str]) -> Vec<Value> {
+    list.iter().map(|s| Value::String(s.to_string())).collect()
+}
+
+#[test]
+fn test_parse_tricky_spellings() {
+    assert_eq!(parse_spelled_note("C4").unwrap(), 60);
+    assert_eq!(parse_spelled_note("B#3").unwrap(), 60);
+    assert_eq!(parse_spelled_note("Cb4").unwrap(), 59);
+    assert_eq!(parse_spelled_note("E#4").unwrap(), 65);
+    assert_eq!(parse_spelled_note("Fb4").unwrap(), 64);
+    assert_eq!(parse_spelled_note("Cx4").unwrap(), 62);
+    assert_eq!(parse_spelled_note("Dbb4").unwrap(), 60);
+    assert_eq!(parse_spelled_note("bb3").unwrap(), 58);
+    assert_eq!(parse_spelled_note("C-1").unwrap(), 0);
+    assert!(parse_spelled_note("H4").is_err());
+    assert!(parse_spelled_note("C").is_err());
+}
+
+#[test]
+fn test_transpose_respells_with_sharps() {
+    let out = transpose(&notes(&["Cb4", "E#4", "Bb4"]), 1).unwrap();
+    assert_eq!(names(&out), vec!["C4", "F#4", "B4"]);
+
+    let numbers = transpose(&[Value::Number(60.0)], -12).unwrap();
+    assert_eq!(numbers, Value::Array(vec![Value::Number(48.0)]));
+    assert!(transpose(&[Value::Number(120.0)], 12).is_err());
+}
+
+#[test]
+fn test_retrograde_keeps_chords_intact() {
+    let chord = Value::Array(notes(&["C4", "E4"]));
+    let out = retrograde(&[chord.clone(), Value::String("G4".to_string())]);
+    assert_eq!(
+        out,
+        Value::Array(vec![Value::String("G4".to_string()), chord])
+    );
+}
+
+#[test]
+fn test_invert_around_axis_and_default_first_note() {
+    let out = invert(&notes(&["C4", "E4", "G4"]), 64).unwrap();
+    assert_eq!(names(&out), vec!["G#4", "E4", "C#4"]);
+
+    let args = vec![Value::Array(notes(&["E4", "G4", "B#3"]))];
+    let out = call_transform("invert", &args).unwrap().unwrap();
+    assert_eq!(names(&out), vec!["E4", "C#4", "G#4"]);
+}
+
+#[test]
+fn test_negative_harmony_maps_major_to_minor() {
+    // C major triad becomes C minor (in some voicing): C->G, E->Eb, G->C
+    let out = negative_harmony(&notes(&["C4", "E4", "G4"]), 0).unwrap();
+    assert_eq!(names(&out), vec!["G3", "D#4", "C5"]);
+
+    // Key spelled with a flat and a mode suffix
+    let args = vec![
+        Value::Array(notes(&["Eb4"])),
+        Value::String("Ebminor".to_string()),
+    ];
+    let out = call_transform("negativeHarmony", &args).unwrap().unwrap();
+    assert_eq!(names(&out), vec!["A#3"]);
+}
+
+#[test]
+fn test_call_transform_ignores_unknown_names() {
+    assert!(call_transform("reverse", &[]).is_none());
+    assert!(
+        call_transform("retrograde", &[Value::Number(1.0)])
+            .unwrap()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_transforms_unwrap_array_literal_items() {
+    let item = |idx: f32, note: &str| {
+        let mut map = std::collections::HashMap::new();
+        map.insert("index".to_string(), Value::Number(idx));
+        map.insert("value".to_string(), Value::Identifier(note.to_string()));
+        Value::Map(map)
+    };
+    let melody = vec![item(0.0, "C4"), item(1.0, "Eb4")];
+
+    let Value::Array(up) = transpose(&melody, 12).unwrap() else {
+        panic!("transpose should return an array");
+    };
+    let out = retrograde(&up);
+    assert_eq!(out, Value::Array(vec![item(0.0, "D#5"), item(1.0, "C5")]));
+}
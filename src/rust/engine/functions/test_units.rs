@@ -0,0 +1,93 @@
+use super::*;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::interpreter::driver::test_support::crash_count;
+
+fn number(result: Option<Result<Value>>) -> f32 {
+    match result {
+        Some(Ok(Value::Number(n))) => n,
+        other => panic!("expected a number, got {:?}", other),
+    }
+}
+
+fn token(s: &str) -> Vec<Value> {
+    vec![Value::String(s.to_string())]
+}
+
+#[test]
+fn test_durations_convert_at_the_given_tempo() {
+    // An eighth of a beat and a dotted quarter beat at 120 BPM
+    assert!((number(call_unit("ms", &token("1/8"), 120.0)) - 62.5).abs() < 1e-3);
+    assert!((number(call_unit("ms", &token("1/4d"), 120.0)) - 187.5).abs() < 1e-3);
+    assert!((number(call_unit("ms", &token("1/8 + 10ms"), 60.0)) - 135.0).abs() < 1e-3);
+    assert!((number(call_unit("beats", &token("500ms"), 120.0)) - 1.0).abs() < 1e-5);
+    assert!((number(call_unit("beats", &token("1 bar"), 90.0)) - 4.0).abs() < 1e-5);
+    // Plain numbers are milliseconds
+    assert!((number(call_unit("beats", &[Value::Number(250.0)], 120.0)) - 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn test_pitch_helpers() {
+    assert!((number(call_unit("hz", &token("A4"), 120.0)) - 440.0).abs() < 1e-3);
+    assert!((number(call_unit("hz", &token("C4"), 120.0)) - 261.626).abs() < 1e-2);
+    assert!((number(call_unit("hz", &[Value::Number(81.0)], 120.0)) - 880.0).abs() < 1e-2);
+    assert!((number(call_unit("semitones", &[Value::Number(2.0)], 120.0)) - 12.0).abs() < 1e-4);
+    assert!((number(call_unit("semitones", &[Value::Number(1.5)], 120.0)) - 7.0196).abs() < 1e-3);
+}
+
+#[test]
+fn test_invalid_arguments_error_and_other_names_pass_through() {
+    assert!(matches!(call_unit("hz", &token("H4"), 120.0), Some(Err(_))));
+    assert!(matches!(
+        call_unit("semitones", &[Value::Number(0.0)], 120.0),
+        Some(Err(_))
+    ));
+    assert!(matches!(call_unit("ms", &[], 120.0), Some(Err(_))));
+    assert!(matches!(
+        call_unit("ms", &token("auto"), 120.0),
+        Some(Err(_))
+    ));
+    assert!(call_unit("transpose", &token("1/8"), 120.0).is_none());
+}
+
+#[test]
+fn test_unit_helpers_follow_the_current_tempo() -> Result<()> {
+    let source = "bpm 120\nlet gate = ms(1/8)\nlet len = beats(500ms)\nlet pitch = hz(A4)\nlet fifth = semitones(1.5)\nbpm 60\nlet slow = ms(1 bar)\n";
+    let statements = crate::language::syntax::parser::driver::parse(
+        source,
+        std::path::PathBuf::from("test.deva"),
+    )?;
+    let mut interp = AudioInterpreter::new(44100);
+    interp.collect_events(&statements)?;
+
+    let number = |name: &str| match interp.variables.get(name) {
+        Some(Value::Number(n)) => *n,
+        other => panic!("{name} should be a number, got {other:?}"),
+    };
+    assert!((number("gate") - 62.5).abs() < 1e-3);
+    assert!((number("len") - 1.0).abs() < 1e-5);
+    assert!((number("pitch") - 440.0).abs() < 1e-3);
+    assert!((number("fifth") - 7.0196).abs() < 1e-3);
+    assert!((number("slow") - 4000.0).abs() < 1e-2);
+
+    // Effect params on triggers use the tempo at the trigger
+    let (_, interp) = crash_count(
+        "bpm 120\n.kit.crash 1/4 -> delay({ time: ms(1/8), feedback: 0.3 })\nbpm 60\n.kit.crash -> delay(ms(1/4))\n",
+    )?;
+    let delays: Vec<Option<Value>> = interp
+        .events
+        .events
+        .iter()
+        .map(|event| match event {
+            crate::engine::audio::events::AudioEvent::Sample {
+                effects: Some(Value::Map(effects)),
+                ..
+            } => effects.get("delay").cloned(),
+            _ => None,
+        })
+        .collect();
+    assert!(
+        matches!(&delays[0], Some(Value::Map(delay)) if delay.get("time") == Some(&Value::Number(62.5)))
+    );
+    assert_eq!(delays[1], Some(Value::Number(250.0)));
+    Ok(())
+}
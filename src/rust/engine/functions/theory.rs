@@ -0,0 +1,249 @@
+/// Music-theory transforms on note collections
+///
+/// Usage (expressions): `let down = transpose(melody, -12)`
+/// Usage (arrow-calls on arrays): `melody -> retrograde() -> invert(E4)`
+///
+/// Available transforms:
+/// - transpose(notes, semitones): Shift every note by a number of semitones
+/// - retrograde(notes): Reverse the order of the notes
+/// - invert(notes, axis): Mirror every note around an axis note (defaults to the first note)
+/// - negativeHarmony(notes, key): Mirror pitch classes around the axis between the key's
+///   minor and major third (the midpoint of tonic and fifth), keeping each note in its
+///   original register
+///
+/// Notes may be MIDI numbers or spelled names (`C4`, `Eb3`, `B#3`, `Fbb4`, `Cx4`). Results keep
+/// the input kind: numbers stay numbers, names are re-spelled with sharps.
+use crate::language::syntax::ast::nodes::Value;
+use anyhow::{Result, anyhow};
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Returns true when `name` is one of the note collection transforms
+pub fn is_transform(name: &str) -> bool {
+    matches!(
+        name,
+        "transpose" | "retrograde" | "invert" | "negativeHarmony"
+    )
+}
+
+/// Execute a transform by name. `args[0]` is the note collection, the remaining arguments are
+/// the transform parameters. Returns `None` when `name` is not a transform.
+pub fn call_transform(name: &str, args: &[Value]) -> Option<Result<Value>> {
+    if !is_transform(name) {
+        return None;
+    }
+    let notes = match args.first() {
+        Some(Value::Array(items)) => items.as_slice(),
+        Some(other) => {
+            return Some(Err(anyhow!(
+                "{}() expects an array of notes, found {:?}",
+                name,
+                other
+            )));
+        }
+        None => return Some(Err(anyhow!("{}() requires an array of notes", name))),
+    };
+    let params = &args[1..];
+
+    Some(match name {
+        "transpose" => params
+            .first()
+            .ok_or_else(|| anyhow!("transpose() requires a number of semitones"))
+            .and_then(|v| match v {
+                Value::Number(n) => transpose(notes, n.round() as i32),
+                other => Err(anyhow!(
+                    "transpose() semitones must be a number, found {:?}",
+                    other
+                )),
+            }),
+        "retrograde" => Ok(retrograde(notes)),
+        "invert" => match params.first() {
+            Some(axis) => note_value_to_midi(axis).and_then(|axis| invert(notes, axis)),
+            None => match notes.iter().find_map(first_midi) {
+                Some(axis) => axis.and_then(|axis| invert(notes, axis)),
+                None => Ok(Value::Array(Vec::new())),
+            },
+        },
+        "negativeHarmony" => params
+            .first()
+            .ok_or_else(|| anyhow!("negativeHarmony() requires a key (e.g. \"C\")"))
+            .and_then(|key| match key {
+                Value::String(s) | Value::Identifier(s) => parse_key_root(s),
+                Value::Number(n) => Ok(n.round() as i32),
+                other => Err(anyhow!(
+                    "negativeHarmony() key must be a note, found {:?}",
+                    other
+                )),
+            })
+            .and_then(|root| negative_harmony(notes, root)),
+        _ => unreachable!(),
+    })
+}
+
+/// Shift every note by `semitones`
+pub fn transpose(notes: &[Value], semitones: i32) -> Result<Value> {
+    map_notes(notes, &|midi| midi + semitones)
+}
+
+/// Reverse the note order (nested chords are kept intact)
+pub fn retrograde(notes: &[Value]) -> Value {
+    Value::Array(
+        notes
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(idx, note)| match note {
+                // Parsed array literals wrap items as `{ index, value }`; keep indices in order
+                Value::Map(map) if map.contains_key("index") => {
+                    let mut map = map.clone();
+                    map.insert("index".to_string(), Value::Number(idx as f32));
+                    Value::Map(map)
+                }
+                other => other.clone(),
+            })
+            .collect(),
+    )
+}
+
+/// Mirror every note around `axis` (a MIDI note number)
+pub fn invert(notes: &[Value], axis: i32) -> Result<Value> {
+    map_notes(notes, &|midi| 2 * axis - midi)
+}
+
+/// Negative harmony in the key whose tonic pitch class is `root`.
+///
+/// Pitch classes are reflected around the axis between the minor and major third above the
+/// tonic (so C <-> G, E <-> Eb, D <-> F in C); each result is placed in the octave closest to
+/// the original note.
+pub fn negative_harmony(notes: &[Value], root: i32) -> Result<Value> {
+    let root = root.rem_euclid(12);
+    map_notes(notes, &|midi| {
+        let reflected_pc = (2 * root + 7 - midi).rem_euclid(12);
+        let base = midi - midi.rem_euclid(12) + reflected_pc;
+        [base - 12, base, base + 12]
+            .into_iter()
+            .min_by_key(|candidate| ((candidate - midi).abs(), *candidate))
+            .unwrap_or(base)
+    })
+}
+
+/// Parse a spelled note (`C4`, `eb3`, `B#3`, `Fbb4`, `Cx4`, `C-1`) into a MIDI number.
+/// Accidentals are applied before the octave, so `B#3` is C4 (60) and `Cb4` is B3 (59).
+pub fn parse_spelled_note(name: &str) -> Result<i32> {
+    let (pitch_class, rest) = parse_pitch(name)?;
+    let octave: i32 = rest
+        .parse()
+        .map_err(|_| anyhow!("Invalid octave in note '{}'", name))?;
+    Ok((octave + 1) * 12 + pitch_class)
+}
+
+/// Spell a MIDI number with sharps (`61` -> `C#4`)
+pub fn midi_to_name(midi: i32) -> String {
+    format!(
+        "{}{}",
+        NOTE_NAMES[midi.rem_euclid(12) as usize],
+        midi.div_euclid(12) - 1
+    )
+}
+
+/// Parse a key root such as `C`, `Eb`, `F#minor` into a pitch class (0-11)
+fn parse_key_root(key: &str) -> Result<i32> {
+    let (pitch_class, _mode) = parse_pitch(key)?;
+    Ok(pitch_class.rem_euclid(12))
+}
+
+/// Parse the letter and accidentals, returning the (unwrapped) pitch offset and the remainder
+fn parse_pitch(name: &str) -> Result<(i32, &str)> {
+    let trimmed = name.trim().trim_matches('"');
+    let mut chars = trimmed.char_indices();
+    let base = match chars.next().map(|(_, c)| c.to_ascii_uppercase()) {
+        Some('C') => 0,
+        Some('D') => 2,
+        Some('E') => 4,
+        Some('F') => 5,
+        Some('G') => 7,
+        Some('A') => 9,
+        Some('B') => 11,
+        _ => return Err(anyhow!("Invalid note name '{}'", name)),
+    };
+
+    let mut offset = 0;
+    let mut rest = &trimmed[1..];
+    for (idx, c) in chars {
+        match c {
+            '#' | '♯' => offset += 1,
+            'x' | '𝄪' => offset += 2,
+            'b' | '♭' => offset -= 1,
+            _ => {
+                rest = &trimmed[idx..];
+                break;
+            }
+        }
+        rest = &trimmed[idx + c.len_utf8()..];
+    }
+
+    Ok((base + offset, rest))
+}
+
+fn note_value_to_midi(value: &Value) -> Result<i32> {
+    match value {
+        Value::Number(n) => Ok(n.round() as i32),
+        Value::String(s) | Value::Identifier(s) => parse_spelled_note(s),
+        other => Err(anyhow!("Expected a note, found {:?}", other)),
+    }
+}
+
+fn first_midi(value: &Value) -> Option<Result<i32>> {
+    match value {
+        Value::Array(items) => items.iter().find_map(first_midi),
+        Value::Map(map) => map.get("value").and_then(first_midi),
+        Value::Null => None,
+        other => Some(note_value_to_midi(other)),
+    }
+}
+
+fn map_notes(notes: &[Value], f: &dyn Fn(i32) -> i32) -> Result<Value> {
+    notes
+        .iter()
+        .map(|note| map_note(note, f))
+        .collect::<Result<Vec<_>>>()
+        .map(Value::Array)
+}
+
+fn map_note(note: &Value, f: &dyn Fn(i32) -> i32) -> Result<Value> {
+    let checked = |midi: i32| -> Result<i32> {
+        let out = f(midi);
+        if !(0..=127).contains(&out) {
+            return Err(anyhow!("Transformed note out of MIDI range: {}", out));
+        }
+        Ok(out)
+    };
+
+    match note {
+        // Chords: transform each voice
+        Value::Array(items) => map_notes(items, f),
+        // Rests pass through untouched
+        Value::Null => Ok(Value::Null),
+        // Array literal items (`{ index, value }`): transform the wrapped note
+        Value::Map(map) if map.contains_key("value") => {
+            let mut map = map.clone();
+            let inner = map.get("value").cloned().unwrap_or(Value::Null);
+            map.insert("value".to_string(), map_note(&inner, f)?);
+            Ok(Value::Map(map))
+        }
+        Value::Number(n) => Ok(Value::Number(checked(n.round() as i32)? as f32)),
+        Value::String(s) => Ok(Value::String(midi_to_name(checked(parse_spelled_note(
+            s,
+        )?)?))),
+        Value::Identifier(s) => Ok(Value::Identifier(midi_to_name(checked(
+            parse_spelled_note(s)?,
+        )?))),
+        other => Err(anyhow!("Expected a note, found {:?}", other)),
+    }
+}
+
+#[cfg(test)]
+#[path = "test_theory.rs"]
+mod tests;
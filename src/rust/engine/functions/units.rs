@@ -0,0 +1,86 @@
+/// Unit conversions and tempo-relative math
+///
+/// Usage (expressions): `let gate = ms(1/8)`, `delay({ time: ms(1/8d) })`, `let f = hz(A4)`
+///
+/// Each helper is named after the unit it returns:
+/// - ms(duration): Length in milliseconds at the current tempo (`ms(1/8)`, `ms(1 bar)`)
+/// - beats(duration): Length in beats at the current tempo (`beats(500ms)`, `beats(2s)`)
+/// - hz(note): Frequency of a note name or MIDI number in 12-TET with A4 = 440 Hz
+/// - semitones(ratio): Interval of a frequency ratio in semitones (`semitones(1.5)` is ~7.02)
+///
+/// Durations accept every duration literal (`1/8d`, `1/4t`, `2 beats`, `1/8 + 10ms`); plain
+/// numbers are milliseconds, as everywhere else in the language.
+use crate::engine::functions::theory::parse_spelled_note;
+use crate::language::syntax::ast::nodes::{DurationValue, Value};
+use crate::language::syntax::parser::driver::duration::parse_duration_token;
+use anyhow::{Result, anyhow};
+
+/// Reference pitch for `hz()`: A4 (MIDI 69)
+const A4_HZ: f32 = 440.0;
+
+/// Returns true when `name` is one of the unit helpers
+pub fn is_unit_function(name: &str) -> bool {
+    matches!(name, "ms" | "beats" | "hz" | "semitones")
+}
+
+/// Execute a unit helper by name, resolving durations against `bpm`. Returns `None` when
+/// `name` is not a unit helper.
+pub fn call_unit(name: &str, args: &[Value], bpm: f32) -> Option<Result<Value>> {
+    if !is_unit_function(name) {
+        return None;
+    }
+    let Some(arg) = args.first() else {
+        return Some(Err(anyhow!("{}() requires one argument", name)));
+    };
+
+    Some(
+        match name {
+            "ms" => duration_seconds(name, arg, bpm).map(|seconds| seconds * 1000.0),
+            "beats" => duration_seconds(name, arg, bpm).map(|seconds| seconds * bpm / 60.0),
+            "hz" => note_midi(arg).map(midi_to_hz),
+            _ => ratio_semitones(arg),
+        }
+        .map(Value::Number),
+    )
+}
+
+/// Frequency of a (fractional) MIDI note number
+pub fn midi_to_hz(midi: f32) -> f32 {
+    A4_HZ * 2.0_f32.powf((midi - 69.0) / 12.0)
+}
+
+fn duration_seconds(name: &str, value: &Value, bpm: f32) -> Result<f32> {
+    let duration = match value {
+        Value::Number(ms) => DurationValue::Milliseconds(*ms),
+        Value::Duration(duration) => duration.clone(),
+        Value::String(token) | Value::Identifier(token) => parse_duration_token(token)?,
+        other => {
+            return Err(anyhow!("{}() expects a duration, found {:?}", name, other));
+        }
+    };
+    duration
+        .to_seconds(bpm)
+        .ok_or_else(|| anyhow!("{}() cannot resolve duration {:?}", name, duration))
+}
+
+fn note_midi(value: &Value) -> Result<f32> {
+    match value {
+        Value::Number(midi) => Ok(*midi),
+        Value::String(note) | Value::Identifier(note) => parse_spelled_note(note).map(|m| m as f32),
+        other => Err(anyhow!("hz() expects a note, found {:?}", other)),
+    }
+}
+
+fn ratio_semitones(value: &Value) -> Result<f32> {
+    match value {
+        Value::Number(ratio) if *ratio > 0.0 => Ok(12.0 * ratio.log2()),
+        other => Err(anyhow!(
+            "semitones() expects a positive frequency ratio, found {:?}",
+            other
+        )),
+    }
+}
+
+#[cfg(test)]
+#[path = "test_units.rs"]
+mod tests;
@@ -1,6 +1,7 @@
 pub mod audio;
 pub mod bridges;
 pub mod curves;
+pub mod embed;
 pub mod events;
 pub mod functions;
 pub mod plugin;
@@ -92,6 +92,202 @@ macro_rules! export_plugin {
     };
 }
 
+/// Export a plugin using the extended ABI with note list and pattern context.
+///
+/// In addition to the single-note arguments of [`export_plugin!`], the host passes
+/// every note pending in the current block together with the BPM and beat phase.
+/// Hosts detect the extended signature automatically; plugins exported with
+/// [`export_plugin!`] keep working unchanged.
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use devalang_bindings::*;
+///
+/// export_plugin_with_context!(my_arp, |out, params, _note, notes, ctx, _freq, amp| {
+///     let step_frames = (ctx.beat_ms() / 4.0 / 1000.0 * params.sample_rate as f32) as u32;
+///     // Cycle through `notes` every sixteenth...
+/// });
+/// ```
+///
+/// # Parameters
+///
+/// The closure receives:
+/// - `out: &mut [f32]` - Output buffer (interleaved)
+/// - `params: BufferParams` - Sample rate, channels, frames
+/// - `note: Note` - The note that triggered this block
+/// - `notes: &[PendingNote]` - All notes pending in this block
+/// - `ctx: PatternContext` - BPM and beat phase
+/// - `freq: f32` - Frequency in Hz
+/// - `amp: f32` - Amplitude (0.0 to 1.0)
+#[macro_export]
+macro_rules! export_plugin_with_context {
+    ($name:ident, $impl_fn:expr) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn $name(
+            out_ptr: *mut f32,
+            out_len: i32,
+            freq: f32,
+            amp: f32,
+            duration_ms: i32,
+            sample_rate: i32,
+            channels: i32,
+            notes_ptr: *const u32,
+            notes_len: i32,
+            bpm: f32,
+            beat_phase: f32,
+        ) {
+            if out_ptr.is_null() {
+                return;
+            }
+
+            let out_len_usize = out_len.max(0) as usize;
+            if out_len_usize == 0 {
+                return;
+            }
+
+            let channels_val = channels.max(1) as u32;
+            let sample_rate_val = sample_rate.max(1) as u32;
+            let frames = (out_len_usize / channels_val as usize) as u32;
+
+            let params = $crate::engine::plugin::bindings::types::BufferParams {
+                sample_rate: sample_rate_val,
+                channels: channels_val,
+                frames,
+            };
+
+            // Recover the MIDI pitch from the frequency sent by the host
+            let pitch = if freq > 0.0 {
+                (69.0 + 12.0 * (freq / 440.0).log2()).round().clamp(0.0, 127.0) as u8
+            } else {
+                60
+            };
+
+            let note = $crate::engine::plugin::bindings::types::Note {
+                pitch,
+                velocity: (amp.clamp(0.0, 1.0) * 127.0) as u8,
+                duration_ms: duration_ms.max(1) as u32,
+            };
+
+            let ctx = $crate::engine::plugin::bindings::types::PatternContext { bpm, beat_phase };
+
+            let implementation: fn(
+                &mut [f32],
+                $crate::engine::plugin::bindings::types::BufferParams,
+                $crate::engine::plugin::bindings::types::Note,
+                &[$crate::engine::plugin::bindings::types::PendingNote],
+                $crate::engine::plugin::bindings::types::PatternContext,
+                f32,
+                f32,
+            ) = $impl_fn;
+
+            // SAFETY: Host guarantees valid pointers and lengths
+            unsafe {
+                let notes = if notes_ptr.is_null() || notes_len <= 0 {
+                    Vec::new()
+                } else {
+                    let words = core::slice::from_raw_parts(notes_ptr, notes_len as usize);
+                    $crate::engine::plugin::bindings::types::PendingNote::decode_all(words)
+                };
+
+                let out = core::slice::from_raw_parts_mut(out_ptr, out_len_usize);
+                implementation(out, params, note, &notes, ctx, freq, amp);
+            }
+        }
+    };
+}
+
+/// Export an effect that processes an existing buffer.
+///
+/// The host hands the export the audio of a chain or insert block by block, along
+/// with the parameter values set on the track. Declare the export in `plugin.toml`
+/// with `kind = "effect"` and list its parameters in order:
+///
+/// ```toml
+/// [[exports]]
+/// name = "crush"
+/// kind = "effect"
+/// params = ["bits", "mix"]
+/// ```
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use devalang_bindings::*;
+///
+/// export_effect_plugin!(crush, |input, out, _params, fx| {
+///     let steps = 2.0_f32.powf(fx.get(0, 8.0));
+///     let mix = fx.get(1, 1.0);
+///     for (dry, wet) in input.iter().zip(out.iter_mut()) {
+///         let crushed = (dry * steps).round() / steps;
+///         *wet = dry + (crushed - dry) * mix;
+///     }
+/// });
+/// ```
+///
+/// # Parameters
+///
+/// The closure receives:
+/// - `input: &[f32]` - Audio to process (interleaved)
+/// - `out: &mut [f32]` - Output buffer, same length as `input`
+/// - `params: BufferParams` - Sample rate, channels, frames
+/// - `fx: EffectParams` - Parameter values in `plugin.toml` order
+#[macro_export]
+macro_rules! export_effect_plugin {
+    ($name:ident, $impl_fn:expr) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn $name(
+            in_ptr: *const f32,
+            out_ptr: *mut f32,
+            len: i32,
+            sample_rate: i32,
+            channels: i32,
+            params_ptr: *const f32,
+            params_len: i32,
+        ) {
+            if in_ptr.is_null() || out_ptr.is_null() {
+                return;
+            }
+
+            let len_usize = len.max(0) as usize;
+            if len_usize == 0 {
+                return;
+            }
+
+            let channels_val = channels.max(1) as u32;
+            let params = $crate::engine::plugin::bindings::types::BufferParams {
+                sample_rate: sample_rate.max(1) as u32,
+                channels: channels_val,
+                frames: (len_usize / channels_val as usize) as u32,
+            };
+
+            // SAFETY: Host guarantees valid, non-overlapping input and output buffers
+            unsafe {
+                let input = core::slice::from_raw_parts(in_ptr, len_usize);
+                let out = core::slice::from_raw_parts_mut(out_ptr, len_usize);
+                let values: &[f32] = if params_ptr.is_null() || params_len <= 0 {
+                    &[]
+                } else {
+                    core::slice::from_raw_parts(params_ptr, params_len as usize)
+                };
+
+                let implementation: fn(
+                    &[f32],
+                    &mut [f32],
+                    $crate::engine::plugin::bindings::types::BufferParams,
+                    $crate::engine::plugin::bindings::types::EffectParams<'_>,
+                ) = $impl_fn;
+                implementation(
+                    input,
+                    out,
+                    params,
+                    $crate::engine::plugin::bindings::types::EffectParams::new(values),
+                );
+            }
+        }
+    };
+}
+
 /// Export a plugin with parameter setters.
 ///
 /// This macro creates both a render function and parameter setter functions.
@@ -10,4 +10,7 @@ pub mod types;
 
 // Re-export commonly used items
 pub use oscillators::{ADSREnvelope, LowPassFilter, Oscillator};
-pub use types::{BufferParams, Note, RenderFn, RenderFnExt, Waveform};
+pub use types::{
+    BufferParams, EffectParams, EffectRenderFn, Note, PatternContext, PendingNote, RenderFn,
+    RenderFnExt, RenderFnWithContext, Waveform,
+};
@@ -0,0 +1,73 @@
+use super::*;
+
+#[test]
+fn test_pending_notes_round_trip_through_words() {
+    let notes = [
+        PendingNote {
+            note: Note::new(60, 100, 250),
+            offset_ms: 0,
+        },
+        PendingNote {
+            note: Note::new(67, 80, 125),
+            offset_ms: 125,
+        },
+    ];
+    let mut words: Vec<u32> = notes.iter().flat_map(PendingNote::to_words).collect();
+    assert_eq!(words, vec![60, 100, 250, 0, 67, 80, 125, 125]);
+    assert_eq!(PendingNote::decode_all(&words), notes);
+
+    // An incomplete trailing note is dropped and out-of-range pitches are clamped
+    words[0] = 300;
+    words.push(1);
+    let decoded = PendingNote::decode_all(&words);
+    assert_eq!(decoded.len(), 2);
+    assert_eq!(decoded[0].note.pitch, 127);
+}
+
+crate::export_plugin_with_context!(
+    context_probe,
+    |out, params, note, notes, ctx, _freq, _amp| {
+        out[0] = params.frames as f32;
+        out[1] = note.pitch as f32;
+        out[2] = notes.len() as f32;
+        out[3] = notes.last().map_or(0.0, |n| n.offset_ms as f32);
+        out[4] = ctx.beat_ms();
+    }
+);
+
+#[test]
+fn test_context_export_decodes_host_arguments() {
+    let mut out = [0.0f32; 8];
+    let words = [60u32, 100, 250, 0, 64, 90, 250, 250];
+    context_probe(
+        out.as_mut_ptr(),
+        out.len() as i32,
+        440.0,
+        0.8,
+        250,
+        44_100,
+        2,
+        words.as_ptr(),
+        words.len() as i32,
+        120.0,
+        0.5,
+    );
+    assert_eq!(&out[..5], &[4.0, 69.0, 2.0, 250.0, 500.0]);
+
+    // Without notes (a null pointer) the block still renders, at middle C
+    let mut out = [0.0f32; 8];
+    context_probe(
+        out.as_mut_ptr(),
+        out.len() as i32,
+        0.0,
+        1.0,
+        0,
+        44_100,
+        1,
+        std::ptr::null(),
+        0,
+        60.0,
+        0.0,
+    );
+    assert_eq!(&out[..5], &[8.0, 60.0, 0.0, 0.0, 1000.0]);
+}
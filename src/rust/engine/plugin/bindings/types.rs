@@ -55,6 +55,74 @@ impl Note {
     }
 }
 
+/// A note scheduled within the current render block.
+///
+/// Used by plugins that opt into the extended ABI to receive every pending
+/// note of a block instead of a single pitch.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PendingNote {
+    /// The note itself
+    pub note: Note,
+    /// Offset from the start of the block in milliseconds
+    pub offset_ms: u32,
+}
+
+impl PendingNote {
+    /// Number of `u32` words used to encode one note across the ABI boundary.
+    pub const STRIDE: usize = 4;
+
+    /// Encode as `[pitch, velocity, duration_ms, offset_ms]`.
+    pub fn to_words(&self) -> [u32; Self::STRIDE] {
+        [
+            self.note.pitch as u32,
+            self.note.velocity as u32,
+            self.note.duration_ms,
+            self.offset_ms,
+        ]
+    }
+
+    /// Decode a flat `u32` buffer written by the host into pending notes.
+    ///
+    /// Trailing words that do not form a complete note are ignored.
+    pub fn decode_all(words: &[u32]) -> Vec<PendingNote> {
+        words
+            .chunks_exact(Self::STRIDE)
+            .map(|w| PendingNote {
+                note: Note::new(w[0].min(127) as u8, w[1].min(127) as u8, w[2]),
+                offset_ms: w[3],
+            })
+            .collect()
+    }
+}
+
+/// Musical context for the current render block.
+///
+/// Lets plugins build tempo-aware behaviour such as arpeggiators or
+/// sequenced textures.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PatternContext {
+    /// Tempo in beats per minute
+    pub bpm: f32,
+    /// Position inside the current beat (0.0 to 1.0) at the start of the block
+    pub beat_phase: f32,
+}
+
+impl Default for PatternContext {
+    fn default() -> Self {
+        Self {
+            bpm: 120.0,
+            beat_phase: 0.0,
+        }
+    }
+}
+
+impl PatternContext {
+    /// Duration of one beat in milliseconds.
+    pub fn beat_ms(&self) -> f32 {
+        60_000.0 / self.bpm.max(1.0)
+    }
+}
+
 /// Parameters describing the audio buffer and rendering context.
 ///
 /// This struct contains all the information needed to render audio correctly.
@@ -147,6 +215,67 @@ pub type RenderFnExt = fn(
     time_ms: u64,
 );
 
+/// Render function signature for plugins using the extended (context) ABI.
+///
+/// # Parameters
+///
+/// - `out`: Mutable slice to write audio samples (interleaved if stereo)
+/// - `params`: Buffer and audio context parameters
+/// - `note`: The note that triggered this block
+/// - `notes`: Every note pending in this block, including `note`
+/// - `ctx`: Tempo and beat phase of the block
+/// - `freq`: Frequency in Hz (derived from note pitch)
+/// - `amp`: Amplitude (0.0 to 1.0)
+pub type RenderFnWithContext = fn(
+    out: &mut [f32],
+    params: BufferParams,
+    note: Note,
+    notes: &[PendingNote],
+    ctx: PatternContext,
+    freq: f32,
+    amp: f32,
+);
+
+/// Parameter values passed to an effect export.
+///
+/// Values arrive in the order the export lists them under `params` in
+/// `plugin.toml`; parameters the track does not set are sent as NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectParams<'a> {
+    values: &'a [f32],
+}
+
+impl<'a> EffectParams<'a> {
+    pub fn new(values: &'a [f32]) -> Self {
+        Self { values }
+    }
+
+    /// Value of the parameter at `index`, or `default` when it was not set.
+    pub fn get(&self, index: usize, default: f32) -> f32 {
+        self.values
+            .get(index)
+            .copied()
+            .filter(|value| !value.is_nan())
+            .unwrap_or(default)
+    }
+
+    /// Raw values, NaN for unset parameters.
+    pub fn values(&self) -> &'a [f32] {
+        self.values
+    }
+}
+
+/// Signature for effect plugins, which process an existing buffer.
+///
+/// # Parameters
+///
+/// - `input`: Audio to process (interleaved if stereo)
+/// - `out`: Mutable slice of the same length receiving the processed audio
+/// - `params`: Buffer and audio context parameters
+/// - `fx`: Effect parameter values (see [`EffectParams`])
+pub type EffectRenderFn =
+    fn(input: &[f32], out: &mut [f32], params: BufferParams, fx: EffectParams<'_>);
+
 /// Waveform types for oscillators
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Waveform {
@@ -168,3 +297,7 @@ impl Waveform {
         }
     }
 }
+
+#[cfg(test)]
+#[path = "test_types.rs"]
+mod tests;
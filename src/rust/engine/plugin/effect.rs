@@ -0,0 +1,191 @@
+//! References to plugin effect exports in effect chains
+//!
+//! Scripts point at an effect export through the alias of a `use` statement:
+//! `-> plugin(crusher.crush)` on a chain, or `group drums -> plugin({ ref: crusher.crush, bits: 6 }):`
+//! for an insert. The interpreter rewrites those entries so the reference is stored as a
+//! plain `author.name:export` string under `ref`, which the mixer can load on its own.
+
+use crate::language::syntax::ast::Value;
+use std::collections::HashMap;
+
+/// Effect name that selects a plugin effect in a chain
+pub const PLUGIN_EFFECT: &str = "plugin";
+
+/// Effect export of an installed plugin
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PluginEffectRef {
+    pub author: String,
+    pub name: String,
+    pub export: String,
+}
+
+impl PluginEffectRef {
+    /// Parse the canonical `author.name:export` form
+    pub fn parse(raw: &str) -> Option<Self> {
+        let (plugin, export) = raw.trim().split_once(':')?;
+        let (author, name) = plugin.split_once('.')?;
+        if [author, name, export]
+            .iter()
+            .any(|part| part.is_empty() || part.contains(char::is_whitespace))
+        {
+            return None;
+        }
+        Some(Self {
+            author: author.to_string(),
+            name: name.to_string(),
+            export: export.to_string(),
+        })
+    }
+
+    /// Read the export map bound by `use author.name as alias` (`alias.export`)
+    pub fn from_export_map(map: &HashMap<String, Value>) -> Option<Self> {
+        let field = |key: &str| match map.get(key) {
+            Some(Value::String(s)) => Some(s.clone()),
+            _ => None,
+        };
+        Some(Self {
+            author: field("_plugin_author")?,
+            name: field("_plugin_name")?,
+            export: field("_export_name")?,
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl std::fmt::Display for PluginEffectRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}:{}", self.author, self.name, self.export)
+    }
+}
+
+/// Rewrite the `plugin` entries of an effect list so each carries a canonical `ref`.
+///
+/// Both entry shapes are handled: `{ plugin: params }` from group inserts and
+/// `{ type: "plugin", ... }` from chained calls. `resolve` looks up an alias path such
+/// as `crusher.crush` and returns `Value::Null` when nothing is bound.
+pub fn normalize_effect_list(
+    effects: &Value,
+    mut resolve: impl FnMut(&str) -> Value,
+) -> Result<Value, String> {
+    let Value::Array(entries) = effects else {
+        return Ok(effects.clone());
+    };
+
+    let mut normalized = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let Value::Map(map) = entry else {
+            normalized.push(entry.clone());
+            continue;
+        };
+        if is_plugin_type(map) {
+            let mut params = normalize_plugin_params(&Value::Map(map.clone()), &mut resolve)?;
+            params.insert("type".to_string(), Value::String(PLUGIN_EFFECT.to_string()));
+            normalized.push(Value::Map(params));
+        } else if let Some(params) = map.get(PLUGIN_EFFECT) {
+            let mut map = map.clone();
+            map.insert(
+                PLUGIN_EFFECT.to_string(),
+                Value::Map(normalize_plugin_params(params, &mut resolve)?),
+            );
+            normalized.push(Value::Map(map));
+        } else {
+            normalized.push(entry.clone());
+        }
+    }
+    Ok(Value::Array(normalized))
+}
+
+/// Parameters of one `plugin` effect with its reference resolved into `ref`.
+///
+/// `params` is the export itself (`plugin(crusher.crush)`) or a map holding it under
+/// `ref` or `value` next to the effect parameters.
+pub fn normalize_plugin_params(
+    params: &Value,
+    resolve: &mut impl FnMut(&str) -> Value,
+) -> Result<HashMap<String, Value>, String> {
+    let (target, mut rest) = match params {
+        Value::Map(map) if !map.contains_key("_export_name") => {
+            let mut rest = map.clone();
+            rest.remove("type");
+            let target = rest
+                .remove("ref")
+                .or_else(|| rest.remove("value"))
+                .unwrap_or(Value::Null);
+            (target, rest)
+        }
+        other => (other.clone(), HashMap::new()),
+    };
+
+    let reference = resolve_reference(&target, resolve)?;
+    rest.insert("ref".to_string(), reference.to_value());
+    Ok(rest)
+}
+
+/// Canonical reference stored by `normalize_plugin_params`
+pub fn plugin_ref(params: &HashMap<String, Value>) -> Option<PluginEffectRef> {
+    match params.get("ref") {
+        Some(Value::String(raw)) => PluginEffectRef::parse(raw),
+        _ => None,
+    }
+}
+
+/// Values for an effect export's `declared` parameters, NaN where `params` sets none
+pub fn ordered_params(declared: &[String], params: &HashMap<String, Value>) -> Vec<f32> {
+    declared
+        .iter()
+        .map(|name| match params.get(name) {
+            Some(Value::Number(n)) => *n,
+            Some(Value::Boolean(b)) => *b as u8 as f32,
+            Some(Value::String(s)) => s.parse::<f32>().unwrap_or(f32::NAN),
+            _ => f32::NAN,
+        })
+        .collect()
+}
+
+fn resolve_reference(
+    target: &Value,
+    resolve: &mut impl FnMut(&str) -> Value,
+) -> Result<PluginEffectRef, String> {
+    match target {
+        Value::Map(map) => {
+            let reference = PluginEffectRef::from_export_map(map)
+                .ok_or_else(|| "plugin effect reference is not a plugin export".to_string())?;
+            match map.get("_export_kind") {
+                Some(Value::String(kind)) if kind != "effect" => Err(format!(
+                    "plugin export '{}' is a {} export, not an effect",
+                    reference, kind
+                )),
+                _ => Ok(reference),
+            }
+        }
+        Value::String(raw) | Value::Identifier(raw) => match resolve(raw) {
+            resolved @ Value::Map(_) => resolve_reference(&resolved, resolve),
+            Value::String(bound) => PluginEffectRef::parse(&bound)
+                .ok_or_else(|| format!("unknown plugin effect '{}'", raw)),
+            _ => PluginEffectRef::parse(raw).ok_or_else(|| {
+                format!(
+                    "unknown plugin effect '{}' (expected alias.export or author.name:export)",
+                    raw
+                )
+            }),
+        },
+        Value::Null => {
+            Err("plugin effect needs a reference, e.g. plugin(alias.export)".to_string())
+        }
+        other => Err(format!("invalid plugin effect reference {:?}", other)),
+    }
+}
+
+fn is_plugin_type(map: &HashMap<String, Value>) -> bool {
+    matches!(
+        map.get("type"),
+        Some(Value::String(name)) | Some(Value::Identifier(name)) if name == PLUGIN_EFFECT
+    )
+}
+
+#[cfg(test)]
+#[path = "test_effect.rs"]
+mod tests;
@@ -1,5 +1,5 @@
 #[cfg(feature = "cli")]
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct PluginInfo {
@@ -14,6 +14,8 @@ pub struct PluginInfo {
 pub struct PluginExport {
     pub name: String,
     pub kind: String,
+    /// Parameter names of an effect export, in the order they are passed
+    pub params: Vec<String>,
 }
 
 #[cfg(feature = "cli")]
@@ -42,15 +44,13 @@ pub fn load_plugin(author: &str, name: &str) -> Result<(PluginInfo, Vec<u8>), St
     struct LocalExportEntry {
         name: String,
         kind: String,
+        #[serde(default)]
+        params: Vec<String>,
     }
 
-    // Find .deva directory from current dir or parents
-    let deva_dir = find_deva_dir()?;
-
-    // Try new layout: .deva/plugins/<publisher>/<name>/
-    let plugin_dir = deva_dir.join("plugins").join(author).join(name);
-    let toml_path = plugin_dir.join("plugin.toml");
-    let wasm_path = plugin_dir.join(format!("{}.wasm", name));
+    let current_dir =
+        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+    let (toml_path, wasm_path) = plugin_files(&current_dir, author, name)?;
 
     if !toml_path.exists() {
         return Err(format!("❌ Plugin file not found: {}", toml_path.display()));
@@ -85,6 +85,7 @@ pub fn load_plugin(author: &str, name: &str) -> Result<(PluginInfo, Vec<u8>), St
             .map(|e| PluginExport {
                 name: e.name.clone(),
                 kind: e.kind.clone(),
+                params: e.params.clone(),
             })
             .collect(),
     };
@@ -92,15 +93,24 @@ pub fn load_plugin(author: &str, name: &str) -> Result<(PluginInfo, Vec<u8>), St
     Ok((info, wasm_bytes))
 }
 
+/// Manifest and module of the plugin `author.name`, in the `.deva` directory of `start`
+/// or its closest parent (`.deva/plugins/<publisher>/<name>/`). The files may not exist.
 #[cfg(feature = "cli")]
-fn find_deva_dir() -> Result<PathBuf, String> {
-    use std::env;
-
-    let current_dir =
-        env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
+pub fn plugin_files(start: &Path, author: &str, name: &str) -> Result<(PathBuf, PathBuf), String> {
+    let plugin_dir = find_deva_dir(start)?
+        .join("plugins")
+        .join(author)
+        .join(name);
+    Ok((
+        plugin_dir.join("plugin.toml"),
+        plugin_dir.join(format!("{}.wasm", name)),
+    ))
+}
 
-    // Start from current dir and walk up to find .deva
-    let mut dir = current_dir.as_path();
+#[cfg(feature = "cli")]
+fn find_deva_dir(start: &Path) -> Result<PathBuf, String> {
+    // Start from `start` and walk up to find .deva
+    let mut dir = start;
     loop {
         let deva_path = dir.join(".deva");
         if deva_path.exists() && deva_path.is_dir() {
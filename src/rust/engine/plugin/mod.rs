@@ -5,6 +5,9 @@ pub mod bindings;
 #[cfg(feature = "plugin")]
 pub use bindings::*;
 
+// Plugin effect references in effect chains
+pub mod effect;
+
 // Plugin loading and running (only available in CLI)
 #[cfg(feature = "cli")]
 pub mod loader;
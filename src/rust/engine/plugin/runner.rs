@@ -1,7 +1,9 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 #[cfg(feature = "cli")]
-use wasmtime::{Engine, Instance, Linker, Module, Store};
+use crate::engine::audio::generator::PluginContext;
+#[cfg(feature = "cli")]
+use wasmtime::{Engine, Instance, Linker, Memory, Module, Store, TypedFunc};
 
 #[cfg(feature = "cli")]
 pub struct WasmPluginRunner {
@@ -27,11 +29,20 @@ impl WasmPluginRunner {
         }
     }
 
+    /// Compilation engine, for `EffectInstance::new`
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
     /// Renders a note using a WASM plugin with optional parameter overrides
     ///
     /// Tries multiple function name patterns in order:
     /// 1. Named export (if synth_name provided): e.g. "synth", "saw"
     /// 2. Generic "render_note"
+    ///
+    /// Exports built with the extended ABI (see `export_plugin_with_context!`) also
+    /// receive the pending notes of the block plus BPM and beat phase. Exports using
+    /// the original 7-argument signature are called as before.
     pub fn render_note_in_place(
         &self,
         wasm_bytes: &[u8],
@@ -44,71 +55,18 @@ impl WasmPluginRunner {
         sample_rate: i32,
         channels: i32,
         options: Option<&HashMap<String, f32>>,
+        context: Option<&PluginContext>,
     ) -> Result<(), String> {
         // Hash du WASM + instance_key pour avoir une instance par synth!
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        let mut hasher = DefaultHasher::new();
-        wasm_bytes.hash(&mut hasher);
-        instance_key.hash(&mut hasher); // Use instance_key (synth_id) for caching!
-        let hash = hasher.finish();
+        let hash = Self::instance_hash(wasm_bytes, instance_key);
 
         // Mutably borrow the cache
         let mut cache = self.cache.borrow_mut();
 
         // Create instance if it doesn't exist
-        if !cache.contains_key(&hash) {
+        if let std::collections::hash_map::Entry::Vacant(slot) = cache.entry(hash) {
             // Creating new instance for synth_id
-            let module = Module::new(&self.engine, wasm_bytes)
-                .map_err(|e| format!("Failed to compile wasm: {e}"))?;
-
-            let mut store = Store::new(&self.engine, ());
-            let mut linker = Linker::new(&self.engine);
-
-            // Add wasm-bindgen placeholder imports (for plugins compiled with wasm-bindgen)
-            linker
-                .func_wrap(
-                    "__wbindgen_placeholder__",
-                    "__wbindgen_describe",
-                    |_: i32| {},
-                )
-                .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
-            linker
-                .func_wrap(
-                    "__wbindgen_placeholder__",
-                    "__wbindgen_object_clone_ref",
-                    |_: i32| -> i32 { 0 },
-                )
-                .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
-            linker
-                .func_wrap(
-                    "__wbindgen_placeholder__",
-                    "__wbindgen_object_drop_ref",
-                    |_: i32| {},
-                )
-                .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
-            linker
-                .func_wrap(
-                    "__wbindgen_placeholder__",
-                    "__wbindgen_string_new",
-                    |_: i32, _: i32| -> i32 { 0 },
-                )
-                .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
-            linker
-                .func_wrap(
-                    "__wbindgen_placeholder__",
-                    "__wbindgen_throw",
-                    |_: i32, _: i32| {},
-                )
-                .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
-
-            let instance = linker
-                .instantiate(&mut store, &module)
-                .map_err(|e| format!("Failed to instantiate wasm: {e}"))?;
-
-            cache.insert(hash, (store, instance));
-        } else {
-            // Reusing cached instance for synth_id
+            slot.insert(self.instantiate(wasm_bytes)?);
         }
 
         // Retrieve cached instance
@@ -133,15 +91,33 @@ impl WasmPluginRunner {
             "render_note"
         };
 
-        let func = entry
+        // Prefer the extended signature (notes + pattern context) when exported
+        let func_ext = entry
             .1
-            .get_typed_func::<(i32, i32, f32, f32, i32, i32, i32), ()>(&mut entry.0, func_name)
-            .map_err(|e| {
-                format!(
-                    "Function '{}' not found or wrong signature: {}",
-                    func_name, e
-                )
-            })?;
+            .get_typed_func::<(i32, i32, f32, f32, i32, i32, i32, i32, i32, f32, f32), ()>(
+                &mut entry.0,
+                func_name,
+            )
+            .ok();
+
+        let func = if func_ext.is_none() {
+            Some(
+                entry
+                    .1
+                    .get_typed_func::<(i32, i32, f32, f32, i32, i32, i32), ()>(
+                        &mut entry.0,
+                        func_name,
+                    )
+                    .map_err(|e| {
+                        format!(
+                            "Function '{}' not found or wrong signature: {}",
+                            func_name, e
+                        )
+                    })?,
+            )
+        } else {
+            None
+        };
 
         // Apply plugin options by calling setter functions if available
         if let Some(opts) = options {
@@ -198,19 +174,61 @@ impl WasmPluginRunner {
         mem_slice.copy_from_slice(src_bytes);
 
         // Call the plugin function
-        func.call(
-            &mut entry.0,
-            (
-                ptr,
-                buffer.len() as i32,
-                freq,
-                amp,
-                duration_ms,
-                sample_rate,
-                channels,
-            ),
-        )
-        .map_err(|e| format!("Error calling '{}': {}", func_name, e))?;
+        if let Some(func_ext) = func_ext {
+            let words = Self::encode_context_notes(context);
+            let (notes_ptr, notes_len) = if words.is_empty() {
+                (0, 0)
+            } else {
+                let notes_byte_len = std::mem::size_of_val(words.as_slice());
+                let notes_ptr =
+                    Self::alloc_temp(&mut entry.0, &entry.1, &memory, notes_byte_len)? as i32;
+                let notes_slice = memory
+                    .data_mut(&mut entry.0)
+                    .get_mut(notes_ptr as usize..(notes_ptr as usize) + notes_byte_len)
+                    .ok_or_else(|| "Failed to get notes memory slice".to_string())?;
+                for (chunk, word) in notes_slice.chunks_exact_mut(4).zip(words.iter()) {
+                    chunk.copy_from_slice(&word.to_le_bytes());
+                }
+                (notes_ptr, words.len() as i32)
+            };
+
+            let (bpm, beat_phase) = context
+                .map(|c| (c.bpm, c.beat_phase))
+                .unwrap_or((120.0, 0.0));
+
+            func_ext
+                .call(
+                    &mut entry.0,
+                    (
+                        ptr,
+                        buffer.len() as i32,
+                        freq,
+                        amp,
+                        duration_ms,
+                        sample_rate,
+                        channels,
+                        notes_ptr,
+                        notes_len,
+                        bpm,
+                        beat_phase,
+                    ),
+                )
+                .map_err(|e| format!("Error calling '{}': {}", func_name, e))?;
+        } else if let Some(func) = func {
+            func.call(
+                &mut entry.0,
+                (
+                    ptr,
+                    buffer.len() as i32,
+                    freq,
+                    amp,
+                    duration_ms,
+                    sample_rate,
+                    channels,
+                ),
+            )
+            .map_err(|e| format!("Error calling '{}': {}", func_name, e))?;
+        }
 
         // Copy result back from WASM memory
         let mem_slice_after = memory
@@ -229,6 +247,38 @@ impl WasmPluginRunner {
         Ok(())
     }
 
+    /// Cache key of the instance for `wasm_bytes` and `instance_key`
+    fn instance_hash(wasm_bytes: &[u8], instance_key: Option<&str>) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        wasm_bytes.hash(&mut hasher);
+        instance_key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Compile and instantiate a plugin module
+    fn instantiate(&self, wasm_bytes: &[u8]) -> Result<(Store<()>, Instance), String> {
+        instantiate(&self.engine, wasm_bytes)
+    }
+
+    /// Flatten pending notes into `[pitch, velocity, duration_ms, offset_ms]` words
+    /// (the layout decoded by `PendingNote::decode_all` on the plugin side)
+    fn encode_context_notes(context: Option<&PluginContext>) -> Vec<u32> {
+        let Some(ctx) = context else {
+            return Vec::new();
+        };
+
+        let mut words = Vec::with_capacity(ctx.notes.len() * 4);
+        for note in &ctx.notes {
+            words.push(note.midi.min(127) as u32);
+            words.push((note.velocity.clamp(0.0, 1.0) * 127.0).round() as u32);
+            words.push(note.duration_ms.max(0.0) as u32);
+            words.push(note.offset_ms.max(0.0) as u32);
+        }
+        words
+    }
+
     /// Helper to allocate temporary memory in WASM module
     fn alloc_temp(
         store: &mut Store<()>,
@@ -252,3 +302,192 @@ impl WasmPluginRunner {
         Ok(ptr)
     }
 }
+
+/// Compile `wasm_bytes` and instantiate it with the imports wasm-bindgen plugins expect
+#[cfg(feature = "cli")]
+fn instantiate(engine: &Engine, wasm_bytes: &[u8]) -> Result<(Store<()>, Instance), String> {
+    let module =
+        Module::new(engine, wasm_bytes).map_err(|e| format!("Failed to compile wasm: {e}"))?;
+
+    let mut store = Store::new(engine, ());
+    let mut linker = Linker::new(engine);
+
+    // Add wasm-bindgen placeholder imports (for plugins compiled with wasm-bindgen)
+    linker
+        .func_wrap(
+            "__wbindgen_placeholder__",
+            "__wbindgen_describe",
+            |_: i32| {},
+        )
+        .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
+    linker
+        .func_wrap(
+            "__wbindgen_placeholder__",
+            "__wbindgen_object_clone_ref",
+            |_: i32| -> i32 { 0 },
+        )
+        .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
+    linker
+        .func_wrap(
+            "__wbindgen_placeholder__",
+            "__wbindgen_object_drop_ref",
+            |_: i32| {},
+        )
+        .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
+    linker
+        .func_wrap(
+            "__wbindgen_placeholder__",
+            "__wbindgen_string_new",
+            |_: i32, _: i32| -> i32 { 0 },
+        )
+        .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
+    linker
+        .func_wrap(
+            "__wbindgen_placeholder__",
+            "__wbindgen_throw",
+            |_: i32, _: i32| {},
+        )
+        .map_err(|e| format!("Failed to define wbindgen import: {e}"))?;
+
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .map_err(|e| format!("Failed to instantiate wasm: {e}"))?;
+
+    Ok((store, instance))
+}
+
+/// `(input, output, len, sample_rate, channels, params, params_len)`
+#[cfg(feature = "cli")]
+type EffectFn = TypedFunc<(i32, i32, i32, i32, i32, i32, i32), ()>;
+
+/// One instance of an effect export (see `export_effect_plugin!`), driven block by block.
+///
+/// Effect exports take the input buffer, an output buffer of the same length and the
+/// parameter values in declaration order. The instance lives as long as its owner, so
+/// effect state such as delay lines survives from one block to the next, and input,
+/// output and params share one region at the end of its memory that is grown in place
+/// when a longer block comes in.
+#[cfg(feature = "cli")]
+pub struct EffectInstance {
+    store: Store<()>,
+    memory: Memory,
+    func: EffectFn,
+    export: String,
+    /// Start and length of the scratch region
+    scratch: Option<(usize, usize)>,
+}
+
+#[cfg(feature = "cli")]
+impl std::fmt::Debug for EffectInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EffectInstance")
+            .field("export", &self.export)
+            .field("scratch", &self.scratch)
+            .finish()
+    }
+}
+
+#[cfg(feature = "cli")]
+impl EffectInstance {
+    /// Instantiate `wasm_bytes` and look up its effect export `export`
+    pub fn new(engine: &Engine, wasm_bytes: &[u8], export: &str) -> Result<Self, String> {
+        let (mut store, instance) = instantiate(engine, wasm_bytes)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| "WASM memory export not found".to_string())?;
+        let func = instance
+            .get_typed_func::<(i32, i32, i32, i32, i32, i32, i32), ()>(&mut store, export)
+            .map_err(|e| format!("Effect '{}' not found or wrong signature: {}", export, e))?;
+        Ok(Self {
+            store,
+            memory,
+            func,
+            export: export.to_string(),
+            scratch: None,
+        })
+    }
+
+    /// Run the effect over `input` (`channels` interleaved), writing the result to `output`
+    pub fn process(
+        &mut self,
+        input: &[f32],
+        output: &mut [f32],
+        sample_rate: i32,
+        channels: i32,
+        params: &[f32],
+    ) -> Result<(), String> {
+        if input.len() != output.len() {
+            return Err("Effect input and output buffers differ in length".to_string());
+        }
+
+        let buffer_bytes = std::mem::size_of_val(input);
+        let params_bytes = std::mem::size_of_val(params);
+        let in_ptr = self.reserve(buffer_bytes * 2 + params_bytes)?;
+        let out_ptr = in_ptr + buffer_bytes;
+        let params_ptr = out_ptr + buffer_bytes;
+
+        let region = self
+            .memory
+            .data_mut(&mut self.store)
+            .get_mut(in_ptr..params_ptr + params_bytes)
+            .ok_or_else(|| "Failed to get effect memory slice".to_string())?;
+        for (chunk, sample) in region[..buffer_bytes].chunks_exact_mut(4).zip(input) {
+            chunk.copy_from_slice(&sample.to_le_bytes());
+        }
+        for (chunk, value) in region[buffer_bytes * 2..].chunks_exact_mut(4).zip(params) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+
+        self.func
+            .call(
+                &mut self.store,
+                (
+                    in_ptr as i32,
+                    out_ptr as i32,
+                    input.len() as i32,
+                    sample_rate,
+                    channels,
+                    if params.is_empty() {
+                        0
+                    } else {
+                        params_ptr as i32
+                    },
+                    params.len() as i32,
+                ),
+            )
+            .map_err(|e| format!("Error calling '{}': {}", self.export, e))?;
+
+        let processed = self
+            .memory
+            .data(&self.store)
+            .get(out_ptr..out_ptr + buffer_bytes)
+            .ok_or_else(|| "Failed to get effect memory slice after".to_string())?;
+        for (sample, chunk) in output.iter_mut().zip(processed.chunks_exact(4)) {
+            *sample = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+
+        Ok(())
+    }
+
+    /// Start of a scratch region of at least `len` bytes. The region sits at the end of
+    /// memory, so a longer block grows it in place instead of leaving the old one behind.
+    fn reserve(&mut self, len: usize) -> Result<usize, String> {
+        let start = match self.scratch {
+            Some((start, capacity)) if capacity >= len => return Ok(start),
+            Some((start, _)) => start,
+            None => self.memory.data_size(&self.store),
+        };
+        let missing = (start + len).saturating_sub(self.memory.data_size(&self.store));
+        if missing > 0 {
+            self.memory
+                .grow(&mut self.store, missing.div_ceil(65536) as u64)
+                .map_err(|e| format!("Failed to grow memory: {}", e))?;
+        }
+        self.scratch = Some((start, len));
+        Ok(start)
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+#[path = "test_runner.rs"]
+mod tests;
@@ -0,0 +1,117 @@
+use super::*;
+
+fn export(kind: &str) -> Value {
+    let mut map = HashMap::new();
+    map.insert(
+        "_plugin_author".to_string(),
+        Value::String("devaloop".into()),
+    );
+    map.insert("_plugin_name".to_string(), Value::String("crusher".into()));
+    map.insert("_export_name".to_string(), Value::String("crush".into()));
+    map.insert("_export_kind".to_string(), Value::String(kind.into()));
+    Value::Map(map)
+}
+
+fn resolver(kind: &'static str) -> impl FnMut(&str) -> Value {
+    move |path| {
+        if path == "fx.crush" {
+            export(kind)
+        } else {
+            Value::Null
+        }
+    }
+}
+
+#[test]
+fn test_parse_canonical_reference() {
+    let reference = PluginEffectRef::parse("devaloop.crusher:crush").unwrap();
+    assert_eq!(reference.author, "devaloop");
+    assert_eq!(reference.export, "crush");
+    assert_eq!(reference.to_string(), "devaloop.crusher:crush");
+    assert!(PluginEffectRef::parse("crusher:crush").is_none());
+    assert!(PluginEffectRef::parse("devaloop.crusher:").is_none());
+}
+
+#[test]
+fn test_normalize_both_entry_shapes() {
+    // Group insert: `plugin({ ref: fx.crush, bits: 6 })`
+    let mut params = HashMap::new();
+    params.insert("ref".to_string(), Value::String("fx.crush".into()));
+    params.insert("bits".to_string(), Value::Number(6.0));
+    let mut insert = HashMap::new();
+    insert.insert("plugin".to_string(), Value::Map(params));
+
+    // Chained call: `-> plugin(fx.crush)` merges the export map into the entry
+    let Value::Map(mut chained) = export("effect") else {
+        unreachable!()
+    };
+    chained.insert("type".to_string(), Value::String("plugin".into()));
+
+    let normalized = normalize_effect_list(
+        &Value::Array(vec![Value::Map(insert), Value::Map(chained)]),
+        resolver("effect"),
+    )
+    .unwrap();
+    let Value::Array(entries) = normalized else {
+        panic!("expected a list")
+    };
+
+    let Value::Map(first) = &entries[0] else {
+        panic!()
+    };
+    let Some(Value::Map(first)) = first.get("plugin") else {
+        panic!()
+    };
+    assert_eq!(
+        plugin_ref(first).unwrap().to_string(),
+        "devaloop.crusher:crush"
+    );
+    assert_eq!(first.get("bits"), Some(&Value::Number(6.0)));
+
+    let Value::Map(second) = &entries[1] else {
+        panic!()
+    };
+    assert_eq!(second.get("type"), Some(&Value::String("plugin".into())));
+    assert_eq!(
+        second.get("ref"),
+        Some(&Value::String("devaloop.crusher:crush".into()))
+    );
+    assert!(!second.contains_key("_export_name"));
+
+    // Normalizing again leaves the canonical form alone
+    assert_eq!(
+        normalize_effect_list(&Value::Array(entries.clone()), |_| Value::Null).unwrap(),
+        Value::Array(entries)
+    );
+}
+
+#[test]
+fn test_normalize_rejects_non_effects() {
+    let entry = |params: Value| {
+        let mut map = HashMap::new();
+        map.insert("plugin".to_string(), params);
+        Value::Array(vec![Value::Map(map)])
+    };
+
+    let err = normalize_effect_list(&entry(Value::String("fx.crush".into())), resolver("synth"))
+        .unwrap_err();
+    assert!(err.contains("synth export"), "{err}");
+    assert!(normalize_effect_list(&entry(Value::Null), resolver("effect")).is_err());
+    assert!(
+        normalize_effect_list(&entry(Value::String("missing".into())), resolver("effect")).is_err()
+    );
+}
+
+#[test]
+fn test_ordered_params_follow_declaration() {
+    let declared = vec!["bits".to_string(), "mix".to_string(), "rate".to_string()];
+    let mut params = HashMap::new();
+    params.insert("mix".to_string(), Value::Number(0.5));
+    params.insert("bits".to_string(), Value::String("6".into()));
+    params.insert("unused".to_string(), Value::Number(1.0));
+
+    let values = ordered_params(&declared, &params);
+    assert_eq!(&values[..2], &[6.0, 0.5]);
+    assert!(values[2].is_nan());
+    assert!(ordered_params(&[], &params).is_empty());
+}
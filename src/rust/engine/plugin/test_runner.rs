@@ -0,0 +1,162 @@
+use super::*;
+use crate::engine::audio::generator::PluginPendingNote;
+
+/// `out[i] = in[i] * params[0] + channels`
+const SCALE_EFFECT: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "scale") (param $in i32) (param $out i32) (param $len i32) (param $rate i32)
+        (param $channels i32) (param $params i32) (param $count i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+        (f32.store
+          (i32.add (local.get $out) (i32.shl (local.get $i) (i32.const 2)))
+          (f32.add
+            (f32.mul
+              (f32.load (i32.add (local.get $in) (i32.shl (local.get $i) (i32.const 2))))
+              (f32.load (local.get $params)))
+            (f32.convert_i32_u (local.get $channels))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))))
+"#;
+
+#[test]
+fn test_effect_instance_passes_buffers_params_and_channels() {
+    let engine = Engine::default();
+    let mut instance = EffectInstance::new(&engine, SCALE_EFFECT.as_bytes(), "scale").unwrap();
+    let mut output = [0.0f32; 4];
+    instance
+        .process(&[1.0, 2.0, 3.0, 4.0], &mut output, 44_100, 3, &[0.5])
+        .unwrap();
+    assert_eq!(output, [3.5, 4.0, 4.5, 5.0]);
+
+    assert!(EffectInstance::new(&engine, SCALE_EFFECT.as_bytes(), "missing").is_err());
+    assert!(
+        instance
+            .process(&[1.0], &mut output, 44_100, 2, &[1.0])
+            .is_err()
+    );
+}
+
+#[test]
+fn test_effect_scratch_region_grows_in_place() {
+    let engine = Engine::default();
+    let mut instance = EffectInstance::new(&engine, SCALE_EFFECT.as_bytes(), "scale").unwrap();
+    let small = vec![1.0f32; 256];
+    let large = vec![1.0f32; 40_000];
+
+    instance
+        .process(&small, &mut vec![0.0; small.len()], 44_100, 2, &[1.0])
+        .unwrap();
+    let after_small = instance.memory.data_size(&instance.store);
+    for _ in 0..4 {
+        let mut output = vec![0.0; large.len()];
+        instance
+            .process(&large, &mut output, 44_100, 2, &[1.0])
+            .unwrap();
+        assert!(output.iter().all(|s| *s == 3.0));
+        instance
+            .process(&small, &mut vec![0.0; small.len()], 44_100, 2, &[1.0])
+            .unwrap();
+    }
+
+    // Input, output and params of the large block need 320_004 bytes on top of the
+    // original memory; repeated blocks reuse that region
+    let grown = instance.memory.data_size(&instance.store) - after_small;
+    assert!(grown <= 320_004 + 65_536, "memory grew by {} bytes", grown);
+}
+
+/// Extended ABI: writes `[notes_len, bpm, beat_phase, first pitch, second offset_ms]`
+const CONTEXT_SYNTH: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "synth") (param $out i32) (param $len i32) (param $freq f32) (param $amp f32)
+        (param $duration i32) (param $rate i32) (param $channels i32) (param $notes i32)
+        (param $count i32) (param $bpm f32) (param $phase f32)
+    (f32.store (local.get $out) (f32.convert_i32_s (local.get $count)))
+    (f32.store offset=4 (local.get $out) (local.get $bpm))
+    (f32.store offset=8 (local.get $out) (local.get $phase))
+    (f32.store offset=12 (local.get $out) (f32.convert_i32_u (i32.load (local.get $notes))))
+    (f32.store offset=16 (local.get $out)
+      (f32.convert_i32_u (i32.load offset=28 (local.get $notes))))))
+"#;
+
+/// Original ABI: writes `[channels, freq]`
+const PLAIN_SYNTH: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "render_note") (param $out i32) (param $len i32) (param $freq f32)
+        (param $amp f32) (param $duration i32) (param $rate i32) (param $channels i32)
+    (f32.store (local.get $out) (f32.convert_i32_s (local.get $channels)))
+    (f32.store offset=4 (local.get $out) (local.get $freq))))
+"#;
+
+fn pending(midi: u8, velocity: f32, duration_ms: f32, offset_ms: f32) -> PluginPendingNote {
+    PluginPendingNote {
+        midi,
+        velocity,
+        duration_ms,
+        offset_ms,
+    }
+}
+
+#[test]
+fn test_context_exports_receive_block_notes_and_tempo() {
+    let runner = WasmPluginRunner::new();
+    let context = PluginContext {
+        notes: vec![pending(60, 1.0, 250.0, 0.0), pending(67, 0.5, 250.0, 125.0)],
+        bpm: 96.0,
+        beat_phase: 0.25,
+    };
+    let mut buffer = [0.0f32; 8];
+    runner
+        .render_note_in_place(
+            CONTEXT_SYNTH.as_bytes(),
+            &mut buffer,
+            None,
+            Some("synth"),
+            261.6,
+            1.0,
+            250,
+            44_100,
+            2,
+            None,
+            Some(&context),
+        )
+        .unwrap();
+    assert_eq!(&buffer[..5], &[8.0, 96.0, 0.25, 60.0, 125.0]);
+
+    // Plugins built before the extended ABI are called with their seven arguments
+    let mut buffer = [0.0f32; 4];
+    runner
+        .render_note_in_place(
+            PLAIN_SYNTH.as_bytes(),
+            &mut buffer,
+            None,
+            Some("synth"),
+            440.0,
+            1.0,
+            250,
+            44_100,
+            2,
+            None,
+            Some(&context),
+        )
+        .unwrap();
+    assert_eq!(&buffer[..2], &[2.0, 440.0]);
+}
+
+#[test]
+fn test_context_notes_encode_as_clamped_words() {
+    let context = PluginContext {
+        notes: vec![pending(200, 2.0, -5.0, 10.4), pending(64, 0.5, 300.0, -1.0)],
+        ..Default::default()
+    };
+    assert_eq!(
+        WasmPluginRunner::encode_context_notes(Some(&context)),
+        vec![127, 127, 0, 10, 64, 64, 300, 0]
+    );
+    assert!(WasmPluginRunner::encode_context_notes(None).is_empty());
+}
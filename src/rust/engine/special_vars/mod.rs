@@ -1,7 +1,10 @@
 /// Special variables system for Devalang
 /// Provides runtime-computed variables like $beat, $time, $random, etc.
+use crate::engine::audio::midi::MidiChord;
 use crate::language::syntax::ast::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Special variable prefix
 pub const SPECIAL_VAR_PREFIX: char = '$';
@@ -13,22 +16,80 @@ pub enum SpecialVarCategory {
     Random,   // $random.noise, $random.float, $random.int
     Music,    // $bpm, $tempo, $duration
     Position, // $position, $progress
-    Midi,     // $midi.note, $midi.velocity
+    Midi,     // $midi.chord, $midi.chord.root, $midi.chord.quality
     System,   // $sampleRate, $channels
 }
 
 /// Special variable context - holds runtime state
 #[derive(Debug, Clone)]
 pub struct SpecialVarContext {
-    pub current_time: f32,   // Current playback time in seconds
-    pub current_beat: f32,   // Current beat position
-    pub current_bar: f32,    // Current bar position
-    pub bpm: f32,            // Current BPM
-    pub duration: f32,       // Beat duration in seconds
-    pub sample_rate: u32,    // Sample rate
-    pub channels: usize,     // Number of channels
-    pub position: f32,       // Normalized position (0.0-1.0)
-    pub total_duration: f32, // Total duration in seconds
+    pub current_time: f32,    // Current playback time in seconds
+    pub current_beat: f32,    // Current beat position
+    pub current_bar: f32,     // Current bar position
+    pub bpm: f32,             // Current BPM
+    pub duration: f32,        // Beat duration in seconds
+    pub sample_rate: u32,     // Sample rate
+    pub channels: usize,      // Number of channels
+    pub position: f32,        // Normalized position (0.0-1.0)
+    pub total_duration: f32,  // Total duration in seconds
+    pub random: RandomSource, // Source for $random.* values
+    pub midi_chords: Arc<Vec<Option<MidiChord>>>, // Chords per bar of the bound MIDI file
+}
+
+/// Source of `$random.*` values. Unseeded it draws from the thread RNG; seeded
+/// (deterministic builds) it yields the same sequence on every run.
+#[derive(Debug, Clone, Default)]
+pub struct RandomSource {
+    seed: Option<u64>,
+    draws: Arc<AtomicU64>,
+}
+
+impl RandomSource {
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            draws: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn is_seeded(&self) -> bool {
+        self.seed.is_some()
+    }
+
+    /// Independent stream for a parallel branch (e.g. a spawn) so its draws don't
+    /// depend on thread scheduling. Unseeded sources are shared as-is.
+    pub fn fork(&self, key: u64) -> Self {
+        match self.seed {
+            Some(seed) => Self::seeded(splitmix64(seed ^ splitmix64(key))),
+            None => self.clone(),
+        }
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        match self.seed {
+            Some(seed) => {
+                let draw = self.draws.fetch_add(1, Ordering::Relaxed);
+                splitmix64(seed.wrapping_add(draw.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+            }
+            #[cfg(any(feature = "cli", feature = "wasm"))]
+            None => rand::random::<u64>(),
+            #[cfg(not(any(feature = "cli", feature = "wasm")))]
+            None => splitmix64(self.draws.fetch_add(1, Ordering::Relaxed)),
+        }
+    }
+
+    /// Uniform value in `[0, 1)`
+    pub fn next_f32(&self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// splitmix64 finalizer: cheap, well-distributed 64-bit mixing
+pub fn splitmix64(mut state: u64) -> u64 {
+    state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    state ^ (state >> 31)
 }
 
 impl Default for SpecialVarContext {
@@ -43,6 +104,8 @@ impl Default for SpecialVarContext {
             channels: 2,
             position: 0.0,
             total_duration: 0.0,
+            random: RandomSource::default(),
+            midi_chords: Arc::new(Vec::new()),
         }
     }
 }
@@ -68,6 +131,12 @@ impl SpecialVarContext {
         }
     }
 
+    /// Chord detected in the current bar of the bound MIDI file, if any
+    pub fn current_chord(&self) -> Option<&MidiChord> {
+        let bar = (self.current_bar + 1e-6).floor().max(0.0) as usize;
+        self.midi_chords.get(bar)?.as_ref()
+    }
+
     /// Update BPM
     pub fn update_bpm(&mut self, bpm: f32) {
         self.bpm = bpm;
@@ -115,30 +184,42 @@ pub fn resolve_special_var(name: &str, context: &SpecialVarContext) -> Option<Va
         "$sampleRate" => Some(Value::Number(context.sample_rate as f32)),
         "$channels" => Some(Value::Number(context.channels as f32)),
 
+        // MIDI harmony variables (Null in bars without a detected chord)
+        "$midi.chord" => Some(chord_value(context, MidiChord::symbol)),
+        "$midi.chord.root" => Some(chord_value(context, |chord| chord.root.clone())),
+        "$midi.chord.quality" => Some(chord_value(context, |chord| chord.quality.clone())),
+
         // Random variables (computed on-demand)
         #[cfg(any(feature = "cli", feature = "wasm"))]
-        "$random" | "$random.float" => Some(Value::Number(rand::random::<f32>())),
+        "$random" | "$random.float" => Some(Value::Number(context.random.next_f32())),
         #[cfg(any(feature = "cli", feature = "wasm"))]
-        "$random.noise" => Some(Value::Number(rand::random::<f32>() * 2.0 - 1.0)), // -1.0 to 1.0
+        "$random.noise" => Some(Value::Number(context.random.next_f32() * 2.0 - 1.0)), // -1.0 to 1.0
         #[cfg(any(feature = "cli", feature = "wasm"))]
-        "$random.int" => Some(Value::Number((rand::random::<u32>() % 100) as f32)),
+        "$random.int" => Some(Value::Number((context.random.next_u64() % 100) as f32)),
         #[cfg(any(feature = "cli", feature = "wasm"))]
-        "$random.bool" => Some(Value::Boolean(rand::random::<bool>())),
+        "$random.bool" => Some(Value::Boolean(context.random.next_u64() & 1 == 1)),
 
         // Nested random with ranges
         #[cfg(any(feature = "cli", feature = "wasm"))]
         _ if name.starts_with("$random.range(") => {
             // Parse $random.range(min, max)
-            parse_random_range(name)
+            parse_random_range(name, &context.random)
         }
 
         _ => None,
     }
 }
 
+/// Project the current bar's chord to a string, or Null when there is none
+fn chord_value(context: &SpecialVarContext, field: impl Fn(&MidiChord) -> String) -> Value {
+    context
+        .current_chord()
+        .map_or(Value::Null, |chord| Value::String(field(chord)))
+}
+
 /// Parse $random.range(min, max) syntax
 #[cfg(any(feature = "cli", feature = "wasm"))]
-fn parse_random_range(name: &str) -> Option<Value> {
+fn parse_random_range(name: &str, random: &RandomSource) -> Option<Value> {
     // Extract content between parentheses
     let start = name.find('(')?;
     let end = name.rfind(')')?;
@@ -155,7 +236,7 @@ fn parse_random_range(name: &str) -> Option<Value> {
     let max: f32 = parts[1].parse().ok()?;
 
     // Generate random value in range
-    let value = min + rand::random::<f32>() * (max - min);
+    let value = min + random.next_f32() * (max - min);
     Some(Value::Number(value))
 }
 
@@ -206,6 +287,20 @@ pub fn get_all_special_vars(context: &SpecialVarContext) -> HashMap<String, Valu
         Value::Number(context.channels as f32),
     );
 
+    // MIDI
+    vars.insert(
+        "$midi.chord".to_string(),
+        chord_value(context, MidiChord::symbol),
+    );
+    vars.insert(
+        "$midi.chord.root".to_string(),
+        chord_value(context, |chord| chord.root.clone()),
+    );
+    vars.insert(
+        "$midi.chord.quality".to_string(),
+        chord_value(context, |chord| chord.quality.clone()),
+    );
+
     vars
 }
 
@@ -254,6 +349,21 @@ pub fn list_special_vars() -> HashMap<&'static str, Vec<(&'static str, &'static
         ],
     );
 
+    categories.insert(
+        "Midi",
+        vec![
+            (
+                "$midi.chord",
+                "Chord symbol of the current bar (e.g. Amin7)",
+            ),
+            ("$midi.chord.root", "Root note of the current bar's chord"),
+            (
+                "$midi.chord.quality",
+                "Quality of the current bar's chord (maj, min7...)",
+            ),
+        ],
+    );
+
     categories.insert(
         "System",
         vec![
@@ -43,10 +43,70 @@ fn test_context_update_time() {
 
 #[test]
 fn test_parse_random_range() {
-    let result = parse_random_range("$random.range(0, 10)");
+    let result = parse_random_range("$random.range(0, 10)", &RandomSource::default());
     assert!(result.is_some());
 
     if let Some(Value::Number(n)) = result {
         assert!(n >= 0.0 && n <= 10.0);
     }
 }
+
+#[test]
+fn test_seeded_random_is_reproducible() {
+    let draw = |seed: u64| -> Vec<Option<Value>> {
+        let mut context = SpecialVarContext::new(120.0, 44100);
+        context.random = RandomSource::seeded(seed);
+        [
+            "$random",
+            "$random.int",
+            "$random.noise",
+            "$random.range(2, 4)",
+        ]
+        .iter()
+        .map(|name| resolve_special_var(name, &context))
+        .collect()
+    };
+
+    assert_eq!(draw(7), draw(7));
+    assert_ne!(draw(7), draw(8));
+
+    // Forked streams are stable per key and independent of the parent's draws
+    let parent = RandomSource::seeded(7);
+    let before = parent.fork(1).next_u64();
+    parent.next_u64();
+    assert_eq!(before, parent.fork(1).next_u64());
+    assert_ne!(before, parent.fork(2).next_u64());
+}
+
+#[test]
+fn test_midi_chord_follows_current_bar() {
+    let mut context = SpecialVarContext::new(120.0, 44100);
+    context.midi_chords = Arc::new(vec![
+        Some(MidiChord {
+            root: "A".to_string(),
+            quality: "min".to_string(),
+        }),
+        None,
+    ]);
+
+    assert_eq!(
+        resolve_special_var("$midi.chord", &context),
+        Some(Value::String("Amin".to_string()))
+    );
+    assert_eq!(
+        resolve_special_var("$midi.chord.root", &context),
+        Some(Value::String("A".to_string()))
+    );
+
+    // Second bar has no chord, and bars past the file resolve to Null as well
+    context.update_time(2.0);
+    assert_eq!(
+        resolve_special_var("$midi.chord.quality", &context),
+        Some(Value::Null)
+    );
+    context.update_time(60.0);
+    assert_eq!(
+        resolve_special_var("$midi.chord", &context),
+        Some(Value::Null)
+    );
+}
@@ -0,0 +1,129 @@
+use super::*;
+
+#[test]
+fn test_eval_keeps_state_between_sources() -> Result<()> {
+    let mut engine = DevalangEngine::new(8_000);
+    engine.eval("bpm 120\nlet lead = synth saw\nlet steps = 3")?;
+    engine.eval("lead -> note(C4) -> duration(1/4)\nlead -> note(E4) -> duration(1/4)")?;
+
+    assert_eq!(engine.get_variable("steps"), Some(&Value::Number(3.0)));
+    assert!(engine.get_variable("missing").is_none());
+    assert_eq!(engine.bpm(), 120.0);
+    let starts: Vec<f32> = engine
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Note { start_time, .. } => Some(*start_time),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(starts, vec![0.0, 0.125]);
+
+    engine.reset();
+    assert!(engine.events().is_empty() && engine.get_variable("lead").is_none());
+    assert!(engine.eval("lead -> note(C4").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_render_returns_the_requested_range() -> Result<()> {
+    let mut engine = DevalangEngine::new(8_000);
+    engine.eval("bpm 120\nlet lead = synth saw\nlead -> note(A4) -> duration(1/2)")?;
+    let full = engine.render(0.0..engine.duration())?;
+    let slice = engine.render(0.1..0.2)?;
+    assert_eq!(slice.len(), 800 * RENDER_CHANNELS);
+    assert_eq!(slice, full[1600..3200]);
+    assert!(engine.render(0.3..0.2)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_builder_rejects_names_it_cannot_provide() -> Result<()> {
+    let err = DevalangEngine::builder(8_000)
+        .with_effects(["reverb", "wobble"])
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "unknown effect 'wobble'");
+    let err = DevalangEngine::builder(8_000)
+        .with_synth_types(["pluck", "theremin"])
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "unknown synth type 'theremin'");
+
+    // Without a selection everything stays available
+    let mut engine = DevalangEngine::builder(8_000).build()?;
+    engine.eval("bpm 120\nlet lead = synth saw\nlead -> note(A4) -> duration(1/4)")?;
+    assert_eq!(engine.events().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_eval_cell_reports_what_the_cell_changed() -> Result<()> {
+    let mut engine = DevalangEngine::new(8_000);
+    let first = engine.eval_cell("let lead = synth saw\nlet steps = 3")?;
+    assert_eq!(first.new_events, 0);
+    assert_eq!(
+        first.variables,
+        vec!["lead".to_string(), "steps".to_string()]
+    );
+
+    let second = engine.eval_cell("let steps = 4\nlead -> note(C4) -> duration(1/4)")?;
+    assert_eq!(second.new_events, 1);
+    assert_eq!(second.variables, vec!["steps".to_string()]);
+
+    assert!(engine.eval_cell("lead -> note(C4").is_err());
+    assert_eq!(engine.events().len(), 1);
+    assert_eq!(engine.get_variable("steps"), Some(&Value::Number(4.0)));
+    Ok(())
+}
+
+#[test]
+fn test_cell_sessions_do_not_share_variables() -> Result<()> {
+    let mut first = DevalangEngine::new(8_000);
+    let mut second = DevalangEngine::new(8_000);
+    first.eval_cell("let lead = synth saw")?;
+    assert!(second.get_variable("lead").is_none());
+    assert_eq!(
+        second.eval_cell("let lead = 2")?.variables,
+        vec!["lead".to_string()]
+    );
+    assert_eq!(second.get_variable("lead"), Some(&Value::Number(2.0)));
+    assert_ne!(first.get_variable("lead"), Some(&Value::Number(2.0)));
+
+    first.reset();
+    assert!(first.get_variable("lead").is_none());
+    assert_eq!(
+        first.eval_cell("let pad = synth sine")?.variables,
+        vec!["pad".to_string()]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_restricted_engine_renders_without_what_it_leaves_out() -> Result<()> {
+    let plain_source = "bpm 120\nlet lead = synth saw\nlead -> note(A4) -> duration(1/4)";
+    let fx_source = "bpm 120\nlet lead = synth saw { type: pluck }\nlead -> note(A4) -> duration(1/4) -> reverb({ size: 0.9, mix: 0.6 })";
+
+    let mut plain = DevalangEngine::new(8_000);
+    plain.eval(plain_source)?;
+    let mut restricted = DevalangEngine::builder(8_000)
+        .with_effects(["lpf"])
+        .with_synth_types(["pad"])
+        .build()?;
+    restricted.eval(fx_source)?;
+    // Built after the restricted one, and unaffected by it
+    let mut full = DevalangEngine::new(8_000);
+    full.eval(fx_source)?;
+
+    let end = plain.duration();
+    assert_eq!(restricted.render(0.0..end)?, plain.render(0.0..end)?);
+    assert_ne!(full.render(0.0..end)?, plain.render(0.0..end)?);
+
+    // `reset` keeps the selection
+    restricted.reset();
+    restricted.eval(fx_source)?;
+    assert_eq!(restricted.render(0.0..end)?, plain.render(0.0..end)?);
+    Ok(())
+}
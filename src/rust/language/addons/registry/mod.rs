@@ -127,18 +127,123 @@ impl BankDefinition {
     }
 }
 
+/// Where a bank comes from, in the order bank-less triggers (`.kick`) are resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BankOrigin {
+    /// Bank kept in the project tree (`bank ./banks/kit`)
+    Local,
+    /// The `[banks] default` bank
+    Default,
+    /// Addon installed under `.deva/banks`
+    Installed,
+}
+
+impl BankOrigin {
+    pub fn label(self) -> &'static str {
+        match self {
+            BankOrigin::Local => "local",
+            BankOrigin::Default => "default",
+            BankOrigin::Installed => "installed",
+        }
+    }
+}
+
+/// A registered bank providing a bank-less trigger
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerCandidate {
+    pub alias: String,
+    pub identifier: String,
+    pub origin: BankOrigin,
+    pub path: PathBuf,
+}
+
 #[derive(Default, Clone)]
 pub struct BankRegistry {
     banks: HashMap<String, BankDefinition>,
+    /// Bank substitutions keyed by alias or bank identifier (`play --remap kit=devaloop.909`)
+    remaps: HashMap<String, String>,
+    /// Identifier of the `[banks] default` bank
+    default_bank: Option<String>,
 }
 
 impl BankRegistry {
     pub fn new() -> Self {
         Self {
             banks: HashMap::new(),
+            remaps: HashMap::new(),
+            default_bank: None,
+        }
+    }
+
+    /// Bank that bank-less triggers fall back to after local banks (`[banks] default`)
+    pub fn set_default_bank(&mut self, identifier: Option<String>) {
+        self.default_bank = identifier;
+    }
+
+    pub fn default_bank(&self) -> Option<&str> {
+        self.default_bank.as_deref()
+    }
+
+    /// Whether a bank with this identifier is registered under any alias
+    pub fn has_identifier(&self, identifier: &str) -> bool {
+        self.banks
+            .values()
+            .any(|bank| bank.identifier == identifier)
+    }
+
+    /// Registered banks providing `trigger`, best first: local project banks, then the
+    /// default bank, then installed banks (by alias within each group). A bank registered
+    /// under several aliases is listed once.
+    pub fn implicit_candidates(&self, trigger: &str) -> Vec<TriggerCandidate> {
+        let mut candidates: Vec<TriggerCandidate> = self
+            .banks
+            .iter()
+            .filter_map(|(alias, bank)| {
+                Some(TriggerCandidate {
+                    alias: alias.clone(),
+                    identifier: bank.identifier.clone(),
+                    origin: self.origin(bank),
+                    path: bank.resolve_trigger(trigger)?,
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| (a.origin, &a.alias).cmp(&(b.origin, &b.alias)));
+        let mut seen = HashSet::new();
+        candidates.retain(|candidate| seen.insert(candidate.identifier.clone()));
+        candidates
+    }
+
+    fn origin(&self, bank: &BankDefinition) -> BankOrigin {
+        if self.default_bank.as_deref() == Some(bank.identifier.as_str()) {
+            return BankOrigin::Default;
+        }
+        let installed = bank
+            .root_dir
+            .components()
+            .collect::<Vec<_>>()
+            .windows(2)
+            .any(|pair| pair[0].as_os_str() == ".deva" && pair[1].as_os_str() == "banks");
+        if installed {
+            BankOrigin::Installed
+        } else {
+            BankOrigin::Local
         }
     }
 
+    /// Load `identifier` wherever the source asks for `from` (an alias or a bank identifier)
+    pub fn remap(&mut self, from: impl Into<String>, identifier: impl Into<String>) {
+        self.remaps.insert(from.into(), identifier.into());
+    }
+
+    /// Bank to load for `bank <identifier> as <alias>`, after remapping
+    pub fn remapped<'a>(&'a self, alias: &str, identifier: &'a str) -> &'a str {
+        self.remaps
+            .get(alias)
+            .or_else(|| self.remaps.get(identifier))
+            .map(String::as_str)
+            .unwrap_or(identifier)
+    }
+
     pub fn register_bank(
         &mut self,
         alias: impl Into<String>,
@@ -167,9 +272,14 @@ impl BankRegistry {
     }
 
     pub fn resolve_trigger(&self, alias: &str, trigger: &str) -> Option<PathBuf> {
-        self.banks
-            .get(alias)
-            .and_then(|bank| bank.resolve_trigger(trigger))
+        let bank = self.banks.get(alias)?;
+        // A bank registered before the remap was set still resolves to the substitute
+        let wanted = self.remapped(alias, &bank.identifier);
+        if wanted != bank.identifier {
+            let substitute = self.banks.values().find(|b| b.identifier == wanted)?;
+            return substitute.resolve_trigger(trigger);
+        }
+        bank.resolve_trigger(trigger)
     }
 
     pub fn has_bank(&self, alias: &str) -> bool {
@@ -339,3 +449,7 @@ fn resolve_trigger_path(audio_root: &Path, manifest_dir: &Path, raw: &str) -> Pa
 fn normalize_path(path: &Path) -> PathBuf {
     fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
 }
+
+#[cfg(test)]
+#[path = "test_registry.rs"]
+mod tests;
@@ -0,0 +1,98 @@
+use super::*;
+
+fn write_bank(root: &Path, publisher: &str, bank: &str) {
+    let dir = root.join(".deva").join("banks").join(publisher).join(bank);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("bank.toml"),
+        format!(
+            "[[triggers]]\nname = \"kick\"\npath = \"{}_kick.wav\"\n",
+            bank
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_remapped_prefers_alias_over_identifier() {
+    let mut registry = BankRegistry::new();
+    assert_eq!(registry.remapped("kit", "devaloop.808"), "devaloop.808");
+
+    registry.remap("devaloop.808", "devaloop.707");
+    assert_eq!(registry.remapped("kit", "devaloop.808"), "devaloop.707");
+
+    registry.remap("kit", "devaloop.909");
+    assert_eq!(registry.remapped("kit", "devaloop.808"), "devaloop.909");
+    assert_eq!(registry.remapped("other", "devaloop.606"), "devaloop.606");
+}
+
+#[test]
+fn test_resolve_trigger_follows_remap() {
+    let project = tempfile::tempdir().unwrap();
+    write_bank(project.path(), "devaloop", "808");
+    write_bank(project.path(), "devaloop", "909");
+
+    let mut registry = BankRegistry::new();
+    registry
+        .register_bank("kit", "devaloop.808", project.path(), project.path())
+        .unwrap();
+    registry
+        .register_bank("alt", "devaloop.909", project.path(), project.path())
+        .unwrap();
+    let file_name = |path: PathBuf| path.file_name().unwrap().to_string_lossy().to_string();
+    assert_eq!(
+        file_name(registry.resolve_trigger("kit", "kick").unwrap()),
+        "808_kick.wav"
+    );
+
+    registry.remap("kit", "devaloop.909");
+    assert_eq!(
+        file_name(registry.resolve_trigger("kit", "kick").unwrap()),
+        "909_kick.wav"
+    );
+}
+
+#[test]
+fn test_implicit_triggers_prefer_local_then_default_bank() {
+    let project = tempfile::tempdir().unwrap();
+    write_bank(project.path(), "devaloop", "808");
+    write_bank(project.path(), "devaloop", "909");
+    let local = project.path().join("banks").join("kit");
+    fs::create_dir_all(&local).unwrap();
+    fs::write(
+        local.join("bank.toml"),
+        "[[triggers]]\nname = \"kick\"\npath = \"local_kick.wav\"\n",
+    )
+    .unwrap();
+
+    let mut registry = BankRegistry::new();
+    registry.set_default_bank(Some("devaloop.909".to_string()));
+    for (alias, identifier) in [("a", "devaloop.808"), ("b", "devaloop.909")] {
+        registry
+            .register_bank(alias, identifier, project.path(), project.path())
+            .unwrap();
+    }
+    let order = |registry: &BankRegistry| {
+        registry
+            .implicit_candidates("kick")
+            .into_iter()
+            .map(|c| (c.identifier, c.origin))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        order(&registry),
+        vec![
+            ("devaloop.909".to_string(), BankOrigin::Default),
+            ("devaloop.808".to_string(), BankOrigin::Installed),
+        ]
+    );
+
+    registry
+        .register_bank("z", "./banks/kit", project.path(), project.path())
+        .unwrap();
+    assert_eq!(
+        order(&registry)[0],
+        ("./banks/kit".to_string(), BankOrigin::Local)
+    );
+    assert!(registry.implicit_candidates("snare").is_empty());
+}
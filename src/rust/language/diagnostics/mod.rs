@@ -1 +1,7 @@
-pub fn placeholder() {}
+//! Static diagnostics over parsed statements (no audio work involved)
+
+// Reports `StructuredError`s from the CLI logger, for `devalang check --strict`
+#[cfg(feature = "cli")]
+pub mod strict;
+
+pub mod usage;
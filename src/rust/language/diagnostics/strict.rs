@@ -0,0 +1,549 @@
+//! Strict checking pass run by `devalang check --strict`
+//!
+//! Walks a parsed file before any audio work and reports what a build would only hit
+//! halfway through (or silently ignore): variables used with the wrong type, user
+//! functions called with the wrong number of arguments, unknown effect names, triggers
+//! missing from their bank and `on` handlers for events nothing emits. Each issue is a
+//! `StructuredError` with its location, a code and, when a close name exists, a suggestion.
+
+use crate::engine::audio::effects::registry::EffectRegistry;
+use crate::engine::events::pattern_matches;
+use crate::engine::functions::FunctionRegistry;
+use crate::engine::plugin::effect::PLUGIN_EFFECT;
+use crate::language::preprocessor::loader::import_binding;
+use crate::language::syntax::ast::{Statement, StatementKind, Value};
+use crate::language::syntax::parser::driver::find_keyword_suggestion;
+use crate::tools::logger::StructuredError;
+use std::collections::{HashMap, HashSet};
+
+/// Events fired by the interpreter itself
+const BUILTIN_EVENTS: &[&str] = &["beat", "bar"];
+
+/// Namespaces of events fired by MIDI bindings rather than `emit`:
+/// `mapping.<in|out>.<device>.<noteOn|noteOff|rest>`
+const MAPPING_EVENT_PREFIX: &str = "mapping.";
+
+/// Chain entries read as sample parameters rather than effect processors
+const EFFECT_PARAMS: &[&str] = &[
+    "vol", "panning", "fadeIn", "fadein", "fade_in", "fadeOut", "fadeout", "fade_out", "tune",
+    "rate", "rev", "velocity",
+];
+
+/// Bank lookups behind the trigger checks
+pub trait BankLookup {
+    /// Load the bank `identifier` under `alias`, or explain why it cannot be found
+    fn register(&mut self, alias: &str, identifier: &str) -> Result<(), String>;
+    /// Trigger names of the bank registered under `alias`
+    fn triggers(&self, alias: &str) -> Option<Vec<String>>;
+}
+
+/// Run every strict check over `statements`, issues sorted by line
+pub fn check_statements(
+    statements: &[Statement],
+    banks: &mut impl BankLookup,
+) -> Vec<StructuredError> {
+    let mut checker = Checker {
+        banks,
+        effects: EffectRegistry::new(),
+        functions: FunctionRegistry::new(),
+        user_functions: HashMap::new(),
+        scope: HashMap::new(),
+        bank_aliases: HashSet::new(),
+        emitted: HashSet::new(),
+        handlers: Vec::new(),
+        issues: Vec::new(),
+    };
+    // Functions can be called before the line that defines them
+    checker.collect_functions(statements);
+    checker.walk(statements);
+    checker.check_handlers();
+
+    let mut issues = checker.issues;
+    issues.sort_by_key(|issue| (issue.line, issue.column));
+    issues
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueType {
+    Number,
+    String,
+    Boolean,
+    Duration,
+    Array,
+    Map,
+    Range,
+    Sample,
+    Midi,
+}
+
+impl ValueType {
+    fn of_literal(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(_) => Some(Self::Number),
+            Value::String(_) => Some(Self::String),
+            Value::Boolean(_) => Some(Self::Boolean),
+            Value::Duration(_) | Value::Beat(_) => Some(Self::Duration),
+            Value::Array(_) => Some(Self::Array),
+            Value::Map(_) => Some(Self::Map),
+            Value::Range { .. } => Some(Self::Range),
+            Value::Sample(_) => Some(Self::Sample),
+            Value::Midi(_) => Some(Self::Midi),
+            _ => None,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Number => "a number",
+            Self::String => "a string",
+            Self::Boolean => "a boolean",
+            Self::Duration => "a duration",
+            Self::Array => "an array",
+            Self::Map => "a map",
+            Self::Range => "a range",
+            Self::Sample => "a sample",
+            Self::Midi => "a MIDI file",
+        }
+    }
+}
+
+/// A name in scope; `ty` is known when it was bound to a literal (or a typed variable)
+#[derive(Debug, Clone, Copy, Default)]
+struct Binding {
+    ty: Option<ValueType>,
+    constant: bool,
+}
+
+struct Checker<'a, B: BankLookup> {
+    banks: &'a mut B,
+    effects: EffectRegistry,
+    functions: FunctionRegistry,
+    /// User function name -> parameter count
+    user_functions: HashMap<String, usize>,
+    scope: HashMap<String, Binding>,
+    /// Aliases of banks that loaded
+    bank_aliases: HashSet<String>,
+    emitted: HashSet<String>,
+    /// `on` handlers as (event, line, column)
+    handlers: Vec<(String, usize, usize)>,
+    issues: Vec<StructuredError>,
+}
+
+impl<B: BankLookup> Checker<'_, B> {
+    fn collect_functions(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            if let StatementKind::Function {
+                name, parameters, ..
+            } = &stmt.kind
+            {
+                self.user_functions.insert(name.clone(), parameters.len());
+            }
+        }
+    }
+
+    fn walk(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            self.check_statement(stmt);
+        }
+    }
+
+    fn check_statement(&mut self, stmt: &Statement) {
+        match &stmt.kind {
+            StatementKind::Let { name, value } | StatementKind::Var { name, value } => {
+                self.declare(stmt, name, value.as_ref(), false);
+            }
+            StatementKind::Const { name, value } | StatementKind::Param { name, value } => {
+                self.declare(stmt, name, value.as_ref(), true);
+            }
+            StatementKind::Function {
+                name,
+                parameters,
+                body,
+            } => {
+                self.bind(name);
+                let outer = self.scope.clone();
+                for parameter in parameters {
+                    self.bind(parameter);
+                }
+                self.walk(body);
+                self.scope = outer;
+            }
+            StatementKind::Call { name, args } | StatementKind::Spawn { name, args } => {
+                self.check_arity(stmt, name, args.len());
+            }
+            StatementKind::Trigger {
+                entity, effects, ..
+            } => {
+                self.check_trigger(stmt, entity);
+                if let Some(effects) = effects {
+                    self.check_effects(stmt, effects);
+                }
+            }
+            StatementKind::Pattern { name, target } => {
+                self.bind(name);
+                if let Some(target) = target {
+                    self.check_trigger(stmt, target);
+                }
+            }
+            StatementKind::Bank { name, alias } => {
+                let alias = alias
+                    .clone()
+                    .unwrap_or_else(|| name.split('.').next_back().unwrap_or(name).to_string());
+                self.bind(&alias);
+                match self.banks.register(&alias, name) {
+                    Ok(()) => {
+                        self.bank_aliases.insert(alias);
+                    }
+                    Err(reason) => {
+                        self.bank_aliases.remove(&alias);
+                        self.report(
+                            stmt,
+                            "UnknownBank",
+                            format!("Bank '{}' could not be loaded: {}", name, reason),
+                            None,
+                        );
+                    }
+                }
+            }
+            StatementKind::Load { alias, .. }
+            | StatementKind::UsePlugin { alias, .. }
+            | StatementKind::Use {
+                alias: Some(alias), ..
+            } => self.bind(alias),
+            StatementKind::Scene { name, .. } | StatementKind::Macro { name, .. } => {
+                self.bind(name)
+            }
+            StatementKind::Import { names, .. } => {
+                for name in names {
+                    self.bind(import_binding(name).1);
+                }
+            }
+            StatementKind::Group { name, body } => {
+                self.bind(name);
+                if let Some(effects) = stmt.value.get("effects") {
+                    self.check_effects(stmt, effects);
+                }
+                self.walk(body);
+            }
+            StatementKind::ArrowCall { .. } => {
+                // The first method may name a route target; chained calls are functions or effects
+                if let Some(Value::Array(chain)) = stmt.value.get("chain") {
+                    for call in chain {
+                        if let Some(Value::String(method)) = call.get("method")
+                            && !self.functions.has(method)
+                        {
+                            self.check_effect_name(stmt, method);
+                        }
+                    }
+                }
+            }
+            StatementKind::Loop { count, body } => {
+                self.check_loop_count(stmt, count);
+                self.walk(body);
+            }
+            StatementKind::For {
+                variable,
+                iterable,
+                body,
+            } => {
+                self.check_iterable(stmt, iterable);
+                let outer = self.scope.clone();
+                self.bind(variable);
+                self.walk(body);
+                self.scope = outer;
+            }
+            StatementKind::On { event, body, .. } => {
+                self.handlers
+                    .push((event_name(event), stmt.line, stmt.column));
+                self.walk(body);
+            }
+            StatementKind::Emit { event, .. } => {
+                self.emitted.insert(event_name(event));
+            }
+            StatementKind::If {
+                body, else_body, ..
+            } => {
+                self.walk(body);
+                if let Some(else_body) = else_body {
+                    self.walk(else_body);
+                }
+            }
+            StatementKind::At { body, .. }
+            | StatementKind::Routing { body }
+            | StatementKind::Tempo {
+                body: Some(body), ..
+            } => self.walk(body),
+            _ => {}
+        }
+    }
+
+    fn bind(&mut self, name: &str) {
+        self.scope.insert(name.to_string(), Binding::default());
+    }
+
+    fn declare(&mut self, stmt: &Statement, name: &str, value: Option<&Value>, constant: bool) {
+        if let Some(Value::Call {
+            name: function,
+            args,
+        }) = value
+        {
+            self.check_arity(stmt, function, args.len());
+        }
+        let ty = value.and_then(|value| self.type_of(value));
+
+        if let Some(existing) = self.scope.get(name).copied() {
+            if existing.constant {
+                self.report(
+                    stmt,
+                    "ConstAssignment",
+                    format!("Cannot redeclare constant '{}'", name),
+                    None,
+                );
+            } else if let (Some(old), Some(new)) = (existing.ty, ty)
+                && old != new
+            {
+                self.report(
+                    stmt,
+                    "StrictTypeError",
+                    format!(
+                        "'{}' was declared as {} but is assigned {}",
+                        name,
+                        old.describe(),
+                        new.describe()
+                    ),
+                    None,
+                );
+            }
+        }
+        self.scope
+            .insert(name.to_string(), Binding { ty, constant });
+    }
+
+    fn type_of(&self, value: &Value) -> Option<ValueType> {
+        match value {
+            // `let x = "intro"` keeps its quotes in an identifier
+            Value::Identifier(raw)
+                if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') =>
+            {
+                Some(ValueType::String)
+            }
+            Value::Identifier(name) => self.scope.get(name).and_then(|binding| binding.ty),
+            other => ValueType::of_literal(other),
+        }
+    }
+
+    fn check_arity(&mut self, stmt: &Statement, name: &str, given: usize) {
+        let Some(&expected) = self.user_functions.get(name) else {
+            return;
+        };
+        if expected != given {
+            self.report(
+                stmt,
+                "ArityError",
+                format!(
+                    "Function '{}' takes {} argument(s) but {} were given",
+                    name, expected, given
+                ),
+                None,
+            );
+        }
+    }
+
+    fn check_loop_count(&mut self, stmt: &Statement, count: &Value) {
+        self.expect_type(stmt, count, &[ValueType::Number], "Loop count");
+    }
+
+    fn check_iterable(&mut self, stmt: &Statement, iterable: &Value) {
+        match iterable {
+            Value::Range { start, end } => {
+                self.expect_type(stmt, start, &[ValueType::Number], "Range start");
+                self.expect_type(stmt, end, &[ValueType::Number], "Range end");
+            }
+            other => self.expect_type(
+                stmt,
+                other,
+                &[ValueType::Array, ValueType::Range],
+                "For iterable",
+            ),
+        }
+    }
+
+    /// Report `value` when it is an undefined name or has a type outside `allowed`
+    fn expect_type(&mut self, stmt: &Statement, value: &Value, allowed: &[ValueType], what: &str) {
+        if let Value::Identifier(name) = value
+            && is_variable_name(name)
+            && !self.scope.contains_key(name)
+        {
+            let known: Vec<&str> = self.scope.keys().map(String::as_str).collect();
+            let suggestion = find_keyword_suggestion(name, &known);
+            self.report(
+                stmt,
+                "UndefinedVariable",
+                format!("{} '{}' is not defined", what, name),
+                suggestion,
+            );
+            return;
+        }
+
+        if let Some(ty) = self.type_of(value)
+            && !allowed.contains(&ty)
+        {
+            let expected: Vec<&str> = allowed.iter().map(|ty| ty.describe()).collect();
+            self.report(
+                stmt,
+                "StrictTypeError",
+                format!(
+                    "{} must be {}, found {}",
+                    what,
+                    expected.join(" or "),
+                    ty.describe()
+                ),
+                None,
+            );
+        }
+    }
+
+    fn check_trigger(&mut self, stmt: &Statement, entity: &str) {
+        let Some((alias, trigger)) = entity.trim_start_matches('.').split_once('.') else {
+            return;
+        };
+        // Other dotted names are variable fields; unloaded banks were reported already
+        if !self.bank_aliases.contains(alias) {
+            return;
+        }
+        let Some(triggers) = self.banks.triggers(alias) else {
+            return;
+        };
+        if triggers.iter().any(|name| name == trigger) {
+            return;
+        }
+        let known: Vec<&str> = triggers.iter().map(String::as_str).collect();
+        let suggestion = find_keyword_suggestion(trigger, &known);
+        self.report(
+            stmt,
+            "UnknownTrigger",
+            format!("Bank '{}' has no trigger '{}'", alias, trigger),
+            suggestion.map(|name| format!("{}.{}", alias, name)),
+        );
+    }
+
+    /// Effect names of a trigger chain (`{ name: params }`) or a group insert list
+    fn check_effects(&mut self, stmt: &Statement, effects: &Value) {
+        match effects {
+            Value::Array(entries) => {
+                for entry in entries {
+                    self.check_effects(stmt, entry);
+                }
+            }
+            Value::Map(map) => match map.get("type").or_else(|| map.get("effect")) {
+                Some(Value::String(name) | Value::Identifier(name)) => {
+                    self.check_effect_name(stmt, name)
+                }
+                _ => {
+                    let mut names: Vec<&String> = map.keys().collect();
+                    names.sort();
+                    for name in names {
+                        self.check_effect_name(stmt, name);
+                    }
+                }
+            },
+            Value::String(name) | Value::Identifier(name) => self.check_effect_name(stmt, name),
+            _ => {}
+        }
+    }
+
+    fn check_effect_name(&mut self, stmt: &Statement, name: &str) {
+        if name == PLUGIN_EFFECT
+            || self.effects.is_effect_available(name, true)
+            || self.effects.is_effect_available(name, false)
+            || self.functions.has(name)
+            || EFFECT_PARAMS.contains(&name)
+        {
+            return;
+        }
+        let mut known = self.effects.list_available_effects(true);
+        known.extend(self.effects.list_available_effects(false));
+        known.sort_unstable();
+        known.dedup();
+        let suggestion = find_keyword_suggestion(name, &known);
+        self.report(
+            stmt,
+            "UnknownEffect",
+            format!("Unknown effect '{}'", name),
+            suggestion,
+        );
+    }
+
+    fn check_handlers(&mut self) {
+        let handlers = std::mem::take(&mut self.handlers);
+        for (event, line, column) in handlers {
+            if event.starts_with(MAPPING_EVENT_PREFIX) {
+                continue;
+            }
+            let fired = BUILTIN_EVENTS
+                .iter()
+                .copied()
+                .chain(self.emitted.iter().map(String::as_str))
+                .any(|name| pattern_matches(&event, name));
+            if fired {
+                continue;
+            }
+            let mut known: Vec<&str> = self.emitted.iter().map(String::as_str).collect();
+            known.extend(BUILTIN_EVENTS);
+            known.sort_unstable();
+            let suggestion = find_keyword_suggestion(&event, &known);
+            self.issues.push(issue(
+                line,
+                column,
+                "UnknownEvent",
+                format!("No 'emit' in this file sends event '{}'", event),
+                suggestion,
+            ));
+        }
+    }
+
+    fn report(
+        &mut self,
+        stmt: &Statement,
+        code: &str,
+        message: String,
+        suggestion: Option<String>,
+    ) {
+        self.issues
+            .push(issue(stmt.line, stmt.column, code, message, suggestion));
+    }
+}
+
+/// Plain variable names; `$` specials, dotted fields and expressions are left to the build
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Event names as the interpreter registers them (`on drop:` keeps its colon)
+fn event_name(raw: &str) -> String {
+    raw.trim_end_matches(':').trim().to_string()
+}
+
+fn issue(
+    line: usize,
+    column: usize,
+    code: &str,
+    message: String,
+    suggestion: Option<String>,
+) -> StructuredError {
+    let error = StructuredError::new(message)
+        .with_location(line, column)
+        .with_type(code);
+    match suggestion {
+        Some(name) => error.with_suggestion(format!("Did you mean '{}' ?", name)),
+        None => error,
+    }
+}
+
+#[cfg(test)]
+#[path = "test_strict.rs"]
+mod tests;
@@ -0,0 +1,132 @@
+use super::*;
+use crate::language::syntax::parser::driver::SimpleParser;
+use std::path::PathBuf;
+
+/// Banks keyed by identifier, as if installed in the project
+struct FakeBanks {
+    installed: HashMap<&'static str, Vec<String>>,
+    registered: HashMap<String, Vec<String>>,
+}
+
+impl FakeBanks {
+    fn new() -> Self {
+        let mut installed = HashMap::new();
+        installed.insert(
+            "devaloop.808",
+            vec!["kick".to_string(), "snare".to_string(), "hat".to_string()],
+        );
+        Self {
+            installed,
+            registered: HashMap::new(),
+        }
+    }
+}
+
+impl BankLookup for FakeBanks {
+    fn register(&mut self, alias: &str, identifier: &str) -> Result<(), String> {
+        let triggers = self
+            .installed
+            .get(identifier)
+            .ok_or_else(|| "not installed".to_string())?;
+        self.registered.insert(alias.to_string(), triggers.clone());
+        Ok(())
+    }
+
+    fn triggers(&self, alias: &str) -> Option<Vec<String>> {
+        self.registered.get(alias).cloned()
+    }
+}
+
+fn check(source: &str) -> Vec<StructuredError> {
+    let statements = SimpleParser::parse(source, PathBuf::from("strict.deva")).unwrap();
+    check_statements(&statements, &mut FakeBanks::new())
+}
+
+fn codes(issues: &[StructuredError]) -> Vec<&str> {
+    issues
+        .iter()
+        .filter_map(|issue| issue.error_type.as_deref())
+        .collect()
+}
+
+#[test]
+fn test_clean_file_has_no_issues() {
+    let source = "bank devaloop.808 as kit\nlet steps = 4\nfunction fill(count):\n    loop count:\n        .kit.snare\ncall fill(2)\nloop steps:\n    .kit.kick -> reverb({ size: 0.4 }) -> gain(0.8)\non drop:\n    .kit.hat\non beat:\n    .kit.hat\nemit drop\n";
+    let issues = check(source);
+    assert!(issues.is_empty(), "{:?}", issues);
+}
+
+#[test]
+fn test_types_and_arity() {
+    let source = "let steps = [1, 2]\nlet name = \"intro\"\nconst bars = 4\nfunction fill(a, b):\n    sleep 1\ncall fill(1)\nloop name:\n    sleep 1\nlet name = 2\nconst bars = 8\nfor step in stpes:\n    sleep 1\n";
+    let issues = check(source);
+    assert_eq!(
+        codes(&issues),
+        vec![
+            "ArityError",
+            "StrictTypeError",
+            "StrictTypeError",
+            "ConstAssignment",
+            "UndefinedVariable"
+        ],
+        "{:?}",
+        issues
+    );
+    assert_eq!(issues[0].line, Some(6));
+    assert!(issues[1].message.contains("found a string"));
+    assert!(issues[2].message.contains("declared as a string"));
+    assert_eq!(
+        issues[4].suggestion.as_deref(),
+        Some("Did you mean 'steps' ?")
+    );
+}
+
+#[test]
+fn test_unknown_banks_triggers_and_effects() {
+    let source = "bank devaloop.808 as kit\nbank someone.missing\n.kit.kik\n.kit.kick -> reverbb({ size: 0.4 })\n.missing.kick\ngroup bus -> lowpas(800):\n    .kit.snare\n";
+    let issues = check(source);
+    assert_eq!(
+        codes(&issues),
+        vec![
+            "UnknownBank",
+            "UnknownTrigger",
+            "UnknownEffect",
+            "UnknownEffect"
+        ],
+        "{:?}",
+        issues
+    );
+    assert_eq!(
+        issues[1].suggestion.as_deref(),
+        Some("Did you mean 'kit.kick' ?")
+    );
+    assert_eq!(
+        issues[2].suggestion.as_deref(),
+        Some("Did you mean 'reverb' ?")
+    );
+    assert_eq!(issues[3].line, Some(6));
+}
+
+#[test]
+fn test_handlers_need_an_emit() {
+    let source = "on dorp:\n    sleep 1\non build*:\n    sleep 1\non bar:\n    sleep 1\nemit drop\nemit buildup\n";
+    let issues = check(source);
+    assert_eq!(codes(&issues), vec!["UnknownEvent"], "{:?}", issues);
+    assert_eq!(issues[0].line, Some(1));
+    assert_eq!(
+        issues[0].suggestion.as_deref(),
+        Some("Did you mean 'drop' ?")
+    );
+}
+
+#[test]
+fn test_mapping_handlers_need_no_emit() {
+    let source = "on mapping.in.keys.noteOn:
+    sleep 1
+on mapping.out.synthA.noteOff:
+    sleep 1
+on mapping.in.keys.*:
+    sleep 1
+";
+    assert!(check(source).is_empty(), "{:?}", check(source));
+}
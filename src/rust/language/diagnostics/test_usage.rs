@@ -0,0 +1,28 @@
+use super::*;
+use crate::language::syntax::parser::driver::SimpleParser;
+use std::path::PathBuf;
+
+fn unused_names(source: &str) -> Vec<String> {
+    let statements = SimpleParser::parse(source, PathBuf::from("usage.deva")).unwrap();
+    unused_variables(&statements, source)
+        .into_iter()
+        .map(|stmt| match &stmt.kind {
+            StatementKind::Let { name, .. }
+            | StatementKind::Var { name, .. }
+            | StatementKind::Const { name, .. } => name.clone(),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_unused_variables_ignore_comments_and_strings() {
+    let source = "let lead = synth sine\nlet speed = 2\nlet _scratch = 1\nconst root = 60\n# speed is tuned by ear\nprint \"root\"\nlead -> note(C4) -> duration(100)\n";
+    assert_eq!(unused_names(source), vec!["speed", "root"]);
+}
+
+#[test]
+fn test_unused_variables_count_dotted_and_dollar_uses() {
+    let source = "let gain = 0.5\nlet pads = synth saw\nlet beat = 1\nprint $gain\npads.volume = 0.8\nsleep beat\n";
+    assert!(unused_names(source).is_empty());
+}
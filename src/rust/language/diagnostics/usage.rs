@@ -0,0 +1,39 @@
+//! Top-level variables that a file declares but never reads
+
+use crate::language::syntax::ast::{Statement, StatementKind};
+use crate::language::syntax::lexer::lex::Lexer;
+use crate::language::syntax::tokens::TokenKind;
+
+/// Top-level `let` / `var` / `const` statements whose name is never used again. Only
+/// identifier tokens count as uses (`pads`, `$pads`, `pads.gain`); names inside comments
+/// and strings do not. Names starting with `_` are left alone, and nothing is reported
+/// when `source` does not lex.
+pub fn unused_variables<'a>(statements: &'a [Statement], source: &str) -> Vec<&'a Statement> {
+    let Ok(tokens) = Lexer::new(source).lex() else {
+        return Vec::new();
+    };
+    let uses = |name: &str| {
+        tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::Identifier)
+            .flat_map(|token| token.lexeme.split('.'))
+            .filter(|part| part.trim_start_matches('$') == name)
+            .count()
+    };
+    statements
+        .iter()
+        .filter(|stmt| match &stmt.kind {
+            StatementKind::Let { name, .. }
+            | StatementKind::Var { name, .. }
+            | StatementKind::Const { name, .. } => {
+                // The declaration itself is one use
+                !name.starts_with('_') && uses(name) <= 1
+            }
+            _ => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "test_usage.rs"]
+mod tests;
@@ -1,8 +1,10 @@
 use crate::language::syntax::ast::{Statement, StatementKind, Value};
 use crate::language::syntax::parser::driver::SimpleParser;
 /// Module loader - handles file loading and module dependencies
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
 
 /// Load a module from a file path
@@ -22,14 +24,30 @@ pub struct ModuleExports {
     pub variables: HashMap<String, Value>,
     pub groups: HashMap<String, Vec<Statement>>,
     pub patterns: HashMap<String, Statement>,
+    /// Parameter values of this instance in declaration order, under the instance-private
+    /// names the exports refer to; the importer binds them alongside the exports
+    pub params: Vec<(String, Value)>,
 }
 
-pub fn load_module_exports(path: &Path) -> Result<ModuleExports> {
+/// Exported name and local binding of an import entry (`riser` or `riser as riser8`)
+pub fn import_binding(entry: &str) -> (&str, &str) {
+    match entry.split_once(" as ") {
+        Some((name, alias)) => (name.trim(), alias.trim()),
+        None => (entry, entry),
+    }
+}
+
+/// Load the exports of a module, instantiated with `params` overriding its `param` defaults.
+///
+/// Each parameter is renamed to a name private to this module and parameter set, so two
+/// imports of one module with different parameters never see each other's values.
+pub fn load_module_exports(path: &Path, params: &HashMap<String, Value>) -> Result<ModuleExports> {
     let mut variables: HashMap<String, Value> = HashMap::new();
     let mut groups: HashMap<String, Vec<Statement>> = HashMap::new();
     let mut patterns: HashMap<String, Statement> = HashMap::new();
 
-    let stmts = SimpleParser::parse_file(path)?;
+    let mut stmts = SimpleParser::parse_file(path)?;
+    let instance_params = instantiate_params(path, &mut stmts, params)?;
 
     // collect exported names
     let mut exports: Vec<String> = Vec::new();
@@ -70,5 +88,182 @@ pub fn load_module_exports(path: &Path) -> Result<ModuleExports> {
         variables,
         groups,
         patterns,
+        params: instance_params,
     })
 }
+
+/// Rename the module's parameters to instance names and resolve their values, overrides
+/// first, then defaults
+fn instantiate_params(
+    path: &Path,
+    stmts: &mut Vec<Statement>,
+    overrides: &HashMap<String, Value>,
+) -> Result<Vec<(String, Value)>> {
+    let declared: Vec<String> = stmts
+        .iter()
+        .filter_map(|s| match &s.kind {
+            StatementKind::Param { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    if let Some(unknown) = overrides.keys().find(|key| !declared.contains(key)) {
+        return Err(anyhow!("{} has no param '{}'", path.display(), unknown));
+    }
+    if declared.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut hasher = DefaultHasher::new();
+    let mut keys: Vec<&String> = overrides.keys().collect();
+    keys.sort();
+    for key in keys {
+        key.hash(&mut hasher);
+        serde_json::to_string(&overrides[key])?.hash(&mut hasher);
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| {
+            s.to_string_lossy()
+                .replace(|c: char| !c.is_alphanumeric(), "_")
+        })
+        .unwrap_or_default();
+    let prefix = format!("__{}_{:x}_", stem, hasher.finish());
+    let renames: HashMap<String, String> = declared
+        .iter()
+        .map(|name| (name.clone(), format!("{}{}", prefix, name)))
+        .collect();
+
+    let mut tree = serde_json::to_value(&*stmts)?;
+    rename_params(&mut tree, &renames);
+    *stmts = serde_json::from_value(tree)?;
+
+    // Defaults are read after the rename so that they may refer to earlier params
+    let mut values = Vec::new();
+    for s in stmts.iter() {
+        if let StatementKind::Param { name, value } = &s.kind {
+            let original = name.strip_prefix(&prefix).unwrap_or(name);
+            let value = overrides.get(original).or(value.as_ref());
+            values.push((name.clone(), value.cloned().unwrap_or(Value::Null)));
+        }
+    }
+    Ok(values)
+}
+
+/// Option keys whose string values are names in their own right, never references to a
+/// parameter
+const SYNTH_STRING_KEYS: &[&str] = &[
+    "type",
+    "synth_type",
+    "waveform",
+    "curve",
+    "attack_curve",
+    "decay_curve",
+    "release_curve",
+    "plugin_author",
+    "plugin_name",
+    "plugin_export",
+];
+
+/// Rename references to parameters in a serialized statement tree: declarations, words of
+/// identifier expressions (`length * 2`) and the option values the parser keeps as bare
+/// strings, in synth declarations (`synth saw { attack: length }`), `with` maps (`call
+/// riser with { velocity: length }`) and trigger effects (`.kit.kick -> gain(length)`).
+/// Other string literals are text and keep their contents.
+fn rename_params(node: &mut serde_json::Value, renames: &HashMap<String, String>) {
+    match node {
+        serde_json::Value::Object(map) => {
+            let tag = map.get("type").or_else(|| map.get("kind")).cloned();
+            match (tag.as_ref().and_then(|t| t.as_str()), map.get_mut("value")) {
+                (Some("Identifier"), Some(serde_json::Value::String(text))) => {
+                    *text = rename_words(text, renames);
+                    return;
+                }
+                (Some("String"), _) => return,
+                (Some("Map"), Some(serde_json::Value::Object(entries)))
+                    if ["type", "synth_type", "waveform", "_plugin_ref"]
+                        .iter()
+                        .any(|key| entries.contains_key(*key)) =>
+                {
+                    rename_options(entries, renames);
+                }
+                _ => {}
+            }
+            for key in ["with", "effects"] {
+                if let Some(serde_json::Value::Object(options)) = map.get_mut(key)
+                    && options.get("type").and_then(|t| t.as_str()) == Some("Map")
+                    && let Some(serde_json::Value::Object(entries)) = options.get_mut("value")
+                {
+                    rename_options(entries, renames);
+                }
+            }
+            if tag.as_ref().and_then(|t| t.as_str()) == Some("Param")
+                && let Some(serde_json::Value::String(name)) = map.get_mut("name")
+                && let Some(renamed) = renames.get(name.as_str())
+            {
+                *name = renamed.clone();
+            }
+            for child in map.values_mut() {
+                rename_params(child, renames);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                rename_params(item, renames);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rename option values that name a parameter
+fn rename_options(
+    entries: &mut serde_json::Map<String, serde_json::Value>,
+    renames: &HashMap<String, String>,
+) {
+    for (key, entry) in entries.iter_mut() {
+        if SYNTH_STRING_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        if let serde_json::Value::Object(value) = entry
+            && value.get("type").and_then(|t| t.as_str()) == Some("String")
+            && let Some(serde_json::Value::String(text)) = value.get_mut("value")
+            && let Some(renamed) = renames.get(text.as_str())
+        {
+            *text = renamed.clone();
+        }
+    }
+}
+
+/// Replace whole words of an expression, leaving member accesses (`.length`) and quoted
+/// text (`"length"`) alone
+fn rename_words(text: &str, renames: &HashMap<String, String>) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    let mut previous = None;
+    let mut quoted = false;
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if is_word(c) {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            match renames.get(&word) {
+                Some(renamed) if previous != Some('.') && !quoted => out.push_str(renamed),
+                _ => out.push_str(&word),
+            }
+            word.clear();
+        }
+        if c == '"' {
+            quoted = !quoted;
+        }
+        out.push(c);
+        previous = Some(c);
+    }
+    out.pop();
+    out
+}
+
+#[cfg(test)]
+#[path = "test_loader.rs"]
+mod tests;
@@ -0,0 +1,82 @@
+use super::*;
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use anyhow::Result;
+use std::path::PathBuf;
+
+fn instantiate(source: &str) -> (Vec<Statement>, Vec<(String, Value)>) {
+    let mut statements = SimpleParser::parse(source, PathBuf::from("module.deva")).unwrap();
+    let params = instantiate_params(
+        Path::new("module.deva"),
+        &mut statements,
+        &HashMap::from([("length".to_string(), Value::Number(3.0))]),
+    )
+    .unwrap();
+    (statements, params)
+}
+
+#[test]
+fn test_params_are_renamed_where_they_are_referenced() {
+    let (statements, params) = instantiate(
+        "param length = 2\nlet lead = synth saw { attack: length }\nloop length:\n    lead -> note(C4) -> duration(length)\ncall riser with { velocity: length }\n.kit.kick -> gain(length)\n",
+    );
+    let (name, value) = &params[0];
+    assert!(
+        name.starts_with("__module_") && name.ends_with("_length"),
+        "{}",
+        name
+    );
+    assert_eq!(value, &Value::Number(3.0));
+
+    let tree = serde_json::to_string(&statements).unwrap();
+    assert_eq!(tree.matches(name.as_str()).count(), 6, "{}", tree);
+    assert!(!tree.contains("\"length\""), "{}", tree);
+}
+
+#[test]
+fn test_string_literals_matching_a_param_survive() {
+    let (statements, params) = instantiate(
+        "param length = 2\nlet label = \"length\"\nprint \"length\"\nprint \"length is \" + length\nlet lead = synth saw { waveform: \"length\" }\n",
+    );
+    let renamed = &params[0].0;
+
+    let tree = serde_json::to_string(&statements).unwrap();
+    // Only the declaration and the word outside the quotes
+    assert_eq!(tree.matches(renamed.as_str()).count(), 2, "{}", tree);
+    match &statements[1].kind {
+        StatementKind::Let {
+            value: Some(Value::Identifier(text)),
+            ..
+        } => assert_eq!(text, "\"length\""),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(statements[2].value, Value::String("length".to_string()));
+}
+
+#[test]
+fn test_imports_instantiate_module_params_per_parameter_set() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    std::fs::write(
+        dir.path().join("riser.deva"),
+        "param length = 2\nparam root = C4\nlet lead = synth saw\ngroup riser:\n    loop length:\n        lead -> note(root) -> duration(1/4)\nexport { riser }\n",
+    )?;
+    let source = "bpm 120\nimport { riser as short } from \"./riser.deva\"\nimport { riser as long } from \"./riser.deva\" with { length: 3, root: E4 }\nimport { riser as typo } from \"./riser.deva\" with { lenght: 3 }\ncall short\ncall long\n";
+    let statements =
+        crate::language::syntax::parser::driver::parse(source, dir.path().join("main.deva"))?;
+    let mut interp = AudioInterpreter::new(44100);
+    interp.collect_events(&statements)?;
+
+    let notes: Vec<u8> = interp
+        .events
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            AudioEvent::Note { midi, .. } => Some(*midi),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(notes, vec![60, 60, 64, 64, 64]);
+    // Overrides must name a declared param
+    assert!(!interp.groups.contains_key("typo"));
+    Ok(())
+}
@@ -19,7 +19,8 @@ pub fn resolve_trigger(
             duration: final_duration,
             effects: final_effects,
         },
-        Value::Null,
+        // Keep trigger modifiers (chance / every) attached to the statement
+        stmt.value.clone(),
         stmt.indent,
         stmt.line,
         stmt.column,
@@ -33,6 +34,9 @@ fn resolve_duration(duration: &DurationValue, variables: &HashMap<String, Value>
                 match val {
                     Value::Number(n) => DurationValue::Milliseconds(*n),
                     Value::Identifier(s) if s == "auto" => DurationValue::Auto,
+                    Value::Duration(dv) if !matches!(dv, DurationValue::Identifier(_)) => {
+                        dv.clone()
+                    }
                     Value::String(s) if s.ends_with("ms") => {
                         if let Ok(ms) = s.trim_end_matches("ms").parse::<f32>() {
                             return DurationValue::Milliseconds(ms);
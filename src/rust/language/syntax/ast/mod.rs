@@ -1,3 +1,5 @@
 pub mod nodes;
 
-pub use nodes::{DurationValue, Statement, StatementKind, Value};
+pub use nodes::{
+    DurationValue, SourceSpan, Statement, StatementKind, TimePosition, TimeSpan, Value,
+};
@@ -9,9 +9,102 @@ pub enum DurationValue {
     Beat(String),
     Beats(f32),
     Milliseconds(f32),
+    /// Mixed musical/absolute time, e.g. `1/8 + 10ms`
+    Span(TimeSpan),
     Auto,
 }
 
+impl DurationValue {
+    /// Tempo-independent form of this duration. `None` for `auto` and unresolved identifiers.
+    pub fn to_span(&self) -> Option<TimeSpan> {
+        match self {
+            DurationValue::Number(ms) | DurationValue::Milliseconds(ms) => {
+                Some(TimeSpan::seconds(ms / 1000.0))
+            }
+            DurationValue::Beats(beats) => Some(TimeSpan::beats(*beats)),
+            DurationValue::Beat(fraction) => {
+                let (num, den) = fraction.split_once('/')?;
+                let (num, den) = (
+                    num.trim().parse::<f32>().ok()?,
+                    den.trim().parse::<f32>().ok()?,
+                );
+                (den.abs() > f32::EPSILON).then(|| TimeSpan::beats(num / den))
+            }
+            DurationValue::Span(span) => Some(*span),
+            DurationValue::Identifier(_) | DurationValue::Auto => None,
+        }
+    }
+
+    pub fn to_seconds(&self, bpm: f32) -> Option<f32> {
+        self.to_span().map(|span| span.to_seconds(bpm))
+    }
+}
+
+/// A length of musical time: a tempo-relative part in beats plus an absolute part in
+/// seconds. Keeping both parts means `1/8 + 10ms` stays an eighth plus 10 ms at any tempo.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct TimeSpan {
+    pub beats: f32,
+    pub seconds: f32,
+}
+
+impl TimeSpan {
+    /// Tick resolution (pulses per quarter note), matching exported MIDI files
+    pub const TICKS_PER_BEAT: f32 = 480.0;
+
+    pub fn beats(beats: f32) -> Self {
+        Self {
+            beats,
+            seconds: 0.0,
+        }
+    }
+
+    pub fn seconds(seconds: f32) -> Self {
+        Self {
+            beats: 0.0,
+            seconds,
+        }
+    }
+
+    pub fn ticks(ticks: f32) -> Self {
+        Self::beats(ticks / Self::TICKS_PER_BEAT)
+    }
+
+    pub fn to_seconds(&self, bpm: f32) -> f32 {
+        self.beats * 60.0 / bpm.max(f32::EPSILON) + self.seconds
+    }
+
+    pub fn to_beats(&self, bpm: f32) -> f32 {
+        self.beats + self.seconds * bpm / 60.0
+    }
+
+    pub fn to_ticks(&self, bpm: f32) -> f32 {
+        self.to_beats(bpm) * Self::TICKS_PER_BEAT
+    }
+}
+
+impl std::ops::Add for TimeSpan {
+    type Output = TimeSpan;
+
+    fn add(self, other: TimeSpan) -> TimeSpan {
+        TimeSpan {
+            beats: self.beats + other.beats,
+            seconds: self.seconds + other.seconds,
+        }
+    }
+}
+
+/// Absolute timeline position targeted by an `at` block
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "value")]
+pub enum TimePosition {
+    Seconds(f32),
+    /// 1-based bar number (`at bar 17:`), fractional bars allowed
+    Bar(f32),
+    /// 1-based beat number (`at beat 5:`)
+    Beat(f32),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", content = "value")]
 pub enum Value {
@@ -119,6 +212,16 @@ pub enum StatementKind {
         name: String,
         value: Option<Value>,
     },
+    /// `macro intensity -> [drums.gain, lead.cutoff x0.5]`, targets as written
+    Macro {
+        name: String,
+        targets: Vec<String>,
+    },
+    /// Module parameter with its default, overridable by `import ... with { name: value }`
+    Param {
+        name: String,
+        value: Option<Value>,
+    },
     Group {
         name: String,
         body: Vec<Statement>,
@@ -127,6 +230,7 @@ pub enum StatementKind {
         name: String,
         args: Vec<Value>,
     },
+    /// `loop` and `for` keep an optional `as <label>` in the statement value
     Loop {
         count: Value,
         body: Vec<Statement>,
@@ -136,6 +240,31 @@ pub enum StatementKind {
         iterable: Value,
         body: Vec<Statement>,
     },
+    At {
+        position: TimePosition,
+        body: Vec<Statement>,
+    },
+    /// `scene verse = [drums, bass]`
+    Scene {
+        name: String,
+        members: Vec<String>,
+    },
+    /// `switch chorus` / `switch chorus over 4 bars`
+    Switch {
+        scene: String,
+        fade: Option<DurationValue>,
+    },
+    /// `snapshot save calm`
+    SnapshotSave {
+        name: String,
+    },
+    /// `snapshot morph calm -> wild over 8 beats curve smooth`
+    SnapshotMorph {
+        from: String,
+        to: String,
+        length: Option<DurationValue>,
+        curve: Option<String>,
+    },
     Routing {
         body: Vec<Statement>,
     },
@@ -152,6 +281,17 @@ pub enum StatementKind {
         destination: String,
         effects: Option<Value>,
     },
+    /// `route drums -> outputs 3-4`: a group insert sent to hardware outputs (1-based)
+    RoutingOutputs {
+        source: String,
+        first: u16,
+        last: u16,
+    },
+    /// `strip drums gain -3db pan -0.2 invert lowcut 80hz`: the group insert's channel strip
+    RoutingStrip {
+        target: String,
+        strip: crate::engine::audio::mixer::ChannelStrip,
+    },
     RoutingDuck {
         source: String,
         destination: String,
@@ -204,7 +344,22 @@ pub enum StatementKind {
     Return {
         value: Option<Box<Value>>,
     },
+    /// `break` / `continue`; a target loop label is kept in the statement value
     Break,
+    Continue,
+    /// `@persist a, b` keeps these variables across live rebuilds (`loops` keeps loop passes)
+    Persist {
+        names: Vec<String>,
+    },
+    /// `metronome on` / `metronome on stem` / `metronome off`; the mode is kept in the
+    /// statement value
+    Metronome {
+        enabled: bool,
+    },
+    /// `mark chorus`: a named cue point at the current time (`devalang play --start-at`)
+    Mark {
+        name: String,
+    },
     Comment,
     Indent,
     Dedent,
@@ -221,6 +376,31 @@ impl Default for StatementKind {
     }
 }
 
+/// Location of a token or statement within its source line.
+///
+/// Byte offsets index the line's UTF-8 text (end exclusive); columns count characters
+/// from 1 (end exclusive), which is what editors use to place a caret or underline.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct SourceSpan {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub start_column: usize,
+    pub end_column: usize,
+}
+
+impl SourceSpan {
+    /// Span of `line[start_byte..end_byte]`; offsets must lie on char boundaries
+    pub fn in_line(line: &str, start_byte: usize, end_byte: usize) -> Self {
+        let start_column = line[..start_byte].chars().count() + 1;
+        Self {
+            start_byte,
+            end_byte,
+            start_column,
+            end_column: start_column + line[start_byte..end_byte].chars().count(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct Statement {
     pub kind: StatementKind,
@@ -229,6 +409,9 @@ pub struct Statement {
     pub indent: usize,
     pub line: usize,
     pub column: usize,
+    /// Source range of the statement (or of its offending token for parse errors)
+    #[serde(default)]
+    pub span: SourceSpan,
 }
 
 impl Statement {
@@ -245,6 +428,7 @@ impl Statement {
             indent,
             line,
             column,
+            span: SourceSpan::default(),
         }
     }
 
@@ -2,5 +2,6 @@ pub mod ast;
 pub mod grammar;
 pub mod lexer;
 pub mod parser;
+pub mod printer;
 pub mod source;
 pub mod tokens;
@@ -23,19 +23,19 @@ fn parse_import_directive(
     line_number: usize,
     file_path: &std::path::Path,
 ) -> Result<Statement> {
-    // syntax: import { a, b } from "path"
+    // syntax: import { a, b as c } from "path" [with { param: value }]
     let rest = line["import".len()..].trim();
     if let Some(open) = rest.find('{') {
-        if let Some(close) = rest.find('}') {
+        if let Some(close) = rest[open..].find('}').map(|idx| open + idx) {
             let names = rest[open + 1..close]
                 .split(',')
-                .map(|s| s.trim().to_string())
+                .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
                 .filter(|s| !s.is_empty())
                 .collect::<Vec<_>>();
             let after = rest[close + 1..].trim();
             let from_prefix = "from";
             if after.starts_with(from_prefix) {
-                let path_part = after[from_prefix.len()..].trim();
+                let (path_part, params) = split_load_options(after[from_prefix.len()..].trim())?;
                 let raw = path_part.trim().trim_matches('"');
                 // Resolve relative to file_path
                 let base = file_path
@@ -48,7 +48,7 @@ fn parse_import_directive(
                         names,
                         source: path,
                     },
-                    Value::Null,
+                    params,
                     0,
                     line_number,
                     1,
@@ -65,7 +65,7 @@ fn parse_export_directive(line: &str, line_number: usize) -> Result<Statement> {
     // syntax: export { a, b }
     let rest = line["export".len()..].trim();
     if let Some(open) = rest.find('{') {
-        if let Some(close) = rest.find('}') {
+        if let Some(close) = rest[open..].find('}').map(|idx| open + idx) {
             let names = rest[open + 1..close]
                 .split(',')
                 .map(|s| s.trim().to_string())
@@ -116,6 +116,7 @@ fn parse_load_directive(
         rest = split.next().unwrap_or("").trim();
     }
 
+    let (rest, options) = split_load_options(rest)?;
     let alias = if rest.starts_with("as ") {
         rest[3..].trim().to_string()
     } else {
@@ -130,7 +131,7 @@ fn parse_load_directive(
             source: path,
             alias,
         },
-        Value::Null,
+        options,
         0,
         line_number,
         1,
@@ -202,44 +203,16 @@ pub fn parse_directive(
     }
 
     if !line.starts_with("@load") {
-        if line.starts_with("@import") {
-            let rest = line["@import".len()..].trim();
-            if let Some(open) = rest.find('{') {
-                if let Some(close) = rest.find('}') {
-                    let names = rest[open + 1..close]
-                        .split(',')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect::<Vec<_>>();
-                    let after = rest[close + 1..].trim();
-                    let from_prefix = "from";
-                    if after.starts_with(from_prefix) {
-                        let path_part = after[from_prefix.len()..].trim();
-                        let raw = path_part.trim().trim_matches('"');
-                        let base = file_path
-                            .parent()
-                            .unwrap_or_else(|| std::path::Path::new("."));
-                        let joined = base.join(raw);
-                        let path = joined.to_string_lossy().to_string();
-                        return Ok(Statement::new(
-                            StatementKind::Import {
-                                names,
-                                source: path,
-                            },
-                            Value::Null,
-                            0,
-                            line_number,
-                            1,
-                        ));
-                    }
-                }
-            }
+        if line.starts_with("@import")
+            && let Ok(stmt) = parse_import_directive(&line[1..], line_number, file_path)
+        {
+            return Ok(stmt);
         }
 
         if line.starts_with("@export") {
             let rest = line["@export".len()..].trim();
             if let Some(open) = rest.find('{') {
-                if let Some(close) = rest.find('}') {
+                if let Some(close) = rest[open..].find('}').map(|idx| open + idx) {
                     let names = rest[open + 1..close]
                         .split(',')
                         .map(|s| s.trim().to_string())
@@ -292,6 +265,7 @@ pub fn parse_directive(
         rest = split.next().unwrap_or("").trim();
     }
 
+    let (rest, options) = split_load_options(rest)?;
     let alias = if rest.starts_with("as ") {
         rest[3..].trim().to_string()
     } else {
@@ -306,9 +280,26 @@ pub fn parse_directive(
             source: path,
             alias,
         },
-        Value::Null,
+        options,
         0,
         line_number,
         1,
     ))
 }
+
+/// Split a trailing `with { ... }` option block off a load or import directive
+fn split_load_options(rest: &str) -> Result<(&str, Value)> {
+    let block_start = if rest.starts_with("with ") {
+        Some(0)
+    } else {
+        rest.rfind(" with ").map(|idx| idx + 1)
+    };
+    match block_start {
+        Some(idx) if rest[idx + "with ".len()..].trim_start().starts_with('{') => {
+            let block = rest[idx + "with ".len()..].trim();
+            let options = super::helpers::parse_map_value(block)?;
+            Ok((rest[..idx].trim_end(), options))
+        }
+        _ => Ok((rest, Value::Null)),
+    }
+}
@@ -1,21 +1,41 @@
-use crate::language::syntax::ast::DurationValue;
+use crate::language::syntax::ast::{DurationValue, TimeSpan};
 use anyhow::{Result, anyhow};
 
+/// Parse a duration token. Supports `auto`, milliseconds (`500`, `500ms`), seconds (`2s`),
+/// fractions of a beat (`1/8`, dotted `1/8d`, triplet `1/4t`), musical units (`2 beats`, `1bar`, `3 measures`, `240 ticks`)
+/// and sums of these (`1/8 + 10ms`). Anything else is kept as a variable reference.
 pub fn parse_duration_token(token: &str) -> Result<DurationValue> {
     let token = token.trim();
     if token.eq_ignore_ascii_case("auto") {
         return Ok(DurationValue::Auto);
     }
 
+    if token.contains('+') {
+        let mut total = TimeSpan::default();
+        for term in token.split('+') {
+            let term = parse_duration_term(term.trim())?;
+            total = total
+                + term.to_span().ok_or_else(|| {
+                    anyhow!("cannot add '{}' in duration '{}'", term_label(&term), token)
+                })?;
+        }
+        return Ok(DurationValue::Span(total));
+    }
+
+    parse_duration_term(token)
+}
+
+fn parse_duration_term(token: &str) -> Result<DurationValue> {
     // Check for milliseconds suffix (e.g., "500ms")
     if let Some(value) = token.strip_suffix("ms") {
         let ms: f32 = value
+            .trim()
             .parse()
             .map_err(|_| anyhow!("invalid milliseconds duration: '{}'", token))?;
         return Ok(DurationValue::Milliseconds(ms));
     }
 
-    // Check for bar/beat/measure suffixes (e.g., "1 bar", "2 beat", "3 measure")
+    // Check for unit suffixes (e.g., "1 bar", "2 beat", "3 measure", "2s", "240 ticks")
     if let Some(result) = parse_temporal_duration(token) {
         return Ok(result);
     }
@@ -25,6 +45,14 @@ pub fn parse_duration_token(token: &str) -> Result<DurationValue> {
         return Ok(DurationValue::Beats(fraction));
     }
 
+    // Dotted (`1/8d`, one and a half times as long) and triplet (`1/4t`, two thirds) fractions
+    if let Some(fraction) = token.strip_suffix('d').and_then(parse_fraction) {
+        return Ok(DurationValue::Beats(fraction * 1.5));
+    }
+    if let Some(fraction) = token.strip_suffix('t').and_then(parse_fraction) {
+        return Ok(DurationValue::Beats(fraction * 2.0 / 3.0));
+    }
+
     // Try to parse as plain number (milliseconds)
     if let Ok(number) = token.parse::<f32>() {
         return Ok(DurationValue::Milliseconds(number));
@@ -34,9 +62,16 @@ pub fn parse_duration_token(token: &str) -> Result<DurationValue> {
     Ok(DurationValue::Identifier(token.to_string()))
 }
 
-/// Parse temporal duration formats like "1 bar", "2 beat", "3 measure"
+fn term_label(term: &DurationValue) -> String {
+    match term {
+        DurationValue::Identifier(name) => name.clone(),
+        DurationValue::Auto => "auto".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Parse temporal duration formats like "1 bar", "2 beat", "3 measure", "2s", "240 ticks"
 /// Also supports: "1bar", "2beats", "1beat", "3 measures", etc.
-/// Returns millisecond-based duration proportional to BPM
 fn parse_temporal_duration(token: &str) -> Option<DurationValue> {
     // Try parsing with spaces first (e.g., "1 bar", "2 beats")
     let parts_with_space: Vec<&str> = token.split_whitespace().collect();
@@ -48,7 +83,10 @@ fn parse_temporal_duration(token: &str) -> Option<DurationValue> {
 
     // Try parsing without space (e.g., "1bar", "2beats")
     // Find where the unit starts (first alphabetic character)
-    let split_pos = token.chars().position(|c| c.is_alphabetic())?;
+    let split_pos = token
+        .char_indices()
+        .find(|(_, c)| c.is_alphabetic())
+        .map(|(idx, _)| idx)?;
     if split_pos == 0 {
         return None; // No number part
     }
@@ -71,20 +109,14 @@ fn try_parse_temporal(num_str: &str, unit_str: &str) -> Option<DurationValue> {
         &unit
     };
 
-    // 1 bar = 4 beats (at 4/4 time)
-    // 1 beat at 120 BPM = 500ms (60000ms / 120 BPM)
-    // 1 measure = 4 beats (same as bar)
-    let multiplier = match unit_singular {
-        "beat" => 1.0,
-        "bar" => 4.0,
-        "measure" => 4.0,
-        _ => return None,
-    };
-
-    // We return Identifier with a special format to be resolved at runtime
-    // Format: "__temporal__<count>_<unit>"
-    let identifier = format!("__temporal__{}_{}s", count * multiplier, unit_singular);
-    Some(DurationValue::Identifier(identifier))
+    // 1 bar = 1 measure = 4 beats (at 4/4 time); beats resolve against the tempo at use
+    match unit_singular {
+        "beat" => Some(DurationValue::Beats(count)),
+        "bar" | "measure" => Some(DurationValue::Beats(count * 4.0)),
+        "tick" => Some(DurationValue::Span(TimeSpan::ticks(count))),
+        "s" | "sec" | "second" => Some(DurationValue::Span(TimeSpan::seconds(count))),
+        _ => None,
+    }
 }
 
 fn parse_fraction(token: &str) -> Option<f32> {
@@ -158,25 +190,25 @@ mod tests {
     fn test_temporal_beat_with_space() {
         // "1 beat"
         match parse_duration_token("1 beat") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__1_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 1.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "2 beat"
         match parse_duration_token("2 beat") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__2_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 2.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "1 beats" (plural)
         match parse_duration_token("1 beats") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__1_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 1.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "3 beats" (plural)
         match parse_duration_token("3 beats") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__3_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 3.0),
             other => panic!("Unexpected result: {:?}", other),
         }
     }
@@ -185,25 +217,25 @@ mod tests {
     fn test_temporal_beat_without_space() {
         // "1beat"
         match parse_duration_token("1beat") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__1_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 1.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "2beat"
         match parse_duration_token("2beat") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__2_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 2.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "1beats" (without space, with 's')
         match parse_duration_token("1beats") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__1_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 1.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "3beats" (without space, with 's')
         match parse_duration_token("3beats") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__3_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 3.0),
             other => panic!("Unexpected result: {:?}", other),
         }
     }
@@ -212,25 +244,25 @@ mod tests {
     fn test_temporal_bar_with_space() {
         // "1 bar" -> 1 * 4 beats = 4
         match parse_duration_token("1 bar") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "2 bar"
         match parse_duration_token("2 bar") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__8_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 8.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "1 bars" (plural)
         match parse_duration_token("1 bars") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "3 bars" (plural)
         match parse_duration_token("3 bars") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__12_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 12.0),
             other => panic!("Unexpected result: {:?}", other),
         }
     }
@@ -239,25 +271,25 @@ mod tests {
     fn test_temporal_bar_without_space() {
         // "1bar" -> 4 beats
         match parse_duration_token("1bar") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "2bar"
         match parse_duration_token("2bar") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__8_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 8.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "1bars" (without space, with 's')
         match parse_duration_token("1bars") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "3bars" (without space, with 's')
         match parse_duration_token("3bars") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__12_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 12.0),
             other => panic!("Unexpected result: {:?}", other),
         }
     }
@@ -266,25 +298,25 @@ mod tests {
     fn test_temporal_measure_with_space() {
         // "1 measure" (same as bar: 4 beats)
         match parse_duration_token("1 measure") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "2 measure"
         match parse_duration_token("2 measure") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__8_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 8.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "1 measures" (plural)
         match parse_duration_token("1 measures") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "3 measures" (plural)
         match parse_duration_token("3 measures") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__12_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 12.0),
             other => panic!("Unexpected result: {:?}", other),
         }
     }
@@ -293,25 +325,25 @@ mod tests {
     fn test_temporal_measure_without_space() {
         // "1measure" -> 4 beats
         match parse_duration_token("1measure") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "2measure"
         match parse_duration_token("2measure") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__8_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 8.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "1measures" (without space, with 's')
         match parse_duration_token("1measures") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // "3measures" (without space, with 's')
         match parse_duration_token("3measures") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__12_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 12.0),
             other => panic!("Unexpected result: {:?}", other),
         }
     }
@@ -320,17 +352,17 @@ mod tests {
     fn test_mixed_case() {
         // Case insensitive
         match parse_duration_token("1 BEAT") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__1_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 1.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         match parse_duration_token("2 Bar") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__8_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 8.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         match parse_duration_token("1MEASURE") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__4_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 4.0),
             other => panic!("Unexpected result: {:?}", other),
         }
     }
@@ -339,20 +371,63 @@ mod tests {
     fn test_float_values() {
         // Float beat values
         match parse_duration_token("0.5 beat") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__0.5_beats"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 0.5),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // Float bar values
         match parse_duration_token("1.5bar") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__6_bars"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 6.0),
             other => panic!("Unexpected result: {:?}", other),
         }
 
         // Float measure values
         match parse_duration_token("2.5 measures") {
-            Ok(DurationValue::Identifier(id)) => assert_eq!(id, "__temporal__10_measures"),
+            Ok(DurationValue::Beats(b)) => assert_eq!(b, 10.0),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_seconds_and_ticks() {
+        match parse_duration_token("2s") {
+            Ok(DurationValue::Span(span)) => assert_eq!(span, TimeSpan::seconds(2.0)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        match parse_duration_token("240 ticks") {
+            Ok(DurationValue::Span(span)) => assert_eq!(span.to_beats(120.0), 0.5),
             other => panic!("Unexpected result: {:?}", other),
         }
     }
+
+    #[test]
+    fn test_duration_sums_keep_musical_and_absolute_parts() {
+        let parsed = parse_duration_token("1/8 + 10ms").unwrap();
+        assert_eq!(
+            parsed,
+            DurationValue::Span(TimeSpan {
+                beats: 0.125,
+                seconds: 0.01
+            })
+        );
+        // An eighth of a beat at 120 BPM is 62.5 ms, at 60 BPM 125 ms
+        assert!((parsed.to_seconds(120.0).unwrap() - 0.0725).abs() < 1e-6);
+        assert!((parsed.to_seconds(60.0).unwrap() - 0.135).abs() < 1e-6);
+
+        assert!(parse_duration_token("1 bar + myVar").is_err());
+    }
+
+    #[test]
+    fn test_dotted_and_triplet_fractions() {
+        assert_eq!(
+            parse_duration_token("1/8d").unwrap(),
+            DurationValue::Beats(0.1875)
+        );
+        let triplet = parse_duration_token("1/4t").unwrap();
+        assert!(matches!(triplet, DurationValue::Beats(b) if (b - 1.0 / 6.0).abs() < 1e-6));
+        assert!(matches!(
+            parse_duration_token("1/8x").unwrap(),
+            DurationValue::Identifier(_)
+        ));
+    }
 }
@@ -1,3 +1,5 @@
+use super::helpers::{is_call_expression, parse_single_arg};
+use crate::engine::functions::units::is_unit_function;
 use crate::language::syntax::ast::Value;
 use anyhow::{Result, anyhow};
 use std::collections::HashMap;
@@ -18,6 +20,24 @@ pub fn parse_chained_effects(effects_str: &str) -> Result<Value> {
     Ok(Value::Map(effects))
 }
 
+/// Parse effect chain syntax into an ordered list, one `{ name: params }` map per effect.
+/// Used where processing order matters (e.g. group insert chains).
+pub fn parse_effect_list(effects_str: &str) -> Result<Value> {
+    effects_str
+        .split("->")
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|effect| {
+            parse_single_effect(effect).map(|(name, params)| {
+                let mut entry = HashMap::new();
+                entry.insert(name, params);
+                Value::Map(entry)
+            })
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Value::Array)
+}
+
 /// Parse a single effect definition into (name, parameters)
 fn parse_single_effect(effect_str: &str) -> Result<(String, Value)> {
     let effect_str = effect_str.trim();
@@ -28,7 +48,8 @@ fn parse_single_effect(effect_str: &str) -> Result<(String, Value)> {
     // Handle function-like syntax: effect_name(param1, param2, ...)
     if let Some((name, params_str)) = effect_str.split_once('(') {
         let name = name.trim().to_string();
-        let params_str = params_str.trim_end_matches(')');
+        let params_str = params_str.trim();
+        let params_str = params_str.strip_suffix(')').unwrap_or(params_str).trim();
 
         // Handle boolean single parameter
         if params_str == "true" {
@@ -58,6 +79,9 @@ fn parse_single_effect(effect_str: &str) -> Result<(String, Value)> {
                         Value::Boolean(false)
                     } else if let Ok(num) = value.parse::<f32>() {
                         Value::Number(num)
+                    } else if is_unit_call(value) {
+                        // `ms(1/8)` and friends are evaluated when the trigger runs
+                        parse_single_arg(value)?
                     } else {
                         Value::String(value.trim_matches('"').to_string())
                     };
@@ -67,6 +91,8 @@ fn parse_single_effect(effect_str: &str) -> Result<(String, Value)> {
             }
 
             Ok((name, Value::Map(params_map)))
+        } else if is_unit_call(params_str) {
+            Ok((name, parse_single_arg(params_str)?))
         } else {
             // Single string parameter
             Ok((
@@ -80,6 +106,14 @@ fn parse_single_effect(effect_str: &str) -> Result<(String, Value)> {
     }
 }
 
+/// `ms(1/8)`, `hz(A4)`: unit helpers, which are kept as calls instead of strings
+fn is_unit_call(value: &str) -> bool {
+    is_call_expression(value)
+        && value
+            .split_once('(')
+            .is_some_and(|(name, _)| is_unit_function(name.trim()))
+}
+
 #[cfg(test)]
 #[path = "test_effects.rs"]
 mod tests;
@@ -17,11 +17,11 @@ pub fn parse_function_args(args_str: &str) -> Result<Vec<Value>> {
                 in_string = !in_string;
                 current_arg.push(ch);
             }
-            '[' | '{' if !in_string => {
+            '[' | '{' | '(' if !in_string => {
                 depth += 1;
                 current_arg.push(ch);
             }
-            ']' | '}' if !in_string => {
+            ']' | '}' | ')' if !in_string => {
                 depth -= 1;
                 current_arg.push(ch);
             }
@@ -51,24 +51,24 @@ pub fn parse_single_arg(arg: &str) -> Result<Value> {
     let arg = arg.trim();
 
     // String literal
-    if arg.starts_with('"') && arg.ends_with('"') {
+    if arg.len() >= 2 && arg.starts_with('"') && arg.ends_with('"') {
         return Ok(Value::String(arg[1..arg.len() - 1].to_string()));
     }
 
     // Array
-    if arg.starts_with('[') && arg.ends_with(']') {
+    if arg.len() >= 2 && arg.starts_with('[') && arg.ends_with(']') {
         let inner = &arg[1..arg.len() - 1];
         let items = parse_function_args(inner)?;
         return Ok(Value::Array(items));
     }
 
     // Map/Object
-    if arg.starts_with('{') && arg.ends_with('}') {
+    if arg.len() >= 2 && arg.starts_with('{') && arg.ends_with('}') {
         let inner = &arg[1..arg.len() - 1];
         let mut map = HashMap::new();
 
-        // Parse key: value pairs
-        for pair in inner.split(',') {
+        // Parse key: value pairs (values may themselves be arrays, maps or calls)
+        for pair in split_top_level(inner) {
             if let Some(colon_idx) = pair.find(':') {
                 let key = pair[..colon_idx].trim().trim_matches('"');
                 let value = parse_single_arg(pair[colon_idx + 1..].trim())?;
@@ -112,6 +112,41 @@ pub fn parse_single_arg(arg: &str) -> Result<Value> {
     Ok(Value::Identifier(arg.to_string()))
 }
 
+/// Returns true for call expressions like `name(args)` or `settings.keys()`
+pub fn is_call_expression(input: &str) -> bool {
+    let Some(open_paren) = input.find('(') else {
+        return false;
+    };
+    let name = &input[..open_paren];
+    input.ends_with(')')
+        && name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Split on commas that are not nested inside brackets, braces, parentheses or strings
+pub fn split_top_level(input: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut start = 0;
+    for (idx, ch) in input.char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            '[' | '{' | '(' if !in_string => depth += 1,
+            ']' | '}' | ')' if !in_string => depth -= 1,
+            ',' if depth == 0 && !in_string => {
+                parts.push(&input[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
 /// Parse synth definition: synth waveform { params } OR synth plugin.<name> { params }
 /// Returns a Map with type="synth", waveform/plugin info, and parameters
 ///
@@ -119,10 +154,46 @@ pub fn parse_single_arg(arg: &str) -> Result<Value> {
 /// - synth "sine" { attack: 0.1 }
 /// - synth plugin.acid.synth { waveform: "sine" }
 /// - synth { waveform: "sine", attack: 0.1 }  // waveform in params
+/// - synth extends basePad { cutoff: 0.7 }    // inherits basePad's parameters
 pub fn parse_synth_definition(input: &str) -> Result<Value> {
     // Remove "synth " prefix
     let input = input.trim_start_matches("synth ").trim();
 
+    // `extends parent` names the definition this one inherits from; the parent is
+    // resolved (and merged) by the interpreter
+    let (input, parent) = split_synth_parent(input);
+    let mut map = parse_synth_body(&input)?;
+    if let (Some(parent), Value::Map(map)) = (parent, &mut map) {
+        map.insert("_extends".to_string(), Value::String(parent));
+    }
+    Ok(map)
+}
+
+/// Split `sine extends basePad { ... }` into the definition without the clause and the
+/// parent name
+fn split_synth_parent(input: &str) -> (String, Option<String>) {
+    let head_end = input.find('{').unwrap_or(input.len());
+    let (head, body) = input.split_at(head_end);
+    let mut words: Vec<&str> = head.split_whitespace().collect();
+    let Some(pos) = words.iter().position(|word| *word == "extends") else {
+        return (input.to_string(), None);
+    };
+    let parent = words.get(pos + 1).map(|name| name.to_string());
+    words.drain(pos..(pos + 2).min(words.len()));
+    (
+        format!("{} {}", words.join(" "), body).trim().to_string(),
+        parent,
+    )
+}
+
+fn parse_synth_body(input: &str) -> Result<Value> {
+    // A bare `extends parent` leaves nothing to parse
+    if input.is_empty() {
+        let mut map = HashMap::new();
+        map.insert("type".to_string(), Value::String("synth".to_string()));
+        return Ok(Value::Map(map));
+    }
+
     // Check if we have braces
     let (waveform_or_plugin, params_str) = if let Some(brace_idx) = input.find('{') {
         let before_brace = input[..brace_idx].trim();
@@ -162,7 +233,9 @@ pub fn parse_synth_definition(input: &str) -> Result<Value> {
     };
 
     // Parse parameters from { key: value, ... }
-    let params_str = params_str.trim_matches(|c| c == '{' || c == '}').trim();
+    let params_str = params_str.trim();
+    let params_str = params_str.strip_prefix('{').unwrap_or(params_str);
+    let params_str = params_str.strip_suffix('}').unwrap_or(params_str).trim();
     let mut params_map = HashMap::new();
 
     // Add type
@@ -226,7 +299,7 @@ pub fn parse_synth_definition(input: &str) -> Result<Value> {
         let cleaned = cleaned_lines.join("\n");
         let normalized = cleaned.replace('\n', ",").replace('\r', "");
 
-        for pair in normalized.split(',') {
+        for pair in split_top_level(&normalized) {
             let pair = pair.trim();
             if pair.is_empty() {
                 continue;
@@ -239,6 +312,12 @@ pub fn parse_synth_definition(input: &str) -> Result<Value> {
                 let value_part = parts[1..].join(":");
                 let value_str = value_part.trim().trim_matches(',').trim_matches('"');
 
+                // Nested maps (e.g. `lfo: { rate: 2.0 }`)
+                if value_str.starts_with('{') {
+                    params_map.insert(key, parse_map_value(value_str)?);
+                    continue;
+                }
+
                 // Parse arrays (for filters)
                 if value_str.starts_with('[') {
                     if let Ok(array_val) = parse_array_value(value_str) {
@@ -0,0 +1,223 @@
+//! Spellings older language versions accepted, read for `devalang migrate`
+//!
+//! `parse_legacy` parses like `parse`, but also reads:
+//! - `deprecated_syntax`: `@import` / `@export` / `@use` / `@load`
+//! - `var_keyword`: `var x = ...`, read as a `Var` declaration with `let` values
+//! - `trigger_keyword`: `trigger .kit.kick 1/4`
+//! - `effect_map`: effect maps, `.kit.kick 1/4 { reverb: 0.5 }` (read as the chain it
+//!   stands for) and `synth saw { effects: { delay: 300 } }` (kept as an `effects` map)
+//!
+//! A line using one of them that still does not parse is kept as an `Unknown` statement
+//! and reported with the error, so the rest of the file can be read.
+
+use super::helpers::{parse_single_arg, split_top_level};
+use super::{SourceSpan, parse_line as parse_current_line, parse_lines, preprocessing, trigger};
+use crate::language::syntax::ast::nodes::{Statement, StatementKind, Value};
+use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+
+const DIRECTIVES: [&str; 4] = ["import", "export", "use", "load"];
+
+/// Legacy spellings read on one (joined) source line
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegacySpelling {
+    pub line: usize,
+    /// Migrations the line needs, in the order they were read
+    pub migrations: Vec<&'static str>,
+    /// Why the line still did not parse; it was kept as it was
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct LegacyParse {
+    pub statements: Vec<Statement>,
+    pub spellings: Vec<LegacySpelling>,
+}
+
+/// Parse `source`, accepting the legacy spellings listed above
+pub fn parse_legacy(source: &str, path: PathBuf) -> Result<LegacyParse> {
+    let preprocessed = preprocessing::preprocess_multiline_arrow_calls(
+        &preprocessing::preprocess_multiline_braces(source),
+    );
+    let lines: Vec<_> = preprocessed.lines().collect();
+    let mut spellings = Vec::new();
+    let statements = parse_lines(&lines, 0, lines.len(), 0, &path, Some(&mut spellings))?;
+    Ok(LegacyParse {
+        statements,
+        spellings,
+    })
+}
+
+/// `parse_line`, recording the legacy spellings `line` uses in `spellings`
+pub(super) fn parse_line(
+    line: &str,
+    line_number: usize,
+    origin: SourceSpan,
+    path: &Path,
+    spellings: &mut Vec<LegacySpelling>,
+) -> Result<Statement> {
+    let mut migrations = Vec::new();
+    let read = read_line(line, line_number, origin, path, &mut migrations);
+    if migrations.is_empty() {
+        return read;
+    }
+    let error = read.as_ref().err().map(|e| e.to_string());
+    spellings.push(LegacySpelling {
+        line: line_number,
+        migrations,
+        error,
+    });
+    read.or_else(|_| {
+        Ok(Statement::new(
+            StatementKind::Unknown,
+            Value::String(line.to_string()),
+            0,
+            line_number,
+            1,
+        ))
+    })
+}
+
+fn read_line(
+    line: &str,
+    line_number: usize,
+    origin: SourceSpan,
+    path: &Path,
+    migrations: &mut Vec<&'static str>,
+) -> Result<Statement> {
+    if let Some(rest) = line.strip_prefix('@')
+        && DIRECTIVES
+            .iter()
+            .any(|directive| keyword_rest(rest, directive).is_some())
+    {
+        migrations.push("deprecated_syntax");
+        return read_line(rest, line_number, origin, path, migrations);
+    }
+
+    if let Some(rest) = keyword_rest(line, "var") {
+        migrations.push("var_keyword");
+        let mut statement = read_line(
+            &format!("let {}", rest),
+            line_number,
+            origin,
+            path,
+            migrations,
+        )?;
+        if let StatementKind::Let { name, value } = statement.kind {
+            statement.kind = StatementKind::Var { name, value };
+        }
+        return Ok(statement);
+    }
+
+    if let Some(rest) = keyword_rest(line, "trigger") {
+        migrations.push("trigger_keyword");
+        let rest = if rest.starts_with('.') {
+            rest.to_string()
+        } else {
+            format!(".{}", rest)
+        };
+        return read_line(&rest, line_number, origin, path, migrations);
+    }
+
+    if line.starts_with('.')
+        && !line.contains("->")
+        && let Some(open) = line.find('{')
+    {
+        migrations.push("effect_map");
+        let close = matching_brace(line, open)
+            .filter(|close| line[close + 1..].trim().is_empty())
+            .ok_or_else(|| anyhow!("effect map must close the trigger line"))?;
+        let mut statement = trigger::parse_trigger_line(line[..open].trim_end(), line_number)?;
+        if let StatementKind::Trigger { effects, .. } = &mut statement.kind {
+            *effects = Some(effect_map(&line[open..=close])?);
+        }
+        return Ok(statement);
+    }
+
+    let mut statement = parse_current_line(line, line_number, origin, path)?;
+    if let StatementKind::Let {
+        value: Some(Value::Map(synth)),
+        ..
+    }
+    | StatementKind::Const {
+        value: Some(Value::Map(synth)),
+        ..
+    } = &mut statement.kind
+        && synth.contains_key("effects")
+        && synth.get("type") == Some(&Value::String("synth".to_string()))
+    {
+        // The synth body parser reads nested maps one level deep, so the `effects`
+        // entry is read again in full
+        migrations.push("effect_map");
+        synth.insert("effects".to_string(), synth_effects(line)?);
+    }
+    Ok(statement)
+}
+
+/// What follows `keyword` and whitespace at the start of `line`
+fn keyword_rest<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(keyword)?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim_start()).filter(|rest| !rest.is_empty())
+}
+
+/// `{ reverb: { size: 0.5 }, gain: 0.8 }` as the map an effect chain parses to
+fn effect_map(text: &str) -> Result<Value> {
+    let Value::Map(entries) = parse_single_arg(text)? else {
+        return Err(anyhow!("expected an effect map, found '{}'", text));
+    };
+    if let Some(name) = entries
+        .keys()
+        .find(|name| name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    {
+        return Err(anyhow!("effect map key '{}' is not an effect name", name));
+    }
+    if entries.is_empty() {
+        return Err(anyhow!("empty effect map"));
+    }
+    Ok(Value::Map(entries))
+}
+
+/// The `effects` entry of the synth body on a `let` / `const` line
+fn synth_effects(line: &str) -> Result<Value> {
+    let definition = line.split("->").next().unwrap_or(line);
+    let open = definition
+        .find('{')
+        .ok_or_else(|| anyhow!("synth effects need a parameter block"))?;
+    let close = matching_brace(definition, open)
+        .ok_or_else(|| anyhow!("unclosed synth parameter block"))?;
+    for entry in split_top_level(&definition[open + 1..close]) {
+        if let Some((key, value)) = entry.split_once(':')
+            && key.trim().trim_matches('"') == "effects"
+        {
+            return effect_map(value.trim());
+        }
+    }
+    Err(anyhow!("synth effects must be a map"))
+}
+
+/// Index of the brace closing the one at `open`, skipping strings
+fn matching_brace(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    for (idx, ch) in text[open..].char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+#[path = "test_legacy.rs"]
+mod tests;
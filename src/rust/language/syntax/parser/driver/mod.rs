@@ -2,23 +2,43 @@ pub mod directive;
 pub mod duration;
 pub mod effects;
 pub mod helpers;
+pub mod legacy;
 pub mod preprocessing;
 pub mod routing;
 pub mod statements;
 pub mod trigger;
 // Re-export statement-level parsers so they are available at driver root
-use crate::language::syntax::ast::nodes::{Statement, StatementKind, Value};
+use crate::language::syntax::ast::nodes::{SourceSpan, Statement, StatementKind, Value};
 use anyhow::{Result, anyhow};
 pub use statements::*;
 use std::path::{Path, PathBuf};
 
+/// A statement that failed to parse, with the line and span it was read from.
+///
+/// Displays as the underlying message so existing error output is unchanged; callers
+/// that want a location (`check`, the wasm error registry) downcast to it.
+#[derive(Debug)]
+pub struct LocatedParseError {
+    pub message: String,
+    pub line: usize,
+    pub span: SourceSpan,
+}
+
+impl std::fmt::Display for LocatedParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for LocatedParseError {}
+
 /// Find the closest keyword suggestion using Levenshtein distance
 /// Returns the suggestion if distance is <= 2 (typo-like)
-fn find_keyword_suggestion(input: &str, keywords: &[&str]) -> Option<String> {
+pub(crate) fn find_keyword_suggestion(input: &str, keywords: &[&str]) -> Option<String> {
     // Calculate Levenshtein distance between two strings
     fn levenshtein(s1: &str, s2: &str) -> usize {
-        let len1 = s1.len();
-        let len2 = s2.len();
+        let len1 = s1.chars().count();
+        let len2 = s2.chars().count();
         let mut matrix = vec![vec![0; len2 + 1]; len1 + 1];
 
         for i in 0..=len1 {
@@ -67,16 +87,18 @@ pub fn parse(source: &str, path: PathBuf) -> Result<Vec<Statement>> {
     let preprocessed = preprocessing::preprocess_multiline_arrow_calls(&braces_pre);
 
     let lines: Vec<_> = preprocessed.lines().collect();
-    parse_lines(&lines, 0, lines.len(), 0, &path)
+    parse_lines(&lines, 0, lines.len(), 0, &path, None)
 }
 
-/// Parse a range of lines into statements, handling indentation for blocks.
+/// Parse a range of lines into statements, handling indentation for blocks. With
+/// `legacy`, lines are read by `legacy::parse_line`, which records their legacy spellings.
 fn parse_lines(
     lines: &Vec<&str>,
     start: usize,
     end: usize,
     indent: usize,
     path: &Path,
+    mut legacy: Option<&mut Vec<legacy::LegacySpelling>>,
 ) -> Result<Vec<Statement>> {
     use crate::language::syntax::ast::nodes::StatementKind;
 
@@ -97,10 +119,30 @@ fn parse_lines(
             break;
         }
 
-        // parse header line
-        let mut statement = parse_line(trimmed, i + 1, path)?;
+        // parse header line; spans are relative to the raw line so columns count the
+        // indentation too
+        let origin = SourceSpan::in_line(raw, current_indent, current_indent);
+        let parsed = match legacy.as_deref_mut() {
+            Some(spellings) => legacy::parse_line(trimmed, i + 1, origin, path, spellings),
+            None => parse_line(trimmed, i + 1, origin, path),
+        };
+        let mut statement = parsed.map_err(|e| {
+            if e.downcast_ref::<LocatedParseError>().is_some() {
+                return e;
+            }
+            anyhow::Error::new(LocatedParseError {
+                message: e.to_string(),
+                line: i + 1,
+                span: SourceSpan::in_line(raw, current_indent, current_indent + trimmed.len()),
+            })
+        })?;
         statement.indent = current_indent;
         statement.line = i + 1;
+        if statement.span == SourceSpan::default() {
+            statement.span =
+                SourceSpan::in_line(raw, current_indent, current_indent + trimmed.len());
+        }
+        statement.column = statement.span.start_column;
 
         // determine body range for block statements
         let body_start = i + 1;
@@ -120,7 +162,19 @@ fn parse_lines(
 
         // If we found a body, parse it and attach appropriately based on kind
         if body_end > body_start {
-            let body = parse_lines(lines, body_start, body_end, current_indent + 1, path)?;
+            // Automation bodies hold `param` timelines, not statements; they are parsed below
+            let body = if matches!(statement.kind, StatementKind::Automate { .. }) {
+                Vec::new()
+            } else {
+                parse_lines(
+                    lines,
+                    body_start,
+                    body_end,
+                    current_indent + 1,
+                    path,
+                    legacy.as_deref_mut(),
+                )?
+            };
 
             // To avoid borrowing `statement.kind` and then assigning to it
             // (which the borrow checker forbids), take ownership of the kind
@@ -144,6 +198,12 @@ fn parse_lines(
                         body: body.clone(),
                     };
                 }
+                StatementKind::At { position, .. } => {
+                    statement.kind = StatementKind::At {
+                        position,
+                        body: body.clone(),
+                    };
+                }
                 StatementKind::Loop { count, .. } => {
                     statement.kind = StatementKind::Loop {
                         count,
@@ -159,14 +219,16 @@ fn parse_lines(
                 }
                 StatementKind::Automate { target } => {
                     statement.kind = StatementKind::Automate { target };
-                    // store raw body as value map if needed elsewhere
-                    let raw_lines: Vec<String> = lines[body_start..body_end]
-                        .iter()
-                        .map(|s| s.to_string())
-                        .collect();
-                    let raw_body = raw_lines.join("\n");
-                    let mut map = std::collections::HashMap::new();
-                    map.insert("body".to_string(), Value::String(raw_body));
+                    let raw_body = lines[body_start..body_end].join("\n");
+                    // Keep the header options (mode) next to the parsed timeline
+                    let mut map = match std::mem::take(&mut statement.value) {
+                        Value::Map(map) => map,
+                        _ => std::collections::HashMap::new(),
+                    };
+                    map.insert(
+                        "params".to_string(),
+                        statements::parse_automate_body(&raw_body),
+                    );
                     statement.value = Value::Map(map);
                 }
                 StatementKind::Function {
@@ -179,14 +241,10 @@ fn parse_lines(
                     };
                     statement.value = Value::Identifier(name.clone());
                 }
-                StatementKind::Group { .. } => {
-                    // attach body for groups, keep name in value if present
-                    let group_name = match &statement.value {
-                        Value::Identifier(s) => s.clone(),
-                        _ => "".to_string(),
-                    };
+                StatementKind::Group { name, .. } => {
+                    // attach body for groups, keep name (and effect chain) in value
                     statement.kind = StatementKind::Group {
-                        name: group_name,
+                        name,
                         body: body.clone(),
                     };
                 }
@@ -396,7 +454,13 @@ fn parse_lines(
     Ok(statements)
 }
 
-fn parse_line(line: &str, line_number: usize, path: &Path) -> Result<Statement> {
+/// Parse one trimmed line; `origin` is the (empty) span where it starts in the raw line
+fn parse_line(
+    line: &str,
+    line_number: usize,
+    origin: SourceSpan,
+    path: &Path,
+) -> Result<Statement> {
     use crate::language::syntax::parser::driver::statements::*;
 
     if line.starts_with('.') {
@@ -410,10 +474,22 @@ fn parse_line(line: &str, line_number: usize, path: &Path) -> Result<Statement>
         .ok_or_else(|| anyhow!("empty line"))?
         .to_string();
     let keyword = first_token.trim_end_matches(':').to_lowercase();
+    // Unknown statements underline their first token
+    let keyword_span = SourceSpan {
+        end_byte: origin.start_byte + first_token.len(),
+        end_column: origin.start_column + first_token.chars().count(),
+        ..origin
+    };
+
+    // A keyword followed by an arrow is a variable named after it (`strip -> note(C4)`)
+    let is_named_value = parts
+        .clone()
+        .next()
+        .is_some_and(|token| token.starts_with("->"));
 
-    // Check for routing statements (node, fx, route, duck, sidechain)
-    let routing_keywords = ["node", "fx", "route", "duck", "sidechain"];
-    if routing_keywords.contains(&keyword.as_str()) {
+    // Check for routing statements (node, fx, route, strip, duck, sidechain)
+    let routing_keywords = ["node", "fx", "route", "strip", "duck", "sidechain"];
+    if routing_keywords.contains(&keyword.as_str()) && !is_named_value {
         return crate::language::syntax::parser::driver::routing::parse_routing_statement(
             line,
             line_number,
@@ -436,16 +512,61 @@ fn parse_line(line: &str, line_number: usize, path: &Path) -> Result<Statement>
         return parse_bind(line, line_number);
     }
 
+    if keyword == "synth" && statements::core::is_synth_declaration(line) {
+        return statements::core::parse_synth_declaration(line, line_number);
+    }
+
+    if statements::core::is_articulated_note(line) {
+        return statements::core::parse_articulated_note(line, line_number);
+    }
+
     // If this line contains an arrow call, parse it as an ArrowCall, UNLESS the
     // statement starts with a reserved keyword that must be handled (let/var/const/etc.).
     // This ensures constructs like `let name = .bank.kick -> reverse(...)` are
     // parsed by `parse_let` rather than being mis-parsed as an ArrowCall.
     let reserved_keywords = [
-        "bpm", "tempo", "print", "sleep", "rest", "wait", "pattern", "bank", "let", "const", "for",
-        "foreach", "loop", "if", "else", "group", "automate", "call", "spawn", "sequence", "layer",
-        "on", "emit", "routing", "return", "break", "import", "export", "use", "load",
+        "bpm",
+        "tempo",
+        "print",
+        "sleep",
+        "rest",
+        "wait",
+        "pattern",
+        "bank",
+        "let",
+        "const",
+        "param",
+        "macro",
+        "for",
+        "foreach",
+        "loop",
+        "if",
+        "else",
+        "group",
+        "automate",
+        "call",
+        "spawn",
+        "sequence",
+        "layer",
+        "on",
+        "emit",
+        "routing",
+        "return",
+        "break",
+        "continue",
+        "import",
+        "export",
+        "use",
+        "load",
+        "at",
+        "accent",
+        "scene",
+        "switch",
+        "snapshot",
+        "metronome",
+        "mark",
     ];
-    if line.contains("->") && !reserved_keywords.contains(&keyword.as_str()) {
+    if line.contains("->") && (is_named_value || !reserved_keywords.contains(&keyword.as_str())) {
         return statements::parse_arrow_call(line, line_number);
     }
 
@@ -460,11 +581,23 @@ fn parse_line(line: &str, line_number: usize, path: &Path) -> Result<Statement>
         "bank" => parse_bank(parts, line_number),
         "let" => parse_let(line, parts, line_number),
         "const" => parse_const(line, parts, line_number),
+        "param" => parse_param(line, parts, line_number),
+        "macro" => statements::core::parse_macro(line, line_number),
         "for" | "foreach" => parse_for(parts, line_number),
         "loop" => parse_loop(parts, line_number),
         "if" => statements::structure::parse_if(parts, line_number),
         "else" => statements::structure::parse_else(line, line_number),
-        "group" => statements::structure::parse_group(parts, line_number),
+        "group" => statements::structure::parse_group(line, line_number),
+        "at" => statements::structure::parse_at(line, line_number),
+        "scene" => statements::structure::parse_scene(line, line_number),
+        "switch" => statements::structure::parse_switch(line, line_number),
+        "snapshot" => statements::structure::parse_snapshot(line, line_number),
+        "accent" if line.split_whitespace().nth(1) == Some("map") => {
+            statements::core::parse_accent_map(line, line_number)
+        }
+        "automate" if statements::structure::is_automate_formula(line) => {
+            statements::structure::parse_automate_formula(line, line_number)
+        }
         "automate" => {
             crate::language::syntax::parser::driver::statements::structure::parse_automate(
                 parts,
@@ -473,11 +606,15 @@ fn parse_line(line: &str, line_number: usize, path: &Path) -> Result<Statement>
         }
         "call" | "sequence" => parse_call(line, parts, line_number),
         "break" => statements::structure::parse_break(parts, line_number),
+        "continue" => statements::structure::parse_continue(parts, line_number),
         "function" => statements::structure::parse_function(line, line_number),
         "spawn" | "layer" => parse_spawn(parts, line_number),
         "on" => parse_on(parts, line_number),
         "emit" => parse_emit(line, parts, line_number),
         "return" => statements::core::parse_return(line, line_number),
+        "@persist" => statements::core::parse_persist(line, line_number),
+        "metronome" => statements::core::parse_metronome(line, line_number),
+        "mark" => statements::core::parse_mark(line, line_number),
         "routing" => {
             crate::language::syntax::parser::driver::routing::parse_routing_command(line_number)
         }
@@ -508,25 +645,28 @@ fn parse_line(line: &str, line_number: usize, path: &Path) -> Result<Statement>
                 {
                     use crate::web::registry::debug;
                     if debug::is_debug_errors_enabled() {
-                        debug::push_parse_error_from_parts(
+                        debug::push_parse_error_with_span(
                             format!(
                                 "Unknown statement '{}'. Did you mean '{}' ?",
                                 keyword, suggestion_str
                             ),
                             line_number,
-                            1,
+                            &keyword_span,
                             "UnknownStatement".to_string(),
                         );
                     }
                 }
 
-                return Ok(Statement::new(
-                    StatementKind::Unknown,
-                    Value::String(error_msg),
-                    0,
-                    line_number,
-                    1,
-                ));
+                return Ok(Statement {
+                    span: keyword_span,
+                    ..Statement::new(
+                        StatementKind::Unknown,
+                        Value::String(error_msg),
+                        0,
+                        line_number,
+                        keyword_span.start_column,
+                    )
+                });
             }
 
             // Check if this looks like a potential trigger identifier (single word, no special chars except dots)
@@ -554,22 +694,25 @@ fn parse_line(line: &str, line_number: usize, path: &Path) -> Result<Statement>
             {
                 use crate::web::registry::debug;
                 if debug::is_debug_errors_enabled() {
-                    debug::push_parse_error_from_parts(
+                    debug::push_parse_error_with_span(
                         format!("Unknown statement '{}'", keyword),
                         line_number,
-                        1,
+                        &keyword_span,
                         "UnknownStatement".to_string(),
                     );
                 }
             }
 
-            return Ok(Statement::new(
-                StatementKind::Unknown,
-                Value::String(error_msg),
-                0,
-                line_number,
-                1,
-            ));
+            return Ok(Statement {
+                span: keyword_span,
+                ..Statement::new(
+                    StatementKind::Unknown,
+                    Value::String(error_msg),
+                    0,
+                    line_number,
+                    keyword_span.start_column,
+                )
+            });
         }
     };
 }
@@ -595,3 +738,7 @@ impl SimpleParser {
         Self::parse(&s, buf)
     }
 }
+
+#[cfg(test)]
+#[path = "test_spans.rs"]
+mod tests;
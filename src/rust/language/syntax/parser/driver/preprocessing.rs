@@ -66,9 +66,10 @@ pub fn preprocess_multiline_braces(source: &str) -> String {
                         merged.push_str(clean_trimmed);
                     }
 
-                    // Update brace depth (use original line for brace counting)
-                    brace_depth += next_line.matches('{').count();
-                    brace_depth -= next_line.matches('}').count();
+                    // Update brace depth (use original line for brace counting); a stray
+                    // extra `}` closes the block instead of underflowing
+                    brace_depth = (brace_depth + next_line.matches('{').count())
+                        .saturating_sub(next_line.matches('}').count());
 
                     i += 1;
                 }
@@ -334,8 +335,15 @@ pub fn preprocess_multiline_arrow_calls(source: &str) -> String {
                     // declaration when used under a bare identifier. Other methods (like `note` or
                     // `duration`) are runtime actions and should remain as separate ArrowCall
                     // statements targeting the identifier.
-                    let synth_param_methods =
-                        ["type", "adsr", "lfo", "filter", "filters", "options"];
+                    let synth_param_methods = [
+                        "type",
+                        "adsr",
+                        "lfo",
+                        "filter",
+                        "filters",
+                        "options",
+                        "articulations",
+                    ];
 
                     let _let_pattern1 = format!("let {} =", token);
                     let _let_pattern2 = format!("let {}=", token);
@@ -1,4 +1,5 @@
 use crate::language::syntax::ast::{Statement, StatementKind, Value};
+use crate::language::syntax::parser::driver::duration::parse_duration_token;
 use crate::language::syntax::parser::driver::effects::parse_chained_effects;
 use anyhow::{Result, anyhow};
 
@@ -14,7 +15,7 @@ pub fn parse_routing_command(line_number: usize) -> Result<Statement> {
     ))
 }
 
-/// Parse routing block statements (node, fx, route, duck, sidechain)
+/// Parse routing block statements (node, fx, route, strip, duck, sidechain)
 pub fn parse_routing_statement<'a>(line: &str, line_number: usize) -> Result<Statement> {
     let trimmed = line.trim();
 
@@ -71,6 +72,32 @@ pub fn parse_routing_statement<'a>(line: &str, line_number: usize) -> Result<Sta
     // route <source> to <dest> with effect(...)
     if trimmed.starts_with("route ") {
         let rest = trimmed[6..].trim();
+        // route <group> -> outputs 3-4
+        if let Some((source, outputs)) = rest.split_once("->") {
+            let channels = outputs
+                .trim()
+                .trim_end_matches(':')
+                .strip_prefix("outputs")
+                .ok_or_else(|| {
+                    anyhow!(
+                        "route statement requires format: route <group> -> outputs <n>[-<m>]: {}",
+                        trimmed
+                    )
+                })?;
+            let (first, last) =
+                crate::engine::audio::outputs::OutputRoute::parse_channels(channels)?;
+            return Ok(Statement::new(
+                StatementKind::RoutingOutputs {
+                    source: source.trim().to_string(),
+                    first,
+                    last,
+                },
+                Value::Null,
+                0,
+                line_number,
+                1,
+            ));
+        }
         if let Some((source_part, rest)) = rest.split_once(" to ") {
             let source = source_part.trim().to_string();
             if let Some((dest_part, effect_part)) = rest.split_once(" with ") {
@@ -110,9 +137,30 @@ pub fn parse_routing_statement<'a>(line: &str, line_number: usize) -> Result<Sta
         }
     }
 
+    // strip <group> [gain 0.8|-3db] [pan -0.2] [invert] [lowcut 80hz]
+    if let Some(rest) = trimmed.strip_prefix("strip ") {
+        let rest = rest.trim().trim_end_matches(':');
+        let (target, options) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let strip = crate::engine::audio::mixer::ChannelStrip::parse_options(options)?;
+        return Ok(Statement::new(
+            StatementKind::RoutingStrip {
+                target: target.to_string(),
+                strip,
+            },
+            Value::Null,
+            0,
+            line_number,
+            1,
+        ));
+    }
+
     // duck <source> to <dest> with effect(...)
+    // duck <target> by <key> [amount 0.6] [attack 5ms] [release 150ms]
     if trimmed.starts_with("duck ") {
         let rest = trimmed[5..].trim();
+        if let Some((target, options)) = rest.split_once(" by ") {
+            return parse_duck_by(target.trim(), options, line_number);
+        }
         if let Some((source_part, rest)) = rest.split_once(" to ") {
             let source = source_part.trim().to_string();
             if let Some((dest_part, effect_part)) = rest.split_once(" with ") {
@@ -138,7 +186,7 @@ pub fn parse_routing_statement<'a>(line: &str, line_number: usize) -> Result<Sta
             }
         } else {
             return Err(anyhow!(
-                "duck statement requires format: duck <source> to <dest> with effect(...): {}",
+                "duck statement requires format: duck <target> by <key> [amount ...] or duck <source> to <dest> with effect(...): {}",
                 trimmed
             ));
         }
@@ -181,6 +229,62 @@ pub fn parse_routing_statement<'a>(line: &str, line_number: usize) -> Result<Sta
     Err(anyhow!("Unknown routing statement: {}", trimmed))
 }
 
+/// `duck pads by kick amount 0.6 attack 5ms release 150ms`: shorthand for
+/// `duck pads to kick with duck({ amount: 0.6, attack: 5ms, release: 150ms })`
+fn parse_duck_by(target: &str, options: &str, line_number: usize) -> Result<Statement> {
+    let mut words = options.split_whitespace();
+    let key = words
+        .next()
+        .map(|word| word.trim_end_matches(':'))
+        .filter(|word| !word.is_empty())
+        .ok_or_else(|| anyhow!("duck statement requires a group after 'by'"))?;
+    if target.is_empty() {
+        return Err(anyhow!("duck statement requires a group before 'by'"));
+    }
+
+    let mut params = std::collections::HashMap::new();
+    while let Some(option) = words.next() {
+        let value = words
+            .next()
+            .map(|word| word.trim_end_matches(':'))
+            .ok_or_else(|| anyhow!("duck option '{}' requires a value", option))?;
+        let value = match option {
+            "amount" => {
+                let (number, divisor) = match value.strip_suffix('%') {
+                    Some(percent) => (percent, 100.0),
+                    None => (value, 1.0),
+                };
+                let amount: f32 = number
+                    .parse()
+                    .map_err(|_| anyhow!("invalid duck amount: '{}'", value))?;
+                Value::Number(amount / divisor)
+            }
+            "attack" | "release" => Value::Duration(parse_duration_token(value)?),
+            other => {
+                return Err(anyhow!(
+                    "unknown duck option '{}' (expected amount, attack or release)",
+                    other
+                ));
+            }
+        };
+        params.insert(option.to_string(), value);
+    }
+
+    let mut effect = std::collections::HashMap::new();
+    effect.insert("duck".to_string(), Value::Map(params));
+    Ok(Statement::new(
+        StatementKind::RoutingDuck {
+            source: target.to_string(),
+            destination: key.to_string(),
+            effect: Value::Map(effect),
+        },
+        Value::Null,
+        0,
+        line_number,
+        1,
+    ))
+}
+
 /// Parse a single routing effect like "effect({ param: value, ... })"
 fn parse_single_routing_effect(effect_str: &str) -> Result<Value> {
     let effect_str = effect_str.trim();
@@ -1,5 +1,8 @@
 use super::super::duration::parse_duration_token;
-use super::super::helpers::{parse_array_value, parse_synth_definition};
+use super::super::helpers::{
+    is_call_expression, parse_array_value, parse_single_arg, parse_synth_definition,
+};
+use crate::engine::audio::settings::ClickMode;
 use crate::language::syntax::ast::{Statement, StatementKind, Value};
 /// Core statement parsing: tempo, print, let, var, const, sleep, bank
 use anyhow::{Result, anyhow};
@@ -124,13 +127,17 @@ pub fn parse_print(line: &str, line_number: usize) -> Result<Statement> {
 
 /// Parse sleep statement
 pub fn parse_sleep(
-    mut parts: impl Iterator<Item = impl AsRef<str>>,
+    parts: impl Iterator<Item = impl AsRef<str>>,
     line_number: usize,
 ) -> Result<Statement> {
     let value = parts
-        .next()
-        .ok_or_else(|| anyhow!("sleep instruction requires a duration"))?;
-    let duration = parse_duration_token(value.as_ref())?;
+        .map(|part| part.as_ref().to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if value.is_empty() {
+        return Err(anyhow!("sleep instruction requires a duration"));
+    }
+    let duration = parse_duration_token(&value)?;
     Ok(Statement::new(
         StatementKind::Sleep,
         Value::Duration(duration),
@@ -245,6 +252,11 @@ pub fn parse_let(
                                     handled_as_synth_param = true;
                                 }
                             }
+                        } else if first_method == "articulations"
+                            && let Some(table @ Value::Map(_)) = args_arr.first()
+                        {
+                            synth_map.insert("articulations".to_string(), table.clone());
+                            handled_as_synth_param = true;
                         }
                     }
 
@@ -309,6 +321,13 @@ pub fn parse_let(
                                         }
                                     }
                                 }
+                                if mname == "articulations"
+                                    && let Some(Value::Array(args_arr)) = call_map.get("args")
+                                    && let Some(table @ Value::Map(_)) = args_arr.first()
+                                {
+                                    synth_map.insert("articulations".to_string(), table.clone());
+                                    continue;
+                                }
                                 let mut eff_map = std::collections::HashMap::new();
                                 eff_map.insert("type".to_string(), Value::String(mname.clone()));
                                 if let Some(Value::Array(args_arr)) = call_map.get("args") {
@@ -345,6 +364,8 @@ pub fn parse_let(
         }
     } else if remainder.starts_with('[') && remainder.ends_with(']') {
         Some(parse_array_value(&remainder)?)
+    } else if is_data_expression(&remainder) {
+        Some(parse_single_arg(&remainder)?)
     } else if remainder.starts_with('.') {
         let stmt = crate::language::syntax::parser::driver::trigger::parse_trigger_line(
             &remainder,
@@ -375,6 +396,11 @@ pub fn parse_let(
     ))
 }
 
+/// Map literals (`{ swing: 0.2 }`) and call expressions (`merge(a, b)`, `settings.keys()`)
+fn is_data_expression(remainder: &str) -> bool {
+    (remainder.starts_with('{') && remainder.ends_with('}')) || is_call_expression(remainder)
+}
+
 /// Parse var statement
 pub fn parse_var(
     line: &str,
@@ -418,6 +444,65 @@ pub fn parse_var(
     ))
 }
 
+/// Whether a `synth` line declares a named definition (`synth lead extends basePad { ... }`)
+/// rather than using a variable called `synth`
+pub fn is_synth_declaration(line: &str) -> bool {
+    let mut words = line.split_whitespace().skip(1);
+    let Some(name) = words.next() else {
+        return false;
+    };
+    name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && words.next().is_some_and(|next| next != "=" && next != "->")
+}
+
+/// Parse a named synth declaration, sugar for `let lead = synth ...`
+pub fn parse_synth_declaration(line: &str, line_number: usize) -> Result<Statement> {
+    let rest = line.trim().trim_start_matches("synth").trim_start();
+    let (name, definition) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let desugared = format!("let {} = synth {}", name, definition.trim());
+    parse_let(&desugared, std::iter::once(name), line_number)
+}
+
+/// Whether a line plays a note through an articulation (`lead!pluck C4 1/8`) rather than
+/// calling methods on it (`lead!pluck -> note(C4)`)
+pub fn is_articulated_note(line: &str) -> bool {
+    let head = line.split("->").next().unwrap_or_default();
+    let mut words = head.split_whitespace();
+    let Some((synth, mode)) = words.next().and_then(|target| target.split_once('!')) else {
+        return false;
+    };
+    let is_name = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_');
+    is_name(synth) && is_name(mode) && words.next().is_some()
+}
+
+/// Parse `lead!pluck C4 1/8 -> velocity(90)`, sugar for
+/// `lead!pluck -> note(C4) -> duration(1/8) -> velocity(90)`
+pub fn parse_articulated_note(line: &str, line_number: usize) -> Result<Statement> {
+    let (head, chain) = match line.split_once("->") {
+        Some((head, chain)) => (head, Some(chain.trim())),
+        None => (line, None),
+    };
+    let words: Vec<&str> = head.split_whitespace().collect();
+    let [target, note, rest @ ..] = words.as_slice() else {
+        return Err(anyhow!("articulated note requires a note: {}", line));
+    };
+    let mut desugared = format!("{} -> note({})", target, note);
+    match rest {
+        [] => {}
+        [duration] => desugared.push_str(&format!(" -> duration({})", duration)),
+        _ => {
+            return Err(anyhow!(
+                "articulated note takes a note and an optional duration: {}",
+                line
+            ));
+        }
+    }
+    if let Some(chain) = chain {
+        desugared.push_str(&format!(" -> {}", chain));
+    }
+    crate::language::syntax::parser::driver::parse_arrow_call(&desugared, line_number)
+}
+
 /// Parse const statement
 pub fn parse_const(
     line: &str,
@@ -456,6 +541,8 @@ pub fn parse_const(
             }
         } else if remainder.starts_with('[') && remainder.ends_with(']') {
             Some(parse_array_value(&remainder)?)
+        } else if is_data_expression(&remainder) {
+            Some(parse_single_arg(&remainder)?)
         } else if remainder.starts_with('.') {
             let stmt = crate::language::syntax::parser::driver::trigger::parse_trigger_line(
                 &remainder,
@@ -487,6 +574,288 @@ pub fn parse_const(
     ))
 }
 
+/// Parse module parameter declaration (`param length = 4`); the default follows the same
+/// rules as `const`
+pub fn parse_param(
+    line: &str,
+    parts: impl Iterator<Item = impl AsRef<str>>,
+    line_number: usize,
+) -> Result<Statement> {
+    if line
+        .split_once('=')
+        .is_none_or(|(_, default)| default.trim().is_empty())
+    {
+        return Err(anyhow!("param declaration requires a default value"));
+    }
+    let mut stmt = parse_const(&line.replacen("param", "const", 1), parts, line_number)?;
+    if let StatementKind::Const { name, value } = stmt.kind {
+        stmt.kind = StatementKind::Param { name, value };
+    }
+    Ok(stmt)
+}
+
+/// Parse a macro control: `macro intensity -> [drums.gain, lead.cutoff x0.5, reverb.mix x0.3]`
+pub fn parse_macro(line: &str, line_number: usize) -> Result<Statement> {
+    use crate::engine::audio::macros::MacroTarget;
+
+    let rest = line.trim().trim_start_matches("macro").trim_start();
+    let (name, targets) = rest
+        .split_once("->")
+        .ok_or_else(|| anyhow!("expected 'macro name -> [owner.param, ...]'"))?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(anyhow!("invalid macro name '{}'", name));
+    }
+    let targets = targets.trim();
+    let targets = targets
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .unwrap_or(targets);
+    let targets = targets
+        .split(',')
+        .map(str::trim)
+        .filter(|target| !target.is_empty())
+        .map(|target| {
+            MacroTarget::parse(target)?;
+            Ok(target.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if targets.is_empty() {
+        return Err(anyhow!("macro '{}' has no targets", name));
+    }
+
+    Ok(Statement::new(
+        StatementKind::Macro {
+            name: name.to_string(),
+            targets,
+        },
+        Value::Null,
+        0,
+        line_number,
+        1,
+    ))
+}
+
+/// Most steps one accent map may hold (64 bars of sixteenths)
+const MAX_ACCENT_STEPS: usize = 1024;
+/// Finest `per 1/n` step of an accent map (the coarsest is `per 1/1`, a whole note)
+const MAX_ACCENT_DIVISION: f32 = 128.0;
+
+/// Parse accent map declaration, stored as a variable so it can be exported as a preset
+/// Supports:
+/// - accent map strong = [1.2, 0.8, 1.1, 0.8]            (one multiplier per beat)
+/// - accent map hats = [1.1, 0.7, 0.9, 0.7] per 1/16     (one multiplier per step)
+/// - accent map ones = { 1: 1.3, 3: 1.1 }                (1-based beat positions in a bar,
+///   unlisted positions keep 1.0)
+pub fn parse_accent_map(line: &str, line_number: usize) -> Result<Statement> {
+    use crate::engine::audio::accent::AccentMap;
+
+    let rest = line
+        .trim()
+        .strip_prefix("accent")
+        .unwrap_or(line)
+        .trim_start()
+        .strip_prefix("map")
+        .ok_or_else(|| anyhow!("expected 'accent map name = [...]'"))?;
+    let (name, body) = rest
+        .split_once('=')
+        .ok_or_else(|| anyhow!("accent map requires '= [...]' or '= {{ beat: gain }}'"))?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(anyhow!("invalid accent map name '{}'", name));
+    }
+    let body = body.trim();
+
+    let gain = |raw: &str| -> Result<f32> {
+        raw.trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| anyhow!("accent map gain '{}' is not a number", raw.trim()))
+    };
+
+    let map = if let Some(inner) = body.strip_prefix('[') {
+        let (list, tail) = inner
+            .split_once(']')
+            .ok_or_else(|| anyhow!("unclosed '[' in accent map"))?;
+        let steps = list
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(gain)
+            .collect::<Result<Vec<f32>>>()?;
+        let step_beats = match tail.trim() {
+            "" => 1.0,
+            tail => {
+                let division = tail
+                    .strip_prefix("per")
+                    .map(str::trim)
+                    .ok_or_else(|| anyhow!("unexpected '{}' after accent map", tail))?;
+                match division {
+                    "beat" => 1.0,
+                    "bar" => 4.0,
+                    _ => {
+                        let denominator = division
+                            .strip_prefix("1/")
+                            .and_then(|d| d.parse::<f32>().ok())
+                            .filter(|d| (1.0..=MAX_ACCENT_DIVISION).contains(d))
+                            .ok_or_else(|| {
+                                anyhow!(
+                                    "accent map step must be like 'per 1/8' (up to 1/{}), got '{}'",
+                                    MAX_ACCENT_DIVISION,
+                                    division
+                                )
+                            })?;
+                        4.0 / denominator
+                    }
+                }
+            }
+        };
+        AccentMap::new(steps, step_beats)
+    } else if body.starts_with('{') && body.ends_with('}') {
+        let mut positions = Vec::new();
+        for pair in body[1..body.len() - 1].split(',') {
+            if pair.trim().is_empty() {
+                continue;
+            }
+            let (beat, value) = pair
+                .split_once(':')
+                .ok_or_else(|| anyhow!("accent map entries are 'beat: gain', got '{}'", pair))?;
+            let beat = gain(beat)?;
+            if beat < 1.0 {
+                return Err(anyhow!("accent map beats start at 1, got {}", beat));
+            }
+            positions.push((beat - 1.0, gain(value)?));
+        }
+        // Finest grid that lands every listed position on a step
+        let step_beats = [1.0f32, 0.5, 0.25]
+            .into_iter()
+            .find(|step| {
+                positions
+                    .iter()
+                    .all(|(beat, _)| ((beat / step) - (beat / step).round()).abs() < 1e-3)
+            })
+            .unwrap_or(0.125);
+        let cycle = positions
+            .iter()
+            .map(|(beat, _)| beat + step_beats)
+            .fold(4.0f32, f32::max);
+        if cycle / step_beats > MAX_ACCENT_STEPS as f32 {
+            return Err(anyhow!(
+                "accent map '{}' spans more than {} steps",
+                name,
+                MAX_ACCENT_STEPS
+            ));
+        }
+        let mut steps = vec![1.0; (cycle / step_beats).ceil() as usize];
+        for (beat, value) in positions {
+            steps[(beat / step_beats).round() as usize] = value;
+        }
+        AccentMap::new(steps, step_beats)
+    } else {
+        return Err(anyhow!(
+            "accent map requires '= [...]' or '= {{ beat: gain }}'"
+        ));
+    };
+
+    if map.steps.is_empty() {
+        return Err(anyhow!("accent map '{}' has no steps", name));
+    }
+    if map.steps.len() > MAX_ACCENT_STEPS {
+        return Err(anyhow!(
+            "accent map '{}' spans more than {} steps",
+            name,
+            MAX_ACCENT_STEPS
+        ));
+    }
+
+    Ok(Statement::new(
+        StatementKind::Let {
+            name: name.to_string(),
+            value: Some(map.to_value()),
+        },
+        Value::Null,
+        0,
+        line_number,
+        1,
+    ))
+}
+
+/// Parse persistence annotation: @persist name[, name...]
+pub fn parse_persist(line: &str, line_number: usize) -> Result<Statement> {
+    let remainder = line
+        .trim_start_matches('@')
+        .strip_prefix("persist")
+        .ok_or_else(|| anyhow!("invalid persist annotation"))?;
+    let names: Vec<String> = remainder
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect();
+    if names.is_empty() {
+        return Err(anyhow!("@persist requires at least one variable name"));
+    }
+    Ok(Statement::new(
+        StatementKind::Persist { names },
+        Value::Null,
+        0,
+        line_number,
+        1,
+    ))
+}
+
+/// Parse metronome switch: metronome on [mix|stem|monitor] / metronome off
+pub fn parse_metronome(line: &str, line_number: usize) -> Result<Statement> {
+    let mut words = line.split_whitespace().skip(1);
+    let usage = "expected 'metronome on', 'metronome on stem|monitor' or 'metronome off'";
+    let enabled = match words.next() {
+        Some("on") => true,
+        Some("off") => false,
+        _ => return Err(anyhow!(usage)),
+    };
+    let value = match words.next() {
+        Some(mode) if enabled && ClickMode::parse(mode).is_some() => {
+            Value::String(mode.to_ascii_lowercase())
+        }
+        None => Value::Null,
+        Some(_) => return Err(anyhow!(usage)),
+    };
+    if words.next().is_some() {
+        return Err(anyhow!(usage));
+    }
+    Ok(Statement::new(
+        StatementKind::Metronome { enabled },
+        value,
+        0,
+        line_number,
+        1,
+    ))
+}
+
+/// Parse a cue point: `mark chorus` or `mark "drop 2"`
+pub fn parse_mark(line: &str, line_number: usize) -> Result<Statement> {
+    let rest = line.trim_start()["mark".len()..].trim();
+    let name = match rest
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+    {
+        Some(quoted) => quoted.to_string(),
+        None if !rest.is_empty() && !rest.contains(char::is_whitespace) => rest.to_string(),
+        None => {
+            return Err(anyhow!("expected 'mark <name>' or 'mark \"<name>\"'"));
+        }
+    };
+    if name.is_empty() {
+        return Err(anyhow!("mark name cannot be empty"));
+    }
+    Ok(Statement::new(
+        StatementKind::Mark { name },
+        Value::Null,
+        0,
+        line_number,
+        1,
+    ))
+}
+
 /// Parse return statement: return <expr>?
 pub fn parse_return(line: &str, line_number: usize) -> Result<Statement> {
     // strip 'return' keyword
@@ -517,3 +886,7 @@ pub fn parse_return(line: &str, line_number: usize) -> Result<Statement> {
         ))
     }
 }
+
+#[cfg(test)]
+#[path = "test_core.rs"]
+mod tests;
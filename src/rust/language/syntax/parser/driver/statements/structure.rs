@@ -1,5 +1,9 @@
-use super::super::helpers::{parse_array_value, parse_condition};
-use crate::language::syntax::ast::{Statement, StatementKind, Value};
+use super::super::duration::parse_duration_token;
+use super::super::helpers::{
+    is_call_expression, parse_array_value, parse_condition, parse_map_value, parse_single_arg,
+};
+use crate::engine::audio::pattern_chain::PatternChain;
+use crate::language::syntax::ast::{Statement, StatementKind, TimePosition, Value};
 /// Structure statement parsing: group, pattern, loop, for, if, on, emit, call, spawn
 use anyhow::{Result, anyhow};
 use std::collections::HashMap;
@@ -9,6 +13,7 @@ use std::iter::Iterator;
 /// Supports:
 /// - pattern name with target = "pattern"
 /// - pattern name with target { options } = "pattern"
+/// - pattern name = a then b then (c alt d)   (a chain of other patterns)
 pub fn parse_pattern(
     mut parts: impl Iterator<Item = impl AsRef<str>>,
     line_number: usize,
@@ -25,6 +30,23 @@ pub fn parse_pattern(
 
     // Check for "with" keyword
     if let Some(word) = parts.next() {
+        // `pattern verse = a then b then (c alt d)`
+        if word.as_ref() == "=" {
+            let chain = parts
+                .map(|part| part.as_ref().to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            PatternChain::parse(&chain)?;
+            let mut map = HashMap::new();
+            map.insert("chain".to_string(), Value::String(chain));
+            return Ok(Statement::new(
+                StatementKind::Pattern { name, target },
+                Value::Map(map),
+                0,
+                line_number,
+                1,
+            ));
+        }
         if word.as_ref() == "with" {
             // Next is the target
             target = parts.next().map(|v| v.as_ref().to_string());
@@ -35,7 +57,7 @@ pub fn parse_pattern(
 
             // Check if there's an options block: { key: value, key: value }
             if let Some(brace_start) = joined.find('{') {
-                if let Some(brace_end) = joined.rfind('}') {
+                if let Some(brace_end) = joined.rfind('}').filter(|end| *end > brace_start) {
                     // Extract options block
                     let options_str = &joined[brace_start + 1..brace_end];
 
@@ -103,29 +125,146 @@ pub fn parse_pattern(
 }
 
 /// Parse group statement
-pub fn parse_group(
-    mut parts: impl Iterator<Item = impl AsRef<str>>,
-    line_number: usize,
-) -> Result<Statement> {
-    let name = parts
+/// Supports:
+/// - group name:
+/// - group name -> compressor({ ratio: 4 }) -> lowpass(8000):
+/// - group name with { accent: strong, velocity: 0.9 }:
+/// - group name solo: / group name mute:
+///
+/// The effect chain is stored in order and applied to the summed group insert at mixdown.
+pub fn parse_group(line: &str, line_number: usize) -> Result<Statement> {
+    let rest = line
+        .trim()
+        .strip_prefix("group")
+        .unwrap_or(line)
+        .trim()
+        .trim_end_matches(':')
+        .trim();
+    let (name_part, effects_part) = match rest.split_once("->") {
+        Some((name, effects)) => (name.trim(), Some(effects.trim())),
+        None => (rest, None),
+    };
+
+    // `group name with { accent: strong }:` applies the block to every call of the group
+    let (name_part, preset) = match split_with_block(name_part) {
+        Some((name, block)) => (name, Some(parse_map_value(block)?)),
+        None => (name_part, None),
+    };
+
+    let mut words = name_part.split_whitespace();
+    let name = words
         .next()
         .ok_or_else(|| anyhow!("group requires a name"))?
-        .as_ref()
-        .trim_end_matches(':')
         .to_string();
+    let flags: Vec<&str> = words.filter(|w| matches!(*w, "solo" | "mute")).collect();
+
+    let value = if effects_part.is_none() && preset.is_none() && flags.is_empty() {
+        Value::Identifier(name.clone())
+    } else {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), Value::Identifier(name.clone()));
+        if let Some(effects) = effects_part {
+            map.insert(
+                "effects".to_string(),
+                crate::language::syntax::parser::driver::effects::parse_effect_list(effects)?,
+            );
+        }
+        if let Some(preset) = preset {
+            map.insert("with".to_string(), preset);
+        }
+        for flag in flags {
+            map.insert(flag.to_string(), Value::Boolean(true));
+        }
+        Value::Map(map)
+    };
 
     Ok(Statement::new(
         StatementKind::Group {
-            name: name.clone(),
+            name,
+            body: Vec::new(),
+        },
+        value,
+        0,
+        line_number,
+        1,
+    ))
+}
+
+/// Parse `at` block header: schedules its body at an absolute position
+/// Supports:
+/// - at 1:23.5:      (minutes:seconds, also h:mm:ss)
+/// - at 83.5s: / at 500ms:
+/// - at bar 17:      (1-based, fractional bars allowed)
+/// - at beat 5:      (1-based)
+pub fn parse_at(line: &str, line_number: usize) -> Result<Statement> {
+    let rest = line.trim().strip_prefix("at").unwrap_or(line).trim();
+    // Only the block colon is stripped so `1:23.5:` keeps its timestamp separator
+    let rest = rest.strip_suffix(':').unwrap_or(rest).trim();
+    if rest.is_empty() {
+        return Err(anyhow!(
+            "at requires a position (e.g. 'at 1:23.5:' or 'at bar 17:')"
+        ));
+    }
+
+    let parse_index = |unit: &str, raw: &str| -> Result<f32> {
+        let n: f32 = raw
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("invalid {} number in 'at': '{}'", unit, raw.trim()))?;
+        if n < 1.0 {
+            return Err(anyhow!("{} numbers start at 1, got {}", unit, n));
+        }
+        Ok(n)
+    };
+
+    let position = if let Some(bar) = rest.strip_prefix("bar") {
+        TimePosition::Bar(parse_index("bar", bar)?)
+    } else if let Some(beat) = rest.strip_prefix("beat") {
+        TimePosition::Beat(parse_index("beat", beat)?)
+    } else {
+        TimePosition::Seconds(parse_timestamp(rest)?)
+    };
+
+    Ok(Statement::new(
+        StatementKind::At {
+            position,
             body: Vec::new(),
         },
-        Value::Identifier(name),
+        Value::Null,
         0,
         line_number,
         1,
     ))
 }
 
+/// Parse an absolute timestamp (`83.5`, `83.5s`, `500ms`, `1:23.5`, `1:02:03`) into seconds
+fn parse_timestamp(raw: &str) -> Result<f32> {
+    let invalid = || anyhow!("invalid timestamp in 'at': '{}'", raw);
+    if let Some(ms) = raw.strip_suffix("ms") {
+        return ms
+            .trim()
+            .parse::<f32>()
+            .map(|ms| ms / 1000.0)
+            .map_err(|_| invalid());
+    }
+    let raw_secs = raw.strip_suffix('s').unwrap_or(raw);
+
+    let fields: Vec<&str> = raw_secs.split(':').collect();
+    if fields.len() > 3 {
+        return Err(invalid());
+    }
+    let mut seconds = 0.0;
+    for (idx, field) in fields.iter().enumerate() {
+        let value: f32 = field.trim().parse().map_err(|_| invalid())?;
+        // Minutes/seconds fields after the first must stay below 60
+        if value < 0.0 || (idx > 0 && value >= 60.0) {
+            return Err(invalid());
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Ok(seconds)
+}
+
 /// Parse automate statement: automate <target> [mode <note|global>]:
 pub fn parse_automate(
     mut parts: impl Iterator<Item = impl AsRef<str>>,
@@ -163,17 +302,148 @@ pub fn parse_automate(
     ))
 }
 
+/// Parse the body of an `automate` block into its timeline: one map per `param` with
+/// `name`, optional `curve` and `loop`, and `keyframes` (`at`, `value`, optional `mode`).
+///
+/// ```text
+/// param cutoff curve $curve.in loop 1 bar {
+///     0% = 200 hold
+///     1/4 = 800 step
+///     50% = 400 $curve.out
+///     100% = 200
+/// }
+/// ```
+///
+/// Positions are percentages (bare numbers included), stored as fractions, or
+/// durations from the start of the block (or of the loop region).
+pub fn parse_automate_body(raw: &str) -> Value {
+    use regex::Regex;
+
+    let re_block = Regex::new(r"param\s+([A-Za-z_][A-Za-z0-9_]*)([^{]*)\{([^}]*)\}").unwrap();
+    // A key is a percentage or a duration literal (`1/8`, `2 beats`, `250ms`, `1/8 + 10ms`)
+    let term = r"[0-9]+(?:\.[0-9]+)?(?:\s*/\s*[0-9]+(?:\.[0-9]+)?)?\s*(?:%|ms|beats?|bars?|measures?|ticks?|s\b)?";
+    // The segment mode follows the value on the same line: `hold`, `step`, `linear` or a curve
+    let re_point = Regex::new(&format!(
+        r"({term}(?:\s*\+\s*{term})*)\s*=\s*([\-0-9\.eE]+)(?:[ \t]+(?:curve[ \t]+)?([A-Za-z_$][A-Za-z0-9_.$]*(?:\([^)]*\))?))?"
+    ))
+    .unwrap();
+
+    let mut params = Vec::new();
+    for cap in re_block.captures_iter(raw) {
+        let mut param = HashMap::new();
+        param.insert("name".to_string(), Value::String(cap[1].to_string()));
+
+        // Header options: `curve <name>` and `loop <position>`, in any order
+        let header: Vec<&str> = cap[2].split_whitespace().collect();
+        let mut idx = 0;
+        while idx < header.len() {
+            match header[idx] {
+                "curve" if idx + 1 < header.len() => {
+                    param.insert(
+                        "curve".to_string(),
+                        Value::String(header[idx + 1].to_string()),
+                    );
+                    idx += 2;
+                }
+                "loop" => {
+                    let end = header[idx + 1..]
+                        .iter()
+                        .position(|word| *word == "curve")
+                        .map_or(header.len(), |pos| idx + 1 + pos);
+                    if let Some(length) = automate_position(&header[idx + 1..end].join(" ")) {
+                        param.insert("loop".to_string(), length);
+                    }
+                    idx = end;
+                }
+                _ => idx += 1,
+            }
+        }
+
+        let mut keyframes = Vec::new();
+        for pcap in re_point.captures_iter(&cap[3]) {
+            let (Some(at), Ok(value)) = (automate_position(&pcap[1]), pcap[2].parse::<f32>())
+            else {
+                continue;
+            };
+            let mut keyframe = HashMap::new();
+            keyframe.insert("at".to_string(), at);
+            keyframe.insert("value".to_string(), Value::Number(value));
+            if let Some(mode) = pcap.get(3) {
+                keyframe.insert("mode".to_string(), Value::String(mode.as_str().to_string()));
+            }
+            keyframes.push(Value::Map(keyframe));
+        }
+        param.insert("keyframes".to_string(), Value::Array(keyframes));
+        params.push(Value::Map(param));
+    }
+    Value::Array(params)
+}
+
+/// `25%` or `25` -> `Number(0.25)`; `1/8 + 10ms` -> `Duration`
+fn automate_position(key: &str) -> Option<Value> {
+    let key = key.trim();
+    // Bare numbers keep their historical meaning of percentages
+    if let Ok(percent) = key.trim_end_matches('%').trim().parse::<f32>() {
+        return Some(Value::Number((percent / 100.0).clamp(0.0, 1.0)));
+    }
+    let duration = parse_duration_token(key).ok()?;
+    duration.to_span().map(|_| Value::Duration(duration))
+}
+
+/// Inline formula header: `automate <target>.<param>: <expression>`
+fn split_automate_formula(line: &str) -> Option<(&str, &str)> {
+    let rest = line.trim().strip_prefix("automate")?;
+    let (head, formula) = rest.split_once(':')?;
+    let formula = formula.trim();
+    (!formula.is_empty() && !formula.starts_with('#')).then_some((head.trim(), formula))
+}
+
+pub fn is_automate_formula(line: &str) -> bool {
+    split_automate_formula(line).is_some()
+}
+
+/// Parse `automate lead.cutoff: 0.3 + 0.2 * sin($time * 2)`; the expression is compiled
+/// when the statement is collected
+pub fn parse_automate_formula(line: &str, line_number: usize) -> Result<Statement> {
+    let (head, formula) = split_automate_formula(line)
+        .ok_or_else(|| anyhow!("automate formula requires '<target>.<param>: <expression>'"))?;
+    let (target, param) = head
+        .rsplit_once('.')
+        .filter(|(target, param)| !target.is_empty() && !param.is_empty())
+        .ok_or_else(|| {
+            anyhow!(
+                "automate formula target must be '<target>.<param>', got '{}'",
+                head
+            )
+        })?;
+
+    let mut map = HashMap::new();
+    map.insert("param".to_string(), Value::String(param.to_string()));
+    map.insert("formula".to_string(), Value::String(formula.to_string()));
+
+    Ok(Statement::new(
+        StatementKind::Automate {
+            target: target.to_string(),
+        },
+        Value::Map(map),
+        0,
+        line_number,
+        1,
+    ))
+}
+
 /// Parse loop statement
 pub fn parse_loop(
-    mut parts: impl Iterator<Item = impl AsRef<str>>,
+    parts: impl Iterator<Item = impl AsRef<str>>,
     line_number: usize,
 ) -> Result<Statement> {
-    // Parse: loop <count>:  OR plain `loop:` (no count)
+    // Parse: loop <count>:  OR plain `loop:` (no count), optionally `... as <label>:`
     // If no count is provided we store Value::Null to indicate an unbounded loop
-    let count_opt = parts.next();
+    let (parts, label) = split_loop_label(parts)?;
+    let count_opt = parts.first();
 
     let count = if let Some(count_str_ref) = count_opt {
-        let count_str = count_str_ref.as_ref().trim_end_matches(':');
+        let count_str = count_str_ref.trim_end_matches(':');
         // Support forms:
         // - loop pass:
         // - loop pass():
@@ -208,7 +478,7 @@ pub fn parse_loop(
             count,
             body: Vec::new(), // Will be filled during indentation parsing
         },
-        Value::Null,
+        label_value(label),
         0,
         line_number,
         1,
@@ -220,8 +490,8 @@ pub fn parse_for(
     parts: impl Iterator<Item = impl AsRef<str>>,
     line_number: usize,
 ) -> Result<Statement> {
-    // Parse: for <var> in <iterable>:
-    let parts_vec: Vec<String> = parts.map(|s| s.as_ref().to_string()).collect();
+    // Parse: for <var> in <iterable>:  optionally `... as <label>:`
+    let (parts_vec, label) = split_loop_label(parts)?;
 
     if parts_vec.is_empty() {
         return Err(anyhow!("for loop requires a variable name"));
@@ -241,10 +511,19 @@ pub fn parse_for(
     let iterable = if iterable_str.starts_with('[') && iterable_str.ends_with(']') {
         // Parse as array: [1, 2, 3] or range: [1..10]
         parse_array_value(iterable_str)?
+    } else if let Some((start, end)) = iterable_str.split_once("..") {
+        // Bare range: 0..16 (end exclusive); bounds may be variables
+        Value::Range {
+            start: Box::new(range_bound(start)?),
+            end: Box::new(range_bound(end)?),
+        }
     } else {
         // Parse as identifier or number
         if let Ok(num) = iterable_str.parse::<f32>() {
             Value::Number(num)
+        } else if is_call_expression(iterable_str) {
+            // Call expression: `settings.keys()`, `transpose(melody, 12)`
+            parse_single_arg(iterable_str)?
         } else {
             Value::Identifier(iterable_str.to_string())
         }
@@ -256,13 +535,60 @@ pub fn parse_for(
             iterable,
             body: Vec::new(), // Will be filled during indentation parsing
         },
-        Value::Null,
+        label_value(label),
         0,
         line_number,
         1,
     ))
 }
 
+/// Split a trailing `as <label>` off loop header tokens. The label is kept in the
+/// statement value so `break <label>` / `continue <label>` can target that loop.
+fn split_loop_label(
+    parts: impl Iterator<Item = impl AsRef<str>>,
+) -> Result<(Vec<String>, Option<String>)> {
+    let mut parts: Vec<String> = parts.map(|s| s.as_ref().to_string()).collect();
+    if let Some(last) = parts.last_mut() {
+        let trimmed = last.trim_end_matches(':').to_string();
+        *last = trimmed;
+        if last.is_empty() {
+            parts.pop();
+        }
+    }
+    if parts.len() >= 2 && parts[parts.len() - 2] == "as" {
+        let label = parts.pop().unwrap_or_default();
+        parts.pop();
+        if !is_loop_label(&label) {
+            return Err(anyhow!("invalid loop label '{}'", label));
+        }
+        return Ok((parts, Some(label)));
+    }
+    Ok((parts, None))
+}
+
+fn is_loop_label(label: &str) -> bool {
+    label
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn label_value(label: Option<String>) -> Value {
+    label.map(Value::String).unwrap_or(Value::Null)
+}
+
+fn range_bound(bound: &str) -> Result<Value> {
+    let bound = bound.trim();
+    if bound.is_empty() {
+        return Err(anyhow!("range requires both a start and an end"));
+    }
+    Ok(match bound.parse::<f32>() {
+        Ok(num) => Value::Number(num),
+        Err(_) => Value::Identifier(bound.to_string()),
+    })
+}
+
 /// Parse function statement: function name(arg1, arg2, ...):
 pub fn parse_function(line: &str, line_number: usize) -> Result<Statement> {
     // Expect form: function <name>(arg1, arg2, ...):
@@ -275,7 +601,7 @@ pub fn parse_function(line: &str, line_number: usize) -> Result<Statement> {
     // Find name and args parentheses
     if let Some(paren_idx) = after_kw.find('(') {
         let name = after_kw[..paren_idx].trim().to_string();
-        if let Some(close_idx) = after_kw.rfind(')') {
+        if let Some(close_idx) = after_kw.rfind(')').filter(|idx| *idx > paren_idx) {
             let args_str = &after_kw[paren_idx + 1..close_idx];
             // Parse parameter names: split by ',' and trim
             let params: Vec<String> = if args_str.trim().is_empty() {
@@ -375,6 +701,38 @@ pub fn parse_else(line: &str, line_number: usize) -> Result<Statement> {
 /// - call groupName
 /// - call target = "pattern" (inline pattern assignment)
 pub fn parse_call(
+    line: &str,
+    parts: impl Iterator<Item = impl AsRef<str>>,
+    line_number: usize,
+) -> Result<Statement> {
+    // `call name with { velocity: 0.8, swing: 60% }` applies the block to triggers inside
+    let Some((target, block)) = split_with_block(line) else {
+        return parse_call_target(line, parts, line_number);
+    };
+    let preset = parse_map_value(block)?;
+    let mut stmt = parse_call_target(target, target.split_whitespace().skip(1), line_number)?;
+    match &mut stmt.value {
+        Value::Map(map) => {
+            map.insert("with".to_string(), preset);
+        }
+        value => {
+            *value = Value::Map(HashMap::from([("with".to_string(), preset)]));
+        }
+    }
+    Ok(stmt)
+}
+
+/// Split `call name(args) with { ... }` into the call and its `{ ... }` parameter block
+fn split_with_block(line: &str) -> Option<(&str, &str)> {
+    let idx = line.rfind(" with ")?;
+    let block = line[idx + " with ".len()..].trim();
+    if !(block.starts_with('{') && block.ends_with('}')) {
+        return None;
+    }
+    Some((line[..idx].trim_end(), block))
+}
+
+fn parse_call_target(
     line: &str,
     mut parts: impl Iterator<Item = impl AsRef<str>>,
     line_number: usize,
@@ -435,7 +793,7 @@ pub fn parse_call(
     // Regular call statement (call groupName or call patternName)
     // Support call with arguments: call name(arg1, arg2)
     if let Some(paren_idx) = line.find('(') {
-        if let Some(close_idx) = line.rfind(')') {
+        if let Some(close_idx) = line.rfind(')').filter(|idx| *idx > paren_idx) {
             let args_str = &line[paren_idx + 1..close_idx];
             let args = if args_str.trim().is_empty() {
                 Vec::new()
@@ -471,11 +829,113 @@ pub fn parse_call(
 
 /// Parse break statement
 pub fn parse_break(
-    _parts: impl Iterator<Item = impl AsRef<str>>,
+    parts: impl Iterator<Item = impl AsRef<str>>,
     line_number: usize,
 ) -> Result<Statement> {
     Ok(Statement::new(
         StatementKind::Break,
+        parse_jump_label(parts, "break")?,
+        0,
+        line_number,
+        1,
+    ))
+}
+
+/// Parse continue statement: `continue` or `continue <label>`
+pub fn parse_continue(
+    parts: impl Iterator<Item = impl AsRef<str>>,
+    line_number: usize,
+) -> Result<Statement> {
+    Ok(Statement::new(
+        StatementKind::Continue,
+        parse_jump_label(parts, "continue")?,
+        0,
+        line_number,
+        1,
+    ))
+}
+
+fn parse_jump_label(
+    mut parts: impl Iterator<Item = impl AsRef<str>>,
+    keyword: &str,
+) -> Result<Value> {
+    let Some(label) = parts.next() else {
+        return Ok(Value::Null);
+    };
+    let label = label.as_ref();
+    if !is_loop_label(label) || parts.next().is_some() {
+        return Err(anyhow!("'{}' accepts only an optional loop label", keyword));
+    }
+    Ok(Value::String(label.to_string()))
+}
+
+/// Parse scene declaration: `scene verse = [drums, bass]`
+pub fn parse_scene(line: &str, line_number: usize) -> Result<Statement> {
+    let rest = line.trim().strip_prefix("scene").unwrap_or(line).trim();
+    let (name, members) = rest
+        .split_once('=')
+        .ok_or_else(|| anyhow!("scene requires members: 'scene verse = [drums, bass]'"))?;
+    let name = name.trim();
+    if !is_loop_label(name) {
+        return Err(anyhow!("invalid scene name '{}'", name));
+    }
+
+    let list = members
+        .trim()
+        .strip_prefix('[')
+        .and_then(|list| list.strip_suffix(']'))
+        .ok_or_else(|| anyhow!("scene '{}' members must be a list like [drums, bass]", name))?;
+    let members = list
+        .split(',')
+        .map(str::trim)
+        .filter(|member| !member.is_empty())
+        .map(|member| {
+            if is_loop_label(member) {
+                Ok(member.to_string())
+            } else {
+                Err(anyhow!("invalid member '{}' in scene '{}'", member, name))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if members.is_empty() {
+        return Err(anyhow!("scene '{}' has no members", name));
+    }
+
+    Ok(Statement::new(
+        StatementKind::Scene {
+            name: name.to_string(),
+            members,
+        },
+        Value::Null,
+        0,
+        line_number,
+        1,
+    ))
+}
+
+/// Parse scene switch: `switch chorus` or `switch chorus over 4 bars`
+pub fn parse_switch(line: &str, line_number: usize) -> Result<Statement> {
+    let rest = line.trim().strip_prefix("switch").unwrap_or(line).trim();
+    let (scene, fade) = match rest.split_once(" over ") {
+        Some((scene, fade)) => (scene.trim(), Some(fade.trim())),
+        None => (rest, None),
+    };
+    if !is_loop_label(scene) {
+        return Err(anyhow!(
+            "switch requires a scene name: 'switch chorus over 4 bars'"
+        ));
+    }
+    let fade = match fade {
+        Some("") => return Err(anyhow!("switch '{}' over requires a duration", scene)),
+        Some(fade) => Some(parse_duration_token(fade)?),
+        None => None,
+    };
+
+    Ok(Statement::new(
+        StatementKind::Switch {
+            scene: scene.to_string(),
+            fade,
+        },
         Value::Null,
         0,
         line_number,
@@ -483,6 +943,69 @@ pub fn parse_break(
     ))
 }
 
+/// Parse snapshot statements: `snapshot save calm` and
+/// `snapshot morph calm -> wild over 8 beats curve smooth` (length and curve optional)
+pub fn parse_snapshot(line: &str, line_number: usize) -> Result<Statement> {
+    let rest = line.trim().strip_prefix("snapshot").unwrap_or(line).trim();
+    let (action, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let rest = rest.trim();
+    let kind = match action {
+        "save" => {
+            if !is_loop_label(rest) {
+                return Err(anyhow!(
+                    "snapshot save requires a name: 'snapshot save calm'"
+                ));
+            }
+            StatementKind::SnapshotSave {
+                name: rest.to_string(),
+            }
+        }
+        "morph" => {
+            let (rest, curve) = match rest.split_once(" curve ") {
+                Some((rest, curve)) => (rest.trim(), Some(curve.trim())),
+                None => (rest, None),
+            };
+            let (names, length) = match rest.split_once(" over ") {
+                Some((names, length)) => (names.trim(), Some(length.trim())),
+                None => (rest, None),
+            };
+            let (from, to) = names
+                .split_once("->")
+                .map(|(from, to)| (from.trim(), to.trim()))
+                .filter(|(from, to)| is_loop_label(from) && is_loop_label(to))
+                .ok_or_else(|| {
+                    anyhow!("snapshot morph requires two snapshots: 'snapshot morph calm -> wild over 8 beats'")
+                })?;
+            let length = match length {
+                Some("") => return Err(anyhow!("snapshot morph over requires a duration")),
+                Some(length) => Some(parse_duration_token(length)?),
+                None => None,
+            };
+            let curve = match curve {
+                Some(curve) => {
+                    crate::engine::audio::snapshot::parse_morph_curve(curve)?;
+                    Some(curve.to_string())
+                }
+                None => None,
+            };
+            StatementKind::SnapshotMorph {
+                from: from.to_string(),
+                to: to.to_string(),
+                length,
+                curve,
+            }
+        }
+        other => {
+            return Err(anyhow!(
+                "unknown snapshot action '{}' (expected save or morph)",
+                other
+            ));
+        }
+    };
+
+    Ok(Statement::new(kind, Value::Null, 0, line_number, 1))
+}
+
 /// Parse spawn statement
 pub fn parse_spawn(
     mut parts: impl Iterator<Item = impl AsRef<str>>,
@@ -0,0 +1,31 @@
+use super::*;
+
+fn persisted(line: &str) -> Vec<String> {
+    match parse_persist(line, 1).unwrap().kind {
+        StatementKind::Persist { names } => names,
+        other => panic!("expected a persist statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_persist_reads_every_name() {
+    assert_eq!(persisted("@persist counter"), vec!["counter"]);
+    assert_eq!(
+        persisted("@persist counter, loops"),
+        vec!["counter", "loops"]
+    );
+    assert_eq!(
+        persisted("@persist counter loops"),
+        vec!["counter", "loops"]
+    );
+}
+
+#[test]
+fn test_parse_persist_needs_a_name() {
+    let err = parse_persist("@persist", 3).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "@persist requires at least one variable name"
+    );
+    assert!(parse_persist("@persist , ", 3).is_err());
+}
@@ -49,3 +49,35 @@ fn test_parse_effect_chain() {
         panic!("Expected map of effects");
     }
 }
+
+#[test]
+fn test_parse_effect_list_keeps_order() {
+    let result = parse_effect_list("compressor({ratio: 4}) -> lowpass(8000) -> mono").unwrap();
+    let Value::Array(effects) = result else {
+        panic!("Expected ordered list of effects");
+    };
+    let names: Vec<&str> = effects
+        .iter()
+        .filter_map(|effect| match effect {
+            Value::Map(map) => map.keys().next().map(String::as_str),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["compressor", "lowpass", "mono"]);
+}
+
+#[test]
+fn test_unit_helpers_stay_calls_in_effect_params() {
+    let (_, params) = parse_single_effect("delay({ time: ms(1/8), feedback: 0.3 })").unwrap();
+    let Value::Map(map) = params else {
+        panic!("expected a map");
+    };
+    assert!(matches!(map.get("time"), Some(Value::Call { name, .. }) if name == "ms"));
+
+    let (_, single) = parse_single_effect("delay(ms(1/4))").unwrap();
+    assert!(matches!(single, Value::Call { name, .. } if name == "ms"));
+
+    // Other call-like params keep their string form
+    let (_, other) = parse_single_effect("lfo({ shape: sine(2) })").unwrap();
+    assert!(matches!(other, Value::Map(m) if matches!(m.get("shape"), Some(Value::String(_)))));
+}
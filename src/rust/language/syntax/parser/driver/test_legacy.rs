@@ -0,0 +1,83 @@
+use super::*;
+
+fn read(source: &str) -> LegacyParse {
+    parse_legacy(source, PathBuf::from("song.deva")).unwrap()
+}
+
+#[test]
+fn test_legacy_spellings_read_as_current_statements() {
+    let parsed = read("@load \"./kick.wav\" as kick\nvar speed = 2\ntrigger kit.kick 1/4\n");
+    assert!(matches!(
+        parsed.statements[0].kind,
+        StatementKind::Load { .. }
+    ));
+    assert!(matches!(
+        &parsed.statements[1].kind,
+        StatementKind::Var { name, value: Some(Value::Number(n)) } if name == "speed" && *n == 2.0
+    ));
+    assert!(matches!(
+        &parsed.statements[2].kind,
+        StatementKind::Trigger { entity, .. } if entity == "kit.kick"
+    ));
+    let migrations: Vec<_> = parsed
+        .spellings
+        .iter()
+        .map(|s| s.migrations.clone())
+        .collect();
+    assert_eq!(
+        migrations,
+        vec![
+            vec!["deprecated_syntax"],
+            vec!["var_keyword"],
+            vec!["trigger_keyword"]
+        ]
+    );
+}
+
+#[test]
+fn test_effect_maps_are_read_in_full() {
+    let parsed = read(
+        ".kit.kick 1/4 { reverb: { size: 0.5, mix: 0.2 } }\nlet lead = synth saw { effects: { delay: 300, reverb: { size: 0.5, mix: 0.2 } } }",
+    );
+    let StatementKind::Trigger {
+        effects: Some(Value::Map(effects)),
+        ..
+    } = &parsed.statements[0].kind
+    else {
+        panic!("expected a trigger with effects");
+    };
+    let Value::Map(reverb) = &effects["reverb"] else {
+        panic!("expected reverb parameters");
+    };
+    assert_eq!(reverb["mix"], Value::Number(0.2));
+
+    let StatementKind::Let {
+        value: Some(Value::Map(synth)),
+        ..
+    } = &parsed.statements[1].kind
+    else {
+        panic!("expected a synth");
+    };
+    let Some(Value::Map(effects)) = synth.get("effects") else {
+        panic!("expected synth effects");
+    };
+    assert_eq!(effects["delay"], Value::Number(300.0));
+    assert!(matches!(&effects["reverb"], Value::Map(reverb) if reverb.len() == 2));
+}
+
+#[test]
+fn test_unreadable_legacy_lines_are_kept_with_their_error() {
+    let parsed = read("trigger .kit.kick 1/4 bogus\n.kit.snare 1/2\n");
+    assert!(matches!(parsed.statements[0].kind, StatementKind::Unknown));
+    assert_eq!(parsed.statements.len(), 2);
+    assert!(
+        parsed.spellings[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("bogus")
+    );
+
+    // Current sources record nothing, and `@persist` is not a legacy directive
+    assert!(read(".kit.kick 1/4\n@persist score\n").spellings.is_empty());
+}
@@ -0,0 +1,108 @@
+use super::*;
+
+fn parse_src(source: &str) -> Result<Vec<Statement>> {
+    SimpleParser::parse(source, PathBuf::from("spans.deva"))
+}
+
+#[test]
+fn test_columns_count_indentation() {
+    let statements = parse_src("bpm 120\nloop 2:\n    print \"héllo ✓\"\n").unwrap();
+
+    assert_eq!(statements[0].column, 1);
+    assert_eq!(statements[0].span.end_column, 8);
+    let StatementKind::Loop { body, .. } = &statements[1].kind else {
+        panic!("expected a loop, got {:?}", statements[1].kind);
+    };
+    let print = &body[0];
+    assert_eq!(print.line, 3);
+    assert_eq!(print.column, 5);
+    // Multi-byte text: bytes and characters diverge past the 'é'
+    assert_eq!((print.span.start_byte, print.span.end_byte), (4, 22));
+    assert_eq!((print.span.start_column, print.span.end_column), (5, 20));
+}
+
+#[test]
+fn test_unknown_statement_spans_its_keyword() {
+    let statements = parse_src("loop 2:\n  prïnt \"x\"\n").unwrap();
+    let StatementKind::Loop { body, .. } = &statements[0].kind else {
+        panic!("expected a loop, got {:?}", statements[0].kind);
+    };
+    let unknown = &body[0];
+
+    assert!(matches!(unknown.kind, StatementKind::Unknown));
+    assert_eq!(unknown.line, 2);
+    assert_eq!((unknown.span.start_byte, unknown.span.end_byte), (2, 8));
+    assert_eq!((unknown.span.start_column, unknown.span.end_column), (3, 8));
+    assert_eq!(unknown.column, 3);
+    // The suggestion still works with a multi-byte keyword
+    let Value::String(message) = &unknown.value else {
+        panic!("expected an error message");
+    };
+    assert!(message.ends_with("|||print"), "{message}");
+}
+
+#[test]
+fn test_parse_errors_carry_location() {
+    let err = parse_src("bpm 120\n\n   trigger kick\n").unwrap_err();
+    let located = err.downcast_ref::<LocatedParseError>().unwrap();
+
+    assert_eq!(located.line, 3);
+    assert_eq!(located.span.start_column, 4);
+    assert_eq!(located.span.end_column, 16);
+    assert!(located.to_string().contains("deprecated"));
+}
+
+#[test]
+fn test_automate_body_is_parsed_into_a_timeline() {
+    let statements =
+        parse_src("automate lead mode global:\n    param cutoff loop 1 bar {\n        0% = 200 hold\n        1/4 = 800 $curve.out\n    }\n")
+            .unwrap();
+    let Value::Map(map) = &statements[0].value else {
+        panic!("expected a map, got {:?}", statements[0].value);
+    };
+    assert!(!map.contains_key("body"));
+    assert_eq!(map["mode"], Value::String("global".to_string()));
+    let Value::Array(params) = &map["params"] else {
+        panic!("expected params, got {:?}", map["params"]);
+    };
+    let Value::Map(param) = &params[0] else {
+        panic!("expected a param map");
+    };
+    assert_eq!(param["name"], Value::String("cutoff".to_string()));
+    assert!(matches!(param["loop"], Value::Duration(_)));
+    let Value::Array(keyframes) = &param["keyframes"] else {
+        panic!("expected keyframes");
+    };
+    let Value::Map(first) = &keyframes[0] else {
+        panic!("expected a keyframe map");
+    };
+    assert_eq!(first["at"], Value::Number(0.0));
+    assert_eq!(first["value"], Value::Number(200.0));
+    assert_eq!(first["mode"], Value::String("hold".to_string()));
+    let Value::Map(second) = &keyframes[1] else {
+        panic!("expected a keyframe map");
+    };
+    assert!(matches!(second["at"], Value::Duration(_)));
+    assert_eq!(second["mode"], Value::String("$curve.out".to_string()));
+}
+
+#[test]
+fn test_keywords_can_still_name_variables() {
+    let statements = parse_src(
+        "let strip = synth saw\nstrip -> note(C4)\nlet snapshot = synth sine\nsnapshot -> note(E4)\nstrip drums gain -3db pan -0.2\n",
+    )
+    .unwrap();
+
+    assert!(matches!(
+        statements[1].kind,
+        StatementKind::ArrowCall { .. }
+    ));
+    assert!(matches!(
+        statements[3].kind,
+        StatementKind::ArrowCall { .. }
+    ));
+    assert!(!matches!(
+        statements[4].kind,
+        StatementKind::ArrowCall { .. } | StatementKind::Unknown
+    ));
+}
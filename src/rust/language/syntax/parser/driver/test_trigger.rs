@@ -0,0 +1,42 @@
+use super::*;
+
+#[test]
+fn test_trigger_duration_accepts_separate_unit_word() -> Result<()> {
+    let trigger = parse_trigger_line(".kit.crash 2 bars chance 50%", 1)?;
+    let StatementKind::Trigger { duration, .. } = &trigger.kind else {
+        panic!("expected a trigger");
+    };
+    assert_eq!(*duration, DurationValue::Beats(8.0));
+    assert!(matches!(&trigger.value, Value::Map(m) if m.contains_key("chance")));
+    Ok(())
+}
+
+#[test]
+fn test_duration_units_are_unit_words_only() {
+    for unit in [
+        "beat", "Beats", "bar", "measures", "ticks", "s", "sec", "seconds",
+    ] {
+        assert!(is_duration_unit(unit), "{unit}");
+    }
+    for word in ["", "chance", "every", "x4"] {
+        assert!(!is_duration_unit(word), "{word}");
+    }
+}
+
+#[test]
+fn test_roll_hit_count_is_capped() -> Result<()> {
+    let trigger = parse_trigger_line(&format!(".kit.hat roll 1/32 x{}", MAX_ROLL_HITS), 1)?;
+    assert!(matches!(&trigger.value, Value::Map(m) if m.contains_key("roll")));
+    let error = parse_trigger_line(".kit.hat roll 1/32 x1000000000", 1).unwrap_err();
+    assert!(error.to_string().contains("at most"), "{error}");
+    assert!(parse_trigger_line(".kit.hat roll 1/32 x0", 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_words_after_the_duration_are_rejected() {
+    let error = parse_trigger_line(".kit.crash 500 soft", 1).unwrap_err();
+    assert!(error.to_string().contains("unexpected token 'soft'"), "{error}");
+    // Modifiers may still follow the duration
+    assert!(parse_trigger_line(".kit.crash 500 every 2", 1).is_ok());
+}
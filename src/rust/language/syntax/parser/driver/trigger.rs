@@ -1,6 +1,9 @@
 use crate::language::syntax::ast::{DurationValue, Statement, StatementKind, Value};
 use crate::language::syntax::parser::driver::effects::parse_chained_effects;
 use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::SplitWhitespace;
 
 pub fn parse_trigger_line(line: &str, line_number: usize) -> Result<Statement> {
     // Split by arrow operator to separate trigger definition from effects chain
@@ -11,22 +14,89 @@ pub fn parse_trigger_line(line: &str, line_number: usize) -> Result<Statement> {
     let mut base_parts = trigger_def
         .trim_start_matches('.')
         .trim()
-        .split_whitespace();
+        .split_whitespace()
+        .peekable();
     let entity = base_parts
         .next()
         .ok_or_else(|| anyhow!("trigger requires a target"))?
         .to_string();
 
     let mut duration = DurationValue::Auto;
-    if let Some(token) = base_parts.next() {
-        if token.eq_ignore_ascii_case("for") {
-            if let Some(value) = base_parts.next() {
-                duration =
-                    crate::language::syntax::parser::driver::duration::parse_duration_token(value)?;
+    let mut modifiers: HashMap<String, Value> = HashMap::new();
+    let mut duration_seen = false;
+    while let Some(token) = base_parts.next() {
+        if token.eq_ignore_ascii_case("chance") {
+            let raw = base_parts
+                .next()
+                .ok_or_else(|| anyhow!("'chance' requires a probability (e.g. 30% or 0.3)"))?;
+            modifiers.insert("chance".to_string(), Value::Number(parse_chance(raw)?));
+        } else if token.eq_ignore_ascii_case("every") {
+            let raw = base_parts
+                .next()
+                .ok_or_else(|| anyhow!("'every' requires a loop pass count"))?;
+            let n = raw
+                .parse::<f32>()
+                .ok()
+                .filter(|n| *n >= 1.0 && n.fract() == 0.0)
+                .ok_or_else(|| anyhow!("'every' expects a positive integer, found '{}'", raw))?;
+            modifiers.insert("every".to_string(), Value::Number(n));
+        } else if token.eq_ignore_ascii_case("roll") {
+            modifiers.insert("roll".to_string(), parse_roll(&mut base_parts)?);
+        } else if let Some((key, inline)) = token.split_once(':')
+            && REGION_OPTIONS.contains(&key)
+        {
+            // `start: 0.25 end: 75% loop: true sync: 2 bars`
+            let raw = match inline {
+                "" => base_parts
+                    .next()
+                    .ok_or_else(|| anyhow!("'{}:' requires a value", key))?
+                    .to_string(),
+                value => value.to_string(),
+            };
+            let value = match key {
+                "loop" => Value::Boolean(match raw.as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(anyhow!("'loop:' expects true or false, found '{}'", raw)),
+                }),
+                "sync" if raw == "auto" => Value::Identifier(raw),
+                "sync" => {
+                    let raw = match base_parts.next_if(|unit| is_duration_unit(unit)) {
+                        Some(unit) => format!("{} {}", raw, unit),
+                        None => raw,
+                    };
+                    Value::Duration(
+                        crate::language::syntax::parser::driver::duration::parse_duration_token(
+                            &raw,
+                        )?,
+                    )
+                }
+                _ => Value::Number(parse_region_bound(key, &raw)?),
+            };
+            modifiers.insert(key.to_string(), value);
+        } else if is_note_name(token) && !modifiers.contains_key("note") {
+            // `.bank.pluck C4` plays a melodic sample at the given pitch
+            let midi = crate::engine::functions::note::parse_note_to_midi(token)?;
+            modifiers.insert("note".to_string(), Value::Number(midi as f32));
+        } else if !duration_seen {
+            duration_seen = true;
+            let value = if token.eq_ignore_ascii_case("for") {
+                base_parts.next()
+            } else {
+                Some(token)
+            };
+            if let Some(value) = value {
+                // `1 beat`, `2 bars`: the unit is a separate word
+                let value = match base_parts.next_if(|unit| is_duration_unit(unit)) {
+                    Some(unit) => format!("{} {}", value, unit),
+                    None => value.to_string(),
+                };
+                duration = crate::language::syntax::parser::driver::duration::parse_duration_token(
+                    &value,
+                )?;
             }
         } else {
-            duration =
-                crate::language::syntax::parser::driver::duration::parse_duration_token(token)?;
+            return Err(anyhow!("unexpected token '{}' in trigger", token));
         }
     }
 
@@ -43,9 +113,142 @@ pub fn parse_trigger_line(line: &str, line_number: usize) -> Result<Statement> {
             duration,
             effects,
         },
-        Value::Null,
+        if modifiers.is_empty() {
+            Value::Null
+        } else {
+            Value::Map(modifiers)
+        },
         0,
         line_number,
         1,
     ))
 }
+
+/// Unit words accepted after a trigger duration (`.kit.kick 1 beat`, `2 bars`, `1 s`),
+/// singular or plural as `parse_duration_token` reads them
+fn is_duration_unit(word: &str) -> bool {
+    let word = word.to_ascii_lowercase();
+    let singular = match word.strip_suffix('s') {
+        Some(stem) if !stem.is_empty() => stem,
+        _ => word.as_str(),
+    };
+    matches!(
+        singular,
+        "beat" | "bar" | "measure" | "tick" | "s" | "sec" | "second"
+    )
+}
+
+/// Note names such as `C4`, `F#3` or `Bb-1` (uppercase letter, so variables are not shadowed)
+fn is_note_name(token: &str) -> bool {
+    let mut chars = token.chars().peekable();
+    if !matches!(chars.next(), Some('A'..='G')) {
+        return false;
+    }
+    if matches!(chars.peek(), Some('#') | Some('b')) {
+        chars.next();
+    }
+    if chars.peek() == Some(&'-') {
+        chars.next();
+    }
+    let octave: String = chars.collect();
+    !octave.is_empty() && octave.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Parse the rest of `roll 1/8 x4 pitch: 12 gain: 0.3`: the spacing between hits (a
+/// duration, fractions are beats), the hit count and optional pitch/gain ramps reached
+/// on the last hit
+/// Most hits one roll may play (`roll 1/32 x64`)
+pub const MAX_ROLL_HITS: usize = 64;
+
+fn parse_roll(parts: &mut Peekable<SplitWhitespace<'_>>) -> Result<Value> {
+    let interval = parts
+        .next()
+        .ok_or_else(|| anyhow!("'roll' requires a spacing and a count (e.g. roll 1/8 x4)"))?;
+    let interval = match parts.next_if(|unit| is_duration_unit(unit)) {
+        Some(unit) => format!("{} {}", interval, unit),
+        None => interval.to_string(),
+    };
+    let interval =
+        crate::language::syntax::parser::driver::duration::parse_duration_token(&interval)?;
+
+    let raw = parts
+        .next()
+        .ok_or_else(|| anyhow!("'roll' requires a hit count (e.g. x4)"))?;
+    let count = raw
+        .strip_prefix(['x', 'X'])
+        .and_then(|n| n.parse::<usize>().ok())
+        .filter(|n| *n >= 1)
+        .ok_or_else(|| anyhow!("'roll' expects a hit count like x4, found '{}'", raw))?;
+    if count > MAX_ROLL_HITS {
+        return Err(anyhow!(
+            "'roll' plays at most {} hits, found '{}'",
+            MAX_ROLL_HITS,
+            raw
+        ));
+    }
+
+    let mut roll = HashMap::new();
+    roll.insert("interval".to_string(), Value::Duration(interval));
+    roll.insert("count".to_string(), Value::Number(count as f32));
+    while let Some(option) = parts.next_if(|t| t.starts_with("pitch:") || t.starts_with("gain:")) {
+        let (key, inline) = option.split_once(':').unwrap_or((option, ""));
+        let raw = match inline {
+            "" => parts
+                .next()
+                .ok_or_else(|| anyhow!("'{}:' requires a value", key))?,
+            value => value,
+        };
+        let value = raw
+            .trim_start_matches('+')
+            .parse::<f32>()
+            .map_err(|_| anyhow!("invalid roll '{}:' value '{}'", key, raw))?;
+        roll.insert(key.to_string(), Value::Number(value));
+    }
+    Ok(Value::Map(roll))
+}
+
+/// Sample region options a trigger accepts as `key: value`
+const REGION_OPTIONS: [&str; 4] = ["start", "end", "loop", "sync"];
+
+/// Parse a region bound written as a fraction of the sample (`0.25`) or a percentage (`25%`)
+fn parse_region_bound(key: &str, raw: &str) -> Result<f32> {
+    let (number, scale) = match raw.strip_suffix('%') {
+        Some(pct) => (pct, 100.0),
+        None => (raw, 1.0),
+    };
+    let value = number
+        .parse::<f32>()
+        .map_err(|_| anyhow!("invalid '{}:' value '{}'", key, raw))?
+        / scale;
+    if !(0.0..=1.0).contains(&value) {
+        return Err(anyhow!(
+            "'{}:' must be between 0 and 1 (or 0% and 100%), found '{}'",
+            key,
+            raw
+        ));
+    }
+    Ok(value)
+}
+
+/// Parse a `chance` probability written as a percentage (`30%`) or a ratio (`0.3`).
+fn parse_chance(raw: &str) -> Result<f32> {
+    let (number, scale) = match raw.strip_suffix('%') {
+        Some(pct) => (pct, 100.0),
+        None => (raw, 1.0),
+    };
+    let value = number
+        .parse::<f32>()
+        .map_err(|_| anyhow!("invalid chance value '{}'", raw))?
+        / scale;
+    if !(0.0..=1.0).contains(&value) {
+        return Err(anyhow!(
+            "chance must be between 0% and 100%, found '{}'",
+            raw
+        ));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+#[path = "test_trigger.rs"]
+mod tests;
@@ -0,0 +1,24 @@
+//! Fuzzing entry point for the parser
+//!
+//! Enabled with the `fuzzing` feature and driven by the cargo-fuzz targets in `fuzz/`
+//! (`cargo fuzz run parse fuzz/corpus/parse`). Inputs are arbitrary bytes; the parser
+//! may reject them but must never panic.
+
+use std::path::PathBuf;
+
+use super::driver::{parse, preprocessing};
+
+/// Run the preprocessing passes and the parser over arbitrary bytes, discarding the result
+pub fn fuzz_parse(data: &[u8]) {
+    let source = String::from_utf8_lossy(data);
+
+    // The preprocessing passes are public on their own, so exercise them directly too
+    let braces = preprocessing::preprocess_multiline_braces(&source);
+    let _ = preprocessing::preprocess_multiline_arrow_calls(&braces);
+
+    let _ = parse(&source, PathBuf::from("fuzz.deva"));
+}
+
+#[cfg(test)]
+#[path = "test_fuzz.rs"]
+mod tests;
@@ -1 +1,3 @@
 pub mod driver;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
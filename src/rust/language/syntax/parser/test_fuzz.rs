@@ -0,0 +1,17 @@
+use super::*;
+
+/// Every corpus entry (including past crashers) must parse or fail without panicking
+#[test]
+fn test_fuzz_corpus_does_not_panic() {
+    let corpus = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/parse");
+    let entries = std::fs::read_dir(&corpus).expect("fuzz corpus directory");
+    let mut count = 0;
+    for entry in entries {
+        let path = entry.expect("corpus entry").path();
+        let data = std::fs::read(&path).expect("corpus file");
+        let result = std::panic::catch_unwind(|| fuzz_parse(&data));
+        assert!(result.is_ok(), "parser panicked on {}", path.display());
+        count += 1;
+    }
+    assert!(count > 0, "fuzz corpus is empty");
+}
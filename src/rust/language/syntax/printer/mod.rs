@@ -0,0 +1,1026 @@
+//! AST-to-source printer: turns statements back into `.deva` text that parses into the
+//! same tree, so tools that rewrite programs (formatter, migrations, generators, MIDI
+//! capture) can write files back out.
+//!
+//! The AST records what a line means rather than how it was spelled, so aliases print in
+//! one canonical form (`tempo 120` as `bpm 120`, `rest 1/4` as `sleep 1/4`, `lead -> vel(90)`
+//! as `lead -> velocity(90)`) and synth chains print as a parameter block plus their effects.
+//! `print_source` also brings back the `#` comments and blank lines of the parsed file.
+
+use crate::engine::functions::theory::midi_to_name;
+use crate::language::syntax::ast::{DurationValue, Statement, StatementKind, TimePosition, Value};
+use crate::language::syntax::parser::driver::preprocessing;
+use std::collections::HashMap;
+use std::path::Path;
+
+const INDENT: &str = "    ";
+
+/// Denominators tried when writing beat counts as fractions (`1/8`, `1/12`)
+const BEAT_DENOMINATORS: [f32; 12] = [
+    2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 24.0, 32.0, 48.0, 64.0, 128.0,
+];
+
+/// Print statements as `.deva` source
+pub fn print_statements(statements: &[Statement]) -> String {
+    let mut printer = Printer::default();
+    printer.block(statements, 0);
+    printer.out
+}
+
+/// Print statements parsed from `source` (read from `path`), keeping its `#` comments and
+/// blank lines next to the statements they preceded. Import and load paths, which the
+/// parser resolves against the file's directory, are written relative to it again.
+/// Lines that did not parse are copied as they were.
+pub fn print_source(statements: &[Statement], source: &str, path: &Path) -> String {
+    // Statement line numbers count lines after multi-line statements are joined
+    let joined = preprocessing::preprocess_multiline_arrow_calls(
+        &preprocessing::preprocess_multiline_braces(source),
+    );
+    let mut printer = Printer {
+        lines: joined.lines().map(str::to_string).collect(),
+        base_dir: path.parent().map(|dir| dir.to_string_lossy().to_string()),
+        ..Printer::default()
+    };
+    printer.block(statements, 0);
+    printer.comments_before(usize::MAX, 0);
+    printer.out
+}
+
+/// Print one value the way a call argument or map entry is written
+pub fn print_value(value: &Value) -> String {
+    match value {
+        Value::Boolean(flag) => flag.to_string(),
+        Value::Number(number) => number_text(*number),
+        Value::Duration(duration) => duration_text(duration),
+        Value::Identifier(name) | Value::Sample(name) | Value::Beat(name) | Value::Midi(name) => {
+            name.clone()
+        }
+        Value::String(text) => format!("\"{}\"", text),
+        Value::Array(items) => format!("[{}]", join(items.iter().map(print_value))),
+        Value::Map(map) => map_text(map, print_value),
+        Value::Call { name, args } => call_text(name, args),
+        Value::Range { start, end } => format!("{}..{}", print_value(start), print_value(end)),
+        Value::Statement(statement) => statement_line(statement).unwrap_or_default(),
+        Value::Block(_) | Value::StatementKind(_) | Value::Unknown => String::new(),
+        Value::Null => "null".to_string(),
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    /// Source lines the statements were parsed from (empty without a source)
+    lines: Vec<String>,
+    /// Index of the first source line not yet checked for comments
+    scanned: usize,
+    /// A blank source line was skipped; written before the next output line
+    blank_pending: bool,
+    base_dir: Option<String>,
+}
+
+impl Printer {
+    fn line(&mut self, depth: usize, text: &str) {
+        if self.blank_pending && !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.blank_pending = false;
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Copy the comments found above source line `line` (1-based), at `depth`
+    fn comments_before(&mut self, line: usize, depth: usize) {
+        let end = line.saturating_sub(1).min(self.lines.len());
+        while self.scanned < end {
+            let text = self.lines[self.scanned].trim().to_string();
+            self.scanned += 1;
+            if text.is_empty() {
+                self.blank_pending = true;
+            } else if text.starts_with('#') {
+                self.line(depth, &text);
+            }
+        }
+        self.scanned = self.scanned.max(line.min(self.lines.len()));
+    }
+
+    fn block(&mut self, statements: &[Statement], depth: usize) {
+        for statement in statements {
+            self.statement(statement, depth, "");
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement, depth: usize, prefix: &str) {
+        self.comments_before(statement.line, depth);
+        match &statement.kind {
+            StatementKind::If {
+                condition,
+                body,
+                else_body,
+            } => {
+                self.line(
+                    depth,
+                    &format!("{}if {}:", prefix, condition_text(condition)),
+                );
+                self.block(body, depth + 1);
+                match else_body.as_deref() {
+                    Some(
+                        [
+                            nested @ Statement {
+                                kind: StatementKind::If { .. },
+                                value: Value::String(marker),
+                                ..
+                            },
+                        ],
+                    ) if marker == "else-if" => self.statement(nested, depth, "else "),
+                    Some(else_body) => {
+                        self.line(depth, "else:");
+                        self.block(else_body, depth + 1);
+                    }
+                    None => {}
+                }
+            }
+            StatementKind::Automate { target } => self.automate(target, &statement.value, depth),
+            StatementKind::Unknown | StatementKind::Error { .. } => {
+                // Keep what the parser could not read rather than dropping it
+                if let Some(text) = statement
+                    .line
+                    .checked_sub(1)
+                    .and_then(|idx| self.lines.get(idx))
+                {
+                    let text = text.trim().to_string();
+                    self.line(depth, &text);
+                }
+            }
+            kind => {
+                let Some(header) = self.header(statement) else {
+                    return;
+                };
+                match block_body(kind) {
+                    Some(body) => {
+                        // `on` keeps the colon in its event name (`on beat:`) when written
+                        let colon = if matches!(kind, StatementKind::On { .. }) {
+                            ""
+                        } else {
+                            ":"
+                        };
+                        self.line(depth, &format!("{}{}", header, colon));
+                        self.block(body, depth + 1);
+                    }
+                    None => self.line(depth, &header),
+                }
+            }
+        }
+    }
+
+    /// Text of a statement's own line (without the block colon)
+    fn header(&self, statement: &Statement) -> Option<String> {
+        let text = match &statement.kind {
+            StatementKind::Load { source, alias } => {
+                let mut text = format!("load \"{}\" as {}", self.relative(source), alias);
+                if let Value::Map(options) = &statement.value {
+                    text.push_str(&format!(" with {}", flat_map_text(options)));
+                }
+                text
+            }
+            StatementKind::Import { names, source } => {
+                let mut text = format!(
+                    "import {{ {} }} from \"{}\"",
+                    names.join(", "),
+                    self.relative(source)
+                );
+                if let Value::Map(params) = &statement.value {
+                    text.push_str(&format!(" with {}", flat_map_text(params)));
+                }
+                text
+            }
+            _ => return statement_line(statement),
+        };
+        Some(text)
+    }
+
+    /// `source` without the directory of the printed file
+    fn relative<'a>(&self, source: &'a str) -> &'a str {
+        match &self.base_dir {
+            Some(dir) if !dir.is_empty() => source
+                .strip_prefix(dir.as_str())
+                .and_then(|rest| rest.strip_prefix(std::path::MAIN_SEPARATOR))
+                .unwrap_or(source),
+            _ => source,
+        }
+    }
+
+    fn automate(&mut self, target: &str, value: &Value, depth: usize) {
+        let empty = HashMap::new();
+        let options = match value {
+            Value::Map(map) => map,
+            _ => &empty,
+        };
+        if let (Some(Value::String(param)), Some(Value::String(formula))) =
+            (options.get("param"), options.get("formula"))
+        {
+            self.line(
+                depth,
+                &format!("automate {}.{}: {}", target, param, formula),
+            );
+            return;
+        }
+
+        let mut header = format!("automate {}", target);
+        if let Some(Value::String(mode)) = options.get("mode") {
+            header.push_str(&format!(" mode {}", mode));
+        }
+        self.line(depth, &format!("{}:", header));
+
+        let Some(Value::Array(params)) = options.get("params") else {
+            return;
+        };
+        for param in params {
+            let Value::Map(param) = param else {
+                continue;
+            };
+            let Some(Value::String(name)) = param.get("name") else {
+                continue;
+            };
+            let mut line = format!("param {}", name);
+            if let Some(Value::String(curve)) = param.get("curve") {
+                line.push_str(&format!(" curve {}", curve));
+            }
+            if let Some(length) = param.get("loop") {
+                line.push_str(&format!(" loop {}", automate_position_text(length)));
+            }
+            self.line(depth + 1, &format!("{} {{", line));
+            if let Some(Value::Array(keyframes)) = param.get("keyframes") {
+                for keyframe in keyframes {
+                    let Value::Map(keyframe) = keyframe else {
+                        continue;
+                    };
+                    let (Some(at), Some(Value::Number(value))) =
+                        (keyframe.get("at"), keyframe.get("value"))
+                    else {
+                        continue;
+                    };
+                    let mut point =
+                        format!("{} = {}", automate_position_text(at), number_text(*value));
+                    if let Some(Value::String(mode)) = keyframe.get("mode") {
+                        point.push_str(&format!(" {}", mode));
+                    }
+                    self.line(depth + 2, &point);
+                }
+            }
+            self.line(depth + 1, "}");
+        }
+    }
+}
+
+/// Body printed under a statement's header line, for block statements
+fn block_body(kind: &StatementKind) -> Option<&[Statement]> {
+    match kind {
+        StatementKind::Tempo {
+            body: Some(body), ..
+        }
+        | StatementKind::Function { body, .. }
+        | StatementKind::Group { body, .. }
+        | StatementKind::Loop { body, .. }
+        | StatementKind::For { body, .. }
+        | StatementKind::At { body, .. }
+        | StatementKind::Routing { body }
+        | StatementKind::On { body, .. } => Some(body),
+        _ => None,
+    }
+}
+
+/// Single-line text of a statement (block statements without their body); `None` for
+/// statements that have no source syntax
+fn statement_line(statement: &Statement) -> Option<String> {
+    let value = &statement.value;
+    let text = match &statement.kind {
+        StatementKind::Tempo { value, .. } => format!("bpm {}", number_text(*value)),
+        StatementKind::Print => format!("print {}", print_message_text(value)),
+        StatementKind::Pattern { name, target } => pattern_text(name, target.as_deref(), value),
+        StatementKind::Trigger {
+            entity,
+            duration,
+            effects,
+        } => trigger_text(entity, duration, effects.as_ref(), value),
+        StatementKind::Sleep => match value {
+            Value::Duration(duration) => format!("sleep {}", duration_text(duration)),
+            other => format!("sleep {}", print_value(other)),
+        },
+        StatementKind::Call { name, args } => call_statement_text(name, args, value),
+        StatementKind::Load { source, alias } => format!("load \"{}\" as {}", source, alias),
+        StatementKind::Use { name, alias } => match alias {
+            Some(alias) => format!("use {} as {}", name, alias),
+            None => format!("use {}", name),
+        },
+        StatementKind::UsePlugin {
+            author,
+            name,
+            alias,
+        } => format!("use {}.{} as {}", author, name, alias),
+        StatementKind::ArrowCall {
+            target,
+            method,
+            args,
+        } => match value {
+            Value::Map(chain) if chain.contains_key("method") => arrow_chain_text(chain),
+            _ => format!("{} -> {}", target, call_text(method, args)),
+        },
+        StatementKind::Function {
+            name, parameters, ..
+        } => format!("function {}({})", name, parameters.join(", ")),
+        StatementKind::Assign { target, property } => {
+            format!("{}.{} = {}", target, property, print_value(value))
+        }
+        StatementKind::Bank { name, alias } => match alias {
+            Some(alias) => format!("bank {} as {}", name, alias),
+            None => format!("bank {}", name),
+        },
+        StatementKind::Let { name, value } => declaration_text("let", name, value.as_ref()),
+        StatementKind::Var { name, value } => declaration_text("var", name, value.as_ref()),
+        StatementKind::Const { name, value } => declaration_text("const", name, value.as_ref()),
+        StatementKind::Param { name, value } => declaration_text("param", name, value.as_ref()),
+        StatementKind::Macro { name, targets } => {
+            format!("macro {} -> [{}]", name, targets.join(", "))
+        }
+        StatementKind::Group { name, .. } => group_text(name, value),
+        StatementKind::Spawn { name, .. } => format!("spawn {}", name),
+        StatementKind::Loop { count, .. } => {
+            let mut text = match count {
+                Value::Null => "loop".to_string(),
+                count => format!("loop {}", print_value(count)),
+            };
+            push_label(&mut text, value);
+            text
+        }
+        StatementKind::For {
+            variable, iterable, ..
+        } => {
+            let iterable = match iterable {
+                Value::Array(items) => array_literal(items),
+                other => print_value(other),
+            };
+            let mut text = format!("for {} in {}", variable, iterable);
+            push_label(&mut text, value);
+            text
+        }
+        StatementKind::At { position, .. } => match position {
+            TimePosition::Seconds(seconds) => format!("at {}s", number_text(*seconds)),
+            TimePosition::Bar(bar) => format!("at bar {}", number_text(*bar)),
+            TimePosition::Beat(beat) => format!("at beat {}", number_text(*beat)),
+        },
+        StatementKind::Scene { name, members } => {
+            format!("scene {} = [{}]", name, members.join(", "))
+        }
+        StatementKind::Switch { scene, fade } => match fade {
+            Some(fade) => format!("switch {} over {}", scene, duration_text(fade)),
+            None => format!("switch {}", scene),
+        },
+        StatementKind::SnapshotSave { name } => format!("snapshot save {}", name),
+        StatementKind::SnapshotMorph {
+            from,
+            to,
+            length,
+            curve,
+        } => {
+            let mut text = format!("snapshot morph {} -> {}", from, to);
+            if let Some(length) = length {
+                text.push_str(&format!(" over {}", duration_text(length)));
+            }
+            if let Some(curve) = curve {
+                text.push_str(&format!(" curve {}", curve));
+            }
+            text
+        }
+        StatementKind::Routing { .. } => "routing".to_string(),
+        StatementKind::RoutingNode { name, alias } => match alias {
+            Some(alias) => format!("node {} = {}", name, alias),
+            None => format!("node {}", name),
+        },
+        StatementKind::RoutingFx { target, effects } => {
+            format!("fx {} -> {}", target, effect_chain_text(effects))
+        }
+        StatementKind::RoutingRoute {
+            source,
+            destination,
+            effects,
+        } => match effects {
+            Some(effects) => format!(
+                "route {} to {} with {}",
+                source,
+                destination,
+                effect_chain_text(effects)
+            ),
+            None => format!("route {} to {}", source, destination),
+        },
+        StatementKind::RoutingOutputs {
+            source,
+            first,
+            last,
+        } => {
+            let route = crate::engine::audio::outputs::OutputRoute {
+                insert: source.clone(),
+                first: *first,
+                last: *last,
+            };
+            format!("route {}", route)
+        }
+        StatementKind::RoutingStrip { target, strip } => {
+            format!("strip {} {}", target, strip).trim_end().to_string()
+        }
+        StatementKind::RoutingDuck {
+            source,
+            destination,
+            effect,
+        } => duck_by_text(source, destination, effect).unwrap_or_else(|| {
+            format!(
+                "duck {} to {} with {}",
+                source,
+                destination,
+                effect_chain_text(effect)
+            )
+        }),
+        StatementKind::RoutingSidechain {
+            source,
+            destination,
+            effect,
+        } => format!(
+            "sidechain {} to {} with {}",
+            source,
+            destination,
+            effect_chain_text(effect)
+        ),
+        StatementKind::Bind { source, target } => match value {
+            Value::Map(options) => {
+                format!(
+                    "bind {} -> {} with {}",
+                    source,
+                    target,
+                    flat_map_text(options)
+                )
+            }
+            _ => format!("bind {} -> {}", source, target),
+        },
+        StatementKind::Export { names, .. } => format!("export {{ {} }}", names.join(", ")),
+        StatementKind::Import { names, source } => {
+            let mut text = format!("import {{ {} }} from \"{}\"", names.join(", "), source);
+            if let Value::Map(params) = value {
+                text.push_str(&format!(" with {}", flat_map_text(params)));
+            }
+            text
+        }
+        StatementKind::On { event, args, .. } => {
+            let mut text = format!("on {}", event);
+            for arg in args.iter().flatten() {
+                match arg {
+                    Value::Number(interval) => {
+                        text.push_str(&format!("({})", number_text(*interval)))
+                    }
+                    Value::String(flag) => text.push_str(&format!(" {}", flag)),
+                    _ => {}
+                }
+            }
+            text
+        }
+        StatementKind::Emit { event, payload } => match payload {
+            Some(Value::Map(payload)) => {
+                format!("emit {} {}", event, map_text(payload, print_value))
+            }
+            _ => format!("emit {}", event),
+        },
+        StatementKind::If { condition, .. } => format!("if {}", condition_text(condition)),
+        StatementKind::Return { value } => match value {
+            Some(value) => format!("return {}", print_value(value)),
+            None => "return".to_string(),
+        },
+        StatementKind::Break => labelled("break", value),
+        StatementKind::Continue => labelled("continue", value),
+        StatementKind::Persist { names } => format!("@persist {}", names.join(", ")),
+        StatementKind::Metronome { enabled: false } => "metronome off".to_string(),
+        StatementKind::Metronome { enabled: true } => match value {
+            Value::String(mode) => format!("metronome on {}", mode),
+            _ => "metronome on".to_string(),
+        },
+        StatementKind::Mark { name } => {
+            if name.contains(char::is_whitespace) {
+                format!("mark \"{}\"", name)
+            } else {
+                format!("mark {}", name)
+            }
+        }
+        StatementKind::Automate { target } => format!("automate {}", target),
+        StatementKind::Synth
+        | StatementKind::FxPipeline { .. }
+        | StatementKind::Node { .. }
+        | StatementKind::Sidechain { .. }
+        | StatementKind::Include(_)
+        | StatementKind::Comment
+        | StatementKind::Indent
+        | StatementKind::Dedent
+        | StatementKind::NewLine
+        | StatementKind::Unknown
+        | StatementKind::Error { .. } => return None,
+    };
+    Some(text)
+}
+
+fn push_label(text: &mut String, value: &Value) {
+    if let Value::String(label) = value {
+        text.push_str(&format!(" as {}", label));
+    }
+}
+
+fn labelled(keyword: &str, value: &Value) -> String {
+    match value {
+        Value::String(label) => format!("{} {}", keyword, label),
+        _ => keyword.to_string(),
+    }
+}
+
+fn print_message_text(value: &Value) -> String {
+    match value {
+        // `print "Loop " + i` keeps its parts in an array
+        Value::Array(parts) => parts
+            .iter()
+            .map(print_value)
+            .collect::<Vec<_>>()
+            .join(" + "),
+        other => print_value(other),
+    }
+}
+
+fn pattern_text(name: &str, target: Option<&str>, value: &Value) -> String {
+    if let Value::Map(map) = value
+        && let Some(Value::String(chain)) = map.get("chain")
+    {
+        return format!("pattern {} = {}", name, chain);
+    }
+    let mut text = format!("pattern {}", name);
+    if let Some(target) = target {
+        text.push_str(&format!(" with {}", target));
+    }
+    match value {
+        Value::String(steps) => text.push_str(&format!(" = \"{}\"", steps)),
+        Value::Map(map) => {
+            let options: Vec<String> = sorted(map)
+                .into_iter()
+                .filter(|(key, _)| key.as_str() != "pattern")
+                .map(|(key, option)| {
+                    let option = match option {
+                        Value::String(text) => format!("\"{}\"", text),
+                        other => print_value(other),
+                    };
+                    format!("{}: {}", key, option)
+                })
+                .collect();
+            text.push_str(&format!(" {{ {} }}", options.join(", ")));
+            if let Some(Value::String(steps)) = map.get("pattern") {
+                text.push_str(&format!(" = \"{}\"", steps));
+            }
+        }
+        _ => {}
+    }
+    text
+}
+
+fn trigger_text(
+    entity: &str,
+    duration: &DurationValue,
+    effects: Option<&Value>,
+    modifiers: &Value,
+) -> String {
+    let mut text = format!(".{}", entity);
+    let modifiers = match modifiers {
+        Value::Map(map) => Some(map),
+        _ => None,
+    };
+    let modifier = |key: &str| modifiers.and_then(|map| map.get(key));
+
+    if let Some(Value::Number(midi)) = modifier("note") {
+        text.push_str(&format!(" {}", midi_to_name(*midi as i32)));
+    }
+    if !matches!(duration, DurationValue::Auto) {
+        text.push_str(&format!(" {}", duration_text(duration)));
+    }
+    if let Some(Value::Number(chance)) = modifier("chance") {
+        text.push_str(&format!(" chance {}", number_text(*chance)));
+    }
+    if let Some(Value::Number(every)) = modifier("every") {
+        text.push_str(&format!(" every {}", number_text(*every)));
+    }
+    if let Some(Value::Map(roll)) = modifier("roll") {
+        if let (Some(Value::Duration(interval)), Some(Value::Number(count))) =
+            (roll.get("interval"), roll.get("count"))
+        {
+            text.push_str(&format!(
+                " roll {} x{}",
+                duration_text(interval),
+                number_text(*count)
+            ));
+        }
+        for key in ["pitch", "gain"] {
+            if let Some(Value::Number(ramp)) = roll.get(key) {
+                text.push_str(&format!(" {}: {}", key, number_text(*ramp)));
+            }
+        }
+    }
+    for key in ["start", "end", "loop", "sync"] {
+        match modifier(key) {
+            Some(Value::Duration(sync)) => {
+                text.push_str(&format!(" {}: {}", key, duration_text(sync)))
+            }
+            Some(option) => text.push_str(&format!(" {}: {}", key, print_value(option))),
+            None => {}
+        }
+    }
+    if let Some(effects) = effects
+        && !matches!(effects, Value::Map(map) if map.is_empty())
+    {
+        text.push_str(&format!(" -> {}", effect_chain_text(effects)));
+    }
+    text
+}
+
+/// `reverb({ size: 0.3 }) -> speed(2)` from an `{ effect: params }` map
+fn effect_chain_text(effects: &Value) -> String {
+    match effects {
+        Value::Map(effects) => sorted(effects)
+            .into_iter()
+            .map(|(name, params)| effect_text(name, params))
+            .collect::<Vec<_>>()
+            .join(" -> "),
+        Value::Array(list) => list
+            .iter()
+            .map(effect_chain_text)
+            .collect::<Vec<_>>()
+            .join(" -> "),
+        other => print_value(other),
+    }
+}
+
+/// `duck pads by kick amount 0.6 attack 5ms`, when the options read back the same way
+fn duck_by_text(target: &str, key: &str, effect: &Value) -> Option<String> {
+    let Value::Map(effect) = effect else {
+        return None;
+    };
+    let Some(Value::Map(params)) = effect.get("duck").filter(|_| effect.len() == 1) else {
+        return None;
+    };
+    let mut text = format!("duck {} by {}", target, key);
+    for (option, value) in sorted(params) {
+        let value = match (option.as_str(), value) {
+            ("amount", Value::Number(amount)) => number_text(*amount),
+            ("attack" | "release", Value::Duration(duration)) => duration_text(duration),
+            _ => return None,
+        };
+        if value.contains(char::is_whitespace) {
+            return None;
+        }
+        text.push_str(&format!(" {} {}", option, value));
+    }
+    Some(text)
+}
+
+fn effect_text(name: &str, params: &Value) -> String {
+    match params {
+        Value::Null => name.to_string(),
+        Value::Map(params) => format!("{}({})", name, flat_map_text(params)),
+        other => format!("{}({})", name, print_value(other)),
+    }
+}
+
+fn call_statement_text(name: &str, args: &[Value], value: &Value) -> String {
+    let options = match value {
+        Value::Map(map) => Some(map),
+        _ => None,
+    };
+    let mut text = match options.and_then(|map| map.get("pattern")) {
+        Some(Value::String(steps))
+            if options.is_some_and(|map| map.contains_key("inline_pattern")) =>
+        {
+            format!("call {} = \"{}\"", name, steps)
+        }
+        _ if args.is_empty() => format!("call {}", name),
+        _ => format!("call {}", call_text(name, args)),
+    };
+    if let Some(Value::Map(preset)) = options.and_then(|map| map.get("with")) {
+        text.push_str(&format!(" with {}", flat_map_text(preset)));
+    }
+    text
+}
+
+fn group_text(name: &str, value: &Value) -> String {
+    let mut text = format!("group {}", name);
+    let Value::Map(options) = value else {
+        return text;
+    };
+    for flag in ["solo", "mute"] {
+        if matches!(options.get(flag), Some(Value::Boolean(true))) {
+            text.push_str(&format!(" {}", flag));
+        }
+    }
+    if let Some(Value::Map(preset)) = options.get("with") {
+        text.push_str(&format!(" with {}", flat_map_text(preset)));
+    }
+    if let Some(effects) = options.get("effects") {
+        text.push_str(&format!(" -> {}", effect_chain_text(effects)));
+    }
+    text
+}
+
+fn declaration_text(keyword: &str, name: &str, value: Option<&Value>) -> String {
+    let Some(value) = value else {
+        return format!("{} {}", keyword, name);
+    };
+    let text = match value {
+        Value::Map(map) if matches!(map.get("type"), Some(Value::String(kind)) if kind == "synth") => {
+            synth_text(map)
+        }
+        // `const lead = synth saw -> ...` keeps the raw arrow chain
+        Value::Map(map)
+            if matches!(map.get("target"), Some(Value::String(target)) if target.starts_with("synth "))
+                && map.contains_key("method") =>
+        {
+            arrow_chain_text(map)
+        }
+        Value::Array(items) => array_literal(items),
+        Value::Range { start, end } => format!("[{}..{}]", print_value(start), print_value(end)),
+        other => print_value(other),
+    };
+    format!("{} {} = {}", keyword, name, text)
+}
+
+/// Array written where the parser reads elements with `parse_array_value`, which wraps
+/// all but the last plain element in `{ index, value }` maps
+fn array_literal(items: &[Value]) -> String {
+    let items = items.iter().enumerate().map(|(position, item)| match item {
+        Value::Map(map) if map.len() == 2 => match (map.get("index"), map.get("value")) {
+            (Some(Value::Number(index)), Some(value)) if *index == position as f32 => {
+                print_value(value)
+            }
+            _ => flat_map_text(map),
+        },
+        Value::Map(map) => flat_map_text(map),
+        other => print_value(other),
+    });
+    format!("[{}]", join(items))
+}
+
+/// Keys of a synth map that come from its header rather than its parameter block
+const SYNTH_HEADER_KEYS: [&str; 6] = [
+    "type",
+    "_plugin_ref",
+    "plugin_author",
+    "plugin_name",
+    "plugin_export",
+    "_extends",
+];
+
+/// `synth saw { attack: 0.01 } -> articulations({...}) -> reverb({ size: 0.5 })`
+fn synth_text(map: &HashMap<String, Value>) -> String {
+    let text_of = |key: &str| match map.get(key) {
+        Some(Value::String(text)) => Some(text.as_str()),
+        _ => None,
+    };
+    let mut text = "synth".to_string();
+    let mut waveform_in_header = false;
+    if let Some(reference) = text_of("_plugin_ref") {
+        text.push_str(&format!(" {}", reference));
+    } else if let (Some(author), Some(name)) = (text_of("plugin_author"), text_of("plugin_name")) {
+        text.push_str(&format!(" plugin.{}.{}", author, name));
+        if let Some(export) = text_of("plugin_export") {
+            text.push_str(&format!(".{}", export));
+        }
+    } else if let Some(waveform) = text_of("waveform") {
+        text.push_str(&format!(" {}", waveform));
+        waveform_in_header = true;
+    }
+    if let Some(parent) = text_of("_extends") {
+        text.push_str(&format!(" extends {}", parent));
+    }
+
+    let params: Vec<String> = sorted(map)
+        .into_iter()
+        .filter(|(key, _)| {
+            let printed_elsewhere = SYNTH_HEADER_KEYS.contains(&key.as_str())
+                || matches!(key.as_str(), "chain" | "articulations")
+                || (waveform_in_header && key.as_str() == "waveform");
+            !printed_elsewhere
+        })
+        .map(|(key, param)| {
+            let param = match param {
+                Value::Array(items) => array_literal(items),
+                Value::Map(nested) => flat_map_text(nested),
+                Value::String(text) => format!("\"{}\"", text),
+                other => print_value(other),
+            };
+            format!("{}: {}", key, param)
+        })
+        .collect();
+    // The parser only records `_plugin_ref` when the reference has a parameter block
+    if !params.is_empty() || map.contains_key("_plugin_ref") {
+        text.push_str(&format!(" {{ {} }}", params.join(", ")));
+    }
+
+    if let Some(articulations) = map.get("articulations") {
+        text.push_str(&format!(
+            " -> articulations({})",
+            print_value(articulations)
+        ));
+    }
+    if let Some(Value::Array(chain)) = map.get("chain") {
+        for effect in chain {
+            let Value::Map(effect) = effect else {
+                continue;
+            };
+            let Some(Value::String(name)) = effect.get("type") else {
+                continue;
+            };
+            let params: HashMap<String, Value> = effect
+                .iter()
+                .filter(|(key, _)| key.as_str() != "type")
+                .map(|(key, param)| (key.clone(), param.clone()))
+                .collect();
+            let args = match params.get("value") {
+                Some(single) if params.len() == 1 && !matches!(single, Value::Map(_)) => {
+                    print_value(single)
+                }
+                _ => map_text(&params, print_value),
+            };
+            text.push_str(&format!(" -> {}({})", name, args));
+        }
+    }
+    text
+}
+
+/// `target -> method(args) -> next(args)` from an arrow call's chain map
+fn arrow_chain_text(chain: &HashMap<String, Value>) -> String {
+    let text_of = |map: &HashMap<String, Value>, key: &str| match map.get(key) {
+        Some(Value::String(text)) => text.clone(),
+        _ => String::new(),
+    };
+    let args_of = |map: &HashMap<String, Value>| match map.get("args") {
+        Some(Value::Array(args)) => args.clone(),
+        _ => Vec::new(),
+    };
+    let mut text = format!(
+        "{} -> {}",
+        text_of(chain, "target"),
+        call_text(&text_of(chain, "method"), &args_of(chain))
+    );
+    if let Some(Value::Array(calls)) = chain.get("chain") {
+        for call in calls {
+            if let Value::Map(call) = call {
+                text.push_str(&format!(
+                    " -> {}",
+                    call_text(&text_of(call, "method"), &args_of(call))
+                ));
+            }
+        }
+    }
+    text
+}
+
+fn call_text(name: &str, args: &[Value]) -> String {
+    if args.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}({})", name, join(args.iter().map(print_value)))
+    }
+}
+
+fn condition_text(condition: &Value) -> String {
+    match condition {
+        Value::Map(map) => match (map.get("left"), map.get("operator"), map.get("right")) {
+            (Some(left), Some(Value::String(operator)), Some(right)) => {
+                format!("{} {} {}", print_value(left), operator, print_value(right))
+            }
+            _ => print_value(condition),
+        },
+        other => print_value(other),
+    }
+}
+
+/// Map written where the parser reads it with `parse_map_value` (one level, no nesting)
+fn flat_map_text(map: &HashMap<String, Value>) -> String {
+    map_text(map, |value| match value {
+        Value::String(text) if is_bare_word(text) => text.clone(),
+        other => print_value(other),
+    })
+}
+
+fn map_text(map: &HashMap<String, Value>, entry: impl Fn(&Value) -> String) -> String {
+    if map.is_empty() {
+        return "{}".to_string();
+    }
+    let entries = sorted(map)
+        .into_iter()
+        .map(|(key, value)| format!("{}: {}", key, entry(value)));
+    format!("{{ {} }}", join(entries))
+}
+
+/// Words that read back as the same string without quotes
+fn is_bare_word(text: &str) -> bool {
+    !text.is_empty()
+        && text.parse::<f32>().is_err()
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '%' | '$' | '#' | '-'))
+}
+
+fn sorted(map: &HashMap<String, Value>) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+/// `f32` in its shortest form that parses back to the same value
+fn number_text(number: f32) -> String {
+    number.to_string()
+}
+
+/// Duration as a trigger, `sleep` or `switch ... over` reads it
+pub fn duration_text(duration: &DurationValue) -> String {
+    match duration {
+        DurationValue::Number(ms) => number_text(*ms),
+        DurationValue::Milliseconds(ms) => format!("{}ms", number_text(*ms)),
+        DurationValue::Identifier(name) | DurationValue::Beat(name) => name.clone(),
+        DurationValue::Beats(beats) => beats_text(*beats, false),
+        DurationValue::Span(span) => match (span.beats != 0.0, span.seconds != 0.0) {
+            (true, true) => format!(
+                "{}+{}s",
+                beats_text(span.beats, true),
+                number_text(span.seconds)
+            ),
+            (false, _) => format!("{}s", number_text(span.seconds)),
+            (true, false) => {
+                let ticks = span.beats * crate::language::syntax::ast::TimeSpan::TICKS_PER_BEAT;
+                if ticks.fract() == 0.0
+                    && crate::language::syntax::ast::TimeSpan::ticks(ticks).beats == span.beats
+                {
+                    format!("{} ticks", number_text(ticks))
+                } else {
+                    format!("{}+0s", beats_text(span.beats, true))
+                }
+            }
+        },
+        DurationValue::Auto => "auto".to_string(),
+    }
+}
+
+/// Beat count as a fraction (`1/8`), whole beats or bars, or `<n> beats`; `compact` keeps
+/// it one word so it can sit inside a sum
+fn beats_text(beats: f32, compact: bool) -> String {
+    if beats > 0.0 && beats < 1.0 {
+        for denominator in BEAT_DENOMINATORS {
+            let numerator = (beats * denominator).round();
+            if numerator / denominator == beats {
+                return format!("{}/{}", number_text(numerator), number_text(denominator));
+            }
+        }
+    }
+    let separator = if compact { "" } else { " " };
+    if !compact && beats >= 4.0 && beats % 4.0 == 0.0 {
+        let bars = beats / 4.0;
+        let unit = if bars == 1.0 { "bar" } else { "bars" };
+        return format!("{} {}", number_text(bars), unit);
+    }
+    let unit = if beats == 1.0 { "beat" } else { "beats" };
+    format!("{}{}{}", number_text(beats), separator, unit)
+}
+
+/// Automation position: fractions as percentages, durations as written
+fn automate_position_text(position: &Value) -> String {
+    match position {
+        Value::Number(fraction) => {
+            let percent = fraction * 100.0;
+            // Shortest percentage that reads back as the same fraction
+            (0..=6)
+                .map(|decimals| {
+                    let text = format!("{:.*}", decimals, percent);
+                    match text.contains('.') {
+                        true => text.trim_end_matches('0').trim_end_matches('.').to_string(),
+                        false => text,
+                    }
+                })
+                .find(|text| {
+                    text.parse::<f32>()
+                        .is_ok_and(|parsed| (parsed / 100.0).clamp(0.0, 1.0) == *fraction)
+                })
+                .map(|text| format!("{}%", text))
+                .unwrap_or_else(|| format!("{}%", number_text(percent)))
+        }
+        Value::Duration(duration) => duration_text(duration),
+        other => print_value(other),
+    }
+}
+
+#[cfg(test)]
+#[path = "test_printer.rs"]
+mod tests;
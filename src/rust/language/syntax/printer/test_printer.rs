@@ -0,0 +1,190 @@
+use super::*;
+use crate::language::syntax::parser::driver::SimpleParser;
+use std::path::PathBuf;
+
+/// Statements as JSON without source positions, which printing is free to change
+fn shape(statements: &[Statement]) -> serde_json::Value {
+    fn strip(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for key in ["line", "column", "span", "indent"] {
+                    map.remove(key);
+                }
+                map.values_mut().for_each(strip);
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::to_value(statements).unwrap();
+    strip(&mut value);
+    value
+}
+
+fn assert_round_trip(source: &str, path: &Path) -> String {
+    let parsed = SimpleParser::parse(source, path.to_path_buf()).unwrap();
+    let printed = print_source(&parsed, source, path);
+    let reparsed = SimpleParser::parse(&printed, path.to_path_buf())
+        .unwrap_or_else(|err| panic!("printed source does not parse ({err}):\n{printed}"));
+    let (before, after) = (shape(&parsed), shape(&reparsed));
+    let (before, after) = (before.as_array().unwrap(), after.as_array().unwrap());
+    for (original, reprinted) in before.iter().zip(after) {
+        assert_eq!(
+            original,
+            reprinted,
+            "{} changed when printed as:\n{}",
+            path.display(),
+            printed
+        );
+    }
+    assert_eq!(
+        before.len(),
+        after.len(),
+        "statement count changed:\n{printed}"
+    );
+    printed
+}
+
+#[test]
+fn test_examples_round_trip() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/scripts");
+    let mut count = 0;
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "deva") {
+            let source = std::fs::read_to_string(&path).unwrap();
+            assert_round_trip(&source, &path);
+            count += 1;
+        }
+    }
+    assert!(count > 0, "no example scripts found");
+}
+
+#[test]
+fn test_statements_round_trip() {
+    let source = r#"bpm 128
+bank devaloop.808 as kit
+load "./samples/snare.wav" as snare with { gain: 0.8 }
+import { lead, groove } from "./shared.deva"
+import { riser as long_riser } from "./riser.deva" with { length: 8, root: E4 }
+param bars = 4
+let steps = [C4, E4, G4]
+let ratio = 0.75
+let loud = true
+let lead = synth saw extends base { attack: 0.01, filters: [{ type: lowpass, cutoff: 800 }] } -> type(pluck) -> articulations({ pluck: { decay: 0.08 } }) -> reverb({ size: 0.5 }) -> drive(0.3)
+let hit = .kit.kick 1/8 -> reverb({ size: 0.3 })
+const name = "lead"
+accent map strong = [1.2, 0.8, 1.1, 0.8] per 1/8
+pattern groove with kit.kick = "x--- x--- x--- x---"
+pattern swung with kit.hat { swing: 0.2, humanize: true } = "x-x-"
+pattern verse = groove then swung
+group verse solo with { velocity: 0.9 } -> compressor({ ratio: 4 }):
+    lead -> note(C4) -> duration(1/4) -> velocity(100)
+    lead!pluck -> chord(Cmaj7) -> duration(1/2)
+    .kit.kick C4 1/8d chance 30% every 2 start: 0.25 loop: true -> reverb({ size: 0.3 }) -> speed(2)
+    .kit.snare roll 1/16 x4 pitch: 12 gain: 0.5
+    .kit.hat 1/8+10ms
+    sleep 2 bars
+    rest 250ms
+    print "hello {count}"
+    print "count: " + count
+loop 4 as outer:
+    for i in 0..4:
+        if i == 2:
+            break outer
+        else if i > 2:
+            continue
+        else:
+            .kit.hat
+call verse
+call fill = "x-x-" with { velocity: 0.7 }
+spawn verse
+function build(a, b):
+    return a
+at bar 3:
+    mark "drop 2"
+at 1:23.5:
+    mark chorus
+on beat(4) once
+    emit hit { note: "C4", velocity: 100 }
+automate lead mode global:
+    param cutoff curve $curve.in loop 1 bar {
+        0% = 200 hold
+        1/4 = 800 step
+        50% = 400 $curve.out
+        100% = 200
+    }
+automate lead.cutoff: 0.3 + 0.2 * sin($time * 2)
+scene main = [verse, drums]
+switch main over 16 beats
+snapshot save calm
+snapshot morph calm -> wild over 8 beats curve smooth
+snapshot morph wild -> calm
+routing:
+    node bass = lead
+    fx bass -> reverb({ size: 0.4 })
+    route bass to master with gain(0.5)
+    duck kit to bass with compressor({ ratio: 4 })
+    duck pads by kick amount 0.6 attack 5ms release 1/16
+    strip kit gain 0.8 pan -0.2 invert lowcut 80hz
+duck bass by kit
+route kit -> outputs 3-4
+route bass -> outputs 5
+strip bass lowcut 40hz
+bind controller -> lead with { channel: 1 }
+lead.cutoff = 800
+metronome on stem
+@persist ratio, loud
+export { lead, groove }
+"#;
+    assert_round_trip(source, Path::new("song.deva"));
+}
+
+#[test]
+fn test_comments_and_blank_lines_are_kept() {
+    let source = "# Intro\nbpm 120\n\n# Drums\nloop 2:\n    # four on the floor\n    .kit.kick 1/4\n\n# end\n";
+    let printed = assert_round_trip(source, Path::new("song.deva"));
+
+    assert_eq!(printed, source);
+}
+
+#[test]
+fn test_unparsed_lines_are_copied() {
+    let source = "bpm 120\nprnt \"x\"\n";
+    let printed = assert_round_trip(source, Path::new("song.deva"));
+
+    assert!(printed.contains("prnt \"x\""), "{printed}");
+}
+
+#[test]
+fn test_paths_are_relative_to_the_printed_file() {
+    let path = PathBuf::from("project").join("song.deva");
+    let source = "load \"./kick.wav\" as kick\nimport { lead } from \"./shared.deva\"\n";
+    let printed = assert_round_trip(source, &path);
+
+    assert_eq!(
+        printed,
+        "load \"./kick.wav\" as kick\nimport { lead } from \"./shared.deva\"\n"
+    );
+}
+
+#[test]
+fn test_print_statements_uses_canonical_spellings() {
+    let source = "tempo 90\nwait 1 beat\nlead -> vel(90)\nloop:\n    .kit.kick 1/4\nduck pads by kick release 150ms amount 60%\n";
+    let statements = SimpleParser::parse(source, PathBuf::from("song.deva")).unwrap();
+
+    assert_eq!(
+        print_statements(&statements),
+        "bpm 90\nsleep 1 beat\nlead -> velocity(90)\nloop:\n    .kit.kick 1/4\nduck pads by kick amount 0.6 release 150ms\n"
+    );
+}
+
+#[test]
+fn test_durations_print_as_they_are_read() {
+    assert_eq!(duration_text(&DurationValue::Beats(0.25)), "1/4");
+    assert_eq!(duration_text(&DurationValue::Beats(1.0 / 3.0)), "1/3");
+    assert_eq!(duration_text(&DurationValue::Beats(8.0)), "2 bars");
+    assert_eq!(duration_text(&DurationValue::Beats(1.5)), "1.5 beats");
+    assert_eq!(duration_text(&DurationValue::Milliseconds(250.0)), "250ms");
+    assert_eq!(duration_text(&DurationValue::Auto), "auto");
+}
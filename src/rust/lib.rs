@@ -6,6 +6,9 @@ pub mod language;
 pub mod shared;
 pub mod utils;
 
+/// Embedding facade: evaluate sources, query variables, pull events and render audio
+pub use engine::embed::{CellChanges, DevalangEngine, EngineBuilder};
+
 // Plugin development SDK (available with "plugin" feature)
 #[cfg(feature = "plugin")]
 pub mod plugin {
@@ -16,8 +19,15 @@ pub mod plugin {
 
     pub use crate::engine::plugin::bindings::*;
 
+    /// Sample editing primitives (reverse, gain, fades, pitch, speed, stretch), the same
+    /// functions the host applies
+    pub use crate::engine::audio::dsp;
+
     // Re-export macros from crate root (they are exported there due to #[macro_export])
-    pub use crate::{export_plugin, export_plugin_with_state, simple_oscillator_plugin};
+    pub use crate::{
+        export_plugin, export_plugin_with_context, export_plugin_with_state,
+        simple_oscillator_plugin,
+    };
 }
 
 // CLI-specific modules (requires terminal, file system, etc.)
@@ -43,6 +53,7 @@ pub mod web {
 
     pub mod registry {
         pub mod samples {
+            pub const REGISTRY_SAMPLE_RATE: u32 = 44100;
             pub fn get_sample(_name: &str) -> Option<Vec<f32>> {
                 None
             }
@@ -116,6 +127,19 @@ pub mod web {
                 _severity: String,
             ) {
             }
+            pub fn push_parse_error_with_span(
+                _message: String,
+                _line: usize,
+                _span: &crate::language::syntax::ast::SourceSpan,
+                _error_type: String,
+            ) {
+            }
+            pub fn push_runtime_warning(
+                _rule: &str,
+                _message: String,
+                _location: Option<(usize, usize)>,
+            ) {
+            }
         }
         pub mod banks {
             pub struct BankEntry {
@@ -139,6 +163,7 @@ pub mod midly {
     pub struct Header;
     pub enum Format {
         SingleTrack,
+        Parallel,
     }
     pub enum Timing {
         Metrical(u16),
@@ -198,6 +223,16 @@ pub mod midly {
     pub enum MidiMessage {
         NoteOn { key: u7, vel: u7 },
         NoteOff { key: u7, vel: u7 },
+        Controller { controller: u7, value: u7 },
+        PitchBend { bend: PitchBend },
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct PitchBend(pub u16);
+    impl PitchBend {
+        pub fn from_f32(float: f32) -> Self {
+            PitchBend((8192.0 + float.clamp(-1.0, 1.0) * 8191.0) as u16)
+        }
     }
 
     pub struct Track(Vec<TrackEvent>);
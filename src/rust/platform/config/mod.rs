@@ -1,15 +1,24 @@
 #![cfg(feature = "cli")]
 
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use atty;
 use inquire;
 use serde::{Deserialize, Serialize};
 
-use crate::engine::audio::settings::{AudioBitDepth, AudioChannels, AudioFormat, ResampleQuality};
+use crate::engine::audio::playback::osc::OscSettings;
+use crate::engine::audio::settings::{
+    AudioBitDepth, AudioChannels, AudioFormat, DEFAULT_BLOCK_SIZE, MixPrecision, MixSettings,
+    ResampleQuality,
+};
+
+pub mod schema;
+
+use schema::ConfigIssue;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -19,6 +28,11 @@ pub struct AppConfig {
     pub audio: AudioSection,
     pub live: LiveSection,
     pub rules: RulesSection,
+    pub banks: BanksSection,
+    pub hooks: HooksSection,
+    /// Version constraints for installed addons, e.g. `devaloop.808 = "^1.2"`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub addons: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,12 +58,73 @@ pub struct AudioSection {
     pub sample_rate: u32,
     pub resample_quality: String,
     pub bpm: f32,
+    /// Convert all bank samples to the project sample rate in the background after loading
+    pub preconvert_samples: bool,
+    /// Generate reversed, half- and double-speed copies of bank samples after loading and
+    /// keep them in `.deva/cache/samples` for later builds
+    pub sample_variants: bool,
+    /// Frames per block when processing insert effect chains
+    pub block_size: usize,
+    /// Mix accumulator: "f32" (default) or "f64"
+    pub mix_precision: String,
+    /// Crossfade (ms) when a sample retriggers while it still rings; 0 turns it off
+    pub retrigger_fade_ms: u32,
+    /// Cut trailing silence from rendered audio, ending at a zero crossing
+    pub auto_trim: bool,
+    /// Render block by block and write audio files as they fill, for pieces too long
+    /// to hold in memory. Output is not peak-normalized in this mode.
+    pub stream: bool,
+    /// Metadata written into FLAC and ALAC exports (`title`, `artist`, `album`, ...)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct LiveSection {
     pub crossfade_ms: u64,
+    /// Output device name used by `devalang play` (default output when unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Output buffer size in frames
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buffer_size: Option<u32>,
+    /// Output channels to open the device with, for `route ... -> outputs` stems
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_channels: Option<u16>,
+    /// Mirror playhead, meters and section changes to OSC during live playback
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub osc: Option<OscSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OscSection {
+    pub host: String,
+    pub port: u16,
+    /// Minimum milliseconds between two messages on the same OSC address
+    pub throttle_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanksSection {
+    /// Bank that triggers named without a bank (`.kick`) fall back to, after the
+    /// project's local banks and before other installed banks, e.g. `devaloop.909`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// Shell commands `devalang build` runs when it finishes (see `services::build::hooks`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksSection {
+    /// Run after a successful build, with the artifact paths in `DEVALANG_*` variables
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_build_success: Option<String>,
+    /// Run after a failed build, with the error in `DEVALANG_ERROR`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_build_failure: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +142,9 @@ pub struct RulesSection {
     pub implicit_type_conversion: RuleLevel,
     #[serde(default)]
     pub unused_variables: RuleLevel,
+    /// Bank-less triggers that more than one bank provides
+    #[serde(default)]
+    pub ambiguous_triggers: RuleLevel,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -140,6 +218,9 @@ impl Default for AppConfig {
             audio: AudioSection::default(),
             live: LiveSection::default(),
             rules: RulesSection::default(),
+            banks: BanksSection::default(),
+            hooks: HooksSection::default(),
+            addons: BTreeMap::new(),
         }
     }
 }
@@ -170,13 +251,37 @@ impl Default for AudioSection {
             sample_rate: 44_100,
             resample_quality: "sinc24".to_string(),
             bpm: 120.0,
+            preconvert_samples: false,
+            sample_variants: false,
+            block_size: DEFAULT_BLOCK_SIZE,
+            mix_precision: "f32".to_string(),
+            retrigger_fade_ms: crate::engine::audio::retrigger::DEFAULT_RETRIGGER_FADE_MS,
+            auto_trim: false,
+            stream: false,
+            tags: BTreeMap::new(),
         }
     }
 }
 
 impl Default for LiveSection {
     fn default() -> Self {
-        Self { crossfade_ms: 50 }
+        Self {
+            crossfade_ms: 50,
+            device: None,
+            buffer_size: None,
+            output_channels: None,
+            osc: None,
+        }
+    }
+}
+
+impl Default for OscSection {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 9000,
+            throttle_ms: 33,
+        }
     }
 }
 
@@ -189,6 +294,7 @@ impl Default for RulesSection {
             missing_duration: RuleLevel::Info,
             implicit_type_conversion: RuleLevel::Info,
             unused_variables: RuleLevel::Warning,
+            ambiguous_triggers: RuleLevel::Warning,
         }
     }
 }
@@ -233,6 +339,28 @@ impl AppConfig {
         }
     }
 
+    /// The config file `load` reads in `root`, without prompting when there are several:
+    /// `devalang.toml`, then `devalang.json`, then `.devalang`
+    pub fn find_path(root: impl AsRef<Path>) -> Option<PathBuf> {
+        let candidates: Vec<PathBuf> = ["devalang.json", ".devalang", "devalang.toml"]
+            .iter()
+            .map(|name| root.as_ref().join(name))
+            .filter(|path| path.exists())
+            .collect();
+        (!candidates.is_empty()).then(|| pick_config_priority(&candidates))
+    }
+
+    /// Check the config file at `path` against the schema. Returns the problems found and
+    /// the effective config, with the offending settings left at their defaults.
+    pub fn inspect(path: &Path) -> Result<(AppConfig, Vec<ConfigIssue>)> {
+        let mut raw = read_raw(path)?;
+        let issues = schema::validate(&raw);
+        schema::strip_invalid(&mut raw);
+        let config = serde_json::from_value(raw)
+            .with_context(|| format!("invalid config: {}", path.display()))?;
+        Ok((config, issues))
+    }
+
     pub fn entry_path(&self, root: impl AsRef<Path>) -> PathBuf {
         root.as_ref().join(&self.paths.entry)
     }
@@ -282,6 +410,30 @@ impl AppConfig {
         }
     }
 
+    /// Mixing options; an unknown `mix_precision` is an error rather than f32
+    pub fn mix_settings(&self) -> Result<MixSettings> {
+        let Some(precision) = MixPrecision::parse(&self.audio.mix_precision) else {
+            bail!(
+                "unknown audio.mix_precision '{}' (expected f32 or f64)",
+                self.audio.mix_precision
+            );
+        };
+        Ok(MixSettings {
+            block_size: self.audio.block_size.clamp(16, 65_536),
+            precision,
+            retrigger_fade_ms: self.audio.retrigger_fade_ms.min(100),
+        })
+    }
+
+    /// OSC output for live playback, when a `[live.osc]` section is configured
+    pub fn osc_settings(&self) -> Option<OscSettings> {
+        self.live.osc.as_ref().map(|osc| OscSettings {
+            host: osc.host.clone(),
+            port: osc.port,
+            throttle: std::time::Duration::from_millis(osc.throttle_ms),
+        })
+    }
+
     pub fn crossfade_ms(&self) -> u64 {
         self.live.crossfade_ms.max(10)
     }
@@ -291,65 +443,57 @@ impl AppConfig {
     }
 }
 
-fn load_json(path: &Path) -> Result<AppConfig> {
-    let file = fs::read_to_string(path)
+/// Parse a config file into a JSON value, whatever its syntax, so it can be checked
+/// against the schema before serde fills in defaults
+fn read_raw(path: &Path) -> Result<serde_json::Value> {
+    let raw = fs::read_to_string(path)
         .with_context(|| format!("failed to read config: {}", path.display()))?;
-    let config = serde_json::from_str(&file)
-        .with_context(|| format!("invalid JSON config: {}", path.display()))?;
-    Ok(config)
-}
 
-fn load_toml(path: &Path) -> Result<AppConfig> {
-    let file = fs::read_to_string(path)
-        .with_context(|| format!("failed to read config: {}", path.display()))?;
-    let config = toml::from_str(&file)
-        .with_context(|| format!("invalid TOML config: {}", path.display()))?;
-    Ok(config)
-}
-
-fn load_config_by_path(path: &Path) -> Result<AppConfig> {
     // If filename is exactly ".devalang", try to detect JSON vs TOML by content
-    if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-        if name == ".devalang" {
-            let raw = fs::read_to_string(path)
-                .with_context(|| format!("failed to read config: {}", path.display()))?;
-            let trimmed = raw.trim_start();
-            if trimmed.starts_with('{') || trimmed.starts_with('[') {
-                let cfg = serde_json::from_str(&raw)
-                    .with_context(|| format!("invalid JSON config: {}", path.display()))?;
-                return Ok(cfg);
-            } else {
-                let cfg = toml::from_str(&raw)
-                    .with_context(|| format!("invalid TOML config: {}", path.display()))?;
-                return Ok(cfg);
-            }
-        }
+    if path.file_name().and_then(|s| s.to_str()) == Some(".devalang") {
+        let trimmed = raw.trim_start();
+        return if trimmed.starts_with('{') || trimmed.starts_with('[') {
+            parse_json(&raw, path)
+        } else {
+            parse_toml(&raw, path)
+        };
     }
 
     // otherwise choose by extension
-    if let Some(ext) = path
+    let ext = path
         .extension()
         .and_then(|s| s.to_str())
-        .map(|s| s.to_lowercase())
-    {
-        match ext.as_str() {
-            "json" => load_json(path),
-            "toml" => load_toml(path),
-            _ => {
-                // default: try json then toml
-                match load_json(path) {
-                    Ok(cfg) => Ok(cfg),
-                    Err(_) => load_toml(path),
-                }
-            }
-        }
-    } else {
+        .map(|s| s.to_lowercase());
+    match ext.as_deref() {
+        Some("json") => parse_json(&raw, path),
+        Some("toml") => parse_toml(&raw, path),
         // default: try json then toml
-        match load_json(path) {
-            Ok(cfg) => Ok(cfg),
-            Err(_) => load_toml(path),
-        }
+        _ => parse_json(&raw, path).or_else(|_| parse_toml(&raw, path)),
+    }
+}
+
+fn parse_json(raw: &str, path: &Path) -> Result<serde_json::Value> {
+    serde_json::from_str(raw).with_context(|| format!("invalid JSON config: {}", path.display()))
+}
+
+fn parse_toml(raw: &str, path: &Path) -> Result<serde_json::Value> {
+    let value: toml::Value =
+        toml::from_str(raw).with_context(|| format!("invalid TOML config: {}", path.display()))?;
+    serde_json::to_value(value).with_context(|| format!("invalid TOML config: {}", path.display()))
+}
+
+fn load_config_by_path(path: &Path) -> Result<AppConfig> {
+    let raw = read_raw(path)?;
+    let issues = schema::validate(&raw);
+    if !issues.is_empty() {
+        let listed: Vec<String> = issues.iter().map(|issue| format!("  {}", issue)).collect();
+        bail!(
+            "invalid config {}:\n{}\nRun `devalang config validate` to see the effective settings",
+            path.display(),
+            listed.join("\n")
+        );
     }
+    serde_json::from_value(raw).with_context(|| format!("invalid config: {}", path.display()))
 }
 
 fn pick_config_priority(candidates: &[PathBuf]) -> PathBuf {
@@ -401,3 +545,7 @@ fn write_default_json(root: &Path, path: &Path, config: &AppConfig) -> Result<()
         .with_context(|| format!("unable to write config file: {}", path.display()))?;
     Ok(())
 }
+
+#[cfg(test)]
+#[path = "test_config.rs"]
+mod tests;
@@ -0,0 +1,268 @@
+//! Schema for `devalang.json` / `devalang.toml`
+//!
+//! Serde fills every missing or unreadable setting with its default, so a typo like
+//! `bit_dept = 24` used to be dropped without a word. The raw file is checked against
+//! this table first: unknown keys (with a suggestion for the closest known one), values
+//! of the wrong type and values outside the accepted set are all reported.
+
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::language::syntax::parser::driver::find_keyword_suggestion;
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Text,
+    Integer,
+    Number,
+    Flag,
+    /// One of the listed strings (case-insensitive)
+    Choice(&'static [&'static str]),
+    /// One of the listed integers
+    IntChoice(&'static [i64]),
+    /// A format name or a list of them
+    Formats,
+    Table(&'static [(&'static str, Kind)]),
+    /// Free-form keys with string values
+    StringMap,
+}
+
+const FORMATS: &[&str] = &["mp3", "wav", "flac", "alac", "m4a", "mid", "midi"];
+const RESAMPLE_QUALITIES: &[&str] = &[
+    "linear2", "linear", "2", "sinc12", "sinc24", "sinc48", "sinc96", "sinc192", "sinc512",
+];
+const MIX_PRECISIONS: &[&str] = &["f32", "f64", "double", "64"];
+const RULE_LEVEL: Kind = Kind::Choice(&["error", "warning", "info", "off"]);
+
+const PROJECT: &[(&str, Kind)] = &[("name", Kind::Text)];
+const PATHS: &[(&str, Kind)] = &[("entry", Kind::Text), ("output", Kind::Text)];
+const AUDIO: &[(&str, Kind)] = &[
+    ("format", Kind::Formats),
+    ("bit_depth", Kind::IntChoice(&[8, 16, 24, 32])),
+    ("channels", Kind::IntChoice(&[1, 2])),
+    ("sample_rate", Kind::Integer),
+    ("resample_quality", Kind::Choice(RESAMPLE_QUALITIES)),
+    ("bpm", Kind::Number),
+    ("preconvert_samples", Kind::Flag),
+    ("sample_variants", Kind::Flag),
+    ("block_size", Kind::Integer),
+    ("mix_precision", Kind::Choice(MIX_PRECISIONS)),
+    ("retrigger_fade_ms", Kind::Integer),
+    ("auto_trim", Kind::Flag),
+    ("stream", Kind::Flag),
+    ("tags", Kind::StringMap),
+];
+const OSC: &[(&str, Kind)] = &[
+    ("host", Kind::Text),
+    ("port", Kind::Integer),
+    ("throttle_ms", Kind::Integer),
+];
+const LIVE: &[(&str, Kind)] = &[
+    ("crossfade_ms", Kind::Integer),
+    ("device", Kind::Text),
+    ("buffer_size", Kind::Integer),
+    ("output_channels", Kind::Integer),
+    ("osc", Kind::Table(OSC)),
+];
+const RULES: &[(&str, Kind)] = &[
+    ("explicit_durations", RULE_LEVEL),
+    ("deprecated_syntax", RULE_LEVEL),
+    ("var_keyword", RULE_LEVEL),
+    ("missing_duration", RULE_LEVEL),
+    ("implicit_type_conversion", RULE_LEVEL),
+    ("unused_variables", RULE_LEVEL),
+    ("ambiguous_triggers", RULE_LEVEL),
+];
+const BANKS: &[(&str, Kind)] = &[("default", Kind::Text)];
+const HOOKS: &[(&str, Kind)] = &[
+    ("on_build_success", Kind::Text),
+    ("on_build_failure", Kind::Text),
+];
+const ROOT: &[(&str, Kind)] = &[
+    ("project", Kind::Table(PROJECT)),
+    ("paths", Kind::Table(PATHS)),
+    ("audio", Kind::Table(AUDIO)),
+    ("live", Kind::Table(LIVE)),
+    ("rules", Kind::Table(RULES)),
+    ("banks", Kind::Table(BANKS)),
+    ("hooks", Kind::Table(HOOKS)),
+    ("addons", Kind::StringMap),
+];
+
+/// One problem found in a config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Dotted key path, e.g. `audio.bit_depth`
+    pub path: String,
+    pub message: String,
+    /// Closest accepted key or value, when one is near enough
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean `{}`?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check a parsed config file (TOML files are converted to JSON values first)
+pub fn validate(config: &Value) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    check(config, Kind::Table(ROOT), "", &mut issues);
+    issues
+}
+
+/// Drop every setting `validate` objects to, so the rest of the file still deserializes
+/// and the dropped ones fall back to their defaults
+pub fn strip_invalid(config: &mut Value) {
+    if !keep_valid(config, Kind::Table(ROOT)) {
+        *config = Value::Object(Default::default());
+    }
+}
+
+fn keep_valid(value: &mut Value, kind: Kind) -> bool {
+    match (kind, value) {
+        (Kind::Table(fields), Value::Object(entries)) => {
+            entries.retain(|key, item| {
+                fields
+                    .iter()
+                    .find(|(name, _)| name == key)
+                    .is_some_and(|(_, field)| keep_valid(item, *field))
+            });
+            true
+        }
+        (Kind::StringMap, Value::Object(entries)) => {
+            entries.retain(|_, item| item.is_string());
+            true
+        }
+        // Unknown format names are skipped when the formats are resolved
+        (Kind::Formats, Value::String(_)) => true,
+        (Kind::Formats, Value::Array(items)) => items.iter().all(Value::is_string),
+        (kind, value) => {
+            let mut issues = Vec::new();
+            check(value, kind, "", &mut issues);
+            issues.is_empty()
+        }
+    }
+}
+
+fn check(value: &Value, kind: Kind, path: &str, issues: &mut Vec<ConfigIssue>) {
+    let mut report = |message: String, suggestion: Option<String>| {
+        issues.push(ConfigIssue {
+            path: if path.is_empty() {
+                "<root>".to_string()
+            } else {
+                path.to_string()
+            },
+            message,
+            suggestion,
+        })
+    };
+
+    match kind {
+        Kind::Text if !value.is_string() => report(expected("a string", value), None),
+        Kind::Integer if !(value.is_i64() || value.is_u64()) => {
+            report(expected("an integer", value), None)
+        }
+        Kind::Number if !value.is_number() => report(expected("a number", value), None),
+        Kind::Flag if !value.is_boolean() => report(expected("true or false", value), None),
+        Kind::Choice(choices) => match value.as_str() {
+            Some(text) => check_choice(text, choices, &mut report),
+            None => report(expected(&one_of(choices), value), None),
+        },
+        Kind::IntChoice(choices) => {
+            let listed: Vec<String> = choices.iter().map(|c| c.to_string()).collect();
+            let listed: Vec<&str> = listed.iter().map(String::as_str).collect();
+            match value.as_i64() {
+                Some(number) if choices.contains(&number) => {}
+                Some(number) => report(format!("`{}` is not {}", number, one_of(&listed)), None),
+                None => report(expected(&one_of(&listed), value), None),
+            }
+        }
+        Kind::Formats => match value {
+            Value::String(text) => check_choice(text, FORMATS, &mut report),
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    check(
+                        item,
+                        Kind::Choice(FORMATS),
+                        &format!("{}[{}]", path, index),
+                        issues,
+                    );
+                }
+            }
+            _ => report(expected("a format name or a list of them", value), None),
+        },
+        Kind::StringMap => match value.as_object() {
+            Some(entries) => {
+                for (key, item) in entries {
+                    check(item, Kind::Text, &join(path, key), issues);
+                }
+            }
+            None => report(expected("a table", value), None),
+        },
+        Kind::Table(fields) => {
+            let Some(entries) = value.as_object() else {
+                report(expected("a table", value), None);
+                return;
+            };
+            let names: Vec<&str> = fields.iter().map(|(name, _)| *name).collect();
+            for (key, item) in entries {
+                match fields.iter().find(|(name, _)| name == key) {
+                    Some((_, field)) => check(item, *field, &join(path, key), issues),
+                    None => issues.push(ConfigIssue {
+                        path: join(path, key),
+                        message: "unknown key".to_string(),
+                        suggestion: find_keyword_suggestion(key, &names),
+                    }),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_choice(text: &str, choices: &[&str], report: &mut impl FnMut(String, Option<String>)) {
+    let lowered = text.to_lowercase();
+    if !choices.contains(&lowered.as_str()) {
+        report(
+            format!("`{}` is not {}", text, one_of(choices)),
+            find_keyword_suggestion(&lowered, choices),
+        );
+    }
+}
+
+fn one_of(choices: &[&str]) -> String {
+    let quoted: Vec<String> = choices.iter().map(|c| format!("`{}`", c)).collect();
+    format!("one of {}", quoted.join(", "))
+}
+
+fn expected(what: &str, found: &Value) -> String {
+    let found = match found {
+        Value::Null => "nothing",
+        Value::Bool(_) => "a boolean",
+        Value::Number(n) if n.is_f64() => "a decimal number",
+        Value::Number(_) => "an integer",
+        Value::String(_) => "a string",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "a table",
+    };
+    format!("expected {}, found {}", what, found)
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+#[cfg(test)]
+#[path = "test_schema.rs"]
+mod tests;
@@ -0,0 +1,12 @@
+use super::*;
+
+#[test]
+fn test_mix_settings_reject_unknown_precision() {
+    let mut config = AppConfig::default();
+    config.audio.mix_precision = "Double".to_string();
+    assert_eq!(config.mix_settings().unwrap().precision, MixPrecision::F64);
+
+    config.audio.mix_precision = "f46".to_string();
+    let error = config.mix_settings().unwrap_err().to_string();
+    assert!(error.contains("f46"), "{}", error);
+}
@@ -0,0 +1,45 @@
+use super::*;
+use crate::platform::config::AppConfig;
+use serde_json::json;
+
+#[test]
+fn test_default_config_is_valid() {
+    let defaults = serde_json::to_value(AppConfig::default()).unwrap();
+    assert_eq!(validate(&defaults), Vec::new());
+}
+
+#[test]
+fn test_reports_typos_types_and_values() {
+    let config = json!({
+        "audio": { "bit_dept": 24, "bpm": "fast", "format": ["wav", "flak"], "channels": 6 },
+        "rules": { "unused_variables": "warnig" },
+        "live": { "osc": { "port": "9000" } }
+    });
+    let issues: Vec<String> = validate(&config).iter().map(|i| i.to_string()).collect();
+    assert_eq!(
+        issues,
+        vec![
+            "audio.bit_dept: unknown key (did you mean `bit_depth`?)",
+            "audio.bpm: expected a number, found a string",
+            "audio.channels: `6` is not one of `1`, `2`",
+            "audio.format[1]: `flak` is not one of `mp3`, `wav`, `flac`, `alac`, `m4a`, `mid`, `midi` (did you mean `flac`?)",
+            "live.osc.port: expected an integer, found a string",
+            "rules.unused_variables: `warnig` is not one of `error`, `warning`, `info`, `off` (did you mean `warning`?)",
+        ]
+    );
+}
+
+#[test]
+fn test_strip_invalid_keeps_readable_settings() {
+    let mut config = json!({
+        "audio": { "bpm": "fast", "sample_rate": 48000, "bit_dept": 24 },
+        "addons": { "devaloop.808": "^1.2", "devaloop.909": 2 }
+    });
+    strip_invalid(&mut config);
+    assert_eq!(
+        config,
+        json!({ "audio": { "sample_rate": 48000 }, "addons": { "devaloop.808": "^1.2" } })
+    );
+    let effective: AppConfig = serde_json::from_value(config).unwrap();
+    assert_eq!(effective.audio.sample_rate, 48000);
+}
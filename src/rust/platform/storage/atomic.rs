@@ -0,0 +1,59 @@
+//! Atomic artifact writes: each file is written beside its destination and renamed over
+//! it once complete, so players, diffs and other builds never read a half-written file.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Temp file standing in for `path` until `commit` renames it into place; removed if
+/// dropped first (a failed write leaves the previous artifact untouched)
+#[derive(Debug)]
+pub struct PendingFile {
+    temp: PathBuf,
+    path: PathBuf,
+    committed: bool,
+}
+
+impl PendingFile {
+    pub fn new(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self {
+            temp: path.with_file_name(format!(".{}.{}.tmp", name, std::process::id())),
+            path: path.to_path_buf(),
+            committed: false,
+        }
+    }
+
+    /// Where the contents are written until `commit`
+    pub fn temp(&self) -> &Path {
+        &self.temp
+    }
+
+    pub fn commit(mut self) -> io::Result<()> {
+        fs::rename(&self.temp, &self.path)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for PendingFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temp);
+        }
+    }
+}
+
+/// `fs::write`, replacing `path` in one step
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let pending = PendingFile::new(path);
+    fs::write(pending.temp(), contents)?;
+    pending.commit()
+}
+
+#[cfg(test)]
+#[path = "test_atomic.rs"]
+mod tests;
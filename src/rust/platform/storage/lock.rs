@@ -0,0 +1,201 @@
+//! Project lock (`.deva/lock`): one build at a time per project
+//!
+//! Builds and play sessions hold an OS advisory lock on the file while they render, so a
+//! live rebuild and a manual `devalang build` never write the same outputs at once. The
+//! lock goes away with its process, even after a crash; the file only records who holds
+//! it for the message shown to the process that has to wait.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const LOCK_FILE: &str = "lock";
+
+/// How often a waiting process retries the lock
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What to do when another process holds the lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LockPolicy {
+    /// Give up right away, naming the holder
+    #[default]
+    Fail,
+    /// Wait for the holder to finish, at most this long when set (`--wait-lock`)
+    Wait(Option<Duration>),
+    /// Take the lock over from a stuck holder (`--steal-lock`)
+    Steal,
+}
+
+impl LockPolicy {
+    /// Policy chosen by `--wait-lock [SECONDS]` and `--steal-lock`
+    pub fn from_flags(wait: Option<Option<u64>>, steal: bool) -> Self {
+        match (wait, steal) {
+            (_, true) => LockPolicy::Steal,
+            (Some(seconds), false) => LockPolicy::Wait(seconds.map(Duration::from_secs)),
+            (None, false) => LockPolicy::Fail,
+        }
+    }
+}
+
+/// Process holding the lock, as recorded in the lock file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub command: String,
+    /// Seconds since the Unix epoch when the lock was taken
+    pub since: u64,
+}
+
+impl LockHolder {
+    fn current(command: &str) -> Self {
+        Self {
+            pid: std::process::id(),
+            command: command.to_string(),
+            since: unix_now(),
+        }
+    }
+
+    /// `devalang build (pid 4242, for 12s)`
+    pub fn describe(&self) -> String {
+        format!(
+            "{} (pid {}, for {}s)",
+            self.command,
+            self.pid,
+            unix_now().saturating_sub(self.since)
+        )
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+fn describe(holder: Option<&LockHolder>) -> String {
+    holder
+        .map(LockHolder::describe)
+        .unwrap_or_else(|| "another devalang process".to_string())
+}
+
+/// Held project lock; released when dropped
+#[derive(Debug)]
+pub struct ProjectLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl ProjectLock {
+    /// Lock `deva_dir/lock` for `command`. `on_contended` hears who holds it before this
+    /// process starts waiting or takes it over.
+    pub fn acquire(
+        deva_dir: &Path,
+        command: &str,
+        policy: LockPolicy,
+        mut on_contended: impl FnMut(Option<&LockHolder>),
+    ) -> Result<Self> {
+        fs::create_dir_all(deva_dir)
+            .with_context(|| format!("failed to create {}", deva_dir.display()))?;
+        let path = deva_dir.join(LOCK_FILE);
+        let deadline = match policy {
+            LockPolicy::Wait(Some(timeout)) => Some(Instant::now() + timeout),
+            _ => None,
+        };
+        let mut announced = false;
+
+        loop {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .with_context(|| format!("failed to open project lock {}", path.display()))?;
+            match file.try_lock() {
+                // A steal may have replaced the file while this one was waited on
+                Ok(()) if !is_current(&file, &path) => continue,
+                Ok(()) => {
+                    let mut lock = Self { file, path };
+                    lock.record(&LockHolder::current(command))?;
+                    return Ok(lock);
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Error(error)) => {
+                    return Err(error)
+                        .with_context(|| format!("failed to lock {}", path.display()));
+                }
+            }
+
+            let holder = read_holder(&path);
+            match policy {
+                LockPolicy::Fail => bail!(
+                    "{} is already building this project; pass --wait-lock to wait for it or --steal-lock to take over ({})",
+                    describe(holder.as_ref()),
+                    path.display()
+                ),
+                LockPolicy::Steal => {
+                    on_contended(holder.as_ref());
+                    fs::remove_file(&path).with_context(|| {
+                        format!("failed to take over project lock {}", path.display())
+                    })?;
+                }
+                LockPolicy::Wait(_) => {
+                    if !announced {
+                        on_contended(holder.as_ref());
+                        announced = true;
+                    }
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        bail!(
+                            "timed out waiting for {} to finish building this project",
+                            describe(holder.as_ref())
+                        );
+                    }
+                    std::thread::sleep(RETRY_INTERVAL);
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, holder: &LockHolder) -> Result<()> {
+        let json = serde_json::to_string(holder)?;
+        self.file.set_len(0)?;
+        self.file
+            .write_all(json.as_bytes())
+            .with_context(|| format!("failed to write project lock {}", self.path.display()))
+    }
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        // The OS lock goes with the handle; clear the holder so nobody reads a stale one
+        let _ = self.file.set_len(0);
+    }
+}
+
+/// Holder recorded in the lock file, if it can be read
+pub fn read_holder(path: &Path) -> Option<LockHolder> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(held), Ok(current)) => held.dev() == current.dev() && held.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+// No file identity to compare here; a steal racing a waiter goes unnoticed
+#[cfg(not(unix))]
+fn is_current(_file: &File, _path: &Path) -> bool {
+    true
+}
+
+#[cfg(test)]
+#[path = "test_lock.rs"]
+mod tests;
@@ -1 +1,6 @@
-pub fn placeholder() {}
+//! Files the CLI shares with other devalang processes working on the same project
+
+#[cfg(feature = "cli")]
+pub mod atomic;
+#[cfg(feature = "cli")]
+pub mod lock;
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn test_write_atomic_replaces_the_file_without_leftovers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("song.json");
+    fs::write(&path, "old").unwrap();
+    write_atomic(&path, "new").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn test_uncommitted_file_keeps_the_previous_artifact() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("song.wav");
+    fs::write(&path, "old").unwrap();
+    let pending = PendingFile::new(&path);
+    fs::write(pending.temp(), "half").unwrap();
+    drop(pending);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
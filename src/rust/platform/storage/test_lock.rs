@@ -0,0 +1,56 @@
+use super::*;
+
+#[test]
+fn test_second_holder_fails_waits_or_steals() {
+    let dir = tempfile::tempdir().unwrap();
+    let held = ProjectLock::acquire(dir.path(), "devalang play", LockPolicy::Fail, |_| {}).unwrap();
+    let holder = read_holder(&dir.path().join(LOCK_FILE)).unwrap();
+    assert_eq!(holder.pid, std::process::id());
+    assert_eq!(holder.command, "devalang play");
+
+    let error = ProjectLock::acquire(dir.path(), "devalang build", LockPolicy::Fail, |_| {})
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("devalang play (pid"), "{error}");
+
+    let mut waited_on = None;
+    let timeout = LockPolicy::Wait(Some(Duration::from_millis(150)));
+    assert!(
+        ProjectLock::acquire(dir.path(), "devalang build", timeout, |holder| {
+            waited_on = holder.cloned()
+        })
+        .is_err()
+    );
+    assert_eq!(waited_on, Some(holder.clone()));
+
+    let mut stolen_from = None;
+    let stolen = ProjectLock::acquire(dir.path(), "devalang build", LockPolicy::Steal, |holder| {
+        stolen_from = holder.cloned()
+    })
+    .unwrap();
+    assert_eq!(stolen_from, Some(holder));
+    drop(held);
+    drop(stolen);
+    assert!(read_holder(&dir.path().join(LOCK_FILE)).is_none());
+}
+
+#[test]
+fn test_lock_is_free_again_once_dropped() {
+    let dir = tempfile::tempdir().unwrap();
+    drop(ProjectLock::acquire(dir.path(), "devalang build", LockPolicy::Fail, |_| {}).unwrap());
+    assert!(ProjectLock::acquire(dir.path(), "devalang build", LockPolicy::Fail, |_| {}).is_ok());
+}
+
+#[test]
+fn test_policy_from_flags() {
+    assert_eq!(LockPolicy::from_flags(None, false), LockPolicy::Fail);
+    assert_eq!(
+        LockPolicy::from_flags(Some(None), false),
+        LockPolicy::Wait(None)
+    );
+    assert_eq!(
+        LockPolicy::from_flags(Some(Some(5)), false),
+        LockPolicy::Wait(Some(Duration::from_secs(5)))
+    );
+    assert_eq!(LockPolicy::from_flags(None, true), LockPolicy::Steal);
+}
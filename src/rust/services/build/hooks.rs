@@ -0,0 +1,113 @@
+//! Commands run after a build (`[hooks]` in the config):
+//!
+//! ```toml
+//! [hooks]
+//! on_build_success = "scp \"$DEVALANG_OUTPUT\" server:releases/"
+//! on_build_failure = "notify-send 'Build failed' \"$DEVALANG_ERROR\""
+//! ```
+//!
+//! Hooks run through the system shell in the project directory. The build's artifacts
+//! are passed in `DEVALANG_*` environment variables (see `success_env`, `failure_env`).
+
+use anyhow::{Result, bail};
+use std::path::Path;
+use std::process::Command;
+
+use super::pipeline::{BuildArtifacts, BuildRequest};
+
+/// Environment for `on_build_success`:
+/// - `DEVALANG_ENTRY`: the built `.deva` file
+/// - `DEVALANG_MODULE`: module name, as in the output file names
+/// - `DEVALANG_OUTPUT`: primary audio file
+/// - `DEVALANG_OUTPUT_DIR`: output root (`paths.output`)
+/// - `DEVALANG_OUTPUT_<FORMAT>`: file exported for each format, e.g. `DEVALANG_OUTPUT_WAV`
+/// - `DEVALANG_FORMATS`: exported formats, space-separated
+/// - `DEVALANG_AST`: AST dump
+/// - `DEVALANG_DURATION`: audio length in seconds
+/// - `DEVALANG_SHA256`: audio content hash (`--deterministic` builds only)
+pub fn success_env(request: &BuildRequest, artifacts: &BuildArtifacts) -> Vec<(String, String)> {
+    let path = |path: &Path| path.display().to_string();
+    let mut env = vec![
+        ("DEVALANG_ENTRY".to_string(), path(&request.entry_path)),
+        ("DEVALANG_MODULE".to_string(), artifacts.module_name.clone()),
+        (
+            "DEVALANG_OUTPUT".to_string(),
+            path(&artifacts.primary_audio_path),
+        ),
+        (
+            "DEVALANG_OUTPUT_DIR".to_string(),
+            path(&request.output_root),
+        ),
+        (
+            "DEVALANG_FORMATS".to_string(),
+            artifacts
+                .exported_formats
+                .iter()
+                .map(|(format, _)| format.label())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        ("DEVALANG_AST".to_string(), path(&artifacts.ast_path)),
+        (
+            "DEVALANG_DURATION".to_string(),
+            format!("{:.3}", artifacts.audio_length.as_secs_f64()),
+        ),
+    ];
+    for (format, file) in &artifacts.exported_formats {
+        env.push((
+            format!("DEVALANG_OUTPUT_{}", format.label().to_uppercase()),
+            path(file),
+        ));
+    }
+    if let Some(hash) = &artifacts.content_hash {
+        env.push(("DEVALANG_SHA256".to_string(), hash.clone()));
+    }
+    env
+}
+
+/// Environment for `on_build_failure`: `DEVALANG_ENTRY`, `DEVALANG_OUTPUT_DIR` and
+/// `DEVALANG_ERROR` (the error message)
+pub fn failure_env(request: &BuildRequest, error: &anyhow::Error) -> Vec<(String, String)> {
+    vec![
+        (
+            "DEVALANG_ENTRY".to_string(),
+            request.entry_path.display().to_string(),
+        ),
+        (
+            "DEVALANG_OUTPUT_DIR".to_string(),
+            request.output_root.display().to_string(),
+        ),
+        ("DEVALANG_ERROR".to_string(), format!("{:#}", error)),
+    ]
+}
+
+/// Run `command` through the shell in `dir` with `env` added; fails when it can't start
+/// or exits unsuccessfully
+pub fn run_hook(command: &str, dir: &Path, env: &[(String, String)]) -> Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let status = shell
+        .arg(command)
+        .current_dir(dir)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .status()
+        .map_err(|e| anyhow::anyhow!("could not run hook `{}`: {}", command, e))?;
+    if !status.success() {
+        match status.code() {
+            Some(code) => bail!("hook `{}` exited with status {}", command, code),
+            None => bail!("hook `{}` was terminated by a signal", command),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "test_hooks.rs"]
+mod tests;
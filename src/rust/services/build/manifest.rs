@@ -0,0 +1,404 @@
+//! Input manifest for `devalang build --check-only-if-changed`
+//!
+//! A build records a SHA-256 over everything that shapes its output (the module graph:
+//! entry, imported modules and loaded files; the banks it uses; the config file and the
+//! build settings) along with copies of the files it wrote, in
+//! `.deva/cache/builds/<entry>/`. A later build whose inputs hash the same restores those
+//! files instead of rendering, so CI can cache `.deva` and skip unchanged modules.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::engine::audio::samples::RateConversion;
+use crate::engine::audio::settings::{AudioFormat, ClickMode};
+use crate::engine::audio::tempo::TempoMap;
+use crate::engine::plugin::loader::plugin_files;
+use crate::language::addons::registry::BankRegistry;
+use crate::language::syntax::ast::{Statement, StatementKind};
+use crate::language::syntax::parser::driver::SimpleParser;
+use crate::platform::config::AppConfig;
+use crate::platform::storage::atomic::write_atomic;
+
+use super::pipeline::{BuildArtifacts, BuildRequest};
+
+/// Folder under `.deva` holding one manifest and output copies per entry file
+pub const BUILD_CACHE_DIR: &str = "cache/builds";
+
+const MANIFEST_FILE: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+
+/// What a build read and wrote, enough to replay its report without rendering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// SHA-256 of the build inputs (`input_hash`)
+    pub inputs: String,
+    pub module_name: String,
+    pub primary_format: String,
+    pub exported_formats: Vec<(String, PathBuf)>,
+    pub ast_path: PathBuf,
+    pub primary_audio_path: PathBuf,
+    pub rms: f32,
+    /// Seconds
+    pub audio_length: f64,
+    pub trimmed: f64,
+    pub content_hash: Option<String>,
+    /// Converted samples as (URI, native rate)
+    pub sample_conversions: Vec<(String, u32)>,
+    pub click: Option<(String, PathBuf)>,
+    /// Hardware output mix and its channel count
+    #[serde(default)]
+    pub outputs: Option<(PathBuf, u16)>,
+    pub markers: Vec<(f32, String)>,
+    #[serde(default)]
+    pub tempo_map: TempoMap,
+    /// Every file the build wrote; copies are kept in the cache, in this order
+    pub files: Vec<PathBuf>,
+}
+
+impl BuildManifest {
+    pub fn new(inputs: String, artifacts: &BuildArtifacts) -> Self {
+        let mut files: Vec<PathBuf> = artifacts
+            .exported_formats
+            .iter()
+            .map(|(_, path)| path.clone())
+            .collect();
+        files.push(artifacts.ast_path.clone());
+        files.extend(artifacts.click.iter().map(|(_, path)| path.clone()));
+        files.extend(artifacts.outputs.iter().map(|(path, _)| path.clone()));
+        files.extend(artifacts.extra_outputs.iter().cloned());
+        files.dedup();
+
+        Self {
+            inputs,
+            module_name: artifacts.module_name.clone(),
+            primary_format: artifacts.primary_format.label().to_string(),
+            exported_formats: artifacts
+                .exported_formats
+                .iter()
+                .map(|(format, path)| (format.label().to_string(), path.clone()))
+                .collect(),
+            ast_path: artifacts.ast_path.clone(),
+            primary_audio_path: artifacts.primary_audio_path.clone(),
+            rms: artifacts.rms,
+            audio_length: artifacts.audio_length.as_secs_f64(),
+            trimmed: artifacts.trimmed.as_secs_f64(),
+            content_hash: artifacts.content_hash.clone(),
+            sample_conversions: artifacts
+                .sample_conversions
+                .iter()
+                .map(|conversion| (conversion.uri.clone(), conversion.source_rate))
+                .collect(),
+            click: artifacts
+                .click
+                .as_ref()
+                .map(|(mode, path)| (format!("{:?}", mode).to_lowercase(), path.clone())),
+            outputs: artifacts.outputs.clone(),
+            markers: artifacts.markers.clone(),
+            tempo_map: artifacts.tempo_map.clone(),
+            files,
+        }
+    }
+
+    /// Artifacts of the recorded build, for a request with the same inputs
+    pub fn artifacts(
+        &self,
+        request: &BuildRequest,
+        statements: Vec<Statement>,
+        total_duration: Duration,
+    ) -> BuildArtifacts {
+        let format = |label: &str| AudioFormat::from_str(label).unwrap_or_default();
+        BuildArtifacts {
+            primary_format: format(&self.primary_format),
+            exported_formats: self
+                .exported_formats
+                .iter()
+                .map(|(label, path)| (format(label), path.clone()))
+                .collect(),
+            bit_depth: request.bit_depth,
+            channels: request.channels,
+            resample_quality: request.resample_quality,
+            sample_rate: request.sample_rate,
+            module_name: self.module_name.clone(),
+            statements,
+            ast_path: self.ast_path.clone(),
+            primary_audio_path: self.primary_audio_path.clone(),
+            rms: self.rms,
+            audio_render_time: Duration::ZERO,
+            audio_length: Duration::from_secs_f64(self.audio_length),
+            total_duration,
+            content_hash: self.content_hash.clone(),
+            sample_conversions: self
+                .sample_conversions
+                .iter()
+                .map(|(uri, source_rate)| RateConversion {
+                    uri: uri.clone(),
+                    source_rate: *source_rate,
+                    target_rate: request.sample_rate,
+                    quality: request.resample_quality,
+                })
+                .collect(),
+            fingerprint: Default::default(),
+            scene: None,
+            trimmed: Duration::from_secs_f64(self.trimmed),
+            click: self
+                .click
+                .as_ref()
+                .and_then(|(mode, path)| Some((ClickMode::parse(mode)?, path.clone()))),
+            outputs: self.outputs.clone(),
+            markers: self.markers.clone(),
+            tempo_map: self.tempo_map.clone(),
+            extra_outputs: self
+                .files
+                .iter()
+                .filter(|path| {
+                    **path != self.ast_path
+                        && !self.exported_formats.iter().any(|(_, file)| file == *path)
+                        && !self.click.iter().any(|(_, file)| file == *path)
+                        && !self.outputs.iter().any(|(file, _)| file == *path)
+                })
+                .cloned()
+                .collect(),
+            restored: true,
+        }
+    }
+}
+
+/// Cache folder of the manifest for `entry`
+pub fn entry_cache_dir(deva_dir: &Path, entry: &Path) -> PathBuf {
+    let entry = entry.canonicalize().unwrap_or_else(|_| entry.to_path_buf());
+    let digest = Sha256::digest(entry.to_string_lossy().as_bytes());
+    let key: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    let stem = entry
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "module".to_string());
+    deva_dir
+        .join(BUILD_CACHE_DIR)
+        .join(format!("{}-{}", stem, key))
+}
+
+/// The recorded manifest in `dir`, when it exists and every output copy is still there
+pub fn load(dir: &Path) -> Option<BuildManifest> {
+    let text = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    let manifest: BuildManifest = serde_json::from_str(&text).ok()?;
+    (0..manifest.files.len())
+        .all(|index| cached_file(dir, &manifest, index).is_file())
+        .then_some(manifest)
+}
+
+/// Record `manifest` in `dir` with copies of the files it lists
+pub fn save(dir: &Path, manifest: &BuildManifest) -> Result<()> {
+    // Drop copies left by an earlier build before writing the new ones
+    let files_dir = dir.join(FILES_DIR);
+    if files_dir.exists() {
+        fs::remove_dir_all(&files_dir)
+            .with_context(|| format!("failed to clear {}", files_dir.display()))?;
+    }
+    fs::create_dir_all(&files_dir)
+        .with_context(|| format!("failed to create {}", files_dir.display()))?;
+    for (index, file) in manifest.files.iter().enumerate() {
+        let copy = cached_file(dir, manifest, index);
+        fs::copy(file, &copy)
+            .with_context(|| format!("failed to cache build output {}", file.display()))?;
+    }
+    let json = serde_json::to_string_pretty(manifest).context("failed to serialize manifest")?;
+    write_atomic(&dir.join(MANIFEST_FILE), json)
+        .with_context(|| format!("unable to write build manifest in {}", dir.display()))
+}
+
+/// Put the recorded outputs back where the build wrote them
+pub fn restore(dir: &Path, manifest: &BuildManifest) -> Result<()> {
+    for (index, file) in manifest.files.iter().enumerate() {
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::copy(cached_file(dir, manifest, index), file)
+            .with_context(|| format!("failed to restore build output {}", file.display()))?;
+    }
+    Ok(())
+}
+
+fn cached_file(dir: &Path, manifest: &BuildManifest, index: usize) -> PathBuf {
+    let name = manifest.files[index]
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    dir.join(FILES_DIR).join(format!("{}-{}", index, name))
+}
+
+/// SHA-256 over the inputs of building `request` in `project_root`: the devalang version,
+/// the build settings, the config file, every module reachable from the entry through
+/// `import` with the files they `load`, the contents of the banks they use and the
+/// manifest and module of every plugin they `use`
+pub fn input_hash(request: &BuildRequest, project_root: &Path) -> Result<String> {
+    let mut hasher = InputHasher::default();
+    hasher.field("version", env!("CARGO_PKG_VERSION"));
+
+    // Maps are sorted so the same settings always hash the same
+    let mut settings = request.clone();
+    let overrides: BTreeMap<String, String> = std::mem::take(&mut settings.variable_overrides)
+        .into_iter()
+        .map(|(name, value)| (name, format!("{:?}", value)))
+        .collect();
+    let remaps: BTreeMap<String, String> = std::mem::take(&mut settings.bank_remaps)
+        .into_iter()
+        .collect();
+    hasher.field(
+        "settings",
+        format!("{:?} {:?} {:?}", settings, overrides, remaps),
+    );
+
+    if let Some(config) = AppConfig::find_path(project_root) {
+        hasher.file("config", &config);
+    }
+
+    // Module graph
+    let mut visited = HashSet::new();
+    let mut pending = vec![request.entry_path.clone()];
+    let mut banks: Vec<(String, String)> = Vec::new();
+    while let Some(module) = pending.pop() {
+        let key = module.canonicalize().unwrap_or_else(|_| module.clone());
+        if !visited.insert(key) {
+            continue;
+        }
+        hasher.file("module", &module);
+        let Ok(statements) = SimpleParser::parse_file(&module) else {
+            continue;
+        };
+        visit(&statements, &mut |stmt| match &stmt.kind {
+            StatementKind::Import { source, .. } => pending.push(PathBuf::from(source)),
+            StatementKind::Load { source, .. } => hasher.file("load", Path::new(source)),
+            StatementKind::UsePlugin { author, name, .. } => {
+                hasher.field("plugin", format!("{}.{}", author, name));
+                match plugin_files(project_root, author, name) {
+                    Ok((manifest, module)) => {
+                        hasher.file("plugin", &manifest);
+                        hasher.file("plugin", &module);
+                    }
+                    Err(_) => hasher.field("plugin", "missing"),
+                }
+            }
+            StatementKind::Bank { name, alias } => {
+                let alias = alias
+                    .clone()
+                    .unwrap_or_else(|| name.split('.').next_back().unwrap_or(name).to_string());
+                banks.push((alias, name.clone()));
+            }
+            _ => {}
+        });
+    }
+
+    // Banks, as the interpreter resolves them
+    let mut registry = BankRegistry::new();
+    for (from, identifier) in &remaps {
+        registry.remap(from.clone(), identifier.clone());
+    }
+    if let Some(default) = &request.default_bank {
+        banks.push((default.clone(), default.clone()));
+    }
+    banks.sort();
+    banks.dedup();
+    for (alias, name) in banks {
+        let identifier = registry.remapped(&alias, &name).to_string();
+        hasher.field("bank", &identifier);
+        match registry.register_bank(alias, &identifier, project_root, project_root) {
+            Ok(bank) => {
+                let root = bank.root_dir().to_path_buf();
+                hasher.dir(&root, &root)?;
+            }
+            Err(_) => hasher.field("bank", "missing"),
+        }
+    }
+
+    Ok(hasher.finish())
+}
+
+/// Call `f` on every statement, including those in nested bodies
+fn visit(statements: &[Statement], f: &mut impl FnMut(&Statement)) {
+    for stmt in statements {
+        f(stmt);
+        match &stmt.kind {
+            StatementKind::Tempo {
+                body: Some(body), ..
+            }
+            | StatementKind::Function { body, .. }
+            | StatementKind::Group { body, .. }
+            | StatementKind::Loop { body, .. }
+            | StatementKind::For { body, .. }
+            | StatementKind::At { body, .. }
+            | StatementKind::Routing { body }
+            | StatementKind::On { body, .. } => visit(body, f),
+            StatementKind::If {
+                body, else_body, ..
+            } => {
+                visit(body, f);
+                if let Some(else_body) = else_body {
+                    visit(else_body, f);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// SHA-256 of labelled, length-prefixed fields
+#[derive(Default)]
+struct InputHasher {
+    hasher: Sha256,
+}
+
+impl InputHasher {
+    fn field(&mut self, label: &str, value: impl AsRef<[u8]>) {
+        let value = value.as_ref();
+        self.hasher.update(label.as_bytes());
+        self.hasher.update((value.len() as u64).to_le_bytes());
+        self.hasher.update(value);
+    }
+
+    /// A file's path and contents; a missing file hashes as such
+    fn file(&mut self, label: &str, path: &Path) {
+        self.field(label, path.to_string_lossy().as_bytes());
+        match fs::read(path) {
+            Ok(bytes) => self.field("contents", bytes),
+            Err(_) => self.field("contents", "missing"),
+        }
+    }
+
+    /// Every file under `dir`, in path order, relative to `root`
+    fn dir(&mut self, dir: &Path, root: &Path) -> Result<()> {
+        let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("failed to read {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                self.dir(&path, root)?;
+            } else {
+                let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                self.field("file", relative.to_string_lossy().as_bytes());
+                self.field("contents", fs::read(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> String {
+        self.hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "test_manifest.rs"]
+mod tests;
@@ -1,4 +1,6 @@
 // Parent `services` module controls `cli` gating; avoid duplicating crate-level cfg here.
+pub mod hooks;
+pub mod manifest;
 pub mod outputs;
 pub mod pipeline;
 
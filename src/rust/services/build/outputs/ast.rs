@@ -1,13 +1,13 @@
 #![cfg(feature = "cli")]
 
-use std::fs::{File, create_dir_all};
-use std::io::Write;
+use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use serde_json::to_string_pretty;
 
 use crate::language::syntax::ast::Statement;
+use crate::platform::storage::atomic::write_atomic;
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct AstBuilder;
@@ -34,9 +34,7 @@ impl AstBuilder {
 
         let file_path = ast_dir.join(format!("{}.json", module_name));
         let json = to_string_pretty(statements).context("failed to serialize AST")?;
-        let mut file = File::create(&file_path)
-            .with_context(|| format!("failed to create AST file: {}", file_path.display()))?;
-        file.write_all(json.as_bytes())
+        write_atomic(&file_path, json)
             .with_context(|| format!("unable to write AST file: {}", file_path.display()))?;
 
         Ok(file_path)
@@ -1,15 +1,40 @@
 #![cfg(feature = "cli")]
 
-use crate::engine::audio::settings::{AudioBitDepth, AudioChannels, AudioFormat, ResampleQuality};
-use crate::language::syntax::ast::Statement;
+use crate::engine::audio::click::{click_times, render_click};
+use crate::engine::audio::diff::InsertAudio;
+use crate::engine::audio::event_export::EventsDocument;
+use crate::engine::audio::events::PrintTimelineEntry;
+use crate::engine::audio::interpreter::driver::PersistSnapshot;
+use crate::engine::audio::mixer::{
+    InsertCache, MASTER_INSERT, METER_WINDOW_SECONDS, peak_envelope,
+};
+use crate::engine::audio::outputs::{interleave, required_channels};
+use crate::engine::audio::playback::region::RenderFingerprint;
+use crate::engine::audio::samples::{self, RateConversion};
+use crate::engine::audio::scene::SceneCue;
+use crate::engine::audio::settings::{
+    AudioBitDepth, AudioChannels, AudioFormat, ClickMode, MixSettings, RenderLimits,
+    ResampleQuality,
+};
+use crate::engine::audio::solo::SoloMute;
+use crate::engine::audio::tempo::TempoMap;
+use crate::language::syntax::ast::{Statement, Value};
 use crate::tools::logger::Logger;
 use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use crate::services::build::outputs::audio::helpers::calculate_rms;
-use crate::services::build::outputs::audio::writer::write_wav;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::platform::storage::atomic::write_atomic;
+use crate::services::build::outputs::audio::helpers::{
+    SilenceHold, calculate_rms, trim_trailing_silence,
+};
+use crate::services::build::outputs::audio::snapshot;
+use crate::services::build::outputs::audio::writer::{
+    LosslessFileStream, WavStream, write_lossless, write_multichannel_wav, write_wav,
+};
 
 #[derive(Debug, Clone)]
 pub struct AudioRenderSummary {
@@ -19,6 +44,30 @@ pub struct AudioRenderSummary {
     pub rms: f32,
     pub render_time: Duration,
     pub audio_length: Duration,
+    /// Scheduled prints with their musical position, in time order
+    pub print_timeline: Vec<PrintTimelineEntry>,
+    /// Collected events in the `--outputs events-json` schema, when requested
+    pub events: Option<EventsDocument>,
+    /// Cue points set by `mark` statements (seconds, name), also written into the WAV
+    pub markers: Vec<(f32, String)>,
+    /// Tempo and meter changes the render followed
+    pub tempo_map: TempoMap,
+    /// `@persist` state to carry into the next build
+    pub persisted: PersistSnapshot,
+    /// Samples whose native rate differs from the render rate
+    pub sample_conversions: Vec<RateConversion>,
+    /// Collected events with their time spans, diffed by live rebuilds
+    pub fingerprint: RenderFingerprint,
+    /// Scene started by the last `switch`, if any
+    pub scene: Option<SceneCue>,
+    /// Files written for the requested formats
+    pub exported: Vec<(AudioFormat, PathBuf)>,
+    /// Trailing silence cut by `auto_trim`
+    pub trimmed: Duration,
+    /// Metronome stem written next to the audio, with the mode that asked for it
+    pub click: Option<(ClickMode, PathBuf)>,
+    /// Hardware output mix (`route ... -> outputs`) and its channel count
+    pub outputs: Option<(PathBuf, u16)>,
 }
 
 #[derive(Debug, Clone)]
@@ -30,6 +79,28 @@ pub struct MultiFormatRenderSummary {
     pub rms: f32,
     pub render_time: Duration,
     pub audio_length: Duration,
+    /// Scheduled prints with their musical position, in time order
+    pub print_timeline: Vec<PrintTimelineEntry>,
+    /// Collected events in the `--outputs events-json` schema, when requested
+    pub events: Option<EventsDocument>,
+    /// Cue points set by `mark` statements (seconds, name), also written into the WAV
+    pub markers: Vec<(f32, String)>,
+    /// Tempo and meter changes the render followed
+    pub tempo_map: TempoMap,
+    /// `@persist` state to carry into the next build
+    pub persisted: PersistSnapshot,
+    /// Samples whose native rate differs from the render rate
+    pub sample_conversions: Vec<RateConversion>,
+    /// Collected events with their time spans, diffed by live rebuilds
+    pub fingerprint: RenderFingerprint,
+    /// Scene started by the last `switch`, if any
+    pub scene: Option<SceneCue>,
+    /// Trailing silence cut by `auto_trim`
+    pub trimmed: Duration,
+    /// Metronome stem written next to the audio, with the mode that asked for it
+    pub click: Option<(ClickMode, PathBuf)>,
+    /// Hardware output mix (`route ... -> outputs`) and its channel count
+    pub outputs: Option<(PathBuf, u16)>,
 }
 
 #[derive(Clone)]
@@ -56,60 +127,131 @@ impl AudioBuilder {
         requested_bit_depth: AudioBitDepth,
         channels: AudioChannels,
         sample_rate: u32,
-        _resample: ResampleQuality,
+        resample: ResampleQuality,
+        preconvert_samples: bool,
+        mix: MixSettings,
+        limits: RenderLimits,
         _bpm: f32,
+        seed: Option<u64>,
+        overrides: &HashMap<String, Value>,
+        remaps: &HashMap<String, String>,
+        default_bank: Option<&str>,
+        tags: &BTreeMap<String, String>,
+        auto_trim: bool,
+        stream: bool,
+        events_json: bool,
+        click: Option<ClickMode>,
+        solo_mute: &SoloMute,
+        persisted: &PersistSnapshot,
+        insert_cache: Option<&Arc<Mutex<InsertCache>>>,
     ) -> Result<MultiFormatRenderSummary> {
         let start = Instant::now();
 
-        // Pick primary format
-        let primary_fmt = if requested_formats.is_empty() {
-            AudioFormat::Wav
-        } else {
-            requested_formats[0]
-        };
-
         let audio_summary = self.render(
             statements,
             _entry_path,
             output_root,
             module_name,
-            primary_fmt,
+            requested_formats,
             requested_bit_depth,
             channels,
             sample_rate,
-            ResampleQuality::Sinc24,
+            resample,
+            preconvert_samples,
+            mix,
+            limits,
+            seed,
+            overrides,
+            remaps,
+            default_bank,
+            tags,
+            auto_trim,
+            stream,
+            events_json,
+            click,
+            solo_mute,
+            persisted,
+            insert_cache,
         )?;
 
-        let exported = vec![(audio_summary.format, audio_summary.path.clone())];
-
         let total_time = start.elapsed();
 
         Ok(MultiFormatRenderSummary {
             primary_path: audio_summary.path,
             primary_format: audio_summary.format,
-            exported_formats: exported,
+            exported_formats: audio_summary.exported,
             bit_depth: audio_summary.bit_depth,
             rms: audio_summary.rms,
             render_time: total_time,
             audio_length: audio_summary.audio_length,
+            print_timeline: audio_summary.print_timeline,
+            events: audio_summary.events,
+            markers: audio_summary.markers,
+            tempo_map: audio_summary.tempo_map,
+            persisted: audio_summary.persisted,
+            sample_conversions: audio_summary.sample_conversions,
+            fingerprint: audio_summary.fingerprint,
+            scene: audio_summary.scene,
+            trimmed: audio_summary.trimmed,
+            click: audio_summary.click,
+            outputs: audio_summary.outputs,
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &self,
         statements: &[Statement],
         _entry_path: &Path,
         output_root: impl AsRef<Path>,
         module_name: &str,
-        requested_format: AudioFormat,
+        requested_formats: &[AudioFormat],
         requested_bit_depth: AudioBitDepth,
         channels: AudioChannels,
         sample_rate: u32,
-        _resample: ResampleQuality,
+        resample: ResampleQuality,
+        preconvert_samples: bool,
+        mix: MixSettings,
+        limits: RenderLimits,
+        seed: Option<u64>,
+        overrides: &HashMap<String, Value>,
+        remaps: &HashMap<String, String>,
+        default_bank: Option<&str>,
+        tags: &BTreeMap<String, String>,
+        auto_trim: bool,
+        stream: bool,
+        events_json: bool,
+        click: Option<ClickMode>,
+        solo_mute: &SoloMute,
+        persisted: &PersistSnapshot,
+        insert_cache: Option<&Arc<Mutex<InsertCache>>>,
     ) -> Result<AudioRenderSummary> {
-        use crate::engine::audio::interpreter::driver::AudioInterpreter;
+        let requested_format = requested_formats
+            .first()
+            .copied()
+            .unwrap_or(AudioFormat::Wav);
 
         let mut interpreter = AudioInterpreter::new(sample_rate);
+        interpreter.resample_quality = resample;
+        interpreter.mix = mix;
+        interpreter.deadline = limits.max_render_time.map(|limit| Instant::now() + limit);
+        if let Some(seed) = seed {
+            interpreter.set_deterministic(seed);
+        }
+        for (from, identifier) in remaps {
+            interpreter.banks.remap(from.clone(), identifier.clone());
+        }
+        interpreter
+            .banks
+            .set_default_bank(default_bank.map(str::to_string));
+        if !overrides.is_empty() {
+            interpreter.set_overrides(overrides.clone());
+        }
+        interpreter.solo_mute = solo_mute.clone();
+        interpreter.set_persisted(persisted.clone());
+        if let Some(cache) = insert_cache {
+            interpreter.enable_insert_cache(cache.clone());
+        }
         // During offline rendering we must not emit prints to stdout/stderr immediately.
         // Schedule prints into the interpreter event list and (optionally) replay them
         // in realtime during the render so the user can see PRINT messages as if
@@ -122,7 +264,97 @@ impl AudioBuilder {
         // build here to avoid duplicate prints when the live player replays the
         // same scheduled logs.
 
-        let buffer = interpreter.interpret(statements)?;
+        interpreter.collect_all_events(statements)?;
+        if let Some(max_seconds) = limits.max_audio_seconds {
+            let seconds = interpreter.calculate_total_duration() as f64;
+            if seconds > max_seconds {
+                anyhow::bail!(
+                    "Project renders {:.1}s of audio, over the {:.0}s limit",
+                    seconds,
+                    max_seconds
+                );
+            }
+        }
+        // Samples not at the render rate; with `preconvert_samples` they are all resampled
+        // here, before the render, instead of on first use
+        let sample_uris = interpreter.events.sample_uris();
+        let sample_conversions =
+            samples::prepare_samples(&sample_uris, sample_rate, resample, preconvert_samples);
+        // Looked up once per URI for the fingerprint and the event export
+        let sample_lengths: HashMap<String, f32> = sample_uris
+            .into_iter()
+            .filter_map(|uri| Some((uri.clone(), samples::sample_seconds(&uri)?)))
+            .collect();
+        let sample_seconds = |uri: &str| sample_lengths.get(uri).copied();
+        let fingerprint = RenderFingerprint::from_events(
+            &interpreter.events,
+            interpreter.routing.fingerprint(),
+            sample_seconds,
+        );
+        let events = events_json.then(|| {
+            EventsDocument::new(
+                module_name,
+                interpreter.bpm,
+                sample_rate,
+                &interpreter.events,
+                sample_seconds,
+            )
+        });
+        // `--click` wins over `metronome on` in the source
+        let click = click.or(interpreter.metronome);
+        // Streamed renders are written chunk by chunk below instead of into one buffer
+        let streamed = stream && interpreter.calculate_total_duration() > 0.0;
+        if streamed && !interpreter.can_stream() {
+            self._logger.warn(
+                "Routing graphs and group effects need the whole render; rendering in memory",
+            );
+        }
+        if streamed && click == Some(ClickMode::Mix) {
+            self._logger
+                .warn("A mixed-in click needs the whole render; rendering in memory");
+        }
+        let routes = interpreter.routing.outputs.clone();
+        if streamed && !routes.is_empty() {
+            self._logger
+                .warn("Hardware output routes need the whole render; rendering in memory");
+        }
+        let streamed = streamed
+            && interpreter.can_stream()
+            && click != Some(ClickMode::Mix)
+            && routes.is_empty();
+        // Builds outside live sessions keep a diff snapshot of every group
+        let (mut buffer, taps) = if streamed {
+            (Vec::new(), HashMap::new())
+        } else if insert_cache.is_none() {
+            interpreter.render_audio_inserts()?
+        } else if routes.is_empty() {
+            (interpreter.render_audio()?, HashMap::new())
+        } else {
+            interpreter.render_audio_outputs()?
+        };
+        let click_track = click.map(|_| {
+            let frames = if buffer.is_empty() {
+                (interpreter.calculate_total_duration() * sample_rate as f32).ceil() as usize
+            } else {
+                buffer.len() / 2
+            };
+            let length = frames as f32 / sample_rate.max(1) as f32;
+            let clicks = click_times(&interpreter.tempo_map, interpreter.bpm, length);
+            render_click(&clicks, sample_rate, frames)
+        });
+        if click == Some(ClickMode::Mix)
+            && let Some(track) = &click_track
+        {
+            for (sample, tick) in buffer.iter_mut().zip(track) {
+                *sample += tick;
+            }
+        }
+        let trimmed = if auto_trim {
+            let frames = trim_trailing_silence(&mut buffer, 2);
+            Duration::from_secs_f64(frames as f64 / sample_rate.max(1) as f64)
+        } else {
+            Duration::ZERO
+        };
 
         let output_root = output_root.as_ref();
         let audio_dir = output_root.join("audio");
@@ -133,7 +365,146 @@ impl AudioBuilder {
             )
         })?;
 
+        // The WAV master is always written: live playback and diffs read it back
         let output_path = audio_dir.join(format!("{}.wav", module_name));
+        let mut exported = Vec::new();
+        if !matches!(requested_format, AudioFormat::Flac | AudioFormat::Alac) {
+            exported.push((requested_format, output_path.clone()));
+        }
+
+        // Stem and monitor clicks go to their own file; a stale one is removed
+        let click_path = output_path.with_file_name(format!("{}.click.wav", module_name));
+        let click = match (click, &click_track) {
+            (Some(mode @ (ClickMode::Stem | ClickMode::Monitor)), Some(track)) => {
+                write_wav(
+                    &click_path,
+                    track,
+                    sample_rate,
+                    requested_bit_depth,
+                    AudioChannels::Stereo,
+                )?;
+                Some((mode, click_path))
+            }
+            _ => {
+                if click_path.exists() {
+                    std::fs::remove_file(&click_path).with_context(|| {
+                        format!("failed to remove click track: {}", click_path.display())
+                    })?;
+                }
+                None
+            }
+        };
+
+        // Main mix on outputs 1-2 (with the monitor click, heard by whoever listens to
+        // it) and each routed group after it; a stale file is removed
+        let outputs_path = output_path.with_file_name(format!("{}.outputs.wav", module_name));
+        let outputs = if routes.is_empty() || buffer.is_empty() {
+            if outputs_path.exists() {
+                std::fs::remove_file(&outputs_path).with_context(|| {
+                    format!("failed to remove output mix: {}", outputs_path.display())
+                })?;
+            }
+            None
+        } else {
+            let count = required_channels(&routes);
+            let mut main = buffer.clone();
+            if let (Some((ClickMode::Monitor, _)), Some(track)) = (&click, &click_track) {
+                for (sample, tick) in main.iter_mut().zip(track) {
+                    *sample += tick;
+                }
+            }
+            let mixed = interleave(&main, &taps, &routes, count);
+            write_multichannel_wav(
+                &outputs_path,
+                &mixed,
+                sample_rate,
+                requested_bit_depth,
+                count,
+            )?;
+            Some((outputs_path, count))
+        };
+
+        if insert_cache.is_none() && !buffer.is_empty() {
+            let mut inserts = vec![InsertAudio::new(MASTER_INSERT, buffer.clone(), 2)];
+            let mut groups: Vec<_> = taps.iter().collect();
+            groups.sort_by(|a, b| a.0.cmp(b.0));
+            inserts.extend(
+                groups
+                    .into_iter()
+                    .map(|(group, tap)| InsertAudio::new(group.as_str(), tap.clone(), 2)),
+            );
+            snapshot::write(output_root, module_name, &inserts, sample_rate)?;
+        }
+
+        // Write scheduled print events sidecar for live playback to consume. Prints are
+        // ordered by musical time (stable, so same-time prints keep execution order).
+        interpreter.events.sort_logs();
+        let log_path = output_path.with_file_name(format!("{}.printlog", module_name));
+        write_print_log(&log_path, &interpreter.events.logs)?;
+        let print_timeline = interpreter
+            .events
+            .print_timeline(&interpreter.tempo_map, interpreter.bpm);
+        let persisted = interpreter.persisted_snapshot();
+        let scene = interpreter.scene.take();
+        let markers = interpreter.events.markers.clone();
+        let tempo_map = interpreter.tempo_map.clone();
+
+        // Live sessions: meter, section and tempo sidecars mirrored to OSC during playback
+        if let Some(cache) = insert_cache {
+            let window = (METER_WINDOW_SECONDS * sample_rate as f32).round() as usize;
+            let mut meters = vec![(MASTER_INSERT.to_string(), peak_envelope(&buffer, 2, window))];
+            if let Ok(cache) = cache.lock() {
+                meters.extend(cache.meters.iter().cloned());
+            }
+            write_meters(
+                &output_path.with_file_name(format!("{}.meters", module_name)),
+                &meters,
+            )?;
+            write_sections(
+                &output_path.with_file_name(format!("{}.sections", module_name)),
+                &interpreter.events.sections(),
+            )?;
+            write_tempo(
+                &output_path.with_file_name(format!("{}.tempo", module_name)),
+                &interpreter.tempo_map,
+            )?;
+        }
+
+        if streamed {
+            let render = render_streamed(
+                &interpreter,
+                &output_path,
+                requested_formats,
+                requested_bit_depth,
+                channels,
+                sample_rate,
+                tags,
+                auto_trim,
+                &markers,
+                &self._logger,
+            )?;
+            exported.extend(render.exported);
+            return Ok(AudioRenderSummary {
+                path: output_path,
+                format: requested_format,
+                bit_depth: render.bit_depth,
+                rms: render.rms,
+                render_time: Duration::from_secs(0),
+                audio_length: render.audio_length,
+                print_timeline,
+                events,
+                markers,
+                tempo_map,
+                persisted,
+                sample_conversions,
+                fingerprint,
+                scene,
+                exported,
+                trimmed: render.trimmed,
+                click,
+                outputs,
+            });
+        }
 
         let mut rms = 0.0f32;
         let audio_length = if buffer.is_empty() {
@@ -154,24 +525,28 @@ impl AudioBuilder {
         };
 
         if !buffer.is_empty() {
-            let applied = write_wav(
-                &output_path,
-                &buffer,
-                sample_rate,
-                requested_bit_depth,
-                channels,
-            )?;
+            let mut wav =
+                WavStream::create(&output_path, sample_rate, requested_bit_depth, channels)?;
+            wav.write(&buffer)?;
+            let applied = wav.finish_with_markers(&markers)?;
 
-            // Write scheduled print events sidecar for live playback to consume.
-            let log_path = output_path.with_file_name(format!("{}.printlog", module_name));
-            if !interpreter.events.logs.is_empty() {
-                if let Ok(mut f) = std::fs::File::create(&log_path) {
-                    use std::io::Write;
-                    for (t, msg) in &interpreter.events.logs {
-                        // time in seconds (float) TAB message NEWLINE
-                        let _ = writeln!(f, "{:.6}\t{}", t, msg);
-                    }
+            for &format in requested_formats {
+                if !matches!(format, AudioFormat::Flac | AudioFormat::Alac)
+                    || exported.iter().any(|(done, _)| *done == format)
+                {
+                    continue;
                 }
+                let path = output_path.with_extension(format.file_extension());
+                write_lossless(
+                    &path,
+                    &buffer,
+                    sample_rate,
+                    requested_bit_depth,
+                    channels,
+                    format,
+                    tags,
+                )?;
+                exported.push((format, path));
             }
 
             Ok(AudioRenderSummary {
@@ -181,6 +556,18 @@ impl AudioBuilder {
                 rms,
                 render_time: Duration::from_secs(0),
                 audio_length,
+                print_timeline,
+                events,
+                markers,
+                tempo_map,
+                persisted,
+                sample_conversions,
+                fingerprint,
+                scene,
+                exported,
+                trimmed,
+                click,
+                outputs,
             })
         } else {
             Ok(AudioRenderSummary {
@@ -190,7 +577,233 @@ impl AudioBuilder {
                 rms: 0.0,
                 render_time: Duration::from_secs(0),
                 audio_length,
+                print_timeline,
+                events,
+                markers,
+                tempo_map,
+                persisted,
+                sample_conversions,
+                fingerprint,
+                scene,
+                exported,
+                trimmed,
+                click,
+                outputs,
             })
         }
     }
 }
+
+/// Length of the chunks a streamed render is written in
+const STREAM_CHUNK_SECONDS: f32 = 1.0;
+
+/// Files and levels of a render streamed straight to disk
+struct StreamedRender {
+    bit_depth: AudioBitDepth,
+    rms: f32,
+    audio_length: Duration,
+    trimmed: Duration,
+    exported: Vec<(AudioFormat, PathBuf)>,
+}
+
+/// Render chunk by chunk into the WAV master and any FLAC or ALAC export (`audio.stream`).
+/// A master peaking over full scale is rendered a second time scaled down, so streamed
+/// output is normalized like an in-memory render without holding it in memory.
+#[allow(clippy::too_many_arguments)]
+fn render_streamed(
+    interpreter: &AudioInterpreter,
+    output_path: &Path,
+    requested_formats: &[AudioFormat],
+    requested_bit_depth: AudioBitDepth,
+    channels: AudioChannels,
+    sample_rate: u32,
+    tags: &BTreeMap<String, String>,
+    auto_trim: bool,
+    markers: &[(f32, String)],
+    logger: &Logger,
+) -> Result<StreamedRender> {
+    let pass = |gain: f32| {
+        stream_pass(
+            interpreter,
+            output_path,
+            requested_formats,
+            requested_bit_depth,
+            channels,
+            sample_rate,
+            tags,
+            auto_trim,
+            markers,
+            gain,
+        )
+    };
+    let (peak, render) = pass(1.0)?;
+    if peak <= 1.0 {
+        return Ok(render);
+    }
+    logger.info(format!(
+        "Streamed render peaks at {:+.1} dBFS; rendering again normalized",
+        20.0 * peak.log10()
+    ));
+    Ok(pass(1.0 / peak)?.1)
+}
+
+/// One streamed render with every sample scaled by `gain`; returns the peak before scaling
+#[allow(clippy::too_many_arguments)]
+fn stream_pass(
+    interpreter: &AudioInterpreter,
+    output_path: &Path,
+    requested_formats: &[AudioFormat],
+    requested_bit_depth: AudioBitDepth,
+    channels: AudioChannels,
+    sample_rate: u32,
+    tags: &BTreeMap<String, String>,
+    auto_trim: bool,
+    markers: &[(f32, String)],
+    gain: f32,
+) -> Result<(f32, StreamedRender)> {
+    let chunk_frames = (STREAM_CHUNK_SECONDS * sample_rate as f32) as usize;
+    let mut wav = WavStream::create(output_path, sample_rate, requested_bit_depth, channels)?;
+    let mut lossless = Vec::new();
+    for &format in requested_formats {
+        if matches!(format, AudioFormat::Flac | AudioFormat::Alac) {
+            let path = output_path.with_extension(format.file_extension());
+            let stream = LosslessFileStream::create(
+                &path,
+                format,
+                sample_rate,
+                requested_bit_depth,
+                channels,
+                tags,
+            )?;
+            lossless.push((format, path, stream));
+        }
+    }
+
+    let mut hold = auto_trim.then(|| SilenceHold::new(2));
+    let mut sum_squares = 0.0f64;
+    let mut written = 0usize;
+    let mut emit = |pcm: &[f32]| -> Result<()> {
+        wav.write(pcm)?;
+        for (_, _, stream) in lossless.iter_mut() {
+            stream.write(pcm)?;
+        }
+        sum_squares += pcm.iter().map(|&s| s as f64 * s as f64).sum::<f64>();
+        written += pcm.len();
+        Ok(())
+    };
+    let mut scaled = Vec::new();
+    let peak = interpreter.render_audio_streamed(chunk_frames, &mut |chunk| {
+        let chunk = if gain == 1.0 {
+            chunk
+        } else {
+            scaled.clear();
+            scaled.extend(chunk.iter().map(|sample| sample * gain));
+            &scaled
+        };
+        match hold.as_mut() {
+            Some(hold) => emit(&hold.push(chunk)),
+            None => emit(chunk),
+        }
+    })?;
+    let trimmed_frames = match hold {
+        Some(hold) => {
+            let (rest, trimmed) = hold.finish();
+            emit(&rest)?;
+            trimmed
+        }
+        None => 0,
+    };
+
+    let bit_depth = wav.finish_with_markers(markers)?;
+    let mut exported = Vec::new();
+    for (format, path, stream) in lossless {
+        stream.finish()?;
+        exported.push((format, path));
+    }
+
+    let rate = sample_rate.max(1) as f64;
+    let frames = written / (channels.count() as usize).max(1);
+    let render = StreamedRender {
+        bit_depth,
+        rms: if written == 0 {
+            0.0
+        } else {
+            (sum_squares / written as f64).sqrt() as f32
+        },
+        audio_length: Duration::from_secs_f64(frames as f64 / rate),
+        trimmed: Duration::from_secs_f64(trimmed_frames as f64 / rate),
+        exported,
+    };
+    Ok((peak, render))
+}
+
+/// Write the `.printlog` sidecar (`<seconds>\t<message>` per line). A stale sidecar from a
+/// previous build is removed when the module no longer prints anything.
+fn write_print_log(path: &Path, logs: &[(f32, String)]) -> Result<()> {
+    use std::fmt::Write;
+
+    if logs.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(path)
+                .with_context(|| format!("failed to remove print log: {}", path.display()))?;
+        }
+        return Ok(());
+    }
+
+    let mut contents = String::new();
+    for (t, msg) in logs {
+        // Keep one record per line even for multi-line messages
+        let _ = writeln!(contents, "{:.6}\t{}", t, msg.replace('\n', " "));
+    }
+    write_atomic(path, contents)
+        .with_context(|| format!("unable to write print log: {}", path.display()))
+}
+
+/// Write the `.meters` sidecar: `<insert>\t<window seconds>\t<peak,peak,...>` per line
+fn write_meters(path: &Path, meters: &[(String, Vec<f32>)]) -> Result<()> {
+    use std::fmt::Write;
+
+    let mut contents = String::new();
+    for (insert, peaks) in meters {
+        let peaks = peaks
+            .iter()
+            .map(|peak| format!("{:.4}", peak))
+            .collect::<Vec<_>>()
+            .join(",");
+        let _ = writeln!(contents, "{}\t{}\t{}", insert, METER_WINDOW_SECONDS, peaks);
+    }
+    write_atomic(path, contents)
+        .with_context(|| format!("unable to write meter log: {}", path.display()))
+}
+
+/// Write the `.tempo` sidecar: `tempo\t<beat>\t<bpm>` and
+/// `meter\t<beat>\t<numerator>\t<denominator>` per change
+fn write_tempo(path: &Path, tempo_map: &TempoMap) -> Result<()> {
+    use std::fmt::Write;
+
+    let mut contents = String::new();
+    for change in &tempo_map.tempos {
+        let _ = writeln!(contents, "tempo\t{}\t{}", change.beat, change.bpm);
+    }
+    for change in &tempo_map.meters {
+        let _ = writeln!(
+            contents,
+            "meter\t{}\t{}\t{}",
+            change.beat, change.numerator, change.denominator
+        );
+    }
+    write_atomic(path, contents)
+        .with_context(|| format!("unable to write tempo log: {}", path.display()))
+}
+
+/// Write the `.sections` sidecar (`<seconds>\t<name>` per line)
+fn write_sections(path: &Path, sections: &[(f32, String)]) -> Result<()> {
+    use std::fmt::Write;
+
+    let mut contents = String::new();
+    for (t, name) in sections {
+        let _ = writeln!(contents, "{:.6}\t{}", t, name);
+    }
+    write_atomic(path, contents)
+        .with_context(|| format!("unable to write section log: {}", path.display()))
+}
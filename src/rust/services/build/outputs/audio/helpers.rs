@@ -8,3 +8,115 @@ pub fn calculate_rms(pcm: &[f32]) -> f32 {
     let mean = sum_sqr / (pcm.len() as f64);
     (mean.sqrt() as f32).abs()
 }
+
+/// Hex-encoded SHA-256 of a rendered file, or `None` when the file was not written
+pub fn content_hash(path: &std::path::Path) -> anyhow::Result<Option<String>> {
+    use sha2::{Digest, Sha256};
+
+    if !path.exists() {
+        return Ok(None);
+    }
+    let digest = Sha256::digest(std::fs::read(path)?);
+    Ok(Some(
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect(),
+    ))
+}
+
+/// Level below which trailing audio counts as silence (-80 dBFS)
+const SILENCE_THRESHOLD: f32 = 1.0e-4;
+
+/// Cut the trailing silence of interleaved `pcm` (`auto_trim`). Each channel ends at its
+/// first zero crossing after the last audible frame, so the cut does not click.
+/// Returns the number of frames removed; fully silent buffers are left as they are.
+pub fn trim_trailing_silence(pcm: &mut Vec<f32>, channels: usize) -> usize {
+    let channels = channels.max(1);
+    let frames = pcm.len() / channels;
+    let Some(last) = (0..frames).rev().find(|&frame| {
+        pcm[frame * channels..(frame + 1) * channels]
+            .iter()
+            .any(|sample| sample.abs() > SILENCE_THRESHOLD)
+    }) else {
+        return 0;
+    };
+
+    let crossings: Vec<Option<usize>> = (0..channels)
+        .map(|channel| {
+            (last + 1..frames).find(|&frame| {
+                let previous = pcm[(frame - 1) * channels + channel];
+                let current = pcm[frame * channels + channel];
+                current == 0.0 || (previous < 0.0) != (current < 0.0)
+            })
+        })
+        .collect();
+    // A channel that never crosses zero (DC offset) is cut right after the audible part
+    let end = crossings
+        .iter()
+        .map(|crossing| crossing.unwrap_or(last + 1))
+        .max()
+        .unwrap_or(frames);
+
+    for (channel, crossing) in crossings.iter().enumerate() {
+        let from = crossing.unwrap_or(last + 1);
+        for frame in from..end {
+            pcm[frame * channels + channel] = 0.0;
+        }
+    }
+    pcm.truncate(end * channels);
+    frames - end
+}
+
+/// `trim_trailing_silence` for streamed renders: audio from the last audible frame on
+/// is held back until louder audio follows, so the tail can still be cut at the end.
+pub struct SilenceHold {
+    channels: usize,
+    /// Starts at the last audible frame once one was seen
+    held: Vec<f32>,
+    audible: bool,
+}
+
+impl SilenceHold {
+    pub fn new(channels: usize) -> Self {
+        Self {
+            channels: channels.max(1),
+            held: Vec::new(),
+            audible: false,
+        }
+    }
+
+    /// Take in the next chunk and return the audio that can be written already
+    pub fn push(&mut self, chunk: &[f32]) -> Vec<f32> {
+        let channels = self.channels;
+        let last_audible = (0..chunk.len() / channels).rev().find(|&frame| {
+            chunk[frame * channels..(frame + 1) * channels]
+                .iter()
+                .any(|sample| sample.abs() > SILENCE_THRESHOLD)
+        });
+
+        match last_audible {
+            // Silence is only ever cut at the end, so nothing before the first
+            // audible frame has to wait
+            None if !self.audible => chunk.to_vec(),
+            None => {
+                self.held.extend_from_slice(chunk);
+                Vec::new()
+            }
+            Some(frame) => {
+                self.audible = true;
+                let mut ready = std::mem::take(&mut self.held);
+                ready.extend_from_slice(&chunk[..frame * channels]);
+                self.held = chunk[frame * channels..].to_vec();
+                ready
+            }
+        }
+    }
+
+    /// Audio left to write after trimming, and the number of frames cut
+    pub fn finish(mut self) -> (Vec<f32>, usize) {
+        let trimmed = trim_trailing_silence(&mut self.held, self.channels);
+        (self.held, trimmed)
+    }
+}
+
+#[cfg(test)]
+#[path = "test_helpers.rs"]
+mod tests;
@@ -1,3 +1,4 @@
 pub mod builder;
 pub mod helpers;
+pub mod snapshot;
 pub mod writer;
@@ -0,0 +1,90 @@
+//! Diff snapshots: the master and the processed signal of every group of a build, kept
+//! under `<output>/diff/<module>/` for `devalang diff`. Each build moves the snapshot of
+//! the build before it to `<module>.previous`, so the last two builds can be compared.
+
+use anyhow::{Context, Result};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::path::{Path, PathBuf};
+
+use crate::engine::audio::diff::InsertAudio;
+
+/// File listing the inserts of a snapshot, one name per line (`<index>.wav`)
+const SNAPSHOT_INDEX: &str = "inserts.txt";
+
+/// Snapshot of the last build of `module_name`
+pub fn current_dir(output_root: &Path, module_name: &str) -> PathBuf {
+    output_root.join("diff").join(module_name)
+}
+
+/// Snapshot of the build before the last one
+pub fn previous_dir(output_root: &Path, module_name: &str) -> PathBuf {
+    output_root
+        .join("diff")
+        .join(format!("{}.previous", module_name))
+}
+
+/// Keep the current snapshot as the previous one and write `inserts` (master first) as
+/// the current one
+pub fn write(
+    output_root: &Path,
+    module_name: &str,
+    inserts: &[InsertAudio],
+    sample_rate: u32,
+) -> Result<()> {
+    let current = current_dir(output_root, module_name);
+    let previous = previous_dir(output_root, module_name);
+    if current.join(SNAPSHOT_INDEX).exists() {
+        if previous.exists() {
+            std::fs::remove_dir_all(&previous)
+                .with_context(|| format!("failed to remove {}", previous.display()))?;
+        }
+        std::fs::rename(&current, &previous)
+            .with_context(|| format!("failed to keep {}", current.display()))?;
+    } else if current.exists() {
+        std::fs::remove_dir_all(&current)
+            .with_context(|| format!("failed to remove {}", current.display()))?;
+    }
+    std::fs::create_dir_all(&current)
+        .with_context(|| format!("failed to create {}", current.display()))?;
+
+    for (i, insert) in inserts.iter().enumerate() {
+        let spec = WavSpec {
+            channels: insert.channels as u16,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let mut writer = WavWriter::create(current.join(format!("{}.wav", i)), spec)?;
+        for &sample in &insert.samples {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+    }
+    // Written last: a snapshot without its index is incomplete and never read
+    let index: Vec<&str> = inserts.iter().map(|insert| insert.name.as_str()).collect();
+    std::fs::write(current.join(SNAPSHOT_INDEX), index.join("\n"))?;
+    Ok(())
+}
+
+/// Inserts (master first) and sample rate of the snapshot in `dir`, if there is one
+pub fn read(dir: &Path) -> Result<Option<(Vec<InsertAudio>, u32)>> {
+    let Ok(index) = std::fs::read_to_string(dir.join(SNAPSHOT_INDEX)) else {
+        return Ok(None);
+    };
+    let mut inserts = Vec::new();
+    let mut sample_rate = 0;
+    for (i, name) in index.lines().enumerate() {
+        let path = dir.join(format!("{}.wav", i));
+        let mut reader =
+            WavReader::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+        let spec = reader.spec();
+        let samples = reader.samples::<f32>().collect::<Result<_, _>>()?;
+        inserts.push(InsertAudio::new(name, samples, spec.channels as usize));
+        sample_rate = spec.sample_rate;
+    }
+    Ok((!inserts.is_empty()).then_some((inserts, sample_rate)))
+}
+
+#[cfg(test)]
+#[path = "test_snapshot.rs"]
+mod tests;
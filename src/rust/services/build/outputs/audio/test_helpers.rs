@@ -0,0 +1,78 @@
+use super::*;
+
+#[test]
+fn test_trim_cuts_trailing_silence_at_zero_crossing() {
+    // One second of a decaying stereo sine followed by two seconds of near silence
+    let mut pcm: Vec<f32> = (0..3000)
+        .flat_map(|i| {
+            let level = if i < 1000 { 0.5 } else { 0.00005 };
+            let sample = (i as f32 * 0.07).sin() * level;
+            [sample, -sample]
+        })
+        .collect();
+    let removed = trim_trailing_silence(&mut pcm, 2);
+
+    let frames = pcm.len() / 2;
+    assert_eq!(frames + removed, 3000);
+    assert!((1000..1050).contains(&frames), "kept {} frames", frames);
+    // The last kept frame sits at (or just before) the crossing, not mid-wave
+    let tail = &pcm[pcm.len() - 2..];
+    assert!(tail.iter().all(|s| s.abs() <= SILENCE_THRESHOLD));
+}
+
+#[test]
+fn test_trim_keeps_silent_and_full_buffers() {
+    let mut silent = vec![0.0f32; 200];
+    assert_eq!(trim_trailing_silence(&mut silent, 2), 0);
+    assert_eq!(silent.len(), 200);
+
+    let mut loud: Vec<f32> = (0..100)
+        .map(|i| (i as f32 * 0.3).sin() * 0.8 + 0.1)
+        .collect();
+    assert_eq!(trim_trailing_silence(&mut loud, 1), 0);
+    assert_eq!(loud.len(), 100);
+}
+
+#[test]
+fn test_silence_hold_matches_whole_buffer_trim() {
+    // Audible bursts separated by a quiet gap, then a long quiet tail
+    let pcm: Vec<f32> = (0..6000)
+        .flat_map(|i| {
+            let level = if i < 1000 || (2000..2500).contains(&i) {
+                0.5
+            } else {
+                0.00005
+            };
+            let sample = (i as f32 * 0.07).sin() * level;
+            [sample, -sample]
+        })
+        .collect();
+
+    let mut expected = pcm.clone();
+    let expected_trim = trim_trailing_silence(&mut expected, 2);
+
+    let mut hold = SilenceHold::new(2);
+    let mut streamed = Vec::new();
+    for chunk in pcm.chunks(2 * 300) {
+        streamed.extend(hold.push(chunk));
+    }
+    let (rest, trimmed) = hold.finish();
+    streamed.extend(rest);
+
+    assert_eq!(trimmed, expected_trim);
+    assert_eq!(streamed, expected);
+}
+
+#[test]
+fn test_content_hash_marks_missing_files() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("out.wav");
+    assert_eq!(content_hash(&path)?, None);
+
+    std::fs::write(&path, b"")?;
+    let empty = content_hash(&path)?;
+    assert!(empty.is_some());
+    std::fs::write(&path, b"RIFF")?;
+    assert_ne!(content_hash(&path)?, empty);
+    Ok(())
+}
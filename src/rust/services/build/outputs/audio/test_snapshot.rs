@@ -0,0 +1,28 @@
+use super::*;
+
+fn master(level: f32) -> Vec<InsertAudio> {
+    vec![
+        InsertAudio::new("master", vec![level; 8], 2),
+        InsertAudio::new("drums", vec![level / 2.0; 8], 2),
+    ]
+}
+
+#[test]
+fn test_each_write_keeps_the_build_before_it() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    assert!(read(&current_dir(root, "index"))?.is_none());
+
+    write(root, "index", &master(0.5), 48_000)?;
+    assert!(read(&previous_dir(root, "index"))?.is_none());
+
+    write(root, "index", &master(0.25), 48_000)?;
+    let (previous, rate) = read(&previous_dir(root, "index"))?.unwrap();
+    let (current, _) = read(&current_dir(root, "index"))?.unwrap();
+    assert_eq!(rate, 48_000);
+    assert_eq!(previous[0].samples, vec![0.5; 8]);
+    assert_eq!(current[0].samples, vec![0.25; 8]);
+    assert_eq!(current[1].name, "drums");
+    assert_eq!(current[1].channels, 2);
+    Ok(())
+}
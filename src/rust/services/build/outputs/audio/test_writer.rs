@@ -0,0 +1,276 @@
+use super::*;
+
+/// Top-level chunks of a RIFF file: id and body
+fn chunks(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let mut found = Vec::new();
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = String::from_utf8_lossy(&bytes[offset..offset + 4]).to_string();
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        found.push((id, bytes[offset + 8..offset + 8 + size].to_vec()));
+        offset += 8 + size + size % 2;
+    }
+    found
+}
+
+#[test]
+fn test_cue_markers_follow_the_audio_and_keep_it_readable() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cues.wav");
+    // 8-bit mono with an odd frame count, so the data chunk needs its pad byte
+    write_wav(
+        &path,
+        &[0.5; 101],
+        100,
+        AudioBitDepth::Bit8,
+        AudioChannels::Mono,
+    )?;
+    let markers = vec![(0.0, "intro".to_string()), (0.5, "drop".to_string())];
+    append_cue_markers(&path, &markers, 100)?;
+
+    assert_eq!(read_wav(&path)?.len(), 101);
+    let bytes = std::fs::read(&path)?;
+    let riff = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    assert_eq!(riff + 8, bytes.len());
+
+    let chunks = chunks(&bytes);
+    let (_, cue) = chunks.iter().find(|(id, _)| id == "cue ").unwrap();
+    let word = |at: usize| u32::from_le_bytes(cue[at..at + 4].try_into().unwrap());
+    assert_eq!(word(0), 2);
+    // Second point: id 2 at frame 50
+    assert_eq!((word(28), word(32), word(48)), (2, 50, 50));
+
+    let (_, list) = chunks.iter().find(|(id, _)| id == "LIST").unwrap();
+    assert_eq!(&list[..4], b"adtl");
+    assert_eq!(&list[4..8], b"labl");
+    assert_eq!(&list[12..16], &1u32.to_le_bytes());
+    assert_eq!(&list[16..22], b"intro\0");
+    Ok(())
+}
+
+/// A ramp through the full range with both clipping ends
+fn ramp(len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| i as f32 / (len - 1) as f32 * 2.4 - 1.2)
+        .collect()
+}
+
+#[test]
+fn test_every_depth_round_trips_within_one_step() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let pcm = ramp(1001);
+    for (depth, bits, format, step) in [
+        (AudioBitDepth::Bit8, 8, SampleFormat::Int, 1.0 / 127.0),
+        (AudioBitDepth::Bit16, 16, SampleFormat::Int, 1.0 / 32767.0),
+        (
+            AudioBitDepth::Bit24,
+            24,
+            SampleFormat::Int,
+            1.0 / 8_388_607.0,
+        ),
+        (AudioBitDepth::Bit32, 32, SampleFormat::Float, 0.0),
+    ] {
+        let path = dir.path().join(format!("{}.wav", bits));
+        assert_eq!(
+            write_wav(&path, &pcm, 22050, depth, AudioChannels::Mono)?,
+            depth
+        );
+        let spec = WavReader::open(&path)?.spec();
+        assert_eq!(
+            (spec.channels, spec.sample_rate, spec.bits_per_sample),
+            (1, 22050, bits)
+        );
+        assert_eq!(spec.sample_format, format);
+
+        let decoded = read_wav(&path)?;
+        assert_eq!(decoded.len(), pcm.len());
+        for (written, read) in pcm.iter().zip(&decoded) {
+            let expected = written.clamp(-1.0, 1.0);
+            assert!(
+                (expected - read).abs() <= step / 2.0 + 1e-6,
+                "{} bits: {} read back as {}",
+                bits,
+                expected,
+                read
+            );
+        }
+        // Out of range samples clip to full scale instead of wrapping
+        assert_eq!((decoded[0], decoded[1000]), (-1.0, 1.0));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_streamed_chunks_match_a_single_write() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let pcm = ramp(2000);
+    let whole = dir.path().join("whole.wav");
+    write_wav(
+        &whole,
+        &pcm,
+        44100,
+        AudioBitDepth::Bit24,
+        AudioChannels::Stereo,
+    )?;
+
+    let chunked = dir.path().join("chunked.wav");
+    let mut stream =
+        WavStream::create(&chunked, 44100, AudioBitDepth::Bit24, AudioChannels::Stereo)?;
+    // Uneven chunk sizes, including a split frame
+    for chunk in pcm.chunks(333) {
+        stream.write(chunk)?;
+    }
+    // Nothing at the destination until the stream finishes
+    assert!(!chunked.exists());
+    stream.finish()?;
+
+    assert_eq!(std::fs::read(&whole)?, std::fs::read(&chunked)?);
+    Ok(())
+}
+
+#[test]
+fn test_multichannel_wav_keeps_frames_interleaved() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("outputs.wav");
+    // Four channels, each holding its own constant level
+    let pcm: Vec<f32> = (0..400).map(|i| (i % 4) as f32 * 0.25).collect();
+    write_multichannel_wav(&path, &pcm, 48000, AudioBitDepth::Bit16, 4)?;
+
+    assert_eq!(WavReader::open(&path)?.spec().channels, 4);
+    let decoded = read_wav(&path)?;
+    assert_eq!(decoded.len(), 400);
+    for frame in decoded.chunks(4) {
+        for (channel, sample) in frame.iter().enumerate() {
+            assert!((sample - channel as f32 * 0.25).abs() < 1e-4);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lossless_depths() {
+    use AudioBitDepth::*;
+    for (depth, flac, alac) in [
+        (Bit8, Bit8, Bit16),
+        (Bit16, Bit16, Bit16),
+        (Bit24, Bit24, Bit24),
+        (Bit32, Bit24, Bit24),
+    ] {
+        assert_eq!(lossless_bit_depth(AudioFormat::Flac, depth), flac);
+        assert_eq!(lossless_bit_depth(AudioFormat::Alac, depth), alac);
+    }
+}
+
+#[test]
+fn test_write_lossless_refuses_other_formats() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("master.wav");
+    let result = write_lossless(
+        &path,
+        &[0.0; 64],
+        44100,
+        AudioBitDepth::Bit16,
+        AudioChannels::Mono,
+        AudioFormat::Wav,
+        &BTreeMap::new(),
+    );
+    assert!(result.is_err());
+    assert!(!path.exists());
+    Ok(())
+}
+
+#[test]
+fn test_streamed_flac_matches_write_lossless() -> Result<()> {
+    use rodio::Source;
+
+    let dir = tempfile::tempdir()?;
+    let pcm = ramp(10_000);
+    let tags = BTreeMap::from([("artist".to_string(), "Deva".to_string())]);
+    let whole = dir.path().join("whole.flac");
+    let depth = write_lossless(
+        &whole,
+        &pcm,
+        44100,
+        AudioBitDepth::Bit32,
+        AudioChannels::Stereo,
+        AudioFormat::Flac,
+        &tags,
+    )?;
+    assert_eq!(depth, AudioBitDepth::Bit24);
+
+    let streamed = dir.path().join("streamed.flac");
+    let mut stream = LosslessFileStream::create(
+        &streamed,
+        AudioFormat::Flac,
+        44100,
+        AudioBitDepth::Bit32,
+        AudioChannels::Stereo,
+        &tags,
+    )?;
+    for chunk in pcm.chunks(1234) {
+        stream.write(chunk)?;
+    }
+    stream.finish()?;
+
+    let decode = |path: &Path| -> Result<(u16, u32, Vec<f32>)> {
+        let decoder = rodio::Decoder::new(std::io::BufReader::new(File::open(path)?))?;
+        Ok((
+            decoder.channels(),
+            decoder.sample_rate(),
+            decoder.convert_samples().collect(),
+        ))
+    };
+    let (channels, rate, decoded) = decode(&streamed)?;
+    assert_eq!((channels, rate), (2, 44100));
+    assert_eq!(decoded, decode(&whole)?.2);
+    assert_eq!(decoded.len(), pcm.len());
+    for (written, read) in pcm.iter().zip(&decoded) {
+        assert!((written.clamp(-1.0, 1.0) - read).abs() < 1e-4);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_alac_stream_matches_whole_encode() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let pcm = ramp(10_000);
+    let tags = BTreeMap::new();
+    let whole = dir.path().join("whole.m4a");
+    write_lossless(
+        &whole,
+        &pcm,
+        44100,
+        AudioBitDepth::Bit16,
+        AudioChannels::Stereo,
+        AudioFormat::Alac,
+        &tags,
+    )?;
+
+    let streamed = dir.path().join("streamed.m4a");
+    let mut stream = LosslessFileStream::create(
+        &streamed,
+        AudioFormat::Alac,
+        44100,
+        AudioBitDepth::Bit16,
+        AudioChannels::Stereo,
+        &tags,
+    )?;
+    for chunk in pcm.chunks(1234) {
+        stream.write(chunk)?;
+    }
+    stream.finish()?;
+
+    assert_eq!(std::fs::read(&streamed)?, std::fs::read(&whole)?);
+    assert!(
+        LosslessFileStream::create(
+            &dir.path().join("lossy.mp3"),
+            AudioFormat::Mp3,
+            44100,
+            AudioBitDepth::Bit16,
+            AudioChannels::Stereo,
+            &tags,
+        )
+        .is_err()
+    );
+    Ok(())
+}
@@ -1,9 +1,16 @@
 #![cfg(feature = "cli")]
 
-use crate::engine::audio::settings::{AudioBitDepth, AudioChannels};
-use anyhow::{Context, Result};
-use hound::{SampleFormat, WavSpec, WavWriter};
-use std::path::Path;
+use crate::engine::audio::encoders::alac::AlacStream;
+use crate::engine::audio::encoders::flac::FlacStream;
+use crate::engine::audio::encoders::{alac, flac, quantize};
+use crate::engine::audio::settings::{AudioBitDepth, AudioChannels, AudioFormat};
+use crate::platform::storage::atomic::{PendingFile, write_atomic};
+use anyhow::{Context, Result, bail};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 
 pub fn write_wav(
     path: &Path,
@@ -12,62 +19,320 @@ pub fn write_wav(
     requested_bit_depth: AudioBitDepth,
     channels: AudioChannels,
 ) -> Result<AudioBitDepth> {
-    let (bit_depth, sample_format) = match requested_bit_depth {
-        AudioBitDepth::Bit32 => (AudioBitDepth::Bit32, SampleFormat::Float),
-        AudioBitDepth::Bit24 => (AudioBitDepth::Bit24, SampleFormat::Int),
-        AudioBitDepth::Bit16 => (AudioBitDepth::Bit16, SampleFormat::Int),
-        AudioBitDepth::Bit8 => (AudioBitDepth::Bit8, SampleFormat::Int),
-    };
+    let mut stream = WavStream::create(path, sample_rate, requested_bit_depth, channels)?;
+    stream.write(pcm)?;
+    stream.finish()
+}
 
-    let spec = WavSpec {
-        channels: channels.count(),
-        sample_rate,
-        bits_per_sample: bit_depth.bits(),
-        sample_format,
-    };
+/// Write interleaved frames of `channel_count` channels, e.g. the hardware output mix
+/// of `route ... -> outputs`
+pub fn write_multichannel_wav(
+    path: &Path,
+    pcm: &[f32],
+    sample_rate: u32,
+    requested_bit_depth: AudioBitDepth,
+    channel_count: u16,
+) -> Result<AudioBitDepth> {
+    let mut stream =
+        WavStream::with_channel_count(path, sample_rate, requested_bit_depth, channel_count)?;
+    stream.write(pcm)?;
+    stream.finish()
+}
 
-    let mut writer = WavWriter::create(path, spec)
-        .with_context(|| format!("failed to open WAV writer for {}", path.display()))?;
+/// WAV file written chunk by chunk; the header sizes are patched by `finish`, which also
+/// moves the file into place
+pub struct WavStream {
+    writer: WavWriter<BufWriter<File>>,
+    bit_depth: AudioBitDepth,
+    sample_rate: u32,
+    path: PathBuf,
+    pending: PendingFile,
+}
+
+impl WavStream {
+    pub fn create(
+        path: &Path,
+        sample_rate: u32,
+        requested_bit_depth: AudioBitDepth,
+        channels: AudioChannels,
+    ) -> Result<Self> {
+        Self::with_channel_count(path, sample_rate, requested_bit_depth, channels.count())
+    }
+
+    pub fn with_channel_count(
+        path: &Path,
+        sample_rate: u32,
+        requested_bit_depth: AudioBitDepth,
+        channel_count: u16,
+    ) -> Result<Self> {
+        let (bit_depth, sample_format) = match requested_bit_depth {
+            AudioBitDepth::Bit32 => (AudioBitDepth::Bit32, SampleFormat::Float),
+            AudioBitDepth::Bit24 => (AudioBitDepth::Bit24, SampleFormat::Int),
+            AudioBitDepth::Bit16 => (AudioBitDepth::Bit16, SampleFormat::Int),
+            AudioBitDepth::Bit8 => (AudioBitDepth::Bit8, SampleFormat::Int),
+        };
+
+        let spec = WavSpec {
+            channels: channel_count,
+            sample_rate,
+            bits_per_sample: bit_depth.bits(),
+            sample_format,
+        };
+
+        let pending = PendingFile::new(path);
+        let writer = WavWriter::create(pending.temp(), spec)
+            .with_context(|| format!("failed to open WAV writer for {}", path.display()))?;
+        Ok(Self {
+            writer,
+            bit_depth,
+            sample_rate,
+            path: path.to_path_buf(),
+            pending,
+        })
+    }
 
-    match bit_depth {
-        AudioBitDepth::Bit32 => {
-            for sample in pcm {
-                writer
-                    .write_sample(sample.clamp(-1.0, 1.0))
-                    .with_context(|| {
+    pub fn write(&mut self, pcm: &[f32]) -> Result<()> {
+        let path = &self.path;
+        let writer = &mut self.writer;
+        match self.bit_depth {
+            AudioBitDepth::Bit32 => {
+                for sample in pcm {
+                    writer
+                        .write_sample(sample.clamp(-1.0, 1.0))
+                        .with_context(|| {
+                            format!("unable to write audio sample to {}", path.display())
+                        })?;
+                }
+            }
+            AudioBitDepth::Bit24 => {
+                for sample in pcm {
+                    let scaled = (sample.clamp(-1.0, 1.0) * 8_388_607.0).round() as i32;
+                    writer.write_sample(scaled).with_context(|| {
                         format!("unable to write audio sample to {}", path.display())
                     })?;
+                }
             }
-        }
-        AudioBitDepth::Bit24 => {
-            for sample in pcm {
-                let scaled = (sample.clamp(-1.0, 1.0) * 8_388_607.0).round() as i32;
-                writer.write_sample(scaled).with_context(|| {
-                    format!("unable to write audio sample to {}", path.display())
-                })?;
+            AudioBitDepth::Bit16 => {
+                for sample in pcm {
+                    let scaled = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+                    writer.write_sample(scaled).with_context(|| {
+                        format!("unable to write audio sample to {}", path.display())
+                    })?;
+                }
             }
-        }
-        AudioBitDepth::Bit16 => {
-            for sample in pcm {
-                let scaled = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
-                writer.write_sample(scaled).with_context(|| {
-                    format!("unable to write audio sample to {}", path.display())
-                })?;
+            AudioBitDepth::Bit8 => {
+                for sample in pcm {
+                    let scaled = (sample.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8;
+                    writer.write_sample(scaled).with_context(|| {
+                        format!("unable to write audio sample to {}", path.display())
+                    })?;
+                }
             }
         }
-        AudioBitDepth::Bit8 => {
-            for sample in pcm {
-                let scaled = (sample.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8;
-                writer.write_sample(scaled).with_context(|| {
-                    format!("unable to write audio sample to {}", path.display())
-                })?;
-            }
+        Ok(())
+    }
+
+    /// Complete the file; returns the depth actually written
+    pub fn finish(self) -> Result<AudioBitDepth> {
+        self.finish_with_markers(&[])
+    }
+
+    /// Complete the file with `markers` as cue points (see `append_cue_markers`)
+    pub fn finish_with_markers(self, markers: &[(f32, String)]) -> Result<AudioBitDepth> {
+        let Self {
+            writer,
+            bit_depth,
+            sample_rate,
+            path,
+            pending,
+        } = self;
+        writer.finalize().with_context(|| {
+            format!("failed to finalize WAV file writer for {}", path.display())
+        })?;
+        append_cue_markers(pending.temp(), markers, sample_rate)?;
+        pending
+            .commit()
+            .with_context(|| format!("unable to write audio file {}", path.display()))?;
+        Ok(bit_depth)
+    }
+}
+
+/// Append `cue ` and `LIST`/`adtl` label chunks to a finished WAV file so DAWs show
+/// `markers` (seconds and name) as cue points, then patch the RIFF size
+pub fn append_cue_markers(path: &Path, markers: &[(f32, String)], sample_rate: u32) -> Result<()> {
+    if markers.is_empty() {
+        return Ok(());
+    }
+    let mut cue = Vec::with_capacity(4 + markers.len() * 24);
+    cue.extend_from_slice(&(markers.len() as u32).to_le_bytes());
+    let mut labels = b"adtl".to_vec();
+    for (index, (time, name)) in markers.iter().enumerate() {
+        let id = index as u32 + 1;
+        let frame = (time.max(0.0) as f64 * sample_rate as f64).round() as u32;
+        cue.extend_from_slice(&id.to_le_bytes());
+        cue.extend_from_slice(&frame.to_le_bytes());
+        cue.extend_from_slice(b"data");
+        cue.extend_from_slice(&0u32.to_le_bytes());
+        cue.extend_from_slice(&0u32.to_le_bytes());
+        cue.extend_from_slice(&frame.to_le_bytes());
+
+        let mut text = name.as_bytes().to_vec();
+        text.push(0);
+        labels.extend_from_slice(b"labl");
+        labels.extend_from_slice(&(4 + text.len() as u32).to_le_bytes());
+        labels.extend_from_slice(&id.to_le_bytes());
+        labels.extend_from_slice(&text);
+        if text.len() % 2 == 1 {
+            labels.push(0);
         }
     }
 
-    writer
-        .finalize()
-        .with_context(|| format!("failed to finalize WAV file writer for {}", path.display()))?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .with_context(|| format!("failed to open {} for cue markers", path.display()))?;
+    let mut end = file.seek(SeekFrom::End(0))?;
+    let mut chunks = Vec::new();
+    // Chunks start on even offsets; an odd-sized data chunk gets its pad byte here
+    if end % 2 == 1 {
+        chunks.push(0);
+    }
+    for (id, body) in [(b"cue ", cue), (b"LIST", labels)] {
+        chunks.extend_from_slice(id);
+        chunks.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        chunks.extend_from_slice(&body);
+    }
+    end += chunks.len() as u64;
+    file.write_all(&chunks)?;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&((end - 8) as u32).to_le_bytes())
+        .with_context(|| format!("failed to write cue markers to {}", path.display()))?;
+    Ok(())
+}
+
+/// Read a WAV file back as interleaved f32, whatever its sample format
+pub fn read_wav(path: &Path) -> Result<Vec<f32>> {
+    let mut reader = WavReader::open(path)
+        .with_context(|| format!("failed to open WAV file {}", path.display()))?;
+    let spec = reader.spec();
+    let samples = match spec.sample_format {
+        SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>(),
+        SampleFormat::Int => {
+            let peak = ((1i64 << (spec.bits_per_sample - 1)) - 1) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|value| value as f32 / peak))
+                .collect()
+        }
+    };
+    samples.with_context(|| format!("unable to read audio samples from {}", path.display()))
+}
+
+/// Depth a FLAC or ALAC export is written at. Both are integer formats: 32-bit requests
+/// are written at 24 bits, and ALAC has no 8-bit mode.
+fn lossless_bit_depth(format: AudioFormat, requested_bit_depth: AudioBitDepth) -> AudioBitDepth {
+    match (format, requested_bit_depth) {
+        (_, AudioBitDepth::Bit32 | AudioBitDepth::Bit24) => AudioBitDepth::Bit24,
+        (AudioFormat::Flac, AudioBitDepth::Bit8) => AudioBitDepth::Bit8,
+        _ => AudioBitDepth::Bit16,
+    }
+}
+
+enum LosslessEncoder {
+    Flac(FlacStream<BufWriter<File>>),
+    Alac(AlacStream<BufWriter<File>>),
+}
+
+/// FLAC or ALAC file written chunk by chunk alongside a streamed WAV master
+pub struct LosslessFileStream {
+    encoder: LosslessEncoder,
+    bits: u8,
+    path: PathBuf,
+    pending: PendingFile,
+}
+
+impl LosslessFileStream {
+    pub fn create(
+        path: &Path,
+        format: AudioFormat,
+        sample_rate: u32,
+        requested_bit_depth: AudioBitDepth,
+        channels: AudioChannels,
+        tags: &BTreeMap<String, String>,
+    ) -> Result<Self> {
+        let bits = lossless_bit_depth(format, requested_bit_depth).bits() as u8;
+        let pending = PendingFile::new(path);
+        let file = File::create(pending.temp())
+            .with_context(|| format!("unable to write audio file {}", path.display()))?;
+        let writer = BufWriter::new(file);
+        let channel_count = channels.count() as u8;
+        let encoder = match format {
+            AudioFormat::Flac => FlacStream::new(writer, channel_count, sample_rate, bits, tags)
+                .map(LosslessEncoder::Flac),
+            AudioFormat::Alac => AlacStream::new(writer, channel_count, sample_rate, bits, tags)
+                .map(LosslessEncoder::Alac),
+            other => bail!("{} is not a lossless export format", other.label()),
+        }
+        .with_context(|| format!("failed to encode {}", path.display()))?;
+        Ok(Self {
+            encoder,
+            bits,
+            path: path.to_path_buf(),
+            pending,
+        })
+    }
 
+    pub fn write(&mut self, pcm: &[f32]) -> Result<()> {
+        let samples = quantize(pcm, self.bits);
+        match &mut self.encoder {
+            LosslessEncoder::Flac(stream) => stream.write(&samples),
+            LosslessEncoder::Alac(stream) => stream.write(&samples),
+        }
+        .with_context(|| format!("failed to encode {}", self.path.display()))
+    }
+
+    pub fn finish(self) -> Result<()> {
+        let path = self.path;
+        match self.encoder {
+            LosslessEncoder::Flac(stream) => stream.finish().map(drop),
+            LosslessEncoder::Alac(stream) => stream.finish().map(drop),
+        }
+        .with_context(|| format!("failed to encode {}", path.display()))?;
+        self.pending
+            .commit()
+            .with_context(|| format!("unable to write audio file {}", path.display()))
+    }
+}
+
+/// Write a FLAC or ALAC export with `tags` (see `lossless_bit_depth`). Returns the depth
+/// actually written.
+pub fn write_lossless(
+    path: &Path,
+    pcm: &[f32],
+    sample_rate: u32,
+    requested_bit_depth: AudioBitDepth,
+    channels: AudioChannels,
+    format: AudioFormat,
+    tags: &BTreeMap<String, String>,
+) -> Result<AudioBitDepth> {
+    let bit_depth = lossless_bit_depth(format, requested_bit_depth);
+    let bits = bit_depth.bits() as u8;
+    let samples = quantize(pcm, bits);
+    let channel_count = channels.count() as u8;
+
+    let bytes = match format {
+        AudioFormat::Flac => flac::encode(&samples, channel_count, sample_rate, bits, tags),
+        AudioFormat::Alac => alac::encode(&samples, channel_count, sample_rate, bits, tags),
+        other => bail!("{} is not a lossless export format", other.label()),
+    }
+    .with_context(|| format!("failed to encode {}", path.display()))?;
+
+    write_atomic(path, bytes)
+        .with_context(|| format!("unable to write audio file {}", path.display()))?;
     Ok(bit_depth)
 }
+
+#[cfg(test)]
+#[path = "test_writer.rs"]
+mod tests;
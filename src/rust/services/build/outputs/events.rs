@@ -0,0 +1,42 @@
+#![cfg(feature = "cli")]
+
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::to_string_pretty;
+
+use crate::engine::audio::event_export::EventsDocument;
+use crate::platform::storage::atomic::write_atomic;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventsWriter;
+
+impl EventsWriter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Write the event list as `events/<module>.json`
+    pub fn write(
+        &self,
+        document: &EventsDocument,
+        output_root: impl AsRef<Path>,
+        module_name: &str,
+    ) -> Result<PathBuf> {
+        let events_dir = output_root.as_ref().join("events");
+        create_dir_all(&events_dir).with_context(|| {
+            format!(
+                "failed to create events output directory: {}",
+                events_dir.display()
+            )
+        })?;
+
+        let file_path = events_dir.join(format!("{}.json", module_name));
+        let json = to_string_pretty(document).context("failed to serialize event list")?;
+        write_atomic(&file_path, json)
+            .with_context(|| format!("unable to write event list: {}", file_path.display()))?;
+
+        Ok(file_path)
+    }
+}
@@ -2,10 +2,14 @@
 
 use std::fs::{OpenOptions, create_dir_all};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use time::OffsetDateTime;
+
+use crate::engine::audio::events::PrintTimelineEntry;
+use crate::engine::audio::settings::LogTimelineFormat;
+use crate::platform::storage::atomic::write_atomic;
 use time::macros::format_description;
 
 const LOG_FILE_NAME: &str = "build.log";
@@ -35,6 +39,24 @@ impl LogWriter {
     }
 
     pub fn append(&self, output_root: impl AsRef<Path>, message: &str) -> Result<()> {
+        let timestamp = OffsetDateTime::now_utc();
+        let formatted = timestamp
+            .format(TIMESTAMP_FORMAT)
+            .unwrap_or_else(|_| "00:00:00".to_string());
+        self.append_record(output_root, &formatted, message)
+    }
+
+    /// Append without the wall-clock timestamp (deterministic builds)
+    pub fn append_untimed(&self, output_root: impl AsRef<Path>, message: &str) -> Result<()> {
+        self.append_record(output_root, "--:--:--", message)
+    }
+
+    fn append_record(
+        &self,
+        output_root: impl AsRef<Path>,
+        stamp: &str,
+        message: &str,
+    ) -> Result<()> {
         let root = output_root.as_ref().join("logs");
         create_dir_all(&root)
             .with_context(|| format!("failed to create log directory: {}", root.display()))?;
@@ -46,14 +68,36 @@ impl LogWriter {
             .open(&log_path)
             .with_context(|| format!("failed to open log file: {}", log_path.display()))?;
 
-        let timestamp = OffsetDateTime::now_utc();
-        let formatted = timestamp
-            .format(TIMESTAMP_FORMAT)
-            .unwrap_or_else(|_| "00:00:00".to_string());
-
-        writeln!(file, "[{}] {}", formatted, message)
+        writeln!(file, "[{}] {}", stamp, message)
             .with_context(|| format!("unable to write log record: {}", log_path.display()))?;
 
         Ok(())
     }
+
+    /// Export the print timeline as `logs/<module>.timeline.<ext>`.
+    pub fn write_timeline(
+        &self,
+        output_root: impl AsRef<Path>,
+        module_name: &str,
+        format: LogTimelineFormat,
+        entries: &[PrintTimelineEntry],
+    ) -> Result<PathBuf> {
+        let root = output_root.as_ref().join("logs");
+        create_dir_all(&root)
+            .with_context(|| format!("failed to create log directory: {}", root.display()))?;
+
+        let path = root.join(format!(
+            "{}.timeline.{}",
+            module_name,
+            format.file_extension()
+        ));
+        let contents = match format {
+            LogTimelineFormat::Json => serde_json::to_string_pretty(entries)
+                .context("failed to serialize print timeline")?,
+        };
+        write_atomic(&path, contents)
+            .with_context(|| format!("unable to write print timeline: {}", path.display()))?;
+
+        Ok(path)
+    }
 }
@@ -1,3 +1,4 @@
 pub mod ast;
 pub mod audio;
+pub mod events;
 pub mod logs;
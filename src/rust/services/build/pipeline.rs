@@ -1,20 +1,38 @@
 #![cfg(feature = "cli")]
 
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
 
-use crate::engine::audio::settings::{AudioBitDepth, AudioChannels, AudioFormat, ResampleQuality};
-use crate::language::syntax::ast::Statement;
+use crate::engine::audio::interpreter::driver::PersistSnapshot;
+use crate::engine::audio::mixer::InsertCache;
+use crate::engine::audio::playback::region::RenderFingerprint;
+use crate::engine::audio::samples::RateConversion;
+use crate::engine::audio::scene::SceneCue;
+use crate::engine::audio::settings::{
+    AudioBitDepth, AudioChannels, AudioFormat, BuildOutput, ClickMode, LogTimelineFormat,
+    MixSettings, RenderLimits, ResampleQuality,
+};
+use crate::engine::audio::solo::SoloMute;
+use crate::engine::audio::tempo::TempoMap;
+use crate::language::syntax::ast::{Statement, Value};
 use crate::language::syntax::parser::driver::SimpleParser;
+use crate::platform::storage::lock::{LockHolder, LockPolicy, ProjectLock};
 use crate::tools::logger::Logger;
 
+use super::manifest::{self, BuildManifest};
 use super::outputs::ast::AstBuilder;
 use super::outputs::audio::builder::AudioBuilder;
+use super::outputs::audio::helpers::content_hash;
+use super::outputs::events::EventsWriter;
 use super::outputs::logs::LogWriter;
 
+/// Seed used for every random source in `--deterministic` builds
+pub const DETERMINISTIC_SEED: u64 = 0x0DE7_A1A6;
+
 #[derive(Debug, Clone)]
 pub struct BuildRequest {
     pub entry_path: PathBuf,
@@ -23,8 +41,37 @@ pub struct BuildRequest {
     pub bit_depth: AudioBitDepth,
     pub channels: AudioChannels,
     pub resample_quality: ResampleQuality,
+    /// Resample every used sample to `sample_rate` before rendering (`audio.preconvert_samples`)
+    pub preconvert_samples: bool,
+    /// Render block size and mix accumulator precision
+    pub mix: MixSettings,
     pub sample_rate: u32,
     pub bpm: f32,
+    /// Export the print timeline alongside the build logs
+    pub log_timeline: Option<LogTimelineFormat>,
+    /// Extra files written next to the audio (`--outputs`)
+    pub outputs: Vec<BuildOutput>,
+    /// Fix random seeds and drop wall-clock values so identical sources render
+    /// byte-identical audio; the audio content hash is reported in the artifacts
+    pub deterministic: bool,
+    /// Top-level variables injected before interpretation (`--set key=value`)
+    pub variable_overrides: HashMap<String, Value>,
+    /// Banks loaded in place of others, keyed by alias or bank identifier (`--remap`)
+    pub bank_remaps: HashMap<String, String>,
+    /// Bank that bank-less triggers fall back to (`[banks] default`)
+    pub default_bank: Option<String>,
+    /// Metadata for FLAC/ALAC exports (`[audio.tags]`)
+    pub tags: BTreeMap<String, String>,
+    /// Cut trailing silence from the rendered audio
+    pub auto_trim: bool,
+    /// Stream the render to disk chunk by chunk (`audio.stream`)
+    pub stream: bool,
+    /// Groups soloed or muted from the command line (`--solo`, `--mute`)
+    pub solo_mute: SoloMute,
+    /// Metronome requested from the command line (`--click`); `metronome on` applies otherwise
+    pub click: Option<ClickMode>,
+    /// Audio length and render time bounds (the render API sets them; builds run unbounded)
+    pub limits: RenderLimits,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +90,28 @@ pub struct BuildArtifacts {
     pub audio_render_time: Duration,
     pub audio_length: Duration,
     pub total_duration: Duration,
+    /// SHA-256 of the primary audio file (deterministic builds only)
+    pub content_hash: Option<String>,
+    /// Samples that had to be converted to the project rate
+    pub sample_conversions: Vec<RateConversion>,
+    /// Collected events with their time spans, diffed by live rebuilds
+    pub fingerprint: RenderFingerprint,
+    /// Scene started by the last `switch`; live playback crossfades when it changes
+    pub scene: Option<SceneCue>,
+    /// Trailing silence removed by `auto_trim` (`audio_length` is the length after it)
+    pub trimmed: Duration,
+    /// Metronome stem (`<module>.click.wav`) and the mode it was written for
+    pub click: Option<(ClickMode, PathBuf)>,
+    /// Hardware output mix (`<module>.outputs.wav`) and its channel count
+    pub outputs: Option<(PathBuf, u16)>,
+    /// Cue points set by `mark` statements (seconds, name); `play --start-at` seeks to them
+    pub markers: Vec<(f32, String)>,
+    /// Tempo and meter changes of the render; live swaps wait for its bar lines
+    pub tempo_map: TempoMap,
+    /// Print timeline and event list files written next to the audio
+    pub extra_outputs: Vec<PathBuf>,
+    /// Outputs were restored from the build cache because no input changed
+    pub restored: bool,
 }
 
 #[derive(Clone)]
@@ -51,6 +120,28 @@ pub struct ProjectBuilder {
     ast_builder: AstBuilder,
     audio_builder: AudioBuilder,
     log_writer: LogWriter,
+    events_writer: EventsWriter,
+    /// `@persist` state handed from each build to the next (live rebuilds)
+    persisted: Arc<Mutex<PersistSnapshot>>,
+    /// Group inserts kept between live rebuilds so unchanged groups are not re-rendered
+    insert_cache: Option<Arc<Mutex<InsertCache>>>,
+    /// Project lock held for the length of every build
+    project_lock: Option<ProjectLockSettings>,
+    /// Skip builds whose inputs match the recorded manifest
+    input_manifest: Option<InputManifestSettings>,
+}
+
+#[derive(Debug, Clone)]
+struct InputManifestSettings {
+    deva_dir: PathBuf,
+    project_root: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+struct ProjectLockSettings {
+    deva_dir: PathBuf,
+    command: String,
+    policy: LockPolicy,
 }
 
 impl ProjectBuilder {
@@ -62,11 +153,75 @@ impl ProjectBuilder {
             ast_builder: AstBuilder::new(),
             audio_builder: AudioBuilder::new(log_writer, audio_logger),
             log_writer,
+            events_writer: EventsWriter::new(),
+            persisted: Arc::new(Mutex::new(PersistSnapshot::default())),
+            insert_cache: None,
+            project_lock: None,
+            input_manifest: None,
         }
     }
 
+    /// Hold the project lock in `deva_dir` while building, so builds from other
+    /// processes (`command` names this one to them) never interleave with ours
+    pub fn with_project_lock(
+        mut self,
+        deva_dir: PathBuf,
+        command: impl Into<String>,
+        policy: LockPolicy,
+    ) -> Self {
+        self.project_lock = Some(ProjectLockSettings {
+            deva_dir,
+            command: command.into(),
+            policy,
+        });
+        self
+    }
+
+    /// Record each build's inputs and outputs in `deva_dir`, and skip rendering when a
+    /// build's inputs (module graph, banks, config in `project_root`, settings) match the
+    /// last one recorded for its entry, restoring that build's outputs instead
+    pub fn with_input_manifest(mut self, deva_dir: PathBuf, project_root: PathBuf) -> Self {
+        self.input_manifest = Some(InputManifestSettings {
+            deva_dir,
+            project_root,
+        });
+        self
+    }
+
+    /// Keep each group's rendered insert between builds and re-render only the groups
+    /// whose events changed (watch / live mode)
+    pub fn with_insert_cache(mut self) -> Self {
+        self.insert_cache = Some(Arc::new(Mutex::new(InsertCache::new())));
+        self
+    }
+
     pub fn build(&self, request: &BuildRequest) -> Result<BuildArtifacts> {
+        let _lock = self.lock_project()?;
         let build_start = Instant::now();
+
+        let Some(settings) = &self.input_manifest else {
+            return self.render(request, build_start);
+        };
+        let cache_dir = manifest::entry_cache_dir(&settings.deva_dir, &request.entry_path);
+        let inputs = manifest::input_hash(request, &settings.project_root)?;
+        if let Some(recorded) = manifest::load(&cache_dir)
+            && recorded.inputs == inputs
+        {
+            manifest::restore(&cache_dir, &recorded)?;
+            self.logger.info(format!(
+                "No input changed since the last build of {}; outputs restored from the build cache",
+                request.entry_path.display()
+            ));
+            let statements = self.parse(&request.entry_path)?;
+            return Ok(recorded.artifacts(request, statements, build_start.elapsed()));
+        }
+
+        let artifacts = self.render(request, build_start)?;
+        manifest::save(&cache_dir, &BuildManifest::new(inputs, &artifacts))?;
+        Ok(artifacts)
+    }
+
+    fn render(&self, request: &BuildRequest, build_start: Instant) -> Result<BuildArtifacts> {
         self.logger.action(format!(
             "Building module from {}",
             request.entry_path.display()
@@ -89,6 +244,17 @@ impl ProjectBuilder {
             rms,
             render_time: audio_render_time,
             audio_length,
+            print_timeline,
+            events,
+            markers,
+            tempo_map,
+            persisted,
+            sample_conversions,
+            fingerprint,
+            scene,
+            trimmed,
+            click,
+            outputs,
         } = self.audio_builder.render_all_formats(
             &statements,
             &request.entry_path,
@@ -99,8 +265,32 @@ impl ProjectBuilder {
             request.channels,
             request.sample_rate,
             request.resample_quality,
+            request.preconvert_samples,
+            request.mix,
+            request.limits,
             request.bpm,
+            request.deterministic.then_some(DETERMINISTIC_SEED),
+            &request.variable_overrides,
+            &request.bank_remaps,
+            request.default_bank.as_deref(),
+            &request.tags,
+            request.auto_trim,
+            request.stream,
+            request.outputs.contains(&BuildOutput::EventsJson),
+            request.click,
+            &request.solo_mute,
+            &self.persisted.lock().map(|s| s.clone()).unwrap_or_default(),
+            self.insert_cache.as_ref(),
         )?;
+        if let Ok(mut state) = self.persisted.lock() {
+            *state = persisted;
+        }
+
+        let content_hash = if request.deterministic {
+            content_hash(&primary_path)?
+        } else {
+            None
+        };
 
         // Clear logs before writing new entries
         self.log_writer.clear(&request.output_root)?;
@@ -112,18 +302,77 @@ impl ProjectBuilder {
             .collect::<Vec<_>>()
             .join(", ");
 
-        self.log_writer.append(
-            &request.output_root,
-            &format!(
-                "Module '{}' built with {} statement(s); exported formats: [{}] ({} bits, {} ch, {:?})",
-                module_name,
-                statements.len(),
-                formats_str,
-                bit_depth.bits(),
-                request.channels.count(),
-                request.resample_quality
-            ),
-        )?;
+        let append_log = |message: &str| {
+            if request.deterministic {
+                self.log_writer
+                    .append_untimed(&request.output_root, message)
+            } else {
+                self.log_writer.append(&request.output_root, message)
+            }
+        };
+
+        append_log(&format!(
+            "Module '{}' built with {} statement(s); exported formats: [{}] ({} bits, {} ch, {:?})",
+            module_name,
+            statements.len(),
+            formats_str,
+            bit_depth.bits(),
+            request.channels.count(),
+            request.resample_quality
+        ))?;
+
+        if let Some(hash) = &content_hash {
+            append_log(&format!("Content hash (sha256): {}", hash))?;
+        }
+
+        if !sample_conversions.is_empty() {
+            append_log(&format!(
+                "{} sample(s) converted to {} Hz:",
+                sample_conversions.len(),
+                request.sample_rate
+            ))?;
+            for conversion in &sample_conversions {
+                append_log(&format!("  {}", conversion.describe()))?;
+            }
+        }
+
+        let sample_stats = crate::engine::audio::samples::get_stats();
+        if sample_stats.shared_bytes > 0 {
+            append_log(&format!(
+                "{} sample URI(s) share {} buffer(s); {:.1} KB saved by deduplication",
+                sample_stats.loaded,
+                sample_stats.buffers,
+                sample_stats.shared_bytes as f64 / 1024.0
+            ))?;
+        }
+
+        let mut extra_outputs = Vec::new();
+        if let Some(format) = request.log_timeline {
+            let timeline_path = self.log_writer.write_timeline(
+                &request.output_root,
+                &module_name,
+                format,
+                &print_timeline,
+            )?;
+            self.logger.info(format!(
+                "Print timeline ({} entries) written to {}",
+                print_timeline.len(),
+                timeline_path.display()
+            ));
+            extra_outputs.push(timeline_path);
+        }
+
+        if let Some(events) = &events {
+            let events_path =
+                self.events_writer
+                    .write(events, &request.output_root, &module_name)?;
+            self.logger.info(format!(
+                "Event list ({} events) written to {}",
+                events.events.len(),
+                events_path.display()
+            ));
+            extra_outputs.push(events_path);
+        }
 
         let total_duration = build_start.elapsed();
         self.logger.watch(format!(
@@ -147,9 +396,45 @@ impl ProjectBuilder {
             audio_render_time,
             audio_length,
             total_duration,
+            content_hash,
+            sample_conversions,
+            fingerprint,
+            scene,
+            trimmed,
+            click,
+            outputs,
+            markers,
+            tempo_map,
+            extra_outputs,
+            restored: false,
         })
     }
 
+    fn lock_project(&self) -> Result<Option<ProjectLock>> {
+        let Some(settings) = &self.project_lock else {
+            return Ok(None);
+        };
+        let lock = ProjectLock::acquire(
+            &settings.deva_dir,
+            &settings.command,
+            settings.policy,
+            |holder| {
+                let holder = holder
+                    .map(LockHolder::describe)
+                    .unwrap_or_else(|| "another devalang process".to_string());
+                match settings.policy {
+                    LockPolicy::Steal => self
+                        .logger
+                        .warn(format!("Taking the project lock over from {}", holder)),
+                    _ => self
+                        .logger
+                        .info(format!("Waiting for {} to finish building...", holder)),
+                }
+            },
+        )?;
+        Ok(Some(lock))
+    }
+
     fn parse(&self, entry: impl AsRef<Path>) -> Result<Vec<Statement>> {
         SimpleParser::parse_file(entry)
     }
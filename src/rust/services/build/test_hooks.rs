@@ -0,0 +1,17 @@
+use super::*;
+
+#[cfg(unix)]
+#[test]
+fn test_hook_runs_in_the_project_dir_with_its_environment() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = vec![(
+        "DEVALANG_OUTPUT".to_string(),
+        "output/track.wav".to_string(),
+    )];
+    run_hook("echo \"$DEVALANG_OUTPUT\" > hook.txt", dir.path(), &env).unwrap();
+    let written = std::fs::read_to_string(dir.path().join("hook.txt")).unwrap();
+    assert_eq!(written.trim(), "output/track.wav");
+
+    let error = run_hook("exit 3", dir.path(), &[]).unwrap_err().to_string();
+    assert!(error.contains("exited with status 3"), "{error}");
+}
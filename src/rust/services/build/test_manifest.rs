@@ -0,0 +1,141 @@
+use super::*;
+use crate::platform::config::AppConfig;
+
+fn request(dir: &Path) -> BuildRequest {
+    let config = AppConfig::default();
+    BuildRequest {
+        entry_path: dir.join("main.deva"),
+        output_root: dir.join("output"),
+        audio_formats: vec![AudioFormat::Wav],
+        bit_depth: config.audio_bit_depth(),
+        channels: config.audio_channels(),
+        resample_quality: config.resample_quality(),
+        preconvert_samples: false,
+        mix: config.mix_settings().unwrap(),
+        sample_rate: 44100,
+        bpm: 120.0,
+        log_timeline: None,
+        outputs: Vec::new(),
+        deterministic: false,
+        variable_overrides: Default::default(),
+        bank_remaps: Default::default(),
+        default_bank: None,
+        tags: Default::default(),
+        auto_trim: false,
+        stream: false,
+        solo_mute: Default::default(),
+        click: None,
+        limits: Default::default(),
+    }
+}
+
+#[test]
+fn test_input_hash_follows_imports_config_and_settings() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("main.deva"),
+        "import { lead } from \"./lib.deva\"\nlead -> note(C4)\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("lib.deva"),
+        "let lead = synth saw\nexport { lead }\n",
+    )
+    .unwrap();
+    fs::write(root.join("devalang.json"), "{}").unwrap();
+    let request = request(root);
+
+    let first = input_hash(&request, root).unwrap();
+    assert_eq!(first, input_hash(&request, root).unwrap());
+
+    fs::write(
+        root.join("lib.deva"),
+        "let lead = synth sine\nexport { lead }\n",
+    )
+    .unwrap();
+    let edited = input_hash(&request, root).unwrap();
+    assert_ne!(first, edited);
+
+    fs::write(root.join("devalang.json"), "{ \"audio\": {} }").unwrap();
+    let configured = input_hash(&request, root).unwrap();
+    assert_ne!(edited, configured);
+
+    let mut trimmed = request.clone();
+    trimmed.auto_trim = true;
+    assert_ne!(configured, input_hash(&trimmed, root).unwrap());
+}
+
+#[test]
+fn test_input_hash_follows_used_plugins() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let plugin_dir = root.join(".deva/plugins/acme/fuzz");
+    fs::create_dir_all(&plugin_dir).unwrap();
+    fs::write(
+        plugin_dir.join("plugin.toml"),
+        "[plugin]\nname = \"fuzz\"\n",
+    )
+    .unwrap();
+    fs::write(plugin_dir.join("fuzz.wasm"), [0, 97, 115, 109]).unwrap();
+    fs::write(root.join("main.deva"), "use acme.fuzz as fuzz\n").unwrap();
+    let request = request(root);
+
+    let first = input_hash(&request, root).unwrap();
+    fs::write(plugin_dir.join("fuzz.wasm"), [0, 97, 115, 109, 1]).unwrap();
+    let rebuilt = input_hash(&request, root).unwrap();
+    assert_ne!(first, rebuilt);
+    fs::write(
+        plugin_dir.join("plugin.toml"),
+        "[plugin]\nname = \"fuzz\"\nversion = \"0.2.0\"\n",
+    )
+    .unwrap();
+    assert_ne!(rebuilt, input_hash(&request, root).unwrap());
+}
+
+#[test]
+fn test_saved_outputs_are_restored() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("output/audio/main.wav");
+    fs::create_dir_all(output.parent().unwrap()).unwrap();
+    fs::write(&output, b"RIFF").unwrap();
+    let manifest = BuildManifest {
+        inputs: "abc".to_string(),
+        module_name: "main".to_string(),
+        primary_format: "wav".to_string(),
+        exported_formats: vec![("wav".to_string(), output.clone())],
+        ast_path: output.clone(),
+        primary_audio_path: output.clone(),
+        rms: 0.5,
+        audio_length: 2.0,
+        trimmed: 0.0,
+        content_hash: None,
+        sample_conversions: Vec::new(),
+        click: None,
+        outputs: None,
+        markers: vec![(1.0, "drop".to_string())],
+        tempo_map: {
+            let mut map = TempoMap::new();
+            map.push_meter(8.0, 3, 4);
+            map
+        },
+        files: vec![output.clone()],
+    };
+    let cache = entry_cache_dir(&dir.path().join(".deva"), &dir.path().join("main.deva"));
+    save(&cache, &manifest).unwrap();
+
+    fs::remove_file(&output).unwrap();
+    let recorded = load(&cache).unwrap();
+    assert_eq!(recorded, manifest);
+    restore(&cache, &recorded).unwrap();
+    assert_eq!(fs::read(&output).unwrap(), b"RIFF");
+
+    let artifacts = recorded.artifacts(&request(dir.path()), Vec::new(), Duration::ZERO);
+    assert!(artifacts.restored);
+    assert_eq!(artifacts.audio_length, Duration::from_secs(2));
+    assert_eq!(artifacts.exported_formats, vec![(AudioFormat::Wav, output)]);
+
+    // A cache missing its copies is not used
+    fs::remove_dir_all(cache.join(FILES_DIR)).unwrap();
+    assert!(load(&cache).is_none());
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "cli")]
+
+//! Keyboard capture for live sessions: keys play bank triggers quantized to the loop grid,
+//! Space taps the tempo and the arrow keys nudge it (Up/Down ±1 BPM, Right/Left ±0.1 BPM)
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use tokio::sync::mpsc;
+
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::playback::keys::KeyBinding;
+use crate::engine::audio::samples::{self, SampleData};
+use crate::engine::audio::settings::ResampleQuality;
+use crate::language::syntax::ast::{Statement, StatementKind};
+use crate::tools::logger::{self, Logger};
+
+#[derive(Debug, Clone)]
+pub struct LiveKeysRequest {
+    pub bindings: Vec<KeyBinding>,
+    /// Note value keypresses snap to, e.g. `1/16`
+    pub grid: String,
+    /// Grid step in beats
+    pub grid_beats: f32,
+    /// Write the quantized hits here when the session ends
+    pub record_take: Option<PathBuf>,
+}
+
+pub enum KeyPress {
+    Key(char),
+    /// Space: tap the tempo
+    Tap,
+    /// Arrow keys: move the tempo by this many BPM
+    Nudge(f32),
+    /// Esc or Ctrl-C: end the session
+    Quit,
+}
+
+/// Raw-mode keyboard reader; the terminal is restored when dropped
+pub struct KeyboardInput {
+    presses: mpsc::UnboundedReceiver<KeyPress>,
+    stop: Arc<AtomicBool>,
+}
+
+impl KeyboardInput {
+    pub fn start() -> Result<Self> {
+        terminal::enable_raw_mode().context("failed to capture the keyboard")?;
+        logger::set_raw_terminal(true);
+
+        let (tx, presses) = mpsc::unbounded_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_reader = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stop_reader.load(Ordering::Relaxed) {
+                // Poll so the thread notices `stop` instead of blocking on `read`
+                if !event::poll(Duration::from_millis(50)).unwrap_or(false) {
+                    continue;
+                }
+                let Ok(Event::Key(key)) = event::read() else {
+                    continue;
+                };
+                if key.kind == KeyEventKind::Release {
+                    continue;
+                }
+                let press = match key.code {
+                    KeyCode::Esc => KeyPress::Quit,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        KeyPress::Quit
+                    }
+                    KeyCode::Char(' ') => KeyPress::Tap,
+                    KeyCode::Up => KeyPress::Nudge(1.0),
+                    KeyCode::Down => KeyPress::Nudge(-1.0),
+                    KeyCode::Right => KeyPress::Nudge(0.1),
+                    KeyCode::Left => KeyPress::Nudge(-0.1),
+                    KeyCode::Char(c) => KeyPress::Key(c.to_ascii_lowercase()),
+                    _ => continue,
+                };
+                if tx.send(press).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self { presses, stop })
+    }
+
+    pub async fn next(&mut self) -> Option<KeyPress> {
+        self.presses.recv().await
+    }
+}
+
+impl Drop for KeyboardInput {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = terminal::disable_raw_mode();
+        logger::set_raw_terminal(false);
+    }
+}
+
+/// Next keypress, or never when the keyboard is not captured
+pub async fn next_press(input: &mut Option<KeyboardInput>) -> Option<KeyPress> {
+    match input {
+        Some(input) => input.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Samples for each bound key, resolved through the project's `bank` declarations
+pub fn resolve_triggers(
+    bindings: &[KeyBinding],
+    statements: &[Statement],
+    sample_rate: u32,
+    quality: ResampleQuality,
+    logger: &Logger,
+) -> HashMap<char, (String, SampleData)> {
+    let banks: Vec<Statement> = statements
+        .iter()
+        .filter(|stmt| matches!(stmt.kind, StatementKind::Bank { .. }))
+        .cloned()
+        .collect();
+    let mut interpreter = AudioInterpreter::new(sample_rate);
+    if let Err(err) = interpreter.collect_events(&banks) {
+        logger.warn(format!("Failed to load banks for key triggers: {err}"));
+    }
+
+    let mut triggers = HashMap::new();
+    for binding in bindings {
+        let uri = interpreter.resolve_sample_uri(&binding.trigger);
+        match samples::get_sample_at_rate(&uri, sample_rate, quality) {
+            Some(data) => {
+                triggers.insert(binding.key, (binding.trigger.clone(), data));
+            }
+            None => logger.warn(format!(
+                "Key '{}': trigger .{} not found",
+                binding.key, binding.trigger
+            )),
+        }
+    }
+    triggers
+}
+
+/// Tempo set by the first top-level `bpm` statement, or `fallback`
+pub fn project_bpm(statements: &[Statement], fallback: f32) -> f32 {
+    statements
+        .iter()
+        .find_map(|stmt| match &stmt.kind {
+            StatementKind::Tempo { value, body: None } => Some(*value),
+            _ => None,
+        })
+        .unwrap_or(fallback)
+}
@@ -0,0 +1,217 @@
+#![cfg(feature = "cli")]
+
+//! Live macro control: keys, MIDI control changes and OSC messages move `macro` values,
+//! each move becoming a `--set` override for the next rebuild
+//!
+//! Moves are not heard right away. Changes queued while a rebuild runs are folded into
+//! the next one. The new render then replaces the playing loop like an edited file does.
+//! A macro usually changes the whole loop, so it switches in at the end of the current
+//! pass. Expect the build time plus up to one loop length between a move and hearing it.
+//!
+//! OSC messages are `/deva/macro/<name> f:value` (or `i:value`), with the value in 0..1.
+//! The listener binds every network interface (`0.0.0.0`), so controllers on other
+//! machines of the network can reach it, and so can anyone else on that network.
+
+use std::collections::HashMap;
+use std::net::UdpSocket;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use midir::{MidiInput, MidiInputConnection};
+use tokio::sync::mpsc;
+
+use crate::engine::audio::macros::{MacroCc, MacroChange, MacroKey};
+use crate::engine::audio::playback::osc::{OscArg, decode_message};
+use crate::language::syntax::ast::{Statement, StatementKind, Value};
+use crate::tools::logger::Logger;
+
+const OSC_MACRO_PREFIX: &str = "/deva/macro/";
+/// Address the OSC listener binds
+pub const OSC_MACRO_HOST: &str = "0.0.0.0";
+
+#[derive(Debug, Clone, Default)]
+pub struct LiveMacrosRequest {
+    pub keys: Vec<MacroKey>,
+    pub ccs: Vec<MacroCc>,
+    /// UDP port OSC macro messages are received on
+    pub osc_port: Option<u16>,
+}
+
+impl LiveMacrosRequest {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.ccs.is_empty() && self.osc_port.is_none()
+    }
+
+    pub fn key(&self, key: char) -> Option<&MacroKey> {
+        self.keys.iter().find(|binding| binding.key == key)
+    }
+}
+
+/// MIDI and OSC listeners feeding macro changes to the live session; they stop when dropped
+pub struct MacroInput {
+    changes: mpsc::UnboundedReceiver<(String, MacroChange)>,
+    stop: Arc<AtomicBool>,
+    _midi: Vec<MidiInputConnection<()>>,
+}
+
+impl MacroInput {
+    pub fn start(request: &LiveMacrosRequest, logger: &Logger) -> Result<Self> {
+        let (tx, changes) = mpsc::unbounded_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let mut midi = Vec::new();
+        if !request.ccs.is_empty() {
+            let ports = MidiInput::new("devalang-macros")
+                .map(|input| input.ports())
+                .unwrap_or_default();
+            if ports.is_empty() {
+                logger.warn("No MIDI input ports; macro CC bindings are inactive");
+            }
+            for port in ports {
+                let input =
+                    MidiInput::new("devalang-macros").context("failed to open MIDI input")?;
+                let name = input
+                    .port_name(&port)
+                    .unwrap_or_else(|_| "unknown".to_string());
+                let bindings: HashMap<u8, String> = request
+                    .ccs
+                    .iter()
+                    .map(|cc| (cc.controller, cc.name.clone()))
+                    .collect();
+                let tx = tx.clone();
+                let connection = input.connect(
+                    &port,
+                    "devalang-macros",
+                    move |_stamp, message, _| {
+                        if let Some((controller, value)) =
+                            crate::engine::audio::midi::control_change(message)
+                            && let Some(name) = bindings.get(&controller)
+                        {
+                            let _ = tx.send((name.clone(), MacroCc::change(value)));
+                        }
+                    },
+                    (),
+                );
+                match connection {
+                    Ok(connection) => midi.push(connection),
+                    Err(err) => logger.warn(format!("MIDI input {name} unavailable: {err}")),
+                }
+            }
+        }
+
+        if let Some(port) = request.osc_port {
+            // Every interface, for controllers on a phone or tablet
+            let socket = UdpSocket::bind((OSC_MACRO_HOST, port))
+                .with_context(|| format!("failed to listen for OSC on port {port}"))?;
+            // Time out so the thread notices `stop`
+            socket.set_read_timeout(Some(Duration::from_millis(200)))?;
+            let stop_reader = Arc::clone(&stop);
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let mut buffer = [0u8; 1024];
+                while !stop_reader.load(Ordering::Relaxed) {
+                    let Ok(len) = socket.recv(&mut buffer) else {
+                        continue;
+                    };
+                    if let Some(change) = osc_change(&buffer[..len])
+                        && tx.send(change).is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            changes,
+            stop,
+            _midi: midi,
+        })
+    }
+
+    /// Wait for a change, then take every change already queued behind it so a moving
+    /// fader costs one rebuild
+    pub async fn next(&mut self) -> Option<Vec<(String, MacroChange)>> {
+        let mut changes = vec![self.changes.recv().await?];
+        while let Ok(change) = self.changes.try_recv() {
+            changes.push(change);
+        }
+        Some(changes)
+    }
+}
+
+impl Drop for MacroInput {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Next batch of macro changes, or never when no listener runs
+pub async fn next_changes(input: &mut Option<MacroInput>) -> Option<Vec<(String, MacroChange)>> {
+    match input {
+        Some(input) => input.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// `/deva/macro/<name>` with a float or int value
+fn osc_change(packet: &[u8]) -> Option<(String, MacroChange)> {
+    let (address, args) = decode_message(packet)?;
+    let name = address.strip_prefix(OSC_MACRO_PREFIX)?;
+    let value = match args.first()? {
+        OscArg::Float(value) => *value,
+        OscArg::Int(value) => *value as f32,
+        OscArg::Str(_) => return None,
+    };
+    Some((name.to_string(), MacroChange::Set(value)))
+}
+
+/// Names declared by top-level `macro` statements
+pub fn declared_macros(statements: &[Statement]) -> Vec<String> {
+    statements
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StatementKind::Macro { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Apply `changes` to the overrides of the next build; returns the macros that moved
+/// with their new values (none when every value was already at its end of the range).
+/// Changes to undeclared macros are reported and skipped.
+pub fn apply_changes(
+    overrides: &mut HashMap<String, Value>,
+    declared: &[String],
+    changes: Vec<(String, MacroChange)>,
+    logger: &Logger,
+) -> Vec<(String, f32)> {
+    let mut moved: Vec<(String, f32)> = Vec::new();
+    for (name, change) in changes {
+        if !declared.contains(&name) {
+            logger.warn(format!("Unknown macro '{name}'"));
+            continue;
+        }
+        let current = match overrides.get(&name) {
+            Some(Value::Number(value)) => *value,
+            _ => 1.0,
+        };
+        let value = change.apply(current);
+        if value == current {
+            continue;
+        }
+        overrides.insert(name.clone(), Value::Number(value));
+        match moved.iter_mut().find(|(moved, _)| *moved == name) {
+            Some(entry) => entry.1 = value,
+            None => moved.push((name, value)),
+        }
+    }
+    moved
+}
+
+#[cfg(test)]
+#[path = "test_macros.rs"]
+mod tests;
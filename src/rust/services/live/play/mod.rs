@@ -1,17 +1,33 @@
 #![cfg(feature = "cli")]
 
+pub mod keyboard;
+pub mod macros;
+
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use tokio::select;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
 
+use crate::engine::audio::macros::MacroChange;
+use crate::engine::audio::playback::keys::{Quantizer, Take};
 use crate::engine::audio::playback::live::{
-    LiveAudioSource, LivePlaybackEngine, LivePlaybackOptions,
+    LiveAudioSource, LivePlaybackEngine, LivePlaybackOptions, OutputDeviceConfig, PreparedLoop,
 };
+use crate::engine::audio::playback::osc::OscSettings;
+use crate::engine::audio::playback::recording::{SessionRecorder, SessionRecording};
+use crate::engine::audio::playback::region::RegionDiff;
+use crate::engine::audio::playback::speed::{LiveRate, PreviewRate};
+use crate::engine::audio::playback::tempo::LiveTempo;
+use crate::engine::audio::settings::{AudioFormat, ClickMode};
+use crate::language::syntax::parser::driver::find_keyword_suggestion;
 use crate::services::build::pipeline::{BuildArtifacts, BuildRequest, ProjectBuilder};
 use crate::services::watch::file::{FileWatcher, WatchOptions};
 use crate::tools::logger::Logger;
+use keyboard::{KeyPress, KeyboardInput, LiveKeysRequest};
+use macros::{LiveMacrosRequest, MacroInput};
 
 #[derive(Debug, Clone)]
 pub struct LivePlayRequest {
@@ -19,12 +35,35 @@ pub struct LivePlayRequest {
     pub live_mode: bool,
     pub crossfade_ms: u64,
     pub volume: f32,
+    /// Mirror playback to OSC (live mode only)
+    pub osc: Option<OscSettings>,
+    /// Play the render back faster or slower (`--preview-rate`)
+    pub preview: Option<PreviewRate>,
+    /// Computer keys bound to bank triggers (live mode only)
+    pub keys: Option<LiveKeysRequest>,
+    /// Keys, MIDI CCs and OSC messages moving `macro` controls (live mode only)
+    pub macros: Option<LiveMacrosRequest>,
+    /// Record the master output with rebuild/scene markers (live mode only)
+    pub record: Option<SessionRecording>,
+    /// `mark` name or seconds playback starts from (`--start-at`)
+    pub start_at: Option<String>,
+    /// Rebuild and decode in a worker while the loop plays, then swap at the next bar
+    /// (`--prerender`, live mode only)
+    pub prerender: bool,
+}
+
+/// A finished live rebuild, with its loop already decoded when it was prerendered
+struct Rebuild {
+    path: PathBuf,
+    result: Result<(BuildArtifacts, Option<PreparedLoop>)>,
 }
 
 pub struct LivePlayService {
     logger: Arc<Logger>,
     playback: LivePlaybackEngine,
     builder: ProjectBuilder,
+    /// Output device the playback engine was opened with
+    output: OutputDeviceConfig,
     /// Guard clone to keep bg_rx alive for the duration of a live session
     bg_rx_guard: std::sync::Mutex<
         Option<
@@ -38,13 +77,18 @@ pub struct LivePlayService {
 }
 
 impl LivePlayService {
-    pub fn new(logger: Arc<Logger>, builder: ProjectBuilder) -> Result<Self> {
-        let playback = LivePlaybackEngine::new(logger.clone())
+    pub fn new(
+        logger: Arc<Logger>,
+        builder: ProjectBuilder,
+        output: OutputDeviceConfig,
+    ) -> Result<Self> {
+        let playback = LivePlaybackEngine::with_output(logger.clone(), &output)
             .context("failed to initialise audio playback engine")?;
         Ok(Self {
             logger,
             playback,
             builder,
+            output,
             bg_rx_guard: std::sync::Mutex::new(None),
         })
     }
@@ -76,8 +120,12 @@ impl LivePlayService {
             artifacts.primary_audio_path.display()
         ));
 
-        let source = LiveAudioSource::from_artifacts(&artifacts);
-        self.playback.play_once(source, request.volume).await?;
+        let channels = self.output_channels(&artifacts);
+        let source = LiveAudioSource::from_artifacts(&artifacts, channels)
+            .starting_at(self.start_position(&request, &artifacts)?);
+        self.playback
+            .play_once(source, request.volume, request.preview)
+            .await?;
         self.logger.info("Playback finished.");
         Ok(())
     }
@@ -102,9 +150,45 @@ impl LivePlayService {
             format_duration(artifacts.audio_length)
         ));
         let poll = Duration::from_millis(request.crossfade_ms.max(10));
-        let options = LivePlaybackOptions::new(poll).with_volume(request.volume);
+        let mut options = LivePlaybackOptions::new(poll)
+            .with_crossfade(Duration::from_millis(request.crossfade_ms))
+            .with_volume(request.volume)
+            .with_output(self.output.clone())
+            .with_preview_rate(request.preview);
+        if let Some(osc) = request.osc.clone() {
+            options = options.with_osc(osc, request.build.bpm);
+        }
+        // Tap/nudge keys need the keyboard, which is only captured on a terminal
+        let macro_keys = request
+            .macros
+            .as_ref()
+            .is_some_and(|macros| !macros.keys.is_empty());
+        let interactive = request.keys.is_some() || macro_keys || atty::is(atty::Stream::Stdin);
+        let mut tempo = LiveTempo::new(keyboard::project_bpm(
+            &artifacts.statements,
+            request.build.bpm,
+        ));
+        let live_rate = LiveRate::new(1.0);
+        if interactive {
+            options = options.with_live_tempo(live_rate.clone());
+        }
+        let recorder = match &request.record {
+            Some(recording) => {
+                let recorder = SessionRecorder::start(recording, &session_stem())?;
+                self.logger.info(format!(
+                    "Recording the session to {} (new file every {})",
+                    recording.dir.display(),
+                    format_duration(recording.segment)
+                ));
+                options = options.with_recorder(recorder.clone());
+                Some(recorder)
+            }
+            None => None,
+        };
 
-        let initial_source = LiveAudioSource::from_artifacts(&artifacts);
+        let channels = self.output_channels(&artifacts);
+        let initial_source = LiveAudioSource::from_artifacts(&artifacts, channels)
+            .starting_at(self.start_position(&request, &artifacts)?);
 
         // Spawn a persistent interpreter thread to keep "loop pass" background workers
         // alive and to print realtime messages while the live session is active.
@@ -291,6 +375,86 @@ impl LivePlayService {
             "Live mode watching {}",
             request.build.entry_path.display()
         ));
+        let mut keyboard = None;
+        let mut triggers = Default::default();
+        let mut take = Take::default();
+        if let Some(keys) = &request.keys {
+            triggers = keyboard::resolve_triggers(
+                &keys.bindings,
+                &artifacts.statements,
+                artifacts.sample_rate,
+                artifacts.resample_quality,
+                &self.logger,
+            );
+        }
+        if interactive {
+            match KeyboardInput::start() {
+                Ok(input) => {
+                    keyboard = Some(input);
+                    if let Some(keys) = &request.keys {
+                        let mut bound: Vec<String> = triggers
+                            .iter()
+                            .map(|(key, (trigger, _))| format!("{key} -> .{trigger}"))
+                            .collect();
+                        bound.sort();
+                        self.logger.info(format!(
+                            "Keys quantized to {}: {} (Esc to stop)",
+                            keys.grid,
+                            bound.join(", ")
+                        ));
+                    }
+                    self.logger.info(format!(
+                        "Tempo {} BPM: Space to tap, Up/Down ±1, Right/Left ±0.1",
+                        tempo.bpm()
+                    ));
+                }
+                Err(err) => self
+                    .logger
+                    .warn(format!("Keyboard controls disabled: {err}")),
+            }
+        }
+        let mut quantizer = live_quantizer(&request, &artifacts);
+        if request.prerender {
+            self.logger
+                .info("Prerendering rebuilds in the background; they swap in at the next bar");
+        }
+        let (rebuilt_tx, mut rebuilt_rx) = unbounded_channel::<Rebuild>();
+        let mut prerendering = false;
+        // Latest change seen while a prerender was running
+        let mut waiting: Option<PathBuf> = None;
+
+        // Macro moves become overrides of every later rebuild
+        let mut build = request.build.clone();
+        let mut declared_macros = macros::declared_macros(&artifacts.statements);
+        let mut macro_input = None;
+        if let Some(macros) = request.macros.as_ref().filter(|macros| !macros.is_empty()) {
+            if !macros.ccs.is_empty() || macros.osc_port.is_some() {
+                match MacroInput::start(macros, &self.logger) {
+                    Ok(input) => macro_input = Some(input),
+                    Err(err) => self.logger.warn(format!("Macro controls disabled: {err}")),
+                }
+            }
+            let mut bound: Vec<String> = macros
+                .keys
+                .iter()
+                .map(|key| format!("{} -> {}", key.key, key.name))
+                .chain(
+                    macros
+                        .ccs
+                        .iter()
+                        .map(|cc| format!("CC{} -> {}", cc.controller, cc.name)),
+                )
+                .collect();
+            if let Some(port) = macros.osc_port {
+                bound.push(format!(
+                    "OSC {}:{port}/deva/macro/<name>",
+                    macros::OSC_MACRO_HOST
+                ));
+            }
+            self.logger
+                .info(format!("Macro controls: {}", bound.join(", ")));
+        }
+
         let watcher = FileWatcher::new(self.logger.clone());
         let mut stream = watcher
             .watch(request.build.entry_path.clone(), WatchOptions::default())
@@ -302,77 +466,437 @@ impl LivePlayService {
                 change = stream.next_change() => {
                     match change {
                         Some(path) => {
-                            self.logger.watch(format!("Rebuilding after change at {}", path.display()));
-                            match self.builder.build(&request.build) {
-                                Ok(new_artifacts) => {
-                                    self.logger
-                                        .debug(format!("Build RMS: {:.4}", new_artifacts.rms));
-                                    self.logger.watch(format!(
-                                        "Audio regenerated in {} (total build {})",
-                                        format_duration(new_artifacts.audio_render_time),
-                                        format_duration(new_artifacts.total_duration)
+                            self.schedule_rebuild(&request, &build, path, &mut prerendering, &mut waiting, &rebuilt_tx);
+                        }
+                        None => {
+                            self.logger.warn("Watch stream ended; shutting down live playback");
+                            break;
+                        }
+                    }
+                }
+                Some(Rebuild { path, result }) = rebuilt_rx.recv() => {
+                    prerendering = false;
+                    if let Some(next) = waiting.take() {
+                        self.logger.info("Newer changes arrived while prerendering; rebuilding again");
+                        self.prerender(&build, next, rebuilt_tx.clone());
+                        prerendering = true;
+                        continue;
+                    }
+                    match result {
+                        Ok((new_artifacts, prepared)) => {
+                            self.logger
+                                .debug(format!("Build RMS: {:.4}", new_artifacts.rms));
+                            self.logger.watch(format!(
+                                "Audio regenerated in {} (total build {})",
+                                format_duration(new_artifacts.audio_render_time),
+                                format_duration(new_artifacts.total_duration)
+                            ));
+                            self.logger.info(format!(
+                                "Loop length ≈ {}",
+                                format_duration(new_artifacts.audio_length)
+                            ));
+                            if new_artifacts.audio_render_time < best_audio_render_time {
+                                best_audio_render_time = new_artifacts.audio_render_time;
+                                self.logger.success(format!(
+                                    "⏱️ New best audio regen time: {}",
+                                    format_duration(best_audio_render_time)
+                                ));
+                            } else {
+                                self.logger.info(format!(
+                                    "Best audio regen time so far: {}",
+                                    format_duration(best_audio_render_time)
+                                ));
+                            }
+                            // Stop previous persistent interpreter (if any) and spawn a new one for updated statements
+                            if let Some(tx) = persistent_stop_tx.take() {
+                                let _ = tx.send(());
+                            }
+                            if let Some(handle) = persistent_handle.take() {
+                                let _ = handle.join();
+                            }
+
+                            if let Some(recorder) = &recorder {
+                                let scene = new_artifacts
+                                    .scene
+                                    .as_ref()
+                                    .filter(|cue| {
+                                        artifacts.scene.as_ref().map(|playing| &playing.scene)
+                                            != Some(&cue.scene)
+                                    });
+                                recorder.mark(match scene {
+                                    Some(cue) => format!("scene {}", cue.scene),
+                                    None => format!("rebuild {}", path.display()),
+                                });
+                            }
+                            let region = changed_region(&artifacts, &new_artifacts);
+                            let scene_fade = new_artifacts.scene.as_ref().and_then(|cue| {
+                                cue.transition_from(artifacts.scene.as_ref())
+                                    .map(|fade| (cue.scene.clone(), fade))
+                            });
+                            artifacts = new_artifacts;
+                            declared_macros = macros::declared_macros(&artifacts.statements);
+                            let playing_bars = quantizer;
+                            quantizer = live_quantizer(&request, &artifacts);
+                            if tempo.rebase(keyboard::project_bpm(&artifacts.statements, request.build.bpm)) {
+                                self.logger.info(format!(
+                                    "Project tempo changed; live tempo reset to {} BPM",
+                                    tempo.bpm()
+                                ));
+                            }
+                            live_rate.set(tempo.rate());
+                            if let Some(keys) = &request.keys {
+                                triggers = keyboard::resolve_triggers(
+                                    &keys.bindings,
+                                    &artifacts.statements,
+                                    artifacts.sample_rate,
+                                    artifacts.resample_quality,
+                                    &self.logger,
+                                );
+                            }
+                            let (tx, handle) = spawn_persistent(artifacts.statements.clone(), artifacts.sample_rate, bg_tx.clone(), bg_rx.clone(), self.logger.clone());
+                            persistent_stop_tx = Some(tx);
+                            persistent_handle = Some(handle);
+
+                            let next_source = LiveAudioSource::from_artifacts(&artifacts, channels);
+                            // Prerendered builds swap in at the next bar; otherwise only the
+                            // changed window is patched into the playing loop and anything
+                            // else switches after the current pass
+                            let queued = match (scene_fade, prepared, region) {
+                                (Some((scene, fade)), _, _) => {
+                                    self.logger.info(format!(
+                                        "Switching to scene '{}' with a {:.2}s crossfade",
+                                        scene, fade
                                     ));
+                                    session
+                                        .crossfade_to(next_source.clone(), Duration::from_secs_f32(fade))
+                                        .or_else(|err| {
+                                            self.logger.warn(format!("Scene crossfade failed: {err}"));
+                                            session.queue_source(next_source)
+                                        })
+                                }
+                                (None, Some(prepared), _) => {
+                                    let at = playing_bars.next_bar(session.playhead());
                                     self.logger.info(format!(
-                                        "Loop length ≈ {}",
-                                        format_duration(new_artifacts.audio_length)
+                                        "Prerendered; swapping in at the bar line at {:.2}s",
+                                        at
                                     ));
-                                    if new_artifacts.audio_render_time < best_audio_render_time {
-                                        best_audio_render_time = new_artifacts.audio_render_time;
-                                        self.logger.success(format!(
-                                            "⏱️ New best audio regen time: {}",
-                                            format_duration(best_audio_render_time)
-                                        ));
-                                    } else {
-                                        self.logger.info(format!(
-                                            "Best audio regen time so far: {}",
-                                            format_duration(best_audio_render_time)
-                                        ));
-                                    }
-                                    // Stop previous persistent interpreter (if any) and spawn a new one for updated statements
-                                    if let Some(tx) = persistent_stop_tx.take() {
-                                        let _ = tx.send(());
-                                    }
-                                    if let Some(handle) = persistent_handle.take() {
-                                        let _ = handle.join();
-                                    }
-
-                                    artifacts = new_artifacts;
-                                    let (tx, handle) = spawn_persistent(artifacts.statements.clone(), artifacts.sample_rate, bg_tx.clone(), bg_rx.clone(), self.logger.clone());
-                                    persistent_stop_tx = Some(tx);
-                                    persistent_handle = Some(handle);
-
-                                    let next_source = LiveAudioSource::from_artifacts(&artifacts);
-                                    if let Err(err) = session.queue_source(next_source) {
-                                        self.logger.error(format!("Failed to queue live buffer: {err}"));
-                                    }
+                                    session.swap_at(
+                                        prepared,
+                                        Duration::from_millis(request.crossfade_ms),
+                                        at,
+                                    )
                                 }
-                                Err(err) => {
-                                    self.logger.error(format!("Build failed after change: {err}"));
+                                (None, None, Some((start, end))) => {
+                                    self.logger.info(format!(
+                                        "Changes limited to {:.2}s-{:.2}s; patching playing loop",
+                                        start, end
+                                    ));
+                                    session
+                                        .patch_region(next_source.clone(), start, end)
+                                        .or_else(|err| {
+                                            self.logger.warn(format!("Live patch failed: {err}"));
+                                            session.queue_source(next_source)
+                                        })
                                 }
+                                (None, None, None) => session.queue_source(next_source),
+                            };
+                            if let Err(err) = queued {
+                                self.logger.error(format!("Failed to queue live buffer: {err}"));
                             }
                         }
-                        None => {
-                            self.logger.warn("Watch stream ended; shutting down live playback");
+                        Err(err) => {
+                            self.logger.error(format!("Build failed after change: {err}"));
+                        }
+                    }
+                }
+                press = keyboard::next_press(&mut keyboard) => {
+                    match press {
+                        Some(KeyPress::Key(key)) => {
+                            if let Some(binding) = request.macros.as_ref().and_then(|macros| macros.key(key)) {
+                                let changes = vec![(binding.name.clone(), binding.change)];
+                                if self.move_macros(&mut build, &declared_macros, changes) {
+                                    let path = request.build.entry_path.clone();
+                                    self.schedule_rebuild(&request, &build, path, &mut prerendering, &mut waiting, &rebuilt_tx);
+                                }
+                                continue;
+                            }
+                            let Some((trigger, sample)) = triggers.get(&key) else {
+                                continue;
+                            };
+                            let (time, delay) = quantizer.snap(session.playhead());
+                            if let Err(err) =
+                                session.inject(sample.samples.clone(), sample.sample_rate, delay)
+                            {
+                                self.logger.warn(format!("Failed to play .{trigger}: {err}"));
+                            }
+                            take.record(time, trigger);
+                        }
+                        Some(KeyPress::Tap) => {
+                            if let Some(bpm) = tempo.tap(std::time::Instant::now()) {
+                                live_rate.set(tempo.rate());
+                                self.logger.info(format!("Tapped tempo: {bpm} BPM"));
+                            }
+                        }
+                        Some(KeyPress::Nudge(delta)) => {
+                            let bpm = tempo.nudge(delta);
+                            live_rate.set(tempo.rate());
+                            self.logger.info(format!("Tempo: {bpm} BPM ({:.3}x)", tempo.rate()));
+                        }
+                        Some(KeyPress::Quit) | None => {
+                            self.logger.info("Stopping live session");
                             break;
                         }
                     }
                 }
+                changes = macros::next_changes(&mut macro_input) => {
+                    match changes {
+                        Some(changes) => {
+                            if self.move_macros(&mut build, &declared_macros, changes) {
+                                let path = request.build.entry_path.clone();
+                                self.schedule_rebuild(&request, &build, path, &mut prerendering, &mut waiting, &rebuilt_tx);
+                            }
+                        }
+                        None => macro_input = None,
+                    }
+                }
                 _ = session.heartbeat() => {}
             }
         }
+        drop(keyboard);
+        drop(macro_input);
+
+        if let Some(keys) = &request.keys
+            && let Some(path) = &keys.record_take
+        {
+            if take.is_empty() {
+                self.logger.info("No keys played; take not written");
+            } else {
+                match std::fs::write(path, take.to_source(&quantizer, &keys.grid)) {
+                    Ok(()) => self.logger.success(format!(
+                        "Recorded {} hit(s) to {}",
+                        take.len(),
+                        path.display()
+                    )),
+                    Err(err) => self
+                        .logger
+                        .error(format!("Failed to write take {}: {err}", path.display())),
+                }
+            }
+        }
 
         // Wait for session completion, then clear our guardian clone so the receiver may be dropped
         let res = session.finish().await;
+        if let Some(recorder) = recorder {
+            match recorder.finish() {
+                Ok(files) => self.logger.success(format!(
+                    "Session recorded to {} file(s), markers alongside: {}",
+                    files.len(),
+                    files
+                        .iter()
+                        .map(|file| file.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+                Err(err) => self
+                    .logger
+                    .error(format!("Failed to finish the session recording: {err}")),
+            }
+        }
         // clear guard
         if let Ok(mut g) = self.bg_rx_guard.lock() {
             let _ = g.take();
         }
         res
     }
+
+    /// Rebuild after a change at `path`: inline, or prerendered in the background with
+    /// at most one prerender running and the latest change queued behind it
+    fn schedule_rebuild(
+        &self,
+        request: &LivePlayRequest,
+        build: &BuildRequest,
+        path: PathBuf,
+        prerendering: &mut bool,
+        waiting: &mut Option<PathBuf>,
+        rebuilt_tx: &UnboundedSender<Rebuild>,
+    ) {
+        if !request.prerender {
+            self.logger
+                .watch(format!("Rebuilding after change at {}", path.display()));
+            let result = self.builder.build(build).map(|built| (built, None));
+            let _ = rebuilt_tx.send(Rebuild { path, result });
+        } else if *prerendering {
+            // Builds share output files, so only one runs at a time
+            *waiting = Some(path);
+        } else {
+            self.prerender(build, path, rebuilt_tx.clone());
+            *prerendering = true;
+        }
+    }
+
+    /// Fold macro changes into the overrides of `build`; true when a value moved and the
+    /// loop needs rebuilding
+    fn move_macros(
+        &self,
+        build: &mut BuildRequest,
+        declared: &[String],
+        changes: Vec<(String, MacroChange)>,
+    ) -> bool {
+        let moved = macros::apply_changes(
+            &mut build.variable_overrides,
+            declared,
+            changes,
+            &self.logger,
+        );
+        for (name, value) in &moved {
+            self.logger.info(format!("Macro {name}: {value:.2}"));
+        }
+        !moved.is_empty()
+    }
+
+    /// Build and decode the change at `path` on a worker thread; the result comes back
+    /// through `done` while the current loop keeps playing
+    fn prerender(&self, build: &BuildRequest, path: PathBuf, done: UnboundedSender<Rebuild>) {
+        self.logger
+            .watch(format!("Prerendering after change at {}", path.display()));
+        let builder = self.builder.clone();
+        let build = build.clone();
+        let channels = self.playback.channels();
+        tokio::task::spawn_blocking(move || {
+            let result = builder.build(&build).and_then(|artifacts| {
+                let prepared =
+                    PreparedLoop::load(LiveAudioSource::from_artifacts(&artifacts, channels))?;
+                Ok((artifacts, Some(prepared)))
+            });
+            let _ = done.send(Rebuild { path, result });
+        });
+    }
+
+    /// Channels of the output stream; warns when the build routes groups to outputs the
+    /// stream does not have, since only the main mix is played then
+    fn output_channels(&self, artifacts: &BuildArtifacts) -> u16 {
+        let channels = self.playback.channels();
+        if let Some((_, needed)) = artifacts.outputs
+            && needed > channels
+        {
+            self.logger.warn(format!(
+                "Output routes need {} channels but the device was opened with {}; playing the main mix (try --output-channels {})",
+                needed, channels, needed
+            ));
+        }
+        channels
+    }
+
+    /// Resolve `--start-at` against the marks of the first build
+    fn start_position(
+        &self,
+        request: &LivePlayRequest,
+        artifacts: &BuildArtifacts,
+    ) -> Result<Duration> {
+        let Some(start_at) = &request.start_at else {
+            return Ok(Duration::ZERO);
+        };
+        let seconds = start_seconds(start_at, &artifacts.markers)?;
+        let length = artifacts.audio_length.as_secs_f32();
+        if seconds >= length {
+            bail!(
+                "--start-at {} ({:.2}s) is past the end of the track ({:.2}s)",
+                start_at,
+                seconds,
+                length
+            );
+        }
+        self.logger
+            .info(format!("Starting at {} ({:.2}s)", start_at, seconds));
+        Ok(Duration::from_secs_f32(seconds))
+    }
+}
+
+/// Seconds `start_at` points to: the name of a mark, or a time like `12.5` or `12.5s`
+fn start_seconds(start_at: &str, markers: &[(f32, String)]) -> Result<f32> {
+    if let Some((time, _)) = markers.iter().find(|(_, name)| name == start_at) {
+        return Ok(*time);
+    }
+    let number = start_at.strip_suffix('s').unwrap_or(start_at);
+    if let Ok(seconds) = number.parse::<f32>()
+        && seconds.is_finite()
+        && seconds >= 0.0
+    {
+        return Ok(seconds);
+    }
+    let names: Vec<&str> = markers.iter().map(|(_, name)| name.as_str()).collect();
+    if names.is_empty() {
+        bail!(
+            "unknown mark `{}`: the track has no `mark` statements",
+            start_at
+        );
+    }
+    let hint = find_keyword_suggestion(start_at, &names)
+        .map(|name| format!(" (did you mean `{}`?)", name))
+        .unwrap_or_default();
+    bail!(
+        "unknown mark `{}`{}; marks in this track: {}",
+        start_at,
+        hint,
+        names.join(", ")
+    )
+}
+
+/// Grid for keyboard triggers over the loop in `artifacts`
+fn live_quantizer(request: &LivePlayRequest, artifacts: &BuildArtifacts) -> Quantizer {
+    let grid_beats = request.keys.as_ref().map_or(0.25, |keys| keys.grid_beats);
+    Quantizer::new(
+        keyboard::project_bpm(&artifacts.statements, request.build.bpm),
+        grid_beats,
+        artifacts.audio_length.as_secs_f32(),
+    )
+    .with_tempo_map(artifacts.tempo_map.clone())
+}
+
+/// Seconds kept after the last changed event so reverb and delay tails are patched too
+const PATCH_TAIL_SECONDS: f32 = 2.0;
+
+/// Window of `next` to patch into the loop rendered from `previous`, when the rebuild
+/// kept the loop layout and only changed events inside it
+fn changed_region(previous: &BuildArtifacts, next: &BuildArtifacts) -> Option<(f32, f32)> {
+    if previous.audio_length != next.audio_length
+        || previous.sample_rate != next.sample_rate
+        || previous.channels != next.channels
+        || previous.primary_format != next.primary_format
+    {
+        return None;
+    }
+    match previous
+        .fingerprint
+        .diff(&next.fingerprint, PATCH_TAIL_SECONDS)
+    {
+        RegionDiff::Region { start, end } => Some((start, end)),
+        RegionDiff::Unchanged | RegionDiff::Full => None,
+    }
 }
 
 impl LiveAudioSource {
-    fn from_artifacts(artifacts: &BuildArtifacts) -> Self {
+    /// Play the build on a stream of `channels` outputs: the hardware output mix when the
+    /// stream has room for every route, the stereo master otherwise. Outputs the file
+    /// does not use are zero-padded.
+    fn from_artifacts(artifacts: &BuildArtifacts, channels: u16) -> Self {
+        if let Some((path, _)) = artifacts
+            .outputs
+            .as_ref()
+            .filter(|(_, needed)| *needed <= channels)
+        {
+            // The monitor click is already on outputs 1-2 of this file
+            return LiveAudioSource::with_path(
+                path.clone(),
+                AudioFormat::Wav,
+                artifacts.bit_depth,
+                artifacts.channels,
+                artifacts.sample_rate,
+                artifacts.resample_quality,
+                artifacts.audio_length,
+            )
+            .on_stream_channels(channels);
+        }
         LiveAudioSource::with_path(
             artifacts.primary_audio_path.clone(),
             artifacts.primary_format,
@@ -382,9 +906,27 @@ impl LiveAudioSource {
             artifacts.resample_quality,
             artifacts.audio_length,
         )
+        .with_overlay(
+            artifacts
+                .click
+                .clone()
+                .filter(|(mode, _)| *mode == ClickMode::Monitor)
+                .map(|(_, path)| path),
+        )
+        .on_stream_channels(channels)
     }
 }
 
+/// File name stem of a session recording, from the start time (UTC)
+fn session_stem() -> String {
+    let stamp = time::OffsetDateTime::now_utc()
+        .format(time::macros::format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .unwrap_or_default();
+    format!("session-{}", stamp)
+}
+
 fn format_duration(duration: Duration) -> String {
     if duration.as_secs() >= 1 {
         format!("{:.2}s", duration.as_secs_f64())
@@ -0,0 +1,74 @@
+use super::*;
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::playback::osc::encode_message;
+
+const SOURCE: &str = "let lead = synth saw\nmacro intensity -> [lead.gain]\nlead -> note(C4)\n";
+
+fn first_gain(overrides: &HashMap<String, Value>) -> f32 {
+    let statements =
+        crate::language::syntax::parser::driver::parse(SOURCE, "live.deva".into()).unwrap();
+    let mut interpreter = AudioInterpreter::new(44100);
+    interpreter.set_overrides(overrides.clone());
+    interpreter.collect_all_events(&statements).unwrap();
+    match interpreter.events.events.first() {
+        Some(AudioEvent::Note { gain, .. }) => *gain,
+        other => panic!("expected a note, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_osc_move_reaches_the_next_rebuild() {
+    let port = UdpSocket::bind("127.0.0.1:0")
+        .and_then(|socket| socket.local_addr())
+        .unwrap()
+        .port();
+    let request = LiveMacrosRequest {
+        osc_port: Some(port),
+        ..Default::default()
+    };
+    let logger = Logger::new();
+    let mut input = MacroInput::start(&request, &logger).unwrap();
+
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender
+        .send_to(
+            &encode_message("/deva/macro/intensity", &[OscArg::Float(0.25)]),
+            ("127.0.0.1", port),
+        )
+        .unwrap();
+    let changes = tokio::time::timeout(Duration::from_secs(5), input.next())
+        .await
+        .expect("no OSC change arrived")
+        .unwrap();
+
+    let statements =
+        crate::language::syntax::parser::driver::parse(SOURCE, "live.deva".into()).unwrap();
+    let mut overrides = HashMap::new();
+    assert_eq!(first_gain(&overrides), 1.0);
+    let moved = apply_changes(
+        &mut overrides,
+        &declared_macros(&statements),
+        changes,
+        &logger,
+    );
+    assert_eq!(moved, vec![("intensity".to_string(), 0.25)]);
+    assert_eq!(first_gain(&overrides), 0.25);
+}
+
+#[test]
+fn test_changes_to_undeclared_macros_are_skipped() {
+    let mut overrides = HashMap::new();
+    let moved = apply_changes(
+        &mut overrides,
+        &["intensity".to_string()],
+        vec![
+            ("volume".to_string(), MacroChange::Set(0.5)),
+            ("intensity".to_string(), MacroChange::Set(0.5)),
+            ("intensity".to_string(), MacroChange::Set(0.75)),
+        ],
+        &Logger::new(),
+    );
+    assert_eq!(moved, vec![("intensity".to_string(), 0.75)]);
+    assert!(!overrides.contains_key("volume"));
+}
@@ -3,4 +3,6 @@ pub mod build;
 #[cfg(feature = "cli")]
 pub mod live;
 #[cfg(feature = "cli")]
+pub mod render_api;
+#[cfg(feature = "cli")]
 pub mod watch;
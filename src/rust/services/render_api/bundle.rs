@@ -0,0 +1,114 @@
+//! Unpacking uploaded project bundles within the render budget
+
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use super::RenderBudget;
+
+/// Unpack a `.tar.gz` or `.zip` bundle into `dest`, refusing entries that escape it
+/// and bundles that exceed the unpacked size or file count budget
+pub fn extract_bundle(bytes: &[u8], dest: &Path, budget: &RenderBudget) -> Result<usize> {
+    fs::create_dir_all(dest)?;
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        extract_tar_gz(bytes, dest, budget)
+    } else if bytes.starts_with(b"PK") {
+        extract_zip(bytes, dest, budget)
+    } else {
+        bail!("Unsupported bundle format (expected .tar.gz or .zip)")
+    }
+}
+
+fn extract_tar_gz(bytes: &[u8], dest: &Path, budget: &RenderBudget) -> Result<usize> {
+    let decoder = flate2::read::GzDecoder::new(Cursor::new(bytes));
+    let mut archive = tar::Archive::new(decoder);
+    let mut tally = Tally::default();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let kind = entry.header().entry_type();
+        if kind.is_dir() {
+            continue;
+        }
+        if !kind.is_file() {
+            bail!("Unsupported bundle entry: {}", path.display());
+        }
+        tally.write(&mut entry, &safe_join(dest, &path)?, budget)?;
+    }
+    Ok(tally.files)
+}
+
+fn extract_zip(bytes: &[u8], dest: &Path, budget: &RenderBudget) -> Result<usize> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| anyhow::anyhow!("Failed to read bundle: {}", e))?;
+    let mut tally = Tally::default();
+
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| anyhow::anyhow!("Failed to read bundle entry: {}", e))?;
+        if file.is_dir() {
+            continue;
+        }
+        let Some(path) = file.enclosed_name() else {
+            bail!("Bundle entry escapes the project: {}", file.name());
+        };
+        tally.write(&mut file, &safe_join(dest, &path)?, budget)?;
+    }
+    Ok(tally.files)
+}
+
+/// Files and bytes written so far
+#[derive(Default)]
+struct Tally {
+    files: usize,
+    bytes: u64,
+}
+
+impl Tally {
+    /// Copy one entry, counting the bytes actually written rather than the declared size
+    fn write(
+        &mut self,
+        reader: &mut impl Read,
+        target: &Path,
+        budget: &RenderBudget,
+    ) -> Result<()> {
+        self.files += 1;
+        if self.files > budget.max_bundle_files {
+            bail!("Bundle has more than {} files", budget.max_bundle_files);
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = fs::File::create(target)?;
+        let remaining = budget.max_unpacked_bytes.saturating_sub(self.bytes);
+        self.bytes += std::io::copy(&mut reader.take(remaining + 1), &mut out)?;
+        if self.bytes > budget.max_unpacked_bytes {
+            bail!(
+                "Bundle unpacks to more than {} bytes",
+                budget.max_unpacked_bytes
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `dest/path`, provided `path` is relative and stays inside `dest`
+pub(super) fn safe_join(dest: &Path, path: &Path) -> Result<PathBuf> {
+    let mut out = dest.to_path_buf();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            _ => bail!("Bundle entry escapes the project: {}", path.display()),
+        }
+    }
+    if out == dest {
+        bail!("Empty bundle entry path");
+    }
+    Ok(out)
+}
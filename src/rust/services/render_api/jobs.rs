@@ -0,0 +1,467 @@
+//! Render job queue: submitted bundles wait here until a worker renders them
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+
+use super::RenderBudget;
+use super::bundle::{extract_bundle, safe_join};
+use crate::engine::audio::settings::{AudioFormat, RenderLimits};
+use crate::platform::config::AppConfig;
+use crate::services::build::pipeline::{BuildArtifacts, BuildRequest, ProjectBuilder};
+use crate::tools::logger::Logger;
+
+const BUNDLE_FILE: &str = "bundle.bin";
+const PROJECT_DIR: &str = "project";
+const OUTPUT_DIR: &str = "output";
+const CONFIG_FILES: [&str; 3] = ["devalang.json", ".devalang", "devalang.toml"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Extracting,
+    Rendering,
+    Done,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Failed)
+    }
+}
+
+/// One step of a job, streamed to clients as a server-sent event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    pub status: JobStatus,
+    /// 0.0 ..= 1.0
+    pub progress: f32,
+    pub message: String,
+}
+
+/// Per-job overrides of the bundle's own config
+#[derive(Debug, Clone, Default)]
+pub struct JobOptions {
+    /// Entry file relative to the bundle root
+    pub entry: Option<String>,
+    /// Export formats; the bundle config is used when empty
+    pub formats: Vec<AudioFormat>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    pub format: String,
+    pub file: String,
+    pub bytes: u64,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobSnapshot {
+    pub id: String,
+    pub status: JobStatus,
+    pub progress: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub artifacts: Vec<Artifact>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_seconds: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub render_ms: Option<f64>,
+}
+
+struct Job {
+    snapshot: JobSnapshot,
+    options: JobOptions,
+    events: Vec<ProgressEvent>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: HashMap<String, Job>,
+    pending: VecDeque<String>,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    /// Signalled on every new job and every progress event
+    changed: Condvar,
+    workdir: PathBuf,
+    budget: RenderBudget,
+    logger: Arc<Logger>,
+}
+
+#[derive(Clone)]
+pub struct JobQueue {
+    shared: Arc<Shared>,
+}
+
+impl JobQueue {
+    /// Jobs are stored under `workdir/<id>/` (bundle, unpacked project and outputs)
+    pub fn new(
+        workdir: impl Into<PathBuf>,
+        budget: RenderBudget,
+        logger: Arc<Logger>,
+    ) -> Result<Self> {
+        let workdir = workdir.into();
+        fs::create_dir_all(&workdir)?;
+        Ok(Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(QueueState::default()),
+                changed: Condvar::new(),
+                workdir,
+                budget,
+                logger,
+            }),
+        })
+    }
+
+    pub fn budget(&self) -> &RenderBudget {
+        &self.shared.budget
+    }
+
+    /// Spawn `budget.workers` threads rendering jobs until `shutdown`
+    pub fn start_workers(&self) -> Vec<JoinHandle<()>> {
+        (0..self.shared.budget.workers.max(1))
+            .map(|_| {
+                let queue = self.clone();
+                std::thread::spawn(move || while queue.run_next(true) {})
+            })
+            .collect()
+    }
+
+    pub fn shutdown(&self) {
+        self.lock().shutdown = true;
+        self.shared.changed.notify_all();
+    }
+
+    /// Store the bundle and queue it; returns the job id
+    pub fn submit(&self, bundle: &[u8], options: JobOptions) -> Result<String> {
+        let budget = &self.shared.budget;
+        if bundle.len() as u64 > budget.max_bundle_bytes {
+            bail!("Bundle is larger than {} bytes", budget.max_bundle_bytes);
+        }
+
+        let mut state = self.lock();
+        let unfinished = state
+            .jobs
+            .values()
+            .filter(|job| !job.snapshot.status.is_finished())
+            .count();
+        if unfinished >= budget.max_pending_jobs {
+            bail!(
+                "Render queue is full ({} jobs pending)",
+                budget.max_pending_jobs
+            );
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let dir = self.shared.workdir.join(&id);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(BUNDLE_FILE), bundle)?;
+
+        let queued = ProgressEvent {
+            status: JobStatus::Queued,
+            progress: 0.0,
+            message: format!("Queued ({} bytes)", bundle.len()),
+        };
+        state.jobs.insert(
+            id.clone(),
+            Job {
+                snapshot: JobSnapshot {
+                    id: id.clone(),
+                    status: JobStatus::Queued,
+                    progress: 0.0,
+                    error: None,
+                    artifacts: Vec::new(),
+                    audio_seconds: None,
+                    render_ms: None,
+                },
+                options,
+                events: vec![queued],
+            },
+        );
+        state.pending.push_back(id.clone());
+        drop(state);
+        self.shared.changed.notify_all();
+        Ok(id)
+    }
+
+    pub fn snapshot(&self, id: &str) -> Option<JobSnapshot> {
+        self.lock().jobs.get(id).map(|job| job.snapshot.clone())
+    }
+
+    pub fn list(&self) -> Vec<JobSnapshot> {
+        self.lock()
+            .jobs
+            .values()
+            .map(|job| job.snapshot.clone())
+            .collect()
+    }
+
+    /// Exported file of a finished job for `format` (e.g. "wav")
+    pub fn artifact(&self, id: &str, format: &str) -> Option<Artifact> {
+        self.lock()
+            .jobs
+            .get(id)?
+            .snapshot
+            .artifacts
+            .iter()
+            .find(|artifact| artifact.format.eq_ignore_ascii_case(format))
+            .cloned()
+    }
+
+    /// Forget a finished job and delete its files; `Ok(false)` if unknown or still running
+    pub fn remove(&self, id: &str) -> Result<bool> {
+        let mut state = self.lock();
+        if !state
+            .jobs
+            .get(id)
+            .is_some_and(|job| job.snapshot.status.is_finished())
+        {
+            return Ok(false);
+        }
+        state.jobs.remove(id);
+        drop(state);
+        fs::remove_dir_all(self.shared.workdir.join(id))?;
+        Ok(true)
+    }
+
+    /// Events from index `from` on, waiting up to `timeout` when there are none yet.
+    /// The flag is true once the job is finished and every event has been returned.
+    pub fn wait_events(
+        &self,
+        id: &str,
+        from: usize,
+        timeout: Duration,
+    ) -> Option<(Vec<ProgressEvent>, bool)> {
+        let state = self.lock();
+        let (state, _) = self
+            .shared
+            .changed
+            .wait_timeout_while(state, timeout, |state| {
+                state.jobs.get(id).is_some_and(|job| {
+                    job.events.len() <= from && !job.snapshot.status.is_finished()
+                })
+            })
+            .unwrap_or_else(|e| e.into_inner());
+        let job = state.jobs.get(id)?;
+        let events = job.events.get(from..).unwrap_or_default().to_vec();
+        Some((events, job.snapshot.status.is_finished()))
+    }
+
+    /// Render the next pending job. With `block`, wait for one to arrive; returns false
+    /// once the queue is shut down (or, without `block`, when nothing is pending).
+    pub fn run_next(&self, block: bool) -> bool {
+        let mut state = self.lock();
+        while block && !state.shutdown && state.pending.is_empty() {
+            state = self
+                .shared
+                .changed
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        if state.shutdown {
+            return false;
+        }
+        let Some(id) = state.pending.pop_front() else {
+            return false;
+        };
+        let Some(options) = state.jobs.get(&id).map(|job| job.options.clone()) else {
+            return true;
+        };
+        drop(state);
+
+        self.shared
+            .logger
+            .action(format!("Render job {} started", id));
+        match self.process(&id, &options) {
+            Ok((artifacts, build)) => {
+                let count = artifacts.len();
+                self.update(
+                    &id,
+                    JobStatus::Done,
+                    1.0,
+                    format!("Rendered {} file(s)", count),
+                    |snapshot| {
+                        snapshot.artifacts = artifacts;
+                        snapshot.audio_seconds = Some(build.audio_length.as_secs_f64());
+                        snapshot.render_ms = Some(build.total_duration.as_secs_f64() * 1000.0);
+                    },
+                );
+                self.shared
+                    .logger
+                    .success(format!("Render job {} done", id));
+            }
+            Err(e) => {
+                let _ = fs::remove_dir_all(self.shared.workdir.join(&id).join(OUTPUT_DIR));
+                let message = e.to_string();
+                self.update(&id, JobStatus::Failed, 1.0, message.clone(), |snapshot| {
+                    snapshot.error = Some(message.clone());
+                });
+                self.shared
+                    .logger
+                    .error(format!("Render job {} failed: {}", id, e));
+            }
+        }
+        true
+    }
+
+    fn process(&self, id: &str, options: &JobOptions) -> Result<(Vec<Artifact>, BuildArtifacts)> {
+        let budget = &self.shared.budget;
+        let dir = self.shared.workdir.join(id);
+        let project = dir.join(PROJECT_DIR);
+
+        self.update(
+            id,
+            JobStatus::Extracting,
+            0.1,
+            "Unpacking bundle".to_string(),
+            |_| {},
+        );
+        let bundle = fs::read(dir.join(BUNDLE_FILE))?;
+        let files = extract_bundle(&bundle, &project, budget)?;
+        fs::remove_file(dir.join(BUNDLE_FILE))?;
+
+        let config = load_bundle_config(&project)?;
+        let entry = resolve_entry(&project, &config, options)?;
+        self.update(
+            id,
+            JobStatus::Rendering,
+            0.3,
+            format!(
+                "Rendering {} ({} file(s) unpacked)",
+                entry.strip_prefix(&project).unwrap_or(&entry).display(),
+                files
+            ),
+            |_| {},
+        );
+
+        let audio_formats = if options.formats.is_empty() {
+            config.audio_formats()
+        } else {
+            options.formats.clone()
+        };
+        if audio_formats.is_empty() {
+            bail!("No valid audio formats requested");
+        }
+        let request = BuildRequest {
+            entry_path: entry,
+            output_root: dir.join(OUTPUT_DIR),
+            audio_formats,
+            bit_depth: config.audio_bit_depth(),
+            channels: config.audio_channels(),
+            resample_quality: config.resample_quality(),
+            preconvert_samples: config.audio.preconvert_samples,
+            mix: config.mix_settings()?,
+            sample_rate: config.sample_rate(),
+            bpm: config.audio.bpm,
+            log_timeline: None,
+            outputs: Vec::new(),
+            deterministic: false,
+            variable_overrides: Default::default(),
+            bank_remaps: Default::default(),
+            default_bank: config.banks.default.clone(),
+            tags: config.audio.tags.clone(),
+            auto_trim: config.audio.auto_trim,
+            stream: config.audio.stream,
+            solo_mute: Default::default(),
+            click: None,
+            limits: RenderLimits {
+                max_audio_seconds: Some(budget.max_audio_seconds),
+                max_render_time: Some(Duration::from_secs_f64(budget.max_render_seconds)),
+            },
+        };
+        let build = ProjectBuilder::new(self.shared.logger.clone()).build(&request)?;
+
+        let artifacts = build
+            .exported_formats
+            .iter()
+            .map(|(format, path)| Artifact {
+                format: format.label().to_string(),
+                file: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                bytes: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+                path: path.clone(),
+            })
+            .collect();
+        Ok((artifacts, build))
+    }
+
+    /// Set the job status, record the progress event and wake event listeners
+    fn update(
+        &self,
+        id: &str,
+        status: JobStatus,
+        progress: f32,
+        message: String,
+        apply: impl FnOnce(&mut JobSnapshot),
+    ) {
+        let mut state = self.lock();
+        if let Some(job) = state.jobs.get_mut(id) {
+            job.snapshot.status = status;
+            job.snapshot.progress = progress;
+            apply(&mut job.snapshot);
+            job.events.push(ProgressEvent {
+                status,
+                progress,
+                message,
+            });
+        }
+        drop(state);
+        self.shared.changed.notify_all();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.shared.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The bundle's config; several candidates would make `AppConfig::load` prompt, which a
+/// server cannot answer
+fn load_bundle_config(project: &Path) -> Result<AppConfig> {
+    let found = CONFIG_FILES
+        .iter()
+        .filter(|name| project.join(name).exists())
+        .count();
+    if found > 1 {
+        bail!("Bundle contains more than one config file");
+    }
+    AppConfig::load(project)
+}
+
+/// Requested entry, else the config entry, else `index.deva` at the bundle root
+fn resolve_entry(project: &Path, config: &AppConfig, options: &JobOptions) -> Result<PathBuf> {
+    if let Some(entry) = &options.entry {
+        let path = safe_join(project, Path::new(entry))?;
+        if !path.is_file() {
+            bail!("Entry file not found in bundle: {}", entry);
+        }
+        return Ok(path);
+    }
+
+    let configured = safe_join(project, &config.paths.entry)
+        .ok()
+        .filter(|path| path.is_file());
+    let fallback = project.join("index.deva");
+    match configured {
+        Some(path) => Ok(path),
+        None if fallback.is_file() => Ok(fallback),
+        None => bail!(
+            "No entry file in bundle (looked for {} and index.deva)",
+            config.paths.entry.display()
+        ),
+    }
+}
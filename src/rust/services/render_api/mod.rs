@@ -0,0 +1,50 @@
+//! Remote render farm (`devalang serve --render-api`)
+//!
+//! Clients upload a project bundle (`.tar.gz` or `.zip` holding the `.deva` sources and
+//! an optional `devalang.json`/`devalang.toml`), the server renders it through the regular
+//! build pipeline on a worker thread and keeps the exported files until they are fetched.
+//!
+//! Banks and plugins resolve from the server's working directory, like a local build.
+
+pub mod bundle;
+pub mod jobs;
+pub mod server;
+
+pub use jobs::{JobOptions, JobQueue, JobSnapshot, JobStatus, ProgressEvent};
+
+/// Limits applied to every submitted job
+#[derive(Debug, Clone, Copy)]
+pub struct RenderBudget {
+    /// Size of the uploaded bundle
+    pub max_bundle_bytes: u64,
+    /// Total size of the files once the bundle is unpacked
+    pub max_unpacked_bytes: u64,
+    /// Number of files in the bundle
+    pub max_bundle_files: usize,
+    /// Jobs waiting or rendering at the same time; further submissions are refused
+    pub max_pending_jobs: usize,
+    /// Jobs rendered in parallel
+    pub workers: usize,
+    /// Length of the rendered audio, checked before rendering
+    pub max_audio_seconds: f64,
+    /// Wall-clock time a job may spend collecting events and rendering
+    pub max_render_seconds: f64,
+}
+
+impl Default for RenderBudget {
+    fn default() -> Self {
+        Self {
+            max_bundle_bytes: 64 * 1024 * 1024,
+            max_unpacked_bytes: 256 * 1024 * 1024,
+            max_bundle_files: 2048,
+            max_pending_jobs: 16,
+            workers: 1,
+            max_audio_seconds: 600.0,
+            max_render_seconds: 300.0,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "test_render_api.rs"]
+mod tests;
@@ -0,0 +1,291 @@
+//! HTTP front end of the render API
+//!
+//! - `POST   /jobs?entry=song.deva&formats=wav,mp3` — body is the bundle; answers `202` with the job
+//! - `GET    /jobs` — every job
+//! - `GET    /jobs/{id}` — status, progress and artifacts
+//! - `GET    /jobs/{id}/events` — progress as server-sent events, ends with an `end` event
+//! - `GET    /jobs/{id}/artifacts/{format}` — a rendered file
+//! - `DELETE /jobs/{id}` — drop a finished job and its files
+
+use std::io::{Cursor, Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use super::jobs::{JobOptions, JobQueue};
+use crate::engine::audio::settings::AudioFormat;
+use crate::tools::logger::Logger;
+
+/// Interval between SSE keep-alive comments while a job is quiet
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    Submit,
+    List,
+    Status(String),
+    Events(String),
+    Artifact(String, String),
+    Remove(String),
+    NotFound,
+}
+
+impl Route {
+    pub fn parse(method: &Method, path: &str) -> Self {
+        let segments: Vec<&str> = path
+            .trim_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        match (method, segments.as_slice()) {
+            (Method::Post, ["jobs"]) => Route::Submit,
+            (Method::Get, ["jobs"]) => Route::List,
+            (Method::Get, ["jobs", id]) => Route::Status(id.to_string()),
+            (Method::Delete, ["jobs", id]) => Route::Remove(id.to_string()),
+            (Method::Get, ["jobs", id, "events"]) => Route::Events(id.to_string()),
+            (Method::Get, ["jobs", id, "artifacts", format]) => {
+                Route::Artifact(id.to_string(), format.to_string())
+            }
+            _ => Route::NotFound,
+        }
+    }
+}
+
+/// `entry` and comma-separated `formats` from a query string
+pub fn parse_job_options(query: &str) -> Result<JobOptions, String> {
+    let mut options = JobOptions::default();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = urlencoding::decode(value)
+            .map_err(|_| format!("Invalid query value for '{}'", key))?
+            .into_owned();
+        match key {
+            "entry" if !value.is_empty() => options.entry = Some(value),
+            "formats" => {
+                for name in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    let format = AudioFormat::from_str(name)
+                        .ok_or_else(|| format!("Unknown format '{}'", name))?;
+                    options.formats.push(format);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(options)
+}
+
+#[derive(Clone)]
+pub struct RenderApiServer {
+    queue: JobQueue,
+    /// Required as `Authorization: Bearer <token>` when set
+    token: Option<String>,
+    logger: Arc<Logger>,
+}
+
+impl RenderApiServer {
+    pub fn new(queue: JobQueue, token: Option<String>, logger: Arc<Logger>) -> Self {
+        Self {
+            queue,
+            token,
+            logger,
+        }
+    }
+
+    /// Serve until the process is stopped; each request is handled on its own thread
+    pub fn run(&self, addr: &str) -> Result<()> {
+        let server = Server::http(addr)
+            .map_err(|e| anyhow::anyhow!("Failed to start render API on {}: {}", addr, e))?;
+        self.logger
+            .success(format!("Render API listening on http://{}", addr));
+
+        for request in server.incoming_requests() {
+            let this = self.clone();
+            std::thread::spawn(move || this.handle(request));
+        }
+        Ok(())
+    }
+
+    fn handle(&self, mut request: Request) {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let route = Route::parse(request.method(), path);
+
+        if !self.authorized(&request) {
+            respond(
+                request,
+                json_response(401, &json!({ "error": "Unauthorized" })),
+            );
+            return;
+        }
+
+        let response = match route {
+            Route::Submit => self.submit(&mut request, query),
+            Route::List => json_response(200, &self.queue.list()),
+            Route::Status(id) => match self.queue.snapshot(&id) {
+                Some(snapshot) => json_response(200, &snapshot),
+                None => not_found("job"),
+            },
+            Route::Remove(id) => match self.queue.remove(&id) {
+                Ok(true) => json_response(200, &json!({ "removed": id })),
+                Ok(false) => not_found("finished job"),
+                Err(e) => json_response(500, &json!({ "error": e.to_string() })),
+            },
+            Route::Artifact(id, format) => {
+                self.send_artifact(request, &id, &format);
+                return;
+            }
+            Route::Events(id) => {
+                self.stream_events(request, &id);
+                return;
+            }
+            Route::NotFound => not_found("route"),
+        };
+        respond(request, response);
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        let expected = format!("Bearer {}", token);
+        request
+            .headers()
+            .iter()
+            .any(|h| h.field.equiv("Authorization") && h.value.as_str() == expected)
+    }
+
+    fn submit(&self, request: &mut Request, query: &str) -> Response<Cursor<Vec<u8>>> {
+        let options = match parse_job_options(query) {
+            Ok(options) => options,
+            Err(e) => return json_response(400, &json!({ "error": e })),
+        };
+
+        let limit = self.queue.budget().max_bundle_bytes;
+        if request.body_length().is_some_and(|len| len as u64 > limit) {
+            return json_response(
+                413,
+                &json!({ "error": format!("Bundle is larger than {} bytes", limit) }),
+            );
+        }
+        let mut bundle = Vec::new();
+        if let Err(e) = request.as_reader().take(limit + 1).read_to_end(&mut bundle) {
+            return json_response(400, &json!({ "error": e.to_string() }));
+        }
+        if bundle.len() as u64 > limit {
+            return json_response(
+                413,
+                &json!({ "error": format!("Bundle is larger than {} bytes", limit) }),
+            );
+        }
+
+        match self.queue.submit(&bundle, options) {
+            Ok(id) => {
+                self.logger
+                    .info(format!("Render job {} queued ({} bytes)", id, bundle.len()));
+                json_response(
+                    202,
+                    &json!({
+                        "id": id,
+                        "status": format!("/jobs/{}", id),
+                        "events": format!("/jobs/{}/events", id),
+                    }),
+                )
+            }
+            Err(e) => json_response(429, &json!({ "error": e.to_string() })),
+        }
+    }
+
+    fn send_artifact(&self, request: Request, id: &str, format: &str) {
+        let Some(artifact) = self.queue.artifact(id, format) else {
+            respond(request, not_found("artifact"));
+            return;
+        };
+        let Ok(file) = std::fs::File::open(&artifact.path) else {
+            respond(request, not_found("artifact file"));
+            return;
+        };
+        let content_type = match artifact.format.as_str() {
+            "wav" => "audio/wav",
+            "mp3" => "audio/mpeg",
+            "flac" => "audio/flac",
+            "alac" => "audio/mp4",
+            "mid" => "audio/midi",
+            _ => "application/octet-stream",
+        };
+        let file_name: String = artifact
+            .file
+            .chars()
+            .filter(|c| c.is_ascii_graphic() && *c != '"')
+            .collect();
+        let disposition = format!("attachment; filename=\"{}\"", file_name);
+        let response = Response::from_file(file)
+            .with_header(header("Content-Type", content_type))
+            .with_header(header("Content-Disposition", &disposition));
+        let _ = request.respond(response);
+    }
+
+    /// Write progress events as they happen; tiny_http buffers chunked bodies, so the
+    /// stream is written on the raw connection and flushed after every event
+    fn stream_events(&self, request: Request, id: &str) {
+        if self.queue.snapshot(id).is_none() {
+            respond(request, not_found("job"));
+            return;
+        }
+
+        let mut writer = request.into_writer();
+        let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+        if writer.write_all(head.as_bytes()).is_err() {
+            return;
+        }
+
+        let mut next = 0;
+        while let Some((events, finished)) = self.queue.wait_events(id, next, KEEP_ALIVE) {
+            let mut chunk = String::new();
+            for event in &events {
+                chunk.push_str(&sse_event("progress", event));
+            }
+            next += events.len();
+            if finished && let Some(snapshot) = self.queue.snapshot(id) {
+                chunk.push_str(&sse_event("end", &snapshot));
+            }
+            if chunk.is_empty() {
+                chunk.push_str(": keep-alive\n\n");
+            }
+            // A failed write means the client went away
+            if writer.write_all(chunk.as_bytes()).is_err() || writer.flush().is_err() || finished {
+                break;
+            }
+        }
+    }
+}
+
+/// One server-sent event with a JSON payload
+pub fn sse_event<T: Serialize>(name: &str, data: &T) -> String {
+    format!(
+        "event: {}\ndata: {}\n\n",
+        name,
+        serde_json::to_string(data).unwrap_or_else(|_| "null".to_string())
+    )
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).expect("header is plain ASCII")
+}
+
+fn json_response<T: Serialize>(status: u16, body: &T) -> Response<Cursor<Vec<u8>>> {
+    Response::from_string(serde_json::to_string(body).unwrap_or_else(|_| "null".to_string()))
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"))
+}
+
+fn not_found(what: &str) -> Response<Cursor<Vec<u8>>> {
+    json_response(404, &json!({ "error": format!("Unknown {}", what) }))
+}
+
+fn respond(request: Request, response: Response<Cursor<Vec<u8>>>) {
+    let _ = request.respond(response);
+}
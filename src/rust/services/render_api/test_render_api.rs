@@ -0,0 +1,192 @@
+use super::bundle::extract_bundle;
+use super::server::{Route, parse_job_options, sse_event};
+use super::*;
+use crate::tools::logger::Logger;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use tiny_http::Method;
+
+/// `.tar.gz` bundle holding `files`; names are written raw so tests can forge bad paths
+fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    let mut builder = tar::Builder::new(encoder);
+    for (name, content) in files {
+        let mut header = tar::Header::new_gnu();
+        let raw = &mut header.as_gnu_mut().unwrap().name;
+        raw[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, content.as_bytes()).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn zip_bundle(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, content) in files {
+        writer
+            .start_file(*name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_extract_bundle_unpacks_tar_gz_and_zip() {
+    let files = [
+        ("index.deva", "print \"hi\"\n"),
+        ("parts/lead.deva", "let a = 1\n"),
+    ];
+    for bundle in [tar_gz(&files), zip_bundle(&files)] {
+        let dir = tempfile::tempdir().unwrap();
+        let count = extract_bundle(&bundle, dir.path(), &RenderBudget::default()).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("parts/lead.deva")).unwrap(),
+            "let a = 1\n"
+        );
+    }
+}
+
+#[test]
+fn test_extract_bundle_enforces_paths_and_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let budget = RenderBudget::default();
+
+    let escaping = tar_gz(&[("../evil.deva", "x")]);
+    assert!(extract_bundle(&escaping, &dir.path().join("a"), &budget).is_err());
+    assert!(!dir.path().join("evil.deva").exists());
+
+    let small = RenderBudget {
+        max_unpacked_bytes: 8,
+        ..budget
+    };
+    let big = tar_gz(&[("a.deva", "12345"), ("b.deva", "67890")]);
+    assert!(extract_bundle(&big, &dir.path().join("b"), &small).is_err());
+
+    let few = RenderBudget {
+        max_bundle_files: 1,
+        ..budget
+    };
+    assert!(extract_bundle(&big, &dir.path().join("c"), &few).is_err());
+
+    assert!(extract_bundle(b"not an archive", &dir.path().join("d"), &budget).is_err());
+}
+
+#[test]
+fn test_routes_and_job_options() {
+    assert_eq!(Route::parse(&Method::Post, "/jobs"), Route::Submit);
+    assert_eq!(
+        Route::parse(&Method::Get, "/jobs/abc/events"),
+        Route::Events("abc".to_string())
+    );
+    assert_eq!(
+        Route::parse(&Method::Get, "/jobs/abc/artifacts/wav"),
+        Route::Artifact("abc".to_string(), "wav".to_string())
+    );
+    assert_eq!(Route::parse(&Method::Put, "/jobs"), Route::NotFound);
+
+    let options = parse_job_options("entry=songs%2Fa.deva&formats=wav,mid").unwrap();
+    assert_eq!(options.entry.as_deref(), Some("songs/a.deva"));
+    assert_eq!(options.formats.len(), 2);
+    assert!(parse_job_options("formats=ogg").is_err());
+
+    let event = ProgressEvent {
+        status: JobStatus::Rendering,
+        progress: 0.5,
+        message: "Rendering".to_string(),
+    };
+    assert_eq!(
+        sse_event("progress", &event),
+        "event: progress\ndata: {\"status\":\"rendering\",\"progress\":0.5,\"message\":\"Rendering\"}\n\n"
+    );
+}
+
+#[test]
+fn test_job_queue_renders_bundle_and_reports_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    let queue =
+        JobQueue::new(dir.path(), RenderBudget::default(), Arc::new(Logger::new())).unwrap();
+
+    let bundle = tar_gz(&[(
+        "song.deva",
+        "let lead = synth sine\nlead -> note(C4) -> duration(250)\n",
+    )]);
+    let options = parse_job_options("entry=song.deva&formats=wav").unwrap();
+    let id = queue.submit(&bundle, options).unwrap();
+    assert_eq!(queue.snapshot(&id).unwrap().status, JobStatus::Queued);
+
+    assert!(queue.run_next(false));
+    let snapshot = queue.snapshot(&id).unwrap();
+    assert_eq!(snapshot.status, JobStatus::Done, "{:?}", snapshot.error);
+    let artifact = queue.artifact(&id, "wav").unwrap();
+    assert!(artifact.path.is_file() && artifact.bytes > 44);
+
+    let (events, finished) = queue.wait_events(&id, 0, Duration::ZERO).unwrap();
+    assert!(finished);
+    let statuses: Vec<JobStatus> = events.iter().map(|e| e.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            JobStatus::Queued,
+            JobStatus::Extracting,
+            JobStatus::Rendering,
+            JobStatus::Done
+        ]
+    );
+
+    assert!(queue.remove(&id).unwrap());
+    assert!(!dir.path().join(&id).exists());
+}
+
+#[test]
+fn test_job_queue_enforces_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let budget = RenderBudget {
+        max_bundle_bytes: 4096,
+        max_pending_jobs: 1,
+        max_audio_seconds: 0.0,
+        ..RenderBudget::default()
+    };
+    let queue = JobQueue::new(dir.path(), budget, Arc::new(Logger::new())).unwrap();
+
+    assert!(queue.submit(&[0; 8192], JobOptions::default()).is_err());
+
+    let bundle = tar_gz(&[(
+        "index.deva",
+        "let lead = synth sine\nlead -> note(C4) -> duration(250)\n",
+    )]);
+    let id = queue.submit(&bundle, JobOptions::default()).unwrap();
+    assert!(queue.submit(&bundle, JobOptions::default()).is_err());
+
+    // Any audio is over a zero-second budget; the job fails before rendering
+    assert!(queue.run_next(false));
+    let snapshot = queue.snapshot(&id).unwrap();
+    assert_eq!(snapshot.status, JobStatus::Failed);
+    assert!(snapshot.error.unwrap().contains("over the 0s limit"));
+    assert!(snapshot.artifacts.is_empty());
+    assert!(!queue.run_next(false));
+}
+
+#[test]
+fn test_job_queue_stops_renders_over_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let budget = RenderBudget {
+        max_render_seconds: 0.0,
+        ..RenderBudget::default()
+    };
+    let queue = JobQueue::new(dir.path(), budget, Arc::new(Logger::new())).unwrap();
+
+    let bundle = tar_gz(&[(
+        "index.deva",
+        "let lead = synth sine\nloop 100000:\n    lead -> note(C4) -> duration(250)\n",
+    )]);
+    let id = queue.submit(&bundle, JobOptions::default()).unwrap();
+    assert!(queue.run_next(false));
+    let snapshot = queue.snapshot(&id).unwrap();
+    assert_eq!(snapshot.status, JobStatus::Failed);
+    assert!(snapshot.error.unwrap().contains("time limit"));
+}
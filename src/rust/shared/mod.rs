@@ -3,4 +3,4 @@ pub mod debugger;
 pub mod store;
 
 pub use debugger::DebugTimer;
-pub use store::GlobalStore;
+pub use store::{GlobalStore, StoreSnapshot};
@@ -1,5 +1,6 @@
 /// Global store module - manages global state and module registry
 use crate::language::syntax::ast::Value;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -15,12 +16,23 @@ pub struct GlobalStore {
     plugins: Arc<RwLock<HashMap<String, (PluginInfo, Vec<u8>)>>>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ModuleInfo {
     pub path: String,
     pub variables: HashMap<String, Value>,
 }
 
+/// Serializable copy of the variables and modules held by a `GlobalStore`
+///
+/// Plugins are not part of the snapshot: they are reloaded from disk by the CLI.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StoreSnapshot {
+    #[serde(default)]
+    pub variables: HashMap<String, Value>,
+    #[serde(default)]
+    pub modules: HashMap<String, ModuleInfo>,
+}
+
 impl GlobalStore {
     pub fn new() -> Self {
         Self {
@@ -37,6 +49,13 @@ impl GlobalStore {
         }
     }
 
+    /// Replace every variable with `variables`; modules are kept
+    pub fn replace_variables(&self, variables: HashMap<String, Value>) {
+        if let Ok(mut vars) = self.variables.write() {
+            *vars = variables;
+        }
+    }
+
     pub fn get_variable(&self, name: &str) -> Option<Value> {
         if let Ok(vars) = self.variables.read() {
             vars.get(name).cloned()
@@ -75,6 +94,34 @@ impl GlobalStore {
         }
     }
 
+    pub fn variables(&self) -> HashMap<String, Value> {
+        self.variables
+            .read()
+            .map(|vars| vars.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn snapshot(&self) -> StoreSnapshot {
+        StoreSnapshot {
+            variables: self.variables(),
+            modules: self
+                .modules
+                .read()
+                .map(|mods| mods.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Replace variables and modules with the content of `snapshot`
+    pub fn restore(&self, snapshot: StoreSnapshot) {
+        if let Ok(mut vars) = self.variables.write() {
+            *vars = snapshot.variables;
+        }
+        if let Ok(mut mods) = self.modules.write() {
+            *mods = snapshot.modules;
+        }
+    }
+
     pub fn clear(&self) {
         if let Ok(mut vars) = self.variables.write() {
             vars.clear();
@@ -21,3 +21,31 @@ fn test_global_store_modules() {
     let result = store.get_module("test");
     assert!(result.is_some());
 }
+
+#[test]
+fn test_global_store_snapshot_round_trip() {
+    let store = GlobalStore::new();
+    store.set_variable("tempo".to_string(), Value::Number(128.0));
+    store.register_module(
+        "drums.deva".to_string(),
+        ModuleInfo {
+            path: "drums.deva".to_string(),
+            variables: HashMap::from([("kick".to_string(), Value::String("kit.kick".into()))]),
+        },
+    );
+
+    let json = serde_json::to_vec(&store.snapshot()).unwrap();
+    let snapshot: StoreSnapshot = serde_json::from_slice(&json).unwrap();
+
+    let restored = GlobalStore::new();
+    restored.set_variable("stale".to_string(), Value::Null);
+    restored.restore(snapshot);
+
+    assert_eq!(restored.get_variable("tempo"), Some(Value::Number(128.0)));
+    assert!(restored.get_variable("stale").is_none());
+    let module = restored.get_module("drums.deva").unwrap();
+    assert_eq!(
+        module.variables.get("kick"),
+        Some(&Value::String("kit.kick".into()))
+    );
+}
@@ -0,0 +1,323 @@
+#![cfg(feature = "cli")]
+
+//! Multi-addon operations: a bounded number of installs/updates/removals run at once,
+//! each with its own progress line, followed by a summary table. One addon failing
+//! never stops the others.
+
+use anyhow::Result;
+use std::future::Future;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Addon operations run at once unless `--jobs` says otherwise
+pub const DEFAULT_JOBS: usize = 4;
+
+/// Minimum delay between two redraws for download ticks
+const REDRAW_INTERVAL: Duration = Duration::from_millis(80);
+
+const BAR_WIDTH: usize = 24;
+
+/// How an addon operation ended without a hard failure
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The operation did its work (`installed`, `updated to 1.2.0`, ...)
+    Done(String),
+    /// Nothing to do (`already installed`, `up to date`)
+    Skipped(String),
+}
+
+/// Current step of one addon, shown on its progress line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stage {
+    Queued,
+    Resolving,
+    Downloading { done: u64, total: Option<u64> },
+    Extracting,
+    Removing,
+    Finished(Result<Outcome, String>),
+}
+
+struct Row {
+    name: String,
+    stage: Stage,
+}
+
+struct Board {
+    rows: Vec<Row>,
+    /// Redraw the rows in place (terminal) rather than printing stage changes
+    live: bool,
+    drawn: usize,
+    last_draw: Option<Instant>,
+}
+
+impl Board {
+    fn draw(&mut self, force: bool) {
+        if self
+            .last_draw
+            .is_some_and(|last| !force && last.elapsed() < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        self.last_draw = Some(Instant::now());
+
+        let width = self
+            .rows
+            .iter()
+            .map(|row| row.name.len())
+            .max()
+            .unwrap_or(0);
+        let mut out = std::io::stderr().lock();
+        if self.drawn > 0 {
+            let _ = write!(out, "\x1b[{}A", self.drawn);
+        }
+        for row in &self.rows {
+            let _ = writeln!(
+                out,
+                "\x1b[2K  {:width$}  {}",
+                row.name,
+                stage_line(&row.stage)
+            );
+        }
+        let _ = out.flush();
+        self.drawn = self.rows.len();
+    }
+}
+
+/// Progress lines for a batch; hidden boards print nothing
+#[derive(Clone)]
+pub struct Progress {
+    board: Option<Arc<Mutex<Board>>>,
+}
+
+impl Progress {
+    /// One line per addon, redrawn in place when stderr is a terminal
+    pub fn new(names: &[String]) -> Self {
+        let board = Board {
+            rows: names
+                .iter()
+                .map(|name| Row {
+                    name: name.clone(),
+                    stage: Stage::Queued,
+                })
+                .collect(),
+            live: atty::is(atty::Stream::Stderr),
+            drawn: 0,
+            last_draw: None,
+        };
+        Self {
+            board: Some(Arc::new(Mutex::new(board))),
+        }
+    }
+
+    pub fn hidden() -> Self {
+        Self { board: None }
+    }
+
+    /// Handle for the addon at `index`
+    pub fn item(&self, index: usize) -> ItemProgress {
+        ItemProgress {
+            board: self.board.clone(),
+            index,
+        }
+    }
+
+    fn start(&self) {
+        if let Some(board) = &self.board
+            && let Ok(mut board) = board.lock()
+            && board.live
+        {
+            board.draw(true);
+        }
+    }
+}
+
+/// Progress of a single addon operation
+#[derive(Clone)]
+pub struct ItemProgress {
+    board: Option<Arc<Mutex<Board>>>,
+    index: usize,
+}
+
+impl ItemProgress {
+    pub fn hidden() -> Self {
+        Self {
+            board: None,
+            index: 0,
+        }
+    }
+
+    pub fn stage(&self, stage: Stage) {
+        let Some(board) = &self.board else { return };
+        let Ok(mut board) = board.lock() else { return };
+        let Some(row) = board.rows.get_mut(self.index) else {
+            return;
+        };
+
+        let tick = matches!(
+            (&row.stage, &stage),
+            (Stage::Downloading { .. }, Stage::Downloading { .. })
+        );
+        row.stage = stage;
+        if board.live {
+            board.draw(!tick);
+        } else if !tick {
+            // Logs and pipes get one line per step instead of redraws
+            let row = &board.rows[self.index];
+            eprintln!("  {}  {}", row.name, stage_line(&row.stage));
+        }
+    }
+
+    pub fn downloaded(&self, done: u64, total: Option<u64>) {
+        self.stage(Stage::Downloading { done, total });
+    }
+}
+
+fn stage_line(stage: &Stage) -> String {
+    match stage {
+        Stage::Queued => "queued".to_string(),
+        Stage::Resolving => "resolving...".to_string(),
+        Stage::Downloading { done, total } => match total {
+            Some(total) if *total > 0 => {
+                let ratio = (*done as f64 / *total as f64).clamp(0.0, 1.0);
+                let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+                format!(
+                    "[{}{}] {:>3.0}% {} / {}",
+                    "#".repeat(filled),
+                    ".".repeat(BAR_WIDTH - filled),
+                    ratio * 100.0,
+                    format_bytes(*done),
+                    format_bytes(*total)
+                )
+            }
+            _ => format!("downloading {}", format_bytes(*done)),
+        },
+        Stage::Extracting => "extracting...".to_string(),
+        Stage::Removing => "removing...".to_string(),
+        Stage::Finished(Ok(Outcome::Done(detail))) => format!("✅ {}", detail),
+        Stage::Finished(Ok(Outcome::Skipped(detail))) => format!("➖ {}", detail),
+        Stage::Finished(Err(error)) => format!("❌ {}", error),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1_024 => format!("{} B", bytes),
+        1_024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1_024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Results of a batch, in the order the addons were given
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub results: Vec<(String, Result<Outcome, String>)>,
+}
+
+impl BatchReport {
+    pub fn failures(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .count()
+    }
+
+    /// Summary table: one row per addon with its result and details
+    pub fn table(&self) -> String {
+        let rows: Vec<(&str, &str, &str)> = self
+            .results
+            .iter()
+            .map(|(name, result)| match result {
+                Ok(Outcome::Done(detail)) => (name.as_str(), "ok", detail.as_str()),
+                Ok(Outcome::Skipped(detail)) => (name.as_str(), "skipped", detail.as_str()),
+                Err(error) => (name.as_str(), "failed", error.as_str()),
+            })
+            .collect();
+        let name_width = rows
+            .iter()
+            .map(|(name, _, _)| name.len())
+            .chain(["Addon".len()])
+            .max()
+            .unwrap_or(0);
+
+        let mut table = format!("{:name_width$}  {:7}  Details\n", "Addon", "Result");
+        for (name, status, detail) in rows {
+            table.push_str(&format!("{:name_width$}  {:7}  {}\n", name, status, detail));
+        }
+        table
+    }
+
+    /// Print the summary and fail only when an addon hit a hard error
+    pub fn finish(self, action: &str) -> Result<()> {
+        if self.results.len() > 1 {
+            println!("\n{}", self.table());
+        }
+        match self.failures() {
+            0 => Ok(()),
+            failed => Err(anyhow::anyhow!(
+                "{} of {} addon(s) failed to {}",
+                failed,
+                self.results.len(),
+                action
+            )),
+        }
+    }
+}
+
+/// Run `op` for every name with at most `jobs` running at once. Each operation gets
+/// its own progress handle; failures are recorded and the rest keep going.
+pub async fn run_batch<F, Fut>(
+    names: &[String],
+    jobs: usize,
+    progress: &Progress,
+    op: F,
+) -> BatchReport
+where
+    F: Fn(String, ItemProgress) -> Fut,
+    Fut: Future<Output = Result<Outcome>> + Send + 'static,
+{
+    let permits = Arc::new(tokio::sync::Semaphore::new(jobs.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+    progress.start();
+
+    for (index, name) in names.iter().enumerate() {
+        let item = progress.item(index);
+        let operation = op(name.clone(), item.clone());
+        let permits = Arc::clone(&permits);
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = operation.await.map_err(|e| e.to_string());
+            item.stage(Stage::Finished(result.clone()));
+            (index, result)
+        });
+    }
+
+    let mut results: Vec<Option<Result<Outcome, String>>> = vec![None; names.len()];
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, result)) => results[index] = Some(result),
+            Err(e) => {
+                // A panicking task only loses its own addon
+                eprintln!("addon task failed: {}", e);
+            }
+        }
+    }
+
+    BatchReport {
+        results: names
+            .iter()
+            .cloned()
+            .zip(results)
+            .map(|(name, result)| {
+                (
+                    name,
+                    result.unwrap_or_else(|| Err("task aborted".to_string())),
+                )
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+#[path = "test_batch.rs"]
+mod tests;
@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use super::batch::{Progress, run_batch};
 use crate::tools::cli::config::path::ensure_deva_dir;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -191,7 +192,11 @@ fn detect_addon_type_in_archive(archive_path: &Path) -> Result<String> {
 }
 
 /// Prompts user to select and install addons interactively
-pub async fn prompt_and_install_addons(addons: &[AddonSearchResult], local: bool) -> Result<()> {
+pub async fn prompt_and_install_addons(
+    addons: &[AddonSearchResult],
+    local: bool,
+    jobs: usize,
+) -> Result<()> {
     if addons.is_empty() {
         println!("\n❌ No addons available to install.\n");
         return Ok(());
@@ -250,51 +255,28 @@ pub async fn prompt_and_install_addons(addons: &[AddonSearchResult], local: bool
 
     println!("\n📦 Installing {} addon(s)...\n", to_install.len());
 
-    // Install each selected addon
-    let mut success_count = 0;
-    let mut failed: Vec<String> = vec![];
-
-    for addon in to_install {
-        let slug = if local {
-            // For local install, use the filename from path
-            addon
-                .path
-                .file_name()
-                .and_then(|f| f.to_str())
-                .unwrap_or(&addon.slug)
-                .to_string()
-        } else {
-            addon.slug.clone()
-        };
-
-        print!("   • Installing {}...", addon.name);
-        std::io::Write::flush(&mut std::io::stdout()).ok();
-
-        match super::install::install_addon(slug.clone(), local, false).await {
-            Ok(_) => {
-                println!(" ✅");
-                success_count += 1;
-            }
-            Err(e) => {
-                println!(" ❌");
-                failed.push(format!("{}: {}", addon.name, e));
+    let slugs: Vec<String> = to_install
+        .iter()
+        .map(|addon| {
+            if local {
+                // For local install, use the filename from path
+                addon
+                    .path
+                    .file_name()
+                    .and_then(|f| f.to_str())
+                    .unwrap_or(&addon.slug)
+                    .to_string()
+            } else {
+                addon.slug.clone()
             }
-        }
-    }
-
-    println!();
-    if success_count > 0 {
-        println!("✅ Successfully installed {} addon(s)", success_count);
-    }
-    if !failed.is_empty() {
-        println!("❌ Failed to install {} addon(s):", failed.len());
-        for f in failed {
-            println!("   • {}", f);
-        }
-    }
-    println!();
+        })
+        .collect();
 
-    Ok(())
+    let report = run_batch(&slugs, jobs, &Progress::new(&slugs), |slug, progress| {
+        super::install::install_addon(slug, local, false, progress)
+    })
+    .await;
+    report.finish("install")
 }
 
 pub fn display_addon_results(addons: &[AddonSearchResult], local: bool) {
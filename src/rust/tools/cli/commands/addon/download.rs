@@ -1,20 +1,26 @@
 #![cfg(feature = "cli")]
 
+use super::batch::{ItemProgress, Outcome, Stage};
 use super::metadata::{AddonMetadata, get_cdn_url};
 use super::utils::ask_api_for_signed_url;
 use anyhow::Result;
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
-/// Downloads a file from the CDN
-pub async fn download_from_cdn(url: &str, destination: &Path) -> Result<()> {
+/// Downloads a file from the CDN, streaming it to disk and reporting bytes received
+pub async fn download_from_cdn(
+    url: &str,
+    destination: &Path,
+    progress: &ItemProgress,
+) -> Result<()> {
     let cdn_url = get_cdn_url();
 
     if !url.starts_with(&cdn_url) {
         return Err(anyhow::anyhow!("Invalid CDN URL: {}", url));
     }
 
-    let response = reqwest::get(url)
+    let mut response = reqwest::get(url)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to download: {}", e))?;
 
@@ -30,12 +36,22 @@ pub async fn download_from_cdn(url: &str, destination: &Path) -> Result<()> {
             .map_err(|e| anyhow::anyhow!("Failed to create directory: {}", e))?;
     }
 
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to read response: {}", e))?;
+    let total = response.content_length();
+    let mut file = fs::File::create(destination)
+        .map_err(|e| anyhow::anyhow!("Failed to write file: {}", e))?;
+    let mut received = 0u64;
+    progress.downloaded(received, total);
 
-    fs::write(destination, bytes).map_err(|e| anyhow::anyhow!("Failed to write file: {}", e))?;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read response: {}", e))?
+    {
+        file.write_all(&chunk)
+            .map_err(|e| anyhow::anyhow!("Failed to write file: {}", e))?;
+        received += chunk.len() as u64;
+        progress.downloaded(received, total);
+    }
 
     Ok(())
 }
@@ -93,8 +109,12 @@ pub fn extract_zip_safely(archive_path: &Path, extract_path: &Path) -> Result<()
     Ok(())
 }
 
-/// Downloads and installs an addon
-pub async fn download_addon(slug: &str, addon_metadata: &AddonMetadata) -> Result<()> {
+/// Downloads and installs an addon; skipped when it is already installed
+pub async fn download_addon(
+    slug: &str,
+    addon_metadata: &AddonMetadata,
+    progress: &ItemProgress,
+) -> Result<Outcome> {
     // Get the .deva directory from the current project (not home dir)
     let deva_dir = crate::tools::cli::config::path::ensure_deva_dir()?;
 
@@ -130,7 +150,7 @@ pub async fn download_addon(slug: &str, addon_metadata: &AddonMetadata) -> Resul
 
     // Check if addon already exists
     if extract_path.exists() {
-        return Ok(());
+        return Ok(Outcome::Skipped("already installed".to_string()));
     }
 
     // Request signed URL (silent - handled by install command logger)
@@ -151,19 +171,25 @@ pub async fn download_addon(slug: &str, addon_metadata: &AddonMetadata) -> Resul
     };
 
     // Download the archive
-    download_from_cdn(&signed_url, &archive_path).await?;
+    let downloaded = download_from_cdn(&signed_url, &archive_path, progress).await;
+    if downloaded.is_err() {
+        let _ = fs::remove_file(&archive_path);
+    }
+    downloaded?;
 
     // Extract the archive
+    progress.stage(Stage::Extracting);
 
     // Detect archive type by extension
-    if archive_path.extension().and_then(|s| s.to_str()) == Some("gz") {
-        extract_tar_gz(&archive_path, &extract_path)?;
+    let extracted = if archive_path.extension().and_then(|s| s.to_str()) == Some("gz") {
+        extract_tar_gz(&archive_path, &extract_path)
     } else {
-        extract_zip_safely(&archive_path, &extract_path)?;
-    }
+        extract_zip_safely(&archive_path, &extract_path)
+    };
 
-    // Clean up temporary files
-    let _ = fs::remove_dir_all(&tmp_root);
+    // Clean up only this addon's archive: other installs may share the tmp directory
+    let _ = fs::remove_file(&archive_path);
+    extracted?;
 
-    Ok(())
+    Ok(Outcome::Done("installed".to_string()))
 }
@@ -1,20 +1,28 @@
 #![cfg(feature = "cli")]
 
+use super::batch::{ItemProgress, Outcome, Stage};
 use super::download::download_addon;
 use super::metadata::get_addon_from_api;
 use super::utils::extract_addon_archive;
 use anyhow::{Context, Result};
 
 /// Installs an addon from remote API or local archive
-pub async fn install_addon(slug: String, local: bool, _no_clear_tmp: bool) -> Result<()> {
+pub async fn install_addon(
+    slug: String,
+    local: bool,
+    _no_clear_tmp: bool,
+    progress: ItemProgress,
+) -> Result<Outcome> {
     if local {
         // Install from local .deva directory
-        install_local_addon(&slug).await
+        progress.stage(Stage::Extracting);
+        install_local_addon(&slug).await?;
+        Ok(Outcome::Done("installed from local archive".to_string()))
     } else {
         // Install from remote API
+        progress.stage(Stage::Resolving);
         let addon_metadata = get_addon_from_api(&slug).await?;
-        download_addon(&slug, &addon_metadata).await?;
-        Ok(())
+        download_addon(&slug, &addon_metadata, &progress).await
     }
 }
 
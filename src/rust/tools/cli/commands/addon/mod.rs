@@ -3,13 +3,16 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
 
+use crate::platform::config::AppConfig;
 use crate::tools::cli::state::CliContext;
 
+mod batch;
 mod discover;
 mod download;
 mod install;
 mod list;
 mod metadata;
+mod outdated;
 mod remove;
 mod update;
 mod utils;
@@ -23,13 +26,19 @@ pub struct AddonCommand {
 #[derive(Debug, Clone, Subcommand)]
 pub enum AddonAction {
     Install {
-        name: String,
+        /// One or more addons, installed concurrently
+        #[arg(required = true, num_args = 1..)]
+        names: Vec<String>,
         /// Install from local .deva directory instead of remote
         #[arg(short, long)]
         local: bool,
+        /// Maximum number of addons installed at once
+        #[arg(short, long, default_value_t = batch::DEFAULT_JOBS)]
+        jobs: usize,
     },
     Remove {
-        name: String,
+        #[arg(required = true, num_args = 1..)]
+        names: Vec<String>,
     },
     List,
     Discover {
@@ -47,13 +56,23 @@ pub enum AddonAction {
         /// Prompt to install discovered addons
         #[arg(short, long)]
         install: bool,
+        /// Maximum number of addons installed at once
+        #[arg(short, long, default_value_t = batch::DEFAULT_JOBS)]
+        jobs: usize,
     },
     Update {
-        name: String,
+        /// One or more addons, updated concurrently
+        #[arg(required = true, num_args = 1..)]
+        names: Vec<String>,
+        /// Maximum number of addons updated at once
+        #[arg(short, long, default_value_t = batch::DEFAULT_JOBS)]
+        jobs: usize,
     },
     Metadata {
         name: String,
     },
+    /// List installed addons with newer releases, and whether the project pins allow them
+    Outdated,
 }
 
 impl AddonCommand {
@@ -61,26 +80,41 @@ impl AddonCommand {
         let logger = ctx.logger();
 
         match &self.action {
-            Some(AddonAction::Install { name, local }) => {
-                logger.action(format!("Installing addon '{}'...", name));
-                match install::install_addon(name.clone(), *local, false).await {
+            Some(AddonAction::Install { names, local, jobs }) => {
+                logger.action(format!("Installing {}...", describe(names)));
+                let local = *local;
+                let report = batch::run_batch(
+                    names,
+                    *jobs,
+                    &batch::Progress::new(names),
+                    |name, progress| install::install_addon(name, local, false, progress),
+                )
+                .await;
+                match report.finish("install") {
                     Ok(_) => {
-                        logger.success(format!("Addon '{}' installed successfully", name));
+                        logger.success(format!("Installed {}", describe(names)));
                     }
                     Err(e) => {
-                        logger.error(format!("Failed to install addon '{}': {}", name, e));
+                        logger.error(format!("Failed to install {}: {}", describe(names), e));
                         return Err(e);
                     }
                 }
             }
-            Some(AddonAction::Remove { name }) => {
-                logger.action(format!("Removing addon '{}'...", name));
-                match remove::remove_addon(name.clone()).await {
+            Some(AddonAction::Remove { names }) => {
+                logger.action(format!("Removing {}...", describe(names)));
+                let report = batch::run_batch(
+                    names,
+                    names.len(),
+                    &batch::Progress::new(names),
+                    remove::remove_addon,
+                )
+                .await;
+                match report.finish("remove") {
                     Ok(_) => {
-                        logger.success(format!("Addon '{}' removed successfully", name));
+                        logger.success(format!("Removed {}", describe(names)));
                     }
                     Err(e) => {
-                        logger.error(format!("Failed to remove addon '{}': {}", name, e));
+                        logger.error(format!("Failed to remove {}: {}", describe(names), e));
                         return Err(e);
                     }
                 }
@@ -101,6 +135,7 @@ impl AddonCommand {
                 author,
                 local,
                 install: should_install,
+                jobs,
             }) => {
                 logger.action("Discovering addons...");
                 match discover::discover_addons(
@@ -114,7 +149,8 @@ impl AddonCommand {
                     Ok(addons) => {
                         if *should_install && !addons.is_empty() {
                             // Interactive installation
-                            match discover::prompt_and_install_addons(&addons, *local).await {
+                            match discover::prompt_and_install_addons(&addons, *local, *jobs).await
+                            {
                                 Ok(_) => {}
                                 Err(e) => {
                                     logger.error(format!("Failed to install addons: {}", e));
@@ -137,14 +173,32 @@ impl AddonCommand {
                     }
                 }
             }
-            Some(AddonAction::Update { name }) => {
-                logger.action(format!("Updating addon '{}'...", name));
-                match update::update_addon(name.clone()).await {
+            Some(AddonAction::Update { names, jobs }) => {
+                logger.action(format!("Updating {}...", describe(names)));
+                let report = batch::run_batch(
+                    names,
+                    *jobs,
+                    &batch::Progress::new(names),
+                    update::update_addon,
+                )
+                .await;
+                match report.finish("update") {
                     Ok(_) => {
-                        logger.success(format!("Addon '{}' updated successfully", name));
+                        logger.success(format!("Updated {}", describe(names)));
+                    }
+                    Err(e) => {
+                        logger.error(format!("Failed to update {}: {}", describe(names), e));
+                        return Err(e);
                     }
+                }
+            }
+            Some(AddonAction::Outdated) => {
+                logger.action("Checking installed addons for updates...");
+                let config = AppConfig::load(std::env::current_dir()?)?;
+                match outdated::outdated_addons(&config.addons, &logger).await {
+                    Ok(addons) => outdated::display_outdated(&addons),
                     Err(e) => {
-                        logger.error(format!("Failed to update addon '{}': {}", name, e));
+                        logger.error(format!("Failed to check for outdated addons: {}", e));
                         return Err(e);
                     }
                 }
@@ -167,3 +221,11 @@ impl AddonCommand {
         Ok(())
     }
 }
+
+/// "addon 'name'" for a single addon, "3 addons" otherwise
+fn describe(names: &[String]) -> String {
+    match names {
+        [name] => format!("addon '{}'", name),
+        _ => format!("{} addons", names.len()),
+    }
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "cli")]
+
+use super::metadata::get_addon_from_api;
+use super::update::fetch_latest_version;
+use crate::tools::cli::config::pins::{self, parse_requirement, parse_version};
+use crate::tools::logger::Logger;
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+pub struct OutdatedAddon {
+    pub id: String,
+    pub installed: String,
+    pub latest: String,
+    /// Constraint from the `[addons]` config section, if the addon is pinned
+    pub constraint: Option<String>,
+    /// Whether the latest version satisfies the pin (always true when unpinned)
+    pub allowed: bool,
+}
+
+/// Installed addons with a newer release, checked against the project's pins
+pub async fn outdated_addons(
+    pinned: &BTreeMap<String, String>,
+    logger: &Logger,
+) -> Result<Vec<OutdatedAddon>> {
+    let mut outdated = Vec::new();
+
+    for addon in pins::installed_addons() {
+        let Some(installed) = addon.version.clone() else {
+            continue;
+        };
+        let slug = addon
+            .id
+            .split_once('.')
+            .map_or(addon.id.as_str(), |(_, name)| name);
+
+        let latest = match get_addon_from_api(slug).await {
+            Ok(metadata) => fetch_latest_version(&metadata.addon_type, &metadata.name).await,
+            Err(e) => Err(e),
+        };
+        let latest = match latest {
+            Ok(latest) => latest.version,
+            Err(e) => {
+                logger.warn(format!("Skipping '{}': {}", addon.id, e));
+                continue;
+            }
+        };
+
+        let (Ok(installed_version), Ok(latest_version)) =
+            (parse_version(&installed), parse_version(&latest))
+        else {
+            continue;
+        };
+        if latest_version <= installed_version {
+            continue;
+        }
+
+        let constraint = pinned.get(&addon.id).cloned();
+        let allowed = constraint.as_deref().is_none_or(|constraint| {
+            parse_requirement(constraint).is_ok_and(|req| req.matches(&latest_version))
+        });
+        outdated.push(OutdatedAddon {
+            id: addon.id,
+            installed,
+            latest,
+            constraint,
+            allowed,
+        });
+    }
+
+    Ok(outdated)
+}
+
+/// Displays outdated addons
+pub fn display_outdated(outdated: &[OutdatedAddon]) {
+    if outdated.is_empty() {
+        println!("All installed addons are up to date.");
+        return;
+    }
+
+    println!("\n⬆️  Outdated addons:");
+    for addon in outdated {
+        let pin = match (&addon.constraint, addon.allowed) {
+            (Some(constraint), true) => format!(" (within {})", constraint),
+            (Some(constraint), false) => {
+                format!(" (pinned to {}; update the pin to upgrade)", constraint)
+            }
+            (None, _) => String::new(),
+        };
+        println!(
+            "  - {} {} -> {}{}",
+            addon.id, addon.installed, addon.latest, pin
+        );
+    }
+
+    let upgradable = outdated.iter().filter(|addon| addon.allowed).count();
+    println!(
+        "\n{} addon(s) can be upgraded with 'devalang addon update <name>'.",
+        upgradable
+    );
+}
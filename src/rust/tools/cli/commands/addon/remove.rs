@@ -1,11 +1,12 @@
 #![cfg(feature = "cli")]
 
+use super::batch::{ItemProgress, Outcome, Stage};
 use crate::tools::cli::config::path::ensure_deva_dir;
 use anyhow::Result;
 use std::fs;
 
 /// Removes an installed addon
-pub async fn remove_addon(slug: String) -> Result<()> {
+pub async fn remove_addon(slug: String, progress: ItemProgress) -> Result<Outcome> {
     let deva_dir = ensure_deva_dir()?;
 
     // Parse the slug (can be "publisher.name" or just "name")
@@ -40,10 +41,10 @@ pub async fn remove_addon(slug: String) -> Result<()> {
         let addon_path = folder.join(&publisher).join(&addon_name);
 
         if addon_path.exists() {
+            progress.stage(Stage::Removing);
             fs::remove_dir_all(&addon_path)
                 .map_err(|e| anyhow::anyhow!("Failed to remove addon files: {}", e))?;
 
-            // Success is handled by the caller in mod.rs
             found = true;
             break;
         }
@@ -57,5 +58,5 @@ pub async fn remove_addon(slug: String) -> Result<()> {
         ));
     }
 
-    Ok(())
+    Ok(Outcome::Done("removed".to_string()))
 }
@@ -0,0 +1,84 @@
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn names(list: &[&str]) -> Vec<String> {
+    list.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_run_batch_limits_concurrency_and_keeps_going_after_failures() {
+    let names = names(&["a.one", "a.two", "a.three", "a.four", "a.five"]);
+    let running = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let report = runtime.block_on(run_batch(&names, 2, &Progress::hidden(), |name, _| {
+        let running = Arc::clone(&running);
+        let peak = Arc::clone(&peak);
+        async move {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            match name.as_str() {
+                "a.two" => Err(anyhow::anyhow!("HTTP 404")),
+                "a.four" => Ok(Outcome::Skipped("already installed".to_string())),
+                _ => Ok(Outcome::Done("installed".to_string())),
+            }
+        }
+    }));
+
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    let order: Vec<&str> = report.results.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(order, ["a.one", "a.two", "a.three", "a.four", "a.five"]);
+    assert_eq!(report.failures(), 1);
+    assert_eq!(report.results[1].1, Err("HTTP 404".to_string()));
+    assert!(report.finish("install").is_err());
+}
+
+#[test]
+fn test_skipped_addons_are_not_failures() {
+    let report = BatchReport {
+        results: vec![
+            (
+                "acme.kit".to_string(),
+                Ok(Outcome::Done("installed".to_string())),
+            ),
+            (
+                "acme.pads".to_string(),
+                Ok(Outcome::Skipped("up to date (1.0.0)".to_string())),
+            ),
+        ],
+    };
+
+    assert_eq!(
+        report.table(),
+        "Addon      Result   Details\n\
+         acme.kit   ok       installed\n\
+         acme.pads  skipped  up to date (1.0.0)\n"
+    );
+    assert!(report.finish("update").is_ok());
+}
+
+#[test]
+fn test_download_line_shows_bar_and_sizes() {
+    let line = stage_line(&Stage::Downloading {
+        done: 512 * 1024,
+        total: Some(1024 * 1024),
+    });
+    assert_eq!(
+        line,
+        format!(
+            "[{}{}]  50% 512.0 KB / 1.0 MB",
+            "#".repeat(12),
+            ".".repeat(12)
+        )
+    );
+    assert_eq!(
+        stage_line(&Stage::Downloading {
+            done: 300,
+            total: None
+        }),
+        "downloading 300 B"
+    );
+}
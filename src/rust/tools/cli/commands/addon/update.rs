@@ -1,7 +1,10 @@
 #![cfg(feature = "cli")]
 
+use super::batch::{ItemProgress, Outcome, Stage};
 use super::download::download_addon;
 use super::metadata::{AddonType, get_addon_from_api, get_addon_publisher_from_api, get_cdn_url};
+use crate::platform::config::AppConfig;
+use crate::tools::cli::config::pins::{parse_requirement, parse_version};
 use anyhow::Result;
 use std::fs;
 
@@ -11,7 +14,10 @@ pub struct AddonVersion {
 }
 
 /// Retrieves the latest version of an addon from the CDN
-async fn fetch_latest_version(addon_type: &AddonType, addon_name: &str) -> Result<AddonVersion> {
+pub async fn fetch_latest_version(
+    addon_type: &AddonType,
+    addon_name: &str,
+) -> Result<AddonVersion> {
     let cdn_url = get_cdn_url();
     let publisher = get_addon_publisher_from_api(addon_name).await?;
 
@@ -78,8 +84,9 @@ fn read_local_version(addon_path: &std::path::Path, addon_type: &AddonType) -> S
     String::new()
 }
 
-/// Updates an addon
-pub async fn update_addon(slug: String) -> Result<()> {
+/// Updates an addon; skipped when it is up to date or pinned below the latest release
+pub async fn update_addon(slug: String, progress: ItemProgress) -> Result<Outcome> {
+    progress.stage(Stage::Resolving);
     let addon_metadata = get_addon_from_api(&slug).await?;
 
     let deva_dir = crate::tools::cli::config::path::ensure_deva_dir()?;
@@ -114,9 +121,20 @@ pub async fn update_addon(slug: String) -> Result<()> {
         ));
     }
 
+    // Never move past the version range pinned in the project config
+    let config = AppConfig::load(std::env::current_dir()?)?;
+    if let Some(constraint) = config.addons.get(&publisher_and_name)
+        && !parse_requirement(constraint)?.matches(&parse_version(&latest.version)?)
+    {
+        return Ok(Outcome::Skipped(format!(
+            "latest {} does not satisfy the pinned {}; update the [addons] section of the project config to upgrade",
+            latest.version, constraint
+        )));
+    }
+
     if local_version == latest.version {
         // Already up-to-date, no action needed
-        return Ok(());
+        return Ok(Outcome::Skipped(format!("up to date ({})", local_version)));
     }
 
     // Remove the old version
@@ -126,8 +144,10 @@ pub async fn update_addon(slug: String) -> Result<()> {
     }
 
     // Download the new version
-    download_addon(&publisher_and_name, &addon_metadata).await?;
+    download_addon(&publisher_and_name, &addon_metadata, &progress).await?;
 
-    // Success is handled by caller in mod.rs
-    Ok(())
+    Ok(Outcome::Done(format!(
+        "updated {} -> {}",
+        local_version, latest.version
+    )))
 }
@@ -3,12 +3,25 @@
 use anyhow::Result;
 use clap::Args;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::engine::audio::settings::AudioFormat;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::playback::capture;
+use crate::engine::audio::settings::{AudioFormat, BuildOutput, ClickMode, LogTimelineFormat};
+use crate::engine::audio::solo::SoloMute;
+use crate::language::syntax::ast::Value;
 use crate::platform::config::AppConfig;
-use crate::services::build::pipeline::{BuildRequest, ProjectBuilder};
+use crate::platform::storage::lock::LockPolicy;
+use crate::services::build::hooks;
+use crate::services::build::outputs::audio::writer::write_wav;
+use crate::services::build::pipeline::{
+    BuildArtifacts, BuildRequest, DETERMINISTIC_SEED, ProjectBuilder,
+};
+use crate::tools::cli::commands::samples;
+use crate::tools::cli::config::pins;
 use crate::tools::cli::rules_reporter::RulesReporter;
 use crate::tools::cli::state::CliContext;
+use crate::tools::logger::Logger;
 
 #[derive(Debug, Clone, Args)]
 pub struct BuildCommand {
@@ -24,6 +37,65 @@ pub struct BuildCommand {
     /// Disable rule checking during build
     #[arg(long, default_value_t = false)]
     pub no_rule: bool,
+
+    /// Export scheduled prints with their bar/beat position (e.g. "json")
+    #[arg(long, value_enum)]
+    pub log_timeline: Option<LogTimelineFormat>,
+
+    /// Extra files to write next to the audio (e.g. "events-json" for the collected
+    /// events, in the schema documented in `engine::audio::event_export`)
+    #[arg(long, value_enum, value_delimiter = ' ', num_args = 1..)]
+    pub outputs: Vec<BuildOutput>,
+
+    /// Reproducible build: fixed random seed, no wall-clock values, and a
+    /// SHA-256 content hash of the rendered audio
+    #[arg(long, default_value_t = false)]
+    pub deterministic: bool,
+
+    /// Cut trailing silence from the rendered audio (also `audio.auto_trim` in config)
+    #[arg(long = "auto-trim", default_value_t = false)]
+    pub auto_trim: bool,
+
+    /// Render block by block and write audio as it is produced, for long pieces
+    /// (also `audio.stream` in config)
+    #[arg(long, default_value_t = false)]
+    pub stream: bool,
+
+    /// Bounce synths bound to external MIDI gear (`bind lead -> mapping.out.<device>`):
+    /// play their notes on the device while recording this audio input (the default
+    /// input when no name is given)
+    #[arg(long = "capture-input", num_args = 0..=1, default_missing_value = "")]
+    pub capture_input: Option<String>,
+
+    /// Round-trip latency of the gear and audio interface removed from the capture, in ms
+    #[arg(long = "input-latency", default_value_t = 0.0)]
+    pub input_latency: f32,
+
+    /// Render only this group and the other soloed ones (repeatable)
+    #[arg(long = "solo", value_name = "GROUP")]
+    pub solo: Vec<String>,
+
+    /// Silence this group (repeatable)
+    #[arg(long = "mute", value_name = "GROUP")]
+    pub mute: Vec<String>,
+
+    /// Render a metronome following the tempo and meter map: mixed into the audio
+    /// (default) or as a separate `<module>.click.wav` stem
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "mix")]
+    pub click: Option<ClickMode>,
+
+    /// When another build holds the project lock, wait for it (at most SECONDS when given)
+    #[arg(long = "wait-lock", value_name = "SECONDS", num_args = 0..=1)]
+    pub wait_lock: Option<Option<u64>>,
+
+    /// Take the project lock over from a stuck build instead of waiting
+    #[arg(long = "steal-lock", conflicts_with = "wait_lock")]
+    pub steal_lock: bool,
+
+    /// Skip rendering when nothing changed since the last build of this entry (modules,
+    /// banks, config, options): its outputs are restored from `.deva/cache/builds`
+    #[arg(long = "check-only-if-changed", default_value_t = false)]
+    pub check_only_if_changed: bool,
 }
 
 impl BuildCommand {
@@ -34,6 +106,7 @@ impl BuildCommand {
         // Load config
         let current_dir = std::env::current_dir()?;
         let config = AppConfig::load(&current_dir)?;
+        pins::enforce_pins(&config.addons)?;
 
         // Initialize rules reporter (only if rules not disabled)
         let rules_reporter = if !self.no_rule {
@@ -89,6 +162,22 @@ impl BuildCommand {
             }
         }
 
+        // Same bank-wide sample work as `play`; the samples this build uses are also
+        // converted right before rendering, so this mostly warms caches for later runs
+        let preparation = if config.audio.preconvert_samples || config.audio.sample_variants {
+            if let Err(e) = crate::engine::audio::samples::auto_load_banks() {
+                logger.warn(format!("Failed to auto-load banks: {}", e));
+            }
+            Some(samples::start_bank_preparation(
+                &config,
+                config.sample_rate(),
+                config.resample_quality(),
+                &logger,
+            ))
+        } else {
+            None
+        };
+
         // Create build request
         let output_root = current_dir.join(&config.paths.output);
         let request = BuildRequest {
@@ -98,13 +187,54 @@ impl BuildCommand {
             bit_depth: config.audio_bit_depth(),
             channels: config.audio_channels(),
             resample_quality: config.resample_quality(),
+            preconvert_samples: config.audio.preconvert_samples,
+            mix: config.mix_settings()?,
             sample_rate: config.sample_rate(),
             bpm: config.audio.bpm,
+            log_timeline: self.log_timeline,
+            outputs: self.outputs.clone(),
+            deterministic: self.deterministic,
+            variable_overrides: Default::default(),
+            bank_remaps: Default::default(),
+            default_bank: config.banks.default.clone(),
+            tags: config.audio.tags.clone(),
+            auto_trim: self.auto_trim || config.audio.auto_trim,
+            stream: self.stream || config.audio.stream,
+            solo_mute: SoloMute::from_names(&self.solo, &self.mute),
+            click: self.click,
+            limits: Default::default(),
         };
 
         // Build project
-        let builder = ProjectBuilder::new(logger.clone());
-        let artifacts = builder.build(&request)?;
+        let mut builder = ProjectBuilder::new(logger.clone());
+        if let Ok(deva) = crate::tools::cli::config::path::get_deva_dir() {
+            builder = builder.with_project_lock(
+                deva,
+                "devalang build",
+                LockPolicy::from_flags(self.wait_lock, self.steal_lock),
+            );
+        }
+        if self.check_only_if_changed {
+            let deva = crate::tools::cli::config::path::ensure_deva_dir()?;
+            builder = builder.with_input_manifest(deva, current_dir.clone());
+        }
+        let result = builder.build(&request);
+        if let Some(preparation) = preparation {
+            samples::finish_bank_preparation(preparation, &logger);
+        }
+        let artifacts = match result {
+            Ok(artifacts) => artifacts,
+            Err(error) => {
+                if let Some(command) = &config.hooks.on_build_failure {
+                    logger.action(format!("Running on_build_failure hook: {}", command));
+                    let env = hooks::failure_env(&request, &error);
+                    if let Err(hook_error) = hooks::run_hook(command, &current_dir, &env) {
+                        logger.warn(format!("{:#}", hook_error));
+                    }
+                }
+                return Err(error);
+            }
+        };
 
         // Log results
         logger.success(format!(
@@ -116,6 +246,43 @@ impl BuildCommand {
             logger.info(format!("  - {:?}: {}", format, path.display()));
         }
 
+        if request.auto_trim {
+            logger.info(format!(
+                "  Trimmed {:.0} ms of trailing silence, final length {:.2}s",
+                artifacts.trimmed.as_secs_f64() * 1000.0,
+                artifacts.audio_length.as_secs_f64()
+            ));
+        }
+
+        if let Some(hash) = &artifacts.content_hash {
+            logger.info(format!("  sha256: {}", hash));
+        }
+
+        if !artifacts.sample_conversions.is_empty() {
+            logger.info(format!(
+                "Resampled {} sample(s) to {} Hz:",
+                artifacts.sample_conversions.len(),
+                artifacts.sample_rate
+            ));
+            for conversion in &artifacts.sample_conversions {
+                logger.info(format!("  - {}", conversion.describe()));
+            }
+        }
+
+        if let Some(input) = &self.capture_input {
+            let input = Some(input.as_str()).filter(|name| !name.is_empty());
+            self.bounce_hardware(&logger, &request, &artifacts, input)?;
+        }
+
+        if let Some(command) = &config.hooks.on_build_success {
+            logger.action(format!("Running on_build_success hook: {}", command));
+            hooks::run_hook(
+                command,
+                &current_dir,
+                &hooks::success_env(&request, &artifacts),
+            )?;
+        }
+
         logger.watch(format!(
             "Total build time: {:.1} ms (audio: {:.1} ms)",
             artifacts.total_duration.as_secs_f64() * 1000.0,
@@ -124,4 +291,98 @@ impl BuildCommand {
 
         Ok(())
     }
+    /// Stream the notes of every synth bound to a MIDI output to its port while
+    /// recording `input`, and write each aligned recording next to the primary audio
+    fn bounce_hardware(
+        &self,
+        logger: &Logger,
+        request: &BuildRequest,
+        artifacts: &BuildArtifacts,
+        input: Option<&str>,
+    ) -> Result<()> {
+        let mut interpreter = AudioInterpreter::new(request.sample_rate);
+        if request.deterministic {
+            interpreter.set_deterministic(DETERMINISTIC_SEED);
+        }
+        interpreter
+            .banks
+            .set_default_bank(request.default_bank.clone());
+        interpreter.solo_mute = request.solo_mute.clone();
+        interpreter.collect_all_events(&artifacts.statements)?;
+
+        let mut binds: Vec<(String, String, usize, u8)> = interpreter
+            .variables
+            .iter()
+            .filter(|(key, _)| key.starts_with("__mapping_bind::mapping.out."))
+            .filter_map(|(_, value)| {
+                let Value::Map(bind) = value else {
+                    return None;
+                };
+                let text = |key: &str| match bind.get(key) {
+                    Some(Value::String(s)) | Some(Value::Identifier(s)) => Some(s.clone()),
+                    _ => None,
+                };
+                let number = |key: &str| match bind.get(key) {
+                    Some(Value::Number(n)) => Some(*n),
+                    _ => None,
+                };
+                Some((
+                    text("source")?,
+                    text("device")?,
+                    number("port")? as usize,
+                    // `channel` counts from 1 like on the gear
+                    (number("channel").unwrap_or(1.0) as u8).clamp(1, 16) - 1,
+                ))
+            })
+            .collect();
+        binds.sort();
+        if binds.is_empty() {
+            logger.warn("--capture-input: no synth is bound to a MIDI output (`bind <synth> -> mapping.out.<device> with { port: N }`)");
+            return Ok(());
+        }
+
+        let latency = Duration::from_secs_f32(self.input_latency.max(0.0) / 1000.0);
+        let length = artifacts.audio_length;
+        let length_frames = (length.as_secs_f64() * request.sample_rate as f64).ceil() as usize;
+        for (source, device, port, channel) in binds {
+            let cues = capture::schedule_notes(&interpreter.events.events, &source, channel);
+            if cues.is_empty() {
+                logger.warn(format!(
+                    "--capture-input: '{}' plays no notes for {}",
+                    source, device
+                ));
+                continue;
+            }
+            logger.action(format!(
+                "Bouncing '{}' through {} (port {}, channel {})...",
+                source,
+                device,
+                port,
+                channel + 1
+            ));
+            let last_cue = Duration::from_secs_f32(cues.last().map_or(0.0, |cue| cue.time));
+            let tail = length.saturating_sub(last_cue) + latency + Duration::from_millis(500);
+            let recording = capture::record_while_sending(input, port, &cues, tail)?;
+            let aligned = capture::align_capture(
+                &recording,
+                latency,
+                request.sample_rate,
+                request.resample_quality,
+                length_frames,
+            );
+
+            let path = artifacts
+                .primary_audio_path
+                .with_file_name(format!("{}.{}.wav", artifacts.module_name, device));
+            write_wav(
+                &path,
+                &aligned,
+                request.sample_rate,
+                request.bit_depth,
+                request.channels,
+            )?;
+            logger.success(format!("Hardware bounce written to {}", path.display()));
+        }
+        Ok(())
+    }
 }
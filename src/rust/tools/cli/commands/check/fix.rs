@@ -0,0 +1,238 @@
+//! Safe automatic fixes for rule violations (`devalang check --fix`)
+//!
+//! Every fix rewrites or removes a single line, so the preview is a per-line diff:
+//! - `var_keyword`: `var x = ...` becomes `let x = ...`
+//! - `deprecated_syntax`: `@import` / `@export` / `@use` / `@load` lose their `@`
+//! - `explicit_durations`: triggers without a duration get the implicit one (`1 beat`)
+//! - `unused_variables`: single-line top-level `let` / `var` / `const` that nothing references
+
+use crate::language::syntax::ast::{DurationValue, StatementKind};
+use crate::language::syntax::parser::driver::trigger::parse_trigger_line;
+use crate::platform::config::RulesSection;
+
+/// Duration a trigger takes when none is written (the cursor advances one beat)
+const DEFAULT_TRIGGER_DURATION: &str = "1 beat";
+const DEPRECATED_DIRECTIVES: [&str; 4] = ["import", "export", "use", "load"];
+
+/// Which rules get fixed; a rule turned `off` in the config is left alone
+#[derive(Debug, Clone, Copy)]
+pub struct FixOptions {
+    pub var_keyword: bool,
+    pub deprecated_syntax: bool,
+    pub explicit_durations: bool,
+    pub unused_variables: bool,
+}
+
+impl FixOptions {
+    pub fn from_rules(rules: &RulesSection) -> Self {
+        Self {
+            var_keyword: rules.var_keyword.should_report(),
+            deprecated_syntax: rules.deprecated_syntax.should_report(),
+            explicit_durations: rules.explicit_durations.should_report(),
+            unused_variables: rules.unused_variables.should_report(),
+        }
+    }
+}
+
+/// One applied fix; `after` is `None` when the line was removed
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineFix {
+    /// 1-based line number in the original source
+    pub line: usize,
+    pub rule: &'static str,
+    pub before: String,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FixResult {
+    pub source: String,
+    pub fixes: Vec<LineFix>,
+}
+
+/// Apply every enabled fix to `source`
+pub fn fix_source(source: &str, options: &FixOptions) -> FixResult {
+    let original: Vec<&str> = source.lines().collect();
+    let mut lines: Vec<Option<String>> = original.iter().map(|l| Some(l.to_string())).collect();
+    let mut rules: Vec<Vec<&'static str>> = vec![Vec::new(); lines.len()];
+
+    for (idx, slot) in lines.iter_mut().enumerate() {
+        let Some(line) = slot.as_mut() else {
+            continue;
+        };
+        if options.var_keyword
+            && let Some(fixed) = replace_var_keyword(line)
+        {
+            *line = fixed;
+            rules[idx].push("var_keyword");
+        }
+        if options.deprecated_syntax
+            && let Some(fixed) = drop_directive_prefix(line)
+        {
+            *line = fixed;
+            rules[idx].push("deprecated_syntax");
+        }
+        if options.explicit_durations
+            && let Some(fixed) = add_trigger_duration(line)
+        {
+            *line = fixed;
+            rules[idx].push("explicit_durations");
+        }
+    }
+
+    if options.unused_variables {
+        for idx in unused_declarations(&lines) {
+            lines[idx] = None;
+            rules[idx].push("unused_variables");
+        }
+    }
+
+    let fixes = original
+        .iter()
+        .zip(&lines)
+        .zip(&rules)
+        .enumerate()
+        .filter(|(_, ((_, _), rules))| !rules.is_empty())
+        .map(|(idx, ((before, after), rules))| LineFix {
+            line: idx + 1,
+            // Report the last rule applied; a removal wins over earlier rewrites
+            rule: rules[rules.len() - 1],
+            before: before.to_string(),
+            after: after.clone(),
+        })
+        .collect();
+
+    let mut fixed: Vec<String> = lines.into_iter().flatten().collect();
+    if source.ends_with('\n') {
+        fixed.push(String::new());
+    }
+    FixResult {
+        source: fixed.join("\n"),
+        fixes,
+    }
+}
+
+/// Render the fixes as a unified-style diff
+pub fn diff_preview(file: &str, fixes: &[LineFix]) -> String {
+    let mut out = format!("--- {}\n+++ {} (fixed)\n", file, file);
+    for fix in fixes {
+        out.push_str(&format!("@@ line {} ({}) @@\n", fix.line, fix.rule));
+        out.push_str(&format!("-{}\n", fix.before));
+        if let Some(after) = &fix.after {
+            out.push_str(&format!("+{}\n", after));
+        }
+    }
+    out
+}
+
+fn split_indent(line: &str) -> (&str, &str) {
+    let body = line.trim_start();
+    (&line[..line.len() - body.len()], body)
+}
+
+fn replace_var_keyword(line: &str) -> Option<String> {
+    let (indent, body) = split_indent(line);
+    let rest = body.strip_prefix("var")?;
+    rest.starts_with(char::is_whitespace)
+        .then(|| format!("{}let{}", indent, rest))
+}
+
+fn drop_directive_prefix(line: &str) -> Option<String> {
+    let (indent, body) = split_indent(line);
+    let rest = body.strip_prefix('@')?;
+    DEPRECATED_DIRECTIVES
+        .iter()
+        .any(|keyword| {
+            rest.strip_prefix(keyword)
+                .is_some_and(|after| after.is_empty() || after.starts_with(char::is_whitespace))
+        })
+        .then(|| format!("{}{}", indent, rest))
+}
+
+fn add_trigger_duration(line: &str) -> Option<String> {
+    let (indent, body) = split_indent(line);
+    if !body.starts_with('.') || body.starts_with("..") {
+        return None;
+    }
+    // Only rewrite triggers that parse and are missing a duration (`auto` is explicit)
+    let statement = parse_trigger_line(body, 0).ok()?;
+    let StatementKind::Trigger { duration, .. } = &statement.kind else {
+        return None;
+    };
+    let words: Vec<&str> = body.split("->").next()?.split_whitespace().collect();
+    if *duration != DurationValue::Auto || words.iter().any(|w| w.eq_ignore_ascii_case("auto")) {
+        return None;
+    }
+
+    let entity = words.first()?;
+    let entity_end = body.find(entity)? + entity.len();
+    Some(format!(
+        "{}{} {}{}",
+        indent,
+        &body[..entity_end],
+        DEFAULT_TRIGGER_DURATION,
+        &body[entity_end..]
+    ))
+}
+
+/// Top-level single-line declarations whose name appears nowhere else
+fn unused_declarations(lines: &[Option<String>]) -> Vec<usize> {
+    let words = |line: &str| -> Vec<String> {
+        line.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+
+    let mut unused = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let Some(line) = line else {
+            continue;
+        };
+        let Some((name, value)) = declaration(line) else {
+            continue;
+        };
+        // Multi-line values and block bodies are not safe to drop line by line
+        let balanced =
+            value.matches(['{', '[', '(']).count() == value.matches(['}', ']', ')']).count();
+        let next_indented = lines[idx + 1..]
+            .iter()
+            .flatten()
+            .find(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+            .is_some_and(|l| l.starts_with(char::is_whitespace));
+        if name.starts_with('_') || !balanced || next_indented {
+            continue;
+        }
+
+        // The declaration itself is one occurrence
+        let uses: usize = lines
+            .iter()
+            .flatten()
+            .map(|l| words(l).iter().filter(|w| **w == name).count())
+            .sum();
+        if uses <= 1 {
+            unused.push(idx);
+        }
+    }
+    unused
+}
+
+/// `(name, value)` of a top-level `let` / `var` / `const` line
+fn declaration(line: &str) -> Option<(&str, &str)> {
+    if line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = ["let ", "var ", "const "]
+        .iter()
+        .find_map(|keyword| line.strip_prefix(keyword))?;
+    let (name, value) = rest.split_once('=')?;
+    let name = name.trim();
+    name.chars()
+        .all(|c| c.is_alphanumeric() || c == '_')
+        .then_some((name, value))
+        .filter(|(name, _)| !name.is_empty())
+}
+
+#[cfg(test)]
+#[path = "test_fix.rs"]
+mod tests;
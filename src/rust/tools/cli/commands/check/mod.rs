@@ -2,12 +2,24 @@
 
 use anyhow::Result;
 use clap::Args;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-use crate::language::syntax::parser::driver::SimpleParser;
+use crate::engine::audio::graph::SignalFlow;
+use crate::engine::audio::samples::{self, RateConversion};
+use crate::engine::audio::settings::ResampleQuality;
+use crate::language::addons::registry::{BankRegistry, TriggerCandidate};
+use crate::language::diagnostics::strict::{self, BankLookup};
+use crate::language::diagnostics::usage::unused_variables;
+use crate::language::syntax::ast::{Statement, StatementKind};
+use crate::language::syntax::parser::driver::{LocatedParseError, SimpleParser};
+use crate::platform::config::AppConfig;
+use crate::tools::cli::config::pins;
 use crate::tools::cli::rules_reporter::RulesReporter;
 use crate::tools::cli::state::CliContext;
+use crate::tools::logger::{Logger, StructuredError};
+
+pub mod fix;
 
 #[derive(Debug, Clone, Args)]
 pub struct CheckCommand {
@@ -26,6 +38,18 @@ pub struct CheckCommand {
     /// Disable rule checking during validation
     #[arg(long, default_value_t = false)]
     pub no_rule: bool,
+
+    /// Rewrite safe rule violations in place (a `.bak` copy of each file is kept)
+    #[arg(long, default_value_t = false)]
+    pub fix: bool,
+
+    /// With --fix, only print the diff preview without writing anything
+    #[arg(long, default_value_t = false, requires = "fix")]
+    pub dry_run: bool,
+
+    /// Also run static checks (types, arity, effects, bank triggers, events) and fail on them
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
 }
 
 impl CheckCommand {
@@ -71,12 +95,20 @@ impl CheckCommand {
 
         logger.info(format!("Found {} file(s) to check", files_to_check.len()));
 
+        let config = AppConfig::load(std::env::current_dir()?)?;
+        pins::enforce_pins(&config.addons)?;
+        let fix_options = self.fix.then(|| fix::FixOptions::from_rules(&config.rules));
+
         // Parse all files
         let mut total_errors = 0;
 
         for file_path in &files_to_check {
             let file_display = file_path.display();
 
+            if let Some(options) = &fix_options {
+                self.fix_file(file_path, options, &logger)?;
+            }
+
             match SimpleParser::parse_file(file_path) {
                 Ok(statements) => {
                     if self.debug {
@@ -87,9 +119,41 @@ impl CheckCommand {
                         ));
                     }
 
+                    self.check_sample_rates(
+                        file_path,
+                        &statements,
+                        config.sample_rate(),
+                        config.resample_quality(),
+                        &logger,
+                    );
+                    self.check_routing(file_path, &statements, &logger);
+
+                    if self.strict {
+                        total_errors += self.check_strict(file_path, &statements, &logger);
+                    }
+
                     // Report on rules (var_keyword, deprecated_syntax, etc.) if enabled
                     if let Some(ref reporter) = rules_reporter {
+                        self.check_implicit_triggers(
+                            file_path,
+                            &statements,
+                            config.banks.default.as_deref(),
+                            reporter,
+                        );
                         let content = std::fs::read_to_string(file_path)?;
+                        for stmt in unused_variables(&statements, &content) {
+                            let (StatementKind::Let { name, .. }
+                            | StatementKind::Var { name, .. }
+                            | StatementKind::Const { name, .. }) = &stmt.kind
+                            else {
+                                continue;
+                            };
+                            if let Some(rule_msg) =
+                                reporter.checker().check_unused_variable(stmt.line, name)
+                            {
+                                reporter.logger().log_rule_message(&rule_msg);
+                            }
+                        }
                         for (line_num, line) in content.lines().enumerate() {
                             let line_number = line_num + 1;
 
@@ -107,7 +171,19 @@ impl CheckCommand {
                     }
                 }
                 Err(e) => {
-                    logger.error(format!("✗ {} - {}", file_display, e));
+                    match e.downcast_ref::<LocatedParseError>() {
+                        Some(located) => logger.log_structured_error(
+                            &StructuredError::new(format!("✗ {} - {}", file_display, located))
+                                .with_file(file_display.to_string())
+                                .with_span(
+                                    located.line,
+                                    located.span.start_column,
+                                    located.span.end_column,
+                                )
+                                .with_type("SyntaxError"),
+                        ),
+                        None => logger.error(format!("✗ {} - {}", file_display, e)),
+                    }
                     total_errors += 1;
                 }
             }
@@ -132,10 +208,269 @@ impl CheckCommand {
 
         Ok(())
     }
+
+    /// Warn about bank samples whose rate is far from the project rate; they are resampled
+    /// at render time (or at build start with `audio.preconvert_samples`)
+    fn check_sample_rates(
+        &self,
+        path: &Path,
+        statements: &[Statement],
+        project_rate: u32,
+        quality: ResampleQuality,
+        logger: &Logger,
+    ) {
+        let Ok(project_root) = std::env::current_dir() else {
+            return;
+        };
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        let mut banks = BankRegistry::new();
+        let mut rates = Vec::new();
+
+        for statement in statements {
+            let StatementKind::Bank { name, alias } = &statement.kind else {
+                continue;
+            };
+            let alias = alias.clone().unwrap_or_else(|| name.clone());
+            // Unresolved banks are reported when the project is built
+            let Ok(bank) = banks.register_bank(alias.clone(), name, &project_root, base_dir) else {
+                continue;
+            };
+            for trigger in bank.list_triggers() {
+                if let Some(file) = bank.resolve_trigger(trigger)
+                    && let Ok(rate) = samples::file_sample_rate(&file)
+                {
+                    rates.push((format!("{}.{}", alias, trigger), rate));
+                }
+            }
+        }
+
+        let severe: Vec<RateConversion> = samples::rate_conversions(rates, project_rate, quality)
+            .into_iter()
+            .filter(RateConversion::is_severe)
+            .collect();
+        if severe.is_empty() {
+            return;
+        }
+        logger.warn(format!(
+            "{}: {} sample(s) far from the project rate ({} Hz); they will be resampled (set audio.preconvert_samples to do it at build start)",
+            path.display(),
+            severe.len(),
+            project_rate
+        ));
+        for conversion in &severe {
+            logger.warn(format!("  - {}", conversion.describe()));
+        }
+    }
+
+    /// Warn about routing inserts that can never carry signal (see `devalang graph`)
+    fn check_routing(&self, path: &Path, statements: &[Statement], logger: &Logger) {
+        for orphan in SignalFlow::from_statements(statements).orphans {
+            logger.warn(format!(
+                "{}:{}: {}",
+                path.display(),
+                orphan.line,
+                orphan.reason
+            ));
+        }
+    }
+
+    /// Report bank-less triggers (`.kick`) that more than one reachable bank provides,
+    /// naming the bank the resolution order picks
+    fn check_implicit_triggers(
+        &self,
+        path: &Path,
+        statements: &[Statement],
+        default_bank: Option<&str>,
+        reporter: &RulesReporter,
+    ) {
+        let Ok(project_root) = std::env::current_dir() else {
+            return;
+        };
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        let mut banks = BankRegistry::new();
+        banks.set_default_bank(default_bank.map(str::to_string));
+        if let Some(default) = default_bank {
+            // Missing banks are reported when the project is built
+            let _ = banks.register_bank(default, default, &project_root, &project_root);
+        }
+
+        let mut declared = Vec::new();
+        let mut triggers = Vec::new();
+        collect_bare_triggers(statements, &mut declared, &mut triggers);
+        for statement in statements {
+            if let StatementKind::Bank { name, alias } = &statement.kind {
+                let alias = alias
+                    .clone()
+                    .unwrap_or_else(|| name.split('.').next_back().unwrap_or(name).to_string());
+                let _ = banks.register_bank(alias, name, &project_root, base_dir);
+            }
+        }
+
+        let describe = |candidate: &TriggerCandidate| {
+            format!("{} ({})", candidate.identifier, candidate.origin.label())
+        };
+        for (line, trigger) in triggers {
+            if declared.contains(&trigger) {
+                continue;
+            }
+            let candidates = banks.implicit_candidates(&trigger);
+            let [chosen, shadowed @ ..] = candidates.as_slice() else {
+                continue;
+            };
+            if shadowed.is_empty() {
+                continue;
+            }
+            let shadowed: Vec<String> = shadowed.iter().map(describe).collect();
+            if let Some(message) = reporter.checker().check_ambiguous_trigger(
+                line,
+                &trigger,
+                &describe(chosen),
+                &shadowed,
+            ) {
+                reporter.logger().log_rule_message(&message);
+            }
+        }
+    }
+
+    /// Log the strict pass issues of one file and return how many there were
+    fn check_strict(&self, path: &Path, statements: &[Statement], logger: &Logger) -> usize {
+        let mut banks = ProjectBanks {
+            registry: BankRegistry::new(),
+            project_root: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            base_dir: path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+        };
+        let issues = strict::check_statements(statements, &mut banks);
+        for issue in &issues {
+            logger.log_structured_error(&issue.clone().with_file(path.display().to_string()));
+        }
+        issues.len()
+    }
+
+    /// Preview and apply the safe fixes for one file, keeping a `.bak` copy of the original
+    fn fix_file(&self, path: &Path, options: &fix::FixOptions, logger: &Logger) -> Result<()> {
+        let source = std::fs::read_to_string(path)?;
+        let result = fix::fix_source(&source, options);
+        if result.fixes.is_empty() {
+            return Ok(());
+        }
+
+        println!(
+            "{}",
+            fix::diff_preview(&path.display().to_string(), &result.fixes)
+        );
+
+        // Never write a rewrite that no longer parses
+        if let Err(e) = SimpleParser::parse(&result.source, path.to_path_buf()) {
+            logger.warn(format!(
+                "Skipping fixes for {}: fixed source does not parse ({})",
+                path.display(),
+                e
+            ));
+            return Ok(());
+        }
+
+        if self.dry_run {
+            logger.info(format!(
+                "{} fix(es) available for {} (dry run)",
+                result.fixes.len(),
+                path.display()
+            ));
+            return Ok(());
+        }
+
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        std::fs::copy(path, &backup)?;
+        std::fs::write(path, &result.source)?;
+        logger.success(format!(
+            "Applied {} fix(es) to {} (backup: {})",
+            result.fixes.len(),
+            path.display(),
+            Path::new(&backup).display()
+        ));
+        Ok(())
+    }
+}
+
+/// Banks resolved the way a build resolves them, for `--strict`
+struct ProjectBanks {
+    registry: BankRegistry,
+    project_root: PathBuf,
+    base_dir: PathBuf,
+}
+
+impl BankLookup for ProjectBanks {
+    fn register(&mut self, alias: &str, identifier: &str) -> Result<(), String> {
+        self.registry
+            .register_bank(alias, identifier, &self.project_root, &self.base_dir)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn triggers(&self, alias: &str) -> Option<Vec<String>> {
+        self.registry
+            .list_banks()
+            .into_iter()
+            .find(|(name, _)| name.as_str() == alias)
+            .map(|(_, bank)| bank.list_triggers().into_iter().cloned().collect())
+    }
+}
+
+/// Names bound anywhere in `statements`, and the triggers written without a bank
+/// (`.kick`) with their lines
+fn collect_bare_triggers(
+    statements: &[Statement],
+    declared: &mut Vec<String>,
+    triggers: &mut Vec<(usize, String)>,
+) {
+    for statement in statements {
+        let body = match &statement.kind {
+            StatementKind::Trigger { entity, .. } => {
+                let name = entity.trim_start_matches('.');
+                if !name.is_empty() && !name.contains('.') {
+                    triggers.push((statement.line, name.to_string()));
+                }
+                None
+            }
+            StatementKind::Let { name, .. }
+            | StatementKind::Var { name, .. }
+            | StatementKind::Const { name, .. }
+            | StatementKind::Param { name, .. }
+            | StatementKind::Macro { name, .. }
+            | StatementKind::Pattern { name, .. } => {
+                declared.push(name.clone());
+                None
+            }
+            StatementKind::Group { name, body } => {
+                declared.push(name.clone());
+                Some(body)
+            }
+            StatementKind::Function { body, .. }
+            | StatementKind::Loop { body, .. }
+            | StatementKind::For { body, .. }
+            | StatementKind::At { body, .. }
+            | StatementKind::On { body, .. }
+            | StatementKind::Tempo {
+                body: Some(body), ..
+            } => Some(body),
+            StatementKind::If {
+                body, else_body, ..
+            } => {
+                if let Some(else_body) = else_body {
+                    collect_bare_triggers(else_body, declared, triggers);
+                }
+                Some(body)
+            }
+            _ => None,
+        };
+        if let Some(body) = body {
+            collect_bare_triggers(body, declared, triggers);
+        }
+    }
 }
 
 /// Recursively find all .deva files in a directory
-fn find_deva_files(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+pub(crate) fn find_deva_files(dir: &Path) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
 
     if dir.is_dir() {
@@ -0,0 +1,60 @@
+use super::*;
+
+const ALL: FixOptions = FixOptions {
+    var_keyword: true,
+    deprecated_syntax: true,
+    explicit_durations: true,
+    unused_variables: true,
+};
+
+#[test]
+fn test_fix_source_rewrites_safe_cases() {
+    let source = "@import { kit } from \"./kit.deva\"\nvar speed = 2\nlet unused = 3\nlet _kept = 4\ngroup main:\n    .kit.kick\n    .kit.snare 1/2 -> reverse(true)\n    .kit.hat -> speed(speed)\n    .kit.clap auto\n";
+    let result = fix_source(source, &ALL);
+
+    assert_eq!(
+        result.source,
+        "import { kit } from \"./kit.deva\"\nlet speed = 2\nlet _kept = 4\ngroup main:\n    .kit.kick 1 beat\n    .kit.snare 1/2 -> reverse(true)\n    .kit.hat 1 beat -> speed(speed)\n    .kit.clap auto\n"
+    );
+    let rules: Vec<(usize, &str)> = result.fixes.iter().map(|f| (f.line, f.rule)).collect();
+    assert_eq!(
+        rules,
+        vec![
+            (1, "deprecated_syntax"),
+            (2, "var_keyword"),
+            (3, "unused_variables"),
+            (6, "explicit_durations"),
+            (8, "explicit_durations"),
+        ]
+    );
+
+    // The fixed source still parses
+    crate::language::syntax::parser::driver::parse(
+        &result.source,
+        std::path::PathBuf::from("fixed.deva"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_fix_source_keeps_multiline_and_disabled_rules() {
+    let source = "let pattern = {\n    steps: 4\n}\nvar x = 1\n";
+    let options = FixOptions {
+        var_keyword: false,
+        ..ALL
+    };
+    let result = fix_source(source, &options);
+    // `pattern` spans several lines; `x` is still removed as unused with the var fix off
+    assert_eq!(result.fixes.len(), 1);
+    assert_eq!(result.fixes[0].line, 4);
+    assert_eq!(result.source, "let pattern = {\n    steps: 4\n}\n");
+}
+
+#[test]
+fn test_diff_preview_lists_changed_lines() {
+    let result = fix_source("var a = 1\nprint a\n", &ALL);
+    assert_eq!(
+        diff_preview("song.deva", &result.fixes),
+        "--- song.deva\n+++ song.deva (fixed)\n@@ line 1 (var_keyword) @@\n-var a = 1\n+let a = 1\n"
+    );
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "cli")]
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+use crate::platform::config::AppConfig;
+use crate::tools::cli::state::CliContext;
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigAction {
+    /// Check devalang.toml / devalang.json against the schema and print the effective config
+    Validate {
+        /// Config file, or the project directory to look in (defaults to the current directory)
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+}
+
+impl ConfigCommand {
+    pub async fn execute(&self, ctx: &CliContext) -> Result<()> {
+        let logger = ctx.logger();
+
+        match &self.action {
+            ConfigAction::Validate { path } => {
+                let path = path.clone().unwrap_or(std::env::current_dir()?);
+                let (config, issues) = if path.is_dir() {
+                    match AppConfig::find_path(&path) {
+                        Some(file) => {
+                            logger.info(format!("Checking {}", file.display()));
+                            AppConfig::inspect(&file)?
+                        }
+                        None => {
+                            logger.info(format!(
+                                "No config file in {}, the defaults apply",
+                                path.display()
+                            ));
+                            (AppConfig::default(), Vec::new())
+                        }
+                    }
+                } else {
+                    AppConfig::inspect(&path)?
+                };
+
+                for issue in &issues {
+                    logger.error(issue.to_string());
+                }
+
+                let effective =
+                    serde_json::to_string_pretty(&config).context("serialize effective config")?;
+                logger.info("Effective config:");
+                println!("{}", effective);
+
+                if !issues.is_empty() {
+                    bail!(
+                        "{} problem(s) found; the settings above fall back to their defaults",
+                        issues.len()
+                    );
+                }
+                logger.success("Config is valid");
+            }
+        }
+
+        Ok(())
+    }
+}
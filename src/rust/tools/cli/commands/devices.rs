@@ -339,6 +339,37 @@ pub struct DevicesListCommand {}
 pub fn execute_list(_cmd: DevicesListCommand, ctx: &CliContext) -> Result<()> {
     let logger = ctx.logger();
 
+    #[cfg(feature = "cli")]
+    {
+        use crate::engine::audio::playback::live::list_output_devices;
+
+        match list_output_devices() {
+            Ok(devices) => {
+                logger.info("Audio outputs:");
+                for (i, device) in devices.iter().enumerate() {
+                    let mut details = Vec::new();
+                    if let Some(ch) = device.channels {
+                        details.push(format!("channels: {}", ch));
+                    }
+                    if let Some(sr) = device.sample_rate {
+                        details.push(format!("{} Hz", sr));
+                    }
+                    if let Some((min, max)) = device.buffer_range {
+                        details.push(format!("buffer: {}-{} frames", min, max));
+                    }
+                    logger.info(format!(
+                        "  [{}] {}{} ({})",
+                        i,
+                        device.name,
+                        if device.is_default { " [default]" } else { "" },
+                        details.join(", ")
+                    ));
+                }
+            }
+            Err(e) => logger.warn(format!("Unable to list audio outputs: {}", e)),
+        }
+    }
+
     #[cfg(feature = "cli")]
     {
         use crate::engine::audio::midi_native::MidiManager;
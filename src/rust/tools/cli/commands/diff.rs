@@ -0,0 +1,251 @@
+#![cfg(feature = "cli")]
+
+use anyhow::{Context, Result};
+use clap::Args;
+use hound::{SampleFormat, WavReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::engine::audio::diff::{AudioDiff, InsertAudio};
+use crate::engine::audio::mixer::MASTER_INSERT;
+use crate::engine::audio::settings::AudioFormat;
+use crate::platform::config::AppConfig;
+use crate::platform::storage::lock::LockPolicy;
+use crate::services::build::outputs::audio::snapshot;
+use crate::services::build::pipeline::{BuildRequest, ProjectBuilder};
+use crate::tools::cli::state::CliContext;
+use crate::tools::logger::Logger;
+
+/// Old and new inserts (master first) at their common sample rate
+type Renders = (Vec<InsertAudio>, Vec<InsertAudio>, u32);
+
+#[derive(Debug, Clone, Args)]
+pub struct DiffCommand {
+    /// Reference render (WAV). Without files, the project is built and compared
+    /// with the previous build (kept by every `devalang build` and `devalang diff`)
+    #[arg(requires = "new")]
+    pub old: Option<PathBuf>,
+
+    /// Render compared against the reference (WAV)
+    pub new: Option<PathBuf>,
+
+    /// Entry .deva file (or project directory) for build comparisons
+    #[arg(long, default_value = "./")]
+    pub path: String,
+
+    /// Largest RMS change per insert, in dB, still counted as neutral
+    #[arg(long, default_value_t = 0.1)]
+    pub threshold_db: f32,
+
+    /// Largest onset shift, in milliseconds, still counted as neutral
+    #[arg(long, default_value_t = 1.0)]
+    pub max_shift_ms: f32,
+
+    /// Write the full report (with the spectral heatmap) as JSON
+    #[arg(long)]
+    pub json: Option<PathBuf>,
+}
+
+impl DiffCommand {
+    pub async fn execute(&self, ctx: &CliContext) -> Result<()> {
+        let logger = ctx.logger();
+
+        let (old, new, sample_rate) = match (&self.old, &self.new) {
+            (Some(old_path), Some(new_path)) => {
+                logger.action(format!(
+                    "Comparing {} with {}...",
+                    old_path.display(),
+                    new_path.display()
+                ));
+                let (old, old_rate) = read_wav(old_path, MASTER_INSERT)?;
+                let (new, new_rate) = read_wav(new_path, MASTER_INSERT)?;
+                if old_rate != new_rate {
+                    anyhow::bail!(
+                        "Sample rates differ ({} Hz vs {} Hz); render both at the same rate",
+                        old_rate,
+                        new_rate
+                    );
+                }
+                (vec![old], vec![new], new_rate)
+            }
+            _ => match self.build_against_snapshot(&logger)? {
+                Some(renders) => renders,
+                None => return Ok(()),
+            },
+        };
+
+        let diff = AudioDiff::compare(&old, &new, sample_rate);
+        report(&diff, self.threshold_db, &logger);
+
+        if let Some(path) = &self.json {
+            std::fs::write(path, serde_json::to_string_pretty(&diff)?)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            logger.info(format!("Full report written to {}", path.display()));
+        }
+
+        if diff.is_neutral(self.threshold_db, self.max_shift_ms) {
+            logger.success("Renders are sonically neutral");
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Renders differ"))
+        }
+    }
+
+    /// Build the project (deterministically) and pair the diff snapshot it writes with
+    /// the one of the build before it
+    fn build_against_snapshot(&self, logger: &Arc<Logger>) -> Result<Option<Renders>> {
+        let current_dir = std::env::current_dir()?;
+        let config = AppConfig::load(&current_dir)?;
+
+        let entry_path = PathBuf::from(&self.path);
+        let entry_path = if entry_path.is_dir() {
+            entry_path.join("index.deva")
+        } else {
+            entry_path
+        };
+        if !entry_path.exists() {
+            anyhow::bail!("Entry file not found: {}", entry_path.display());
+        }
+
+        let output_root = current_dir.join(&config.paths.output);
+        let request = BuildRequest {
+            entry_path,
+            output_root: output_root.clone(),
+            audio_formats: vec![AudioFormat::Wav],
+            bit_depth: config.audio_bit_depth(),
+            channels: config.audio_channels(),
+            resample_quality: config.resample_quality(),
+            preconvert_samples: config.audio.preconvert_samples,
+            mix: config.mix_settings()?,
+            sample_rate: config.sample_rate(),
+            bpm: config.audio.bpm,
+            log_timeline: None,
+            outputs: Vec::new(),
+            // Random sources must not show up as differences
+            deterministic: true,
+            variable_overrides: Default::default(),
+            bank_remaps: Default::default(),
+            default_bank: config.banks.default.clone(),
+            tags: Default::default(),
+            auto_trim: false,
+            // Streamed builds keep no snapshot
+            stream: false,
+            solo_mute: Default::default(),
+            click: None,
+            limits: Default::default(),
+        };
+
+        let mut builder = ProjectBuilder::new(logger.clone());
+        // The comparison build overwrites the project's outputs; let a running build finish
+        if let Ok(deva) = crate::tools::cli::config::path::get_deva_dir() {
+            builder = builder.with_project_lock(deva, "devalang diff", LockPolicy::Wait(None));
+        }
+        let artifacts = builder.build(&request)?;
+
+        let current_path = snapshot::current_dir(&output_root, &artifacts.module_name);
+        let Some((current, sample_rate)) = snapshot::read(&current_path)? else {
+            anyhow::bail!("The build rendered nothing to compare");
+        };
+        match snapshot::read(&snapshot::previous_dir(
+            &output_root,
+            &artifacts.module_name,
+        ))? {
+            Some((previous, previous_rate)) if previous_rate == sample_rate => {
+                Ok(Some((previous, current, sample_rate)))
+            }
+            Some((_, previous_rate)) => {
+                logger.warn(format!(
+                    "Previous build was rendered at {} Hz; saved this build as the new baseline",
+                    previous_rate
+                ));
+                Ok(None)
+            }
+            None => {
+                logger.info(format!(
+                    "No previous build to compare with; saved this build to {}",
+                    current_path.display()
+                ));
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn report(diff: &AudioDiff, threshold_db: f32, logger: &Logger) {
+    logger.info("RMS per insert:");
+    for insert in &diff.inserts {
+        let level = |db: Option<f32>| db.map_or("-".to_string(), |db| format!("{:.2} dB", db));
+        let change = match insert.change_db() {
+            Some(db) => format!("{:+.2} dB", db),
+            None if insert.old_rms_db.is_none() => "added".to_string(),
+            None => "removed".to_string(),
+        };
+        logger.info(format!(
+            "  - {}: {} -> {} ({})",
+            insert.name,
+            level(insert.old_rms_db),
+            level(insert.new_rms_db),
+            change
+        ));
+    }
+
+    let heatmap = &diff.heatmap;
+    match heatmap.first_change(threshold_db) {
+        Some(seconds) => logger.info(format!(
+            "Spectrum: up to {:.1} dB per band, first change at {:.2}s ({} windows x {} bands)",
+            heatmap.max_abs_db(),
+            seconds,
+            heatmap.cells.len(),
+            heatmap.band_edges_hz.len().saturating_sub(1)
+        )),
+        None => logger.info("Spectrum: unchanged"),
+    }
+
+    let onsets = &diff.onsets;
+    logger.info(format!(
+        "Onsets: {} matched (max shift {:.2} ms), {} removed, {} added",
+        onsets.matched.len(),
+        onsets.max_shift_ms(),
+        onsets.removed.len(),
+        onsets.added.len()
+    ));
+    for shift in onsets.matched.iter().filter(|s| s.shift_ms().abs() >= 0.05) {
+        logger.info(format!(
+            "  - {:.3}s moved {:+.2} ms",
+            shift.old_seconds,
+            shift.shift_ms()
+        ));
+    }
+    for seconds in &onsets.removed {
+        logger.info(format!("  - {:.3}s removed", seconds));
+    }
+    for seconds in &onsets.added {
+        logger.info(format!("  - {:.3}s added", seconds));
+    }
+    if diff.length_change_seconds != 0.0 {
+        logger.info(format!(
+            "Length changed by {:+.3}s",
+            diff.length_change_seconds
+        ));
+    }
+}
+
+fn read_wav(path: &Path, name: &str) -> Result<(InsertAudio, u32)> {
+    let mut reader =
+        WavReader::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+    Ok((
+        InsertAudio::new(name, samples, spec.channels as usize),
+        spec.sample_rate,
+    ))
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "cli")]
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::engine::audio::graph::{DiagramFormat, SignalFlow};
+use crate::language::syntax::parser::driver::SimpleParser;
+use crate::tools::cli::state::CliContext;
+
+#[derive(Debug, Clone, Args)]
+pub struct GraphCommand {
+    /// Entry .deva file (or project directory)
+    #[arg(long, default_value = "./")]
+    pub path: String,
+
+    /// Diagram language
+    #[arg(long, value_enum, default_value_t = DiagramFormat::Dot)]
+    pub format: DiagramFormat,
+
+    /// Write the diagram to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+impl GraphCommand {
+    pub async fn execute(&self, ctx: &CliContext) -> Result<()> {
+        let logger = ctx.logger();
+
+        let entry_path = PathBuf::from(&self.path);
+        let entry_path = if entry_path.is_dir() {
+            entry_path.join("index.deva")
+        } else {
+            entry_path
+        };
+        if !entry_path.exists() {
+            anyhow::bail!("Entry file not found: {}", entry_path.display());
+        }
+
+        let statements = SimpleParser::parse_file(&entry_path)?;
+        let flow = SignalFlow::from_statements(&statements);
+        let diagram = flow.render(self.format);
+
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, &diagram)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                logger.success(format!("Routing graph written to {}", path.display()));
+            }
+            None => print!("{}", diagram),
+        }
+
+        for orphan in &flow.orphans {
+            let message = format!(
+                "{}:{}: {}",
+                entry_path.display(),
+                orphan.line,
+                orphan.reason
+            );
+            // The logger writes to stdout, which may be piped into `dot`
+            match self.output {
+                Some(_) => logger.warn(message),
+                None => eprintln!("warning: {}", message),
+            }
+        }
+        Ok(())
+    }
+}
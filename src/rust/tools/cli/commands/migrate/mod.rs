@@ -0,0 +1,195 @@
+#![cfg(feature = "cli")]
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::check::{find_deva_files, fix};
+use crate::language::syntax::parser::driver::SimpleParser;
+use crate::tools::cli::state::CliContext;
+use crate::tools::logger::Logger;
+
+pub mod rewrite;
+
+#[derive(Debug, Clone, Args)]
+pub struct MigrateCommand {
+    /// Entry file or directory to migrate
+    #[arg(short, long, default_value = ".")]
+    pub entry: String,
+
+    /// Only print the change report without writing anything
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// Also write the change report as JSON to this path
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+}
+
+/// Migration outcome of one file, for the report
+struct FileReport {
+    path: PathBuf,
+    result: rewrite::MigrationResult,
+    written: bool,
+}
+
+impl MigrateCommand {
+    pub async fn execute(&self, ctx: &CliContext) -> Result<()> {
+        let logger = ctx.logger();
+
+        let entry_path = Path::new(&self.entry);
+        if !entry_path.exists() {
+            logger.error(format!("Entry path '{}' does not exist", self.entry));
+            return Err(anyhow::anyhow!("Entry path not found"));
+        }
+        let files = if entry_path.is_file() {
+            vec![entry_path.to_path_buf()]
+        } else {
+            find_deva_files(entry_path)?
+        };
+        if files.is_empty() {
+            logger.warn("No .deva files found");
+            return Ok(());
+        }
+
+        logger.action(format!("Migrating {} file(s)...", files.len()));
+        let mut reports = Vec::new();
+        for path in files {
+            reports.push(self.migrate_file(path, &logger)?);
+        }
+
+        // Summary: how often each migration applied, then the lines left to edit by hand
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for change in reports.iter().flat_map(|r| &r.result.changes) {
+            *counts.entry(change.rule).or_default() += 1;
+        }
+        let changed = reports
+            .iter()
+            .filter(|r| !r.result.changes.is_empty())
+            .count();
+        if counts.is_empty() {
+            logger.success("Nothing to migrate, the project is up to date");
+        } else {
+            let summary: Vec<String> = counts
+                .iter()
+                .map(|(rule, count)| format!("{} x{}", rule, count))
+                .collect();
+            logger.success(format!(
+                "{} {} line(s) in {} file(s): {}",
+                if self.dry_run {
+                    "Would migrate"
+                } else {
+                    "Migrated"
+                },
+                counts.values().sum::<usize>(),
+                changed,
+                summary.join(", ")
+            ));
+        }
+        for report in &reports {
+            for edit in &report.result.manual {
+                logger.warn(format!(
+                    "{}:{} needs a manual edit ({}): {}",
+                    report.path.display(),
+                    edit.line,
+                    edit.migrations.join(", "),
+                    edit.text.trim()
+                ));
+            }
+        }
+
+        if let Some(report_path) = &self.report {
+            std::fs::write(
+                report_path,
+                serde_json::to_string_pretty(&report_json(&reports))?,
+            )?;
+            logger.info(format!(
+                "Change report written to {}",
+                report_path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Migrate one file in place, keeping a `.bak` copy of the original
+    fn migrate_file(&self, path: PathBuf, logger: &Logger) -> Result<FileReport> {
+        let source = std::fs::read_to_string(&path)?;
+        let result = match rewrite::migrate_source(&source, &path) {
+            Ok(result) => result,
+            Err(e) => {
+                logger.warn(format!("Skipping {}: {}", path.display(), e));
+                rewrite::MigrationResult {
+                    source: source.clone(),
+                    changes: Vec::new(),
+                    manual: Vec::new(),
+                }
+            }
+        };
+        let mut report = FileReport {
+            path,
+            result,
+            written: false,
+        };
+        if report.result.changes.is_empty() {
+            return Ok(report);
+        }
+
+        println!(
+            "{}",
+            fix::diff_preview(&report.path.display().to_string(), &report.result.changes)
+        );
+        if self.dry_run {
+            return Ok(report);
+        }
+
+        // Never break a file that parsed before; legacy sources usually do not parse until
+        // their manual edits are done, so those are still written
+        if let Err(e) = SimpleParser::parse(&report.result.source, report.path.clone())
+            && SimpleParser::parse(&source, report.path.clone()).is_ok()
+        {
+            logger.warn(format!(
+                "Skipping {}: migrated source does not parse ({})",
+                report.path.display(),
+                e
+            ));
+            return Ok(report);
+        }
+
+        let mut backup = report.path.as_os_str().to_owned();
+        backup.push(".bak");
+        std::fs::copy(&report.path, &backup)?;
+        std::fs::write(&report.path, &report.result.source)?;
+        report.written = true;
+        Ok(report)
+    }
+}
+
+fn report_json(reports: &[FileReport]) -> serde_json::Value {
+    let files: Vec<serde_json::Value> = reports
+        .iter()
+        .filter(|r| !r.result.changes.is_empty() || !r.result.manual.is_empty())
+        .map(|r| {
+            json!({
+                "path": r.path.display().to_string(),
+                "written": r.written,
+                "changes": r.result.changes.iter().map(|c| json!({
+                    "line": c.line,
+                    "migration": c.rule,
+                    "before": c.before,
+                    "after": c.after,
+                })).collect::<Vec<_>>(),
+                "manual": r.result.manual.iter().map(|m| json!({
+                    "line": m.line,
+                    "migrations": m.migrations,
+                    "text": m.text,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "files": files,
+    })
+}
@@ -0,0 +1,196 @@
+//! Migrations for `devalang migrate`
+//!
+//! The source is read with `parse_legacy`, which accepts the spellings older language
+//! versions used, the tree is upgraded, and the file is written back with the printer:
+//! - `deprecated_syntax`: `@import` / `@export` / `@use` / `@load` lose their `@`
+//! - `var_keyword`: `var x = ...` becomes `let x = ...`
+//! - `trigger_keyword`: `trigger .kit.kick 1/4` becomes `.kit.kick 1/4`
+//! - `effect_map`: effect maps become chained calls, so `.kit.kick 1/4 { reverb: 0.5 }`
+//!   becomes `.kit.kick 1/4 -> reverb(0.5)` and `synth saw { attack: 0.1, effects: { delay: 300 } }`
+//!   becomes `synth saw { attack: 0.1 } -> delay(300)`
+//!
+//! A migrated file is written in the printer's canonical form. Lines the legacy reader
+//! could not read are copied as they were and reported as needing a manual edit.
+
+use super::super::check::fix::LineFix;
+use crate::language::syntax::ast::{Statement, StatementKind, Value};
+use crate::language::syntax::parser::driver::legacy::{LegacySpelling, parse_legacy};
+use crate::language::syntax::parser::driver::preprocessing;
+use crate::language::syntax::printer::print_source;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A line a migration matched but could not rewrite safely
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManualEdit {
+    /// 1-based line number, counting a multi-line statement as one line like the parser
+    pub line: usize,
+    pub migrations: Vec<&'static str>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationResult {
+    pub source: String,
+    pub changes: Vec<LineFix>,
+    pub manual: Vec<ManualEdit>,
+}
+
+/// Apply every migration to `source`, read from `path`; fails when the file does not
+/// parse for reasons other than legacy syntax
+pub fn migrate_source(source: &str, path: &Path) -> Result<MigrationResult> {
+    let mut parsed = parse_legacy(source, path.to_path_buf())?;
+    if parsed.spellings.is_empty() {
+        return Ok(MigrationResult {
+            source: source.to_string(),
+            changes: Vec::new(),
+            manual: Vec::new(),
+        });
+    }
+    upgrade(&mut parsed.statements);
+
+    // Line numbers count lines after multi-line statements are joined, like the parser's
+    let joined = preprocessing::preprocess_multiline_arrow_calls(
+        &preprocessing::preprocess_multiline_braces(source),
+    );
+    let lines: Vec<&str> = joined.lines().collect();
+    let original = |line: usize| lines.get(line - 1).copied().unwrap_or_default().to_string();
+
+    let mut changes = Vec::new();
+    let mut manual = Vec::new();
+    for LegacySpelling {
+        line,
+        migrations,
+        error,
+    } in parsed.spellings
+    {
+        if error.is_some() {
+            manual.push(ManualEdit {
+                line,
+                migrations,
+                text: original(line),
+            });
+            continue;
+        }
+        let Some(statement) = find_statement(&parsed.statements, line) else {
+            continue;
+        };
+        let before = original(line);
+        let indent = &before[..before.len() - before.trim_start().len()];
+        let printed = print_source(std::slice::from_ref(statement), "", path);
+        changes.push(LineFix {
+            line,
+            // Report the last migration applied, like `check --fix`
+            rule: migrations[migrations.len() - 1],
+            after: Some(format!(
+                "{}{}",
+                indent,
+                printed.lines().next().unwrap_or("")
+            )),
+            before,
+        });
+    }
+
+    Ok(MigrationResult {
+        source: print_source(&parsed.statements, source, path),
+        changes,
+        manual,
+    })
+}
+
+/// Rewrite the legacy statements `parse_legacy` read into their current form
+fn upgrade(statements: &mut [Statement]) {
+    for statement in statements {
+        if let StatementKind::Var { name, value } = &mut statement.kind {
+            let (name, value) = (std::mem::take(name), value.take());
+            statement.kind = StatementKind::Let { name, value };
+        }
+        match &mut statement.kind {
+            StatementKind::Let {
+                value: Some(Value::Map(synth)),
+                ..
+            }
+            | StatementKind::Const {
+                value: Some(Value::Map(synth)),
+                ..
+            } => chain_synth_effects(synth),
+            StatementKind::If {
+                body, else_body, ..
+            } => {
+                upgrade(body);
+                if let Some(else_body) = else_body {
+                    upgrade(else_body);
+                }
+            }
+            StatementKind::Tempo {
+                body: Some(body), ..
+            }
+            | StatementKind::Function { body, .. }
+            | StatementKind::Group { body, .. }
+            | StatementKind::Loop { body, .. }
+            | StatementKind::For { body, .. }
+            | StatementKind::At { body, .. }
+            | StatementKind::Routing { body }
+            | StatementKind::On { body, .. } => upgrade(body),
+            _ => {}
+        }
+    }
+}
+
+/// Move a synth's `effects` map to the end of its chain, one `{ type, ...params }` entry
+/// per effect as the parser builds them from `-> effect(params)`
+fn chain_synth_effects(synth: &mut HashMap<String, Value>) {
+    let Some(Value::Map(effects)) = synth.remove("effects") else {
+        return;
+    };
+    let mut effects: Vec<(String, Value)> = effects.into_iter().collect();
+    effects.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut chain = match synth.remove("chain") {
+        Some(Value::Array(chain)) => chain,
+        _ => Vec::new(),
+    };
+    for (name, params) in effects {
+        let mut entry = match params {
+            Value::Map(params) => params,
+            value => HashMap::from([("value".to_string(), value)]),
+        };
+        entry.insert("type".to_string(), Value::String(name));
+        chain.push(Value::Map(entry));
+    }
+    synth.insert("chain".to_string(), Value::Array(chain));
+}
+
+/// The statement read from `line`, searching block bodies
+fn find_statement(statements: &[Statement], line: usize) -> Option<&Statement> {
+    statements.iter().find_map(|statement| {
+        if statement.line == line {
+            return Some(statement);
+        }
+        match &statement.kind {
+            StatementKind::If {
+                body, else_body, ..
+            } => find_statement(body, line).or_else(|| {
+                else_body
+                    .as_deref()
+                    .and_then(|body| find_statement(body, line))
+            }),
+            StatementKind::Tempo {
+                body: Some(body), ..
+            }
+            | StatementKind::Function { body, .. }
+            | StatementKind::Group { body, .. }
+            | StatementKind::Loop { body, .. }
+            | StatementKind::For { body, .. }
+            | StatementKind::At { body, .. }
+            | StatementKind::Routing { body }
+            | StatementKind::On { body, .. } => find_statement(body, line),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+#[path = "test_rewrite.rs"]
+mod tests;
@@ -0,0 +1,72 @@
+use super::*;
+use crate::language::syntax::parser::driver::SimpleParser;
+use std::path::PathBuf;
+
+fn migrate(source: &str) -> MigrationResult {
+    migrate_source(source, Path::new("song.deva")).unwrap()
+}
+
+#[test]
+fn test_migrate_source_rewrites_legacy_syntax() {
+    let source = "# drums\n@import { kit } from \"./kit.deva\"\nvar speed = 2\nlet lead = synth saw { attack: 0.1, effects: { reverb: { size: 0.5 }, delay: 300 } }\ngroup main:\n    trigger .kit.kick 1/4\n    .kit.snare 1/2 { reverb: 0.4, gain: 0.8 }\n    .kit.hat 1/4 -> reverse(true)\n";
+    let result = migrate(source);
+
+    assert_eq!(
+        result.source,
+        "# drums\nimport { kit } from \"./kit.deva\"\nlet speed = 2\nlet lead = synth saw { attack: 0.1 } -> delay(300) -> reverb({ size: 0.5 })\ngroup main:\n    .kit.kick 1/4\n    .kit.snare 1/2 -> gain(0.8) -> reverb(0.4)\n    .kit.hat 1/4 -> reverse(true)\n"
+    );
+    let rules: Vec<(usize, &str)> = result.changes.iter().map(|c| (c.line, c.rule)).collect();
+    assert_eq!(
+        rules,
+        vec![
+            (2, "deprecated_syntax"),
+            (3, "var_keyword"),
+            (4, "effect_map"),
+            (6, "trigger_keyword"),
+            (7, "effect_map"),
+        ]
+    );
+    assert_eq!(
+        result.changes[3].after.as_deref(),
+        Some("    .kit.kick 1/4")
+    );
+    assert!(result.manual.is_empty());
+    // The migrated file parses with the current parser
+    assert!(SimpleParser::parse(&result.source, PathBuf::from("song.deva")).is_ok());
+}
+
+#[test]
+fn test_migrations_compose_and_unreadable_lines_are_reported() {
+    let result = migrate("trigger .kit.kick 1/4 { reverb: 0.4 }\n.kit.snare 1/2 { bad-key: 2 }\n");
+    assert_eq!(result.changes.len(), 1);
+    assert_eq!(
+        result.changes[0].after.as_deref(),
+        Some(".kit.kick 1/4 -> reverb(0.4)")
+    );
+    assert_eq!(result.changes[0].rule, "effect_map");
+    // Keys must be plain effect names; the line is kept for a manual edit
+    assert_eq!(
+        result.manual,
+        vec![ManualEdit {
+            line: 2,
+            migrations: vec!["effect_map"],
+            text: ".kit.snare 1/2 { bad-key: 2 }".to_string(),
+        }]
+    );
+    assert!(result.source.ends_with(".kit.snare 1/2 { bad-key: 2 }\n"));
+
+    let result = migrate("trigger .kit.kick 1/4 bogus");
+    assert!(result.changes.is_empty());
+    assert_eq!(result.manual[0].migrations, vec!["trigger_keyword"]);
+}
+
+#[test]
+fn test_current_sources_are_left_untouched() {
+    let source = "tempo 120\n.kit.kick 1/4 -> reverb(0.3)\n";
+    let result = migrate(source);
+    assert_eq!(result.source, source);
+    assert!(result.changes.is_empty() && result.manual.is_empty());
+
+    // Parse errors unrelated to legacy syntax are not migrations
+    assert!(migrate_source(".kit.kick 1/4 ???", Path::new("song.deva")).is_err());
+}
@@ -4,6 +4,13 @@ pub mod addon;
 pub mod auth;
 pub mod build;
 pub mod check;
+pub mod config;
 pub mod devices;
+pub mod diff;
+pub mod graph;
 pub mod init;
+pub mod migrate;
 pub mod play;
+pub mod plugin;
+pub mod samples;
+pub mod serve;
@@ -1,17 +1,33 @@
 #![cfg(feature = "cli")]
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Args;
 
-use crate::engine::audio::settings::{AudioBitDepth, AudioChannels, AudioFormat, ResampleQuality};
+use crate::engine::audio::macros::{MacroCc, MacroKey};
+use crate::engine::audio::playback::keys::{KeyBinding, Quantizer};
+use crate::engine::audio::playback::live::OutputDeviceConfig;
+use crate::engine::audio::playback::recording::SessionRecording;
+use crate::engine::audio::playback::speed::PreviewRate;
+use crate::engine::audio::settings::{
+    AudioBitDepth, AudioChannels, AudioFormat, ClickMode, ResampleQuality,
+};
+use crate::engine::audio::solo::SoloMute;
+use crate::language::syntax::ast::Value;
 use crate::platform::config::AppConfig;
+use crate::platform::storage::lock::LockPolicy;
 use crate::services::build::pipeline::{BuildRequest, ProjectBuilder};
+use crate::services::live::play::keyboard::LiveKeysRequest;
+use crate::services::live::play::macros::LiveMacrosRequest;
 use crate::services::live::play::{LivePlayRequest, LivePlayService};
+use crate::tools::cli::config::{path, pins};
 use crate::tools::cli::rules_reporter::RulesReporter;
 use crate::tools::cli::state::CliContext;
+use crate::tools::logger::Logger;
 
 #[derive(Debug, Clone, Args)]
 pub struct PlayCommand {
@@ -43,7 +59,7 @@ pub struct PlayCommand {
     #[arg(long = "resample-quality", value_enum)]
     pub resample_quality: Option<ResampleQuality>,
 
-    /// Enable live mode with watch + crossfade
+    /// Enable live mode with watch + crossfade (Space taps the tempo, arrow keys nudge it)
     #[arg(long)]
     pub live: bool,
 
@@ -51,6 +67,14 @@ pub struct PlayCommand {
     #[arg(long = "crossfade-ms")]
     pub crossfade_ms: Option<u64>,
 
+    /// Play back faster or slower without re-rendering, e.g. `1.5x` or `75%`
+    #[arg(long = "preview-rate", value_parser = PreviewRate::parse_rate)]
+    pub preview_rate: Option<f32>,
+
+    /// Keep the original pitch when previewing at another rate
+    #[arg(long = "preserve-pitch", requires = "preview_rate")]
+    pub preserve_pitch: bool,
+
     /// Mute the audio output
     #[arg(long)]
     pub quiet: bool,
@@ -62,12 +86,181 @@ pub struct PlayCommand {
     /// Disable rule checking during playback
     #[arg(long, default_value_t = false)]
     pub no_rule: bool,
+
+    /// Audio output device name (see `devalang devices list`)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Output buffer size in frames (must be within the range `devalang devices list` shows)
+    #[arg(long = "buffer-size")]
+    pub buffer_size: Option<u32>,
+
+    /// Open the output device with this many channels, for `route ... -> outputs` stems
+    #[arg(long = "output-channels")]
+    pub output_channels: Option<u16>,
+
+    /// Bind a computer key to a bank trigger in live mode (repeatable), e.g. `--key a=.kit.kick`
+    #[arg(long = "key", value_name = "KEY=TRIGGER", requires = "live", value_parser = KeyBinding::parse)]
+    pub keys: Vec<KeyBinding>,
+
+    /// Grid keypresses are quantized to
+    #[arg(long, default_value = "1/16", value_parser = parse_grid)]
+    pub quantize: String,
+
+    /// Write the keys played in live mode to this file as `at beat` blocks
+    #[arg(long = "record-take", value_name = "FILE", requires = "keys")]
+    pub record_take: Option<PathBuf>,
+
+    /// Move a `macro` with a computer key in live mode (repeatable), e.g.
+    /// `--macro-key ]=intensity+0.1` or `--macro-key 0=intensity@0`
+    #[arg(long = "macro-key", value_name = "KEY=MACRO+STEP", requires = "live", value_parser = MacroKey::parse)]
+    pub macro_keys: Vec<MacroKey>,
+
+    /// Drive a `macro` from a MIDI control change in live mode (repeatable), e.g. `--macro-cc 74=intensity`
+    #[arg(long = "macro-cc", value_name = "CC=MACRO", requires = "live", value_parser = MacroCc::parse)]
+    pub macro_ccs: Vec<MacroCc>,
+
+    /// Receive `/deva/macro/<name> <value>` OSC messages on this UDP port in live mode;
+    /// the port is open on every network interface
+    #[arg(long = "macro-osc-port", value_name = "PORT", requires = "live")]
+    pub macro_osc_port: Option<u16>,
+
+    /// Override a top-level variable before interpretation (repeatable), e.g. `--set bpm=140`
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_override)]
+    pub set: Vec<(String, Value)>,
+
+    /// Load another bank in place of an alias or bank (repeatable), e.g. `--remap kit=devaloop.909`
+    #[arg(long = "remap", value_name = "ALIAS=BANK", value_parser = parse_remap)]
+    pub remap: Vec<(String, String)>,
+
+    /// Play only this group and the other soloed ones (repeatable). In live mode the
+    /// set is kept for the next session
+    #[arg(long = "solo", value_name = "GROUP")]
+    pub solo: Vec<String>,
+
+    /// Silence this group (repeatable)
+    #[arg(long = "mute", value_name = "GROUP")]
+    pub mute: Vec<String>,
+
+    /// Add a metronome following the tempo and meter map; `monitor` (default) is only
+    /// heard while playing, `mix` and `stem` also reach the written audio
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "monitor")]
+    pub click: Option<ClickMode>,
+
+    /// Record the live session's master output with a marker at every rebuild and scene
+    /// switch (defaults to `<output>/sessions`)
+    #[arg(long = "record-session", value_name = "DIR", num_args = 0..=1, requires = "live")]
+    pub record_session: Option<Option<PathBuf>>,
+
+    /// Minutes per session recording file before a new one is started
+    #[arg(
+        long = "record-segment",
+        value_name = "MINUTES",
+        default_value_t = 10.0,
+        requires = "record_session"
+    )]
+    pub record_segment: f32,
+
+    /// Start playback at a `mark` (e.g. `--start-at chorus`) or a time in seconds
+    #[arg(long = "start-at", value_name = "MARK|SECONDS")]
+    pub start_at: Option<String>,
+
+    /// Rebuild in the background while the loop keeps playing and swap the new render in
+    /// at the next bar with a crossfade
+    #[arg(long, requires = "live")]
+    pub prerender: bool,
+
+    /// Forget the solo/mute set saved by the last live session
+    #[arg(long = "clear-solo", requires = "live")]
+    pub clear_solo: bool,
+
+    /// When another build holds the project lock, wait for it (at most SECONDS when given)
+    #[arg(long = "wait-lock", value_name = "SECONDS", num_args = 0..=1)]
+    pub wait_lock: Option<Option<u64>>,
+
+    /// Take the project lock over from a stuck build instead of waiting
+    #[arg(long = "steal-lock", conflicts_with = "wait_lock")]
+    pub steal_lock: bool,
+}
+
+/// Live session file holding the last `--solo`/`--mute` set, inside `.deva`
+const SOLO_MUTE_SESSION: &str = "solo_mute.json";
+
+fn parse_grid(raw: &str) -> Result<String> {
+    Quantizer::parse_grid(raw)?;
+    Ok(raw.trim().to_string())
+}
+
+/// Parse `key=value`; numbers and booleans keep their type, anything else is a string
+fn parse_override(raw: &str) -> Result<(String, Value)> {
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected KEY=VALUE, got '{}'", raw))?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(anyhow!("missing variable name in '{}'", raw));
+    }
+    let value = value.trim();
+    let value = if let Ok(number) = value.parse::<f32>() {
+        Value::Number(number)
+    } else if let Ok(flag) = value.parse::<bool>() {
+        Value::Boolean(flag)
+    } else {
+        Value::String(value.trim_matches('"').to_string())
+    };
+    Ok((key.to_string(), value))
+}
+
+/// Parse `alias=publisher.bank`
+fn parse_remap(raw: &str) -> Result<(String, String)> {
+    let (from, to) = raw
+        .split_once('=')
+        .map(|(from, to)| (from.trim(), to.trim()))
+        .ok_or_else(|| anyhow!("expected ALIAS=BANK, got '{}'", raw))?;
+    if from.is_empty() || to.is_empty() {
+        return Err(anyhow!("expected ALIAS=BANK, got '{}'", raw));
+    }
+    Ok((from.to_string(), to.to_string()))
+}
+
+/// Solo/mute set for this run. Live sessions save the flags they were given and
+/// reuse the saved set when started without any.
+fn session_solo_mute(command: &PlayCommand, logger: &Logger) -> SoloMute {
+    let flags = SoloMute::from_names(&command.solo, &command.mute);
+    if !command.live {
+        return flags;
+    }
+    let Ok(deva) = path::ensure_deva_dir() else {
+        return flags;
+    };
+    let session = deva.join(SOLO_MUTE_SESSION);
+    if command.clear_solo {
+        let _ = fs::remove_file(&session);
+    }
+    if !flags.is_empty() {
+        if let Err(e) = flags.save(&session) {
+            logger.warn(format!("Failed to save the solo/mute set: {}", e));
+        }
+        return flags;
+    }
+    if command.clear_solo {
+        return flags;
+    }
+    let saved = SoloMute::load(&session).unwrap_or_default();
+    if !saved.is_empty() {
+        logger.info(format!(
+            "Restoring solo {:?} and mute {:?} from the last session (--clear-solo to reset)",
+            saved.solo, saved.mute
+        ));
+    }
+    saved
 }
 
 pub async fn execute(command: PlayCommand, ctx: &CliContext) -> Result<()> {
     let logger = ctx.logger();
     let cwd = std::env::current_dir()?;
     let config = AppConfig::load(&cwd)?;
+    pins::enforce_pins(&config.addons)?;
 
     // Initialize rules reporter (only if rules not disabled)
     let rules_reporter = if !command.no_rule {
@@ -109,6 +302,19 @@ pub async fn execute(command: PlayCommand, ctx: &CliContext) -> Result<()> {
         .unwrap_or_else(|| config.crossfade_ms());
     let live_mode = command.live;
 
+    // Convert bank samples to the project rate and generate their variants ahead of
+    // the first render; the outcome is logged once the workers are done
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use crate::tools::cli::commands::samples;
+        let preparation =
+            samples::start_bank_preparation(&config, sample_rate, resample_quality, &logger);
+        if !preparation.is_empty() {
+            let logger = logger.clone();
+            std::thread::spawn(move || samples::finish_bank_preparation(preparation, &logger));
+        }
+    }
+
     // Check for rule violations in entry file before playing (if enabled)
     if let Some(ref reporter) = rules_reporter {
         if let Ok(content) = fs::read_to_string(&entry_path) {
@@ -138,6 +344,12 @@ pub async fn execute(command: PlayCommand, ctx: &CliContext) -> Result<()> {
         resample_quality
     ));
 
+    for (from, bank) in &command.remap {
+        logger.info(format!("Remapping bank {} -> {}", from, bank));
+    }
+
+    let solo_mute = session_solo_mute(&command, &logger);
+
     fs::create_dir_all(&output_root)?;
 
     let build_request = BuildRequest {
@@ -147,12 +359,45 @@ pub async fn execute(command: PlayCommand, ctx: &CliContext) -> Result<()> {
         bit_depth,
         channels,
         resample_quality,
+        preconvert_samples: config.audio.preconvert_samples,
+        mix: config.mix_settings()?,
         sample_rate,
         bpm: config.audio.bpm,
+        log_timeline: None,
+        outputs: Vec::new(),
+        deterministic: false,
+        variable_overrides: command.set.iter().cloned().collect::<HashMap<_, _>>(),
+        bank_remaps: command.remap.iter().cloned().collect::<HashMap<_, _>>(),
+        default_bank: config.banks.default.clone(),
+        tags: config.audio.tags.clone(),
+        auto_trim: config.audio.auto_trim,
+        stream: config.audio.stream,
+        solo_mute,
+        click: command.click,
+        limits: Default::default(),
     };
 
-    let builder = ProjectBuilder::new(logger.clone());
-    let service = LivePlayService::new(logger.clone(), builder)?;
+    let mut builder = ProjectBuilder::new(logger.clone());
+    if let Ok(deva) = path::get_deva_dir() {
+        builder = builder.with_project_lock(
+            deva,
+            "devalang play",
+            LockPolicy::from_flags(command.wait_lock, command.steal_lock),
+        );
+    }
+    if live_mode {
+        // Rebuilds on save only re-render the groups that changed
+        builder = builder.with_insert_cache();
+    }
+    let output = OutputDeviceConfig {
+        device: command
+            .device
+            .clone()
+            .or_else(|| config.live.device.clone()),
+        buffer_size: command.buffer_size.or(config.live.buffer_size),
+        channels: command.output_channels.or(config.live.output_channels),
+    };
+    let service = LivePlayService::new(logger.clone(), builder, output)?;
 
     let volume = if command.quiet {
         0.0
@@ -170,6 +415,28 @@ pub async fn execute(command: PlayCommand, ctx: &CliContext) -> Result<()> {
         live_mode,
         crossfade_ms,
         volume,
+        osc: config.osc_settings(),
+        preview: command.preview_rate.map(|rate| {
+            PreviewRate::new(rate, command.preserve_pitch).with_quality(resample_quality)
+        }),
+        keys: (!command.keys.is_empty()).then(|| LiveKeysRequest {
+            bindings: command.keys.clone(),
+            grid_beats: Quantizer::parse_grid(&command.quantize).unwrap_or(0.25),
+            grid: command.quantize.clone(),
+            record_take: command.record_take.clone(),
+        }),
+        macros: Some(LiveMacrosRequest {
+            keys: command.macro_keys.clone(),
+            ccs: command.macro_ccs.clone(),
+            osc_port: command.macro_osc_port,
+        })
+        .filter(|macros| !macros.is_empty()),
+        record: command.record_session.clone().map(|dir| SessionRecording {
+            dir: dir.unwrap_or_else(|| output_root.join("sessions")),
+            segment: Duration::from_secs_f32(command.record_segment.max(0.1) * 60.0),
+        }),
+        start_at: command.start_at.clone(),
+        prerender: command.prerender,
     };
 
     service.run(request).await
@@ -0,0 +1,74 @@
+#![cfg(feature = "cli")]
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::manifest::{PluginManifest, WASM_TARGET};
+use crate::tools::cli::config::path::{DEVA_DIR, find_project_root_from};
+
+/// Run `cargo build --target wasm32-unknown-unknown` in the plugin crate
+pub fn compile(dir: &Path, manifest: &PluginManifest, release: bool) -> Result<PathBuf> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("build")
+        .arg("--lib")
+        .arg("--target")
+        .arg(WASM_TARGET)
+        .current_dir(dir);
+    if release {
+        cmd.arg("--release");
+    }
+
+    let status = cmd
+        .status()
+        .context("Failed to run cargo (is the Rust toolchain installed?)")?;
+    if !status.success() {
+        anyhow::bail!(
+            "cargo build failed; make sure the target is installed: rustup target add {}",
+            WASM_TARGET
+        );
+    }
+
+    let wasm_path = manifest.wasm_path(dir, release);
+    if !wasm_path.exists() {
+        anyhow::bail!("Expected WASM output not found: {}", wasm_path.display());
+    }
+    Ok(wasm_path)
+}
+
+/// Copy the module and `plugin.toml` to `.deva/plugins/<publisher>/<name>/`,
+/// where `use <publisher>.<name>` looks them up
+pub fn install(
+    dir: &Path,
+    manifest: &PluginManifest,
+    wasm_path: &Path,
+    project: Option<&Path>,
+) -> Result<PathBuf> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let root = match project {
+        Some(project) => project.to_path_buf(),
+        None => find_project_root_from(&dir).ok_or_else(|| {
+            anyhow::anyhow!(
+                "No Devalang project found above '{}'; pass --project <path>",
+                dir.display()
+            )
+        })?,
+    };
+
+    let target = root
+        .join(DEVA_DIR)
+        .join("plugins")
+        .join(&manifest.publisher)
+        .join(&manifest.name);
+    fs::create_dir_all(&target)
+        .with_context(|| format!("Failed to create '{}'", target.display()))?;
+
+    fs::copy(wasm_path, target.join(format!("{}.wasm", manifest.name)))?;
+    fs::copy(dir.join("plugin.toml"), target.join("plugin.toml"))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+#[path = "test_build.rs"]
+mod tests;
@@ -0,0 +1,105 @@
+#![cfg(feature = "cli")]
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+pub const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+/// What the plugin commands need from a plugin crate's `Cargo.toml` and `plugin.toml`
+#[derive(Debug, Clone)]
+pub struct PluginManifest {
+    pub crate_name: String,
+    pub name: String,
+    pub publisher: String,
+    pub exports: Vec<(String, String)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoToml {
+    package: CargoPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoPackage {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginToml {
+    plugin: PluginSection,
+    #[serde(default)]
+    exports: Vec<ExportEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginSection {
+    name: String,
+    #[serde(default)]
+    publisher: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportEntry {
+    name: String,
+    kind: String,
+}
+
+impl PluginManifest {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let cargo_path = dir.join("Cargo.toml");
+        let cargo: CargoToml = toml::from_str(
+            &std::fs::read_to_string(&cargo_path)
+                .with_context(|| format!("No plugin crate at '{}'", dir.display()))?,
+        )
+        .with_context(|| format!("Failed to parse '{}'", cargo_path.display()))?;
+
+        let plugin_path = dir.join("plugin.toml");
+        let plugin: PluginToml = toml::from_str(
+            &std::fs::read_to_string(&plugin_path)
+                .with_context(|| format!("Missing '{}'", plugin_path.display()))?,
+        )
+        .with_context(|| format!("Failed to parse '{}'", plugin_path.display()))?;
+
+        Ok(Self {
+            crate_name: cargo.package.name,
+            name: plugin.plugin.name,
+            publisher: plugin.plugin.publisher.unwrap_or_else(|| "local".into()),
+            exports: plugin
+                .exports
+                .into_iter()
+                .map(|e| (e.name, e.kind))
+                .collect(),
+        })
+    }
+
+    /// Path of the compiled module inside the crate's target directory
+    pub fn wasm_path(&self, dir: &Path, release: bool) -> PathBuf {
+        dir.join("target")
+            .join(WASM_TARGET)
+            .join(if release { "release" } else { "debug" })
+            .join(format!("{}.wasm", self.crate_name.replace('-', "_")))
+    }
+
+    /// Exports the host renders as notes
+    pub fn synth_exports(&self) -> Vec<&str> {
+        self.exports
+            .iter()
+            .filter(|(_, kind)| kind == "synth")
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Exports the host runs as chain or insert effects
+    pub fn effect_exports(&self) -> Vec<&str> {
+        self.exports
+            .iter()
+            .filter(|(_, kind)| kind == "effect")
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "test_manifest.rs"]
+mod tests;
@@ -0,0 +1,132 @@
+#![cfg(feature = "cli")]
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+use crate::tools::cli::state::CliContext;
+
+mod build;
+mod manifest;
+mod scaffold;
+mod smoke;
+
+#[derive(Debug, Clone, Args)]
+pub struct PluginCommand {
+    #[command(subcommand)]
+    pub action: PluginAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum PluginAction {
+    /// Scaffold a new plugin crate using the devalang bindings
+    New {
+        /// Plugin name (also the crate and directory name)
+        name: String,
+        /// Publisher used for the install path (`.deva/plugins/<publisher>/<name>`)
+        #[arg(short, long, default_value = "local")]
+        publisher: String,
+        /// Parent directory for the new crate (defaults to the current directory)
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+    /// Compile the plugin to WASM and install it into the project's .deva directory
+    Build {
+        /// Plugin crate directory (defaults to the current directory)
+        #[arg(long)]
+        path: Option<PathBuf>,
+        /// Project to install into (defaults to the nearest project above the plugin)
+        #[arg(long)]
+        project: Option<PathBuf>,
+        /// Build without `--release`
+        #[arg(long)]
+        debug: bool,
+        /// Only compile, do not install
+        #[arg(long)]
+        no_install: bool,
+    },
+    /// Render one note with every synth export and check the output is not silent
+    Test {
+        /// Plugin crate directory (defaults to the current directory)
+        #[arg(long)]
+        path: Option<PathBuf>,
+        /// Test the debug build instead of the release build
+        #[arg(long)]
+        debug: bool,
+    },
+}
+
+impl PluginCommand {
+    pub async fn execute(&self, ctx: &CliContext) -> Result<()> {
+        let logger = ctx.logger();
+        let cwd = std::env::current_dir()?;
+
+        match &self.action {
+            PluginAction::New {
+                name,
+                publisher,
+                path,
+            } => {
+                let parent = path.clone().unwrap_or(cwd);
+                logger.action(format!("Creating plugin '{}'...", name));
+                let dir = scaffold::scaffold_plugin(&parent, name, publisher)?;
+                logger.success(format!("Plugin crate created at '{}'", dir.display()));
+                logger.info("Next steps:");
+                logger.info(format!("  cd {}", dir.display()));
+                logger.info("  devalang plugin build");
+                logger.info("  devalang plugin test");
+            }
+            PluginAction::Build {
+                path,
+                project,
+                debug,
+                no_install,
+            } => {
+                let dir = path.clone().unwrap_or(cwd);
+                let manifest = manifest::PluginManifest::load(&dir)?;
+                logger.action(format!("Compiling plugin '{}' to WASM...", manifest.name));
+                let wasm_path = build::compile(&dir, &manifest, !*debug)?;
+                logger.success(format!("Built {}", wasm_path.display()));
+
+                if !*no_install {
+                    let installed =
+                        build::install(&dir, &manifest, &wasm_path, project.as_deref())?;
+                    logger.success(format!("Installed to {}", installed.display()));
+                    logger.info(format!(
+                        "Use it with: use {}.{} as {}",
+                        manifest.publisher, manifest.name, manifest.name
+                    ));
+                }
+            }
+            PluginAction::Test { path, debug } => {
+                let dir = path.clone().unwrap_or(cwd);
+                let manifest = manifest::PluginManifest::load(&dir)?;
+                let wasm_path = manifest.wasm_path(&dir, !*debug);
+                logger.action(format!(
+                    "Smoke testing {} export(s) of '{}'...",
+                    manifest.synth_exports().len() + manifest.effect_exports().len(),
+                    manifest.name
+                ));
+                let reports = smoke::run(&wasm_path, &manifest)?;
+                let mut failed = 0;
+                for report in &reports {
+                    match &report.error {
+                        None => logger.success(format!(
+                            "{}: peak {:.3}, rms {:.3}",
+                            report.export, report.peak, report.rms
+                        )),
+                        Some(err) => {
+                            failed += 1;
+                            logger.error(format!("{}: {}", report.export, err));
+                        }
+                    }
+                }
+                if failed > 0 {
+                    anyhow::bail!("{} of {} export(s) failed", failed, reports.len());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "cli")]
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Create `<parent>/<name>` with a Cargo manifest, `plugin.toml` and a sine synth export
+pub fn scaffold_plugin(parent: &Path, name: &str, publisher: &str) -> Result<PathBuf> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        anyhow::bail!(
+            "Invalid plugin name '{}': use letters, digits, '-' or '_'",
+            name
+        );
+    }
+
+    let dir = parent.join(name);
+    if dir.exists() {
+        anyhow::bail!("Directory '{}' already exists", dir.display());
+    }
+
+    fs::create_dir_all(dir.join("src"))?;
+    fs::write(dir.join("Cargo.toml"), cargo_template(name))?;
+    fs::write(dir.join("plugin.toml"), plugin_template(name, publisher))?;
+    fs::write(dir.join("src").join("lib.rs"), lib_template())?;
+    fs::write(dir.join(".gitignore"), "/target\n")?;
+
+    Ok(dir)
+}
+
+fn cargo_template(name: &str) -> String {
+    format!(
+        r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2024"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+devalang_bindings = {{ package = "devalang", version = "{version}", default-features = false, features = ["plugin"] }}
+
+[profile.release]
+opt-level = "s"
+lto = true
+"#,
+        version = env!("CARGO_PKG_VERSION"),
+    )
+}
+
+fn plugin_template(name: &str, publisher: &str) -> String {
+    format!(
+        r#"[plugin]
+name = "{name}"
+version = "0.1.0"
+description = "A Devalang synth plugin"
+publisher = "{publisher}"
+
+[[exports]]
+name = "synth"
+kind = "synth"
+"#
+    )
+}
+
+fn lib_template() -> &'static str {
+    r#"use devalang_bindings::*;
+
+// Called once per note; `out` is an interleaved buffer of `params.frames` frames.
+export_plugin!(synth, |out, params, _note, freq, amp| {
+    let sr = params.sample_rate as f32;
+    let release = (params.frames / 10).max(1);
+    for frame in 0..params.frames {
+        let t = frame as f32 / sr;
+        let fade = ((params.frames - frame) as f32 / release as f32).min(1.0);
+        let sample = (2.0 * core::f32::consts::PI * freq * t).sin() * amp * fade;
+        for ch in 0..params.channels {
+            out[(frame * params.channels + ch) as usize] = sample;
+        }
+    }
+});
+"#
+}
+
+#[cfg(test)]
+#[path = "test_scaffold.rs"]
+mod tests;
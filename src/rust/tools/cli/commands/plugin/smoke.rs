@@ -0,0 +1,112 @@
+#![cfg(feature = "cli")]
+
+use anyhow::Result;
+use std::path::Path;
+
+use super::manifest::PluginManifest;
+use crate::engine::plugin::runner::{EffectInstance, WasmPluginRunner};
+
+const SAMPLE_RATE: i32 = 44100;
+const CHANNELS: i32 = 2;
+const NOTE_MS: i32 = 500;
+/// Peak level below which a rendered note counts as silence
+const SILENCE_THRESHOLD: f32 = 1e-4;
+
+#[derive(Debug, Clone)]
+pub struct SmokeReport {
+    pub export: String,
+    pub peak: f32,
+    pub rms: f32,
+    pub error: Option<String>,
+}
+
+/// Render an A4 through every synth export and a test tone through every effect export,
+/// and check the result is finite and audible
+pub fn run(wasm_path: &Path, manifest: &PluginManifest) -> Result<Vec<SmokeReport>> {
+    let bytes = std::fs::read(wasm_path).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read '{}' ({}); run 'devalang plugin build' first",
+            wasm_path.display(),
+            e
+        )
+    })?;
+
+    let synths = manifest.synth_exports();
+    let effects = manifest.effect_exports();
+    if synths.is_empty() && effects.is_empty() {
+        anyhow::bail!("plugin.toml declares no exports with kind = \"synth\" or \"effect\"");
+    }
+
+    let runner = WasmPluginRunner::new();
+    Ok(synths
+        .into_iter()
+        .map(|export| render_export(&runner, &bytes, export))
+        .chain(
+            effects
+                .into_iter()
+                .map(|export| process_export(&runner, &bytes, export)),
+        )
+        .collect())
+}
+
+fn process_export(runner: &WasmPluginRunner, bytes: &[u8], export: &str) -> SmokeReport {
+    let frames = (SAMPLE_RATE * NOTE_MS / 1000) as usize;
+    let input: Vec<f32> = (0..frames)
+        .flat_map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let sample = 0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+            [sample; CHANNELS as usize]
+        })
+        .collect();
+    let mut buffer = vec![0.0f32; input.len()];
+
+    // Parameters are left unset so the export falls back to its defaults
+    let result = EffectInstance::new(runner.engine(), bytes, export)
+        .and_then(|mut instance| instance.process(&input, &mut buffer, SAMPLE_RATE, CHANNELS, &[]));
+    report(export, &buffer, result)
+}
+
+fn render_export(runner: &WasmPluginRunner, bytes: &[u8], export: &str) -> SmokeReport {
+    let frames = (SAMPLE_RATE * NOTE_MS / 1000) as usize;
+    let mut buffer = vec![0.0f32; frames * CHANNELS as usize];
+
+    let result = runner.render_note_in_place(
+        bytes,
+        &mut buffer,
+        Some(export),
+        Some(export),
+        440.0,
+        0.8,
+        NOTE_MS,
+        SAMPLE_RATE,
+        CHANNELS,
+        None,
+        None,
+    );
+
+    report(export, &buffer, result)
+}
+
+fn report(export: &str, buffer: &[f32], result: Result<(), String>) -> SmokeReport {
+    let peak = buffer.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    let rms = (buffer.iter().map(|s| s * s).sum::<f32>() / buffer.len() as f32).sqrt();
+    let error = match result {
+        Err(e) => Some(e),
+        Ok(()) if buffer.iter().any(|s| !s.is_finite()) => {
+            Some("output contains NaN or infinite samples".to_string())
+        }
+        Ok(()) if peak < SILENCE_THRESHOLD => Some("output is silent".to_string()),
+        Ok(()) => None,
+    };
+
+    SmokeReport {
+        export: export.to_string(),
+        peak,
+        rms,
+        error,
+    }
+}
+
+#[cfg(test)]
+#[path = "test_smoke.rs"]
+mod tests;
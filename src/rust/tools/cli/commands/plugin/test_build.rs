@@ -0,0 +1,28 @@
+use super::*;
+
+#[test]
+fn test_install_copies_the_module_into_the_project() {
+    let project = tempfile::tempdir().unwrap();
+    let crate_dir = tempfile::tempdir().unwrap();
+    fs::write(
+        crate_dir.path().join("plugin.toml"),
+        "[plugin]\nname = \"acid\"\n",
+    )
+    .unwrap();
+    let wasm = crate_dir.path().join("acid.wasm");
+    fs::write(&wasm, b"\0asm").unwrap();
+    let manifest = PluginManifest {
+        crate_name: "acid".into(),
+        name: "acid".into(),
+        publisher: "devaloop".into(),
+        exports: Vec::new(),
+    };
+
+    let target = install(crate_dir.path(), &manifest, &wasm, Some(project.path())).unwrap();
+    assert_eq!(
+        target,
+        project.path().join(DEVA_DIR).join("plugins/devaloop/acid")
+    );
+    assert_eq!(fs::read(target.join("acid.wasm")).unwrap(), b"\0asm");
+    assert!(target.join("plugin.toml").exists());
+}
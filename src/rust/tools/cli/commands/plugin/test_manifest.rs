@@ -0,0 +1,48 @@
+use super::*;
+
+fn write_crate(dir: &Path, plugin_toml: &str) {
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"acid-synth\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("plugin.toml"), plugin_toml).unwrap();
+}
+
+#[test]
+fn test_manifest_reads_crate_name_and_exports() {
+    let dir = tempfile::tempdir().unwrap();
+    write_crate(
+        dir.path(),
+        "[plugin]\nname = \"acid\"\npublisher = \"devaloop\"\n\n[[exports]]\nname = \"bass\"\nkind = \"synth\"\n\n[[exports]]\nname = \"drive\"\nkind = \"effect\"\n",
+    );
+    let manifest = PluginManifest::load(dir.path()).unwrap();
+    assert_eq!(manifest.crate_name, "acid-synth");
+    assert_eq!(
+        (manifest.name.as_str(), manifest.publisher.as_str()),
+        ("acid", "devaloop")
+    );
+    assert_eq!(manifest.synth_exports(), vec!["bass"]);
+    assert_eq!(manifest.effect_exports(), vec!["drive"]);
+    assert_eq!(
+        manifest.wasm_path(dir.path(), true),
+        dir.path()
+            .join("target/wasm32-unknown-unknown/release/acid_synth.wasm")
+    );
+}
+
+#[test]
+fn test_manifest_defaults_and_missing_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let error = PluginManifest::load(dir.path()).unwrap_err();
+    assert!(error.to_string().contains("No plugin crate"), "{error}");
+
+    write_crate(dir.path(), "[plugin]\nname = \"acid\"\n");
+    let manifest = PluginManifest::load(dir.path()).unwrap();
+    assert_eq!(manifest.publisher, "local");
+    assert!(manifest.exports.is_empty());
+
+    std::fs::remove_file(dir.path().join("plugin.toml")).unwrap();
+    let error = PluginManifest::load(dir.path()).unwrap_err();
+    assert!(error.to_string().contains("Missing"), "{error}");
+}
@@ -0,0 +1,35 @@
+use super::*;
+use crate::tools::cli::commands::plugin::manifest::PluginManifest;
+
+#[test]
+fn test_scaffold_writes_a_loadable_plugin_crate() {
+    let parent = tempfile::tempdir().unwrap();
+    let dir = scaffold_plugin(parent.path(), "my-synth", "devaloop").unwrap();
+    assert_eq!(dir, parent.path().join("my-synth"));
+    assert!(dir.join("src/lib.rs").exists());
+    assert_eq!(
+        fs::read_to_string(dir.join(".gitignore")).unwrap(),
+        "/target\n"
+    );
+
+    let manifest = PluginManifest::load(&dir).unwrap();
+    assert_eq!(manifest.crate_name, "my-synth");
+    assert_eq!(manifest.publisher, "devaloop");
+    assert_eq!(manifest.synth_exports(), vec!["synth"]);
+    let lib = fs::read_to_string(dir.join("src/lib.rs")).unwrap();
+    assert!(lib.contains("export_plugin!(synth,"));
+}
+
+#[test]
+fn test_scaffold_refuses_bad_names_and_existing_directories() {
+    let parent = tempfile::tempdir().unwrap();
+    for name in ["", "my synth", "../up"] {
+        assert!(
+            scaffold_plugin(parent.path(), name, "local").is_err(),
+            "{name}"
+        );
+    }
+    scaffold_plugin(parent.path(), "twice", "local").unwrap();
+    let error = scaffold_plugin(parent.path(), "twice", "local").unwrap_err();
+    assert!(error.to_string().contains("already exists"), "{error}");
+}
@@ -0,0 +1,86 @@
+use super::*;
+
+/// `loud` writes 0.5 everywhere, `quiet` nothing, `halve` scales its input
+const PLUGIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "loud") (param $out i32) (param $len i32) (param $freq f32) (param $amp f32)
+        (param $duration i32) (param $rate i32) (param $channels i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+        (f32.store (i32.add (local.get $out) (i32.shl (local.get $i) (i32.const 2)))
+          (f32.const 0.5))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next))))
+  (func (export "quiet") (param i32 i32 f32 f32 i32 i32 i32))
+  (func (export "halve") (param $in i32) (param $out i32) (param $len i32) (param $rate i32)
+        (param $channels i32) (param $params i32) (param $count i32)
+    (local $i i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+        (f32.store (i32.add (local.get $out) (i32.shl (local.get $i) (i32.const 2)))
+          (f32.mul (f32.const 0.5)
+            (f32.load (i32.add (local.get $in) (i32.shl (local.get $i) (i32.const 2))))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))))
+"#;
+
+fn manifest(exports: &[(&str, &str)]) -> PluginManifest {
+    PluginManifest {
+        crate_name: "smoke".into(),
+        name: "smoke".into(),
+        publisher: "local".into(),
+        exports: exports
+            .iter()
+            .map(|(name, kind)| (name.to_string(), kind.to_string()))
+            .collect(),
+    }
+}
+
+#[test]
+fn test_smoke_reports_each_export() {
+    let dir = tempfile::tempdir().unwrap();
+    let wasm = dir.path().join("smoke.wasm");
+    std::fs::write(&wasm, PLUGIN).unwrap();
+
+    let reports = run(
+        &wasm,
+        &manifest(&[("loud", "synth"), ("quiet", "synth"), ("halve", "effect")]),
+    )
+    .unwrap();
+    let summary: Vec<(&str, Option<&str>)> = reports
+        .iter()
+        .map(|report| (report.export.as_str(), report.error.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("loud", None),
+            ("quiet", Some("output is silent")),
+            ("halve", None),
+        ]
+    );
+    assert_eq!((reports[0].peak, reports[0].rms), (0.5, 0.5));
+    assert!((reports[2].peak - 0.25).abs() < 1e-3);
+}
+
+#[test]
+fn test_smoke_needs_a_module_and_exports() {
+    let dir = tempfile::tempdir().unwrap();
+    let wasm = dir.path().join("smoke.wasm");
+    let error = run(&wasm, &manifest(&[("loud", "synth")])).unwrap_err();
+    assert!(
+        error.to_string().contains("devalang plugin build"),
+        "{error}"
+    );
+
+    std::fs::write(&wasm, PLUGIN).unwrap();
+    let error = run(&wasm, &manifest(&[("loud", "sampler")])).unwrap_err();
+    assert!(error.to_string().contains("declares no exports"), "{error}");
+
+    let reports = run(&wasm, &manifest(&[("missing", "synth")])).unwrap();
+    assert!(reports[0].error.is_some());
+}
@@ -0,0 +1,296 @@
+#![cfg(feature = "cli")]
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::engine::audio::encoders::{EncoderOptions, encode_audio};
+use crate::engine::audio::samples::prepare::{self, AUDIO_EXTENSIONS, PrepareOptions};
+use crate::engine::audio::samples::{self, BankPreparation};
+use crate::engine::audio::settings::ResampleQuality;
+use crate::platform::config::AppConfig;
+use crate::tools::cli::config::path;
+use crate::tools::cli::state::CliContext;
+use crate::tools::logger::Logger;
+
+#[derive(Debug, Clone, Args)]
+pub struct SamplesCommand {
+    #[command(subcommand)]
+    pub action: SamplesAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum SamplesAction {
+    /// Normalize, trim and convert a folder of samples, then write a bank.toml for it
+    Prepare {
+        /// Folder holding the raw samples (not scanned recursively)
+        dir: PathBuf,
+        /// Bank name (defaults to the folder name)
+        #[arg(long)]
+        name: Option<String>,
+        /// Bank publisher
+        #[arg(short, long, default_value = "local")]
+        publisher: String,
+        /// Sub-folder the prepared WAV files are written to; originals are left untouched
+        #[arg(long, default_value = "audio")]
+        audio_dir: String,
+        /// Level (dBFS) below which leading/trailing audio is trimmed
+        #[arg(long, default_value_t = -60.0, allow_hyphen_values = true)]
+        threshold_db: f32,
+        /// Peak level (dBFS) samples are normalized to
+        #[arg(long, default_value_t = -1.0, allow_hyphen_values = true)]
+        peak_db: f32,
+        /// Keep silence at both ends
+        #[arg(long)]
+        no_trim: bool,
+        /// Keep the original gain
+        #[arg(long)]
+        no_normalize: bool,
+        /// Replace an existing bank.toml
+        #[arg(long)]
+        force: bool,
+    },
+    /// Detect the tempo (loops) and root note (pitched samples) of a bank's triggers and
+    /// record them in its bank.toml
+    Analyze {
+        /// Bank folder holding bank.toml
+        dir: PathBuf,
+        /// Replace tempos and roots the manifest already declares
+        #[arg(long)]
+        force: bool,
+        /// Print what was detected without writing bank.toml
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+impl SamplesCommand {
+    pub async fn execute(&self, ctx: &CliContext) -> Result<()> {
+        let logger = ctx.logger();
+
+        match &self.action {
+            SamplesAction::Prepare {
+                dir,
+                name,
+                publisher,
+                audio_dir,
+                threshold_db,
+                peak_db,
+                no_trim,
+                no_normalize,
+                force,
+            } => {
+                let manifest_path = dir.join("bank.toml");
+                if manifest_path.exists() && !force {
+                    anyhow::bail!(
+                        "{} already exists; pass --force to replace it",
+                        manifest_path.display()
+                    );
+                }
+
+                let config = AppConfig::load(std::env::current_dir()?)?;
+                let options = PrepareOptions {
+                    sample_rate: config.sample_rate(),
+                    quality: config.resample_quality(),
+                    trim_threshold_db: (!no_trim).then_some(*threshold_db),
+                    normalize_peak_db: (!no_normalize).then_some(*peak_db),
+                };
+                // The WAV encoder has no 8-bit mode
+                let bit_depth = config.audio_bit_depth().bits().max(16) as u8;
+                let encoder = EncoderOptions::wav(options.sample_rate, bit_depth);
+
+                let files = audio_files(dir)?;
+                if files.is_empty() {
+                    anyhow::bail!("No audio files found in {}", dir.display());
+                }
+
+                logger.action(format!(
+                    "Preparing {} sample(s) at {} Hz / {}-bit...",
+                    files.len(),
+                    options.sample_rate,
+                    bit_depth
+                ));
+
+                let out_dir = dir.join(audio_dir);
+                std::fs::create_dir_all(&out_dir)
+                    .with_context(|| format!("failed to create {}", out_dir.display()))?;
+
+                let mut triggers: Vec<(String, String)> = Vec::new();
+                for file in &files {
+                    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+                    let mut trigger = prepare::trigger_name(&stem);
+                    // Files differing only by extension or punctuation get distinct triggers
+                    let base = trigger.clone();
+                    let mut n = 2;
+                    while triggers.iter().any(|(existing, _)| existing == &trigger) {
+                        trigger = format!("{}_{}", base, n);
+                        n += 1;
+                    }
+
+                    let prepared = match prepare::prepare_file(file, &options) {
+                        Ok(prepared) => prepared,
+                        Err(e) => {
+                            logger.warn(format!("Skipping {}: {}", file.display(), e));
+                            continue;
+                        }
+                    };
+                    if prepared.samples.is_empty() {
+                        logger.warn(format!("Skipping {}: silent", file.display()));
+                        continue;
+                    }
+
+                    let file_name = format!("{}.wav", trigger);
+                    let bytes = encode_audio(&prepared.samples, &encoder)?;
+                    std::fs::write(out_dir.join(&file_name), bytes)
+                        .with_context(|| format!("failed to write {}", file_name))?;
+                    logger.info(format!(
+                        "  - {} -> {} ({:.2}s)",
+                        file.display(),
+                        trigger,
+                        prepared.samples.len() as f32 / options.sample_rate as f32
+                    ));
+                    triggers.push((trigger, file_name));
+                }
+
+                let bank_name = match name {
+                    Some(name) => name.clone(),
+                    None => bank_name_for(dir)?,
+                };
+                let audio_path = format!("{}/", audio_dir.trim_end_matches('/'));
+                std::fs::write(
+                    &manifest_path,
+                    prepare::bank_manifest(&bank_name, publisher, &audio_path, &triggers),
+                )
+                .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+
+                logger.success(format!(
+                    "Bank '{}.{}' ready with {} trigger(s): {}",
+                    publisher,
+                    bank_name,
+                    triggers.len(),
+                    manifest_path.display()
+                ));
+            }
+            SamplesAction::Analyze {
+                dir,
+                force,
+                dry_run,
+            } => {
+                let manifest_path = dir.join("bank.toml");
+                logger.action(format!("Analyzing {}...", manifest_path.display()));
+
+                let mut entries = HashMap::new();
+                for (trigger, analysis) in prepare::analyze_bank(dir)? {
+                    let analysis = match analysis {
+                        Ok(analysis) => analysis,
+                        Err(e) => {
+                            logger.warn(format!("Skipping {}: {}", trigger, e));
+                            continue;
+                        }
+                    };
+                    let bpm = analysis
+                        .bpm
+                        .map_or("-".to_string(), |bpm| format!("{:.2} BPM", bpm));
+                    let root = analysis.root.clone().unwrap_or_else(|| "-".to_string());
+                    logger.info(format!("  - {}: tempo {}, root {}", trigger, bpm, root));
+                    entries.insert(trigger, analysis.entries());
+                }
+
+                if *dry_run {
+                    return Ok(());
+                }
+                let manifest = std::fs::read_to_string(&manifest_path)
+                    .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+                let updated = prepare::set_trigger_metadata(&manifest, &entries, *force);
+                if updated == manifest {
+                    logger.success("bank.toml already declares everything detected");
+                } else {
+                    std::fs::write(&manifest_path, updated)
+                        .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+                    logger.success(format!("Updated {}", manifest_path.display()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Start the bank-wide work `audio.preconvert_samples` and `audio.sample_variants` ask
+/// for on the loaded banks; variants are read from, and written to, `.deva`
+pub fn start_bank_preparation(
+    config: &AppConfig,
+    sample_rate: u32,
+    quality: ResampleQuality,
+    logger: &Logger,
+) -> BankPreparation {
+    let variants = config.audio.sample_variants
+        && match path::ensure_deva_dir() {
+            Ok(deva) => {
+                samples::set_variant_cache_dir(Some(deva.join(samples::VARIANT_CACHE_DIR)));
+                true
+            }
+            Err(e) => {
+                logger.warn(format!("Sample variants disabled: {}", e));
+                false
+            }
+        };
+    if config.audio.preconvert_samples {
+        logger.info(format!(
+            "Pre-converting bank samples to {} Hz in the background...",
+            sample_rate
+        ));
+    }
+    if variants {
+        logger.info("Generating reversed and 0.5x/2x sample variants in the background...");
+    }
+    BankPreparation::start(
+        sample_rate,
+        quality,
+        config.audio.preconvert_samples,
+        variants,
+    )
+}
+
+/// Wait for `preparation` and log what it did
+pub fn finish_bank_preparation(preparation: BankPreparation, logger: &Logger) {
+    match preparation.finish() {
+        Ok(report) => {
+            if let Some(converted) = report.converted {
+                logger.info(format!("Pre-converted {} bank sample(s)", converted));
+            }
+            if let Some(variants) = report.variants {
+                logger.info(format!("Generated {} sample variant(s)", variants));
+            }
+        }
+        Err(e) => logger.warn(format!("{:#}", e)),
+    }
+}
+
+/// Audio files directly inside `dir`, sorted by name
+fn audio_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn bank_name_for(dir: &Path) -> Result<String> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let name = dir
+        .file_name()
+        .map(|name| prepare::trigger_name(&name.to_string_lossy()))
+        .context("cannot name a bank after this folder; pass --name")?;
+    Ok(name)
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "cli")]
+
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::services::render_api::server::RenderApiServer;
+use crate::services::render_api::{JobQueue, RenderBudget};
+use crate::tools::cli::state::CliContext;
+
+#[derive(Debug, Clone, Args)]
+pub struct ServeCommand {
+    /// Accept project bundles over HTTP and render them (remote render farm)
+    #[arg(long, default_value_t = false)]
+    pub render_api: bool,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Port to listen on
+    #[arg(long, default_value_t = 7979)]
+    pub port: u16,
+
+    /// Directory holding submitted bundles and rendered files
+    #[arg(long, default_value = ".deva/render-api")]
+    pub workdir: PathBuf,
+
+    /// Require `Authorization: Bearer <token>` on every request
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// Jobs rendered in parallel
+    #[arg(long)]
+    pub workers: Option<usize>,
+
+    /// Largest accepted bundle, in megabytes
+    #[arg(long)]
+    pub max_bundle_mb: Option<u64>,
+
+    /// Jobs allowed to wait or render at once
+    #[arg(long)]
+    pub max_pending: Option<usize>,
+
+    /// Longest rendered audio accepted, in seconds
+    #[arg(long)]
+    pub max_audio_seconds: Option<f64>,
+
+    /// Longest a job may take to render, in seconds of wall-clock time
+    #[arg(long)]
+    pub max_render_seconds: Option<f64>,
+}
+
+impl ServeCommand {
+    pub async fn execute(&self, ctx: &CliContext) -> Result<()> {
+        let logger = ctx.logger();
+        if !self.render_api {
+            anyhow::bail!("Nothing to serve; use `devalang serve --render-api`");
+        }
+
+        let defaults = RenderBudget::default();
+        let budget = RenderBudget {
+            workers: self.workers.unwrap_or(defaults.workers).max(1),
+            max_bundle_bytes: self
+                .max_bundle_mb
+                .map_or(defaults.max_bundle_bytes, |mb| mb * 1024 * 1024),
+            max_pending_jobs: self.max_pending.unwrap_or(defaults.max_pending_jobs),
+            max_audio_seconds: self.max_audio_seconds.unwrap_or(defaults.max_audio_seconds),
+            max_render_seconds: self
+                .max_render_seconds
+                .unwrap_or(defaults.max_render_seconds),
+            ..defaults
+        };
+        if self.token.is_none() && self.host != "127.0.0.1" && self.host != "localhost" {
+            logger.warn("Render API is reachable from the network without --token");
+        }
+
+        let queue = JobQueue::new(&self.workdir, budget, logger.clone())?;
+        let _workers = queue.start_workers();
+        logger.info(format!(
+            "{} worker(s), bundles up to {} MB, {} pending job(s), {:.0}s of audio and {:.0}s of rendering per job",
+            budget.workers,
+            budget.max_bundle_bytes / (1024 * 1024),
+            budget.max_pending_jobs,
+            budget.max_audio_seconds,
+            budget.max_render_seconds
+        ));
+
+        let server = RenderApiServer::new(queue, self.token.clone(), logger);
+        server.run(&format!("{}:{}", self.host, self.port))
+    }
+}
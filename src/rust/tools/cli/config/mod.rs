@@ -1,6 +1,7 @@
 #![cfg(feature = "cli")]
 
 pub mod path;
+pub mod pins;
 pub mod sso;
 pub mod telemetry;
 pub mod urls;
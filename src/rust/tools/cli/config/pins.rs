@@ -0,0 +1,218 @@
+#![cfg(feature = "cli")]
+
+//! Addon version pins from the `[addons]` config section
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use semver::{Version, VersionReq};
+
+use super::path::{DEVA_DIR, get_deva_dir};
+
+/// Addon folders inside a `.deva` directory and the manifest section each addon declares
+pub const ADDON_KINDS: [(&str, &str); 4] = [
+    ("banks", "bank"),
+    ("plugins", "plugin"),
+    ("presets", "preset"),
+    ("templates", "template"),
+];
+
+#[derive(Debug, Clone)]
+pub struct InstalledAddon {
+    /// `publisher.name`
+    pub id: String,
+    /// Manifest section: bank, plugin, preset or template
+    pub kind: &'static str,
+    pub version: Option<String>,
+    pub path: PathBuf,
+}
+
+/// Parse an addon version; `1.2` and `v1.2.0` are accepted alongside full semver
+pub fn parse_version(raw: &str) -> Result<Version> {
+    let raw = raw.trim().trim_start_matches('v');
+    let padded = match raw.split('.').count() {
+        1 => format!("{}.0.0", raw),
+        2 => format!("{}.0", raw),
+        _ => raw.to_string(),
+    };
+    Version::parse(&padded).map_err(|e| anyhow!("invalid version '{}': {}", raw, e))
+}
+
+/// Parse a version constraint (`^1.2`, `~1.4.0`, `>=2, <3`, `*`)
+pub fn parse_requirement(raw: &str) -> Result<VersionReq> {
+    VersionReq::parse(raw.trim()).map_err(|e| anyhow!("invalid constraint '{}': {}", raw, e))
+}
+
+/// Installed addons from the project `.deva` folder, then the one in the home directory.
+/// An addon installed in both places is reported once, from the project.
+pub fn installed_addons() -> Vec<InstalledAddon> {
+    let mut roots = Vec::new();
+    match get_deva_dir() {
+        Ok(dir) => roots.push(dir),
+        Err(_) => {
+            if let Ok(cwd) = std::env::current_dir() {
+                roots.push(cwd.join(DEVA_DIR));
+            }
+        }
+    }
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join(DEVA_DIR));
+    }
+
+    let mut addons: Vec<InstalledAddon> = Vec::new();
+    for root in roots {
+        for addon in scan_deva_dir(&root) {
+            if !addons.iter().any(|existing| existing.id == addon.id) {
+                addons.push(addon);
+            }
+        }
+    }
+    addons
+}
+
+/// Addons under `deva_dir/<kind>/<publisher>/<name>`, sorted by id
+pub fn scan_deva_dir(deva_dir: &Path) -> Vec<InstalledAddon> {
+    let mut addons = Vec::new();
+    for (folder, kind) in ADDON_KINDS {
+        let Ok(publishers) = fs::read_dir(deva_dir.join(folder)) else {
+            continue;
+        };
+        for publisher in publishers.flatten().filter(|entry| entry.path().is_dir()) {
+            let Ok(entries) = fs::read_dir(publisher.path()) else {
+                continue;
+            };
+            for entry in entries.flatten().filter(|entry| entry.path().is_dir()) {
+                let path = entry.path();
+                addons.push(InstalledAddon {
+                    id: format!(
+                        "{}.{}",
+                        publisher.file_name().to_string_lossy(),
+                        entry.file_name().to_string_lossy()
+                    ),
+                    kind,
+                    version: manifest_version(&path, kind),
+                    path,
+                });
+            }
+        }
+    }
+    addons.sort_by(|a, b| a.id.cmp(&b.id));
+    addons
+}
+
+/// `version` from the `[<kind>]` section of the addon's `<kind>.toml`
+pub fn manifest_version(addon_path: &Path, kind: &str) -> Option<String> {
+    let content = fs::read_to_string(addon_path.join(format!("{}.toml", kind))).ok()?;
+    let manifest: toml::Value = toml::from_str(&content).ok()?;
+    manifest
+        .get(kind)?
+        .get("version")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinIssue {
+    NotInstalled,
+    /// The addon manifest has no `version`
+    Unversioned,
+    InvalidConstraint(String),
+    InvalidVersion(String),
+    Mismatch {
+        installed: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinViolation {
+    pub addon: String,
+    pub constraint: String,
+    pub issue: PinIssue,
+}
+
+impl fmt::Display for PinViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.issue {
+            PinIssue::NotInstalled => write!(
+                f,
+                "{} is pinned to {} but not installed (devalang addon install {})",
+                self.addon, self.constraint, self.addon
+            ),
+            PinIssue::Unversioned => write!(
+                f,
+                "{} is pinned to {} but its manifest declares no version",
+                self.addon, self.constraint
+            ),
+            PinIssue::InvalidConstraint(err) | PinIssue::InvalidVersion(err) => {
+                write!(f, "{}: {}", self.addon, err)
+            }
+            PinIssue::Mismatch { installed } => write!(
+                f,
+                "{} {} is installed but the project requires {}",
+                self.addon, installed, self.constraint
+            ),
+        }
+    }
+}
+
+/// Pins in `pins` (addon id -> constraint) that `installed` does not satisfy
+pub fn check_pins(
+    pins: &BTreeMap<String, String>,
+    installed: &[InstalledAddon],
+) -> Vec<PinViolation> {
+    let mut violations = Vec::new();
+    for (addon, constraint) in pins {
+        let violation = |issue| PinViolation {
+            addon: addon.clone(),
+            constraint: constraint.clone(),
+            issue,
+        };
+        let requirement = match parse_requirement(constraint) {
+            Ok(requirement) => requirement,
+            Err(err) => {
+                violations.push(violation(PinIssue::InvalidConstraint(err.to_string())));
+                continue;
+            }
+        };
+        let Some(found) = installed.iter().find(|candidate| &candidate.id == addon) else {
+            violations.push(violation(PinIssue::NotInstalled));
+            continue;
+        };
+        let Some(raw) = &found.version else {
+            violations.push(violation(PinIssue::Unversioned));
+            continue;
+        };
+        match parse_version(raw) {
+            Ok(version) if requirement.matches(&version) => {}
+            Ok(_) => violations.push(violation(PinIssue::Mismatch {
+                installed: raw.clone(),
+            })),
+            Err(err) => violations.push(violation(PinIssue::InvalidVersion(err.to_string()))),
+        }
+    }
+    violations
+}
+
+/// Fail when an installed addon does not satisfy the project's `[addons]` pins
+pub fn enforce_pins(pins: &BTreeMap<String, String>) -> Result<()> {
+    if pins.is_empty() {
+        return Ok(());
+    }
+    let violations = check_pins(pins, &installed_addons());
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let details: Vec<String> = violations.iter().map(|v| format!("  - {}", v)).collect();
+    Err(anyhow!(
+        "{} addon version pin(s) not satisfied:\n{}\nInstall a matching version or update the [addons] section of the project config",
+        violations.len(),
+        details.join("\n")
+    ))
+}
+
+#[cfg(test)]
+#[path = "test_pins.rs"]
+mod tests;
@@ -1,18 +1,105 @@
 #![cfg(feature = "cli")]
 
-use anyhow::Result;
+//! Opt-in usage telemetry (`devalang telemetry enable`)
+//!
+//! Each finished command produces one `TelemetryEvent`. Where it goes is the `sink` in
+//! `~/.devalang/telemetry.json`: Devalang's endpoint, an HTTPS endpoint of your own, a
+//! local JSONL file that nothing leaves (for audits), or nowhere at all.
+
+use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::urls::get_api_url;
+use super::user::get_user_config;
+
+/// How long a command waits for the endpoint before dropping its event
+const SEND_TIMEOUT: Duration = Duration::from_secs(2);
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct TelemetryConfig {
     pub enabled: bool,
+    #[serde(default)]
+    pub sink: TelemetrySink,
+}
+
+/// Where telemetry events go
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum TelemetrySink {
+    /// Devalang's endpoint (`<api>/v1/telemetry`)
+    #[default]
+    Devalang,
+    /// HTTPS endpoint of your own, receiving the same JSON body
+    Endpoint { url: String },
+    /// Append events to a JSONL file (`~/.devalang/telemetry.jsonl` by default); nothing
+    /// leaves the machine
+    Local { path: Option<PathBuf> },
+    /// Record nothing, even while telemetry is enabled
+    Off,
+}
+
+impl TelemetrySink {
+    /// HTTPS endpoint of your own; plain HTTP is refused
+    pub fn endpoint(url: &str) -> Result<Self> {
+        let url = url.trim();
+        match url.strip_prefix("https://") {
+            Some(host) if !host.is_empty() => Ok(TelemetrySink::Endpoint {
+                url: url.to_string(),
+            }),
+            _ => bail!("telemetry endpoint must be an https:// URL, got '{}'", url),
+        }
+    }
+
+    /// Where events end up, for `devalang telemetry status`
+    pub fn describe(&self) -> String {
+        match self {
+            TelemetrySink::Devalang => format!("Devalang ({})", devalang_endpoint()),
+            TelemetrySink::Endpoint { url } => format!("custom endpoint ({})", url),
+            TelemetrySink::Local { path } => format!(
+                "local file {} (nothing leaves this machine)",
+                local_path(path.as_deref()).display()
+            ),
+            TelemetrySink::Off => "off (nothing is recorded)".to_string(),
+        }
+    }
+}
+
+/// One finished command, as sent or written
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TelemetryEvent {
+    pub event: String,
+    pub command: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    /// Random id from `~/.devalang/config.json`, when the user config exists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_id: Option<String>,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
 }
 
-impl Default for TelemetryConfig {
-    fn default() -> Self {
-        Self { enabled: false }
+impl TelemetryEvent {
+    pub fn command(command: &str, success: bool, duration: Duration) -> Self {
+        Self {
+            event: "command".to_string(),
+            command: command.to_string(),
+            success,
+            duration_ms: duration.as_millis() as u64,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            install_id: get_user_config().map(|config| config.telemetry.uuid),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+        }
     }
 }
 
@@ -21,23 +108,26 @@ fn get_telemetry_config_path() -> PathBuf {
     home.join(".devalang").join("telemetry.json")
 }
 
-pub fn is_telemetry_enabled() -> bool {
-    let config_path = get_telemetry_config_path();
+fn devalang_endpoint() -> String {
+    format!("{}/v1/telemetry", get_api_url())
+}
 
-    if !config_path.exists() {
-        return false;
+fn local_path(path: Option<&Path>) -> PathBuf {
+    match path {
+        Some(path) => path.to_path_buf(),
+        None => get_telemetry_config_path().with_file_name("telemetry.jsonl"),
     }
+}
 
-    match fs::read_to_string(&config_path) {
-        Ok(content) => match serde_json::from_str::<TelemetryConfig>(&content) {
-            Ok(config) => config.enabled,
-            Err(_) => false,
-        },
-        Err(_) => false,
-    }
+/// Settings from `~/.devalang/telemetry.json`; missing or unreadable files mean disabled
+pub fn load_telemetry_config() -> TelemetryConfig {
+    fs::read_to_string(get_telemetry_config_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
 }
 
-pub fn enable_telemetry() -> Result<()> {
+fn save_telemetry_config(config: &TelemetryConfig) -> Result<()> {
     let config_path = get_telemetry_config_path();
 
     // Ensure directory exists
@@ -45,26 +135,37 @@ pub fn enable_telemetry() -> Result<()> {
         fs::create_dir_all(parent)?;
     }
 
-    let config = TelemetryConfig { enabled: true };
-    let json = serde_json::to_string_pretty(&config)?;
+    let json = serde_json::to_string_pretty(config)?;
     fs::write(&config_path, json)?;
 
     Ok(())
 }
 
-pub fn disable_telemetry() -> Result<()> {
-    let config_path = get_telemetry_config_path();
+pub fn is_telemetry_enabled() -> bool {
+    let config = load_telemetry_config();
+    config.enabled && config.sink != TelemetrySink::Off
+}
 
-    // Ensure directory exists
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
+pub fn enable_telemetry() -> Result<()> {
+    save_telemetry_config(&TelemetryConfig {
+        enabled: true,
+        ..load_telemetry_config()
+    })
+}
 
-    let config = TelemetryConfig { enabled: false };
-    let json = serde_json::to_string_pretty(&config)?;
-    fs::write(&config_path, json)?;
+pub fn disable_telemetry() -> Result<()> {
+    save_telemetry_config(&TelemetryConfig {
+        enabled: false,
+        ..load_telemetry_config()
+    })
+}
 
-    Ok(())
+/// Choose where events go; the enabled flag is kept
+pub fn set_telemetry_sink(sink: TelemetrySink) -> Result<()> {
+    save_telemetry_config(&TelemetryConfig {
+        sink,
+        ..load_telemetry_config()
+    })
 }
 
 pub fn get_telemetry_status() -> String {
@@ -74,3 +175,46 @@ pub fn get_telemetry_status() -> String {
         "disabled".to_string()
     }
 }
+
+/// Hand `event` to the configured sink. Telemetry never fails a command, so delivery
+/// problems are dropped.
+pub async fn record(event: &TelemetryEvent) {
+    let config = load_telemetry_config();
+    if !config.enabled {
+        return;
+    }
+    let _ = match &config.sink {
+        TelemetrySink::Devalang => post(&devalang_endpoint(), event).await,
+        TelemetrySink::Endpoint { url } => post(url, event).await,
+        TelemetrySink::Local { path } => append_jsonl(&local_path(path.as_deref()), event),
+        TelemetrySink::Off => Ok(()),
+    };
+}
+
+async fn post(url: &str, event: &TelemetryEvent) -> Result<()> {
+    let client = reqwest::Client::builder().timeout(SEND_TIMEOUT).build()?;
+    client
+        .post(url)
+        .json(event)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Append `event` as one JSON line
+fn append_jsonl(path: &Path, event: &TelemetryEvent) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "test_telemetry.rs"]
+mod tests;
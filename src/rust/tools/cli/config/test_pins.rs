@@ -0,0 +1,59 @@
+use super::*;
+
+fn addon(id: &str, version: Option<&str>) -> InstalledAddon {
+    InstalledAddon {
+        id: id.to_string(),
+        kind: "bank",
+        version: version.map(str::to_string),
+        path: PathBuf::from(id),
+    }
+}
+
+#[test]
+fn test_parse_version_accepts_short_forms() {
+    assert_eq!(parse_version("1.2").unwrap(), Version::new(1, 2, 0));
+    assert_eq!(parse_version("v2").unwrap(), Version::new(2, 0, 0));
+    assert_eq!(parse_version("1.4.3").unwrap(), Version::new(1, 4, 3));
+    assert!(parse_version("latest").is_err());
+}
+
+#[test]
+fn test_check_pins_reports_each_issue() {
+    let installed = vec![
+        addon("devaloop.808", Some("1.4.0")),
+        addon("devaloop.909", Some("2.0.1")),
+        addon("local.kit", None),
+    ];
+    let pins: BTreeMap<String, String> = [
+        ("devaloop.808", "^1.2"),
+        ("devaloop.909", "^1.0"),
+        ("local.kit", "*"),
+        ("devaloop.acid", "~0.3"),
+        ("devaloop.fx", "one"),
+    ]
+    .into_iter()
+    .map(|(id, constraint)| (id.to_string(), constraint.to_string()))
+    .collect();
+
+    let issues: Vec<(String, PinIssue)> = check_pins(&pins, &installed)
+        .into_iter()
+        .map(|violation| (violation.addon, violation.issue))
+        .collect();
+
+    assert_eq!(issues.len(), 4);
+    assert_eq!(
+        issues[0],
+        (
+            "devaloop.909".to_string(),
+            PinIssue::Mismatch {
+                installed: "2.0.1".to_string()
+            }
+        )
+    );
+    assert_eq!(
+        issues[1],
+        ("devaloop.acid".to_string(), PinIssue::NotInstalled)
+    );
+    assert!(matches!(issues[2].1, PinIssue::InvalidConstraint(_)));
+    assert_eq!(issues[3], ("local.kit".to_string(), PinIssue::Unversioned));
+}
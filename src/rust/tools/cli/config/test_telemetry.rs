@@ -0,0 +1,48 @@
+use super::*;
+
+#[test]
+fn test_config_without_a_sink_keeps_the_devalang_endpoint() {
+    let config: TelemetryConfig = serde_json::from_str(r#"{ "enabled": true }"#).unwrap();
+    assert!(config.enabled);
+    assert_eq!(config.sink, TelemetrySink::Devalang);
+
+    let local: TelemetryConfig = serde_json::from_str(
+        r#"{ "enabled": true, "sink": { "kind": "local", "path": "/var/log/deva.jsonl" } }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        local.sink,
+        TelemetrySink::Local {
+            path: Some(PathBuf::from("/var/log/deva.jsonl"))
+        }
+    );
+}
+
+#[test]
+fn test_endpoint_sink_requires_https() {
+    assert_eq!(
+        TelemetrySink::endpoint(" https://telemetry.example.com/deva ").unwrap(),
+        TelemetrySink::Endpoint {
+            url: "https://telemetry.example.com/deva".to_string()
+        }
+    );
+    assert!(TelemetrySink::endpoint("http://telemetry.example.com").is_err());
+    assert!(TelemetrySink::endpoint("https://").is_err());
+}
+
+#[test]
+fn test_local_sink_appends_one_line_per_event() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit").join("telemetry.jsonl");
+    let event = TelemetryEvent::command("build", true, Duration::from_millis(1250));
+    append_jsonl(&path, &event).unwrap();
+    append_jsonl(&path, &event).unwrap();
+
+    let content = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let line: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(line["command"], "build");
+    assert_eq!(line["duration_ms"], 1250);
+    assert_eq!(line["success"], true);
+}
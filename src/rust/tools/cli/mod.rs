@@ -11,6 +11,8 @@ use commands::devices::DevicesListCommand;
 use commands::play::PlayCommand;
 use state::CliContext;
 
+use crate::tools::logger::{LogStyle, set_log_style};
+
 #[derive(Parser, Debug)]
 #[command(name = "devalang")]
 #[command(
@@ -20,20 +22,39 @@ use state::CliContext;
 pub struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Log output: `fancy` (emoji and colors), `plain` (neither) or `ascii` (plain, ASCII
+    /// only). Colors are also off when NO_COLOR is set.
+    #[arg(long = "log-style", value_enum, global = true, default_value = "fancy")]
+    log_style: LogStyle,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Build and play deva file(s)
-    Play(PlayCommand),
+    Play(Box<PlayCommand>),
     /// Initialize a new project
     Init(commands::init::InitCommand),
     /// Builds deva file(s)
     Build(commands::build::BuildCommand),
     /// Check syntax without building
     Check(commands::check::CheckCommand),
+    /// Inspect the project config (`validate`: check it and print the effective settings)
+    Config(commands::config::ConfigCommand),
+    /// Compare two renders, or the project's build with the previous one
+    Diff(commands::diff::DiffCommand),
+    /// Draw the routing graph (inserts, fx, routes and ducks) as DOT or Mermaid
+    Graph(commands::graph::GraphCommand),
+    /// Upgrade project sources written for older language versions
+    Migrate(commands::migrate::MigrateCommand),
     /// Manages addons (install, update, remove, list, discover)
     Addon(commands::addon::AddonCommand),
+    /// Develop plugins (new, build, test)
+    Plugin(commands::plugin::PluginCommand),
+    /// Prepare sample folders (normalize, trim, convert) into banks
+    Samples(commands::samples::SamplesCommand),
+    /// Run a server (`--render-api`: render submitted project bundles)
+    Serve(commands::serve::ServeCommand),
     /// Login to Devalang (authenticate with token)
     Login {
         /// Authentication token (optional, will prompt if not provided)
@@ -48,7 +69,7 @@ pub enum Commands {
         #[command(subcommand)]
         action: TelemetryAction,
     },
-    /// Manage MIDI devices
+    /// Manage audio output and MIDI devices
     Devices {
         #[command(subcommand)]
         action: DevicesCommands,
@@ -57,7 +78,7 @@ pub enum Commands {
 
 #[derive(Subcommand, Debug)]
 pub enum DevicesCommands {
-    /// List MIDI devices
+    /// List audio output and MIDI devices
     List(DevicesListCommand),
     /// Preview incoming/outgoing notes (non-writing)
     Preview(commands::devices::DevicesLiveCommand),
@@ -71,55 +92,152 @@ pub enum TelemetryAction {
     Enable,
     /// Disable telemetry
     Disable,
-    /// Show telemetry status
+    /// Show telemetry status, where events go and an example of what is sent
     Status,
+    /// Choose where telemetry events go
+    Sink {
+        #[command(subcommand)]
+        sink: TelemetrySinkAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TelemetrySinkAction {
+    /// Send events to Devalang (the default)
+    Devalang,
+    /// Send events to your own HTTPS endpoint
+    Endpoint {
+        /// `https://` URL receiving each event as a JSON POST
+        url: String,
+    },
+    /// Only append events to a local JSONL file (audit mode; nothing is sent)
+    Local {
+        /// File to append to (defaults to `~/.devalang/telemetry.jsonl`)
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Record nothing, even while telemetry is enabled
+    Off,
+}
+
+impl Commands {
+    /// Command name reported by telemetry
+    fn name(&self) -> &'static str {
+        match self {
+            Commands::Play(_) => "play",
+            Commands::Init(_) => "init",
+            Commands::Build(_) => "build",
+            Commands::Check(_) => "check",
+            Commands::Config(_) => "config",
+            Commands::Diff(_) => "diff",
+            Commands::Graph(_) => "graph",
+            Commands::Migrate(_) => "migrate",
+            Commands::Addon(_) => "addon",
+            Commands::Plugin(_) => "plugin",
+            Commands::Samples(_) => "samples",
+            Commands::Serve(_) => "serve",
+            Commands::Login { .. } => "login",
+            Commands::Logout => "logout",
+            Commands::Me => "me",
+            Commands::Telemetry { .. } => "telemetry",
+            Commands::Devices { .. } => "devices",
+        }
+    }
 }
 
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
+    set_log_style(cli.log_style);
     let ctx = CliContext::new();
     let runtime = tokio::runtime::Runtime::new()?;
 
     runtime.block_on(async move {
-        match cli.command {
-            Commands::Play(command) => commands::play::execute(command, &ctx).await?,
-            Commands::Init(command) => command.execute(&ctx).await?,
-            Commands::Build(command) => command.execute(&ctx).await?,
-            Commands::Check(command) => command.execute(&ctx).await?,
-            Commands::Addon(command) => command.execute(&ctx).await?,
-            Commands::Login { token } => commands::auth::login(token).await?,
-            Commands::Logout => commands::auth::logout().await?,
-            Commands::Me => commands::auth::check_auth_status().await?,
-            Commands::Telemetry { action } => {
-                let logger = ctx.logger();
-                match action {
-                    TelemetryAction::Enable => {
-                        config::telemetry::enable_telemetry()?;
-                        logger.success("Telemetry enabled");
-                        logger.info("Thank you for helping us improve Devalang!");
-                    }
-                    TelemetryAction::Disable => {
-                        config::telemetry::disable_telemetry()?;
-                        logger.success("Telemetry disabled");
-                    }
-                    TelemetryAction::Status => {
-                        let status = config::telemetry::get_telemetry_status();
-                        logger.info(format!("Telemetry is currently: {}", status));
-                    }
+        let name = cli.command.name();
+        let started = std::time::Instant::now();
+        let result = run_command(cli.command, &ctx).await;
+        if name != "telemetry" {
+            let event =
+                config::telemetry::TelemetryEvent::command(name, result.is_ok(), started.elapsed());
+            config::telemetry::record(&event).await;
+        }
+        result
+    })
+}
+
+async fn run_command(command: Commands, ctx: &CliContext) -> Result<()> {
+    match command {
+        Commands::Play(command) => commands::play::execute(*command, ctx).await?,
+        Commands::Init(command) => command.execute(ctx).await?,
+        Commands::Build(command) => command.execute(ctx).await?,
+        Commands::Check(command) => command.execute(ctx).await?,
+        Commands::Config(command) => command.execute(ctx).await?,
+        Commands::Diff(command) => command.execute(ctx).await?,
+        Commands::Graph(command) => command.execute(ctx).await?,
+        Commands::Migrate(command) => command.execute(ctx).await?,
+        Commands::Addon(command) => command.execute(ctx).await?,
+        Commands::Plugin(command) => command.execute(ctx).await?,
+        Commands::Samples(command) => command.execute(ctx).await?,
+        Commands::Serve(command) => command.execute(ctx).await?,
+        Commands::Login { token } => commands::auth::login(token).await?,
+        Commands::Logout => commands::auth::logout().await?,
+        Commands::Me => commands::auth::check_auth_status().await?,
+        Commands::Telemetry { action } => {
+            let logger = ctx.logger();
+            match action {
+                TelemetryAction::Enable => {
+                    config::telemetry::enable_telemetry()?;
+                    logger.success("Telemetry enabled");
+                    logger.info("Thank you for helping us improve Devalang!");
                 }
-            }
-            Commands::Devices { action } => match action {
-                DevicesCommands::List(cmd) => {
-                    commands::devices::execute_list(cmd, &ctx)?;
+                TelemetryAction::Disable => {
+                    config::telemetry::disable_telemetry()?;
+                    logger.success("Telemetry disabled");
                 }
-                DevicesCommands::Preview(cmd) => {
-                    commands::devices::execute_preview(cmd, &ctx).await?;
+                TelemetryAction::Status => {
+                    let status = config::telemetry::get_telemetry_status();
+                    logger.info(format!("Telemetry is currently: {}", status));
+                    let settings = config::telemetry::load_telemetry_config();
+                    logger.info(format!("Events go to: {}", settings.sink.describe()));
+                    let example = config::telemetry::TelemetryEvent::command(
+                        "build",
+                        true,
+                        std::time::Duration::from_millis(1250),
+                    );
+                    logger.info(format!(
+                        "Each command records one event like:\n{}",
+                        serde_json::to_string_pretty(&example)?
+                    ));
                 }
-                DevicesCommands::Write(cmd) => {
-                    commands::devices::execute_write(cmd, &ctx).await?;
+                TelemetryAction::Sink { sink } => {
+                    use config::telemetry::TelemetrySink;
+                    let sink = match sink {
+                        TelemetrySinkAction::Devalang => TelemetrySink::Devalang,
+                        TelemetrySinkAction::Endpoint { url } => TelemetrySink::endpoint(&url)?,
+                        TelemetrySinkAction::Local { path } => TelemetrySink::Local { path },
+                        TelemetrySinkAction::Off => TelemetrySink::Off,
+                    };
+                    let description = sink.describe();
+                    config::telemetry::set_telemetry_sink(sink)?;
+                    logger.success(format!("Telemetry events now go to: {}", description));
+                    if !config::telemetry::load_telemetry_config().enabled {
+                        logger.info(
+                            "Telemetry is disabled; `devalang telemetry enable` to turn it on",
+                        );
+                    }
                 }
-            },
+            }
         }
-        Ok(())
-    })
+        Commands::Devices { action } => match action {
+            DevicesCommands::List(cmd) => {
+                commands::devices::execute_list(cmd, ctx)?;
+            }
+            DevicesCommands::Preview(cmd) => {
+                commands::devices::execute_preview(cmd, ctx).await?;
+            }
+            DevicesCommands::Write(cmd) => {
+                commands::devices::execute_write(cmd, ctx).await?;
+            }
+        },
+    }
+    Ok(())
 }
@@ -1,2 +1,212 @@
-// Formatting helpers will live here during migration.
-pub fn placeholder() {}
+//! Log line renderers, one per `--log-style`
+//!
+//! `fancy` (the default) prefixes lines with an emoji and colors them. `plain` drops the
+//! colors and every emoji or pictographic symbol, including those inside messages, for
+//! screen readers and CI logs, and `ascii` also rewrites messages to plain ASCII. A non-empty `NO_COLOR` keeps the chosen layout but turns colors off.
+
+#[cfg(feature = "cli")]
+use super::LogLevel;
+#[cfg(feature = "cli")]
+use crossterm::style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor};
+#[cfg(feature = "cli")]
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum LogStyle {
+    #[default]
+    Fancy,
+    Plain,
+    Ascii,
+}
+
+#[cfg(feature = "cli")]
+static STYLE: AtomicU8 = AtomicU8::new(LogStyle::Fancy as u8);
+#[cfg(feature = "cli")]
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// Style every logger uses from now on; colors stay off when `NO_COLOR` is set
+#[cfg(feature = "cli")]
+pub fn set_log_style(style: LogStyle) {
+    STYLE.store(style as u8, Ordering::Relaxed);
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    COLOR.store(style == LogStyle::Fancy && !no_color, Ordering::Relaxed);
+}
+
+#[cfg(feature = "cli")]
+pub(super) fn current() -> Renderer {
+    let style = match STYLE.load(Ordering::Relaxed) {
+        1 => LogStyle::Plain,
+        2 => LogStyle::Ascii,
+        _ => LogStyle::Fancy,
+    };
+    Renderer {
+        style,
+        color: COLOR.load(Ordering::Relaxed),
+    }
+}
+
+/// Renders log lines in one style
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Renderer {
+    pub style: LogStyle,
+    pub color: bool,
+}
+
+#[cfg(feature = "cli")]
+impl Renderer {
+    /// `✅ [Devalang] [SUCCESS] message`, without the emoji unless fancy
+    pub fn line(&self, level: LogLevel, message: &str) -> String {
+        let (emoji, color) = level.visuals();
+        let mut out = String::new();
+        if self.style == LogStyle::Fancy {
+            out.push_str(emoji);
+            out.push(' ');
+        }
+        out.push_str(&self.paint("[", Some(Color::Grey), false));
+        out.push_str(&self.paint(
+            "Devalang",
+            Some(Color::Rgb {
+                r: 36,
+                g: 199,
+                b: 181,
+            }),
+            true,
+        ));
+        out.push_str(&self.paint("]", Some(Color::Grey), false));
+        out.push(' ');
+        out.push_str(&self.paint(&format!("[{}]", level.as_label()), Some(color), true));
+        out.push(' ');
+        out.push_str(&self.text(message));
+        out
+    }
+
+    /// `   ↳ detail`
+    pub fn detail(&self, detail: &str) -> String {
+        format!("   {} {}", self.arrow(), self.text(detail))
+    }
+
+    /// `   ↳ label: content`, the label in bold grey
+    pub fn labeled_detail(&self, label: &str, content: &str) -> String {
+        format!(
+            "   {} {}: {}",
+            self.arrow(),
+            self.paint(
+                label,
+                Some(Color::Rgb {
+                    r: 110,
+                    g: 110,
+                    b: 110
+                }),
+                true
+            ),
+            self.paint(&self.text(content), Some(Color::White), false)
+        )
+    }
+
+    fn arrow(&self) -> &'static str {
+        match self.style {
+            LogStyle::Ascii => "->",
+            LogStyle::Fancy | LogStyle::Plain => "↳",
+        }
+    }
+
+    fn text(&self, text: &str) -> String {
+        match self.style {
+            LogStyle::Ascii => to_ascii(&strip_emoji(text)),
+            LogStyle::Plain => strip_emoji(text),
+            LogStyle::Fancy => text.to_string(),
+        }
+    }
+
+    fn paint(&self, text: &str, color: Option<Color>, bold: bool) -> String {
+        if !self.color {
+            return text.to_string();
+        }
+        let mut out = String::new();
+        if let Some(color) = color {
+            out.push_str(&SetForegroundColor(color).to_string());
+        }
+        if bold {
+            out.push_str(&SetAttribute(Attribute::Bold).to_string());
+        }
+        out.push_str(text);
+        if bold {
+            out.push_str(&SetAttribute(Attribute::Reset).to_string());
+        }
+        out.push_str(&ResetColor.to_string());
+        out
+    }
+}
+
+/// Emoji and pictographic symbols (`✅`, `✗`, `⏱️`, `🎵`), with their variation
+/// selectors and joiners; arrows and math symbols are text and stay
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x2300..=0x23FF
+            | 0x2600..=0x27BF
+            | 0x2B00..=0x2BFF
+            | 0x1F000..=0x1FAFF
+            | 0x200D
+            | 0x20E3
+            | 0xFE0E..=0xFE0F
+            | 0xE0020..=0xE007F
+    )
+}
+
+/// `text` without emoji, each dropped with the space that separated it from the next word
+pub fn strip_emoji(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !is_emoji(c) {
+            out.push(c);
+            continue;
+        }
+        if chars.peek().is_none_or(|next| !is_emoji(*next))
+            && (out.is_empty() || out.ends_with(' '))
+            && chars.peek() == Some(&' ')
+        {
+            chars.next();
+        }
+    }
+    if out.ends_with(' ') && !text.ends_with(' ') {
+        out.truncate(out.trim_end().len());
+    }
+    out
+}
+
+/// `text` with the symbols the CLI uses spelled out in ASCII and anything else outside
+/// ASCII (emoji) dropped
+pub fn to_ascii(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            c if c.is_ascii() => out.push(c),
+            '→' | '↳' => out.push_str("->"),
+            '←' => out.push_str("<-"),
+            '≈' => out.push('~'),
+            '±' => out.push_str("+/-"),
+            '×' => out.push('x'),
+            '…' => out.push_str("..."),
+            '–' | '—' => out.push('-'),
+            '‘' | '’' => out.push('\''),
+            '“' | '”' => out.push('"'),
+            '•' | '·' => out.push('*'),
+            '≥' => out.push_str(">="),
+            '≤' => out.push_str("<="),
+            _ => {}
+        }
+    }
+    // A dropped leading emoji leaves its separating space behind
+    if text.chars().next().is_some_and(|c| !c.is_ascii()) {
+        return out.trim_start().to_string();
+    }
+    out
+}
+
+#[cfg(test)]
+#[path = "test_format.rs"]
+mod tests;
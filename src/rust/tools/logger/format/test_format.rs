@@ -0,0 +1,65 @@
+use super::*;
+
+#[test]
+fn test_plain_and_ascii_lines_have_no_emoji_or_escapes() {
+    let plain = Renderer {
+        style: LogStyle::Plain,
+        color: false,
+    };
+    assert_eq!(
+        plain.line(LogLevel::Info, "Loop length ≈ 2.00s"),
+        "[Devalang] [INFO] Loop length ≈ 2.00s"
+    );
+    assert_eq!(plain.detail("path"), "   ↳ path");
+    assert_eq!(
+        plain.line(LogLevel::Success, "⏱️ New best audio regen time: 12ms"),
+        "[Devalang] [SUCCESS] New best audio regen time: 12ms"
+    );
+    assert_eq!(
+        plain.line(LogLevel::Error, "kick.wav ✗ missing, snare.wav ✅"),
+        "[Devalang] [ERROR] kick.wav missing, snare.wav"
+    );
+    assert_eq!(
+        plain.labeled_detail("bank", "🥁 drums → café"),
+        "   ↳ bank: drums → café"
+    );
+
+    let ascii = Renderer {
+        style: LogStyle::Ascii,
+        color: false,
+    };
+    assert_eq!(
+        ascii.line(LogLevel::Success, "⏱️ New best audio regen time: 12ms"),
+        "[Devalang] [SUCCESS] New best audio regen time: 12ms"
+    );
+    assert_eq!(
+        ascii.labeled_detail("help", "a → b ≈ ±1"),
+        "   -> help: a -> b ~ +/-1"
+    );
+    assert!(ascii.line(LogLevel::Warning, "🎵 done").is_ascii());
+    assert_eq!(
+        ascii.line(LogLevel::Error, "kick.wav ✗ missing"),
+        "[Devalang] [ERROR] kick.wav missing"
+    );
+}
+
+#[test]
+fn test_fancy_keeps_the_emoji_and_colors_only_when_enabled() {
+    let fancy = Renderer {
+        style: LogStyle::Fancy,
+        color: true,
+    };
+    let line = fancy.line(LogLevel::Success, "built");
+    assert!(line.starts_with("✅ "));
+    assert!(line.contains('\u{1b}'));
+
+    // NO_COLOR keeps the layout but drops the escapes
+    let no_color = Renderer {
+        style: LogStyle::Fancy,
+        color: false,
+    };
+    assert_eq!(
+        no_color.line(LogLevel::Success, "built"),
+        "✅ [Devalang] [SUCCESS] built"
+    );
+}
@@ -1,7 +1,7 @@
 #[cfg(feature = "cli")]
-use crossterm::style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::style::Color;
 #[cfg(feature = "cli")]
-use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub mod rule_checker;
 pub use rule_checker::{RuleChecker, RuleMessage};
@@ -21,6 +21,26 @@ pub enum LogLevel {
 #[derive(Debug, Clone, Default)]
 pub struct Logger;
 
+/// Set while the terminal is in raw mode (live keyboard input)
+#[cfg(feature = "cli")]
+static RAW_TERMINAL: AtomicBool = AtomicBool::new(false);
+
+/// Tell the logger whether the terminal is in raw mode, where a bare `\n` does not
+/// return the cursor to the start of the line
+#[cfg(feature = "cli")]
+pub fn set_raw_terminal(enabled: bool) {
+    RAW_TERMINAL.store(enabled, Ordering::Relaxed);
+}
+
+#[cfg(feature = "cli")]
+fn emit_line(line: &str) {
+    if RAW_TERMINAL.load(Ordering::Relaxed) {
+        print!("{}\r\n", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
 impl Logger {
     pub fn new() -> Self {
         Self
@@ -105,7 +125,7 @@ impl Logger {
     fn print_detail(&self, detail: &str) {
         #[cfg(feature = "cli")]
         {
-            println!("   ↳ {}", detail);
+            emit_line(&format::current().detail(detail));
         }
         #[cfg(not(feature = "cli"))]
         {
@@ -113,105 +133,28 @@ impl Logger {
         }
     }
 
-    /// Print a colored detail line with a label
-    /// Format: "   ↳ label: content" where label is in medium-dark grey
-    #[cfg(feature = "cli")]
-    fn print_colored_detail(&self, label: &str, content: &str) {
-        let mut output = String::new();
-        output.push_str("   ↳ ");
-
-        // Label in medium-dark grey (RGB 110, 110, 110) with bold
-        output.push_str(&format!(
-            "{}{}{}{}",
-            SetForegroundColor(Color::Rgb {
-                r: 110,
-                g: 110,
-                b: 110
-            }),
-            SetAttribute(Attribute::Bold),
-            label,
-            SetAttribute(Attribute::Reset)
-        ));
-
-        // Colon separator
-        output.push_str(": ");
-
-        // Content in normal color (white)
-        output.push_str(&format!("{}{}", SetForegroundColor(Color::White), content));
-
-        output.push_str(&format!("{}", ResetColor));
-
-        println!("{}", output);
-    }
-
-    /// Print a colored detail line with a label (non-CLI version)
-    #[cfg(not(feature = "cli"))]
+    /// Print a detail line with a label: "   ↳ label: content"
     fn print_colored_detail(&self, label: &str, content: &str) {
-        println!("   -> {}: {}", label, content);
+        #[cfg(feature = "cli")]
+        {
+            emit_line(&format::current().labeled_detail(label, content));
+        }
+        #[cfg(not(feature = "cli"))]
+        {
+            println!("   -> {}: {}", label, content);
+        }
     }
 
     fn print_line(&self, level: LogLevel, message: &str) {
         #[cfg(feature = "cli")]
         {
-            println!("{}", self.render_colored_line(level, message));
+            emit_line(&format::current().line(level, message));
         }
         #[cfg(not(feature = "cli"))]
         {
             println!("[{}] {}", level.as_plain_label(), message);
         }
     }
-
-    #[cfg(feature = "cli")]
-    fn render_colored_line(&self, level: LogLevel, message: &str) -> String {
-        let mut out = String::new();
-        let (emoji, color) = level.visuals();
-
-        out.push_str(emoji);
-        out.push(' ');
-        out.push_str(&self.render_signature());
-        out.push(' ');
-        out.push_str(&self.render_status(level, color));
-        out.push(' ');
-        out.push_str(message);
-        out
-    }
-
-    #[cfg(feature = "cli")]
-    fn render_signature(&self) -> String {
-        let mut s = String::new();
-        write!(&mut s, "{}", SetForegroundColor(Color::Grey)).unwrap();
-        s.push('[');
-        write!(
-            &mut s,
-            "{}",
-            SetForegroundColor(Color::Rgb {
-                r: 36,
-                g: 199,
-                b: 181,
-            })
-        )
-        .unwrap();
-        write!(&mut s, "{}", SetAttribute(Attribute::Bold)).unwrap();
-        s.push_str("Devalang");
-        write!(&mut s, "{}", SetAttribute(Attribute::Reset)).unwrap();
-        write!(&mut s, "{}", SetForegroundColor(Color::Grey)).unwrap();
-        s.push(']');
-        write!(&mut s, "{}", ResetColor).unwrap();
-        s
-    }
-
-    #[cfg(feature = "cli")]
-    fn render_status(&self, level: LogLevel, color: Color) -> String {
-        let mut s = String::new();
-        write!(&mut s, "{}", SetForegroundColor(color)).unwrap();
-        write!(&mut s, "{}", SetAttribute(Attribute::Bold)).unwrap();
-        s.push('[');
-        s.push_str(level.as_label());
-        s.push(']');
-        write!(&mut s, "{}", SetAttribute(Attribute::Reset)).unwrap();
-        write!(&mut s, "{}", ResetColor).unwrap();
-        s
-    }
 }
 
 impl LogLevel {
@@ -310,4 +253,7 @@ pub mod layers;
 pub mod sinks;
 pub mod structured_error;
 
+pub use format::LogStyle;
+#[cfg(feature = "cli")]
+pub use format::set_log_style;
 pub use structured_error::StructuredError;
@@ -116,6 +116,32 @@ impl RuleChecker {
         })
     }
 
+    /// Check for a bank-less trigger that several banks provide
+    pub fn check_ambiguous_trigger(
+        &self,
+        line_number: usize,
+        trigger: &str,
+        chosen: &str,
+        shadowed: &[String],
+    ) -> Option<RuleMessage> {
+        let level = self.config.rules.ambiguous_triggers;
+        if !level.should_report() {
+            return None;
+        }
+
+        Some(RuleMessage {
+            level,
+            rule_name: "ambiguous_triggers",
+            message: format!(
+                "Line {}: '.{}' resolves to {} but is also in {}; name the bank to be explicit",
+                line_number,
+                trigger,
+                chosen,
+                shadowed.join(", ")
+            ),
+        })
+    }
+
     /// Check for unused variables
     pub fn check_unused_variable(&self, line_number: usize, var_name: &str) -> Option<RuleMessage> {
         let level = self.config.rules.unused_variables;
@@ -7,8 +7,10 @@ pub struct StructuredError {
     pub file_path: Option<String>,
     /// Line number in the file
     pub line: Option<usize>,
-    /// Column number in the file
+    /// Column number in the file (1-based, counted in characters)
     pub column: Option<usize>,
+    /// Column just past the offending token, for editors that underline a range
+    pub end_column: Option<usize>,
     /// Error type/category (e.g., "SyntaxError", "UnknownStatement", "RuntimeError")
     pub error_type: Option<String>,
     /// Optional "Did you mean ... ?" suggestion
@@ -25,6 +27,7 @@ impl StructuredError {
             file_path: None,
             line: None,
             column: None,
+            end_column: None,
             error_type: None,
             suggestion: None,
             stacktrace: Vec::new(),
@@ -44,6 +47,14 @@ impl StructuredError {
         self
     }
 
+    /// Set line and the character columns `start..end` of the offending token
+    pub fn with_span(mut self, line: usize, start_column: usize, end_column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(start_column);
+        self.end_column = Some(end_column);
+        self
+    }
+
     /// Set error type
     pub fn with_type(mut self, error_type: impl Into<String>) -> Self {
         self.error_type = Some(error_type.into());
@@ -144,6 +155,16 @@ mod tests {
         assert_eq!(error.column, Some(3));
         assert_eq!(error.error_type, Some("SyntaxError".to_string()));
         assert_eq!(error.suggestion, Some("Did you mean 'sleep' ?".to_string()));
+        assert_eq!(error.end_column, None);
+    }
+
+    #[test]
+    fn test_structured_error_span() {
+        let error = StructuredError::new("Unknown statement").with_span(2, 7, 12);
+
+        assert_eq!(error.line, Some(2));
+        assert_eq!(error.column, Some(7));
+        assert_eq!(error.end_column, Some(12));
     }
 
     #[test]
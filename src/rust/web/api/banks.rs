@@ -74,6 +74,210 @@ pub fn register_bank_json(manifest_json: &str) -> Result<(), JsValue> {
     Ok(())
 }
 
+/// Trigger registered from a bank archive
+#[derive(Serialize)]
+struct ZipTrigger {
+    name: String,
+    uri: String,
+    relative: String,
+    frames: usize,
+}
+
+#[derive(Serialize)]
+struct ZipBankResponse {
+    ok: bool,
+    bank: String,
+    alias: String,
+    triggers: Vec<ZipTrigger>,
+    /// Archive entries that could not be used (missing file, WAV parse error)
+    skipped: Vec<String>,
+}
+
+/// Size limits for an archive dropped into the playground
+struct ZipLimits {
+    /// Size of the archive itself
+    archive_bytes: usize,
+    /// Largest single file once unpacked
+    entry_bytes: u64,
+    /// All files together once unpacked
+    unpacked_bytes: u64,
+    files: usize,
+}
+
+impl ZipLimits {
+    const BANK: ZipLimits = ZipLimits {
+        archive_bytes: 64 * 1024 * 1024,
+        entry_bytes: 32 * 1024 * 1024,
+        unpacked_bytes: 256 * 1024 * 1024,
+        files: 2048,
+    };
+}
+
+/// Read every file of a zip archive into memory, keyed by its `/`-separated path.
+/// Sizes are counted from the bytes actually inflated, not the sizes the archive declares.
+fn unpack_zip(bytes: &[u8], limits: &ZipLimits) -> Result<HashMap<String, Vec<u8>>, String> {
+    if bytes.len() > limits.archive_bytes {
+        return Err(format!(
+            "Zip archive is larger than {} bytes",
+            limits.archive_bytes
+        ));
+    }
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("Invalid zip archive: {}", e))?;
+
+    let mut files = HashMap::new();
+    let mut unpacked = 0u64;
+    for idx in 0..archive.len() {
+        let entry = archive
+            .by_index(idx)
+            .map_err(|e| format!("Zip entry error: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        if files.len() == limits.files {
+            return Err(format!("Zip archive has more than {} files", limits.files));
+        }
+        let name = entry.name().replace('\\', "/");
+        let mut data = Vec::new();
+        std::io::Read::read_to_end(
+            &mut std::io::Read::take(entry, limits.entry_bytes + 1),
+            &mut data,
+        )
+        .map_err(|e| format!("Zip read error '{}': {}", name, e))?;
+        if data.len() as u64 > limits.entry_bytes {
+            return Err(format!(
+                "Zip entry '{}' unpacks to more than {} bytes",
+                name, limits.entry_bytes
+            ));
+        }
+        unpacked += data.len() as u64;
+        if unpacked > limits.unpacked_bytes {
+            return Err(format!(
+                "Zip archive unpacks to more than {} bytes",
+                limits.unpacked_bytes
+            ));
+        }
+        files.insert(name, data);
+    }
+    Ok(files)
+}
+
+/// Register a bank from a zip archive (e.g. dropped into the playground)
+///
+/// The archive either holds a `bank.toml` (at its root or in one folder), whose trigger
+/// paths are resolved like `register_bank_from_manifest`, or only WAV files, each
+/// becoming a trigger named after its file. `alias` is the name used in code; it
+/// defaults to the bank name, or `user` for plain archives.
+///
+/// Samples are registered with URI: devalang://bank/{publisher.name}/{path}. Loading a
+/// bank with the same name again replaces it, so the next render uses the new samples.
+/// Archives over 64 MB, files over 32 MB, more than 256 MB unpacked or more than 2048
+/// files are refused.
+///
+/// Returns: { ok, bank, alias, triggers: [{ name, uri, relative, frames }], skipped }
+#[wasm_bindgen]
+pub fn register_bank_from_zip(bytes: &[u8], alias: Option<String>) -> Result<JsValue, JsValue> {
+    let files = unpack_zip(bytes, &ZipLimits::BANK).map_err(|e| JsValue::from_str(&e))?;
+
+    // Shallowest manifest wins; trigger paths are relative to its folder
+    let manifest = files
+        .keys()
+        .filter(|name| *name == "bank.toml" || name.ends_with("/bank.toml"))
+        .min_by_key(|name| name.matches('/').count())
+        .cloned();
+
+    // (trigger name, path inside the archive, path relative to the bank)
+    let mut entries: Vec<(String, String, String)> = Vec::new();
+    let (full_name, alias) = if let Some(manifest) = manifest {
+        let root = manifest.trim_end_matches("bank.toml").to_string();
+        let toml_str = std::str::from_utf8(&files[&manifest])
+            .map_err(|_| JsValue::from_str("bank.toml is not text"))?;
+        let parsed: BankToml = toml::from_str(toml_str)
+            .map_err(|e| JsValue::from_str(&format!("TOML parse error: {}", e)))?;
+
+        let mut audio_path_norm = parsed.bank.audio_path.replace('\\', "/");
+        if !audio_path_norm.ends_with('/') {
+            audio_path_norm.push('/');
+        }
+        while audio_path_norm.starts_with('/') {
+            audio_path_norm.remove(0);
+        }
+        for trigger in &parsed.triggers {
+            let clean_path = sanitize_trigger_path(&trigger.path, &audio_path_norm);
+            let in_audio_dir = format!("{}{}{}", root, audio_path_norm, clean_path);
+            let archive_path = if files.contains_key(&in_audio_dir) {
+                in_audio_dir
+            } else {
+                format!("{}{}", root, clean_path)
+            };
+            entries.push((trigger.name.clone(), archive_path, clean_path));
+        }
+
+        let publisher = parsed.bank.publisher.unwrap_or_else(|| "user".to_string());
+        let full_name = format!("{}.{}", publisher, parsed.bank.name);
+        (full_name, alias.unwrap_or(parsed.bank.name))
+    } else {
+        let mut wavs: Vec<&String> = files
+            .keys()
+            .filter(|name| name.to_lowercase().ends_with(".wav"))
+            .collect();
+        wavs.sort();
+        for path in wavs {
+            let file_name = path.rsplit('/').next().unwrap_or(path);
+            let stem = &file_name[..file_name.len() - ".wav".len()];
+            entries.push((stem.to_string(), path.clone(), path.clone()));
+        }
+        let alias = alias.unwrap_or_else(|| "user".to_string());
+        (format!("user.{}", alias), alias)
+    };
+
+    let mut triggers = Vec::new();
+    let mut skipped = Vec::new();
+    let mut triggers_map = HashMap::new();
+    for (name, archive_path, relative) in entries {
+        let Some(data) = files.get(&archive_path) else {
+            skipped.push(format!("{}: missing {}", name, archive_path));
+            continue;
+        };
+        match wav_parser::parse_wav_generic(data) {
+            Ok((_channels, sample_rate, mono_i16)) => {
+                let uri = format!("devalang://bank/{}/{}", full_name, relative);
+                let frames = mono_i16.len();
+                samples::register_sample_at_rate(uri.clone(), mono_i16, sample_rate);
+                triggers_map.insert(name.clone(), uri.clone());
+                triggers.push(ZipTrigger {
+                    name,
+                    uri,
+                    relative,
+                    frames,
+                });
+            }
+            Err(e) => {
+                debug::log_sample_load(format!("❌ WAV parse error '{}': {}", archive_path, e));
+                skipped.push(format!("{}: {}", archive_path, e));
+            }
+        }
+    }
+
+    banks::register_bank(full_name.clone(), alias.clone(), triggers_map);
+    debug::log_playback_debug(format!(
+        "Registered bank from zip: {} as '{}' ({} triggers)",
+        full_name,
+        alias,
+        triggers.len()
+    ));
+
+    let response = ZipBankResponse {
+        ok: true,
+        bank: full_name,
+        alias,
+        triggers,
+        skipped,
+    };
+    serde_wasm_bindgen::to_value(&response)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
 fn default_audio_path() -> String {
     "audio/".to_string()
 }
@@ -519,3 +723,7 @@ async fn register_bank_from_manifest_json(manifest_url: &str) -> Result<JsValue,
     serde_wasm_bindgen::to_value(&response)
         .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
 }
+
+#[cfg(test)]
+#[path = "test_banks.rs"]
+mod tests;
@@ -22,7 +22,7 @@ pub struct ExportOptions {
     pub bit_depth: u8, // 16, 24, or 32
 
     #[serde(default = "default_format")]
-    pub format: String, // "wav", "mp3", "ogg", "flac", "opus"
+    pub format: String, // "wav", "mp3", "ogg", "flac", "alac", "opus"
 
     #[serde(default = "default_mp3_bitrate")]
     pub mp3_bitrate: u32, // 128, 192, 256, 320 (for MP3/OGG/Opus)
@@ -105,7 +105,7 @@ pub fn get_render_metadata(user_code: &str, options: JsValue) -> Result<JsValue,
         .map_err(|e| to_js_error(&format!("Event collection error: {}", e)))?;
 
     // Get duration and event count
-    let duration = interpreter.events().total_duration();
+    let duration = interpreter.calculate_total_duration();
     let event_count = interpreter.events().events.len();
     let sample_count = (duration * opts.sample_rate as f32) as usize;
 
@@ -119,6 +119,11 @@ pub fn get_render_metadata(user_code: &str, options: JsValue) -> Result<JsValue,
             // Compressed formats: (bitrate * duration) / 8
             ((opts.mp3_bitrate as f32 * duration) / 8.0) as usize
         }
+        "alac" | "m4a" => {
+            // ALAC frames are stored uncompressed
+            let bytes_per_sample = (opts.bit_depth.clamp(16, 24) / 8) as usize;
+            sample_count * 2 * bytes_per_sample + 1024
+        }
         "flac" => {
             // FLAC is lossless but compressed, estimate ~50-60% of WAV size
             let bytes_per_sample = (opts.bit_depth / 8) as usize;
@@ -201,7 +206,7 @@ pub fn export_audio(
 
         let format = AudioFormat::from_str(&opts.format).ok_or_else(|| {
             to_js_error(&format!(
-                "Unsupported format: '{}'. Supported formats: wav, mp3, ogg, flac, opus",
+                "Unsupported format: '{}'. Supported formats: wav, mp3, ogg, flac, alac, opus",
                 opts.format
             ))
         })?;
@@ -211,6 +216,7 @@ pub fn export_audio(
             AudioFormat::Mp3 => EncoderOptions::mp3(opts.sample_rate, opts.mp3_bitrate),
             AudioFormat::Ogg => EncoderOptions::ogg(opts.sample_rate, opts.quality),
             AudioFormat::Flac => EncoderOptions::flac(opts.sample_rate, opts.bit_depth),
+            AudioFormat::Alac => EncoderOptions::alac(opts.sample_rate, opts.bit_depth),
             AudioFormat::Opus => {
                 let mut opt = EncoderOptions::default();
                 opt.format = AudioFormat::Opus;
@@ -102,8 +102,14 @@ pub fn render_midi_array(
 
     // Convert to MIDI bytes using engine function
     use crate::engine::audio::midi::events_to_midi_bytes;
-    let midi_bytes = events_to_midi_bytes(events, opts.bpm)
-        .map_err(|e| to_js_error(&format!("MIDI export error: {}", e)))?;
+    let midi_bytes = events_to_midi_bytes(
+        events,
+        opts.bpm,
+        &interpreter.tempo_map,
+        Some(&interpreter.automation_registry),
+        &interpreter.events().markers,
+    )
+    .map_err(|e| to_js_error(&format!("MIDI export error: {}", e)))?;
 
     // Convert to Uint8Array
     let array = Uint8Array::new_with_length(midi_bytes.len() as u32);
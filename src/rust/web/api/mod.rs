@@ -5,6 +5,9 @@
 pub mod banks;
 pub mod export;
 pub mod midi;
+pub mod notebook;
 pub mod parse;
 pub mod playback;
 pub mod render;
+pub mod session;
+pub mod stream;
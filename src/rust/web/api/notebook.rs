@@ -0,0 +1,211 @@
+//! Notebook API for WASM
+//!
+//! Cell-based editors evaluate a piece one cell at a time against a persistent
+//! interpreter instead of resubmitting the whole program:
+//!
+//! ```js
+//! const id = create_session({ sample_rate: 44100, bpm: 120 });
+//! eval_cell(id, "let lead = synth saw");
+//! const { new_events } = eval_cell(id, "lead -> note(C4) -> duration(1/4)");
+//! const audio = render_session(id);          // everything so far
+//! const intro = render_session(id, 0.0, 4.0); // or a range, in seconds
+//! close_session(id);
+//! ```
+//!
+//! Variables, synths, tempo and the timeline cursor carry over between cells, so running
+//! a cell again adds its events a second time; `reset_session` starts over. A cell that
+//! fails to parse changes nothing, while one that fails while running keeps the events it
+//! collected before the error.
+//!
+//! Each session keeps its variables to itself: they are not shared with other sessions
+//! or with `render_audio`, and the playground session store is left alone.
+
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::engine::embed::DevalangEngine;
+use crate::language::syntax::parser::driver::LocatedParseError;
+use crate::web::registry::{banks, debug};
+use crate::web::utils::errors::to_js_error;
+use js_sys::Float32Array;
+
+#[derive(Clone, Deserialize)]
+pub struct NotebookOptions {
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u32,
+    #[serde(default = "default_bpm")]
+    pub bpm: f32,
+}
+
+fn default_sample_rate() -> u32 {
+    44100
+}
+fn default_bpm() -> f32 {
+    120.0
+}
+
+impl Default for NotebookOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            bpm: 120.0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CellResult {
+    /// Events the cell added
+    pub new_events: usize,
+    /// Events collected by every cell so far
+    pub event_count: usize,
+    /// Seconds until the last collected sound has rung out
+    pub duration: f32,
+    pub bpm: f32,
+    /// Variables the cell defined or changed, sorted
+    pub variables: Vec<String>,
+}
+
+struct Notebook {
+    engine: DevalangEngine,
+    /// Options the session was created with, for `reset_session`
+    options: NotebookOptions,
+}
+
+thread_local! {
+    static NOTEBOOKS: RefCell<HashMap<u32, Notebook>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<u32> = const { Cell::new(1) };
+}
+
+fn with_notebook<T>(
+    id: u32,
+    f: impl FnOnce(&mut Notebook) -> Result<T, JsValue>,
+) -> Result<T, JsValue> {
+    NOTEBOOKS.with(|notebooks| {
+        let mut notebooks = notebooks.borrow_mut();
+        let notebook = notebooks
+            .get_mut(&id)
+            .ok_or_else(|| to_js_error(&format!("Unknown notebook session {}", id)))?;
+        f(notebook)
+    })
+}
+
+/// Interpreter with the registered banks, as `render_audio` starts from
+fn new_engine(opts: &NotebookOptions) -> DevalangEngine {
+    let mut engine = DevalangEngine::new(opts.sample_rate);
+    let interpreter = engine.interpreter_mut();
+    interpreter.bpm = opts.bpm;
+    banks::inject_registered_banks(interpreter);
+    engine
+}
+
+/// Start an empty notebook session and return its id
+#[wasm_bindgen]
+pub fn create_session(options: JsValue) -> Result<u32, JsValue> {
+    let opts: NotebookOptions = if options.is_undefined() || options.is_null() {
+        NotebookOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| to_js_error(&format!("Invalid options: {}", e)))?
+    };
+
+    let id = NEXT_ID.with(|next| {
+        let id = next.get();
+        next.set(id.wrapping_add(1).max(1));
+        id
+    });
+    NOTEBOOKS.with(|notebooks| {
+        notebooks.borrow_mut().insert(
+            id,
+            Notebook {
+                engine: new_engine(&opts),
+                options: opts,
+            },
+        );
+    });
+    Ok(id)
+}
+
+/// Run one cell of code in session `id`, after the cells evaluated before it
+#[wasm_bindgen]
+pub fn eval_cell(id: u32, code: &str) -> Result<JsValue, JsValue> {
+    with_notebook(id, |Notebook { engine, .. }| {
+        let changes = match engine.eval_cell(code) {
+            Ok(changes) => changes,
+            Err(e) => {
+                if debug::is_debug_errors_enabled() {
+                    match e.downcast_ref::<LocatedParseError>() {
+                        Some(located) => debug::push_parse_error_with_span(
+                            format!("Parse error: {}", located),
+                            located.line,
+                            &located.span,
+                            "ParseError".to_string(),
+                        ),
+                        None => {
+                            let (line, column) = engine
+                                .interpreter_mut()
+                                .current_statement_location()
+                                .unwrap_or((0, 0));
+                            debug::push_parse_error_from_parts(
+                                format!("{}", e),
+                                line,
+                                column,
+                                "RuntimeError".to_string(),
+                            );
+                        }
+                    }
+                }
+                return Err(to_js_error(&format!("Cell error: {}", e)));
+            }
+        };
+
+        let result = CellResult {
+            new_events: changes.new_events,
+            event_count: engine.events().len(),
+            duration: engine.duration(),
+            bpm: engine.bpm(),
+            variables: changes.variables,
+        };
+        serde_wasm_bindgen::to_value(&result)
+            .map_err(|e| to_js_error(&format!("Serialization error: {}", e)))
+    })
+}
+
+/// Render session `id` as interleaved stereo: everything evaluated so far, or the range
+/// `start..end` (seconds) of it
+#[wasm_bindgen]
+pub fn render_session(
+    id: u32,
+    start: Option<f32>,
+    end: Option<f32>,
+) -> Result<Float32Array, JsValue> {
+    let buffer = with_notebook(id, |Notebook { engine, .. }| {
+        let range = start.unwrap_or(0.0)..end.unwrap_or(f32::MAX);
+        engine
+            .render(range)
+            .map_err(|e| to_js_error(&format!("Render error: {}", e)))
+    })?;
+
+    let array = Float32Array::new_with_length(buffer.len() as u32);
+    array.copy_from(&buffer);
+    Ok(array)
+}
+
+/// Forget the cells of session `id` and start it over with its original options
+#[wasm_bindgen]
+pub fn reset_session(id: u32) -> Result<(), JsValue> {
+    with_notebook(id, |notebook| {
+        notebook.engine = new_engine(&notebook.options);
+        Ok(())
+    })
+}
+
+/// Drop session `id`; unknown ids are ignored
+#[wasm_bindgen]
+pub fn close_session(id: u32) {
+    NOTEBOOKS.with(|notebooks| {
+        notebooks.borrow_mut().remove(&id);
+    });
+}
@@ -101,8 +101,16 @@ pub fn register_addon(path: &str) -> Result<JsValue, JsValue> {
 }
 
 /// Register a sample with PCM data
+///
+/// `rate` is the sample rate of `pcm` (defaults to 44100); other rates are converted
+/// so user samples dropped into the playground play at their recorded pitch.
+/// Registering an existing URI again replaces the sample for the next render.
 #[wasm_bindgen]
-pub fn register_sample(uri: &str, pcm: &js_sys::Float32Array) -> Result<JsValue, JsValue> {
+pub fn register_sample(
+    uri: &str,
+    pcm: &js_sys::Float32Array,
+    rate: Option<u32>,
+) -> Result<JsValue, JsValue> {
     // Convert Float32Array to Vec<i16>
     let pcm_f32 = pcm.to_vec();
     let pcm_i16: Vec<i16> = pcm_f32
@@ -111,10 +119,11 @@ pub fn register_sample(uri: &str, pcm: &js_sys::Float32Array) -> Result<JsValue,
         .collect();
 
     let sample_count = pcm_i16.len();
-    samples::register_sample(uri.to_string(), pcm_i16);
+    let rate = rate.unwrap_or(samples::REGISTRY_SAMPLE_RATE);
+    samples::register_sample_at_rate(uri.to_string(), pcm_i16, rate);
     debug::log_sample_load(format!(
-        "Registered sample: {} ({} samples)",
-        uri, sample_count
+        "Registered sample: {} ({} samples at {} Hz)",
+        uri, sample_count, rate
     ));
 
     Ok(JsValue::from_bool(true))
@@ -6,9 +6,14 @@ use js_sys::{Float32Array, Uint8Array};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+use crate::engine::audio::diagnostics::{SilenceReport, clipping_warning};
 use crate::engine::audio::interpreter::driver::AudioInterpreter;
-use crate::language::syntax::parser::driver::SimpleParser;
-use crate::web::registry::banks;
+use crate::language::diagnostics::usage::unused_variables;
+use crate::language::syntax::ast::{Statement, StatementKind};
+use crate::language::syntax::parser::driver::{LocatedParseError, SimpleParser};
+use crate::shared::StoreSnapshot;
+use crate::web::registry::debug::RuntimeWarning;
+use crate::web::registry::{banks, session};
 use crate::web::utils::errors::to_js_error;
 
 #[derive(Serialize, Deserialize)]
@@ -17,6 +22,9 @@ pub struct RenderOptions {
     pub sample_rate: u32,
     #[serde(default = "default_bpm")]
     pub bpm: f32,
+    /// Variables to start from, e.g. the `store` of an `export_state()` snapshot
+    #[serde(default)]
+    pub state: Option<StoreSnapshot>,
 }
 
 fn default_sample_rate() -> u32 {
@@ -31,6 +39,7 @@ impl Default for RenderOptions {
         Self {
             sample_rate: 44100,
             bpm: 120.0,
+            state: None,
         }
     }
 }
@@ -42,6 +51,10 @@ pub struct DebugRenderResult {
     pub duration: f32,
     pub event_count: usize,
     pub bpm: f32,
+    /// Missing triggers, unresolved banks, clipping, silence and unused variables
+    pub warnings: Vec<RuntimeWarning>,
+    /// Levels over time, insert activity and dropped triggers, to explain a quiet render
+    pub silence: SilenceReport,
 }
 
 /// Render audio from Devalang code
@@ -73,12 +86,20 @@ pub fn render_audio(user_code: &str, options: JsValue) -> Result<Float32Array, J
     let statements = SimpleParser::parse(user_code, std::path::PathBuf::from("wasm_input.deva"))
         .map_err(|e| {
             if debug::is_debug_errors_enabled() {
-                debug::push_parse_error_from_parts(
-                    format!("Parse error: {:?}", e),
-                    0,
-                    0,
-                    "ParseError".to_string(),
-                );
+                match e.downcast_ref::<LocatedParseError>() {
+                    Some(located) => debug::push_parse_error_with_span(
+                        format!("Parse error: {}", located),
+                        located.line,
+                        &located.span,
+                        "ParseError".to_string(),
+                    ),
+                    None => debug::push_parse_error_from_parts(
+                        format!("Parse error: {:?}", e),
+                        0,
+                        0,
+                        "ParseError".to_string(),
+                    ),
+                }
             }
             to_js_error(&format!("Parse error: {:?}", e))
         })?;
@@ -89,6 +110,7 @@ pub fn render_audio(user_code: &str, options: JsValue) -> Result<Float32Array, J
 
     // Inject registered banks
     banks::inject_registered_banks(&mut interpreter);
+    session::inject_session_variables(&mut interpreter, opts.state.as_ref());
 
     // Render audio buffer
     let buffer = interpreter.interpret(&statements).map_err(|e| {
@@ -106,6 +128,7 @@ pub fn render_audio(user_code: &str, options: JsValue) -> Result<Float32Array, J
         }
         to_js_error(&format!("Render error: {}", error_msg))
     })?;
+    session::remember_variables(&interpreter.variables);
 
     // Convert to Float32Array
     let array = Float32Array::new_with_length(buffer.len() as u32);
@@ -117,9 +140,10 @@ pub fn render_audio(user_code: &str, options: JsValue) -> Result<Float32Array, J
 /// Render audio with debug information
 #[wasm_bindgen]
 pub fn debug_render(user_code: &str, options: JsValue) -> Result<JsValue, JsValue> {
-    // Clear previous playhead events
-    use crate::web::registry::playhead;
+    // Clear previous playhead events and runtime warnings
+    use crate::web::registry::{debug, playhead};
     playhead::clear_events();
+    debug::get_runtime_warnings(true);
 
     // Parse options
     let opts: RenderOptions = if options.is_undefined() || options.is_null() {
@@ -139,16 +163,25 @@ pub fn debug_render(user_code: &str, options: JsValue) -> Result<JsValue, JsValu
 
     // Inject registered banks
     banks::inject_registered_banks(&mut interpreter);
+    session::inject_session_variables(&mut interpreter, opts.state.as_ref());
 
-    // Render audio buffer
-    let buffer = interpreter
-        .interpret(&statements)
+    // Render audio buffer, tapping every group insert for the silence report
+    interpreter
+        .collect_all_events(&statements)
         .map_err(|e| to_js_error(&format!("Render error: {}", e)))?;
+    let (buffer, silence) = interpreter
+        .render_audio_diagnosed()
+        .map_err(|e| to_js_error(&format!("Render error: {}", e)))?;
+    session::remember_variables(&interpreter.variables);
 
     // Get event count from interpreter
     let event_count = interpreter.events().events.len();
     let duration = buffer.len() as f32 / opts.sample_rate as f32;
 
+    check_clipping(&buffer, opts.sample_rate);
+    check_silence(&silence);
+    check_unused_variables(&statements, user_code);
+
     // Build debug result
     let result = DebugRenderResult {
         audio: buffer,
@@ -156,6 +189,8 @@ pub fn debug_render(user_code: &str, options: JsValue) -> Result<JsValue, JsValu
         duration,
         event_count,
         bpm: opts.bpm,
+        warnings: debug::get_runtime_warnings(true),
+        silence,
     };
 
     // Serialize to JS
@@ -163,6 +198,43 @@ pub fn debug_render(user_code: &str, options: JsValue) -> Result<JsValue, JsValu
         .map_err(|e| to_js_error(&format!("Serialization error: {}", e)))
 }
 
+/// Warn when the rendered stereo buffer exceeds full scale
+fn check_clipping(buffer: &[f32], sample_rate: u32) {
+    if let Some(warning) = clipping_warning(buffer, sample_rate) {
+        crate::web::registry::debug::push_runtime_warning("clipped_output", warning, None);
+    }
+}
+
+/// Warn when nothing in the render is audible, with the likely causes
+fn check_silence(report: &SilenceReport) {
+    if !report.is_silent() {
+        return;
+    }
+    crate::web::registry::debug::push_runtime_warning(
+        "silent_output",
+        format!("Output is silent: {}", report.reasons().join("; ")),
+        None,
+    );
+}
+
+/// Warn about top-level `let`/`var`/`const` names that are never referenced, worded
+/// like the CLI `unused_variables` rule
+fn check_unused_variables(statements: &[Statement], source: &str) {
+    for stmt in unused_variables(statements, source) {
+        let (StatementKind::Let { name, .. }
+        | StatementKind::Var { name, .. }
+        | StatementKind::Const { name, .. }) = &stmt.kind
+        else {
+            continue;
+        };
+        crate::web::registry::debug::push_runtime_warning(
+            "unused_variables",
+            format!("Variable '{}' is defined but never used", name),
+            Some((stmt.line, stmt.column)),
+        );
+    }
+}
+
 /// Render WAV file preview
 #[wasm_bindgen]
 pub fn render_wav_preview(
@@ -198,6 +270,7 @@ pub fn render_wav_preview(
 
     // Inject registered banks
     banks::inject_registered_banks(&mut interpreter);
+    session::inject_session_variables(&mut interpreter, opts.state.as_ref());
 
     // Progress: setup done (50%)
     if let Some(callback) = &on_progress {
@@ -208,6 +281,7 @@ pub fn render_wav_preview(
     let buffer = interpreter
         .interpret(&statements)
         .map_err(|e| to_js_error(&format!("Render error: {}", e)))?;
+    session::remember_variables(&interpreter.variables);
 
     // Progress: rendering done (75%)
     if let Some(callback) = &on_progress {
@@ -252,6 +326,7 @@ pub fn get_code_to_buffer_metadata(user_code: &str, options: JsValue) -> Result<
 
     // Inject registered banks
     banks::inject_registered_banks(&mut interpreter);
+    session::inject_session_variables(&mut interpreter, opts.state.as_ref());
 
     // Get performance.now() for WASM-compatible timing
     let render_start = js_sys::Date::now();
@@ -260,6 +335,7 @@ pub fn get_code_to_buffer_metadata(user_code: &str, options: JsValue) -> Result<
     let buffer = interpreter
         .interpret(&statements)
         .map_err(|e| to_js_error(&format!("Render error: {}", e)))?;
+    session::remember_variables(&interpreter.variables);
 
     let render_end = js_sys::Date::now();
     let render_duration = (render_end - render_start) / 1000.0; // Convert ms to seconds
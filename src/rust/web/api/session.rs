@@ -0,0 +1,35 @@
+//! Session persistence API for WASM
+//!
+//! Lets the playground save its working state (variables, banks, samples) and restore
+//! it after a page reload without re-running all setup code.
+
+use js_sys::Uint8Array;
+use wasm_bindgen::prelude::*;
+
+use crate::web::registry::session::{self, SessionState};
+use crate::web::utils::errors::to_js_error;
+
+/// Serialize the current session to bytes (JSON)
+#[wasm_bindgen]
+pub fn export_state() -> Result<Uint8Array, JsValue> {
+    let bytes = serde_json::to_vec(&session::capture_session())
+        .map_err(|e| to_js_error(&format!("Serialization error: {}", e)))?;
+
+    let array = Uint8Array::new_with_length(bytes.len() as u32);
+    array.copy_from(&bytes);
+    Ok(array)
+}
+
+/// Restore a session previously produced by `export_state()`
+#[wasm_bindgen]
+pub fn import_state(bytes: &[u8]) -> Result<(), JsValue> {
+    let state: SessionState = serde_json::from_slice(bytes)
+        .map_err(|e| to_js_error(&format!("Invalid session state: {}", e)))?;
+    session::restore_session(state).map_err(|e| to_js_error(&e))
+}
+
+/// Forget the variables kept between renders
+#[wasm_bindgen]
+pub fn clear_state() {
+    session::clear_session();
+}
@@ -0,0 +1,275 @@
+//! Real-time streaming API for WASM
+//!
+//! Instead of rendering a whole piece to one Float32Array, an AudioWorklet processor
+//! pulls audio block by block:
+//!
+//! ```js
+//! start_stream(code, { sample_rate: sampleRate, repeat: true });
+//! const ptr = alloc_block(128);
+//! // in process(): fill_block(ptr, 128), then copy the left half to outputs[0][0]
+//! // and the right half to outputs[0][1] from a Float32Array view on wasm memory
+//! ```
+//!
+//! With `repeat`, the program is collected again each time a pass runs out, so
+//! generative pieces play indefinitely. Programs with group inserts or a routing graph
+//! cannot be mixed event by event; each of their passes is rendered whole when it
+//! starts, so the first block of a pass takes longer.
+//!
+//! Web MIDI input can drive `on mapping.in.<device>.*` handlers while it plays:
+//!
+//! ```js
+//! const t0 = performance.now(); // when the stream started
+//! input.onmidimessage = (msg) => push_midi_message(msg.data, msg.timeStamp - t0);
+//! ```
+
+use serde::Deserialize;
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+
+use crate::engine::audio::events::AudioEvent;
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::engine::audio::interpreter::driver::renderer::BlockStream;
+use crate::language::syntax::parser::driver::SimpleParser;
+use crate::shared::StoreSnapshot;
+use crate::web::registry::playhead::{self, PlayheadEvent};
+use crate::web::registry::{banks, session};
+use crate::web::utils::errors::to_js_error;
+
+#[derive(Deserialize)]
+pub struct StreamOptions {
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u32,
+    #[serde(default = "default_bpm")]
+    pub bpm: f32,
+    /// Start a new pass of the program whenever the previous one ends
+    #[serde(default)]
+    pub repeat: bool,
+    /// Variables to start from, e.g. the `store` of an `export_state()` snapshot
+    #[serde(default)]
+    pub state: Option<StoreSnapshot>,
+}
+
+fn default_sample_rate() -> u32 {
+    44100
+}
+fn default_bpm() -> f32 {
+    120.0
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            bpm: 120.0,
+            repeat: false,
+            state: None,
+        }
+    }
+}
+
+struct StreamState {
+    stream: BlockStream,
+    /// Interleaved scratch block, split into planar output by `fill_block`
+    interleaved: Vec<f32>,
+}
+
+thread_local! {
+    static STREAM: RefCell<Option<StreamState>> = RefCell::new(None);
+}
+
+/// Parse `user_code` and get ready to stream it; replaces any running stream
+#[wasm_bindgen]
+pub fn start_stream(user_code: &str, options: JsValue) -> Result<(), JsValue> {
+    let opts: StreamOptions = if options.is_undefined() || options.is_null() {
+        StreamOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| to_js_error(&format!("Invalid options: {}", e)))?
+    };
+
+    let statements = SimpleParser::parse(user_code, std::path::PathBuf::from("wasm_input.deva"))
+        .map_err(|e| to_js_error(&format!("Parse error: {:?}", e)))?;
+
+    playhead::clear_events();
+    let repeat = opts.repeat;
+    let mut first_pass = true;
+    let build = move || -> anyhow::Result<AudioInterpreter> {
+        let mut interpreter = AudioInterpreter::new(opts.sample_rate);
+        interpreter.bpm = opts.bpm;
+        banks::inject_registered_banks(&mut interpreter);
+        session::inject_session_variables(&mut interpreter, opts.state.as_ref());
+        interpreter.collect_all_events(&statements)?;
+        // Later passes only differ by their random choices
+        if std::mem::take(&mut first_pass) {
+            session::remember_variables(&interpreter.variables);
+        }
+        Ok(interpreter)
+    };
+    let stream = BlockStream::new(Box::new(build), repeat)
+        .map_err(|e| to_js_error(&format!("Render error: {}", e)))?;
+
+    STREAM.with(|state| {
+        *state.borrow_mut() = Some(StreamState {
+            stream,
+            interleaved: Vec::new(),
+        });
+    });
+    Ok(())
+}
+
+/// Allocate a block of `frames * 2` floats in wasm memory for `fill_block` to write to
+#[wasm_bindgen]
+pub fn alloc_block(frames: usize) -> *mut f32 {
+    let block = vec![0.0f32; frames * 2].into_boxed_slice();
+    Box::into_raw(block) as *mut f32
+}
+
+/// Release a block returned by `alloc_block` for the same `frames`
+#[wasm_bindgen]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn free_block(ptr: *mut f32, frames: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    // SAFETY: `ptr` came from `alloc_block(frames)`, which leaked a boxed slice of this length
+    unsafe {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            ptr,
+            frames * 2,
+        )));
+    }
+}
+
+/// Render the next `frames` into the block at `ptr`: left channel first, then right.
+/// Returns the frames produced; 0 once a non-repeating stream has ended. Notes that
+/// start in the block are reported as playhead events on the stream timeline.
+#[wasm_bindgen]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn fill_block(ptr: *mut f32, frames: usize) -> Result<usize, JsValue> {
+    if ptr.is_null() {
+        return Err(to_js_error("fill_block: null block"));
+    }
+    // SAFETY: `ptr` came from `alloc_block(frames)`, so it holds `frames * 2` floats, and
+    // the stream state never points into it
+    let out = unsafe { std::slice::from_raw_parts_mut(ptr, frames * 2) };
+
+    let (produced, started) = STREAM.with(|state| {
+        let mut state = state.borrow_mut();
+        let Some(state) = state.as_mut() else {
+            return Err(to_js_error("No stream started; call start_stream() first"));
+        };
+        state.interleaved.resize(frames * 2, 0.0);
+        let produced = state
+            .stream
+            .fill(&mut state.interleaved)
+            .map_err(|e| to_js_error(&format!("Render error: {}", e)))?;
+        let (left, right) = out.split_at_mut(frames);
+        for (frame, pair) in state.interleaved.chunks_exact(2).enumerate() {
+            left[frame] = pair[0];
+            right[frame] = pair[1];
+        }
+        Ok((produced, state.stream.take_started()))
+    })?;
+
+    // Callbacks run outside the borrow so they may query the stream
+    for event in started {
+        push_playhead(event);
+    }
+    Ok(produced)
+}
+
+/// Device name Web MIDI messages are reported under when the program binds no
+/// `mapping.in.*` device: `on mapping.in.web.noteOn:`
+const WEB_MIDI_DEVICE: &str = "web";
+
+/// Feed a Web MIDI message (`MIDIMessageEvent.data`) to the running stream. `timestamp`
+/// is in milliseconds on the stream clock; events the matching `on mapping.in.*`
+/// handlers add play from then on, or from the next block if it has already passed.
+/// Returns the number of events added.
+#[wasm_bindgen]
+pub fn push_midi_message(bytes: &[u8], timestamp: f64) -> Result<usize, JsValue> {
+    STREAM.with(|state| {
+        let mut state = state.borrow_mut();
+        let Some(state) = state.as_mut() else {
+            return Err(to_js_error("No stream started; call start_stream() first"));
+        };
+        state
+            .stream
+            .insert_events((timestamp / 1000.0).max(0.0) as f32, |interpreter| {
+                interpreter
+                    .receive_midi_message(bytes, WEB_MIDI_DEVICE)
+                    .map(|_| ())
+            })
+            .map_err(|e| to_js_error(&format!("MIDI input error: {}", e)))
+    })
+}
+
+/// Seconds of audio handed out so far
+#[wasm_bindgen]
+pub fn stream_position() -> f64 {
+    STREAM.with(|state| {
+        state.borrow().as_ref().map_or(0.0, |state| {
+            state.stream.position() as f64 / state.stream.sample_rate() as f64
+        })
+    })
+}
+
+/// Drop the running stream
+#[wasm_bindgen]
+pub fn stop_stream() {
+    STREAM.with(|state| {
+        state.borrow_mut().take();
+    });
+}
+
+fn push_playhead(event: AudioEvent) {
+    let (on, off, midi, start_time, duration, velocity, synth_id) = match event {
+        AudioEvent::Note {
+            midi,
+            start_time,
+            duration,
+            velocity,
+            synth_id,
+            ..
+        } => (
+            "note_on",
+            "note_off",
+            vec![midi],
+            start_time,
+            duration,
+            velocity,
+            synth_id,
+        ),
+        AudioEvent::Chord {
+            midis,
+            start_time,
+            duration,
+            velocity,
+            synth_id,
+            ..
+        } => (
+            "chord_on",
+            "chord_off",
+            midis,
+            start_time,
+            duration,
+            velocity,
+            synth_id,
+        ),
+        AudioEvent::Sample { .. } => return,
+    };
+    playhead::push_event(PlayheadEvent {
+        event_type: on.to_string(),
+        midi: midi.clone(),
+        time: start_time,
+        velocity,
+        synth_id: synth_id.clone(),
+    });
+    playhead::push_event(PlayheadEvent {
+        event_type: off.to_string(),
+        midi,
+        time: start_time + duration,
+        velocity: 0.0,
+        synth_id,
+    });
+}
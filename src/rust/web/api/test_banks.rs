@@ -0,0 +1,64 @@
+use super::*;
+use std::io::Write;
+
+fn zip_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, content) in files {
+        writer
+            .start_file(*name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(content).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+fn limits() -> ZipLimits {
+    ZipLimits {
+        archive_bytes: 64 * 1024,
+        entry_bytes: 1000,
+        unpacked_bytes: 1500,
+        files: 3,
+    }
+}
+
+#[test]
+fn test_unpack_zip_reads_files_by_path() {
+    let archive = zip_archive(&[
+        ("kit/bank.toml", b"[bank]\nname = \"kit\"\n"),
+        ("kit\\audio\\kick.wav", b"RIFF"),
+    ]);
+    let files = unpack_zip(&archive, &limits()).unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files["kit/audio/kick.wav"], b"RIFF");
+    assert!(files.contains_key("kit/bank.toml"));
+}
+
+#[test]
+fn test_unpack_zip_refuses_archives_over_the_limits() {
+    let big = vec![0u8; 1001];
+    let error = unpack_zip(&zip_archive(&[("big.wav", &big)]), &limits()).unwrap_err();
+    assert!(
+        error.contains("'big.wav' unpacks to more than 1000 bytes"),
+        "{error}"
+    );
+
+    // Zeros deflate to a few bytes; the inflated total is what counts
+    let half = vec![0u8; 800];
+    let archive = zip_archive(&[("a.wav", &half), ("b.wav", &half)]);
+    assert!(archive.len() < 1500);
+    let error = unpack_zip(&archive, &limits()).unwrap_err();
+    assert!(error.contains("more than 1500 bytes"), "{error}");
+
+    let archive = zip_archive(&[("a", b""), ("b", b""), ("c", b""), ("d", b"")]);
+    let error = unpack_zip(&archive, &limits()).unwrap_err();
+    assert!(error.contains("more than 3 files"), "{error}");
+
+    let small = ZipLimits {
+        archive_bytes: 10,
+        ..limits()
+    };
+    let error = unpack_zip(&zip_archive(&[("a", b"")]), &small).unwrap_err();
+    assert!(error.contains("larger than 10 bytes"), "{error}");
+
+    assert!(unpack_zip(b"not a zip", &limits()).is_err());
+}
@@ -17,6 +17,8 @@ pub mod utils;
 
 // Re-export main API functions for convenience
 pub use api::midi::*;
+pub use api::notebook::*;
 pub use api::parse::*;
 pub use api::playback::*;
 pub use api::render::*;
+pub use api::session::*;
@@ -7,7 +7,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 
 /// Represents a registered audio bank
-#[derive(Clone, Debug, serde::Serialize)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct RegisteredBank {
     /// Full name in format "publisher.name"
     pub full_name: String,
@@ -23,10 +23,12 @@ thread_local! {
     pub static REGISTERED_BANKS: RefCell<Vec<RegisteredBank>> = RefCell::new(Vec::new());
 }
 
-/// Register a new bank
+/// Register a new bank, replacing any bank registered under the same full name
 pub fn register_bank(full_name: String, alias: String, triggers: HashMap<String, String>) {
     REGISTERED_BANKS.with(|banks| {
-        banks.borrow_mut().push(RegisteredBank {
+        let mut banks = banks.borrow_mut();
+        banks.retain(|bank| bank.full_name != full_name);
+        banks.push(RegisteredBank {
             full_name,
             alias,
             triggers,
@@ -41,6 +43,13 @@ pub fn clear_banks() {
     });
 }
 
+/// Replace all registered banks (used when restoring a playground session)
+pub fn restore_banks(restored: Vec<RegisteredBank>) {
+    REGISTERED_BANKS.with(|banks| {
+        *banks.borrow_mut() = restored;
+    });
+}
+
 /// Get list of all registered banks
 pub fn list_banks() -> Vec<RegisteredBank> {
     REGISTERED_BANKS.with(|banks| banks.borrow().clone())
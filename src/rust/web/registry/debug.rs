@@ -9,6 +9,9 @@ pub struct ParseError {
     pub message: String,
     pub line: usize,
     pub column: usize,
+    /// Character column just past the offending token (equal to `column` when unknown)
+    #[serde(default, rename = "endColumn")]
+    pub end_column: usize,
     #[serde(rename = "type")]
     pub error_type: String,
 }
@@ -19,11 +22,22 @@ impl ParseError {
             message,
             line,
             column,
+            end_column: column,
             error_type,
         }
     }
 }
 
+/// Runtime warning in the CLI rule checker's shape (level, rule name, message) plus location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeWarning {
+    pub level: String,
+    pub rule: String,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
 thread_local! {
     /// Log of sample loading events
     pub static SAMPLE_LOAD_LOG: RefCell<Vec<String>> = RefCell::new(Vec::new());
@@ -39,6 +53,9 @@ thread_local! {
 
     /// Storage for structured parse errors
     pub static PARSE_ERRORS: RefCell<Vec<ParseError>> = RefCell::new(Vec::new());
+
+    /// Warnings gathered while collecting and rendering events
+    pub static RUNTIME_WARNINGS: RefCell<Vec<RuntimeWarning>> = RefCell::new(Vec::new());
 }
 
 /// Log a sample loading event
@@ -113,6 +130,48 @@ pub fn push_parse_error_from_parts(
     push_parse_error(ParseError::new(message, line, column, error_type));
 }
 
+/// Store a parse error covering `span` so editors can underline the exact token
+pub fn push_parse_error_with_span(
+    message: String,
+    line: usize,
+    span: &crate::language::syntax::ast::SourceSpan,
+    error_type: String,
+) {
+    let mut error = ParseError::new(message, line, span.start_column, error_type);
+    error.end_column = span.end_column;
+    push_parse_error(error);
+}
+
+/// Store a runtime warning; `location` is the (line, column) of the statement being run,
+/// `None` for whole-render warnings such as clipping
+pub fn push_runtime_warning(rule: &str, message: String, location: Option<(usize, usize)>) {
+    let (line, column) = location.unwrap_or((0, 0));
+    let message = match location {
+        Some((line, _)) => format!("Line {}: {}", line, message),
+        None => message,
+    };
+    RUNTIME_WARNINGS.with(|warnings| {
+        warnings.borrow_mut().push(RuntimeWarning {
+            level: "warning".to_string(),
+            rule: rule.to_string(),
+            message,
+            line,
+            column,
+        });
+    });
+}
+
+/// Get and optionally clear runtime warnings
+pub fn get_runtime_warnings(clear: bool) -> Vec<RuntimeWarning> {
+    RUNTIME_WARNINGS.with(|warnings| {
+        let result = warnings.borrow().clone();
+        if clear {
+            warnings.borrow_mut().clear();
+        }
+        result
+    })
+}
+
 /// Get and optionally clear last errors
 pub fn get_errors(clear: bool) -> Vec<String> {
     LAST_ERRORS.with(|errors| {
@@ -141,6 +200,7 @@ pub fn clear_all_logs() {
     PLAYBACK_DEBUG_LOG.with(|log| log.borrow_mut().clear());
     LAST_ERRORS.with(|errors| errors.borrow_mut().clear());
     PARSE_ERRORS.with(|errors| errors.borrow_mut().clear());
+    RUNTIME_WARNINGS.with(|warnings| warnings.borrow_mut().clear());
 }
 
 /// Get debug state summary
@@ -150,6 +210,7 @@ pub struct DebugState {
     pub playback_debug_count: usize,
     pub error_count: usize,
     pub parse_error_count: usize,
+    pub runtime_warning_count: usize,
     pub debug_errors_enabled: bool,
 }
 
@@ -159,6 +220,7 @@ pub fn get_debug_state() -> DebugState {
         playback_debug_count: PLAYBACK_DEBUG_LOG.with(|log| log.borrow().len()),
         error_count: LAST_ERRORS.with(|errors| errors.borrow().len()),
         parse_error_count: PARSE_ERRORS.with(|errors| errors.borrow().len()),
+        runtime_warning_count: RUNTIME_WARNINGS.with(|warnings| warnings.borrow().len()),
         debug_errors_enabled: is_debug_errors_enabled(),
     }
 }
@@ -7,15 +7,18 @@
 //! - Error tracking
 //! - Playhead events for UI feedback
 //! - Hot reload support
+//! - Session state persisted across playground reloads
 
 pub mod banks;
 pub mod debug;
 pub mod hotreload;
 pub mod playhead;
 pub mod samples;
+pub mod session;
 
 pub use banks::*;
 pub use debug::*;
 pub use hotreload::*;
 pub use playhead::*;
 pub use samples::*;
+pub use session::*;
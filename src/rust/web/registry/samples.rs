@@ -16,6 +16,9 @@ thread_local! {
     pub static ORIGIN_TO_URI: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
 }
 
+/// Rate registered samples are stored at; rendering and length estimates assume it
+pub const REGISTRY_SAMPLE_RATE: u32 = 44100;
+
 /// Register a sample with PCM data
 pub fn register_sample(uri: String, pcm: Vec<i16>) {
     REGISTERED_SAMPLES.with(|samples| {
@@ -23,6 +26,34 @@ pub fn register_sample(uri: String, pcm: Vec<i16>) {
     });
 }
 
+/// Register PCM recorded at `sample_rate`, converted to the registry rate.
+/// Registering an existing URI again replaces its data.
+pub fn register_sample_at_rate(uri: String, pcm: Vec<i16>, sample_rate: u32) {
+    register_sample(
+        uri,
+        resample_linear(&pcm, sample_rate, REGISTRY_SAMPLE_RATE),
+    );
+}
+
+/// Linear-interpolation rate conversion, enough for dropped-in user samples
+fn resample_linear(pcm: &[i16], source_rate: u32, target_rate: u32) -> Vec<i16> {
+    if pcm.is_empty() || source_rate == 0 || source_rate == target_rate {
+        return pcm.to_vec();
+    }
+    let step = source_rate as f64 / target_rate as f64;
+    let out_len = (pcm.len() as f64 / step).ceil() as usize;
+    (0..out_len)
+        .map(|j| {
+            let pos = j as f64 * step;
+            let i = pos.floor() as usize;
+            let frac = pos - i as f64;
+            let a = pcm.get(i).copied().unwrap_or(0) as f64;
+            let b = pcm.get(i + 1).map_or(a, |&v| v as f64);
+            (a + (b - a) * frac).round() as i16
+        })
+        .collect()
+}
+
 /// Register origin URL for a sample
 pub fn register_sample_origin(uri: String, origin_url: String) {
     SAMPLE_ORIGIN_URLS.with(|origins| {
@@ -87,3 +118,7 @@ pub struct SampleMetadata {
     pub size_bytes: usize,
     pub origin_url: Option<String>,
 }
+
+#[cfg(test)]
+#[path = "test_samples.rs"]
+mod tests;
@@ -0,0 +1,113 @@
+//! Session registry for WASM
+//!
+//! Records the variable table of the last render and packs it, together with registered
+//! banks and samples, into a snapshot the browser can store and restore after a reload.
+//!
+//! Renders never read the recorded table back on their own: a deleted `let` must stop
+//! resolving on the next render. A caller that wants to resume from a snapshot passes its
+//! `store` in the render options (`{ state: snapshot.store }`).
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::audio::interpreter::driver::AudioInterpreter;
+use crate::language::syntax::ast::Value;
+use crate::shared::{GlobalStore, StoreSnapshot};
+use crate::web::registry::{banks, samples};
+
+/// Bumped whenever the snapshot layout changes incompatibly
+pub const SESSION_STATE_VERSION: u32 = 1;
+
+/// Variables left by the last render, for `capture_session`
+pub static SESSION_STORE: Lazy<GlobalStore> = Lazy::new(GlobalStore::new);
+
+/// A registered sample with its PCM data
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionSample {
+    pub uri: String,
+    #[serde(default)]
+    pub origin_url: Option<String>,
+    pub pcm: Vec<i16>,
+}
+
+/// Everything needed to resume a playground session
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionState {
+    pub version: u32,
+    #[serde(default)]
+    pub store: StoreSnapshot,
+    #[serde(default)]
+    pub banks: Vec<banks::RegisteredBank>,
+    #[serde(default)]
+    pub samples: Vec<SessionSample>,
+}
+
+/// Make the variables of `state`, when the caller passed one, visible to `interpreter`
+/// before user code runs
+pub fn inject_session_variables(interpreter: &mut AudioInterpreter, state: Option<&StoreSnapshot>) {
+    let Some(state) = state else {
+        return;
+    };
+    for (name, value) in &state.variables {
+        interpreter
+            .variables
+            .entry(name.clone())
+            .or_insert_with(|| value.clone());
+    }
+}
+
+/// Record the variable table left by a successful render, replacing the previous one
+pub fn remember_variables(variables: &HashMap<String, Value>) {
+    SESSION_STORE.replace_variables(variables.clone());
+}
+
+/// Forget the recorded variables (banks and samples are left registered)
+pub fn clear_session() {
+    SESSION_STORE.clear();
+}
+
+/// Capture the current session
+pub fn capture_session() -> SessionState {
+    let mut uris = samples::list_sample_uris();
+    uris.sort();
+    let samples = uris
+        .into_iter()
+        .filter_map(|uri| {
+            samples::get_sample(&uri).map(|pcm| SessionSample {
+                origin_url: samples::get_origin_url(&uri),
+                uri,
+                pcm,
+            })
+        })
+        .collect();
+
+    SessionState {
+        version: SESSION_STATE_VERSION,
+        store: SESSION_STORE.snapshot(),
+        banks: banks::list_banks(),
+        samples,
+    }
+}
+
+/// Replace the current session with `state`
+pub fn restore_session(state: SessionState) -> Result<(), String> {
+    if state.version > SESSION_STATE_VERSION {
+        return Err(format!(
+            "Session state version {} is newer than supported version {}",
+            state.version, SESSION_STATE_VERSION
+        ));
+    }
+
+    SESSION_STORE.restore(state.store);
+    banks::restore_banks(state.banks);
+    samples::clear_samples();
+    for sample in state.samples {
+        if let Some(origin) = sample.origin_url {
+            samples::register_sample_origin(sample.uri.clone(), origin);
+        }
+        samples::register_sample(sample.uri, sample.pcm);
+    }
+    Ok(())
+}
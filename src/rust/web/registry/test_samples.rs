@@ -0,0 +1,26 @@
+use super::*;
+
+#[test]
+fn test_resample_linear_keeps_matching_rates() {
+    let pcm = vec![0, 100, -100, 200];
+    assert_eq!(resample_linear(&pcm, 48000, 48000), pcm);
+    assert_eq!(resample_linear(&pcm, 0, 48000), pcm);
+    assert!(resample_linear(&[], 22050, 48000).is_empty());
+}
+
+#[test]
+fn test_resample_linear_interpolates_between_samples() {
+    // Doubling the rate puts a midpoint between every pair; the last sample holds
+    assert_eq!(
+        resample_linear(&[0, 100, -100], 24000, 48000),
+        vec![0, 50, 100, 0, -100, -100]
+    );
+    // Halving it keeps every other sample
+    assert_eq!(
+        resample_linear(&[0, 10, 20, 30, 40], 96000, 48000),
+        vec![0, 20, 40]
+    );
+    let upsampled = resample_linear(&vec![1000; 44100], 44100, 48000);
+    assert_eq!(upsampled.len(), 48000);
+    assert!(upsampled.iter().all(|&sample| sample == 1000));
+}
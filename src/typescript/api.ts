@@ -12,6 +12,7 @@ import type {
   DebugRenderResult,
   CodeMetadata,
   RegisteredBank,
+  ZipBankResult,
   DebugState
 } from './types.js';
 
@@ -198,16 +199,35 @@ export async function getCodeMetadata(code: string, options?: RenderOptions): Pr
  * 
  * @param uri - Sample URI (e.g., "devalang://bank/kick.wav")
  * @param pcm - PCM audio data as Float32Array
+ * @param rate - Sample rate of `pcm` (defaults to 44100)
  * 
  * @example
  * ```typescript
  * const pcm = new Float32Array([0.5, -0.5, 0.25, -0.25]);
- * await registerSample('devalang://bank/test.wav', pcm);
+ * await registerSample('devalang://bank/test.wav', pcm, 48000);
  * ```
  */
-export async function registerSample(uri: string, pcm: Float32Array): Promise<boolean> {
+export async function registerSample(uri: string, pcm: Float32Array, rate?: number): Promise<boolean> {
   await ensureWasmLoaded();
-  return wasmModule.register_sample(uri, pcm);
+  return wasmModule.register_sample(uri, pcm, rate);
+}
+
+/**
+ * Register a bank from a zip archive holding a bank.toml or plain WAV files
+ * 
+ * @param bytes - Archive contents
+ * @param alias - Name used in code (defaults to the bank name, or "user")
+ * 
+ * @example
+ * ```typescript
+ * const bytes = new Uint8Array(await file.arrayBuffer());
+ * const bank = await registerBankFromZip(bytes, 'mykit');
+ * console.log(bank.triggers.map(t => t.name));
+ * ```
+ */
+export async function registerBankFromZip(bytes: Uint8Array, alias?: string): Promise<ZipBankResult> {
+  await ensureWasmLoaded();
+  return wasmModule.register_bank_from_zip(bytes, alias);
 }
 
 /**
@@ -44,6 +44,50 @@ export interface DebugRenderResult {
   duration: number;
   eventCount: number;
   bpm: number;
+  warnings: RuntimeWarning[];
+  /** Why the render, or part of it, is quiet */
+  silence: SilenceReport;
+}
+
+/**
+ * Level of a group insert after its effects and strip
+ */
+export interface InsertActivity {
+  name: string;
+  muted: boolean;
+  peak: number;
+  rms: number;
+  /** Seconds until the insert is first audible (above -60 dBFS) */
+  firstSound: number | null;
+}
+
+/**
+ * Silence diagnostics of a debug render
+ */
+export interface SilenceReport {
+  /** RMS of each second of the output */
+  rmsPerSecond: number[];
+  /** Seconds until the output is first audible (above -60 dBFS) */
+  firstSound: number | null;
+  peak: number;
+  /** Group inserts; empty when routing graphs are used */
+  inserts: InsertActivity[];
+  /** Sample triggers dropped because their sample is not loaded */
+  droppedEvents: number;
+  missingSamples: string[];
+  /** Triggers that matched no sample */
+  unresolvedTriggers: string[];
+}
+
+/**
+ * Runtime warning gathered during a debug render (same shape as CLI rule messages)
+ */
+export interface RuntimeWarning {
+  level: 'warning';
+  rule: 'missing_trigger' | 'unresolved_bank' | 'clipped_output' | 'silent_output' | 'unused_variables';
+  message: string;
+  line: number;
+  column: number;
 }
 
 /**
@@ -64,6 +108,18 @@ export interface RegisteredBank {
   triggers: Record<string, string>;
 }
 
+/**
+ * Bank registered from a zip archive
+ */
+export interface ZipBankResult {
+  ok: boolean;
+  bank: string;
+  alias: string;
+  triggers: { name: string; uri: string; relative: string; frames: number }[];
+  /** Archive entries that could not be loaded */
+  skipped: string[];
+}
+
 /**
  * Debug state information
  */